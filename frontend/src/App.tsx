@@ -11,6 +11,7 @@ import {
   useAuthStore,
 } from './stores/authStore';
 import { usePermissionsStore } from './stores/permissionsStore';
+import { withBasePath } from './services/basePath';
 
 // Lazy load all page components for code splitting
 const Login = lazy(() => import('./pages/Login'));
@@ -91,7 +92,7 @@ function App() {
     const checkIdleTimeout = () => {
       if (Date.now() - getLastSessionActivity() > SESSION_IDLE_TIMEOUT_MS) {
         logout('Your session expired after 30 minutes of inactivity.');
-        window.location.replace('/login');
+        window.location.replace(withBasePath('/login'));
       }
     };
 
@@ -0,0 +1,143 @@
+import { useState } from 'react';
+import { useQuery } from '@tanstack/react-query';
+import { formatDistanceToNow } from 'date-fns';
+import { ChevronDown, ChevronUp } from 'lucide-react';
+import { api } from '../../services/api';
+import type { CompileErrorKind } from '../../types';
+
+type TimeRange = '1d' | '7d' | '30d';
+
+const DAYS_FOR_RANGE: Record<TimeRange, number> = {
+  '1d': 1,
+  '7d': 7,
+  '30d': 30,
+};
+
+const KIND_LABELS: Record<CompileErrorKind, string> = {
+  missing_class: 'Missing class',
+  duplicate_declaration: 'Duplicate declaration',
+  hiera_lookup: 'Hiera lookup',
+  syntax_error: 'Syntax error',
+  other: 'Other',
+};
+
+export default function CompileErrors() {
+  const [timeRange, setTimeRange] = useState<TimeRange>('7d');
+  const [expanded, setExpanded] = useState<string | null>(null);
+
+  const { data, isLoading, error } = useQuery({
+    queryKey: ['analytics', 'compile-errors', timeRange],
+    queryFn: () => api.getCompileErrors({ days: DAYS_FOR_RANGE[timeRange] }),
+  });
+
+  return (
+    <div className="w-full">
+      <div className="flex items-center justify-between mb-4">
+        <h3 className="text-lg font-semibold text-gray-900">Top Compile Errors</h3>
+        <div className="flex rounded-lg border border-gray-300 overflow-hidden">
+          {(['1d', '7d', '30d'] as TimeRange[]).map((range) => (
+            <button
+              key={range}
+              onClick={() => setTimeRange(range)}
+              className={`px-3 py-1 text-sm ${
+                timeRange === range
+                  ? 'bg-primary-500 text-white'
+                  : 'bg-white text-gray-600 hover:bg-gray-50'
+              }`}
+            >
+              {range}
+            </button>
+          ))}
+        </div>
+      </div>
+
+      <div className="grid grid-cols-3 gap-4 mb-4">
+        <div className="text-center">
+          <p className="text-2xl font-bold text-gray-900">{data?.compile_failures ?? '-'}</p>
+          <p className="text-xs text-gray-500">Failed Compilations</p>
+        </div>
+        <div className="text-center">
+          <p className="text-2xl font-bold text-gray-900">{data?.nodes_affected ?? '-'}</p>
+          <p className="text-xs text-gray-500">Nodes Affected</p>
+        </div>
+        <div className="text-center">
+          <p className="text-2xl font-bold text-gray-900">{data?.total_signatures ?? '-'}</p>
+          <p className="text-xs text-gray-500">Distinct Errors</p>
+        </div>
+      </div>
+
+      {isLoading ? (
+        <div className="py-8 text-center text-sm text-gray-500">Loading…</div>
+      ) : error ? (
+        <div className="py-8 text-center text-sm text-danger-600">Failed to load compile errors</div>
+      ) : !data || data.errors.length === 0 ? (
+        <div className="py-8 text-center text-sm text-gray-500">
+          No catalog compilation errors in this period
+        </div>
+      ) : (
+        <table className="min-w-full divide-y divide-gray-200">
+          <thead>
+            <tr className="text-left text-xs font-medium uppercase text-gray-500">
+              <th className="py-2 pr-4">Error</th>
+              <th className="py-2 pr-4 text-right">Nodes</th>
+              <th className="py-2 pr-4 text-right">Failed Runs</th>
+              <th className="py-2 pr-4">Environments</th>
+              <th className="py-2">Last Seen</th>
+            </tr>
+          </thead>
+          <tbody className="divide-y divide-gray-100">
+            {data.errors.map((group) => {
+              const isExpanded = expanded === group.signature;
+              return (
+                <tr key={group.signature} className="align-top text-sm">
+                  <td className="py-2 pr-4">
+                    <button
+                      onClick={() => setExpanded(isExpanded ? null : group.signature)}
+                      className="flex items-start gap-1 text-left"
+                    >
+                      {isExpanded ? (
+                        <ChevronUp className="w-4 h-4 mt-0.5 flex-shrink-0 text-gray-400" />
+                      ) : (
+                        <ChevronDown className="w-4 h-4 mt-0.5 flex-shrink-0 text-gray-400" />
+                      )}
+                      <span>
+                        <span className="font-medium text-gray-900">{group.signature}</span>
+                        <span className="ml-2 inline-block rounded bg-gray-100 px-1.5 py-0.5 text-xs text-gray-600">
+                          {KIND_LABELS[group.kind]}
+                        </span>
+                      </span>
+                    </button>
+                    {isExpanded && (
+                      <div className="mt-2 ml-5 space-y-1 text-xs text-gray-600">
+                        {group.file && (
+                          <p className="font-mono">
+                            {group.file}
+                            {group.line != null && `:${group.line}`}
+                          </p>
+                        )}
+                        <p className="font-mono whitespace-pre-wrap break-all">{group.message}</p>
+                        <p>
+                          {group.nodes.join(', ')}
+                          {group.node_count > group.nodes.length &&
+                            ` and ${group.node_count - group.nodes.length} more`}
+                        </p>
+                      </div>
+                    )}
+                  </td>
+                  <td className="py-2 pr-4 text-right">{group.node_count}</td>
+                  <td className="py-2 pr-4 text-right">{group.occurrences}</td>
+                  <td className="py-2 pr-4 text-gray-600">{group.environments.join(', ')}</td>
+                  <td className="py-2 whitespace-nowrap text-gray-600">
+                    {group.last_seen
+                      ? formatDistanceToNow(new Date(group.last_seen), { addSuffix: true })
+                      : '-'}
+                  </td>
+                </tr>
+              );
+            })}
+          </tbody>
+        </table>
+      )}
+    </div>
+  );
+}
@@ -0,0 +1,165 @@
+import { useMemo, useState } from 'react';
+import { useQuery } from '@tanstack/react-query';
+import {
+  LineChart,
+  Line,
+  XAxis,
+  YAxis,
+  CartesianGrid,
+  Tooltip,
+  ResponsiveContainer,
+  Legend,
+} from 'recharts';
+import { format } from 'date-fns';
+import { api } from '../../services/api';
+
+interface FleetTrendsProps {
+  title?: string;
+}
+
+type TimeRange = '30d' | '90d';
+
+const DAYS_FOR_RANGE: Record<TimeRange, number> = {
+  '30d': 30,
+  '90d': 90,
+};
+
+const COLORS = {
+  total: '#3b82f6',
+  failed: '#ef4444',
+  unreported: '#9ca3af',
+  runTime: '#8b5cf6',
+  compileTime: '#f59e0b',
+};
+
+export default function FleetTrends({ title = 'Fleet Trends' }: FleetTrendsProps) {
+  const [timeRange, setTimeRange] = useState<TimeRange>('30d');
+
+  // Daily rollups of the hourly fleet snapshots recorded by the backend, so
+  // the chart does not depend on how long PuppetDB keeps reports.
+  const { data: snapshots = [], isLoading } = useQuery({
+    queryKey: ['analytics', 'fleet-metrics', timeRange],
+    queryFn: () => api.getFleetMetrics({ days: DAYS_FOR_RANGE[timeRange], resolution: 'day' }),
+  });
+
+  const chartData = useMemo(
+    () =>
+      snapshots.map((s) => ({
+        label: format(new Date(s.bucket), 'MMM d'),
+        total: s.total_nodes,
+        failed: s.failed_nodes,
+        unreported: s.unreported_nodes,
+        runTime: s.avg_run_time_seconds != null ? Number(s.avg_run_time_seconds.toFixed(1)) : null,
+        compileTime:
+          s.avg_compile_time_seconds != null ? Number(s.avg_compile_time_seconds.toFixed(1)) : null,
+        resources: s.total_resources,
+      })),
+    [snapshots]
+  );
+
+  const latest = chartData[chartData.length - 1];
+
+  return (
+    <div className="w-full">
+      <div className="flex items-center justify-between mb-4">
+        <h3 className="text-lg font-semibold text-gray-900">{title}</h3>
+        <div className="flex rounded-lg border border-gray-300 overflow-hidden">
+          {(['30d', '90d'] as TimeRange[]).map((range) => (
+            <button
+              key={range}
+              onClick={() => setTimeRange(range)}
+              className={`px-3 py-1 text-sm ${
+                timeRange === range
+                  ? 'bg-primary-500 text-white'
+                  : 'bg-white text-gray-600 hover:bg-gray-50'
+              }`}
+            >
+              {range}
+            </button>
+          ))}
+        </div>
+      </div>
+
+      <div className="grid grid-cols-4 gap-4 mb-4">
+        <div className="text-center">
+          <p className="text-2xl font-bold text-gray-900">{latest?.total ?? '-'}</p>
+          <p className="text-xs text-gray-500">Nodes</p>
+        </div>
+        <div className="text-center">
+          <p className="text-2xl font-bold text-gray-900">{latest?.resources ?? '-'}</p>
+          <p className="text-xs text-gray-500">Managed Resources</p>
+        </div>
+        <div className="text-center">
+          <p className="text-2xl font-bold text-gray-900">
+            {latest?.runTime != null ? `${latest.runTime}s` : '-'}
+          </p>
+          <p className="text-xs text-gray-500">Avg Run Time</p>
+        </div>
+        <div className="text-center">
+          <p className="text-2xl font-bold text-gray-900">
+            {latest?.compileTime != null ? `${latest.compileTime}s` : '-'}
+          </p>
+          <p className="text-xs text-gray-500">Avg Compile Time</p>
+        </div>
+      </div>
+
+      {isLoading ? (
+        <div className="h-64 flex items-center justify-center text-sm text-gray-500">Loading…</div>
+      ) : chartData.length === 0 ? (
+        <div className="h-64 flex items-center justify-center text-sm text-gray-500">
+          No fleet snapshots recorded yet
+        </div>
+      ) : (
+        <div className="grid grid-cols-1 lg:grid-cols-2 gap-6">
+          <div className="h-64">
+            <ResponsiveContainer width="100%" height="100%">
+              <LineChart data={chartData} margin={{ top: 10, right: 30, left: 0, bottom: 0 }}>
+                <CartesianGrid strokeDasharray="3 3" />
+                <XAxis dataKey="label" tick={{ fontSize: 12 }} tickLine={false} />
+                <YAxis tick={{ fontSize: 12 }} tickLine={false} allowDecimals={false} />
+                <Tooltip />
+                <Legend />
+                <Line type="linear" dataKey="total" stroke={COLORS.total} name="Nodes" dot={false} />
+                <Line type="linear" dataKey="failed" stroke={COLORS.failed} name="Failed" dot={false} />
+                <Line
+                  type="linear"
+                  dataKey="unreported"
+                  stroke={COLORS.unreported}
+                  name="Unreported"
+                  dot={false}
+                />
+              </LineChart>
+            </ResponsiveContainer>
+          </div>
+          <div className="h-64">
+            <ResponsiveContainer width="100%" height="100%">
+              <LineChart data={chartData} margin={{ top: 10, right: 30, left: 0, bottom: 0 }}>
+                <CartesianGrid strokeDasharray="3 3" />
+                <XAxis dataKey="label" tick={{ fontSize: 12 }} tickLine={false} />
+                <YAxis tick={{ fontSize: 12 }} tickLine={false} unit="s" />
+                <Tooltip />
+                <Legend />
+                <Line
+                  type="linear"
+                  dataKey="runTime"
+                  stroke={COLORS.runTime}
+                  name="Avg run time"
+                  dot={false}
+                  connectNulls
+                />
+                <Line
+                  type="linear"
+                  dataKey="compileTime"
+                  stroke={COLORS.compileTime}
+                  name="Avg compile time"
+                  dot={false}
+                  connectNulls
+                />
+              </LineChart>
+            </ResponsiveContainer>
+          </div>
+        </div>
+      )}
+    </div>
+  );
+}
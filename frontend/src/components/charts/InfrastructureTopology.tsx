@@ -1,29 +1,36 @@
 import { useMemo, useState } from 'react';
-import { Server, Folder, ChevronDown, ChevronRight, Circle } from 'lucide-react';
-import type { Node, NodeGroup } from '../../types';
+import { Server, Folder, ChevronDown, ChevronRight, Circle, Database, ShieldCheck } from 'lucide-react';
+import type { InfrastructureTopologyGraph, Node, NodeGroup } from '../../types';
 
 interface InfrastructureTopologyProps {
   nodes: Node[];
   groups: NodeGroup[];
+  // Server graph from GET /nodes/topology; enables the "By Server" view
+  topology?: InfrastructureTopologyGraph;
   title?: string;
 }
 
 interface TreeNode {
   id: string;
   name: string;
-  type: 'environment' | 'group' | 'node';
+  type: 'environment' | 'group' | 'node' | 'server' | 'puppetdb' | 'ca';
   children: TreeNode[];
   status?: string;
+  message?: string;
   nodeCount?: number;
 }
 
 function getStatusColor(status: string | null | undefined): string {
   switch (status) {
     case 'changed':
+    case 'healthy':
       return 'text-success-500';
     case 'unchanged':
       return 'text-primary-500';
+    case 'degraded':
+      return 'text-warning-500';
     case 'failed':
+    case 'unhealthy':
       return 'text-danger-500';
     default:
       return 'text-gray-400';
@@ -71,12 +78,20 @@ function TreeNodeComponent({
         {node.type === 'group' && (
           <Folder className="w-4 h-4 text-primary-500" />
         )}
-        {node.type === 'node' && (
+        {(node.type === 'node' || node.type === 'server') && (
           <Server className={`w-4 h-4 ${getStatusColor(node.status)}`} />
         )}
+        {node.type === 'puppetdb' && (
+          <Database className={`w-4 h-4 ${getStatusColor(node.status)}`} />
+        )}
+        {node.type === 'ca' && (
+          <ShieldCheck className={`w-4 h-4 ${getStatusColor(node.status)}`} />
+        )}
 
         {/* Name */}
-        <span className="text-sm text-gray-900 flex-1">{node.name}</span>
+        <span className="text-sm text-gray-900 flex-1" title={node.message}>
+          {node.name}
+        </span>
 
         {/* Node count badge */}
         {node.nodeCount !== undefined && node.nodeCount > 0 && (
@@ -86,7 +101,7 @@ function TreeNodeComponent({
         )}
 
         {/* Status indicator for nodes */}
-        {node.type === 'node' && (
+        {node.type !== 'environment' && node.type !== 'group' && (
           <Circle
             className={`w-2 h-2 fill-current ${getStatusColor(node.status)}`}
           />
@@ -114,13 +129,59 @@ function TreeNodeComponent({
 export default function InfrastructureTopology({
   nodes,
   groups,
+  topology,
   title = 'Infrastructure Topology',
 }: InfrastructureTopologyProps) {
   const [expanded, setExpanded] = useState<Set<string>>(new Set(['root']));
-  const [viewMode, setViewMode] = useState<'environment' | 'group'>('environment');
+  const [viewMode, setViewMode] = useState<'environment' | 'group' | 'server'>('environment');
 
   const tree = useMemo(() => {
-    if (viewMode === 'environment') {
+    if (viewMode === 'server' && topology) {
+      // Servers with the agents compiling against them, next to PuppetDB and the CA
+      const agentsByServer = new Map<string, TreeNode[]>();
+      const byId = new Map(topology.nodes.map((n) => [n.id, n]));
+      topology.edges
+        .filter((edge) => edge.kind === 'catalog')
+        .forEach((edge) => {
+          const agent = byId.get(edge.source);
+          if (!agent) return;
+          const agents = agentsByServer.get(edge.target) || [];
+          agents.push({
+            id: `node-${agent.name}`,
+            name: agent.name,
+            type: 'node' as const,
+            status: agent.status,
+            message: agent.message,
+            children: [],
+          });
+          agentsByServer.set(edge.target, agents);
+        });
+
+      const children: TreeNode[] = topology.nodes
+        .filter((n) => n.kind !== 'agent')
+        .map((n) => ({
+          id: n.id,
+          name: n.kind === 'compile_server' ? `${n.name} (compiler)` : n.name,
+          type:
+            n.kind === 'puppetdb'
+              ? ('puppetdb' as const)
+              : n.kind === 'certificate_authority'
+                ? ('ca' as const)
+                : ('server' as const),
+          status: n.status,
+          message: n.message,
+          nodeCount: n.agent_count,
+          children: (agentsByServer.get(n.id) || []).slice(0, 20),
+        }));
+
+      return {
+        id: 'root',
+        name: 'Infrastructure',
+        type: 'environment' as const,
+        nodeCount: nodes.length,
+        children,
+      };
+    } else if (viewMode === 'environment') {
       // Group by environment
       const envMap = new Map<string, Node[]>();
 
@@ -199,7 +260,7 @@ export default function InfrastructureTopology({
         children: groupNodes,
       };
     }
-  }, [nodes, groups, viewMode]);
+  }, [nodes, groups, topology, viewMode]);
 
   const handleToggle = (id: string) => {
     setExpanded((prev) => {
@@ -254,6 +315,18 @@ export default function InfrastructureTopology({
             >
               By Group
             </button>
+            {topology && (
+              <button
+                onClick={() => setViewMode('server')}
+                className={`px-3 py-1 text-sm ${
+                  viewMode === 'server'
+                    ? 'bg-primary-500 text-white'
+                    : 'bg-white text-gray-600 hover:bg-gray-50'
+                }`}
+              >
+                By Server
+              </button>
+            )}
           </div>
 
           {/* Expand/Collapse buttons */}
@@ -0,0 +1,134 @@
+import { useMemo, useState } from 'react';
+import { useQuery } from '@tanstack/react-query';
+import {
+  LineChart,
+  Line,
+  XAxis,
+  YAxis,
+  CartesianGrid,
+  Tooltip,
+  ResponsiveContainer,
+  Legend,
+} from 'recharts';
+import { format } from 'date-fns';
+import { AlertTriangle } from 'lucide-react';
+import { api } from '../../services/api';
+
+interface NodePerformanceChartProps {
+  certname: string;
+}
+
+type TimeRange = '7d' | '14d' | '30d';
+
+const DAYS_FOR_RANGE: Record<TimeRange, number> = {
+  '7d': 7,
+  '14d': 14,
+  '30d': 30,
+};
+
+const COLORS = {
+  runTime: '#8b5cf6',
+  total: '#3b82f6',
+  changed: '#22c55e',
+  failed: '#ef4444',
+};
+
+export default function NodePerformanceChart({ certname }: NodePerformanceChartProps) {
+  const [timeRange, setTimeRange] = useState<TimeRange>('14d');
+
+  const { data: performance, isLoading } = useQuery({
+    queryKey: ['nodes', certname, 'performance', timeRange],
+    queryFn: () => api.getNodePerformance(certname, { days: DAYS_FOR_RANGE[timeRange] }),
+  });
+
+  const chartData = useMemo(
+    () =>
+      (performance?.points ?? [])
+        .filter((p) => p.end_time)
+        .map((p) => ({
+          label: format(new Date(p.end_time as string), 'MMM d HH:mm'),
+          runTime: p.run_time_seconds != null ? Number(p.run_time_seconds.toFixed(1)) : null,
+          total: p.total_resources ?? null,
+          changed: p.changed_resources ?? null,
+          failed: p.failed_resources ?? null,
+        })),
+    [performance]
+  );
+
+  return (
+    <div className="w-full">
+      <div className="flex items-center justify-between mb-4">
+        <h3 className="text-lg font-semibold text-gray-900">Performance</h3>
+        <div className="flex rounded-lg border border-gray-300 overflow-hidden">
+          {(['7d', '14d', '30d'] as TimeRange[]).map((range) => (
+            <button
+              key={range}
+              onClick={() => setTimeRange(range)}
+              className={`px-3 py-1 text-sm ${
+                timeRange === range
+                  ? 'bg-primary-500 text-white'
+                  : 'bg-white text-gray-600 hover:bg-gray-50'
+              }`}
+            >
+              {range}
+            </button>
+          ))}
+        </div>
+      </div>
+
+      {performance?.run_time_regressed && (
+        <div className="mb-4 flex items-center gap-2 rounded-lg border border-warning-200 bg-warning-50 p-3 text-sm text-warning-700">
+          <AlertTriangle className="w-4 h-4 flex-shrink-0" />
+          <span>
+            Run time regressed: recent runs take {performance.recent_run_time_seconds?.toFixed(1)}s
+            against {performance.baseline_run_time_seconds?.toFixed(1)}s before.
+          </span>
+        </div>
+      )}
+
+      {isLoading ? (
+        <div className="h-64 flex items-center justify-center text-sm text-gray-500">Loading…</div>
+      ) : chartData.length === 0 ? (
+        <div className="h-64 flex items-center justify-center text-sm text-gray-500">
+          No reports in this period
+        </div>
+      ) : (
+        <div className="grid grid-cols-1 lg:grid-cols-2 gap-6">
+          <div className="h-64">
+            <ResponsiveContainer width="100%" height="100%">
+              <LineChart data={chartData} margin={{ top: 10, right: 30, left: 0, bottom: 0 }}>
+                <CartesianGrid strokeDasharray="3 3" />
+                <XAxis dataKey="label" tick={{ fontSize: 12 }} tickLine={false} minTickGap={24} />
+                <YAxis tick={{ fontSize: 12 }} tickLine={false} unit="s" />
+                <Tooltip />
+                <Legend />
+                <Line
+                  type="linear"
+                  dataKey="runTime"
+                  stroke={COLORS.runTime}
+                  name="Run time"
+                  dot={false}
+                  connectNulls
+                />
+              </LineChart>
+            </ResponsiveContainer>
+          </div>
+          <div className="h-64">
+            <ResponsiveContainer width="100%" height="100%">
+              <LineChart data={chartData} margin={{ top: 10, right: 30, left: 0, bottom: 0 }}>
+                <CartesianGrid strokeDasharray="3 3" />
+                <XAxis dataKey="label" tick={{ fontSize: 12 }} tickLine={false} minTickGap={24} />
+                <YAxis tick={{ fontSize: 12 }} tickLine={false} allowDecimals={false} />
+                <Tooltip />
+                <Legend />
+                <Line type="linear" dataKey="total" stroke={COLORS.total} name="Resources" dot={false} />
+                <Line type="linear" dataKey="changed" stroke={COLORS.changed} name="Changed" dot={false} />
+                <Line type="linear" dataKey="failed" stroke={COLORS.failed} name="Failed" dot={false} />
+              </LineChart>
+            </ResponsiveContainer>
+          </div>
+        </div>
+      )}
+    </div>
+  );
+}
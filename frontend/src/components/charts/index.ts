@@ -3,3 +3,5 @@ export { default as GroupMembershipChart } from './GroupMembershipChart';
 export { default as FactDistributionChart } from './FactDistributionChart';
 export { default as InfrastructureTopology } from './InfrastructureTopology';
 export { default as TimeSeriesMetrics } from './TimeSeriesMetrics';
+export { default as FleetTrends } from './FleetTrends';
+export { default as NodePerformanceChart } from './NodePerformanceChart';
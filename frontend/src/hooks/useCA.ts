@@ -9,6 +9,8 @@ export const caKeys = {
   requests: () => [...caKeys.all, 'requests'] as const,
   certificates: () => [...caKeys.all, 'certificates'] as const,
   certificate: (certname: string) => [...caKeys.all, 'certificate', certname] as const,
+  chain: () => [...caKeys.all, 'chain'] as const,
+  certificateChain: (certname: string) => [...caKeys.all, 'chain', certname] as const,
 };
 
 // CA Status
@@ -46,6 +48,23 @@ export function useCertificate(certname: string) {
   });
 }
 
+// CA Chain
+export function useCAChain() {
+  return useQuery({
+    queryKey: caKeys.chain(),
+    queryFn: api.getCaChain,
+  });
+}
+
+// Certificate Chain Validation
+export function useCertificateChain(certname: string) {
+  return useQuery({
+    queryKey: caKeys.certificateChain(certname),
+    queryFn: () => api.validateCertificateChain(certname),
+    enabled: !!certname,
+  });
+}
+
 // Sign Certificate
 export function useSignCertificate() {
   const queryClient = useQueryClient();
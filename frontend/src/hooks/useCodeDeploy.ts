@@ -2,6 +2,7 @@ import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query';
 import { api } from '../services/api';
 import type {
   CreateSshKeyRequest,
+  GenerateSshKeyRequest,
   CreatePatTokenRequest,
   UpdatePatTokenRequest,
   CreateRepositoryRequest,
@@ -10,6 +11,7 @@ import type {
   TriggerDeploymentRequest,
   ApproveDeploymentRequest,
   RejectDeploymentRequest,
+  StartCanaryRequest,
   ListDeploymentsQuery,
   ListEnvironmentsQuery,
 } from '../types';
@@ -46,6 +48,16 @@ export function useCreateSshKey() {
   });
 }
 
+export function useGenerateSshKey() {
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: (request: GenerateSshKeyRequest) => api.generateSshKey(request),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ['code-ssh-keys'] });
+    },
+  });
+}
+
 export function useDeleteSshKey() {
   const queryClient = useQueryClient();
   return useMutation({
@@ -74,6 +86,13 @@ export function useExpiringPatTokens(days: number = 30) {
   });
 }
 
+export function useCredentialHealth() {
+  return useQuery({
+    queryKey: ['code-credential-health'],
+    queryFn: () => api.getCredentialHealth(),
+  });
+}
+
 export function useCreatePatToken() {
   const queryClient = useQueryClient();
   return useMutation({
@@ -225,6 +244,14 @@ export function useCodeDeployment(id: string) {
   });
 }
 
+export function useDeploymentImpact(id: string) {
+  return useQuery({
+    queryKey: ['code-deployment-impact', id],
+    queryFn: () => api.getDeploymentImpact(id),
+    enabled: !!id,
+  });
+}
+
 export function useTriggerDeployment() {
   const queryClient = useQueryClient();
   return useMutation({
@@ -262,6 +289,52 @@ export function useRejectDeployment() {
   });
 }
 
+export function useCanary(deploymentId: string) {
+  return useQuery({
+    queryKey: ['code-canary', deploymentId],
+    queryFn: () => api.getCanary(deploymentId),
+    enabled: !!deploymentId,
+    retry: false,
+    refetchInterval: 30000,
+  });
+}
+
+export function useStartCanary() {
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: ({ id, request }: { id: string; request: StartCanaryRequest }) =>
+      api.startCanary(id, request),
+    onSuccess: (_, variables) => {
+      queryClient.invalidateQueries({ queryKey: ['code-canary', variables.id] });
+    },
+  });
+}
+
+export function usePromoteCanary() {
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: (id: string) => api.promoteCanary(id),
+    onSuccess: (_, id) => {
+      queryClient.invalidateQueries({ queryKey: ['code-canary', id] });
+      queryClient.invalidateQueries({ queryKey: ['code-deployments'] });
+      queryClient.invalidateQueries({ queryKey: ['code-deployment', id] });
+    },
+  });
+}
+
+export function useRollbackCanary() {
+  const queryClient = useQueryClient();
+  return useMutation({
+    mutationFn: ({ id, reason }: { id: string; reason?: string }) =>
+      api.rollbackCanary(id, reason),
+    onSuccess: (_, variables) => {
+      queryClient.invalidateQueries({ queryKey: ['code-canary', variables.id] });
+      queryClient.invalidateQueries({ queryKey: ['code-deployments'] });
+      queryClient.invalidateQueries({ queryKey: ['code-deployment', variables.id] });
+    },
+  });
+}
+
 export function useRetryDeployment() {
   const queryClient = useQueryClient();
   return useMutation({
@@ -32,8 +32,8 @@ export function useUpdateGroup() {
   const queryClient = useQueryClient();
 
   return useMutation({
-    mutationFn: ({ id, data }: { id: string; data: UpdateGroupRequest }) =>
-      api.updateGroup(id, data),
+    mutationFn: ({ id, data, version }: { id: string; data: UpdateGroupRequest; version?: number }) =>
+      api.updateGroup(id, data, version),
     onSuccess: (_, variables) => {
       queryClient.invalidateQueries({ queryKey: ['groups'] });
       queryClient.invalidateQueries({ queryKey: ['group', variables.id] });
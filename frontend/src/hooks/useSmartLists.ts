@@ -0,0 +1,61 @@
+import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query';
+import { api } from '../services/api';
+import type { CreateSmartListRequest, UpdateSmartListRequest } from '../types';
+
+export function useSmartLists() {
+  return useQuery({
+    queryKey: ['smart-lists'],
+    queryFn: api.getSmartLists,
+  });
+}
+
+export function useSmartListNodes(id: string | undefined) {
+  return useQuery({
+    queryKey: ['smart-list-nodes', id],
+    queryFn: () => api.getSmartListNodes(id!),
+    enabled: !!id,
+  });
+}
+
+export function useSmartListCounts() {
+  return useQuery({
+    queryKey: ['smart-list-counts'],
+    queryFn: () => api.getSmartListCounts(),
+  });
+}
+
+export function useCreateSmartList() {
+  const queryClient = useQueryClient();
+
+  return useMutation({
+    mutationFn: (data: CreateSmartListRequest) => api.createSmartList(data),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ['smart-lists'] });
+    },
+  });
+}
+
+export function useUpdateSmartList() {
+  const queryClient = useQueryClient();
+
+  return useMutation({
+    mutationFn: ({ id, data }: { id: string; data: UpdateSmartListRequest }) =>
+      api.updateSmartList(id, data),
+    onSuccess: (_, variables) => {
+      queryClient.invalidateQueries({ queryKey: ['smart-lists'] });
+      queryClient.invalidateQueries({ queryKey: ['smart-list-nodes', variables.id] });
+      queryClient.invalidateQueries({ queryKey: ['smart-list-counts'] });
+    },
+  });
+}
+
+export function useDeleteSmartList() {
+  const queryClient = useQueryClient();
+
+  return useMutation({
+    mutationFn: (id: string) => api.deleteSmartList(id),
+    onSuccess: () => {
+      queryClient.invalidateQueries({ queryKey: ['smart-lists'] });
+    },
+  });
+}
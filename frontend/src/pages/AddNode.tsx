@@ -2,6 +2,7 @@ import { useState, useEffect } from 'react';
 import { Link } from 'react-router-dom';
 import { ArrowLeft, Copy, Check, Terminal, Server, Package, AlertTriangle, ExternalLink } from 'lucide-react';
 import { api } from '../services/api';
+import { BASE_PATH } from '../services/basePath';
 import type { BootstrapConfigResponse } from '../types';
 
 export default function AddNode() {
@@ -34,7 +35,7 @@ export default function AddNode() {
   // When SSL validation is disabled, use curl's -k (insecure) flag on Linux
   // and a permissive certificate validation callback on Windows.
   const curlFlags = ignoreSsl ? '-ksSL' : '-sSL';
-  const curlCommand = `curl ${curlFlags} ${protocol}//${serverHost}/api/v1/bootstrap/script | sudo bash`;
+  const curlCommand = `curl ${curlFlags} ${protocol}//${serverHost}${BASE_PATH}/api/v1/bootstrap/script | sudo bash`;
 
   // When SSL validation is disabled, use Invoke-WebRequest's -SkipCertificateCheck
   // flag (works better than the ServicePointManager callback). Otherwise ensure a
@@ -43,7 +44,7 @@ export default function AddNode() {
   const winTlsPrefix = ignoreSsl
     ? ''
     : '[Net.ServicePointManager]::SecurityProtocol = [Net.SecurityProtocolType]::Tls12; ';
-  const windowsCommand = `${winTlsPrefix}iex (iwr ${winIwrFlags}'${protocol}//${serverHost}/api/v1/bootstrap/windows-script').Content`;
+  const windowsCommand = `${winTlsPrefix}iex (iwr ${winIwrFlags}'${protocol}//${serverHost}${BASE_PATH}/api/v1/bootstrap/windows-script').Content`;
 
   const handleCopy = () => {
     navigator.clipboard.writeText(curlCommand);
@@ -276,7 +277,7 @@ export default function AddNode() {
               For automated deployments:
             </p>
             <pre className="bg-gray-100 text-gray-800 p-3 rounded-lg font-mono text-sm overflow-x-auto">
-              {`${winTlsPrefix}$script = (iwr ${winIwrFlags}'${protocol}//${serverHost}/api/v1/bootstrap/windows-script').Content; & ([scriptblock]::Create($script)) -NonInteractive`}
+              {`${winTlsPrefix}$script = (iwr ${winIwrFlags}'${protocol}//${serverHost}${BASE_PATH}/api/v1/bootstrap/windows-script').Content; & ([scriptblock]::Create($script)) -NonInteractive`}
             </pre>
           </div>
           <div>
@@ -285,7 +286,7 @@ export default function AddNode() {
               To see what the script will do without making changes:
             </p>
             <pre className="bg-gray-100 text-gray-800 p-3 rounded-lg font-mono text-sm overflow-x-auto">
-              {`${winTlsPrefix}$script = (iwr ${winIwrFlags}'${protocol}//${serverHost}/api/v1/bootstrap/windows-script').Content; & ([scriptblock]::Create($script)) -DryRun`}
+              {`${winTlsPrefix}$script = (iwr ${winIwrFlags}'${protocol}//${serverHost}${BASE_PATH}/api/v1/bootstrap/windows-script').Content; & ([scriptblock]::Create($script)) -DryRun`}
             </pre>
           </div>
         </div>
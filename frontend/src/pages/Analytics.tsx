@@ -21,8 +21,10 @@ import {
   FactDistributionChart,
   InfrastructureTopology,
   TimeSeriesMetrics,
+  FleetTrends,
 } from '../components/charts';
 import UpdatesTab from '../components/analytics/UpdatesTab';
+import CompileErrors from '../components/analytics/CompileErrors';
 import {
   useSavedReports,
   useReportTemplates,
@@ -47,6 +49,7 @@ import type {
   ComplianceBaseline,
   ComplianceRule,
   DriftBaseline,
+  DriftSnapshotPreview,
   NodeGroup,
   ReportResult,
   NodeHealthReport,
@@ -263,6 +266,12 @@ export default function Analytics() {
     enabled: activeTab === 'topology',
   });
 
+  const { data: topology } = useQuery({
+    queryKey: ['topology'],
+    queryFn: () => api.getTopology(),
+    enabled: activeTab === 'topology',
+  });
+
   const {
     data: groups = [],
     isLoading: groupsLoading,
@@ -361,6 +370,9 @@ export default function Analytics() {
       const execution = await executeReport.mutateAsync({ id: reportId });
       if (execution.output_data) {
         setReportResult(execution.output_data as ReportResult);
+      } else if (execution.output_file_path) {
+        // Large outputs are stored on the server and fetched separately
+        setReportResult((await api.getExecutionOutput(execution.id)) as ReportResult);
       }
     } catch (error) {
       console.error('Failed to execute report:', error);
@@ -462,6 +474,16 @@ export default function Analytics() {
             <TimeSeriesMetrics />
           </div>
 
+          {/* Fleet Trends */}
+          <div className="card">
+            <FleetTrends />
+          </div>
+
+          {/* Catalog Compilation Errors */}
+          <div className="card">
+            <CompileErrors />
+          </div>
+
           {/* Two Column Layout */}
           <div className="grid grid-cols-1 lg:grid-cols-2 gap-6">
             <div className="card">
@@ -535,7 +557,7 @@ export default function Analytics() {
 
       {activeTab === 'topology' && (
         <div className="card">
-          <InfrastructureTopology nodes={nodes} groups={groups} />
+          <InfrastructureTopology nodes={nodes} groups={groups} topology={topology} />
         </div>
       )}
 
@@ -1054,6 +1076,19 @@ function ReportResultView({ result }: { result: ReportResult }) {
         <div className="text-sm text-gray-500">
           Compliance Rate: {(data.summary.compliance_rate * 100).toFixed(1)}%
         </div>
+        {(data.baselines?.length ?? 0) > 1 && (
+          <ul className="divide-y divide-gray-100 text-sm">
+            {data.baselines.map((b) => (
+              <li key={b.baseline_id} className="flex justify-between py-1">
+                <span className="text-gray-900">{b.baseline_name}</span>
+                <span className="text-gray-500">
+                  {b.summary.compliance_rate.toFixed(1)}% compliant &middot;{' '}
+                  {b.summary.total_violations} violations
+                </span>
+              </li>
+            ))}
+          </ul>
+        )}
       </div>
     );
   }
@@ -1825,6 +1860,16 @@ function NewDriftBaselineModal({
               </div>
             </div>
 
+            <DriftSnapshotPanel
+              groups={groups}
+              onCapture={(facts, groupId) => {
+                setTrackedFacts(toDriftFactDrafts(facts));
+                if (groupId && !nodeGroupId) {
+                  setNodeGroupId(groupId);
+                }
+              }}
+            />
+
             <DriftFactEditor facts={trackedFacts} onChange={setTrackedFacts} factNames={factNames} />
           </div>
           <div className="px-6 py-4 border-t border-gray-200 flex justify-end gap-3">
@@ -1845,6 +1890,143 @@ function NewDriftBaselineModal({
   );
 }
 
+function DriftSnapshotPanel({
+  groups,
+  onCapture,
+}: {
+  groups: NodeGroup[];
+  onCapture: (facts: Record<string, unknown>, groupId?: string) => void;
+}) {
+  const [sourceType, setSourceType] = useState<'node' | 'group'>('node');
+  const [certname, setCertname] = useState('');
+  const [groupId, setGroupId] = useState('');
+  const [preview, setPreview] = useState<DriftSnapshotPreview | null>(null);
+  const [excluded, setExcluded] = useState<Set<string>>(new Set());
+  const [isCapturing, setIsCapturing] = useState(false);
+  const [error, setError] = useState<string | null>(null);
+
+  const capturedFacts = preview ? Object.keys(preview.baseline_facts).sort() : [];
+
+  const applyExclusions = (snapshot: DriftSnapshotPreview, exclusions: Set<string>) => {
+    const facts = Object.fromEntries(
+      Object.entries(snapshot.baseline_facts).filter(([factName]) => !exclusions.has(factName)),
+    );
+    onCapture(facts, snapshot.source.type === 'group' ? snapshot.source.group_id : undefined);
+  };
+
+  const handleCapture = async () => {
+    setIsCapturing(true);
+    setError(null);
+    try {
+      // Capture volatile facts too so they can be toggled back in
+      const snapshot = await api.previewDriftSnapshot({
+        source:
+          sourceType === 'node'
+            ? { type: 'node', certname: certname.trim() }
+            : { type: 'group', group_id: groupId },
+        exclude_volatile: false,
+      });
+      const exclusions = new Set(snapshot.volatile_facts);
+      setPreview(snapshot);
+      setExcluded(exclusions);
+      applyExclusions(snapshot, exclusions);
+    } catch (err) {
+      setError(err instanceof Error ? err.message : 'Failed to capture facts');
+    } finally {
+      setIsCapturing(false);
+    }
+  };
+
+  const toggleFact = (factName: string) => {
+    if (!preview) return;
+    const exclusions = new Set(excluded);
+    if (exclusions.has(factName)) {
+      exclusions.delete(factName);
+    } else {
+      exclusions.add(factName);
+    }
+    setExcluded(exclusions);
+    applyExclusions(preview, exclusions);
+  };
+
+  return (
+    <div className="border border-gray-200 rounded-lg p-4 space-y-3">
+      <div>
+        <h4 className="text-sm font-medium text-gray-900">Capture from a snapshot</h4>
+        <p className="text-xs text-gray-500">
+          Fill the tracked facts from the current facts of a golden node, or the facts shared by every member of a group.
+        </p>
+      </div>
+      <div className="flex flex-wrap gap-2">
+        <select
+          value={sourceType}
+          onChange={(e) => setSourceType(e.target.value as 'node' | 'group')}
+          className="px-3 py-2 border border-gray-300 rounded-lg focus:ring-2 focus:ring-primary-500 focus:border-primary-500"
+        >
+          <option value="node">Node</option>
+          <option value="group">Node group</option>
+        </select>
+        {sourceType === 'node' ? (
+          <input
+            type="text"
+            value={certname}
+            onChange={(e) => setCertname(e.target.value)}
+            placeholder="certname"
+            className="flex-1 px-3 py-2 border border-gray-300 rounded-lg focus:ring-2 focus:ring-primary-500 focus:border-primary-500"
+          />
+        ) : (
+          <select
+            value={groupId}
+            onChange={(e) => setGroupId(e.target.value)}
+            className="flex-1 px-3 py-2 border border-gray-300 rounded-lg focus:ring-2 focus:ring-primary-500 focus:border-primary-500"
+          >
+            <option value="">Select a group</option>
+            {groups.map((group) => (
+              <option key={group.id} value={group.id}>
+                {group.name}
+              </option>
+            ))}
+          </select>
+        )}
+        <button
+          type="button"
+          onClick={handleCapture}
+          disabled={isCapturing || (sourceType === 'node' ? !certname.trim() : !groupId)}
+          className="btn btn-secondary"
+        >
+          {isCapturing ? 'Capturing...' : 'Capture Facts'}
+        </button>
+      </div>
+      {error && <p className="text-sm text-red-600">{error}</p>}
+      {preview && (
+        <div className="space-y-2">
+          <p className="text-xs text-gray-500">
+            {capturedFacts.length} facts from {preview.node_count} node{preview.node_count === 1 ? '' : 's'}
+            {preview.differing_facts.length > 0 &&
+              `; ${preview.differing_facts.length} left out because they differ between nodes`}
+            . Uncheck facts to exclude them; volatile facts start excluded.
+          </p>
+          <div className="max-h-48 overflow-y-auto grid grid-cols-1 md:grid-cols-2 gap-1">
+            {capturedFacts.map((factName) => (
+              <label key={factName} className="flex items-center gap-2 text-sm text-gray-700">
+                <input
+                  type="checkbox"
+                  checked={!excluded.has(factName)}
+                  onChange={() => toggleFact(factName)}
+                />
+                <span className="font-mono truncate">{factName}</span>
+                {preview.volatile_facts.includes(factName) && (
+                  <span className="text-xs text-amber-600">volatile</span>
+                )}
+              </label>
+            ))}
+          </div>
+        </div>
+      )}
+    </div>
+  );
+}
+
 function EditDriftBaselineModal({
   baseline,
   factNames,
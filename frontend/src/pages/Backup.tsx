@@ -105,9 +105,13 @@ export default function Backup() {
 
   const handleRestoreBackup = async (id: string, password: string) => {
     try {
-      await restoreBackupMutation.mutateAsync({ id, request: { password, confirm: true } });
+      const restore = await restoreBackupMutation.mutateAsync({ id, request: { password, confirm: true } });
       setRestoringBackup(null);
-      alert('Backup restored successfully! The server may need to restart.');
+      alert(
+        restore.restart_required
+          ? 'Backup restored successfully! Restart the server to switch to the restored database.'
+          : 'Backup restored successfully!'
+      );
     } catch {
       // Error handled by mutation
     }
@@ -20,6 +20,7 @@ import {
   useSignCertificate,
   useRejectCertificate,
   useRevokeCertificate,
+  useCAChain,
 } from '../hooks/useCA';
 import type { CertificateRequest, Certificate } from '../types';
 
@@ -410,6 +411,8 @@ function OverviewTab({
         </div>
       )}
 
+      <CAChainPanel />
+
       {/* Quick Actions */}
       <div className="bg-white rounded-lg border border-gray-200 p-6">
         <h3 className="text-lg font-medium text-gray-900 mb-4">Quick Actions</h3>
@@ -447,6 +450,62 @@ function OverviewTab({
   );
 }
 
+// CA Chain Panel Component
+function CAChainPanel() {
+  const { data: chain } = useCAChain();
+
+  // A single self-signed CA needs no chain view
+  if (!chain || chain.certificates.length < 2) return null;
+
+  return (
+    <div className="bg-white rounded-lg border border-gray-200 p-6">
+      <h3 className="text-lg font-medium text-gray-900 mb-4 flex items-center gap-2">
+        <Shield className="w-5 h-5" />
+        CA Chain
+      </h3>
+      {chain.warnings.length > 0 && (
+        <div className="mb-4 bg-yellow-50 border border-yellow-200 rounded-md p-3 space-y-1">
+          {chain.warnings.map((warning) => (
+            <p key={warning} className="text-sm text-yellow-800 flex items-center gap-2">
+              <AlertTriangle className="w-4 h-4 text-yellow-600" />
+              {warning}
+            </p>
+          ))}
+        </div>
+      )}
+      <ul className="divide-y divide-gray-200">
+        {chain.certificates.map((cert) => (
+          <li key={cert.fingerprint} className="py-3 flex items-start justify-between gap-4">
+            <div className="min-w-0">
+              <p className="text-sm font-medium text-gray-900 break-all">{cert.subject}</p>
+              <p className="text-xs text-gray-500 break-all">Issued by {cert.issuer}</p>
+              <p className="mt-1 font-mono text-xs text-gray-400 break-all">{cert.fingerprint}</p>
+            </div>
+            <div className="text-right shrink-0">
+              <span
+                className={clsx(
+                  'inline-block px-2 py-0.5 text-xs font-medium rounded-full capitalize',
+                  cert.role === 'root' ? 'bg-purple-100 text-purple-800' : 'bg-blue-100 text-blue-800'
+                )}
+              >
+                {cert.role}
+              </span>
+              <p
+                className={clsx(
+                  'mt-1 text-xs',
+                  cert.expiring_soon ? 'text-orange-600 font-medium' : 'text-gray-500'
+                )}
+              >
+                Expires {new Date(cert.not_after).toLocaleDateString()}
+              </p>
+            </div>
+          </li>
+        ))}
+      </ul>
+    </div>
+  );
+}
+
 // Requests Tab Component
 function RequestsTab({
   requests,
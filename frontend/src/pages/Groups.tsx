@@ -209,8 +209,15 @@ export default function Groups() {
   });
 
   const updateMutation = useMutation({
-    mutationFn: ({ id, data }: { id: string; data: Parameters<typeof api.updateGroup>[1] }) =>
-      api.updateGroup(id, data),
+    mutationFn: ({
+      id,
+      data,
+      version,
+    }: {
+      id: string;
+      data: Parameters<typeof api.updateGroup>[1];
+      version: number;
+    }) => api.updateGroup(id, data, version),
     onSuccess: (updatedGroup) => {
       queryClient.invalidateQueries({ queryKey: ['groups'] });
       setSelectedGroup(updatedGroup);
@@ -463,6 +470,7 @@ export default function Groups() {
     if (!selectedGroup) return;
     updateMutation.mutate({
       id: selectedGroup.id,
+      version: selectedGroup.version,
       data: {
         name: formName,
         description: formDescription || undefined,
@@ -500,6 +508,7 @@ export default function Groups() {
     };
     updateMutation.mutate({
       id: selectedGroup.id,
+      version: selectedGroup.version,
       data: { classes: updatedClasses },
     });
     setIsAddClassOpen(false);
@@ -512,6 +521,7 @@ export default function Groups() {
     delete updatedClasses[className];
     updateMutation.mutate({
       id: selectedGroup.id,
+      version: selectedGroup.version,
       data: { classes: updatedClasses },
     });
   };
@@ -537,6 +547,7 @@ export default function Groups() {
     };
     updateMutation.mutate({
       id: selectedGroup.id,
+      version: selectedGroup.version,
       data: { classes: updatedClasses },
     });
   };
@@ -552,6 +563,7 @@ export default function Groups() {
     };
     updateMutation.mutate({
       id: selectedGroup.id,
+      version: selectedGroup.version,
       data: { classes: updatedClasses },
     });
   };
@@ -566,6 +578,7 @@ export default function Groups() {
     };
     updateMutation.mutate({
       id: selectedGroup.id,
+      version: selectedGroup.version,
       data: { variables: updatedVars },
     });
     setIsAddVarOpen(false);
@@ -578,6 +591,7 @@ export default function Groups() {
     delete vars[key];
     updateMutation.mutate({
       id: selectedGroup.id,
+      version: selectedGroup.version,
       data: { variables: vars },
     });
   };
@@ -612,6 +626,7 @@ export default function Groups() {
 
     updateMutation.mutate({
       id: selectedGroup.id,
+      version: selectedGroup.version,
       data: { variables: currentVars },
     });
     resetEditingVariableForm();
@@ -33,6 +33,7 @@ import {
 import { api } from '../services/api';
 import { useNodeVulnerabilities } from '../hooks/useCve';
 import { getDeleteNodeErrorMessage } from './nodeDetailErrors';
+import { NodePerformanceChart } from '../components/charts';
 import { usePermissionsStore } from '../stores/permissionsStore';
 import type {
   Report,
@@ -2392,7 +2393,10 @@ export default function NodeDetail() {
               <div className="animate-spin rounded-full h-8 w-8 border-b-2 border-primary-600" />
             </div>
           ) : (
-            <ReportsTimeline reports={reports} />
+            <div className="space-y-6">
+              {certname && <NodePerformanceChart certname={certname} />}
+              <ReportsTimeline reports={reports} />
+            </div>
           )
         )}
 
@@ -50,6 +50,7 @@ function PendingRemovalBadge({ removal }: { removal: PendingNodeRemoval }) {
     revoked_certificate: 'Revoked Certificate',
     no_certificate: 'No Certificate',
     manual: 'Manual',
+    stale_node: 'Stale Node',
   };
 
   const reasonLabel = reasonLabels[removal.removal_reason] || removal.removal_reason;
@@ -1,6 +1,6 @@
 import { useState } from 'react';
-import { useMutation, useQueryClient } from '@tanstack/react-query';
-import { User, Lock, Mail, AlertCircle, CheckCircle, Eye, EyeOff, Sun, Moon } from 'lucide-react';
+import { useMutation, useQuery, useQueryClient } from '@tanstack/react-query';
+import { User, Lock, Mail, AlertCircle, CheckCircle, Eye, EyeOff, Sun, Moon, History } from 'lucide-react';
 import { api } from '../services/api';
 import { useAuthStore } from '../stores/authStore';
 import { useThemeStore } from '../stores/themeStore';
@@ -20,6 +20,12 @@ export default function Profile() {
   const [error, setError] = useState<string | null>(null);
   const [success, setSuccess] = useState<string | null>(null);
 
+  const { data: currentUser } = useQuery({
+    queryKey: ['currentUser'],
+    queryFn: api.getCurrentUser,
+  });
+  const recentLogins = currentUser?.recent_logins ?? [];
+
   const changePasswordMutation = useMutation({
     mutationFn: () => api.changePassword(currentPassword, newPassword),
     onSuccess: () => {
@@ -110,6 +116,52 @@ export default function Profile() {
         </div>
       </div>
 
+      {/* Recent Logins Card */}
+      {recentLogins.length > 0 && (
+        <div className="card mb-6">
+          <h2 className="text-lg font-semibold text-gray-900 dark:text-gray-100 mb-1 flex items-center gap-2">
+            <History className="w-5 h-5" />
+            Recent Logins
+          </h2>
+          <p className="text-sm text-gray-500 dark:text-gray-400 mb-4">
+            If you do not recognize a login, change your password.
+          </p>
+          <ul className="divide-y divide-gray-200 dark:divide-gray-700">
+            {recentLogins.map((login) => (
+              <li key={login.id} className="py-3 flex items-start justify-between gap-4">
+                <div className="min-w-0">
+                  <p className="text-sm text-gray-900 dark:text-gray-100 truncate" title={login.user_agent ?? undefined}>
+                    {login.user_agent || 'Unknown client'}
+                  </p>
+                  <p className="text-xs text-gray-500 dark:text-gray-400">
+                    {new Date(login.created_at).toLocaleString()}
+                    {login.location && ` · ${login.location}`}
+                    {login.ip_address && ` · ${login.ip_address}`}
+                    {login.method === 'saml' && ' · SSO'}
+                  </p>
+                </div>
+                <div className="flex flex-shrink-0 gap-1">
+                  {(login.new_device || login.new_location) && (
+                    <span className="inline-flex items-center px-2 py-0.5 rounded-full text-xs font-medium bg-warning-100 text-warning-800 dark:bg-warning-900/40 dark:text-warning-400">
+                      {login.new_device ? 'New device' : 'New location'}
+                    </span>
+                  )}
+                  <span
+                    className={`inline-flex items-center px-2 py-0.5 rounded-full text-xs font-medium ${
+                      login.success
+                        ? 'bg-success-100 text-success-800 dark:bg-success-900/40 dark:text-success-400'
+                        : 'bg-danger-100 text-danger-800 dark:bg-danger-900/40 dark:text-danger-400'
+                    }`}
+                  >
+                    {login.success ? 'Success' : 'Failed'}
+                  </span>
+                </div>
+              </li>
+            ))}
+          </ul>
+        </div>
+      )}
+
       {/* Theme Selection Card */}
       <div className="card mb-6">
         <h2 className="text-lg font-semibold text-gray-900 dark:text-gray-100 mb-4">Appearance</h2>
@@ -1,6 +1,7 @@
 import { useEffect, useState } from 'react';
 import { useNavigate, useSearchParams } from 'react-router-dom';
 import { Loader2, AlertCircle } from 'lucide-react';
+import { api } from '../services/api';
 import { useAuthStore } from '../stores/authStore';
 import { usePermissionsStore } from '../stores/permissionsStore';
 
@@ -23,7 +24,20 @@ export default function SamlCallback() {
     }
 
     if (!accessToken) {
-      setError('No authentication token received');
+      // In cookie auth mode the server set the session cookies instead
+      api.getCurrentUser()
+        .then((user) => {
+          login({
+            id: user.id,
+            username: user.username,
+            email: user.email,
+            role: user.role as 'admin' | 'user' | 'viewer',
+            force_password_change: user.force_password_change,
+          });
+          fetchPermissions(user.id);
+          navigate(redirect, { replace: true });
+        })
+        .catch(() => setError('No authentication token received'));
       return;
     }
 
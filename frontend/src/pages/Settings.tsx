@@ -42,6 +42,7 @@ import {
   useUpdateUpdateJobSettings,
 } from '../hooks/useSettings';
 import { api } from '../services/api';
+import { withBasePath } from '../services/basePath';
 import {
   useCveFeeds,
   useCreateCveFeed,
@@ -553,6 +554,10 @@ function RbacSettingsTab() {
             label="Lockout Duration"
             value={`${rbacConfig.lockout_duration_minutes} min`}
           />
+          <SettingRow
+            label="Max Lockout Duration"
+            value={`${rbacConfig.max_lockout_minutes} min`}
+          />
         </div>
       </div>
 
@@ -1018,7 +1023,7 @@ function ServerInfoTab() {
             {serverInfo.saml.configured && (
               <div className="pt-4 border-t border-gray-100">
                 <a
-                  href="/api/v1/auth/saml/metadata"
+                  href={withBasePath('/api/v1/auth/saml/metadata')}
                   target="_blank"
                   rel="noopener noreferrer"
                   className="btn btn-secondary btn-sm flex items-center inline-flex"
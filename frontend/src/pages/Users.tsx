@@ -1,9 +1,9 @@
 import { useState } from 'react';
 import { useQuery, useMutation, useQueryClient } from '@tanstack/react-query';
-import { Plus, User, Shield, Trash2, Mail, ChevronRight, X, Loader2, Key, Globe } from 'lucide-react';
+import { Plus, User, Shield, Trash2, Mail, ChevronRight, X, Loader2, Key, Globe, Bot, Unlock } from 'lucide-react';
 import clsx from 'clsx';
 import { api } from '../services/api';
-import type { Role, AuthProvider } from '../types';
+import type { Role, AuthProvider, UserKind } from '../types';
 
 interface UserData {
   id: string;
@@ -11,6 +11,7 @@ interface UserData {
   email: string;
   role: string;
   auth_provider?: AuthProvider;
+  kind?: UserKind;
   roles?: Array<{ id: string; name: string; display_name: string }>;
   created_at: string;
 }
@@ -53,6 +54,14 @@ export default function Users() {
     },
   });
 
+  const [unlockMessage, setUnlockMessage] = useState<string | null>(null);
+  const unlockMutation = useMutation({
+    mutationFn: api.unlockUser,
+    onSuccess: (result) => {
+      setUnlockMessage(result.was_locked ? 'Account unlocked' : 'Account was not locked');
+    },
+  });
+
   const assignRolesMutation = useMutation({
     mutationFn: ({ userId, roleIds }: { userId: string; roleIds: string[] }) =>
       api.assignUserRoles(userId, roleIds),
@@ -106,6 +115,7 @@ export default function Users() {
   const handleSelectUser = (user: UserData) => {
     setSelectedUser(user);
     setPendingRoleChanges(new Set());
+    setUnlockMessage(null);
   };
 
   const handleRoleToggle = (roleId: string) => {
@@ -358,6 +368,12 @@ export default function Users() {
                       <User className="w-4 h-4 text-primary-600" />
                     </div>
                     <span className="font-medium text-gray-900">{user.username}</span>
+                    {user.kind === 'service_account' && (
+                      <span className="ml-2 inline-flex items-center px-2 py-1 rounded-full text-xs font-medium bg-amber-100 text-amber-700">
+                        <Bot className="w-3 h-3 mr-1" />
+                        Service account
+                      </span>
+                    )}
                   </div>
                 </td>
                 <td className="px-6 py-4 whitespace-nowrap">
@@ -534,7 +550,27 @@ export default function Users() {
               </div>
             </div>
 
-            <div className="p-6 border-t border-gray-200">
+            <div className="p-6 border-t border-gray-200 space-y-3">
+              {selectedUser.kind !== 'service_account' && selectedUser.auth_provider !== 'saml' && (
+                <div>
+                  <button
+                    onClick={() => unlockMutation.mutate(selectedUser.id)}
+                    disabled={unlockMutation.isPending}
+                    className="btn btn-secondary w-full flex items-center justify-center"
+                    title="Clear failed login attempts and end a lockout"
+                  >
+                    {unlockMutation.isPending ? (
+                      <Loader2 className="w-4 h-4 mr-2 animate-spin" />
+                    ) : (
+                      <Unlock className="w-4 h-4 mr-2" />
+                    )}
+                    Unlock Account
+                  </button>
+                  {unlockMessage && (
+                    <p className="text-xs text-gray-500 text-center mt-1">{unlockMessage}</p>
+                  )}
+                </div>
+              )}
               <button
                 onClick={() => deleteMutation.mutate(selectedUser.id)}
                 disabled={deleteMutation.isPending}
@@ -8,11 +8,26 @@ import type {
 import type {
   Node,
   NodesQueryParams,
+  NodeMetadata,
+  NodeMetadataQuery,
+  UpdateNodeMetadataRequest,
   PaginatedNodes,
   NodeStats,
+  InfrastructureTopologyGraph,
   NodeGroup,
+  SmartList,
+  SmartListCount,
+  CreateSmartListRequest,
+  UpdateSmartListRequest,
+  MaintenanceWindow,
+  CreateMaintenanceWindowRequest,
+  UpdateMaintenanceWindowRequest,
+  NodeInMaintenance,
   Report,
   ResourceEvent,
+  ReportDrillDown,
+  ReportDiff,
+  NodePerformance,
   CreateGroupRequest,
   UpdateGroupRequest,
   CreateRuleRequest,
@@ -22,9 +37,20 @@ import type {
   Permission,
   CreateRoleRequest,
   UserResponse,
+  CurrentUserResponse,
   CreateUserRequest,
   UpdateUserRequest,
+  ImportUserRow,
+  ImportUsersResponse,
   EffectivePermissions,
+  RoleEffectivePermissions,
+  RoleElevation,
+  CreateElevationRequest,
+  ElevationStatus,
+  ImpersonationSession,
+  StartImpersonationRequest,
+  ImpersonationResponse,
+  AuthSession,
   ResourceInfo,
   ActionInfo,
   PermissionMatrix,
@@ -37,6 +63,7 @@ import type {
   GenerateFactsRequest,
   GeneratedFacts,
   ExportFormat,
+  ExternalFactFile,
   SettingsResponse,
   DeleteNodeResponse,
   DashboardConfig,
@@ -57,6 +84,17 @@ import type {
   SignResponse,
   RejectResponse,
   RevokeResponse,
+  BulkSignRequest,
+  BulkCertificateResponse,
+  CrlResponse,
+  CaSnapshotSummary,
+  CaChain,
+  ChainValidation,
+  RenewalCampaign,
+  RenewalCampaignDetail,
+  RenewalCandidate,
+  RenewalTargetStatus,
+  CreateRenewalCampaignRequest,
   RenewCARequest,
   RenewCAResponse,
   SavedReport,
@@ -65,15 +103,26 @@ import type {
   ReportSchedule,
   CreateScheduleRequest,
   UpdateScheduleRequest,
+  ReportEmailTemplate,
+  UpsertReportEmailTemplateRequest,
   ReportExecution,
   ExecuteReportRequest,
   ReportTemplate,
   ComplianceBaseline,
+  CorrectiveChangeAnalytics,
+  CompileErrorAnalytics,
+  FleetMetricsSnapshot,
   CreateComplianceBaselineRequest,
   UpdateComplianceBaselineRequest,
+  ComplianceRulePack,
+  ImportRulePackRequest,
+  ApplyRulePackRequest,
   DriftBaseline,
   CreateDriftBaselineRequest,
   UpdateDriftBaselineRequest,
+  DriftSnapshotRequest,
+  DriftSnapshotPreview,
+  CreateDriftBaselineFromSnapshotRequest,
   GenerateReportRequest,
   ReportType,
   ReportQueryConfig,
@@ -97,6 +146,7 @@ import type {
   // Code Deploy types
   CodeSshKey,
   CreateSshKeyRequest,
+  GenerateSshKeyRequest,
   CodeRepository,
   CreateRepositoryRequest,
   UpdateRepositoryRequest,
@@ -109,6 +159,10 @@ import type {
   ListDeploymentsQuery,
   ListEnvironmentsQuery,
   CodePatToken,
+  CredentialHealthReport,
+  DeploymentImpact,
+  CodeCanary,
+  StartCanaryRequest,
   CreatePatTokenRequest,
   UpdatePatTokenRequest,
   // Group-scoped permissions types
@@ -130,6 +184,7 @@ import type {
   NodeRemovalAudit,
   PendingRemovalStats,
   NodeRemovalFeatureStatus,
+  JanitorRun,
   MarkNodeForRemovalRequest,
   ExtendRemovalDeadlineRequest,
   // Bootstrap types
@@ -162,13 +217,17 @@ import type {
   UpdatePreviewRequest,
   UpdatePreviewResponse,
 } from '../types';
+import { withBasePath } from './basePath';
 
 const client = axios.create({
-  baseURL: '/api/v1',
+  baseURL: withBasePath('/api/v1'),
   timeout: 15_000,
   headers: {
     'Content-Type': 'application/json',
   },
+  // Echo the CSRF cookie the server sets when auth.csrf is enabled
+  xsrfCookieName: 'openvox_csrf',
+  xsrfHeaderName: 'X-CSRF-Token',
 });
 
 interface RetriableRequestConfig {
@@ -177,7 +236,7 @@ interface RetriableRequestConfig {
   headers?: Record<string, string>;
 }
 
-let refreshRequestPromise: Promise<string> | null = null;
+let refreshRequestPromise: Promise<string | null> | null = null;
 
 const isAuthEndpoint = (url?: string): boolean => {
   if (!url) {
@@ -191,21 +250,43 @@ const isAuthEndpoint = (url?: string): boolean => {
   );
 };
 
-const refreshAccessToken = async (): Promise<string> => {
+// Resolves to the new access token, or null for a cookie session, where the
+// server replaces the session cookies itself
+const refreshAccessToken = async (): Promise<string | null> => {
+  if (useAuthStore.getState().cookieSession) {
+    await client.post('/auth/refresh', {});
+    return null;
+  }
+
   const refreshToken = localStorage.getItem('refresh_token');
 
   if (!refreshToken) {
     throw new Error('No refresh token available');
   }
 
-  const response = await client.post('/auth/refresh', { refresh_token: refreshToken });
+  let response;
+  try {
+    response = await client.post('/auth/refresh', { refresh_token: refreshToken });
+  } catch (error) {
+    // Refresh tokens are single-use: another tab may have exchanged this one
+    // first and stored its replacement
+    const currentToken = localStorage.getItem('auth_token');
+    if (localStorage.getItem('refresh_token') !== refreshToken && currentToken) {
+      return currentToken;
+    }
+    throw error;
+  }
   const newAccessToken = response.data?.access_token as string | undefined;
+  const newRefreshToken = response.data?.refresh_token as string | undefined;
 
   if (!newAccessToken) {
     throw new Error('No access token returned by refresh endpoint');
   }
 
   localStorage.setItem('auth_token', newAccessToken);
+  if (newRefreshToken) {
+    localStorage.setItem('refresh_token', newRefreshToken);
+  }
   useAuthStore.setState((state) => ({
     ...state,
     token: newAccessToken,
@@ -234,7 +315,7 @@ client.interceptors.response.use(
       status === 401
       && !originalRequest._retry
       && !isAuthEndpoint(originalRequest.url)
-      && localStorage.getItem('refresh_token')
+      && (localStorage.getItem('refresh_token') || useAuthStore.getState().cookieSession)
     ) {
       originalRequest._retry = true;
 
@@ -246,10 +327,12 @@ client.interceptors.response.use(
         }
 
         const newAccessToken = await refreshRequestPromise;
-        originalRequest.headers = {
-          ...(originalRequest.headers ?? {}),
-          Authorization: `Bearer ${newAccessToken}`,
-        };
+        if (newAccessToken) {
+          originalRequest.headers = {
+            ...(originalRequest.headers ?? {}),
+            Authorization: `Bearer ${newAccessToken}`,
+          };
+        }
 
         return client(originalRequest);
       } catch {
@@ -270,8 +353,8 @@ client.interceptors.response.use(
 
       useAuthStore.getState().logout(reason);
 
-      if (typeof window !== 'undefined' && window.location.pathname !== '/login') {
-        window.location.replace('/login');
+      if (typeof window !== 'undefined' && window.location.pathname !== withBasePath('/login')) {
+        window.location.replace(withBasePath('/login'));
       }
     }
 
@@ -279,10 +362,11 @@ client.interceptors.response.use(
   }
 );
 
-// Auth response types
+// Auth response types; the tokens are left out when the server keeps them
+// in cookies (auth.mode: cookie)
 interface LoginResponse {
-  access_token: string;
-  refresh_token: string;
+  access_token?: string;
+  refresh_token?: string;
   token_type: string;
   expires_in: number;
   user: {
@@ -295,7 +379,8 @@ interface LoginResponse {
 }
 
 interface RefreshResponse {
-  access_token: string;
+  access_token?: string;
+  refresh_token?: string;
   token_type: string;
   expires_in: number;
 }
@@ -324,7 +409,7 @@ export const api = {
     return response.data;
   },
 
-  getCurrentUser: async (): Promise<UserResponse> => {
+  getCurrentUser: async (): Promise<CurrentUserResponse> => {
     const response = await client.get('/auth/me');
     return response.data;
   },
@@ -363,6 +448,14 @@ export const api = {
     return response.data;
   },
 
+  // Graph of the servers, PuppetDB, the CA and the agents with their health
+  getTopology: async (includeAgents = true): Promise<InfrastructureTopologyGraph> => {
+    const response = await client.get('/nodes/topology', {
+      params: { include_agents: includeAgents },
+    });
+    return response.data;
+  },
+
   getNode: async (certname: string): Promise<Node | null> => {
     const response = await client.get(`/nodes/${certname}`);
     return response.data;
@@ -378,6 +471,22 @@ export const api = {
     return response.data;
   },
 
+  getNodeReportDiff: async (
+    certname: string,
+    params: { from: string; to: string; min_increase_seconds?: number; min_increase_percent?: number }
+  ): Promise<ReportDiff> => {
+    const response = await client.get(`/nodes/${certname}/reports/diff`, { params });
+    return response.data;
+  },
+
+  getNodePerformance: async (
+    certname: string,
+    params?: { days?: number; min_increase_seconds?: number; min_increase_percent?: number }
+  ): Promise<NodePerformance> => {
+    const response = await client.get(`/nodes/${certname}/performance`, { params });
+    return response.data;
+  },
+
   getNodeInventory: async (certname: string): Promise<NodeInventory | null> => {
     const response = await client.get(`/nodes/${certname}/inventory`);
     return response.data;
@@ -393,6 +502,29 @@ export const api = {
     return response.data;
   },
 
+  // Local node metadata; tags are usable in rules as the `webui.tags` fact
+  getNodeMetadata: async (certname: string): Promise<NodeMetadata> => {
+    const response = await client.get(`/nodes/${certname}/metadata`);
+    return response.data;
+  },
+
+  updateNodeMetadata: async (
+    certname: string,
+    data: UpdateNodeMetadataRequest
+  ): Promise<NodeMetadata> => {
+    const response = await client.put(`/nodes/${certname}/metadata`, data);
+    return response.data;
+  },
+
+  deleteNodeMetadata: async (certname: string): Promise<void> => {
+    await client.delete(`/nodes/${certname}/metadata`);
+  },
+
+  searchNodeMetadata: async (params: NodeMetadataQuery = {}): Promise<NodeMetadata[]> => {
+    const response = await client.get('/nodes/metadata', { params });
+    return response.data;
+  },
+
   getInventorySummary: async (): Promise<InventoryFleetStatusSummary> => {
     const response = await client.get('/inventory/summary');
     return response.data;
@@ -502,8 +634,15 @@ export const api = {
     return response.data;
   },
 
-  updateGroup: async (id: string, data: UpdateGroupRequest): Promise<NodeGroup> => {
-    const response = await client.put(`/groups/${id}`, data);
+  updateGroup: async (
+    id: string,
+    data: UpdateGroupRequest,
+    version?: number
+  ): Promise<NodeGroup> => {
+    // The server rejects the update when the group changed since `version`
+    const response = await client.put(`/groups/${id}`, data, {
+      headers: { 'If-Match': version === undefined ? '*' : `"${version}"` },
+    });
     return response.data;
   },
 
@@ -566,6 +705,89 @@ export const api = {
     return response.data;
   },
 
+  // Smart Lists (saved node filters)
+  getSmartLists: async (): Promise<SmartList[]> => {
+    const response = await client.get('/smart-lists');
+    return response.data;
+  },
+
+  getSmartList: async (id: string): Promise<SmartList> => {
+    const response = await client.get(`/smart-lists/${id}`);
+    return response.data;
+  },
+
+  createSmartList: async (data: CreateSmartListRequest): Promise<SmartList> => {
+    const response = await client.post('/smart-lists', data);
+    return response.data;
+  },
+
+  updateSmartList: async (id: string, data: UpdateSmartListRequest): Promise<SmartList> => {
+    const response = await client.put(`/smart-lists/${id}`, data);
+    return response.data;
+  },
+
+  deleteSmartList: async (id: string): Promise<void> => {
+    await client.delete(`/smart-lists/${id}`);
+  },
+
+  getSmartListNodes: async (id: string): Promise<Node[]> => {
+    const response = await client.get(`/smart-lists/${id}/nodes`);
+    return response.data;
+  },
+
+  getSmartListCounts: async (refresh = false): Promise<SmartListCount[]> => {
+    const response = await client.get('/smart-lists/counts', { params: { refresh } });
+    return response.data;
+  },
+
+  getSmartListCount: async (id: string, refresh = false): Promise<SmartListCount> => {
+    const response = await client.get(`/smart-lists/${id}/count`, { params: { refresh } });
+    return response.data;
+  },
+
+  exportSmartList: async (id: string, format: 'csv' | 'json' = 'csv'): Promise<Blob> => {
+    const response = await client.get(`/smart-lists/${id}/export`, {
+      params: { format },
+      responseType: 'blob',
+    });
+    return response.data;
+  },
+
+  // Maintenance windows
+  getMaintenanceWindows: async (): Promise<MaintenanceWindow[]> => {
+    const response = await client.get('/maintenance-windows');
+    return response.data;
+  },
+
+  getMaintenanceWindow: async (id: string): Promise<MaintenanceWindow> => {
+    const response = await client.get(`/maintenance-windows/${id}`);
+    return response.data;
+  },
+
+  createMaintenanceWindow: async (
+    data: CreateMaintenanceWindowRequest
+  ): Promise<MaintenanceWindow> => {
+    const response = await client.post('/maintenance-windows', data);
+    return response.data;
+  },
+
+  updateMaintenanceWindow: async (
+    id: string,
+    data: UpdateMaintenanceWindowRequest
+  ): Promise<MaintenanceWindow> => {
+    const response = await client.put(`/maintenance-windows/${id}`, data);
+    return response.data;
+  },
+
+  deleteMaintenanceWindow: async (id: string): Promise<void> => {
+    await client.delete(`/maintenance-windows/${id}`);
+  },
+
+  getNodesInMaintenance: async (): Promise<NodeInMaintenance[]> => {
+    const response = await client.get('/maintenance-windows/active');
+    return response.data;
+  },
+
   // Facts
   getFacts: async (params?: { name?: string; certname?: string }): Promise<Array<{ certname: string; name: string; value: unknown }>> => {
     const response = await client.get('/facts', { params });
@@ -650,6 +872,14 @@ export const api = {
     return response.data;
   },
 
+  getReportDrillDown: async (
+    hash: string,
+    params?: { status?: string; type?: string; level?: string }
+  ): Promise<ReportDrillDown> => {
+    const response = await client.get(`/reports/${hash}/drilldown`, { params });
+    return response.data;
+  },
+
   // Query
   executeQuery: async (query: string): Promise<unknown[]> => {
     const response = await client.post('/query', { query });
@@ -682,6 +912,21 @@ export const api = {
     return response.data;
   },
 
+  getRoleParents: async (id: string): Promise<Role[]> => {
+    const response = await client.get(`/roles/${id}/parents`);
+    return response.data;
+  },
+
+  setRoleParents: async (id: string, parentIds: string[]): Promise<Role> => {
+    const response = await client.put(`/roles/${id}/parents`, { parent_ids: parentIds });
+    return response.data;
+  },
+
+  getRoleEffectivePermissions: async (id: string): Promise<RoleEffectivePermissions> => {
+    const response = await client.get(`/roles/${id}/effective-permissions`);
+    return response.data;
+  },
+
   getRolePermissions: async (id: string): Promise<Permission[]> => {
     const response = await client.get(`/roles/${id}/permissions`);
     return response.data;
@@ -692,6 +937,79 @@ export const api = {
     return response.data;
   },
 
+  // Role elevations (time-bound, approval-gated)
+  getElevations: async (status?: ElevationStatus): Promise<RoleElevation[]> => {
+    const response = await client.get('/elevations', { params: status ? { status } : {} });
+    return response.data;
+  },
+
+  requestElevation: async (data: CreateElevationRequest): Promise<RoleElevation> => {
+    const response = await client.post('/elevations', data);
+    return response.data;
+  },
+
+  approveElevation: async (id: string, comment?: string): Promise<RoleElevation> => {
+    const response = await client.post(`/elevations/${id}/approve`, { comment });
+    return response.data;
+  },
+
+  denyElevation: async (id: string, comment?: string): Promise<RoleElevation> => {
+    const response = await client.post(`/elevations/${id}/deny`, { comment });
+    return response.data;
+  },
+
+  revokeElevation: async (id: string, comment?: string): Promise<RoleElevation> => {
+    const response = await client.post(`/elevations/${id}/revoke`, { comment });
+    return response.data;
+  },
+
+  activateElevation: async (id: string): Promise<RefreshResponse> => {
+    const response = await client.post(`/elevations/${id}/activate`);
+    return response.data;
+  },
+
+  // User impersonation (audited, admin only)
+  getImpersonations: async (params?: {
+    impersonator_id?: string;
+    user_id?: string;
+    limit?: number;
+  }): Promise<ImpersonationSession[]> => {
+    const response = await client.get('/impersonation', { params });
+    return response.data;
+  },
+
+  startImpersonation: async (data: StartImpersonationRequest): Promise<ImpersonationResponse> => {
+    const response = await client.post('/impersonation', data);
+    return response.data;
+  },
+
+  getCurrentImpersonation: async (): Promise<ImpersonationSession> => {
+    const response = await client.get('/impersonation/current');
+    return response.data;
+  },
+
+  endImpersonation: async (): Promise<ImpersonationSession> => {
+    const response = await client.post('/impersonation/end');
+    return response.data;
+  },
+
+  // Login sessions
+  getSessions: async (userId?: string): Promise<AuthSession[]> => {
+    const response = await client.get('/sessions', { params: userId ? { user_id: userId } : {} });
+    return response.data;
+  },
+
+  revokeSession: async (id: string): Promise<void> => {
+    await client.delete(`/sessions/${id}`);
+  },
+
+  revokeAllSessions: async (userId?: string): Promise<{ revoked: number }> => {
+    const response = await client.delete('/sessions', {
+      params: userId ? { user_id: userId } : {},
+    });
+    return response.data;
+  },
+
   // Users
   getUsers: async (): Promise<UserResponse[]> => {
     const response = await client.get('/users');
@@ -708,6 +1026,18 @@ export const api = {
     return response.data;
   },
 
+  // Rows as JSON, or CSV text with a header row
+  importUsers: async (
+    data: ImportUserRow[] | string,
+    dryRun = false
+  ): Promise<ImportUsersResponse> => {
+    const response = await client.post('/users/import', data, {
+      params: dryRun ? { dry_run: true } : {},
+      headers: typeof data === 'string' ? { 'Content-Type': 'text/csv' } : undefined,
+    });
+    return response.data;
+  },
+
   updateUser: async (id: string, data: UpdateUserRequest): Promise<UserResponse> => {
     const response = await client.put(`/users/${id}`, data);
     return response.data;
@@ -718,6 +1048,11 @@ export const api = {
     return response.data;
   },
 
+  unlockUser: async (id: string): Promise<{ was_locked: boolean }> => {
+    const response = await client.post(`/users/${id}/unlock`);
+    return response.data;
+  },
+
   getUserRoles: async (id: string): Promise<Role[]> => {
     const response = await client.get(`/users/${id}/roles`);
     return response.data;
@@ -805,6 +1140,17 @@ export const api = {
     return response.data;
   },
 
+  renderFactFiles: async (
+    certname: string,
+    format: Exclude<ExportFormat, 'shell'> = 'yaml',
+    templates?: string[]
+  ): Promise<ExternalFactFile[]> => {
+    const response = await client.get(`/facter/files/${encodeURIComponent(certname)}`, {
+      params: { format, templates: templates?.join(',') },
+    });
+    return response.data;
+  },
+
   // Settings
   getSettings: async (): Promise<SettingsResponse> => {
     const response = await client.get('/settings');
@@ -821,6 +1167,11 @@ export const api = {
     return response.data;
   },
 
+  resetDashboardConfig: async (): Promise<DashboardConfig> => {
+    const response = await client.delete('/settings/dashboard');
+    return response.data;
+  },
+
   getRbacConfig: async (): Promise<RbacConfigResponse> => {
     const response = await client.get('/settings/rbac');
     return response.data;
@@ -876,7 +1227,7 @@ export const api = {
   // Bootstrap (public endpoint - no auth required)
   getBootstrapConfig: async (): Promise<BootstrapConfigResponse> => {
     // Use axios directly since this is a public endpoint
-    const response = await axios.get('/api/v1/bootstrap/config');
+    const response = await axios.get(withBasePath('/api/v1/bootstrap/config'));
     return response.data;
   },
 
@@ -916,11 +1267,98 @@ export const api = {
     return response.data;
   },
 
+  bulkSignCertificates: async (request: BulkSignRequest): Promise<BulkCertificateResponse> => {
+    const response = await client.post('/ca/bulk/sign', request);
+    return response.data;
+  },
+
+  bulkRevokeCertificates: async (certnames: string[]): Promise<BulkCertificateResponse> => {
+    const response = await client.post('/ca/bulk/revoke', { certnames });
+    return response.data;
+  },
+
+  getCrl: async (): Promise<CrlResponse> => {
+    const response = await client.get('/ca/crl');
+    return response.data;
+  },
+
+  downloadCrl: async (): Promise<Blob> => {
+    const response = await client.get('/ca/crl', {
+      params: { format: 'pem' },
+      responseType: 'blob',
+    });
+    return response.data;
+  },
+
+  getCaChain: async (): Promise<CaChain> => {
+    const response = await client.get('/ca/chain');
+    return response.data;
+  },
+
+  validateCertificateChain: async (certname: string): Promise<ChainValidation> => {
+    const response = await client.get(
+      `/ca/certificates/${encodeURIComponent(certname)}/chain`
+    );
+    return response.data;
+  },
+
+  downloadCaBundle: async (): Promise<Blob> => {
+    const response = await client.get('/ca/bundle', { responseType: 'blob' });
+    return response.data;
+  },
+
+  exportCa: async (): Promise<Blob> => {
+    const response = await client.get('/ca/export', { responseType: 'blob' });
+    return response.data;
+  },
+
+  syncCaSnapshot: async (): Promise<CaSnapshotSummary> => {
+    const response = await client.post('/ca/snapshot');
+    return response.data;
+  },
+
   renewCA: async (request: RenewCARequest): Promise<RenewCAResponse> => {
     const response = await client.post('/ca/renew', request);
     return response.data;
   },
 
+  getRenewalCampaigns: async (): Promise<RenewalCampaign[]> => {
+    const response = await client.get('/ca/renewals');
+    return response.data;
+  },
+
+  getRenewalCandidates: async (withinDays?: number): Promise<RenewalCandidate[]> => {
+    const params = withinDays ? { within_days: withinDays } : {};
+    const response = await client.get('/ca/renewals/candidates', { params });
+    return response.data;
+  },
+
+  createRenewalCampaign: async (
+    request: CreateRenewalCampaignRequest
+  ): Promise<RenewalCampaignDetail> => {
+    const response = await client.post('/ca/renewals', request);
+    return response.data;
+  },
+
+  getRenewalCampaign: async (
+    id: string,
+    status?: RenewalTargetStatus
+  ): Promise<RenewalCampaignDetail> => {
+    const params = status ? { status } : {};
+    const response = await client.get(`/ca/renewals/${id}`, { params });
+    return response.data;
+  },
+
+  checkRenewalCampaign: async (id: string): Promise<RenewalCampaignDetail> => {
+    const response = await client.post(`/ca/renewals/${id}/check`);
+    return response.data;
+  },
+
+  cancelRenewalCampaign: async (id: string): Promise<RenewalCampaignDetail> => {
+    const response = await client.post(`/ca/renewals/${id}/cancel`);
+    return response.data;
+  },
+
   // Analytics & Reporting
   getSavedReports: async (reportType?: ReportType): Promise<SavedReport[]> => {
     const params = reportType ? { report_type: reportType } : {};
@@ -993,6 +1431,27 @@ export const api = {
     await client.delete(`/analytics/schedules/${id}`);
   },
 
+  getReportEmailTemplates: async (): Promise<ReportEmailTemplate[]> => {
+    const response = await client.get('/analytics/email-templates');
+    return response.data;
+  },
+
+  getDefaultReportEmailTemplate: async (): Promise<UpsertReportEmailTemplateRequest> => {
+    const response = await client.get('/analytics/email-templates/defaults');
+    return response.data;
+  },
+
+  saveReportEmailTemplate: async (
+    request: UpsertReportEmailTemplateRequest
+  ): Promise<ReportEmailTemplate> => {
+    const response = await client.put('/analytics/email-templates', request);
+    return response.data;
+  },
+
+  deleteReportEmailTemplate: async (id: string): Promise<void> => {
+    await client.delete(`/analytics/email-templates/${id}`);
+  },
+
   generateReport: async (request: GenerateReportRequest): Promise<unknown> => {
     const response = await client.post('/analytics/generate', request);
     return response.data;
@@ -1003,6 +1462,24 @@ export const api = {
     return response.data;
   },
 
+  getCorrectiveChanges: async (params?: { days?: number; limit?: number }): Promise<CorrectiveChangeAnalytics> => {
+    const response = await client.get('/analytics/corrective-changes', { params });
+    return response.data;
+  },
+
+  getCompileErrors: async (params?: { days?: number; limit?: number }): Promise<CompileErrorAnalytics> => {
+    const response = await client.get('/analytics/compile-errors', { params });
+    return response.data;
+  },
+
+  getFleetMetrics: async (params?: {
+    days?: number;
+    resolution?: 'hour' | 'day';
+  }): Promise<FleetMetricsSnapshot[]> => {
+    const response = await client.get('/analytics/fleet-metrics', { params });
+    return response.data;
+  },
+
   getComplianceBaselines: async (): Promise<ComplianceBaseline[]> => {
     const response = await client.get('/analytics/compliance-baselines');
     return response.data;
@@ -1027,6 +1504,30 @@ export const api = {
     await client.delete(`/analytics/compliance-baselines/${id}`);
   },
 
+  getRulePacks: async (): Promise<ComplianceRulePack[]> => {
+    const response = await client.get('/analytics/compliance-rule-packs');
+    return response.data;
+  },
+
+  getRulePack: async (id: string): Promise<ComplianceRulePack> => {
+    const response = await client.get(`/analytics/compliance-rule-packs/${id}`);
+    return response.data;
+  },
+
+  importRulePack: async (request: ImportRulePackRequest): Promise<ComplianceRulePack> => {
+    const response = await client.post('/analytics/compliance-rule-packs', request);
+    return response.data;
+  },
+
+  applyRulePack: async (id: string, request: ApplyRulePackRequest): Promise<ComplianceBaseline> => {
+    const response = await client.post(`/analytics/compliance-rule-packs/${id}/apply`, request);
+    return response.data;
+  },
+
+  deleteRulePack: async (id: string): Promise<void> => {
+    await client.delete(`/analytics/compliance-rule-packs/${id}`);
+  },
+
   getDriftBaselines: async (): Promise<DriftBaseline[]> => {
     const response = await client.get('/analytics/drift-baselines');
     return response.data;
@@ -1051,6 +1552,18 @@ export const api = {
     await client.delete(`/analytics/drift-baselines/${id}`);
   },
 
+  previewDriftSnapshot: async (request: DriftSnapshotRequest): Promise<DriftSnapshotPreview> => {
+    const response = await client.post('/analytics/drift-baselines/snapshot/preview', request);
+    return response.data;
+  },
+
+  createDriftBaselineFromSnapshot: async (
+    request: CreateDriftBaselineFromSnapshotRequest
+  ): Promise<DriftBaseline> => {
+    const response = await client.post('/analytics/drift-baselines/snapshot', request);
+    return response.data;
+  },
+
   exportExecution: async (id: string, format?: string): Promise<Blob> => {
     const params = format ? { format } : {};
     const response = await client.get(`/analytics/executions/${id}/export`, {
@@ -1060,6 +1573,11 @@ export const api = {
     return response.data;
   },
 
+  getExecutionOutput: async (id: string): Promise<unknown> => {
+    const response = await client.get(`/analytics/executions/${id}/output`);
+    return response.data;
+  },
+
   // ============================================================================
   // Alerting
   // ============================================================================
@@ -1215,6 +1733,11 @@ export const api = {
     return response.data;
   },
 
+  generateSshKey: async (request: GenerateSshKeyRequest): Promise<CodeSshKey> => {
+    const response = await client.post('/code/ssh-keys/generate', request);
+    return response.data;
+  },
+
   deleteSshKey: async (id: string): Promise<void> => {
     await client.delete(`/code/ssh-keys/${id}`);
   },
@@ -1235,6 +1758,11 @@ export const api = {
     return response.data;
   },
 
+  getCredentialHealth: async (): Promise<CredentialHealthReport> => {
+    const response = await client.get('/code/credentials/health');
+    return response.data;
+  },
+
   createPatToken: async (request: CreatePatTokenRequest): Promise<CodePatToken> => {
     const response = await client.post('/code/pat-tokens', request);
     return response.data;
@@ -1306,6 +1834,33 @@ export const api = {
     return response.data;
   },
 
+  getDeploymentImpact: async (id: string): Promise<DeploymentImpact> => {
+    const response = await client.get(`/code/deployments/${id}/impact`);
+    return response.data;
+  },
+
+  getCanary: async (deploymentId: string): Promise<CodeCanary> => {
+    const response = await client.get(`/code/deployments/${deploymentId}/canary`);
+    return response.data;
+  },
+
+  startCanary: async (deploymentId: string, request: StartCanaryRequest): Promise<CodeCanary> => {
+    const response = await client.post(`/code/deployments/${deploymentId}/canary`, request);
+    return response.data;
+  },
+
+  promoteCanary: async (deploymentId: string): Promise<CodeCanary> => {
+    const response = await client.post(`/code/deployments/${deploymentId}/canary/promote`);
+    return response.data;
+  },
+
+  rollbackCanary: async (deploymentId: string, reason?: string): Promise<CodeCanary> => {
+    const response = await client.post(`/code/deployments/${deploymentId}/canary/rollback`, {
+      reason,
+    });
+    return response.data;
+  },
+
   triggerDeployment: async (request: TriggerDeploymentRequest): Promise<CodeDeployment> => {
     const response = await client.post('/code/deployments', request);
     return response.data;
@@ -1538,6 +2093,22 @@ export const nodeRemovalApi = {
     return response.data;
   },
 
+  // Stale node janitor
+  runJanitor: async (dryRun = false): Promise<JanitorRun> => {
+    const response = await client.post('/node-removal/janitor/run', { dry_run: dryRun });
+    return response.data;
+  },
+
+  listJanitorRuns: async (): Promise<JanitorRun[]> => {
+    const response = await client.get('/node-removal/janitor/runs');
+    return response.data;
+  },
+
+  getJanitorRun: async (id: string): Promise<JanitorRun> => {
+    const response = await client.get(`/node-removal/janitor/runs/${id}`);
+    return response.data;
+  },
+
 };
 
 // CVE / Vulnerability API - separated to avoid TypeScript object literal property limit
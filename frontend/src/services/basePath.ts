@@ -0,0 +1,9 @@
+/**
+ * URL prefix the WebUI is served under (e.g. "/puppet"), or "" at the root.
+ *
+ * The backend injects it into index.html from `server.base_path`.
+ */
+export const BASE_PATH: string = window.__OPENVOX_BASE_PATH__ ?? '';
+
+/** Prefix an absolute application path (e.g. "/login") with the base path */
+export const withBasePath = (path: string): string => `${BASE_PATH}${path}`;
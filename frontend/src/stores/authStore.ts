@@ -77,9 +77,12 @@ export function consumeSessionLogoutReason(): string | null {
 interface AuthState {
   user: User | null;
   token: string | null;
+  // The server holds the tokens in HttpOnly cookies (auth.mode: cookie)
+  cookieSession: boolean;
   isAuthenticated: boolean;
   hasHydrated: boolean;
-  login: (user: User, token: string, refreshToken?: string) => void;
+  // Without a token the session is kept in cookies set by the server
+  login: (user: User, token?: string, refreshToken?: string) => void;
   logout: (reason?: string) => void;
 }
 
@@ -88,17 +91,23 @@ export const useAuthStore = create<AuthState>()(
     (set) => ({
       user: null,
       token: typeof window !== 'undefined' ? safeLocalStorageGet('auth_token') : null,
+      cookieSession: false,
       isAuthenticated: typeof window !== 'undefined' ? !!safeLocalStorageGet('auth_token') : false,
       hasHydrated: false,
 
-      login: (user: User, token: string, refreshToken?: string) => {
-        safeLocalStorageSet('auth_token', token);
-        if (refreshToken !== undefined) {
-          safeLocalStorageSet('refresh_token', refreshToken);
+      login: (user: User, token?: string, refreshToken?: string) => {
+        if (token) {
+          safeLocalStorageSet('auth_token', token);
+          if (refreshToken !== undefined) {
+            safeLocalStorageSet('refresh_token', refreshToken);
+          }
+        } else {
+          safeLocalStorageRemove('auth_token');
+          safeLocalStorageRemove('refresh_token');
         }
         safeSessionStorageRemove(AUTH_LOGOUT_REASON_KEY);
         recordSessionActivity();
-        set({ user, token, isAuthenticated: true });
+        set({ user, token: token || null, cookieSession: !token, isAuthenticated: true });
       },
 
       logout: (reason?: string) => {
@@ -110,7 +119,7 @@ export const useAuthStore = create<AuthState>()(
         }
         // Clear permissions when logging out
         usePermissionsStore.getState().clearPermissions();
-        set({ user: null, token: null, isAuthenticated: false });
+        set({ user: null, token: null, cookieSession: false, isAuthenticated: false });
       },
     }),
     {
@@ -118,6 +127,7 @@ export const useAuthStore = create<AuthState>()(
       partialize: (state) => ({
         user: state.user,
         token: state.token,
+        cookieSession: state.cookieSession,
         isAuthenticated: state.isAuthenticated,
       }),
       onRehydrateStorage: () => (state, _error) => {
@@ -125,7 +135,7 @@ export const useAuthStore = create<AuthState>()(
         if (state) {
           state.hasHydrated = true;
           state.token = state.token ?? storedToken;
-          state.isAuthenticated = !!(state.token ?? storedToken);
+          state.isAuthenticated = !!(state.token ?? storedToken) || !!state.cookieSession;
         }
       },
     }
@@ -1,6 +1,7 @@
 import { create } from 'zustand';
 import { Notification, NotificationStats, NotificationEvent } from '../types/notification';
 import { notificationApi } from '../services/api';
+import { withBasePath } from '../services/basePath';
 import { useAuthStore } from './authStore';
 
 interface NotificationStore {
@@ -140,14 +141,16 @@ export const useNotificationStore = create<NotificationStore>((set, get) => ({
     }
 
     // Get the auth token - EventSource API doesn't support custom headers,
-    // so we pass the token as a query parameter
-    const token = useAuthStore.getState().token;
-    if (!token) {
+    // so we pass the token as a query parameter. Cookie sessions need none:
+    // the browser sends the session cookie.
+    const { token, cookieSession } = useAuthStore.getState();
+    if (!token && !cookieSession) {
       console.warn('No auth token available for SSE connection');
       return;
     }
 
-    const eventSource = new EventSource(`/api/v1/notifications/stream?token=${encodeURIComponent(token)}`, {
+    const streamUrl = withBasePath('/api/v1/notifications/stream');
+    const eventSource = new EventSource(token ? `${streamUrl}?token=${encodeURIComponent(token)}` : streamUrl, {
       withCredentials: true,
     });
 
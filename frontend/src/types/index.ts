@@ -25,6 +25,38 @@ export interface NodesQueryParams {
   offset?: number;
   order_by?: string;
   order_dir?: 'asc' | 'desc';
+  smart_list?: string;
+  tag?: string;
+}
+
+// Local node metadata (tags, owner, tickets, notes) kept by the WebUI
+export interface TicketLink {
+  url: string;
+  label?: string | null;
+}
+
+export interface NodeMetadata {
+  certname: string;
+  tags: string[];
+  owner_team: string | null;
+  ticket_links: TicketLink[];
+  notes: string | null;
+  updated_by: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface UpdateNodeMetadataRequest {
+  tags?: string[];
+  owner_team?: string | null;
+  ticket_links?: TicketLink[];
+  notes?: string | null;
+}
+
+export interface NodeMetadataQuery {
+  tag?: string;
+  owner_team?: string;
+  q?: string;
 }
 
 // Paginated node list result (data plus total count from X-Total-Count header)
@@ -41,6 +73,42 @@ export interface NodeStats {
   by_health: Record<string, number>;
 }
 
+// Infrastructure topology graph (from GET /nodes/topology)
+export type TopologyNodeKind =
+  | 'puppet_server'
+  | 'compile_server'
+  | 'puppetdb'
+  | 'certificate_authority'
+  | 'agent';
+
+export type TopologyStatus = 'healthy' | 'degraded' | 'unhealthy' | 'unknown';
+
+export interface TopologyNode {
+  id: string;
+  kind: TopologyNodeKind;
+  name: string;
+  status: TopologyStatus;
+  message?: string;
+  agent_count?: number;
+  failed_agent_count?: number;
+  environment?: string;
+  last_report?: string;
+}
+
+export interface TopologyEdge {
+  source: string;
+  target: string;
+  kind: 'catalog' | 'storage' | 'certificates';
+}
+
+export interface InfrastructureTopologyGraph {
+  nodes: TopologyNode[];
+  edges: TopologyEdge[];
+  unassigned_agents: number;
+  truncated: boolean;
+  generated_at: string;
+}
+
 // Node deletion response
 export interface DeleteNodeResponse {
   success: boolean;
@@ -371,7 +439,8 @@ export type UpdateOperationType =
   | 'package_install'
   | 'package_remove'
   | 'system_patch'
-  | 'security_patch';
+  | 'security_patch'
+  | 'cert_renewal';
 
 export interface UpdateJobTarget {
   id: string;
@@ -459,6 +528,110 @@ export interface ClassificationRule {
 // Classes in Puppet Enterprise format: {"class_name": {"param": "value"}, ...}
 export type PuppetClasses = Record<string, Record<string, unknown>>;
 
+// Smart lists (saved node filters)
+export interface SmartListFactCondition {
+  fact_path: string;
+  operator: RuleOperator;
+  value: unknown;
+}
+
+export interface SmartListFilter {
+  environments?: string[];
+  statuses?: string[];
+  certname_pattern?: string;
+  group_ids?: string[];
+  facts?: SmartListFactCondition[];
+  tags?: string[];
+}
+
+export interface SmartList {
+  id: string;
+  organization_id: string;
+  name: string;
+  description?: string;
+  filter: SmartListFilter;
+  is_shared: boolean;
+  created_by: string;
+  created_at: string;
+  updated_at: string;
+}
+
+// Number of nodes matching a smart list (cached server-side)
+export interface SmartListCount {
+  smart_list_id: string;
+  count: number;
+  computed_at: string;
+  cached: boolean;
+}
+
+export interface CreateSmartListRequest {
+  name: string;
+  description?: string;
+  filter: SmartListFilter;
+  is_shared?: boolean;
+}
+
+export interface UpdateSmartListRequest {
+  name?: string;
+  description?: string | null;
+  filter?: SmartListFilter;
+  is_shared?: boolean;
+}
+
+// Maintenance windows
+export type MaintenanceTarget =
+  | { type: 'nodes'; certnames: string[] }
+  | { type: 'group'; group_id: string }
+  | { type: 'fact_query'; facts: SmartListFactCondition[] };
+
+// Recurring windows start at each match of a 6-field cron expression (UTC)
+export type MaintenanceSchedule =
+  | { type: 'one_time'; starts_at: string; ends_at: string }
+  | { type: 'recurring'; cron_expression: string; duration_minutes: number };
+
+export interface MaintenanceWindow {
+  id: string;
+  organization_id: string;
+  name: string;
+  description?: string | null;
+  target: MaintenanceTarget;
+  schedule: MaintenanceSchedule;
+  enabled: boolean;
+  created_by: string;
+  created_at: string;
+  updated_at: string;
+  active: boolean;
+  active_until?: string | null;
+  next_start?: string | null;
+}
+
+export interface CreateMaintenanceWindowRequest {
+  name: string;
+  description?: string;
+  target: MaintenanceTarget;
+  schedule: MaintenanceSchedule;
+  enabled?: boolean;
+}
+
+export interface UpdateMaintenanceWindowRequest {
+  name?: string;
+  description?: string | null;
+  target?: MaintenanceTarget;
+  schedule?: MaintenanceSchedule;
+  enabled?: boolean;
+}
+
+export interface NodeMaintenance {
+  window_id: string;
+  name: string;
+  ends_at: string;
+}
+
+export interface NodeInMaintenance {
+  certname: string;
+  windows: NodeMaintenance[];
+}
+
 export interface NodeGroup {
   id: string;
   name: string;
@@ -474,6 +647,8 @@ export interface NodeGroup {
   variables: Record<string, unknown>;
   rules: ClassificationRule[];
   pinned_nodes: string[];
+  /** Revision sent back in If-Match when updating the group */
+  version: number;
 }
 
 export interface CreateGroupRequest {
@@ -502,6 +677,10 @@ export interface UpdateGroupRequest {
   rule_match_type?: RuleMatchType;
   classes?: PuppetClasses;
   variables?: Record<string, unknown>;
+  /** Replaces all rules of the group */
+  rules?: CreateRuleRequest[];
+  /** Replaces all pinned nodes of the group */
+  pinned_nodes?: string[];
 }
 
 export interface CreateRuleRequest {
@@ -579,6 +758,81 @@ export interface ResourceEvent {
   environment?: string | null;
 }
 
+export interface ReportLog {
+  level: string;
+  message: string;
+  source?: string | null;
+  tags: string[];
+  time?: string | null;
+  file?: string | null;
+  line?: number | null;
+}
+
+export interface ReportDrillDown {
+  hash: string;
+  certname: string;
+  status?: ReportStatus | null;
+  events: ResourceEvent[];
+  logs: ReportLog[];
+  total_events: number;
+  total_logs: number;
+}
+
+export interface ReportDiffSide {
+  hash: string;
+  status?: ReportStatus | null;
+  start_time?: string | null;
+  end_time?: string | null;
+  configuration_version?: string | null;
+  environment?: string | null;
+}
+
+export interface ResourceDiffEntry {
+  resource_type: string;
+  resource_title: string;
+  message?: string | null;
+  file?: string | null;
+  line?: number | null;
+}
+
+export interface TimingRegression {
+  name: string;
+  from_seconds: number;
+  to_seconds: number;
+  increase_seconds: number;
+  increase_percent?: number | null;
+}
+
+export interface ReportDiff {
+  certname: string;
+  from: ReportDiffSide;
+  to: ReportDiffSide;
+  newly_failing: ResourceDiffEntry[];
+  newly_fixed: ResourceDiffEntry[];
+  still_failing: ResourceDiffEntry[];
+  timing_regressions: TimingRegression[];
+}
+
+export interface NodePerformancePoint {
+  hash: string;
+  end_time?: string | null;
+  status?: 'changed' | 'unchanged' | 'failed' | null;
+  noop: boolean;
+  run_time_seconds?: number | null;
+  total_resources?: number | null;
+  changed_resources?: number | null;
+  failed_resources?: number | null;
+}
+
+export interface NodePerformance {
+  certname: string;
+  days: number;
+  points: NodePerformancePoint[];
+  baseline_run_time_seconds?: number | null;
+  recent_run_time_seconds?: number | null;
+  run_time_regressed: boolean;
+}
+
 // Fact types
 export interface Fact {
   certname: string;
@@ -605,6 +859,8 @@ export interface ClassificationResult {
   /** Combined variables from all matched groups, exported to the node as external facts */
   variables?: Record<string, unknown>;
   environment?: string | null;
+  /** Active maintenance windows covering the node */
+  maintenance?: NodeMaintenance[];
 }
 
 // Auth types
@@ -652,7 +908,8 @@ export type Action =
   | 'admin'
   | 'export'
   | 'classify'
-  | 'generate';
+  | 'generate'
+  | 'impersonate';
 
 export type Scope =
   | { type: 'all' }
@@ -682,6 +939,8 @@ export interface Role {
   description?: string | null;
   is_system: boolean;
   parent_id?: string | null;
+  /** Roles whose permissions this role inherits */
+  parent_ids?: string[];
   permissions: Permission[];
   created_at: string;
   updated_at: string;
@@ -692,6 +951,7 @@ export interface CreateRoleRequest {
   display_name: string;
   description?: string;
   parent_id?: string;
+  parent_ids?: string[];
   permissions?: Array<{
     resource: Resource;
     action: Action;
@@ -706,6 +966,7 @@ export interface UserResponse {
   role: string;
   force_password_change?: boolean;
   auth_provider?: AuthProvider;
+  kind?: UserKind;
   roles?: Array<{ id: string; name: string; display_name: string }>;
   created_at: string;
 }
@@ -713,9 +974,10 @@ export interface UserResponse {
 export interface CreateUserRequest {
   username: string;
   email: string;
-  password?: string; // Optional for SAML-only users
+  password?: string; // Optional for SAML-only users and service accounts
   role_ids?: string[];
   auth_provider?: AuthProvider;
+  kind?: UserKind; // Service accounts authenticate with API keys only
   external_id?: string; // For SAML users
 }
 
@@ -727,12 +989,134 @@ export interface UpdateUserRequest {
   external_id?: string;
 }
 
+export interface ImportUserRow {
+  username: string;
+  email: string;
+  role?: string; // Defaults to viewer
+  organization?: string; // Slug or id
+  password?: string;
+  password_hash?: string; // Argon2 PHC string
+}
+
+export type ImportUserStatus = 'created' | 'valid' | 'failed';
+
+export interface ImportUserResult {
+  row: number;
+  username: string;
+  status: ImportUserStatus;
+  user_id?: string;
+  organization_id?: string;
+  role?: string;
+  generated_password?: string;
+  error?: string;
+}
+
+export interface ImportUsersResponse {
+  dry_run: boolean;
+  total: number;
+  created: number;
+  failed: number;
+  results: ImportUserResult[];
+}
+
 export interface EffectivePermissions {
   user_id: string;
   permissions: Permission[];
   roles: string[];
 }
 
+export interface RoleEffectivePermissions {
+  role_id: string;
+  inherited_role_ids: string[];
+  permissions: Array<Permission & { role_id: string; role_name: string }>;
+}
+
+export type ElevationStatus = 'pending' | 'approved' | 'denied' | 'revoked' | 'expired';
+
+export interface RoleElevation {
+  id: string;
+  organization_id: string;
+  user_id: string;
+  role_id: string;
+  role_name: string;
+  reason: string;
+  duration_hours: number;
+  status: ElevationStatus;
+  decided_by?: string | null;
+  decided_at?: string | null;
+  decision_comment?: string | null;
+  /** Set when approved; the role is granted until then */
+  expires_at?: string | null;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface CreateElevationRequest {
+  role_id: string;
+  duration_hours: number;
+  reason: string;
+}
+
+export interface ImpersonationSession {
+  id: string;
+  organization_id: string;
+  impersonator_id: string;
+  impersonator_username: string;
+  user_id: string;
+  username: string;
+  reason: string;
+  created_at: string;
+  expires_at: string;
+  /** Set when the impersonation was ended before expiring */
+  ended_at?: string | null;
+}
+
+export interface StartImpersonationRequest {
+  user_id: string;
+  reason: string;
+  duration_minutes?: number;
+}
+
+export interface ImpersonationResponse {
+  access_token: string;
+  token_type: string;
+  expires_in: number;
+  session: ImpersonationSession;
+}
+
+export interface AuthSession {
+  id: string;
+  user_id: string;
+  ip_address?: string | null;
+  user_agent?: string | null;
+  created_at: string;
+  last_activity_at: string;
+  expires_at: string;
+  /** True for the session making the request */
+  current: boolean;
+}
+
+export type LoginMethod = 'password' | 'saml';
+
+export interface LoginEvent {
+  id: string;
+  user_id: string;
+  success: boolean;
+  method: LoginMethod;
+  ip_address?: string | null;
+  user_agent?: string | null;
+  /** Country and city from the proxy's geo headers, or the client network */
+  location?: string | null;
+  new_device: boolean;
+  new_location: boolean;
+  created_at: string;
+}
+
+export interface CurrentUserResponse extends UserResponse {
+  /** Latest login attempts on the account, newest first */
+  recent_logins: LoginEvent[];
+}
+
 export interface ResourceInfo {
   name: string;
   display_name: string;
@@ -846,6 +1230,12 @@ export interface GeneratedFacts {
 
 export type ExportFormat = 'json' | 'yaml' | 'shell';
 
+export interface ExternalFactFile {
+  template: string;
+  filename: string;
+  content: string;
+}
+
 // Settings types
 export interface ServerSettings {
   host: string;
@@ -902,6 +1292,7 @@ export interface DashboardConfig {
   inactive_threshold_hours: number;
   theme: string;
   widgets: WidgetConfig[];
+  default_filters: Record<string, unknown>;
 }
 
 export interface WidgetConfig {
@@ -911,6 +1302,28 @@ export interface WidgetConfig {
   enabled: boolean;
   position?: WidgetPosition;
   config?: Record<string, unknown>;
+  binding?: WidgetBinding;
+}
+
+export type WidgetBinding = (
+  | { source: 'pql'; query: string; label_field: string; value_fields?: string[] }
+  | { source: 'saved_report'; report_id: string }
+) & {
+  thresholds?: WidgetThreshold[];
+};
+
+export interface WidgetThreshold {
+  value: number;
+  label?: string;
+  level?: string;
+}
+
+export interface WidgetData {
+  labels: string[];
+  series: { name: string; values: number[] }[];
+  thresholds: WidgetThreshold[];
+  truncated: boolean;
+  generated_at: string;
 }
 
 export interface WidgetPosition {
@@ -925,6 +1338,7 @@ export interface RbacSettings {
   session_timeout_minutes: number;
   max_failed_logins: number;
   lockout_duration_minutes: number;
+  max_lockout_minutes: number;
   custom_roles_count: number;
 }
 
@@ -979,6 +1393,7 @@ export interface RbacConfigResponse {
   session_timeout_minutes: number;
   max_failed_logins: number;
   lockout_duration_minutes: number;
+  max_lockout_minutes: number;
   roles: RoleDefinition[];
 }
 
@@ -1071,6 +1486,33 @@ export interface Certificate {
   dns_alt_names: string[];
   fingerprint: string;
   state: CertificateStatus;
+  revocation?: RevocationInfo;
+}
+
+export interface RevocationInfo {
+  revoked_at: string;
+  reason?: string | null;
+  crl_issuer: string;
+}
+
+export interface RevokedCertificate {
+  serial: string;
+  revoked_at: string;
+  reason?: string | null;
+}
+
+export interface CertificateRevocationList {
+  issuer: string;
+  crl_number?: string | null;
+  this_update: string;
+  next_update?: string | null;
+  revoked_count: number;
+  revoked: RevokedCertificate[];
+}
+
+export interface CrlResponse {
+  fetched_at: string;
+  crls: CertificateRevocationList[];
 }
 
 export interface CAStatus {
@@ -1079,6 +1521,97 @@ export interface CAStatus {
   ca_expires_at?: string | null;
   pending_requests: number;
   signed_certificates: number;
+  snapshot_at?: string | null;
+}
+
+export type ChainRole = 'root' | 'intermediate' | 'leaf';
+
+export interface ChainCertificate {
+  subject: string;
+  issuer: string;
+  serial: string;
+  not_before: string;
+  not_after: string;
+  fingerprint: string;
+  role: ChainRole;
+  days_until_expiry: number;
+  expiring_soon: boolean;
+}
+
+export interface CaChain {
+  certificates: ChainCertificate[];
+  warnings: string[];
+}
+
+export interface ChainValidation {
+  certname: string;
+  valid: boolean;
+  error?: string | null;
+  chain: ChainCertificate[];
+  warnings: string[];
+}
+
+export type RenewalCampaignStatus = 'active' | 'completed' | 'cancelled';
+
+export type RenewalTargetStatus = 'pending' | 'renewed' | 'failed';
+
+export interface RenewalCandidate {
+  certname: string;
+  serial: string;
+  not_after: string;
+  days_until_expiry: number;
+}
+
+export interface RenewalSummary {
+  total: number;
+  pending: number;
+  renewed: number;
+  failed: number;
+}
+
+export interface RenewalCampaign {
+  id: string;
+  name: string;
+  status: RenewalCampaignStatus;
+  deadline: string;
+  trigger_agents: boolean;
+  update_job_id?: string | null;
+  created_by: string;
+  created_at: string;
+  updated_at: string;
+  completed_at?: string | null;
+  summary: RenewalSummary;
+}
+
+export interface RenewalTarget {
+  certname: string;
+  status: RenewalTargetStatus;
+  old_serial: string;
+  old_not_after: string;
+  new_serial?: string | null;
+  new_not_after?: string | null;
+  error?: string | null;
+  updated_at: string;
+  instructions?: string[];
+}
+
+export interface RenewalCampaignDetail extends RenewalCampaign {
+  targets: RenewalTarget[];
+}
+
+export interface CreateRenewalCampaignRequest {
+  name?: string;
+  certnames?: string[];
+  within_days?: number;
+  deadline?: string;
+  trigger_agents?: boolean;
+}
+
+export interface CaSnapshotSummary {
+  synced_at: string;
+  certificates: number;
+  requests: number;
+  has_crl: boolean;
 }
 
 export interface SignRequest {
@@ -1100,6 +1633,24 @@ export interface RevokeResponse {
   message: string;
 }
 
+export interface BulkSignRequest {
+  certnames?: string[];
+  all_pending?: boolean;
+}
+
+export interface BulkCertificateResult {
+  certname: string;
+  success: boolean;
+  message: string;
+}
+
+export interface BulkCertificateResponse {
+  total: number;
+  succeeded: number;
+  failed: number;
+  results: BulkCertificateResult[];
+}
+
 export interface RenewCARequest {
   days: number;
 }
@@ -1112,7 +1663,7 @@ export interface RenewCAResponse {
 
 // Analytics & Reporting types
 export type ReportType = 'node_health' | 'compliance' | 'change_tracking' | 'drift_detection' | 'custom';
-export type OutputFormat = 'json' | 'csv' | 'pdf';
+export type OutputFormat = 'json' | 'csv' | 'pdf' | 'xlsx' | 'html';
 export type ExecutionStatus = 'pending' | 'running' | 'completed' | 'failed';
 export type SeverityLevel = 'low' | 'medium' | 'high' | 'critical';
 
@@ -1122,11 +1673,13 @@ export interface ReportQueryConfig {
   environment_filter?: string[];
   node_group_filter?: string[];
   certname_pattern?: string;
+  smart_list_id?: string;
   group_by?: string;
   include_resources?: boolean;
   include_error_details?: boolean;
   metrics?: string[];
   severity_filter?: string[];
+  compliance_baseline_ids?: string[];
   compare_mode?: string;
   ignore_volatile_facts?: boolean;
   custom_params?: Record<string, unknown>;
@@ -1134,6 +1687,7 @@ export interface ReportQueryConfig {
 
 export interface SavedReport {
   id: string;
+  organization_id: string;
   name: string;
   description?: string;
   report_type: ReportType;
@@ -1161,12 +1715,14 @@ export interface UpdateSavedReportRequest {
 
 export interface ReportSchedule {
   id: string;
+  organization_id: string;
   report_id: string;
   schedule_cron: string;
   timezone: string;
   is_enabled: boolean;
   output_format: OutputFormat;
   email_recipients?: string[];
+  delivery_targets: DeliveryTarget[];
   last_run_at?: string;
   next_run_at?: string;
   created_at: string;
@@ -1180,6 +1736,7 @@ export interface CreateScheduleRequest {
   is_enabled?: boolean;
   output_format?: OutputFormat;
   email_recipients?: string[];
+  delivery_targets?: DeliveryTarget[];
 }
 
 export interface UpdateScheduleRequest {
@@ -1188,10 +1745,58 @@ export interface UpdateScheduleRequest {
   is_enabled?: boolean;
   output_format?: OutputFormat;
   email_recipients?: string[];
+  delivery_targets?: DeliveryTarget[];
+}
+
+// Tera templates of scheduled report emails; without report_type the
+// template applies to every type that has none of its own
+export interface ReportEmailTemplate {
+  id: string;
+  organization_id: string;
+  report_type?: ReportType;
+  subject_template: string;
+  html_template: string;
+  text_template: string;
+  updated_by?: string;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface UpsertReportEmailTemplateRequest {
+  report_type?: ReportType;
+  subject_template: string;
+  html_template: string;
+  text_template: string;
+}
+
+// Credentials come back as '********'; sending the placeholder keeps them
+export type DeliveryTarget =
+  | { type: 'filesystem'; directory: string }
+  | {
+      type: 's3';
+      endpoint: string;
+      bucket: string;
+      region?: string;
+      prefix?: string;
+      access_key_id: string;
+      secret_access_key: string;
+    }
+  | { type: 'webdav'; url: string; username?: string; password?: string };
+
+export type DeliveryStatus = 'delivered' | 'partial' | 'failed';
+
+export interface DeliveryResult {
+  target_type: DeliveryTarget['type'];
+  target: string;
+  success: boolean;
+  location?: string;
+  error?: string;
+  delivered_at: string;
 }
 
 export interface ReportExecution {
   id: string;
+  organization_id: string;
   report_id: string;
   schedule_id?: string;
   executed_by?: string;
@@ -1204,6 +1809,8 @@ export interface ReportExecution {
   output_file_path?: string;
   error_message?: string;
   execution_time_ms?: number;
+  delivery_status?: DeliveryStatus;
+  delivery_results: DeliveryResult[];
 }
 
 export interface ExecuteReportRequest {
@@ -1229,14 +1836,20 @@ export interface ComplianceRule {
   operator: string;
   expected_value: unknown;
   severity: SeverityLevel;
+  references?: string[];
+  remediation?: string;
 }
 
 export interface ComplianceBaseline {
   id: string;
+  organization_id: string;
   name: string;
   description?: string;
   rules: ComplianceRule[];
   severity_level: SeverityLevel;
+  node_group_ids: string[];
+  rule_pack_id?: string;
+  rule_pack_version?: string;
   created_by: string;
   created_at: string;
   updated_at: string;
@@ -1247,6 +1860,7 @@ export interface CreateComplianceBaselineRequest {
   description?: string;
   rules: ComplianceRule[];
   severity_level?: SeverityLevel;
+  node_group_ids?: string[];
 }
 
 export interface UpdateComplianceBaselineRequest {
@@ -1254,6 +1868,36 @@ export interface UpdateComplianceBaselineRequest {
   description?: string | null;
   rules?: ComplianceRule[];
   severity_level?: SeverityLevel;
+  node_group_ids?: string[];
+}
+
+export interface ComplianceRulePack {
+  id: string;
+  pack_key: string;
+  name: string;
+  version: string;
+  description?: string;
+  framework?: string;
+  publisher?: string;
+  references: string[];
+  rules: ComplianceRule[];
+  checksum: string;
+  imported_by: string;
+  imported_at: string;
+}
+
+export type RulePackFormat = 'yaml' | 'json';
+
+export interface ImportRulePackRequest {
+  content: string;
+  format?: RulePackFormat;
+}
+
+export interface ApplyRulePackRequest {
+  baseline_id?: string;
+  name?: string;
+  severity_level?: SeverityLevel;
+  node_group_ids?: string[];
 }
 
 export interface DriftToleranceConfig {
@@ -1264,6 +1908,7 @@ export interface DriftToleranceConfig {
 
 export interface DriftBaseline {
   id: string;
+  organization_id: string;
   name: string;
   description?: string;
   node_group_id?: string;
@@ -1290,6 +1935,31 @@ export interface UpdateDriftBaselineRequest {
   tolerance_config?: DriftToleranceConfig | null;
 }
 
+export type DriftSnapshotSource =
+  | { type: 'node'; certname: string }
+  | { type: 'group'; group_id: string };
+
+export interface DriftSnapshotRequest {
+  source: DriftSnapshotSource;
+  exclude_facts?: string[];
+  exclude_volatile?: boolean;
+}
+
+export interface DriftSnapshotPreview {
+  source: DriftSnapshotSource;
+  node_count: number;
+  baseline_facts: Record<string, unknown>;
+  volatile_facts: string[];
+  excluded_facts: string[];
+  differing_facts: string[];
+}
+
+export interface CreateDriftBaselineFromSnapshotRequest extends DriftSnapshotRequest {
+  name: string;
+  description?: string;
+  tolerance_config?: DriftToleranceConfig;
+}
+
 // Report Results
 export interface NodeHealthSummary {
   total_nodes: number;
@@ -1298,6 +1968,8 @@ export interface NodeHealthSummary {
   failed_count: number;
   noop_count: number;
   unreported_count: number;
+  /** Failed nodes in maintenance, not counted in failed_count */
+  maintenance_count?: number;
   compliance_rate: number;
 }
 
@@ -1316,6 +1988,7 @@ export interface NodeHealthDetail {
   last_report_at?: string;
   failed_resources?: number;
   changed_resources?: number;
+  in_maintenance?: boolean;
 }
 
 export interface NodeHealthReport {
@@ -1342,12 +2015,24 @@ export interface SeverityBreakdown {
 
 export interface ComplianceViolation {
   certname: string;
+  baseline_id?: string;
+  baseline_name?: string;
   rule_id: string;
   rule_name: string;
   fact_name: string;
   expected_value: unknown;
   actual_value: unknown;
   severity: SeverityLevel;
+  remediation?: string;
+}
+
+export interface BaselineComplianceSummary {
+  baseline_id: string;
+  baseline_name: string;
+  severity_level: SeverityLevel;
+  node_group_ids: string[];
+  summary: ComplianceSummary;
+  by_severity: SeverityBreakdown[];
 }
 
 export interface ComplianceReport {
@@ -1355,6 +2040,7 @@ export interface ComplianceReport {
   baseline_name: string;
   summary: ComplianceSummary;
   by_severity: SeverityBreakdown[];
+  baselines: BaselineComplianceSummary[];
   violations: ComplianceViolation[];
 }
 
@@ -1363,6 +2049,8 @@ export interface ChangeSummary {
   nodes_affected: number;
   resources_changed: number;
   resources_failed: number;
+  corrective_changes: number;
+  intentional_changes: number;
 }
 
 export interface ChangeTypeBreakdown {
@@ -1379,6 +2067,7 @@ export interface ChangeDetail {
   old_value?: unknown;
   new_value?: unknown;
   status: string;
+  corrective_change: boolean;
 }
 
 export interface ChangeTrackingReport {
@@ -1389,6 +2078,82 @@ export interface ChangeTrackingReport {
   changes: ChangeDetail[];
 }
 
+export interface CorrectiveChangeSummary {
+  reports_analyzed: number;
+  corrective_changes: number;
+  intentional_changes: number;
+  nodes_with_drift: number;
+}
+
+export interface CorrectiveChangeDay {
+  date: string;
+  corrective_changes: number;
+  intentional_changes: number;
+  nodes_corrected: number;
+}
+
+export interface NodeCorrectiveChanges {
+  certname: string;
+  corrective_changes: number;
+  intentional_changes: number;
+  reports_with_drift: number;
+  last_corrective_at?: string | null;
+}
+
+export interface CorrectiveChangeAnalytics {
+  generated_at: string;
+  days: number;
+  summary: CorrectiveChangeSummary;
+  daily: CorrectiveChangeDay[];
+  nodes: NodeCorrectiveChanges[];
+}
+
+export type CompileErrorKind =
+  | 'missing_class'
+  | 'duplicate_declaration'
+  | 'hiera_lookup'
+  | 'syntax_error'
+  | 'other';
+
+export interface CompileErrorGroup {
+  signature: string;
+  kind: CompileErrorKind;
+  message: string;
+  file?: string | null;
+  line?: number | null;
+  occurrences: number;
+  node_count: number;
+  nodes: string[];
+  environments: string[];
+  first_seen?: string | null;
+  last_seen?: string | null;
+}
+
+export interface CompileErrorAnalytics {
+  generated_at: string;
+  days: number;
+  failed_reports: number;
+  compile_failures: number;
+  nodes_affected: number;
+  total_signatures: number;
+  errors: CompileErrorGroup[];
+}
+
+export interface FleetMetricsSnapshot {
+  bucket: string;
+  total_nodes: number;
+  changed_nodes: number;
+  unchanged_nodes: number;
+  failed_nodes: number;
+  unreported_nodes: number;
+  report_count: number;
+  avg_run_time_seconds?: number | null;
+  avg_compile_time_seconds?: number | null;
+  total_resources: number;
+  changed_resources: number;
+  failed_resources: number;
+}
+
 export interface DriftSummary {
   total_nodes: number;
   nodes_with_drift: number;
@@ -1696,6 +2461,11 @@ export interface CreateSshKeyRequest {
   private_key: string;
 }
 
+export interface GenerateSshKeyRequest {
+  name: string;
+  comment?: string;
+}
+
 // PAT Token types
 export interface CodePatToken {
   id: string;
@@ -1727,8 +2497,54 @@ export interface UpdatePatTokenRequest {
   expires_at?: string;
 }
 
+// Credential health types
+export type CredentialHealthStatus = 'healthy' | 'warning' | 'critical';
+
+export interface PatTokenHealth {
+  id: string;
+  name: string;
+  status: CredentialHealthStatus;
+  issues: string[];
+  expires_at?: string;
+  days_until_expiration?: number;
+  last_validated_at?: string;
+  repository_count: number;
+}
+
+export interface SshKeyHealth {
+  id: string;
+  name: string;
+  status: CredentialHealthStatus;
+  issues: string[];
+  age_days: number;
+  repository_count: number;
+}
+
+export interface RepositoryAuthHealth {
+  id: string;
+  name: string;
+  url: string;
+  auth_type: AuthType;
+  status: CredentialHealthStatus;
+  issues: string[];
+  auth_failed: boolean;
+  last_error?: string;
+  last_error_at?: string;
+}
+
+export interface CredentialHealthReport {
+  status: CredentialHealthStatus;
+  summary: { healthy: number; warning: number; critical: number };
+  pat_tokens: PatTokenHealth[];
+  ssh_keys: SshKeyHealth[];
+  repositories: RepositoryAuthHealth[];
+  generated_at: string;
+}
+
 export type AuthType = 'ssh' | 'pat' | 'none';
 
+export type DeployStrategy = 'r10k' | 'librarian_puppet' | 'git_checkout';
+
 export interface CodeRepository {
   id: string;
   name: string;
@@ -1743,6 +2559,7 @@ export interface CodeRepository {
   webhook_url?: string;
   poll_interval_seconds: number;
   is_control_repo: boolean;
+  deploy_strategy: DeployStrategy;
   last_error?: string;
   last_error_at?: string;
   environment_count: number;
@@ -1760,6 +2577,7 @@ export interface CreateRepositoryRequest {
   github_pat?: string; // Deprecated, use pat_token_id
   poll_interval_seconds?: number;
   is_control_repo?: boolean;
+  deploy_strategy?: DeployStrategy;
 }
 
 export interface UpdateRepositoryRequest {
@@ -1775,6 +2593,7 @@ export interface UpdateRepositoryRequest {
   clear_github_pat?: boolean;
   poll_interval_seconds?: number;
   is_control_repo?: boolean;
+  deploy_strategy?: DeployStrategy;
   regenerate_webhook_secret?: boolean;
 }
 
@@ -1831,13 +2650,91 @@ export interface CodeDeployment {
   duration_seconds?: number;
   error_message?: string;
   r10k_output?: string;
+  options: DeploymentOptions;
+  command_line?: string;
+  created_at: string;
+  updated_at: string;
+}
+
+export interface DeploymentOptions {
+  module?: string;
+  skip_puppetfile?: boolean;
+  incremental?: boolean;
+  generate_types?: boolean;
+}
+
+export type ModuleChangeSource = 'code' | 'puppetfile';
+
+export interface ModuleImpact {
+  name: string;
+  sources: ModuleChangeSource[];
+  classes: { name: string; node_count?: number }[];
+  node_count?: number;
+}
+
+export interface DeploymentImpact {
+  deployment_id: string;
+  environment: string;
+  base_commit?: string;
+  commit_sha: string;
+  changed_files: string[];
+  modules: ModuleImpact[];
+  site_manifest_changed: boolean;
+  data_changed: boolean;
+  affected_nodes?: number;
+  environment_nodes?: number;
+  puppetdb_error?: string;
+  generated_at: string;
+}
+
+export type CanaryStatus =
+  | 'running'
+  | 'passed'
+  | 'failed'
+  | 'promoted'
+  | 'rolled_back'
+  | 'abandoned';
+
+export interface CanaryNodeReports {
+  certname: string;
+  successful_reports: number;
+  failed_reports: number;
+  last_status?: string;
+  last_report_at?: string;
+}
+
+export interface CodeCanary {
+  id: string;
+  deployment_id: string;
+  organization_id: string;
+  environment: string;
+  source_group_id: string;
+  canary_group_id?: string;
+  nodes: string[];
+  required_reports: number;
+  auto_promote: boolean;
+  status: CanaryStatus;
+  node_reports: CanaryNodeReports[];
+  error_message?: string;
+  started_by?: string;
+  deadline_at: string;
+  completed_at?: string;
   created_at: string;
   updated_at: string;
 }
 
+export interface StartCanaryRequest {
+  group_id: string;
+  sample_size?: number;
+  required_reports?: number;
+  timeout_minutes?: number;
+  auto_promote?: boolean;
+}
+
 export interface TriggerDeploymentRequest {
   environment_id: string;
   commit_sha?: string;
+  options?: DeploymentOptions;
 }
 
 export interface ApproveDeploymentRequest {
@@ -1932,6 +2829,7 @@ export interface BackupRestore {
   restored_by?: string | null;
   restored_by_username?: string | null;
   created_at: string;
+  restart_required?: boolean;
 }
 
 export interface BackupFeatureStatus {
@@ -1993,7 +2891,7 @@ export interface ListBackupsQuery {
 // Node Removal Types
 // ============================================================================
 
-export type RemovalReason = 'revoked_certificate' | 'no_certificate' | 'manual';
+export type RemovalReason = 'revoked_certificate' | 'no_certificate' | 'manual' | 'stale_node';
 export type RemovalAuditAction = 'marked' | 'unmarked' | 'removed' | 'extended';
 
 export interface PendingNodeRemoval {
@@ -2036,6 +2934,41 @@ export interface NodeRemovalFeatureStatus {
   check_interval_secs: number;
   puppetdb_connected: boolean;
   puppet_ca_connected: boolean;
+  janitor: JanitorStatus;
+}
+
+export interface JanitorStatus {
+  enabled: boolean;
+  stale_after_days: number;
+  interval_secs: number;
+  deactivate: boolean;
+  flag_certificates: boolean;
+  exclude: string[];
+}
+
+export type JanitorNodeStatus = 'purged' | 'excluded' | 'failed';
+
+export interface JanitorNodeResult {
+  certname: string;
+  last_seen_at?: string | null;
+  status: JanitorNodeStatus;
+  deactivated: boolean;
+  certificate_flagged: boolean;
+  error?: string | null;
+}
+
+export interface JanitorRun {
+  id: string;
+  started_at: string;
+  finished_at: string;
+  dry_run: boolean;
+  triggered_by?: string | null;
+  stale_after_days: number;
+  stale_count: number;
+  purged_count: number;
+  excluded_count: number;
+  failed_count: number;
+  nodes?: JanitorNodeResult[];
 }
 
 export interface MarkNodeForRemovalRequest {
@@ -2225,3 +3158,5 @@ export interface UpdatePreviewPackage {
   to_version: string;
   cve_ids: string[];
 }
+
+export type UserKind = 'human' | 'service_account';
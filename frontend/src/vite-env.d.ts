@@ -1,3 +1,8 @@
 /// <reference types="vite/client" />
 
 declare const __APP_VERSION__: string;
+
+interface Window {
+  /** Set in index.html; see services/basePath.ts */
+  __OPENVOX_BASE_PATH__?: string;
+}
@@ -69,6 +69,20 @@ export default defineConfig(({ mode }) => {
       },
       chunkSizeWarningLimit: 500, // Keep warning at 500KB
     },
+    experimental: {
+      // Resolve lazily loaded chunks against the runtime base path so one
+      // build works both at the root and under server.base_path. index.html
+      // keeps root-relative URLs, which the backend prefixes when serving it.
+      renderBuiltUrl(filename, { hostType }) {
+        if (hostType === 'js') {
+          return { runtime: `window.__OPENVOX_BASE_PATH__ + ${JSON.stringify(`/${filename}`)}` };
+        }
+        if (hostType === 'css') {
+          return { relative: true };
+        }
+        return `/${filename}`;
+      },
+    },
     define: {
       __APP_VERSION__: JSON.stringify(appVersion),
     },
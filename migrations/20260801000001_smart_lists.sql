@@ -0,0 +1,28 @@
+-- Saved node filters ("smart lists")
+--
+-- A smart list is a named node filter combining facts, groups, status, tags,
+-- environment and certname criteria. Lists are owned by a user within an
+-- organization and can optionally be shared with every user in that
+-- organization. The filter definition is stored as JSON so new criteria can be
+-- added without schema changes.
+
+CREATE TABLE IF NOT EXISTS smart_lists (
+    id TEXT PRIMARY KEY,
+    organization_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    filter TEXT NOT NULL DEFAULT '{}',
+    is_shared INTEGER NOT NULL DEFAULT 0,
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (organization_id) REFERENCES organizations(id) ON DELETE CASCADE,
+    FOREIGN KEY (created_by) REFERENCES users(id) ON DELETE CASCADE,
+    UNIQUE (organization_id, created_by, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_smart_lists_organization_id
+    ON smart_lists(organization_id);
+
+CREATE INDEX IF NOT EXISTS idx_smart_lists_created_by
+    ON smart_lists(created_by);
@@ -0,0 +1,14 @@
+-- Last known state of the Puppet CA
+--
+-- Synced periodically while the CA is reachable so certificate listing keeps
+-- working when the CA host is offline (e.g. only powered on during signing
+-- windows). Only one row (id = 1) is kept.
+
+CREATE TABLE IF NOT EXISTS ca_snapshots (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    ca_bundle_pem TEXT,
+    crl_pem TEXT,
+    certificates TEXT NOT NULL DEFAULT '[]',
+    requests TEXT NOT NULL DEFAULT '[]',
+    synced_at TEXT NOT NULL
+);
@@ -0,0 +1,35 @@
+-- Certificate renewal campaigns
+--
+-- A campaign targets node certificates close to expiry and tracks each node
+-- until the CA holds a newer certificate for it. Nodes still on their old
+-- certificate at the deadline (or at its expiry, if earlier) are marked failed.
+
+CREATE TABLE IF NOT EXISTS cert_renewal_campaigns (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'active',  -- active, completed, cancelled
+    deadline TEXT NOT NULL,
+    trigger_agents INTEGER NOT NULL DEFAULT 0,
+    update_job_id TEXT,
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    completed_at TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_cert_renewal_campaigns_status
+    ON cert_renewal_campaigns(status);
+
+CREATE TABLE IF NOT EXISTS cert_renewal_targets (
+    campaign_id TEXT NOT NULL,
+    certname TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',  -- pending, renewed, failed
+    old_serial TEXT NOT NULL,
+    old_not_after TEXT NOT NULL,
+    new_serial TEXT,
+    new_not_after TEXT,
+    error TEXT,
+    updated_at TEXT NOT NULL,
+    PRIMARY KEY (campaign_id, certname),
+    FOREIGN KEY (campaign_id) REFERENCES cert_renewal_campaigns(id) ON DELETE CASCADE
+);
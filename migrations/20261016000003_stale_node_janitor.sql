@@ -0,0 +1,65 @@
+-- Stale node janitor: nodes that stopped reporting are deactivated in PuppetDB
+-- and/or flagged for removal with the new 'stale_node' reason. Each janitor run
+-- keeps a report of the nodes it found.
+--
+-- SQLite cannot ALTER a CHECK constraint in place, so pending_node_removals is
+-- rebuilt to accept 'stale_node'.
+
+CREATE TABLE pending_node_removals_new (
+    id TEXT PRIMARY KEY NOT NULL,
+    certname TEXT NOT NULL UNIQUE,
+    removal_reason TEXT NOT NULL CHECK (removal_reason IN ('revoked_certificate', 'no_certificate', 'manual', 'stale_node')),
+    marked_at TEXT NOT NULL DEFAULT (datetime('now')),
+    scheduled_removal_at TEXT NOT NULL,
+    removed_at TEXT,
+    notes TEXT,
+    marked_by TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+INSERT INTO pending_node_removals_new (
+    id, certname, removal_reason, marked_at, scheduled_removal_at,
+    removed_at, notes, marked_by, created_at, updated_at
+)
+SELECT
+    id, certname, removal_reason, marked_at, scheduled_removal_at,
+    removed_at, notes, marked_by, created_at, updated_at
+FROM pending_node_removals;
+
+DROP TABLE pending_node_removals;
+ALTER TABLE pending_node_removals_new RENAME TO pending_node_removals;
+
+CREATE INDEX IF NOT EXISTS idx_pending_node_removals_certname ON pending_node_removals(certname);
+CREATE INDEX IF NOT EXISTS idx_pending_node_removals_scheduled ON pending_node_removals(scheduled_removal_at) WHERE removed_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_pending_node_removals_reason ON pending_node_removals(removal_reason);
+
+-- One row per janitor run
+CREATE TABLE IF NOT EXISTS node_janitor_runs (
+    id TEXT PRIMARY KEY NOT NULL,
+    started_at TEXT NOT NULL,
+    finished_at TEXT NOT NULL,
+    -- Nothing was changed; the report shows what would have been purged
+    dry_run INTEGER NOT NULL DEFAULT 0,
+    -- User who started the run (NULL for the scheduler)
+    triggered_by TEXT,
+    stale_after_days INTEGER NOT NULL,
+    stale_count INTEGER NOT NULL DEFAULT 0,
+    purged_count INTEGER NOT NULL DEFAULT 0,
+    excluded_count INTEGER NOT NULL DEFAULT 0,
+    failed_count INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_node_janitor_runs_started ON node_janitor_runs(started_at);
+
+-- Stale nodes found by a run and what was done with them
+CREATE TABLE IF NOT EXISTS node_janitor_run_nodes (
+    run_id TEXT NOT NULL REFERENCES node_janitor_runs(id) ON DELETE CASCADE,
+    certname TEXT NOT NULL,
+    last_seen_at TEXT,
+    status TEXT NOT NULL CHECK (status IN ('purged', 'excluded', 'failed')),
+    deactivated INTEGER NOT NULL DEFAULT 0,
+    certificate_flagged INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    PRIMARY KEY (run_id, certname)
+);
@@ -0,0 +1,19 @@
+-- Local node metadata kept by the WebUI: free-form tags, owning team, ticket
+-- links and markdown notes. Tags are exposed to classification rules as the
+-- `webui.tags` pseudo-fact.
+
+CREATE TABLE IF NOT EXISTS node_metadata (
+    certname TEXT PRIMARY KEY NOT NULL,
+    -- JSON array of tag strings
+    tags TEXT NOT NULL DEFAULT '[]',
+    owner_team TEXT,
+    -- JSON array of {"url": ..., "label": ...}
+    ticket_links TEXT NOT NULL DEFAULT '[]',
+    -- Markdown
+    notes TEXT,
+    updated_by TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_node_metadata_owner_team ON node_metadata(owner_team);
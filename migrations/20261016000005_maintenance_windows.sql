@@ -0,0 +1,32 @@
+-- Maintenance windows
+--
+-- While a window is active, report failures of the nodes it covers are left
+-- out of dashboards and alerts, and the classification and analytics
+-- endpoints flag those nodes as in maintenance. A window targets explicit
+-- nodes, a node group or a fact query, and is either one-off (starts_at ..
+-- ends_at) or recurring (a cron expression for the start plus a duration).
+
+CREATE TABLE IF NOT EXISTS maintenance_windows (
+    id TEXT PRIMARY KEY,
+    organization_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    description TEXT,
+    -- JSON: {"type": "nodes" | "group" | "fact_query", ...}
+    target TEXT NOT NULL,
+    schedule_type TEXT NOT NULL CHECK (schedule_type IN ('one_time', 'recurring')),
+    starts_at TEXT,                 -- one_time
+    ends_at TEXT,                   -- one_time
+    cron_expression TEXT,           -- recurring: 6-field cron (UTC) for window starts
+    duration_minutes INTEGER,       -- recurring
+    enabled INTEGER NOT NULL DEFAULT 1,
+    created_by TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (organization_id) REFERENCES organizations(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_maintenance_windows_organization_id
+    ON maintenance_windows(organization_id);
+
+CREATE INDEX IF NOT EXISTS idx_maintenance_windows_enabled
+    ON maintenance_windows(enabled);
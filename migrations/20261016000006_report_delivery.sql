@@ -0,0 +1,12 @@
+-- Delivery of scheduled report output to a local directory, an S3-compatible
+-- bucket or a WebDAV server, in addition to email.
+
+-- JSON array of delivery targets, encrypted with the settings master key when
+-- one is loaded since targets carry credentials
+ALTER TABLE report_schedules ADD COLUMN delivery_targets TEXT;
+
+-- Overall outcome: 'delivered', 'partial' or 'failed'; NULL when the
+-- execution had nothing to deliver
+ALTER TABLE report_executions ADD COLUMN delivery_status TEXT;
+-- JSON array with the result of each target
+ALTER TABLE report_executions ADD COLUMN delivery_results TEXT;
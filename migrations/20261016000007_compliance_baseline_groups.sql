@@ -0,0 +1,5 @@
+-- Scope compliance baselines to node groups. A baseline with no groups
+-- applies to every node in a report's scope.
+
+-- JSON array of node group IDs
+ALTER TABLE compliance_baselines ADD COLUMN node_group_ids TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,24 @@
+-- Compliance rule packs: versioned bundles of compliance rules (CIS, STIG and
+-- similar benchmarks) imported from YAML or JSON. Every imported version is
+-- kept; baselines record the pack version their rules came from.
+
+CREATE TABLE IF NOT EXISTS compliance_rule_packs (
+    id TEXT PRIMARY KEY,
+    pack_key TEXT NOT NULL, -- Stable identifier of the pack across versions
+    name TEXT NOT NULL,
+    version TEXT NOT NULL,
+    description TEXT,
+    framework TEXT, -- e.g. 'CIS', 'STIG'
+    publisher TEXT,
+    pack_references TEXT NOT NULL DEFAULT '[]', -- JSON array of URLs/document references
+    rules TEXT NOT NULL, -- JSON array of compliance rules
+    checksum TEXT NOT NULL, -- SHA-256 of the imported document
+    imported_by TEXT NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    imported_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE (pack_key, version)
+);
+
+CREATE INDEX IF NOT EXISTS idx_compliance_rule_packs_key ON compliance_rule_packs(pack_key);
+
+ALTER TABLE compliance_baselines ADD COLUMN rule_pack_id TEXT;
+ALTER TABLE compliance_baselines ADD COLUMN rule_pack_version TEXT;
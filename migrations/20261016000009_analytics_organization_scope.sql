@@ -0,0 +1,31 @@
+-- Organization scope for reports and baselines. The organization_id columns
+-- were added with the default organization; move existing rows to the
+-- organization of the user who created them, and schedules and executions to
+-- the organization of their report.
+
+UPDATE saved_reports
+SET organization_id = (SELECT u.organization_id FROM users u WHERE u.id = saved_reports.created_by)
+WHERE EXISTS (SELECT 1 FROM users u WHERE u.id = saved_reports.created_by);
+
+UPDATE report_schedules
+SET organization_id = (SELECT r.organization_id FROM saved_reports r WHERE r.id = report_schedules.report_id)
+WHERE EXISTS (SELECT 1 FROM saved_reports r WHERE r.id = report_schedules.report_id);
+
+UPDATE report_executions
+SET organization_id = (SELECT r.organization_id FROM saved_reports r WHERE r.id = report_executions.report_id)
+WHERE EXISTS (SELECT 1 FROM saved_reports r WHERE r.id = report_executions.report_id);
+
+UPDATE compliance_baselines
+SET organization_id = (SELECT u.organization_id FROM users u WHERE u.id = compliance_baselines.created_by)
+WHERE EXISTS (SELECT 1 FROM users u WHERE u.id = compliance_baselines.created_by);
+
+UPDATE drift_baselines
+SET organization_id = (SELECT u.organization_id FROM users u WHERE u.id = drift_baselines.created_by)
+WHERE EXISTS (SELECT 1 FROM users u WHERE u.id = drift_baselines.created_by);
+
+-- Report and baseline changes are now checked against the reports
+-- permissions; operators keep managing them
+INSERT OR IGNORE INTO permissions (id, role_id, resource, action, scope_type) VALUES
+    ('00000000-0000-0002-0001-000000000009', '00000000-0000-0000-0000-000000000002', 'reports', 'create', 'all'),
+    ('00000000-0000-0002-0001-000000000010', '00000000-0000-0000-0000-000000000002', 'reports', 'update', 'all'),
+    ('00000000-0000-0002-0001-000000000011', '00000000-0000-0000-0000-000000000002', 'reports', 'delete', 'all');
@@ -0,0 +1,6 @@
+-- Per-organization resource quotas. NULL means unlimited.
+
+ALTER TABLE organizations ADD COLUMN max_groups INTEGER;
+ALTER TABLE organizations ADD COLUMN max_saved_reports INTEGER;
+ALTER TABLE organizations ADD COLUMN max_api_keys INTEGER;
+ALTER TABLE organizations ADD COLUMN max_users INTEGER;
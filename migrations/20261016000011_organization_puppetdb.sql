@@ -0,0 +1,14 @@
+-- Per-organization PuppetDB. Organizations without a row use the PuppetDB
+-- from the configuration file.
+
+CREATE TABLE IF NOT EXISTS organization_puppetdb (
+    organization_id TEXT PRIMARY KEY REFERENCES organizations(id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    timeout_secs INTEGER NOT NULL DEFAULT 30,
+    ssl_verify BOOLEAN NOT NULL DEFAULT TRUE,
+    ssl_cert TEXT, -- Client certificate path on the WebUI host
+    ssl_key TEXT, -- Client private key path on the WebUI host
+    ssl_ca TEXT, -- CA bundle path on the WebUI host
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,17 @@
+-- Composite roles: a role inherits the permissions of every parent role.
+-- roles.parent_id is kept as the first parent for older clients.
+
+CREATE TABLE IF NOT EXISTS role_parents (
+    role_id TEXT NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    parent_role_id TEXT NOT NULL REFERENCES roles(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL DEFAULT 0,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (role_id, parent_role_id),
+    CHECK (role_id <> parent_role_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_role_parents_parent ON role_parents(parent_role_id);
+
+INSERT OR IGNORE INTO role_parents (role_id, parent_role_id, position)
+SELECT id, parent_id, 0 FROM roles
+WHERE parent_id IS NOT NULL AND parent_id <> id;
@@ -0,0 +1,32 @@
+-- Time-bound privilege elevation ("break-glass")
+--
+-- A user requests a role for a number of hours and an approver confirms it.
+-- The elevation starts when it is approved and ends at expires_at, when the
+-- expiry task marks it expired. Only approved elevations that have not yet
+-- expired grant the role.
+
+CREATE TABLE IF NOT EXISTS role_elevations (
+    id TEXT PRIMARY KEY,
+    organization_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    role_id TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    duration_hours INTEGER NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'approved', 'denied', 'revoked', 'expired')),
+    decided_by TEXT,                -- approver / denier / revoker
+    decided_at TEXT,
+    decision_comment TEXT,
+    expires_at TEXT,                -- set on approval
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (organization_id) REFERENCES organizations(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (role_id) REFERENCES roles(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_role_elevations_user_status
+    ON role_elevations(user_id, status);
+
+CREATE INDEX IF NOT EXISTS idx_role_elevations_organization_status
+    ON role_elevations(organization_id, status);
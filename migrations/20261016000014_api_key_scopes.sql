@@ -0,0 +1,19 @@
+-- Per-key permission scopes and IP allowlists for API keys
+
+-- JSON array of IP addresses and CIDR ranges the key may be used from;
+-- NULL allows any address
+ALTER TABLE api_keys ADD COLUMN allowed_ips TEXT;
+
+-- Resource/action pairs a key is limited to; a key without rows here is
+-- limited by its roles only
+CREATE TABLE IF NOT EXISTS api_key_permissions (
+    id TEXT PRIMARY KEY NOT NULL,
+    api_key_id TEXT NOT NULL,
+    resource TEXT NOT NULL,
+    action TEXT NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    FOREIGN KEY (api_key_id) REFERENCES api_keys(id) ON DELETE CASCADE,
+    UNIQUE(api_key_id, resource, action)
+);
+
+CREATE INDEX IF NOT EXISTS idx_api_key_permissions_key ON api_key_permissions(api_key_id);
@@ -0,0 +1,8 @@
+-- API key rotation with an overlap window
+--
+-- Rotating a key replaces its secret. The previous secret keeps working
+-- until previous_key_expires_at so clients can switch without downtime.
+
+ALTER TABLE api_keys ADD COLUMN previous_key_hash TEXT;
+ALTER TABLE api_keys ADD COLUMN previous_key_expires_at TEXT;
+ALTER TABLE api_keys ADD COLUMN rotated_at TEXT;
@@ -0,0 +1,4 @@
+-- Client details for login sessions, shown when users review their sessions
+
+ALTER TABLE auth_sessions ADD COLUMN ip_address TEXT;
+ALTER TABLE auth_sessions ADD COLUMN user_agent TEXT;
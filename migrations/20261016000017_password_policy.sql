@@ -0,0 +1,16 @@
+-- Password policy: history of previous passwords and password age
+
+CREATE TABLE IF NOT EXISTS password_history (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    password_hash TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_password_history_user
+    ON password_history(user_id, created_at);
+
+-- When the password was last set; existing users count from their last update
+ALTER TABLE users ADD COLUMN password_changed_at TEXT;
+UPDATE users SET password_changed_at = COALESCE(updated_at, created_at);
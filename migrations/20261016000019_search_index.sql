@@ -0,0 +1,21 @@
+-- Global full-text search index
+--
+-- One row per searchable object (node, group, class, report, user, saved
+-- report) of an organization. The cache sync job rebuilds an organization's
+-- rows from PuppetDB and the database, so the index can always be dropped
+-- and repopulated.
+
+CREATE VIRTUAL TABLE IF NOT EXISTS search_index USING fts5(
+    organization_id UNINDEXED,
+    -- node, group, class, report, user or saved_report
+    kind UNINDEXED,
+    -- Identifier of the object within its kind (certname, UUID, hash, ...)
+    ref_id UNINDEXED,
+    -- User the object is private to (unshared saved reports); NULL if visible
+    -- to everyone allowed to read the kind
+    owner_id UNINDEXED,
+    title,
+    body,
+    tokenize = 'unicode61 remove_diacritics 2',
+    prefix = '2 3'
+);
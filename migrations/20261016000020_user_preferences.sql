@@ -0,0 +1,13 @@
+-- Per-user preferences
+--
+-- Settings a user changes for themselves, such as their dashboard widgets,
+-- layout, theme and default filters. Unset values fall back to the global
+-- configuration.
+
+CREATE TABLE IF NOT EXISTS user_preferences (
+    user_id TEXT PRIMARY KEY,
+    -- JSON object of dashboard overrides
+    dashboard TEXT NOT NULL DEFAULT '{}',
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
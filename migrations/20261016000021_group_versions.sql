@@ -0,0 +1,7 @@
+-- Optimistic concurrency for node groups
+--
+-- Every change to a group, its rules or its pinned nodes bumps the version.
+-- The API exposes it as the group's ETag and requires a matching If-Match
+-- header on updates, so concurrent edits no longer overwrite each other.
+
+ALTER TABLE node_groups ADD COLUMN version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,14 @@
+-- Soft deletion of node groups, users and saved reports
+--
+-- Deleting one of these records sets deleted_at instead of removing the row,
+-- which keeps it (and everything that cascades from it) restorable from the
+-- recycle bin. Rows are removed for good once the configured retention
+-- period has passed or when purged from the recycle bin.
+
+ALTER TABLE node_groups ADD COLUMN deleted_at TEXT;
+ALTER TABLE users ADD COLUMN deleted_at TEXT;
+ALTER TABLE saved_reports ADD COLUMN deleted_at TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_node_groups_deleted_at ON node_groups(deleted_at);
+CREATE INDEX IF NOT EXISTS idx_users_deleted_at ON users(deleted_at);
+CREATE INDEX IF NOT EXISTS idx_saved_reports_deleted_at ON saved_reports(deleted_at);
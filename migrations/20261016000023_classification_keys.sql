@@ -0,0 +1,25 @@
+-- Managed shared keys for the classification endpoints
+--
+-- Puppet Server (or anything else that cannot present a client certificate)
+-- authenticates to /nodes/{certname}/classify and the other node endpoints
+-- with an X-Classification-Key header. Besides the single key from the
+-- configuration file, keys can now be created, rotated and revoked through
+-- the API. Only a hash of each secret is stored.
+
+CREATE TABLE IF NOT EXISTS classification_keys (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    description TEXT,
+    key_hash TEXT NOT NULL,
+    -- Secret replaced by the last rotation, accepted until previous_key_expires_at
+    previous_key_hash TEXT,
+    previous_key_expires_at TEXT,
+    -- JSON array of certname patterns (`*` and `?` wildcards) the key may be
+    -- used for; empty allows any node
+    allowed_cn_patterns TEXT NOT NULL DEFAULT '[]',
+    created_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TEXT NOT NULL,
+    rotated_at TEXT,
+    revoked_at TEXT,
+    last_used_at TEXT
+);
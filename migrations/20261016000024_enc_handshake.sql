@@ -0,0 +1,8 @@
+-- ENC script handshakes
+--
+-- The ENC script generated by /enc/bootstrap can ping OpenVox WebUI with its
+-- classification key (`enc.sh --ping`). The last handshake is kept on the
+-- key so the Puppet Server setup can be checked from the web UI.
+
+ALTER TABLE classification_keys ADD COLUMN last_handshake_at TEXT;
+ALTER TABLE classification_keys ADD COLUMN last_handshake_host TEXT;
@@ -0,0 +1,10 @@
+-- Request IDs on audit entries
+--
+-- Each API request gets an ID (honoring an incoming X-Request-Id header)
+-- that is returned in the X-Request-Id response header, error bodies and the
+-- server logs. Recording it on audit entries links them to the request that
+-- made the change.
+
+ALTER TABLE audit_log ADD COLUMN request_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_request_id ON audit_log(request_id);
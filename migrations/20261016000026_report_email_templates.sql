@@ -0,0 +1,21 @@
+-- Email templates of scheduled reports
+--
+-- Schedules with email recipients mail a summary of each run with the full
+-- export attached. The subject and bodies are Tera templates; an
+-- organization can override the built-in ones for every report type
+-- (report_type NULL) or for a single type.
+
+CREATE TABLE IF NOT EXISTS report_email_templates (
+    id TEXT PRIMARY KEY,
+    organization_id TEXT NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    report_type TEXT,
+    subject_template TEXT NOT NULL,
+    html_template TEXT NOT NULL,
+    text_template TEXT NOT NULL,
+    updated_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+    created_at TEXT NOT NULL,
+    updated_at TEXT NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_report_email_templates_org_type
+    ON report_email_templates(organization_id, COALESCE(report_type, ''));
@@ -0,0 +1,10 @@
+-- Deduplication of notifications raised by background checks
+--
+-- Checks that run repeatedly, such as the expiring PAT token check, tag their
+-- notifications with a key; a user is not notified again while a
+-- notification with the same key is kept.
+
+ALTER TABLE notifications ADD COLUMN dedup_key TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_notifications_user_dedup
+    ON notifications(user_id, dedup_key) WHERE dedup_key IS NOT NULL;
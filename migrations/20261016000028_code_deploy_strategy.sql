@@ -0,0 +1,3 @@
+-- Deploy strategy of Code Deploy repositories
+-- 'r10k' (default), 'librarian_puppet' or 'git_checkout'
+ALTER TABLE code_repositories ADD COLUMN deploy_strategy TEXT NOT NULL DEFAULT 'r10k';
@@ -0,0 +1,4 @@
+-- r10k flags of Code Deploy deployments (JSON, NULL for a full deployment)
+-- and the command line each deployment ran
+ALTER TABLE code_deployments ADD COLUMN options TEXT;
+ALTER TABLE code_deployments ADD COLUMN command_line TEXT;
@@ -0,0 +1,32 @@
+-- Canary runs of Code Deploy deployments: the deployed commit is checked out
+-- into a temporary environment, a sample of nodes is pinned to it through a
+-- classifier group, and their reports decide whether the deployment is
+-- promoted or rolled back
+CREATE TABLE IF NOT EXISTS code_canaries (
+    id TEXT PRIMARY KEY NOT NULL,
+    deployment_id TEXT NOT NULL REFERENCES code_deployments(id) ON DELETE CASCADE,
+    organization_id TEXT NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    -- Name of the temporary environment
+    environment TEXT NOT NULL,
+    -- Group the sample was taken from
+    source_group_id TEXT NOT NULL,
+    -- Group pinning the sample to the temporary environment, removed once the
+    -- canary ends
+    canary_group_id TEXT,
+    -- JSON array of the certnames of the sample
+    nodes TEXT NOT NULL,
+    required_reports INTEGER NOT NULL DEFAULT 1,
+    auto_promote INTEGER NOT NULL DEFAULT 0,
+    status TEXT NOT NULL DEFAULT 'running',
+    -- JSON array of the report counts of each node
+    node_reports TEXT,
+    error_message TEXT,
+    started_by TEXT REFERENCES users(id) ON DELETE SET NULL,
+    deadline_at TEXT NOT NULL,
+    completed_at TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_code_canaries_deployment ON code_canaries(deployment_id);
+CREATE INDEX IF NOT EXISTS idx_code_canaries_status ON code_canaries(status);
@@ -0,0 +1,23 @@
+-- Hourly snapshots of fleet-wide metrics. A background job records node
+-- counts by latest report status and the run time, catalog compile time and
+-- resource totals of the reports of the hour, so the Analytics trend charts
+-- can cover 30/90 days without querying PuppetDB or depending on its report
+-- retention.
+CREATE TABLE IF NOT EXISTS fleet_metrics_hourly (
+    hour                     TEXT PRIMARY KEY,  -- UTC bucket start, e.g. "2026-10-16T13:00:00Z"
+    total_nodes              INTEGER NOT NULL DEFAULT 0,
+    changed_nodes            INTEGER NOT NULL DEFAULT 0,
+    unchanged_nodes          INTEGER NOT NULL DEFAULT 0,
+    failed_nodes             INTEGER NOT NULL DEFAULT 0,
+    unreported_nodes         INTEGER NOT NULL DEFAULT 0,
+    -- Reports received during the hour
+    report_count             INTEGER NOT NULL DEFAULT 0,
+    -- NULL when no report of the hour had the metric
+    avg_run_time_seconds     REAL,
+    avg_compile_time_seconds REAL,
+    -- Resources of the latest report of each node reporting during the hour
+    total_resources          INTEGER NOT NULL DEFAULT 0,
+    changed_resources        INTEGER NOT NULL DEFAULT 0,
+    failed_resources         INTEGER NOT NULL DEFAULT 0,
+    updated_at               TEXT NOT NULL
+);
@@ -0,0 +1,11 @@
+-- High-water mark of the report ingester: the latest PuppetDB receive_time
+-- processed, with the hashes of the reports received at that instant so a
+-- restart neither skips nor replays them
+CREATE TABLE IF NOT EXISTS report_ingestion_cursors (
+    -- PuppetDB the cursor follows
+    source TEXT PRIMARY KEY NOT NULL,
+    receive_time TEXT NOT NULL,
+    -- JSON array of report hashes
+    hashes TEXT NOT NULL DEFAULT '[]',
+    updated_at TEXT NOT NULL
+);
@@ -0,0 +1,32 @@
+-- User impersonation
+--
+-- Admins with the `impersonate` action on users get a short-lived access
+-- token acting as another user, to reproduce what that user sees. Each
+-- impersonation is recorded with the admin, the user and the reason; its ID
+-- is the ID of the login session the token belongs to. Audit entries written
+-- under an impersonation keep the impersonated user in user_id and the admin
+-- in impersonator_id.
+
+CREATE TABLE IF NOT EXISTS impersonation_sessions (
+    id TEXT PRIMARY KEY,            -- auth_sessions.id of the token
+    organization_id TEXT NOT NULL,  -- organization of the impersonated user
+    impersonator_id TEXT NOT NULL,
+    user_id TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    ended_at TEXT,                  -- set when ended before expiring
+    FOREIGN KEY (organization_id) REFERENCES organizations(id) ON DELETE CASCADE,
+    FOREIGN KEY (impersonator_id) REFERENCES users(id) ON DELETE CASCADE,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_impersonation_sessions_organization
+    ON impersonation_sessions(organization_id, created_at);
+
+CREATE INDEX IF NOT EXISTS idx_impersonation_sessions_impersonator
+    ON impersonation_sessions(impersonator_id);
+
+ALTER TABLE audit_log ADD COLUMN impersonator_id TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_audit_log_impersonator ON audit_log(impersonator_id);
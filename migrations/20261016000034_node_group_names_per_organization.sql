@@ -0,0 +1,51 @@
+-- no-transaction
+-- Make node group names unique per organization instead of globally, so
+-- every organization can have its own "All Nodes" group (new organizations
+-- are seeded from group templates). SQLite cannot drop the column's UNIQUE
+-- constraint in place, so the table is rebuilt.
+--
+-- This migration runs outside a transaction so foreign keys can be disabled
+-- during the rebuild; otherwise DROP TABLE would cascade-delete the rules,
+-- pinned nodes and other rows that reference node_groups.
+
+PRAGMA foreign_keys=OFF;
+
+CREATE TABLE node_groups_new (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    description TEXT,
+    parent_id TEXT REFERENCES node_groups(id) ON DELETE SET NULL,
+    environment TEXT,
+    rule_match_type TEXT NOT NULL DEFAULT 'all',
+    classes TEXT NOT NULL DEFAULT '[]',
+    parameters TEXT NOT NULL DEFAULT '{}',
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    variables TEXT NOT NULL DEFAULT '{}',
+    organization_id TEXT NOT NULL DEFAULT '00000000-0000-0000-0000-000000000010',
+    is_environment_group INTEGER NOT NULL DEFAULT 0,
+    match_all_nodes INTEGER NOT NULL DEFAULT 0,
+    version INTEGER NOT NULL DEFAULT 1,
+    deleted_at TEXT,
+    UNIQUE (organization_id, name)
+);
+
+INSERT INTO node_groups_new (
+    id, name, description, parent_id, environment, rule_match_type, classes,
+    parameters, created_at, updated_at, variables, organization_id,
+    is_environment_group, match_all_nodes, version, deleted_at
+)
+SELECT
+    id, name, description, parent_id, environment, rule_match_type, classes,
+    parameters, created_at, updated_at, variables, organization_id,
+    is_environment_group, match_all_nodes, version, deleted_at
+FROM node_groups;
+
+DROP TABLE node_groups;
+ALTER TABLE node_groups_new RENAME TO node_groups;
+
+CREATE INDEX IF NOT EXISTS idx_node_groups_parent ON node_groups(parent_id);
+CREATE INDEX IF NOT EXISTS idx_node_groups_org ON node_groups(organization_id);
+CREATE INDEX IF NOT EXISTS idx_node_groups_deleted_at ON node_groups(deleted_at);
+
+PRAGMA foreign_keys=ON;
@@ -0,0 +1,34 @@
+-- no-transaction
+-- Make fact template names unique per organization instead of globally, like
+-- node group names, so organizations can be merged and split with their
+-- templates. SQLite cannot drop the column's UNIQUE constraint in place, so
+-- the table is rebuilt.
+--
+-- This migration runs outside a transaction so foreign keys can be disabled
+-- during the rebuild.
+
+PRAGMA foreign_keys=OFF;
+
+CREATE TABLE fact_templates_new (
+    id TEXT PRIMARY KEY,
+    name TEXT NOT NULL,
+    description TEXT,
+    facts TEXT NOT NULL DEFAULT '[]',
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    organization_id TEXT NOT NULL DEFAULT '00000000-0000-0000-0000-000000000010',
+    UNIQUE (organization_id, name)
+);
+
+INSERT INTO fact_templates_new (
+    id, name, description, facts, created_at, updated_at, organization_id
+)
+SELECT id, name, description, facts, created_at, updated_at, organization_id
+FROM fact_templates;
+
+DROP TABLE fact_templates;
+ALTER TABLE fact_templates_new RENAME TO fact_templates;
+
+CREATE INDEX IF NOT EXISTS idx_fact_templates_org ON fact_templates(organization_id);
+
+PRAGMA foreign_keys=ON;
@@ -0,0 +1,13 @@
+-- Service accounts
+--
+-- Users of kind 'service_account' are for automation: they cannot log in
+-- with a password or SAML and authenticate with API keys only. Audit
+-- entries record the kind of their user so automated changes stand out.
+
+ALTER TABLE users ADD COLUMN kind TEXT NOT NULL DEFAULT 'human';
+
+ALTER TABLE audit_log ADD COLUMN user_kind TEXT;
+
+UPDATE audit_log
+SET user_kind = (SELECT kind FROM users WHERE users.id = audit_log.user_id)
+WHERE user_id IS NOT NULL;
@@ -0,0 +1,21 @@
+-- JWT signing keys
+--
+-- With `auth.signing_keys` configured for an asymmetric algorithm or a
+-- rotation period, access and refresh tokens are signed with keys stored
+-- here instead of `auth.jwt_secret`, and name their key in the `kid` header.
+-- A replaced key is retired rather than deleted: it keeps verifying tokens
+-- until expires_at, so rotating never logs anyone out.
+--
+-- The row with kid 'legacy' has no key material; its expires_at is how long
+-- tokens signed with `auth.jwt_secret` (which have no `kid`) stay valid after
+-- switching to stored keys.
+
+CREATE TABLE IF NOT EXISTS jwt_signing_keys (
+    kid TEXT PRIMARY KEY,
+    algorithm TEXT NOT NULL,        -- HS256, RS256 or EdDSA
+    private_key TEXT,               -- base64 secret or DER key, encrypted with the settings master key when loaded
+    public_key TEXT,                -- JWK of asymmetric keys
+    created_at TEXT NOT NULL,
+    retired_at TEXT,                -- set when replaced; NULL for the signing key
+    expires_at TEXT                 -- end of the verification grace window
+);
@@ -0,0 +1,17 @@
+-- One-time-use refresh tokens
+--
+-- Every refresh token handed out is recorded here by its SHA-256 hash.
+-- Exchanging it at /auth/refresh marks it used and returns a new one; the
+-- session is the token family. Presenting a used token again means it was
+-- copied, so the whole session is revoked and the reuse is audited.
+
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+    token_hash TEXT PRIMARY KEY,
+    session_id TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    expires_at TEXT NOT NULL,
+    used_at TEXT,                   -- set when exchanged for a new token
+    FOREIGN KEY (session_id) REFERENCES auth_sessions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_session ON refresh_tokens(session_id);
@@ -0,0 +1,24 @@
+-- Login history
+--
+-- Every password or SAML login of a known user is recorded with the client
+-- address, user agent and location, successful or not. A successful login
+-- from a device or location the user has not logged in from before is
+-- flagged so the user can be notified. Rows older than
+-- auth.login_alerts.history_days are pruned on login.
+
+CREATE TABLE IF NOT EXISTS login_history (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    success INTEGER NOT NULL,
+    method TEXT NOT NULL,           -- password, saml
+    ip_address TEXT,
+    user_agent TEXT,
+    device TEXT,                    -- user agent without version numbers
+    location TEXT,                  -- geo from proxy headers, else the client network
+    new_device INTEGER NOT NULL DEFAULT 0,
+    new_location INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_login_history_user ON login_history(user_id, created_at);
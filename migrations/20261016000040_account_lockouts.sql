@@ -0,0 +1,16 @@
+-- Per-account brute force protection
+--
+-- Failed password logins are counted per user, in addition to the per-address
+-- rate limit. rbac.max_failed_logins failures within
+-- rbac.lockout_duration_minutes lock the account; every further lockout
+-- doubles the duration up to rbac.max_lockout_minutes. A successful login or
+-- an admin unlock clears the row.
+
+CREATE TABLE IF NOT EXISTS account_lockouts (
+    user_id TEXT PRIMARY KEY,
+    failed_attempts INTEGER NOT NULL DEFAULT 0,  -- failures since the last lockout
+    last_failed_at TEXT,
+    locked_until TEXT,
+    lockouts INTEGER NOT NULL DEFAULT 0,         -- consecutive lockouts, for backoff
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+);
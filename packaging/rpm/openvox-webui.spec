@@ -90,6 +90,8 @@ install -m 640 config/config.example.yaml %{buildroot}%{_sysconfdir}/openvox-web
 
 # Install systemd units
 install -m 644 packaging/systemd/openvox-webui.service %{buildroot}%{_unitdir}/openvox-webui.service
+%{_unitdir}/openvox-webui.socket
+install -m 644 packaging/systemd/openvox-webui.socket %{buildroot}%{_unitdir}/openvox-webui.socket
 
 # Install configuration script
 install -m 755 packaging/scripts/configure-openvox-webui.sh %{buildroot}%{_datadir}/openvox-webui/scripts/configure-openvox-webui.sh
@@ -1304,6 +1304,8 @@ module OpenVoxInventory
       execute_package_install(family, packages, config)
     when 'package_remove', 'PackageRemove'
       execute_package_remove(family, packages, config)
+    when 'cert_renewal', 'CertRenewal'
+      execute_cert_renewal(config)
     else
       { 'status' => 'failed', 'summary' => "Unknown operation type: #{operation}", 'output' => '' }
     end
@@ -1390,6 +1392,60 @@ module OpenVoxInventory
     run_update_command(cmd)
   end
 
+  # Renew the agent certificate through the CA's certificate_renewal endpoint.
+  # Requires `allow-auto-renewal: true` on the CA.
+  def execute_cert_renewal(_config)
+    unless defined?(Puppet) && Puppet.respond_to?(:[])
+      return { 'status' => 'failed', 'summary' => 'Puppet settings are not available', 'output' => '' }
+    end
+
+    hostcert = Puppet[:hostcert]
+    hostprivkey = Puppet[:hostprivkey]
+    localcacert = Puppet[:localcacert]
+    missing = [hostcert, hostprivkey, localcacert].reject { |path| path && File.exist?(path) }
+    unless missing.empty?
+      return { 'status' => 'failed', 'summary' => "Missing SSL files: #{missing.compact.join(', ')}", 'output' => '' }
+    end
+
+    uri = URI.parse("https://#{Puppet[:ca_server]}:#{Puppet[:ca_port]}/puppet-ca/v1/certificate_renewal")
+    http = Net::HTTP.new(uri.host, uri.port)
+    http.open_timeout = 10
+    http.read_timeout = 30
+    http.use_ssl = true
+    http.verify_mode = OpenSSL::SSL::VERIFY_PEER
+    http.ca_file = localcacert
+    http.cert = OpenSSL::X509::Certificate.new(File.read(hostcert))
+    http.key = OpenSSL::PKey.read(File.read(hostprivkey))
+
+    request = Net::HTTP::Post.new(uri.request_uri)
+    request['Accept'] = 'text/plain'
+    request['Content-Type'] = 'text/plain'
+    request['User-Agent'] = 'OpenVox-InventoryCollector/1.0'
+
+    response = http.request(request)
+    unless response.code.to_i >= 200 && response.code.to_i < 300
+      return {
+        'status' => 'failed',
+        'summary' => "Certificate renewal failed with HTTP #{response.code}",
+        'output' => response.body.to_s.slice(0, 10_000)
+      }
+    end
+
+    renewed = OpenSSL::X509::Certificate.new(response.body)
+    tmp_path = "#{hostcert}.renewal"
+    File.write(tmp_path, renewed.to_pem)
+    File.chmod(File.stat(hostcert).mode & 0o777, tmp_path)
+    File.rename(tmp_path, hostcert)
+
+    {
+      'status' => 'succeeded',
+      'summary' => "Certificate renewed, valid until #{renewed.not_after.utc.iso8601}",
+      'output' => "serial #{renewed.serial}"
+    }
+  rescue StandardError => e
+    { 'status' => 'failed', 'summary' => "Certificate renewal error: #{e.message}", 'output' => '' }
+  end
+
   def run_update_command(cmd)
     output = `#{cmd}`
     exit_code = $CHILD_STATUS.exitstatus
@@ -113,6 +113,7 @@ class openvox_webui::auth {
       content => epp('openvox_webui/ca.conf.epp', {
           client_certname         => $client_certname,
           allow_subject_alt_names => $openvox_webui::ca_allow_subject_alt_names,
+          allow_auto_renewal      => $openvox_webui::ca_allow_auto_renewal,
       }),
     }
 
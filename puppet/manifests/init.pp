@@ -137,6 +137,11 @@
 # @param ca_allow_subject_alt_names
 #   Whether to allow Subject Alternative Names in certificate requests.
 #
+# @param ca_allow_auto_renewal
+#   Whether agents may renew their own certificates through the CA's
+#   certificate_renewal endpoint. Required for renewal campaigns that
+#   trigger agents.
+#
 # @param saml_enabled
 #   Whether to enable SAML 2.0 SSO authentication.
 #
@@ -422,6 +427,7 @@ class openvox_webui (
   # Manages ca.conf to enable the certificate_status endpoint (disabled by default)
   Boolean                             $manage_puppetserver_ca_conf = false,
   Boolean                             $ca_allow_subject_alt_names  = true,
+  Boolean                             $ca_allow_auto_renewal       = false,
 
   # SAML 2.0 SSO settings
   Boolean                             $saml_enabled                    = false,
@@ -0,0 +1,132 @@
+#!/bin/bash
+#
+# OpenVox WebUI External Node Classifier
+#
+# This script is dynamically generated by OpenVox WebUI with its own
+# classification key. Puppet Server runs it with a certname and reads the
+# node's classification as YAML from standard output.
+#
+# Install:
+#   curl -sSL -H "Authorization: Bearer $TOKEN" \
+#     https://your-openvox-webui/api/v1/enc/bootstrap -o /opt/openvox/enc.sh
+#   chmod 0750 /opt/openvox/enc.sh
+#   chown root:puppet /opt/openvox/enc.sh
+#
+# puppet.conf ([server] section):
+#   node_terminus = exec
+#   external_nodes = /opt/openvox/enc.sh
+#
+# Verify the setup (the handshake is shown on the key in OpenVox WebUI):
+#   /opt/openvox/enc.sh --ping
+#
+# Generated by OpenVox WebUI - https://github.com/ffquintella/openvox-webui
+#
+
+# Don't use set -e as grep returns non-zero when no match found
+
+# Configuration (injected by OpenVox WebUI)
+WEBUI_URL="{{WEBUI_URL}}"
+CLASSIFICATION_KEY="{{CLASSIFICATION_KEY}}"
+SSL_VERIFY="{{SSL_VERIFY}}"
+
+CURL_OPTS=(-s --max-time 30)
+if [ "$SSL_VERIFY" != "true" ]; then
+    # Allow self-signed certificates
+    CURL_OPTS+=(-k)
+fi
+CURL_AUTH_OPTS=("${CURL_OPTS[@]}" -H "X-Classification-Key: ${CLASSIFICATION_KEY}")
+
+# Handshake with OpenVox WebUI to validate the URL and key
+if [ "$1" = "--ping" ]; then
+    HOST=$(hostname -f 2>/dev/null || hostname)
+    RESPONSE=$(curl "${CURL_AUTH_OPTS[@]}" -w '\n%{http_code}' \
+        "${WEBUI_URL}/api/v1/enc/ping?host=${HOST}" 2>&1)
+    STATUS=$(echo "$RESPONSE" | tail -n 1)
+    BODY=$(echo "$RESPONSE" | sed '$d')
+    if [ "$STATUS" = "200" ]; then
+        echo "OK: ${WEBUI_URL} accepted the classification key"
+        echo "$BODY"
+        exit 0
+    fi
+    echo "Error: handshake with ${WEBUI_URL} failed (HTTP ${STATUS})" >&2
+    echo "$BODY" >&2
+    exit 1
+fi
+
+CERTNAME="$1"
+
+# Validate input
+if [ -z "$CERTNAME" ]; then
+    echo "Error: No certname provided" >&2
+    echo "Usage: $0 <certname> | --ping" >&2
+    exit 1
+fi
+
+# Function to get environment from unauthenticated endpoint
+get_environment_only() {
+    local env_response
+    env_response=$(curl "${CURL_OPTS[@]}" "${WEBUI_URL}/api/v1/nodes/${CERTNAME}/environment" 2>/dev/null) || true
+    if echo "$env_response" | grep -q '"environment"' 2>/dev/null; then
+        echo "$env_response" | python3 -c '
+import sys
+import json
+try:
+    data = json.load(sys.stdin)
+    env = data.get("environment") or "production"
+    print(env)
+except:
+    print("production")
+' 2>/dev/null || echo "production"
+    else
+        echo "production"
+    fi
+}
+
+# Query OpenVox WebUI classification API
+CLASSIFICATION=$(curl "${CURL_AUTH_OPTS[@]}" "${WEBUI_URL}/api/v1/nodes/${CERTNAME}/classify" 2>&1) || true
+
+# Check if we got valid JSON with successful classification
+if echo "$CLASSIFICATION" | grep -q '"certname"' 2>/dev/null; then
+    # Convert JSON to YAML format expected by Puppet
+    YAML_OUTPUT=$(echo "$CLASSIFICATION" | python3 -c '
+import sys
+import json
+import yaml
+
+try:
+    data = json.load(sys.stdin)
+
+    # Puppet expects specific format
+    output = {
+        "environment": data.get("environment", "production"),
+        "classes": data.get("classes", {}),
+    }
+
+    # Add parameters/variables if present
+    if "parameters" in data:
+        output["parameters"] = data["parameters"]
+    elif "variables" in data:
+        output["parameters"] = data["variables"]
+
+    # Output as YAML
+    print(yaml.dump(output, default_flow_style=False, explicit_start=True))
+except json.JSONDecodeError as e:
+    print(f"Error: Invalid JSON from OpenVox WebUI: {e}", file=sys.stderr)
+    sys.exit(1)
+except Exception as e:
+    print(f"Error processing classification: {e}", file=sys.stderr)
+    sys.exit(1)
+' 2>&1)
+    EXIT_CODE=$?
+    if [ $EXIT_CODE -eq 0 ]; then
+        echo "$YAML_OUTPUT"
+        exit 0
+    fi
+fi
+
+# Authentication failed or API error
+# Fall back to the unauthenticated /environment endpoint
+ENVIRONMENT=$(get_environment_only)
+echo "---"
+echo "environment: ${ENVIRONMENT}"
+echo "classes: {}"
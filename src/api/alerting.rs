@@ -12,12 +12,15 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
+    api::smart_lists::can_see_smart_list,
+    db::SmartListRepository,
     models::{
-        Alert, AlertRule, AlertRuleType, AlertSeverity, AlertSilence, AlertStats, AlertStatus,
-        CreateAlertRuleRequest, CreateChannelRequest, CreateSilenceRequest, NotificationChannel,
-        TestChannelRequest, TestChannelResponse, UpdateAlertRuleRequest, UpdateChannelRequest,
+        Alert, AlertCondition, AlertRule, AlertRuleType, AlertSeverity, AlertSilence, AlertStats,
+        AlertStatus, CreateAlertRuleRequest, CreateChannelRequest, CreateSilenceRequest,
+        NotificationChannel, TestChannelRequest, TestChannelResponse, UpdateAlertRuleRequest,
+        UpdateChannelRequest,
     },
-    services::AlertingService,
+    services::alerting::rule_smart_list_ids,
     AppState, AuthUser,
 };
 
@@ -109,11 +112,7 @@ async fn list_channels(
     State(state): State<AppState>,
     _user: AuthUser,
 ) -> Result<Json<AlertingResponse<Vec<NotificationChannel>>>, StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    let service = state.alerting_service();
 
     match service.get_channels().await {
         Ok(channels) => Ok(Json(AlertingResponse { data: channels })),
@@ -130,11 +129,7 @@ async fn get_channel(
     Path(id): Path<Uuid>,
     _user: AuthUser,
 ) -> Result<Json<AlertingResponse<NotificationChannel>>, StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    let service = state.alerting_service();
 
     match service.get_channel(id).await {
         Ok(Some(channel)) => Ok(Json(AlertingResponse { data: channel })),
@@ -152,11 +147,7 @@ async fn create_channel(
     user: AuthUser,
     Json(req): Json<CreateChannelRequest>,
 ) -> Result<(StatusCode, Json<AlertingResponse<NotificationChannel>>), StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    let service = state.alerting_service();
 
     match service.create_channel(&req, Some(user.user_id())).await {
         Ok(channel) => Ok((
@@ -177,11 +168,7 @@ async fn update_channel(
     _user: AuthUser,
     Json(req): Json<UpdateChannelRequest>,
 ) -> Result<Json<AlertingResponse<NotificationChannel>>, StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    let service = state.alerting_service();
 
     match service.update_channel(id, &req).await {
         Ok(Some(channel)) => Ok(Json(AlertingResponse { data: channel })),
@@ -199,11 +186,7 @@ async fn delete_channel(
     Path(id): Path<Uuid>,
     _user: AuthUser,
 ) -> Result<StatusCode, StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    let service = state.alerting_service();
 
     match service.delete_channel(id).await {
         Ok(true) => Ok(StatusCode::NO_CONTENT),
@@ -222,11 +205,7 @@ async fn test_channel(
     _user: AuthUser,
     Json(req): Json<TestChannelRequest>,
 ) -> Result<Json<AlertingResponse<TestChannelResponse>>, StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    let service = state.alerting_service();
 
     match service.test_channel(id, &req).await {
         Ok(response) => Ok(Json(AlertingResponse { data: response })),
@@ -247,11 +226,7 @@ async fn list_rules(
     Query(query): Query<RulesQuery>,
     _user: AuthUser,
 ) -> Result<Json<AlertingResponse<Vec<AlertRule>>>, StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    let service = state.alerting_service();
 
     let rules = if let Some(type_str) = query.rule_type {
         if let Some(rule_type) = AlertRuleType::from_str(&type_str) {
@@ -280,11 +255,7 @@ async fn get_rule(
     Path(id): Path<Uuid>,
     _user: AuthUser,
 ) -> Result<Json<AlertingResponse<AlertRule>>, StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    let service = state.alerting_service();
 
     match service.get_rule(id).await {
         Ok(Some(rule)) => Ok(Json(AlertingResponse { data: rule })),
@@ -302,11 +273,8 @@ async fn create_rule(
     user: AuthUser,
     Json(req): Json<CreateAlertRuleRequest>,
 ) -> Result<(StatusCode, Json<AlertingResponse<AlertRule>>), StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    check_rule_smart_lists(&state, &user, &req.conditions).await?;
+    let service = state.alerting_service();
 
     match service.create_rule(&req, Some(user.user_id())).await {
         Ok(rule) => Ok((StatusCode::CREATED, Json(AlertingResponse { data: rule }))),
@@ -317,18 +285,45 @@ async fn create_rule(
     }
 }
 
+/// Check that the smart lists a rule's conditions name exist in the caller's
+/// organization and are visible to them
+async fn check_rule_smart_lists(
+    state: &AppState,
+    user: &AuthUser,
+    conditions: &[AlertCondition],
+) -> Result<(), StatusCode> {
+    let repo = SmartListRepository::new(&state.db);
+    for id in rule_smart_list_ids(conditions) {
+        let Ok(id) = Uuid::parse_str(&id) else {
+            tracing::debug!("Rejected rule with invalid smart list ID '{}'", id);
+            return Err(StatusCode::BAD_REQUEST);
+        };
+        let list = repo
+            .get_by_id(user.organization_id, id)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to get smart list: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            })?;
+        if !list.is_some_and(|list| can_see_smart_list(user, &list)) {
+            tracing::debug!("Rejected rule with unknown smart list {}", id);
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+    Ok(())
+}
+
 /// Update an alert rule
 async fn update_rule(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-    _user: AuthUser,
+    user: AuthUser,
     Json(req): Json<UpdateAlertRuleRequest>,
 ) -> Result<Json<AlertingResponse<AlertRule>>, StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    if let Some(conditions) = &req.conditions {
+        check_rule_smart_lists(&state, &user, conditions).await?;
+    }
+    let service = state.alerting_service();
 
     match service.update_rule(id, &req).await {
         Ok(Some(rule)) => Ok(Json(AlertingResponse { data: rule })),
@@ -346,11 +341,7 @@ async fn delete_rule(
     Path(id): Path<Uuid>,
     _user: AuthUser,
 ) -> Result<StatusCode, StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    let service = state.alerting_service();
 
     match service.delete_rule(id).await {
         Ok(true) => Ok(StatusCode::NO_CONTENT),
@@ -372,11 +363,7 @@ async fn list_alerts(
     Query(query): Query<AlertsQuery>,
     _user: AuthUser,
 ) -> Result<Json<AlertingResponse<Vec<Alert>>>, StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    let service = state.alerting_service();
 
     let status = query.status.and_then(|s| AlertStatus::from_str(&s));
     let severity = query.severity.and_then(|s| AlertSeverity::from_str(&s));
@@ -398,11 +385,7 @@ async fn get_alert_stats(
     State(state): State<AppState>,
     _user: AuthUser,
 ) -> Result<Json<AlertingResponse<AlertStats>>, StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    let service = state.alerting_service();
 
     match service.get_alert_stats().await {
         Ok(stats) => Ok(Json(AlertingResponse { data: stats })),
@@ -419,11 +402,7 @@ async fn get_alert(
     Path(id): Path<Uuid>,
     _user: AuthUser,
 ) -> Result<Json<AlertingResponse<Alert>>, StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    let service = state.alerting_service();
 
     match service.get_alert(id).await {
         Ok(Some(alert)) => Ok(Json(AlertingResponse { data: alert })),
@@ -441,11 +420,7 @@ async fn acknowledge_alert(
     Path(id): Path<Uuid>,
     user: AuthUser,
 ) -> Result<Json<AlertingResponse<Alert>>, StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    let service = state.alerting_service();
 
     match service.acknowledge_alert(id, user.user_id()).await {
         Ok(Some(alert)) => Ok(Json(AlertingResponse { data: alert })),
@@ -463,11 +438,7 @@ async fn resolve_alert(
     Path(id): Path<Uuid>,
     _user: AuthUser,
 ) -> Result<Json<AlertingResponse<Alert>>, StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    let service = state.alerting_service();
 
     match service.resolve_alert(id).await {
         Ok(Some(alert)) => Ok(Json(AlertingResponse { data: alert })),
@@ -485,11 +456,7 @@ async fn silence_alert(
     Path(id): Path<Uuid>,
     _user: AuthUser,
 ) -> Result<Json<AlertingResponse<Alert>>, StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    let service = state.alerting_service();
 
     match service.silence_alert(id).await {
         Ok(Some(alert)) => Ok(Json(AlertingResponse { data: alert })),
@@ -510,11 +477,7 @@ async fn list_silences(
     State(state): State<AppState>,
     _user: AuthUser,
 ) -> Result<Json<AlertingResponse<Vec<AlertSilence>>>, StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    let service = state.alerting_service();
 
     match service.get_silences().await {
         Ok(silences) => Ok(Json(AlertingResponse { data: silences })),
@@ -531,11 +494,7 @@ async fn create_silence(
     user: AuthUser,
     Json(req): Json<CreateSilenceRequest>,
 ) -> Result<(StatusCode, Json<AlertingResponse<AlertSilence>>), StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    let service = state.alerting_service();
 
     match service.create_silence(&req, Some(user.user_id())).await {
         Ok(silence) => Ok((
@@ -555,11 +514,7 @@ async fn delete_silence(
     Path(id): Path<Uuid>,
     _user: AuthUser,
 ) -> Result<StatusCode, StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    let service = state.alerting_service();
 
     match service.delete_silence(id).await {
         Ok(true) => Ok(StatusCode::NO_CONTENT),
@@ -581,11 +536,7 @@ async fn trigger_alert(
     _user: AuthUser,
     Json(req): Json<TriggerAlertRequest>,
 ) -> Result<(StatusCode, Json<AlertingResponse<Alert>>), StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    let service = state.alerting_service();
 
     match service
         .trigger_manual_alert(req.rule_id, &req.title, &req.message, req.context)
@@ -604,11 +555,7 @@ async fn evaluate_rules(
     State(state): State<AppState>,
     _user: AuthUser,
 ) -> Result<Json<AlertingResponse<EvaluateResponse>>, StatusCode> {
-    let service = AlertingService::new(
-        state.db.clone(),
-        state.puppetdb.clone(),
-        Some(state.notification_service.clone()),
-    );
+    let service = state.alerting_service();
 
     match service.evaluate_rules().await {
         Ok(alerts) => Ok(Json(AlertingResponse {
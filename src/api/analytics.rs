@@ -1,26 +1,48 @@
 //! Analytics and Reporting API endpoints
 
 use axum::{
+    body::Body,
     extract::{Path, Query, State},
-    routing::{get, post},
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+    routing::{delete, get, post},
     Json, Router,
 };
 use serde::Deserialize;
 use uuid::Uuid;
 
+use crate::api::smart_lists::can_see_smart_list;
 use crate::db::repository::{
-    ComplianceBaselineRepository, DriftBaselineRepository, ReportExecutionRepository,
-    ReportScheduleRepository, ReportTemplateRepository, SavedReportRepository,
+    ComplianceBaselineRepository, ComplianceRulePackRepository, DriftBaselineRepository,
+    GroupRepository, ReportExecutionRepository, ReportScheduleRepository, ReportTemplateRepository,
+    SavedReportRepository,
+};
+use crate::db::{
+    FleetMetricsRepository, FleetMetricsSnapshot, ReportEmailTemplateRepository,
+    SmartListRepository,
 };
 use crate::middleware::auth::AuthUser;
 use crate::models::{
-    ComplianceBaseline, CreateComplianceBaselineRequest, CreateDriftBaselineRequest,
-    CreateSavedReportRequest, CreateScheduleRequest, DriftBaseline, ExecuteReportRequest,
-    OutputFormat, ReportExecution, ReportQueryConfig, ReportResult, ReportSchedule, ReportTemplate,
-    ReportType, SavedReport, UpdateComplianceBaselineRequest, UpdateDriftBaselineRequest,
-    UpdateSavedReportRequest, UpdateScheduleRequest,
+    Action, ApplyRulePackRequest, CompileErrorAnalytics, ComplianceBaseline, ComplianceRulePack,
+    CorrectiveChangeAnalytics, CreateComplianceBaselineRequest,
+    CreateDriftBaselineFromSnapshotRequest, CreateDriftBaselineRequest, CreateSavedReportRequest,
+    CreateScheduleRequest, DeliveryTarget, DriftBaseline, DriftSnapshotPreview,
+    DriftSnapshotRequest, DriftSnapshotSource, ExecuteReportRequest, ImportRulePackRequest,
+    OutputFormat, QuotaResource, ReportEmailTemplate, ReportExecution, ReportQueryConfig,
+    ReportResult, ReportSchedule, ReportTemplate, ReportType, Resource, SavedReport,
+    UpdateComplianceBaselineRequest, UpdateDriftBaselineRequest, UpdateSavedReportRequest,
+    UpdateScheduleRequest, UpsertReportEmailTemplateRequest,
+};
+use crate::services::compile_errors::compile_error_analytics;
+use crate::services::drift_snapshot::capture_snapshot;
+use crate::services::fleet_metrics_scheduler::format_hour;
+use crate::services::quotas::check_quota;
+use crate::services::report_delivery::validate_target;
+use crate::services::report_email::{
+    validate_templates, DEFAULT_HTML_TEMPLATE, DEFAULT_SUBJECT_TEMPLATE, DEFAULT_TEXT_TEMPLATE,
 };
-use crate::services::ReportingService;
+use crate::services::rule_packs::{parse_rule_pack, rule_pack_checksum};
+use crate::services::{ReportOutputStore, ReportingService};
 use crate::utils::error::{AppError, AppResult};
 use crate::AppState;
 
@@ -54,6 +76,13 @@ pub fn routes() -> Router<AppState> {
                 .put(update_schedule)
                 .delete(delete_schedule),
         )
+        // Scheduled report email templates
+        .route(
+            "/email-templates",
+            get(list_email_templates).put(upsert_email_template),
+        )
+        .route("/email-templates/defaults", get(get_default_email_template))
+        .route("/email-templates/{id}", delete(delete_email_template))
         // Generate reports on-demand (without saving)
         .route("/generate", post(generate_report))
         .route("/generate/{report_type}", post(generate_report_by_type))
@@ -68,23 +97,83 @@ pub fn routes() -> Router<AppState> {
                 .put(update_compliance_baseline)
                 .delete(delete_compliance_baseline),
         )
+        // Compliance Rule Packs
+        .route(
+            "/compliance-rule-packs",
+            get(list_rule_packs).post(import_rule_pack),
+        )
+        .route(
+            "/compliance-rule-packs/{id}",
+            get(get_rule_pack).delete(delete_rule_pack),
+        )
+        .route("/compliance-rule-packs/{id}/apply", post(apply_rule_pack))
         // Drift Baselines
         .route(
             "/drift-baselines",
             get(list_drift_baselines).post(create_drift_baseline),
         )
+        .route(
+            "/drift-baselines/snapshot",
+            post(create_drift_baseline_from_snapshot),
+        )
+        .route(
+            "/drift-baselines/snapshot/preview",
+            post(preview_drift_snapshot),
+        )
         .route(
             "/drift-baselines/{id}",
             get(get_drift_baseline)
                 .put(update_drift_baseline)
                 .delete(delete_drift_baseline),
         )
+        // Dashboard widgets
+        .route("/corrective-changes", get(get_corrective_changes))
+        .route("/compile-errors", get(get_compile_errors))
+        // Fleet trends
+        .route("/fleet-metrics", get(get_fleet_metrics))
         // Export
         .route("/executions/{id}/export", get(export_execution))
+        .route("/executions/{id}/output", get(download_execution_output))
 }
 
 // ==================== Query Parameters ====================
 
+#[derive(Debug, Deserialize, Default)]
+struct OrgQuery {
+    organization_id: Option<Uuid>,
+}
+
+fn resolve_org(auth_user: &AuthUser, requested: Option<Uuid>) -> Result<Uuid, AppError> {
+    match requested {
+        Some(_) if !auth_user.is_super_admin() => Err(AppError::forbidden(
+            "organization_id can only be specified by super_admin",
+        )),
+        Some(org_id) => Ok(org_id),
+        None => Ok(auth_user.organization_id),
+    }
+}
+
+/// Check that the user may perform an action on reports and baselines
+async fn check_reports_permission(
+    state: &AppState,
+    auth_user: &AuthUser,
+    action: Action,
+) -> Result<(), AppError> {
+    let check = state
+        .rbac_db
+        .check_permission(&auth_user.user_id(), Resource::Reports, action, None, None)
+        .await
+        .map_err(|e| AppError::internal(format!("Permission check failed: {}", e)))?;
+
+    if check.allowed {
+        Ok(())
+    } else {
+        Err(AppError::forbidden(&check.reason.unwrap_or_else(|| {
+            "No matching permission found".to_string()
+        })))
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ListReportsQuery {
     pub report_type: Option<String>,
@@ -106,16 +195,18 @@ pub struct ExportQuery {
 async fn list_saved_reports(
     State(state): State<AppState>,
     Query(query): Query<ListReportsQuery>,
+    Query(org): Query<OrgQuery>,
     auth_user: AuthUser,
 ) -> AppResult<Json<Vec<SavedReport>>> {
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
     let repo = SavedReportRepository::new(&state.db);
 
     let reports = if let Some(type_str) = query.report_type {
         let report_type = ReportType::from_str(&type_str)
             .ok_or_else(|| AppError::bad_request("Invalid report type"))?;
-        repo.get_by_type(report_type).await?
+        repo.get_by_type(org_id, report_type).await?
     } else {
-        repo.get_by_user(auth_user.user_id()).await?
+        repo.get_by_user(org_id, auth_user.user_id()).await?
     };
 
     Ok(Json(reports))
@@ -125,10 +216,13 @@ async fn list_saved_reports(
 async fn get_saved_report(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
 ) -> AppResult<Json<SavedReport>> {
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
     let repo = SavedReportRepository::new(&state.db);
     let report = repo
-        .get_by_id(id)
+        .get_by_id(org_id, id)
         .await?
         .ok_or_else(|| AppError::not_found("Saved report not found"))?;
 
@@ -138,11 +232,17 @@ async fn get_saved_report(
 /// Create a new saved report
 async fn create_saved_report(
     State(state): State<AppState>,
+    Query(org): Query<OrgQuery>,
     auth_user: AuthUser,
     Json(req): Json<CreateSavedReportRequest>,
 ) -> AppResult<Json<SavedReport>> {
+    check_reports_permission(&state, &auth_user, Action::Create).await?;
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
+    validate_baseline_selection(&state, org_id, &req.query_config).await?;
+    validate_smart_list_selection(&state, &auth_user, org_id, &req.query_config).await?;
+    check_quota(&state.db, org_id, QuotaResource::SavedReports).await?;
     let repo = SavedReportRepository::new(&state.db);
-    let report = repo.create(&req, auth_user.user_id()).await?;
+    let report = repo.create(org_id, &req, auth_user.user_id()).await?;
     Ok(Json(report))
 }
 
@@ -150,23 +250,37 @@ async fn create_saved_report(
 async fn update_saved_report(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
     Json(req): Json<UpdateSavedReportRequest>,
 ) -> AppResult<Json<SavedReport>> {
+    check_reports_permission(&state, &auth_user, Action::Update).await?;
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
+    if let Some(config) = &req.query_config {
+        validate_baseline_selection(&state, org_id, config).await?;
+        validate_smart_list_selection(&state, &auth_user, org_id, config).await?;
+    }
     let repo = SavedReportRepository::new(&state.db);
     let report = repo
-        .update(id, &req)
+        .update(org_id, id, &req)
         .await?
         .ok_or_else(|| AppError::not_found("Saved report not found"))?;
     Ok(Json(report))
 }
 
 /// Delete a saved report
+///
+/// The report moves to the recycle bin.
 async fn delete_saved_report(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
 ) -> AppResult<Json<serde_json::Value>> {
+    check_reports_permission(&state, &auth_user, Action::Delete).await?;
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
     let repo = SavedReportRepository::new(&state.db);
-    let deleted = repo.delete(id).await?;
+    let deleted = repo.delete(org_id, id).await?;
 
     if deleted {
         Ok(Json(
@@ -181,18 +295,25 @@ async fn delete_saved_report(
 async fn execute_saved_report(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Query(org): Query<OrgQuery>,
     auth_user: AuthUser,
     Json(req): Json<ExecuteReportRequest>,
 ) -> AppResult<Json<ReportExecution>> {
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
     let repo = SavedReportRepository::new(&state.db);
     let report = repo
-        .get_by_id(id)
+        .get_by_id(org_id, id)
         .await?
         .ok_or_else(|| AppError::not_found("Saved report not found"))?;
+    if let Some(config) = &req.query_config_override {
+        validate_smart_list_selection(&state, &auth_user, org_id, config).await?;
+    }
 
-    let service = ReportingService::new(state.db.clone(), state.puppetdb.clone());
+    let puppetdb = state.puppetdb_for(org_id).await;
+    let service = ReportingService::new(state.db.clone(), puppetdb)
+        .with_output_store(ReportOutputStore::new(&state.config.reporting));
     let execution = service
-        .execute_report(&report, &req, Some(auth_user.user_id()))
+        .execute_report(&report, &req, Some(auth_user.user_id()), None)
         .await?;
 
     Ok(Json(execution))
@@ -203,9 +324,12 @@ async fn list_report_executions(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
     Query(query): Query<ExecutionsQuery>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
 ) -> AppResult<Json<Vec<ReportExecution>>> {
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
     let repo = ReportExecutionRepository::new(&state.db);
-    let executions = repo.get_by_report(id, query.limit).await?;
+    let executions = repo.get_by_report(org_id, id, query.limit).await?;
     Ok(Json(executions))
 }
 
@@ -245,65 +369,145 @@ async fn get_report_template(
 
 // ==================== Schedules ====================
 
+/// Hide delivery target credentials from API responses
+fn redact_schedule(mut schedule: ReportSchedule) -> ReportSchedule {
+    schedule.delivery_targets = schedule
+        .delivery_targets
+        .iter()
+        .map(DeliveryTarget::redacted)
+        .collect();
+    schedule
+}
+
+fn validate_delivery_targets(targets: &[DeliveryTarget]) -> AppResult<()> {
+    for target in targets {
+        validate_target(target).map_err(AppError::validation)?;
+        // A placeholder left after restoring stored credentials has no
+        // stored target to come from
+        if target.has_redacted_secret() {
+            return Err(AppError::validation(format!(
+                "Credentials of {} delivery target {} must be provided",
+                target.type_name(),
+                target.describe()
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn validate_email_recipients(recipients: Option<&Vec<String>>) -> AppResult<()> {
+    for recipient in recipients.into_iter().flatten() {
+        if recipient
+            .trim()
+            .parse::<lettre::message::Mailbox>()
+            .is_err()
+        {
+            return Err(AppError::validation(format!(
+                "Invalid email recipient: {}",
+                recipient
+            )));
+        }
+    }
+    Ok(())
+}
+
 /// List all schedules
-async fn list_schedules(State(state): State<AppState>) -> AppResult<Json<Vec<ReportSchedule>>> {
-    let repo = ReportScheduleRepository::new(&state.db);
-    let schedules = repo.get_all().await?;
-    Ok(Json(schedules))
+async fn list_schedules(
+    State(state): State<AppState>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
+) -> AppResult<Json<Vec<ReportSchedule>>> {
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
+    let repo = ReportScheduleRepository::new(&state.db, state.settings_cipher.as_ref());
+    let schedules = repo.get_all(org_id).await?;
+    Ok(Json(schedules.into_iter().map(redact_schedule).collect()))
 }
 
 /// Get a schedule by ID
 async fn get_schedule(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
 ) -> AppResult<Json<ReportSchedule>> {
-    let repo = ReportScheduleRepository::new(&state.db);
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
+    let repo = ReportScheduleRepository::new(&state.db, state.settings_cipher.as_ref());
     let schedule = repo
-        .get_by_id(id)
+        .get_by_id(org_id, id)
         .await?
         .ok_or_else(|| AppError::not_found("Schedule not found"))?;
 
-    Ok(Json(schedule))
+    Ok(Json(redact_schedule(schedule)))
 }
 
 /// Create a new schedule
 async fn create_schedule(
     State(state): State<AppState>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
     Json(req): Json<CreateScheduleRequest>,
 ) -> AppResult<Json<ReportSchedule>> {
-    // Validate that the report exists
+    check_reports_permission(&state, &auth_user, Action::Create).await?;
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
+
+    // Validate that the report exists in the organization
     let report_repo = SavedReportRepository::new(&state.db);
     report_repo
-        .get_by_id(req.report_id)
+        .get_by_id(org_id, req.report_id)
         .await?
         .ok_or_else(|| AppError::bad_request("Report not found"))?;
+    validate_delivery_targets(&req.delivery_targets)?;
+    validate_email_recipients(req.email_recipients.as_ref())?;
 
-    let repo = ReportScheduleRepository::new(&state.db);
-    let schedule = repo.create(&req).await?;
-    Ok(Json(schedule))
+    let repo = ReportScheduleRepository::new(&state.db, state.settings_cipher.as_ref());
+    let schedule = repo.create(org_id, &req).await?;
+    Ok(Json(redact_schedule(schedule)))
 }
 
 /// Update a schedule
 async fn update_schedule(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-    Json(req): Json<UpdateScheduleRequest>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
+    Json(mut req): Json<UpdateScheduleRequest>,
 ) -> AppResult<Json<ReportSchedule>> {
-    let repo = ReportScheduleRepository::new(&state.db);
+    check_reports_permission(&state, &auth_user, Action::Update).await?;
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
+    let repo = ReportScheduleRepository::new(&state.db, state.settings_cipher.as_ref());
+
+    if let Some(targets) = req.delivery_targets.as_mut() {
+        // Clients send back the redacted targets they were given; keep the
+        // stored credentials of the target at the same position
+        let existing = repo
+            .get_by_id(org_id, id)
+            .await?
+            .ok_or_else(|| AppError::not_found("Schedule not found"))?;
+        for (target, stored) in targets.iter_mut().zip(&existing.delivery_targets) {
+            target.restore_secrets(stored);
+        }
+        validate_delivery_targets(targets)?;
+    }
+    validate_email_recipients(req.email_recipients.as_ref())?;
+
     let schedule = repo
-        .update(id, &req)
+        .update(org_id, id, &req)
         .await?
         .ok_or_else(|| AppError::not_found("Schedule not found"))?;
-    Ok(Json(schedule))
+    Ok(Json(redact_schedule(schedule)))
 }
 
 /// Delete a schedule
 async fn delete_schedule(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
 ) -> AppResult<Json<serde_json::Value>> {
-    let repo = ReportScheduleRepository::new(&state.db);
-    let deleted = repo.delete(id).await?;
+    check_reports_permission(&state, &auth_user, Action::Delete).await?;
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
+    let repo = ReportScheduleRepository::new(&state.db, state.settings_cipher.as_ref());
+    let deleted = repo.delete(org_id, id).await?;
 
     if deleted {
         Ok(Json(
@@ -314,6 +518,64 @@ async fn delete_schedule(
     }
 }
 
+// ==================== Email Templates ====================
+
+/// List the email templates of scheduled reports
+async fn list_email_templates(
+    State(state): State<AppState>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
+) -> AppResult<Json<Vec<ReportEmailTemplate>>> {
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
+    let repo = ReportEmailTemplateRepository::new(&state.db);
+    Ok(Json(repo.list(org_id).await?))
+}
+
+/// Get the built-in email templates, used when none is stored
+async fn get_default_email_template(_auth_user: AuthUser) -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "subject_template": DEFAULT_SUBJECT_TEMPLATE,
+        "html_template": DEFAULT_HTML_TEMPLATE,
+        "text_template": DEFAULT_TEXT_TEMPLATE,
+    }))
+}
+
+/// Create or replace the email template of a report type
+async fn upsert_email_template(
+    State(state): State<AppState>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
+    Json(req): Json<UpsertReportEmailTemplateRequest>,
+) -> AppResult<Json<ReportEmailTemplate>> {
+    check_reports_permission(&state, &auth_user, Action::Update).await?;
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
+    validate_templates(&req).map_err(|e| AppError::validation(format!("{:#}", e)))?;
+
+    let repo = ReportEmailTemplateRepository::new(&state.db);
+    let template = repo.upsert(org_id, auth_user.user_id(), &req).await?;
+    Ok(Json(template))
+}
+
+/// Delete an email template, falling back to the built-in one
+async fn delete_email_template(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
+) -> AppResult<Json<serde_json::Value>> {
+    check_reports_permission(&state, &auth_user, Action::Delete).await?;
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
+    let repo = ReportEmailTemplateRepository::new(&state.db);
+
+    if repo.delete(org_id, id).await? {
+        Ok(Json(
+            serde_json::json!({"message": "Email template deleted successfully"}),
+        ))
+    } else {
+        Err(AppError::not_found("Email template not found"))
+    }
+}
+
 // ==================== Generate Reports On-Demand ====================
 
 /// Request body for on-demand report generation
@@ -331,11 +593,16 @@ pub struct GenerateReportRequest {
 /// Generate a report on-demand (without saving)
 async fn generate_report(
     State(state): State<AppState>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
     Json(req): Json<GenerateReportRequest>,
 ) -> AppResult<Json<ReportResult>> {
-    let service = ReportingService::new(state.db.clone(), state.puppetdb.clone());
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
+    validate_smart_list_selection(&state, &auth_user, org_id, &req.config).await?;
+    let puppetdb = state.puppetdb_for(org_id).await;
+    let service = ReportingService::new(state.db.clone(), puppetdb);
     let (result, _) = service
-        .generate_report(req.report_type, &req.config)
+        .generate_report(org_id, auth_user.user_id(), req.report_type, &req.config)
         .await?;
     Ok(Json(result))
 }
@@ -344,24 +611,198 @@ async fn generate_report(
 async fn generate_report_by_type(
     State(state): State<AppState>,
     Path(report_type): Path<String>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
     Json(config): Json<ReportQueryConfig>,
 ) -> AppResult<Json<ReportResult>> {
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
     let report_type = ReportType::from_str(&report_type)
         .ok_or_else(|| AppError::bad_request("Invalid report type"))?;
+    validate_smart_list_selection(&state, &auth_user, org_id, &config).await?;
 
-    let service = ReportingService::new(state.db.clone(), state.puppetdb.clone());
-    let (result, _) = service.generate_report(report_type, &config).await?;
+    let puppetdb = state.puppetdb_for(org_id).await;
+    let service = ReportingService::new(state.db.clone(), puppetdb);
+    let (result, _) = service
+        .generate_report(org_id, auth_user.user_id(), report_type, &config)
+        .await?;
     Ok(Json(result))
 }
 
+// ==================== Dashboard Widgets ====================
+
+#[derive(Debug, Deserialize)]
+pub struct CorrectiveChangesQuery {
+    /// Number of UTC days back from today. Defaults to 7, capped at 31.
+    pub days: Option<u32>,
+    /// Maximum number of nodes returned. Defaults to 20, capped at 500.
+    pub limit: Option<usize>,
+}
+
+/// Corrective vs intentional changes for the dashboard widget
+///
+/// GET /api/v1/analytics/corrective-changes
+async fn get_corrective_changes(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<CorrectiveChangesQuery>,
+) -> AppResult<Json<CorrectiveChangeAnalytics>> {
+    let days = query.days.unwrap_or(7).clamp(1, 31);
+    let limit = query.limit.unwrap_or(20).min(500);
+
+    let puppetdb = state.puppetdb_for(auth_user.organization_id).await;
+    let service = ReportingService::new(state.db.clone(), puppetdb);
+    let analytics = service.corrective_change_analytics(days, limit).await?;
+    Ok(Json(analytics))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CompileErrorsQuery {
+    /// Number of days back from now. Defaults to 7, capped at 31.
+    pub days: Option<u32>,
+    /// Maximum number of error signatures returned. Defaults to 20, capped
+    /// at 500.
+    pub limit: Option<usize>,
+}
+
+/// Catalog compilation errors grouped by signature, most widespread first
+///
+/// GET /api/v1/analytics/compile-errors
+async fn get_compile_errors(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<CompileErrorsQuery>,
+) -> AppResult<Json<CompileErrorAnalytics>> {
+    let days = query.days.unwrap_or(7).clamp(1, 31);
+    let limit = query.limit.unwrap_or(20).min(500);
+
+    let puppetdb = state
+        .puppetdb_for(auth_user.organization_id)
+        .await
+        .ok_or_else(|| AppError::ServiceUnavailable("PuppetDB is not configured".to_string()))?;
+    let analytics = compile_error_analytics(&puppetdb, days, limit)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to analyze compile errors: {}", e)))?;
+    Ok(Json(analytics))
+}
+
+// ==================== Fleet Trends ====================
+
+#[derive(Debug, Deserialize)]
+pub struct FleetMetricsQuery {
+    /// Number of days back from now. Defaults to 30, capped at 90.
+    pub days: Option<u32>,
+    /// `hour` for the hourly snapshots, `day` (default) for daily rollups
+    pub resolution: Option<String>,
+}
+
+/// Fleet metrics over time, from the hourly snapshots
+///
+/// GET /api/v1/analytics/fleet-metrics
+///
+/// Buckets without a snapshot (the server was down or PuppetDB unreachable)
+/// are left out rather than reported as zero nodes.
+async fn get_fleet_metrics(
+    State(state): State<AppState>,
+    _auth_user: AuthUser,
+    Query(query): Query<FleetMetricsQuery>,
+) -> AppResult<Json<Vec<FleetMetricsSnapshot>>> {
+    let days = query.days.unwrap_or(30).clamp(1, 90);
+    let now = chrono::Utc::now();
+    let end = format_hour(now);
+
+    let repo = FleetMetricsRepository::new(state.db.clone());
+    let snapshots = match query.resolution.as_deref().unwrap_or("day") {
+        "hour" => {
+            let start = format_hour(now - chrono::Duration::days(days as i64));
+            repo.range_hourly(&start, &end).await
+        }
+        "day" => {
+            // Whole UTC days, today included
+            let first_day = now.date_naive() - chrono::Duration::days(days as i64 - 1);
+            let start = format!("{}T00:00:00Z", first_day);
+            repo.range_daily(&start, &end).await
+        }
+        other => {
+            return Err(AppError::bad_request(format!(
+                "Invalid resolution '{}': expected 'hour' or 'day'",
+                other
+            )))
+        }
+    }
+    .map_err(|e| AppError::internal(format!("Failed to load fleet metrics: {}", e)))?;
+
+    Ok(Json(snapshots))
+}
+
 // ==================== Compliance Baselines ====================
 
+/// Check that the compliance baselines selected by a report exist in the
+/// report's organization
+async fn validate_baseline_selection(
+    state: &AppState,
+    org_id: Uuid,
+    config: &ReportQueryConfig,
+) -> AppResult<()> {
+    let repo = ComplianceBaselineRepository::new(&state.db);
+    for id in config.compliance_baseline_ids.iter().flatten() {
+        if repo.get_by_id(org_id, *id).await?.is_none() {
+            return Err(AppError::validation(format!(
+                "Compliance baseline {} not found",
+                id
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Check that the smart list a report is scoped to exists in the report's
+/// organization and is visible to the caller
+async fn validate_smart_list_selection(
+    state: &AppState,
+    auth_user: &AuthUser,
+    org_id: Uuid,
+    config: &ReportQueryConfig,
+) -> AppResult<()> {
+    let Some(id) = config.smart_list_id else {
+        return Ok(());
+    };
+    let list = SmartListRepository::new(&state.db)
+        .get_by_id(org_id, id)
+        .await?;
+    if !list.is_some_and(|list| can_see_smart_list(auth_user, &list)) {
+        return Err(AppError::validation(format!("Smart list {} not found", id)));
+    }
+    Ok(())
+}
+
+/// Check that a baseline's node groups exist in the baseline's organization
+async fn validate_baseline_groups(
+    state: &AppState,
+    org_id: Uuid,
+    node_group_ids: &[Uuid],
+) -> AppResult<()> {
+    let repo = GroupRepository::new(&state.db);
+    for group_id in node_group_ids {
+        let group_org = repo.get_group_organization_id(*group_id).await?;
+        if group_org != Some(org_id) {
+            return Err(AppError::validation(format!(
+                "Node group {} not found",
+                group_id
+            )));
+        }
+    }
+    Ok(())
+}
+
 /// List all compliance baselines
 async fn list_compliance_baselines(
     State(state): State<AppState>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
 ) -> AppResult<Json<Vec<ComplianceBaseline>>> {
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
     let repo = ComplianceBaselineRepository::new(&state.db);
-    let baselines = repo.get_all().await?;
+    let baselines = repo.get_all(org_id).await?;
     Ok(Json(baselines))
 }
 
@@ -369,10 +810,13 @@ async fn list_compliance_baselines(
 async fn get_compliance_baseline(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
 ) -> AppResult<Json<ComplianceBaseline>> {
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
     let repo = ComplianceBaselineRepository::new(&state.db);
     let baseline = repo
-        .get_by_id(id)
+        .get_by_id(org_id, id)
         .await?
         .ok_or_else(|| AppError::not_found("Compliance baseline not found"))?;
 
@@ -382,11 +826,15 @@ async fn get_compliance_baseline(
 /// Create a new compliance baseline
 async fn create_compliance_baseline(
     State(state): State<AppState>,
+    Query(org): Query<OrgQuery>,
     auth_user: AuthUser,
     Json(req): Json<CreateComplianceBaselineRequest>,
 ) -> AppResult<Json<ComplianceBaseline>> {
+    check_reports_permission(&state, &auth_user, Action::Create).await?;
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
+    validate_baseline_groups(&state, org_id, &req.node_group_ids).await?;
     let repo = ComplianceBaselineRepository::new(&state.db);
-    let baseline = repo.create(&req, auth_user.user_id()).await?;
+    let baseline = repo.create(org_id, &req, auth_user.user_id()).await?;
     Ok(Json(baseline))
 }
 
@@ -394,11 +842,18 @@ async fn create_compliance_baseline(
 async fn update_compliance_baseline(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
     Json(req): Json<UpdateComplianceBaselineRequest>,
 ) -> AppResult<Json<ComplianceBaseline>> {
+    check_reports_permission(&state, &auth_user, Action::Update).await?;
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
+    if let Some(node_group_ids) = &req.node_group_ids {
+        validate_baseline_groups(&state, org_id, node_group_ids).await?;
+    }
     let repo = ComplianceBaselineRepository::new(&state.db);
     let baseline = repo
-        .update(id, &req)
+        .update(org_id, id, &req)
         .await?
         .ok_or_else(|| AppError::not_found("Compliance baseline not found"))?;
     Ok(Json(baseline))
@@ -408,9 +863,13 @@ async fn update_compliance_baseline(
 async fn delete_compliance_baseline(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
 ) -> AppResult<Json<serde_json::Value>> {
+    check_reports_permission(&state, &auth_user, Action::Delete).await?;
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
     let repo = ComplianceBaselineRepository::new(&state.db);
-    let deleted = repo.delete(id).await?;
+    let deleted = repo.delete(org_id, id).await?;
 
     if deleted {
         Ok(Json(
@@ -421,14 +880,161 @@ async fn delete_compliance_baseline(
     }
 }
 
+// ==================== Compliance Rule Packs ====================
+
+/// List all imported rule pack versions
+async fn list_rule_packs(
+    State(state): State<AppState>,
+) -> AppResult<Json<Vec<ComplianceRulePack>>> {
+    let repo = ComplianceRulePackRepository::new(&state.db);
+    let packs = repo.get_all().await?;
+    Ok(Json(packs))
+}
+
+/// Get a rule pack version by ID
+async fn get_rule_pack(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> AppResult<Json<ComplianceRulePack>> {
+    let repo = ComplianceRulePackRepository::new(&state.db);
+    let pack = repo
+        .get_by_id(id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Rule pack not found"))?;
+
+    Ok(Json(pack))
+}
+
+/// Import a rule pack version from a YAML or JSON document
+async fn import_rule_pack(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(req): Json<ImportRulePackRequest>,
+) -> AppResult<Json<ComplianceRulePack>> {
+    check_reports_permission(&state, &auth_user, Action::Create).await?;
+    let doc = parse_rule_pack(&req.content, req.format)?;
+
+    let repo = ComplianceRulePackRepository::new(&state.db);
+    if repo.exists(&doc.id, &doc.version).await? {
+        return Err(AppError::conflict(format!(
+            "Rule pack {} version {} is already imported",
+            doc.id, doc.version
+        )));
+    }
+
+    let pack = repo
+        .create(&doc, &rule_pack_checksum(&req.content), auth_user.user_id())
+        .await?;
+    Ok(Json(pack))
+}
+
+/// Load a rule pack version into a new or existing compliance baseline
+///
+/// Rule packs are shared by all organizations; the baseline belongs to the
+/// caller's. An existing baseline can only move between versions of the pack
+/// it was loaded from.
+async fn apply_rule_pack(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
+    Json(req): Json<ApplyRulePackRequest>,
+) -> AppResult<Json<ComplianceBaseline>> {
+    let action = if req.baseline_id.is_some() {
+        Action::Update
+    } else {
+        Action::Create
+    };
+    check_reports_permission(&state, &auth_user, action).await?;
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
+
+    let pack_repo = ComplianceRulePackRepository::new(&state.db);
+    let pack = pack_repo
+        .get_by_id(id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Rule pack not found"))?;
+    if let Some(node_group_ids) = &req.node_group_ids {
+        validate_baseline_groups(&state, org_id, node_group_ids).await?;
+    }
+
+    let repo = ComplianceBaselineRepository::new(&state.db);
+    let baseline_id = match req.baseline_id {
+        Some(baseline_id) => {
+            let existing = repo
+                .get_by_id(org_id, baseline_id)
+                .await?
+                .ok_or_else(|| AppError::not_found("Compliance baseline not found"))?;
+            if let Some(current_pack_id) = existing.rule_pack_id {
+                let current = pack_repo.get_by_id(current_pack_id).await?;
+                if current.is_some_and(|current| current.pack_key != pack.pack_key) {
+                    return Err(AppError::validation(format!(
+                        "Baseline '{}' was loaded from a different rule pack",
+                        existing.name
+                    )));
+                }
+            }
+
+            let update = UpdateComplianceBaselineRequest {
+                name: req.name,
+                description: None,
+                rules: None,
+                severity_level: req.severity_level,
+                node_group_ids: req.node_group_ids,
+            };
+            repo.update(org_id, baseline_id, &update).await?;
+            baseline_id
+        }
+        None => {
+            let create = CreateComplianceBaselineRequest {
+                name: req.name.unwrap_or_else(|| pack.name.clone()),
+                description: pack.description.clone(),
+                rules: pack.rules.clone(),
+                severity_level: req.severity_level.unwrap_or_default(),
+                node_group_ids: req.node_group_ids.unwrap_or_default(),
+            };
+            repo.create(org_id, &create, auth_user.user_id()).await?.id
+        }
+    };
+
+    let baseline = repo
+        .apply_rule_pack(org_id, baseline_id, &pack)
+        .await?
+        .ok_or_else(|| AppError::not_found("Compliance baseline not found"))?;
+    Ok(Json(baseline))
+}
+
+/// Delete a rule pack version
+///
+/// Baselines loaded from it keep their rules.
+async fn delete_rule_pack(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    auth_user: AuthUser,
+) -> AppResult<Json<serde_json::Value>> {
+    check_reports_permission(&state, &auth_user, Action::Delete).await?;
+    let repo = ComplianceRulePackRepository::new(&state.db);
+    let deleted = repo.delete(id).await?;
+
+    if deleted {
+        Ok(Json(
+            serde_json::json!({"message": "Rule pack deleted successfully"}),
+        ))
+    } else {
+        Err(AppError::not_found("Rule pack not found"))
+    }
+}
+
 // ==================== Drift Baselines ====================
 
 /// List all drift baselines
 async fn list_drift_baselines(
     State(state): State<AppState>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
 ) -> AppResult<Json<Vec<DriftBaseline>>> {
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
     let repo = DriftBaselineRepository::new(&state.db);
-    let baselines = repo.get_all().await?;
+    let baselines = repo.get_all(org_id).await?;
     Ok(Json(baselines))
 }
 
@@ -436,10 +1042,13 @@ async fn list_drift_baselines(
 async fn get_drift_baseline(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
 ) -> AppResult<Json<DriftBaseline>> {
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
     let repo = DriftBaselineRepository::new(&state.db);
     let baseline = repo
-        .get_by_id(id)
+        .get_by_id(org_id, id)
         .await?
         .ok_or_else(|| AppError::not_found("Drift baseline not found"))?;
 
@@ -449,11 +1058,73 @@ async fn get_drift_baseline(
 /// Create a new drift baseline
 async fn create_drift_baseline(
     State(state): State<AppState>,
+    Query(org): Query<OrgQuery>,
     auth_user: AuthUser,
     Json(req): Json<CreateDriftBaselineRequest>,
 ) -> AppResult<Json<DriftBaseline>> {
+    check_reports_permission(&state, &auth_user, Action::Create).await?;
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
+    if let Some(group_id) = req.node_group_id {
+        validate_baseline_groups(&state, org_id, &[group_id]).await?;
+    }
     let repo = DriftBaselineRepository::new(&state.db);
-    let baseline = repo.create(&req, auth_user.user_id()).await?;
+    let baseline = repo.create(org_id, &req, auth_user.user_id()).await?;
+    Ok(Json(baseline))
+}
+
+/// Capture the facts of a node or group of an organization for a drift baseline
+async fn snapshot_facts(
+    state: &AppState,
+    org_id: Uuid,
+    req: &DriftSnapshotRequest,
+) -> AppResult<DriftSnapshotPreview> {
+    let puppetdb = state
+        .puppetdb_for(org_id)
+        .await
+        .ok_or_else(|| AppError::ServiceUnavailable("PuppetDB is not configured".to_string()))?;
+    capture_snapshot(&state.db, &puppetdb, org_id, req).await
+}
+
+/// Preview the facts a drift baseline snapshot would capture
+async fn preview_drift_snapshot(
+    State(state): State<AppState>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
+    Json(req): Json<DriftSnapshotRequest>,
+) -> AppResult<Json<DriftSnapshotPreview>> {
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
+    let preview = snapshot_facts(&state, org_id, &req).await?;
+    Ok(Json(preview))
+}
+
+/// Create a drift baseline from the current facts of a node or group
+async fn create_drift_baseline_from_snapshot(
+    State(state): State<AppState>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
+    Json(req): Json<CreateDriftBaselineFromSnapshotRequest>,
+) -> AppResult<Json<DriftBaseline>> {
+    check_reports_permission(&state, &auth_user, Action::Create).await?;
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
+    if req.name.trim().is_empty() {
+        return Err(AppError::validation("Baseline name is required"));
+    }
+
+    let preview = snapshot_facts(&state, org_id, &req.snapshot).await?;
+    let node_group_id = match req.snapshot.source {
+        DriftSnapshotSource::Group { group_id } => Some(group_id),
+        DriftSnapshotSource::Node { .. } => None,
+    };
+
+    let create = CreateDriftBaselineRequest {
+        name: req.name,
+        description: req.description,
+        node_group_id,
+        baseline_facts: preview.baseline_facts,
+        tolerance_config: req.tolerance_config,
+    };
+    let repo = DriftBaselineRepository::new(&state.db);
+    let baseline = repo.create(org_id, &create, auth_user.user_id()).await?;
     Ok(Json(baseline))
 }
 
@@ -461,11 +1132,18 @@ async fn create_drift_baseline(
 async fn update_drift_baseline(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
     Json(req): Json<UpdateDriftBaselineRequest>,
 ) -> AppResult<Json<DriftBaseline>> {
+    check_reports_permission(&state, &auth_user, Action::Update).await?;
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
+    if let Some(Some(group_id)) = req.node_group_id {
+        validate_baseline_groups(&state, org_id, &[group_id]).await?;
+    }
     let repo = DriftBaselineRepository::new(&state.db);
     let baseline = repo
-        .update(id, &req)
+        .update(org_id, id, &req)
         .await?
         .ok_or_else(|| AppError::not_found("Drift baseline not found"))?;
     Ok(Json(baseline))
@@ -475,9 +1153,13 @@ async fn update_drift_baseline(
 async fn delete_drift_baseline(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
 ) -> AppResult<Json<serde_json::Value>> {
+    check_reports_permission(&state, &auth_user, Action::Delete).await?;
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
     let repo = DriftBaselineRepository::new(&state.db);
-    let deleted = repo.delete(id).await?;
+    let deleted = repo.delete(org_id, id).await?;
 
     if deleted {
         Ok(Json(
@@ -495,15 +1177,22 @@ async fn export_execution(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
     Query(query): Query<ExportQuery>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
 ) -> AppResult<(axum::http::StatusCode, [(String, String); 2], Vec<u8>)> {
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
     let repo = ReportExecutionRepository::new(&state.db);
     let execution = repo
-        .get_by_id(id)
+        .get_by_id(org_id, id)
         .await?
         .ok_or_else(|| AppError::not_found("Execution not found"))?;
 
-    let output_data = execution
-        .output_data
+    let output_data = ReportOutputStore::load(&execution)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load output of execution {}: {:#}", id, e);
+            AppError::internal("Failed to load report output")
+        })?
         .ok_or_else(|| AppError::bad_request("Execution has no output data"))?;
 
     let format = query
@@ -515,7 +1204,8 @@ async fn export_execution(
     let result: ReportResult = serde_json::from_value(output_data)
         .map_err(|_| AppError::internal("Failed to parse stored report data"))?;
 
-    let service = ReportingService::new(state.db.clone(), state.puppetdb.clone());
+    let puppetdb = state.puppetdb_for(org_id).await;
+    let service = ReportingService::new(state.db.clone(), puppetdb);
     let data = service.export_report(&result, format)?;
 
     let content_type = format.content_type().to_string();
@@ -533,3 +1223,59 @@ async fn export_execution(
         data,
     ))
 }
+
+/// Download the raw JSON output of an execution
+///
+/// Outputs offloaded to a file are streamed from disk rather than loaded in
+/// memory.
+async fn download_execution_output(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(org): Query<OrgQuery>,
+    auth_user: AuthUser,
+) -> AppResult<Response> {
+    let org_id = resolve_org(&auth_user, org.organization_id)?;
+    let execution = ReportExecutionRepository::new(&state.db)
+        .get_by_id(org_id, id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Execution not found"))?;
+
+    let (body, length) = match (&execution.output_data, &execution.output_file_path) {
+        (Some(data), _) => {
+            let data = serde_json::to_vec(data)
+                .map_err(|e| AppError::internal(format!("Failed to serialize output: {}", e)))?;
+            let length = data.len() as u64;
+            (Body::from(data), length)
+        }
+        (None, Some(path)) => {
+            let file = tokio::fs::File::open(path).await.map_err(|e| {
+                tracing::error!("Failed to open output of execution {}: {}", id, e);
+                AppError::not_found("Execution output file not found")
+            })?;
+            let length = file
+                .metadata()
+                .await
+                .map_err(|e| AppError::internal(format!("Failed to read output file: {}", e)))?
+                .len();
+            (
+                Body::from_stream(tokio_util::io::ReaderStream::new(file)),
+                length,
+            )
+        }
+        (None, None) => return Err(AppError::bad_request("Execution has no output data")),
+    };
+
+    Ok((
+        StatusCode::OK,
+        [
+            (header::CONTENT_TYPE, "application/json".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"report-{}.json\"", id),
+            ),
+            (header::CONTENT_LENGTH, length.to_string()),
+        ],
+        body,
+    )
+        .into_response())
+}
@@ -3,7 +3,7 @@
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
-    routing::{delete, get},
+    routing::{delete, get, post},
     Json, Router,
 };
 use base64::Engine;
@@ -13,10 +13,10 @@ use sqlx::Row;
 use uuid::Uuid;
 
 use crate::{
-    db::{ApiKeyRepository, AuditRepository},
+    db::ApiKeyRepository,
     middleware::AuthUser,
-    models::{CreateApiKeyRequest, CreateApiKeyResponse},
-    services::AuthService,
+    models::{CreateApiKeyRequest, CreateApiKeyResponse, QuotaResource},
+    services::{api_key_policy::IpRule, quotas::check_quota, AuthService},
     utils::AppError,
     AppState,
 };
@@ -25,14 +25,25 @@ pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/", get(list_api_keys).post(create_api_key))
         .route("/{id}", delete(delete_api_key))
+        .route("/{id}/rotate", post(rotate_api_key))
 }
 
+/// Longest grace period for the previous secret of a rotated key (7 days)
+const MAX_ROTATION_GRACE_MINUTES: u64 = 7 * 24 * 60;
+
 #[derive(Debug, Deserialize, Default)]
 struct ApiKeyListQuery {
     organization_id: Option<Uuid>,
     user_id: Option<Uuid>,
 }
 
+#[derive(Debug, Deserialize, Default)]
+struct RotateApiKeyQuery {
+    /// How long the current secret keeps working; defaults to
+    /// `auth.api_key_rotation_grace_minutes`
+    grace_period_minutes: Option<u64>,
+}
+
 fn resolve_org(auth_user: &AuthUser, requested: Option<Uuid>) -> Result<Uuid, AppError> {
     match requested {
         Some(_) if !auth_user.is_super_admin() => Err(AppError::forbidden(
@@ -53,10 +64,12 @@ async fn list_api_keys(
     Query(query): Query<ApiKeyListQuery>,
 ) -> Result<Json<Vec<crate::models::ApiKey>>, AppError> {
     let org_id = resolve_org(&auth_user, query.organization_id)?;
+    // Keys are listed within the organization, so admins may look at the
+    // keys of its service accounts
     let user_id = match query.user_id {
-        Some(_) if !auth_user.is_super_admin() => {
+        Some(_) if !is_admin(&auth_user) => {
             return Err(AppError::forbidden(
-                "user_id can only be specified by super_admin",
+                "user_id can only be specified by admins",
             ));
         }
         Some(u) => u,
@@ -79,6 +92,8 @@ async fn create_api_key(
 ) -> Result<(StatusCode, Json<CreateApiKeyResponse>), AppError> {
     let auth_service = AuthService::new(state.db.clone());
 
+    // Roles of a service account the key is created for
+    let mut service_account_roles = None;
     let (org_id, user_id) = match (payload.organization_id, payload.user_id) {
         (Some(org_id), Some(user_id)) => {
             if !auth_user.is_super_admin() {
@@ -89,7 +104,7 @@ async fn create_api_key(
             (org_id, user_id)
         }
         (None, Some(user_id)) => {
-            if !auth_user.is_super_admin() {
+            if !is_admin(&auth_user) {
                 return Err(AppError::forbidden("user_id override requires super_admin"));
             }
             // Infer org from target user
@@ -101,6 +116,22 @@ async fn create_api_key(
                     AppError::internal("Failed to fetch user")
                 })?
                 .ok_or_else(|| AppError::not_found("User not found"))?;
+            // Service accounts have no login, so admins of their organization
+            // create their keys
+            if !auth_user.is_super_admin()
+                && !(user.is_service_account() && user.organization_id == auth_user.organization_id)
+            {
+                return Err(AppError::forbidden(
+                    "user_id override requires super_admin, except for service accounts of your organization",
+                ));
+            }
+            if user.is_service_account() {
+                let roles = state.rbac_db.get_user_roles(&user_id).await.map_err(|e| {
+                    tracing::error!("Failed to fetch service account roles: {}", e);
+                    AppError::internal("Failed to fetch user roles")
+                })?;
+                service_account_roles = Some(roles.into_iter().map(|r| r.id).collect::<Vec<_>>());
+            }
             (user.organization_id, user_id)
         }
         (Some(org_id), None) => {
@@ -114,8 +145,11 @@ async fn create_api_key(
         (None, None) => (auth_user.organization_id, auth_user.user_id()),
     };
 
-    // Role scoping: default to caller roles; if specified, must be a subset unless super_admin.
-    let requested_roles = payload.role_ids.clone();
+    check_quota(&state.db, org_id, QuotaResource::ApiKeys).await?;
+
+    // Role scoping: default to caller roles (or the service account's own
+    // roles); if specified, must be a subset unless super_admin.
+    let requested_roles = payload.role_ids.clone().or(service_account_roles);
     let role_ids = match requested_roles {
         Some(role_ids) if role_ids.is_empty() => {
             return Err(AppError::bad_request("role_ids cannot be empty"));
@@ -142,17 +176,39 @@ async fn create_api_key(
         }
     };
 
-    // Create API key: use an id in the plaintext key so auth can look it up efficiently.
-    let api_key_id = Uuid::new_v4();
-    let mut secret_bytes = [0u8; 32];
-    rand::rng().fill_bytes(&mut secret_bytes);
-    let secret = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(secret_bytes);
-    let key = format!("ovk_{}_{}", api_key_id, secret);
+    // Scopes narrow the key further; each must be granted by the key's roles.
+    for scope in payload.scopes.as_deref().unwrap_or_default() {
+        let granted = state
+            .rbac_db
+            .roles_grant_action(&role_ids, scope.resource, scope.action)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to check api key scope: {}", e);
+                AppError::internal("Failed to create api key")
+            })?;
+        if !granted {
+            return Err(AppError::forbidden(format!(
+                "Scope {}:{} is not granted by the key's roles",
+                scope.resource.as_str(),
+                scope.action.as_str()
+            )));
+        }
+    }
 
-    let key_hash = AuthService::hash_password(&secret).map_err(|e| {
-        tracing::error!("Failed to hash api key: {}", e);
-        AppError::internal("Failed to create api key")
-    })?;
+    if let Some(invalid) = payload
+        .allowed_ips
+        .iter()
+        .flatten()
+        .find(|ip| IpRule::parse(ip).is_none())
+    {
+        return Err(AppError::validation(format!(
+            "Invalid IP address or CIDR range: {}",
+            invalid
+        )));
+    }
+
+    let api_key_id = Uuid::new_v4();
+    let (key, key_hash) = generate_key(api_key_id)?;
 
     let repo = ApiKeyRepository::new(&state.db);
     let api_key = repo
@@ -164,7 +220,7 @@ async fn create_api_key(
         })?;
 
     // Audit
-    let audit_repo = AuditRepository::new(&state.db);
+    let audit_repo = state.audit_repository();
     let _ = audit_repo
         .insert(
             org_id,
@@ -176,6 +232,8 @@ async fn create_api_key(
                 "name": api_key.name,
                 "user_id": user_id,
                 "role_ids": role_ids,
+                "scopes": api_key.scopes,
+                "allowed_ips": api_key.allowed_ips,
                 "expires_at": payload.expires_at,
             })),
             None,
@@ -188,22 +246,39 @@ async fn create_api_key(
     ))
 }
 
-async fn delete_api_key(
-    State(state): State<AppState>,
-    auth_user: AuthUser,
-    Path(id): Path<String>,
-) -> Result<StatusCode, AppError> {
-    let api_key_id =
-        Uuid::parse_str(&id).map_err(|_| AppError::bad_request("Invalid API key ID"))?;
+/// Generate a plaintext key and the hash stored for it
+///
+/// The key embeds its id so auth can look it up efficiently.
+fn generate_key(api_key_id: Uuid) -> Result<(String, String), AppError> {
+    let mut secret_bytes = [0u8; 32];
+    rand::rng().fill_bytes(&mut secret_bytes);
+    let secret = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(secret_bytes);
+    let key = format!("ovk_{}_{}", api_key_id, secret);
+
+    let key_hash = AuthService::hash_password(&secret).map_err(|e| {
+        tracing::error!("Failed to hash api key: {}", e);
+        AppError::internal("Failed to generate api key")
+    })?;
 
-    // Look up key ownership/org.
+    Ok((key, key_hash))
+}
+
+/// Look up the organization and owner of a key the caller may manage
+///
+/// Owners manage their own keys; admins manage any key in their organization.
+async fn load_managed_key(
+    state: &AppState,
+    auth_user: &AuthUser,
+    api_key_id: Uuid,
+    action: &str,
+) -> Result<(Uuid, Uuid), AppError> {
     let row = sqlx::query("SELECT organization_id, user_id FROM api_keys WHERE id = ?")
         .bind(api_key_id.to_string())
         .fetch_optional(&state.db)
         .await
         .map_err(|e| {
             tracing::error!("Failed to fetch api key: {}", e);
-            AppError::internal("Failed to delete api key")
+            AppError::internal(format!("Failed to {} api key", action))
         })?
         .ok_or_else(|| AppError::not_found("API key not found"))?;
 
@@ -218,11 +293,97 @@ async fn delete_api_key(
         return Err(AppError::not_found("API key not found"));
     }
 
-    // Ownership/admin checks (admins can revoke within their org).
-    if owner_id != auth_user.user_id() && !is_admin(&auth_user) {
-        return Err(AppError::forbidden("Not allowed to revoke this API key"));
+    // Ownership/admin checks (admins can manage keys within their org).
+    if owner_id != auth_user.user_id() && !is_admin(auth_user) {
+        return Err(AppError::forbidden(format!(
+            "Not allowed to {} this API key",
+            action
+        )));
+    }
+
+    Ok((org_id, owner_id))
+}
+
+/// Issue a new secret for a key
+///
+/// The current secret keeps working for the grace period so clients can
+/// switch to the new one without downtime.
+async fn rotate_api_key(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<String>,
+    Query(query): Query<RotateApiKeyQuery>,
+) -> Result<Json<CreateApiKeyResponse>, AppError> {
+    let api_key_id =
+        Uuid::parse_str(&id).map_err(|_| AppError::bad_request("Invalid API key ID"))?;
+
+    let grace_minutes = query
+        .grace_period_minutes
+        .unwrap_or(state.config.auth.api_key_rotation_grace_minutes);
+    if grace_minutes > MAX_ROTATION_GRACE_MINUTES {
+        return Err(AppError::validation(format!(
+            "grace_period_minutes cannot exceed {}",
+            MAX_ROTATION_GRACE_MINUTES
+        )));
+    }
+
+    let (org_id, owner_id) = load_managed_key(&state, &auth_user, api_key_id, "rotate").await?;
+
+    let (key, key_hash) = generate_key(api_key_id)?;
+    let grace_until = (grace_minutes > 0)
+        .then(|| chrono::Utc::now() + chrono::Duration::minutes(grace_minutes as i64));
+
+    let repo = ApiKeyRepository::new(&state.db);
+    let rotated = repo
+        .rotate(org_id, api_key_id, &key_hash, grace_until)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to rotate api key: {}", e);
+            AppError::internal("Failed to rotate api key")
+        })?;
+    if !rotated {
+        return Err(AppError::not_found("API key not found"));
     }
 
+    let api_key = repo
+        .get_by_id(org_id, api_key_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to fetch rotated api key: {}", e);
+            AppError::internal("Failed to rotate api key")
+        })?
+        .ok_or_else(|| AppError::not_found("API key not found"))?;
+
+    let audit_repo = state.audit_repository();
+    let _ = audit_repo
+        .insert(
+            org_id,
+            Some(auth_user.user_id()),
+            "api_key.rotate",
+            "api_keys",
+            Some(&api_key_id.to_string()),
+            Some(&serde_json::json!({
+                "owner_id": owner_id,
+                "grace_period_minutes": grace_minutes,
+                "previous_key_expires_at": grace_until,
+            })),
+            None,
+        )
+        .await;
+
+    Ok(Json(CreateApiKeyResponse { api_key, key }))
+}
+
+async fn delete_api_key(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    let api_key_id =
+        Uuid::parse_str(&id).map_err(|_| AppError::bad_request("Invalid API key ID"))?;
+
+    let (org_id, owner_id) = load_managed_key(&state, &auth_user, api_key_id, "revoke").await?;
+
     let repo = ApiKeyRepository::new(&state.db);
     let deleted = repo.delete(org_id, api_key_id).await.map_err(|e| {
         tracing::error!("Failed to delete api key: {}", e);
@@ -233,7 +394,7 @@ async fn delete_api_key(
         return Err(AppError::not_found("API key not found"));
     }
 
-    let audit_repo = AuditRepository::new(&state.db);
+    let audit_repo = state.audit_repository();
     let _ = audit_repo
         .insert(
             org_id,
@@ -2,20 +2,43 @@
 
 use axum::{
     extract::{Query, State},
+    http::StatusCode,
     routing::get,
     Json, Router,
 };
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
 
 use crate::{
-    db::AuditRepository,
-    middleware::AuthUser,
-    models::{AuditLogEntry, AuditLogQuery},
+    middleware::{AuthUser, SessionClient},
+    models::{AuditLogEntry, AuditLogQuery, UserKind},
     utils::AppError,
     AppState,
 };
 
+/// Most entries a single export returns
+const MAX_EXPORT_ROWS: u32 = 100_000;
+
 pub fn routes() -> Router<AppState> {
-    Router::new().route("/", get(list_audit_logs))
+    Router::new()
+        .route("/", get(list_audit_logs))
+        .route("/export", get(export_audit_logs))
+}
+
+#[derive(Debug, Deserialize)]
+struct ExportQuery {
+    organization_id: Option<Uuid>,
+    user_id: Option<Uuid>,
+    impersonator_id: Option<Uuid>,
+    user_kind: Option<UserKind>,
+    resource_type: Option<String>,
+    action: Option<String>,
+    request_id: Option<String>,
+    from: Option<DateTime<Utc>>,
+    to: Option<DateTime<Utc>>,
+    /// Output format: csv (default) or jsonl
+    format: Option<String>,
 }
 
 fn can_view_audit_logs(auth_user: &AuthUser) -> bool {
@@ -24,6 +47,16 @@ fn can_view_audit_logs(auth_user: &AuthUser) -> bool {
         || auth_user.roles.iter().any(|r| r == "auditor")
 }
 
+fn resolve_org(auth_user: &AuthUser, requested: Option<Uuid>) -> Result<Uuid, AppError> {
+    match requested {
+        Some(_) if !auth_user.is_super_admin() => Err(AppError::forbidden(
+            "organization_id can only be specified by super_admin",
+        )),
+        Some(org_id) => Ok(org_id),
+        None => Ok(auth_user.organization_id),
+    }
+}
+
 async fn list_audit_logs(
     State(state): State<AppState>,
     auth_user: AuthUser,
@@ -33,17 +66,9 @@ async fn list_audit_logs(
         return Err(AppError::forbidden("Not allowed to view audit logs"));
     }
 
-    let org_id = match query.organization_id {
-        Some(_) if !auth_user.is_super_admin() => {
-            return Err(AppError::forbidden(
-                "organization_id can only be specified by super_admin",
-            ));
-        }
-        Some(org_id) => org_id,
-        None => auth_user.organization_id,
-    };
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
 
-    let repo = AuditRepository::new(&state.db);
+    let repo = state.audit_repository();
     let logs = repo.list(org_id, &query).await.map_err(|e| {
         tracing::error!("Failed to list audit logs: {}", e);
         AppError::internal("Failed to list audit logs")
@@ -51,3 +76,157 @@ async fn list_audit_logs(
 
     Ok(Json(logs))
 }
+
+async fn export_audit_logs(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    client: SessionClient,
+    Query(query): Query<ExportQuery>,
+) -> Result<(StatusCode, [(String, String); 2], Vec<u8>), AppError> {
+    if !can_view_audit_logs(&auth_user) {
+        return Err(AppError::forbidden("Not allowed to view audit logs"));
+    }
+
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let format = query.format.as_deref().unwrap_or("csv");
+    if format != "csv" && format != "jsonl" {
+        return Err(AppError::bad_request(format!(
+            "Unsupported export format '{}'; use csv or jsonl",
+            format
+        )));
+    }
+
+    let filters = AuditLogQuery {
+        organization_id: Some(org_id),
+        user_id: query.user_id,
+        impersonator_id: query.impersonator_id,
+        user_kind: query.user_kind,
+        resource_type: query.resource_type.clone(),
+        action: query.action.clone(),
+        request_id: query.request_id.clone(),
+        from: query.from,
+        to: query.to,
+        limit: None,
+        offset: None,
+    };
+
+    let repo = state.audit_repository();
+    let entries = repo
+        .export(org_id, &filters, MAX_EXPORT_ROWS)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to export audit logs: {}", e);
+            AppError::internal("Failed to export audit logs")
+        })?;
+
+    let (content_type, data) = if format == "csv" {
+        ("text/csv", entries_to_csv(&entries).into_bytes())
+    } else {
+        let mut data = Vec::new();
+        for entry in &entries {
+            serde_json::to_writer(&mut data, entry)
+                .map_err(|_| AppError::internal("Failed to serialize audit logs"))?;
+            data.push(b'\n');
+        }
+        ("application/x-ndjson", data)
+    };
+
+    let _ = repo
+        .insert(
+            org_id,
+            Some(auth_user.user_id()),
+            "audit_log.export",
+            "audit_log",
+            None,
+            Some(&serde_json::json!({
+                "format": format,
+                "count": entries.len(),
+                "user_id": query.user_id,
+                "resource_type": query.resource_type,
+                "action": query.action,
+                "from": query.from,
+                "to": query.to,
+            })),
+            client.ip_address.as_deref(),
+        )
+        .await;
+
+    let filename = format!(
+        "audit-log-{}.{}",
+        Utc::now().format("%Y%m%dT%H%M%SZ"),
+        format
+    );
+
+    Ok((
+        StatusCode::OK,
+        [
+            ("Content-Type".to_string(), content_type.to_string()),
+            (
+                "Content-Disposition".to_string(),
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        data,
+    ))
+}
+
+fn entries_to_csv(entries: &[AuditLogEntry]) -> String {
+    let mut csv = String::from(
+        "id,created_at,organization_id,user_id,impersonator_id,user_kind,action,resource_type,resource_id,ip_address,request_id,details\n",
+    );
+    for entry in entries {
+        let fields = [
+            entry.id.to_string(),
+            entry.created_at.to_rfc3339(),
+            entry.organization_id.to_string(),
+            entry.user_id.map(|id| id.to_string()).unwrap_or_default(),
+            entry
+                .impersonator_id
+                .map(|id| id.to_string())
+                .unwrap_or_default(),
+            entry
+                .user_kind
+                .map(|kind| kind.to_string())
+                .unwrap_or_default(),
+            entry.action.clone(),
+            entry.resource_type.clone(),
+            entry.resource_id.clone().unwrap_or_default(),
+            entry.ip_address.clone().unwrap_or_default(),
+            entry.request_id.clone().unwrap_or_default(),
+            entry
+                .details
+                .as_ref()
+                .map(|d| d.to_string())
+                .unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+/// Quote a CSV field if it contains a separator, quote or line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field_escaping() {
+        assert_eq!(csv_field("user.login"), "user.login");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(
+            csv_field(r#"{"name":"x","tags":["a","b"]}"#),
+            r#""{""name"":""x"",""tags"":[""a"",""b""]}""#
+        );
+        assert_eq!(csv_field("line\nbreak"), "\"line\nbreak\"");
+    }
+}
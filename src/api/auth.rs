@@ -10,20 +10,36 @@ use axum::{
     Json, Router,
 };
 use chrono::{Duration, Utc};
+use jsonwebtoken::jwk::JwkSet;
 use serde::Serialize;
 use uuid::Uuid;
 
 use crate::{
-    middleware::auth::{
-        create_access_token, create_auth_session, create_refresh_token, ensure_auth_session_active,
-        revoke_auth_session, validate_token, AuthError, AuthUser, TokenType,
+    db::{AuthSessionRepository, LoginHistoryRepository, RefreshTokenRepository, RefreshTokenUse},
+    middleware::{
+        auth::{
+            create_access_token_until, create_auth_session, create_refresh_token,
+            ensure_auth_session_active, revoke_auth_session, validate_token, AuthError, AuthUser,
+            SessionClient, TokenType,
+        },
+        session_cookie::{self, SetCookies, REFRESH_COOKIE, SESSION_COOKIE},
     },
-    models::{AuthResponse, LoginRequest, RefreshTokenRequest, TokenResponse, UserPublic},
-    services::AuthService,
-    utils::error::ErrorResponse,
+    models::{
+        default_organization_uuid, AuthResponse, LoginEvent, LoginMethod, LoginRequest,
+        QuotaResource, RefreshTokenRequest, TokenResponse, User, UserPublic,
+    },
+    services::{
+        account_lockout, auth::PASSWORD_RESET_TOKEN_TTL_MINUTES, elevation, login_history,
+        password_policy::PasswordPolicy, quotas::check_quota, AuthService,
+    },
+    utils::error::{ApiError, AppError},
     AppState,
 };
 
+/// How long after a refresh token was spent its reuse is taken for a
+/// concurrent refresh rather than a stolen token
+const REFRESH_TOKEN_REUSE_GRACE_SECS: i64 = 10;
+
 /// Create public routes for authentication endpoints (no auth required)
 pub fn public_routes() -> Router<AppState> {
     Router::new()
@@ -33,6 +49,7 @@ pub fn public_routes() -> Router<AppState> {
         .route("/register", post(register))
         .route("/forgot-password", post(forgot_password))
         .route("/reset-password", post(reset_password))
+        .route("/jwks.json", get(jwks))
 }
 
 /// Create protected routes for authentication endpoints (auth required)
@@ -53,12 +70,45 @@ pub struct RegisterRequest {
 /// Login handler
 ///
 /// POST /api/v1/auth/login
+///
+/// Failed attempts are counted per account; too many lock it (see
+/// [`account_lockout`]). A locked account is refused before its password is
+/// checked, with the same error as a wrong password so the answer does not
+/// tell which usernames exist. In cookie auth mode the tokens are set as
+/// cookies instead of being returned.
 async fn login(
     State(state): State<AppState>,
+    client: SessionClient,
+    headers: HeaderMap,
     Json(payload): Json<LoginRequest>,
-) -> Result<Json<AuthResponse>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<(SetCookies, Json<AuthResponse>), (StatusCode, Json<ApiError>)> {
     let auth_service = AuthService::new(state.db.clone());
 
+    // The account being logged in to, for its lockout and login history
+    let target = auth_service
+        .get_user_by_username(&payload.username)
+        .await
+        .ok()
+        .flatten()
+        .filter(|user| !user.is_service_account());
+    if let Some(target) = &target {
+        if account_lockout::locked_until(&state.db, target.id, Utc::now())
+            .await
+            .is_some()
+        {
+            login_history::record_login(
+                &state,
+                target,
+                LoginMethod::Password,
+                &client,
+                &headers,
+                false,
+            )
+            .await;
+            return Err(invalid_credentials());
+        }
+    }
+
     // Authenticate user
     let user = auth_service
         .authenticate(&payload.username, &payload.password)
@@ -66,119 +116,234 @@ async fn login(
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "internal_error".to_string(),
-                    message: format!("Authentication failed: {}", e),
-                    details: None,
-                    code: None,
-                }),
-            )
-        })?
-        .ok_or_else(|| {
-            (
-                StatusCode::UNAUTHORIZED,
-                Json(ErrorResponse {
-                    error: "unauthorized".to_string(),
-                    message: "Invalid username or password".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Authentication failed: {}", e),
+                )),
             )
         })?;
+    let Some(mut user) = user else {
+        if let Some(target) = &target {
+            login_history::record_login(
+                &state,
+                target,
+                LoginMethod::Password,
+                &client,
+                &headers,
+                false,
+            )
+            .await;
+
+            let locked = account_lockout::register_failure(
+                &state.db,
+                &state.config.rbac,
+                target.id,
+                Utc::now(),
+            )
+            .await;
+            if let Some(until) = locked {
+                let _ = state
+                    .audit_repository()
+                    .insert(
+                        target.organization_id,
+                        Some(target.id),
+                        "user.locked",
+                        "users",
+                        Some(&target.id.to_string()),
+                        Some(&serde_json::json!({
+                            "username": target.username,
+                            "locked_until": until,
+                            "failed_attempts": state.config.rbac.max_failed_logins,
+                            "user_agent": client.user_agent,
+                        })),
+                        client.ip_address.as_deref(),
+                    )
+                    .await;
+                login_history::notify_locked(&state, target, &client, until);
+            }
+        }
+        return Err(invalid_credentials());
+    };
+    account_lockout::clear(&state.db, user.id).await;
+
+    // Passwords older than the configured maximum age must be changed
+    if !user.force_password_change {
+        let policy = PasswordPolicy::new(&state.config.auth);
+        if let Ok(Some(changed_at)) = auth_service.password_changed_at(&user.id).await {
+            if policy.is_expired(changed_at, Utc::now()) {
+                match auth_service.set_force_password_change(&user.id, true).await {
+                    Ok(()) => user.force_password_change = true,
+                    Err(e) => tracing::error!("Failed to flag expired password: {}", e),
+                }
+            }
+        }
+    }
 
     // Get user roles from RBAC
     let roles = auth_service
         .get_user_roles(&user.id)
         .await
         .unwrap_or_else(|_| vec![user.role.clone()]);
+    let grant = elevation::token_grant(
+        &state.db,
+        user.id,
+        roles,
+        Utc::now() + Duration::hours(state.config.auth.token_expiry_hours as i64),
+    )
+    .await;
 
     let session_id = Uuid::new_v4();
     let session_expires_at =
         Utc::now() + Duration::days(state.config.auth.refresh_token_expiry_days as i64);
-    create_auth_session(&state.db, &session_id, &user.id, session_expires_at)
-        .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "internal_error".to_string(),
-                    message: format!("Failed to create session: {}", auth_error_message(&e)),
-                    details: None,
-                    code: None,
-                }),
-            )
-        })?;
+    create_auth_session(
+        &state.db,
+        &session_id,
+        &user.id,
+        session_expires_at,
+        &client,
+    )
+    .await
+    .map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new(
+                "internal_error",
+                format!("Failed to create session: {}", auth_error_message(&e)),
+            )),
+        )
+    })?;
 
     // Create tokens
-    let access_token = create_access_token(
+    let access_token = create_access_token_until(
         &user.id,
         &user.organization_id,
         &session_id,
         &user.username,
         &user.email,
-        roles,
+        grant.roles.clone(),
         &state.config.auth.jwt_secret,
-        state.config.auth.token_expiry_hours,
+        &state.jwt_keys,
+        grant.expires_at,
     )
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "internal_error".to_string(),
-                message: format!("Failed to create access token: {}", e),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "internal_error",
+                format!("Failed to create access token: {}", e),
+            )),
         )
     })?;
 
+    let refresh_token = issue_refresh_token(&state, &user, &session_id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to create refresh token: {}", e),
+                )),
+            )
+        })?;
+
+    login_history::record_login(
+        &state,
+        &user,
+        LoginMethod::Password,
+        &client,
+        &headers,
+        true,
+    )
+    .await;
+
+    let expires_in = grant.expires_in(Utc::now());
+    let cookies =
+        session_cookie::session_cookies(&state.config, &access_token, expires_in, &refresh_token);
+    let (access_token, refresh_token) = if state.config.auth.cookie_mode() {
+        (String::new(), String::new())
+    } else {
+        (access_token, refresh_token)
+    };
+
+    Ok((
+        cookies,
+        Json(AuthResponse {
+            access_token,
+            refresh_token,
+            token_type: "Bearer".to_string(),
+            expires_in,
+            user: user.into(),
+        }),
+    ))
+}
+
+/// Error for a failed login, whether the username is unknown, the password
+/// wrong or the account locked
+fn invalid_credentials() -> (StatusCode, Json<ApiError>) {
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(ApiError::new(
+            "unauthorized",
+            "Invalid username or password",
+        )),
+    )
+}
+
+/// Create a refresh token for a session and record it for one-time use
+pub(crate) async fn issue_refresh_token(
+    state: &AppState,
+    user: &User,
+    session_id: &Uuid,
+) -> anyhow::Result<String> {
     let refresh_token = create_refresh_token(
         &user.id,
-        &session_id,
+        session_id,
         &user.username,
         &user.email,
         &state.config.auth.jwt_secret,
+        &state.jwt_keys,
         state.config.auth.refresh_token_expiry_days,
-    )
-    .map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "internal_error".to_string(),
-                message: format!("Failed to create refresh token: {}", e),
-                details: None,
-                code: None,
-            }),
-        )
-    })?;
-
-    Ok(Json(AuthResponse {
-        access_token,
-        refresh_token,
-        token_type: "Bearer".to_string(),
-        expires_in: state.config.auth.token_expiry_hours * 3600,
-        user: user.into(),
-    }))
+    )?;
+    let expires_at =
+        Utc::now() + Duration::days(state.config.auth.refresh_token_expiry_days as i64);
+    RefreshTokenRepository::new(&state.db)
+        .record(*session_id, &refresh_token, expires_at)
+        .await?;
+    Ok(refresh_token)
 }
 
 /// Refresh token handler
 ///
 /// POST /api/v1/auth/refresh
+///
+/// Refresh tokens are single-use: the response carries a new refresh token
+/// that replaces the one exchanged. A spent token presented again means it
+/// was copied, so the whole session is revoked and the reuse audited. Reuse
+/// within a few seconds is taken for two tabs refreshing at once and only
+/// rejected. In cookie auth mode the refresh token is read from its cookie
+/// when the body has none, and the new tokens are set as cookies.
 async fn refresh_token(
     State(state): State<AppState>,
+    client: SessionClient,
+    headers: HeaderMap,
     Json(payload): Json<RefreshTokenRequest>,
-) -> Result<Json<TokenResponse>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<(SetCookies, Json<TokenResponse>), (StatusCode, Json<ApiError>)> {
+    let presented = if payload.refresh_token.is_empty() && state.config.auth.cookie_mode() {
+        session_cookie::request_cookie(&headers, REFRESH_COOKIE).unwrap_or_default()
+    } else {
+        payload.refresh_token.as_str()
+    };
+
     // Validate the refresh token
-    let token_data = validate_token(&payload.refresh_token, &state.config.auth.jwt_secret)
+    let token_data = validate_token(presented, &state.config.auth.jwt_secret, &state.jwt_keys)
         .map_err(|_| {
             (
                 StatusCode::UNAUTHORIZED,
-                Json(ErrorResponse {
-                    error: "unauthorized".to_string(),
-                    message: "Invalid or expired refresh token".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new(
+                    "unauthorized",
+                    "Invalid or expired refresh token",
+                )),
             )
         })?;
 
@@ -186,12 +351,7 @@ async fn refresh_token(
     if token_data.claims.token_type != TokenType::Refresh {
         return Err((
             StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                error: "unauthorized".to_string(),
-                message: "Invalid token type".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new("unauthorized", "Invalid token type")),
         ));
     }
 
@@ -204,12 +364,7 @@ async fn refresh_token(
     let user_id = uuid::Uuid::parse_str(&token_data.claims.sub).map_err(|_| {
         (
             StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                error: "unauthorized".to_string(),
-                message: "Invalid user ID in token".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new("unauthorized", "Invalid user ID in token")),
         )
     })?;
 
@@ -219,23 +374,13 @@ async fn refresh_token(
         .map_err(|_| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "internal_error".to_string(),
-                    message: "Failed to fetch user".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("internal_error", "Failed to fetch user")),
             )
         })?
         .ok_or_else(|| {
             (
                 StatusCode::UNAUTHORIZED,
-                Json(ErrorResponse {
-                    error: "unauthorized".to_string(),
-                    message: "User not found".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("unauthorized", "User not found")),
             )
         })?;
 
@@ -244,38 +389,144 @@ async fn refresh_token(
         .get_user_roles(&user.id)
         .await
         .unwrap_or_else(|_| vec![user.role.clone()]);
+    let grant = elevation::token_grant(
+        &state.db,
+        user.id,
+        roles,
+        Utc::now() + Duration::hours(state.config.auth.token_expiry_hours as i64),
+    )
+    .await;
 
     let session_id = Uuid::parse_str(&token_data.claims.jti)
         .map_err(|_| auth_error_response(AuthError::InvalidToken))?;
 
+    let token_use = RefreshTokenRepository::new(&state.db)
+        .consume(session_id, presented)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to use refresh token: {:#}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new(
+                    "internal_error",
+                    "Failed to use refresh token",
+                )),
+            )
+        })?;
+    if let RefreshTokenUse::Reused(used_at) = token_use {
+        return Err(refresh_token_reused(&state, &user, session_id, used_at, &client).await);
+    }
+
     // Create new access token
-    let access_token = create_access_token(
+    let access_token = create_access_token_until(
         &user.id,
         &user.organization_id,
         &session_id,
         &user.username,
         &user.email,
-        roles,
+        grant.roles.clone(),
         &state.config.auth.jwt_secret,
-        state.config.auth.token_expiry_hours,
+        &state.jwt_keys,
+        grant.expires_at,
     )
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "internal_error".to_string(),
-                message: format!("Failed to create access token: {}", e),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "internal_error",
+                format!("Failed to create access token: {}", e),
+            )),
         )
     })?;
 
-    Ok(Json(TokenResponse {
-        access_token,
-        token_type: "Bearer".to_string(),
-        expires_in: state.config.auth.token_expiry_hours * 3600,
-    }))
+    let refresh_token = issue_refresh_token(&state, &user, &session_id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to create refresh token: {}", e),
+                )),
+            )
+        })?;
+
+    let expires_in = grant.expires_in(Utc::now());
+    let cookies =
+        session_cookie::session_cookies(&state.config, &access_token, expires_in, &refresh_token);
+    let (access_token, refresh_token) = if state.config.auth.cookie_mode() {
+        (String::new(), None)
+    } else {
+        (access_token, Some(refresh_token))
+    };
+
+    Ok((
+        cookies,
+        Json(TokenResponse {
+            access_token,
+            refresh_token,
+            token_type: "Bearer".to_string(),
+            expires_in,
+        }),
+    ))
+}
+
+/// Handle a refresh token presented after it was spent
+///
+/// Revokes the session unless the token was spent moments ago.
+async fn refresh_token_reused(
+    state: &AppState,
+    user: &User,
+    session_id: Uuid,
+    used_at: Option<chrono::DateTime<Utc>>,
+    client: &SessionClient,
+) -> (StatusCode, Json<ApiError>) {
+    let within_grace = used_at.is_some_and(|used_at| {
+        Utc::now() - used_at < Duration::seconds(REFRESH_TOKEN_REUSE_GRACE_SECS)
+    });
+    if within_grace {
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(ApiError::new(
+                "unauthorized",
+                "Refresh token has already been used",
+            )),
+        );
+    }
+
+    tracing::warn!(
+        "Refresh token reuse for user {} (session {}); revoking the session",
+        user.username,
+        session_id
+    );
+    if let Err(e) = revoke_auth_session(&state.db, &session_id.to_string()).await {
+        tracing::error!("Failed to revoke session {}: {:?}", session_id, e);
+    }
+    let _ = state
+        .audit_repository()
+        .insert(
+            user.organization_id,
+            Some(user.id),
+            "auth.refresh_token_reuse",
+            "auth_sessions",
+            Some(&session_id.to_string()),
+            Some(&serde_json::json!({
+                "username": user.username,
+                "used_at": used_at,
+                "user_agent": client.user_agent,
+                "session_revoked": true,
+            })),
+            client.ip_address.as_deref(),
+        )
+        .await;
+
+    (
+        StatusCode::UNAUTHORIZED,
+        Json(ApiError::new(
+            "unauthorized",
+            "Refresh token reuse detected; the session has been revoked",
+        )),
+    )
 }
 
 /// Logout response
@@ -288,25 +539,41 @@ struct LogoutResponse {
 ///
 /// POST /api/v1/auth/logout
 ///
-/// Revokes the current auth session when a bearer token is supplied.
-async fn logout(State(state): State<AppState>, headers: HeaderMap) -> Json<LogoutResponse> {
-    if let Some(auth_header) = headers
+/// Revokes the current auth session when a bearer token, or in cookie auth
+/// mode a session cookie, is supplied, and clears the session cookies.
+async fn logout(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> (SetCookies, Json<LogoutResponse>) {
+    let bearer = headers
         .get(axum::http::header::AUTHORIZATION)
         .and_then(|value| value.to_str().ok())
         .and_then(|value| {
             value
                 .strip_prefix("Bearer ")
                 .or_else(|| value.strip_prefix("bearer "))
-        })
-    {
-        if let Ok(token_data) = validate_token(auth_header, &state.config.auth.jwt_secret) {
+        });
+    // The access cookie may have expired while the refresh cookie has not
+    let cookies = [SESSION_COOKIE, REFRESH_COOKIE]
+        .into_iter()
+        .filter(|_| state.config.auth.cookie_mode())
+        .filter_map(|name| session_cookie::request_cookie(&headers, name));
+
+    for token in bearer.into_iter().chain(cookies) {
+        if let Ok(token_data) =
+            validate_token(token, &state.config.auth.jwt_secret, &state.jwt_keys)
+        {
             let _ = revoke_auth_session(&state.db, &token_data.claims.jti).await;
+            break;
         }
     }
 
-    Json(LogoutResponse {
-        message: "Successfully logged out".to_string(),
-    })
+    (
+        session_cookie::clear_session_cookies(&state.config),
+        Json(LogoutResponse {
+            message: "Successfully logged out".to_string(),
+        }),
+    )
 }
 
 fn auth_error_message(error: &AuthError) -> &'static str {
@@ -316,62 +583,89 @@ fn auth_error_message(error: &AuthError) -> &'static str {
         AuthError::TokenExpired => "Authentication token has expired",
         AuthError::SessionExpired => "Session expired due to inactivity",
         AuthError::InvalidTokenType => "Invalid token type",
+        AuthError::Forbidden(message) => message,
     }
 }
 
-fn auth_error_response(error: AuthError) -> (StatusCode, Json<ErrorResponse>) {
+fn auth_error_response(error: AuthError) -> (StatusCode, Json<ApiError>) {
     (
         StatusCode::UNAUTHORIZED,
-        Json(ErrorResponse {
-            error: "unauthorized".to_string(),
-            message: auth_error_message(&error).to_string(),
-            details: None,
-            code: None,
-        }),
+        Json(ApiError::new(
+            "unauthorized",
+            auth_error_message(&error).to_string(),
+        )),
     )
 }
 
+/// Check a new password against the configured password policy
+///
+/// `user_id` is the user whose password changes, if they already exist, so
+/// their previous passwords can be rejected.
+pub(crate) async fn check_password_policy(
+    state: &AppState,
+    user_id: Option<Uuid>,
+    username: &str,
+    password: &str,
+) -> Result<(), (StatusCode, Json<ApiError>)> {
+    let violations = PasswordPolicy::new(&state.config.auth)
+        .validate(&state.db, user_id, Some(username), password)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to check password policy: {}", e),
+                )),
+            )
+        })?;
+
+    if violations.is_empty() {
+        return Ok(());
+    }
+
+    Err((
+        StatusCode::BAD_REQUEST,
+        Json(ApiError::new("validation_error", violations.join("; "))),
+    ))
+}
+
 /// Register a new user
 ///
 /// POST /api/v1/auth/register
 async fn register(
     State(state): State<AppState>,
     Json(payload): Json<RegisterRequest>,
-) -> Result<(StatusCode, Json<UserPublic>), (StatusCode, Json<ErrorResponse>)> {
+) -> Result<(StatusCode, Json<UserPublic>), (StatusCode, Json<ApiError>)> {
     // Validate input
     if payload.username.len() < 3 {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "validation_error".to_string(),
-                message: "Username must be at least 3 characters".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "validation_error",
+                "Username must be at least 3 characters",
+            )),
         ));
     }
 
-    if payload.password.len() < 8 {
+    check_password_policy(&state, None, &payload.username, &payload.password).await?;
+
+    if !payload.email.contains('@') {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "validation_error".to_string(),
-                message: "Password must be at least 8 characters".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new("validation_error", "Invalid email address")),
         ));
     }
 
-    if !payload.email.contains('@') {
+    if let Err(e) = check_quota(&state.db, default_organization_uuid(), QuotaResource::Users).await
+    {
+        let (status, error) = match &e {
+            AppError::Conflict(_) => (StatusCode::CONFLICT, "conflict"),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, "internal_error"),
+        };
         return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "validation_error".to_string(),
-                message: "Invalid email address".to_string(),
-                details: None,
-                code: None,
-            }),
+            status,
+            Json(ApiError::new(error.to_string(), e.to_string())),
         ));
     }
 
@@ -391,22 +685,15 @@ async fn register(
             if message.contains("already exists") {
                 (
                     StatusCode::CONFLICT,
-                    Json(ErrorResponse {
-                        error: "conflict".to_string(),
-                        message,
-                        details: None,
-                        code: None,
-                    }),
+                    Json(ApiError::new("conflict", message)),
                 )
             } else {
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse {
-                        error: "internal_error".to_string(),
-                        message: format!("Failed to create user: {}", e),
-                        details: None,
-                        code: None,
-                    }),
+                    Json(ApiError::new(
+                        "internal_error",
+                        format!("Failed to create user: {}", e),
+                    )),
                 )
             }
         })?;
@@ -433,46 +720,89 @@ pub struct ForgotPasswordResponse {
 ///
 /// POST /api/v1/auth/forgot-password
 ///
-/// Note: In production, this would send an email with the reset link.
-/// For development/testing, the token is returned directly in the response.
+/// Emails a reset link to local accounts with this address. The response is
+/// the same whether or not the address exists, and the email is sent in the
+/// background so response times do not reveal it either. A user gets at most
+/// one email per cooldown period; every request is audited.
+///
+/// Debug builds also return the token in the response for testing.
 async fn forgot_password(
     State(state): State<AppState>,
+    client: SessionClient,
     Json(payload): Json<ForgotPasswordRequest>,
-) -> Result<Json<ForgotPasswordResponse>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<ForgotPasswordResponse>, (StatusCode, Json<ApiError>)> {
     if !payload.email.contains('@') {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "validation_error".to_string(),
-                message: "Invalid email address".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new("validation_error", "Invalid email address")),
         ));
     }
 
-    let auth_service = AuthService::new(state.db.clone());
+    let internal_error = |e: anyhow::Error| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new(
+                "internal_error",
+                format!("Failed to process request: {}", e),
+            )),
+        )
+    };
 
-    // Create reset token (returns None if email doesn't exist, but we don't reveal that)
-    let _token = auth_service
-        .create_password_reset_token(&payload.email)
+    let auth_service = AuthService::new(state.db.clone());
+    let user = auth_service
+        .get_user_by_email(&payload.email)
         .await
-        .map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "internal_error".to_string(),
-                    message: format!("Failed to process request: {}", e),
-                    details: None,
-                    code: None,
-                }),
-            )
-        })?;
+        .map_err(internal_error)?
+        .filter(|user| user.auth_provider.allows_local() && !user.is_service_account());
+
+    let token = match &user {
+        Some(user) => auth_service
+            .create_password_reset_token(user)
+            .await
+            .map_err(internal_error)?,
+        None => None,
+    };
+
+    let (org_id, user_id, outcome) = match (&user, &token) {
+        (Some(user), Some(_)) => (user.organization_id, Some(user.id), "sent"),
+        (Some(user), None) => (user.organization_id, Some(user.id), "throttled"),
+        (None, _) => (default_organization_uuid(), None, "unknown_account"),
+    };
+    let _ = state
+        .audit_repository()
+        .insert(
+            org_id,
+            user_id,
+            "user.password_reset_requested",
+            "users",
+            user_id.map(|id| id.to_string()).as_deref(),
+            Some(&serde_json::json!({
+                "email": payload.email,
+                "outcome": outcome,
+                "user_agent": client.user_agent,
+            })),
+            client.ip_address.as_deref(),
+        )
+        .await;
+
+    if let (Some(user), Some(token)) = (&user, &token) {
+        let (text_body, html_body) =
+            password_reset_email(token, state.config.auth.password_reset_url.as_deref());
+        let mailer = state.mailer();
+        let email = user.email.clone();
+        let username = user.username.clone();
+        tokio::spawn(async move {
+            if let Err(e) = mailer
+                .send(&email, "OpenVox password reset", &text_body, &html_body)
+                .await
+            {
+                tracing::error!("Failed to send password reset email to {}: {}", username, e);
+            }
+        });
+    }
 
-    // In a real application, you would send an email here
-    // For now, we return the token in development mode
     #[cfg(debug_assertions)]
-    let reset_token = _token;
+    let reset_token = token;
 
     #[cfg(not(debug_assertions))]
     let reset_token: Option<String> = None;
@@ -485,6 +815,43 @@ async fn forgot_password(
     }))
 }
 
+/// Plain text and HTML bodies of a password reset email
+fn password_reset_email(token: &str, reset_url: Option<&str>) -> (String, String) {
+    let validity = format!(
+        "The reset is valid for {} minutes. If you did not request a password reset, you can ignore this email.",
+        PASSWORD_RESET_TOKEN_TTL_MINUTES
+    );
+
+    match reset_url {
+        Some(url) => {
+            let separator = if url.contains('?') { '&' } else { '?' };
+            let link = format!("{}{}token={}", url, separator, token);
+            (
+                format!(
+                    "A password reset was requested for your OpenVox WebUI account.\n\nOpen this link to choose a new password:\n{}\n\n{}",
+                    link, validity
+                ),
+                format!(
+                    "<p>A password reset was requested for your OpenVox WebUI account.</p>\
+                     <p><a href=\"{link}\">Choose a new password</a></p>\
+                     <p>{validity}</p>"
+                ),
+            )
+        }
+        None => (
+            format!(
+                "A password reset was requested for your OpenVox WebUI account.\n\nReset token: {}\n\n{}",
+                token, validity
+            ),
+            format!(
+                "<p>A password reset was requested for your OpenVox WebUI account.</p>\
+                 <p>Reset token: <code>{token}</code></p>\
+                 <p>{validity}</p>"
+            ),
+        ),
+    }
+}
+
 /// Reset password request
 #[derive(Debug, serde::Deserialize)]
 pub struct ResetPasswordRequest {
@@ -503,37 +870,77 @@ pub struct ResetPasswordResponse {
 /// POST /api/v1/auth/reset-password
 async fn reset_password(
     State(state): State<AppState>,
+    client: SessionClient,
     Json(payload): Json<ResetPasswordRequest>,
-) -> Result<Json<ResetPasswordResponse>, (StatusCode, Json<ErrorResponse>)> {
-    if payload.new_password.len() < 8 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "validation_error".to_string(),
-                message: "Password must be at least 8 characters".to_string(),
-                details: None,
-                code: None,
-            }),
-        ));
-    }
-
+) -> Result<Json<ResetPasswordResponse>, (StatusCode, Json<ApiError>)> {
     let auth_service = AuthService::new(state.db.clone());
 
+    // An unknown token fails below; only check the policy for real users
+    let reset_user = match auth_service.validate_reset_token(&payload.token).await {
+        Ok(Some(user_id)) => auth_service.get_user_by_id(&user_id).await.ok().flatten(),
+        _ => None,
+    };
+    if let Some(user) = &reset_user {
+        check_password_policy(&state, Some(user.id), &user.username, &payload.new_password).await?;
+    }
+
     let success = auth_service
         .reset_password(&payload.token, &payload.new_password)
         .await
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "internal_error".to_string(),
-                    message: format!("Failed to reset password: {}", e),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to reset password: {}", e),
+                )),
             )
         })?;
 
+    let audit = state.audit_repository();
+    match (&reset_user, success) {
+        (Some(user), true) => {
+            // Sign out everywhere, in case the account was taken over
+            let revoked = AuthSessionRepository::new(&state.db)
+                .revoke_all_for_user(user.id, None)
+                .await
+                .unwrap_or_else(|e| {
+                    tracing::error!("Failed to revoke sessions after password reset: {}", e);
+                    0
+                });
+            let _ = audit
+                .insert(
+                    user.organization_id,
+                    Some(user.id),
+                    "user.password_reset",
+                    "users",
+                    Some(&user.id.to_string()),
+                    Some(&serde_json::json!({
+                        "revoked_sessions": revoked,
+                        "user_agent": client.user_agent,
+                    })),
+                    client.ip_address.as_deref(),
+                )
+                .await;
+        }
+        _ => {
+            let _ = audit
+                .insert(
+                    default_organization_uuid(),
+                    None,
+                    "user.password_reset_failed",
+                    "users",
+                    None,
+                    Some(&serde_json::json!({
+                        "reason": "invalid_token",
+                        "user_agent": client.user_agent,
+                    })),
+                    client.ip_address.as_deref(),
+                )
+                .await;
+        }
+    }
+
     if success {
         Ok(Json(ResetPasswordResponse {
             message: "Password has been reset successfully.".to_string(),
@@ -541,12 +948,10 @@ async fn reset_password(
     } else {
         Err((
             StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "invalid_token".to_string(),
-                message: "Invalid or expired reset token".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(
+                ApiError::new("bad_request", "Invalid or expired reset token")
+                    .with_code("invalid_token"),
+            ),
         ))
     }
 }
@@ -571,32 +976,37 @@ async fn change_password(
     State(state): State<AppState>,
     auth_user: AuthUser,
     Json(payload): Json<ChangePasswordRequest>,
-) -> Result<Json<ChangePasswordResponse>, (StatusCode, Json<ErrorResponse>)> {
-    if payload.new_password.len() < 8 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "validation_error".to_string(),
-                message: "New password must be at least 8 characters".to_string(),
-                details: None,
-                code: None,
-            }),
-        ));
-    }
-
+) -> Result<Json<ChangePasswordResponse>, (StatusCode, Json<ApiError>)> {
     if payload.current_password == payload.new_password {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "validation_error".to_string(),
-                message: "New password must be different from current password".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "validation_error",
+                "New password must be different from current password",
+            )),
         ));
     }
 
     let auth_service = AuthService::new(state.db.clone());
+    if let Ok(Some(user)) = auth_service.get_user_by_id(&auth_user.id).await {
+        if user.is_service_account() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new(
+                    "validation_error",
+                    "Service accounts have no password; they authenticate with API keys",
+                )),
+            ));
+        }
+    }
+
+    check_password_policy(
+        &state,
+        Some(auth_user.id),
+        &auth_user.username,
+        &payload.new_password,
+    )
+    .await?;
 
     let success = auth_service
         .change_password(
@@ -608,12 +1018,10 @@ async fn change_password(
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "internal_error".to_string(),
-                    message: format!("Failed to change password: {}", e),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to change password: {}", e),
+                )),
             )
         })?;
 
@@ -624,23 +1032,33 @@ async fn change_password(
     } else {
         Err((
             StatusCode::UNAUTHORIZED,
-            Json(ErrorResponse {
-                error: "unauthorized".to_string(),
-                message: "Current password is incorrect".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "unauthorized",
+                "Current password is incorrect",
+            )),
         ))
     }
 }
 
+/// Current user with their recent logins
+#[derive(Debug, Serialize)]
+pub struct CurrentUserResponse {
+    #[serde(flatten)]
+    pub user: UserPublic,
+    /// Latest login attempts on the account, newest first
+    pub recent_logins: Vec<LoginEvent>,
+}
+
 /// Get current authenticated user profile
 ///
 /// GET /api/v1/auth/me
+///
+/// Includes the latest logins to the account, successful or not, so users
+/// can spot access they do not recognize.
 async fn get_current_user(
     State(state): State<AppState>,
     auth_user: AuthUser,
-) -> Result<Json<UserPublic>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<CurrentUserResponse>, (StatusCode, Json<ApiError>)> {
     let auth_service = AuthService::new(state.db.clone());
 
     let user = auth_service
@@ -649,25 +1067,46 @@ async fn get_current_user(
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "internal_error".to_string(),
-                    message: format!("Failed to fetch user: {}", e),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to fetch user: {}", e),
+                )),
             )
         })?
         .ok_or_else(|| {
             (
                 StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "not_found".to_string(),
-                    message: "User not found".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("not_found", "User not found")),
             )
         })?;
 
-    Ok(Json(user.into()))
+    let recent_logins = LoginHistoryRepository::new(&state.db)
+        .list_for_user(user.id, login_history::RECENT_LOGINS)
+        .await
+        .unwrap_or_else(|e| {
+            tracing::warn!("Failed to list login history: {}", e);
+            Vec::new()
+        });
+
+    Ok(Json(CurrentUserResponse {
+        user: user.into(),
+        recent_logins,
+    }))
+}
+
+/// Public keys that verify our tokens
+///
+/// GET /api/v1/auth/jwks.json
+///
+/// Lists the RS256 and EdDSA keys that sign or still verify tokens, so other
+/// services can validate them. The set is empty while tokens are signed with
+/// HS256, whose keys are secret.
+async fn jwks(State(state): State<AppState>) -> Json<JwkSet> {
+    Json(
+        state
+            .jwt_keys
+            .current()
+            .map(|keyring| keyring.jwks(Utc::now()))
+            .unwrap_or(JwkSet { keys: Vec::new() }),
+    )
 }
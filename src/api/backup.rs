@@ -285,6 +285,9 @@ async fn verify_backup(
 }
 
 /// Restore from a backup
+///
+/// Configuration files are restored in place; a restored database is staged
+/// and replaces the live one when the server restarts.
 async fn restore_backup(
     State(state): State<AppState>,
     auth_user: AuthUser,
@@ -322,11 +325,11 @@ async fn restore_backup(
             AppError::internal(format!("Failed to restore backup: {}", e))
         })?;
 
-    Ok(Json(BackupRestoreResponse::from_restore(
-        restore,
-        Some(backup.filename),
-        Some(username),
-    )))
+    let mut response =
+        BackupRestoreResponse::from_restore(restore, Some(backup.filename), Some(username));
+    response.restart_required = backup.includes_database;
+
+    Ok(Json(response))
 }
 
 // ============================================================================
@@ -1,68 +1,219 @@
 //! Puppet CA management API endpoints
 
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::IntoResponse,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
     routing::{delete, get, post},
     Json, Router,
 };
 
-use crate::models::{RenewCARequest, SignRequest};
+use crate::db::CertRenewalRepository;
+use crate::middleware::AuthUser;
+use crate::models::{
+    Action, BulkRevokeRequest, BulkSignRequest, CAStatus, CaSnapshot, CaSnapshotSummary,
+    CertificateStatusCheck, CertificateValidity, CreateRenewalCampaignRequest, CrlResponse,
+    RenewCARequest, RenewalCampaignDetail, RenewalCampaignStatus, RenewalCandidatesQuery,
+    RenewalTargetStatus, RenewalTargetsQuery, Resource, SignRequest, UpdateOperationType,
+};
+use crate::services::ca_snapshot;
+use crate::services::cert_renewal::{refresh_campaign, renewal_candidates, renewal_instructions};
 use crate::utils::error::AppError;
+use crate::utils::x509;
 use crate::AppState;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Maximum number of certnames accepted by a bulk operation
+const MAX_BULK_CERTNAMES: usize = 1000;
+
+/// Renewal window used when neither the request nor the config sets one
+const DEFAULT_RENEWAL_WITHIN_DAYS: i64 = 30;
+
+/// Number of campaigns returned by the renewal list
+const RENEWAL_CAMPAIGN_LIST_LIMIT: i64 = 100;
+
+/// Response header set when data comes from the offline CA snapshot
+const SNAPSHOT_HEADER: &str = "x-ca-snapshot-at";
+
+/// How long clients may cache a certificate status check
+const CERT_STATUS_MAX_AGE_SECS: i64 = 60;
 
 /// Create CA routes
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/ca/status", get(get_ca_status))
+        .route("/ca/status/{certname}", get(get_certificate_validity))
         .route("/ca/requests", get(list_certificate_requests))
         .route("/ca/certificates", get(list_certificates))
         .route("/ca/certificates/{certname}", get(get_certificate))
+        .route(
+            "/ca/certificates/{certname}/chain",
+            get(validate_certificate_chain),
+        )
         .route("/ca/sign/{certname}", post(sign_certificate))
         .route("/ca/reject/{certname}", post(reject_certificate))
         .route("/ca/certificates/{certname}", delete(revoke_certificate))
+        .route("/ca/bulk/sign", post(bulk_sign_certificates))
+        .route("/ca/bulk/revoke", post(bulk_revoke_certificates))
         .route("/ca/renew", post(renew_ca_certificate))
+        .route("/ca/crl", get(get_crl))
+        .route("/ca/bundle", get(get_ca_bundle))
+        .route("/ca/chain", get(get_ca_chain))
+        .route("/ca/export", get(export_ca))
+        .route("/ca/snapshot", post(sync_ca_snapshot))
+        .route(
+            "/ca/renewals",
+            get(list_renewal_campaigns).post(create_renewal_campaign),
+        )
+        .route("/ca/renewals/candidates", get(list_renewal_candidates))
+        .route("/ca/renewals/{id}", get(get_renewal_campaign))
+        .route("/ca/renewals/{id}/check", post(check_renewal_campaign))
+        .route("/ca/renewals/{id}/cancel", post(cancel_renewal_campaign))
+}
+
+/// Query parameters for the CRL endpoint
+#[derive(Debug, Deserialize)]
+struct CrlQuery {
+    /// `pem` or `json`; defaults to the Accept header, then JSON
+    format: Option<String>,
+}
+
+/// Require a certificates permission for the authenticated user
+async fn check_certificate_permission(
+    state: &AppState,
+    auth_user: &AuthUser,
+    action: Action,
+) -> Result<(), AppError> {
+    let check = state
+        .rbac_db
+        .check_permission(
+            &auth_user.user_id(),
+            Resource::Certificates,
+            action,
+            None,
+            None,
+        )
+        .await
+        .map_err(|e| AppError::internal(format!("Permission check failed: {}", e)))?;
+
+    if check.allowed {
+        Ok(())
+    } else {
+        Err(AppError::forbidden(check.reason.unwrap_or_else(|| {
+            "No matching permission found".to_string()
+        })))
+    }
+}
+
+/// Serve `live`, or the stored snapshot when the CA is unreachable
+///
+/// Snapshot responses carry an `X-CA-Snapshot-At` header with the sync time.
+async fn live_or_snapshot<T: Serialize>(
+    state: &AppState,
+    live: Result<T, AppError>,
+    from_snapshot: impl FnOnce(CaSnapshot) -> Result<T, AppError>,
+) -> Result<Response, AppError> {
+    match live {
+        Ok(value) => Ok(Json(value).into_response()),
+        Err(AppError::ServiceUnavailable(msg)) => {
+            let Some(snapshot) = ca_snapshot::load_snapshot(&state.db).await else {
+                return Err(AppError::ServiceUnavailable(msg));
+            };
+            tracing::debug!("Puppet CA unreachable ({}), serving snapshot", msg);
+            let synced_at = snapshot.synced_at.to_rfc3339();
+            let value = from_snapshot(snapshot)?;
+            Ok(([(SNAPSHOT_HEADER, synced_at)], Json(value)).into_response())
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// PEM download response
+fn pem_response(pem: String, filename: &str) -> Response {
+    (
+        [
+            (header::CONTENT_TYPE, "application/x-pem-file".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        pem,
+    )
+        .into_response()
+}
+
+/// Deduplicate and validate the certnames of a bulk request
+fn normalize_certnames(certnames: Vec<String>) -> Result<Vec<String>, AppError> {
+    let mut seen = std::collections::HashSet::new();
+    let certnames: Vec<String> = certnames
+        .into_iter()
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty() && seen.insert(c.clone()))
+        .collect();
+
+    if certnames.is_empty() {
+        return Err(AppError::bad_request("No certnames given"));
+    }
+    if certnames.len() > MAX_BULK_CERTNAMES {
+        return Err(AppError::bad_request(format!(
+            "At most {} certnames can be processed per request",
+            MAX_BULK_CERTNAMES
+        )));
+    }
+    Ok(certnames)
 }
 
 /// GET /api/v1/ca/status - Get CA service status
 ///
 /// Returns information about the CA service including pending requests and signed certificates.
-async fn get_ca_status(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+async fn get_ca_status(State(state): State<AppState>) -> Result<Response, AppError> {
     let Some(ca) = state.puppet_ca.as_ref() else {
         return Err(AppError::ServiceUnavailable(
             "Puppet CA not configured".to_string(),
         ));
     };
 
-    let status = ca.get_status().await?;
-    Ok(Json(status))
+    live_or_snapshot(&state, ca.get_status().await, |snapshot| {
+        Ok(CAStatus {
+            available: false,
+            ca_fingerprint: None,
+            ca_expires_at: None,
+            pending_requests: snapshot.requests.len(),
+            signed_certificates: snapshot.certificates.len(),
+            snapshot_at: Some(snapshot.synced_at),
+        })
+    })
+    .await
 }
 
 /// GET /api/v1/ca/requests - List pending certificate requests
 ///
 /// Returns all pending certificate signing requests.
-async fn list_certificate_requests(
-    State(state): State<AppState>,
-) -> Result<impl IntoResponse, AppError> {
+async fn list_certificate_requests(State(state): State<AppState>) -> Result<Response, AppError> {
     let Some(ca) = state.puppet_ca.as_ref() else {
-        return Ok(Json(Vec::<crate::models::CertificateRequest>::new()));
+        return Ok(Json(Vec::<crate::models::CertificateRequest>::new()).into_response());
     };
 
-    let requests = ca.list_requests().await?;
-    Ok(Json(requests))
+    live_or_snapshot(&state, ca.list_requests().await, |snapshot| {
+        Ok(snapshot.requests)
+    })
+    .await
 }
 
 /// GET /api/v1/ca/certificates - List signed certificates
 ///
 /// Returns all signed certificates.
-async fn list_certificates(State(state): State<AppState>) -> Result<impl IntoResponse, AppError> {
+async fn list_certificates(State(state): State<AppState>) -> Result<Response, AppError> {
     let Some(ca) = state.puppet_ca.as_ref() else {
-        return Ok(Json(Vec::<crate::models::Certificate>::new()));
+        return Ok(Json(Vec::<crate::models::Certificate>::new()).into_response());
     };
 
-    let certificates = ca.list_certificates().await?;
-    Ok(Json(certificates))
+    live_or_snapshot(&state, ca.list_certificates().await, |snapshot| {
+        Ok(snapshot.certificates)
+    })
+    .await
 }
 
 /// GET /api/v1/ca/certificates/:certname - Get certificate details
@@ -71,6 +222,206 @@ async fn list_certificates(State(state): State<AppState>) -> Result<impl IntoRes
 async fn get_certificate(
     State(state): State<AppState>,
     Path(certname): Path<String>,
+) -> Result<Response, AppError> {
+    let Some(ca) = state.puppet_ca.as_ref() else {
+        return Err(AppError::ServiceUnavailable(
+            "Puppet CA not configured".to_string(),
+        ));
+    };
+
+    live_or_snapshot(&state, ca.get_certificate(&certname).await, |snapshot| {
+        snapshot
+            .certificates
+            .into_iter()
+            .find(|c| c.certname == certname)
+            .ok_or_else(|| AppError::NotFound(format!("Certificate not found: {}", certname)))
+    })
+    .await
+}
+
+/// GET /api/v1/ca/status/:certname - Lightweight certificate status
+///
+/// Returns `valid`, `revoked`, `expired` or `unknown` with the serial and
+/// expiry, for load balancers and provisioning tools that only need a quick
+/// check. Responses carry an `ETag` and a short private `Cache-Control`
+/// max-age (never past the certificate's expiry); a matching `If-None-Match`
+/// yields `304 Not Modified`. Falls back to the CA snapshot when the CA is
+/// unreachable.
+async fn get_certificate_validity(
+    State(state): State<AppState>,
+    Path(certname): Path<String>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let Some(ca) = state.puppet_ca.as_ref() else {
+        return Err(AppError::ServiceUnavailable(
+            "Puppet CA not configured".to_string(),
+        ));
+    };
+
+    let (certificate, snapshot_at) = match ca.get_certificate(&certname).await {
+        Ok(certificate) => (Some(certificate), None),
+        Err(AppError::NotFound(_)) => (None, None),
+        Err(AppError::ServiceUnavailable(msg)) => {
+            let Some(snapshot) = ca_snapshot::load_snapshot(&state.db).await else {
+                return Err(AppError::ServiceUnavailable(msg));
+            };
+            let certificate = snapshot
+                .certificates
+                .into_iter()
+                .find(|c| c.certname == certname);
+            (certificate, Some(snapshot.synced_at))
+        }
+        Err(e) => return Err(e),
+    };
+
+    let now = chrono::Utc::now();
+    let status = CertificateStatusCheck::from_certificate(&certname, certificate.as_ref(), now);
+    let max_age = match (status.status, status.not_after) {
+        (CertificateValidity::Valid, Some(not_after)) => (not_after - now)
+            .num_seconds()
+            .clamp(0, CERT_STATUS_MAX_AGE_SECS),
+        _ => CERT_STATUS_MAX_AGE_SECS,
+    };
+
+    let etag = status.etag();
+    let mut response_headers = HeaderMap::new();
+    response_headers.insert(
+        header::CACHE_CONTROL,
+        format!("private, max-age={}", max_age)
+            .parse()
+            .map_err(|_| AppError::internal("Invalid Cache-Control header"))?,
+    );
+    response_headers.insert(
+        header::ETAG,
+        etag.parse()
+            .map_err(|_| AppError::internal("Invalid ETag header"))?,
+    );
+    if let Some(synced_at) = snapshot_at {
+        if let Ok(value) = synced_at.to_rfc3339().parse() {
+            response_headers.insert(SNAPSHOT_HEADER, value);
+        }
+    }
+
+    if etag_matches(&headers, &etag) {
+        return Ok((StatusCode::NOT_MODIFIED, response_headers).into_response());
+    }
+
+    Ok((response_headers, Json(status)).into_response())
+}
+
+/// Whether the request's `If-None-Match` header matches `etag`
+fn etag_matches(headers: &HeaderMap, etag: &str) -> bool {
+    headers
+        .get_all(header::IF_NONE_MATCH)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(|tag| tag.trim().trim_start_matches("W/"))
+        .any(|tag| tag == "*" || tag == etag)
+}
+
+/// GET /api/v1/ca/crl - Get the certificate revocation list
+///
+/// Returns the decoded CRL as JSON, or the CRL as served by the CA with
+/// `?format=pem` (or `Accept: application/x-pem-file`). The CRL is cached for
+/// `puppet_ca.crl_cache_secs`.
+async fn get_crl(
+    State(state): State<AppState>,
+    Query(query): Query<CrlQuery>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let Some(ca) = state.puppet_ca.as_ref() else {
+        return Err(AppError::ServiceUnavailable(
+            "Puppet CA not configured".to_string(),
+        ));
+    };
+
+    let wants_pem = match query.format.as_deref() {
+        Some("pem") => true,
+        Some("json") => false,
+        Some(other) => {
+            return Err(AppError::bad_request(format!(
+                "Unsupported CRL format '{}'; use pem or json",
+                other
+            )));
+        }
+        None => headers
+            .get(header::ACCEPT)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|accept| accept.contains("application/x-pem-file")),
+    };
+
+    if wants_pem {
+        let pem = match ca.get_crl_pem().await {
+            Err(AppError::ServiceUnavailable(msg)) => ca_snapshot::load_snapshot(&state.db)
+                .await
+                .and_then(|snapshot| snapshot.crl_pem)
+                .ok_or(AppError::ServiceUnavailable(msg))?,
+            result => result?,
+        };
+        Ok(pem_response(pem, "crl.pem"))
+    } else {
+        live_or_snapshot(&state, ca.get_crl().await, |snapshot| {
+            let pem = snapshot
+                .crl_pem
+                .ok_or_else(|| AppError::NotFound("No CRL in CA snapshot".to_string()))?;
+            Ok(CrlResponse {
+                fetched_at: snapshot.synced_at,
+                crls: x509::parse_crl_pem(&pem).map_err(AppError::internal)?,
+            })
+        })
+        .await
+    }
+}
+
+/// GET /api/v1/ca/bundle - Download the CA certificate bundle
+///
+/// Returns the CA certificate (and any intermediates) as PEM, from the
+/// offline snapshot when the CA is unreachable.
+async fn get_ca_bundle(State(state): State<AppState>) -> Result<Response, AppError> {
+    let Some(ca) = state.puppet_ca.as_ref() else {
+        return Err(AppError::ServiceUnavailable(
+            "Puppet CA not configured".to_string(),
+        ));
+    };
+
+    let pem = match ca.get_ca_bundle_pem().await {
+        Err(AppError::ServiceUnavailable(msg)) => ca_snapshot::load_snapshot(&state.db)
+            .await
+            .and_then(|snapshot| snapshot.ca_bundle_pem)
+            .ok_or(AppError::ServiceUnavailable(msg))?,
+        result => result?,
+    };
+    Ok(pem_response(pem, "ca_bundle.pem"))
+}
+
+/// GET /api/v1/ca/chain - Get the CA certificate chain
+///
+/// Lists the root and intermediate CAs of the CA bundle with expiry warnings
+/// based on `ca_expiry_warning_days`.
+async fn get_ca_chain(State(state): State<AppState>) -> Result<Response, AppError> {
+    let Some(ca) = state.puppet_ca.as_ref() else {
+        return Err(AppError::ServiceUnavailable(
+            "Puppet CA not configured".to_string(),
+        ));
+    };
+
+    live_or_snapshot(&state, ca.get_ca_chain().await, |snapshot| {
+        let pem = snapshot
+            .ca_bundle_pem
+            .ok_or_else(|| AppError::NotFound("No CA bundle in CA snapshot".to_string()))?;
+        ca.ca_chain_from_pem(&pem)
+    })
+    .await
+}
+
+/// GET /api/v1/ca/certificates/{certname}/chain - Validate a certificate's chain
+///
+/// Verifies signatures, validity periods and CA constraints from the node
+/// certificate up to a root of the CA bundle.
+async fn validate_certificate_chain(
+    State(state): State<AppState>,
+    Path(certname): Path<String>,
 ) -> Result<impl IntoResponse, AppError> {
     let Some(ca) = state.puppet_ca.as_ref() else {
         return Err(AppError::ServiceUnavailable(
@@ -78,8 +429,117 @@ async fn get_certificate(
         ));
     };
 
-    let certificate = ca.get_certificate(&certname).await?;
-    Ok(Json(certificate))
+    let validation = ca.validate_certificate_chain(&certname).await?;
+    Ok(Json(validation))
+}
+
+/// GET /api/v1/ca/export - Export the CA state as a tar.gz archive
+///
+/// Contains `ca_bundle.pem`, `crl.pem`, `certificates.json`,
+/// `requests.json` and `snapshot.json` (sync time and counts). Taken live when
+/// the CA is reachable (refreshing the stored snapshot), otherwise from the
+/// last snapshot. Requires `certificates:admin`.
+async fn export_ca(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Response, AppError> {
+    check_certificate_permission(&state, &auth_user, Action::Admin).await?;
+
+    let Some(ca) = state.puppet_ca.as_ref() else {
+        return Err(AppError::ServiceUnavailable(
+            "Puppet CA not configured".to_string(),
+        ));
+    };
+
+    let snapshot = match ca_snapshot::sync_snapshot(
+        &state.db,
+        ca,
+        &state.notification_service,
+        &state.webhooks,
+    )
+    .await
+    {
+        Err(AppError::ServiceUnavailable(msg)) => ca_snapshot::load_snapshot(&state.db)
+            .await
+            .ok_or(AppError::ServiceUnavailable(msg))?,
+        result => result?,
+    };
+
+    let archive = build_export_archive(&snapshot)
+        .map_err(|e| AppError::internal(format!("Failed to build CA export: {}", e)))?;
+    let filename = format!(
+        "puppet-ca-{}.tar.gz",
+        snapshot.synced_at.format("%Y%m%d%H%M%S")
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/gzip".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        archive,
+    )
+        .into_response())
+}
+
+/// Build the tar.gz archive for a CA export
+fn build_export_archive(snapshot: &CaSnapshot) -> std::io::Result<Vec<u8>> {
+    use flate2::{write::GzEncoder, Compression};
+
+    let mut files: Vec<(&str, Vec<u8>)> = vec![
+        (
+            "snapshot.json",
+            serde_json::to_vec_pretty(&CaSnapshotSummary::from(snapshot))?,
+        ),
+        (
+            "certificates.json",
+            serde_json::to_vec_pretty(&snapshot.certificates)?,
+        ),
+        (
+            "requests.json",
+            serde_json::to_vec_pretty(&snapshot.requests)?,
+        ),
+    ];
+    if let Some(pem) = &snapshot.ca_bundle_pem {
+        files.push(("ca_bundle.pem", pem.clone().into_bytes()));
+    }
+    if let Some(pem) = &snapshot.crl_pem {
+        files.push(("crl.pem", pem.clone().into_bytes()));
+    }
+
+    let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+    for (name, data) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(snapshot.synced_at.timestamp().max(0) as u64);
+        builder.append_data(&mut header, name, data.as_slice())?;
+    }
+    builder.into_inner()?.finish()
+}
+
+/// POST /api/v1/ca/snapshot - Sync the offline CA snapshot now
+///
+/// Useful at the end of a signing window, before the CA host goes offline.
+async fn sync_ca_snapshot(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<impl IntoResponse, AppError> {
+    check_certificate_permission(&state, &auth_user, Action::Admin).await?;
+
+    let Some(ca) = state.puppet_ca.as_ref() else {
+        return Err(AppError::ServiceUnavailable(
+            "Puppet CA not configured".to_string(),
+        ));
+    };
+
+    let snapshot =
+        ca_snapshot::sync_snapshot(&state.db, ca, &state.notification_service, &state.webhooks)
+            .await?;
+    Ok(Json(CaSnapshotSummary::from(&snapshot)))
 }
 
 /// POST /api/v1/ca/sign/:certname - Sign a certificate request
@@ -141,6 +601,88 @@ async fn revoke_certificate(
     Ok((StatusCode::OK, Json(response)))
 }
 
+/// POST /api/v1/ca/bulk/sign - Sign many certificate requests
+///
+/// Requires the `certificates:bulk_sign` permission. Each certname is signed
+/// independently; failures are reported per item and do not stop the batch.
+///
+/// Request body:
+/// ```json
+/// {
+///   "certnames": ["node1.example.com", "node2.example.com"]
+/// }
+/// ```
+/// or `{"all_pending": true}` to sign every pending request.
+async fn bulk_sign_certificates(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(request): Json<BulkSignRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    check_certificate_permission(&state, &auth_user, Action::BulkSign).await?;
+
+    let Some(ca) = state.puppet_ca.as_ref() else {
+        return Err(AppError::ServiceUnavailable(
+            "Puppet CA not configured".to_string(),
+        ));
+    };
+
+    let certnames = if request.all_pending {
+        ca.list_requests()
+            .await?
+            .into_iter()
+            .map(|r| r.certname)
+            .collect()
+    } else {
+        request.certnames
+    };
+    let certnames = normalize_certnames(certnames)?;
+
+    tracing::info!(
+        "User '{}' is bulk signing {} certificate request(s)",
+        auth_user.username,
+        certnames.len()
+    );
+
+    let response = ca.sign_certificates(&certnames).await;
+    Ok((StatusCode::OK, Json(response)))
+}
+
+/// POST /api/v1/ca/bulk/revoke - Revoke many certificates
+///
+/// Requires the `certificates:bulk_revoke` permission. Results are reported
+/// per certname.
+///
+/// Request body:
+/// ```json
+/// {
+///   "certnames": ["node1.example.com", "node2.example.com"]
+/// }
+/// ```
+async fn bulk_revoke_certificates(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(request): Json<BulkRevokeRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    check_certificate_permission(&state, &auth_user, Action::BulkRevoke).await?;
+
+    let Some(ca) = state.puppet_ca.as_ref() else {
+        return Err(AppError::ServiceUnavailable(
+            "Puppet CA not configured".to_string(),
+        ));
+    };
+
+    let certnames = normalize_certnames(request.certnames)?;
+
+    tracing::info!(
+        "User '{}' is bulk revoking {} certificate(s)",
+        auth_user.username,
+        certnames.len()
+    );
+
+    let response = ca.revoke_certificates(&certnames).await;
+    Ok((StatusCode::OK, Json(response)))
+}
+
 /// POST /api/v1/ca/renew - Renew CA certificate
 ///
 /// Renews the CA certificate with a new expiration date.
@@ -170,3 +712,351 @@ async fn renew_ca_certificate(
     let response = ca.renew_ca(&request).await?;
     Ok((StatusCode::OK, Json(response)))
 }
+
+/// Renewal window from the request, then `puppet_ca.leaf_expiry_warning_days`
+fn renewal_window(state: &AppState, within_days: Option<i64>) -> Result<i64, AppError> {
+    let days = within_days.unwrap_or_else(|| {
+        state
+            .config
+            .puppet_ca
+            .as_ref()
+            .map(|c| c.leaf_expiry_warning_days)
+            .unwrap_or(DEFAULT_RENEWAL_WITHIN_DAYS)
+    });
+    if !(1..=3650).contains(&days) {
+        return Err(AppError::bad_request(
+            "within_days must be between 1 and 3650",
+        ));
+    }
+    Ok(days)
+}
+
+/// Load a campaign with its targets; pending targets carry renewal instructions
+async fn renewal_campaign_detail(
+    state: &AppState,
+    id: Uuid,
+    status: Option<RenewalTargetStatus>,
+) -> Result<RenewalCampaignDetail, AppError> {
+    let repo = CertRenewalRepository::new(&state.db);
+    let campaign = repo
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Renewal campaign not found"))?;
+
+    let mut targets = repo.list_targets(id, status).await?;
+    for target in &mut targets {
+        if target.status == RenewalTargetStatus::Pending {
+            target.instructions = renewal_instructions(&target.certname);
+        }
+    }
+
+    Ok(RenewalCampaignDetail { campaign, targets })
+}
+
+/// GET /api/v1/ca/renewals - List certificate renewal campaigns
+///
+/// Returns the most recent campaigns, newest first.
+async fn list_renewal_campaigns(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, AppError> {
+    let campaigns = CertRenewalRepository::new(&state.db)
+        .list(RENEWAL_CAMPAIGN_LIST_LIMIT)
+        .await?;
+    Ok(Json(campaigns))
+}
+
+/// GET /api/v1/ca/renewals/candidates - List certificates close to expiry
+///
+/// Query parameter `within_days` defaults to `puppet_ca.leaf_expiry_warning_days`.
+async fn list_renewal_candidates(
+    State(state): State<AppState>,
+    Query(query): Query<RenewalCandidatesQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    let Some(ca) = state.puppet_ca.as_ref() else {
+        return Err(AppError::ServiceUnavailable(
+            "Puppet CA not configured".to_string(),
+        ));
+    };
+
+    let within_days = renewal_window(&state, query.within_days)?;
+    let certificates = ca.list_certificates().await?;
+    Ok(Json(renewal_candidates(
+        &certificates,
+        within_days,
+        chrono::Utc::now(),
+    )))
+}
+
+/// POST /api/v1/ca/renewals - Start a certificate renewal campaign
+///
+/// Requires the `certificates:admin` permission. Targets are the given
+/// certnames, or every signed certificate expiring within `within_days`.
+/// With `trigger_agents` a `cert_renewal` update job asks the agents to renew
+/// on their next run.
+///
+/// Request body:
+/// ```json
+/// {
+///   "within_days": 30,
+///   "deadline": "2026-11-01T00:00:00Z",
+///   "trigger_agents": true
+/// }
+/// ```
+async fn create_renewal_campaign(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(request): Json<CreateRenewalCampaignRequest>,
+) -> Result<impl IntoResponse, AppError> {
+    check_certificate_permission(&state, &auth_user, Action::Admin).await?;
+
+    let Some(ca) = state.puppet_ca.as_ref() else {
+        return Err(AppError::ServiceUnavailable(
+            "Puppet CA not configured".to_string(),
+        ));
+    };
+
+    let now = chrono::Utc::now();
+    let certificates = ca.list_certificates().await?;
+    let targets = if request.certnames.is_empty() {
+        let within_days = renewal_window(&state, request.within_days)?;
+        let targets = renewal_candidates(&certificates, within_days, now);
+        if targets.is_empty() {
+            return Err(AppError::bad_request(format!(
+                "No certificates expire within {} days",
+                within_days
+            )));
+        }
+        targets
+    } else {
+        let certnames = normalize_certnames(request.certnames)?;
+        // An explicit list may include certificates outside the expiry window
+        let candidates = renewal_candidates(&certificates, i64::MAX, now);
+        let missing: Vec<&str> = certnames
+            .iter()
+            .filter(|c| !candidates.iter().any(|t| &t.certname == *c))
+            .map(String::as_str)
+            .collect();
+        if !missing.is_empty() {
+            return Err(AppError::bad_request(format!(
+                "No valid signed certificate for: {}",
+                missing.join(", ")
+            )));
+        }
+        candidates
+            .into_iter()
+            .filter(|t| certnames.contains(&t.certname))
+            .collect()
+    };
+
+    let deadline = match request.deadline {
+        Some(deadline) => deadline,
+        None => targets.iter().map(|t| t.not_after).max().unwrap_or(now),
+    };
+    if deadline <= now {
+        return Err(AppError::bad_request("Deadline must be in the future"));
+    }
+
+    let name = request
+        .name
+        .map(|n| n.trim().to_string())
+        .filter(|n| !n.is_empty())
+        .unwrap_or_else(|| format!("Certificate renewal {}", now.format("%Y-%m-%d")));
+
+    let repo = CertRenewalRepository::new(&state.db);
+    let campaign = repo
+        .create(
+            &name,
+            deadline,
+            request.trigger_agents,
+            &auth_user.user_id().to_string(),
+            &targets,
+        )
+        .await?;
+
+    if request.trigger_agents {
+        let certnames: Vec<String> = targets.iter().map(|t| t.certname.clone()).collect();
+        let notes = format!("Renewal campaign '{}'", name);
+        let job = state
+            .inventory_repository()
+            .create_update_job(
+                UpdateOperationType::CertRenewal,
+                &[],
+                None,
+                &certnames,
+                false,
+                None,
+                None,
+                None,
+                &auth_user.username,
+                Some(&notes),
+            )
+            .await;
+
+        match job {
+            Ok(job) => repo.set_update_job(campaign.id, &job.id).await?,
+            Err(e) => {
+                tracing::error!("Failed to queue certificate renewal job: {}", e);
+                repo.finish(campaign.id, RenewalCampaignStatus::Cancelled)
+                    .await?;
+                return Err(AppError::internal(
+                    "Failed to queue certificate renewal job",
+                ));
+            }
+        }
+    }
+
+    tracing::info!(
+        "User '{}' started renewal campaign '{}' for {} certificate(s)",
+        auth_user.username,
+        name,
+        targets.len()
+    );
+
+    let detail = renewal_campaign_detail(&state, campaign.id, None).await?;
+    Ok((StatusCode::CREATED, Json(detail)))
+}
+
+/// GET /api/v1/ca/renewals/:id - Get a renewal campaign with its targets
+///
+/// Query parameter `status` filters targets (`pending`, `renewed`, `failed`).
+async fn get_renewal_campaign(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Query(query): Query<RenewalTargetsQuery>,
+) -> Result<impl IntoResponse, AppError> {
+    Ok(Json(
+        renewal_campaign_detail(&state, id, query.status).await?,
+    ))
+}
+
+/// POST /api/v1/ca/renewals/:id/check - Check a campaign against the CA now
+///
+/// Runs the same update as the background tracker.
+async fn check_renewal_campaign(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    let Some(ca) = state.puppet_ca.as_ref() else {
+        return Err(AppError::ServiceUnavailable(
+            "Puppet CA not configured".to_string(),
+        ));
+    };
+
+    let campaign = CertRenewalRepository::new(&state.db)
+        .get(id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Renewal campaign not found"))?;
+
+    if campaign.status == RenewalCampaignStatus::Active {
+        let certificates = ca.list_certificates().await?;
+        refresh_campaign(&state.db, &campaign, &certificates).await?;
+    }
+
+    Ok(Json(renewal_campaign_detail(&state, id, None).await?))
+}
+
+/// POST /api/v1/ca/renewals/:id/cancel - Stop tracking a renewal campaign
+///
+/// Requires the `certificates:admin` permission.
+async fn cancel_renewal_campaign(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<impl IntoResponse, AppError> {
+    check_certificate_permission(&state, &auth_user, Action::Admin).await?;
+
+    let repo = CertRenewalRepository::new(&state.db);
+    if repo.get(id).await?.is_none() {
+        return Err(AppError::not_found("Renewal campaign not found"));
+    }
+    if !repo.finish(id, RenewalCampaignStatus::Cancelled).await? {
+        return Err(AppError::bad_request("Renewal campaign is not active"));
+    }
+
+    tracing::info!(
+        "User '{}' cancelled renewal campaign {}",
+        auth_user.username,
+        id
+    );
+
+    Ok(Json(renewal_campaign_detail(&state, id, None).await?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_certnames() {
+        let certnames = normalize_certnames(vec![
+            " node1.example.com ".to_string(),
+            "node2.example.com".to_string(),
+            "node1.example.com".to_string(),
+            "".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(certnames, vec!["node1.example.com", "node2.example.com"]);
+
+        assert!(normalize_certnames(vec![" ".to_string()]).is_err());
+        assert!(normalize_certnames(
+            (0..=MAX_BULK_CERTNAMES)
+                .map(|i| format!("node{}", i))
+                .collect()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_etag_matches() {
+        let etag = "\"valid-1A-1700000000\"";
+        let mut headers = HeaderMap::new();
+        assert!(!etag_matches(&headers, etag));
+
+        headers.insert(
+            header::IF_NONE_MATCH,
+            "\"expired-1A-1700000000\", W/\"valid-1A-1700000000\""
+                .parse()
+                .unwrap(),
+        );
+        assert!(etag_matches(&headers, etag));
+
+        headers.insert(header::IF_NONE_MATCH, "*".parse().unwrap());
+        assert!(etag_matches(&headers, etag));
+    }
+
+    #[test]
+    fn test_certificate_status_check() {
+        let now = chrono::Utc::now();
+        let mut certificate = crate::models::Certificate {
+            certname: "web01".to_string(),
+            serial: "1A".to_string(),
+            not_before: now - chrono::Duration::days(30),
+            not_after: now + chrono::Duration::days(30),
+            dns_alt_names: Vec::new(),
+            fingerprint: String::new(),
+            state: crate::models::CertificateStatus::Signed,
+            revocation: None,
+        };
+
+        let check = CertificateStatusCheck::from_certificate("web01", Some(&certificate), now);
+        assert_eq!(check.status, CertificateValidity::Valid);
+        assert_eq!(check.serial.as_deref(), Some("1A"));
+
+        certificate.not_after = now - chrono::Duration::days(1);
+        let check = CertificateStatusCheck::from_certificate("web01", Some(&certificate), now);
+        assert_eq!(check.status, CertificateValidity::Expired);
+
+        certificate.state = crate::models::CertificateStatus::Revoked;
+        let check = CertificateStatusCheck::from_certificate("web01", Some(&certificate), now);
+        assert_eq!(check.status, CertificateValidity::Revoked);
+
+        certificate.state = crate::models::CertificateStatus::Requested;
+        let check = CertificateStatusCheck::from_certificate("web01", Some(&certificate), now);
+        assert_eq!(check.status, CertificateValidity::Unknown);
+        assert_eq!(check.serial, None);
+
+        let check = CertificateStatusCheck::from_certificate("web02", None, now);
+        assert_eq!(check.status, CertificateValidity::Unknown);
+        assert_eq!(check.etag(), "\"unknown-none-0\"");
+    }
+}
@@ -0,0 +1,385 @@
+//! Classification key management endpoints
+//!
+//! Keys authenticate Puppet Server and other clients to the classification
+//! and node endpoints through the `X-Classification-Key` header. Classification
+//! spans all organizations, so keys are managed by super admins.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, post, put},
+    Json, Router,
+};
+use base64::Engine;
+use rand::Rng;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    db::ClassificationKeyRepository,
+    middleware::AuthUser,
+    models::{
+        ClassificationKey, CreateClassificationKeyRequest, CreateClassificationKeyResponse,
+        UpdateClassificationKeyRequest,
+    },
+    services::AuthService,
+    utils::AppError,
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_keys).post(create_key))
+        .route("/{id}", put(update_key))
+        .route("/{id}/rotate", post(rotate_key))
+        .route("/{id}/revoke", post(revoke_key))
+}
+
+/// Prefix of managed classification keys
+pub const KEY_PREFIX: &str = "ock_";
+
+/// Longest grace period for the previous secret of a rotated key (7 days)
+const MAX_ROTATION_GRACE_MINUTES: u64 = 7 * 24 * 60;
+
+#[derive(Debug, Deserialize, Default)]
+struct RotateKeyQuery {
+    /// How long the current secret keeps working; it stops immediately by
+    /// default
+    #[serde(default)]
+    grace_period_minutes: u64,
+}
+
+pub(crate) fn require_super_admin(auth_user: &AuthUser) -> Result<(), AppError> {
+    if auth_user.is_super_admin() {
+        Ok(())
+    } else {
+        Err(AppError::forbidden(
+            "Classification keys can only be managed by super_admin",
+        ))
+    }
+}
+
+/// Trim the patterns
+///
+/// A blank pattern is refused rather than dropped: dropping the only pattern
+/// would leave an empty list, which allows any node.
+fn normalize_patterns(patterns: Option<Vec<String>>) -> Result<Option<Vec<String>>, AppError> {
+    patterns
+        .map(|patterns| {
+            patterns
+                .into_iter()
+                .map(|p| match p.trim() {
+                    "" => Err(AppError::bad_request(
+                        "allowed_cn_patterns cannot contain empty patterns",
+                    )),
+                    p => Ok(p.to_string()),
+                })
+                .collect()
+        })
+        .transpose()
+}
+
+/// Generate a plaintext key and the hash stored for it
+///
+/// The key embeds its id so it can be looked up without scanning every hash.
+fn generate_key(id: Uuid) -> Result<(String, String), AppError> {
+    let mut secret_bytes = [0u8; 32];
+    rand::rng().fill_bytes(&mut secret_bytes);
+    let secret = base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(secret_bytes);
+    let key = format!("{}{}_{}", KEY_PREFIX, id, secret);
+
+    let key_hash = AuthService::hash_password(&secret).map_err(|e| {
+        tracing::error!("Failed to hash classification key: {}", e);
+        AppError::internal("Failed to generate classification key")
+    })?;
+
+    Ok((key, key_hash))
+}
+
+/// Split a managed key into its id and secret
+fn parse_key(key: &str) -> Option<(Uuid, &str)> {
+    let (id, secret) = key.strip_prefix(KEY_PREFIX)?.split_once('_')?;
+    let id = Uuid::parse_str(id).ok()?;
+    (!secret.is_empty()).then_some((id, secret))
+}
+
+async fn audit(
+    state: &AppState,
+    auth_user: &AuthUser,
+    action: &str,
+    id: Uuid,
+    details: serde_json::Value,
+) {
+    let _ = state
+        .audit_repository()
+        .insert(
+            auth_user.organization_id,
+            Some(auth_user.user_id()),
+            action,
+            "classification_keys",
+            Some(&id.to_string()),
+            Some(&details),
+            None,
+        )
+        .await;
+}
+
+async fn list_keys(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<ClassificationKey>>, AppError> {
+    require_super_admin(&auth_user)?;
+
+    let keys = ClassificationKeyRepository::new(&state.db)
+        .list()
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list classification keys: {}", e);
+            AppError::internal("Failed to list classification keys")
+        })?;
+    Ok(Json(keys))
+}
+
+async fn create_key(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<CreateClassificationKeyRequest>,
+) -> Result<(StatusCode, Json<CreateClassificationKeyResponse>), AppError> {
+    require_super_admin(&auth_user)?;
+    let created = issue_key(&state, &auth_user, payload).await?;
+    Ok((StatusCode::CREATED, Json(created)))
+}
+
+/// Create a key and return it with its plaintext secret
+///
+/// Callers check that the user is a super admin.
+pub(crate) async fn issue_key(
+    state: &AppState,
+    auth_user: &AuthUser,
+    mut payload: CreateClassificationKeyRequest,
+) -> Result<CreateClassificationKeyResponse, AppError> {
+    if payload.name.trim().is_empty() {
+        return Err(AppError::validation("name is required"));
+    }
+    payload.allowed_cn_patterns = normalize_patterns(payload.allowed_cn_patterns)?;
+
+    let id = Uuid::new_v4();
+    let (key, key_hash) = generate_key(id)?;
+    let classification_key = ClassificationKeyRepository::new(&state.db)
+        .create(id, &payload, &key_hash, auth_user.user_id())
+        .await
+        .map_err(|e| {
+            if format!("{:#}", e).contains("UNIQUE constraint failed") {
+                AppError::conflict("A classification key with this name already exists")
+            } else {
+                tracing::error!("Failed to create classification key: {}", e);
+                AppError::internal("Failed to create classification key")
+            }
+        })?;
+
+    audit(
+        state,
+        auth_user,
+        "classification_key.create",
+        id,
+        serde_json::json!({
+            "name": classification_key.name,
+            "allowed_cn_patterns": classification_key.allowed_cn_patterns,
+        }),
+    )
+    .await;
+
+    Ok(CreateClassificationKeyResponse {
+        classification_key,
+        key,
+    })
+}
+
+/// Look up the managed key a presented X-Classification-Key belongs to
+///
+/// Returns `None` for anything that is not a current key, including revoked
+/// keys and secrets whose rotation grace period is over.
+pub(crate) async fn find_key(
+    state: &AppState,
+    key: &str,
+) -> Result<Option<ClassificationKey>, AppError> {
+    let Some((key_id, secret)) = parse_key(key) else {
+        return Ok(None);
+    };
+
+    let secrets = ClassificationKeyRepository::new(&state.db)
+        .get_secrets(key_id)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to check classification key: {}", e)))?;
+    let Some(secrets) = secrets else {
+        return Ok(None);
+    };
+
+    let matches = |hash: &str| AuthService::verify_password(secret, hash).unwrap_or(false);
+    if matches(&secrets.key_hash) || secrets.previous_key_hash.as_deref().is_some_and(matches) {
+        Ok(Some(secrets.key))
+    } else {
+        Ok(None)
+    }
+}
+
+async fn update_key(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(mut payload): Json<UpdateClassificationKeyRequest>,
+) -> Result<Json<ClassificationKey>, AppError> {
+    require_super_admin(&auth_user)?;
+    payload.allowed_cn_patterns = normalize_patterns(payload.allowed_cn_patterns)?;
+
+    let classification_key = ClassificationKeyRepository::new(&state.db)
+        .update(id, &payload)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to update classification key: {}", e);
+            AppError::internal("Failed to update classification key")
+        })?
+        .ok_or_else(|| AppError::not_found("Classification key not found"))?;
+
+    audit(
+        &state,
+        &auth_user,
+        "classification_key.update",
+        id,
+        serde_json::json!({
+            "description": classification_key.description,
+            "allowed_cn_patterns": classification_key.allowed_cn_patterns,
+        }),
+    )
+    .await;
+
+    Ok(Json(classification_key))
+}
+
+/// Issue a new secret for a key
+///
+/// With `grace_period_minutes` the current secret keeps working for that
+/// long so clients can switch to the new one without downtime.
+async fn rotate_key(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+    Query(query): Query<RotateKeyQuery>,
+) -> Result<Json<CreateClassificationKeyResponse>, AppError> {
+    require_super_admin(&auth_user)?;
+    if query.grace_period_minutes > MAX_ROTATION_GRACE_MINUTES {
+        return Err(AppError::validation(format!(
+            "grace_period_minutes cannot exceed {}",
+            MAX_ROTATION_GRACE_MINUTES
+        )));
+    }
+
+    let (key, key_hash) = generate_key(id)?;
+    let grace_until = (query.grace_period_minutes > 0)
+        .then(|| chrono::Utc::now() + chrono::Duration::minutes(query.grace_period_minutes as i64));
+
+    let repo = ClassificationKeyRepository::new(&state.db);
+    let internal = |e: anyhow::Error| {
+        tracing::error!("Failed to rotate classification key: {}", e);
+        AppError::internal("Failed to rotate classification key")
+    };
+    if !repo
+        .rotate(id, &key_hash, grace_until)
+        .await
+        .map_err(internal)?
+    {
+        return Err(AppError::not_found("Classification key not found"));
+    }
+    let classification_key = repo
+        .get_by_id(id)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| AppError::not_found("Classification key not found"))?;
+
+    audit(
+        &state,
+        &auth_user,
+        "classification_key.rotate",
+        id,
+        serde_json::json!({
+            "grace_period_minutes": query.grace_period_minutes,
+            "previous_key_expires_at": grace_until,
+        }),
+    )
+    .await;
+
+    Ok(Json(CreateClassificationKeyResponse {
+        classification_key,
+        key,
+    }))
+}
+
+/// Stop accepting a key right away
+async fn revoke_key(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    require_super_admin(&auth_user)?;
+
+    let revoked = ClassificationKeyRepository::new(&state.db)
+        .revoke(id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to revoke classification key: {}", e);
+            AppError::internal("Failed to revoke classification key")
+        })?;
+    if !revoked {
+        return Err(AppError::not_found("Classification key not found"));
+    }
+
+    audit(
+        &state,
+        &auth_user,
+        "classification_key.revoke",
+        id,
+        serde_json::json!({}),
+    )
+    .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_key_round_trips_generated_keys() {
+        let id = Uuid::new_v4();
+        let key = format!("{}{}_c2VjcmV0", KEY_PREFIX, id);
+        assert_eq!(parse_key(&key), Some((id, "c2VjcmV0")));
+    }
+
+    #[test]
+    fn test_parse_key_rejects_other_keys() {
+        assert_eq!(parse_key("abc123"), None);
+        assert_eq!(parse_key(&format!("ovk_{}_secret", Uuid::new_v4())), None);
+        assert_eq!(
+            parse_key(&format!("{}{}_", KEY_PREFIX, Uuid::new_v4())),
+            None
+        );
+    }
+
+    #[test]
+    fn test_normalize_patterns() {
+        let patterns = |p: &[&str]| Some(p.iter().map(|p| p.to_string()).collect::<Vec<_>>());
+        assert_eq!(normalize_patterns(None).unwrap(), None);
+        assert_eq!(normalize_patterns(patterns(&[])).unwrap(), patterns(&[]));
+        assert_eq!(
+            normalize_patterns(patterns(&[" web*.example.com "])).unwrap(),
+            patterns(&["web*.example.com"])
+        );
+        for blank in [vec![""], vec![" "], vec!["web*", "\t"]] {
+            assert!(matches!(
+                normalize_patterns(patterns(&blank)),
+                Err(AppError::BadRequest(_))
+            ));
+        }
+    }
+}
@@ -14,11 +14,13 @@ use uuid::Uuid;
 use crate::{
     middleware::AuthUser,
     models::{
-        ApproveDeploymentRequest, CodeDeploymentResponse, CodeEnvironmentResponse,
+        ApproveDeploymentRequest, CodeCanary, CodeDeploymentResponse, CodeEnvironmentResponse,
         CodePatTokenResponse, CodeRepositoryResponse, CodeSshKeyResponse, CreatePatTokenRequest,
-        CreateRepositoryRequest, CreateSshKeyRequest, ListDeploymentsQuery, ListEnvironmentsQuery,
-        RejectDeploymentRequest, TriggerDeploymentRequest, UpdateEnvironmentRequest,
-        UpdatePatTokenRequest, UpdateRepositoryRequest,
+        CreateRepositoryRequest, CreateSshKeyRequest, CredentialHealthReport, DeploymentImpact,
+        GenerateSshKeyRequest, ListDeploymentsQuery, ListEnvironmentsQuery,
+        RejectDeploymentRequest, RollbackCanaryRequest, StartCanaryRequest,
+        TriggerDeploymentRequest, UpdateEnvironmentRequest, UpdatePatTokenRequest,
+        UpdateRepositoryRequest,
     },
     utils::AppError,
     AppState,
@@ -30,6 +32,7 @@ pub fn routes() -> Router<AppState> {
         .route("/status", get(get_feature_status))
         // SSH Keys
         .route("/ssh-keys", get(list_ssh_keys).post(create_ssh_key))
+        .route("/ssh-keys/generate", post(generate_ssh_key))
         .route("/ssh-keys/{id}", get(get_ssh_key).delete(delete_ssh_key))
         // PAT Tokens
         .route("/pat-tokens", get(list_pat_tokens).post(create_pat_token))
@@ -40,6 +43,8 @@ pub fn routes() -> Router<AppState> {
                 .delete(delete_pat_token),
         )
         .route("/pat-tokens/expiring", get(list_expiring_pat_tokens))
+        // Credential health
+        .route("/credentials/health", get(get_credential_health))
         // Repositories
         .route(
             "/repositories",
@@ -68,6 +73,13 @@ pub fn routes() -> Router<AppState> {
             get(list_deployments).post(trigger_deployment),
         )
         .route("/deployments/{id}", get(get_deployment))
+        .route("/deployments/{id}/impact", get(get_deployment_impact))
+        .route(
+            "/deployments/{id}/canary",
+            get(get_canary).post(start_canary),
+        )
+        .route("/deployments/{id}/canary/promote", post(promote_canary))
+        .route("/deployments/{id}/canary/rollback", post(rollback_canary))
         .route("/deployments/{id}/approve", post(approve_deployment))
         .route("/deployments/{id}/reject", post(reject_deployment))
         .route("/deployments/{id}/cancel", post(cancel_deployment))
@@ -173,6 +185,32 @@ async fn create_ssh_key(
     Ok((StatusCode::CREATED, Json(key)))
 }
 
+/// Generate an Ed25519 keypair server-side
+///
+/// The private key never leaves the server; the response only contains the
+/// public key to register as a deploy key with the Git hosting provider.
+async fn generate_ssh_key(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<GenerateSshKeyRequest>,
+) -> Result<(StatusCode, Json<CodeSshKeyResponse>), AppError> {
+    require_permission(&auth_user, "code_ssh_key_manage")?;
+
+    let service = state.code_deploy_service()?;
+    let key = service.generate_ssh_key(&payload).await.map_err(|e| {
+        tracing::error!("Failed to generate SSH key: {}", e);
+        if e.to_string().contains("already exists") {
+            AppError::conflict("SSH key with this name already exists")
+        } else if e.to_string().contains("cannot be empty") {
+            AppError::bad_request(e.to_string())
+        } else {
+            AppError::internal("Failed to generate SSH key")
+        }
+    })?;
+
+    Ok((StatusCode::CREATED, Json(key)))
+}
+
 async fn delete_ssh_key(
     State(state): State<AppState>,
     auth_user: AuthUser,
@@ -333,6 +371,25 @@ async fn list_expiring_pat_tokens(
     Ok(Json(tokens))
 }
 
+// ============================================================================
+// Credential Health Handlers
+// ============================================================================
+
+async fn get_credential_health(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<CredentialHealthReport>, AppError> {
+    require_permission(&auth_user, "code_credential_view")?;
+
+    let service = state.code_deploy_service()?;
+    let report = service.credential_health().await.map_err(|e| {
+        tracing::error!("Failed to assess credential health: {}", e);
+        AppError::internal("Failed to assess credential health")
+    })?;
+
+    Ok(Json(report))
+}
+
 // ============================================================================
 // Repository Handlers
 // ============================================================================
@@ -596,6 +653,112 @@ async fn get_deployment(
     Ok(Json(deployment))
 }
 
+/// Get the modules and classes a deployment changes and how many nodes use them
+async fn get_deployment_impact(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<DeploymentImpact>, AppError> {
+    require_permission(&auth_user, "code_deployment_view")?;
+
+    let service = state.code_deploy_service()?;
+    let puppetdb = state.puppetdb_for(auth_user.organization_id).await;
+    let impact = service
+        .deployment_impact(id, puppetdb.as_deref())
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to compute deployment impact: {:#}", e);
+            AppError::internal(&format!("Failed to compute deployment impact: {:#}", e))
+        })?
+        .ok_or_else(|| AppError::not_found("Deployment not found"))?;
+
+    Ok(Json(impact))
+}
+
+/// Get the latest canary of a deployment
+async fn get_canary(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<CodeCanary>, AppError> {
+    require_permission(&auth_user, "code_deployment_view")?;
+
+    let service = state.code_deploy_service()?;
+    let canary = service
+        .get_canary(id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get canary: {}", e);
+            AppError::internal("Failed to get canary")
+        })?
+        .ok_or_else(|| AppError::not_found("Canary not found"))?;
+
+    Ok(Json(canary))
+}
+
+/// Deploy a pending deployment to a temporary environment and pin a sample
+/// of a group's nodes to it
+async fn start_canary(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<StartCanaryRequest>,
+) -> Result<(StatusCode, Json<CodeCanary>), AppError> {
+    require_permission(&auth_user, "code_deployment_trigger")?;
+
+    let service = state.code_deploy_service()?;
+    let puppetdb = state.puppetdb_for(auth_user.organization_id).await;
+    let canary = service
+        .start_canary(
+            id,
+            auth_user.organization_id,
+            &payload,
+            puppetdb.as_deref(),
+            auth_user.user_id(),
+        )
+        .await
+        .map_err(canary_error)?;
+
+    Ok((StatusCode::CREATED, Json(canary)))
+}
+
+/// Approve the deployment of a passed canary and release its nodes
+async fn promote_canary(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<Json<CodeCanary>, AppError> {
+    require_permission(&auth_user, "code_deployment_approve")?;
+
+    let service = state.code_deploy_service()?;
+    let canary = service
+        .promote_canary(id, auth_user.user_id())
+        .await
+        .map_err(canary_error)?
+        .ok_or_else(|| AppError::not_found("Canary not found"))?;
+
+    Ok(Json(canary))
+}
+
+/// Reject the deployment of a canary and release its nodes
+async fn rollback_canary(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<RollbackCanaryRequest>,
+) -> Result<Json<CodeCanary>, AppError> {
+    require_permission(&auth_user, "code_deployment_approve")?;
+
+    let service = state.code_deploy_service()?;
+    let canary = service
+        .rollback_canary(id, auth_user.user_id(), payload.reason.as_deref())
+        .await
+        .map_err(canary_error)?
+        .ok_or_else(|| AppError::not_found("Canary not found"))?;
+
+    Ok(Json(canary))
+}
+
 async fn trigger_deployment(
     State(state): State<AppState>,
     auth_user: AuthUser,
@@ -608,6 +771,7 @@ async fn trigger_deployment(
         .trigger_deployment(
             payload.environment_id,
             payload.commit_sha.as_deref(),
+            &payload.options,
             Some(auth_user.user_id()),
         )
         .await
@@ -615,6 +779,8 @@ async fn trigger_deployment(
             tracing::error!("Failed to trigger deployment: {}", e);
             if e.to_string().contains("not found") {
                 AppError::not_found("Environment not found")
+            } else if e.to_string().starts_with("Invalid deployment options") {
+                AppError::bad_request(&e.to_string())
             } else {
                 AppError::internal(&format!("Failed to trigger deployment: {}", e))
             }
@@ -966,6 +1132,18 @@ async fn handle_bitbucket_webhook(
 // Helper Functions
 // ============================================================================
 
+fn canary_error(e: anyhow::Error) -> AppError {
+    tracing::error!("Canary operation failed: {:#}", e);
+    let message = e.to_string();
+    if message.ends_with("not found") {
+        AppError::not_found(&message)
+    } else if message.starts_with("Invalid canary") {
+        AppError::bad_request(&message)
+    } else {
+        AppError::internal(&format!("Canary operation failed: {:#}", e))
+    }
+}
+
 fn require_permission(auth_user: &AuthUser, _permission: &str) -> Result<(), AppError> {
     // Super admins have all permissions
     if auth_user.is_super_admin() {
@@ -0,0 +1,486 @@
+//! Privilege elevation endpoints
+//!
+//! Any user can ask for a higher role for a limited time. Users with the
+//! `admin` action on roles approve or deny requests, but never their own. Once
+//! approved, the requester activates the elevation to get an access token
+//! carrying the role; the token expires together with the elevation. Every
+//! step is written to the audit log.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{Duration, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    db::RoleElevationRepository,
+    middleware::{
+        auth::{create_access_token_until, revoke_user_auth_sessions, AuthUser},
+        session_cookie::{self, SetCookies},
+    },
+    models::{
+        Action, CreateElevationRequest, ElevationDecisionRequest, ElevationStatus, Resource,
+        RoleElevation, SystemRole, TokenResponse, MAX_ELEVATION_HOURS,
+    },
+    services::{elevation::token_grant, AuthService},
+    utils::AppError,
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_elevations).post(request_elevation))
+        .route("/{id}", get(get_elevation))
+        .route("/{id}/approve", post(approve_elevation))
+        .route("/{id}/deny", post(deny_elevation))
+        .route("/{id}/revoke", post(revoke_elevation))
+        .route("/{id}/activate", post(activate_elevation))
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct OrgQuery {
+    organization_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct ListElevationsQuery {
+    organization_id: Option<Uuid>,
+    user_id: Option<Uuid>,
+    status: Option<ElevationStatus>,
+}
+
+fn resolve_org(auth_user: &AuthUser, requested: Option<Uuid>) -> Result<Uuid, AppError> {
+    match requested {
+        Some(_) if !auth_user.is_super_admin() => Err(AppError::forbidden(
+            "organization_id can only be specified by super_admin",
+        )),
+        Some(org_id) => Ok(org_id),
+        None => Ok(auth_user.organization_id),
+    }
+}
+
+/// Whether the user may approve, deny and revoke elevations
+async fn is_approver(state: &AppState, auth_user: &AuthUser) -> Result<bool, AppError> {
+    if auth_user.is_super_admin() {
+        return Ok(true);
+    }
+
+    let check = state
+        .rbac_db
+        .check_permission(
+            &auth_user.user_id(),
+            Resource::Roles,
+            Action::Admin,
+            None,
+            None,
+        )
+        .await
+        .map_err(|e| AppError::internal(format!("Permission check failed: {}", e)))?;
+    Ok(check.allowed)
+}
+
+async fn require_approver(state: &AppState, auth_user: &AuthUser) -> Result<(), AppError> {
+    if is_approver(state, auth_user).await? {
+        Ok(())
+    } else {
+        Err(AppError::forbidden(
+            "Admin permission on roles required to decide on elevations",
+        ))
+    }
+}
+
+async fn load_elevation(
+    state: &AppState,
+    org_id: Uuid,
+    id: Uuid,
+) -> Result<RoleElevation, AppError> {
+    RoleElevationRepository::new(&state.db)
+        .get_by_id(org_id, id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get role elevation: {}", e);
+            AppError::internal("Failed to get role elevation")
+        })?
+        .ok_or_else(|| AppError::not_found("Role elevation not found"))
+}
+
+async fn audit(
+    state: &AppState,
+    auth_user: &AuthUser,
+    action: &str,
+    elevation: &RoleElevation,
+    comment: Option<&str>,
+) {
+    let _ = state
+        .audit_repository()
+        .insert(
+            elevation.organization_id,
+            Some(auth_user.user_id()),
+            action,
+            "role_elevations",
+            Some(&elevation.id.to_string()),
+            Some(&serde_json::json!({
+                "user_id": elevation.user_id,
+                "role": elevation.role_name,
+                "duration_hours": elevation.duration_hours,
+                "reason": elevation.reason,
+                "comment": comment,
+                "expires_at": elevation.expires_at,
+            })),
+            None,
+        )
+        .await;
+}
+
+/// List elevations
+///
+/// GET /api/v1/elevations
+///
+/// Approvers see every elevation of the organization; other users only
+/// their own.
+async fn list_elevations(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<ListElevationsQuery>,
+) -> Result<Json<Vec<RoleElevation>>, AppError> {
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let user_id = if is_approver(&state, &auth_user).await? {
+        query.user_id
+    } else {
+        Some(auth_user.user_id())
+    };
+
+    let elevations = RoleElevationRepository::new(&state.db)
+        .list(org_id, user_id, query.status)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list role elevations: {}", e);
+            AppError::internal("Failed to list role elevations")
+        })?;
+    Ok(Json(elevations))
+}
+
+/// Ask for a temporary role
+///
+/// POST /api/v1/elevations
+async fn request_elevation(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(payload): Json<CreateElevationRequest>,
+) -> Result<(StatusCode, Json<RoleElevation>), AppError> {
+    if payload.duration_hours == 0 || payload.duration_hours > MAX_ELEVATION_HOURS {
+        return Err(AppError::validation(format!(
+            "duration_hours must be between 1 and {}",
+            MAX_ELEVATION_HOURS
+        )));
+    }
+    if payload.reason.trim().is_empty() {
+        return Err(AppError::validation("A reason is required"));
+    }
+
+    state
+        .rbac_db
+        .get_role(&payload.role_id)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to get role: {}", e)))?
+        .ok_or_else(|| AppError::validation("Role not found"))?;
+
+    let user_id = auth_user.user_id();
+    let held = state
+        .rbac_db
+        .get_user_role_ids(&user_id)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to get user roles: {}", e)))?;
+    if held.contains(&payload.role_id) {
+        return Err(AppError::conflict("You already hold this role"));
+    }
+
+    let repo = RoleElevationRepository::new(&state.db);
+    let open = repo
+        .find_open(user_id, payload.role_id, Utc::now())
+        .await
+        .map_err(|e| AppError::internal(e.to_string()))?;
+    if open.is_some() {
+        return Err(AppError::conflict(
+            "An elevation to this role is already pending or active",
+        ));
+    }
+
+    let elevation = repo
+        .create(auth_user.organization_id, user_id, &payload)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to create role elevation: {}", e);
+            AppError::internal("Failed to create role elevation")
+        })?;
+
+    audit(
+        &state,
+        &auth_user,
+        "role_elevation.request",
+        &elevation,
+        None,
+    )
+    .await;
+    Ok((StatusCode::CREATED, Json(elevation)))
+}
+
+/// Get an elevation
+///
+/// GET /api/v1/elevations/{id}
+async fn get_elevation(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RoleElevation>, AppError> {
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let elevation = load_elevation(&state, org_id, id).await?;
+    if elevation.user_id != auth_user.user_id() && !is_approver(&state, &auth_user).await? {
+        return Err(AppError::not_found("Role elevation not found"));
+    }
+    Ok(Json(elevation))
+}
+
+/// Approve a pending elevation; its time starts now
+///
+/// POST /api/v1/elevations/{id}/approve
+async fn approve_elevation(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ElevationDecisionRequest>,
+) -> Result<Json<RoleElevation>, AppError> {
+    require_approver(&state, &auth_user).await?;
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+
+    let elevation = load_elevation(&state, org_id, id).await?;
+    if elevation.status != ElevationStatus::Pending {
+        return Err(AppError::conflict(
+            "Only pending elevations can be approved",
+        ));
+    }
+    if elevation.user_id == auth_user.user_id() {
+        return Err(AppError::forbidden("You cannot approve your own elevation"));
+    }
+    if elevation.role_id == SystemRole::SuperAdmin.uuid() && !auth_user.is_super_admin() {
+        return Err(AppError::forbidden(
+            "Only a super_admin can approve elevation to super_admin",
+        ));
+    }
+
+    let expires_at = Utc::now() + Duration::hours(elevation.duration_hours as i64);
+    let approved = RoleElevationRepository::new(&state.db)
+        .approve(
+            org_id,
+            id,
+            auth_user.user_id(),
+            payload.comment.as_deref(),
+            expires_at,
+        )
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to approve role elevation: {}", e);
+            AppError::internal("Failed to approve role elevation")
+        })?;
+    if !approved {
+        return Err(AppError::conflict(
+            "Only pending elevations can be approved",
+        ));
+    }
+
+    state.rbac_db.invalidate_user_cache(&elevation.user_id);
+    let elevation = load_elevation(&state, org_id, id).await?;
+    audit(
+        &state,
+        &auth_user,
+        "role_elevation.approve",
+        &elevation,
+        payload.comment.as_deref(),
+    )
+    .await;
+    Ok(Json(elevation))
+}
+
+/// Deny a pending elevation
+///
+/// POST /api/v1/elevations/{id}/deny
+async fn deny_elevation(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ElevationDecisionRequest>,
+) -> Result<Json<RoleElevation>, AppError> {
+    require_approver(&state, &auth_user).await?;
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+
+    let closed = RoleElevationRepository::new(&state.db)
+        .close(
+            org_id,
+            id,
+            ElevationStatus::Pending,
+            ElevationStatus::Denied,
+            auth_user.user_id(),
+            payload.comment.as_deref(),
+        )
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to deny role elevation: {}", e);
+            AppError::internal("Failed to deny role elevation")
+        })?;
+
+    let elevation = load_elevation(&state, org_id, id).await?;
+    if !closed {
+        return Err(AppError::conflict("Only pending elevations can be denied"));
+    }
+
+    audit(
+        &state,
+        &auth_user,
+        "role_elevation.deny",
+        &elevation,
+        payload.comment.as_deref(),
+    )
+    .await;
+    Ok(Json(elevation))
+}
+
+/// End an approved elevation early
+///
+/// POST /api/v1/elevations/{id}/revoke
+///
+/// Allowed for the requester and for approvers. The requester's sessions are
+/// revoked too, so tokens carrying the role stop working immediately.
+async fn revoke_elevation(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<ElevationDecisionRequest>,
+) -> Result<Json<RoleElevation>, AppError> {
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+
+    let elevation = load_elevation(&state, org_id, id).await?;
+    if elevation.user_id != auth_user.user_id() {
+        require_approver(&state, &auth_user).await?;
+    }
+
+    let closed = RoleElevationRepository::new(&state.db)
+        .close(
+            org_id,
+            id,
+            ElevationStatus::Approved,
+            ElevationStatus::Revoked,
+            auth_user.user_id(),
+            payload.comment.as_deref(),
+        )
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to revoke role elevation: {}", e);
+            AppError::internal("Failed to revoke role elevation")
+        })?;
+    if !closed {
+        return Err(AppError::conflict(
+            "Only approved elevations can be revoked",
+        ));
+    }
+
+    state.rbac_db.invalidate_user_cache(&elevation.user_id);
+    if let Err(e) = revoke_user_auth_sessions(&state.db, &elevation.user_id).await {
+        tracing::error!(
+            "Failed to revoke sessions of user {}: {}",
+            elevation.user_id,
+            e
+        );
+    }
+
+    let elevation = load_elevation(&state, org_id, id).await?;
+    audit(
+        &state,
+        &auth_user,
+        "role_elevation.revoke",
+        &elevation,
+        payload.comment.as_deref(),
+    )
+    .await;
+    Ok(Json(elevation))
+}
+
+/// Reissue the requester's access token with the elevated role
+///
+/// POST /api/v1/elevations/{id}/activate
+///
+/// The new token belongs to the current session and expires when the
+/// elevation does (or earlier, at the regular token expiry). In cookie auth
+/// mode it replaces the session cookie instead.
+async fn activate_elevation(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<(SetCookies, Json<TokenResponse>), AppError> {
+    let elevation = load_elevation(&state, auth_user.organization_id, id).await?;
+    if elevation.user_id != auth_user.user_id() {
+        return Err(AppError::not_found("Role elevation not found"));
+    }
+    if !elevation.is_active(Utc::now()) {
+        return Err(AppError::conflict("The elevation is not active"));
+    }
+
+    let session_id = Uuid::parse_str(&auth_user.session_id).map_err(|_| {
+        AppError::bad_request("Elevations can only be activated from a login session")
+    })?;
+
+    let roles = AuthService::new(state.db.clone())
+        .get_user_roles(&auth_user.user_id())
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to get user roles: {}", e)))?;
+    let grant = token_grant(
+        &state.db,
+        auth_user.user_id(),
+        roles,
+        Utc::now() + Duration::hours(state.config.auth.token_expiry_hours as i64),
+    )
+    .await;
+
+    let mut access_token = create_access_token_until(
+        &auth_user.user_id(),
+        &auth_user.organization_id,
+        &session_id,
+        &auth_user.username,
+        &auth_user.email,
+        grant.roles.clone(),
+        &state.config.auth.jwt_secret,
+        &state.jwt_keys,
+        grant.expires_at,
+    )
+    .map_err(|e| AppError::internal(format!("Failed to create access token: {}", e)))?;
+
+    audit(
+        &state,
+        &auth_user,
+        "role_elevation.activate",
+        &elevation,
+        None,
+    )
+    .await;
+    let expires_in = grant.expires_in(Utc::now());
+    let cookies = session_cookie::access_cookie(&state.config, &access_token, expires_in);
+    if state.config.auth.cookie_mode() {
+        access_token.clear();
+    }
+    Ok((
+        cookies,
+        Json(TokenResponse {
+            access_token,
+            refresh_token: None,
+            token_type: "Bearer".to_string(),
+            expires_in,
+        }),
+    ))
+}
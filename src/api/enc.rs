@@ -0,0 +1,253 @@
+//! ENC script endpoints
+//!
+//! `/enc/bootstrap` hands super admins an External Node Classifier script for
+//! Puppet Server, preconfigured with the WebUI URL and a classification key
+//! minted for it. The script's `--ping` mode calls `/enc/ping`, which records
+//! a handshake on the key so the `puppet.conf` setup can be checked from the
+//! WebUI.
+
+use axum::{
+    extract::{Query, State},
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::classification_keys;
+use crate::{
+    db::ClassificationKeyRepository, middleware::AuthUser, models::CreateClassificationKeyRequest,
+    utils::AppError, AppState,
+};
+
+/// Protected routes
+pub fn routes() -> Router<AppState> {
+    Router::new().route("/bootstrap", get(get_enc_script))
+}
+
+/// Public routes (authenticated with the X-Classification-Key header)
+pub fn public_routes() -> Router<AppState> {
+    Router::new().route("/ping", get(ping))
+}
+
+/// Longest host name recorded for a handshake
+const MAX_HOST_LEN: usize = 255;
+
+#[derive(Debug, Deserialize, Default)]
+struct EncScriptQuery {
+    /// Name of the classification key minted for the script
+    name: Option<String>,
+    /// URL the script reaches OpenVox WebUI at; derived from the request
+    /// when omitted
+    webui_url: Option<String>,
+    /// Verify the WebUI TLS certificate (default true)
+    ssl_verify: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct PingQuery {
+    /// Host running the ENC script
+    host: Option<String>,
+}
+
+/// Response to an ENC handshake
+#[derive(Debug, Serialize)]
+pub struct EncPingResponse {
+    pub status: String,
+    /// Managed key that authenticated the handshake; `None` for the
+    /// configured shared key
+    pub key_id: Option<Uuid>,
+    pub key_name: Option<String>,
+    /// Certname patterns the key may classify; empty allows any node
+    pub allowed_cn_patterns: Vec<String>,
+    pub version: String,
+    pub server_time: DateTime<Utc>,
+}
+
+/// GET /api/v1/enc/bootstrap
+///
+/// Returns the ENC script with a new classification key. The key is shown
+/// once, inside the script; its id is in the `X-Classification-Key-Id`
+/// header.
+async fn get_enc_script(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    headers: HeaderMap,
+    Query(query): Query<EncScriptQuery>,
+) -> Result<Response, AppError> {
+    classification_keys::require_super_admin(&auth_user)?;
+
+    let webui_url = match query.webui_url {
+        Some(url) if !url.starts_with("http://") && !url.starts_with("https://") => {
+            return Err(AppError::validation(
+                "webui_url must start with http:// or https://",
+            ));
+        }
+        Some(url) => url.trim_end_matches('/').to_string(),
+        None => default_webui_url(&state, &headers),
+    };
+    let name = query
+        .name
+        .unwrap_or_else(|| format!("puppet-enc-{}", Utc::now().format("%Y%m%d%H%M%S")));
+
+    let created = classification_keys::issue_key(
+        &state,
+        &auth_user,
+        CreateClassificationKeyRequest {
+            name,
+            description: Some("ENC script generated by /enc/bootstrap".to_string()),
+            allowed_cn_patterns: None,
+        },
+    )
+    .await?;
+
+    let script = generate_enc_script(&webui_url, &created.key, query.ssl_verify.unwrap_or(true));
+    let key_id = HeaderValue::from_str(&created.classification_key.id.to_string())
+        .map_err(|e| AppError::internal(e.to_string()))?;
+
+    Ok((
+        StatusCode::OK,
+        [
+            (
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("text/x-shellscript; charset=utf-8"),
+            ),
+            (
+                header::CONTENT_DISPOSITION,
+                HeaderValue::from_static("attachment; filename=\"openvox-enc.sh\""),
+            ),
+            (HeaderName::from_static("x-classification-key-id"), key_id),
+        ],
+        script,
+    )
+        .into_response())
+}
+
+/// GET /api/v1/enc/ping
+///
+/// Validates the X-Classification-Key header and records the handshake on
+/// the managed key it belongs to.
+async fn ping(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<PingQuery>,
+) -> Result<Json<EncPingResponse>, AppError> {
+    let key = headers
+        .get("X-Classification-Key")
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| AppError::Unauthorized("X-Classification-Key header required".into()))?;
+
+    let configured_shared_key = state
+        .config
+        .classification
+        .as_ref()
+        .and_then(|c| c.shared_key.as_deref());
+    let classification_key = if configured_shared_key == Some(key) {
+        None
+    } else {
+        let classification_key = classification_keys::find_key(&state, key)
+            .await?
+            .ok_or_else(|| AppError::Unauthorized("Invalid X-Classification-Key header".into()))?;
+
+        let host = query
+            .host
+            .as_deref()
+            .map(str::trim)
+            .filter(|host| !host.is_empty())
+            .map(|host| host.chars().take(MAX_HOST_LEN).collect::<String>());
+        ClassificationKeyRepository::new(&state.db)
+            .record_handshake(classification_key.id, host.as_deref())
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to record ENC handshake: {}", e);
+                AppError::internal("Failed to record ENC handshake")
+            })?;
+        Some(classification_key)
+    };
+
+    Ok(Json(EncPingResponse {
+        status: "ok".to_string(),
+        key_id: classification_key.as_ref().map(|k| k.id),
+        key_name: classification_key.as_ref().map(|k| k.name.clone()),
+        allowed_cn_patterns: classification_key
+            .map(|k| k.allowed_cn_patterns)
+            .unwrap_or_default(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        server_time: Utc::now(),
+    }))
+}
+
+/// URL the ENC script should use, based on the request's Host header
+///
+/// With a dedicated ENC listener the script talks to that listener on the
+/// same host name.
+fn default_webui_url(state: &AppState, headers: &HeaderMap) -> String {
+    let server = &state.config.server;
+    let host = headers
+        .get(header::HOST)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+        .unwrap_or_else(|| format!("{}:{}", server.host, server.port));
+
+    let (tls, authority) = match server.effective_enc_listener() {
+        Some(listener) => (
+            listener.tls.is_some(),
+            format!("{}:{}", strip_port(&host), listener.port),
+        ),
+        None => (server.tls.is_some(), host),
+    };
+    let scheme = if tls { "https" } else { "http" };
+    format!(
+        "{}://{}{}",
+        scheme,
+        authority,
+        server.normalized_base_path()
+    )
+}
+
+/// Host name of a Host header value, without the port
+fn strip_port(host: &str) -> &str {
+    if host.ends_with(']') {
+        return host;
+    }
+    match host.rsplit_once(':') {
+        Some((name, port)) if port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host,
+    }
+}
+
+/// Generate the ENC script with configuration values injected
+fn generate_enc_script(webui_url: &str, classification_key: &str, ssl_verify: bool) -> String {
+    let script_template = include_str!("../../scripts/openvox-enc.sh");
+
+    script_template
+        .replace("{{WEBUI_URL}}", webui_url)
+        .replace("{{CLASSIFICATION_KEY}}", classification_key)
+        .replace("{{SSL_VERIFY}}", if ssl_verify { "true" } else { "false" })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_enc_script_replaces_placeholders() {
+        let script = generate_enc_script("https://webui.example.com", "ock_abc_secret", false);
+
+        assert!(script.contains("WEBUI_URL=\"https://webui.example.com\""));
+        assert!(script.contains("CLASSIFICATION_KEY=\"ock_abc_secret\""));
+        assert!(script.contains("SSL_VERIFY=\"false\""));
+        assert!(!script.contains("{{"));
+    }
+
+    #[test]
+    fn test_strip_port() {
+        assert_eq!(strip_port("webui.example.com:8443"), "webui.example.com");
+        assert_eq!(strip_port("webui.example.com"), "webui.example.com");
+        assert_eq!(strip_port("[::1]:8443"), "[::1]");
+        assert_eq!(strip_port("[::1]"), "[::1]");
+    }
+}
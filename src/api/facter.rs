@@ -13,17 +13,16 @@ use crate::{
     db::repository::{FactTemplateRepository, GroupRepository},
     middleware::AuthUser,
     models::{
-        CreateFactTemplateRequest, ExportFormat, FactTemplate, GenerateFactsRequest,
-        UpdateFactTemplateRequest,
+        ClassificationResult, CreateFactTemplateRequest, ExportFormat, FactTemplate,
+        GenerateFactsRequest, UpdateFactTemplateRequest,
     },
     services::{
-        classification::{build_classification_facts, ClassificationService},
-        facter::{ExportFormat as ServiceExportFormat, FacterService, GeneratedFacts},
-    },
-    utils::{
-        validation::{format_validation_errors, validate_fact_template},
-        AppError,
+        classification::{build_node_classification_facts, ClassificationService},
+        facter::{
+            ExportFormat as ServiceExportFormat, ExternalFactFile, FacterService, GeneratedFacts,
+        },
     },
+    utils::{validation::validate_fact_template, AppError},
     AppState,
 };
 
@@ -39,6 +38,7 @@ pub fn routes() -> Router<AppState> {
         )
         .route("/generate", post(generate_facts))
         .route("/export/{certname}", get(export_facts))
+        .route("/files/{certname}", get(render_fact_files))
 }
 
 #[derive(Debug, Deserialize, Default)]
@@ -88,12 +88,7 @@ async fn create_template(
         facts: payload.facts.clone(),
     };
 
-    if let Err(errors) = validate_fact_template(&template_for_validation) {
-        return Err(AppError::bad_request(format!(
-            "Validation failed: {}",
-            format_validation_errors(&errors)
-        )));
-    }
+    validate_fact_template(&template_for_validation)?;
 
     let repo = FactTemplateRepository::new(&state.db);
     let template = repo
@@ -167,12 +162,7 @@ async fn update_template(
         facts: payload.facts.clone().unwrap_or(existing.facts.clone()),
     };
 
-    if let Err(errors) = validate_fact_template(&template_for_validation) {
-        return Err(AppError::bad_request(format!(
-            "Validation failed: {}",
-            format_validation_errors(&errors)
-        )));
-    }
+    validate_fact_template(&template_for_validation)?;
 
     let template = repo
         .update(
@@ -247,7 +237,7 @@ async fn generate_facts(
         Some(facts) => facts,
         None => {
             // Try to get from PuppetDB if configured
-            if let Some(ref puppetdb) = state.puppetdb {
+            if let Some(puppetdb) = state.puppetdb_for(org_id).await {
                 let facts = puppetdb
                     .get_node_facts(&payload.certname)
                     .await
@@ -255,7 +245,7 @@ async fn generate_facts(
                         tracing::warn!("Failed to get facts from PuppetDB: {}", e);
                         AppError::internal("Failed to get facts from PuppetDB")
                     })?;
-                build_classification_facts(facts, &payload.certname, None)
+                build_node_classification_facts(&state.db, facts, &payload.certname, None).await
             } else {
                 serde_json::json!({})
             }
@@ -321,29 +311,7 @@ async fn export_facts(
     let template = template
         .ok_or_else(|| AppError::not_found(format!("Template '{}' not found", query.template)))?;
 
-    // Get existing facts from PuppetDB if available
-    let existing_facts = if let Some(ref puppetdb) = state.puppetdb {
-        match puppetdb.get_node_facts(&certname).await {
-            Ok(facts) => build_classification_facts(facts, &certname, None),
-            Err(e) => {
-                tracing::warn!("Failed to get facts from PuppetDB: {}", e);
-                serde_json::json!({})
-            }
-        }
-    } else {
-        serde_json::json!({})
-    };
-
-    // Get all groups for classification
-    let group_repo = GroupRepository::new(&state.db);
-    let all_groups = group_repo.get_all(org_id).await.map_err(|e| {
-        tracing::error!("Failed to get groups for classification: {}", e);
-        AppError::internal("Failed to get groups for classification")
-    })?;
-
-    // Classify the node to get variables from matched groups
-    let classification_service = ClassificationService::new(all_groups);
-    let classification = classification_service.classify(&certname, &existing_facts);
+    let (classification, existing_facts) = classify_node(&state, org_id, &certname).await?;
 
     // Generate facts
     let service = FacterService::new(vec![template]);
@@ -367,3 +335,102 @@ async fn export_facts(
         AppError::internal(format!("Failed to export facts: {}", e))
     })
 }
+
+/// Query parameters for the external fact files endpoint
+#[derive(Debug, Deserialize)]
+struct FactFilesQuery {
+    /// File format (json, yaml)
+    #[serde(default)]
+    format: ExportFormat,
+    /// Comma-separated template names; every template when omitted
+    templates: Option<String>,
+    /// Optional organization override (super_admin only)
+    organization_id: Option<Uuid>,
+}
+
+/// Render the external fact files of a node from the organization's templates
+///
+/// The node is classified first, so the files carry the variables and
+/// classification of the groups it matches.
+async fn render_fact_files(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(certname): Path<String>,
+    Query(query): Query<FactFilesQuery>,
+) -> Result<Json<Vec<ExternalFactFile>>, AppError> {
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let format = match query.format {
+        ExportFormat::Json => ServiceExportFormat::Json,
+        ExportFormat::Yaml => ServiceExportFormat::Yaml,
+        ExportFormat::Shell => {
+            return Err(AppError::bad_request(
+                "External fact files must be json or yaml",
+            ))
+        }
+    };
+
+    let repo = FactTemplateRepository::new(&state.db);
+    let mut templates = repo.get_all(org_id).await.map_err(|e| {
+        tracing::error!("Failed to list fact templates: {}", e);
+        AppError::internal("Failed to list fact templates")
+    })?;
+    if let Some(names) = query.templates.as_deref() {
+        let names: Vec<&str> = names
+            .split(',')
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+            .collect();
+        if let Some(missing) = names
+            .iter()
+            .find(|name| !templates.iter().any(|t| t.name == **name))
+        {
+            return Err(AppError::not_found(format!(
+                "Template '{}' not found",
+                missing
+            )));
+        }
+        templates.retain(|t| names.contains(&t.name.as_str()));
+    }
+
+    let (classification, existing_facts) = classify_node(&state, org_id, &certname).await?;
+
+    let files = FacterService::new(templates)
+        .render_files(&classification, &existing_facts, format)
+        .map_err(|e| {
+            tracing::error!("Failed to render fact files: {:#}", e);
+            AppError::internal(format!("Failed to render fact files: {:#}", e))
+        })?;
+
+    Ok(Json(files))
+}
+
+/// Classify a node with its PuppetDB facts, when available
+async fn classify_node(
+    state: &AppState,
+    org_id: Uuid,
+    certname: &str,
+) -> Result<(ClassificationResult, serde_json::Value), AppError> {
+    // Get existing facts from PuppetDB if available
+    let existing_facts = if let Some(puppetdb) = state.puppetdb_for(org_id).await {
+        match puppetdb.get_node_facts(certname).await {
+            Ok(facts) => build_node_classification_facts(&state.db, facts, certname, None).await,
+            Err(e) => {
+                tracing::warn!("Failed to get facts from PuppetDB: {}", e);
+                serde_json::json!({})
+            }
+        }
+    } else {
+        serde_json::json!({})
+    };
+
+    // Get all groups for classification
+    let group_repo = GroupRepository::new(&state.db);
+    let all_groups = group_repo.get_all(org_id).await.map_err(|e| {
+        tracing::error!("Failed to get groups for classification: {}", e);
+        AppError::internal("Failed to get groups for classification")
+    })?;
+
+    // Classify the node to get variables from matched groups
+    let classification = ClassificationService::new(all_groups).classify(certname, &existing_facts);
+    Ok((classification, existing_facts))
+}
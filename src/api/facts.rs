@@ -10,6 +10,7 @@ use axum::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    middleware::AuthUser,
     models::Fact,
     services::puppetdb::{QueryBuilder, QueryParams},
     utils::error::{AppError, AppResult},
@@ -61,11 +62,12 @@ pub struct FactsResponse {
 /// - `offset`: Number of results to skip
 async fn query_facts(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Query(query): Query<FactsQuery>,
 ) -> AppResult<Json<FactsResponse>> {
     let puppetdb = state
-        .puppetdb
-        .as_ref()
+        .puppetdb_for(auth_user.organization_id)
+        .await
         .ok_or_else(|| AppError::ServiceUnavailable("PuppetDB is not configured".to_string()))?;
 
     // Resolve the effective page size: the client-requested limit clamped to
@@ -220,10 +222,13 @@ async fn query_facts(
 /// List all unique fact names
 ///
 /// GET /api/v1/facts/names
-async fn list_fact_names(State(state): State<AppState>) -> AppResult<Json<Vec<String>>> {
+async fn list_fact_names(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> AppResult<Json<Vec<String>>> {
     let puppetdb = state
-        .puppetdb
-        .as_ref()
+        .puppetdb_for(auth_user.organization_id)
+        .await
         .ok_or_else(|| AppError::ServiceUnavailable("PuppetDB is not configured".to_string()))?;
 
     let names = puppetdb
@@ -245,10 +250,13 @@ pub struct FactPathResponse {
 /// List all unique fact paths (for structured facts)
 ///
 /// GET /api/v1/facts/paths
-async fn list_fact_paths(State(state): State<AppState>) -> AppResult<Json<Vec<FactPathResponse>>> {
+async fn list_fact_paths(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> AppResult<Json<Vec<FactPathResponse>>> {
     let puppetdb = state
-        .puppetdb
-        .as_ref()
+        .puppetdb_for(auth_user.organization_id)
+        .await
         .ok_or_else(|| AppError::ServiceUnavailable("PuppetDB is not configured".to_string()))?;
 
     let paths = puppetdb
@@ -1,8 +1,13 @@
 //! Node group API endpoints
 
+use std::collections::{HashMap, HashSet};
+
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{
+        header::{HeaderName, ETAG, IF_MATCH},
+        HeaderMap, StatusCode,
+    },
     routing::{delete, get, post},
     Json, Router,
 };
@@ -10,23 +15,35 @@ use serde::Deserialize;
 use uuid::Uuid;
 
 use crate::{
-    db::repository::GroupRepository,
-    middleware::AuthUser,
+    db::repository::{GroupRepository, GroupUpdate},
+    middleware::{rbac, AuditChange, AuthUser, RbacError},
     models::{
-        Action, AddPinnedNodeRequest, ClassificationRule, CreateGroupRequest,
-        CreateGroupUpdateScheduleRequest, CreateRuleRequest, GroupUpdateSchedule, NodeGroup,
-        Resource, UpdateGroupRequest, UpdateGroupUpdateScheduleRequest, UpdateJob,
+        Action, AddPinnedNodeRequest, BulkAddClassRequest, BulkCreateRulesRequest,
+        BulkGroupResponse, BulkGroupResult, BulkPinnedNodesRequest, ClassificationRule,
+        CreateGroupRequest, CreateGroupUpdateScheduleRequest, CreateRuleRequest,
+        GroupUpdateSchedule, MovePinnedNodesRequest, NodeGroup, QuotaResource, Resource,
+        UpdateGroupRequest, UpdateGroupUpdateScheduleRequest, UpdateJob, WebhookEvent,
     },
-    services::classification::{build_classification_facts, ClassificationService},
+    services::classification::{build_node_classification_facts, ClassificationService},
     services::puppetdb::PuppetDbClient,
+    services::quotas::check_quota,
+    services::webhooks::Webhooks,
     utils::AppError,
     AppState,
 };
 
+/// Maximum number of items accepted by a bulk operation
+const MAX_BULK_ITEMS: usize = 1000;
+
 /// Create routes for group endpoints
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/", get(list_groups).post(create_group))
+        .route("/bulk/pin", post(bulk_pin_nodes))
+        .route("/bulk/unpin", post(bulk_unpin_nodes))
+        .route("/bulk/move", post(bulk_move_nodes))
+        .route("/bulk/classes", post(bulk_add_class))
+        .route("/bulk/rules", post(bulk_create_rules))
         .route(
             "/{id}",
             get(get_group).put(update_group).delete(delete_group),
@@ -69,32 +86,20 @@ fn resolve_org(auth_user: &AuthUser, requested: Option<Uuid>) -> Result<Uuid, Ap
 
 /// Check if user has permission to perform an action on a specific group
 /// This supports group-scoped permissions where users can have edit access
-/// to specific groups without having global group permissions.
+/// to specific groups without having global group permissions. A permission
+/// scoped to a group also covers all of its descendants.
 async fn check_group_permission(
     state: &AppState,
     auth_user: &AuthUser,
     action: Action,
     group_id: Option<Uuid>,
 ) -> Result<(), AppError> {
-    let check = state
-        .rbac_db
-        .check_permission(
-            &auth_user.user_id(),
-            Resource::Groups,
-            action,
-            group_id,
-            None,
-        )
+    rbac::check_group_permission(state, auth_user, action, group_id)
         .await
-        .map_err(|e| AppError::internal(format!("Permission check failed: {}", e)))?;
-
-    if check.allowed {
-        Ok(())
-    } else {
-        Err(AppError::forbidden(&check.reason.unwrap_or_else(|| {
-            "No matching permission found".to_string()
-        })))
-    }
+        .map_err(|e| match e {
+            RbacError::PermissionDenied { reason, .. } => AppError::forbidden(&reason),
+            other => AppError::internal(other.to_string()),
+        })
 }
 
 /// List all node groups
@@ -118,23 +123,35 @@ async fn create_group(
     auth_user: AuthUser,
     Query(query): Query<OrgQuery>,
     Json(payload): Json<CreateGroupRequest>,
-) -> Result<(StatusCode, Json<NodeGroup>), AppError> {
+) -> Result<(StatusCode, [(HeaderName, String); 1], Json<NodeGroup>), AppError> {
     // Check create permission. If a parent group is provided, use it to satisfy
     // group-scoped permissions; otherwise require a global create permission.
     let permission_scope = payload.parent_id;
     check_group_permission(&state, &auth_user, Action::Create, permission_scope).await?;
 
     let org_id = resolve_org(&auth_user, query.organization_id)?;
+    check_quota(&state.db, org_id, QuotaResource::Groups).await?;
     let repo = GroupRepository::new(&state.db);
-    let group = repo.create(org_id, &payload).await.map_err(|e| {
-        tracing::error!("Failed to create group: {}", e);
-        if e.to_string().contains("UNIQUE constraint failed") {
-            AppError::conflict("A group with this name already exists")
-        } else {
-            AppError::internal("Failed to create group")
+    // A parent in the recycle bin would hide the new group from classification
+    if let Some(parent_id) = payload.parent_id {
+        require_group(&repo, org_id, parent_id).await?;
+    }
+    let group = match repo.create(org_id, &payload).await {
+        Ok(group) => group,
+        Err(e) if format!("{:#}", e).contains("UNIQUE constraint failed") => {
+            return Err(group_name_conflict(&repo, org_id, &payload.name).await);
         }
-    })?;
-    Ok((StatusCode::CREATED, Json(group)))
+        Err(e) => {
+            tracing::error!("Failed to create group: {:#}", e);
+            return Err(AppError::internal("Failed to create group"));
+        }
+    };
+    emit_group_changed(&state.webhooks, org_id, "created", &group);
+    Ok((
+        StatusCode::CREATED,
+        [(ETAG, group_etag(group.version))],
+        Json(group),
+    ))
 }
 
 /// Get a specific node group
@@ -143,7 +160,7 @@ async fn get_group(
     auth_user: AuthUser,
     Query(query): Query<OrgQuery>,
     Path(id): Path<String>,
-) -> Result<Json<NodeGroup>, AppError> {
+) -> Result<([(HeaderName, String); 1], Json<NodeGroup>), AppError> {
     let uuid = Uuid::parse_str(&id).map_err(|_| AppError::bad_request("Invalid group ID"))?;
     let org_id = resolve_org(&auth_user, query.organization_id)?;
 
@@ -154,49 +171,139 @@ async fn get_group(
     })?;
 
     match group {
-        Some(g) => Ok(Json(g)),
+        Some(g) => Ok(([(ETAG, group_etag(g.version))], Json(g))),
         None => Err(AppError::not_found("Group not found")),
     }
 }
 
+/// The ETag of a group version
+fn group_etag(version: i64) -> String {
+    format!("\"{}\"", version)
+}
+
+/// The group version an update's If-Match header expects
+///
+/// Updates must carry the ETag the client last read; `*` accepts any
+/// version and still guards against changes made during the update itself.
+fn if_match_version(headers: &HeaderMap) -> Result<Option<i64>, AppError> {
+    let value = headers
+        .get(IF_MATCH)
+        .ok_or_else(|| {
+            AppError::precondition_required(
+                "Updating a group requires an If-Match header with its ETag",
+            )
+        })?
+        .to_str()
+        .map_err(|_| AppError::bad_request("Invalid If-Match header"))?
+        .trim();
+    if value == "*" {
+        return Ok(None);
+    }
+
+    let tag = value.strip_prefix("W/").unwrap_or(value).trim_matches('"');
+    tag.parse()
+        .map(Some)
+        .map_err(|_| AppError::precondition_failed("If-Match does not match the group's ETag"))
+}
+
 /// Update a node group
 async fn update_group(
     State(state): State<AppState>,
     auth_user: AuthUser,
     Query(query): Query<OrgQuery>,
     Path(id): Path<String>,
-    Json(payload): Json<UpdateGroupRequest>,
-) -> Result<Json<NodeGroup>, AppError> {
+    headers: HeaderMap,
+    Json(mut payload): Json<UpdateGroupRequest>,
+) -> Result<(AuditChange, [(HeaderName, String); 1], Json<NodeGroup>), AppError> {
     let uuid = Uuid::parse_str(&id).map_err(|_| AppError::bad_request("Invalid group ID"))?;
 
     // Check update permission for this specific group
     check_group_permission(&state, &auth_user, Action::Update, Some(uuid)).await?;
 
     let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let expected_version = if_match_version(&headers)?;
+
+    if let Some(rules) = &payload.rules {
+        if rules.iter().any(|rule| rule.fact_path.trim().is_empty()) {
+            return Err(AppError::validation("Every rule needs a fact_path"));
+        }
+    }
+    if let Some(certnames) = payload.pinned_nodes.take() {
+        let mut seen = HashSet::new();
+        payload.pinned_nodes = Some(
+            certnames
+                .into_iter()
+                .map(|c| c.trim().to_string())
+                .filter(|c| !c.is_empty() && seen.insert(c.clone()))
+                .collect(),
+        );
+    }
 
     let repo = GroupRepository::new(&state.db);
-    let group = repo.update(org_id, uuid, &payload).await.map_err(|e| {
-        tracing::error!("Failed to update group: {}", e);
-        if e.to_string().contains("UNIQUE constraint failed") {
-            AppError::conflict("A group with this name already exists")
-        } else {
+    let before = repo
+        .get_by_id(org_id, uuid)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get group: {}", e);
+            AppError::internal("Failed to update group")
+        })?
+        .ok_or_else(|| AppError::not_found("Group not found"))?;
+
+    // Moving a group re-parents its whole subtree, so the caller must also be
+    // allowed to create groups under the new parent. A group cannot be moved
+    // below itself.
+    if let Some(new_parent) = payload.parent_id {
+        require_group(&repo, org_id, new_parent).await?;
+        let ancestors = repo.get_ancestor_ids(new_parent).await.map_err(|e| {
+            tracing::error!("Failed to load group ancestors: {}", e);
             AppError::internal("Failed to update group")
+        })?;
+        if ancestors.contains(&uuid) {
+            return Err(AppError::bad_request(
+                "A group cannot be moved below itself",
+            ));
         }
-    })?;
+        check_group_permission(&state, &auth_user, Action::Create, Some(new_parent)).await?;
+    }
 
-    match group {
-        Some(g) => Ok(Json(g)),
-        None => Err(AppError::not_found("Group not found")),
+    let update = match repo.update(org_id, uuid, &payload, expected_version).await {
+        Ok(update) => update,
+        Err(e) if format!("{:#}", e).contains("UNIQUE constraint failed") => {
+            let name = payload.name.as_deref().unwrap_or_default();
+            return Err(group_name_conflict(&repo, org_id, name).await);
+        }
+        Err(e) => {
+            tracing::error!("Failed to update group: {:#}", e);
+            return Err(AppError::internal("Failed to update group"));
+        }
+    };
+
+    match update {
+        GroupUpdate::Updated(g) => {
+            emit_group_changed(&state.webhooks, org_id, "updated", &g);
+            Ok((
+                AuditChange::new(&before, &g),
+                [(ETAG, group_etag(g.version))],
+                Json(g),
+            ))
+        }
+        GroupUpdate::VersionMismatch { current } => Err(AppError::precondition_failed(format!(
+            "The group was changed by someone else (current ETag {}); reload it and retry",
+            group_etag(current)
+        ))),
+        GroupUpdate::NotFound => Err(AppError::not_found("Group not found")),
     }
 }
 
 /// Delete a node group
+///
+/// The group and its subgroups move to the recycle bin.
 async fn delete_group(
     State(state): State<AppState>,
     auth_user: AuthUser,
     Query(query): Query<OrgQuery>,
     Path(id): Path<String>,
-) -> Result<Json<bool>, AppError> {
+) -> Result<(AuditChange, Json<bool>), AppError> {
     let uuid = Uuid::parse_str(&id).map_err(|_| AppError::bad_request("Invalid group ID"))?;
 
     // Check delete permission for this specific group
@@ -205,12 +312,38 @@ async fn delete_group(
     let org_id = resolve_org(&auth_user, query.organization_id)?;
 
     let repo = GroupRepository::new(&state.db);
+    let before = repo.get_by_id(org_id, uuid).await.map_err(|e| {
+        tracing::error!("Failed to get group: {}", e);
+        AppError::internal("Failed to delete group")
+    })?;
     let deleted = repo.delete(org_id, uuid).await.map_err(|e| {
         tracing::error!("Failed to delete group: {}", e);
         AppError::internal("Failed to delete group")
     })?;
 
-    Ok(Json(deleted))
+    let change = match (&before, deleted) {
+        (Some(group), true) => {
+            emit_group_changed(&state.webhooks, org_id, "deleted", group);
+            AuditChange::deleted(group)
+        }
+        _ => AuditChange::default(),
+    };
+    Ok((change, Json(deleted)))
+}
+
+/// Notify the organization's webhooks of a group change
+fn emit_group_changed(webhooks: &Webhooks, org_id: Uuid, action: &str, group: &NodeGroup) {
+    webhooks.emit(
+        Some(org_id),
+        WebhookEvent::GroupChanged,
+        serde_json::json!({
+            "action": action,
+            "group_id": group.id,
+            "name": group.name,
+            "parent_id": group.parent_id,
+            "environment": group.environment,
+        }),
+    );
 }
 
 /// Core group membership resolver: returns pinned nodes plus any nodes that
@@ -249,11 +382,15 @@ pub(crate) async fn classify_group_members(
 
             // Get facts for the node
             let facts = match puppetdb.get_node_facts(&node.certname).await {
-                Ok(facts) => build_classification_facts(
-                    facts,
-                    &node.certname,
-                    node.catalog_environment.as_deref(),
-                ),
+                Ok(facts) => {
+                    build_node_classification_facts(
+                        repo.pool(),
+                        facts,
+                        &node.certname,
+                        node.catalog_environment.as_deref(),
+                    )
+                    .await
+                }
                 Err(e) => {
                     tracing::warn!("Failed to get facts for {}: {}", node.certname, e);
                     continue;
@@ -313,7 +450,8 @@ pub(crate) async fn resolve_group_member_certnames(
         return Err(AppError::not_found("Group not found"));
     }
 
-    classify_group_members(&repo, state.puppetdb.as_deref(), org_id, group_id)
+    let puppetdb = state.puppetdb_for(org_id).await;
+    classify_group_members(&repo, puppetdb.as_deref(), org_id, group_id)
         .await
         .map_err(|e| {
             tracing::error!("Failed to resolve group members: {}", e);
@@ -511,6 +649,388 @@ async fn remove_pinned_node(
     }
 }
 
+// ── Bulk Endpoints ─────────────────────────────────────────────────
+
+fn check_bulk_size(count: usize, what: &str) -> Result<(), AppError> {
+    if count == 0 {
+        return Err(AppError::bad_request(format!("No {} given", what)));
+    }
+    if count > MAX_BULK_ITEMS {
+        return Err(AppError::bad_request(format!(
+            "At most {} {} can be processed per request",
+            MAX_BULK_ITEMS, what
+        )));
+    }
+    Ok(())
+}
+
+/// Deduplicate and validate the certnames of a bulk request
+fn normalize_certnames(certnames: Vec<String>) -> Result<Vec<String>, AppError> {
+    let mut seen = HashSet::new();
+    let certnames: Vec<String> = certnames
+        .into_iter()
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty() && seen.insert(c.clone()))
+        .collect();
+    check_bulk_size(certnames.len(), "certnames")?;
+    Ok(certnames)
+}
+
+/// Load a group of the organization or fail with 404
+async fn require_group(
+    repo: &GroupRepository<'_>,
+    org_id: Uuid,
+    group_id: Uuid,
+) -> Result<NodeGroup, AppError> {
+    repo.get_by_id(org_id, group_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to check group: {}", e);
+            AppError::internal("Failed to check group")
+        })?
+        .ok_or_else(|| AppError::not_found(format!("Group {} not found", group_id)))
+}
+
+/// The conflict of a group name that is already taken
+///
+/// Deleted groups keep their name until they are purged; the error then
+/// points to the recycle bin.
+async fn group_name_conflict(repo: &GroupRepository<'_>, org_id: Uuid, name: &str) -> AppError {
+    match repo.name_held_by_deleted_group(org_id, name).await {
+        Ok(true) => AppError::conflict(
+            "A group with this name is in the recycle bin; restore or purge it first",
+        ),
+        Ok(false) => AppError::conflict("A group with this name already exists"),
+        Err(e) => {
+            tracing::error!("Failed to check deleted groups: {:#}", e);
+            AppError::internal("Failed to check group name")
+        }
+    }
+}
+
+/// Check the update permission on a group for one item of a bulk request
+///
+/// A denied permission becomes the item's failure message instead of failing
+/// the whole request.
+async fn check_bulk_item_permission(
+    state: &AppState,
+    auth_user: &AuthUser,
+    group_id: Uuid,
+) -> Result<Result<(), String>, AppError> {
+    match check_group_permission(state, auth_user, Action::Update, Some(group_id)).await {
+        Ok(()) => Ok(Ok(())),
+        Err(AppError::Forbidden(reason)) => Ok(Err(format!("Permission denied: {}", reason))),
+        Err(e) => Err(e),
+    }
+}
+
+fn bulk_ok(item: impl Into<String>, message: impl Into<String>) -> BulkGroupResult {
+    BulkGroupResult {
+        item: item.into(),
+        success: true,
+        message: message.into(),
+    }
+}
+
+fn bulk_err(item: impl Into<String>, message: impl Into<String>) -> BulkGroupResult {
+    BulkGroupResult {
+        item: item.into(),
+        success: false,
+        message: message.into(),
+    }
+}
+
+/// POST /api/v1/groups/bulk/pin - Pin many nodes to a group
+///
+/// Requires update permission on the group. Results are reported per
+/// certname; nodes that are already pinned succeed unchanged.
+async fn bulk_pin_nodes(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Json(payload): Json<BulkPinnedNodesRequest>,
+) -> Result<Json<BulkGroupResponse>, AppError> {
+    check_group_permission(&state, &auth_user, Action::Update, Some(payload.group_id)).await?;
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let certnames = normalize_certnames(payload.certnames)?;
+
+    let repo = GroupRepository::new(&state.db);
+    let group = require_group(&repo, org_id, payload.group_id).await?;
+    let pinned: HashSet<String> = group.pinned_nodes.into_iter().collect();
+
+    let mut results = Vec::with_capacity(certnames.len());
+    for certname in certnames {
+        if pinned.contains(&certname) {
+            results.push(bulk_ok(certname, "Already pinned"));
+            continue;
+        }
+        results.push(match repo.add_pinned_node(group.id, &certname).await {
+            Ok(()) => bulk_ok(certname, "Pinned"),
+            Err(e) => {
+                tracing::error!("Failed to pin {} to group {}: {}", certname, group.id, e);
+                bulk_err(certname, "Failed to pin node")
+            }
+        });
+    }
+
+    tracing::info!(
+        "User '{}' bulk pinned nodes to group '{}'",
+        auth_user.username,
+        group.name
+    );
+    Ok(Json(BulkGroupResponse::from_results(results)))
+}
+
+/// POST /api/v1/groups/bulk/unpin - Unpin many nodes from a group
+///
+/// Requires update permission on the group. Certnames that are not pinned to
+/// the group are reported as failures.
+async fn bulk_unpin_nodes(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Json(payload): Json<BulkPinnedNodesRequest>,
+) -> Result<Json<BulkGroupResponse>, AppError> {
+    check_group_permission(&state, &auth_user, Action::Update, Some(payload.group_id)).await?;
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let certnames = normalize_certnames(payload.certnames)?;
+
+    let repo = GroupRepository::new(&state.db);
+    let group = require_group(&repo, org_id, payload.group_id).await?;
+
+    let mut results = Vec::with_capacity(certnames.len());
+    for certname in certnames {
+        results.push(match repo.remove_pinned_node(group.id, &certname).await {
+            Ok(true) => bulk_ok(certname, "Unpinned"),
+            Ok(false) => bulk_err(certname, "Not pinned to this group"),
+            Err(e) => {
+                tracing::error!(
+                    "Failed to unpin {} from group {}: {}",
+                    certname,
+                    group.id,
+                    e
+                );
+                bulk_err(certname, "Failed to unpin node")
+            }
+        });
+    }
+
+    tracing::info!(
+        "User '{}' bulk unpinned nodes from group '{}'",
+        auth_user.username,
+        group.name
+    );
+    Ok(Json(BulkGroupResponse::from_results(results)))
+}
+
+/// POST /api/v1/groups/bulk/move - Move pinned nodes between groups
+///
+/// Requires update permission on both groups. Each node is unpinned from the
+/// source group and pinned to the target group atomically; nodes not pinned
+/// to the source group are reported as failures.
+async fn bulk_move_nodes(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Json(payload): Json<MovePinnedNodesRequest>,
+) -> Result<Json<BulkGroupResponse>, AppError> {
+    if payload.from_group_id == payload.to_group_id {
+        return Err(AppError::bad_request("Source and target group must differ"));
+    }
+    check_group_permission(
+        &state,
+        &auth_user,
+        Action::Update,
+        Some(payload.from_group_id),
+    )
+    .await?;
+    check_group_permission(
+        &state,
+        &auth_user,
+        Action::Update,
+        Some(payload.to_group_id),
+    )
+    .await?;
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let certnames = normalize_certnames(payload.certnames)?;
+
+    let repo = GroupRepository::new(&state.db);
+    let from = require_group(&repo, org_id, payload.from_group_id).await?;
+    let to = require_group(&repo, org_id, payload.to_group_id).await?;
+
+    let mut results = Vec::with_capacity(certnames.len());
+    for certname in certnames {
+        results.push(
+            match repo.move_pinned_node(from.id, to.id, &certname).await {
+                Ok(true) => bulk_ok(certname, "Moved"),
+                Ok(false) => bulk_err(certname, "Not pinned to the source group"),
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to move {} from group {} to {}: {}",
+                        certname,
+                        from.id,
+                        to.id,
+                        e
+                    );
+                    bulk_err(certname, "Failed to move node")
+                }
+            },
+        );
+    }
+
+    tracing::info!(
+        "User '{}' bulk moved nodes from group '{}' to '{}'",
+        auth_user.username,
+        from.name,
+        to.name
+    );
+    Ok(Json(BulkGroupResponse::from_results(results)))
+}
+
+/// POST /api/v1/groups/bulk/classes - Declare a class on many groups
+///
+/// Results are reported per group; groups the caller may not update fail
+/// without stopping the batch. Groups that already declare the class keep
+/// their parameters unless `overwrite` is set.
+async fn bulk_add_class(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Json(payload): Json<BulkAddClassRequest>,
+) -> Result<Json<BulkGroupResponse>, AppError> {
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let class = payload.class.trim().to_string();
+    if class.is_empty() {
+        return Err(AppError::validation("Class name is required"));
+    }
+    let parameters = payload.parameters.unwrap_or_else(|| serde_json::json!({}));
+    if !parameters.is_object() {
+        return Err(AppError::validation("Class parameters must be an object"));
+    }
+    let mut seen = HashSet::new();
+    let group_ids: Vec<Uuid> = payload
+        .group_ids
+        .into_iter()
+        .filter(|id| seen.insert(*id))
+        .collect();
+    check_bulk_size(group_ids.len(), "groups")?;
+
+    let repo = GroupRepository::new(&state.db);
+    let mut results = Vec::with_capacity(group_ids.len());
+    for group_id in group_ids {
+        let item = group_id.to_string();
+        if let Err(message) = check_bulk_item_permission(&state, &auth_user, group_id).await? {
+            results.push(bulk_err(item, message));
+            continue;
+        }
+        let group = match repo.get_by_id(org_id, group_id).await {
+            Ok(Some(group)) => group,
+            Ok(None) => {
+                results.push(bulk_err(item, "Group not found"));
+                continue;
+            }
+            Err(e) => {
+                tracing::error!("Failed to get group {}: {}", group_id, e);
+                results.push(bulk_err(item, "Failed to get group"));
+                continue;
+            }
+        };
+
+        let mut classes = group.classes.as_object().cloned().unwrap_or_default();
+        let declared = classes.contains_key(&class);
+        if declared && !payload.overwrite {
+            results.push(bulk_ok(item, "Class already declared"));
+            continue;
+        }
+        classes.insert(class.clone(), parameters.clone());
+
+        let update = UpdateGroupRequest {
+            classes: Some(serde_json::Value::Object(classes)),
+            ..Default::default()
+        };
+        results.push(match repo.update(org_id, group_id, &update, None).await {
+            Ok(GroupUpdate::Updated(updated)) => {
+                emit_group_changed(&state.webhooks, org_id, "updated", &updated);
+                bulk_ok(
+                    item,
+                    if declared {
+                        "Class parameters replaced"
+                    } else {
+                        "Class added"
+                    },
+                )
+            }
+            Ok(GroupUpdate::VersionMismatch { .. }) => {
+                bulk_err(item, "Group was changed concurrently; retry")
+            }
+            Ok(GroupUpdate::NotFound) => bulk_err(item, "Group not found"),
+            Err(e) => {
+                tracing::error!("Failed to add class to group {}: {}", group_id, e);
+                bulk_err(item, "Failed to update group")
+            }
+        });
+    }
+
+    Ok(Json(BulkGroupResponse::from_results(results)))
+}
+
+/// POST /api/v1/groups/bulk/rules - Create classification rules on many groups
+///
+/// Each rule names its group. Results are reported per rule, in request
+/// order; rules on groups the caller may not update fail without stopping
+/// the batch.
+async fn bulk_create_rules(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Json(payload): Json<BulkCreateRulesRequest>,
+) -> Result<Json<BulkGroupResponse>, AppError> {
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    check_bulk_size(payload.rules.len(), "rules")?;
+
+    let repo = GroupRepository::new(&state.db);
+    // Group ID -> whether rules may be added, or why not
+    let mut checked: HashMap<Uuid, Result<(), String>> = HashMap::new();
+    let mut results = Vec::with_capacity(payload.rules.len());
+    for item in payload.rules {
+        let group_id = item.group_id;
+        let label = group_id.to_string();
+        if item.rule.fact_path.trim().is_empty() {
+            results.push(bulk_err(label, "fact_path is required"));
+            continue;
+        }
+
+        if !checked.contains_key(&group_id) {
+            let check = match check_bulk_item_permission(&state, &auth_user, group_id).await? {
+                Ok(()) => match repo.get_by_id(org_id, group_id).await {
+                    Ok(Some(_)) => Ok(()),
+                    Ok(None) => Err("Group not found".to_string()),
+                    Err(e) => {
+                        tracing::error!("Failed to get group {}: {}", group_id, e);
+                        Err("Failed to get group".to_string())
+                    }
+                },
+                Err(message) => Err(message),
+            };
+            checked.insert(group_id, check);
+        }
+        if let Err(message) = &checked[&group_id] {
+            results.push(bulk_err(label, message.clone()));
+            continue;
+        }
+
+        results.push(match repo.add_rule(group_id, &item.rule).await {
+            Ok(rule) => bulk_ok(label, format!("Created rule {}", rule.id)),
+            Err(e) => {
+                tracing::error!("Failed to add rule to group {}: {}", group_id, e);
+                bulk_err(label, "Failed to add rule")
+            }
+        });
+    }
+
+    Ok(Json(BulkGroupResponse::from_results(results)))
+}
+
 // ── Update Schedule Endpoints ──────────────────────────────────────
 
 async fn list_update_schedules(
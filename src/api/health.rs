@@ -2,10 +2,18 @@
 //!
 //! Provides health check endpoints for monitoring and load balancers.
 
+use std::future::Future;
+use std::time::{Duration, Instant};
+
 use axum::{extract::State, http::StatusCode, Json};
+use chrono::{DateTime, Utc};
 use serde::Serialize;
 
-use crate::{db, AppState};
+use crate::{
+    db,
+    services::health::{self, HealthTracker},
+    AppState,
+};
 
 /// Basic health response
 #[derive(Serialize)]
@@ -17,6 +25,7 @@ pub struct HealthResponse {
 /// Detailed health response with component status
 #[derive(Serialize)]
 pub struct DetailedHealthResponse {
+    /// `healthy`, `degraded` or `unhealthy`
     pub status: String,
     pub version: String,
     pub components: ComponentHealth,
@@ -27,36 +36,57 @@ pub struct DetailedHealthResponse {
 pub struct ComponentHealth {
     pub database: ComponentStatus,
     pub puppetdb: ComponentStatus,
+    pub puppet_ca: ComponentStatus,
+    pub scheduler: ComponentStatus,
 }
 
 /// Status of a single component
 #[derive(Serialize)]
 pub struct ComponentStatus {
+    /// `healthy`, `degraded`, `unhealthy` or `not_configured`
     pub status: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
+    /// Time the probe took
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latency_ms: Option<u64>,
+    /// Latency above which the component counts as degraded
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget_ms: Option<u64>,
+    /// Last time the component was seen working
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_success: Option<DateTime<Utc>>,
 }
 
 impl ComponentStatus {
-    fn healthy() -> Self {
+    fn with_status(status: &str, message: Option<String>) -> Self {
         Self {
-            status: "healthy".to_string(),
-            message: None,
+            status: status.to_string(),
+            message,
+            latency_ms: None,
+            budget_ms: None,
+            last_success: None,
         }
     }
 
+    fn healthy() -> Self {
+        Self::with_status("healthy", None)
+    }
+
+    fn degraded(message: impl Into<String>) -> Self {
+        Self::with_status("degraded", Some(message.into()))
+    }
+
     fn unhealthy(message: impl Into<String>) -> Self {
-        Self {
-            status: "unhealthy".to_string(),
-            message: Some(message.into()),
-        }
+        Self::with_status("unhealthy", Some(message.into()))
     }
 
     fn not_configured() -> Self {
-        Self {
-            status: "not_configured".to_string(),
-            message: None,
-        }
+        Self::with_status("not_configured", None)
+    }
+
+    fn is(&self, status: &str) -> bool {
+        self.status == status
     }
 }
 
@@ -73,51 +103,153 @@ pub async fn health_check() -> Json<HealthResponse> {
 
 /// Detailed health check endpoint
 ///
-/// Checks the health of all components (database, PuppetDB).
-/// Returns 200 if all components are healthy, 503 otherwise.
+/// Probes the database, PuppetDB and the Puppet CA, and checks that the
+/// scheduler is running. The overall status is `unhealthy` (503) when the
+/// database or PuppetDB is down, and `degraded` (200) when a component is
+/// slower than its latency budget or the CA or scheduler is down.
 pub async fn health_check_detailed(
     State(state): State<AppState>,
 ) -> (StatusCode, Json<DetailedHealthResponse>) {
-    // Check database health
-    let database_status = match db::check_health(&state.db).await {
-        Ok(_) => ComponentStatus::healthy(),
-        Err(e) => ComponentStatus::unhealthy(e.to_string()),
-    };
+    let config = &state.config.health;
+    let timeout = Duration::from_millis(config.timeout_ms);
 
-    // Check PuppetDB health (if configured)
-    let puppetdb_status = if let Some(ref _client) = state.puppetdb {
-        // TODO: Add actual health check when PuppetDB client supports it
-        ComponentStatus::healthy()
-    } else {
-        ComponentStatus::not_configured()
+    let database = probe(
+        &state.health,
+        "database",
+        config.database_budget_ms,
+        timeout,
+        db::check_health(&state.db),
+    );
+    let puppetdb = async {
+        match state.puppetdb {
+            Some(ref client) => {
+                probe(
+                    &state.health,
+                    "puppetdb",
+                    config.puppetdb_budget_ms,
+                    timeout,
+                    client.get_version(),
+                )
+                .await
+            }
+            None => ComponentStatus::not_configured(),
+        }
     };
+    let puppet_ca = async {
+        match state.puppet_ca {
+            Some(ref ca) => {
+                probe(
+                    &state.health,
+                    "puppet_ca",
+                    config.puppet_ca_budget_ms,
+                    timeout,
+                    ca.get_ca_bundle_pem(),
+                )
+                .await
+            }
+            None => ComponentStatus::not_configured(),
+        }
+    };
+    let (database, puppetdb, puppet_ca) = tokio::join!(database, puppetdb, puppet_ca);
 
-    // Determine overall status
-    let overall_healthy = database_status.status == "healthy"
-        && (puppetdb_status.status == "healthy" || puppetdb_status.status == "not_configured");
-
-    let status_code = if overall_healthy {
-        StatusCode::OK
-    } else {
+    let components = ComponentHealth {
+        database,
+        puppetdb,
+        puppet_ca,
+        scheduler: scheduler_status(
+            state.health.last_success(health::SCHEDULER),
+            config.scheduler_max_age_secs,
+        ),
+    };
+    let status = overall_status(&components);
+    let status_code = if status == "unhealthy" {
         StatusCode::SERVICE_UNAVAILABLE
+    } else {
+        StatusCode::OK
     };
 
     let response = DetailedHealthResponse {
-        status: if overall_healthy {
-            "healthy".to_string()
-        } else {
-            "unhealthy".to_string()
-        },
+        status: status.to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
-        components: ComponentHealth {
-            database: database_status,
-            puppetdb: puppetdb_status,
-        },
+        components,
     };
 
     (status_code, Json(response))
 }
 
+/// Run a probe within the timeout and grade it against its latency budget
+async fn probe<T, E: std::fmt::Display>(
+    health: &HealthTracker,
+    component: &'static str,
+    budget_ms: u64,
+    timeout: Duration,
+    check: impl Future<Output = Result<T, E>>,
+) -> ComponentStatus {
+    let started = Instant::now();
+    let result = tokio::time::timeout(timeout, check).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let mut status = match result {
+        Ok(Ok(_)) => {
+            health.record_success(component);
+            if latency_ms > budget_ms {
+                ComponentStatus::degraded(format!(
+                    "Responded in {} ms, over the {} ms budget",
+                    latency_ms, budget_ms
+                ))
+            } else {
+                ComponentStatus::healthy()
+            }
+        }
+        Ok(Err(e)) => ComponentStatus::unhealthy(e.to_string()),
+        Err(_) => {
+            ComponentStatus::unhealthy(format!("No response within {} ms", timeout.as_millis()))
+        }
+    };
+    status.latency_ms = Some(latency_ms);
+    status.budget_ms = Some(budget_ms);
+    status.last_success = health.last_success(component);
+    status
+}
+
+/// Status of the scheduler from the time of its last run
+fn scheduler_status(last_run: Option<DateTime<Utc>>, max_age_secs: u64) -> ComponentStatus {
+    let mut status = match last_run {
+        None => ComponentStatus::unhealthy("Scheduler is not running"),
+        Some(last_run) if (Utc::now() - last_run).num_seconds() > max_age_secs as i64 => {
+            ComponentStatus::unhealthy(format!(
+                "Scheduler has not run for more than {} seconds",
+                max_age_secs
+            ))
+        }
+        Some(_) => ComponentStatus::healthy(),
+    };
+    status.last_success = last_run;
+    status
+}
+
+/// Roll the component statuses up into `healthy`, `degraded` or `unhealthy`
+///
+/// The WebUI cannot serve requests without the database or PuppetDB; the
+/// Puppet CA and the scheduler only affect some features.
+fn overall_status(components: &ComponentHealth) -> &'static str {
+    let critical = [&components.database, &components.puppetdb];
+    let all = [
+        &components.database,
+        &components.puppetdb,
+        &components.puppet_ca,
+        &components.scheduler,
+    ];
+
+    if critical.iter().any(|c| c.is("unhealthy")) {
+        "unhealthy"
+    } else if all.iter().any(|c| c.is("unhealthy") || c.is("degraded")) {
+        "degraded"
+    } else {
+        "healthy"
+    }
+}
+
 /// Liveness probe (for Kubernetes)
 ///
 /// Returns 200 OK if the process is alive.
@@ -125,15 +257,94 @@ pub async fn liveness() -> StatusCode {
     StatusCode::OK
 }
 
+/// Readiness response
+#[derive(Serialize)]
+pub struct ReadinessResponse {
+    /// `ready` or `not_ready`
+    pub status: String,
+    pub checks: ReadinessChecks,
+}
+
+/// Outcome of each readiness condition: `passed`, `pending`, `failed` or
+/// `disabled`
+#[derive(Serialize)]
+pub struct ReadinessChecks {
+    pub database: String,
+    pub migrations: String,
+    pub rbac_seed: String,
+    pub cache_warmup: String,
+}
+
+impl ReadinessChecks {
+    fn ready(&self) -> bool {
+        [
+            &self.database,
+            &self.migrations,
+            &self.rbac_seed,
+            &self.cache_warmup,
+        ]
+        .iter()
+        .all(|check| *check == "passed" || *check == "disabled")
+    }
+}
+
+/// Outcome of a readiness condition
+fn check_outcome(enabled: bool, result: anyhow::Result<bool>) -> String {
+    match (enabled, result) {
+        (false, _) => "disabled",
+        (true, Ok(true)) => "passed",
+        (true, Ok(false)) => "pending",
+        (true, Err(e)) => {
+            tracing::warn!("Readiness check failed: {:#}", e);
+            "failed"
+        }
+    }
+    .to_string()
+}
+
 /// Readiness probe (for Kubernetes)
 ///
-/// Returns 200 OK if the service is ready to accept traffic.
-pub async fn readiness(State(state): State<AppState>) -> StatusCode {
-    // Check if database is accessible
-    match db::check_health(&state.db).await {
-        Ok(_) => StatusCode::OK,
-        Err(_) => StatusCode::SERVICE_UNAVAILABLE,
-    }
+/// Returns 200 OK once the service is ready to accept traffic: the database
+/// is reachable and, as configured in `health.readiness`, the migrations
+/// have run, the RBAC roles are seeded and the search index has been built
+/// from PuppetDB. Returns 503 until then, so a cold replica gets no traffic.
+pub async fn readiness(State(state): State<AppState>) -> (StatusCode, Json<ReadinessResponse>) {
+    let config = &state.config.health.readiness;
+
+    let database = check_outcome(true, db::check_health(&state.db).await.map(|_| true));
+    let migrations = check_outcome(
+        config.migrations,
+        db::migrations::check_migrations(&state.db)
+            .await
+            .map(|applied| applied && state.is_inventory_ready()),
+    );
+    let rbac_seed = check_outcome(config.rbac_seed, db::check_rbac_seed(&state.db).await);
+    // The index is only built from PuppetDB by the cache sync job
+    let warms_up = state.puppetdb.is_some() && state.config.cache.search_index_interval_secs > 0;
+    let cache_warmup = check_outcome(
+        config.cache_warmup && warms_up,
+        Ok(state.health.last_success(health::SEARCH_INDEX).is_some()),
+    );
+
+    let checks = ReadinessChecks {
+        database,
+        migrations,
+        rbac_seed,
+        cache_warmup,
+    };
+    let (status_code, status) = if checks.ready() {
+        (StatusCode::OK, "ready")
+    } else {
+        (StatusCode::SERVICE_UNAVAILABLE, "not_ready")
+    };
+
+    (
+        status_code,
+        Json(ReadinessResponse {
+            status: status.to_string(),
+            checks,
+        }),
+    )
 }
 
 #[cfg(test)]
@@ -165,4 +376,113 @@ mod tests {
         assert_eq!(status.status, "unhealthy");
         assert_eq!(status.message.unwrap(), "Connection failed");
     }
+
+    #[tokio::test]
+    async fn test_probe_within_budget() {
+        let health = HealthTracker::default();
+        let status = probe(&health, "test-fast", 1000, Duration::from_secs(1), async {
+            Ok::<_, String>(())
+        })
+        .await;
+        assert_eq!(status.status, "healthy");
+        assert_eq!(status.budget_ms, Some(1000));
+        assert!(status.last_success.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_probe_over_budget_is_degraded() {
+        let health = HealthTracker::default();
+        let status = probe(&health, "test-slow", 0, Duration::from_secs(1), async {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            Ok::<_, String>(())
+        })
+        .await;
+        assert_eq!(status.status, "degraded");
+    }
+
+    #[tokio::test]
+    async fn test_probe_timeout_is_unhealthy() {
+        let health = HealthTracker::default();
+        let status = probe(
+            &health,
+            "test-hung",
+            1000,
+            Duration::from_millis(5),
+            async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok::<_, String>(())
+            },
+        )
+        .await;
+        assert_eq!(status.status, "unhealthy");
+        assert!(status.last_success.is_none());
+
+        let status = probe(
+            &health,
+            "test-failed",
+            1000,
+            Duration::from_secs(1),
+            async { Err::<(), _>("connection refused") },
+        )
+        .await;
+        assert_eq!(status.message.as_deref(), Some("connection refused"));
+    }
+
+    #[test]
+    fn test_scheduler_status() {
+        assert_eq!(scheduler_status(None, 300).status, "unhealthy");
+        assert_eq!(scheduler_status(Some(Utc::now()), 300).status, "healthy");
+        let stale = Utc::now() - chrono::Duration::seconds(301);
+        assert_eq!(scheduler_status(Some(stale), 300).status, "unhealthy");
+    }
+
+    #[test]
+    fn test_check_outcome() {
+        assert_eq!(check_outcome(false, Ok(false)), "disabled");
+        assert_eq!(check_outcome(true, Ok(true)), "passed");
+        assert_eq!(check_outcome(true, Ok(false)), "pending");
+        assert_eq!(
+            check_outcome(true, Err(anyhow::anyhow!("no such table"))),
+            "failed"
+        );
+    }
+
+    #[test]
+    fn test_overall_status() {
+        let components = |database: ComponentStatus, puppet_ca: ComponentStatus| ComponentHealth {
+            database,
+            puppetdb: ComponentStatus::not_configured(),
+            puppet_ca,
+            scheduler: ComponentStatus::healthy(),
+        };
+
+        assert_eq!(
+            overall_status(&components(
+                ComponentStatus::healthy(),
+                ComponentStatus::healthy()
+            )),
+            "healthy"
+        );
+        assert_eq!(
+            overall_status(&components(
+                ComponentStatus::healthy(),
+                ComponentStatus::unhealthy("down")
+            )),
+            "degraded"
+        );
+        assert_eq!(
+            overall_status(&components(
+                ComponentStatus::degraded("slow"),
+                ComponentStatus::healthy()
+            )),
+            "degraded"
+        );
+        assert_eq!(
+            overall_status(&components(
+                ComponentStatus::unhealthy("down"),
+                ComponentStatus::healthy()
+            )),
+            "unhealthy"
+        );
+    }
 }
@@ -0,0 +1,111 @@
+//! Hiera data endpoints
+//!
+//! Serve the group variables and class parameters of a node to a Hiera HTTP
+//! backend on Puppet Server, so the control repository can look up data
+//! managed in the WebUI. Authentication matches the public classification
+//! endpoint.
+
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    routing::get,
+    Json, Router,
+};
+use serde_json::{Map, Value};
+
+use super::nodes::{authenticate_node_request, is_classification_authentication_disabled};
+use crate::{
+    db::repository::GroupRepository,
+    middleware::OptionalClientCert,
+    models::default_organization_uuid,
+    services::{
+        classification::{build_node_classification_facts, ClassificationService},
+        hiera,
+    },
+    utils::error::{AppError, AppResult},
+    AppState,
+};
+
+/// Public routes (authenticated with the X-Classification-Key header or a
+/// client certificate)
+pub fn public_routes() -> Router<AppState> {
+    Router::new()
+        .route("/{environment}/{certname}", get(get_node_data))
+        .route("/{environment}/{certname}/{key}", get(get_node_key))
+}
+
+/// GET /api/v1/hiera/:environment/:certname
+///
+/// Returns every Hiera key of the node as one hash, for backends that dig the
+/// lookup key out of the response.
+async fn get_node_data(
+    State(state): State<AppState>,
+    Path((environment, certname)): Path<(String, String)>,
+    headers: HeaderMap,
+    client_cert: OptionalClientCert,
+) -> AppResult<Json<Map<String, Value>>> {
+    let data = node_data(&state, &environment, &certname, &headers, &client_cert).await?;
+    Ok(Json(data))
+}
+
+/// GET /api/v1/hiera/:environment/:certname/:key
+///
+/// Returns the value of a single key, or 404 when the node has no such key so
+/// Hiera falls through to the next level.
+async fn get_node_key(
+    State(state): State<AppState>,
+    Path((environment, certname, key)): Path<(String, String, String)>,
+    headers: HeaderMap,
+    client_cert: OptionalClientCert,
+) -> AppResult<Json<Value>> {
+    let mut data = node_data(&state, &environment, &certname, &headers, &client_cert).await?;
+    data.remove(&key)
+        .map(Json)
+        .ok_or_else(|| AppError::not_found(format!("Key '{}' not found for '{}'", key, certname)))
+}
+
+/// Classify the node for the environment being compiled and return its Hiera
+/// data
+///
+/// The environment is the node's `catalog_environment` fact during
+/// classification, so groups matching on it apply to the catalog Puppet
+/// Server is compiling rather than the last one the node received.
+async fn node_data(
+    state: &AppState,
+    environment: &str,
+    certname: &str,
+    headers: &HeaderMap,
+    client_cert: &OptionalClientCert,
+) -> AppResult<Map<String, Value>> {
+    if !is_classification_authentication_disabled(state) {
+        authenticate_node_request(state, certname, headers, client_cert).await?;
+    }
+
+    let puppetdb = state
+        .puppetdb
+        .as_ref()
+        .ok_or_else(|| AppError::ServiceUnavailable("PuppetDB is not configured".to_string()))?;
+
+    let facts = puppetdb
+        .get_node_facts(certname)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to fetch node facts: {}", e)))?;
+    let facts_json =
+        build_node_classification_facts(&state.db, facts, certname, Some(environment)).await;
+
+    let all_groups = GroupRepository::new(&state.db)
+        .get_all_across_organizations()
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to get groups: {}", e)))?;
+
+    let classification = ClassificationService::new(all_groups).classify_across_organizations(
+        certname,
+        &facts_json,
+        default_organization_uuid(),
+    );
+    if let Some(error) = classification.conflict_error {
+        return Err(AppError::conflict(error));
+    }
+
+    Ok(hiera::node_data(&classification))
+}
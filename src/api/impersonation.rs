@@ -0,0 +1,325 @@
+//! User impersonation endpoints
+//!
+//! Users with the `impersonate` action on users can act as another user of
+//! their organization for up to an hour, e.g. to reproduce a permission
+//! problem. The target's permissions must all be held by the caller, so an
+//! impersonation never grants more than the caller already has. The returned token has the permissions of the impersonated user,
+//! cannot change credentials, sessions or elevations, and names the admin as
+//! its real actor: every audited action made with it records both users.
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use chrono::{Duration, Utc};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    db::ImpersonationRepository,
+    middleware::auth::{
+        create_auth_session, create_impersonation_token, revoke_auth_session, AuthUser,
+        Impersonator, SessionClient,
+    },
+    models::{
+        Action, ImpersonationResponse, ImpersonationSession, Resource, StartImpersonationRequest,
+        DEFAULT_IMPERSONATION_MINUTES, MAX_IMPERSONATION_MINUTES,
+    },
+    services::{elevation::token_grant, AuthService},
+    utils::AppError,
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_impersonations).post(start_impersonation))
+        .route("/current", get(current_impersonation))
+        .route("/end", post(end_impersonation))
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct ListImpersonationsQuery {
+    organization_id: Option<Uuid>,
+    impersonator_id: Option<Uuid>,
+    user_id: Option<Uuid>,
+    limit: Option<u32>,
+}
+
+fn resolve_org(auth_user: &AuthUser, requested: Option<Uuid>) -> Result<Uuid, AppError> {
+    match requested {
+        Some(_) if !auth_user.is_super_admin() => Err(AppError::forbidden(
+            "organization_id can only be specified by super_admin",
+        )),
+        Some(org_id) => Ok(org_id),
+        None => Ok(auth_user.organization_id),
+    }
+}
+
+async fn require_impersonate(state: &AppState, auth_user: &AuthUser) -> Result<(), AppError> {
+    if auth_user.is_super_admin() {
+        return Ok(());
+    }
+
+    let check = state
+        .rbac_db
+        .check_permission(
+            &auth_user.user_id(),
+            Resource::Users,
+            Action::Impersonate,
+            None,
+            None,
+        )
+        .await
+        .map_err(|e| AppError::internal(format!("Permission check failed: {}", e)))?;
+    if check.allowed {
+        Ok(())
+    } else {
+        Err(AppError::forbidden(
+            "Impersonate permission on users required",
+        ))
+    }
+}
+
+async fn audit(
+    state: &AppState,
+    auth_user: &AuthUser,
+    action: &str,
+    session: &ImpersonationSession,
+) {
+    let _ = state
+        .audit_repository()
+        .insert(
+            session.organization_id,
+            Some(auth_user.user_id()),
+            action,
+            "impersonation_sessions",
+            Some(&session.id.to_string()),
+            Some(&serde_json::json!({
+                "impersonator_id": session.impersonator_id,
+                "impersonator": session.impersonator_username,
+                "user_id": session.user_id,
+                "username": session.username,
+                "reason": session.reason,
+                "expires_at": session.expires_at,
+            })),
+            None,
+        )
+        .await;
+}
+
+/// The impersonation behind the caller's token
+async fn load_current(
+    state: &AppState,
+    auth_user: &AuthUser,
+) -> Result<ImpersonationSession, AppError> {
+    if auth_user.impersonator.is_none() {
+        return Err(AppError::not_found("Not impersonating a user"));
+    }
+    let id = Uuid::parse_str(&auth_user.session_id)
+        .map_err(|_| AppError::not_found("Not impersonating a user"))?;
+
+    ImpersonationRepository::new(&state.db)
+        .get(id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get impersonation session: {}", e);
+            AppError::internal("Failed to get impersonation session")
+        })?
+        .ok_or_else(|| AppError::not_found("Not impersonating a user"))
+}
+
+/// List impersonations
+///
+/// GET /api/v1/impersonation
+async fn list_impersonations(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<ListImpersonationsQuery>,
+) -> Result<Json<Vec<ImpersonationSession>>, AppError> {
+    require_impersonate(&state, &auth_user).await?;
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+
+    let sessions = ImpersonationRepository::new(&state.db)
+        .list(
+            org_id,
+            query.impersonator_id,
+            query.user_id,
+            query.limit.unwrap_or(100).clamp(1, 1000),
+        )
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list impersonation sessions: {}", e);
+            AppError::internal("Failed to list impersonation sessions")
+        })?;
+    Ok(Json(sessions))
+}
+
+/// Start acting as another user
+///
+/// POST /api/v1/impersonation
+///
+/// Returns an access token for a new session of the target user. There is
+/// no refresh token; the impersonation ends when the token expires or is
+/// ended explicitly.
+async fn start_impersonation(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    client: SessionClient,
+    Json(payload): Json<StartImpersonationRequest>,
+) -> Result<(StatusCode, Json<ImpersonationResponse>), AppError> {
+    if auth_user.api_key_id.is_some() || Uuid::parse_str(&auth_user.session_id).is_err() {
+        return Err(AppError::bad_request(
+            "Impersonation can only be started from a login session",
+        ));
+    }
+    require_impersonate(&state, &auth_user).await?;
+
+    let minutes = payload
+        .duration_minutes
+        .unwrap_or(DEFAULT_IMPERSONATION_MINUTES);
+    if minutes == 0 || minutes > MAX_IMPERSONATION_MINUTES {
+        return Err(AppError::validation(format!(
+            "duration_minutes must be between 1 and {}",
+            MAX_IMPERSONATION_MINUTES
+        )));
+    }
+    let reason = payload.reason.trim();
+    if reason.is_empty() {
+        return Err(AppError::validation("A reason is required"));
+    }
+    if payload.user_id == auth_user.user_id() {
+        return Err(AppError::validation("You cannot impersonate yourself"));
+    }
+
+    // Only users of the caller's own organization, for super admins too
+    let auth_service = AuthService::new(state.db.clone());
+    let target = auth_service
+        .get_user_by_id_in_org(auth_user.organization_id, &payload.user_id)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to get user: {}", e)))?
+        .ok_or_else(|| AppError::not_found("User not found"))?;
+    if target.is_service_account() {
+        return Err(AppError::forbidden(
+            "Service accounts cannot be impersonated; they authenticate with API keys",
+        ));
+    }
+
+    let roles = auth_service
+        .get_user_roles(&target.id)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to get user roles: {}", e)))?;
+    if roles
+        .iter()
+        .any(|r| r == "super_admin" || r == "superadmin")
+    {
+        return Err(AppError::forbidden(
+            "super_admin users cannot be impersonated",
+        ));
+    }
+    // Impersonating must not grant the caller anything they lack
+    let covered = state
+        .rbac_db
+        .covers_permissions_of(&auth_user.user_id(), &target.id)
+        .await
+        .map_err(|e| AppError::internal(format!("Permission check failed: {}", e)))?;
+    if !covered {
+        return Err(AppError::forbidden(
+            "You can only impersonate users whose permissions you have",
+        ));
+    }
+
+    let expires_at = Utc::now() + Duration::minutes(minutes as i64);
+    let session_id = Uuid::new_v4();
+    create_auth_session(&state.db, &session_id, &target.id, expires_at, &client)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to create session: {:?}", e)))?;
+
+    let session = ImpersonationRepository::new(&state.db)
+        .create(
+            session_id,
+            target.organization_id,
+            auth_user.user_id(),
+            target.id,
+            reason,
+            expires_at,
+        )
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to create impersonation session: {}", e);
+            AppError::internal("Failed to create impersonation session")
+        })?;
+
+    let grant = token_grant(&state.db, target.id, roles, expires_at).await;
+    let access_token = create_impersonation_token(
+        &target.id,
+        &target.organization_id,
+        &session_id,
+        &target.username,
+        &target.email,
+        grant.roles,
+        &state.config.auth.jwt_secret,
+        &state.jwt_keys,
+        grant.expires_at,
+        &Impersonator {
+            id: auth_user.user_id(),
+            username: auth_user.username.clone(),
+        },
+    )
+    .map_err(|e| AppError::internal(format!("Failed to create access token: {}", e)))?;
+
+    audit(&state, &auth_user, "impersonation.start", &session).await;
+    Ok((
+        StatusCode::CREATED,
+        Json(ImpersonationResponse {
+            access_token,
+            token_type: "Bearer".to_string(),
+            expires_in: (grant.expires_at - Utc::now()).num_seconds().max(0) as u64,
+            session,
+        }),
+    ))
+}
+
+/// The impersonation of the current token
+///
+/// GET /api/v1/impersonation/current
+async fn current_impersonation(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<ImpersonationSession>, AppError> {
+    Ok(Json(load_current(&state, &auth_user).await?))
+}
+
+/// End the impersonation of the current token
+///
+/// POST /api/v1/impersonation/end
+///
+/// Revokes the impersonation session, so the token stops working
+/// immediately. The admin continues with their own token.
+async fn end_impersonation(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<ImpersonationSession>, AppError> {
+    let session = load_current(&state, &auth_user).await?;
+
+    let repo = ImpersonationRepository::new(&state.db);
+    let ended = repo.end(session.id).await.map_err(|e| {
+        tracing::error!("Failed to end impersonation session: {}", e);
+        AppError::internal("Failed to end impersonation session")
+    })?;
+    if !ended {
+        return Err(AppError::conflict("The impersonation has already ended"));
+    }
+    if let Err(e) = revoke_auth_session(&state.db, &auth_user.session_id).await {
+        tracing::error!(
+            "Failed to revoke impersonation session {}: {:?}",
+            session.id,
+            e
+        );
+    }
+
+    let session = load_current(&state, &auth_user).await?;
+    audit(&state, &auth_user, "impersonation.end", &session).await;
+    Ok(Json(session))
+}
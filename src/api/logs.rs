@@ -0,0 +1,144 @@
+//! Application log viewer API endpoints
+//!
+//! Serves recent log records from the in-memory log buffer, with level,
+//! target and text filters, and a follow mode over Server-Sent Events.
+
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    response::{
+        sse::{Event, KeepAlive},
+        Json, Sse,
+    },
+    routing::get,
+    Router,
+};
+use futures::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tokio_stream::wrappers::BroadcastStream;
+
+use super::payload_debug::require_settings_admin;
+use crate::middleware::AuthUser;
+use crate::services::log_buffer::{LogBuffer, LogFilter, LogRecord, MAX_QUERY_LIMIT};
+use crate::utils::error::AppError;
+use crate::AppState;
+
+/// Records returned when no limit is given
+const DEFAULT_LIMIT: usize = 200;
+
+/// Create log viewer routes
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_logs))
+        .route("/stream", get(stream_logs))
+}
+
+/// Log query parameters
+#[derive(Debug, Default, Deserialize)]
+pub struct LogQuery {
+    /// Minimum level (`error`, `warn`, `info`, `debug`, `trace`)
+    pub level: Option<String>,
+    /// Target prefix, e.g. `openvox_webui::services::puppetdb`
+    pub target: Option<String>,
+    /// Case-insensitive text search
+    pub search: Option<String>,
+    /// Only records after this sequence number
+    pub after: Option<u64>,
+    /// Maximum records returned (newest kept; default 200)
+    pub limit: Option<usize>,
+}
+
+impl LogQuery {
+    fn filter(&self) -> LogFilter {
+        LogFilter {
+            level: self.level.clone(),
+            target: self.target.clone(),
+            search: self.search.clone(),
+            after: self.after,
+        }
+    }
+
+    fn limit(&self) -> usize {
+        self.limit.unwrap_or(DEFAULT_LIMIT).min(MAX_QUERY_LIMIT)
+    }
+}
+
+/// Buffered log records
+#[derive(Debug, Serialize)]
+pub struct LogsResponse {
+    /// Number of records the buffer holds
+    pub capacity: usize,
+    /// Matching records, oldest first
+    pub records: Vec<LogRecord>,
+}
+
+fn buffer(state: &AppState) -> Result<Arc<LogBuffer>, AppError> {
+    state.log_buffer.clone().ok_or_else(|| {
+        AppError::service_unavailable("Log viewer is disabled (logging.buffer_lines is 0)")
+    })
+}
+
+/// List recent log records
+async fn list_logs(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<LogQuery>,
+) -> Result<Json<LogsResponse>, AppError> {
+    require_settings_admin(&state, &auth_user).await?;
+
+    let buffer = buffer(&state)?;
+    let filter = query.filter();
+    let min_level = filter.min_level().map_err(AppError::bad_request)?;
+
+    Ok(Json(LogsResponse {
+        capacity: buffer.capacity(),
+        records: buffer.query(&filter, min_level, query.limit()),
+    }))
+}
+
+/// Follow the log over Server-Sent Events
+///
+/// Sends the matching buffered records first, then new records as they are
+/// logged. Each event id is the record's sequence number, so a reconnecting
+/// client can pass it as `after` to resume without duplicates.
+async fn stream_logs(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<LogQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AppError> {
+    require_settings_admin(&state, &auth_user).await?;
+
+    let buffer = buffer(&state)?;
+    let filter = query.filter();
+    let min_level = filter.min_level().map_err(AppError::bad_request)?;
+
+    // Subscribe before reading the backlog so nothing is missed in between
+    let receiver = buffer.subscribe();
+    let backlog = buffer.query(&filter, min_level, query.limit());
+    let last_seq = backlog.last().map(|r| r.seq).or(filter.after).unwrap_or(0);
+
+    let live = BroadcastStream::new(receiver).filter_map(move |result| {
+        let record = match result {
+            Ok(record) if record.seq > last_seq && filter.matches(&record, min_level) => {
+                Some(record)
+            }
+            // Records that match nothing, or were dropped because this
+            // client fell behind, are skipped
+            _ => None,
+        };
+        async move { record }
+    });
+
+    let stream = stream::iter(backlog).chain(live).map(|record| {
+        Ok::<_, Infallible>(
+            Event::default()
+                .id(record.seq.to_string())
+                .json_data(&record)
+                .unwrap_or_default(),
+        )
+    });
+
+    Ok(Sse::new(stream).keep_alive(KeepAlive::default()))
+}
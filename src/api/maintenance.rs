@@ -0,0 +1,322 @@
+//! Maintenance window endpoints
+//!
+//! Windows belong to an organization. Everyone in the organization can see
+//! them; creating, changing and deleting them requires the admin or operator
+//! role.
+
+use std::collections::HashSet;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    db::{repository::GroupRepository, MaintenanceWindowRepository},
+    middleware::AuthUser,
+    models::{
+        CreateMaintenanceWindowRequest, MaintenanceSchedule, MaintenanceTarget, MaintenanceWindow,
+        MaintenanceWindowStatus, NodeInMaintenance, UpdateMaintenanceWindowRequest,
+    },
+    services::maintenance::{active_period, next_start, validate_window, ActiveMaintenance},
+    utils::AppError,
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_windows).post(create_window))
+        .route("/active", get(list_nodes_in_maintenance))
+        .route(
+            "/{id}",
+            get(get_window).put(update_window).delete(delete_window),
+        )
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct OrgQuery {
+    organization_id: Option<Uuid>,
+}
+
+fn resolve_org(auth_user: &AuthUser, requested: Option<Uuid>) -> Result<Uuid, AppError> {
+    match requested {
+        Some(_) if !auth_user.is_super_admin() => Err(AppError::forbidden(
+            "organization_id can only be specified by super_admin",
+        )),
+        Some(org_id) => Ok(org_id),
+        None => Ok(auth_user.organization_id),
+    }
+}
+
+fn require_write_permission(auth_user: &AuthUser) -> Result<(), AppError> {
+    let allowed = auth_user.is_super_admin()
+        || auth_user
+            .roles
+            .iter()
+            .any(|r| r == "admin" || r == "operator");
+    if allowed {
+        Ok(())
+    } else {
+        Err(AppError::forbidden(
+            "Admin or operator role required to manage maintenance windows",
+        ))
+    }
+}
+
+fn window_status(window: MaintenanceWindow) -> MaintenanceWindowStatus {
+    let now = Utc::now();
+    let period = active_period(&window.schedule, now).filter(|_| window.enabled);
+    let next_start = next_start(&window.schedule, now).filter(|_| window.enabled);
+    MaintenanceWindowStatus {
+        window,
+        active: period.is_some(),
+        active_until: period.map(|(_, end)| end),
+        next_start,
+    }
+}
+
+/// Validate a window and check that a targeted group exists in the organization
+async fn validate_request(
+    state: &AppState,
+    org_id: Uuid,
+    name: Option<&str>,
+    target: &MaintenanceTarget,
+    schedule: &MaintenanceSchedule,
+) -> Result<(), AppError> {
+    if name.is_some_and(|n| n.trim().is_empty()) {
+        return Err(AppError::bad_request(
+            "Maintenance window name cannot be empty",
+        ));
+    }
+
+    validate_window(target, schedule).map_err(AppError::validation)?;
+
+    if let MaintenanceTarget::Group { group_id } = target {
+        GroupRepository::new(&state.db)
+            .get_by_id(org_id, *group_id)
+            .await
+            .map_err(|e| {
+                tracing::error!("Failed to get group: {}", e);
+                AppError::internal("Failed to get group")
+            })?
+            .ok_or_else(|| AppError::validation("Target group not found"))?;
+    }
+
+    Ok(())
+}
+
+async fn load_window(
+    state: &AppState,
+    org_id: Uuid,
+    id: Uuid,
+) -> Result<MaintenanceWindow, AppError> {
+    MaintenanceWindowRepository::new(&state.db)
+        .get_by_id(org_id, id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get maintenance window: {}", e);
+            AppError::internal("Failed to get maintenance window")
+        })?
+        .ok_or_else(|| AppError::not_found("Maintenance window not found"))
+}
+
+/// List the organization's maintenance windows with their current state
+async fn list_windows(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+) -> Result<Json<Vec<MaintenanceWindowStatus>>, AppError> {
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let windows = MaintenanceWindowRepository::new(&state.db)
+        .list(org_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list maintenance windows: {}", e);
+            AppError::internal("Failed to list maintenance windows")
+        })?;
+
+    Ok(Json(windows.into_iter().map(window_status).collect()))
+}
+
+/// List the organization's nodes currently in maintenance
+async fn list_nodes_in_maintenance(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+) -> Result<Json<Vec<NodeInMaintenance>>, AppError> {
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let org_windows: HashSet<Uuid> = MaintenanceWindowRepository::new(&state.db)
+        .list(org_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list maintenance windows: {}", e);
+            AppError::internal("Failed to list maintenance windows")
+        })?
+        .into_iter()
+        .map(|w| w.id)
+        .collect();
+
+    let puppetdb = state.puppetdb_for(org_id).await;
+    let active = ActiveMaintenance::load(&state.db, puppetdb.as_deref(), Utc::now())
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to resolve maintenance windows: {}", e);
+            AppError::internal("Failed to resolve maintenance windows")
+        })?;
+
+    let nodes = active
+        .into_nodes()
+        .into_iter()
+        .filter_map(|mut node| {
+            node.windows.retain(|w| org_windows.contains(&w.window_id));
+            (!node.windows.is_empty()).then_some(node)
+        })
+        .collect();
+
+    Ok(Json(nodes))
+}
+
+async fn create_window(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Json(payload): Json<CreateMaintenanceWindowRequest>,
+) -> Result<(StatusCode, Json<MaintenanceWindowStatus>), AppError> {
+    require_write_permission(&auth_user)?;
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    validate_request(
+        &state,
+        org_id,
+        Some(&payload.name),
+        &payload.target,
+        &payload.schedule,
+    )
+    .await?;
+
+    let window = MaintenanceWindowRepository::new(&state.db)
+        .create(org_id, auth_user.user_id(), &payload)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to create maintenance window: {}", e);
+            AppError::internal("Failed to create maintenance window")
+        })?;
+
+    let audit_repo = state.audit_repository();
+    let _ = audit_repo
+        .insert(
+            org_id,
+            Some(auth_user.user_id()),
+            "maintenance_window.create",
+            "maintenance_windows",
+            Some(&window.id.to_string()),
+            Some(&serde_json::json!({
+                "name": window.name,
+                "target": window.target,
+                "schedule": window.schedule,
+            })),
+            None,
+        )
+        .await;
+
+    Ok((StatusCode::CREATED, Json(window_status(window))))
+}
+
+async fn get_window(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<MaintenanceWindowStatus>, AppError> {
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let window = load_window(&state, org_id, id).await?;
+    Ok(Json(window_status(window)))
+}
+
+async fn update_window(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateMaintenanceWindowRequest>,
+) -> Result<Json<MaintenanceWindowStatus>, AppError> {
+    require_write_permission(&auth_user)?;
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let existing = load_window(&state, org_id, id).await?;
+
+    // Validate the window as it will be stored
+    let target = payload.target.as_ref().unwrap_or(&existing.target);
+    let schedule = payload.schedule.as_ref().unwrap_or(&existing.schedule);
+    validate_request(&state, org_id, payload.name.as_deref(), target, schedule).await?;
+
+    let window = MaintenanceWindowRepository::new(&state.db)
+        .update(org_id, id, &payload)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to update maintenance window: {}", e);
+            AppError::internal("Failed to update maintenance window")
+        })?
+        .ok_or_else(|| AppError::not_found("Maintenance window not found"))?;
+
+    let audit_repo = state.audit_repository();
+    let _ = audit_repo
+        .insert(
+            org_id,
+            Some(auth_user.user_id()),
+            "maintenance_window.update",
+            "maintenance_windows",
+            Some(&window.id.to_string()),
+            Some(&serde_json::json!({
+                "name": window.name,
+                "target": window.target,
+                "schedule": window.schedule,
+                "enabled": window.enabled,
+            })),
+            None,
+        )
+        .await;
+
+    Ok(Json(window_status(window)))
+}
+
+async fn delete_window(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    require_write_permission(&auth_user)?;
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let existing = load_window(&state, org_id, id).await?;
+
+    let deleted = MaintenanceWindowRepository::new(&state.db)
+        .delete(org_id, id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to delete maintenance window: {}", e);
+            AppError::internal("Failed to delete maintenance window")
+        })?;
+
+    if !deleted {
+        return Err(AppError::not_found("Maintenance window not found"));
+    }
+
+    let audit_repo = state.audit_repository();
+    let _ = audit_repo
+        .insert(
+            org_id,
+            Some(auth_user.user_id()),
+            "maintenance_window.delete",
+            "maintenance_windows",
+            Some(&id.to_string()),
+            Some(&serde_json::json!({ "name": existing.name })),
+            None,
+        )
+        .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -14,40 +14,57 @@ mod auth;
 mod backup;
 mod bootstrap;
 mod ca;
+mod classification_keys;
 mod code_deploy;
 mod cve;
+mod elevations;
+mod enc;
 mod facter;
 mod facts;
 pub(crate) mod groups;
 mod health;
+mod hiera;
+mod impersonation;
 mod inventory;
+mod logs;
+mod maintenance;
 mod node_removal;
 mod nodes;
 mod notifications;
+mod openapi;
 mod organizations;
+mod payload_debug;
 mod permissions;
 mod query;
+mod recycle_bin;
 mod reports;
 mod roles;
 mod saml;
+mod search;
+mod sessions;
 mod settings;
+mod smart_lists;
 mod users;
+mod webhooks;
 
 pub use health::*;
 
 /// Public API routes (no authentication required)
 pub fn public_routes() -> Router<AppState> {
-    Router::new()
-        // Health check endpoints
-        .route("/health", get(health::health_check))
-        .route("/health/detailed", get(health::health_check_detailed))
-        .route("/health/live", get(health::liveness))
-        .route("/health/ready", get(health::readiness))
+    public_routes_without_enc().merge(enc_routes())
+}
+
+/// Public API routes except the ENC endpoints
+///
+/// Used for the main listeners when the ENC endpoints are served exclusively
+/// from `server.enc_listener`.
+pub fn public_routes_without_enc() -> Router<AppState> {
+    health_routes()
         // Authentication endpoints (no auth required)
         .nest("/auth", auth::public_routes())
         // SAML SSO endpoints (no auth required)
         .nest("/auth/saml", saml::public_routes())
-        // Node classification endpoint for Puppet agents (uses client cert auth)
+        // Node inventory and update-job endpoints for Puppet agents
         .nest("/nodes", nodes::public_routes())
         // Webhook endpoints (use signature verification instead of auth)
         .nest("/webhooks", code_deploy::webhook_routes())
@@ -55,6 +72,30 @@ pub fn public_routes() -> Router<AppState> {
         .nest("/settings", settings::public_routes())
         // Bootstrap script endpoints (no auth required for node enrollment)
         .nest("/bootstrap", bootstrap::public_routes())
+        // OpenAPI document and reference page
+        .merge(openapi::routes())
+}
+
+/// ENC and Hiera endpoints used by Puppet Server (no authentication required)
+pub fn enc_routes() -> Router<AppState> {
+    Router::new()
+        .nest("/nodes", nodes::enc_routes())
+        .nest("/enc", enc::public_routes())
+        .nest("/hiera", hiera::public_routes())
+}
+
+/// Routes served by the dedicated ENC listener
+pub fn enc_listener_routes() -> Router<AppState> {
+    health_routes().merge(enc_routes())
+}
+
+/// Health check endpoints
+fn health_routes() -> Router<AppState> {
+    Router::new()
+        .route("/health", get(health::health_check))
+        .route("/health/detailed", get(health::health_check_detailed))
+        .route("/health/live", get(health::liveness))
+        .route("/health/ready", get(health::readiness))
 }
 
 /// Protected API routes (authentication required)
@@ -65,12 +106,22 @@ pub fn protected_routes() -> Router<AppState> {
         // Resource endpoints
         .nest("/nodes", nodes::routes())
         .nest("/groups", groups::routes())
+        .nest("/smart-lists", smart_lists::routes())
+        .nest("/maintenance-windows", maintenance::routes())
+        .nest("/webhooks", webhooks::routes())
+        .nest("/search", search::routes())
+        .nest("/recycle-bin", recycle_bin::routes())
         .nest("/facts", facts::routes())
         .nest("/facter", facter::routes())
         .nest("/reports", reports::routes())
         .nest("/api-keys", api_keys::routes())
+        .nest("/classification-keys", classification_keys::routes())
+        .nest("/enc", enc::routes())
         .nest("/audit-logs", audit_logs::routes())
         .nest("/roles", roles::routes())
+        .nest("/elevations", elevations::routes())
+        .nest("/impersonation", impersonation::routes())
+        .nest("/sessions", sessions::routes())
         .nest("/users", users::routes())
         .nest("/organizations", organizations::routes())
         .nest("/permissions", permissions::routes())
@@ -95,6 +146,10 @@ pub fn protected_routes() -> Router<AppState> {
         .nest("/inventory", inventory::routes())
         // CVE vulnerability endpoints
         .nest("/cve", cve::routes())
+        // Payload capture for debugging integrations
+        .nest("/debug/payloads", payload_debug::routes())
+        // Application log viewer
+        .nest("/logs", logs::routes())
 }
 
 /// Create the full API router (public + protected; useful for tests)
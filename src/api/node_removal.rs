@@ -13,9 +13,10 @@ use crate::{
     db::NodeRemovalRepository,
     middleware::AuthUser,
     models::{
-        ExtendRemovalDeadlineRequest, MarkNodeForRemovalRequest, NodeRemovalAudit,
-        PendingNodeRemovalResponse, PendingRemovalStats, RemovalReason,
+        ExtendRemovalDeadlineRequest, JanitorRun, MarkNodeForRemovalRequest, NodeRemovalAudit,
+        PendingNodeRemovalResponse, PendingRemovalStats, RemovalReason, RunJanitorRequest,
     },
+    services::node_janitor,
     utils::AppError,
     AppState,
 };
@@ -37,6 +38,10 @@ pub fn routes() -> Router<AppState> {
         // Audit log
         .route("/audit", get(list_audit_log))
         .route("/audit/{certname}", get(get_node_audit_log))
+        // Stale node janitor
+        .route("/janitor/run", post(run_janitor))
+        .route("/janitor/runs", get(list_janitor_runs))
+        .route("/janitor/runs/{id}", get(get_janitor_run))
 }
 
 /// Check if user has read permission for node removal
@@ -89,6 +94,18 @@ pub struct NodeRemovalFeatureStatus {
     pub check_interval_secs: u64,
     pub puppetdb_connected: bool,
     pub puppet_ca_connected: bool,
+    pub janitor: JanitorStatus,
+}
+
+/// Stale node janitor settings
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct JanitorStatus {
+    pub enabled: bool,
+    pub stale_after_days: i64,
+    pub interval_secs: u64,
+    pub deactivate: bool,
+    pub flag_certificates: bool,
+    pub exclude: Vec<String>,
 }
 
 /// Get node removal feature status
@@ -106,6 +123,14 @@ async fn get_feature_status(
         check_interval_secs: config.check_interval_secs.unwrap_or(300),
         puppetdb_connected: state.puppetdb.is_some(),
         puppet_ca_connected: state.puppet_ca.is_some(),
+        janitor: JanitorStatus {
+            enabled: config.enabled && config.janitor.enabled,
+            stale_after_days: config.janitor.stale_after_days,
+            interval_secs: config.janitor.interval_secs,
+            deactivate: config.janitor.deactivate,
+            flag_certificates: config.janitor.flag_certificates,
+            exclude: config.janitor.exclude,
+        },
     }))
 }
 
@@ -297,3 +322,74 @@ async fn get_node_audit_log(
 
     Ok(Json(audit))
 }
+
+// ============================================================================
+// Stale Node Janitor
+// ============================================================================
+
+/// Run the stale node janitor now
+///
+/// Uses the configured janitor settings even when the scheduled janitor is
+/// disabled. With `dry_run` nothing is changed.
+async fn run_janitor(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    request: Option<Json<RunJanitorRequest>>,
+) -> Result<Json<JanitorRun>, AppError> {
+    require_write_permission(&auth_user)?;
+
+    let Some(puppetdb) = state.puppetdb.as_ref() else {
+        return Err(AppError::ServiceUnavailable(
+            "PuppetDB is not configured".to_string(),
+        ));
+    };
+
+    let config: NodeRemovalConfig = state.config.node_removal.clone().unwrap_or_default();
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+
+    let run = node_janitor::run_janitor(
+        &state.db,
+        puppetdb,
+        &config,
+        request.dry_run,
+        Some(&auth_user.username),
+    )
+    .await
+    .map_err(|e| AppError::Internal(format!("Janitor run failed: {}", e)))?;
+
+    Ok(Json(run))
+}
+
+/// List recent janitor runs
+async fn list_janitor_runs(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<Vec<JanitorRun>>, AppError> {
+    require_read_permission(&auth_user)?;
+
+    let repo = NodeRemovalRepository::new(state.db.clone());
+    let runs = repo
+        .list_janitor_runs(100)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to fetch janitor runs: {}", e)))?;
+
+    Ok(Json(runs))
+}
+
+/// Get a janitor run with the nodes it purged
+async fn get_janitor_run(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<JanitorRun>, AppError> {
+    require_read_permission(&auth_user)?;
+
+    let repo = NodeRemovalRepository::new(state.db.clone());
+    let run = repo
+        .get_janitor_run(&id)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to fetch janitor run: {}", e)))?
+        .ok_or_else(|| AppError::NotFound(format!("Janitor run not found: {}", id)))?;
+
+    Ok(Json(run))
+}
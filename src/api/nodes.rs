@@ -4,24 +4,44 @@
 
 use axum::{
     extract::{Path, Query, State},
-    http::{header::HeaderMap, StatusCode},
+    http::{
+        header::{self, HeaderMap},
+        HeaderValue, StatusCode,
+    },
+    response::{IntoResponse, Response},
     routing::{get, post},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
 use tracing::{debug, warn};
 
+use super::classification_keys;
 use crate::{
-    db::{repository::GroupRepository, InventoryRepository},
+    db::{
+        repository::{FactTemplateRepository, GroupRepository},
+        ClassificationKeyRepository, InventoryRepository, NodeMetadataRepository,
+    },
     middleware::{AuthUser, OptionalClientCert},
     models::{
-        default_organization_uuid, Action, ClassificationResult, Fact, InventoryPayload,
-        InventorySnapshotSummary, Node, NodeInventory, NodePendingUpdateJob, Report,
-        Resource as RbacResource, SubmitUpdateJobResultRequest, UpdateJob,
+        default_organization_uuid, Action, ClassificationResult, ExportFormat, Fact,
+        InfrastructureTopology, InventoryPayload, InventorySnapshotSummary, Node, NodeInventory,
+        NodeMetadata, NodeMetadataQuery, NodePendingUpdateJob, NodePerformance, Report, ReportDiff,
+        ReportDiffSide, Resource as RbacResource, SubmitUpdateJobResultRequest, TicketLink,
+        UpdateJob, UpdateNodeMetadataRequest,
     },
     services::{
-        classification::{build_classification_facts, ClassificationService},
+        classification::{build_node_classification_facts, ClassificationService},
+        facter::{ExportFormat as FactFileFormat, FacterService, FactsPackage, FactsPackageFile},
+        maintenance::{node_maintenance, ActiveMaintenance},
+        node_performance::{
+            node_performance, DEFAULT_REGRESSION_PERCENT, DEFAULT_REGRESSION_SECONDS,
+        },
         puppetdb::{NodeStats, QueryBuilder, QueryParams, Resource},
+        report_diff::{
+            diff_resources, timing_regressions, DEFAULT_MIN_INCREASE_PERCENT,
+            DEFAULT_MIN_INCREASE_SECONDS,
+        },
+        topology::load_topology,
     },
     utils::error::{AppError, AppResult},
     AppState,
@@ -29,14 +49,24 @@ use crate::{
 
 const POST_INGEST_CATALOG_REFRESH_DEBOUNCE_SECS: i64 = 120;
 
+/// Limits on node metadata accepted from the API
+const MAX_NODE_TAGS: usize = 50;
+const MAX_NODE_TAG_LEN: usize = 64;
+const MAX_TICKET_LINKS: usize = 20;
+const MAX_NODE_NOTES_LEN: usize = 64 * 1024;
+
 /// Create routes for node endpoints (protected, requires JWT auth)
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/", get(list_nodes))
         .route("/stats", get(get_node_stats))
+        .route("/topology", get(get_topology))
+        .route("/metadata", get(search_node_metadata))
         .route("/{certname}", get(get_node).delete(delete_node))
         .route("/{certname}/facts", get(get_node_facts))
         .route("/{certname}/reports", get(get_node_reports))
+        .route("/{certname}/reports/diff", get(get_node_report_diff))
+        .route("/{certname}/performance", get(get_node_performance))
         .route("/{certname}/resources", get(get_node_resources))
         .route("/{certname}/catalog", get(get_node_catalog))
         .route("/{certname}/classification", get(get_node_classification))
@@ -45,20 +75,40 @@ pub fn routes() -> Router<AppState> {
             "/{certname}/inventory/history",
             get(get_node_inventory_history),
         )
+        .route(
+            "/{certname}/metadata",
+            get(get_node_metadata)
+                .put(update_node_metadata)
+                .delete(delete_node_metadata),
+        )
 }
 
 /// Public routes for node endpoints (no JWT required, uses client cert auth)
-/// These endpoints are used by Puppet agents to fetch their own classification
+/// These endpoints are used by Puppet agents to report inventory, fetch
+/// update jobs and their external facts; the ENC endpoints are in
+/// [`enc_routes`]
 pub fn public_routes() -> Router<AppState> {
     Router::new()
-        // Use /classify path to avoid conflict with protected /classification endpoint
-        .route("/{certname}/classify", get(get_node_classification_public))
         .route("/{certname}/inventory", post(ingest_node_inventory))
         .route("/{certname}/update-jobs", get(get_pending_node_update_jobs))
         .route(
             "/{certname}/update-jobs/{job_id}/targets/{target_id}/results",
             post(submit_node_update_job_result),
         )
+        .route("/{certname}/facts-package", get(get_facts_package))
+        .route(
+            "/{certname}/facts-package/checksum",
+            get(get_facts_package_checksum),
+        )
+}
+
+/// ENC routes (no JWT required) used by Puppet Server to classify nodes
+///
+/// Can be served from a dedicated listener (`server.enc_listener`).
+pub fn enc_routes() -> Router<AppState> {
+    Router::new()
+        // Use /classify path to avoid conflict with protected /classification endpoint
+        .route("/{certname}/classify", get(get_node_classification_public))
         // Environment-only endpoint (unauthenticated) - used early in Puppet agent run
         .route("/{certname}/environment", get(get_node_environment_public))
 }
@@ -85,6 +135,10 @@ pub struct NodesQuery {
     pub order_by: Option<String>,
     /// Order direction (asc/desc)
     pub order_dir: Option<String>,
+    /// Restrict results to the nodes matching a saved smart list
+    pub smart_list: Option<uuid::Uuid>,
+    /// Restrict results to the nodes carrying this metadata tag
+    pub tag: Option<String>,
 }
 
 // For compatibility with existing tests, return a plain array.
@@ -102,19 +156,26 @@ pub struct NodesQuery {
 /// - `offset`: Number of results to skip
 /// - `order_by`: Field to order by (default: certname)
 /// - `order_dir`: Order direction (asc/desc, default: asc)
+/// - `smart_list`: Restrict results to the nodes matching a saved smart list
+/// - `tag`: Restrict results to the nodes carrying this metadata tag
 ///
 /// The total number of matching nodes (independent of pagination) is returned
 /// in the `X-Total-Count` response header so the UI can render correct counts
 /// and page controls without fetching the whole fleet.
 async fn list_nodes(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Query(query): Query<NodesQuery>,
 ) -> AppResult<(HeaderMap, Json<Vec<Node>>)> {
     // If PuppetDB is not configured, return empty list (stub behavior expected by tests)
-    let Some(puppetdb) = state.puppetdb.as_ref() else {
+    let Some(puppetdb) = state.puppetdb_for(auth_user.organization_id).await else {
         return Ok((HeaderMap::new(), Json(vec![])));
     };
 
+    if let Some(list_id) = query.smart_list {
+        return list_smart_list_nodes(&state, &auth_user, list_id, &query).await;
+    }
+
     // Build query
     let mut qb = QueryBuilder::new();
 
@@ -130,6 +191,19 @@ async fn list_nodes(
         qb = qb.matches("certname", search);
     }
 
+    if let Some(ref tag) = query.tag {
+        let certnames = NodeMetadataRepository::new(&state.db)
+            .certnames_with_tag(tag)
+            .await?;
+        if certnames.is_empty() {
+            let mut headers = HeaderMap::new();
+            headers.insert("X-Total-Count", HeaderValue::from_static("0"));
+            return Ok((headers, Json(vec![])));
+        }
+        let certnames: Vec<&str> = certnames.iter().map(String::as_str).collect();
+        qb = qb.in_array("certname", &certnames);
+    }
+
     // Build pagination params. The limit defaults to the configured page size
     // and is clamped to the configured maximum to keep responses bounded.
     let limit = state.config.pagination.resolve_limit(query.limit);
@@ -159,6 +233,62 @@ async fn list_nodes(
     Ok((headers, Json(result.data)))
 }
 
+/// List nodes from a smart list, applying the remaining query parameters in
+/// memory since smart list membership cannot be expressed as a single PuppetDB
+/// query.
+async fn list_smart_list_nodes(
+    state: &AppState,
+    auth_user: &AuthUser,
+    list_id: uuid::Uuid,
+    query: &NodesQuery,
+) -> AppResult<(HeaderMap, Json<Vec<Node>>)> {
+    let list = super::smart_lists::load_visible_smart_list(
+        state,
+        auth_user,
+        auth_user.organization_id,
+        list_id,
+    )
+    .await?;
+    let mut nodes = super::smart_lists::resolve_nodes_for_request(state, &list).await?;
+
+    if let Some(ref env) = query.environment {
+        nodes.retain(|n| n.catalog_environment.as_deref() == Some(env.as_str()));
+    }
+    if let Some(ref status) = query.status {
+        nodes.retain(|n| n.latest_report_status.as_deref() == Some(status.as_str()));
+    }
+    if let Some(ref search) = query.search {
+        let re = regex::Regex::new(search)
+            .map_err(|e| AppError::bad_request(format!("Invalid search pattern: {}", e)))?;
+        nodes.retain(|n| re.is_match(&n.certname));
+    }
+    if let Some(ref tag) = query.tag {
+        let tagged: std::collections::HashSet<String> = NodeMetadataRepository::new(&state.db)
+            .certnames_with_tag(tag)
+            .await?
+            .into_iter()
+            .collect();
+        nodes.retain(|n| tagged.contains(&n.certname));
+    }
+
+    // Nodes come back sorted by certname; only the direction needs handling
+    if query.order_dir.as_deref() == Some("desc") {
+        nodes.reverse();
+    }
+
+    let total = nodes.len();
+    let limit = state.config.pagination.resolve_limit(query.limit) as usize;
+    let offset = query.offset.unwrap_or(0) as usize;
+    let page: Vec<Node> = nodes.into_iter().skip(offset).take(limit).collect();
+
+    let mut headers = HeaderMap::new();
+    if let Ok(value) = total.to_string().parse() {
+        headers.insert("X-Total-Count", value);
+    }
+
+    Ok((headers, Json(page)))
+}
+
 /// Get aggregate node statistics
 ///
 /// GET /api/v1/nodes/stats
@@ -166,29 +296,85 @@ async fn list_nodes(
 /// Returns fleet-wide counts (total, by status, by environment) computed by
 /// PuppetDB aggregate queries. This lets dashboards show accurate counts
 /// without fetching every node record.
-async fn get_node_stats(State(state): State<AppState>) -> AppResult<Json<NodeStats>> {
-    let Some(puppetdb) = state.puppetdb.as_ref() else {
+async fn get_node_stats(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> AppResult<Json<NodeStats>> {
+    let Some(puppetdb) = state.puppetdb_for(auth_user.organization_id).await else {
         return Ok(Json(NodeStats::default()));
     };
 
-    let stats = puppetdb
+    let mut stats = puppetdb
         .get_node_stats()
         .await
         .map_err(|e| AppError::Internal(format!("Failed to query node stats: {}", e)))?;
 
+    // Failures of nodes in maintenance are expected; count them separately
+    let now = chrono::Utc::now();
+    let maintenance =
+        ActiveMaintenance::load_or_empty(&state.db, Some(puppetdb.as_ref()), now).await;
+    if !maintenance.is_empty() {
+        let certnames: Vec<&str> = maintenance.certnames().collect();
+        match puppetdb
+            .query_nodes(&QueryBuilder::new().in_array("certname", &certnames))
+            .await
+        {
+            Ok(nodes) => maintenance.exclude_from_stats(&mut stats, &nodes, now),
+            Err(e) => warn!("Failed to query nodes in maintenance: {}", e),
+        }
+    }
+
     Ok(Json(stats))
 }
 
+/// Query parameters of the infrastructure topology
+#[derive(Debug, Deserialize)]
+pub struct TopologyQuery {
+    /// Return a node for each agent (default: true)
+    pub include_agents: Option<bool>,
+}
+
+/// Get the infrastructure topology
+///
+/// GET /api/v1/nodes/topology
+///
+/// Returns the graph of the servers, PuppetDB, the CA and the agents, with
+/// the health of each. Query parameters:
+/// - `include_agents`: Set to false to return only the servers' agent counts
+async fn get_topology(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<TopologyQuery>,
+) -> AppResult<Json<InfrastructureTopology>> {
+    let puppetdb = state
+        .puppetdb_for(auth_user.organization_id)
+        .await
+        .ok_or_else(|| AppError::ServiceUnavailable("PuppetDB is not configured".to_string()))?;
+
+    let topology = load_topology(
+        &puppetdb,
+        state.puppet_ca.as_deref(),
+        &state.config.health,
+        state.config.dashboard.inactive_threshold_hours,
+        query.include_agents.unwrap_or(true),
+    )
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to build topology: {}", e)))?;
+
+    Ok(Json(topology))
+}
+
 /// Get a specific node by certname
 ///
 /// GET /api/v1/nodes/:certname
 async fn get_node(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(certname): Path<String>,
 ) -> AppResult<Json<Node>> {
     let puppetdb = state
-        .puppetdb
-        .as_ref()
+        .puppetdb_for(auth_user.organization_id)
+        .await
         .ok_or_else(|| AppError::ServiceUnavailable("PuppetDB is not configured".to_string()))?;
 
     let node = puppetdb
@@ -215,12 +401,13 @@ pub struct NodeFactsQuery {
 /// - `name`: Filter by fact name
 async fn get_node_facts(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(certname): Path<String>,
     Query(query): Query<NodeFactsQuery>,
 ) -> AppResult<Json<Vec<Fact>>> {
     let puppetdb = state
-        .puppetdb
-        .as_ref()
+        .puppetdb_for(auth_user.organization_id)
+        .await
         .ok_or_else(|| AppError::ServiceUnavailable("PuppetDB is not configured".to_string()))?;
 
     // First verify the node exists
@@ -270,12 +457,13 @@ pub struct NodeReportsQuery {
 /// - `limit`: Maximum number of results (default: 10)
 async fn get_node_reports(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(certname): Path<String>,
     Query(query): Query<NodeReportsQuery>,
 ) -> AppResult<Json<Vec<Report>>> {
     let puppetdb = state
-        .puppetdb
-        .as_ref()
+        .puppetdb_for(auth_user.organization_id)
+        .await
         .ok_or_else(|| AppError::ServiceUnavailable("PuppetDB is not configured".to_string()))?;
 
     // First verify the node exists
@@ -308,6 +496,159 @@ async fn get_node_reports(
     Ok(Json(reports))
 }
 
+/// Query parameters for the node report diff
+#[derive(Debug, Deserialize)]
+pub struct ReportDiffQuery {
+    /// Hash of the earlier report
+    pub from: String,
+    /// Hash of the later report
+    pub to: String,
+    /// Minimum time increase, in seconds, reported as a regression
+    pub min_increase_seconds: Option<f64>,
+    /// Minimum time increase, in percent, reported as a regression
+    pub min_increase_percent: Option<f64>,
+}
+
+/// Compare two reports of a node
+///
+/// GET /api/v1/nodes/:certname/reports/diff?from=<hash>&to=<hash>
+///
+/// Returns the resources that started failing, stopped failing or kept
+/// failing between the two runs, and the time metrics that grew by at least
+/// `min_increase_seconds` (default 1) and `min_increase_percent` (default 20).
+async fn get_node_report_diff(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(certname): Path<String>,
+    Query(query): Query<ReportDiffQuery>,
+) -> AppResult<Json<ReportDiff>> {
+    let puppetdb = state
+        .puppetdb_for(auth_user.organization_id)
+        .await
+        .ok_or_else(|| AppError::ServiceUnavailable("PuppetDB is not configured".to_string()))?;
+
+    let min_increase_seconds = query
+        .min_increase_seconds
+        .unwrap_or(DEFAULT_MIN_INCREASE_SECONDS);
+    let min_increase_percent = query
+        .min_increase_percent
+        .unwrap_or(DEFAULT_MIN_INCREASE_PERCENT);
+    if min_increase_seconds < 0.0 || min_increase_percent < 0.0 {
+        return Err(AppError::bad_request(
+            "min_increase_seconds and min_increase_percent cannot be negative",
+        ));
+    }
+
+    let (from, to) = tokio::try_join!(
+        puppetdb.get_report(&query.from),
+        puppetdb.get_report(&query.to)
+    )
+    .map_err(|e| AppError::Internal(format!("Failed to fetch reports: {}", e)))?;
+
+    let node_report = |report: Option<Report>, hash: &str| {
+        report.filter(|r| r.certname == certname).ok_or_else(|| {
+            AppError::NotFound(format!(
+                "Report '{}' not found for node '{}'",
+                hash, certname
+            ))
+        })
+    };
+    let from = node_report(from, &query.from)?;
+    let to = node_report(to, &query.to)?;
+
+    let (from_events, to_events, from_metrics, to_metrics) = tokio::try_join!(
+        puppetdb.get_report_resource_events(&from.hash),
+        puppetdb.get_report_resource_events(&to.hash),
+        puppetdb.get_report_metrics(&from.hash),
+        puppetdb.get_report_metrics(&to.hash)
+    )
+    .map_err(|e| AppError::Internal(format!("Failed to fetch report details: {}", e)))?;
+
+    let changes = diff_resources(&from_events, &to_events);
+    let timing_regressions = timing_regressions(
+        &from_metrics,
+        &to_metrics,
+        min_increase_seconds,
+        min_increase_percent,
+    );
+
+    Ok(Json(ReportDiff {
+        certname,
+        from: ReportDiffSide::from(&from),
+        to: ReportDiffSide::from(&to),
+        newly_failing: changes.newly_failing,
+        newly_fixed: changes.newly_fixed,
+        still_failing: changes.still_failing,
+        timing_regressions,
+    }))
+}
+
+/// Query parameters for the node performance history
+#[derive(Debug, Deserialize)]
+pub struct NodePerformanceQuery {
+    /// Number of days back from now. Defaults to 14, capped at 90.
+    pub days: Option<u32>,
+    /// Minimum increase, in seconds, of the recent median run time flagged as
+    /// a regression
+    pub min_increase_seconds: Option<f64>,
+    /// Minimum increase, in percent, of the recent median run time flagged as
+    /// a regression
+    pub min_increase_percent: Option<f64>,
+}
+
+/// Run duration and resource counts of a node over time
+///
+/// GET /api/v1/nodes/:certname/performance?days=14
+///
+/// `run_time_regressed` is set when the median run time of the last runs grew
+/// by at least `min_increase_seconds` (default 5) and `min_increase_percent`
+/// (default 50) over the median of the earlier runs of the period.
+async fn get_node_performance(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(certname): Path<String>,
+    Query(query): Query<NodePerformanceQuery>,
+) -> AppResult<Json<NodePerformance>> {
+    let puppetdb = state
+        .puppetdb_for(auth_user.organization_id)
+        .await
+        .ok_or_else(|| AppError::ServiceUnavailable("PuppetDB is not configured".to_string()))?;
+
+    let days = query.days.unwrap_or(14).clamp(1, 90);
+    let min_increase_seconds = query
+        .min_increase_seconds
+        .unwrap_or(DEFAULT_REGRESSION_SECONDS);
+    let min_increase_percent = query
+        .min_increase_percent
+        .unwrap_or(DEFAULT_REGRESSION_PERCENT);
+    if min_increase_seconds < 0.0 || min_increase_percent < 0.0 {
+        return Err(AppError::bad_request(
+            "min_increase_seconds and min_increase_percent cannot be negative",
+        ));
+    }
+
+    let node_exists = puppetdb
+        .get_node(&certname)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to check node: {}", e)))?
+        .is_some();
+    if !node_exists {
+        return Err(AppError::NotFound(format!("Node '{}' not found", certname)));
+    }
+
+    let performance = node_performance(
+        &puppetdb,
+        &certname,
+        days,
+        min_increase_seconds,
+        min_increase_percent,
+    )
+    .await
+    .map_err(|e| AppError::Internal(format!("Failed to fetch node performance: {}", e)))?;
+
+    Ok(Json(performance))
+}
+
 /// GET /api/v1/nodes/:certname/inventory
 async fn get_node_inventory(
     State(state): State<AppState>,
@@ -340,6 +681,220 @@ async fn get_node_inventory_history(
     Ok(Json(history))
 }
 
+/// Search node metadata
+///
+/// GET /api/v1/nodes/metadata
+///
+/// Query parameters:
+/// - `tag`: Nodes carrying this tag
+/// - `owner_team`: Nodes owned by this team
+/// - `q`: Substring matched against certname, tags, owner team, notes and
+///   ticket links
+async fn search_node_metadata(
+    State(state): State<AppState>,
+    Query(query): Query<NodeMetadataQuery>,
+) -> AppResult<Json<Vec<NodeMetadata>>> {
+    let metadata = NodeMetadataRepository::new(&state.db)
+        .search(&query)
+        .await?;
+
+    Ok(Json(metadata))
+}
+
+/// Get the local metadata of a node
+///
+/// GET /api/v1/nodes/:certname/metadata
+///
+/// Nodes without stored metadata return an empty record rather than 404 so
+/// the UI can render the editor for any node.
+async fn get_node_metadata(
+    State(state): State<AppState>,
+    Path(certname): Path<String>,
+) -> AppResult<Json<NodeMetadata>> {
+    let metadata = NodeMetadataRepository::new(&state.db)
+        .get(&certname)
+        .await?
+        .unwrap_or_else(|| {
+            let now = chrono::Utc::now();
+            NodeMetadata {
+                certname,
+                tags: Vec::new(),
+                owner_team: None,
+                ticket_links: Vec::new(),
+                notes: None,
+                updated_by: None,
+                created_at: now,
+                updated_at: now,
+            }
+        });
+
+    Ok(Json(metadata))
+}
+
+/// Replace the local metadata of a node
+///
+/// PUT /api/v1/nodes/:certname/metadata
+///
+/// Tags are exposed to classification rules as the `webui.tags` pseudo-fact,
+/// so this requires the `nodes:classify` permission.
+async fn update_node_metadata(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(certname): Path<String>,
+    Json(request): Json<UpdateNodeMetadataRequest>,
+) -> AppResult<Json<NodeMetadata>> {
+    require_node_permission(&state, &auth_user, Action::Classify).await?;
+    let request = normalize_node_metadata(request)?;
+
+    let metadata = NodeMetadataRepository::new(&state.db)
+        .upsert(
+            &certname,
+            &request.tags,
+            request.owner_team.as_deref(),
+            &request.ticket_links,
+            request.notes.as_deref(),
+            &auth_user.username,
+        )
+        .await?;
+
+    tracing::info!(
+        "User '{}' updated metadata of node '{}'",
+        auth_user.username,
+        certname
+    );
+
+    Ok(Json(metadata))
+}
+
+/// Delete the local metadata of a node
+///
+/// DELETE /api/v1/nodes/:certname/metadata
+async fn delete_node_metadata(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(certname): Path<String>,
+) -> AppResult<StatusCode> {
+    require_node_permission(&state, &auth_user, Action::Classify).await?;
+
+    if !NodeMetadataRepository::new(&state.db)
+        .delete(&certname)
+        .await?
+    {
+        return Err(AppError::not_found(format!(
+            "Metadata for node '{}' not found",
+            certname
+        )));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Check that the user holds `nodes:<action>`
+async fn require_node_permission(
+    state: &AppState,
+    auth_user: &AuthUser,
+    action: Action,
+) -> AppResult<()> {
+    let permission_check = state
+        .rbac_db
+        .check_permission(
+            &auth_user.user_id(),
+            RbacResource::Nodes,
+            action,
+            None,
+            None,
+        )
+        .await
+        .map_err(|e| {
+            AppError::internal(format!(
+                "Failed to check nodes:{} permission for user '{}': {}",
+                action.as_str(),
+                auth_user.username,
+                e
+            ))
+        })?;
+
+    if !permission_check.allowed {
+        return Err(AppError::forbidden(
+            permission_check
+                .reason
+                .unwrap_or_else(|| "No matching permission found".to_string()),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Trim, deduplicate and bound a metadata update
+fn normalize_node_metadata(
+    request: UpdateNodeMetadataRequest,
+) -> AppResult<UpdateNodeMetadataRequest> {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in request.tags {
+        let tag = tag.trim();
+        if tag.is_empty() {
+            continue;
+        }
+        if tag.chars().count() > MAX_NODE_TAG_LEN {
+            return Err(AppError::bad_request(format!(
+                "Tag '{}' exceeds {} characters",
+                tag, MAX_NODE_TAG_LEN
+            )));
+        }
+        if !tags.iter().any(|t| t.eq_ignore_ascii_case(tag)) {
+            tags.push(tag.to_string());
+        }
+    }
+    if tags.len() > MAX_NODE_TAGS {
+        return Err(AppError::bad_request(format!(
+            "A node can have at most {} tags",
+            MAX_NODE_TAGS
+        )));
+    }
+
+    if request.ticket_links.len() > MAX_TICKET_LINKS {
+        return Err(AppError::bad_request(format!(
+            "A node can have at most {} ticket links",
+            MAX_TICKET_LINKS
+        )));
+    }
+    let mut ticket_links = Vec::with_capacity(request.ticket_links.len());
+    for link in request.ticket_links {
+        let url = link.url.trim();
+        if !(url.starts_with("https://") || url.starts_with("http://")) {
+            return Err(AppError::bad_request(format!(
+                "Ticket link '{}' must be an http(s) URL",
+                url
+            )));
+        }
+        ticket_links.push(TicketLink {
+            url: url.to_string(),
+            label: link
+                .label
+                .map(|l| l.trim().to_string())
+                .filter(|l| !l.is_empty()),
+        });
+    }
+
+    let notes = request.notes.filter(|n| !n.trim().is_empty());
+    if notes.as_ref().is_some_and(|n| n.len() > MAX_NODE_NOTES_LEN) {
+        return Err(AppError::bad_request(format!(
+            "Notes exceed {} bytes",
+            MAX_NODE_NOTES_LEN
+        )));
+    }
+
+    Ok(UpdateNodeMetadataRequest {
+        tags,
+        owner_team: request
+            .owner_team
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty()),
+        ticket_links,
+        notes,
+    })
+}
+
 /// Query parameters for node resources
 #[derive(Debug, Deserialize)]
 pub struct NodeResourcesQuery {
@@ -356,12 +911,13 @@ pub struct NodeResourcesQuery {
 /// - `type`: Filter by resource type (e.g., "File", "Package", "Service")
 async fn get_node_resources(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(certname): Path<String>,
     Query(query): Query<NodeResourcesQuery>,
 ) -> AppResult<Json<Vec<Resource>>> {
     let puppetdb = state
-        .puppetdb
-        .as_ref()
+        .puppetdb_for(auth_user.organization_id)
+        .await
         .ok_or_else(|| AppError::ServiceUnavailable("PuppetDB is not configured".to_string()))?;
 
     // First verify the node exists
@@ -408,11 +964,12 @@ pub struct CatalogResponse {
 /// GET /api/v1/nodes/:certname/catalog
 async fn get_node_catalog(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(certname): Path<String>,
 ) -> AppResult<(StatusCode, Json<CatalogResponse>)> {
     let puppetdb = state
-        .puppetdb
-        .as_ref()
+        .puppetdb_for(auth_user.organization_id)
+        .await
         .ok_or_else(|| AppError::ServiceUnavailable("PuppetDB is not configured".to_string()))?;
 
     let catalog = puppetdb
@@ -495,8 +1052,8 @@ async fn get_node_classification(
     }
 
     let puppetdb = state
-        .puppetdb
-        .as_ref()
+        .puppetdb_for(auth_user.organization_id)
+        .await
         .ok_or_else(|| AppError::ServiceUnavailable("PuppetDB is not configured".to_string()))?;
 
     // Get facts for the node from PuppetDB
@@ -509,11 +1066,13 @@ async fn get_node_classification(
         .get_node(&certname)
         .await
         .map_err(|e| AppError::Internal(format!("Failed to fetch node: {}", e)))?;
-    let facts_json = build_classification_facts(
+    let facts_json = build_node_classification_facts(
+        &state.db,
         facts,
         &certname,
         node.and_then(|n| n.catalog_environment).as_deref(),
-    );
+    )
+    .await;
 
     // Get organization ID from authenticated user, or allow override for super_admin
     let org_id = query.organization_id.unwrap_or(auth_user.organization_id);
@@ -527,7 +1086,8 @@ async fn get_node_classification(
 
     // Classify the node
     let classification_service = ClassificationService::new(all_groups);
-    let classification = classification_service.classify(&certname, &facts_json);
+    let mut classification = classification_service.classify(&certname, &facts_json);
+    annotate_maintenance(&state, &mut classification, &facts_json).await;
 
     Ok(Json(classification))
 }
@@ -561,33 +1121,20 @@ async fn get_node_classification_public(
     // Check for shared key authentication first
     let shared_key_header = headers
         .get("X-Classification-Key")
-        .and_then(|v| v.to_str().ok())
-        .map(|s| s.to_string());
-
-    let configured_shared_key = state
-        .config
-        .classification
-        .as_ref()
-        .and_then(|c| c.shared_key.as_ref());
+        .and_then(|v| v.to_str().ok());
 
     let authenticated = if is_classification_authentication_disabled(&state) {
         true
-    } else if let (Some(header_key), Some(config_key)) = (&shared_key_header, configured_shared_key)
+    } else if let Some(header_key) =
+        shared_key_header.filter(|key| classification_key_applies(&state, key))
     {
         // Shared key authentication
-        if header_key == config_key {
-            tracing::debug!(
-                "Classification: Shared key authentication successful for node '{}' (debug mode)",
-                certname
-            );
-            true
-        } else {
-            tracing::warn!(
-                "Classification: Invalid shared key provided for node '{}'",
-                certname
-            );
-            false
-        }
+        verify_classification_key(&state, &certname, header_key).await?;
+        tracing::debug!(
+            "Classification: Shared key authentication successful for node '{}'",
+            certname
+        );
+        true
     } else if let Some(ref cert) = client_cert.0 {
         // Client certificate authentication
         if cert.matches_certname(&certname) {
@@ -632,11 +1179,13 @@ async fn get_node_classification_public(
         .get_node(&certname)
         .await
         .map_err(|e| AppError::Internal(format!("Failed to fetch node: {}", e)))?;
-    let facts_json = build_classification_facts(
+    let facts_json = build_node_classification_facts(
+        &state.db,
         facts,
         &certname,
         node.and_then(|n| n.catalog_environment).as_deref(),
-    );
+    )
+    .await;
 
     // Get ALL groups from ALL organizations for cross-org classification
     let group_repo = GroupRepository::new(&state.db);
@@ -649,15 +1198,42 @@ async fn get_node_classification_public(
     // This will detect if the node matches groups from multiple orgs (conflict)
     // and use the default org if no matches are found
     let classification_service = ClassificationService::new(all_groups);
-    let classification = classification_service.classify_across_organizations(
+    let mut classification = classification_service.classify_across_organizations(
         &certname,
         &facts_json,
         default_organization_uuid(),
     );
+    annotate_maintenance(&state, &mut classification, &facts_json).await;
 
     Ok(Json(classification))
 }
 
+/// Attach the active maintenance windows covering a classified node
+///
+/// Best effort: a failure is logged and leaves the node out of maintenance.
+async fn annotate_maintenance(
+    state: &AppState,
+    classification: &mut ClassificationResult,
+    facts: &serde_json::Value,
+) {
+    let group_ids: Vec<uuid::Uuid> = classification.groups.iter().map(|g| g.id).collect();
+    match node_maintenance(
+        &state.db,
+        &classification.certname,
+        facts,
+        &group_ids,
+        chrono::Utc::now(),
+    )
+    .await
+    {
+        Ok(windows) => classification.maintenance = windows,
+        Err(e) => warn!(
+            "Failed to resolve maintenance windows for {}: {}",
+            classification.certname, e
+        ),
+    }
+}
+
 /// POST /api/v1/nodes/:certname/inventory
 async fn ingest_node_inventory(
     State(state): State<AppState>,
@@ -666,7 +1242,7 @@ async fn ingest_node_inventory(
     client_cert: OptionalClientCert,
     Json(payload): Json<InventoryPayload>,
 ) -> AppResult<(StatusCode, Json<NodeInventory>)> {
-    authenticate_node_request(&state, &certname, &headers, &client_cert)?;
+    authenticate_node_request(&state, &certname, &headers, &client_cert).await?;
 
     let inventory_repo = state.inventory_repository();
     let inventory = inventory_repo
@@ -723,7 +1299,7 @@ async fn get_pending_node_update_jobs(
     headers: HeaderMap,
     client_cert: OptionalClientCert,
 ) -> AppResult<Json<Vec<NodePendingUpdateJob>>> {
-    authenticate_node_request(&state, &certname, &headers, &client_cert)?;
+    authenticate_node_request(&state, &certname, &headers, &client_cert).await?;
 
     let inventory_repo = state.inventory_repository();
     let jobs = inventory_repo
@@ -742,7 +1318,7 @@ async fn submit_node_update_job_result(
     client_cert: OptionalClientCert,
     Json(payload): Json<SubmitUpdateJobResultRequest>,
 ) -> AppResult<Json<UpdateJob>> {
-    authenticate_node_request(&state, &certname, &headers, &client_cert)?;
+    authenticate_node_request(&state, &certname, &headers, &client_cert).await?;
 
     let inventory_repo = state.inventory_repository();
     let job = inventory_repo
@@ -759,6 +1335,142 @@ async fn submit_node_update_job_result(
     Ok(Json(job))
 }
 
+/// Query parameters for the facts package endpoints
+#[derive(Debug, Deserialize, Default)]
+pub struct FactsPackageQuery {
+    /// Format of the fact files (yaml or json, default yaml)
+    pub format: Option<ExportFormat>,
+}
+
+/// Checksum of the facts package of a node
+#[derive(Debug, Serialize)]
+pub struct FactsPackageChecksum {
+    pub certname: String,
+    /// SHA-256 of the package archive, also its ETag
+    pub checksum: String,
+    pub files: Vec<FactsPackageFile>,
+}
+
+/// GET /api/v1/nodes/:certname/facts-package
+///
+/// Returns the external fact files rendered from the fact templates of the
+/// node's organization as a tar.gz archive to extract into `facts.d`. The
+/// archive checksum is sent as the ETag, so agents can poll with
+/// `If-None-Match` and only download a changed package.
+async fn get_facts_package(
+    State(state): State<AppState>,
+    Path(certname): Path<String>,
+    Query(query): Query<FactsPackageQuery>,
+    headers: HeaderMap,
+    client_cert: OptionalClientCert,
+) -> AppResult<Response> {
+    authenticate_node_request(&state, &certname, &headers, &client_cert).await?;
+
+    let package = build_facts_package(&state, &certname, query.format).await?;
+    let etag = format!("\"{}\"", package.checksum);
+    if headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        == Some(etag.as_str())
+    {
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response());
+    }
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "application/gzip".to_string()),
+            (
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"{}-facts.tar.gz\"", certname),
+            ),
+            (header::ETAG, etag),
+        ],
+        package.archive,
+    )
+        .into_response())
+}
+
+/// GET /api/v1/nodes/:certname/facts-package/checksum
+///
+/// Returns the checksum of the facts package and of each file in it, for
+/// configuration management that keeps `facts.d` in sync without
+/// downloading the archive.
+async fn get_facts_package_checksum(
+    State(state): State<AppState>,
+    Path(certname): Path<String>,
+    Query(query): Query<FactsPackageQuery>,
+    headers: HeaderMap,
+    client_cert: OptionalClientCert,
+) -> AppResult<Json<FactsPackageChecksum>> {
+    authenticate_node_request(&state, &certname, &headers, &client_cert).await?;
+
+    let package = build_facts_package(&state, &certname, query.format).await?;
+    Ok(Json(FactsPackageChecksum {
+        certname,
+        checksum: package.checksum,
+        files: package.files,
+    }))
+}
+
+/// Classify a node and bundle the fact files of its organization's templates
+///
+/// Nodes being provisioned may not be in PuppetDB yet; they are classified
+/// on their certname alone.
+async fn build_facts_package(
+    state: &AppState,
+    certname: &str,
+    format: Option<ExportFormat>,
+) -> AppResult<FactsPackage> {
+    let format = match format.unwrap_or(ExportFormat::Yaml) {
+        ExportFormat::Json => FactFileFormat::Json,
+        ExportFormat::Yaml => FactFileFormat::Yaml,
+        ExportFormat::Shell => {
+            return Err(AppError::BadRequest(
+                "External fact files must be json or yaml".to_string(),
+            ))
+        }
+    };
+
+    let facts = match state.puppetdb.as_ref() {
+        Some(puppetdb) => puppetdb.get_node_facts(certname).await.unwrap_or_else(|e| {
+            warn!(
+                "Failed to fetch facts of '{}' for its facts package: {}",
+                certname, e
+            );
+            Vec::new()
+        }),
+        None => Vec::new(),
+    };
+    let facts_json = build_node_classification_facts(&state.db, facts, certname, None).await;
+
+    let all_groups = GroupRepository::new(&state.db)
+        .get_all_across_organizations()
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to get groups: {}", e)))?;
+    let classification = ClassificationService::new(all_groups).classify_across_organizations(
+        certname,
+        &facts_json,
+        default_organization_uuid(),
+    );
+    if let Some(error) = classification.conflict_error {
+        return Err(AppError::Conflict(error));
+    }
+    let organization_id = classification
+        .organization_id
+        .unwrap_or_else(default_organization_uuid);
+
+    let templates = FactTemplateRepository::new(&state.db)
+        .get_all(organization_id)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to get fact templates: {}", e)))?;
+    let files = FacterService::new(templates)
+        .render_files(&classification, &facts_json, format)
+        .map_err(|e| AppError::Internal(format!("Failed to render fact files: {:#}", e)))?;
+
+    FactsPackage::build(&files)
+        .map_err(|e| AppError::Internal(format!("Failed to build facts package: {:#}", e)))
+}
+
 /// Response for environment-only endpoint
 #[derive(Debug, Serialize)]
 pub struct EnvironmentResponse {
@@ -802,11 +1514,13 @@ async fn get_node_environment_public(
         .get_node(&certname)
         .await
         .map_err(|e| AppError::Internal(format!("Failed to fetch node: {}", e)))?;
-    let facts_json = build_classification_facts(
+    let facts_json = build_node_classification_facts(
+        &state.db,
         facts,
         &certname,
         node.and_then(|n| n.catalog_environment).as_deref(),
-    );
+    )
+    .await;
 
     // Get ALL groups from ALL organizations for cross-org classification
     let group_repo = GroupRepository::new(&state.db);
@@ -829,7 +1543,7 @@ async fn get_node_environment_public(
     }))
 }
 
-fn authenticate_node_request(
+pub(super) async fn authenticate_node_request(
     state: &AppState,
     certname: &str,
     headers: &HeaderMap,
@@ -838,17 +1552,57 @@ fn authenticate_node_request(
     let shared_key_header = headers
         .get("X-Classification-Key")
         .and_then(|v| v.to_str().ok());
+
+    if let Some(header_key) = shared_key_header.filter(|key| classification_key_applies(state, key))
+    {
+        return verify_classification_key(state, certname, header_key).await;
+    }
+
+    if let Some(ref cert) = client_cert.0 {
+        if cert.matches_certname(certname) {
+            return Ok(());
+        }
+
+        return Err(AppError::Forbidden(format!(
+            "Certificate CN '{}' does not match requested node '{}'",
+            cert.cn, certname
+        )));
+    }
+
+    Err(AppError::Unauthorized(
+        "Client certificate or shared key required. Provide X-SSL-Client-CN header or X-Classification-Key header.".to_string(),
+    ))
+}
+
+/// Whether an X-Classification-Key header is checked at all
+///
+/// Managed keys always are; anything else only when a shared key is
+/// configured, so clients that send a stray header can still authenticate
+/// with their certificate.
+fn classification_key_applies(state: &AppState, key: &str) -> bool {
+    key.starts_with(classification_keys::KEY_PREFIX)
+        || state
+            .config
+            .classification
+            .as_ref()
+            .is_some_and(|c| c.shared_key.is_some())
+}
+
+/// Check an X-Classification-Key header for a node
+///
+/// Accepts the shared key from the configuration and the managed keys whose
+/// allowed CN patterns cover the certname.
+async fn verify_classification_key(state: &AppState, certname: &str, key: &str) -> AppResult<()> {
     let configured_shared_key = state
         .config
         .classification
         .as_ref()
         .and_then(|c| c.shared_key.as_deref());
+    if configured_shared_key == Some(key) {
+        return Ok(());
+    }
 
-    if let (Some(header_key), Some(config_key)) = (shared_key_header, configured_shared_key) {
-        if header_key == config_key {
-            return Ok(());
-        }
-
+    let Some(classification_key) = classification_keys::find_key(state, key).await? else {
         tracing::warn!(
             "Node public auth: invalid shared key provided for node '{}'",
             certname
@@ -856,25 +1610,30 @@ fn authenticate_node_request(
         return Err(AppError::Unauthorized(
             "Invalid X-Classification-Key header".to_string(),
         ));
-    }
-
-    if let Some(ref cert) = client_cert.0 {
-        if cert.matches_certname(certname) {
-            return Ok(());
-        }
+    };
 
+    if !classification_key.allows(certname) {
+        tracing::warn!(
+            "Node public auth: classification key '{}' is not allowed for node '{}'",
+            classification_key.name,
+            certname
+        );
         return Err(AppError::Forbidden(format!(
-            "Certificate CN '{}' does not match requested node '{}'",
-            cert.cn, certname
+            "Classification key '{}' is not allowed for node '{}'",
+            classification_key.name, certname
         )));
     }
 
-    Err(AppError::Unauthorized(
-        "Client certificate or shared key required. Provide X-SSL-Client-CN header or X-Classification-Key header.".to_string(),
-    ))
+    if let Err(e) = ClassificationKeyRepository::new(&state.db)
+        .touch(classification_key.id)
+        .await
+    {
+        tracing::warn!("Failed to record classification key usage: {}", e);
+    }
+    Ok(())
 }
 
-fn is_classification_authentication_disabled(state: &AppState) -> bool {
+pub(super) fn is_classification_authentication_disabled(state: &AppState) -> bool {
     state
         .config
         .classification
@@ -911,30 +1670,7 @@ async fn delete_node(
     auth_user: AuthUser,
     Path(certname): Path<String>,
 ) -> Result<Json<DeleteNodeResponse>, AppError> {
-    let permission_check = state
-        .rbac_db
-        .check_permission(
-            &auth_user.user_id(),
-            RbacResource::Nodes,
-            Action::Delete,
-            None,
-            None,
-        )
-        .await
-        .map_err(|e| {
-            AppError::internal(format!(
-                "Failed to check nodes:delete permission for user '{}': {}",
-                auth_user.username, e
-            ))
-        })?;
-
-    if !permission_check.allowed {
-        return Err(AppError::forbidden(
-            &permission_check
-                .reason
-                .unwrap_or_else(|| "No matching permission found".to_string()),
-        ));
-    }
+    require_node_permission(&state, &auth_user, Action::Delete).await?;
 
     tracing::info!(
         "User '{}' is deleting node '{}'",
@@ -964,6 +1700,13 @@ async fn delete_node(
         );
     }
 
+    if let Err(e) = NodeMetadataRepository::new(&state.db)
+        .delete(&certname)
+        .await
+    {
+        tracing::warn!("Failed to delete metadata for '{}': {}", certname, e);
+    }
+
     // Step 2: Attempt to revoke certificate if CA is configured
     let mut certificate_revoked = false;
     if let Some(ca) = state.puppet_ca.as_ref() {
@@ -985,7 +1728,7 @@ async fn delete_node(
 
     // Step 3: Attempt to deactivate node in PuppetDB if configured
     let mut puppetdb_deactivated = false;
-    if let Some(puppetdb) = state.puppetdb.as_ref() {
+    if let Some(puppetdb) = state.puppetdb_for(auth_user.organization_id).await {
         match puppetdb.deactivate_node(&certname).await {
             Ok(_) => {
                 tracing::info!("Deactivated node '{}' in PuppetDB", certname);
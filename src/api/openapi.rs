@@ -0,0 +1,1879 @@
+//! OpenAPI description of the REST API
+//!
+//! `GET /api/v1/openapi.json` serves an OpenAPI 3.1 document listing every
+//! endpoint with its path parameters and authentication, from which clients
+//! can be generated; `GET /api/v1/docs` renders the same list as a page.
+//! Request and response bodies are described as JSON objects; their fields
+//! are documented under `docs/api/`.
+//!
+//! New endpoints are added to [`SECTIONS`] along with their route.
+
+use axum::{extract::State, response::Html, routing::get, Json, Router};
+use serde_json::{json, Map, Value};
+
+use crate::AppState;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/openapi.json", get(get_openapi))
+        .route("/docs", get(get_docs))
+}
+
+/// Endpoints sharing a tag and an authentication requirement
+struct Section {
+    tag: &'static str,
+    /// Reachable without credentials
+    public: bool,
+    /// Method, path relative to `/api/v1` and summary
+    operations: &'static [(&'static str, &'static str, &'static str)],
+}
+
+/// Every endpoint of the API, in the order of the routers in `api/mod.rs`
+const SECTIONS: &[Section] = &[
+    Section {
+        tag: "Health",
+        public: true,
+        operations: &[
+            ("GET", "/health", "Health check"),
+            (
+                "GET",
+                "/health/detailed",
+                "Health and latency of each dependency",
+            ),
+            ("GET", "/health/live", "Liveness probe"),
+            ("GET", "/health/ready", "Readiness probe"),
+        ],
+    },
+    Section {
+        tag: "Documentation",
+        public: true,
+        operations: &[
+            ("GET", "/openapi.json", "This OpenAPI description"),
+            ("GET", "/docs", "API reference page"),
+        ],
+    },
+    Section {
+        tag: "Authentication",
+        public: true,
+        operations: &[
+            ("POST", "/auth/login", "Log in with a username and password"),
+            (
+                "POST",
+                "/auth/refresh",
+                "Exchange a refresh token for a new access token",
+            ),
+            ("POST", "/auth/logout", "Log out and revoke the session"),
+            ("POST", "/auth/register", "Register a new user"),
+            ("POST", "/auth/forgot-password", "Request a password reset"),
+            (
+                "POST",
+                "/auth/reset-password",
+                "Reset password using a valid reset token",
+            ),
+            (
+                "GET",
+                "/auth/jwks.json",
+                "Public keys that verify issued tokens (JWKS)",
+            ),
+            ("GET", "/auth/saml/metadata", "Get SP metadata XML"),
+            (
+                "GET",
+                "/auth/saml/login",
+                "Initiate SAML login (SP-initiated SSO)",
+            ),
+            (
+                "POST",
+                "/auth/saml/acs",
+                "SAML Assertion Consumer Service (ACS)",
+            ),
+        ],
+    },
+    Section {
+        tag: "Nodes",
+        public: true,
+        operations: &[
+            (
+                "POST",
+                "/nodes/{certname}/inventory",
+                "Submit the software inventory of a node",
+            ),
+            (
+                "GET",
+                "/nodes/{certname}/update-jobs",
+                "List update jobs pending for a node",
+            ),
+            (
+                "POST",
+                "/nodes/{certname}/update-jobs/{job_id}/targets/{target_id}/results",
+                "Report the result of an update job on a node",
+            ),
+            (
+                "GET",
+                "/nodes/{certname}/facts-package",
+                "Download the external facts package of a node",
+            ),
+            (
+                "GET",
+                "/nodes/{certname}/facts-package/checksum",
+                "Get the checksum of a node's external facts package",
+            ),
+        ],
+    },
+    Section {
+        tag: "Code Deploy",
+        public: true,
+        operations: &[
+            (
+                "POST",
+                "/webhooks/github/{repo_id}",
+                "Receive a GitHub push webhook",
+            ),
+            (
+                "POST",
+                "/webhooks/gitlab/{repo_id}",
+                "Receive a GitLab push webhook",
+            ),
+            (
+                "POST",
+                "/webhooks/bitbucket/{repo_id}",
+                "Receive a Bitbucket push webhook",
+            ),
+        ],
+    },
+    Section {
+        tag: "Settings",
+        public: true,
+        operations: &[("GET", "/settings/server", "Get server information")],
+    },
+    Section {
+        tag: "Bootstrap",
+        public: true,
+        operations: &[
+            (
+                "GET",
+                "/bootstrap/config",
+                "Get the node bootstrap configuration",
+            ),
+            (
+                "GET",
+                "/bootstrap/script",
+                "Download the Linux bootstrap script",
+            ),
+            (
+                "GET",
+                "/bootstrap/windows-script",
+                "Download the Windows bootstrap script",
+            ),
+        ],
+    },
+    Section {
+        tag: "Classification",
+        public: true,
+        operations: &[
+            ("GET", "/nodes/{certname}/classify", "Classify a node (ENC)"),
+            (
+                "GET",
+                "/nodes/{certname}/environment",
+                "Get the environment of a node (ENC)",
+            ),
+            (
+                "GET",
+                "/enc/ping",
+                "Check an ENC script's classification key",
+            ),
+            (
+                "GET",
+                "/hiera/{environment}/{certname}",
+                "Get the Hiera data of a node",
+            ),
+            (
+                "GET",
+                "/hiera/{environment}/{certname}/{key}",
+                "Look up a Hiera key of a node",
+            ),
+        ],
+    },
+    Section {
+        tag: "Authentication",
+        public: false,
+        operations: &[
+            (
+                "POST",
+                "/auth/change-password",
+                "Change password for the authenticated user",
+            ),
+            ("GET", "/auth/me", "Get current authenticated user profile"),
+        ],
+    },
+    Section {
+        tag: "Nodes",
+        public: false,
+        operations: &[
+            ("GET", "/nodes", "List all nodes"),
+            ("GET", "/nodes/stats", "Get aggregate node statistics"),
+            ("GET", "/nodes/topology", "Get the infrastructure topology"),
+            ("GET", "/nodes/metadata", "Search node metadata"),
+            (
+                "GET",
+                "/nodes/{certname}",
+                "Get a specific node by certname",
+            ),
+            ("DELETE", "/nodes/{certname}", "Delete a node"),
+            (
+                "GET",
+                "/nodes/{certname}/facts",
+                "Get facts for a specific node",
+            ),
+            (
+                "GET",
+                "/nodes/{certname}/reports",
+                "Get reports for a specific node",
+            ),
+            (
+                "GET",
+                "/nodes/{certname}/reports/diff",
+                "Compare two reports of a node",
+            ),
+            (
+                "GET",
+                "/nodes/{certname}/performance",
+                "Run duration and resource count history of a node with run time regression flag",
+            ),
+            (
+                "GET",
+                "/nodes/{certname}/resources",
+                "Get resources for a specific node",
+            ),
+            (
+                "GET",
+                "/nodes/{certname}/catalog",
+                "Get catalog for a specific node",
+            ),
+            (
+                "GET",
+                "/nodes/{certname}/classification",
+                "Get the classification of a node",
+            ),
+            (
+                "GET",
+                "/nodes/{certname}/inventory",
+                "Get the software inventory of a node",
+            ),
+            (
+                "GET",
+                "/nodes/{certname}/inventory/history",
+                "Get the inventory history of a node",
+            ),
+            (
+                "GET",
+                "/nodes/{certname}/metadata",
+                "Get the local metadata of a node",
+            ),
+            (
+                "PUT",
+                "/nodes/{certname}/metadata",
+                "Replace the local metadata of a node",
+            ),
+            (
+                "DELETE",
+                "/nodes/{certname}/metadata",
+                "Delete the local metadata of a node",
+            ),
+        ],
+    },
+    Section {
+        tag: "Groups",
+        public: false,
+        operations: &[
+            ("GET", "/groups", "List all node groups"),
+            ("POST", "/groups", "Create a new node group"),
+            ("GET", "/groups/{id}", "Get a specific node group"),
+            (
+                "PUT",
+                "/groups/{id}",
+                "Update a node group; requires If-Match with the group's ETag",
+            ),
+            (
+                "DELETE",
+                "/groups/{id}",
+                "Move a node group and its subgroups to the recycle bin",
+            ),
+            (
+                "GET",
+                "/groups/{id}/nodes",
+                "Get the pinned and rule-matched nodes of a group",
+            ),
+            (
+                "GET",
+                "/groups/{id}/rules",
+                "Get classification rules for a group",
+            ),
+            (
+                "POST",
+                "/groups/{id}/rules",
+                "Add a classification rule to a group",
+            ),
+            (
+                "DELETE",
+                "/groups/{id}/rules/{rule_id}",
+                "Delete a classification rule from a group",
+            ),
+            (
+                "POST",
+                "/groups/{id}/pinned",
+                "Add a pinned node to a group",
+            ),
+            (
+                "DELETE",
+                "/groups/{id}/pinned/{certname}",
+                "Remove a pinned node from a group",
+            ),
+            ("POST", "/groups/bulk/pin", "Pin many nodes to a group"),
+            (
+                "POST",
+                "/groups/bulk/unpin",
+                "Unpin many nodes from a group",
+            ),
+            (
+                "POST",
+                "/groups/bulk/move",
+                "Move pinned nodes from one group to another",
+            ),
+            (
+                "POST",
+                "/groups/bulk/classes",
+                "Declare a class on many groups",
+            ),
+            (
+                "POST",
+                "/groups/bulk/rules",
+                "Create classification rules on many groups",
+            ),
+            (
+                "GET",
+                "/groups/{id}/update-schedules",
+                "List the update schedules of a group",
+            ),
+            (
+                "POST",
+                "/groups/{id}/update-schedules",
+                "Create an update schedule for a group",
+            ),
+            (
+                "GET",
+                "/groups/{id}/update-schedules/{schedule_id}",
+                "Get an update schedule",
+            ),
+            (
+                "PUT",
+                "/groups/{id}/update-schedules/{schedule_id}",
+                "Update an update schedule",
+            ),
+            (
+                "DELETE",
+                "/groups/{id}/update-schedules/{schedule_id}",
+                "Delete an update schedule",
+            ),
+            (
+                "POST",
+                "/groups/{id}/update-schedules/{schedule_id}/run",
+                "Run an update schedule now",
+            ),
+        ],
+    },
+    Section {
+        tag: "Smart Lists",
+        public: false,
+        operations: &[
+            (
+                "GET",
+                "/smart-lists",
+                "List smart lists owned by or shared with the caller",
+            ),
+            ("POST", "/smart-lists", "Create a smart list"),
+            (
+                "GET",
+                "/smart-lists/counts",
+                "Get the number of nodes matching each smart list",
+            ),
+            ("GET", "/smart-lists/{id}", "Get a smart list"),
+            ("PUT", "/smart-lists/{id}", "Update a smart list"),
+            ("DELETE", "/smart-lists/{id}", "Delete a smart list"),
+            (
+                "GET",
+                "/smart-lists/{id}/nodes",
+                "Get the nodes currently matching a smart list",
+            ),
+            (
+                "GET",
+                "/smart-lists/{id}/count",
+                "Get the number of nodes matching a smart list",
+            ),
+            (
+                "GET",
+                "/smart-lists/{id}/export",
+                "Export the nodes matching a smart list as CSV or JSON",
+            ),
+        ],
+    },
+    Section {
+        tag: "Maintenance Windows",
+        public: false,
+        operations: &[
+            (
+                "GET",
+                "/maintenance-windows",
+                "List the organization's maintenance windows with their current state",
+            ),
+            (
+                "POST",
+                "/maintenance-windows",
+                "Create a maintenance window",
+            ),
+            (
+                "GET",
+                "/maintenance-windows/active",
+                "List the organization's nodes currently in maintenance",
+            ),
+            (
+                "GET",
+                "/maintenance-windows/{id}",
+                "Get a maintenance window",
+            ),
+            (
+                "PUT",
+                "/maintenance-windows/{id}",
+                "Update a maintenance window",
+            ),
+            (
+                "DELETE",
+                "/maintenance-windows/{id}",
+                "Delete a maintenance window",
+            ),
+        ],
+    },
+    Section {
+        tag: "Webhooks",
+        public: false,
+        operations: &[
+            ("GET", "/webhooks", "List the organization's webhooks"),
+            (
+                "POST",
+                "/webhooks",
+                "Create a webhook; the signing secret is only returned here",
+            ),
+            ("GET", "/webhooks/{id}", "Get a webhook"),
+            ("PUT", "/webhooks/{id}", "Update a webhook"),
+            ("DELETE", "/webhooks/{id}", "Delete a webhook"),
+            (
+                "POST",
+                "/webhooks/{id}/ping",
+                "Queue a ping delivery to check the endpoint",
+            ),
+            (
+                "GET",
+                "/webhooks/{id}/deliveries",
+                "Delivery log of a webhook, newest first",
+            ),
+            (
+                "GET",
+                "/webhooks/{id}/deliveries/{delivery_id}",
+                "Get a webhook delivery",
+            ),
+            (
+                "POST",
+                "/webhooks/{id}/deliveries/{delivery_id}/redeliver",
+                "Send a logged delivery again",
+            ),
+        ],
+    },
+    Section {
+        tag: "Recycle Bin",
+        public: false,
+        operations: &[
+            (
+                "GET",
+                "/recycle-bin",
+                "List deleted groups, users and saved reports",
+            ),
+            (
+                "POST",
+                "/recycle-bin/{type}/{id}/restore",
+                "Restore a deleted record",
+            ),
+            (
+                "DELETE",
+                "/recycle-bin/{type}/{id}",
+                "Purge a deleted record now",
+            ),
+        ],
+    },
+    Section {
+        tag: "Search",
+        public: false,
+        operations: &[
+            (
+                "GET",
+                "/search",
+                "Search nodes, groups, classes, reports, users and saved reports",
+            ),
+            (
+                "POST",
+                "/search/reindex",
+                "Rebuild the organization's search index now",
+            ),
+        ],
+    },
+    Section {
+        tag: "Facts",
+        public: false,
+        operations: &[
+            ("GET", "/facts", "Query facts across all nodes"),
+            ("GET", "/facts/names", "List all unique fact names"),
+            (
+                "GET",
+                "/facts/paths",
+                "List all unique fact paths (for structured facts)",
+            ),
+        ],
+    },
+    Section {
+        tag: "Facter",
+        public: false,
+        operations: &[
+            ("GET", "/facter/templates", "List all fact templates"),
+            ("POST", "/facter/templates", "Create a new fact template"),
+            (
+                "GET",
+                "/facter/templates/{id}",
+                "Get a specific fact template",
+            ),
+            ("PUT", "/facter/templates/{id}", "Update a fact template"),
+            ("DELETE", "/facter/templates/{id}", "Delete a fact template"),
+            (
+                "POST",
+                "/facter/generate",
+                "Generate facts for a node using a template",
+            ),
+            (
+                "GET",
+                "/facter/export/{certname}",
+                "Export facts for a node in the specified format",
+            ),
+            (
+                "GET",
+                "/facter/files/{certname}",
+                "Render the external fact files of a node",
+            ),
+        ],
+    },
+    Section {
+        tag: "Reports",
+        public: false,
+        operations: &[
+            ("GET", "/reports", "Query reports"),
+            (
+                "GET",
+                "/reports/daily-summary",
+                "Report counts per day and status",
+            ),
+            (
+                "GET",
+                "/reports/hourly-summary",
+                "Report counts per hour and status",
+            ),
+            (
+                "GET",
+                "/reports/activity-heatmap",
+                "Report activity per weekday and hour",
+            ),
+            ("GET", "/reports/{hash}", "Get a specific report by hash"),
+            (
+                "GET",
+                "/reports/{hash}/events",
+                "Get events from a specific report",
+            ),
+            (
+                "GET",
+                "/reports/{hash}/drilldown",
+                "Get the resource events and log lines of a report",
+            ),
+        ],
+    },
+    Section {
+        tag: "API Keys",
+        public: false,
+        operations: &[
+            ("GET", "/api-keys", "List API keys"),
+            ("POST", "/api-keys", "Create an API key"),
+            ("DELETE", "/api-keys/{id}", "Delete an API key"),
+            (
+                "POST",
+                "/api-keys/{id}/rotate",
+                "Issue a new secret for an API key",
+            ),
+        ],
+    },
+    Section {
+        tag: "Classification Keys",
+        public: false,
+        operations: &[
+            ("GET", "/classification-keys", "List classification keys"),
+            (
+                "POST",
+                "/classification-keys",
+                "Create a classification key",
+            ),
+            (
+                "PUT",
+                "/classification-keys/{id}",
+                "Update the description or CN patterns of a classification key",
+            ),
+            (
+                "POST",
+                "/classification-keys/{id}/rotate",
+                "Issue a new secret for a classification key",
+            ),
+            (
+                "POST",
+                "/classification-keys/{id}/revoke",
+                "Revoke a classification key",
+            ),
+            (
+                "GET",
+                "/enc/bootstrap",
+                "Download an ENC script with a new classification key",
+            ),
+        ],
+    },
+    Section {
+        tag: "Audit Logs",
+        public: false,
+        operations: &[
+            ("GET", "/audit-logs", "List audit log entries"),
+            (
+                "GET",
+                "/audit-logs/export",
+                "Export audit log entries as CSV or JSON Lines",
+            ),
+        ],
+    },
+    Section {
+        tag: "Roles",
+        public: false,
+        operations: &[
+            ("GET", "/roles", "List all roles"),
+            ("POST", "/roles", "Create a new role"),
+            ("GET", "/roles/{id}", "Get a specific role"),
+            ("PUT", "/roles/{id}", "Update a role"),
+            ("DELETE", "/roles/{id}", "Delete a role"),
+            (
+                "GET",
+                "/roles/{id}/parents",
+                "Get the parent roles of a role",
+            ),
+            (
+                "PUT",
+                "/roles/{id}/parents",
+                "Replace the parent roles of a role",
+            ),
+            (
+                "GET",
+                "/roles/{id}/effective-permissions",
+                "Get the permissions a role grants, including inherited ones",
+            ),
+            (
+                "GET",
+                "/roles/{id}/permissions",
+                "Get permissions for a role",
+            ),
+            (
+                "POST",
+                "/roles/{id}/permissions",
+                "Add a single permission to a role",
+            ),
+            (
+                "PUT",
+                "/roles/{id}/permissions",
+                "Update permissions for a role (replace all)",
+            ),
+            (
+                "DELETE",
+                "/roles/{id}/permissions/{permission_id}",
+                "Remove a permission from a role",
+            ),
+            (
+                "GET",
+                "/roles/{id}/group-permissions",
+                "Get group-scoped permissions for a role",
+            ),
+            (
+                "POST",
+                "/roles/{id}/group-permissions",
+                "Add a group-scoped permission to a role",
+            ),
+            (
+                "DELETE",
+                "/roles/{id}/group-permissions/{group_id}",
+                "Remove a group-scoped permission from a role",
+            ),
+        ],
+    },
+    Section {
+        tag: "Elevations",
+        public: false,
+        operations: &[
+            ("GET", "/elevations", "List elevations"),
+            ("POST", "/elevations", "Ask for a temporary role"),
+            ("GET", "/elevations/{id}", "Get an elevation"),
+            (
+                "POST",
+                "/elevations/{id}/approve",
+                "Approve a pending elevation; its time starts now",
+            ),
+            ("POST", "/elevations/{id}/deny", "Deny a pending elevation"),
+            (
+                "POST",
+                "/elevations/{id}/revoke",
+                "End an approved elevation early",
+            ),
+            (
+                "POST",
+                "/elevations/{id}/activate",
+                "Reissue the requester's access token with the elevated role",
+            ),
+        ],
+    },
+    Section {
+        tag: "Impersonation",
+        public: false,
+        operations: &[
+            ("GET", "/impersonation", "List impersonations"),
+            ("POST", "/impersonation", "Start acting as another user"),
+            (
+                "GET",
+                "/impersonation/current",
+                "The impersonation of the current token",
+            ),
+            (
+                "POST",
+                "/impersonation/end",
+                "End the impersonation of the current token",
+            ),
+        ],
+    },
+    Section {
+        tag: "Sessions",
+        public: false,
+        operations: &[
+            ("GET", "/sessions", "List active sessions"),
+            ("DELETE", "/sessions", "Revoke all sessions of a user"),
+            ("DELETE", "/sessions/{id}", "Revoke one session"),
+        ],
+    },
+    Section {
+        tag: "Users",
+        public: false,
+        operations: &[
+            ("GET", "/users", "List all users"),
+            ("POST", "/users", "Create a new user"),
+            (
+                "POST",
+                "/users/import",
+                "Import users from CSV or JSON with per-row results",
+            ),
+            ("GET", "/users/{id}", "Get a specific user"),
+            ("PUT", "/users/{id}", "Update a user"),
+            ("DELETE", "/users/{id}", "Move a user to the recycle bin"),
+            ("GET", "/users/{id}/roles", "Get roles assigned to a user"),
+            ("PUT", "/users/{id}/roles", "Assign roles to a user"),
+            (
+                "GET",
+                "/users/{id}/permissions",
+                "Get effective permissions for a user",
+            ),
+            (
+                "POST",
+                "/users/{id}/unlock",
+                "Unlock a user locked out after failed logins",
+            ),
+        ],
+    },
+    Section {
+        tag: "Organizations",
+        public: false,
+        operations: &[
+            ("GET", "/organizations", "List organizations"),
+            ("POST", "/organizations", "Create an organization"),
+            (
+                "GET",
+                "/organizations/current",
+                "Get the organization of the caller",
+            ),
+            ("GET", "/organizations/{id}", "Get an organization"),
+            ("PUT", "/organizations/{id}", "Update an organization"),
+            ("DELETE", "/organizations/{id}", "Delete an organization"),
+            (
+                "POST",
+                "/organizations/{id}/merge",
+                "Move the groups, users and fact templates of an organization into another",
+            ),
+            (
+                "POST",
+                "/organizations/{id}/split",
+                "Move selected groups of an organization into a new organization",
+            ),
+            (
+                "PUT",
+                "/organizations/{id}/quotas",
+                "Update the quotas of an organization",
+            ),
+            (
+                "GET",
+                "/organizations/{id}/usage",
+                "Get the resource usage of an organization",
+            ),
+            (
+                "GET",
+                "/organizations/{id}/puppetdb",
+                "Get the PuppetDB connection of an organization",
+            ),
+            (
+                "PUT",
+                "/organizations/{id}/puppetdb",
+                "Set the PuppetDB connection of an organization",
+            ),
+            (
+                "DELETE",
+                "/organizations/{id}/puppetdb",
+                "Remove the PuppetDB connection of an organization",
+            ),
+        ],
+    },
+    Section {
+        tag: "Permissions",
+        public: false,
+        operations: &[
+            ("GET", "/permissions", "List all defined permissions"),
+            (
+                "GET",
+                "/permissions/resources",
+                "List all available resources",
+            ),
+            ("GET", "/permissions/actions", "List all available actions"),
+            ("GET", "/permissions/matrix", "Get permission matrix"),
+            (
+                "POST",
+                "/permissions/bulk",
+                "Perform bulk permission operations",
+            ),
+        ],
+    },
+    Section {
+        tag: "Settings",
+        public: false,
+        operations: &[
+            ("GET", "/settings", "Get current settings (read-only view)"),
+            (
+                "GET",
+                "/settings/dashboard",
+                "Get the caller's dashboard configuration",
+            ),
+            (
+                "PUT",
+                "/settings/dashboard",
+                "Save the caller's dashboard preferences",
+            ),
+            (
+                "DELETE",
+                "/settings/dashboard",
+                "Reset the caller's dashboard to the global configuration",
+            ),
+            (
+                "POST",
+                "/settings/dashboard/widgets/preview",
+                "Run a widget data binding before saving it",
+            ),
+            (
+                "GET",
+                "/settings/dashboard/widgets/{widget_id}/data",
+                "Chart data of a bound dashboard widget",
+            ),
+            ("GET", "/settings/rbac", "Get RBAC configuration"),
+            (
+                "GET",
+                "/settings/effective-config",
+                "Show the running configuration with secrets redacted",
+            ),
+            (
+                "POST",
+                "/settings/reload",
+                "Re-read the configuration files and apply the safe-to-change settings",
+            ),
+            (
+                "GET",
+                "/settings/export",
+                "Export current configuration as YAML",
+            ),
+            ("POST", "/settings/import", "Import configuration from YAML"),
+            ("POST", "/settings/validate", "Validate configuration YAML"),
+            ("GET", "/settings/history", "Get configuration history"),
+            ("GET", "/settings/smtp", "Get SMTP settings"),
+            ("PUT", "/settings/smtp", "Update SMTP settings"),
+            ("GET", "/settings/update-jobs", "Get update-job settings"),
+            ("PUT", "/settings/update-jobs", "Update update-job settings"),
+        ],
+    },
+    Section {
+        tag: "Analytics",
+        public: false,
+        operations: &[
+            (
+                "GET",
+                "/analytics/saved-reports",
+                "List all saved reports (user's own + public)",
+            ),
+            (
+                "POST",
+                "/analytics/saved-reports",
+                "Create a new saved report",
+            ),
+            (
+                "GET",
+                "/analytics/saved-reports/{id}",
+                "Get a saved report by ID",
+            ),
+            (
+                "PUT",
+                "/analytics/saved-reports/{id}",
+                "Update a saved report",
+            ),
+            (
+                "DELETE",
+                "/analytics/saved-reports/{id}",
+                "Move a saved report to the recycle bin",
+            ),
+            (
+                "POST",
+                "/analytics/saved-reports/{id}/execute",
+                "Execute a saved report",
+            ),
+            (
+                "GET",
+                "/analytics/saved-reports/{id}/executions",
+                "List executions for a saved report",
+            ),
+            ("GET", "/analytics/templates", "List all report templates"),
+            (
+                "GET",
+                "/analytics/templates/{id}",
+                "Get a report template by ID",
+            ),
+            ("GET", "/analytics/schedules", "List all schedules"),
+            ("POST", "/analytics/schedules", "Create a new schedule"),
+            ("GET", "/analytics/schedules/{id}", "Get a schedule by ID"),
+            ("PUT", "/analytics/schedules/{id}", "Update a schedule"),
+            ("DELETE", "/analytics/schedules/{id}", "Delete a schedule"),
+            (
+                "GET",
+                "/analytics/email-templates",
+                "List the email templates of scheduled reports",
+            ),
+            (
+                "PUT",
+                "/analytics/email-templates",
+                "Create or replace the email template of a report type",
+            ),
+            (
+                "GET",
+                "/analytics/email-templates/defaults",
+                "Get the built-in email templates",
+            ),
+            (
+                "DELETE",
+                "/analytics/email-templates/{id}",
+                "Delete an email template",
+            ),
+            (
+                "POST",
+                "/analytics/generate",
+                "Generate a report on-demand (without saving)",
+            ),
+            (
+                "POST",
+                "/analytics/generate/{report_type}",
+                "Generate a specific type of report",
+            ),
+            (
+                "GET",
+                "/analytics/compliance-baselines",
+                "List all compliance baselines",
+            ),
+            (
+                "POST",
+                "/analytics/compliance-baselines",
+                "Create a new compliance baseline",
+            ),
+            (
+                "GET",
+                "/analytics/compliance-baselines/{id}",
+                "Get a compliance baseline by ID",
+            ),
+            (
+                "PUT",
+                "/analytics/compliance-baselines/{id}",
+                "Update a compliance baseline",
+            ),
+            (
+                "DELETE",
+                "/analytics/compliance-baselines/{id}",
+                "Delete a compliance baseline",
+            ),
+            (
+                "GET",
+                "/analytics/compliance-rule-packs",
+                "List all imported rule pack versions",
+            ),
+            (
+                "POST",
+                "/analytics/compliance-rule-packs",
+                "Import a rule pack version from a YAML or JSON document",
+            ),
+            (
+                "GET",
+                "/analytics/compliance-rule-packs/{id}",
+                "Get a rule pack version by ID",
+            ),
+            (
+                "DELETE",
+                "/analytics/compliance-rule-packs/{id}",
+                "Delete a rule pack version",
+            ),
+            (
+                "POST",
+                "/analytics/compliance-rule-packs/{id}/apply",
+                "Load a rule pack version into a new or existing compliance baseline",
+            ),
+            (
+                "GET",
+                "/analytics/drift-baselines",
+                "List all drift baselines",
+            ),
+            (
+                "POST",
+                "/analytics/drift-baselines",
+                "Create a new drift baseline",
+            ),
+            (
+                "POST",
+                "/analytics/drift-baselines/snapshot",
+                "Create a drift baseline from the current facts of a node or group",
+            ),
+            (
+                "POST",
+                "/analytics/drift-baselines/snapshot/preview",
+                "Preview the facts a drift baseline snapshot would capture",
+            ),
+            (
+                "GET",
+                "/analytics/drift-baselines/{id}",
+                "Get a drift baseline by ID",
+            ),
+            (
+                "PUT",
+                "/analytics/drift-baselines/{id}",
+                "Update a drift baseline",
+            ),
+            (
+                "DELETE",
+                "/analytics/drift-baselines/{id}",
+                "Delete a drift baseline",
+            ),
+            (
+                "GET",
+                "/analytics/corrective-changes",
+                "Corrective vs intentional changes for the dashboard widget",
+            ),
+            (
+                "GET",
+                "/analytics/compile-errors",
+                "Catalog compilation errors grouped by signature",
+            ),
+            (
+                "GET",
+                "/analytics/fleet-metrics",
+                "Fleet metrics over the last 30/90 days from the hourly snapshots",
+            ),
+            (
+                "GET",
+                "/analytics/executions/{id}/export",
+                "Export an execution result in the specified format",
+            ),
+            (
+                "GET",
+                "/analytics/executions/{id}/output",
+                "Download the raw JSON output of an execution",
+            ),
+        ],
+    },
+    Section {
+        tag: "Alerting",
+        public: false,
+        operations: &[
+            (
+                "GET",
+                "/alerting/channels",
+                "List all notification channels",
+            ),
+            (
+                "POST",
+                "/alerting/channels",
+                "Create a new notification channel",
+            ),
+            (
+                "GET",
+                "/alerting/channels/{id}",
+                "Get a notification channel by ID",
+            ),
+            (
+                "PUT",
+                "/alerting/channels/{id}",
+                "Update a notification channel",
+            ),
+            (
+                "DELETE",
+                "/alerting/channels/{id}",
+                "Delete a notification channel",
+            ),
+            (
+                "POST",
+                "/alerting/channels/{id}/test",
+                "Test a notification channel",
+            ),
+            ("GET", "/alerting/rules", "List all alert rules"),
+            ("POST", "/alerting/rules", "Create a new alert rule"),
+            ("GET", "/alerting/rules/{id}", "Get an alert rule by ID"),
+            ("PUT", "/alerting/rules/{id}", "Update an alert rule"),
+            ("DELETE", "/alerting/rules/{id}", "Delete an alert rule"),
+            (
+                "GET",
+                "/alerting/alerts",
+                "List alerts with optional filtering",
+            ),
+            ("GET", "/alerting/alerts/stats", "Get alert statistics"),
+            ("GET", "/alerting/alerts/{id}", "Get an alert by ID"),
+            (
+                "POST",
+                "/alerting/alerts/{id}/acknowledge",
+                "Acknowledge an alert",
+            ),
+            ("POST", "/alerting/alerts/{id}/resolve", "Resolve an alert"),
+            ("POST", "/alerting/alerts/{id}/silence", "Silence an alert"),
+            ("GET", "/alerting/silences", "List all silences"),
+            ("POST", "/alerting/silences", "Create a silence"),
+            ("DELETE", "/alerting/silences/{id}", "Delete a silence"),
+            (
+                "POST",
+                "/alerting/trigger",
+                "Manually trigger an alert for a rule",
+            ),
+            (
+                "POST",
+                "/alerting/evaluate",
+                "Evaluate all enabled rules and trigger alerts as needed",
+            ),
+        ],
+    },
+    Section {
+        tag: "Query",
+        public: false,
+        operations: &[("POST", "/query", "Execute a PQL query")],
+    },
+    Section {
+        tag: "Certificate Authority",
+        public: false,
+        operations: &[
+            ("GET", "/ca/status", "Get CA service status"),
+            (
+                "GET",
+                "/ca/status/{certname}",
+                "Lightweight certificate status",
+            ),
+            ("GET", "/ca/requests", "List pending certificate requests"),
+            ("GET", "/ca/certificates", "List signed certificates"),
+            (
+                "GET",
+                "/ca/certificates/{certname}",
+                "Get certificate details",
+            ),
+            (
+                "GET",
+                "/ca/certificates/{certname}/chain",
+                "Validate a certificate's chain",
+            ),
+            ("POST", "/ca/sign/{certname}", "Sign a certificate request"),
+            (
+                "POST",
+                "/ca/reject/{certname}",
+                "Reject a certificate request",
+            ),
+            (
+                "DELETE",
+                "/ca/certificates/{certname}",
+                "Revoke a certificate",
+            ),
+            ("POST", "/ca/bulk/sign", "Sign many certificate requests"),
+            ("POST", "/ca/bulk/revoke", "Revoke many certificates"),
+            ("POST", "/ca/renew", "Renew CA certificate"),
+            ("GET", "/ca/crl", "Get the certificate revocation list"),
+            ("GET", "/ca/bundle", "Download the CA certificate bundle"),
+            ("GET", "/ca/chain", "Get the CA certificate chain"),
+            (
+                "GET",
+                "/ca/export",
+                "Export the CA state as a tar.gz archive",
+            ),
+            ("POST", "/ca/snapshot", "Sync the offline CA snapshot now"),
+            ("GET", "/ca/renewals", "List certificate renewal campaigns"),
+            (
+                "POST",
+                "/ca/renewals",
+                "Start a certificate renewal campaign",
+            ),
+            (
+                "GET",
+                "/ca/renewals/candidates",
+                "List certificates close to expiry",
+            ),
+            (
+                "GET",
+                "/ca/renewals/{id}",
+                "Get a renewal campaign with its targets",
+            ),
+            (
+                "POST",
+                "/ca/renewals/{id}/check",
+                "Check a campaign against the CA now",
+            ),
+            (
+                "POST",
+                "/ca/renewals/{id}/cancel",
+                "Stop tracking a renewal campaign",
+            ),
+        ],
+    },
+    Section {
+        tag: "Code Deploy",
+        public: false,
+        operations: &[
+            ("GET", "/code/status", "Get Code Deploy feature status"),
+            ("GET", "/code/ssh-keys", "List SSH keys"),
+            ("POST", "/code/ssh-keys", "Upload an SSH key"),
+            (
+                "POST",
+                "/code/ssh-keys/generate",
+                "Generate an Ed25519 keypair server-side",
+            ),
+            ("GET", "/code/ssh-keys/{id}", "Get an SSH key"),
+            ("DELETE", "/code/ssh-keys/{id}", "Delete an SSH key"),
+            ("GET", "/code/pat-tokens", "List personal access tokens"),
+            ("POST", "/code/pat-tokens", "Store a personal access token"),
+            (
+                "GET",
+                "/code/pat-tokens/{id}",
+                "Get a personal access token",
+            ),
+            (
+                "PUT",
+                "/code/pat-tokens/{id}",
+                "Update a personal access token",
+            ),
+            (
+                "DELETE",
+                "/code/pat-tokens/{id}",
+                "Delete a personal access token",
+            ),
+            (
+                "GET",
+                "/code/pat-tokens/expiring",
+                "List personal access tokens close to expiry",
+            ),
+            ("GET", "/code/repositories", "List control repositories"),
+            ("POST", "/code/repositories", "Add a control repository"),
+            ("GET", "/code/repositories/{id}", "Get a control repository"),
+            (
+                "PUT",
+                "/code/repositories/{id}",
+                "Update a control repository",
+            ),
+            (
+                "DELETE",
+                "/code/repositories/{id}",
+                "Remove a control repository",
+            ),
+            (
+                "POST",
+                "/code/repositories/{id}/sync",
+                "Synchronize the environments of a control repository",
+            ),
+            ("GET", "/code/environments", "List environments"),
+            ("GET", "/code/environments/{id}", "Get an environment"),
+            (
+                "PUT",
+                "/code/environments/{id}",
+                "Update the deployment settings of an environment",
+            ),
+            (
+                "GET",
+                "/code/environments/{id}/deployments",
+                "List the deployments of an environment",
+            ),
+            ("GET", "/code/deployments", "List deployments"),
+            ("POST", "/code/deployments", "Start a deployment"),
+            ("GET", "/code/deployments/{id}", "Get a deployment"),
+            (
+                "POST",
+                "/code/deployments/{id}/approve",
+                "Approve a deployment",
+            ),
+            (
+                "POST",
+                "/code/deployments/{id}/reject",
+                "Reject a deployment",
+            ),
+            (
+                "POST",
+                "/code/deployments/{id}/cancel",
+                "Cancel a deployment",
+            ),
+            (
+                "POST",
+                "/code/deployments/{id}/retry",
+                "Retry a failed deployment",
+            ),
+            (
+                "GET",
+                "/code/deployments/{id}/canary",
+                "Get the latest canary of a deployment",
+            ),
+            (
+                "POST",
+                "/code/deployments/{id}/canary",
+                "Start a canary of a pending deployment",
+            ),
+            (
+                "POST",
+                "/code/deployments/{id}/canary/promote",
+                "Promote a passed canary",
+            ),
+            (
+                "POST",
+                "/code/deployments/{id}/canary/rollback",
+                "Roll a canary back",
+            ),
+        ],
+    },
+    Section {
+        tag: "Backup",
+        public: false,
+        operations: &[
+            ("GET", "/backup/status", "Get backup feature status"),
+            ("GET", "/backup/backups", "List all backups"),
+            ("POST", "/backup/backups", "Create a new backup"),
+            ("GET", "/backup/backups/{id}", "Get a single backup by ID"),
+            ("DELETE", "/backup/backups/{id}", "Delete a backup"),
+            (
+                "GET",
+                "/backup/backups/{id}/download",
+                "Download a backup file",
+            ),
+            (
+                "POST",
+                "/backup/backups/{id}/verify",
+                "Verify backup integrity",
+            ),
+            (
+                "POST",
+                "/backup/backups/{id}/restore",
+                "Restore from a backup",
+            ),
+            ("GET", "/backup/schedule", "Get backup schedule"),
+            ("PUT", "/backup/schedule", "Update backup schedule"),
+            ("GET", "/backup/restores", "List restore history"),
+        ],
+    },
+    Section {
+        tag: "Node Removal",
+        public: false,
+        operations: &[
+            (
+                "GET",
+                "/node-removal/status",
+                "Get node removal feature status",
+            ),
+            (
+                "GET",
+                "/node-removal/pending",
+                "List all nodes pending removal",
+            ),
+            (
+                "GET",
+                "/node-removal/pending/{certname}",
+                "Get a specific pending removal by certname",
+            ),
+            (
+                "DELETE",
+                "/node-removal/pending/{certname}",
+                "Unmark a node (cancel pending removal)",
+            ),
+            (
+                "POST",
+                "/node-removal/mark",
+                "Manually mark a node for removal",
+            ),
+            (
+                "POST",
+                "/node-removal/extend",
+                "Extend the removal deadline for a node",
+            ),
+            (
+                "GET",
+                "/node-removal/stats",
+                "Get statistics for pending removals",
+            ),
+            (
+                "GET",
+                "/node-removal/audit",
+                "List recent audit log entries",
+            ),
+            (
+                "GET",
+                "/node-removal/audit/{certname}",
+                "Get audit log for a specific node",
+            ),
+            (
+                "POST",
+                "/node-removal/janitor/run",
+                "Run the stale node janitor now",
+            ),
+            (
+                "GET",
+                "/node-removal/janitor/runs",
+                "List recent janitor runs",
+            ),
+            (
+                "GET",
+                "/node-removal/janitor/runs/{id}",
+                "Get a janitor run with the nodes it purged",
+            ),
+        ],
+    },
+    Section {
+        tag: "Notifications",
+        public: false,
+        operations: &[
+            ("GET", "/notifications", "List notifications"),
+            ("POST", "/notifications", "Create a new notification"),
+            ("GET", "/notifications/stats", "Get notification statistics"),
+            (
+                "POST",
+                "/notifications/mark-all-read",
+                "Mark all notifications as read",
+            ),
+            (
+                "POST",
+                "/notifications/bulk-mark-read",
+                "Bulk mark notifications as read/unread",
+            ),
+            (
+                "GET",
+                "/notifications/stream",
+                "Server-Sent Events stream for real-time notifications",
+            ),
+            ("GET", "/notifications/{id}", "Get a single notification"),
+            (
+                "PUT",
+                "/notifications/{id}/read",
+                "Mark notification as read/unread",
+            ),
+            (
+                "POST",
+                "/notifications/{id}/dismiss",
+                "Dismiss a notification",
+            ),
+            ("DELETE", "/notifications/{id}", "Delete a notification"),
+        ],
+    },
+    Section {
+        tag: "Inventory",
+        public: false,
+        operations: &[
+            ("GET", "/inventory/updates", "List update jobs"),
+            ("POST", "/inventory/updates", "Create an update job"),
+            (
+                "POST",
+                "/inventory/updates/preview",
+                "Preview the nodes an update job would target",
+            ),
+            ("GET", "/inventory/updates/{job_id}", "Get an update job"),
+            (
+                "POST",
+                "/inventory/updates/{job_id}/approve",
+                "Approve an update job",
+            ),
+            (
+                "POST",
+                "/inventory/updates/{job_id}/cancel",
+                "Cancel an update job",
+            ),
+            ("GET", "/inventory/dashboard", "Get the inventory dashboard"),
+            (
+                "GET",
+                "/inventory/dashboard/outdated-software/{name}",
+                "List nodes with an outdated package",
+            ),
+            (
+                "GET",
+                "/inventory/dashboard/compliance/{category}",
+                "List nodes in a compliance category",
+            ),
+            (
+                "GET",
+                "/inventory/dashboard/patch-age/{bucket}",
+                "List nodes in a patch age bucket",
+            ),
+            (
+                "GET",
+                "/inventory/summary",
+                "Get the fleet inventory summary",
+            ),
+            (
+                "GET",
+                "/inventory/catalog",
+                "List package versions offered by repositories",
+            ),
+            (
+                "GET",
+                "/inventory/repositories",
+                "List package repositories used by the fleet",
+            ),
+            (
+                "POST",
+                "/inventory/repositories/check",
+                "Check package repositories for newer versions now",
+            ),
+        ],
+    },
+    Section {
+        tag: "CVE",
+        public: false,
+        operations: &[
+            ("GET", "/cve/dashboard", "Get the vulnerability dashboard"),
+            ("GET", "/cve/nodes", "List vulnerable nodes"),
+            (
+                "GET",
+                "/cve/nodes/{certname}",
+                "Get the vulnerabilities of a node",
+            ),
+            ("GET", "/cve/entries", "Search CVE entries"),
+            ("GET", "/cve/entries/{cve_id}", "Get a CVE entry"),
+            ("GET", "/cve/feeds", "List CVE feeds"),
+            ("POST", "/cve/feeds", "Add a CVE feed"),
+            ("PUT", "/cve/feeds/{id}", "Update a CVE feed"),
+            ("DELETE", "/cve/feeds/{id}", "Remove a CVE feed"),
+            ("POST", "/cve/feeds/{id}/sync", "Synchronize a CVE feed now"),
+            (
+                "POST",
+                "/cve/refresh-matches",
+                "Match the inventory against CVE entries now",
+            ),
+        ],
+    },
+    Section {
+        tag: "Debug",
+        public: false,
+        operations: &[
+            (
+                "GET",
+                "/debug/payloads",
+                "Get capture status and captured exchanges (newest first)",
+            ),
+            (
+                "POST",
+                "/debug/payloads",
+                "Start (or replace) a capture session",
+            ),
+            (
+                "DELETE",
+                "/debug/payloads",
+                "Stop the capture session, keeping captured exchanges",
+            ),
+            (
+                "DELETE",
+                "/debug/payloads/exchanges",
+                "Discard captured exchanges",
+            ),
+        ],
+    },
+    Section {
+        tag: "Logs",
+        public: false,
+        operations: &[
+            ("GET", "/logs", "List recent log records"),
+            (
+                "GET",
+                "/logs/stream",
+                "Follow the log over Server-Sent Events",
+            ),
+        ],
+    },
+];
+
+/// Parameters of a path template, e.g. `certname` in `/nodes/{certname}`
+fn path_params(path: &str) -> Vec<&str> {
+    path.split('/')
+        .filter_map(|segment| segment.strip_prefix('{')?.strip_suffix('}'))
+        .collect()
+}
+
+/// Stable operation ID, e.g. `getGroupsIdRules` for `GET /groups/{id}/rules`
+fn operation_id(method: &str, path: &str) -> String {
+    let mut id = method.to_ascii_lowercase();
+    for word in path
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+    {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            id.push(first.to_ascii_uppercase());
+            id.push_str(chars.as_str());
+        }
+    }
+    id
+}
+
+/// OpenAPI document for the API served under `base_path`
+pub fn openapi_spec(base_path: &str) -> Value {
+    let mut tags: Vec<&str> = Vec::new();
+    let mut paths = Map::new();
+
+    for section in SECTIONS {
+        if !tags.contains(&section.tag) {
+            tags.push(section.tag);
+        }
+        for (method, path, summary) in section.operations {
+            let mut responses = json!({
+                "200": {
+                    "description": "Success",
+                    "content": { "application/json": { "schema": {} } },
+                },
+                "default": { "$ref": "#/components/responses/Error" },
+            });
+            let mut operation = json!({
+                "operationId": operation_id(method, path),
+                "summary": summary,
+                "tags": [section.tag],
+            });
+
+            let parameters: Vec<Value> = path_params(path)
+                .into_iter()
+                .map(|name| {
+                    json!({
+                        "name": name,
+                        "in": "path",
+                        "required": true,
+                        "schema": { "type": "string" },
+                    })
+                })
+                .collect();
+            if !parameters.is_empty() {
+                operation["parameters"] = Value::Array(parameters);
+            }
+            if matches!(*method, "POST" | "PUT" | "PATCH") {
+                operation["requestBody"] = json!({
+                    "content": { "application/json": { "schema": { "type": "object" } } },
+                });
+            }
+            if section.public {
+                operation["security"] = json!([]);
+            } else {
+                responses["401"] = json!({ "$ref": "#/components/responses/Error" });
+                responses["403"] = json!({ "$ref": "#/components/responses/Error" });
+            }
+            operation["responses"] = responses;
+
+            paths.entry(path.to_string()).or_insert_with(|| json!({}))
+                [method.to_ascii_lowercase()] = operation;
+        }
+    }
+
+    json!({
+        "openapi": "3.1.0",
+        "info": {
+            "title": "OpenVox WebUI API",
+            "version": env!("CARGO_PKG_VERSION"),
+            "license": { "name": "Apache-2.0", "identifier": "Apache-2.0" },
+        },
+        "servers": [{ "url": format!("{}/api/v1", base_path) }],
+        "tags": tags.iter().map(|name| json!({ "name": name })).collect::<Vec<_>>(),
+        "security": [{ "bearerAuth": [] }, { "apiKey": [] }],
+        "paths": paths,
+        "components": {
+            "securitySchemes": {
+                "bearerAuth": { "type": "http", "scheme": "bearer", "bearerFormat": "JWT" },
+                "apiKey": { "type": "apiKey", "in": "header", "name": "X-API-Key" },
+            },
+            "schemas": {
+                "ApiError": {
+                    "type": "object",
+                    "required": ["error", "code", "message"],
+                    "properties": {
+                        "error": { "type": "string" },
+                        "code": { "type": "string" },
+                        "message": { "type": "string" },
+                        "details": {},
+                        "fields": {
+                            "type": "array",
+                            "items": {
+                                "type": "object",
+                                "required": ["field", "message"],
+                                "properties": {
+                                    "field": { "type": "string" },
+                                    "message": { "type": "string" },
+                                },
+                            },
+                        },
+                        "request_id": { "type": "string" },
+                    },
+                },
+            },
+            "responses": {
+                "Error": {
+                    "description": "Error",
+                    "content": {
+                        "application/json": {
+                            "schema": { "$ref": "#/components/schemas/ApiError" },
+                        },
+                    },
+                },
+            },
+        },
+    })
+}
+
+/// Simple HTML escaping
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#x27;")
+}
+
+/// Get the OpenAPI document
+///
+/// GET /api/v1/openapi.json
+async fn get_openapi(State(state): State<AppState>) -> Json<Value> {
+    Json(openapi_spec(&state.config.server.normalized_base_path()))
+}
+
+/// API reference page
+///
+/// GET /api/v1/docs
+///
+/// Self-contained, so it works under the default Content-Security-Policy.
+async fn get_docs(State(state): State<AppState>) -> Html<String> {
+    let base_path = state.config.server.normalized_base_path();
+    let mut body = String::new();
+    let mut tag = "";
+    for section in SECTIONS {
+        if section.tag != tag {
+            tag = section.tag;
+            body.push_str(&format!("<h2>{}</h2>\n", html_escape(tag)));
+        }
+        for (method, path, summary) in section.operations {
+            body.push_str(&format!(
+                "<div class=\"op\"><span class=\"method {}\">{}</span> <code>{}</code>{} <span class=\"summary\">{}</span></div>\n",
+                method.to_ascii_lowercase(),
+                method,
+                html_escape(path),
+                if section.public { " <span class=\"public\">public</span>" } else { "" },
+                html_escape(summary)
+            ));
+        }
+    }
+
+    Html(format!(
+        r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+    <meta charset="UTF-8">
+    <meta name="viewport" content="width=device-width, initial-scale=1.0">
+    <title>OpenVox WebUI API</title>
+    <style>
+        body {{ font-family: -apple-system, BlinkMacSystemFont, 'Segoe UI', Roboto, sans-serif; max-width: 960px; margin: 2rem auto; padding: 0 1rem; color: #1f2937; }}
+        h2 {{ border-bottom: 1px solid #e5e7eb; padding-bottom: 0.25rem; margin-top: 2rem; }}
+        .op {{ padding: 0.25rem 0; }}
+        .method {{ display: inline-block; width: 4.5rem; font-weight: 600; font-size: 0.8rem; }}
+        .get {{ color: #2563eb; }} .post {{ color: #16a34a; }} .put {{ color: #d97706; }} .delete {{ color: #dc2626; }}
+        .public {{ font-size: 0.75rem; color: #6b7280; border: 1px solid #d1d5db; border-radius: 4px; padding: 0 0.25rem; }}
+        .summary {{ color: #4b5563; }}
+    </style>
+</head>
+<body>
+    <h1>OpenVox WebUI API {version}</h1>
+    <p>Paths are relative to <code>{base}</code>. Authenticate with <code>Authorization: Bearer &lt;token&gt;</code>
+    or an <code>X-API-Key</code> header. The <a href="{base}/openapi.json">OpenAPI document</a> can be loaded into
+    Swagger UI, Redoc or a client generator.</p>
+{body}</body>
+</html>"#,
+        version = env!("CARGO_PKG_VERSION"),
+        base = html_escape(&format!("{}/api/v1", base_path)),
+        body = body,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_operation_ids_are_unique() {
+        let mut ids = HashSet::new();
+        for section in SECTIONS {
+            for (method, path, _) in section.operations {
+                assert!(
+                    ids.insert(operation_id(method, path)),
+                    "duplicate operation {} {}",
+                    method,
+                    path
+                );
+            }
+        }
+        assert_eq!(
+            operation_id("GET", "/groups/{id}/rules"),
+            "getGroupsIdRules"
+        );
+        assert_eq!(
+            operation_id("POST", "/auth/forgot-password"),
+            "postAuthForgotPassword"
+        );
+    }
+
+    #[test]
+    fn test_openapi_spec() {
+        let spec = openapi_spec("/puppet");
+        assert_eq!(spec["openapi"], "3.1.0");
+        assert_eq!(spec["servers"][0]["url"], "/puppet/api/v1");
+
+        let login = &spec["paths"]["/auth/login"]["post"];
+        assert_eq!(login["security"], json!([]));
+        assert!(login["requestBody"].is_object());
+
+        let node = &spec["paths"]["/nodes/{certname}"]["get"];
+        assert_eq!(node["parameters"][0]["name"], "certname");
+        assert!(node.get("security").is_none());
+        assert!(node["responses"]["401"].is_object());
+    }
+}
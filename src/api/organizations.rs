@@ -3,15 +3,24 @@
 use axum::{
     extract::{Path, State},
     http::StatusCode,
-    routing::get,
+    routing::{get, post, put},
     Json, Router,
 };
 use uuid::Uuid;
 
 use crate::{
-    db::{AuditRepository, OrganizationRepository},
-    middleware::AuthUser,
-    models::{CreateOrganizationRequest, Organization, UpdateOrganizationRequest},
+    db::OrganizationRepository,
+    middleware::{auth::revoke_user_auth_sessions, AuthUser},
+    models::{
+        CreateOrganizationRequest, MergeOrganizationRequest, Organization, OrganizationPuppetDb,
+        OrganizationQuotas, OrganizationTransferReport, OrganizationUsage,
+        SetOrganizationPuppetDbRequest, SplitOrganizationRequest, TransferKind,
+        UpdateOrganizationRequest,
+    },
+    services::{
+        group_templates, organization_transfer, puppetdb_registry::client_config,
+        quotas::organization_usage, PuppetDbClient,
+    },
     utils::AppError,
     AppState,
 };
@@ -26,6 +35,16 @@ pub fn routes() -> Router<AppState> {
                 .put(update_organization)
                 .delete(delete_organization),
         )
+        .route("/{id}/merge", post(merge_organization))
+        .route("/{id}/split", post(split_organization))
+        .route("/{id}/quotas", put(update_organization_quotas))
+        .route("/{id}/usage", get(get_organization_usage))
+        .route(
+            "/{id}/puppetdb",
+            get(get_organization_puppetdb)
+                .put(set_organization_puppetdb)
+                .delete(delete_organization_puppetdb),
+        )
 }
 
 fn require_super_admin(auth_user: &AuthUser) -> Result<(), AppError> {
@@ -110,7 +129,25 @@ async fn create_organization(
         }
     })?;
 
-    let audit_repo = AuditRepository::new(&state.db);
+    // An organization is only created with all of its template groups
+    let groups = match group_templates::instantiate(
+        &state.db,
+        org.id,
+        &state.config.organizations.group_templates,
+    )
+    .await
+    {
+        Ok(groups) => groups.into_iter().map(|g| g.name).collect::<Vec<_>>(),
+        Err(e) => {
+            tracing::error!("Failed to create template groups for {}: {:#}", org.id, e);
+            if let Err(e) = repo.delete_with_groups(org.id).await {
+                tracing::error!("Failed to roll back organization {}: {:#}", org.id, e);
+            }
+            return Err(AppError::internal("Failed to create organization groups"));
+        }
+    };
+
+    let audit_repo = state.audit_repository();
     let _ = audit_repo
         .insert(
             auth_user.organization_id,
@@ -118,7 +155,7 @@ async fn create_organization(
             "organization.create",
             "organizations",
             Some(&org.id.to_string()),
-            Some(&serde_json::json!({ "name": org.name, "slug": org.slug })),
+            Some(&serde_json::json!({ "name": org.name, "slug": org.slug, "groups": groups })),
             None,
         )
         .await;
@@ -148,7 +185,7 @@ async fn update_organization(
 
     match updated {
         Some(org) => {
-            let audit_repo = AuditRepository::new(&state.db);
+            let audit_repo = state.audit_repository();
             let _ = audit_repo
                 .insert(
                     auth_user.organization_id,
@@ -188,7 +225,7 @@ async fn delete_organization(
     })?;
 
     if deleted {
-        let audit_repo = AuditRepository::new(&state.db);
+        let audit_repo = state.audit_repository();
         let _ = audit_repo
             .insert(
                 auth_user.organization_id,
@@ -204,3 +241,320 @@ async fn delete_organization(
 
     Ok(Json(deleted))
 }
+
+async fn load_organization(state: &AppState, id: Uuid) -> Result<Organization, AppError> {
+    OrganizationRepository::new(&state.db)
+        .get_by_id(id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get organization: {}", e);
+            AppError::internal("Failed to get organization")
+        })?
+        .ok_or_else(|| AppError::not_found("Organization not found"))
+}
+
+/// Sign moved users out, so their next token carries the new organization
+async fn after_transfer(state: &AppState, report: &OrganizationTransferReport) {
+    for item in report.items.iter().filter(|i| i.kind == TransferKind::User) {
+        state.rbac_db.invalidate_user_cache(&item.id);
+        if let Err(e) = revoke_user_auth_sessions(&state.db, &item.id).await {
+            tracing::error!("Failed to revoke sessions of user {}: {:?}", item.id, e);
+        }
+    }
+}
+
+/// Move the groups, users and fact templates of an organization into another
+///
+/// POST /api/v1/organizations/{id}/merge
+///
+/// With `dry_run` the report lists what would move and which names conflict,
+/// without changing anything. The emptied organization is kept.
+async fn merge_organization(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<String>,
+    Json(payload): Json<MergeOrganizationRequest>,
+) -> Result<Json<OrganizationTransferReport>, AppError> {
+    require_super_admin(&auth_user)?;
+    let uuid =
+        Uuid::parse_str(&id).map_err(|_| AppError::bad_request("Invalid organization ID"))?;
+
+    let source = load_organization(&state, uuid).await?;
+    let target = load_organization(&state, payload.target_id).await?;
+    let report = organization_transfer::merge(
+        &state.db,
+        &source,
+        &target,
+        payload.on_conflict,
+        payload.dry_run,
+    )
+    .await?;
+    if report.dry_run {
+        return Ok(Json(report));
+    }
+
+    after_transfer(&state, &report).await;
+    let renamed: Vec<_> = report
+        .items
+        .iter()
+        .filter_map(|i| i.renamed_to.as_ref().map(|to| (&i.name, to)))
+        .collect();
+    let _ = state
+        .audit_repository()
+        .insert(
+            target.id,
+            Some(auth_user.user_id()),
+            "organization.merge",
+            "organizations",
+            Some(&source.id.to_string()),
+            Some(&serde_json::json!({
+                "source": source.slug,
+                "target": target.slug,
+                "moved": report.items.len(),
+                "api_keys": report.api_keys,
+                "renamed": renamed,
+            })),
+            None,
+        )
+        .await;
+
+    Ok(Json(report))
+}
+
+/// Move selected groups of an organization into a new organization
+///
+/// POST /api/v1/organizations/{id}/split
+///
+/// Each selected group moves with the groups below it, as do the selected
+/// users and fact templates. With `dry_run` nothing is created or moved.
+async fn split_organization(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<String>,
+    Json(payload): Json<SplitOrganizationRequest>,
+) -> Result<(StatusCode, Json<OrganizationTransferReport>), AppError> {
+    require_super_admin(&auth_user)?;
+    let uuid =
+        Uuid::parse_str(&id).map_err(|_| AppError::bad_request("Invalid organization ID"))?;
+
+    let source = load_organization(&state, uuid).await?;
+    let report = organization_transfer::split(&state.db, &source, &payload).await?;
+    let Some(target_id) = report.target_id else {
+        return Ok((StatusCode::OK, Json(report)));
+    };
+
+    after_transfer(&state, &report).await;
+    let _ = state
+        .audit_repository()
+        .insert(
+            source.id,
+            Some(auth_user.user_id()),
+            "organization.split",
+            "organizations",
+            Some(&target_id.to_string()),
+            Some(&serde_json::json!({
+                "source": source.slug,
+                "name": payload.name,
+                "slug": payload.slug,
+                "moved": report.items.len(),
+                "api_keys": report.api_keys,
+            })),
+            None,
+        )
+        .await;
+
+    Ok((StatusCode::CREATED, Json(report)))
+}
+
+async fn update_organization_quotas(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<String>,
+    Json(payload): Json<OrganizationQuotas>,
+) -> Result<Json<Organization>, AppError> {
+    require_super_admin(&auth_user)?;
+    let uuid =
+        Uuid::parse_str(&id).map_err(|_| AppError::bad_request("Invalid organization ID"))?;
+
+    let limits = [
+        payload.max_groups,
+        payload.max_saved_reports,
+        payload.max_api_keys,
+        payload.max_users,
+    ];
+    if limits.iter().flatten().any(|limit| *limit < 0) {
+        return Err(AppError::validation("Quota limits cannot be negative"));
+    }
+
+    let repo = OrganizationRepository::new(&state.db);
+    let updated = repo.set_quotas(uuid, &payload).await.map_err(|e| {
+        tracing::error!("Failed to update organization quotas: {}", e);
+        AppError::internal("Failed to update organization quotas")
+    })?;
+
+    match updated {
+        Some(org) => {
+            let audit_repo = state.audit_repository();
+            let _ = audit_repo
+                .insert(
+                    auth_user.organization_id,
+                    Some(auth_user.user_id()),
+                    "organization.quotas.update",
+                    "organizations",
+                    Some(&org.id.to_string()),
+                    Some(&serde_json::json!(org.quotas)),
+                    None,
+                )
+                .await;
+            Ok(Json(org))
+        }
+        None => Err(AppError::not_found("Organization not found")),
+    }
+}
+
+async fn get_organization_usage(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<OrganizationUsage>, AppError> {
+    let uuid =
+        Uuid::parse_str(&id).map_err(|_| AppError::bad_request("Invalid organization ID"))?;
+
+    // Admins see their own organization's usage; other organizations require super_admin.
+    if uuid != auth_user.organization_id {
+        require_super_admin(&auth_user)?;
+    } else if !auth_user.is_super_admin() && !auth_user.roles.iter().any(|r| r == "admin") {
+        return Err(AppError::forbidden("admin role required"));
+    }
+
+    let usage = organization_usage(&state.db, uuid).await?;
+    Ok(Json(usage))
+}
+
+async fn get_organization_puppetdb(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<String>,
+) -> Result<Json<OrganizationPuppetDb>, AppError> {
+    require_super_admin(&auth_user)?;
+    let uuid =
+        Uuid::parse_str(&id).map_err(|_| AppError::bad_request("Invalid organization ID"))?;
+
+    let repo = OrganizationRepository::new(&state.db);
+    let mapping = repo.get_puppetdb(uuid).await.map_err(|e| {
+        tracing::error!("Failed to get organization PuppetDB: {}", e);
+        AppError::internal("Failed to get organization PuppetDB")
+    })?;
+
+    mapping
+        .map(Json)
+        .ok_or_else(|| AppError::not_found("Organization uses the shared PuppetDB"))
+}
+
+async fn set_organization_puppetdb(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<String>,
+    Json(payload): Json<SetOrganizationPuppetDbRequest>,
+) -> Result<Json<OrganizationPuppetDb>, AppError> {
+    require_super_admin(&auth_user)?;
+    let uuid =
+        Uuid::parse_str(&id).map_err(|_| AppError::bad_request("Invalid organization ID"))?;
+
+    let url = payload.url.trim();
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return Err(AppError::validation(
+            "PuppetDB URL must start with http:// or https://",
+        ));
+    }
+    if payload.timeout_secs == 0 {
+        return Err(AppError::validation("timeout_secs must be at least 1"));
+    }
+
+    let repo = OrganizationRepository::new(&state.db);
+    let exists = repo.get_by_id(uuid).await.map_err(|e| {
+        tracing::error!("Failed to get organization: {}", e);
+        AppError::internal("Failed to get organization")
+    })?;
+    if exists.is_none() {
+        return Err(AppError::not_found("Organization not found"));
+    }
+
+    let payload = SetOrganizationPuppetDbRequest {
+        url: url.trim_end_matches('/').to_string(),
+        ..payload
+    };
+
+    // Build a client up front so unreadable certificates are reported now
+    // rather than on the organization's next request.
+    let now = chrono::Utc::now();
+    let candidate = OrganizationPuppetDb {
+        organization_id: uuid,
+        url: payload.url.clone(),
+        timeout_secs: payload.timeout_secs,
+        ssl_verify: payload.ssl_verify,
+        ssl_cert: payload.ssl_cert.clone(),
+        ssl_key: payload.ssl_key.clone(),
+        ssl_ca: payload.ssl_ca.clone(),
+        created_at: now,
+        updated_at: now,
+    };
+    PuppetDbClient::new(&client_config(&candidate))
+        .map_err(|e| AppError::validation(format!("Invalid PuppetDB settings: {}", e)))?;
+
+    let mapping = repo.set_puppetdb(uuid, &payload).await.map_err(|e| {
+        tracing::error!("Failed to set organization PuppetDB: {}", e);
+        AppError::internal("Failed to set organization PuppetDB")
+    })?;
+    state.puppetdb_tenants.invalidate(uuid).await;
+
+    let audit_repo = state.audit_repository();
+    let _ = audit_repo
+        .insert(
+            auth_user.organization_id,
+            Some(auth_user.user_id()),
+            "organization.puppetdb.update",
+            "organization_puppetdb",
+            Some(&uuid.to_string()),
+            Some(&serde_json::json!({ "url": mapping.url })),
+            None,
+        )
+        .await;
+
+    Ok(Json(mapping))
+}
+
+async fn delete_organization_puppetdb(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<String>,
+) -> Result<StatusCode, AppError> {
+    require_super_admin(&auth_user)?;
+    let uuid =
+        Uuid::parse_str(&id).map_err(|_| AppError::bad_request("Invalid organization ID"))?;
+
+    let repo = OrganizationRepository::new(&state.db);
+    let deleted = repo.delete_puppetdb(uuid).await.map_err(|e| {
+        tracing::error!("Failed to delete organization PuppetDB: {}", e);
+        AppError::internal("Failed to delete organization PuppetDB")
+    })?;
+    if !deleted {
+        return Err(AppError::not_found("Organization uses the shared PuppetDB"));
+    }
+    state.puppetdb_tenants.invalidate(uuid).await;
+
+    let audit_repo = state.audit_repository();
+    let _ = audit_repo
+        .insert(
+            auth_user.organization_id,
+            Some(auth_user.user_id()),
+            "organization.puppetdb.delete",
+            "organization_puppetdb",
+            Some(&uuid.to_string()),
+            None,
+            None,
+        )
+        .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -0,0 +1,145 @@
+//! Payload capture API endpoints
+//!
+//! Lets administrators start a short, route-scoped capture of request and
+//! response bodies and inspect the sanitized results.
+
+use axum::{
+    extract::State,
+    http::StatusCode,
+    routing::{delete, get},
+    Json, Router,
+};
+use chrono::{Duration, Utc};
+use serde::Deserialize;
+
+use crate::middleware::payload_debug::{
+    CaptureSession, PayloadDebugSnapshot, DEFAULT_MAX_BODY_BYTES, MAX_BODY_BYTES_LIMIT,
+    MAX_CAPTURE_DURATION_SECS,
+};
+use crate::middleware::AuthUser;
+use crate::models::{Action, Resource};
+use crate::utils::error::AppError;
+use crate::AppState;
+
+/// Default capture duration
+const DEFAULT_DURATION_MINUTES: u64 = 15;
+
+/// Create payload capture routes
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route(
+            "/",
+            get(get_capture).post(start_capture).delete(stop_capture),
+        )
+        .route("/exchanges", delete(clear_exchanges))
+}
+
+/// Request to start a capture
+#[derive(Debug, Deserialize)]
+pub struct StartCaptureRequest {
+    /// Path pattern, e.g. `/api/v1/nodes/*/classify` or `/api/v1/webhooks/**`
+    pub route_pattern: String,
+    /// Capture duration in minutes (default 15, max 60)
+    pub duration_minutes: Option<u64>,
+    /// Bytes kept per body (default 16 KiB, max 256 KiB)
+    pub max_body_bytes: Option<usize>,
+}
+
+/// Require `settings:admin`
+///
+/// Diagnostic endpoints (payload capture, log viewer) expose request data and
+/// internals, so they need more than settings read access.
+pub(super) async fn require_settings_admin(
+    state: &AppState,
+    auth_user: &AuthUser,
+) -> Result<(), AppError> {
+    let check = state
+        .rbac_db
+        .check_permission(
+            &auth_user.user_id(),
+            Resource::Settings,
+            Action::Admin,
+            None,
+            None,
+        )
+        .await
+        .map_err(|e| AppError::internal(format!("Permission check failed: {}", e)))?;
+
+    if check.allowed {
+        Ok(())
+    } else {
+        Err(AppError::forbidden(check.reason.unwrap_or_else(|| {
+            "No matching permission found".to_string()
+        })))
+    }
+}
+
+/// Get capture status and captured exchanges (newest first)
+async fn get_capture(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<PayloadDebugSnapshot>, AppError> {
+    require_settings_admin(&state, &auth_user).await?;
+    Ok(Json(state.payload_debug.snapshot()))
+}
+
+/// Start (or replace) a capture session
+async fn start_capture(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(req): Json<StartCaptureRequest>,
+) -> Result<Json<PayloadDebugSnapshot>, AppError> {
+    require_settings_admin(&state, &auth_user).await?;
+
+    let route_pattern = req.route_pattern.trim();
+    if !route_pattern.starts_with('/') {
+        return Err(AppError::bad_request("route_pattern must start with '/'"));
+    }
+
+    let duration_secs = req.duration_minutes.unwrap_or(DEFAULT_DURATION_MINUTES) * 60;
+    if duration_secs == 0 || duration_secs > MAX_CAPTURE_DURATION_SECS {
+        return Err(AppError::bad_request(format!(
+            "duration_minutes must be between 1 and {}",
+            MAX_CAPTURE_DURATION_SECS / 60
+        )));
+    }
+
+    let max_body_bytes = req.max_body_bytes.unwrap_or(DEFAULT_MAX_BODY_BYTES);
+    if max_body_bytes > MAX_BODY_BYTES_LIMIT {
+        return Err(AppError::bad_request(format!(
+            "max_body_bytes cannot exceed {}",
+            MAX_BODY_BYTES_LIMIT
+        )));
+    }
+
+    let started_at = Utc::now();
+    state.payload_debug.start(CaptureSession {
+        route_pattern: route_pattern.to_string(),
+        started_at,
+        expires_at: started_at + Duration::seconds(duration_secs as i64),
+        max_body_bytes,
+        started_by: auth_user.username.clone(),
+    });
+
+    Ok(Json(state.payload_debug.snapshot()))
+}
+
+/// Stop the capture session, keeping captured exchanges
+async fn stop_capture(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<StatusCode, AppError> {
+    require_settings_admin(&state, &auth_user).await?;
+    state.payload_debug.stop();
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Discard captured exchanges
+async fn clear_exchanges(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<StatusCode, AppError> {
+    require_settings_admin(&state, &auth_user).await?;
+    state.payload_debug.clear();
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -12,7 +12,7 @@ use uuid::Uuid;
 
 use crate::{
     models::{Action, CreatePermissionRequest, PermissionWithRole, Resource, Role},
-    utils::error::ErrorResponse,
+    utils::error::ApiError,
     AppState,
 };
 
@@ -31,16 +31,14 @@ pub fn routes() -> Router<AppState> {
 /// GET /api/v1/permissions
 async fn list_permissions(
     State(state): State<AppState>,
-) -> Result<Json<Vec<PermissionWithRole>>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<Vec<PermissionWithRole>>, (StatusCode, Json<ApiError>)> {
     let permissions = state.rbac_db.get_all_permissions().await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "internal_error".to_string(),
-                message: format!("Failed to fetch permissions: {}", e),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "internal_error",
+                format!("Failed to fetch permissions: {}", e),
+            )),
         )
     })?;
 
@@ -127,15 +125,23 @@ fn get_resource_actions(resource: &Resource) -> Vec<String> {
     match resource {
         Resource::Nodes => vec!["read", "classify", "delete"],
         Resource::Groups => vec!["read", "create", "update", "delete", "admin"],
-        Resource::Reports => vec!["read", "export"],
+        Resource::Reports => vec!["read", "create", "update", "delete", "export", "admin"],
         Resource::Facts => vec!["read", "generate", "export"],
-        Resource::Users => vec!["read", "create", "update", "delete", "admin"],
+        Resource::Users => vec!["read", "create", "update", "delete", "impersonate", "admin"],
         Resource::Roles => vec!["read", "create", "update", "delete", "admin"],
-        Resource::Settings => vec!["read", "update"],
+        Resource::Settings => vec!["read", "update", "admin"],
         Resource::AuditLogs => vec!["read"],
         Resource::FacterTemplates => vec!["read", "create", "update", "delete"],
         Resource::ApiKeys => vec!["read", "create", "delete"],
-        Resource::Certificates => vec!["read", "sign", "reject", "revoke", "admin"],
+        Resource::Certificates => vec![
+            "read",
+            "sign",
+            "reject",
+            "revoke",
+            "bulk_sign",
+            "bulk_revoke",
+            "admin",
+        ],
     }
     .iter()
     .map(|s| s.to_string())
@@ -155,6 +161,9 @@ fn format_action_name(action: &Action) -> String {
         Action::Sign => "Sign".to_string(),
         Action::Reject => "Reject".to_string(),
         Action::Revoke => "Revoke".to_string(),
+        Action::BulkSign => "Bulk Sign".to_string(),
+        Action::BulkRevoke => "Bulk Revoke".to_string(),
+        Action::Impersonate => "Impersonate".to_string(),
     }
 }
 
@@ -170,6 +179,9 @@ fn get_action_description(action: &Action) -> String {
         Action::Sign => "Sign certificate requests".to_string(),
         Action::Reject => "Reject certificate requests".to_string(),
         Action::Revoke => "Revoke signed certificates".to_string(),
+        Action::BulkSign => "Sign many certificate requests at once".to_string(),
+        Action::BulkRevoke => "Revoke many certificates at once".to_string(),
+        Action::Impersonate => "Act as another user, with an audit trail".to_string(),
         Action::Generate => "Generate derived data (e.g., facts)".to_string(),
     }
 }
@@ -211,17 +223,15 @@ pub struct ResourceWithActions {
 /// GET /api/v1/permissions/matrix
 async fn get_permission_matrix(
     State(state): State<AppState>,
-) -> Result<Json<PermissionMatrix>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<PermissionMatrix>, (StatusCode, Json<ApiError>)> {
     // Get all roles
     let roles = state.rbac_db.get_all_roles().await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "internal_error".to_string(),
-                message: format!("Failed to fetch roles: {}", e),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "internal_error",
+                format!("Failed to fetch roles: {}", e),
+            )),
         )
     })?;
 
@@ -358,7 +368,7 @@ pub struct BulkOperationResult {
 async fn bulk_update_permissions(
     State(state): State<AppState>,
     Json(payload): Json<BulkPermissionRequest>,
-) -> Result<Json<BulkPermissionResult>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<BulkPermissionResult>, (StatusCode, Json<ApiError>)> {
     let total = payload.operations.len();
     let mut succeeded = 0;
     let mut failed = 0;
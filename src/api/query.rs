@@ -6,6 +6,7 @@ use axum::{extract::State, routing::post, Json, Router};
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    middleware::AuthUser,
     utils::error::{AppError, AppResult},
     AppState,
 };
@@ -50,11 +51,12 @@ pub struct PqlResponse {
 /// - `resources { type = 'Package' and title = 'httpd' }` - Find package resources
 async fn execute_pql(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Json(request): Json<PqlRequest>,
 ) -> AppResult<Json<PqlResponse>> {
     let puppetdb = state
-        .puppetdb
-        .as_ref()
+        .puppetdb_for(auth_user.organization_id)
+        .await
         .ok_or_else(|| AppError::ServiceUnavailable("PuppetDB is not configured".to_string()))?;
 
     // Validate query is not empty
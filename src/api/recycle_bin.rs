@@ -0,0 +1,195 @@
+//! Recycle bin endpoints
+//!
+//! Deleted node groups, users and saved reports stay in the recycle bin for
+//! `recycle_bin.retention_days` before they are purged. These endpoints list
+//! them, restore them, or purge them right away. Every record needs the
+//! `delete` permission on its resource; group-scoped permissions apply to
+//! deleted groups as well.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{delete, get, post},
+    Json, Router,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    db::RecycleBinRepository,
+    middleware::{rbac, AuthUser, RbacError},
+    models::{Action, RecycleBinKind, RecycleBinResponse, RestoreResponse},
+    services::quotas::check_quota,
+    utils::AppError,
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_deleted))
+        .route("/{kind}/{id}", delete(purge_deleted))
+        .route("/{kind}/{id}/restore", post(restore_deleted))
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct OrgQuery {
+    organization_id: Option<Uuid>,
+}
+
+fn resolve_org(auth_user: &AuthUser, requested: Option<Uuid>) -> Result<Uuid, AppError> {
+    match requested {
+        Some(_) if !auth_user.is_super_admin() => Err(AppError::forbidden(
+            "organization_id can only be specified by super_admin",
+        )),
+        Some(org_id) => Ok(org_id),
+        None => Ok(auth_user.organization_id),
+    }
+}
+
+/// Whether the user may restore or purge a deleted record
+async fn may_manage(
+    state: &AppState,
+    auth_user: &AuthUser,
+    kind: RecycleBinKind,
+    id: Uuid,
+) -> Result<bool, AppError> {
+    if kind == RecycleBinKind::Group {
+        return match rbac::check_group_permission(state, auth_user, Action::Delete, Some(id)).await
+        {
+            Ok(()) => Ok(true),
+            Err(RbacError::PermissionDenied { .. }) => Ok(false),
+            Err(other) => Err(AppError::internal(other.to_string())),
+        };
+    }
+
+    let check = state
+        .rbac_db
+        .check_permission(
+            &auth_user.user_id(),
+            kind.resource(),
+            Action::Delete,
+            None,
+            None,
+        )
+        .await
+        .map_err(|e| AppError::internal(format!("Permission check failed: {}", e)))?;
+    Ok(check.allowed)
+}
+
+/// Parse the record type and check the user may manage the record
+async fn authorize(
+    state: &AppState,
+    auth_user: &AuthUser,
+    kind: &str,
+    id: Uuid,
+) -> Result<RecycleBinKind, AppError> {
+    let kind: RecycleBinKind = kind.parse().map_err(AppError::bad_request)?;
+    if !may_manage(state, auth_user, kind, id).await? {
+        return Err(AppError::forbidden(format!(
+            "Delete permission on {} required",
+            kind.resource().as_str()
+        )));
+    }
+    Ok(kind)
+}
+
+/// List the deleted records the user may restore, most recent first
+async fn list_deleted(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+) -> Result<Json<RecycleBinResponse>, AppError> {
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let retention_days = state.config.recycle_bin.retention_days;
+
+    let deleted = RecycleBinRepository::new(&state.db)
+        .list(org_id, retention_days)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list the recycle bin: {}", e);
+            AppError::internal("Failed to list the recycle bin")
+        })?;
+
+    let mut items = Vec::with_capacity(deleted.len());
+    for item in deleted {
+        if may_manage(&state, &auth_user, item.kind, item.id).await? {
+            items.push(item);
+        }
+    }
+
+    Ok(Json(RecycleBinResponse {
+        retention_days,
+        items,
+    }))
+}
+
+/// Restore a deleted record
+///
+/// A group comes back with the subgroups deleted along with it. A group
+/// whose parent is still deleted cannot be restored on its own.
+async fn restore_deleted(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Path((kind, id)): Path<(String, Uuid)>,
+) -> Result<Json<RestoreResponse>, AppError> {
+    let kind = authorize(&state, &auth_user, &kind, id).await?;
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+
+    let repo = RecycleBinRepository::new(&state.db);
+    let internal = |e: anyhow::Error| {
+        tracing::error!("Failed to restore {} {}: {}", kind.as_str(), id, e);
+        AppError::internal("Failed to restore record")
+    };
+    repo.get(org_id, kind, id, state.config.recycle_bin.retention_days)
+        .await
+        .map_err(internal)?
+        .ok_or_else(|| AppError::not_found("Deleted record not found"))?;
+
+    if kind == RecycleBinKind::Group {
+        if let Some(parent) = repo
+            .deleted_parent_name(org_id, id)
+            .await
+            .map_err(internal)?
+        {
+            return Err(AppError::conflict(format!(
+                "Parent group '{}' is deleted; restore it first",
+                parent
+            )));
+        }
+    }
+    check_quota(&state.db, org_id, kind.quota()).await?;
+
+    let restored = repo.restore(org_id, kind, id).await.map_err(internal)?;
+    if restored == 0 {
+        return Err(AppError::not_found("Deleted record not found"));
+    }
+
+    Ok(Json(RestoreResponse { kind, id, restored }))
+}
+
+/// Purge a deleted record now instead of at the end of its retention
+///
+/// A group is purged with the subgroups deleted along with it.
+async fn purge_deleted(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Path((kind, id)): Path<(String, Uuid)>,
+) -> Result<StatusCode, AppError> {
+    let kind = authorize(&state, &auth_user, &kind, id).await?;
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+
+    let purged = RecycleBinRepository::new(&state.db)
+        .purge(org_id, kind, id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to purge {} {}: {}", kind.as_str(), id, e);
+            AppError::internal("Failed to purge record")
+        })?;
+
+    if purged == 0 {
+        return Err(AppError::not_found("Deleted record not found"));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
@@ -11,7 +11,8 @@ use serde::Deserialize;
 
 use crate::{
     db::{ActivityHeatmapCell, ReportDailySummary, ReportHourlySummary, ReportSummaryRepository},
-    models::{Report, ResourceEvent},
+    middleware::AuthUser,
+    models::{Report, ReportDrillDown, ReportLog, ResourceEvent},
     services::puppetdb::{QueryBuilder, QueryParams},
     utils::error::{AppError, AppResult},
     AppState,
@@ -26,6 +27,7 @@ pub fn routes() -> Router<AppState> {
         .route("/activity-heatmap", get(get_activity_heatmap))
         .route("/{hash}", get(get_report))
         .route("/{hash}/events", get(get_report_events))
+        .route("/{hash}/drilldown", get(get_report_drilldown))
 }
 
 /// Query parameters for the daily summary endpoint.
@@ -116,10 +118,11 @@ pub struct ReportsQuery {
 /// - `order_dir`: Order direction (asc/desc, default: desc)
 async fn query_reports(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Query(query): Query<ReportsQuery>,
 ) -> AppResult<Json<Vec<Report>>> {
     // If PuppetDB is not configured, return empty list (stub behavior expected by tests)
-    let Some(puppetdb) = state.puppetdb.as_ref() else {
+    let Some(puppetdb) = state.puppetdb_for(auth_user.organization_id).await else {
         return Ok(Json(vec![]));
     };
 
@@ -293,11 +296,12 @@ fn format_hour(ts: chrono::DateTime<chrono::Utc>) -> String {
 /// GET /api/v1/reports/:hash
 async fn get_report(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(hash): Path<String>,
 ) -> AppResult<Json<Report>> {
     let puppetdb = state
-        .puppetdb
-        .as_ref()
+        .puppetdb_for(auth_user.organization_id)
+        .await
         .ok_or_else(|| AppError::ServiceUnavailable("PuppetDB is not configured".to_string()))?;
 
     let report = puppetdb
@@ -328,12 +332,13 @@ pub struct ReportEventsQuery {
 /// - `type`: Filter by resource type
 async fn get_report_events(
     State(state): State<AppState>,
+    auth_user: AuthUser,
     Path(hash): Path<String>,
     Query(query): Query<ReportEventsQuery>,
 ) -> AppResult<Json<Vec<ResourceEvent>>> {
     let puppetdb = state
-        .puppetdb
-        .as_ref()
+        .puppetdb_for(auth_user.organization_id)
+        .await
         .ok_or_else(|| AppError::ServiceUnavailable("PuppetDB is not configured".to_string()))?;
 
     // Build query for events - filter by report hash
@@ -355,3 +360,218 @@ async fn get_report_events(
 
     Ok(Json(events))
 }
+
+/// Query parameters for the report drill-down
+#[derive(Debug, Default, Deserialize)]
+pub struct ReportDrillDownQuery {
+    /// Comma-separated event statuses to keep (success, failure, noop, skipped)
+    pub status: Option<String>,
+    /// Resource type to keep (case-insensitive)
+    #[serde(rename = "type")]
+    pub resource_type: Option<String>,
+    /// Comma-separated log levels to keep (e.g. "err,warning")
+    pub level: Option<String>,
+}
+
+/// Get the resource events and log lines of a report
+///
+/// GET /api/v1/reports/:hash/drilldown
+///
+/// Reads the report's `events` and `logs` children from PuppetDB and filters
+/// them server-side, so the UI can show what failed without loading the
+/// whole report.
+///
+/// Query parameters:
+/// - `status`: Comma-separated event statuses (success, failure, noop, skipped)
+/// - `type`: Resource type
+/// - `level`: Comma-separated log levels
+///
+/// When `status` or `type` is set, only the log lines of the matching
+/// resources are kept, plus error lines that belong to no resource (such as
+/// catalog retrieval failures).
+async fn get_report_drilldown(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(hash): Path<String>,
+    Query(query): Query<ReportDrillDownQuery>,
+) -> AppResult<Json<ReportDrillDown>> {
+    let puppetdb = state
+        .puppetdb_for(auth_user.organization_id)
+        .await
+        .ok_or_else(|| AppError::ServiceUnavailable("PuppetDB is not configured".to_string()))?;
+
+    let report = puppetdb
+        .get_report(&hash)
+        .await
+        .map_err(|e| AppError::Internal(format!("Failed to fetch report: {}", e)))?
+        .ok_or_else(|| AppError::NotFound(format!("Report '{}' not found", hash)))?;
+
+    let (events, logs) = tokio::try_join!(
+        puppetdb.get_report_resource_events(&hash),
+        puppetdb.get_report_logs(&hash)
+    )
+    .map_err(|e| AppError::Internal(format!("Failed to fetch report details: {}", e)))?;
+
+    let total_events = events.len();
+    let total_logs = logs.len();
+    let (events, logs) = filter_drilldown(events, logs, &query);
+
+    Ok(Json(ReportDrillDown {
+        hash: report.hash,
+        certname: report.certname,
+        status: report.status,
+        events,
+        logs,
+        total_events,
+        total_logs,
+    }))
+}
+
+fn split_filter(value: Option<&str>) -> Option<Vec<String>> {
+    let values: Vec<String> = value?
+        .split(',')
+        .map(|v| v.trim().to_lowercase())
+        .filter(|v| !v.is_empty())
+        .collect();
+    (!values.is_empty()).then_some(values)
+}
+
+/// Apply the drill-down filters to a report's events and logs
+fn filter_drilldown(
+    events: Vec<ResourceEvent>,
+    logs: Vec<ReportLog>,
+    query: &ReportDrillDownQuery,
+) -> (Vec<ResourceEvent>, Vec<ReportLog>) {
+    let statuses = split_filter(query.status.as_deref());
+    let resource_type = query
+        .resource_type
+        .as_deref()
+        .map(str::trim)
+        .filter(|t| !t.is_empty());
+    let levels = split_filter(query.level.as_deref());
+
+    let events: Vec<ResourceEvent> = events
+        .into_iter()
+        .filter(|e| {
+            statuses
+                .as_ref()
+                .is_none_or(|s| s.iter().any(|s| s == e.status.as_str()))
+        })
+        .filter(|e| resource_type.is_none_or(|t| e.resource_type.eq_ignore_ascii_case(t)))
+        .collect();
+
+    // Log sources name resources as "Type[title]" inside a containment path,
+    // e.g. "/Stage[main]/Motd/File[/etc/motd]/content"
+    let resources: Option<Vec<String>> =
+        (statuses.is_some() || resource_type.is_some()).then(|| {
+            events
+                .iter()
+                .map(|e| format!("{}[{}]", e.resource_type, e.resource_title))
+                .collect()
+        });
+
+    let logs = logs
+        .into_iter()
+        .filter(|l| {
+            levels
+                .as_ref()
+                .is_none_or(|lv| lv.iter().any(|lv| lv.eq_ignore_ascii_case(&l.level)))
+        })
+        .filter(|l| {
+            let Some(resources) = &resources else {
+                return true;
+            };
+            match l.source.as_deref() {
+                Some(source) if source.starts_with('/') => {
+                    resources.iter().any(|r| source.contains(r.as_str()))
+                }
+                _ => l.is_error(),
+            }
+        })
+        .collect();
+
+    (events, logs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::EventStatus;
+
+    fn event(resource_type: &str, title: &str, status: EventStatus) -> ResourceEvent {
+        serde_json::from_value(serde_json::json!({
+            "resource_type": resource_type,
+            "resource_title": title,
+            "status": status,
+        }))
+        .unwrap()
+    }
+
+    fn log(level: &str, source: &str) -> ReportLog {
+        serde_json::from_value(serde_json::json!({
+            "level": level,
+            "message": "message",
+            "source": source,
+        }))
+        .unwrap()
+    }
+
+    fn fixture() -> (Vec<ResourceEvent>, Vec<ReportLog>) {
+        (
+            vec![
+                event("File", "/etc/motd", EventStatus::Failure),
+                event("Service", "sshd", EventStatus::Success),
+                event("Exec", "reload", EventStatus::Skipped),
+            ],
+            vec![
+                log("err", "/Stage[main]/Motd/File[/etc/motd]/ensure"),
+                log("notice", "/Stage[main]/Ssh/Service[sshd]/ensure"),
+                log("err", "Puppet"),
+                log("notice", "Puppet"),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_filter_drilldown_no_filters() {
+        let (events, logs) = fixture();
+        let (events, logs) = filter_drilldown(events, logs, &ReportDrillDownQuery::default());
+        assert_eq!(events.len(), 3);
+        assert_eq!(logs.len(), 4);
+    }
+
+    #[test]
+    fn test_filter_drilldown_by_status() {
+        let (events, logs) = fixture();
+        let query = ReportDrillDownQuery {
+            status: Some("failure, skipped".to_string()),
+            ..Default::default()
+        };
+        let (events, logs) = filter_drilldown(events, logs, &query);
+        assert_eq!(events.len(), 2);
+        // The failed resource's line and the run-level error
+        let sources: Vec<_> = logs.iter().filter_map(|l| l.source.as_deref()).collect();
+        assert_eq!(
+            sources,
+            vec!["/Stage[main]/Motd/File[/etc/motd]/ensure", "Puppet"]
+        );
+    }
+
+    #[test]
+    fn test_filter_drilldown_by_type_and_level() {
+        let (events, logs) = fixture();
+        let query = ReportDrillDownQuery {
+            resource_type: Some("service".to_string()),
+            level: Some("notice".to_string()),
+            ..Default::default()
+        };
+        let (events, logs) = filter_drilldown(events, logs, &query);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].resource_title, "sshd");
+        assert_eq!(logs.len(), 1);
+        assert_eq!(
+            logs[0].source.as_deref(),
+            Some("/Stage[main]/Ssh/Service[sshd]/ensure")
+        );
+    }
+}
@@ -12,11 +12,12 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
+    middleware::AuditChange,
     models::{
         Action, CreatePermissionRequest, CreateRoleRequest, Permission, PermissionConstraint,
-        Resource, Role, Scope,
+        Resource, Role, RoleEffectivePermissions, Scope, SetRoleParentsRequest,
     },
-    utils::error::ErrorResponse,
+    utils::error::ApiError,
     AppState,
 };
 
@@ -25,6 +26,11 @@ pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/", get(list_roles).post(create_role))
         .route("/{id}", get(get_role).put(update_role).delete(delete_role))
+        .route("/{id}/parents", get(get_role_parents).put(set_role_parents))
+        .route(
+            "/{id}/effective-permissions",
+            get(get_role_effective_permissions),
+        )
         .route(
             "/{id}/permissions",
             get(get_role_permissions)
@@ -50,16 +56,14 @@ pub fn routes() -> Router<AppState> {
 /// GET /api/v1/roles
 async fn list_roles(
     State(state): State<AppState>,
-) -> Result<Json<Vec<Role>>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<Vec<Role>>, (StatusCode, Json<ApiError>)> {
     let roles = state.rbac_db.get_all_roles().await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "internal_error".to_string(),
-                message: format!("Failed to fetch roles: {}", e),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "internal_error",
+                format!("Failed to fetch roles: {}", e),
+            )),
         )
     })?;
 
@@ -72,29 +76,22 @@ async fn list_roles(
 async fn create_role(
     State(state): State<AppState>,
     Json(payload): Json<CreateRoleRequest>,
-) -> Result<(StatusCode, Json<Role>), (StatusCode, Json<ErrorResponse>)> {
+) -> Result<(StatusCode, Json<Role>), (StatusCode, Json<ApiError>)> {
     // Validate input
     if payload.name.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "validation_error".to_string(),
-                message: "Role name is required".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new("validation_error", "Role name is required")),
         ));
     }
 
     if payload.display_name.is_empty() {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "validation_error".to_string(),
-                message: "Role display name is required".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "validation_error",
+                "Role display name is required",
+            )),
         ));
     }
 
@@ -103,22 +100,20 @@ async fn create_role(
         if message.contains("already exists") {
             (
                 StatusCode::CONFLICT,
-                Json(ErrorResponse {
-                    error: "conflict".to_string(),
-                    message,
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("conflict", message)),
+            )
+        } else if is_hierarchy_error(&message) {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new("validation_error", message)),
             )
         } else {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "internal_error".to_string(),
-                    message: format!("Failed to create role: {}", e),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to create role: {}", e),
+                )),
             )
         }
     })?;
@@ -132,7 +127,7 @@ async fn create_role(
 async fn get_role(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<Json<Role>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<Role>, (StatusCode, Json<ApiError>)> {
     let role = state
         .rbac_db
         .get_role(&id)
@@ -140,23 +135,16 @@ async fn get_role(
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "internal_error".to_string(),
-                    message: format!("Failed to fetch role: {}", e),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to fetch role: {}", e),
+                )),
             )
         })?
         .ok_or_else(|| {
             (
                 StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "not_found".to_string(),
-                    message: "Role not found".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("not_found", "Role not found")),
             )
         })?;
 
@@ -170,53 +158,47 @@ async fn update_role(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
     Json(payload): Json<CreateRoleRequest>,
-) -> Result<Json<Role>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<(AuditChange, Json<Role>), (StatusCode, Json<ApiError>)> {
+    // A missing role is reported by update_role below
+    let before = state.rbac_db.get_role(&id).await.ok().flatten();
     let role = state.rbac_db.update_role(&id, payload).await.map_err(|e| {
         let message = e.to_string();
-        if message.contains("not found") {
+        if is_hierarchy_error(&message) {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new("validation_error", message)),
+            )
+        } else if message.contains("not found") {
             (
                 StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "not_found".to_string(),
-                    message: "Role not found".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("not_found", "Role not found")),
             )
         } else if message.contains("Cannot modify system") {
             (
                 StatusCode::FORBIDDEN,
-                Json(ErrorResponse {
-                    error: "forbidden".to_string(),
-                    message,
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("forbidden", message)),
             )
         } else if message.contains("already exists") {
             (
                 StatusCode::CONFLICT,
-                Json(ErrorResponse {
-                    error: "conflict".to_string(),
-                    message,
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("conflict", message)),
             )
         } else {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "internal_error".to_string(),
-                    message: format!("Failed to update role: {}", e),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to update role: {}", e),
+                )),
             )
         }
     })?;
 
-    Ok(Json(role))
+    let change = match &before {
+        Some(before) => AuditChange::new(before, &role),
+        None => AuditChange::created(&role),
+    };
+    Ok((change, Json(role)))
 }
 
 /// Delete a role
@@ -225,38 +207,26 @@ async fn update_role(
 async fn delete_role(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<StatusCode, (StatusCode, Json<ApiError>)> {
     state.rbac_db.delete_role(&id).await.map_err(|e| {
         let message = e.to_string();
         if message.contains("not found") {
             (
                 StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "not_found".to_string(),
-                    message: "Role not found".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("not_found", "Role not found")),
             )
         } else if message.contains("Cannot delete system") {
             (
                 StatusCode::FORBIDDEN,
-                Json(ErrorResponse {
-                    error: "forbidden".to_string(),
-                    message,
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("forbidden", message)),
             )
         } else {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "internal_error".to_string(),
-                    message: format!("Failed to delete role: {}", e),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to delete role: {}", e),
+                )),
             )
         }
     })?;
@@ -264,13 +234,148 @@ async fn delete_role(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// Whether a role service error is about an invalid parent role
+fn is_hierarchy_error(message: &str) -> bool {
+    message.starts_with("Parent role") || message.starts_with("Role hierarchy cycle")
+}
+
+/// Get the parent roles of a role
+///
+/// GET /api/v1/roles/:id/parents
+async fn get_role_parents(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<Role>>, (StatusCode, Json<ApiError>)> {
+    let internal_error = |e: anyhow::Error| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new(
+                "internal_error",
+                format!("Failed to fetch role: {}", e),
+            )),
+        )
+    };
+
+    let role = state
+        .rbac_db
+        .get_role(&id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                Json(ApiError::new("not_found", "Role not found")),
+            )
+        })?;
+
+    let mut parents = Vec::new();
+    for parent_id in &role.parent_ids {
+        if let Some(parent) = state
+            .rbac_db
+            .get_role(parent_id)
+            .await
+            .map_err(internal_error)?
+        {
+            parents.push(parent);
+        }
+    }
+
+    Ok(Json(parents))
+}
+
+/// Replace the parent roles of a role
+///
+/// PUT /api/v1/roles/:id/parents
+///
+/// The role inherits the permissions of every parent. Parents that would make
+/// the role inherit from itself are rejected.
+async fn set_role_parents(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<SetRoleParentsRequest>,
+) -> Result<Json<Role>, (StatusCode, Json<ApiError>)> {
+    let role = state
+        .rbac_db
+        .set_role_parents(&id, &payload.parent_ids)
+        .await
+        .map_err(|e| {
+            let message = e.to_string();
+            if is_hierarchy_error(&message) {
+                (
+                    StatusCode::BAD_REQUEST,
+                    Json(ApiError::new("validation_error", message)),
+                )
+            } else if message.contains("not found") {
+                (
+                    StatusCode::NOT_FOUND,
+                    Json(ApiError::new("not_found", "Role not found")),
+                )
+            } else if message.contains("Cannot modify system") {
+                (
+                    StatusCode::FORBIDDEN,
+                    Json(ApiError::new("forbidden", message)),
+                )
+            } else {
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiError::new(
+                        "internal_error",
+                        format!("Failed to update role parents: {}", e),
+                    )),
+                )
+            }
+        })?;
+
+    Ok(Json(role))
+}
+
+/// Get the permissions a role grants, including inherited ones
+///
+/// GET /api/v1/roles/:id/effective-permissions
+async fn get_role_effective_permissions(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<RoleEffectivePermissions>, (StatusCode, Json<ApiError>)> {
+    let exists = state.rbac_db.get_role(&id).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new(
+                "internal_error",
+                format!("Failed to fetch role: {}", e),
+            )),
+        )
+    })?;
+    if exists.is_none() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiError::new("not_found", "Role not found")),
+        ));
+    }
+
+    let effective = state
+        .rbac_db
+        .get_role_effective_permissions(&id)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to compute effective permissions: {}", e),
+                )),
+            )
+        })?;
+
+    Ok(Json(effective))
+}
+
 /// Get permissions for a role
 ///
 /// GET /api/v1/roles/:id/permissions
 async fn get_role_permissions(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<Json<Vec<Permission>>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<Vec<Permission>>, (StatusCode, Json<ApiError>)> {
     let role = state
         .rbac_db
         .get_role(&id)
@@ -278,23 +383,16 @@ async fn get_role_permissions(
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "internal_error".to_string(),
-                    message: format!("Failed to fetch role: {}", e),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to fetch role: {}", e),
+                )),
             )
         })?
         .ok_or_else(|| {
             (
                 StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "not_found".to_string(),
-                    message: "Role not found".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("not_found", "Role not found")),
             )
         })?;
 
@@ -308,7 +406,15 @@ async fn update_role_permissions(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
     Json(payload): Json<Vec<CreatePermissionRequest>>,
-) -> Result<Json<Vec<Permission>>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<(AuditChange, Json<Vec<Permission>>), (StatusCode, Json<ApiError>)> {
+    let before = state
+        .rbac_db
+        .get_role(&id)
+        .await
+        .ok()
+        .flatten()
+        .map(|role| role.permissions)
+        .unwrap_or_default();
     let permissions = state
         .rbac_db
         .set_role_permissions(&id, payload)
@@ -318,37 +424,25 @@ async fn update_role_permissions(
             if message.contains("not found") {
                 (
                     StatusCode::NOT_FOUND,
-                    Json(ErrorResponse {
-                        error: "not_found".to_string(),
-                        message: "Role not found".to_string(),
-                        details: None,
-                        code: None,
-                    }),
+                    Json(ApiError::new("not_found", "Role not found")),
                 )
             } else if message.contains("system role") {
                 (
                     StatusCode::FORBIDDEN,
-                    Json(ErrorResponse {
-                        error: "forbidden".to_string(),
-                        message,
-                        details: None,
-                        code: None,
-                    }),
+                    Json(ApiError::new("forbidden", message)),
                 )
             } else {
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse {
-                        error: "internal_error".to_string(),
-                        message: format!("Failed to update permissions: {}", e),
-                        details: None,
-                        code: None,
-                    }),
+                    Json(ApiError::new(
+                        "internal_error",
+                        format!("Failed to update permissions: {}", e),
+                    )),
                 )
             }
         })?;
 
-    Ok(Json(permissions))
+    Ok((AuditChange::new(&before, &permissions), Json(permissions)))
 }
 
 /// Add a single permission to a role
@@ -358,7 +452,7 @@ async fn add_permission_to_role(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
     Json(payload): Json<CreatePermissionRequest>,
-) -> Result<Json<Role>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<Role>, (StatusCode, Json<ApiError>)> {
     // Get the current role
     let role = state
         .rbac_db
@@ -367,23 +461,16 @@ async fn add_permission_to_role(
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "internal_error".to_string(),
-                    message: format!("Failed to fetch role: {}", e),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to fetch role: {}", e),
+                )),
             )
         })?
         .ok_or_else(|| {
             (
                 StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "not_found".to_string(),
-                    message: "Role not found".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("not_found", "Role not found")),
             )
         })?;
 
@@ -391,12 +478,10 @@ async fn add_permission_to_role(
     if role.is_system {
         return Err((
             StatusCode::FORBIDDEN,
-            Json(ErrorResponse {
-                error: "forbidden".to_string(),
-                message: "Cannot modify permissions of system roles".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "forbidden",
+                "Cannot modify permissions of system roles",
+            )),
         ));
     }
 
@@ -420,12 +505,10 @@ async fn add_permission_to_role(
     if already_exists {
         return Err((
             StatusCode::CONFLICT,
-            Json(ErrorResponse {
-                error: "conflict".to_string(),
-                message: "Permission already exists for this role".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "conflict",
+                "Permission already exists for this role",
+            )),
         ));
     }
 
@@ -439,12 +522,10 @@ async fn add_permission_to_role(
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "internal_error".to_string(),
-                    message: format!("Failed to add permission: {}", e),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to add permission: {}", e),
+                )),
             )
         })?;
 
@@ -456,23 +537,16 @@ async fn add_permission_to_role(
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "internal_error".to_string(),
-                    message: format!("Failed to fetch updated role: {}", e),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to fetch updated role: {}", e),
+                )),
             )
         })?
         .ok_or_else(|| {
             (
                 StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "not_found".to_string(),
-                    message: "Role not found after update".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("not_found", "Role not found after update")),
             )
         })?;
 
@@ -485,7 +559,7 @@ async fn add_permission_to_role(
 async fn remove_permission_from_role(
     State(state): State<AppState>,
     Path((role_id, permission_id)): Path<(Uuid, Uuid)>,
-) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<StatusCode, (StatusCode, Json<ApiError>)> {
     // Get the current role
     let role = state
         .rbac_db
@@ -494,23 +568,16 @@ async fn remove_permission_from_role(
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "internal_error".to_string(),
-                    message: format!("Failed to fetch role: {}", e),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to fetch role: {}", e),
+                )),
             )
         })?
         .ok_or_else(|| {
             (
                 StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "not_found".to_string(),
-                    message: "Role not found".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("not_found", "Role not found")),
             )
         })?;
 
@@ -518,12 +585,10 @@ async fn remove_permission_from_role(
     if role.is_system {
         return Err((
             StatusCode::FORBIDDEN,
-            Json(ErrorResponse {
-                error: "forbidden".to_string(),
-                message: "Cannot modify permissions of system roles".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "forbidden",
+                "Cannot modify permissions of system roles",
+            )),
         ));
     }
 
@@ -544,12 +609,10 @@ async fn remove_permission_from_role(
     if new_permissions.len() == role.permissions.len() {
         return Err((
             StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: "not_found".to_string(),
-                message: "Permission not found in this role".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "not_found",
+                "Permission not found in this role",
+            )),
         ));
     }
 
@@ -561,12 +624,10 @@ async fn remove_permission_from_role(
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "internal_error".to_string(),
-                    message: format!("Failed to remove permission: {}", e),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to remove permission: {}", e),
+                )),
             )
         })?;
 
@@ -603,7 +664,7 @@ pub struct GroupPermissionInfo {
 async fn get_group_permissions(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
-) -> Result<Json<Vec<GroupPermissionInfo>>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<Vec<GroupPermissionInfo>>, (StatusCode, Json<ApiError>)> {
     let role = state
         .rbac_db
         .get_role(&id)
@@ -611,23 +672,16 @@ async fn get_group_permissions(
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "internal_error".to_string(),
-                    message: format!("Failed to fetch role: {}", e),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to fetch role: {}", e),
+                )),
             )
         })?
         .ok_or_else(|| {
             (
                 StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "not_found".to_string(),
-                    message: "Role not found".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("not_found", "Role not found")),
             )
         })?;
 
@@ -681,7 +735,7 @@ async fn add_group_permission(
     State(state): State<AppState>,
     Path(id): Path<Uuid>,
     Json(payload): Json<AddGroupPermissionRequest>,
-) -> Result<(StatusCode, Json<Permission>), (StatusCode, Json<ErrorResponse>)> {
+) -> Result<(StatusCode, Json<Permission>), (StatusCode, Json<ApiError>)> {
     // Verify the role exists
     let role = state
         .rbac_db
@@ -690,23 +744,16 @@ async fn add_group_permission(
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "internal_error".to_string(),
-                    message: format!("Failed to fetch role: {}", e),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to fetch role: {}", e),
+                )),
             )
         })?
         .ok_or_else(|| {
             (
                 StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "not_found".to_string(),
-                    message: "Role not found".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("not_found", "Role not found")),
             )
         })?;
 
@@ -714,12 +761,10 @@ async fn add_group_permission(
     if role.is_system {
         return Err((
             StatusCode::FORBIDDEN,
-            Json(ErrorResponse {
-                error: "forbidden".to_string(),
-                message: "Cannot modify permissions of system roles".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "forbidden",
+                "Cannot modify permissions of system roles",
+            )),
         ));
     }
 
@@ -741,22 +786,18 @@ async fn add_group_permission(
             if message.contains("already exists") {
                 (
                     StatusCode::CONFLICT,
-                    Json(ErrorResponse {
-                        error: "conflict".to_string(),
-                        message: "This group permission already exists for this role".to_string(),
-                        details: None,
-                        code: None,
-                    }),
+                    Json(ApiError::new(
+                        "conflict",
+                        "This group permission already exists for this role",
+                    )),
                 )
             } else {
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse {
-                        error: "internal_error".to_string(),
-                        message: format!("Failed to add permission: {}", e),
-                        details: None,
-                        code: None,
-                    }),
+                    Json(ApiError::new(
+                        "internal_error",
+                        format!("Failed to add permission: {}", e),
+                    )),
                 )
             }
         })?;
@@ -770,7 +811,7 @@ async fn add_group_permission(
 async fn remove_group_permission(
     State(state): State<AppState>,
     Path((role_id, group_id)): Path<(Uuid, Uuid)>,
-) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<StatusCode, (StatusCode, Json<ApiError>)> {
     // Verify the role exists
     let role = state
         .rbac_db
@@ -779,23 +820,16 @@ async fn remove_group_permission(
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "internal_error".to_string(),
-                    message: format!("Failed to fetch role: {}", e),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to fetch role: {}", e),
+                )),
             )
         })?
         .ok_or_else(|| {
             (
                 StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "not_found".to_string(),
-                    message: "Role not found".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("not_found", "Role not found")),
             )
         })?;
 
@@ -803,12 +837,10 @@ async fn remove_group_permission(
     if role.is_system {
         return Err((
             StatusCode::FORBIDDEN,
-            Json(ErrorResponse {
-                error: "forbidden".to_string(),
-                message: "Cannot modify permissions of system roles".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "forbidden",
+                "Cannot modify permissions of system roles",
+            )),
         ));
     }
 
@@ -831,12 +863,10 @@ async fn remove_group_permission(
     if permissions_to_remove.is_empty() {
         return Err((
             StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: "not_found".to_string(),
-                message: "No group permission found for this group".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "not_found",
+                "No group permission found for this group",
+            )),
         ));
     }
 
@@ -849,12 +879,10 @@ async fn remove_group_permission(
             .map_err(|e| {
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse {
-                        error: "internal_error".to_string(),
-                        message: format!("Failed to remove permission: {}", e),
-                        details: None,
-                        code: None,
-                    }),
+                    Json(ApiError::new(
+                        "internal_error",
+                        format!("Failed to remove permission: {}", e),
+                    )),
                 )
             })?;
     }
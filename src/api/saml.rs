@@ -4,7 +4,7 @@
 
 use axum::{
     extract::{Query, State},
-    http::{header, StatusCode},
+    http::{header, HeaderMap, StatusCode},
     response::{IntoResponse, Redirect, Response},
     routing::{get, post},
     Form, Router,
@@ -14,9 +14,14 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
-    middleware::auth::{create_access_token, create_auth_session, create_refresh_token},
-    services::{AuthService, SamlService},
-    utils::error::ErrorResponse,
+    api::auth::issue_refresh_token,
+    middleware::{
+        auth::{create_access_token_until, create_auth_session, SessionClient},
+        session_cookie,
+    },
+    models::LoginMethod,
+    services::{elevation, login_history, AuthService, SamlService},
+    utils::error::ApiError,
     AppState,
 };
 
@@ -57,7 +62,7 @@ struct SamlErrorPage {
 }
 
 impl SamlErrorPage {
-    fn to_html(&self) -> String {
+    fn to_html(&self, login_url: &str) -> String {
         format!(
             r#"<!DOCTYPE html>
 <html>
@@ -79,12 +84,13 @@ impl SamlErrorPage {
     <div class="error-box">
         <h1>Authentication Error</h1>
         <p>{}: {}</p>
-        <a href="/login">Return to Login</a>
+        <a href="{}">Return to Login</a>
     </div>
 </body>
 </html>"#,
             html_escape(&self.error),
-            html_escape(&self.message)
+            html_escape(&self.message),
+            html_escape(login_url)
         )
     }
 }
@@ -115,12 +121,7 @@ async fn saml_metadata(State(state): State<AppState>) -> Response {
             tracing::warn!("SAML metadata requested but SAML is disabled");
             return (
                 StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "not_found".to_string(),
-                    message: "SAML is not enabled".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("not_found", "SAML is not enabled")),
             )
                 .into_response();
         }
@@ -128,12 +129,7 @@ async fn saml_metadata(State(state): State<AppState>) -> Response {
             tracing::warn!("SAML metadata requested but no SAML configuration exists");
             return (
                 StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "not_found".to_string(),
-                    message: "SAML is not enabled".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("not_found", "SAML is not enabled")),
             )
                 .into_response();
         }
@@ -176,12 +172,7 @@ async fn saml_login(
             tracing::error!("SAML login attempted but SAML is disabled in configuration");
             return (
                 StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "not_found".to_string(),
-                    message: "SAML is not enabled".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("not_found", "SAML is not enabled")),
             )
                 .into_response();
         }
@@ -189,12 +180,7 @@ async fn saml_login(
             tracing::error!("SAML login attempted but no SAML configuration exists");
             return (
                 StatusCode::NOT_FOUND,
-                Json(ErrorResponse {
-                    error: "not_found".to_string(),
-                    message: "SAML is not enabled".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("not_found", "SAML is not enabled")),
             )
                 .into_response();
         }
@@ -218,12 +204,10 @@ async fn saml_login(
         );
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "saml_error".to_string(),
-                message: "Failed to initialize SAML service".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(
+                ApiError::new("internal_error", "Failed to initialize SAML service")
+                    .with_code("saml_error"),
+            ),
         )
             .into_response();
     }
@@ -250,12 +234,10 @@ async fn saml_login(
             );
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "saml_error".to_string(),
-                    message: "Failed to initiate SAML login".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(
+                    ApiError::new("internal_error", "Failed to initiate SAML login")
+                        .with_code("saml_error"),
+                ),
             )
                 .into_response()
         }
@@ -267,8 +249,14 @@ async fn saml_login(
 /// POST /api/v1/auth/saml/acs
 ///
 /// Receives the SAML Response from the IdP, validates it, and creates a session.
-async fn saml_acs(State(state): State<AppState>, Form(form): Form<SamlAcsForm>) -> Response {
+async fn saml_acs(
+    State(state): State<AppState>,
+    client: SessionClient,
+    headers: HeaderMap,
+    Form(form): Form<SamlAcsForm>,
+) -> Response {
     tracing::info!("=== SAML ACS: Received IdP Response ===");
+    let login_url = state.config.server.prefixed_path("/login");
     tracing::debug!(
         "SAML Response length: {} bytes, RelayState: {:?}",
         form.saml_response.len(),
@@ -293,7 +281,7 @@ async fn saml_acs(State(state): State<AppState>, Form(form): Form<SamlAcsForm>)
             return (
                 StatusCode::NOT_FOUND,
                 [(header::CONTENT_TYPE, "text/html")],
-                error.to_html(),
+                error.to_html(&login_url),
             )
                 .into_response();
         }
@@ -306,7 +294,7 @@ async fn saml_acs(State(state): State<AppState>, Form(form): Form<SamlAcsForm>)
             return (
                 StatusCode::NOT_FOUND,
                 [(header::CONTENT_TYPE, "text/html")],
-                error.to_html(),
+                error.to_html(&login_url),
             )
                 .into_response();
         }
@@ -335,7 +323,7 @@ async fn saml_acs(State(state): State<AppState>, Form(form): Form<SamlAcsForm>)
             return (
                 StatusCode::BAD_REQUEST,
                 [(header::CONTENT_TYPE, "text/html")],
-                error.to_html(),
+                error.to_html(&login_url),
             )
                 .into_response();
         }
@@ -433,7 +421,7 @@ async fn saml_acs(State(state): State<AppState>, Form(form): Form<SamlAcsForm>)
             );
 
             // Check if user is allowed to use SAML
-            if !user.auth_provider.allows_saml() {
+            if !user.auth_provider.allows_saml() || user.is_service_account() {
                 tracing::warn!(
                     "SAML login denied for user '{}': auth_provider={} does not allow SAML. \
                     User must have auth_provider='saml' or 'both' to use SSO.",
@@ -447,7 +435,7 @@ async fn saml_acs(State(state): State<AppState>, Form(form): Form<SamlAcsForm>)
                 return (
                     StatusCode::FORBIDDEN,
                     [(header::CONTENT_TYPE, "text/html")],
-                    error.to_html(),
+                    error.to_html(&login_url),
                 )
                     .into_response();
             }
@@ -474,7 +462,7 @@ async fn saml_acs(State(state): State<AppState>, Form(form): Form<SamlAcsForm>)
                 return (
                     StatusCode::FORBIDDEN,
                     [(header::CONTENT_TYPE, "text/html")],
-                    error.to_html(),
+                    error.to_html(&login_url),
                 )
                     .into_response();
             }
@@ -493,7 +481,7 @@ async fn saml_acs(State(state): State<AppState>, Form(form): Form<SamlAcsForm>)
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 [(header::CONTENT_TYPE, "text/html")],
-                error.to_html(),
+                error.to_html(&login_url),
             )
                 .into_response();
         }
@@ -536,11 +524,25 @@ async fn saml_acs(State(state): State<AppState>, Form(form): Form<SamlAcsForm>)
             vec![user.role.clone()]
         });
     tracing::debug!("User roles: {:?}", roles);
+    let grant = elevation::token_grant(
+        &state.db,
+        user.id,
+        roles,
+        Utc::now() + Duration::hours(state.config.auth.token_expiry_hours as i64),
+    )
+    .await;
 
     let session_id = Uuid::new_v4();
     let session_expires_at =
         Utc::now() + Duration::days(state.config.auth.refresh_token_expiry_days as i64);
-    if let Err(e) = create_auth_session(&state.db, &session_id, &user.id, session_expires_at).await
+    if let Err(e) = create_auth_session(
+        &state.db,
+        &session_id,
+        &user.id,
+        session_expires_at,
+        &client,
+    )
+    .await
     {
         tracing::error!("Failed to create SAML auth session: {:?}", e);
         let error = SamlErrorPage {
@@ -550,22 +552,23 @@ async fn saml_acs(State(state): State<AppState>, Form(form): Form<SamlAcsForm>)
         return (
             StatusCode::INTERNAL_SERVER_ERROR,
             [(header::CONTENT_TYPE, "text/html")],
-            error.to_html(),
+            error.to_html(&login_url),
         )
             .into_response();
     }
 
     // Create JWT tokens
     tracing::debug!("Creating JWT access token...");
-    let access_token = match create_access_token(
+    let access_token = match create_access_token_until(
         &user.id,
         &user.organization_id,
         &session_id,
         &user.username,
         &user.email,
-        roles,
+        grant.roles.clone(),
         &state.config.auth.jwt_secret,
-        state.config.auth.token_expiry_hours,
+        &state.jwt_keys,
+        grant.expires_at,
     ) {
         Ok(token) => {
             tracing::debug!("Access token created successfully");
@@ -580,21 +583,14 @@ async fn saml_acs(State(state): State<AppState>, Form(form): Form<SamlAcsForm>)
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 [(header::CONTENT_TYPE, "text/html")],
-                error.to_html(),
+                error.to_html(&login_url),
             )
                 .into_response();
         }
     };
 
     tracing::debug!("Creating JWT refresh token...");
-    let refresh_token = match create_refresh_token(
-        &user.id,
-        &session_id,
-        &user.username,
-        &user.email,
-        &state.config.auth.jwt_secret,
-        state.config.auth.refresh_token_expiry_days,
-    ) {
+    let refresh_token = match issue_refresh_token(&state, &user, &session_id).await {
         Ok(token) => {
             tracing::debug!("Refresh token created successfully");
             token
@@ -608,12 +604,14 @@ async fn saml_acs(State(state): State<AppState>, Form(form): Form<SamlAcsForm>)
             return (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 [(header::CONTENT_TYPE, "text/html")],
-                error.to_html(),
+                error.to_html(&login_url),
             )
                 .into_response();
         }
     };
 
+    login_history::record_login(&state, &user, LoginMethod::Saml, &client, &headers, true).await;
+
     // Determine redirect URL from relay_state or form
     let redirect_url = relay_state
         .or(form.relay_state)
@@ -631,13 +629,29 @@ async fn saml_acs(State(state): State<AppState>, Form(form): Form<SamlAcsForm>)
     };
 
     // Build callback URL with tokens
-    // The frontend will extract these and store them
-    let callback_url = format!(
-        "/saml-callback?access_token={}&refresh_token={}&redirect={}",
-        urlencoding::encode(&access_token),
-        urlencoding::encode(&refresh_token),
-        urlencoding::encode(&safe_redirect)
+    // The frontend will extract these and store them. In cookie auth mode
+    // the tokens are set as cookies and left out of the URL.
+    let cookies = session_cookie::session_cookies(
+        &state.config,
+        &access_token,
+        grant.expires_in(Utc::now()),
+        &refresh_token,
     );
+    let callback_url = if state.config.auth.cookie_mode() {
+        format!(
+            "{}?redirect={}",
+            state.config.server.prefixed_path("/saml-callback"),
+            urlencoding::encode(&safe_redirect)
+        )
+    } else {
+        format!(
+            "{}?access_token={}&refresh_token={}&redirect={}",
+            state.config.server.prefixed_path("/saml-callback"),
+            urlencoding::encode(&access_token),
+            urlencoding::encode(&refresh_token),
+            urlencoding::encode(&safe_redirect)
+        )
+    };
 
     tracing::info!(
         "=== SAML Login Successful === user='{}', redirect='{}'",
@@ -647,7 +661,12 @@ async fn saml_acs(State(state): State<AppState>, Form(form): Form<SamlAcsForm>)
 
     // Use 303 See Other to force the browser to use GET for the redirect
     // (307 Temporary Redirect preserves the POST method, which causes 405 errors)
-    (StatusCode::SEE_OTHER, [(header::LOCATION, callback_url)]).into_response()
+    (
+        StatusCode::SEE_OTHER,
+        cookies,
+        [(header::LOCATION, callback_url)],
+    )
+        .into_response()
 }
 
 use axum::Json;
@@ -0,0 +1,191 @@
+//! Global search endpoints
+//!
+//! Searches nodes, groups, classes, reports, users and saved reports of the
+//! caller's organization through the full-text index kept by the cache sync
+//! job. Results of a type are only returned to users who may read it.
+
+use std::collections::HashMap;
+
+use axum::{
+    extract::{Query, State},
+    routing::{get, post},
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    db::SearchRepository,
+    middleware::AuthUser,
+    models::{Action, SearchKind, SearchResponse},
+    services::SearchIndexer,
+    utils::AppError,
+    AppState,
+};
+
+/// Results returned by default and at most
+const DEFAULT_LIMIT: u32 = 25;
+const MAX_LIMIT: u32 = 200;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(search))
+        .route("/reindex", post(reindex))
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct SearchQuery {
+    /// Free text; every term is matched as a prefix
+    #[serde(default)]
+    q: String,
+    /// Comma-separated result types (node, group, class, report, user,
+    /// saved_report); all readable types when omitted
+    types: Option<String>,
+    limit: Option<u32>,
+    organization_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct OrgQuery {
+    organization_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReindexResponse {
+    organization_id: Uuid,
+    documents: usize,
+}
+
+fn resolve_org(auth_user: &AuthUser, requested: Option<Uuid>) -> Result<Uuid, AppError> {
+    match requested {
+        Some(_) if !auth_user.is_super_admin() => Err(AppError::forbidden(
+            "organization_id can only be specified by super_admin",
+        )),
+        Some(org_id) => Ok(org_id),
+        None => Ok(auth_user.organization_id),
+    }
+}
+
+fn parse_kinds(types: Option<&str>) -> Result<Vec<SearchKind>, AppError> {
+    let Some(types) = types.filter(|t| !t.trim().is_empty()) else {
+        return Ok(SearchKind::all());
+    };
+
+    let mut kinds = Vec::new();
+    for name in types.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        let kind: SearchKind = name.parse().map_err(AppError::validation)?;
+        if !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+    }
+    Ok(kinds)
+}
+
+/// Keep the kinds whose RBAC resource the user may read
+async fn readable_kinds(
+    state: &AppState,
+    auth_user: &AuthUser,
+    kinds: Vec<SearchKind>,
+) -> Result<Vec<SearchKind>, AppError> {
+    if auth_user.is_super_admin() {
+        return Ok(kinds);
+    }
+
+    let mut allowed = HashMap::new();
+    let mut readable = Vec::new();
+    for kind in kinds {
+        let resource = kind.resource();
+        if !allowed.contains_key(&resource) {
+            let check = state
+                .rbac_db
+                .check_permission(&auth_user.user_id(), resource, Action::Read, None, None)
+                .await
+                .map_err(|e| AppError::internal(format!("Permission check failed: {}", e)))?;
+            allowed.insert(resource, check.allowed);
+        }
+        if allowed[&resource] {
+            readable.push(kind);
+        }
+    }
+    Ok(readable)
+}
+
+/// Search the organization's nodes, groups, classes, reports, users and
+/// saved reports
+async fn search(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<SearchQuery>,
+) -> Result<Json<SearchResponse>, AppError> {
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let text = query.q.trim();
+    if text.is_empty() {
+        return Err(AppError::validation("Query parameter 'q' is required"));
+    }
+    let kinds = parse_kinds(query.types.as_deref())?;
+    let kinds = readable_kinds(&state, &auth_user, kinds).await?;
+
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let results = SearchRepository::new(&state.db)
+        .search(org_id, text, &kinds, auth_user.user_id(), limit)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to search: {}", e);
+            AppError::internal("Failed to search")
+        })?;
+
+    Ok(Json(SearchResponse {
+        query: text.to_string(),
+        results,
+    }))
+}
+
+/// Rebuild the organization's search index now instead of waiting for the
+/// next sync
+async fn reindex(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+) -> Result<Json<ReindexResponse>, AppError> {
+    if !auth_user.is_super_admin() && !auth_user.roles.iter().any(|r| r == "admin") {
+        return Err(AppError::forbidden(
+            "Admin role required to rebuild the search index",
+        ));
+    }
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+
+    let indexer = SearchIndexer::new(
+        state.db.clone(),
+        state.puppetdb.clone(),
+        state.puppetdb_tenants.clone(),
+    );
+    let documents = indexer.rebuild(org_id).await.map_err(|e| {
+        tracing::error!("Failed to rebuild search index: {}", e);
+        AppError::internal("Failed to rebuild search index")
+    })?;
+
+    Ok(Json(ReindexResponse {
+        organization_id: org_id,
+        documents,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_kinds_defaults_to_all() {
+        assert_eq!(parse_kinds(None).unwrap(), SearchKind::all());
+        assert_eq!(parse_kinds(Some(" ")).unwrap(), SearchKind::all());
+    }
+
+    #[test]
+    fn test_parse_kinds_dedups_and_rejects_unknown() {
+        assert_eq!(
+            parse_kinds(Some("node, group,node")).unwrap(),
+            vec![SearchKind::Node, SearchKind::Group]
+        );
+        assert!(parse_kinds(Some("node,fact")).is_err());
+    }
+}
@@ -0,0 +1,235 @@
+//! Login session endpoints
+//!
+//! Users list their active sessions with the address and client they logged
+//! in from, and sign out individual sessions or every other session. Admins
+//! (the `admin` action on users) do the same for users of their organization,
+//! e.g. to cut off a stolen token. Revocations are written to the audit log
+//! and take effect on the token's next use.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{delete, get},
+    Json, Router,
+};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    db::AuthSessionRepository,
+    middleware::auth::{AuthUser, SESSION_IDLE_TIMEOUT_MINUTES},
+    models::{Action, AuthSession, Resource},
+    services::AuthService,
+    utils::AppError,
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_sessions).delete(revoke_all_sessions))
+        .route("/{id}", delete(revoke_session))
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct SessionQuery {
+    /// Another user's sessions (admins only); defaults to the caller
+    user_id: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize)]
+struct RevokeSessionsResponse {
+    revoked: u64,
+}
+
+/// Whether the user may manage other users' sessions
+async fn is_session_admin(state: &AppState, auth_user: &AuthUser) -> Result<bool, AppError> {
+    if auth_user.is_super_admin() {
+        return Ok(true);
+    }
+
+    let check = state
+        .rbac_db
+        .check_permission(
+            &auth_user.user_id(),
+            Resource::Users,
+            Action::Admin,
+            None,
+            None,
+        )
+        .await
+        .map_err(|e| AppError::internal(format!("Permission check failed: {}", e)))?;
+    Ok(check.allowed)
+}
+
+/// Resolve whose sessions a request is about and return that user's
+/// organization
+///
+/// The caller's own sessions are always accessible. Other users' sessions
+/// need session admin rights and, except for super admins, a user in the
+/// caller's organization.
+async fn resolve_target(
+    state: &AppState,
+    auth_user: &AuthUser,
+    user_id: Uuid,
+) -> Result<Uuid, AppError> {
+    if user_id == auth_user.user_id() {
+        return Ok(auth_user.organization_id);
+    }
+
+    if !is_session_admin(state, auth_user).await? {
+        return Err(AppError::forbidden(
+            "Admin permission on users required to manage other users' sessions",
+        ));
+    }
+
+    let auth_service = AuthService::new(state.db.clone());
+    let user = if auth_user.is_super_admin() {
+        auth_service.get_user_by_id(&user_id).await
+    } else {
+        auth_service
+            .get_user_by_id_in_org(auth_user.organization_id, &user_id)
+            .await
+    }
+    .map_err(|e| {
+        tracing::error!("Failed to fetch user for sessions: {}", e);
+        AppError::internal("Failed to fetch user")
+    })?
+    .ok_or_else(|| AppError::not_found("User not found"))?;
+
+    Ok(user.organization_id)
+}
+
+fn current_session_id(auth_user: &AuthUser) -> Option<Uuid> {
+    Uuid::parse_str(&auth_user.session_id).ok()
+}
+
+/// List active sessions
+///
+/// GET /api/v1/sessions
+async fn list_sessions(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<SessionQuery>,
+) -> Result<Json<Vec<AuthSession>>, AppError> {
+    let user_id = query.user_id.unwrap_or_else(|| auth_user.user_id());
+    resolve_target(&state, &auth_user, user_id).await?;
+
+    let now = Utc::now();
+    let idle_cutoff = now - Duration::minutes(SESSION_IDLE_TIMEOUT_MINUTES);
+    let mut sessions = AuthSessionRepository::new(&state.db)
+        .list_active_for_user(user_id, idle_cutoff, now)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list sessions: {}", e);
+            AppError::internal("Failed to list sessions")
+        })?;
+
+    let current = current_session_id(&auth_user);
+    for session in &mut sessions {
+        session.current = Some(session.id) == current;
+    }
+
+    Ok(Json(sessions))
+}
+
+/// Revoke one session
+///
+/// DELETE /api/v1/sessions/:id
+async fn revoke_session(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let repo = AuthSessionRepository::new(&state.db);
+    let session = repo
+        .get_by_id(id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get session: {}", e);
+            AppError::internal("Failed to revoke session")
+        })?
+        .ok_or_else(|| AppError::not_found("Session not found"))?;
+
+    // Hide sessions of users the caller cannot manage
+    let org_id = match resolve_target(&state, &auth_user, session.user_id).await {
+        Ok(org_id) => org_id,
+        Err(AppError::Forbidden(_)) => return Err(AppError::not_found("Session not found")),
+        Err(e) => return Err(e),
+    };
+
+    let revoked = repo.revoke(id).await.map_err(|e| {
+        tracing::error!("Failed to revoke session: {}", e);
+        AppError::internal("Failed to revoke session")
+    })?;
+    if !revoked {
+        return Err(AppError::not_found("Session not found"));
+    }
+
+    let _ = state
+        .audit_repository()
+        .insert(
+            org_id,
+            Some(auth_user.user_id()),
+            "session.revoke",
+            "auth_sessions",
+            Some(&id.to_string()),
+            Some(&serde_json::json!({
+                "user_id": session.user_id,
+                "ip_address": session.ip_address,
+                "user_agent": session.user_agent,
+            })),
+            None,
+        )
+        .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Revoke all sessions of a user
+///
+/// DELETE /api/v1/sessions
+///
+/// For the caller's own sessions the current one is kept ("sign out
+/// everywhere else"); for another user every session is revoked.
+async fn revoke_all_sessions(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<SessionQuery>,
+) -> Result<Json<RevokeSessionsResponse>, AppError> {
+    let user_id = query.user_id.unwrap_or_else(|| auth_user.user_id());
+    let org_id = resolve_target(&state, &auth_user, user_id).await?;
+
+    let keep = if user_id == auth_user.user_id() {
+        current_session_id(&auth_user)
+    } else {
+        None
+    };
+
+    let revoked = AuthSessionRepository::new(&state.db)
+        .revoke_all_for_user(user_id, keep)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to revoke sessions: {}", e);
+            AppError::internal("Failed to revoke sessions")
+        })?;
+
+    let _ = state
+        .audit_repository()
+        .insert(
+            org_id,
+            Some(auth_user.user_id()),
+            "session.revoke_all",
+            "auth_sessions",
+            None,
+            Some(&serde_json::json!({
+                "user_id": user_id,
+                "revoked": revoked,
+                "kept_current": keep.is_some(),
+            })),
+            None,
+        )
+        .await;
+
+    Ok(Json(RevokeSessionsResponse { revoked }))
+}
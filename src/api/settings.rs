@@ -4,7 +4,7 @@
 //! application settings.
 
 use axum::{
-    extract::State,
+    extract::{Path, State},
     http::StatusCode,
     routing::{get, post},
     Json, Router,
@@ -12,8 +12,15 @@ use axum::{
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    config::{DashboardConfig, RbacConfig},
-    utils::error::ErrorResponse,
+    config::{
+        AppConfig, DashboardConfig, DashboardPreferences, RbacConfig, WidgetBinding,
+        WidgetDataSource,
+    },
+    db::{repository::SavedReportRepository, UserPreferencesRepository},
+    middleware::{AuditChange, AuthUser},
+    services::widget_data::{load_widget_data, WidgetData, WidgetViewer},
+    services::ReloadResult,
+    utils::{error::ApiError, AppError},
     AppState,
 };
 
@@ -30,13 +37,22 @@ pub fn routes() -> Router<AppState> {
     Router::new()
         // Get current configuration (read-only view)
         .route("/", get(get_settings))
-        // Get dashboard configuration
+        // The caller's dashboard configuration and preferences
         .route(
             "/dashboard",
-            get(get_dashboard_config).put(update_dashboard_config),
+            get(get_dashboard_config)
+                .put(update_dashboard_config)
+                .delete(reset_dashboard_config),
         )
+        // Chart data of bound (custom) widgets
+        .route("/dashboard/widgets/preview", post(preview_widget_data))
+        .route("/dashboard/widgets/{widget_id}/data", get(get_widget_data))
         // Get RBAC configuration
         .route("/rbac", get(get_rbac_config))
+        // Running configuration with secrets redacted (settings admin only)
+        .route("/effective-config", get(get_effective_config))
+        // Re-read the config files and apply the safe-to-change settings
+        .route("/reload", post(reload_config))
         // Export configuration as YAML
         .route("/export", get(export_config))
         // Import configuration from YAML
@@ -130,6 +146,7 @@ pub struct RbacSettings {
     pub session_timeout_minutes: u64,
     pub max_failed_logins: u32,
     pub lockout_duration_minutes: u64,
+    pub max_lockout_minutes: u64,
     pub custom_roles_count: usize,
 }
 
@@ -144,7 +161,7 @@ pub struct NodeBootstrapSettings {
 ///
 /// GET /api/v1/settings
 async fn get_settings(State(state): State<AppState>) -> Json<SettingsResponse> {
-    let config = &state.config;
+    let config = state.config_reloader.current();
 
     let response = SettingsResponse {
         server: ServerSettings {
@@ -196,6 +213,7 @@ async fn get_settings(State(state): State<AppState>) -> Json<SettingsResponse> {
             session_timeout_minutes: config.rbac.session_timeout_minutes,
             max_failed_logins: config.rbac.max_failed_logins,
             lockout_duration_minutes: config.rbac.lockout_duration_minutes,
+            max_lockout_minutes: config.rbac.max_lockout_minutes,
             custom_roles_count: config.rbac.roles.len(),
         },
         node_bootstrap: config
@@ -211,106 +229,194 @@ async fn get_settings(State(state): State<AppState>) -> Json<SettingsResponse> {
     Json(response)
 }
 
-/// Get dashboard configuration
+/// Get the caller's dashboard configuration
 ///
 /// GET /api/v1/settings/dashboard
-async fn get_dashboard_config(State(state): State<AppState>) -> Json<DashboardConfig> {
-    Json(state.config.dashboard.clone())
-}
-
-/// Update dashboard configuration request
-#[derive(Debug, Deserialize)]
-pub struct UpdateDashboardRequest {
-    #[serde(default)]
-    pub default_time_range: Option<String>,
-    #[serde(default)]
-    pub refresh_interval_secs: Option<u64>,
-    #[serde(default)]
-    pub nodes_per_page: Option<usize>,
-    #[serde(default)]
-    pub reports_per_page: Option<usize>,
-    #[serde(default)]
-    pub show_inactive_nodes: Option<bool>,
-    #[serde(default)]
-    pub inactive_threshold_hours: Option<u64>,
-    #[serde(default)]
-    pub theme: Option<String>,
+///
+/// The global dashboard configuration with the user's saved preferences
+/// applied on top.
+async fn get_dashboard_config(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<DashboardConfig>, AppError> {
+    let preferences = load_dashboard_preferences(&state, &auth_user).await?;
+    let config = state.config_reloader.current();
+    Ok(Json(config.dashboard.with_preferences(&preferences)))
 }
 
-/// Update dashboard configuration
+/// Save the caller's dashboard preferences
 ///
 /// PUT /api/v1/settings/dashboard
+///
+/// Only the fields present in the request change; the others keep their
+/// saved value or keep following the global configuration.
 async fn update_dashboard_config(
     State(state): State<AppState>,
-    Json(request): Json<UpdateDashboardRequest>,
-) -> Result<Json<DashboardConfig>, (StatusCode, Json<ErrorResponse>)> {
+    auth_user: AuthUser,
+    Json(request): Json<DashboardPreferences>,
+) -> Result<Json<DashboardConfig>, AppError> {
     // Validate time range
     if let Some(ref range) = request.default_time_range {
         if !["1h", "6h", "12h", "24h", "7d", "30d"].contains(&range.as_str()) {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: "validation_error".to_string(),
-                    message: format!(
-                        "Invalid time range: {}. Must be one of: 1h, 6h, 12h, 24h, 7d, 30d",
-                        range
-                    ),
-                    details: None,
-                    code: None,
-                }),
-            ));
+            return Err(AppError::bad_request(format!(
+                "Invalid time range: {}. Must be one of: 1h, 6h, 12h, 24h, 7d, 30d",
+                range
+            )));
         }
     }
 
     // Validate theme
     if let Some(ref theme) = request.theme {
         if !["light", "dark", "system"].contains(&theme.as_str()) {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: "validation_error".to_string(),
-                    message: format!(
-                        "Invalid theme: {}. Must be one of: light, dark, system",
-                        theme
-                    ),
-                    details: None,
-                    code: None,
-                }),
-            ));
+            return Err(AppError::bad_request(format!(
+                "Invalid theme: {}. Must be one of: light, dark, system",
+                theme
+            )));
         }
     }
 
-    // Build response with updated values (merged with current config)
-    let current = &state.config.dashboard;
-    let updated = DashboardConfig {
-        default_time_range: request
-            .default_time_range
-            .unwrap_or_else(|| current.default_time_range.clone()),
-        refresh_interval_secs: request
-            .refresh_interval_secs
-            .unwrap_or(current.refresh_interval_secs),
-        nodes_per_page: request.nodes_per_page.unwrap_or(current.nodes_per_page),
-        reports_per_page: request.reports_per_page.unwrap_or(current.reports_per_page),
-        show_inactive_nodes: request
-            .show_inactive_nodes
-            .unwrap_or(current.show_inactive_nodes),
-        inactive_threshold_hours: request
-            .inactive_threshold_hours
-            .unwrap_or(current.inactive_threshold_hours),
-        theme: request.theme.unwrap_or_else(|| current.theme.clone()),
-        widgets: current.widgets.clone(),
+    if request.nodes_per_page == Some(0) || request.reports_per_page == Some(0) {
+        return Err(AppError::bad_request("Page sizes must be at least 1"));
+    }
+
+    let mut preferences = load_dashboard_preferences(&state, &auth_user).await?;
+    preferences.apply(request);
+    UserPreferencesRepository::new(&state.db)
+        .set_dashboard(auth_user.user_id(), &preferences)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to save dashboard preferences: {}", e);
+            AppError::internal("Failed to save dashboard preferences")
+        })?;
+
+    let config = state.config_reloader.current();
+    Ok(Json(config.dashboard.with_preferences(&preferences)))
+}
+
+/// Drop the caller's dashboard preferences and follow the global
+/// configuration again
+///
+/// DELETE /api/v1/settings/dashboard
+async fn reset_dashboard_config(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<DashboardConfig>, AppError> {
+    UserPreferencesRepository::new(&state.db)
+        .clear_dashboard(auth_user.user_id())
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to reset dashboard preferences: {}", e);
+            AppError::internal("Failed to reset dashboard preferences")
+        })?;
+
+    Ok(Json(state.config_reloader.current().dashboard.clone()))
+}
+
+/// Chart data of one of the caller's dashboard widgets
+///
+/// GET /api/v1/settings/dashboard/widgets/{widget_id}/data
+async fn get_widget_data(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Path(widget_id): Path<String>,
+) -> Result<Json<WidgetData>, AppError> {
+    let preferences = load_dashboard_preferences(&state, &auth_user).await?;
+    let dashboard = state
+        .config_reloader
+        .current()
+        .dashboard
+        .with_preferences(&preferences);
+    let widget = dashboard
+        .widgets
+        .into_iter()
+        .find(|w| w.id == widget_id)
+        .ok_or_else(|| AppError::not_found("Widget not found"))?;
+    let binding = widget
+        .binding
+        .ok_or_else(|| AppError::bad_request("Widget has no data binding"))?;
+
+    Ok(Json(
+        run_widget_binding(&state, &auth_user, &binding).await?,
+    ))
+}
+
+/// Run a binding before saving it on a widget
+///
+/// POST /api/v1/settings/dashboard/widgets/preview
+async fn preview_widget_data(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Json(binding): Json<WidgetBinding>,
+) -> Result<Json<WidgetData>, AppError> {
+    Ok(Json(
+        run_widget_binding(&state, &auth_user, &binding).await?,
+    ))
+}
+
+async fn run_widget_binding(
+    state: &AppState,
+    auth_user: &AuthUser,
+    binding: &WidgetBinding,
+) -> Result<WidgetData, AppError> {
+    let viewer = WidgetViewer {
+        organization_id: auth_user.organization_id,
+        user_id: auth_user.user_id(),
+        sees_private_reports: auth_user.is_super_admin(),
     };
+    let puppetdb = state.puppetdb_for(viewer.organization_id).await;
+
+    match &binding.source {
+        WidgetDataSource::Pql {
+            query, label_field, ..
+        } => {
+            if query.trim().is_empty() || label_field.trim().is_empty() {
+                return Err(AppError::bad_request(
+                    "A PQL binding needs a query and a label_field",
+                ));
+            }
+            if puppetdb.is_none() {
+                return Err(AppError::service_unavailable("PuppetDB is not configured"));
+            }
+        }
+        WidgetDataSource::SavedReport { report_id } => {
+            let visible = SavedReportRepository::new(&state.db)
+                .get_by_id(viewer.organization_id, *report_id)
+                .await?
+                .is_some_and(|report| {
+                    report.is_public
+                        || report.created_by == viewer.user_id
+                        || viewer.sees_private_reports
+                });
+            if !visible {
+                return Err(AppError::not_found("Saved report not found"));
+            }
+        }
+    }
+
+    load_widget_data(&state.db, puppetdb, &viewer, binding)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to load widget data: {}", e)))
+}
 
-    // Note: In a production system, this would persist the changes
-    // For now, we return the updated configuration
-    Ok(Json(updated))
+async fn load_dashboard_preferences(
+    state: &AppState,
+    auth_user: &AuthUser,
+) -> Result<DashboardPreferences, AppError> {
+    let preferences = UserPreferencesRepository::new(&state.db)
+        .get_dashboard(auth_user.user_id())
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to load dashboard preferences: {}", e);
+            AppError::internal("Failed to load dashboard preferences")
+        })?;
+    Ok(preferences.unwrap_or_default())
 }
 
 /// Get RBAC configuration
 ///
 /// GET /api/v1/settings/rbac
 async fn get_rbac_config(State(state): State<AppState>) -> Json<RbacConfig> {
-    Json(state.config.rbac.clone())
+    Json(state.config_reloader.current().rbac.clone())
 }
 
 /// Export configuration response
@@ -321,14 +427,86 @@ pub struct ExportConfigResponse {
     pub timestamp: String,
 }
 
+/// Effective configuration response
+#[derive(Debug, Serialize)]
+pub struct EffectiveConfigResponse {
+    /// Configuration file the server reads, if any
+    pub config_file: Option<String>,
+    /// Running configuration (file and environment merged), secrets redacted
+    pub config: serde_json::Value,
+    /// `config` as YAML
+    pub yaml: String,
+    /// Settings whose value comes from environment variables
+    pub env_overrides: Vec<String>,
+    /// Settings that are valid but probably not intended
+    pub warnings: Vec<String>,
+}
+
+/// Show the running configuration with secrets redacted
+///
+/// GET /api/v1/settings/effective-config
+async fn get_effective_config(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<Json<EffectiveConfigResponse>, AppError> {
+    super::payload_debug::require_settings_admin(&state, &auth_user).await?;
+
+    let running = state.config_reloader.current();
+    let config = running.redacted();
+    let yaml = serde_norway::to_string(&config)
+        .map_err(|e| AppError::internal(format!("Failed to serialize configuration: {}", e)))?;
+
+    let mut warnings = running.warnings();
+    let env_overrides = AppConfig::env_overridden_settings().unwrap_or_else(|e| {
+        warnings.push(format!(
+            "Could not compare with the configuration file: {:#}",
+            e
+        ));
+        Vec::new()
+    });
+
+    Ok(Json(EffectiveConfigResponse {
+        config_file: AppConfig::config_file_path()
+            .filter(|path| path.exists())
+            .map(|path| path.display().to_string()),
+        config,
+        yaml,
+        env_overrides,
+        warnings,
+    }))
+}
+
+/// Re-read the configuration files and apply the safe-to-change settings
+///
+/// POST /api/v1/settings/reload
+///
+/// Applies the log level, dashboard, cache, rate limit and custom RBAC role
+/// settings; other changes are listed as needing a restart.
+async fn reload_config(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+) -> Result<(AuditChange, Json<ReloadResult>), AppError> {
+    super::payload_debug::require_settings_admin(&state, &auth_user).await?;
+
+    let before = state.config_reloader.current().redacted();
+    let result = state
+        .config_reloader
+        .reload()
+        .await
+        .map_err(|e| AppError::validation(format!("{:#}", e)))?;
+    let after = state.config_reloader.current().redacted();
+
+    Ok((AuditChange::new(&before, &after), Json(result)))
+}
+
 /// Export current configuration as YAML
 ///
 /// GET /api/v1/settings/export
 async fn export_config(
     State(state): State<AppState>,
-) -> Result<Json<ExportConfigResponse>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<ExportConfigResponse>, (StatusCode, Json<ApiError>)> {
     // Create a sanitized version of the config (without secrets)
-    let config = &state.config;
+    let config = state.config_reloader.current();
 
     let sanitized = serde_json::json!({
         "server": {
@@ -359,6 +537,8 @@ async fn export_config(
             "min_connections": config.database.min_connections,
             "connect_timeout_secs": config.database.connect_timeout_secs,
             "idle_timeout_secs": config.database.idle_timeout_secs,
+            "busy_timeout_ms": config.database.busy_timeout_ms,
+            "busy_retries": config.database.busy_retries,
         },
         "logging": {
             "level": config.logging.level,
@@ -381,18 +561,20 @@ async fn export_config(
             "session_timeout_minutes": config.rbac.session_timeout_minutes,
             "max_failed_logins": config.rbac.max_failed_logins,
             "lockout_duration_minutes": config.rbac.lockout_duration_minutes,
+            "max_lockout_minutes": config.rbac.max_lockout_minutes,
         },
     });
 
     let yaml_content = serde_norway::to_string(&sanitized).map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "serialization_error".to_string(),
-                message: format!("Failed to serialize config: {}", e),
-                details: None,
-                code: None,
-            }),
+            Json(
+                ApiError::new(
+                    "internal_error",
+                    format!("Failed to serialize config: {}", e),
+                )
+                .with_code("serialization_error"),
+            ),
         )
     })?;
 
@@ -633,7 +815,7 @@ async fn get_server_info(State(state): State<AppState>) -> Json<ServerInfoRespon
                 }
 
                 let login_url = if configured {
-                    Some("/api/v1/auth/saml/login".to_string())
+                    Some(state.config.server.prefixed_path("/api/v1/auth/saml/login"))
                 } else {
                     None
                 };
@@ -673,7 +855,7 @@ async fn get_server_info(State(state): State<AppState>) -> Json<ServerInfoRespon
         ("puppetdb", state.puppetdb.is_some()),
         ("puppet_ca", state.puppet_ca.is_some()),
         ("rbac", true),
-        ("caching", state.config.cache.enabled),
+        ("caching", state.config_reloader.current().cache.enabled),
         ("facter_templates", true),
         ("saml", saml_info.enabled && saml_info.configured),
     ];
@@ -906,10 +1088,8 @@ database:
 /// GET /api/v1/settings/smtp
 async fn get_smtp_settings(
     State(state): State<AppState>,
-) -> Result<Json<crate::models::SmtpSettings>, (StatusCode, Json<ErrorResponse>)> {
-    use crate::db::SettingsRepository;
-
-    let repo = SettingsRepository::new(state.db.clone());
+) -> Result<Json<crate::models::SmtpSettings>, (StatusCode, Json<ApiError>)> {
+    let repo = state.settings_repository();
 
     match repo.get_smtp_settings().await {
         Ok(smtp) => Ok(Json(smtp)),
@@ -917,7 +1097,7 @@ async fn get_smtp_settings(
             tracing::error!("Failed to get SMTP settings: {}", e);
             Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse::new(
+                Json(ApiError::new(
                     "internal_error",
                     "Failed to retrieve SMTP settings",
                 )),
@@ -932,10 +1112,8 @@ async fn get_smtp_settings(
 async fn update_smtp_settings(
     State(state): State<AppState>,
     Json(req): Json<crate::models::UpdateSmtpSettingsRequest>,
-) -> Result<Json<crate::models::SmtpSettings>, (StatusCode, Json<ErrorResponse>)> {
-    use crate::db::SettingsRepository;
-
-    let repo = SettingsRepository::new(state.db.clone());
+) -> Result<Json<crate::models::SmtpSettings>, (StatusCode, Json<ApiError>)> {
+    let repo = state.settings_repository();
 
     match repo.update_smtp_settings(&req).await {
         Ok(smtp) => Ok(Json(smtp)),
@@ -943,7 +1121,7 @@ async fn update_smtp_settings(
             tracing::error!("Failed to update SMTP settings: {}", e);
             Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse::new(
+                Json(ApiError::new(
                     "internal_error",
                     "Failed to update SMTP settings",
                 )),
@@ -961,10 +1139,8 @@ async fn update_smtp_settings(
 /// GET /api/v1/settings/update-jobs
 async fn get_update_job_settings(
     State(state): State<AppState>,
-) -> Result<Json<crate::models::UpdateJobSettings>, (StatusCode, Json<ErrorResponse>)> {
-    use crate::db::SettingsRepository;
-
-    let repo = SettingsRepository::new(state.db.clone());
+) -> Result<Json<crate::models::UpdateJobSettings>, (StatusCode, Json<ApiError>)> {
+    let repo = state.settings_repository();
 
     match repo.get_update_job_settings().await {
         Ok(settings) => Ok(Json(settings)),
@@ -972,7 +1148,7 @@ async fn get_update_job_settings(
             tracing::error!("Failed to get update job settings: {}", e);
             Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse::new(
+                Json(ApiError::new(
                     "internal_error",
                     "Failed to retrieve update job settings",
                 )),
@@ -987,10 +1163,8 @@ async fn get_update_job_settings(
 async fn update_update_job_settings(
     State(state): State<AppState>,
     Json(req): Json<crate::models::UpdateUpdateJobSettingsRequest>,
-) -> Result<Json<crate::models::UpdateJobSettings>, (StatusCode, Json<ErrorResponse>)> {
-    use crate::db::SettingsRepository;
-
-    let repo = SettingsRepository::new(state.db.clone());
+) -> Result<Json<crate::models::UpdateJobSettings>, (StatusCode, Json<ApiError>)> {
+    let repo = state.settings_repository();
 
     match repo.update_update_job_settings(&req).await {
         Ok(settings) => Ok(Json(settings)),
@@ -998,7 +1172,7 @@ async fn update_update_job_settings(
             tracing::error!("Failed to update update job settings: {}", e);
             Err((
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse::new(
+                Json(ApiError::new(
                     "internal_error",
                     "Failed to update update job settings",
                 )),
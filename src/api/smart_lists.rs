@@ -0,0 +1,431 @@
+//! Smart list (saved node filter) endpoints
+//!
+//! Smart lists are owned by a user within an organization. Owners can share a
+//! list with everyone in the organization; shared lists are read-only for
+//! other users unless they are an admin.
+//!
+//! Member counts are resolved on demand and cached, see
+//! [`crate::services::smart_list`].
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::get,
+    Json, Router,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    db::SmartListRepository,
+    middleware::AuthUser,
+    models::{CreateSmartListRequest, Node, SmartList, SmartListCount, UpdateSmartListRequest},
+    services::smart_list::{
+        is_visible_to, resolve_smart_list_nodes, smart_list_member_count, validate_filter,
+    },
+    utils::AppError,
+    AppState,
+};
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_smart_lists).post(create_smart_list))
+        .route("/counts", get(get_smart_list_counts))
+        .route(
+            "/{id}",
+            get(get_smart_list)
+                .put(update_smart_list)
+                .delete(delete_smart_list),
+        )
+        .route("/{id}/nodes", get(get_smart_list_nodes))
+        .route("/{id}/count", get(get_smart_list_count))
+        .route("/{id}/export", get(export_smart_list))
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct OrgQuery {
+    organization_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct CountQuery {
+    organization_id: Option<Uuid>,
+    /// Resolve the membership again instead of using a cached count
+    #[serde(default)]
+    refresh: bool,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct ExportQuery {
+    organization_id: Option<Uuid>,
+    /// Output format: csv (default) or json
+    format: Option<String>,
+}
+
+fn resolve_org(auth_user: &AuthUser, requested: Option<Uuid>) -> Result<Uuid, AppError> {
+    match requested {
+        Some(_) if !auth_user.is_super_admin() => Err(AppError::forbidden(
+            "organization_id can only be specified by super_admin",
+        )),
+        Some(org_id) => Ok(org_id),
+        None => Ok(auth_user.organization_id),
+    }
+}
+
+fn is_admin(auth_user: &AuthUser) -> bool {
+    auth_user.roles.iter().any(|r| r == "admin") || auth_user.is_super_admin()
+}
+
+/// Whether the caller may use a smart list
+pub(crate) fn can_see_smart_list(auth_user: &AuthUser, list: &SmartList) -> bool {
+    is_visible_to(list, auth_user.user_id(), is_admin(auth_user))
+}
+
+/// Load a smart list the caller is allowed to see
+///
+/// Private lists owned by other users are reported as not found so their
+/// existence is not disclosed.
+pub(crate) async fn load_visible_smart_list(
+    state: &AppState,
+    auth_user: &AuthUser,
+    org_id: Uuid,
+    id: Uuid,
+) -> Result<SmartList, AppError> {
+    let repo = SmartListRepository::new(&state.db);
+    let list = repo
+        .get_by_id(org_id, id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get smart list: {}", e);
+            AppError::internal("Failed to get smart list")
+        })?
+        .ok_or_else(|| AppError::not_found("Smart list not found"))?;
+
+    if !can_see_smart_list(auth_user, &list) {
+        return Err(AppError::not_found("Smart list not found"));
+    }
+
+    Ok(list)
+}
+
+/// Resolve the nodes matching a smart list for an API request
+pub(crate) async fn resolve_nodes_for_request(
+    state: &AppState,
+    list: &SmartList,
+) -> Result<Vec<Node>, AppError> {
+    let Some(puppetdb) = state.puppetdb_for(list.organization_id).await else {
+        return Ok(vec![]);
+    };
+
+    let nodes = resolve_smart_list_nodes(&state.db, &puppetdb, list)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to resolve smart list '{}': {}", list.name, e);
+            AppError::internal("Failed to resolve smart list nodes")
+        })?;
+    state.smart_list_counts.record(list, nodes.len()).await;
+    Ok(nodes)
+}
+
+/// Count the nodes matching a smart list, from the cache when possible
+async fn count_for_request(
+    state: &AppState,
+    list: &SmartList,
+    refresh: bool,
+) -> Result<SmartListCount, AppError> {
+    let Some(puppetdb) = state.puppetdb_for(list.organization_id).await else {
+        return Ok(state.smart_list_counts.record(list, 0).await);
+    };
+
+    smart_list_member_count(
+        &state.db,
+        &puppetdb,
+        &state.smart_list_counts,
+        list,
+        refresh,
+    )
+    .await
+    .map_err(|e| {
+        tracing::error!("Failed to count smart list '{}': {}", list.name, e);
+        AppError::internal("Failed to count smart list nodes")
+    })
+}
+
+/// List smart lists owned by or shared with the caller
+async fn list_smart_lists(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+) -> Result<Json<Vec<SmartList>>, AppError> {
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let repo = SmartListRepository::new(&state.db);
+    let lists = repo
+        .list_visible(org_id, auth_user.user_id())
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list smart lists: {}", e);
+            AppError::internal("Failed to list smart lists")
+        })?;
+
+    Ok(Json(lists))
+}
+
+async fn create_smart_list(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Json(payload): Json<CreateSmartListRequest>,
+) -> Result<(StatusCode, Json<SmartList>), AppError> {
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+
+    if payload.name.trim().is_empty() {
+        return Err(AppError::bad_request("Smart list name cannot be empty"));
+    }
+    validate_filter(&payload.filter).map_err(AppError::validation)?;
+
+    let repo = SmartListRepository::new(&state.db);
+    let list = repo
+        .create(org_id, auth_user.user_id(), &payload)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to create smart list: {}", e);
+            if e.root_cause().to_string().contains("UNIQUE") {
+                AppError::conflict("A smart list with this name already exists")
+            } else {
+                AppError::internal("Failed to create smart list")
+            }
+        })?;
+
+    let audit_repo = state.audit_repository();
+    let _ = audit_repo
+        .insert(
+            org_id,
+            Some(auth_user.user_id()),
+            "smart_list.create",
+            "smart_lists",
+            Some(&list.id.to_string()),
+            Some(&serde_json::json!({ "name": list.name, "is_shared": list.is_shared })),
+            None,
+        )
+        .await;
+
+    Ok((StatusCode::CREATED, Json(list)))
+}
+
+async fn get_smart_list(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SmartList>, AppError> {
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let list = load_visible_smart_list(&state, &auth_user, org_id, id).await?;
+    Ok(Json(list))
+}
+
+async fn update_smart_list(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateSmartListRequest>,
+) -> Result<Json<SmartList>, AppError> {
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let existing = load_visible_smart_list(&state, &auth_user, org_id, id).await?;
+
+    if existing.created_by != auth_user.user_id() && !is_admin(&auth_user) {
+        return Err(AppError::forbidden(
+            "Only the owner or an admin can modify this smart list",
+        ));
+    }
+    if let Some(name) = &payload.name {
+        if name.trim().is_empty() {
+            return Err(AppError::bad_request("Smart list name cannot be empty"));
+        }
+    }
+    if let Some(filter) = &payload.filter {
+        validate_filter(filter).map_err(AppError::validation)?;
+    }
+
+    let repo = SmartListRepository::new(&state.db);
+    let list = repo
+        .update(org_id, id, &payload)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to update smart list: {}", e);
+            if e.root_cause().to_string().contains("UNIQUE") {
+                AppError::conflict("A smart list with this name already exists")
+            } else {
+                AppError::internal("Failed to update smart list")
+            }
+        })?
+        .ok_or_else(|| AppError::not_found("Smart list not found"))?;
+    state.smart_list_counts.forget(id).await;
+
+    let audit_repo = state.audit_repository();
+    let _ = audit_repo
+        .insert(
+            org_id,
+            Some(auth_user.user_id()),
+            "smart_list.update",
+            "smart_lists",
+            Some(&list.id.to_string()),
+            Some(&serde_json::json!({ "name": list.name, "is_shared": list.is_shared })),
+            None,
+        )
+        .await;
+
+    Ok(Json(list))
+}
+
+async fn delete_smart_list(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let existing = load_visible_smart_list(&state, &auth_user, org_id, id).await?;
+
+    if existing.created_by != auth_user.user_id() && !is_admin(&auth_user) {
+        return Err(AppError::forbidden(
+            "Only the owner or an admin can delete this smart list",
+        ));
+    }
+
+    let repo = SmartListRepository::new(&state.db);
+    let deleted = repo.delete(org_id, id).await.map_err(|e| {
+        tracing::error!("Failed to delete smart list: {}", e);
+        AppError::internal("Failed to delete smart list")
+    })?;
+
+    if !deleted {
+        return Err(AppError::not_found("Smart list not found"));
+    }
+    state.smart_list_counts.forget(id).await;
+
+    let audit_repo = state.audit_repository();
+    let _ = audit_repo
+        .insert(
+            org_id,
+            Some(auth_user.user_id()),
+            "smart_list.delete",
+            "smart_lists",
+            Some(&id.to_string()),
+            Some(&serde_json::json!({ "name": existing.name })),
+            None,
+        )
+        .await;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Get the nodes currently matching a smart list
+async fn get_smart_list_nodes(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<Node>>, AppError> {
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let list = load_visible_smart_list(&state, &auth_user, org_id, id).await?;
+    let nodes = resolve_nodes_for_request(&state, &list).await?;
+    Ok(Json(nodes))
+}
+
+/// Get the number of nodes matching each smart list visible to the caller
+///
+/// Lists that fail to resolve are left out.
+async fn get_smart_list_counts(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<CountQuery>,
+) -> Result<Json<Vec<SmartListCount>>, AppError> {
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let lists = SmartListRepository::new(&state.db)
+        .list_visible(org_id, auth_user.user_id())
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list smart lists: {}", e);
+            AppError::internal("Failed to list smart lists")
+        })?;
+
+    let mut counts = Vec::with_capacity(lists.len());
+    for list in &lists {
+        if let Ok(count) = count_for_request(&state, list, query.refresh).await {
+            counts.push(count);
+        }
+    }
+    Ok(Json(counts))
+}
+
+/// Get the number of nodes matching a smart list
+async fn get_smart_list_count(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<CountQuery>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<SmartListCount>, AppError> {
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let list = load_visible_smart_list(&state, &auth_user, org_id, id).await?;
+    let count = count_for_request(&state, &list, query.refresh).await?;
+    Ok(Json(count))
+}
+
+/// Export the nodes matching a smart list as CSV or JSON
+async fn export_smart_list(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<ExportQuery>,
+    Path(id): Path<Uuid>,
+) -> Result<(StatusCode, [(String, String); 2], Vec<u8>), AppError> {
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let list = load_visible_smart_list(&state, &auth_user, org_id, id).await?;
+    let nodes = resolve_nodes_for_request(&state, &list).await?;
+
+    let (content_type, extension, data) = match query.format.as_deref().unwrap_or("csv") {
+        "csv" => ("text/csv", "csv", nodes_to_csv(&nodes).into_bytes()),
+        "json" => (
+            "application/json",
+            "json",
+            serde_json::to_vec_pretty(&nodes)
+                .map_err(|_| AppError::internal("Failed to serialize nodes"))?,
+        ),
+        other => {
+            return Err(AppError::bad_request(format!(
+                "Unsupported export format '{}'; use csv or json",
+                other
+            )))
+        }
+    };
+
+    let filename = format!("smart-list-{}.{}", list.id, extension);
+
+    Ok((
+        StatusCode::OK,
+        [
+            ("Content-Type".to_string(), content_type.to_string()),
+            (
+                "Content-Disposition".to_string(),
+                format!("attachment; filename=\"{}\"", filename),
+            ),
+        ],
+        data,
+    ))
+}
+
+fn nodes_to_csv(nodes: &[Node]) -> String {
+    let mut csv = String::from("Certname,Environment,Status,Last Report\n");
+    for node in nodes {
+        csv.push_str(&format!(
+            "{},{},{},{}\n",
+            node.certname,
+            node.catalog_environment.as_deref().unwrap_or(""),
+            node.latest_report_status.as_deref().unwrap_or("unreported"),
+            node.report_timestamp
+                .map(|t| t.to_rfc3339())
+                .unwrap_or_default()
+        ));
+    }
+    csv
+}
@@ -2,20 +2,29 @@
 //!
 //! Provides user CRUD operations and role/permission management.
 
+use std::collections::HashSet;
+
 use axum::{
+    body::Bytes,
     extract::{Path, Query, State},
-    http::StatusCode,
-    routing::get,
+    http::{header::CONTENT_TYPE, HeaderMap, StatusCode},
+    routing::{get, post},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
-    middleware::AuthUser,
-    models::{AssignRolesRequest, EffectivePermissions, Role, UserPublic, UserRoleInfo},
-    services::AuthService,
-    utils::error::ErrorResponse,
+    api::auth::check_password_policy,
+    db::{AccountLockoutRepository, OrganizationRepository},
+    middleware::{auth::revoke_user_auth_sessions, AuthUser},
+    models::{
+        Action, AssignRolesRequest, EffectivePermissions, ImportUserResult, ImportUserRow,
+        ImportUserStatus, ImportUsersResponse, Organization, QuotaResource, Resource, Role,
+        SystemRole, UserKind, UserPublic, UserRoleInfo,
+    },
+    services::{password_policy::PasswordPolicy, quotas::check_quota, user_import, AuthService},
+    utils::error::{ApiError, AppError},
     AppState,
 };
 
@@ -23,9 +32,11 @@ use crate::{
 pub fn routes() -> Router<AppState> {
     Router::new()
         .route("/", get(list_users).post(create_user))
+        .route("/import", post(import_users))
         .route("/{id}", get(get_user).put(update_user).delete(delete_user))
         .route("/{id}/roles", get(get_user_roles).put(assign_user_roles))
         .route("/{id}/permissions", get(get_user_permissions))
+        .route("/{id}/unlock", post(unlock_user))
 }
 
 /// Create user request
@@ -43,6 +54,10 @@ pub struct CreateUserRequest {
     pub auth_provider: String,
     /// External ID for SAML users (e.g., email from IdP)
     pub external_id: Option<String>,
+    /// "human" (default) or "service_account"; service accounts have no
+    /// password and authenticate with API keys only
+    #[serde(default)]
+    pub kind: UserKind,
 }
 
 fn default_auth_provider() -> String {
@@ -71,27 +86,35 @@ pub struct UserWithRolesResponse {
     pub roles: Vec<Role>,
 }
 
+/// Result of unlocking a user
+#[derive(Debug, Serialize)]
+pub struct UnlockUserResponse {
+    /// Whether the account was locked when it was unlocked
+    pub was_locked: bool,
+}
+
 #[derive(Debug, Deserialize, Default)]
 struct OrgQuery {
     organization_id: Option<Uuid>,
 }
 
-fn forbidden(message: &str) -> (StatusCode, Json<ErrorResponse>) {
+#[derive(Debug, Deserialize, Default)]
+struct ListUsersQuery {
+    organization_id: Option<Uuid>,
+    kind: Option<UserKind>,
+}
+
+fn forbidden(message: &str) -> (StatusCode, Json<ApiError>) {
     (
         StatusCode::FORBIDDEN,
-        Json(ErrorResponse {
-            error: "forbidden".to_string(),
-            message: message.to_string(),
-            details: None,
-            code: None,
-        }),
+        Json(ApiError::new("forbidden", message.to_string())),
     )
 }
 
 fn resolve_org(
     auth_user: &AuthUser,
     requested: Option<Uuid>,
-) -> Result<Option<Uuid>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Option<Uuid>, (StatusCode, Json<ApiError>)> {
     match requested {
         Some(org_id) if !auth_user.is_super_admin() && org_id != auth_user.organization_id => Err(
             forbidden("organization_id can only be specified by super_admin"),
@@ -107,13 +130,13 @@ fn resolve_org(
 async fn list_users(
     State(state): State<AppState>,
     auth_user: AuthUser,
-    Query(query): Query<OrgQuery>,
-) -> Result<Json<Vec<UserPublic>>, (StatusCode, Json<ErrorResponse>)> {
+    Query(query): Query<ListUsersQuery>,
+) -> Result<Json<Vec<UserPublic>>, (StatusCode, Json<ApiError>)> {
     let auth_service = AuthService::new(state.db.clone());
 
     let requested_org = resolve_org(&auth_user, query.organization_id)?;
 
-    let users = match (auth_user.is_super_admin(), requested_org) {
+    let mut users = match (auth_user.is_super_admin(), requested_org) {
         (true, Some(org_id)) => auth_service.list_users_in_org(org_id).await,
         (true, None) => auth_service.list_users().await,
         (false, _) => {
@@ -125,14 +148,15 @@ async fn list_users(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "internal_error".to_string(),
-                message: format!("Failed to fetch users: {}", e),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "internal_error",
+                format!("Failed to fetch users: {}", e),
+            )),
         )
     })?;
+    if let Some(kind) = query.kind {
+        users.retain(|user| user.kind == kind);
+    }
 
     // Fetch roles for each user
     let mut users_with_roles = Vec::with_capacity(users.len());
@@ -163,19 +187,17 @@ async fn create_user(
     State(state): State<AppState>,
     auth_user: AuthUser,
     Json(payload): Json<CreateUserRequest>,
-) -> Result<(StatusCode, Json<UserPublic>), (StatusCode, Json<ErrorResponse>)> {
+) -> Result<(StatusCode, Json<UserPublic>), (StatusCode, Json<ApiError>)> {
     use crate::models::AuthProvider;
 
     // Parse auth_provider
     let auth_provider: AuthProvider = payload.auth_provider.parse().map_err(|_| {
         (
             StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "validation_error".to_string(),
-                message: "Invalid auth_provider. Must be 'local', 'saml', or 'both'".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "validation_error",
+                "Invalid auth_provider. Must be 'local', 'saml', or 'both'",
+            )),
         )
     })?;
 
@@ -183,12 +205,10 @@ async fn create_user(
     if payload.username.len() < 3 {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "validation_error".to_string(),
-                message: "Username must be at least 3 characters".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "validation_error",
+                "Username must be at least 3 characters",
+            )),
         ));
     }
 
@@ -196,41 +216,40 @@ async fn create_user(
     if !payload.email.contains('@') {
         return Err((
             StatusCode::BAD_REQUEST,
-            Json(ErrorResponse {
-                error: "validation_error".to_string(),
-                message: "Invalid email address".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new("validation_error", "Invalid email address")),
         ));
     }
 
+    if payload.kind == UserKind::ServiceAccount {
+        if payload.password.is_some() || auth_provider != AuthProvider::Local {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new(
+                    "validation_error",
+                    "Service accounts have no password or SAML login; they authenticate with API keys",
+                )),
+            ));
+        }
+    }
+
     // Password validation depends on auth_provider
     let password = match (&auth_provider, &payload.password) {
+        // Service accounts have no password
+        _ if payload.kind == UserKind::ServiceAccount => None,
         // Local or Both auth requires a password
         (AuthProvider::Local | AuthProvider::Both, None) => {
             return Err((
                 StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: "validation_error".to_string(),
-                    message: "Password is required for local authentication".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new(
+                    "validation_error",
+                    "Password is required for local authentication",
+                )),
             ));
         }
-        (AuthProvider::Local | AuthProvider::Both, Some(p)) if p.len() < 8 => {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                Json(ErrorResponse {
-                    error: "validation_error".to_string(),
-                    message: "Password must be at least 8 characters".to_string(),
-                    details: None,
-                    code: None,
-                }),
-            ));
+        (AuthProvider::Local | AuthProvider::Both, Some(p)) => {
+            check_password_policy(&state, None, &payload.username, p).await?;
+            Some(p.as_str())
         }
-        (AuthProvider::Local | AuthProvider::Both, Some(p)) => Some(p.as_str()),
         // SAML-only users don't need a password
         (AuthProvider::Saml, _) => None,
     };
@@ -258,41 +277,54 @@ async fn create_user(
         None => auth_user.organization_id,
     };
 
-    let user = auth_service
-        .create_user_with_auth_provider(
-            &payload.username,
-            &payload.email,
-            password,
-            role,
-            org_id,
-            auth_provider,
-            external_id,
-        )
-        .await
-        .map_err(|e| {
-            let message = e.to_string();
-            if message.contains("already exists") {
-                (
-                    StatusCode::CONFLICT,
-                    Json(ErrorResponse {
-                        error: "conflict".to_string(),
-                        message,
-                        details: None,
-                        code: None,
-                    }),
-                )
-            } else {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse {
-                        error: "internal_error".to_string(),
-                        message: format!("Failed to create user: {}", e),
-                        details: None,
-                        code: None,
-                    }),
+    if let Err(e) = check_quota(&state.db, org_id, QuotaResource::Users).await {
+        let (status, error) = match &e {
+            AppError::Conflict(_) => (StatusCode::CONFLICT, "conflict"),
+            _ => (StatusCode::INTERNAL_SERVER_ERROR, "internal_error"),
+        };
+        return Err((
+            status,
+            Json(ApiError::new(error.to_string(), e.to_string())),
+        ));
+    }
+
+    let created = match payload.kind {
+        UserKind::ServiceAccount => {
+            auth_service
+                .create_service_account(&payload.username, &payload.email, role, org_id)
+                .await
+        }
+        UserKind::Human => {
+            auth_service
+                .create_user_with_auth_provider(
+                    &payload.username,
+                    &payload.email,
+                    password,
+                    role,
+                    org_id,
+                    auth_provider,
+                    external_id,
                 )
-            }
-        })?;
+                .await
+        }
+    };
+    let user = created.map_err(|e| {
+        let message = e.to_string();
+        if message.contains("already exists") {
+            (
+                StatusCode::CONFLICT,
+                Json(ApiError::new("conflict", message)),
+            )
+        } else {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to create user: {}", e),
+                )),
+            )
+        }
+    })?;
 
     // Assign roles if provided
     if let Some(role_ids) = payload.role_ids {
@@ -304,12 +336,10 @@ async fn create_user(
                 .map_err(|e| {
                     (
                         StatusCode::INTERNAL_SERVER_ERROR,
-                        Json(ErrorResponse {
-                            error: "internal_error".to_string(),
-                            message: format!("Failed to assign roles: {}", e),
-                            details: None,
-                            code: None,
-                        }),
+                        Json(ApiError::new(
+                            "internal_error",
+                            format!("Failed to assign roles: {}", e),
+                        )),
                     )
                 })?;
         }
@@ -318,6 +348,267 @@ async fn create_user(
     Ok((StatusCode::CREATED, Json(user.into())))
 }
 
+#[derive(Debug, Deserialize, Default)]
+struct ImportQuery {
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Shared state of one import run
+struct ImportContext<'a> {
+    state: &'a AppState,
+    auth_user: &'a AuthUser,
+    auth_service: AuthService,
+    policy: PasswordPolicy<'a>,
+    organizations: Vec<Organization>,
+    dry_run: bool,
+    usernames: HashSet<String>,
+    emails: HashSet<String>,
+}
+
+/// Import users from CSV or JSON
+///
+/// POST /api/v1/users/import
+///
+/// A `text/csv` body needs a header row naming its columns; a JSON body is
+/// an array of rows or an object with a `users` array. Every row is checked
+/// and created on its own and gets a result entry, so one bad row does not
+/// stop the others. With `?dry_run=true` the rows are only checked.
+async fn import_users(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<ImportQuery>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<ImportUsersResponse>, (StatusCode, Json<ApiError>)> {
+    if !auth_user.is_super_admin() {
+        let check = state
+            .rbac_db
+            .check_permission(
+                &auth_user.user_id(),
+                Resource::Users,
+                Action::Create,
+                None,
+                None,
+            )
+            .await
+            .map_err(|e| internal_error(format!("Permission check failed: {}", e)))?;
+        if !check.allowed {
+            return Err(forbidden("Create permission on users required"));
+        }
+    }
+
+    let is_csv = headers
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/csv"));
+    let rows = if is_csv {
+        std::str::from_utf8(&body)
+            .map_err(|_| "CSV import must be UTF-8".to_string())
+            .and_then(user_import::parse_csv)
+    } else {
+        user_import::parse_json(&body)
+    }
+    .map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("validation_error", e)),
+        )
+    })?;
+
+    let organizations = OrganizationRepository::new(&state.db)
+        .list()
+        .await
+        .map_err(|e| internal_error(format!("Failed to fetch organizations: {}", e)))?;
+    let mut ctx = ImportContext {
+        state: &state,
+        auth_user: &auth_user,
+        auth_service: AuthService::new(state.db.clone()),
+        policy: PasswordPolicy::new(&state.config.auth),
+        organizations,
+        dry_run: query.dry_run,
+        usernames: HashSet::new(),
+        emails: HashSet::new(),
+    };
+
+    let mut results = Vec::with_capacity(rows.len());
+    for (i, row) in rows.iter().enumerate() {
+        let mut result = ImportUserResult {
+            row: i + 1,
+            username: row.username.clone(),
+            status: ImportUserStatus::Failed,
+            user_id: None,
+            organization_id: None,
+            role: None,
+            generated_password: None,
+            error: None,
+        };
+        if let Err(e) = import_row(&mut ctx, row, &mut result).await {
+            result.status = ImportUserStatus::Failed;
+            result.error = Some(e);
+        }
+        results.push(result);
+    }
+
+    let created: Vec<_> = results
+        .iter()
+        .filter(|r| r.status == ImportUserStatus::Created)
+        .collect();
+    let failed = results
+        .iter()
+        .filter(|r| r.status == ImportUserStatus::Failed)
+        .count();
+    if !created.is_empty() {
+        let _ = state
+            .audit_repository()
+            .insert(
+                auth_user.organization_id,
+                Some(auth_user.user_id()),
+                "users.import",
+                "users",
+                None,
+                Some(&serde_json::json!({
+                    "created": created
+                        .iter()
+                        .map(|r| serde_json::json!({
+                            "user_id": r.user_id,
+                            "username": r.username,
+                            "organization_id": r.organization_id,
+                            "role": r.role,
+                        }))
+                        .collect::<Vec<_>>(),
+                    "failed": failed,
+                })),
+                None,
+            )
+            .await;
+    }
+
+    Ok(Json(ImportUsersResponse {
+        dry_run: query.dry_run,
+        total: results.len(),
+        created: created.len(),
+        failed,
+        results,
+    }))
+}
+
+/// Check one import row and, unless this is a dry run, create its user
+async fn import_row(
+    ctx: &mut ImportContext<'_>,
+    row: &ImportUserRow,
+    result: &mut ImportUserResult,
+) -> Result<(), String> {
+    user_import::validate_row(row)?;
+    if !ctx.usernames.insert(row.username.to_lowercase()) {
+        return Err("Username appears more than once in the import".to_string());
+    }
+    if !ctx.emails.insert(row.email.to_lowercase()) {
+        return Err("Email appears more than once in the import".to_string());
+    }
+
+    let org_id = match row.organization.as_deref() {
+        Some(org) => {
+            ctx.organizations
+                .iter()
+                .find(|o| o.slug == org || o.id.to_string() == org)
+                .ok_or_else(|| format!("Unknown organization '{}'", org))?
+                .id
+        }
+        None => ctx.auth_user.organization_id,
+    };
+    if !ctx.auth_user.is_super_admin() && org_id != ctx.auth_user.organization_id {
+        return Err("Only super_admin can import users into other organizations".to_string());
+    }
+    result.organization_id = Some(org_id);
+
+    let role_name = row.role.as_deref().unwrap_or("viewer");
+    let role = ctx
+        .state
+        .rbac_db
+        .get_role_by_name(role_name)
+        .await
+        .map_err(|e| format!("Failed to look up role: {}", e))?
+        .ok_or_else(|| format!("Unknown role '{}'", role_name))?;
+    if role.id == SystemRole::SuperAdmin.uuid() && !ctx.auth_user.is_super_admin() {
+        return Err("Only super_admin can assign the super_admin role".to_string());
+    }
+    result.role = Some(role.name.clone());
+
+    ctx.auth_service
+        .check_available(&row.username, &row.email)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let generated = match (&row.password, &row.password_hash) {
+        (_, Some(hash)) if !AuthService::is_supported_password_hash(hash) => {
+            return Err("password_hash must be a PHC-format Argon2 hash".to_string());
+        }
+        (Some(password), _) => {
+            let violations = ctx
+                .policy
+                .validate(&ctx.state.db, None, Some(&row.username), password)
+                .await
+                .map_err(|e| format!("Failed to check password policy: {}", e))?;
+            if !violations.is_empty() {
+                return Err(violations.join("; "));
+            }
+            None
+        }
+        (None, Some(_)) => None,
+        (None, None) => Some(ctx.policy.generate(Some(&row.username)).ok_or_else(|| {
+            "Could not generate a password that satisfies the password policy".to_string()
+        })?),
+    };
+
+    check_quota(&ctx.state.db, org_id, QuotaResource::Users)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if ctx.dry_run {
+        result.status = ImportUserStatus::Valid;
+        return Ok(());
+    }
+
+    let password_hash = match (&row.password_hash, &row.password, &generated) {
+        (Some(hash), _, _) => hash.clone(),
+        (None, Some(password), _) | (None, None, Some(password)) => {
+            AuthService::hash_password(password).map_err(|e| e.to_string())?
+        }
+        (None, None, None) => unreachable!("a password is generated when none is given"),
+    };
+    let user = ctx
+        .auth_service
+        .create_user_with_password_hash(
+            &row.username,
+            &row.email,
+            &password_hash,
+            &role.name,
+            org_id,
+            generated.is_some(),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+    result.user_id = Some(user.id);
+
+    ctx.state
+        .rbac_db
+        .assign_roles(&user.id, &[role.id])
+        .await
+        .map_err(|e| format!("User created, but assigning the role failed: {}", e))?;
+
+    result.status = ImportUserStatus::Created;
+    result.generated_password = generated;
+    Ok(())
+}
+
+fn internal_error(message: String) -> (StatusCode, Json<ApiError>) {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        Json(ApiError::new("internal_error", message)),
+    )
+}
+
 /// Get a specific user
 ///
 /// GET /api/v1/users/:id
@@ -326,7 +617,7 @@ async fn get_user(
     auth_user: AuthUser,
     Query(query): Query<OrgQuery>,
     Path(id): Path<Uuid>,
-) -> Result<Json<UserPublic>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<UserPublic>, (StatusCode, Json<ApiError>)> {
     let auth_service = AuthService::new(state.db.clone());
 
     let requested_org = resolve_org(&auth_user, query.organization_id)?;
@@ -343,23 +634,16 @@ async fn get_user(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "internal_error".to_string(),
-                message: format!("Failed to fetch user: {}", e),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "internal_error",
+                format!("Failed to fetch user: {}", e),
+            )),
         )
     })?
     .ok_or_else(|| {
         (
             StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: "not_found".to_string(),
-                message: "User not found".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new("not_found", "User not found")),
         )
     })?;
 
@@ -391,7 +675,7 @@ async fn update_user(
     Query(query): Query<OrgQuery>,
     Path(id): Path<Uuid>,
     Json(payload): Json<UpdateUserRequest>,
-) -> Result<Json<UserPublic>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<UserPublic>, (StatusCode, Json<ApiError>)> {
     let auth_service = AuthService::new(state.db.clone());
 
     let requested_org = resolve_org(&auth_user, query.organization_id)?;
@@ -407,25 +691,38 @@ async fn update_user(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "internal_error".to_string(),
-                message: format!("Failed to fetch user: {}", e),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "internal_error",
+                format!("Failed to fetch user: {}", e),
+            )),
         )
     })?;
 
-    if existing.is_none() {
+    let Some(existing) = existing else {
         return Err((
             StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: "not_found".to_string(),
-                message: "User not found".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new("not_found", "User not found")),
         ));
+    };
+
+    if existing.is_service_account()
+        && (payload.password.is_some()
+            || payload.auth_provider.is_some()
+            || payload.external_id.is_some())
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new(
+                "validation_error",
+                "Service accounts have no password or SAML login; they authenticate with API keys",
+            )),
+        ));
+    }
+
+    // Admin password resets follow the same policy as self-service changes
+    if let Some(password) = payload.password.as_deref() {
+        let username = payload.username.as_deref().unwrap_or(&existing.username);
+        check_password_policy(&state, Some(existing.id), username, password).await?;
     }
 
     let user = auth_service
@@ -444,42 +741,25 @@ async fn update_user(
             if message.contains("not found") {
                 (
                     StatusCode::NOT_FOUND,
-                    Json(ErrorResponse {
-                        error: "not_found".to_string(),
-                        message: "User not found".to_string(),
-                        details: None,
-                        code: None,
-                    }),
+                    Json(ApiError::new("not_found", "User not found")),
                 )
             } else if message.contains("already exists") {
                 (
                     StatusCode::CONFLICT,
-                    Json(ErrorResponse {
-                        error: "conflict".to_string(),
-                        message,
-                        details: None,
-                        code: None,
-                    }),
+                    Json(ApiError::new("conflict", message)),
                 )
             } else if message.contains("Invalid auth_provider") {
                 (
                     StatusCode::BAD_REQUEST,
-                    Json(ErrorResponse {
-                        error: "validation_error".to_string(),
-                        message,
-                        details: None,
-                        code: None,
-                    }),
+                    Json(ApiError::new("validation_error", message)),
                 )
             } else {
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse {
-                        error: "internal_error".to_string(),
-                        message: format!("Failed to update user: {}", e),
-                        details: None,
-                        code: None,
-                    }),
+                    Json(ApiError::new(
+                        "internal_error",
+                        format!("Failed to update user: {}", e),
+                    )),
                 )
             }
         })?;
@@ -489,13 +769,15 @@ async fn update_user(
 
 /// Delete a user
 ///
+/// The user moves to the recycle bin and their sessions are revoked.
+///
 /// DELETE /api/v1/users/:id
 async fn delete_user(
     State(state): State<AppState>,
     auth_user: AuthUser,
     Query(query): Query<OrgQuery>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<StatusCode, (StatusCode, Json<ApiError>)> {
     let auth_service = AuthService::new(state.db.clone());
 
     let requested_org = resolve_org(&auth_user, query.organization_id)?;
@@ -511,50 +793,40 @@ async fn delete_user(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "internal_error".to_string(),
-                message: format!("Failed to fetch user: {}", e),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "internal_error",
+                format!("Failed to fetch user: {}", e),
+            )),
         )
     })?;
 
     if existing.is_none() {
         return Err((
             StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: "not_found".to_string(),
-                message: "User not found".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new("not_found", "User not found")),
         ));
     }
 
     let deleted = auth_service.delete_user(&id).await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "internal_error".to_string(),
-                message: format!("Failed to delete user: {}", e),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "internal_error",
+                format!("Failed to delete user: {}", e),
+            )),
         )
     })?;
 
     if deleted {
+        state.rbac_db.invalidate_user_cache(&id);
+        if let Err(e) = revoke_user_auth_sessions(&state.db, &id).await {
+            tracing::error!("Failed to revoke sessions of user {}: {}", id, e);
+        }
         Ok(StatusCode::NO_CONTENT)
     } else {
         Err((
             StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: "not_found".to_string(),
-                message: "User not found".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new("not_found", "User not found")),
         ))
     }
 }
@@ -567,7 +839,7 @@ async fn get_user_roles(
     auth_user: AuthUser,
     Query(query): Query<OrgQuery>,
     Path(id): Path<Uuid>,
-) -> Result<Json<Vec<Role>>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<Vec<Role>>, (StatusCode, Json<ApiError>)> {
     // Verify user exists
     let auth_service = AuthService::new(state.db.clone());
     let requested_org = resolve_org(&auth_user, query.organization_id)?;
@@ -583,36 +855,27 @@ async fn get_user_roles(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "internal_error".to_string(),
-                message: format!("Failed to fetch user: {}", e),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "internal_error",
+                format!("Failed to fetch user: {}", e),
+            )),
         )
     })?;
 
     if existing.is_none() {
         return Err((
             StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: "not_found".to_string(),
-                message: "User not found".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new("not_found", "User not found")),
         ));
     }
 
     let roles = state.rbac_db.get_user_roles(&id).await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "internal_error".to_string(),
-                message: format!("Failed to fetch user roles: {}", e),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "internal_error",
+                format!("Failed to fetch user roles: {}", e),
+            )),
         )
     })?;
 
@@ -628,7 +891,7 @@ async fn assign_user_roles(
     Query(query): Query<OrgQuery>,
     Path(id): Path<Uuid>,
     Json(payload): Json<AssignRolesRequest>,
-) -> Result<Json<Vec<Role>>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<Vec<Role>>, (StatusCode, Json<ApiError>)> {
     // Verify user exists
     let auth_service = AuthService::new(state.db.clone());
     let requested_org = resolve_org(&auth_user, query.organization_id)?;
@@ -644,24 +907,17 @@ async fn assign_user_roles(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "internal_error".to_string(),
-                message: format!("Failed to fetch user: {}", e),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "internal_error",
+                format!("Failed to fetch user: {}", e),
+            )),
         )
     })?;
 
     if existing.is_none() {
         return Err((
             StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: "not_found".to_string(),
-                message: "User not found".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new("not_found", "User not found")),
         ));
     }
 
@@ -674,22 +930,18 @@ async fn assign_user_roles(
             if message.contains("not found") {
                 (
                     StatusCode::BAD_REQUEST,
-                    Json(ErrorResponse {
-                        error: "bad_request".to_string(),
-                        message: format!("Role not found: {}", e),
-                        details: None,
-                        code: None,
-                    }),
+                    Json(ApiError::new(
+                        "bad_request",
+                        format!("Role not found: {}", e),
+                    )),
                 )
             } else {
                 (
                     StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ErrorResponse {
-                        error: "internal_error".to_string(),
-                        message: format!("Failed to assign roles: {}", e),
-                        details: None,
-                        code: None,
-                    }),
+                    Json(ApiError::new(
+                        "internal_error",
+                        format!("Failed to assign roles: {}", e),
+                    )),
                 )
             }
         })?;
@@ -698,12 +950,10 @@ async fn assign_user_roles(
     let roles = state.rbac_db.get_user_roles(&id).await.map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "internal_error".to_string(),
-                message: format!("Failed to fetch user roles: {}", e),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "internal_error",
+                format!("Failed to fetch user roles: {}", e),
+            )),
         )
     })?;
 
@@ -718,7 +968,7 @@ async fn get_user_permissions(
     auth_user: AuthUser,
     Query(query): Query<OrgQuery>,
     Path(id): Path<Uuid>,
-) -> Result<Json<EffectivePermissions>, (StatusCode, Json<ErrorResponse>)> {
+) -> Result<Json<EffectivePermissions>, (StatusCode, Json<ApiError>)> {
     // Verify user exists
     let auth_service = AuthService::new(state.db.clone());
     let requested_org = resolve_org(&auth_user, query.organization_id)?;
@@ -734,24 +984,17 @@ async fn get_user_permissions(
     .map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
-            Json(ErrorResponse {
-                error: "internal_error".to_string(),
-                message: format!("Failed to fetch user: {}", e),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new(
+                "internal_error",
+                format!("Failed to fetch user: {}", e),
+            )),
         )
     })?;
 
     if existing.is_none() {
         return Err((
             StatusCode::NOT_FOUND,
-            Json(ErrorResponse {
-                error: "not_found".to_string(),
-                message: "User not found".to_string(),
-                details: None,
-                code: None,
-            }),
+            Json(ApiError::new("not_found", "User not found")),
         ));
     }
 
@@ -762,14 +1005,91 @@ async fn get_user_permissions(
         .map_err(|e| {
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ErrorResponse {
-                    error: "internal_error".to_string(),
-                    message: format!("Failed to fetch permissions: {}", e),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new(
+                    "internal_error",
+                    format!("Failed to fetch permissions: {}", e),
+                )),
             )
         })?;
 
     Ok(Json(permissions))
 }
+
+/// Unlock a user locked out after failed logins
+///
+/// POST /api/v1/users/:id/unlock
+///
+/// Also forgets the failed attempts and earlier lockouts, so the next
+/// lockout starts at the base duration again. Needs the `admin` action on
+/// users.
+async fn unlock_user(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<UnlockUserResponse>, (StatusCode, Json<ApiError>)> {
+    if !auth_user.is_super_admin() {
+        let check = state
+            .rbac_db
+            .check_permission(
+                &auth_user.user_id(),
+                Resource::Users,
+                Action::Admin,
+                None,
+                None,
+            )
+            .await
+            .map_err(|e| internal_error(format!("Permission check failed: {}", e)))?;
+        if !check.allowed {
+            return Err(forbidden("Admin permission on users required"));
+        }
+    }
+
+    let auth_service = AuthService::new(state.db.clone());
+    let requested_org = resolve_org(&auth_user, query.organization_id)?;
+    let user = match (auth_user.is_super_admin(), requested_org) {
+        (true, Some(org_id)) => auth_service.get_user_by_id_in_org(org_id, &id).await,
+        (true, None) => auth_service.get_user_by_id(&id).await,
+        (false, _) => {
+            auth_service
+                .get_user_by_id_in_org(auth_user.organization_id, &id)
+                .await
+        }
+    }
+    .map_err(|e| internal_error(format!("Failed to fetch user: {}", e)))?
+    .ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            Json(ApiError::new("not_found", "User not found")),
+        )
+    })?;
+
+    let repo = AccountLockoutRepository::new(&state.db);
+    let now = chrono::Utc::now();
+    let was_locked = repo
+        .get(user.id)
+        .await
+        .map_err(|e| internal_error(format!("Failed to get lockout: {}", e)))?
+        .is_some_and(|lockout| lockout.is_locked(now));
+    repo.clear(user.id)
+        .await
+        .map_err(|e| internal_error(format!("Failed to unlock user: {}", e)))?;
+
+    let _ = state
+        .audit_repository()
+        .insert(
+            user.organization_id,
+            Some(auth_user.user_id()),
+            "user.unlock",
+            "users",
+            Some(&user.id.to_string()),
+            Some(&serde_json::json!({
+                "username": user.username,
+                "was_locked": was_locked,
+            })),
+            None,
+        )
+        .await;
+
+    Ok(Json(UnlockUserResponse { was_locked }))
+}
@@ -0,0 +1,352 @@
+//! Outbound webhook endpoints
+//!
+//! Webhooks belong to an organization and are managed by its admins. Every
+//! delivery is kept in a log that can be listed per webhook, and any delivery
+//! can be sent again.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    routing::{get, post},
+    Json, Router,
+};
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    middleware::{AuditChange, AuthUser},
+    models::{
+        CreateWebhookRequest, CreatedWebhook, UpdateWebhookRequest, Webhook, WebhookDelivery,
+        WebhookDeliveryStatus, WebhookEvent,
+    },
+    services::webhooks::event_payload,
+    utils::AppError,
+    AppState,
+};
+
+/// Deliveries returned by default and at most
+const DEFAULT_DELIVERY_LIMIT: u32 = 50;
+const MAX_DELIVERY_LIMIT: u32 = 500;
+
+pub fn routes() -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_webhooks).post(create_webhook))
+        .route(
+            "/{id}",
+            get(get_webhook).put(update_webhook).delete(delete_webhook),
+        )
+        .route("/{id}/ping", post(ping_webhook))
+        .route("/{id}/deliveries", get(list_deliveries))
+        .route("/{id}/deliveries/{delivery_id}", get(get_delivery))
+        .route("/{id}/deliveries/{delivery_id}/redeliver", post(redeliver))
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct OrgQuery {
+    organization_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+struct DeliveriesQuery {
+    organization_id: Option<Uuid>,
+    /// Only deliveries in this state (pending, retrying, delivered, failed)
+    status: Option<WebhookDeliveryStatus>,
+    limit: Option<u32>,
+}
+
+fn resolve_org(auth_user: &AuthUser, requested: Option<Uuid>) -> Result<Uuid, AppError> {
+    match requested {
+        Some(_) if !auth_user.is_super_admin() => Err(AppError::forbidden(
+            "organization_id can only be specified by super_admin",
+        )),
+        Some(org_id) => Ok(org_id),
+        None => Ok(auth_user.organization_id),
+    }
+}
+
+fn require_admin(auth_user: &AuthUser) -> Result<(), AppError> {
+    if auth_user.is_super_admin() || auth_user.roles.iter().any(|r| r == "admin") {
+        Ok(())
+    } else {
+        Err(AppError::forbidden(
+            "Admin role required to manage webhooks",
+        ))
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), AppError> {
+    if name.trim().is_empty() {
+        return Err(AppError::validation("Webhook name cannot be empty"));
+    }
+    Ok(())
+}
+
+fn validate_url(url: &str) -> Result<(), AppError> {
+    let parsed =
+        reqwest::Url::parse(url).map_err(|_| AppError::validation("Webhook URL is not valid"))?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return Err(AppError::validation("Webhook URL must be an http(s) URL"));
+    }
+    Ok(())
+}
+
+fn validate_secret(secret: &str) -> Result<(), AppError> {
+    if secret.trim().is_empty() {
+        return Err(AppError::validation("Webhook secret cannot be empty"));
+    }
+    Ok(())
+}
+
+fn map_write_error(e: anyhow::Error, action: &str) -> AppError {
+    tracing::error!("Failed to {} webhook: {}", action, e);
+    if e.root_cause().to_string().contains("UNIQUE") {
+        AppError::conflict("A webhook with this name already exists")
+    } else {
+        AppError::internal(format!("Failed to {} webhook", action))
+    }
+}
+
+async fn load_webhook(state: &AppState, org_id: Uuid, id: Uuid) -> Result<Webhook, AppError> {
+    state
+        .webhooks
+        .repository()
+        .get_by_id(org_id, id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get webhook: {}", e);
+            AppError::internal("Failed to get webhook")
+        })?
+        .ok_or_else(|| AppError::not_found("Webhook not found"))
+}
+
+/// List the organization's webhooks
+async fn list_webhooks(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+) -> Result<Json<Vec<Webhook>>, AppError> {
+    require_admin(&auth_user)?;
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let webhooks = state
+        .webhooks
+        .repository()
+        .list(org_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list webhooks: {}", e);
+            AppError::internal("Failed to list webhooks")
+        })?;
+
+    Ok(Json(webhooks))
+}
+
+/// Create a webhook; the signing secret is only returned here
+async fn create_webhook(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Json(payload): Json<CreateWebhookRequest>,
+) -> Result<(StatusCode, AuditChange, Json<CreatedWebhook>), AppError> {
+    require_admin(&auth_user)?;
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    validate_name(&payload.name)?;
+    validate_url(&payload.url)?;
+    let secret = match &payload.secret {
+        Some(secret) => {
+            validate_secret(secret)?;
+            secret.clone()
+        }
+        None => hex::encode(rand::random::<[u8; 32]>()),
+    };
+
+    let webhook = state
+        .webhooks
+        .repository()
+        .create(org_id, Some(auth_user.user_id()), &payload, &secret)
+        .await
+        .map_err(|e| map_write_error(e, "create"))?;
+
+    Ok((
+        StatusCode::CREATED,
+        AuditChange::created(&webhook),
+        Json(CreatedWebhook { webhook, secret }),
+    ))
+}
+
+async fn get_webhook(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Webhook>, AppError> {
+    require_admin(&auth_user)?;
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    Ok(Json(load_webhook(&state, org_id, id).await?))
+}
+
+async fn update_webhook(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Path(id): Path<Uuid>,
+    Json(payload): Json<UpdateWebhookRequest>,
+) -> Result<(AuditChange, Json<Webhook>), AppError> {
+    require_admin(&auth_user)?;
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    if let Some(name) = &payload.name {
+        validate_name(name)?;
+    }
+    if let Some(url) = &payload.url {
+        validate_url(url)?;
+    }
+    if let Some(secret) = &payload.secret {
+        validate_secret(secret)?;
+    }
+    let before = load_webhook(&state, org_id, id).await?;
+
+    let webhook = state
+        .webhooks
+        .repository()
+        .update(org_id, id, &payload)
+        .await
+        .map_err(|e| map_write_error(e, "update"))?
+        .ok_or_else(|| AppError::not_found("Webhook not found"))?;
+
+    Ok((AuditChange::new(&before, &webhook), Json(webhook)))
+}
+
+async fn delete_webhook(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Path(id): Path<Uuid>,
+) -> Result<(StatusCode, AuditChange), AppError> {
+    require_admin(&auth_user)?;
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let existing = load_webhook(&state, org_id, id).await?;
+
+    let deleted = state
+        .webhooks
+        .repository()
+        .delete(org_id, id)
+        .await
+        .map_err(|e| map_write_error(e, "delete"))?;
+    if !deleted {
+        return Err(AppError::not_found("Webhook not found"));
+    }
+
+    Ok((StatusCode::NO_CONTENT, AuditChange::deleted(&existing)))
+}
+
+/// Queue a `ping` delivery to check the endpoint and its signature handling
+async fn ping_webhook(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Path(id): Path<Uuid>,
+) -> Result<(StatusCode, Json<WebhookDelivery>), AppError> {
+    require_admin(&auth_user)?;
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let webhook = load_webhook(&state, org_id, id).await?;
+
+    let payload = event_payload(
+        org_id,
+        WebhookEvent::Ping,
+        serde_json::json!({
+            "webhook_id": webhook.id,
+            "name": webhook.name,
+            "requested_by": auth_user.username,
+        }),
+    );
+    let delivery = state
+        .webhooks
+        .queue_delivery(&webhook, WebhookEvent::Ping, &payload)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to queue webhook ping: {}", e);
+            AppError::internal("Failed to queue webhook ping")
+        })?;
+
+    Ok((StatusCode::ACCEPTED, Json(delivery)))
+}
+
+/// Delivery log of a webhook, newest first
+async fn list_deliveries(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<DeliveriesQuery>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<WebhookDelivery>>, AppError> {
+    require_admin(&auth_user)?;
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let webhook = load_webhook(&state, org_id, id).await?;
+
+    let limit = query
+        .limit
+        .unwrap_or(DEFAULT_DELIVERY_LIMIT)
+        .clamp(1, MAX_DELIVERY_LIMIT);
+    let deliveries = state
+        .webhooks
+        .repository()
+        .list_deliveries(webhook.id, query.status, limit)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to list webhook deliveries: {}", e);
+            AppError::internal("Failed to list webhook deliveries")
+        })?;
+
+    Ok(Json(deliveries))
+}
+
+async fn load_delivery(
+    state: &AppState,
+    webhook_id: Uuid,
+    delivery_id: Uuid,
+) -> Result<WebhookDelivery, AppError> {
+    state
+        .webhooks
+        .repository()
+        .get_delivery(webhook_id, delivery_id)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to get webhook delivery: {}", e);
+            AppError::internal("Failed to get webhook delivery")
+        })?
+        .ok_or_else(|| AppError::not_found("Delivery not found"))
+}
+
+async fn get_delivery(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Path((id, delivery_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<WebhookDelivery>, AppError> {
+    require_admin(&auth_user)?;
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let webhook = load_webhook(&state, org_id, id).await?;
+    Ok(Json(load_delivery(&state, webhook.id, delivery_id).await?))
+}
+
+/// Send a logged delivery again as a new delivery with the same payload
+async fn redeliver(
+    State(state): State<AppState>,
+    auth_user: AuthUser,
+    Query(query): Query<OrgQuery>,
+    Path((id, delivery_id)): Path<(Uuid, Uuid)>,
+) -> Result<(StatusCode, Json<WebhookDelivery>), AppError> {
+    require_admin(&auth_user)?;
+    let org_id = resolve_org(&auth_user, query.organization_id)?;
+    let webhook = load_webhook(&state, org_id, id).await?;
+    let original = load_delivery(&state, webhook.id, delivery_id).await?;
+
+    let delivery = state
+        .webhooks
+        .queue_delivery(&webhook, original.event, &original.payload)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to queue webhook redelivery: {}", e);
+            AppError::internal("Failed to queue webhook redelivery")
+        })?;
+
+    Ok((StatusCode::ACCEPTED, Json(delivery)))
+}
@@ -19,6 +19,7 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use anyhow::Result;
+use openvox_webui::services::report_output::ReportOutputStore;
 use openvox_webui::AppConfig;
 use sqlx::sqlite::SqlitePoolOptions;
 use tracing::{error, info, warn, Level};
@@ -79,7 +80,7 @@ async fn main() -> Result<()> {
     info!("OpenVox WebUI - Scheduled Reports Runner");
 
     // Load configuration
-    let config = if let Some(path) = config_path {
+    let mut config = if let Some(path) = config_path {
         info!("Config file: {}", path.display());
         std::env::set_var("OPENVOX_CONFIG", path.to_str().unwrap_or(""));
         AppConfig::load()?
@@ -87,6 +88,7 @@ async fn main() -> Result<()> {
         info!("Using default configuration paths");
         AppConfig::load()?
     };
+    let secrets = config.resolve_secrets().await?;
 
     // Connect to database
     let db_url = &config.database.url;
@@ -101,6 +103,9 @@ async fn main() -> Result<()> {
     // Run migrations
     sqlx::migrate!("./migrations").run(&pool).await?;
 
+    // Delivery targets and the SMTP password are stored encrypted
+    let cipher = openvox_webui::services::settings_encryption::init(&config, &pool).await?;
+
     // Create PuppetDB client if configured
     let puppetdb = if let Some(ref puppetdb_config) = config.puppetdb {
         match openvox_webui::services::PuppetDbClient::new(puppetdb_config) {
@@ -122,16 +127,22 @@ async fn main() -> Result<()> {
     };
 
     // Create scheduler
-    let scheduler = openvox_webui::services::ReportScheduler::new(pool.clone(), puppetdb);
+    let scheduler = openvox_webui::services::ReportScheduler::new(
+        pool.clone(),
+        puppetdb,
+        Some(cipher.clone()),
+        secrets,
+    )
+    .with_output_store(ReportOutputStore::new(&config.reporting));
 
     if dry_run {
         info!("Dry run mode - showing what would be executed");
 
         use openvox_webui::db::repository::ReportScheduleRepository;
-        let repo = ReportScheduleRepository::new(&pool);
+        let repo = ReportScheduleRepository::new(&pool, Some(&cipher));
 
         if let Some(id) = schedule_id {
-            if let Some(schedule) = repo.get_by_id(id).await? {
+            if let Some(schedule) = repo.find_by_id(id).await? {
                 println!("Would execute schedule:");
                 println!("  ID: {}", schedule.id);
                 println!("  Report ID: {}", schedule.report_id);
@@ -184,8 +195,13 @@ async fn main() -> Result<()> {
         for result in &results {
             if result.success {
                 info!(
-                    "  [OK] Schedule {} completed in {}ms",
-                    result.schedule_id, result.execution_time_ms
+                    "  [OK] Schedule {} completed in {}ms{}",
+                    result.schedule_id,
+                    result.execution_time_ms,
+                    result
+                        .delivery_status
+                        .map(|s| format!(" (delivery: {})", s.as_str()))
+                        .unwrap_or_default()
                 );
             } else {
                 error!(
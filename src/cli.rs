@@ -0,0 +1,465 @@
+//! Operational subcommands
+//!
+//! `openvox-webui admin|db|config ...` runs a single maintenance task
+//! against the configured database and exits, so operators can recover a
+//! locked-out instance or check a configuration without starting the server
+//! or editing SQLite by hand.
+
+use std::io::BufRead;
+
+use anyhow::{bail, Context, Result};
+use uuid::Uuid;
+
+use crate::config::AppConfig;
+use crate::db::{self, AuditRepository, AuthSessionRepository, DbPool};
+use crate::models::{default_organization_uuid, AuthProvider};
+use crate::services::password_policy::PasswordPolicy;
+use crate::services::{AuthService, DbRbacService};
+
+/// Usage text for the subcommands, shown by `--help`
+pub const USAGE: &str = r#"SUBCOMMANDS:
+    admin reset-password <username> [--password <pw> | --password-stdin]
+                            Set a new password for a local user, sign them
+                            out everywhere and require a change at next
+                            login. Without a password one is generated and
+                            printed.
+    admin create-user <username> --email <email> [--role <name>]...
+                      [--organization <id>] [--password <pw> | --password-stdin]
+                            Create a local user (default role: viewer).
+                            Without a password one is generated and printed.
+    db migrate              Apply pending migrations to the main and
+                            inventory databases, then exit.
+    config validate         Load and validate the configuration, list the
+                            settings taken from environment variables and
+                            any warnings, then exit. Same as --check-config.
+    config print-effective  Print the configuration after environment
+                            overrides as YAML, with secrets redacted."#;
+
+/// Where a new password comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PasswordSource {
+    Argument(String),
+    Stdin,
+    Generate,
+}
+
+/// A parsed subcommand
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    ResetPassword {
+        username: String,
+        password: PasswordSource,
+    },
+    CreateUser {
+        username: String,
+        email: String,
+        roles: Vec<String>,
+        organization_id: Option<Uuid>,
+        password: PasswordSource,
+    },
+    DbMigrate,
+    ConfigValidate,
+    ConfigPrintEffective,
+}
+
+impl Command {
+    /// Parse the arguments after the program name
+    ///
+    /// Returns `Ok(None)` when they do not start with a subcommand, so the
+    /// server's own flags keep working.
+    pub fn parse(args: &[String]) -> Result<Option<Self>> {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let command = match args.as_slice() {
+            ["admin", "reset-password", rest @ ..] => {
+                let mut options = Options::parse(rest)?;
+                Command::ResetPassword {
+                    username: options.single_positional("username")?,
+                    password: options.password()?,
+                }
+            }
+            ["admin", "create-user", rest @ ..] => {
+                let mut options = Options::parse(rest)?;
+                let organization_id = options
+                    .take("--organization")
+                    .map(|id| Uuid::parse_str(&id).context("Invalid --organization ID"))
+                    .transpose()?;
+                Command::CreateUser {
+                    username: options.single_positional("username")?,
+                    email: options.take("--email").context("--email is required")?,
+                    roles: options.take_all("--role"),
+                    organization_id,
+                    password: options.password()?,
+                }
+            }
+            ["db", "migrate"] => Command::DbMigrate,
+            ["config", "validate"] => Command::ConfigValidate,
+            ["config", "print-effective"] => Command::ConfigPrintEffective,
+            ["admin" | "db" | "config", ..] => {
+                bail!("Unknown subcommand: {}\n\n{}", args.join(" "), USAGE)
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(command))
+    }
+}
+
+/// Flags and positional arguments of a subcommand
+struct Options {
+    positional: Vec<String>,
+    flags: Vec<(String, Option<String>)>,
+}
+
+impl Options {
+    fn parse(args: &[&str]) -> Result<Self> {
+        const VALUE_FLAGS: &[&str] = &["--password", "--email", "--role", "--organization"];
+        const SWITCHES: &[&str] = &["--password-stdin"];
+
+        let mut options = Options {
+            positional: Vec::new(),
+            flags: Vec::new(),
+        };
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            if VALUE_FLAGS.contains(arg) {
+                let value = iter
+                    .next()
+                    .with_context(|| format!("{} needs a value", arg))?;
+                options
+                    .flags
+                    .push((arg.to_string(), Some(value.to_string())));
+            } else if SWITCHES.contains(arg) {
+                options.flags.push((arg.to_string(), None));
+            } else if arg.starts_with("--") {
+                bail!("Unknown option: {}", arg);
+            } else {
+                options.positional.push(arg.to_string());
+            }
+        }
+        Ok(options)
+    }
+
+    fn take(&mut self, flag: &str) -> Option<String> {
+        let index = self.flags.iter().position(|(name, _)| name == flag)?;
+        self.flags.remove(index).1
+    }
+
+    fn take_all(&mut self, flag: &str) -> Vec<String> {
+        std::iter::from_fn(|| self.take(flag)).collect()
+    }
+
+    fn single_positional(&mut self, name: &str) -> Result<String> {
+        match self.positional.as_slice() {
+            [value] => Ok(value.clone()),
+            [] => bail!("Missing <{}>", name),
+            _ => bail!("Expected a single <{}>", name),
+        }
+    }
+
+    fn password(&mut self) -> Result<PasswordSource> {
+        let stdin = self
+            .flags
+            .iter()
+            .any(|(name, _)| name == "--password-stdin");
+        match (self.take("--password"), stdin) {
+            (Some(_), true) => bail!("Use either --password or --password-stdin"),
+            (Some(password), false) => Ok(PasswordSource::Argument(password)),
+            (None, true) => Ok(PasswordSource::Stdin),
+            (None, false) => Ok(PasswordSource::Generate),
+        }
+    }
+}
+
+/// Run a subcommand to completion
+pub async fn run(command: Command) -> Result<()> {
+    let mut config = AppConfig::load().context("Failed to load configuration")?;
+
+    // The database URL may be a secret reference
+    let needs_database = !matches!(
+        command,
+        Command::ConfigValidate | Command::ConfigPrintEffective
+    );
+    if needs_database {
+        config
+            .resolve_secrets()
+            .await
+            .context("Failed to resolve configuration secrets")?;
+    }
+
+    match command {
+        Command::ConfigValidate => {
+            match AppConfig::config_file_path().filter(|path| path.exists()) {
+                Some(path) => println!("Configuration file: {}", path.display()),
+                None => println!("Configuration file: none (using defaults)"),
+            }
+            let overridden = AppConfig::env_overridden_settings()?;
+            if !overridden.is_empty() {
+                println!("Set by environment variables:");
+                for setting in &overridden {
+                    println!("  {}", setting);
+                }
+            }
+            let warnings = config.warnings();
+            if !warnings.is_empty() {
+                println!("Warnings:");
+                for warning in &warnings {
+                    println!("  {}", warning);
+                }
+            }
+            println!("Configuration is valid");
+            Ok(())
+        }
+        Command::ConfigPrintEffective => {
+            let yaml = serde_norway::to_string(&config.redacted())
+                .context("Failed to serialize configuration")?;
+            print!("{}", yaml);
+            Ok(())
+        }
+        Command::DbMigrate => {
+            db::init_pool(&config.database)
+                .await
+                .context("Failed to migrate database")?;
+            println!("Database is up to date: {}", config.database.url);
+
+            let inventory = config.inventory.clone().unwrap_or_default();
+            db::init_inventory_pool(&inventory.database_url, &config.database)
+                .await
+                .context("Failed to migrate inventory database")?;
+            println!(
+                "Inventory database is up to date: {}",
+                inventory.database_url
+            );
+            Ok(())
+        }
+        Command::ResetPassword { username, password } => {
+            let pool = db::init_pool(&config.database)
+                .await
+                .context("Failed to open database")?;
+            reset_password(&pool, &config, &username, password).await
+        }
+        Command::CreateUser {
+            username,
+            email,
+            roles,
+            organization_id,
+            password,
+        } => {
+            let pool = db::init_pool(&config.database)
+                .await
+                .context("Failed to open database")?;
+            let organization_id = organization_id.unwrap_or_else(default_organization_uuid);
+            create_user(
+                &pool,
+                &config,
+                &username,
+                &email,
+                &roles,
+                organization_id,
+                password,
+            )
+            .await
+        }
+    }
+}
+
+async fn reset_password(
+    pool: &DbPool,
+    config: &AppConfig,
+    username: &str,
+    source: PasswordSource,
+) -> Result<()> {
+    let auth = AuthService::new(pool.clone());
+    let user = auth
+        .get_user_by_username(username)
+        .await?
+        .with_context(|| format!("User '{}' not found", username))?;
+    if user.auth_provider == AuthProvider::Saml {
+        bail!("User '{}' signs in through SAML only", username);
+    }
+    if user.is_service_account() {
+        bail!(
+            "User '{}' is a service account and authenticates with API keys",
+            username
+        );
+    }
+
+    let (password, generated) = new_password(pool, config, Some(user.id), username, source).await?;
+    auth.update_user(&user.id, None, None, Some(&password), None)
+        .await?;
+    auth.set_force_password_change(&user.id, true).await?;
+    let revoked = AuthSessionRepository::new(pool)
+        .revoke_all_for_user(user.id, None)
+        .await?;
+
+    let _ = AuditRepository::new(pool, None)
+        .insert(
+            user.organization_id,
+            None,
+            "user.password_reset",
+            "user",
+            Some(&user.id.to_string()),
+            Some(&serde_json::json!({
+                "username": user.username,
+                "via": "cli",
+                "sessions_revoked": revoked,
+            })),
+            None,
+        )
+        .await;
+
+    println!(
+        "Password of '{}' reset; {} session(s) revoked",
+        username, revoked
+    );
+    if generated {
+        println!("New password: {}", password);
+    }
+    println!("The password must be changed at next login");
+    Ok(())
+}
+
+async fn create_user(
+    pool: &DbPool,
+    config: &AppConfig,
+    username: &str,
+    email: &str,
+    roles: &[String],
+    organization_id: Uuid,
+    source: PasswordSource,
+) -> Result<()> {
+    let rbac = DbRbacService::new(pool.clone());
+    let mut role_ids = Vec::new();
+    for name in roles {
+        let role = rbac
+            .get_role_by_name(name)
+            .await?
+            .with_context(|| format!("Role '{}' not found", name))?;
+        role_ids.push(role.id);
+    }
+
+    let (password, generated) = new_password(pool, config, None, username, source).await?;
+    let primary_role = roles.first().map(String::as_str).unwrap_or("viewer");
+    let auth = AuthService::new(pool.clone());
+    let user = auth
+        .create_user_in_org(username, email, &password, primary_role, organization_id)
+        .await?;
+    if !role_ids.is_empty() {
+        rbac.assign_roles(&user.id, &role_ids).await?;
+    }
+    if generated {
+        auth.set_force_password_change(&user.id, true).await?;
+    }
+
+    let _ = AuditRepository::new(pool, None)
+        .insert(
+            organization_id,
+            None,
+            "user.create",
+            "user",
+            Some(&user.id.to_string()),
+            Some(&serde_json::json!({
+                "username": user.username,
+                "email": user.email,
+                "roles": roles,
+                "via": "cli",
+            })),
+            None,
+        )
+        .await;
+
+    println!("Created user '{}' ({})", username, user.id);
+    if generated {
+        println!("Password: {}", password);
+        println!("The password must be changed at next login");
+    }
+    Ok(())
+}
+
+/// The new password and whether it was generated
+///
+/// Supplied passwords must satisfy the password policy.
+async fn new_password(
+    pool: &DbPool,
+    config: &AppConfig,
+    user_id: Option<Uuid>,
+    username: &str,
+    source: PasswordSource,
+) -> Result<(String, bool)> {
+    let policy = PasswordPolicy::new(&config.auth);
+    let password = match source {
+        PasswordSource::Argument(password) => password,
+        PasswordSource::Stdin => {
+            let mut line = String::new();
+            std::io::stdin()
+                .lock()
+                .read_line(&mut line)
+                .context("Failed to read password from stdin")?;
+            line.trim_end_matches(['\r', '\n']).to_string()
+        }
+        PasswordSource::Generate => {
+            return match policy.generate(Some(username)) {
+                Some(password) => Ok((password, true)),
+                None => bail!("Could not generate a password that satisfies the password policy"),
+            };
+        }
+    };
+
+    let violations = policy
+        .validate(pool, user_id, Some(username), &password)
+        .await?;
+    if !violations.is_empty() {
+        bail!("Password rejected: {}", violations.join("; "));
+    }
+    Ok((password, false))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(str::to_string).collect()
+    }
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            Command::parse(&args("admin reset-password alice")).unwrap(),
+            Some(Command::ResetPassword {
+                username: "alice".to_string(),
+                password: PasswordSource::Generate,
+            })
+        );
+        assert_eq!(
+            Command::parse(&args(
+                "admin create-user bob --email bob@example.com --role admin --role operator --password-stdin"
+            ))
+            .unwrap(),
+            Some(Command::CreateUser {
+                username: "bob".to_string(),
+                email: "bob@example.com".to_string(),
+                roles: vec!["admin".to_string(), "operator".to_string()],
+                organization_id: None,
+                password: PasswordSource::Stdin,
+            })
+        );
+        assert_eq!(
+            Command::parse(&args("config print-effective")).unwrap(),
+            Some(Command::ConfigPrintEffective)
+        );
+        assert_eq!(Command::parse(&args("--fix-database")).unwrap(), None);
+        assert_eq!(Command::parse(&[]).unwrap(), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Command::parse(&args("admin create-user bob")).is_err());
+        assert!(Command::parse(&args("admin reset-password")).is_err());
+        assert!(Command::parse(&args("admin reset-password a b")).is_err());
+        assert!(Command::parse(&args("admin reset-password a --password")).is_err());
+        assert!(Command::parse(&args(
+            "admin reset-password a --password x --password-stdin"
+        ))
+        .is_err());
+        assert!(Command::parse(&args("db drop")).is_err());
+    }
+}
@@ -10,7 +10,11 @@
 
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+
+use crate::middleware::RateLimitConfig;
 
 /// Main application configuration
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -60,6 +64,730 @@ pub struct AppConfig {
     /// Pagination defaults for list endpoints (nodes, facts)
     #[serde(default)]
     pub pagination: PaginationConfig,
+    /// External secrets provider for `secret:` references in configuration
+    #[serde(default)]
+    pub secrets: Option<SecretsConfig>,
+    /// Encryption of sensitive values stored in the settings table
+    #[serde(default)]
+    pub settings_encryption: SettingsEncryptionConfig,
+    /// Audit log forwarding and retention
+    #[serde(default)]
+    pub audit: AuditConfig,
+    /// Request rate limits per client, user or API key
+    #[serde(default)]
+    pub rate_limit: RateLimitsConfig,
+    /// Reloading of the safe-to-change settings while running
+    #[serde(default)]
+    pub hot_reload: HotReloadConfig,
+    /// Delivery of outbound webhooks
+    #[serde(default)]
+    pub webhooks: WebhooksConfig,
+    /// Retention of deleted groups, users and saved reports
+    #[serde(default)]
+    pub recycle_bin: RecycleBinConfig,
+    /// Dependency probes of `/health/detailed`
+    #[serde(default)]
+    pub health: HealthConfig,
+    /// Retention of in-app notifications
+    #[serde(default)]
+    pub notifications: NotificationsConfig,
+    /// Storage and retention of report executions
+    #[serde(default)]
+    pub reporting: ReportingConfig,
+    /// Near real-time ingestion of new Puppet reports
+    #[serde(default)]
+    pub report_ingestion: ReportIngestionConfig,
+    /// Defaults applied to newly created organizations
+    #[serde(default)]
+    pub organizations: OrganizationsConfig,
+}
+
+/// Request rate limits
+///
+/// Authenticated requests are counted per API key or user, public endpoints
+/// per client address.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RateLimitsConfig {
+    /// Limit for the authenticated API
+    #[serde(default = "crate::middleware::api_rate_limit_config")]
+    pub api: RateLimitConfig,
+    /// Stricter limit for login and the other public endpoints
+    #[serde(default = "crate::middleware::auth_rate_limit_config")]
+    pub auth: RateLimitConfig,
+    /// Additional limit for expensive endpoints, selected by `paths`
+    #[serde(default = "crate::middleware::expensive_rate_limit_config")]
+    pub expensive: RateLimitConfig,
+}
+
+impl Default for RateLimitsConfig {
+    fn default() -> Self {
+        Self {
+            api: crate::middleware::api_rate_limit_config(),
+            auth: crate::middleware::auth_rate_limit_config(),
+            expensive: crate::middleware::expensive_rate_limit_config(),
+        }
+    }
+}
+
+/// Reloading of the safe-to-change settings while running
+///
+/// The log level, dashboard, cache, rate limit and custom RBAC role settings
+/// are applied when the configuration file or groups.yaml changes, or on
+/// `POST /api/v1/settings/reload`. Other settings need a restart.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HotReloadConfig {
+    /// Watch the configuration files for changes
+    #[serde(default = "default_true_val")]
+    pub watch: bool,
+    /// How often the files are checked for changes
+    #[serde(default = "default_hot_reload_poll_interval")]
+    pub poll_interval_secs: u64,
+}
+
+fn default_hot_reload_poll_interval() -> u64 {
+    5
+}
+
+impl Default for HotReloadConfig {
+    fn default() -> Self {
+        Self {
+            watch: true,
+            poll_interval_secs: default_hot_reload_poll_interval(),
+        }
+    }
+}
+
+/// Delivery of outbound webhooks
+///
+/// Webhooks themselves are managed through `/api/v1/webhooks`; these settings
+/// control how their deliveries are sent, retried and kept.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WebhooksConfig {
+    /// Timeout of a single delivery attempt
+    #[serde(default = "default_webhook_timeout")]
+    pub timeout_secs: u64,
+    /// Attempts before a delivery is given up as failed
+    #[serde(default = "default_webhook_max_attempts")]
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled on each further attempt
+    #[serde(default = "default_webhook_retry_delay")]
+    pub retry_delay_secs: u64,
+    /// Upper bound of the retry delay
+    #[serde(default = "default_webhook_max_retry_delay")]
+    pub max_retry_delay_secs: u64,
+    /// How often PuppetDB is checked for failed runs (0 disables the
+    /// `node.failed_run` event)
+    #[serde(default = "default_webhook_failed_run_poll")]
+    pub failed_run_poll_secs: u64,
+    /// Delete finished deliveries older than this many days
+    #[serde(default = "default_webhook_retention_days")]
+    pub retention_days: u32,
+}
+
+fn default_webhook_timeout() -> u64 {
+    10
+}
+
+fn default_webhook_max_attempts() -> u32 {
+    8
+}
+
+fn default_webhook_retry_delay() -> u64 {
+    30
+}
+
+fn default_webhook_max_retry_delay() -> u64 {
+    3600
+}
+
+fn default_webhook_failed_run_poll() -> u64 {
+    300
+}
+
+fn default_webhook_retention_days() -> u32 {
+    30
+}
+
+impl Default for WebhooksConfig {
+    fn default() -> Self {
+        Self {
+            timeout_secs: default_webhook_timeout(),
+            max_attempts: default_webhook_max_attempts(),
+            retry_delay_secs: default_webhook_retry_delay(),
+            max_retry_delay_secs: default_webhook_max_retry_delay(),
+            failed_run_poll_secs: default_webhook_failed_run_poll(),
+            retention_days: default_webhook_retention_days(),
+        }
+    }
+}
+
+/// Recycle bin of soft-deleted records
+///
+/// Deleted node groups, users and saved reports can be restored through
+/// `/api/v1/recycle-bin` until they are purged for good.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RecycleBinConfig {
+    /// Purge deleted records after this many days
+    #[serde(default = "default_recycle_bin_retention_days")]
+    pub retention_days: u32,
+}
+
+fn default_recycle_bin_retention_days() -> u32 {
+    30
+}
+
+impl Default for RecycleBinConfig {
+    fn default() -> Self {
+        Self {
+            retention_days: default_recycle_bin_retention_days(),
+        }
+    }
+}
+
+/// Retention of in-app notifications
+///
+/// A background task deletes notifications once an hour: read and dismissed
+/// ones after `read_retention_days`, unread ones after `retention_days`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NotificationsConfig {
+    /// Delete read and dismissed notifications after this many days
+    #[serde(default = "default_notification_read_retention_days")]
+    pub read_retention_days: u32,
+    /// Delete unread notifications after this many days
+    #[serde(default = "default_notification_retention_days")]
+    pub retention_days: u32,
+}
+
+fn default_notification_read_retention_days() -> u32 {
+    30
+}
+
+fn default_notification_retention_days() -> u32 {
+    90
+}
+
+impl Default for NotificationsConfig {
+    fn default() -> Self {
+        Self {
+            read_retention_days: default_notification_read_retention_days(),
+            retention_days: default_notification_retention_days(),
+        }
+    }
+}
+
+/// Storage and retention of report executions
+///
+/// Execution outputs larger than `offload_threshold_bytes` are written to
+/// files under `output_dir` instead of the database. A background task
+/// deletes executions and their files once an hour after
+/// `execution_retention_days`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReportingConfig {
+    /// Directory holding offloaded execution outputs
+    #[serde(default = "default_report_output_dir")]
+    pub output_dir: PathBuf,
+    /// Store outputs larger than this many bytes as files
+    #[serde(default = "default_report_offload_threshold_bytes")]
+    pub offload_threshold_bytes: usize,
+    /// Delete executions after this many days
+    #[serde(default = "default_report_execution_retention_days")]
+    pub execution_retention_days: u32,
+}
+
+fn default_report_output_dir() -> PathBuf {
+    PathBuf::from("/var/lib/openvox-webui/reports")
+}
+
+fn default_report_offload_threshold_bytes() -> usize {
+    1024 * 1024
+}
+
+fn default_report_execution_retention_days() -> u32 {
+    90
+}
+
+impl Default for ReportingConfig {
+    fn default() -> Self {
+        Self {
+            output_dir: default_report_output_dir(),
+            offload_threshold_bytes: default_report_offload_threshold_bytes(),
+            execution_retention_days: default_report_execution_retention_days(),
+        }
+    }
+}
+
+/// Near real-time ingestion of new Puppet reports
+///
+/// When enabled, a background task follows the reports PuppetDB receives with
+/// a persisted high-water mark on `receive_time`, every `poll_interval_secs`.
+/// New reports refresh the cached smart list counts and, when a run failed or
+/// a node changed status, evaluate the node status and report failure alert
+/// rules, so alerts fire within seconds of a run finishing.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReportIngestionConfig {
+    /// Run the ingester
+    #[serde(default)]
+    pub enabled: bool,
+    /// How often PuppetDB is polled for new reports
+    #[serde(default = "default_report_ingestion_poll_interval")]
+    pub poll_interval_secs: u64,
+    /// Reports fetched per query; a full batch is followed by another query
+    /// right away
+    #[serde(default = "default_report_ingestion_batch_size")]
+    pub batch_size: u32,
+    /// Evaluate alert rules when new reports call for it
+    #[serde(default = "default_true_val")]
+    pub evaluate_alerts: bool,
+}
+
+fn default_report_ingestion_poll_interval() -> u64 {
+    10
+}
+
+fn default_report_ingestion_batch_size() -> u32 {
+    500
+}
+
+impl Default for ReportIngestionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            poll_interval_secs: default_report_ingestion_poll_interval(),
+            batch_size: default_report_ingestion_batch_size(),
+            evaluate_alerts: true,
+        }
+    }
+}
+
+/// Defaults applied to newly created organizations
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct OrganizationsConfig {
+    /// Node groups created in every new organization, parents first; an
+    /// empty list creates none
+    #[serde(default = "default_group_templates")]
+    pub group_templates: Vec<GroupTemplate>,
+}
+
+impl Default for OrganizationsConfig {
+    fn default() -> Self {
+        Self {
+            group_templates: default_group_templates(),
+        }
+    }
+}
+
+/// Node group created in every new organization
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GroupTemplate {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Name of a template listed before this one
+    #[serde(default)]
+    pub parent: Option<String>,
+    #[serde(default)]
+    pub environment: Option<String>,
+    #[serde(default)]
+    pub is_environment_group: bool,
+    #[serde(default)]
+    pub match_all_nodes: bool,
+    #[serde(default)]
+    pub rule_match_type: crate::models::RuleMatchType,
+    /// Classes in Puppet Enterprise format: {"class_name": {"param": "value"}}
+    #[serde(default = "empty_json_object")]
+    pub classes: serde_json::Value,
+    #[serde(default = "empty_json_object")]
+    pub variables: serde_json::Value,
+    #[serde(default)]
+    pub rules: Vec<crate::models::CreateRuleRequest>,
+}
+
+fn empty_json_object() -> serde_json::Value {
+    serde_json::json!({})
+}
+
+/// All Nodes, with Production and Development below it
+///
+/// The defaults only lay out the hierarchy: none of them match nodes or
+/// assign classes, since a node matched in two organizations is a
+/// classification conflict.
+fn default_group_templates() -> Vec<GroupTemplate> {
+    let group = |name: &str, description: String, parent: Option<&str>| GroupTemplate {
+        name: name.to_string(),
+        description: Some(description),
+        parent: parent.map(str::to_string),
+        environment: None,
+        is_environment_group: false,
+        match_all_nodes: false,
+        rule_match_type: crate::models::RuleMatchType::All,
+        classes: empty_json_object(),
+        variables: empty_json_object(),
+        rules: Vec::new(),
+    };
+    let environment_group = |name: &str, environment: &str| GroupTemplate {
+        environment: Some(environment.to_string()),
+        ..group(
+            name,
+            format!("Nodes in the {} environment", environment),
+            Some("All Nodes"),
+        )
+    };
+
+    vec![
+        group("All Nodes", "Root group for all nodes".to_string(), None),
+        environment_group("Production", "production"),
+        environment_group("Development", "development"),
+    ]
+}
+
+/// Dependency probes of `/health/detailed`
+///
+/// A component that answers slower than its budget is reported as degraded;
+/// one that does not answer within `timeout_ms` is unhealthy.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct HealthConfig {
+    /// Give up on a probe after this long
+    #[serde(default = "default_health_timeout")]
+    pub timeout_ms: u64,
+    /// Latency budget of the database
+    #[serde(default = "default_health_database_budget")]
+    pub database_budget_ms: u64,
+    /// Latency budget of PuppetDB
+    #[serde(default = "default_health_remote_budget")]
+    pub puppetdb_budget_ms: u64,
+    /// Latency budget of the Puppet CA
+    #[serde(default = "default_health_remote_budget")]
+    pub puppet_ca_budget_ms: u64,
+    /// The scheduler is unhealthy when it has not run for this long
+    #[serde(default = "default_health_scheduler_max_age")]
+    pub scheduler_max_age_secs: u64,
+    /// What `/health/ready` waits for
+    #[serde(default)]
+    pub readiness: ReadinessConfig,
+}
+
+/// Conditions of `/health/ready`, besides a reachable database
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReadinessConfig {
+    /// Wait until all database migrations, including the inventory data
+    /// migration, have been applied
+    #[serde(default = "default_true_val")]
+    pub migrations: bool,
+    /// Wait until the built-in RBAC roles exist
+    #[serde(default = "default_true_val")]
+    pub rbac_seed: bool,
+    /// Wait until the search index has been built from PuppetDB once
+    #[serde(default)]
+    pub cache_warmup: bool,
+}
+
+impl Default for ReadinessConfig {
+    fn default() -> Self {
+        Self {
+            migrations: true,
+            rbac_seed: true,
+            cache_warmup: false,
+        }
+    }
+}
+
+fn default_health_timeout() -> u64 {
+    5000
+}
+
+fn default_health_database_budget() -> u64 {
+    250
+}
+
+fn default_health_remote_budget() -> u64 {
+    1000
+}
+
+fn default_health_scheduler_max_age() -> u64 {
+    300
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            timeout_ms: default_health_timeout(),
+            database_budget_ms: default_health_database_budget(),
+            puppetdb_budget_ms: default_health_remote_budget(),
+            puppet_ca_budget_ms: default_health_remote_budget(),
+            scheduler_max_age_secs: default_health_scheduler_max_age(),
+            readiness: ReadinessConfig::default(),
+        }
+    }
+}
+
+/// Audit log forwarding and retention
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct AuditConfig {
+    /// External destinations every audit entry is also sent to
+    #[serde(default)]
+    pub sinks: Vec<AuditSinkConfig>,
+    /// Delete entries older than this many days (kept forever if unset)
+    #[serde(default)]
+    pub retention_days: Option<u32>,
+    /// Directory that expired entries are written to (one JSON Lines file
+    /// per pruning run) before they are deleted
+    #[serde(default)]
+    pub archive_dir: Option<PathBuf>,
+}
+
+/// An external destination for audit entries (syslog or an HTTP collector)
+///
+/// Entries are queued in memory and sent in batches by a background task, so
+/// a slow or unreachable destination never blocks the request that wrote the
+/// entry. Failed batches are retried with exponential backoff and dropped
+/// (with an error in the log) once `max_retries` is exhausted.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AuditSinkConfig {
+    /// Name used in log messages (defaults to the sink type)
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(flatten)]
+    pub target: AuditSinkTarget,
+    /// Entries queued while the destination is slow or unreachable; new
+    /// entries are dropped when the queue is full
+    #[serde(default = "default_audit_sink_buffer_size")]
+    pub buffer_size: usize,
+    /// Entries sent per request or connection
+    #[serde(default = "default_audit_sink_batch_size")]
+    pub batch_size: usize,
+    /// Retries of a failed batch before it is dropped
+    #[serde(default = "default_audit_sink_max_retries")]
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled on each further attempt
+    #[serde(default = "default_audit_sink_retry_delay")]
+    pub retry_delay_secs: u64,
+}
+
+fn default_audit_sink_buffer_size() -> usize {
+    10_000
+}
+
+fn default_audit_sink_batch_size() -> usize {
+    100
+}
+
+fn default_audit_sink_max_retries() -> u32 {
+    5
+}
+
+fn default_audit_sink_retry_delay() -> u64 {
+    2
+}
+
+/// Where and how an audit sink delivers entries
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum AuditSinkTarget {
+    /// Syslog server
+    Syslog {
+        /// Server address (host:port)
+        address: String,
+        #[serde(default)]
+        protocol: SyslogProtocol,
+        #[serde(default)]
+        format: SyslogFormat,
+        /// Syslog facility (default 13, "log audit")
+        #[serde(default = "default_syslog_facility")]
+        facility: u8,
+        /// APP-NAME of the syslog messages
+        #[serde(default = "default_syslog_app_name")]
+        app_name: String,
+    },
+    /// HTTP collector
+    Http {
+        /// Collector endpoint, e.g. `https://splunk:8088/services/collector/event`
+        /// or `https://elastic:9200/_bulk`
+        url: String,
+        #[serde(default)]
+        format: AuditHttpFormat,
+        /// HEC token, Elastic API key or bearer token (may be a `secret:`
+        /// reference)
+        #[serde(default)]
+        token: Option<String>,
+        /// Splunk index or Elasticsearch index (default "openvox-audit" for
+        /// Elasticsearch)
+        #[serde(default)]
+        index: Option<String>,
+        /// Verify the collector's TLS certificate
+        #[serde(default = "default_true_val")]
+        ssl_verify: bool,
+    },
+}
+
+fn default_syslog_facility() -> u8 {
+    13
+}
+
+fn default_syslog_app_name() -> String {
+    "openvox-webui".to_string()
+}
+
+/// Syslog transport
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SyslogProtocol {
+    #[default]
+    Udp,
+    /// TCP with octet-counting framing (RFC 6587)
+    Tcp,
+}
+
+/// Syslog message format
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SyslogFormat {
+    /// RFC 5424 with the entry in structured data and details as message
+    #[default]
+    Rfc5424,
+    /// ArcSight Common Event Format inside an RFC 5424 message
+    Cef,
+}
+
+/// Payload format of an HTTP audit sink
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditHttpFormat {
+    /// JSON array of entries, token sent as bearer token
+    #[default]
+    Json,
+    /// Splunk HTTP Event Collector
+    SplunkHec,
+    /// Elasticsearch bulk API
+    Elastic,
+}
+
+/// Master key for sensitive settings stored in the database
+///
+/// Values such as the SMTP password are encrypted with a key derived from
+/// `master_key` (or the contents of `master_key_file`). When neither is set a
+/// random key is generated next to the SQLite database on first start.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SettingsEncryptionConfig {
+    /// Master key (may be a `secret:` reference)
+    #[serde(default)]
+    pub master_key: Option<String>,
+    /// File containing the master key
+    #[serde(default)]
+    pub master_key_file: Option<PathBuf>,
+}
+
+/// External secrets provider configuration
+///
+/// Sensitive values (e.g. `auth.jwt_secret`, `database.url`,
+/// `code_deploy.encryption_key`, the SMTP password) may be written as
+/// `secret:<path>#<key>` and are resolved from the provider at startup
+/// instead of being stored in plaintext.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SecretsConfig {
+    /// Which provider resolves secret references
+    pub provider: SecretsProviderKind,
+    /// HashiCorp Vault settings (provider: vault)
+    #[serde(default)]
+    pub vault: Option<VaultSecretsConfig>,
+    /// Executable provider settings (provider: exec)
+    #[serde(default)]
+    pub exec: Option<ExecSecretsConfig>,
+    /// How long resolved values are cached, in seconds
+    #[serde(default = "default_secrets_cache_ttl_secs")]
+    pub cache_ttl_secs: u64,
+}
+
+/// Supported external secrets providers
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SecretsProviderKind {
+    /// HashiCorp Vault KV secrets engine
+    Vault,
+    /// External command printing the secret on stdout
+    Exec,
+}
+
+/// HashiCorp Vault provider configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct VaultSecretsConfig {
+    /// Vault address (e.g. https://vault.example.com:8200)
+    pub address: String,
+    /// Vault Enterprise namespace
+    #[serde(default)]
+    pub namespace: Option<String>,
+    /// Static token (falls back to `token_file`, then `VAULT_TOKEN`)
+    #[serde(default)]
+    pub token: Option<String>,
+    /// File containing the token (e.g. written by Vault Agent)
+    #[serde(default)]
+    pub token_file: Option<PathBuf>,
+    /// AppRole authentication instead of a static token
+    #[serde(default)]
+    pub approle: Option<VaultAppRoleConfig>,
+    /// Mount point of the KV secrets engine
+    #[serde(default = "default_vault_kv_mount")]
+    pub kv_mount: String,
+    /// KV secrets engine version (1 or 2)
+    #[serde(default = "default_vault_kv_version")]
+    pub kv_version: u8,
+    /// CA bundle used to verify the Vault server certificate
+    #[serde(default)]
+    pub ca_cert: Option<PathBuf>,
+    /// Request timeout in seconds
+    #[serde(default = "default_vault_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+/// Vault AppRole credentials
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct VaultAppRoleConfig {
+    pub role_id: String,
+    #[serde(default)]
+    pub secret_id: Option<String>,
+    /// File containing the secret ID
+    #[serde(default)]
+    pub secret_id_file: Option<PathBuf>,
+    /// AppRole auth mount point
+    #[serde(default = "default_vault_approle_mount")]
+    pub mount: String,
+}
+
+/// Executable secrets provider configuration
+///
+/// The command is run with `args` followed by the reference path and key,
+/// and must print the secret value on stdout.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ExecSecretsConfig {
+    pub command: PathBuf,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Maximum run time in seconds
+    #[serde(default = "default_exec_secrets_timeout_secs")]
+    pub timeout_secs: u64,
+}
+
+fn default_secrets_cache_ttl_secs() -> u64 {
+    300
+}
+
+fn default_vault_kv_mount() -> String {
+    "secret".to_string()
+}
+
+fn default_vault_kv_version() -> u8 {
+    2
+}
+
+fn default_vault_timeout_secs() -> u64 {
+    10
+}
+
+fn default_vault_approle_mount() -> String {
+    "approle".to_string()
+}
+
+fn default_exec_secrets_timeout_secs() -> u64 {
+    10
 }
 
 /// Pagination configuration for list endpoints
@@ -122,12 +850,340 @@ pub struct ServerConfig {
     /// TLS/HTTPS configuration (if not set, server runs HTTP)
     #[serde(default)]
     pub tls: Option<TlsConfig>,
+    /// HTTP/3 (QUIC) listener next to the HTTPS server
+    #[serde(default)]
+    pub http3: Option<Http3Config>,
     /// Path to static files directory (frontend build output)
     #[serde(default = "default_static_dir")]
     pub static_dir: Option<PathBuf>,
     /// Whether to serve the frontend SPA (enables fallback to index.html)
     #[serde(default = "default_serve_frontend")]
     pub serve_frontend: bool,
+    /// Caching and precompression of the served frontend assets
+    #[serde(default)]
+    pub static_assets: StaticAssetsConfig,
+    /// URL path prefix the WebUI is served under behind a reverse proxy
+    /// (e.g. "/puppet"); empty to serve from the root
+    #[serde(default)]
+    pub base_path: String,
+    /// Additional listeners (replaces `host`/`port` when non-empty)
+    #[serde(default)]
+    pub listeners: Vec<ListenerConfig>,
+    /// Dedicated listener for the Puppet ENC endpoints
+    #[serde(default)]
+    pub enc_listener: Option<EncListenerConfig>,
+    /// Unix domain socket serving the application over plain HTTP
+    #[serde(default)]
+    pub unix_socket: Option<UnixSocketConfig>,
+    /// Reverse proxies (addresses or CIDR ranges) whose `X-Forwarded-For`
+    /// and `X-SSL-Client-*` headers are honored
+    #[serde(default = "default_trusted_proxies")]
+    pub trusted_proxies: Vec<String>,
+    /// Security headers added to every response
+    #[serde(default)]
+    pub security_headers: SecurityHeadersConfig,
+    /// Largest request bodies accepted, globally and per route
+    #[serde(default)]
+    pub body_limits: BodyLimitsConfig,
+    /// GraphQL read API at `/api/graphql`
+    #[serde(default)]
+    pub graphql: Option<GraphqlConfig>,
+}
+
+fn default_trusted_proxies() -> Vec<String> {
+    vec!["127.0.0.1".to_string(), "::1".to_string()]
+}
+
+/// Unix domain socket for a local reverse proxy
+///
+/// When set and `listeners` is empty, no TCP port is opened for the
+/// application; `host`/`port` are ignored.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UnixSocketConfig {
+    /// Socket path; a stale socket left by a previous run is replaced
+    pub path: PathBuf,
+    /// Octal permission bits of the socket file
+    #[serde(default = "default_unix_socket_mode")]
+    pub mode: String,
+    /// Group owning the socket file (name or numeric ID), e.g. the reverse
+    /// proxy's group
+    #[serde(default)]
+    pub group: Option<String>,
+}
+
+fn default_unix_socket_mode() -> String {
+    "0660".to_string()
+}
+
+impl UnixSocketConfig {
+    /// Permission bits parsed from `mode`
+    pub fn mode_bits(&self) -> Result<u32> {
+        let digits = self.mode.trim();
+        let digits = digits.strip_prefix("0o").unwrap_or(digits);
+        u32::from_str_radix(digits, 8)
+            .ok()
+            .filter(|bits| *bits <= 0o777)
+            .with_context(|| {
+                format!(
+                    "Invalid unix socket mode '{}': expected octal permissions such as 0660",
+                    self.mode
+                )
+            })
+    }
+}
+
+/// Dedicated listener for the unauthenticated ENC endpoints
+///
+/// Serves only node classification, the node environment lookup and health
+/// checks, so it can be firewalled to the Puppet Server network.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct EncListenerConfig {
+    #[serde(flatten)]
+    pub listener: ListenerConfig,
+    /// Remove the ENC endpoints from the main listeners
+    #[serde(default = "default_true_val")]
+    pub exclusive: bool,
+}
+
+/// A single address the server listens on
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ListenerConfig {
+    /// IPv4 or IPv6 address (e.g. "0.0.0.0", "::", "[::1]")
+    pub host: String,
+    pub port: u16,
+    /// TLS for this listener; falls back to `server.tls` when not set
+    #[serde(default)]
+    pub tls: Option<TlsConfig>,
+    /// Serve plain HTTP even when `server.tls` is configured
+    #[serde(default)]
+    pub plaintext: bool,
+}
+
+impl ListenerConfig {
+    /// Socket address of the listener
+    pub fn socket_addr(&self) -> Result<SocketAddr> {
+        parse_listen_addr(&self.host, self.port)
+    }
+}
+
+/// Parse a listen address, accepting bare or bracketed IPv6 hosts
+pub fn parse_listen_addr(host: &str, port: u16) -> Result<SocketAddr> {
+    let host = host.trim();
+    let ip: IpAddr = host
+        .strip_prefix('[')
+        .and_then(|h| h.strip_suffix(']'))
+        .unwrap_or(host)
+        .parse()
+        .with_context(|| format!("Invalid listen address '{}': expected an IP address", host))?;
+    Ok(SocketAddr::new(ip, port))
+}
+
+impl ServerConfig {
+    /// Base path with a leading slash and no trailing slash ("" for the root)
+    pub fn normalized_base_path(&self) -> String {
+        let trimmed = self.base_path.trim().trim_matches('/');
+        if trimmed.is_empty() {
+            String::new()
+        } else {
+            format!("/{}", trimmed)
+        }
+    }
+
+    /// Prefix an absolute application path (e.g. "/login") with the base path
+    pub fn prefixed_path(&self, path: &str) -> String {
+        format!("{}{}", self.normalized_base_path(), path)
+    }
+
+    /// Listeners to bind, with TLS resolved
+    ///
+    /// Without `listeners`, this is the single `host`/`port` listener, or
+    /// none when `unix_socket` is set. Listeners without their own `tls` use
+    /// `server.tls` unless `plaintext`.
+    pub fn effective_listeners(&self) -> Vec<ListenerConfig> {
+        if self.listeners.is_empty() && self.unix_socket.is_some() {
+            return Vec::new();
+        }
+        if self.listeners.is_empty() {
+            return vec![ListenerConfig {
+                host: self.host.clone(),
+                port: self.port,
+                tls: self.tls.clone(),
+                plaintext: false,
+            }];
+        }
+
+        self.listeners
+            .iter()
+            .map(|listener| self.with_inherited_tls(listener))
+            .collect()
+    }
+
+    /// The ENC listener, with TLS resolved like [`Self::effective_listeners`]
+    pub fn effective_enc_listener(&self) -> Option<ListenerConfig> {
+        self.enc_listener
+            .as_ref()
+            .map(|enc| self.with_inherited_tls(&enc.listener))
+    }
+
+    /// Whether the main listeners must not serve the ENC endpoints
+    pub fn enc_endpoints_exclusive(&self) -> bool {
+        self.enc_listener.as_ref().is_some_and(|enc| enc.exclusive)
+    }
+
+    fn with_inherited_tls(&self, listener: &ListenerConfig) -> ListenerConfig {
+        let mut listener = listener.clone();
+        if listener.plaintext {
+            listener.tls = None;
+        } else if listener.tls.is_none() {
+            listener.tls = self.tls.clone();
+        }
+        listener
+    }
+}
+
+/// Static frontend asset delivery configuration
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StaticAssetsConfig {
+    /// Serve `.br`/`.gz` siblings produced at build time when the client accepts them
+    #[serde(default = "default_static_precompressed")]
+    pub precompressed: bool,
+    /// `max-age` for fingerprinted (content-hashed) assets, in seconds
+    #[serde(default = "default_immutable_max_age_secs")]
+    pub immutable_max_age_secs: u64,
+}
+
+impl Default for StaticAssetsConfig {
+    fn default() -> Self {
+        Self {
+            precompressed: default_static_precompressed(),
+            immutable_max_age_secs: default_immutable_max_age_secs(),
+        }
+    }
+}
+
+/// Security header overrides
+///
+/// The defaults suit the bundled UI served from this server. Sites embedding
+/// the UI in another application or loading assets from a CDN can relax the
+/// framing and Content-Security-Policy rules here.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SecurityHeadersConfig {
+    /// `Strict-Transport-Security` max-age in seconds (0 omits the header)
+    #[serde(default = "default_hsts_max_age_secs")]
+    pub hsts_max_age_secs: u64,
+    /// Add `includeSubDomains` to `Strict-Transport-Security`
+    #[serde(default = "default_true_val")]
+    pub hsts_include_subdomains: bool,
+    /// Add `preload` to `Strict-Transport-Security`
+    #[serde(default)]
+    pub hsts_preload: bool,
+    /// Sources allowed to frame the UI (CSP `frame-ancestors`)
+    ///
+    /// `X-Frame-Options` is sent only for `'self'` (SAMEORIGIN) and `'none'`
+    /// (DENY), since it cannot express other origins.
+    #[serde(default = "default_frame_ancestors")]
+    pub frame_ancestors: Vec<String>,
+    /// `Referrer-Policy` value
+    #[serde(default = "default_referrer_policy")]
+    pub referrer_policy: String,
+    /// `Permissions-Policy` value (defaults to denying device APIs)
+    #[serde(default)]
+    pub permissions_policy: Option<String>,
+    /// Content-Security-Policy directives replacing or adding to the
+    /// defaults, e.g. `script-src: "'self' https://cdn.example.com"`; an empty
+    /// value removes a directive
+    #[serde(default)]
+    pub content_security_policy: BTreeMap<String, String>,
+    /// Send the policy as `Content-Security-Policy-Report-Only`
+    #[serde(default)]
+    pub csp_report_only: bool,
+}
+
+impl Default for SecurityHeadersConfig {
+    fn default() -> Self {
+        Self {
+            hsts_max_age_secs: default_hsts_max_age_secs(),
+            hsts_include_subdomains: true,
+            hsts_preload: false,
+            frame_ancestors: default_frame_ancestors(),
+            referrer_policy: default_referrer_policy(),
+            permissions_policy: None,
+            content_security_policy: BTreeMap::new(),
+            csp_report_only: false,
+        }
+    }
+}
+
+fn default_hsts_max_age_secs() -> u64 {
+    // One year, the minimum for HSTS preload lists
+    31_536_000
+}
+
+fn default_frame_ancestors() -> Vec<String> {
+    vec!["'self'".to_string()]
+}
+
+fn default_referrer_policy() -> String {
+    "strict-origin-when-cross-origin".to_string()
+}
+
+/// Request body size limits
+///
+/// Requests with a larger body are rejected with `413 Payload Too Large`
+/// before it is read into memory.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BodyLimitsConfig {
+    /// Limit for routes without a more specific entry in `routes`
+    #[serde(default = "default_body_limit_bytes")]
+    pub max_bytes: usize,
+    /// Per-route limits; the longest matching `path` prefix wins
+    #[serde(default = "default_body_limit_routes")]
+    pub routes: Vec<BodyLimitRoute>,
+}
+
+impl Default for BodyLimitsConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: default_body_limit_bytes(),
+            routes: default_body_limit_routes(),
+        }
+    }
+}
+
+/// Body size limit for the API routes under a path prefix
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct BodyLimitRoute {
+    /// Path prefix relative to `/api/v1`, e.g. `/auth`
+    pub path: String,
+    pub max_bytes: usize,
+}
+
+fn default_body_limit_bytes() -> usize {
+    2 * 1024 * 1024
+}
+
+fn default_body_limit_routes() -> Vec<BodyLimitRoute> {
+    vec![
+        // Credentials only
+        BodyLimitRoute {
+            path: "/auth".to_string(),
+            max_bytes: 16 * 1024,
+        },
+        // Signed SAML responses carry certificates
+        BodyLimitRoute {
+            path: "/auth/saml".to_string(),
+            max_bytes: 256 * 1024,
+        },
+        // Configuration and group exports can be large
+        BodyLimitRoute {
+            path: "/settings/import".to_string(),
+            max_bytes: 16 * 1024 * 1024,
+        },
+        BodyLimitRoute {
+            path: "/settings/validate".to_string(),
+            max_bytes: 16 * 1024 * 1024,
+        },
+    ]
 }
 
 /// TLS/HTTPS configuration
@@ -143,6 +1199,108 @@ pub struct TlsConfig {
     /// TLS cipher suites (if empty, uses secure defaults)
     #[serde(default)]
     pub ciphers: Vec<String>,
+    /// Client certificate verification (mTLS); disabled when unset
+    #[serde(default)]
+    pub client_auth: Option<ClientAuthConfig>,
+}
+
+impl TlsConfig {
+    fn validate(&self) -> Result<()> {
+        if !self.cert_file.exists() {
+            anyhow::bail!("TLS certificate file not found: {:?}", self.cert_file);
+        }
+        if !self.key_file.exists() {
+            anyhow::bail!("TLS key file not found: {:?}", self.key_file);
+        }
+        if self.min_version != "1.2" && self.min_version != "1.3" {
+            anyhow::bail!(
+                "Invalid TLS minimum version: {}. Must be '1.2' or '1.3'",
+                self.min_version
+            );
+        }
+        if let Some(ref client_auth) = self.client_auth {
+            if !client_auth.ca_file.exists() {
+                anyhow::bail!("TLS client CA file not found: {:?}", client_auth.ca_file);
+            }
+            if let Some(ref crl_file) = client_auth.crl_file {
+                if !crl_file.exists() {
+                    anyhow::bail!("TLS client CRL file not found: {:?}", crl_file);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// TLS client certificate verification
+///
+/// Clients presenting a certificate signed by `ca_file` (e.g. Puppet agents
+/// with their Puppet certificate) are identified by its CN.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ClientAuthConfig {
+    /// Whether a client certificate is optional or required
+    #[serde(default)]
+    pub mode: ClientAuthMode,
+    /// CA bundle client certificates must chain to (PEM format)
+    pub ca_file: PathBuf,
+    /// Certificate revocation list(s) checked for client certificates (PEM format)
+    #[serde(default)]
+    pub crl_file: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ClientAuthMode {
+    /// Ask for a certificate but accept connections without one
+    #[default]
+    Request,
+    /// Refuse the TLS handshake without a valid certificate
+    Require,
+}
+
+/// HTTP/3 (QUIC) listener configuration
+///
+/// Requires `server.tls` and a build with the `http3` feature. The listener
+/// shares the HTTPS certificate and is advertised to browsers via `Alt-Svc`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Http3Config {
+    /// Enable the HTTP/3 listener
+    #[serde(default)]
+    pub enabled: bool,
+    /// UDP port to listen on (defaults to `server.port`)
+    #[serde(default)]
+    pub port: Option<u16>,
+    /// How long clients may remember the `Alt-Svc` advertisement, in seconds
+    #[serde(default = "default_alt_svc_max_age_secs")]
+    pub alt_svc_max_age_secs: u64,
+}
+
+fn default_alt_svc_max_age_secs() -> u64 {
+    86400
+}
+
+/// GraphQL read API
+///
+/// Requires a build with the `graphql` feature.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GraphqlConfig {
+    /// Serve `POST /api/graphql`
+    #[serde(default)]
+    pub enabled: bool,
+    /// Deepest nesting of fields a query may use
+    #[serde(default = "default_graphql_max_depth")]
+    pub max_depth: usize,
+    /// Most fields a query may select in total, counting nested ones
+    #[serde(default = "default_graphql_max_complexity")]
+    pub max_complexity: usize,
+}
+
+fn default_graphql_max_depth() -> usize {
+    8
+}
+
+fn default_graphql_max_complexity() -> usize {
+    500
 }
 
 fn default_host() -> String {
@@ -171,6 +1329,15 @@ fn default_serve_frontend() -> bool {
     true
 }
 
+fn default_static_precompressed() -> bool {
+    true
+}
+
+fn default_immutable_max_age_secs() -> u64 {
+    // One year, the conventional maximum for immutable assets
+    31_536_000
+}
+
 fn default_min_tls_version() -> String {
     "1.3".to_string()
 }
@@ -192,6 +1359,33 @@ pub struct PuppetDbSslConfig {
     pub verify: bool,
 }
 
+/// Fault injection for a dependency client
+///
+/// Requires a build with the `fault-injection` feature; other builds ignore
+/// it with a warning. Each request first waits `latency_ms` plus up to
+/// `latency_jitter_ms`, then fails with probability `error_rate`. Meant for
+/// integration tests and staging, to exercise cache fallback and error
+/// handling under partial outages.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct FaultInjectionConfig {
+    /// Share of requests that fail (0.0 - 1.0)
+    #[serde(default)]
+    pub error_rate: f64,
+    /// Latency added to every request, in milliseconds
+    #[serde(default)]
+    pub latency_ms: u64,
+    /// Maximum random latency added on top of `latency_ms`, in milliseconds
+    #[serde(default)]
+    pub latency_jitter_ms: u64,
+}
+
+impl FaultInjectionConfig {
+    /// Whether the configuration injects anything at all
+    pub fn is_active(&self) -> bool {
+        self.error_rate > 0.0 || self.latency_ms > 0 || self.latency_jitter_ms > 0
+    }
+}
+
 /// PuppetDB connection configuration
 /// Supports both flat format (ssl_cert, ssl_key, ssl_ca) and nested format (ssl.cert_path, etc.)
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -211,6 +1405,9 @@ pub struct PuppetDbConfig {
     /// Nested format: ssl configuration block (from Puppet module)
     #[serde(default)]
     pub ssl: Option<PuppetDbSslConfig>,
+    /// Injected failures and latency (testing and staging only)
+    #[serde(default)]
+    pub fault_injection: Option<FaultInjectionConfig>,
 }
 
 impl PuppetDbConfig {
@@ -291,6 +1488,37 @@ pub struct PuppetCAConfig {
     /// Nested format: ssl configuration block (from Puppet module)
     #[serde(default)]
     pub ssl: Option<PuppetCASslConfig>,
+    /// How long the fetched CRL is cached, in seconds
+    #[serde(default = "default_crl_cache_secs")]
+    pub crl_cache_secs: u64,
+    /// Interval for syncing the offline CA snapshot, in seconds (0 = disabled)
+    #[serde(default = "default_ca_snapshot_interval")]
+    pub snapshot_interval_secs: u64,
+    /// Days before expiry at which node certificates are flagged
+    #[serde(default = "default_leaf_expiry_warning_days")]
+    pub leaf_expiry_warning_days: i64,
+    /// Days before expiry at which root and intermediate CAs are flagged
+    #[serde(default = "default_ca_expiry_warning_days")]
+    pub ca_expiry_warning_days: i64,
+    /// Injected failures and latency (testing and staging only)
+    #[serde(default)]
+    pub fault_injection: Option<FaultInjectionConfig>,
+}
+
+fn default_crl_cache_secs() -> u64 {
+    300
+}
+
+fn default_ca_snapshot_interval() -> u64 {
+    900
+}
+
+fn default_leaf_expiry_warning_days() -> i64 {
+    30
+}
+
+fn default_ca_expiry_warning_days() -> i64 {
+    90
 }
 
 impl PuppetCAConfig {
@@ -339,6 +1567,103 @@ pub struct AuthConfig {
     pub bcrypt_cost: u32,
     #[serde(default = "default_password_min_length")]
     pub password_min_length: usize,
+    /// How long the previous secret of a rotated API key keeps working
+    #[serde(default = "default_api_key_rotation_grace")]
+    pub api_key_rotation_grace_minutes: u64,
+    /// Rules for new passwords beyond the minimum length
+    #[serde(default)]
+    pub password_policy: PasswordPolicyConfig,
+    /// Page that completes a password reset (e.g.
+    /// "https://openvox.example.com/reset-password"); reset emails link to
+    /// it with the token appended as the `token` query parameter. Without
+    /// it the email contains only the token.
+    #[serde(default)]
+    pub password_reset_url: Option<String>,
+    /// Keys that sign access and refresh tokens
+    #[serde(default)]
+    pub signing_keys: SigningKeysConfig,
+    /// Login history and notifications of unusual logins
+    #[serde(default)]
+    pub login_alerts: LoginAlertsConfig,
+    /// CSRF protection of requests authenticated by the browser
+    #[serde(default)]
+    pub csrf: CsrfConfig,
+    /// How the frontend holds its tokens
+    #[serde(default)]
+    pub mode: AuthMode,
+    /// Attributes of the session cookies in cookie mode
+    #[serde(default)]
+    pub cookie: SessionCookieConfig,
+}
+
+impl AuthConfig {
+    /// Whether login sets session cookies instead of returning tokens
+    pub fn cookie_mode(&self) -> bool {
+        self.mode == AuthMode::Cookie
+    }
+
+    /// Whether unsafe requests must carry the CSRF token
+    ///
+    /// On by default in cookie mode, where the browser sends the credentials
+    /// with every request.
+    pub fn csrf_enabled(&self) -> bool {
+        self.csrf.enabled.unwrap_or(self.cookie_mode())
+    }
+}
+
+/// How the frontend holds its tokens
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthMode {
+    /// Login returns the tokens, which are sent as `Authorization: Bearer`
+    #[default]
+    Token,
+    /// Login sets HttpOnly cookies that scripts cannot read; the browser
+    /// sends them with every request
+    Cookie,
+}
+
+/// Session cookies of cookie mode
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SessionCookieConfig {
+    /// `SameSite` attribute of the cookies
+    #[serde(default)]
+    pub same_site: CookieSameSite,
+    /// Mark the cookies `Secure`; turn off only when serving plain HTTP
+    #[serde(default = "default_true_val")]
+    pub secure: bool,
+}
+
+impl Default for SessionCookieConfig {
+    fn default() -> Self {
+        Self {
+            same_site: CookieSameSite::default(),
+            secure: true,
+        }
+    }
+}
+
+/// `SameSite` attribute of a cookie
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CookieSameSite {
+    /// Never sent with cross-site requests
+    #[default]
+    Strict,
+    /// Also sent when following a link from another site
+    Lax,
+    /// Sent with all requests; requires `secure`
+    None,
+}
+
+impl CookieSameSite {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CookieSameSite::Strict => "Strict",
+            CookieSameSite::Lax => "Lax",
+            CookieSameSite::None => "None",
+        }
+    }
 }
 
 fn default_token_expiry() -> u64 {
@@ -357,6 +1682,227 @@ fn default_password_min_length() -> usize {
     8
 }
 
+fn default_api_key_rotation_grace() -> u64 {
+    60
+}
+
+/// JWT signing keys
+///
+/// By default tokens are signed with HS256 and `jwt_secret`. Choosing RS256
+/// or EdDSA, or a rotation period, switches to keys generated and stored in
+/// the database: tokens name their key in the `kid` header and a replaced key
+/// keeps verifying tokens for `grace_hours`. Public keys of RS256 and EdDSA
+/// are published at `/api/v1/auth/jwks.json`.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct SigningKeysConfig {
+    /// Algorithm of new signing keys
+    #[serde(default)]
+    pub algorithm: JwtAlgorithm,
+    /// Replace the signing key after this many days (0 never rotates)
+    #[serde(default)]
+    pub rotation_days: u32,
+    /// How long a replaced key keeps verifying tokens; defaults to the
+    /// longest token lifetime
+    #[serde(default)]
+    pub grace_hours: Option<u64>,
+}
+
+impl SigningKeysConfig {
+    /// Whether tokens are signed with stored keys instead of `jwt_secret`
+    pub fn uses_stored_keys(&self) -> bool {
+        self.algorithm != JwtAlgorithm::Hs256 || self.rotation_days > 0
+    }
+}
+
+/// Signature algorithm of JWTs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum JwtAlgorithm {
+    #[default]
+    #[serde(rename = "HS256", alias = "hs256")]
+    Hs256,
+    #[serde(rename = "RS256", alias = "rs256")]
+    Rs256,
+    #[serde(rename = "EdDSA", alias = "eddsa")]
+    EdDsa,
+}
+
+impl JwtAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JwtAlgorithm::Hs256 => "HS256",
+            JwtAlgorithm::Rs256 => "RS256",
+            JwtAlgorithm::EdDsa => "EdDSA",
+        }
+    }
+}
+
+/// Login history and alerts
+///
+/// Every login of a known user is recorded with the client address, user
+/// agent and location, and users see their recent logins on `/auth/me`. A
+/// successful login from a browser or location the user has not used before
+/// notifies them, as does a lockout of their account after
+/// `rbac.max_failed_logins` failed attempts.
+///
+/// The location is read from headers set by a geo-aware proxy (e.g.
+/// `CF-IPCountry` behind Cloudflare); without them it is the client's
+/// network (/24 for IPv4, /48 for IPv6).
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LoginAlertsConfig {
+    /// Notify users of logins from a new browser or operating system
+    #[serde(default = "default_true_val")]
+    pub new_device: bool,
+    /// Notify users of logins from a new location
+    #[serde(default = "default_true_val")]
+    pub new_location: bool,
+    /// Notify users when failed logins lock their account
+    #[serde(default = "default_true_val")]
+    pub failed_attempts: bool,
+    /// Also email these notifications to the user
+    #[serde(default)]
+    pub email: bool,
+    /// Request header carrying the client's country
+    #[serde(default)]
+    pub country_header: Option<String>,
+    /// Request header carrying the client's city
+    #[serde(default)]
+    pub city_header: Option<String>,
+    /// Days logins are kept
+    #[serde(default = "default_login_history_days")]
+    pub history_days: u32,
+}
+
+fn default_login_history_days() -> u32 {
+    90
+}
+
+impl Default for LoginAlertsConfig {
+    fn default() -> Self {
+        Self {
+            new_device: true,
+            new_location: true,
+            failed_attempts: true,
+            email: false,
+            country_header: None,
+            city_header: None,
+            history_days: default_login_history_days(),
+        }
+    }
+}
+
+/// CSRF protection (double-submit cookie)
+///
+/// Responses set a random token in the `openvox_csrf` cookie; unsafe
+/// requests (POST, PUT, PATCH, DELETE) must echo it in the `X-CSRF-Token`
+/// header. A cross-site page can make the browser send the cookie but cannot
+/// read it. Requests with an `Authorization` or `X-API-Key` header are
+/// token-authenticated API clients and are not checked, nor are
+/// `exempt_paths`.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CsrfConfig {
+    /// Require the token; defaults to on in cookie mode (`auth.mode`)
+    #[serde(default)]
+    pub enabled: Option<bool>,
+    /// Mark the cookie `Secure`; turn off only when serving plain HTTP
+    #[serde(default = "default_true_val")]
+    pub secure_cookie: bool,
+    /// API paths (relative to `/api/v1`) that are not checked, e.g. callbacks
+    /// from other sites; `*` matches one path segment, and a path must match
+    /// an entry as a whole
+    #[serde(default = "default_csrf_exempt_paths")]
+    pub exempt_paths: Vec<String>,
+}
+
+fn default_csrf_exempt_paths() -> Vec<String> {
+    [
+        "/auth/saml/acs",
+        "/webhooks/github/*",
+        "/webhooks/gitlab/*",
+        "/webhooks/bitbucket/*",
+        "/nodes/*/inventory",
+        "/nodes/*/update-jobs/*/targets/*/results",
+    ]
+    .iter()
+    .map(|path| path.to_string())
+    .collect()
+}
+
+impl Default for CsrfConfig {
+    fn default() -> Self {
+        Self {
+            enabled: None,
+            secure_cookie: true,
+            exempt_paths: default_csrf_exempt_paths(),
+        }
+    }
+}
+
+/// Password policy
+///
+/// Applied whenever a password is set: registration, user creation, admin
+/// resets, password reset links and change-password.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct PasswordPolicyConfig {
+    /// Require at least one uppercase letter
+    #[serde(default)]
+    pub require_uppercase: bool,
+    /// Require at least one lowercase letter
+    #[serde(default)]
+    pub require_lowercase: bool,
+    /// Require at least one digit
+    #[serde(default)]
+    pub require_digit: bool,
+    /// Require at least one character that is not a letter or digit
+    #[serde(default)]
+    pub require_symbol: bool,
+    /// Reject passwords that contain the username
+    #[serde(default)]
+    pub reject_username: bool,
+    /// Reject common passwords (built-in list plus `dictionary_path`)
+    #[serde(default)]
+    pub dictionary_check: bool,
+    /// Extra words to reject, one per line
+    #[serde(default)]
+    pub dictionary_path: Option<PathBuf>,
+    /// Reject passwords found in the Have I Been Pwned breach corpus
+    ///
+    /// Only the first five characters of the password's SHA-1 hash leave
+    /// the server (k-anonymity range lookup).
+    #[serde(default)]
+    pub breach_check: bool,
+    /// Range API used for the breach check
+    #[serde(default = "default_breach_api_url")]
+    pub breach_api_url: String,
+    /// Number of previous passwords that cannot be reused (0 disables)
+    #[serde(default)]
+    pub history_count: u32,
+    /// Days after which users must change their password (unset disables)
+    #[serde(default)]
+    pub max_age_days: Option<u32>,
+}
+
+fn default_breach_api_url() -> String {
+    "https://api.pwnedpasswords.com/range/".to_string()
+}
+
+impl Default for PasswordPolicyConfig {
+    fn default() -> Self {
+        Self {
+            require_uppercase: false,
+            require_lowercase: false,
+            require_digit: false,
+            require_symbol: false,
+            reject_username: false,
+            dictionary_check: false,
+            dictionary_path: None,
+            breach_check: false,
+            breach_api_url: default_breach_api_url(),
+            history_count: 0,
+            max_age_days: None,
+        }
+    }
+}
+
 /// Database configuration
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct DatabaseConfig {
@@ -369,6 +1915,12 @@ pub struct DatabaseConfig {
     pub connect_timeout_secs: u64,
     #[serde(default = "default_idle_timeout")]
     pub idle_timeout_secs: u64,
+    /// How long a connection waits for another one's write lock
+    #[serde(default = "default_busy_timeout")]
+    pub busy_timeout_ms: u64,
+    /// Retries of a busy write after the busy timeout ran out
+    #[serde(default = "default_busy_retries")]
+    pub busy_retries: u32,
 }
 
 fn default_max_connections() -> u32 {
@@ -387,6 +1939,14 @@ fn default_idle_timeout() -> u64 {
     600
 }
 
+fn default_busy_timeout() -> u64 {
+    5000
+}
+
+fn default_busy_retries() -> u32 {
+    3
+}
+
 /// Logging configuration
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct LoggingConfig {
@@ -409,6 +1969,9 @@ pub struct LoggingConfig {
     /// Maximum number of log files to keep (0 = unlimited)
     #[serde(default = "default_max_log_files")]
     pub max_log_files: usize,
+    /// Recent log lines kept in memory for the admin log viewer (0 = disabled)
+    #[serde(default = "default_log_buffer_lines")]
+    pub buffer_lines: usize,
     /// Deprecated: use log_dir instead
     #[serde(default)]
     pub file: Option<PathBuf>,
@@ -455,6 +2018,10 @@ fn default_max_log_files() -> usize {
     30 // Keep 30 days of logs by default
 }
 
+fn default_log_buffer_lines() -> usize {
+    2000
+}
+
 /// Cache configuration for PuppetDB data
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct CacheConfig {
@@ -482,6 +2049,14 @@ pub struct CacheConfig {
     /// Background sync interval in seconds (0 to disable)
     #[serde(default = "default_sync_interval")]
     pub sync_interval_secs: u64,
+    /// How often the sync job rebuilds the global search index, in seconds
+    /// (0 to disable)
+    #[serde(default = "default_search_index_interval")]
+    pub search_index_interval_secs: u64,
+    /// How long smart list member counts are cached, in seconds (0 to
+    /// disable)
+    #[serde(default = "default_smart_list_count_ttl")]
+    pub smart_list_count_ttl_secs: u64,
 }
 
 fn default_cache_enabled() -> bool {
@@ -516,6 +2091,14 @@ fn default_sync_interval() -> u64 {
     0 // Disabled by default
 }
 
+fn default_search_index_interval() -> u64 {
+    300 // 5 minutes
+}
+
+fn default_smart_list_count_ttl() -> u64 {
+    300 // 5 minutes
+}
+
 impl Default for CacheConfig {
     fn default() -> Self {
         Self {
@@ -527,6 +2110,8 @@ impl Default for CacheConfig {
             catalog_ttl_secs: default_catalog_ttl(),
             max_entries: default_max_entries(),
             sync_interval_secs: default_sync_interval(),
+            search_index_interval_secs: default_search_index_interval(),
+            smart_list_count_ttl_secs: default_smart_list_count_ttl(),
         }
     }
 }
@@ -558,6 +2143,9 @@ pub struct DashboardConfig {
     /// Dashboard widgets configuration
     #[serde(default)]
     pub widgets: Vec<WidgetConfig>,
+    /// Filters applied to listings by default (e.g. `environment`, `status`)
+    #[serde(default)]
+    pub default_filters: BTreeMap<String, serde_json::Value>,
 }
 
 fn default_time_range() -> String {
@@ -599,6 +2187,97 @@ impl Default for DashboardConfig {
             inactive_threshold_hours: default_inactive_threshold(),
             theme: default_theme(),
             widgets: Vec::new(),
+            default_filters: BTreeMap::new(),
+        }
+    }
+}
+
+impl DashboardConfig {
+    /// The dashboard as seen by a user with the given preferences
+    pub fn with_preferences(&self, preferences: &DashboardPreferences) -> DashboardConfig {
+        let preferences = preferences.clone();
+        DashboardConfig {
+            default_time_range: preferences
+                .default_time_range
+                .unwrap_or_else(|| self.default_time_range.clone()),
+            refresh_interval_secs: preferences
+                .refresh_interval_secs
+                .unwrap_or(self.refresh_interval_secs),
+            nodes_per_page: preferences.nodes_per_page.unwrap_or(self.nodes_per_page),
+            reports_per_page: preferences
+                .reports_per_page
+                .unwrap_or(self.reports_per_page),
+            show_inactive_nodes: preferences
+                .show_inactive_nodes
+                .unwrap_or(self.show_inactive_nodes),
+            inactive_threshold_hours: preferences
+                .inactive_threshold_hours
+                .unwrap_or(self.inactive_threshold_hours),
+            theme: preferences.theme.unwrap_or_else(|| self.theme.clone()),
+            widgets: preferences.widgets.unwrap_or_else(|| self.widgets.clone()),
+            default_filters: preferences
+                .default_filters
+                .unwrap_or_else(|| self.default_filters.clone()),
+        }
+    }
+}
+
+/// A user's own dashboard settings
+///
+/// Every field is optional; unset fields fall back to the global
+/// [`DashboardConfig`].
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+pub struct DashboardPreferences {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_time_range: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_interval_secs: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nodes_per_page: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reports_per_page: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub show_inactive_nodes: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inactive_threshold_hours: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub theme: Option<String>,
+    /// Widgets with their grid positions (the dashboard layout)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub widgets: Option<Vec<WidgetConfig>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_filters: Option<BTreeMap<String, serde_json::Value>>,
+}
+
+impl DashboardPreferences {
+    /// Overlay the fields set in `update` onto these preferences
+    pub fn apply(&mut self, update: DashboardPreferences) {
+        if update.default_time_range.is_some() {
+            self.default_time_range = update.default_time_range;
+        }
+        if update.refresh_interval_secs.is_some() {
+            self.refresh_interval_secs = update.refresh_interval_secs;
+        }
+        if update.nodes_per_page.is_some() {
+            self.nodes_per_page = update.nodes_per_page;
+        }
+        if update.reports_per_page.is_some() {
+            self.reports_per_page = update.reports_per_page;
+        }
+        if update.show_inactive_nodes.is_some() {
+            self.show_inactive_nodes = update.show_inactive_nodes;
+        }
+        if update.inactive_threshold_hours.is_some() {
+            self.inactive_threshold_hours = update.inactive_threshold_hours;
+        }
+        if update.theme.is_some() {
+            self.theme = update.theme;
+        }
+        if update.widgets.is_some() {
+            self.widgets = update.widgets;
+        }
+        if update.default_filters.is_some() {
+            self.default_filters = update.default_filters;
         }
     }
 }
@@ -623,12 +2302,57 @@ pub struct WidgetConfig {
     /// Widget-specific configuration
     #[serde(default)]
     pub config: serde_json::Value,
+    /// Data the widget charts, for widgets fed by a query or saved report
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub binding: Option<WidgetBinding>,
 }
 
 fn default_widget_enabled() -> bool {
     true
 }
 
+/// Where a widget's data comes from, and how to read it
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WidgetBinding {
+    #[serde(flatten)]
+    pub source: WidgetDataSource,
+    /// Reference lines drawn on the chart
+    #[serde(default)]
+    pub thresholds: Vec<WidgetThreshold>,
+}
+
+/// Data source of a bound widget
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum WidgetDataSource {
+    /// A PQL query; every row becomes one point of the chart
+    Pql {
+        query: String,
+        /// Row field used as the point label
+        label_field: String,
+        /// Row fields charted as values, one series each
+        #[serde(default = "default_widget_value_fields")]
+        value_fields: Vec<String>,
+    },
+    /// A saved report, generated when the widget is loaded
+    SavedReport { report_id: uuid::Uuid },
+}
+
+fn default_widget_value_fields() -> Vec<String> {
+    vec!["count".to_string()]
+}
+
+/// A reference value on a widget chart
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct WidgetThreshold {
+    pub value: f64,
+    #[serde(default)]
+    pub label: Option<String>,
+    /// Display hint such as `warning` or `critical`
+    #[serde(default)]
+    pub level: Option<String>,
+}
+
 /// Widget position on dashboard grid
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct WidgetPosition {
@@ -660,6 +2384,9 @@ pub enum WidgetType {
     InfrastructureTopology,
     QuickSearch,
     RecentActivity,
+    CorrectiveChanges,
+    /// Chart fed by the widget's binding
+    Custom,
 }
 
 /// RBAC configuration
@@ -674,9 +2401,14 @@ pub struct RbacConfig {
     /// Maximum failed login attempts before lockout
     #[serde(default = "default_max_failed_logins")]
     pub max_failed_logins: u32,
-    /// Account lockout duration in minutes
+    /// Account lockout duration in minutes; also the window in which
+    /// failed logins are counted
     #[serde(default = "default_lockout_duration")]
     pub lockout_duration_minutes: u64,
+    /// Longest lockout; every repeated lockout doubles the duration up to
+    /// this
+    #[serde(default = "default_max_lockout")]
+    pub max_lockout_minutes: u64,
     /// Custom role definitions (in addition to built-in roles)
     #[serde(default)]
     pub roles: Vec<RoleDefinition>,
@@ -698,6 +2430,10 @@ fn default_lockout_duration() -> u64 {
     30
 }
 
+fn default_max_lockout() -> u64 {
+    1440 // 24 hours
+}
+
 impl Default for RbacConfig {
     fn default() -> Self {
         Self {
@@ -705,6 +2441,7 @@ impl Default for RbacConfig {
             session_timeout_minutes: default_session_timeout(),
             max_failed_logins: default_max_failed_logins(),
             lockout_duration_minutes: default_lockout_duration(),
+            max_lockout_minutes: default_max_lockout(),
             roles: Vec::new(),
         }
     }
@@ -774,6 +2511,9 @@ pub struct CodeDeployYamlConfig {
     /// r10k thread pool size (default: 1 to avoid Ruby 3.2 threading segfault)
     #[serde(default = "default_r10k_pool_size")]
     pub r10k_pool_size: u32,
+    /// Path to the librarian-puppet binary (`librarian_puppet` deploy strategy)
+    #[serde(default = "default_librarian_puppet_binary_path")]
+    pub librarian_puppet_binary_path: PathBuf,
     /// Encryption key for SSH private keys (should come from secure storage)
     #[serde(default)]
     pub encryption_key: String,
@@ -783,6 +2523,21 @@ pub struct CodeDeployYamlConfig {
     /// Retain deployment history for this many days
     #[serde(default = "default_retain_history_days")]
     pub retain_history_days: u32,
+    /// Maximum number of environments deployed in parallel (deployments to
+    /// the same environment are always serialized)
+    #[serde(default = "default_max_concurrent_deployments")]
+    pub max_concurrent_deployments: usize,
+    /// Days before expiration operators are warned about a PAT token
+    #[serde(default = "default_pat_token_warning_days")]
+    pub pat_token_warning_days: i64,
+    /// Age in days after which operators are asked to rotate an SSH key
+    /// (0 = never)
+    #[serde(default = "default_ssh_key_max_age_days")]
+    pub ssh_key_max_age_days: i64,
+    /// Injected failures and latency for Git clone/fetch (testing and
+    /// staging only)
+    #[serde(default)]
+    pub fault_injection: Option<FaultInjectionConfig>,
 }
 
 fn default_repos_base_dir() -> PathBuf {
@@ -813,10 +2568,26 @@ fn default_r10k_pool_size() -> u32 {
     1
 }
 
+fn default_librarian_puppet_binary_path() -> PathBuf {
+    PathBuf::from("/opt/puppetlabs/puppet/bin/librarian-puppet")
+}
+
 fn default_retain_history_days() -> u32 {
     90
 }
 
+fn default_max_concurrent_deployments() -> usize {
+    1
+}
+
+fn default_pat_token_warning_days() -> i64 {
+    30
+}
+
+fn default_ssh_key_max_age_days() -> i64 {
+    365
+}
+
 impl Default for CodeDeployYamlConfig {
     fn default() -> Self {
         Self {
@@ -828,9 +2599,14 @@ impl Default for CodeDeployYamlConfig {
             environments_basedir: default_environments_basedir(),
             r10k_cachedir: default_r10k_cachedir(),
             r10k_pool_size: default_r10k_pool_size(),
+            librarian_puppet_binary_path: default_librarian_puppet_binary_path(),
             encryption_key: String::new(),
             webhook_base_url: None,
             retain_history_days: default_retain_history_days(),
+            max_concurrent_deployments: default_max_concurrent_deployments(),
+            pat_token_warning_days: default_pat_token_warning_days(),
+            ssh_key_max_age_days: default_ssh_key_max_age_days(),
+            fault_injection: None,
         }
     }
 }
@@ -1000,6 +2776,9 @@ pub struct BackupConfig {
     /// What to include in backups
     #[serde(default)]
     pub include: BackupIncludeConfig,
+    /// Destinations each backup is also copied to, e.g. an S3 bucket
+    #[serde(default)]
+    pub upload_targets: Vec<crate::models::DeliveryTarget>,
 }
 
 /// Backup schedule configuration
@@ -1055,7 +2834,7 @@ pub struct BackupEncryptionConfig {
 /// What to include in backups
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct BackupIncludeConfig {
-    /// Include database files (openvox.db, .wal, .shm)
+    /// Include an online snapshot of the database (openvox.db)
     #[serde(default = "default_include_database")]
     pub database: bool,
     /// Include configuration files (config.yaml, groups.yaml)
@@ -1108,6 +2887,7 @@ impl Default for BackupConfig {
             retention: BackupRetentionConfig::default(),
             encryption: BackupEncryptionConfig::default(),
             include: BackupIncludeConfig::default(),
+            upload_targets: Vec::new(),
         }
     }
 }
@@ -1168,19 +2948,71 @@ pub struct NodeRemovalConfig {
     /// Whether the node removal tracking feature is enabled
     #[serde(default)]
     pub enabled: bool,
-    /// Number of days to wait before removing a node (default: 10)
-    #[serde(default = "default_node_removal_retention_days")]
-    pub retention_days: i64,
-    /// How often to check for certificate status changes (in seconds, default: 300 = 5 minutes)
+    /// Number of days to wait before removing a node (default: 10)
+    #[serde(default = "default_node_removal_retention_days")]
+    pub retention_days: i64,
+    /// How often to check for certificate status changes (in seconds, default: 300 = 5 minutes)
+    #[serde(default)]
+    pub check_interval_secs: Option<u64>,
+    /// How long to keep audit log entries (in days, default: 90)
+    #[serde(default)]
+    pub audit_retention_days: Option<i64>,
+    /// Stale node janitor
+    #[serde(default)]
+    pub janitor: StaleNodeJanitorConfig,
+}
+
+fn default_node_removal_retention_days() -> i64 {
+    10
+}
+
+/// Stale node janitor configuration
+///
+/// Periodically finds nodes that have not checked in for `stale_after_days`
+/// and purges them: deactivates them in PuppetDB and/or flags their
+/// certificates for removal. Each run is kept as a report.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct StaleNodeJanitorConfig {
+    /// Whether the janitor runs on a schedule
+    #[serde(default)]
+    pub enabled: bool,
+    /// Days without a report, facts or catalog before a node is stale (default: 30)
+    #[serde(default = "default_janitor_stale_after_days")]
+    pub stale_after_days: i64,
+    /// How often the janitor runs (in seconds, default: 86400 = daily)
+    #[serde(default = "default_janitor_interval_secs")]
+    pub interval_secs: u64,
+    /// Deactivate stale nodes in PuppetDB
     #[serde(default)]
-    pub check_interval_secs: Option<u64>,
-    /// How long to keep audit log entries (in days, default: 90)
+    pub deactivate: bool,
+    /// Mark stale nodes for removal (reason `stale_node`) so their
+    /// certificates show up for cleanup
+    #[serde(default = "default_true_val")]
+    pub flag_certificates: bool,
+    /// Certname patterns that are never purged (`*` and `?` wildcards)
     #[serde(default)]
-    pub audit_retention_days: Option<i64>,
+    pub exclude: Vec<String>,
 }
 
-fn default_node_removal_retention_days() -> i64 {
-    10
+fn default_janitor_stale_after_days() -> i64 {
+    30
+}
+
+fn default_janitor_interval_secs() -> u64 {
+    86400
+}
+
+impl Default for StaleNodeJanitorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            stale_after_days: default_janitor_stale_after_days(),
+            interval_secs: default_janitor_interval_secs(),
+            deactivate: false,
+            flag_certificates: true,
+            exclude: Vec::new(),
+        }
+    }
 }
 
 impl Default for NodeRemovalConfig {
@@ -1190,6 +3022,7 @@ impl Default for NodeRemovalConfig {
             retention_days: default_node_removal_retention_days(),
             check_interval_secs: Some(300),
             audit_retention_days: Some(90),
+            janitor: StaleNodeJanitorConfig::default(),
         }
     }
 }
@@ -1494,6 +3327,7 @@ impl Default for LoggingConfig {
             log_prefix: default_log_prefix(),
             daily_rotation: default_log_rotation(),
             max_log_files: default_max_log_files(),
+            buffer_lines: default_log_buffer_lines(),
             file: None,
         }
     }
@@ -1519,15 +3353,33 @@ impl Default for AppConfig {
                 tls: None,
                 static_dir: default_static_dir(),
                 serve_frontend: default_serve_frontend(),
+                static_assets: StaticAssetsConfig::default(),
+                base_path: String::new(),
+                http3: None,
+                listeners: Vec::new(),
+                enc_listener: None,
+                unix_socket: None,
+                trusted_proxies: default_trusted_proxies(),
+                security_headers: SecurityHeadersConfig::default(),
+                body_limits: BodyLimitsConfig::default(),
+                graphql: None,
             },
             puppetdb: None,
             puppet_ca: None,
             auth: AuthConfig {
-                jwt_secret: "change-me-in-production-minimum-32-characters-long".to_string(),
+                jwt_secret: DEFAULT_JWT_SECRET.to_string(),
                 token_expiry_hours: default_token_expiry(),
                 refresh_token_expiry_days: default_refresh_expiry(),
                 bcrypt_cost: default_bcrypt_cost(),
                 password_min_length: default_password_min_length(),
+                api_key_rotation_grace_minutes: default_api_key_rotation_grace(),
+                password_policy: PasswordPolicyConfig::default(),
+                password_reset_url: None,
+                signing_keys: SigningKeysConfig::default(),
+                login_alerts: LoginAlertsConfig::default(),
+                csrf: CsrfConfig::default(),
+                mode: AuthMode::default(),
+                cookie: SessionCookieConfig::default(),
             },
             database: DatabaseConfig {
                 url: "sqlite://./data/openvox.db".to_string(),
@@ -1535,6 +3387,8 @@ impl Default for AppConfig {
                 min_connections: default_min_connections(),
                 connect_timeout_secs: default_connect_timeout(),
                 idle_timeout_secs: default_idle_timeout(),
+                busy_timeout_ms: default_busy_timeout(),
+                busy_retries: default_busy_retries(),
             },
             logging: LoggingConfig::default(),
             cache: CacheConfig::default(),
@@ -1550,7 +3404,51 @@ impl Default for AppConfig {
             inventory: None,
             cve: None,
             pagination: PaginationConfig::default(),
+            secrets: None,
+            settings_encryption: SettingsEncryptionConfig::default(),
+            audit: AuditConfig::default(),
+            rate_limit: RateLimitsConfig::default(),
+            hot_reload: HotReloadConfig::default(),
+            webhooks: WebhooksConfig::default(),
+            recycle_bin: RecycleBinConfig::default(),
+            health: HealthConfig::default(),
+            notifications: NotificationsConfig::default(),
+            reporting: ReportingConfig::default(),
+            report_ingestion: ReportIngestionConfig::default(),
+            organizations: OrganizationsConfig::default(),
+        }
+    }
+}
+
+/// Placeholder JWT secret used when none is configured
+const DEFAULT_JWT_SECRET: &str = "change-me-in-production-minimum-32-characters-long";
+
+/// Collect the dotted paths of leaf values that differ between two documents
+fn changed_paths(
+    before: &serde_json::Value,
+    after: &serde_json::Value,
+    prefix: &str,
+    paths: &mut Vec<String>,
+) {
+    match (before, after) {
+        (serde_json::Value::Object(old), serde_json::Value::Object(new)) => {
+            let keys: std::collections::BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+            for key in keys {
+                let path = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                changed_paths(
+                    old.get(key).unwrap_or(&serde_json::Value::Null),
+                    new.get(key).unwrap_or(&serde_json::Value::Null),
+                    &path,
+                    paths,
+                );
+            }
         }
+        _ if before != after => paths.push(prefix.to_string()),
+        _ => {}
     }
 }
 
@@ -1566,18 +3464,12 @@ impl AppConfig {
         let _ = dotenvy::dotenv();
 
         // Check for config path override from environment
-        let config_path = std::env::var("OPENVOX_CONFIG")
-            .map(PathBuf::from)
-            .ok()
-            .or_else(Self::find_config_file);
+        let config_path = Self::config_file_path();
 
         let mut config = if let Some(ref path) = config_path {
             if path.exists() {
                 eprintln!("[CONFIG] Loading configuration from: {:?}", path);
-                let contents = std::fs::read_to_string(path)
-                    .with_context(|| format!("Failed to read config file: {:?}", path))?;
-                let parsed: AppConfig = serde_norway::from_str(&contents)
-                    .with_context(|| format!("Failed to parse config file: {:?}", path))?;
+                let parsed = Self::from_file(path)?;
 
                 // Debug: Log SAML config right after parsing
                 if let Some(ref saml) = parsed.saml {
@@ -1629,6 +3521,180 @@ impl AppConfig {
         Ok(config)
     }
 
+    /// Replace `secret:` references with values from the secrets provider
+    ///
+    /// Must run before the configuration is used. The returned resolver is
+    /// needed for secrets read later at runtime (e.g. the SMTP password).
+    pub async fn resolve_secrets(
+        &mut self,
+    ) -> Result<Option<std::sync::Arc<crate::services::secrets::SecretsResolver>>> {
+        let resolver = crate::services::secrets::resolve_config_secrets(self).await?;
+        self.validate()?;
+        Ok(resolver)
+    }
+
+    /// Path of the configuration file `load` reads, if any
+    pub fn config_file_path() -> Option<PathBuf> {
+        std::env::var("OPENVOX_CONFIG")
+            .map(PathBuf::from)
+            .ok()
+            .or_else(Self::find_config_file)
+    }
+
+    /// Parse a configuration file, without environment overrides
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file: {:?}", path))?;
+        serde_norway::from_str(&contents)
+            .with_context(|| format!("Failed to parse config file: {:?}", path))
+    }
+
+    /// Read the configuration again the way `load` does, without its
+    /// startup diagnostics and `.env` handling
+    pub fn reload() -> Result<Self> {
+        let mut config = match Self::config_file_path().filter(|path| path.exists()) {
+            Some(path) => Self::from_file(&path)?,
+            None => Self::default(),
+        };
+        config.apply_env_overrides();
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Dotted paths of the settings that differ from `other`
+    pub fn changed_settings(&self, other: &Self) -> Vec<String> {
+        let mut paths = Vec::new();
+        if let (Ok(before), Ok(after)) = (serde_json::to_value(self), serde_json::to_value(other)) {
+            changed_paths(&before, &after, "", &mut paths);
+        }
+        paths
+    }
+
+    /// Path of the node groups file, if one is configured or found
+    pub fn groups_file_path(&self) -> Option<PathBuf> {
+        self.groups_config_path
+            .clone()
+            .or_else(GroupsConfig::find_config_file)
+    }
+
+    /// Settings whose value currently comes from environment variables
+    ///
+    /// Compares the configuration file with and without the environment
+    /// overrides and returns the dotted paths that differ, such as
+    /// `auth.jwt_secret`.
+    pub fn env_overridden_settings() -> Result<Vec<String>> {
+        let base = match Self::config_file_path().filter(|path| path.exists()) {
+            Some(path) => Self::from_file(&path)?,
+            None => Self::default(),
+        };
+        let mut merged = base.clone();
+        merged.apply_env_overrides();
+
+        let mut paths = Vec::new();
+        changed_paths(
+            &serde_json::to_value(&base)?,
+            &serde_json::to_value(&merged)?,
+            "",
+            &mut paths,
+        );
+        Ok(paths)
+    }
+
+    /// Settings that are valid but probably not intended in production
+    pub fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+
+        if self.auth.jwt_secret == DEFAULT_JWT_SECRET {
+            warnings
+                .push("auth.jwt_secret is the built-in default; set a unique secret".to_string());
+        }
+        if self.auth.bcrypt_cost < 10 {
+            warnings.push(format!(
+                "auth.bcrypt_cost is {}; values below 10 make password hashes cheap to crack",
+                self.auth.bcrypt_cost
+            ));
+        }
+        let loopback = matches!(self.server.host.as_str(), "127.0.0.1" | "::1" | "localhost");
+        let has_tcp_listener = !self.server.effective_listeners().is_empty();
+        if self.server.tls.is_none() && !loopback && has_tcp_listener {
+            warnings.push(format!(
+                "server.tls is not set; the API is served over plain HTTP on {}",
+                self.server.host
+            ));
+        }
+        if self.puppetdb.as_ref().is_some_and(|p| !p.ssl_verify) {
+            warnings.push("puppetdb.ssl_verify is disabled".to_string());
+        }
+        if self.puppet_ca.as_ref().is_some_and(|ca| !ca.ssl_verify) {
+            warnings.push("puppet_ca.ssl_verify is disabled".to_string());
+        }
+        for sink in &self.audit.sinks {
+            if let AuditSinkTarget::Http {
+                url,
+                ssl_verify: false,
+                ..
+            } = &sink.target
+            {
+                warnings.push(format!("audit sink {} does not verify certificates", url));
+            }
+        }
+        let level = self.logging.level.to_lowercase();
+        if level.contains("debug") || level.contains("trace") {
+            warnings.push(format!(
+                "logging.level is '{}'; verbose logs may contain request details",
+                self.logging.level
+            ));
+        }
+
+        warnings
+    }
+
+    /// The configuration as JSON with secrets replaced by `[REDACTED]`
+    ///
+    /// String values of credential-like keys are redacted unless they are
+    /// `secret:` references or name a file, directory or URL.
+    pub fn redacted(&self) -> serde_json::Value {
+        fn is_secret_key(key: &str) -> bool {
+            const MARKERS: &[&str] = &[
+                "password",
+                "secret",
+                "token",
+                "master_key",
+                "private_key",
+                "api_key",
+                "encryption_key",
+                "credential",
+            ];
+            const EXEMPT_SUFFIXES: &[&str] = &["_file", "_path", "_dir", "_url"];
+            let key = key.to_ascii_lowercase();
+            MARKERS.iter().any(|m| key.contains(m))
+                && !EXEMPT_SUFFIXES.iter().any(|s| key.ends_with(s))
+        }
+
+        fn redact(value: &mut serde_json::Value) {
+            match value {
+                serde_json::Value::Object(map) => {
+                    for (key, value) in map.iter_mut() {
+                        match value {
+                            serde_json::Value::String(s)
+                                if is_secret_key(key) && !s.starts_with("secret:") =>
+                            {
+                                *s = "[REDACTED]".to_string();
+                            }
+                            _ => redact(value),
+                        }
+                    }
+                }
+                serde_json::Value::Array(items) => items.iter_mut().for_each(redact),
+                _ => {}
+            }
+        }
+
+        let mut value = serde_json::to_value(self).unwrap_or(serde_json::Value::Null);
+        redact(&mut value);
+        value
+    }
+
     /// Find the configuration file in standard locations
     fn find_config_file() -> Option<PathBuf> {
         let paths = [
@@ -1667,6 +3733,9 @@ impl AppConfig {
         if let Ok(secret) = std::env::var("JWT_SECRET") {
             self.auth.jwt_secret = secret;
         }
+        if let Ok(key) = std::env::var("OPENVOX_SETTINGS_MASTER_KEY") {
+            self.settings_encryption.master_key = Some(key);
+        }
 
         // Logging overrides
         if let Ok(level) = std::env::var("RUST_LOG") {
@@ -1690,6 +3759,7 @@ impl AppConfig {
                 ssl_key: None,
                 ssl_ca: None,
                 ssl: None,
+                fault_injection: None,
             });
             puppetdb.url = url;
         }
@@ -1721,6 +3791,11 @@ impl AppConfig {
                 ssl_key: None,
                 ssl_ca: None,
                 ssl: None,
+                crl_cache_secs: default_crl_cache_secs(),
+                snapshot_interval_secs: default_ca_snapshot_interval(),
+                leaf_expiry_warning_days: default_leaf_expiry_warning_days(),
+                ca_expiry_warning_days: default_ca_expiry_warning_days(),
+                fault_injection: None,
             });
             puppet_ca.url = url;
         }
@@ -1752,6 +3827,7 @@ impl AppConfig {
                     min_version: std::env::var("OPENVOX_TLS_MIN_VERSION")
                         .unwrap_or_else(|_| default_min_tls_version()),
                     ciphers: Vec::new(),
+                    client_auth: self.server.tls.take().and_then(|tls| tls.client_auth),
                 });
             }
         }
@@ -1766,6 +3842,16 @@ impl AppConfig {
             self.server.serve_frontend = serve.parse().unwrap_or(true);
         }
 
+        // Base path override
+        if let Ok(base_path) = std::env::var("OPENVOX_BASE_PATH") {
+            self.server.base_path = base_path;
+        }
+
+        // Precompressed static assets override
+        if let Ok(precompressed) = std::env::var("OPENVOX_STATIC_PRECOMPRESSED") {
+            self.server.static_assets.precompressed = precompressed.parse().unwrap_or(true);
+        }
+
         // Logging target override
         if let Ok(target) = std::env::var("OPENVOX_LOG_TARGET") {
             self.logging.target = match target.to_lowercase().as_str() {
@@ -1827,6 +3913,11 @@ impl AppConfig {
                 code_deploy.r10k_binary_path = PathBuf::from(path);
             }
         }
+        if let Ok(path) = std::env::var("CODE_DEPLOY_LIBRARIAN_PUPPET_BINARY_PATH") {
+            if let Some(ref mut code_deploy) = self.code_deploy {
+                code_deploy.librarian_puppet_binary_path = PathBuf::from(path);
+            }
+        }
         if let Ok(path) = std::env::var("CODE_DEPLOY_R10K_CONFIG_PATH") {
             if let Some(ref mut code_deploy) = self.code_deploy {
                 code_deploy.r10k_config_path = PathBuf::from(path);
@@ -1856,6 +3947,13 @@ impl AppConfig {
                 }
             }
         }
+        if let Ok(val) = std::env::var("CODE_DEPLOY_MAX_CONCURRENT_DEPLOYMENTS") {
+            if let Ok(n) = val.parse() {
+                if let Some(ref mut code_deploy) = self.code_deploy {
+                    code_deploy.max_concurrent_deployments = n;
+                }
+            }
+        }
 
         // SAML overrides - create SAML config from env vars if SAML_ENABLED is true
         if let Ok(enabled) = std::env::var("SAML_ENABLED") {
@@ -2003,11 +4101,113 @@ impl AppConfig {
 
     /// Validate configuration
     fn validate(&self) -> Result<()> {
-        // Validate JWT secret length
-        if self.auth.jwt_secret.len() < 32 {
+        // Validate JWT secret length (secret references are checked once resolved)
+        if !crate::services::secrets::is_secret_ref(&self.auth.jwt_secret)
+            && self.auth.jwt_secret.len() < 32
+        {
             anyhow::bail!("JWT secret must be at least 32 characters long");
         }
 
+        // Validate password policy
+        let policy = &self.auth.password_policy;
+        if policy.history_count > crate::services::password_policy::MAX_PASSWORD_HISTORY {
+            anyhow::bail!(
+                "auth.password_policy.history_count cannot exceed {}",
+                crate::services::password_policy::MAX_PASSWORD_HISTORY
+            );
+        }
+        if policy.max_age_days == Some(0) {
+            anyhow::bail!("auth.password_policy.max_age_days must be at least 1");
+        }
+        if self.auth.signing_keys.grace_hours == Some(0) {
+            anyhow::bail!("auth.signing_keys.grace_hours must be at least 1");
+        }
+        if self.auth.login_alerts.history_days == 0 {
+            anyhow::bail!("auth.login_alerts.history_days must be at least 1");
+        }
+
+        if self.rbac.max_lockout_minutes < self.rbac.lockout_duration_minutes {
+            anyhow::bail!(
+                "rbac.max_lockout_minutes cannot be less than rbac.lockout_duration_minutes"
+            );
+        }
+
+        // Validate audit retention and sinks
+        if self.audit.retention_days == Some(0) {
+            anyhow::bail!("audit.retention_days must be at least 1");
+        }
+        for (i, sink) in self.audit.sinks.iter().enumerate() {
+            if sink.buffer_size == 0 || sink.batch_size == 0 {
+                anyhow::bail!(
+                    "audit.sinks[{}]: buffer_size and batch_size must be at least 1",
+                    i
+                );
+            }
+            match &sink.target {
+                AuditSinkTarget::Syslog {
+                    address, facility, ..
+                } => {
+                    if address.is_empty() {
+                        anyhow::bail!("audit.sinks[{}]: syslog address cannot be empty", i);
+                    }
+                    if *facility > 23 {
+                        anyhow::bail!("audit.sinks[{}]: syslog facility must be 0-23", i);
+                    }
+                }
+                AuditSinkTarget::Http { url, .. } => {
+                    if !url.starts_with("http://") && !url.starts_with("https://") {
+                        anyhow::bail!("audit.sinks[{}]: url must be an http(s) URL", i);
+                    }
+                }
+            }
+        }
+
+        if let Err(e) =
+            crate::services::group_templates::validate(&self.organizations.group_templates)
+        {
+            anyhow::bail!("organizations.group_templates: {}", e);
+        }
+
+        if let Some(ref backup) = self.backup {
+            for (i, target) in backup.upload_targets.iter().enumerate() {
+                if let Err(e) = crate::services::report_delivery::validate_target(target) {
+                    anyhow::bail!("backup.upload_targets[{}]: {}", i, e);
+                }
+            }
+        }
+
+        if let Some(ref unix_socket) = self.server.unix_socket {
+            unix_socket.mode_bits()?;
+            if unix_socket.path.as_os_str().is_empty() {
+                anyhow::bail!("server.unix_socket.path cannot be empty");
+            }
+        }
+
+        for proxy in &self.server.trusted_proxies {
+            if crate::services::api_key_policy::IpRule::parse(proxy).is_none() {
+                anyhow::bail!("Invalid server.trusted_proxies entry: {}", proxy);
+            }
+        }
+        crate::middleware::SecurityHeaders::from_config(&self.server.security_headers)
+            .context("Invalid server.security_headers")?;
+        crate::middleware::CsrfProtection::from_config(&self.auth.csrf)
+            .context("Invalid auth.csrf")?;
+        if self.auth.cookie.same_site == CookieSameSite::None && !self.auth.cookie.secure {
+            anyhow::bail!("auth.cookie.same_site none requires auth.cookie.secure");
+        }
+        for route in &self.server.body_limits.routes {
+            if !route.path.starts_with('/') {
+                anyhow::bail!(
+                    "server.body_limits.routes path must start with '/': {}",
+                    route.path
+                );
+            }
+        }
+
+        if self.webhooks.max_attempts == 0 || self.webhooks.timeout_secs == 0 {
+            anyhow::bail!("webhooks.max_attempts and webhooks.timeout_secs must be at least 1");
+        }
+
         // Validate port
         if self.server.port == 0 {
             anyhow::bail!("Server port cannot be 0");
@@ -2017,23 +4217,95 @@ impl AppConfig {
         if self.database.url.is_empty() {
             anyhow::bail!("Database URL cannot be empty");
         }
+        if self.database.max_connections == 0 {
+            anyhow::bail!("database.max_connections must be at least 1");
+        }
+        if self.database.min_connections > self.database.max_connections {
+            anyhow::bail!("database.min_connections cannot exceed database.max_connections");
+        }
 
         // Validate TLS configuration if present
         if let Some(ref tls) = self.server.tls {
-            if !tls.cert_file.exists() {
-                anyhow::bail!("TLS certificate file not found: {:?}", tls.cert_file);
+            tls.validate()?;
+        }
+
+        // Validate listeners
+        let mut bound = std::collections::HashSet::new();
+        let all_listeners = self
+            .server
+            .effective_listeners()
+            .into_iter()
+            .chain(self.server.effective_enc_listener());
+        for listener in all_listeners {
+            let addr = listener.socket_addr()?;
+            if listener.port == 0 {
+                anyhow::bail!("Listener port cannot be 0 ({})", listener.host);
             }
-            if !tls.key_file.exists() {
-                anyhow::bail!("TLS key file not found: {:?}", tls.key_file);
+            if !bound.insert(addr) {
+                anyhow::bail!("Duplicate listener address: {}", addr);
             }
-            if tls.min_version != "1.2" && tls.min_version != "1.3" {
+            if let Some(ref tls) = listener.tls {
+                tls.validate()
+                    .with_context(|| format!("Invalid TLS settings for listener {}", addr))?;
+            }
+        }
+
+        if self.server.http3.as_ref().is_some_and(|h3| h3.enabled)
+            && self
+                .server
+                .effective_listeners()
+                .iter()
+                .all(|l| l.tls.is_none())
+        {
+            anyhow::bail!("server.http3 requires a listener with TLS configured");
+        }
+
+        // Validate base path (used as a route prefix and rewritten into HTML)
+        let base_path = self.server.normalized_base_path();
+        if base_path.split('/').any(|segment| segment == "." || segment == "..")
+            || !base_path
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '/' | '-' | '_' | '.' | '~'))
+        {
+            anyhow::bail!(
+                "Invalid server.base_path '{}': use path segments of letters, digits, '-', '_', '.' or '~'",
+                self.server.base_path
+            );
+        }
+
+        let fault_injection = [
+            (
+                "puppetdb",
+                self.puppetdb.as_ref().and_then(|c| c.fault_injection.as_ref()),
+            ),
+            (
+                "puppet_ca",
+                self.puppet_ca.as_ref().and_then(|c| c.fault_injection.as_ref()),
+            ),
+            (
+                "code_deploy",
+                self.code_deploy.as_ref().and_then(|c| c.fault_injection.as_ref()),
+            ),
+        ];
+        for (section, faults) in fault_injection {
+            if faults.is_some_and(|f| !(0.0..=1.0).contains(&f.error_rate)) {
                 anyhow::bail!(
-                    "Invalid TLS minimum version: {}. Must be '1.2' or '1.3'",
-                    tls.min_version
+                    "{}.fault_injection.error_rate must be between 0.0 and 1.0",
+                    section
                 );
             }
         }
 
+        // A janitor threshold below a day would purge healthy nodes between runs
+        if let Some(ref node_removal) = self.node_removal {
+            if node_removal.janitor.stale_after_days < 1 {
+                anyhow::bail!("node_removal.janitor.stale_after_days must be at least 1");
+            }
+            if node_removal.janitor.interval_secs == 0 {
+                anyhow::bail!("node_removal.janitor.interval_secs cannot be 0");
+            }
+        }
+
         // Validate static directory if specified
         if let Some(ref static_dir) = self.server.static_dir {
             if !static_dir.exists() {
@@ -2112,6 +4384,14 @@ logging:
         assert!(config.validate().is_err());
     }
 
+    #[test]
+    fn test_validation_database_pool_size() {
+        let mut config = AppConfig::default();
+        config.database.min_connections = 5;
+        config.database.max_connections = 2;
+        assert!(config.validate().is_err());
+    }
+
     #[test]
     fn test_validation_valid_config() {
         let config = AppConfig::default();
@@ -2187,6 +4467,7 @@ dashboard:
         assert_eq!(config.session_timeout_minutes, 480);
         assert_eq!(config.max_failed_logins, 5);
         assert_eq!(config.lockout_duration_minutes, 30);
+        assert_eq!(config.max_lockout_minutes, 1440);
         assert!(config.roles.is_empty());
     }
 
@@ -2374,4 +4655,267 @@ classification:
                 .disable_authentication
         );
     }
+
+    #[test]
+    fn test_static_assets_config_parsing() {
+        let yaml = r#"
+server:
+  host: "127.0.0.1"
+  port: 3000
+  static_assets:
+    precompressed: false
+auth:
+  jwt_secret: "test-secret-that-is-at-least-32-characters-long"
+database:
+  url: "sqlite://test.db"
+"#;
+        let config: AppConfig = serde_norway::from_str(yaml).unwrap();
+        assert!(!config.server.static_assets.precompressed);
+        assert_eq!(config.server.static_assets.immutable_max_age_secs, 31_536_000);
+    }
+
+    #[test]
+    fn test_base_path_normalization() {
+        let mut config = AppConfig::default();
+        assert_eq!(config.server.normalized_base_path(), "");
+        assert_eq!(config.server.prefixed_path("/login"), "/login");
+
+        for raw in ["puppet", "/puppet", "/puppet/", " /puppet/ "] {
+            config.server.base_path = raw.to_string();
+            assert_eq!(config.server.normalized_base_path(), "/puppet");
+        }
+        assert_eq!(config.server.prefixed_path("/login"), "/puppet/login");
+
+        config.server.base_path = "/".to_string();
+        assert_eq!(config.server.normalized_base_path(), "");
+    }
+
+    #[test]
+    fn test_settings_encryption_config_parsing() {
+        let yaml = r#"
+auth:
+  jwt_secret: "test-secret-that-is-at-least-32-characters-long"
+database:
+  url: "sqlite://test.db"
+settings_encryption:
+  master_key_file: "/etc/openvox-webui/settings.key"
+"#;
+        let config: AppConfig = serde_norway::from_str(yaml).unwrap();
+        assert!(config.settings_encryption.master_key.is_none());
+        assert_eq!(
+            config.settings_encryption.master_key_file,
+            Some(PathBuf::from("/etc/openvox-webui/settings.key"))
+        );
+    }
+
+    #[test]
+    fn test_audit_sinks_config_parsing() {
+        let yaml = r#"
+auth:
+  jwt_secret: "test-secret-that-is-at-least-32-characters-long"
+database:
+  url: "sqlite://test.db"
+audit:
+  sinks:
+    - type: syslog
+      address: "siem.example.com:6514"
+      protocol: tcp
+      format: cef
+    - name: splunk
+      type: http
+      url: "https://splunk.example.com:8088/services/collector/event"
+      format: splunk_hec
+      token: "secret:openvox/splunk#hec_token"
+      batch_size: 50
+"#;
+        let config: AppConfig = serde_norway::from_str(yaml).unwrap();
+        assert_eq!(config.audit.sinks.len(), 2);
+
+        let syslog = &config.audit.sinks[0];
+        assert!(syslog.name.is_none());
+        assert_eq!(syslog.buffer_size, 10_000);
+        match &syslog.target {
+            AuditSinkTarget::Syslog {
+                protocol,
+                format,
+                facility,
+                ..
+            } => {
+                assert_eq!(*protocol, SyslogProtocol::Tcp);
+                assert_eq!(*format, SyslogFormat::Cef);
+                assert_eq!(*facility, 13);
+            }
+            other => panic!("unexpected sink {:?}", other),
+        }
+
+        let http = &config.audit.sinks[1];
+        assert_eq!(http.name.as_deref(), Some("splunk"));
+        assert_eq!(http.batch_size, 50);
+        match &http.target {
+            AuditSinkTarget::Http {
+                format, ssl_verify, ..
+            } => {
+                assert_eq!(*format, AuditHttpFormat::SplunkHec);
+                assert!(*ssl_verify);
+            }
+            other => panic!("unexpected sink {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_effective_listeners() {
+        let yaml = r#"
+server:
+  listeners:
+    - host: "::"
+      port: 5051
+    - host: "0.0.0.0"
+      port: 5051
+    - host: "[::1]"
+      port: 8080
+      plaintext: true
+auth:
+  jwt_secret: "test-secret-that-is-at-least-32-characters-long"
+database:
+  url: "sqlite://test.db"
+"#;
+        let mut config: AppConfig = serde_norway::from_str(yaml).unwrap();
+        config.server.tls = Some(TlsConfig {
+            cert_file: PathBuf::from("server.crt"),
+            key_file: PathBuf::from("server.key"),
+            min_version: "1.2".to_string(),
+            ciphers: Vec::new(),
+            client_auth: None,
+        });
+
+        let listeners = config.server.effective_listeners();
+        assert_eq!(listeners.len(), 3);
+        assert!(listeners[0].tls.is_some());
+        assert!(listeners[1].tls.is_some());
+        assert!(listeners[2].tls.is_none());
+        assert_eq!(
+            listeners[0].socket_addr().unwrap(),
+            "[::]:5051".parse::<SocketAddr>().unwrap()
+        );
+        assert_eq!(
+            listeners[2].socket_addr().unwrap(),
+            "[::1]:8080".parse::<SocketAddr>().unwrap()
+        );
+
+        // Without listeners, host/port is the only listener
+        config.server.listeners.clear();
+        let listeners = config.server.effective_listeners();
+        assert_eq!(listeners.len(), 1);
+        assert_eq!(listeners[0].port, 5051);
+        assert!(parse_listen_addr("localhost", 80).is_err());
+    }
+
+    #[test]
+    fn test_enc_listener_config() {
+        let yaml = r#"
+server:
+  host: "0.0.0.0"
+  port: 5051
+  enc_listener:
+    host: "10.0.0.5"
+    port: 5052
+auth:
+  jwt_secret: "test-secret-that-is-at-least-32-characters-long"
+database:
+  url: "sqlite://test.db"
+"#;
+        let config: AppConfig = serde_norway::from_str(yaml).unwrap();
+        let enc = config.server.effective_enc_listener().unwrap();
+        assert_eq!(enc.host, "10.0.0.5");
+        assert_eq!(enc.port, 5052);
+        assert!(enc.tls.is_none());
+        assert!(config.server.enc_endpoints_exclusive());
+
+        assert!(!AppConfig::default().server.enc_endpoints_exclusive());
+    }
+
+    #[test]
+    fn test_unix_socket_config() {
+        let yaml = r#"
+server:
+  unix_socket:
+    path: "/run/openvox-webui/webui.sock"
+    group: "nginx"
+auth:
+  jwt_secret: "test-secret-that-is-at-least-32-characters-long"
+database:
+  url: "sqlite://test.db"
+"#;
+        let mut config: AppConfig = serde_norway::from_str(yaml).unwrap();
+        let unix_socket = config.server.unix_socket.clone().unwrap();
+        assert_eq!(unix_socket.mode_bits().unwrap(), 0o660);
+        assert_eq!(unix_socket.group.as_deref(), Some("nginx"));
+
+        // The socket replaces the default TCP listener
+        assert!(config.server.effective_listeners().is_empty());
+        assert!(config.validate().is_ok());
+
+        let mut invalid = unix_socket.clone();
+        invalid.mode = "0999".to_string();
+        assert!(invalid.mode_bits().is_err());
+        invalid.mode = "0o600".to_string();
+        assert_eq!(invalid.mode_bits().unwrap(), 0o600);
+
+        config.server.listeners = vec![ListenerConfig {
+            host: "127.0.0.1".to_string(),
+            port: 5051,
+            tls: None,
+            plaintext: false,
+        }];
+        assert_eq!(config.server.effective_listeners().len(), 1);
+    }
+
+    #[test]
+    fn test_redacted_config() {
+        let mut config = AppConfig::default();
+        config.auth.jwt_secret = "a-very-long-jwt-signing-secret-value".to_string();
+        config.auth.password_reset_url = Some("https://webui.example.com/reset".to_string());
+
+        let redacted = config.redacted();
+        assert_eq!(redacted["auth"]["jwt_secret"], "[REDACTED]");
+        assert_eq!(redacted["auth"]["token_expiry_hours"], 24);
+        assert_eq!(
+            redacted["auth"]["password_reset_url"],
+            "https://webui.example.com/reset"
+        );
+        assert!(!redacted
+            .to_string()
+            .contains("a-very-long-jwt-signing-secret-value"));
+
+        config.auth.jwt_secret = "secret:openvox/webui#jwt".to_string();
+        assert_eq!(
+            config.redacted()["auth"]["jwt_secret"],
+            "secret:openvox/webui#jwt"
+        );
+    }
+
+    #[test]
+    fn test_config_warnings() {
+        let mut config = AppConfig::default();
+        config.server.host = "127.0.0.1".to_string();
+        config.auth.jwt_secret = "a-unique-secret-that-is-long-enough-to-use".to_string();
+        config.logging.level = "info".to_string();
+        assert!(config.warnings().is_empty(), "{:?}", config.warnings());
+
+        config.server.host = "0.0.0.0".to_string();
+        config.auth.jwt_secret = DEFAULT_JWT_SECRET.to_string();
+        config.logging.level = "debug".to_string();
+        let warnings = config.warnings();
+        assert_eq!(warnings.len(), 3, "{:?}", warnings);
+        assert!(warnings[0].starts_with("auth.jwt_secret"));
+    }
+
+    #[test]
+    fn test_changed_paths() {
+        let before = serde_json::json!({"server": {"host": "0.0.0.0", "port": 5051}, "a": 1});
+        let after = serde_json::json!({"server": {"host": "0.0.0.0", "port": 8443}, "b": 2});
+        let mut paths = Vec::new();
+        changed_paths(&before, &after, "", &mut paths);
+        assert_eq!(paths, vec!["a", "b", "server.port"]);
+    }
 }
@@ -0,0 +1,130 @@
+//! Account lockout repository
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::AccountLockout;
+
+#[derive(Debug, sqlx::FromRow)]
+struct AccountLockoutRow {
+    user_id: String,
+    failed_attempts: i64,
+    last_failed_at: Option<String>,
+    locked_until: Option<String>,
+    lockouts: i64,
+}
+
+pub struct AccountLockoutRepository<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> AccountLockoutRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn get(&self, user_id: Uuid) -> Result<Option<AccountLockout>> {
+        let row = sqlx::query_as::<_, AccountLockoutRow>(
+            r#"
+            SELECT user_id, failed_attempts, last_failed_at, locked_until, lockouts
+            FROM account_lockouts
+            WHERE user_id = ?
+            "#,
+        )
+        .bind(user_id.to_string())
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to get account lockout")?;
+
+        row.map(row_to_lockout).transpose()
+    }
+
+    /// Count a failed login
+    ///
+    /// The count restarts when the previous failure was before
+    /// `window_start`, and the lockout count when the account has been quiet
+    /// since before `forgive_before`.
+    pub async fn record_failure(
+        &self,
+        user_id: Uuid,
+        now: DateTime<Utc>,
+        window_start: DateTime<Utc>,
+        forgive_before: DateTime<Utc>,
+    ) -> Result<AccountLockout> {
+        let row = sqlx::query_as::<_, AccountLockoutRow>(
+            r#"
+            INSERT INTO account_lockouts (user_id, failed_attempts, last_failed_at, lockouts)
+            VALUES (?1, 1, ?2, 0)
+            ON CONFLICT(user_id) DO UPDATE SET
+                failed_attempts = CASE
+                    WHEN last_failed_at IS NULL OR last_failed_at < ?3 THEN 1
+                    ELSE failed_attempts + 1
+                END,
+                lockouts = CASE
+                    WHEN COALESCE(locked_until, last_failed_at, '') < ?4 THEN 0
+                    ELSE lockouts
+                END,
+                last_failed_at = ?2
+            RETURNING user_id, failed_attempts, last_failed_at, locked_until, lockouts
+            "#,
+        )
+        .bind(user_id.to_string())
+        .bind(now.to_rfc3339())
+        .bind(window_start.to_rfc3339())
+        .bind(forgive_before.to_rfc3339())
+        .fetch_one(self.pool)
+        .await
+        .context("Failed to record failed login")?;
+
+        row_to_lockout(row)
+    }
+
+    /// Lock an account until a point in time and restart its failure count
+    pub async fn lock(&self, user_id: Uuid, until: DateTime<Utc>) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE account_lockouts
+            SET locked_until = ?, lockouts = lockouts + 1, failed_attempts = 0
+            WHERE user_id = ?
+            "#,
+        )
+        .bind(until.to_rfc3339())
+        .bind(user_id.to_string())
+        .execute(self.pool)
+        .await
+        .context("Failed to lock account")?;
+        Ok(())
+    }
+
+    /// Forget the failed logins and lockouts of an account; returns false
+    /// when there were none
+    pub async fn clear(&self, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM account_lockouts WHERE user_id = ?")
+            .bind(user_id.to_string())
+            .execute(self.pool)
+            .await
+            .context("Failed to clear account lockout")?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn row_to_lockout(row: AccountLockoutRow) -> Result<AccountLockout> {
+    let parse = |ts: Option<String>| -> Result<Option<DateTime<Utc>>> {
+        ts.map(|ts| {
+            DateTime::parse_from_rfc3339(&ts)
+                .map(|dt| dt.with_timezone(&Utc))
+                .context("Invalid account lockout timestamp")
+        })
+        .transpose()
+    };
+
+    Ok(AccountLockout {
+        user_id: Uuid::parse_str(&row.user_id).context("Invalid user id")?,
+        failed_attempts: row.failed_attempts.max(0) as u32,
+        last_failed_at: parse(row.last_failed_at)?,
+        locked_until: parse(row.locked_until)?,
+        lockouts: row.lockouts.max(0) as u32,
+    })
+}
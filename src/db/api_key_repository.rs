@@ -5,7 +5,8 @@ use chrono::{DateTime, Utc};
 use sqlx::{Row, SqlitePool};
 use uuid::Uuid;
 
-use crate::models::{ApiKey, CreateApiKeyRequest};
+use crate::models::{ApiKey, ApiKeyScope, CreateApiKeyRequest};
+use crate::services::rbac_db::{parse_action, parse_resource};
 
 #[derive(Debug, sqlx::FromRow)]
 struct ApiKeyRow {
@@ -13,8 +14,11 @@ struct ApiKeyRow {
     organization_id: String,
     user_id: String,
     name: String,
+    allowed_ips: Option<String>,
     last_used_at: Option<String>,
     expires_at: Option<String>,
+    rotated_at: Option<String>,
+    previous_key_expires_at: Option<String>,
     created_at: String,
 }
 
@@ -30,7 +34,8 @@ impl<'a> ApiKeyRepository<'a> {
     pub async fn list_for_user(&self, organization_id: Uuid, user_id: Uuid) -> Result<Vec<ApiKey>> {
         let rows = sqlx::query_as::<_, ApiKeyRow>(
             r#"
-            SELECT id, organization_id, user_id, name, last_used_at, expires_at, created_at
+            SELECT id, organization_id, user_id, name, allowed_ips, last_used_at, expires_at,
+                   rotated_at, previous_key_expires_at, created_at
             FROM api_keys
             WHERE organization_id = ? AND user_id = ?
             ORDER BY created_at DESC
@@ -52,7 +57,8 @@ impl<'a> ApiKeyRepository<'a> {
     pub async fn get_by_id(&self, organization_id: Uuid, id: Uuid) -> Result<Option<ApiKey>> {
         let row = sqlx::query_as::<_, ApiKeyRow>(
             r#"
-            SELECT id, organization_id, user_id, name, last_used_at, expires_at, created_at
+            SELECT id, organization_id, user_id, name, allowed_ips, last_used_at, expires_at,
+                   rotated_at, previous_key_expires_at, created_at
             FROM api_keys
             WHERE organization_id = ? AND id = ?
             "#,
@@ -80,6 +86,40 @@ impl<'a> ApiKeyRepository<'a> {
         Ok(result.rows_affected() > 0)
     }
 
+    /// Replace the secret of a key
+    ///
+    /// With `grace_until` the current secret stays valid until then;
+    /// without it the current secret stops working immediately. Returns
+    /// false when the key does not exist.
+    pub async fn rotate(
+        &self,
+        organization_id: Uuid,
+        id: Uuid,
+        key_hash: &str,
+        grace_until: Option<DateTime<Utc>>,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE api_keys
+            SET previous_key_hash = CASE WHEN ?1 IS NULL THEN NULL ELSE key_hash END,
+                previous_key_expires_at = ?1,
+                key_hash = ?2,
+                rotated_at = ?3
+            WHERE organization_id = ?4 AND id = ?5
+            "#,
+        )
+        .bind(grace_until.map(|d| d.to_rfc3339()))
+        .bind(key_hash)
+        .bind(Utc::now().to_rfc3339())
+        .bind(organization_id.to_string())
+        .bind(id.to_string())
+        .execute(self.pool)
+        .await
+        .context("Failed to rotate api key")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     pub async fn create_hashed_key(
         &self,
         api_key_id: Uuid,
@@ -90,11 +130,19 @@ impl<'a> ApiKeyRepository<'a> {
         role_ids: &[Uuid],
     ) -> Result<ApiKey> {
         let created_at = Utc::now();
+        let allowed_ips = match req.allowed_ips.as_deref() {
+            Some(ips) if !ips.is_empty() => {
+                Some(serde_json::to_string(ips).context("Failed to encode allowed IPs")?)
+            }
+            _ => None,
+        };
 
         sqlx::query(
             r#"
-            INSERT INTO api_keys (id, user_id, organization_id, name, key_hash, expires_at, created_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO api_keys (
+                id, user_id, organization_id, name, key_hash, allowed_ips, expires_at, created_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(api_key_id.to_string())
@@ -102,6 +150,7 @@ impl<'a> ApiKeyRepository<'a> {
         .bind(organization_id.to_string())
         .bind(&req.name)
         .bind(key_hash)
+        .bind(allowed_ips)
         .bind(req.expires_at.map(|d| d.to_rfc3339()))
         .bind(created_at.to_rfc3339())
         .execute(self.pool)
@@ -120,6 +169,22 @@ impl<'a> ApiKeyRepository<'a> {
             .context("Failed to assign api key roles")?;
         }
 
+        for scope in req.scopes.as_deref().unwrap_or_default() {
+            sqlx::query(
+                r#"
+                INSERT OR IGNORE INTO api_key_permissions (id, api_key_id, resource, action)
+                VALUES (?, ?, ?, ?)
+                "#,
+            )
+            .bind(Uuid::new_v4().to_string())
+            .bind(api_key_id.to_string())
+            .bind(scope.resource.as_str())
+            .bind(scope.action.as_str())
+            .execute(self.pool)
+            .await
+            .context("Failed to assign api key scopes")?;
+        }
+
         self.get_by_id(organization_id, api_key_id)
             .await?
             .context("Failed to retrieve created api key")
@@ -128,6 +193,11 @@ impl<'a> ApiKeyRepository<'a> {
     async fn row_to_api_key(&self, row: ApiKeyRow) -> Result<ApiKey> {
         let id = Uuid::parse_str(&row.id).context("Invalid api key id")?;
         let role_ids = self.get_role_ids(id).await?;
+        let scopes = self.get_scopes(id).await?;
+        let allowed_ips = match row.allowed_ips.as_deref() {
+            Some(json) => serde_json::from_str(json).context("Invalid api key allowed IPs")?,
+            None => Vec::new(),
+        };
 
         Ok(ApiKey {
             id,
@@ -136,8 +206,15 @@ impl<'a> ApiKeyRepository<'a> {
             user_id: Uuid::parse_str(&row.user_id).context("Invalid user id")?,
             name: row.name,
             role_ids,
+            scopes,
+            allowed_ips,
             last_used_at: row.last_used_at.as_deref().map(parse_db_timestamp),
             expires_at: row.expires_at.as_deref().map(parse_db_timestamp),
+            rotated_at: row.rotated_at.as_deref().map(parse_db_timestamp),
+            previous_key_expires_at: row
+                .previous_key_expires_at
+                .as_deref()
+                .map(parse_db_timestamp),
             created_at: parse_db_timestamp(&row.created_at),
         })
     }
@@ -156,6 +233,30 @@ impl<'a> ApiKeyRepository<'a> {
             .filter_map(|s| Uuid::parse_str(&s).ok())
             .collect())
     }
+
+    /// Permission scopes of a key, in a stable order
+    pub async fn get_scopes(&self, api_key_id: Uuid) -> Result<Vec<ApiKeyScope>> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            r#"
+            SELECT resource, action FROM api_key_permissions
+            WHERE api_key_id = ?
+            ORDER BY resource, action
+            "#,
+        )
+        .bind(api_key_id.to_string())
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to fetch api key scopes")?;
+
+        rows.into_iter()
+            .map(|(resource, action)| {
+                Ok(ApiKeyScope {
+                    resource: parse_resource(&resource)?,
+                    action: parse_action(&action)?,
+                })
+            })
+            .collect()
+    }
 }
 
 fn parse_db_timestamp(ts: &str) -> DateTime<Utc> {
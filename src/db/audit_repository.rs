@@ -5,28 +5,38 @@ use chrono::{DateTime, Utc};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
+use crate::db::retry_busy;
 use crate::models::{AuditLogEntry, AuditLogQuery};
+use crate::services::audit_forwarding::AuditForwarder;
 
 #[derive(Debug, sqlx::FromRow)]
 struct AuditRow {
     id: String,
     organization_id: String,
     user_id: Option<String>,
+    impersonator_id: Option<String>,
+    user_kind: Option<String>,
     action: String,
     resource_type: String,
     resource_id: Option<String>,
     details: Option<String>,
     ip_address: Option<String>,
+    request_id: Option<String>,
     created_at: String,
 }
 
+type AuditQuery<'q> =
+    sqlx::query::QueryAs<'q, sqlx::Sqlite, AuditRow, sqlx::sqlite::SqliteArguments>;
+
 pub struct AuditRepository<'a> {
     pool: &'a SqlitePool,
+    forwarder: Option<&'a AuditForwarder>,
 }
 
 impl<'a> AuditRepository<'a> {
-    pub fn new(pool: &'a SqlitePool) -> Self {
-        Self { pool }
+    /// Inserted entries are also queued on `forwarder`, if any
+    pub fn new(pool: &'a SqlitePool, forwarder: Option<&'a AuditForwarder>) -> Self {
+        Self { pool, forwarder }
     }
 
     pub async fn insert(
@@ -42,37 +52,66 @@ impl<'a> AuditRepository<'a> {
         let id = Uuid::new_v4();
         let created_at = Utc::now().to_rfc3339();
         let details_str = details.map(|d| d.to_string());
+        let request_id = crate::middleware::current_request_id();
+        let impersonator_id = crate::middleware::auth::current_impersonator();
+        let user_kind = match user_id {
+            Some(user_id) => {
+                sqlx::query_scalar::<_, String>("SELECT kind FROM users WHERE id = ?")
+                    .bind(user_id.to_string())
+                    .fetch_optional(self.pool)
+                    .await
+                    .context("Failed to look up user kind")?
+                    .and_then(|kind| kind.parse().ok())
+            }
+            None => None,
+        };
 
-        sqlx::query(
-            r#"
-            INSERT INTO audit_log (id, organization_id, user_id, action, resource_type, resource_id, details, ip_address, created_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(id.to_string())
-        .bind(organization_id.to_string())
-        .bind(user_id.map(|u| u.to_string()))
-        .bind(action)
-        .bind(resource_type)
-        .bind(resource_id)
-        .bind(details_str.as_deref())
-        .bind(ip_address)
-        .bind(&created_at)
-        .execute(self.pool)
-        .await
-        .context("Failed to insert audit log entry")?;
+        // Every audited request writes here, so it is the first write to hit
+        // a busy database
+        retry_busy(|| async {
+            sqlx::query(
+                r#"
+                INSERT INTO audit_log (id, organization_id, user_id, impersonator_id, user_kind, action, resource_type, resource_id, details, ip_address, request_id, created_at)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(id.to_string())
+            .bind(organization_id.to_string())
+            .bind(user_id.map(|u| u.to_string()))
+            .bind(impersonator_id.map(|u| u.to_string()))
+            .bind(user_kind.map(|k| k.to_string()))
+            .bind(action)
+            .bind(resource_type)
+            .bind(resource_id)
+            .bind(details_str.as_deref())
+            .bind(ip_address)
+            .bind(request_id.as_deref())
+            .bind(&created_at)
+            .execute(self.pool)
+            .await
+            .context("Failed to insert audit log entry")
+        })
+        .await?;
 
-        Ok(AuditLogEntry {
+        let entry = AuditLogEntry {
             id,
             organization_id,
             user_id,
+            impersonator_id,
+            user_kind,
             action: action.to_string(),
             resource_type: resource_type.to_string(),
             resource_id: resource_id.map(|s| s.to_string()),
             details: details.map(|d| d.clone()),
             ip_address: ip_address.map(|s| s.to_string()),
+            request_id,
             created_at: parse_db_timestamp(&created_at),
-        })
+        };
+        if let Some(forwarder) = self.forwarder {
+            forwarder.forward(&entry);
+        }
+
+        Ok(entry)
     }
 
     pub async fn list(
@@ -81,18 +120,9 @@ impl<'a> AuditRepository<'a> {
         query: &AuditLogQuery,
     ) -> Result<Vec<AuditLogEntry>> {
         let mut sql = String::from(
-            "SELECT id, organization_id, user_id, action, resource_type, resource_id, details, ip_address, created_at FROM audit_log WHERE organization_id = ?",
+            "SELECT id, organization_id, user_id, impersonator_id, user_kind, action, resource_type, resource_id, details, ip_address, request_id, created_at FROM audit_log WHERE organization_id = ?",
         );
-
-        if query.user_id.is_some() {
-            sql.push_str(" AND user_id = ?");
-        }
-        if query.resource_type.is_some() {
-            sql.push_str(" AND resource_type = ?");
-        }
-        if query.action.is_some() {
-            sql.push_str(" AND action = ?");
-        }
+        push_filters(&mut sql, query);
 
         sql.push_str(" ORDER BY created_at DESC");
 
@@ -107,15 +137,7 @@ impl<'a> AuditRepository<'a> {
 
         let mut q = sqlx::query_as::<_, AuditRow>(sqlx::AssertSqlSafe(sql.as_str()))
             .bind(organization_id.to_string());
-        if let Some(user_id) = query.user_id {
-            q = q.bind(user_id.to_string());
-        }
-        if let Some(ref resource_type) = query.resource_type {
-            q = q.bind(resource_type);
-        }
-        if let Some(ref action) = query.action {
-            q = q.bind(action);
-        }
+        q = bind_filters(q, query);
         if let Some(limit) = query.limit {
             q = q.bind(limit as i64);
         }
@@ -130,6 +152,137 @@ impl<'a> AuditRepository<'a> {
 
         Ok(rows.into_iter().map(row_to_audit).collect())
     }
+
+    /// All entries matching the filters, oldest first, without paging
+    ///
+    /// Returns at most `max_rows` entries.
+    pub async fn export(
+        &self,
+        organization_id: Uuid,
+        query: &AuditLogQuery,
+        max_rows: u32,
+    ) -> Result<Vec<AuditLogEntry>> {
+        let mut sql = String::from(
+            "SELECT id, organization_id, user_id, impersonator_id, user_kind, action, resource_type, resource_id, details, ip_address, request_id, created_at FROM audit_log WHERE organization_id = ?",
+        );
+        push_filters(&mut sql, query);
+        sql.push_str(" ORDER BY created_at ASC LIMIT ?");
+
+        let q = sqlx::query_as::<_, AuditRow>(sqlx::AssertSqlSafe(sql.as_str()))
+            .bind(organization_id.to_string());
+        let rows = bind_filters(q, query)
+            .bind(max_rows as i64)
+            .fetch_all(self.pool)
+            .await
+            .context("Failed to export audit logs")?;
+
+        Ok(rows.into_iter().map(row_to_audit).collect())
+    }
+
+    /// Oldest entries of all organizations created before `cutoff`
+    pub async fn list_before(
+        &self,
+        cutoff: DateTime<Utc>,
+        limit: u32,
+    ) -> Result<Vec<AuditLogEntry>> {
+        let rows = sqlx::query_as::<_, AuditRow>(
+            "SELECT id, organization_id, user_id, impersonator_id, user_kind, action, resource_type, resource_id, details, ip_address, request_id, created_at FROM audit_log WHERE created_at < ? ORDER BY created_at ASC LIMIT ?",
+        )
+        .bind(cutoff.to_rfc3339())
+        .bind(limit as i64)
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to list expired audit logs")?;
+
+        Ok(rows.into_iter().map(row_to_audit).collect())
+    }
+
+    /// Delete entries by id
+    pub async fn delete_by_ids(&self, ids: &[Uuid]) -> Result<u64> {
+        if ids.is_empty() {
+            return Ok(0);
+        }
+
+        let placeholders = vec!["?"; ids.len()].join(", ");
+        let sql = format!("DELETE FROM audit_log WHERE id IN ({})", placeholders);
+        let mut q = sqlx::query(sqlx::AssertSqlSafe(sql));
+        for id in ids {
+            q = q.bind(id.to_string());
+        }
+
+        let result = q
+            .execute(self.pool)
+            .await
+            .context("Failed to delete audit logs")?;
+        Ok(result.rows_affected())
+    }
+
+    /// Delete all entries created before `cutoff`
+    pub async fn delete_before(&self, cutoff: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM audit_log WHERE created_at < ?")
+            .bind(cutoff.to_rfc3339())
+            .execute(self.pool)
+            .await
+            .context("Failed to prune audit logs")?;
+        Ok(result.rows_affected())
+    }
+}
+
+/// Append the WHERE conditions of the query's filters
+fn push_filters(sql: &mut String, query: &AuditLogQuery) {
+    if query.user_id.is_some() {
+        sql.push_str(" AND user_id = ?");
+    }
+    if query.impersonator_id.is_some() {
+        sql.push_str(" AND impersonator_id = ?");
+    }
+    if query.user_kind.is_some() {
+        sql.push_str(" AND user_kind = ?");
+    }
+    if query.resource_type.is_some() {
+        sql.push_str(" AND resource_type = ?");
+    }
+    if query.action.is_some() {
+        sql.push_str(" AND action = ?");
+    }
+    if query.request_id.is_some() {
+        sql.push_str(" AND request_id = ?");
+    }
+    if query.from.is_some() {
+        sql.push_str(" AND created_at >= ?");
+    }
+    if query.to.is_some() {
+        sql.push_str(" AND created_at < ?");
+    }
+}
+
+/// Bind the values of the conditions added by [`push_filters`]
+fn bind_filters<'q>(mut q: AuditQuery<'q>, query: &'q AuditLogQuery) -> AuditQuery<'q> {
+    if let Some(user_id) = query.user_id {
+        q = q.bind(user_id.to_string());
+    }
+    if let Some(impersonator_id) = query.impersonator_id {
+        q = q.bind(impersonator_id.to_string());
+    }
+    if let Some(user_kind) = query.user_kind {
+        q = q.bind(user_kind.to_string());
+    }
+    if let Some(ref resource_type) = query.resource_type {
+        q = q.bind(resource_type);
+    }
+    if let Some(ref action) = query.action {
+        q = q.bind(action);
+    }
+    if let Some(ref request_id) = query.request_id {
+        q = q.bind(request_id);
+    }
+    if let Some(from) = query.from {
+        q = q.bind(from.to_rfc3339());
+    }
+    if let Some(to) = query.to {
+        q = q.bind(to.to_rfc3339());
+    }
+    q
 }
 
 fn parse_db_timestamp(ts: &str) -> DateTime<Utc> {
@@ -147,11 +300,17 @@ fn row_to_audit(row: AuditRow) -> AuditLogEntry {
         id: Uuid::parse_str(&row.id).unwrap_or_else(|_| Uuid::nil()),
         organization_id: Uuid::parse_str(&row.organization_id).unwrap_or_else(|_| Uuid::nil()),
         user_id: row.user_id.as_deref().and_then(|s| Uuid::parse_str(s).ok()),
+        impersonator_id: row
+            .impersonator_id
+            .as_deref()
+            .and_then(|s| Uuid::parse_str(s).ok()),
+        user_kind: row.user_kind.and_then(|s| s.parse().ok()),
         action: row.action,
         resource_type: row.resource_type,
         resource_id: row.resource_id,
         details: row.details.and_then(|s| serde_json::from_str(&s).ok()),
         ip_address: row.ip_address,
+        request_id: row.request_id,
         created_at: parse_db_timestamp(&row.created_at),
     }
 }
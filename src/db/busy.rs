@@ -0,0 +1,152 @@
+//! SQLite busy handling
+//!
+//! SQLite allows one writer at a time. A connection waits up to
+//! `database.busy_timeout_ms` for the write lock, but a transaction that has
+//! already read cannot wait for it: SQLite fails its first write with
+//! `SQLITE_BUSY` right away, the source of intermittent "database is locked"
+//! errors under load. Write transactions therefore start with
+//! [`begin_write`], which takes the lock up front, and writes on busy paths
+//! run through [`retry_busy`], which retries them with backoff up to
+//! `database.busy_retries` times.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::Duration;
+
+use anyhow::Result;
+use sqlx::{Sqlite, Transaction};
+
+use crate::db::DbPool;
+
+/// Primary result code of a database locked by another connection
+const SQLITE_BUSY: i32 = 5;
+
+/// Primary result code of a table locked within the same connection
+const SQLITE_LOCKED: i32 = 6;
+
+/// Delay before the first retry, doubled for each further one
+const INITIAL_BACKOFF: Duration = Duration::from_millis(50);
+
+static BUSY_RETRIES: AtomicU32 = AtomicU32::new(3);
+
+/// Set how many times [`retry_busy`] retries an operation
+pub fn set_busy_retries(retries: u32) {
+    BUSY_RETRIES.store(retries, Ordering::Relaxed);
+}
+
+/// Whether an error is SQLite reporting the database busy or locked
+pub fn is_busy(error: &anyhow::Error) -> bool {
+    error
+        .chain()
+        .any(|cause| match cause.downcast_ref::<sqlx::Error>() {
+            Some(sqlx::Error::Database(db_error)) => db_error
+                .code()
+                .and_then(|code| code.parse::<i32>().ok())
+                .is_some_and(|code| matches!(code & 0xff, SQLITE_BUSY | SQLITE_LOCKED)),
+            _ => false,
+        })
+}
+
+/// Run a database operation, retrying it while the database is busy
+///
+/// The operation is run again from the start, so it must be a single
+/// statement or a whole transaction.
+pub async fn retry_busy<T, F, Fut>(mut operation: F) -> Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let retries = BUSY_RETRIES.load(Ordering::Relaxed);
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 0;
+    loop {
+        match operation().await {
+            Err(e) if attempt < retries && is_busy(&e) => {
+                attempt += 1;
+                tracing::debug!("Database busy, retry {}/{}: {:#}", attempt, retries, e);
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Begin a transaction that writes
+///
+/// `BEGIN IMMEDIATE` takes the write lock when the transaction starts, where
+/// waiting for it is covered by the busy timeout, instead of at its first
+/// write, where it is not.
+pub async fn begin_write(pool: &DbPool) -> Result<Transaction<'static, Sqlite>> {
+    retry_busy(|| async { Ok(pool.begin_with("BEGIN IMMEDIATE").await?) }).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+
+    use anyhow::Context;
+    use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+
+    async fn pool(path: &std::path::Path) -> DbPool {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true)
+            .busy_timeout(Duration::ZERO);
+        SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect_with(options)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_busy_write_is_retried() {
+        let path = std::env::temp_dir().join(format!("openvox-busy-{}.db", uuid::Uuid::new_v4()));
+        let holder = pool(&path).await;
+        let writer = pool(&path).await;
+        sqlx::query("CREATE TABLE t (v INTEGER)")
+            .execute(&holder)
+            .await
+            .unwrap();
+
+        // Hold the write lock for a while from another connection
+        let tx = begin_write(&holder).await.unwrap();
+        let release = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(80)).await;
+            tx.commit().await.unwrap();
+        });
+
+        let attempts = AtomicUsize::new(0);
+        retry_busy(|| async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            sqlx::query("INSERT INTO t VALUES (1)")
+                .execute(&writer)
+                .await
+                .context("Failed to insert")?;
+            Ok(())
+        })
+        .await
+        .unwrap();
+        release.await.unwrap();
+
+        assert!(attempts.load(Ordering::SeqCst) > 1);
+        for suffix in ["", "-wal", "-shm", "-journal"] {
+            std::fs::remove_file(format!("{}{}", path.display(), suffix)).ok();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_other_errors_are_not_retried() {
+        let attempts = AtomicUsize::new(0);
+        let result: Result<()> = retry_busy(|| async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            anyhow::bail!("not busy")
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+    }
+}
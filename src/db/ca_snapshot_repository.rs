@@ -0,0 +1,81 @@
+//! Puppet CA snapshot repository
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+use crate::models::CaSnapshot;
+
+#[derive(Debug, sqlx::FromRow)]
+struct CaSnapshotRow {
+    ca_bundle_pem: Option<String>,
+    crl_pem: Option<String>,
+    certificates: String,
+    requests: String,
+    synced_at: String,
+}
+
+pub struct CaSnapshotRepository<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> CaSnapshotRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Get the last synced snapshot
+    pub async fn get(&self) -> Result<Option<CaSnapshot>> {
+        let row = sqlx::query_as::<_, CaSnapshotRow>(
+            r#"
+            SELECT ca_bundle_pem, crl_pem, certificates, requests, synced_at
+            FROM ca_snapshots
+            WHERE id = 1
+            "#,
+        )
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to load CA snapshot")?;
+
+        row.map(|row| {
+            Ok(CaSnapshot {
+                ca_bundle_pem: row.ca_bundle_pem,
+                crl_pem: row.crl_pem,
+                certificates: serde_json::from_str(&row.certificates)
+                    .context("Invalid certificates in CA snapshot")?,
+                requests: serde_json::from_str(&row.requests)
+                    .context("Invalid requests in CA snapshot")?,
+                synced_at: DateTime::parse_from_rfc3339(&row.synced_at)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .context("Invalid synced_at in CA snapshot")?,
+            })
+        })
+        .transpose()
+    }
+
+    /// Replace the stored snapshot
+    pub async fn save(&self, snapshot: &CaSnapshot) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO ca_snapshots (id, ca_bundle_pem, crl_pem, certificates, requests, synced_at)
+            VALUES (1, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET
+                ca_bundle_pem = excluded.ca_bundle_pem,
+                crl_pem = excluded.crl_pem,
+                certificates = excluded.certificates,
+                requests = excluded.requests,
+                synced_at = excluded.synced_at
+            "#,
+        )
+        .bind(&snapshot.ca_bundle_pem)
+        .bind(&snapshot.crl_pem)
+        .bind(serde_json::to_string(&snapshot.certificates)?)
+        .bind(serde_json::to_string(&snapshot.requests)?)
+        .bind(snapshot.synced_at.to_rfc3339())
+        .execute(self.pool)
+        .await
+        .context("Failed to save CA snapshot")?;
+
+        Ok(())
+    }
+}
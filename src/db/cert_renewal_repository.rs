@@ -0,0 +1,326 @@
+//! Certificate renewal campaign repository
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::db::begin_write;
+use crate::models::{
+    RenewalCampaign, RenewalCampaignStatus, RenewalCandidate, RenewalSummary, RenewalTarget,
+    RenewalTargetStatus,
+};
+
+/// Campaign columns plus target counts
+const CAMPAIGN_SELECT: &str = r#"
+    SELECT c.id, c.name, c.status, c.deadline, c.trigger_agents, c.update_job_id,
+           c.created_by, c.created_at, c.updated_at, c.completed_at,
+           COUNT(t.certname) AS total,
+           COALESCE(SUM(t.status = 'pending'), 0) AS pending,
+           COALESCE(SUM(t.status = 'renewed'), 0) AS renewed,
+           COALESCE(SUM(t.status = 'failed'), 0) AS failed
+    FROM cert_renewal_campaigns c
+    LEFT JOIN cert_renewal_targets t ON t.campaign_id = c.id
+"#;
+
+#[derive(Debug, sqlx::FromRow)]
+struct CampaignRow {
+    id: String,
+    name: String,
+    status: String,
+    deadline: String,
+    trigger_agents: i32,
+    update_job_id: Option<String>,
+    created_by: String,
+    created_at: String,
+    updated_at: String,
+    completed_at: Option<String>,
+    total: i64,
+    pending: i64,
+    renewed: i64,
+    failed: i64,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct TargetRow {
+    certname: String,
+    status: String,
+    old_serial: String,
+    old_not_after: String,
+    new_serial: Option<String>,
+    new_not_after: Option<String>,
+    error: Option<String>,
+    updated_at: String,
+}
+
+pub struct CertRenewalRepository<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> CertRenewalRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Create a campaign tracking `targets`
+    pub async fn create(
+        &self,
+        name: &str,
+        deadline: DateTime<Utc>,
+        trigger_agents: bool,
+        created_by: &str,
+        targets: &[RenewalCandidate],
+    ) -> Result<RenewalCampaign> {
+        let id = Uuid::new_v4();
+        let now = Utc::now().to_rfc3339();
+
+        let mut tx = begin_write(self.pool)
+            .await
+            .context("Failed to begin renewal campaign transaction")?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO cert_renewal_campaigns (
+                id, name, status, deadline, trigger_agents, created_by, created_at, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(id.to_string())
+        .bind(name)
+        .bind(RenewalCampaignStatus::Active.as_str())
+        .bind(deadline.to_rfc3339())
+        .bind(trigger_agents)
+        .bind(created_by)
+        .bind(&now)
+        .bind(&now)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to insert renewal campaign")?;
+
+        for target in targets {
+            sqlx::query(
+                r#"
+                INSERT INTO cert_renewal_targets (
+                    campaign_id, certname, status, old_serial, old_not_after, updated_at
+                ) VALUES (?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(id.to_string())
+            .bind(&target.certname)
+            .bind(RenewalTargetStatus::Pending.as_str())
+            .bind(&target.serial)
+            .bind(target.not_after.to_rfc3339())
+            .bind(&now)
+            .execute(&mut *tx)
+            .await
+            .with_context(|| format!("Failed to insert renewal target '{}'", target.certname))?;
+        }
+
+        tx.commit()
+            .await
+            .context("Failed to commit renewal campaign")?;
+
+        self.get(id)
+            .await?
+            .context("Renewal campaign was created but could not be reloaded")
+    }
+
+    /// Record the update job that asks the agents to renew
+    pub async fn set_update_job(&self, id: Uuid, update_job_id: &str) -> Result<()> {
+        sqlx::query(
+            "UPDATE cert_renewal_campaigns SET update_job_id = ?, updated_at = ? WHERE id = ?",
+        )
+        .bind(update_job_id)
+        .bind(Utc::now().to_rfc3339())
+        .bind(id.to_string())
+        .execute(self.pool)
+        .await
+        .context("Failed to set renewal campaign update job")?;
+
+        Ok(())
+    }
+
+    /// List campaigns, newest first
+    pub async fn list(&self, limit: i64) -> Result<Vec<RenewalCampaign>> {
+        let rows = sqlx::query_as::<_, CampaignRow>(sqlx::AssertSqlSafe(format!(
+            "{} GROUP BY c.id ORDER BY c.created_at DESC LIMIT ?",
+            CAMPAIGN_SELECT
+        )))
+        .bind(limit)
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to list renewal campaigns")?;
+
+        rows.into_iter().map(row_to_campaign).collect()
+    }
+
+    /// Campaigns whose targets are still tracked
+    pub async fn list_active(&self) -> Result<Vec<RenewalCampaign>> {
+        let rows = sqlx::query_as::<_, CampaignRow>(sqlx::AssertSqlSafe(format!(
+            "{} WHERE c.status = ? GROUP BY c.id ORDER BY c.created_at ASC",
+            CAMPAIGN_SELECT
+        )))
+        .bind(RenewalCampaignStatus::Active.as_str())
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to list active renewal campaigns")?;
+
+        rows.into_iter().map(row_to_campaign).collect()
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<Option<RenewalCampaign>> {
+        let row = sqlx::query_as::<_, CampaignRow>(sqlx::AssertSqlSafe(format!(
+            "{} WHERE c.id = ? GROUP BY c.id",
+            CAMPAIGN_SELECT
+        )))
+        .bind(id.to_string())
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to get renewal campaign")?;
+
+        row.map(row_to_campaign).transpose()
+    }
+
+    /// Targets of a campaign, optionally filtered by status
+    pub async fn list_targets(
+        &self,
+        id: Uuid,
+        status: Option<RenewalTargetStatus>,
+    ) -> Result<Vec<RenewalTarget>> {
+        let rows = sqlx::query_as::<_, TargetRow>(
+            r#"
+            SELECT certname, status, old_serial, old_not_after, new_serial, new_not_after,
+                   error, updated_at
+            FROM cert_renewal_targets
+            WHERE campaign_id = ? AND (? IS NULL OR status = ?)
+            ORDER BY certname ASC
+            "#,
+        )
+        .bind(id.to_string())
+        .bind(status.map(|s| s.as_str()))
+        .bind(status.map(|s| s.as_str()))
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to list renewal targets")?;
+
+        rows.into_iter().map(row_to_target).collect()
+    }
+
+    /// Mark a target renewed with its new certificate
+    pub async fn mark_renewed(
+        &self,
+        id: Uuid,
+        certname: &str,
+        new_serial: &str,
+        new_not_after: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE cert_renewal_targets
+            SET status = ?, new_serial = ?, new_not_after = ?, error = NULL, updated_at = ?
+            WHERE campaign_id = ? AND certname = ?
+            "#,
+        )
+        .bind(RenewalTargetStatus::Renewed.as_str())
+        .bind(new_serial)
+        .bind(new_not_after.to_rfc3339())
+        .bind(Utc::now().to_rfc3339())
+        .bind(id.to_string())
+        .bind(certname)
+        .execute(self.pool)
+        .await
+        .context("Failed to mark renewal target renewed")?;
+
+        Ok(())
+    }
+
+    /// Mark a target failed
+    pub async fn mark_failed(&self, id: Uuid, certname: &str, error: &str) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE cert_renewal_targets
+            SET status = ?, error = ?, updated_at = ?
+            WHERE campaign_id = ? AND certname = ?
+            "#,
+        )
+        .bind(RenewalTargetStatus::Failed.as_str())
+        .bind(error)
+        .bind(Utc::now().to_rfc3339())
+        .bind(id.to_string())
+        .bind(certname)
+        .execute(self.pool)
+        .await
+        .context("Failed to mark renewal target failed")?;
+
+        Ok(())
+    }
+
+    /// Move an active campaign to `status`; returns false if it was not active
+    pub async fn finish(&self, id: Uuid, status: RenewalCampaignStatus) -> Result<bool> {
+        let now = Utc::now().to_rfc3339();
+        let result = sqlx::query(
+            r#"
+            UPDATE cert_renewal_campaigns
+            SET status = ?, completed_at = ?, updated_at = ?
+            WHERE id = ? AND status = ?
+            "#,
+        )
+        .bind(status.as_str())
+        .bind(&now)
+        .bind(&now)
+        .bind(id.to_string())
+        .bind(RenewalCampaignStatus::Active.as_str())
+        .execute(self.pool)
+        .await
+        .context("Failed to update renewal campaign status")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn row_to_campaign(row: CampaignRow) -> Result<RenewalCampaign> {
+    Ok(RenewalCampaign {
+        id: Uuid::parse_str(&row.id).context("Invalid renewal campaign id")?,
+        name: row.name,
+        status: RenewalCampaignStatus::parse(&row.status)
+            .with_context(|| format!("Invalid renewal campaign status '{}'", row.status))?,
+        deadline: parse_db_timestamp(&row.deadline),
+        trigger_agents: row.trigger_agents != 0,
+        update_job_id: row.update_job_id,
+        created_by: row.created_by,
+        created_at: parse_db_timestamp(&row.created_at),
+        updated_at: parse_db_timestamp(&row.updated_at),
+        completed_at: row.completed_at.as_deref().map(parse_db_timestamp),
+        summary: RenewalSummary {
+            total: row.total as usize,
+            pending: row.pending as usize,
+            renewed: row.renewed as usize,
+            failed: row.failed as usize,
+        },
+    })
+}
+
+fn row_to_target(row: TargetRow) -> Result<RenewalTarget> {
+    Ok(RenewalTarget {
+        certname: row.certname,
+        status: RenewalTargetStatus::parse(&row.status)
+            .with_context(|| format!("Invalid renewal target status '{}'", row.status))?,
+        old_serial: row.old_serial,
+        old_not_after: parse_db_timestamp(&row.old_not_after),
+        new_serial: row.new_serial,
+        new_not_after: row.new_not_after.as_deref().map(parse_db_timestamp),
+        error: row.error,
+        updated_at: parse_db_timestamp(&row.updated_at),
+        instructions: Vec::new(),
+    })
+}
+
+fn parse_db_timestamp(ts: &str) -> DateTime<Utc> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(ts) {
+        return dt.with_timezone(&Utc);
+    }
+    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S") {
+        return DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc);
+    }
+    Utc::now()
+}
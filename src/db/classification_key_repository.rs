@@ -0,0 +1,291 @@
+//! Classification key repository
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::db::retry_busy;
+use crate::models::{
+    ClassificationKey, CreateClassificationKeyRequest, UpdateClassificationKeyRequest,
+};
+
+#[derive(Debug, sqlx::FromRow)]
+struct ClassificationKeyRow {
+    id: String,
+    name: String,
+    description: Option<String>,
+    allowed_cn_patterns: String,
+    created_by: Option<String>,
+    created_at: String,
+    rotated_at: Option<String>,
+    previous_key_expires_at: Option<String>,
+    revoked_at: Option<String>,
+    last_used_at: Option<String>,
+    last_handshake_at: Option<String>,
+    last_handshake_host: Option<String>,
+}
+
+/// Hashes a presented secret is checked against
+pub struct ClassificationKeySecrets {
+    pub key: ClassificationKey,
+    pub key_hash: String,
+    /// Hash of the secret replaced by the last rotation, while still in its
+    /// grace period
+    pub previous_key_hash: Option<String>,
+}
+
+const COLUMNS: &str = "id, name, description, allowed_cn_patterns, created_by, created_at, \
+                       rotated_at, previous_key_expires_at, revoked_at, last_used_at, \
+                       last_handshake_at, last_handshake_host";
+
+pub struct ClassificationKeyRepository<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> ClassificationKeyRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// All keys, revoked ones included, newest first
+    pub async fn list(&self) -> Result<Vec<ClassificationKey>> {
+        let rows = sqlx::query_as::<_, ClassificationKeyRow>(sqlx::AssertSqlSafe(format!(
+            "SELECT {} FROM classification_keys ORDER BY created_at DESC",
+            COLUMNS
+        )))
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to list classification keys")?;
+
+        rows.into_iter().map(row_to_key).collect()
+    }
+
+    pub async fn get_by_id(&self, id: Uuid) -> Result<Option<ClassificationKey>> {
+        let row = sqlx::query_as::<_, ClassificationKeyRow>(sqlx::AssertSqlSafe(format!(
+            "SELECT {} FROM classification_keys WHERE id = ?",
+            COLUMNS
+        )))
+        .bind(id.to_string())
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to get classification key")?;
+
+        row.map(row_to_key).transpose()
+    }
+
+    pub async fn create(
+        &self,
+        id: Uuid,
+        req: &CreateClassificationKeyRequest,
+        key_hash: &str,
+        created_by: Uuid,
+    ) -> Result<ClassificationKey> {
+        let patterns =
+            serde_json::to_string(req.allowed_cn_patterns.as_deref().unwrap_or_default())
+                .context("Failed to encode allowed CN patterns")?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO classification_keys (
+                id, name, description, key_hash, allowed_cn_patterns, created_by, created_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(id.to_string())
+        .bind(req.name.trim())
+        .bind(&req.description)
+        .bind(key_hash)
+        .bind(patterns)
+        .bind(created_by.to_string())
+        .bind(Utc::now().to_rfc3339())
+        .execute(self.pool)
+        .await
+        .context("Failed to create classification key")?;
+
+        self.get_by_id(id)
+            .await?
+            .context("Failed to retrieve created classification key")
+    }
+
+    /// Change the description or patterns of a key that is not revoked
+    pub async fn update(
+        &self,
+        id: Uuid,
+        req: &UpdateClassificationKeyRequest,
+    ) -> Result<Option<ClassificationKey>> {
+        let patterns = req
+            .allowed_cn_patterns
+            .as_deref()
+            .map(serde_json::to_string)
+            .transpose()
+            .context("Failed to encode allowed CN patterns")?;
+
+        let result = sqlx::query(
+            r#"
+            UPDATE classification_keys
+            SET description = COALESCE(?, description),
+                allowed_cn_patterns = COALESCE(?, allowed_cn_patterns)
+            WHERE id = ? AND revoked_at IS NULL
+            "#,
+        )
+        .bind(&req.description)
+        .bind(patterns)
+        .bind(id.to_string())
+        .execute(self.pool)
+        .await
+        .context("Failed to update classification key")?;
+
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+        self.get_by_id(id).await
+    }
+
+    /// Replace the secret of a key that is not revoked
+    ///
+    /// With `grace_until` the current secret stays valid until then;
+    /// without it the current secret stops working immediately. Returns
+    /// false when no such key exists.
+    pub async fn rotate(
+        &self,
+        id: Uuid,
+        key_hash: &str,
+        grace_until: Option<DateTime<Utc>>,
+    ) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE classification_keys
+            SET previous_key_hash = CASE WHEN ?1 IS NULL THEN NULL ELSE key_hash END,
+                previous_key_expires_at = ?1,
+                key_hash = ?2,
+                rotated_at = ?3
+            WHERE id = ?4 AND revoked_at IS NULL
+            "#,
+        )
+        .bind(grace_until.map(|d| d.to_rfc3339()))
+        .bind(key_hash)
+        .bind(Utc::now().to_rfc3339())
+        .bind(id.to_string())
+        .execute(self.pool)
+        .await
+        .context("Failed to rotate classification key")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Stop accepting a key; the record is kept for auditing
+    pub async fn revoke(&self, id: Uuid) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE classification_keys
+            SET revoked_at = ?, previous_key_hash = NULL, previous_key_expires_at = NULL
+            WHERE id = ? AND revoked_at IS NULL
+            "#,
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(id.to_string())
+        .execute(self.pool)
+        .await
+        .context("Failed to revoke classification key")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// The hashes of a key that is not revoked
+    pub async fn get_secrets(&self, id: Uuid) -> Result<Option<ClassificationKeySecrets>> {
+        let row: Option<(String, Option<String>)> = sqlx::query_as(
+            r#"
+            SELECT key_hash, previous_key_hash
+            FROM classification_keys
+            WHERE id = ? AND revoked_at IS NULL
+            "#,
+        )
+        .bind(id.to_string())
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to get classification key")?;
+        let Some((key_hash, previous_key_hash)) = row else {
+            return Ok(None);
+        };
+        let Some(key) = self.get_by_id(id).await? else {
+            return Ok(None);
+        };
+
+        let in_grace = key
+            .previous_key_expires_at
+            .is_some_and(|until| Utc::now() < until);
+        Ok(Some(ClassificationKeySecrets {
+            key,
+            key_hash,
+            previous_key_hash: previous_key_hash.filter(|_| in_grace),
+        }))
+    }
+
+    /// Record that a key was just used
+    pub async fn touch(&self, id: Uuid) -> Result<()> {
+        retry_busy(|| async {
+            sqlx::query("UPDATE classification_keys SET last_used_at = ? WHERE id = ?")
+                .bind(Utc::now().to_rfc3339())
+                .bind(id.to_string())
+                .execute(self.pool)
+                .await
+                .context("Failed to update classification key usage")
+        })
+        .await?;
+
+        Ok(())
+    }
+
+    /// Record a handshake from an ENC script, which also counts as a use
+    pub async fn record_handshake(&self, id: Uuid, host: Option<&str>) -> Result<()> {
+        sqlx::query(
+            r#"
+            UPDATE classification_keys
+            SET last_used_at = ?1, last_handshake_at = ?1, last_handshake_host = ?2
+            WHERE id = ?3
+            "#,
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(host)
+        .bind(id.to_string())
+        .execute(self.pool)
+        .await
+        .context("Failed to record ENC handshake")?;
+
+        Ok(())
+    }
+}
+
+fn parse_timestamp(ts: Option<String>) -> Result<Option<DateTime<Utc>>> {
+    ts.map(|ts| {
+        DateTime::parse_from_rfc3339(&ts)
+            .map(|dt| dt.with_timezone(&Utc))
+            .context("Invalid classification key timestamp")
+    })
+    .transpose()
+}
+
+fn row_to_key(row: ClassificationKeyRow) -> Result<ClassificationKey> {
+    Ok(ClassificationKey {
+        id: Uuid::parse_str(&row.id).context("Invalid classification key id")?,
+        name: row.name,
+        description: row.description,
+        allowed_cn_patterns: serde_json::from_str(&row.allowed_cn_patterns)
+            .context("Invalid classification key CN patterns")?,
+        created_by: row
+            .created_by
+            .map(|id| Uuid::parse_str(&id))
+            .transpose()
+            .context("Invalid classification key creator")?,
+        created_at: parse_timestamp(Some(row.created_at))?
+            .context("Missing classification key creation time")?,
+        rotated_at: parse_timestamp(row.rotated_at)?,
+        previous_key_expires_at: parse_timestamp(row.previous_key_expires_at)?,
+        revoked_at: parse_timestamp(row.revoked_at)?,
+        last_used_at: parse_timestamp(row.last_used_at)?,
+        last_handshake_at: parse_timestamp(row.last_handshake_at)?,
+        last_handshake_host: row.last_handshake_host,
+    })
+}
@@ -6,9 +6,9 @@ use sqlx::SqlitePool;
 use uuid::Uuid;
 
 use crate::models::{
-    CodeDeployment, CodeEnvironment, CodeRepository, CodeSshKey, CreateRepositoryRequest,
-    CreateSshKeyRequest, DeploymentStatus, ListDeploymentsQuery, ListEnvironmentsQuery,
-    UpdateEnvironmentRequest, UpdateRepositoryRequest,
+    CanaryNodeReports, CanaryStatus, CodeCanary, CodeDeployment, CodeEnvironment, CodeRepository,
+    CodeSshKey, CreateRepositoryRequest, CreateSshKeyRequest, DeploymentOptions, DeploymentStatus,
+    ListDeploymentsQuery, ListEnvironmentsQuery, UpdateEnvironmentRequest, UpdateRepositoryRequest,
 };
 
 // ============================================================================
@@ -391,6 +391,7 @@ struct RepositoryRow {
     ssh_key_id: Option<String>,
     pat_token_id: Option<String>,
     github_pat_encrypted: Option<String>,
+    deploy_strategy: String,
     webhook_secret: Option<String>,
     poll_interval_seconds: i32,
     is_control_repo: bool,
@@ -415,8 +416,8 @@ impl<'a> CodeRepositoryRepository<'a> {
         let rows = sqlx::query_as::<_, RepositoryRow>(
             r#"
             SELECT id, name, url, branch_pattern, auth_type, ssh_key_id, pat_token_id, github_pat_encrypted,
-                   webhook_secret, poll_interval_seconds, is_control_repo, last_error, last_error_at,
-                   created_at, updated_at
+                   deploy_strategy, webhook_secret, poll_interval_seconds, is_control_repo, last_error,
+                   last_error_at, created_at, updated_at
             FROM code_repositories
             ORDER BY name
             "#,
@@ -433,8 +434,8 @@ impl<'a> CodeRepositoryRepository<'a> {
         let row = sqlx::query_as::<_, RepositoryRow>(
             r#"
             SELECT id, name, url, branch_pattern, auth_type, ssh_key_id, pat_token_id, github_pat_encrypted,
-                   webhook_secret, poll_interval_seconds, is_control_repo, last_error, last_error_at,
-                   created_at, updated_at
+                   deploy_strategy, webhook_secret, poll_interval_seconds, is_control_repo, last_error,
+                   last_error_at, created_at, updated_at
             FROM code_repositories
             WHERE id = ?
             "#,
@@ -452,8 +453,8 @@ impl<'a> CodeRepositoryRepository<'a> {
         let row = sqlx::query_as::<_, RepositoryRow>(
             r#"
             SELECT id, name, url, branch_pattern, auth_type, ssh_key_id, pat_token_id, github_pat_encrypted,
-                   webhook_secret, poll_interval_seconds, is_control_repo, last_error, last_error_at,
-                   created_at, updated_at
+                   deploy_strategy, webhook_secret, poll_interval_seconds, is_control_repo, last_error,
+                   last_error_at, created_at, updated_at
             FROM code_repositories
             WHERE name = ?
             "#,
@@ -471,8 +472,8 @@ impl<'a> CodeRepositoryRepository<'a> {
         let rows = sqlx::query_as::<_, RepositoryRow>(
             r#"
             SELECT id, name, url, branch_pattern, auth_type, ssh_key_id, pat_token_id, github_pat_encrypted,
-                   webhook_secret, poll_interval_seconds, is_control_repo, last_error, last_error_at,
-                   created_at, updated_at
+                   deploy_strategy, webhook_secret, poll_interval_seconds, is_control_repo, last_error,
+                   last_error_at, created_at, updated_at
             FROM code_repositories
             WHERE poll_interval_seconds > 0
             ORDER BY name
@@ -497,8 +498,9 @@ impl<'a> CodeRepositoryRepository<'a> {
         sqlx::query(
             r#"
             INSERT INTO code_repositories (id, name, url, branch_pattern, auth_type, ssh_key_id, pat_token_id,
-                                          github_pat_encrypted, webhook_secret, poll_interval_seconds, is_control_repo)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                                          github_pat_encrypted, deploy_strategy, webhook_secret,
+                                          poll_interval_seconds, is_control_repo)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(id.to_string())
@@ -509,6 +511,7 @@ impl<'a> CodeRepositoryRepository<'a> {
         .bind(req.ssh_key_id.map(|k| k.to_string()))
         .bind(req.pat_token_id.map(|k| k.to_string()))
         .bind(github_pat_encrypted)
+        .bind(req.deploy_strategy.as_str())
         .bind(&webhook_secret)
         .bind(req.poll_interval_seconds)
         .bind(req.is_control_repo)
@@ -558,6 +561,7 @@ impl<'a> CodeRepositoryRepository<'a> {
         } else {
             existing.github_pat_encrypted
         };
+        let deploy_strategy = req.deploy_strategy.unwrap_or(existing.deploy_strategy);
         let poll_interval = req
             .poll_interval_seconds
             .unwrap_or(existing.poll_interval_seconds);
@@ -572,8 +576,8 @@ impl<'a> CodeRepositoryRepository<'a> {
             r#"
             UPDATE code_repositories
             SET name = ?, url = ?, branch_pattern = ?, auth_type = ?, ssh_key_id = ?, pat_token_id = ?,
-                github_pat_encrypted = ?, webhook_secret = ?, poll_interval_seconds = ?, is_control_repo = ?,
-                updated_at = CURRENT_TIMESTAMP
+                github_pat_encrypted = ?, deploy_strategy = ?, webhook_secret = ?, poll_interval_seconds = ?,
+                is_control_repo = ?, updated_at = CURRENT_TIMESTAMP
             WHERE id = ?
             "#,
         )
@@ -584,6 +588,7 @@ impl<'a> CodeRepositoryRepository<'a> {
         .bind(ssh_key_id.map(|k| k.to_string()))
         .bind(pat_token_id.map(|k| k.to_string()))
         .bind(github_pat)
+        .bind(deploy_strategy.as_str())
         .bind(&webhook_secret)
         .bind(poll_interval)
         .bind(is_control_repo)
@@ -651,13 +656,14 @@ impl<'a> CodeRepositoryRepository<'a> {
 }
 
 fn row_to_repository(row: RepositoryRow) -> CodeRepository {
-    use crate::models::AuthType;
+    use crate::models::{AuthType, DeployStrategy};
 
     let auth_type = row
         .auth_type
         .as_deref()
         .and_then(AuthType::from_str)
         .unwrap_or_default();
+    let deploy_strategy = DeployStrategy::from_str(&row.deploy_strategy).unwrap_or_default();
 
     CodeRepository {
         id: Uuid::parse_str(&row.id).unwrap_or_default(),
@@ -668,6 +674,7 @@ fn row_to_repository(row: RepositoryRow) -> CodeRepository {
         ssh_key_id: row.ssh_key_id.and_then(|s| Uuid::parse_str(&s).ok()),
         pat_token_id: row.pat_token_id.and_then(|s| Uuid::parse_str(&s).ok()),
         github_pat_encrypted: row.github_pat_encrypted,
+        deploy_strategy,
         webhook_secret: row.webhook_secret,
         poll_interval_seconds: row.poll_interval_seconds,
         is_control_repo: row.is_control_repo,
@@ -1004,6 +1011,8 @@ struct DeploymentRow {
     completed_at: Option<String>,
     error_message: Option<String>,
     r10k_output: Option<String>,
+    options: Option<String>,
+    command_line: Option<String>,
     created_at: String,
     updated_at: String,
 }
@@ -1025,7 +1034,7 @@ impl<'a> CodeDeploymentRepository<'a> {
             SELECT d.id, d.environment_id, d.commit_sha, d.commit_message, d.commit_author,
                    d.status, d.requested_by, d.approved_by, d.approved_at, d.rejected_at,
                    d.rejection_reason, d.started_at, d.completed_at, d.error_message,
-                   d.r10k_output, d.created_at, d.updated_at
+                   d.r10k_output, d.options, d.command_line, d.created_at, d.updated_at
             FROM code_deployments d
             "#,
         );
@@ -1086,7 +1095,7 @@ impl<'a> CodeDeploymentRepository<'a> {
             SELECT id, environment_id, commit_sha, commit_message, commit_author,
                    status, requested_by, approved_by, approved_at, rejected_at,
                    rejection_reason, started_at, completed_at, error_message,
-                   r10k_output, created_at, updated_at
+                   r10k_output, options, command_line, created_at, updated_at
             FROM code_deployments
             WHERE environment_id = ?
             ORDER BY created_at DESC
@@ -1109,7 +1118,7 @@ impl<'a> CodeDeploymentRepository<'a> {
             SELECT id, environment_id, commit_sha, commit_message, commit_author,
                    status, requested_by, approved_by, approved_at, rejected_at,
                    rejection_reason, started_at, completed_at, error_message,
-                   r10k_output, created_at, updated_at
+                   r10k_output, options, command_line, created_at, updated_at
             FROM code_deployments
             WHERE id = ?
             "#,
@@ -1129,7 +1138,7 @@ impl<'a> CodeDeploymentRepository<'a> {
             SELECT id, environment_id, commit_sha, commit_message, commit_author,
                    status, requested_by, approved_by, approved_at, rejected_at,
                    rejection_reason, started_at, completed_at, error_message,
-                   r10k_output, created_at, updated_at
+                   r10k_output, options, command_line, created_at, updated_at
             FROM code_deployments
             WHERE status = 'approved'
             ORDER BY approved_at ASC
@@ -1152,7 +1161,7 @@ impl<'a> CodeDeploymentRepository<'a> {
             SELECT id, environment_id, commit_sha, commit_message, commit_author,
                    status, requested_by, approved_by, approved_at, rejected_at,
                    rejection_reason, started_at, completed_at, error_message,
-                   r10k_output, created_at, updated_at
+                   r10k_output, options, command_line, created_at, updated_at
             FROM code_deployments
             WHERE environment_id = ? AND status = 'pending'
             ORDER BY created_at DESC
@@ -1177,7 +1186,7 @@ impl<'a> CodeDeploymentRepository<'a> {
             SELECT id, environment_id, commit_sha, commit_message, commit_author,
                    status, requested_by, approved_by, approved_at, rejected_at,
                    rejection_reason, started_at, completed_at, error_message,
-                   r10k_output, created_at, updated_at
+                   r10k_output, options, command_line, created_at, updated_at
             FROM code_deployments
             WHERE environment_id = ?
             ORDER BY created_at DESC
@@ -1201,15 +1210,45 @@ impl<'a> CodeDeploymentRepository<'a> {
         commit_author: Option<&str>,
         status: DeploymentStatus,
         requested_by: Option<Uuid>,
+    ) -> Result<CodeDeployment> {
+        self.create_with_options(
+            environment_id,
+            commit_sha,
+            commit_message,
+            commit_author,
+            status,
+            requested_by,
+            &DeploymentOptions::default(),
+        )
+        .await
+    }
+
+    /// Create a new deployment with r10k flags
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_with_options(
+        &self,
+        environment_id: Uuid,
+        commit_sha: &str,
+        commit_message: Option<&str>,
+        commit_author: Option<&str>,
+        status: DeploymentStatus,
+        requested_by: Option<Uuid>,
+        options: &DeploymentOptions,
     ) -> Result<CodeDeployment> {
         let id = Uuid::new_v4();
         let now = Utc::now().to_rfc3339();
+        let options = if options.is_default() {
+            None
+        } else {
+            Some(serde_json::to_string(options).context("Failed to serialize options")?)
+        };
 
         sqlx::query(
             r#"
             INSERT INTO code_deployments (id, environment_id, commit_sha, commit_message,
-                                         commit_author, status, requested_by, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                                         commit_author, status, requested_by, options,
+                                         created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(id.to_string())
@@ -1219,6 +1258,7 @@ impl<'a> CodeDeploymentRepository<'a> {
         .bind(commit_author)
         .bind(status.as_str())
         .bind(requested_by.map(|u| u.to_string()))
+        .bind(options)
         .bind(&now)
         .bind(&now)
         .execute(self.pool)
@@ -1308,6 +1348,19 @@ impl<'a> CodeDeploymentRepository<'a> {
         Ok(())
     }
 
+    /// Record the command line run by a deployment
+    pub async fn set_command_line(&self, id: Uuid, command_line: &str) -> Result<()> {
+        sqlx::query("UPDATE code_deployments SET command_line = ?, updated_at = ? WHERE id = ?")
+            .bind(command_line)
+            .bind(Utc::now().to_rfc3339())
+            .bind(id.to_string())
+            .execute(self.pool)
+            .await
+            .context("Failed to record deployment command line")?;
+
+        Ok(())
+    }
+
     /// Mark deployment as succeeded
     pub async fn mark_success(&self, id: Uuid, r10k_output: Option<&str>) -> Result<()> {
         let now = Utc::now().to_rfc3339();
@@ -1391,6 +1444,214 @@ impl<'a> CodeDeploymentRepository<'a> {
     }
 }
 
+// ============================================================================
+// Canary Repository
+// ============================================================================
+
+#[derive(Debug, sqlx::FromRow)]
+struct CanaryRow {
+    id: String,
+    deployment_id: String,
+    organization_id: String,
+    environment: String,
+    source_group_id: String,
+    canary_group_id: Option<String>,
+    nodes: String,
+    required_reports: i64,
+    auto_promote: bool,
+    status: String,
+    node_reports: Option<String>,
+    error_message: Option<String>,
+    started_by: Option<String>,
+    deadline_at: String,
+    completed_at: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+const CANARY_COLUMNS: &str = "SELECT id, deployment_id, organization_id, environment, \
+     source_group_id, canary_group_id, nodes, required_reports, auto_promote, status, \
+     node_reports, error_message, started_by, deadline_at, completed_at, created_at, \
+     updated_at FROM code_canaries";
+
+/// Repository for canary runs of deployments
+pub struct CodeCanaryRepository<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> CodeCanaryRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Get a canary by ID
+    pub async fn get_by_id(&self, id: Uuid) -> Result<Option<CodeCanary>> {
+        let row = sqlx::query_as::<_, CanaryRow>(sqlx::AssertSqlSafe(format!(
+            "{} WHERE id = ?",
+            CANARY_COLUMNS
+        )))
+        .bind(id.to_string())
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to fetch canary")?;
+
+        Ok(row.map(row_to_canary))
+    }
+
+    /// Get the latest canary of a deployment
+    pub async fn get_latest_for_deployment(
+        &self,
+        deployment_id: Uuid,
+    ) -> Result<Option<CodeCanary>> {
+        let row = sqlx::query_as::<_, CanaryRow>(sqlx::AssertSqlSafe(format!(
+            "{} WHERE deployment_id = ? ORDER BY created_at DESC LIMIT 1",
+            CANARY_COLUMNS
+        )))
+        .bind(deployment_id.to_string())
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to fetch canary")?;
+
+        Ok(row.map(row_to_canary))
+    }
+
+    /// Get the canaries still waiting for reports
+    pub async fn get_running(&self) -> Result<Vec<CodeCanary>> {
+        let rows = sqlx::query_as::<_, CanaryRow>(sqlx::AssertSqlSafe(format!(
+            "{} WHERE status = 'running' ORDER BY created_at",
+            CANARY_COLUMNS
+        )))
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to fetch running canaries")?;
+
+        Ok(rows.into_iter().map(row_to_canary).collect())
+    }
+
+    /// Create a canary
+    pub async fn create(&self, canary: &CodeCanary) -> Result<CodeCanary> {
+        let now = Utc::now().to_rfc3339();
+        let nodes = serde_json::to_string(&canary.nodes).context("Failed to serialize nodes")?;
+        let node_reports =
+            serde_json::to_string(&canary.node_reports).context("Failed to serialize reports")?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO code_canaries (id, deployment_id, organization_id, environment,
+                                       source_group_id, canary_group_id, nodes,
+                                       required_reports, auto_promote, status, node_reports,
+                                       started_by, deadline_at, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(canary.id.to_string())
+        .bind(canary.deployment_id.to_string())
+        .bind(canary.organization_id.to_string())
+        .bind(&canary.environment)
+        .bind(canary.source_group_id.to_string())
+        .bind(canary.canary_group_id.map(|id| id.to_string()))
+        .bind(nodes)
+        .bind(canary.required_reports as i64)
+        .bind(canary.auto_promote)
+        .bind(canary.status.as_str())
+        .bind(node_reports)
+        .bind(canary.started_by.map(|u| u.to_string()))
+        .bind(canary.deadline_at.to_rfc3339())
+        .bind(&now)
+        .bind(&now)
+        .execute(self.pool)
+        .await
+        .context("Failed to create canary")?;
+
+        self.get_by_id(canary.id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Failed to retrieve created canary"))
+    }
+
+    /// Record the reports of the canary nodes
+    pub async fn set_node_reports(&self, id: Uuid, reports: &[CanaryNodeReports]) -> Result<()> {
+        let reports = serde_json::to_string(reports).context("Failed to serialize reports")?;
+        sqlx::query("UPDATE code_canaries SET node_reports = ?, updated_at = ? WHERE id = ?")
+            .bind(reports)
+            .bind(Utc::now().to_rfc3339())
+            .bind(id.to_string())
+            .execute(self.pool)
+            .await
+            .context("Failed to record canary reports")?;
+
+        Ok(())
+    }
+
+    /// Move a canary to another status, completing it unless it remains
+    /// active
+    ///
+    /// Returns false when the canary was no longer in `from`.
+    pub async fn transition(
+        &self,
+        id: Uuid,
+        from: &[CanaryStatus],
+        to: CanaryStatus,
+        error_message: Option<&str>,
+    ) -> Result<bool> {
+        let now = Utc::now().to_rfc3339();
+        let completed_at = if to.is_active() {
+            None
+        } else {
+            Some(now.clone())
+        };
+        let from = from
+            .iter()
+            .map(|s| format!("'{}'", s.as_str()))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let result = sqlx::query(sqlx::AssertSqlSafe(format!(
+            r#"
+            UPDATE code_canaries
+            SET status = ?, error_message = COALESCE(?, error_message),
+                completed_at = ?, updated_at = ?
+            WHERE id = ? AND status IN ({})
+            "#,
+            from
+        )))
+        .bind(to.as_str())
+        .bind(error_message)
+        .bind(completed_at)
+        .bind(&now)
+        .bind(id.to_string())
+        .execute(self.pool)
+        .await
+        .context("Failed to update canary status")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn row_to_canary(row: CanaryRow) -> CodeCanary {
+    CodeCanary {
+        id: Uuid::parse_str(&row.id).unwrap_or_default(),
+        deployment_id: Uuid::parse_str(&row.deployment_id).unwrap_or_default(),
+        organization_id: Uuid::parse_str(&row.organization_id).unwrap_or_default(),
+        environment: row.environment,
+        source_group_id: Uuid::parse_str(&row.source_group_id).unwrap_or_default(),
+        canary_group_id: row.canary_group_id.and_then(|s| Uuid::parse_str(&s).ok()),
+        nodes: serde_json::from_str(&row.nodes).unwrap_or_default(),
+        required_reports: row.required_reports as u32,
+        auto_promote: row.auto_promote,
+        status: CanaryStatus::from_str(&row.status).unwrap_or_default(),
+        node_reports: row
+            .node_reports
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default(),
+        error_message: row.error_message,
+        started_by: row.started_by.and_then(|s| Uuid::parse_str(&s).ok()),
+        deadline_at: parse_timestamp_required(&row.deadline_at),
+        completed_at: row.completed_at.and_then(|s| parse_timestamp(&s)),
+        created_at: parse_timestamp_required(&row.created_at),
+        updated_at: parse_timestamp_required(&row.updated_at),
+    }
+}
+
 /// Parse a timestamp string that may be in RFC3339 format or SQLite datetime format.
 /// SQLite uses "YYYY-MM-DD HH:MM:SS" format, while RFC3339 uses "YYYY-MM-DDTHH:MM:SS+00:00".
 fn parse_timestamp(s: &str) -> Option<DateTime<Utc>> {
@@ -1429,6 +1690,11 @@ fn row_to_deployment(row: DeploymentRow) -> CodeDeployment {
         completed_at: row.completed_at.and_then(|s| parse_timestamp(&s)),
         error_message: row.error_message,
         r10k_output: row.r10k_output,
+        options: row
+            .options
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default(),
+        command_line: row.command_line,
         created_at: parse_timestamp_required(&row.created_at),
         updated_at: parse_timestamp_required(&row.updated_at),
     }
@@ -0,0 +1,326 @@
+//! Privilege elevation repository
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::{CreateElevationRequest, ElevationStatus, RoleElevation};
+
+#[derive(Debug, sqlx::FromRow)]
+struct RoleElevationRow {
+    id: String,
+    organization_id: String,
+    user_id: String,
+    role_id: String,
+    role_name: String,
+    reason: String,
+    duration_hours: i64,
+    status: String,
+    decided_by: Option<String>,
+    decided_at: Option<String>,
+    decision_comment: Option<String>,
+    expires_at: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+pub struct RoleElevationRepository<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> RoleElevationRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Elevations of an organization, newest first
+    pub async fn list(
+        &self,
+        organization_id: Uuid,
+        user_id: Option<Uuid>,
+        status: Option<ElevationStatus>,
+    ) -> Result<Vec<RoleElevation>> {
+        let rows = sqlx::query_as::<_, RoleElevationRow>(
+            r#"
+            SELECT e.id, e.organization_id, e.user_id, e.role_id, r.name AS role_name, e.reason,
+                   e.duration_hours, e.status, e.decided_by, e.decided_at, e.decision_comment,
+                   e.expires_at, e.created_at, e.updated_at
+            FROM role_elevations e
+            INNER JOIN roles r ON r.id = e.role_id
+            WHERE e.organization_id = ?1
+              AND (?2 IS NULL OR e.user_id = ?2)
+              AND (?3 IS NULL OR e.status = ?3)
+            ORDER BY e.created_at DESC
+            "#,
+        )
+        .bind(organization_id.to_string())
+        .bind(user_id.map(|id| id.to_string()))
+        .bind(status.map(|s| s.as_str()))
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to list role elevations")?;
+
+        rows.into_iter().map(row_to_elevation).collect()
+    }
+
+    pub async fn get_by_id(
+        &self,
+        organization_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<RoleElevation>> {
+        let row = sqlx::query_as::<_, RoleElevationRow>(
+            r#"
+            SELECT e.id, e.organization_id, e.user_id, e.role_id, r.name AS role_name, e.reason,
+                   e.duration_hours, e.status, e.decided_by, e.decided_at, e.decision_comment,
+                   e.expires_at, e.created_at, e.updated_at
+            FROM role_elevations e
+            INNER JOIN roles r ON r.id = e.role_id
+            WHERE e.organization_id = ? AND e.id = ?
+            "#,
+        )
+        .bind(organization_id.to_string())
+        .bind(id.to_string())
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to get role elevation")?;
+
+        row.map(row_to_elevation).transpose()
+    }
+
+    /// Elevations of a user that grant their role at `now`
+    pub async fn list_active_for_user(
+        &self,
+        user_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<RoleElevation>> {
+        let rows = sqlx::query_as::<_, RoleElevationRow>(
+            r#"
+            SELECT e.id, e.organization_id, e.user_id, e.role_id, r.name AS role_name, e.reason,
+                   e.duration_hours, e.status, e.decided_by, e.decided_at, e.decision_comment,
+                   e.expires_at, e.created_at, e.updated_at
+            FROM role_elevations e
+            INNER JOIN roles r ON r.id = e.role_id
+            WHERE e.user_id = ? AND e.status = 'approved' AND e.expires_at > ?
+            ORDER BY e.expires_at ASC
+            "#,
+        )
+        .bind(user_id.to_string())
+        .bind(now.to_rfc3339())
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to list active role elevations")?;
+
+        rows.into_iter().map(row_to_elevation).collect()
+    }
+
+    /// A pending or still active elevation of a user to a role
+    pub async fn find_open(
+        &self,
+        user_id: Uuid,
+        role_id: Uuid,
+        now: DateTime<Utc>,
+    ) -> Result<Option<Uuid>> {
+        let row: Option<(String,)> = sqlx::query_as(
+            r#"
+            SELECT id FROM role_elevations
+            WHERE user_id = ? AND role_id = ?
+              AND (status = 'pending' OR (status = 'approved' AND expires_at > ?))
+            LIMIT 1
+            "#,
+        )
+        .bind(user_id.to_string())
+        .bind(role_id.to_string())
+        .bind(now.to_rfc3339())
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to look up open role elevation")?;
+
+        row.map(|(id,)| Uuid::parse_str(&id).context("Invalid role elevation id"))
+            .transpose()
+    }
+
+    pub async fn create(
+        &self,
+        organization_id: Uuid,
+        user_id: Uuid,
+        req: &CreateElevationRequest,
+    ) -> Result<RoleElevation> {
+        let id = Uuid::new_v4();
+        let now = Utc::now().to_rfc3339();
+
+        sqlx::query(
+            r#"
+            INSERT INTO role_elevations (
+                id, organization_id, user_id, role_id, reason, duration_hours, status,
+                created_at, updated_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, 'pending', ?, ?)
+            "#,
+        )
+        .bind(id.to_string())
+        .bind(organization_id.to_string())
+        .bind(user_id.to_string())
+        .bind(req.role_id.to_string())
+        .bind(req.reason.trim())
+        .bind(req.duration_hours as i64)
+        .bind(&now)
+        .bind(&now)
+        .execute(self.pool)
+        .await
+        .context("Failed to create role elevation")?;
+
+        self.get_by_id(organization_id, id)
+            .await?
+            .context("Failed to retrieve created role elevation")
+    }
+
+    /// Approve a pending elevation, starting its clock
+    ///
+    /// Returns false when the elevation was no longer pending, so two
+    /// approvers racing on the same request cannot both win.
+    pub async fn approve(
+        &self,
+        organization_id: Uuid,
+        id: Uuid,
+        approver: Uuid,
+        comment: Option<&str>,
+        expires_at: DateTime<Utc>,
+    ) -> Result<bool> {
+        let now = Utc::now().to_rfc3339();
+        let result = sqlx::query(
+            r#"
+            UPDATE role_elevations
+            SET status = 'approved', decided_by = ?, decided_at = ?, decision_comment = ?,
+                expires_at = ?, updated_at = ?
+            WHERE organization_id = ? AND id = ? AND status = 'pending'
+            "#,
+        )
+        .bind(approver.to_string())
+        .bind(&now)
+        .bind(comment)
+        .bind(expires_at.to_rfc3339())
+        .bind(&now)
+        .bind(organization_id.to_string())
+        .bind(id.to_string())
+        .execute(self.pool)
+        .await
+        .context("Failed to approve role elevation")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Close an elevation: deny a pending one or revoke an approved one
+    ///
+    /// Returns false when the elevation was no longer in `from`.
+    pub async fn close(
+        &self,
+        organization_id: Uuid,
+        id: Uuid,
+        from: ElevationStatus,
+        to: ElevationStatus,
+        decided_by: Uuid,
+        comment: Option<&str>,
+    ) -> Result<bool> {
+        let now = Utc::now().to_rfc3339();
+        let result = sqlx::query(
+            r#"
+            UPDATE role_elevations
+            SET status = ?, decided_by = ?, decided_at = ?, decision_comment = ?, updated_at = ?
+            WHERE organization_id = ? AND id = ? AND status = ?
+            "#,
+        )
+        .bind(to.as_str())
+        .bind(decided_by.to_string())
+        .bind(&now)
+        .bind(comment)
+        .bind(&now)
+        .bind(organization_id.to_string())
+        .bind(id.to_string())
+        .bind(from.as_str())
+        .execute(self.pool)
+        .await
+        .context("Failed to update role elevation")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Mark approved elevations whose time is up as expired
+    ///
+    /// Returns the elevations that were expired by this call.
+    pub async fn expire_due(&self, now: DateTime<Utc>) -> Result<Vec<RoleElevation>> {
+        let rows = sqlx::query_as::<_, RoleElevationRow>(
+            r#"
+            SELECT e.id, e.organization_id, e.user_id, e.role_id, r.name AS role_name, e.reason,
+                   e.duration_hours, e.status, e.decided_by, e.decided_at, e.decision_comment,
+                   e.expires_at, e.created_at, e.updated_at
+            FROM role_elevations e
+            INNER JOIN roles r ON r.id = e.role_id
+            WHERE e.status = 'approved' AND e.expires_at <= ?
+            "#,
+        )
+        .bind(now.to_rfc3339())
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to list due role elevations")?;
+
+        let mut expired = Vec::with_capacity(rows.len());
+        for row in rows {
+            let mut elevation = row_to_elevation(row)?;
+            let result = sqlx::query(
+                r#"
+                UPDATE role_elevations SET status = 'expired', updated_at = ?
+                WHERE id = ? AND status = 'approved'
+                "#,
+            )
+            .bind(now.to_rfc3339())
+            .bind(elevation.id.to_string())
+            .execute(self.pool)
+            .await
+            .context("Failed to expire role elevation")?;
+
+            if result.rows_affected() > 0 {
+                elevation.status = ElevationStatus::Expired;
+                expired.push(elevation);
+            }
+        }
+
+        Ok(expired)
+    }
+}
+
+fn row_to_elevation(row: RoleElevationRow) -> Result<RoleElevation> {
+    Ok(RoleElevation {
+        id: Uuid::parse_str(&row.id).context("Invalid role elevation id")?,
+        organization_id: Uuid::parse_str(&row.organization_id)
+            .context("Invalid organization id")?,
+        user_id: Uuid::parse_str(&row.user_id).context("Invalid user id")?,
+        role_id: Uuid::parse_str(&row.role_id).context("Invalid role id")?,
+        role_name: row.role_name,
+        reason: row.reason,
+        duration_hours: row.duration_hours as u32,
+        status: ElevationStatus::parse(&row.status)
+            .with_context(|| format!("Invalid role elevation status '{}'", row.status))?,
+        decided_by: row
+            .decided_by
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .context("Invalid user id")?,
+        decided_at: row.decided_at.as_deref().map(parse_db_timestamp),
+        decision_comment: row.decision_comment,
+        expires_at: row.expires_at.as_deref().map(parse_db_timestamp),
+        created_at: parse_db_timestamp(&row.created_at),
+        updated_at: parse_db_timestamp(&row.updated_at),
+    })
+}
+
+fn parse_db_timestamp(ts: &str) -> DateTime<Utc> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(ts) {
+        return dt.with_timezone(&Utc);
+    }
+    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S") {
+        return DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc);
+    }
+    Utc::now()
+}
@@ -0,0 +1,164 @@
+//! Repository for the `fleet_metrics_hourly` table.
+//!
+//! Hourly snapshots of fleet-wide node and report metrics, recorded by the
+//! fleet metrics scheduler. Trend charts read from here so they can go back
+//! further than PuppetDB keeps reports.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use sqlx::FromRow;
+
+use crate::db::DbPool;
+
+/// Fleet metrics of one bucket (an hour, or a day when rolled up)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, FromRow)]
+pub struct FleetMetricsSnapshot {
+    /// UTC bucket start, formatted as RFC3339 (`YYYY-MM-DDTHH:00:00Z`)
+    pub bucket: String,
+    pub total_nodes: i64,
+    pub changed_nodes: i64,
+    pub unchanged_nodes: i64,
+    pub failed_nodes: i64,
+    pub unreported_nodes: i64,
+    pub report_count: i64,
+    pub avg_run_time_seconds: Option<f64>,
+    pub avg_compile_time_seconds: Option<f64>,
+    pub total_resources: i64,
+    pub changed_resources: i64,
+    pub failed_resources: i64,
+}
+
+pub struct FleetMetricsRepository {
+    pool: DbPool,
+}
+
+impl FleetMetricsRepository {
+    pub fn new(pool: DbPool) -> Self {
+        Self { pool }
+    }
+
+    /// Insert or replace the snapshot of an hour
+    pub async fn upsert(&self, snapshot: &FleetMetricsSnapshot) -> Result<()> {
+        let now = chrono::Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO fleet_metrics_hourly (
+                hour, total_nodes, changed_nodes, unchanged_nodes, failed_nodes,
+                unreported_nodes, report_count, avg_run_time_seconds, avg_compile_time_seconds,
+                total_resources, changed_resources, failed_resources, updated_at
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13)
+            ON CONFLICT(hour) DO UPDATE SET
+                total_nodes = excluded.total_nodes,
+                changed_nodes = excluded.changed_nodes,
+                unchanged_nodes = excluded.unchanged_nodes,
+                failed_nodes = excluded.failed_nodes,
+                unreported_nodes = excluded.unreported_nodes,
+                report_count = excluded.report_count,
+                avg_run_time_seconds = excluded.avg_run_time_seconds,
+                avg_compile_time_seconds = excluded.avg_compile_time_seconds,
+                total_resources = excluded.total_resources,
+                changed_resources = excluded.changed_resources,
+                failed_resources = excluded.failed_resources,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(&snapshot.bucket)
+        .bind(snapshot.total_nodes)
+        .bind(snapshot.changed_nodes)
+        .bind(snapshot.unchanged_nodes)
+        .bind(snapshot.failed_nodes)
+        .bind(snapshot.unreported_nodes)
+        .bind(snapshot.report_count)
+        .bind(snapshot.avg_run_time_seconds)
+        .bind(snapshot.avg_compile_time_seconds)
+        .bind(snapshot.total_resources)
+        .bind(snapshot.changed_resources)
+        .bind(snapshot.failed_resources)
+        .bind(&now)
+        .execute(&self.pool)
+        .await
+        .context("Failed to upsert fleet_metrics_hourly row")?;
+        Ok(())
+    }
+
+    /// Hourly snapshots with bucket hour in `[start_hour, end_hour]`
+    /// inclusive, ordered ascending
+    pub async fn range_hourly(
+        &self,
+        start_hour: &str,
+        end_hour: &str,
+    ) -> Result<Vec<FleetMetricsSnapshot>> {
+        let rows = sqlx::query_as::<_, FleetMetricsSnapshot>(
+            r#"
+            SELECT hour AS bucket, total_nodes, changed_nodes, unchanged_nodes, failed_nodes,
+                   unreported_nodes, report_count, avg_run_time_seconds,
+                   avg_compile_time_seconds, total_resources, changed_resources,
+                   failed_resources
+            FROM fleet_metrics_hourly
+            WHERE hour >= ?1 AND hour <= ?2
+            ORDER BY hour ASC
+            "#,
+        )
+        .bind(start_hour)
+        .bind(end_hour)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query fleet_metrics_hourly range")?;
+        Ok(rows)
+    }
+
+    /// Snapshots of `[start_hour, end_hour]` rolled up per UTC day, ordered
+    /// ascending
+    ///
+    /// Node and resource counts are averaged over the hours of the day,
+    /// report counts summed, and run and compile times averaged weighted by
+    /// the report count of each hour.
+    pub async fn range_daily(
+        &self,
+        start_hour: &str,
+        end_hour: &str,
+    ) -> Result<Vec<FleetMetricsSnapshot>> {
+        let rows = sqlx::query_as::<_, FleetMetricsSnapshot>(
+            r#"
+            SELECT
+                substr(hour, 1, 10) || 'T00:00:00Z' AS bucket,
+                CAST(ROUND(AVG(total_nodes)) AS INTEGER) AS total_nodes,
+                CAST(ROUND(AVG(changed_nodes)) AS INTEGER) AS changed_nodes,
+                CAST(ROUND(AVG(unchanged_nodes)) AS INTEGER) AS unchanged_nodes,
+                CAST(ROUND(AVG(failed_nodes)) AS INTEGER) AS failed_nodes,
+                CAST(ROUND(AVG(unreported_nodes)) AS INTEGER) AS unreported_nodes,
+                SUM(report_count) AS report_count,
+                SUM(avg_run_time_seconds * report_count)
+                    / NULLIF(SUM(CASE WHEN avg_run_time_seconds IS NOT NULL
+                                      THEN report_count END), 0) AS avg_run_time_seconds,
+                SUM(avg_compile_time_seconds * report_count)
+                    / NULLIF(SUM(CASE WHEN avg_compile_time_seconds IS NOT NULL
+                                      THEN report_count END), 0) AS avg_compile_time_seconds,
+                CAST(ROUND(AVG(total_resources)) AS INTEGER) AS total_resources,
+                CAST(ROUND(AVG(changed_resources)) AS INTEGER) AS changed_resources,
+                CAST(ROUND(AVG(failed_resources)) AS INTEGER) AS failed_resources
+            FROM fleet_metrics_hourly
+            WHERE hour >= ?1 AND hour <= ?2
+            GROUP BY substr(hour, 1, 10)
+            ORDER BY bucket ASC
+            "#,
+        )
+        .bind(start_hour)
+        .bind(end_hour)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to query daily fleet metrics")?;
+        Ok(rows)
+    }
+
+    /// Delete snapshots older than `before_hour`
+    pub async fn delete_before(&self, before_hour: &str) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM fleet_metrics_hourly WHERE hour < ?1")
+            .bind(before_hour)
+            .execute(&self.pool)
+            .await
+            .context("Failed to delete old fleet metrics")?;
+        Ok(result.rows_affected())
+    }
+}
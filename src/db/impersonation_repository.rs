@@ -0,0 +1,153 @@
+//! User impersonation repository
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::ImpersonationSession;
+
+#[derive(Debug, sqlx::FromRow)]
+struct ImpersonationRow {
+    id: String,
+    organization_id: String,
+    impersonator_id: String,
+    impersonator_username: String,
+    user_id: String,
+    username: String,
+    reason: String,
+    created_at: String,
+    expires_at: String,
+    ended_at: Option<String>,
+}
+
+const SELECT_IMPERSONATIONS: &str = r#"
+    SELECT i.id, i.organization_id, i.impersonator_id, a.username AS impersonator_username,
+           i.user_id, u.username, i.reason, i.created_at, i.expires_at, i.ended_at
+    FROM impersonation_sessions i
+    INNER JOIN users a ON a.id = i.impersonator_id
+    INNER JOIN users u ON u.id = i.user_id
+"#;
+
+pub struct ImpersonationRepository<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> ImpersonationRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Record a new impersonation under the login session `id`
+    pub async fn create(
+        &self,
+        id: Uuid,
+        organization_id: Uuid,
+        impersonator_id: Uuid,
+        user_id: Uuid,
+        reason: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<ImpersonationSession> {
+        sqlx::query(
+            r#"
+            INSERT INTO impersonation_sessions
+                (id, organization_id, impersonator_id, user_id, reason, created_at, expires_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(id.to_string())
+        .bind(organization_id.to_string())
+        .bind(impersonator_id.to_string())
+        .bind(user_id.to_string())
+        .bind(reason)
+        .bind(Utc::now().to_rfc3339())
+        .bind(expires_at.to_rfc3339())
+        .execute(self.pool)
+        .await
+        .context("Failed to create impersonation session")?;
+
+        self.get(id)
+            .await?
+            .context("Impersonation session missing after insert")
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<Option<ImpersonationSession>> {
+        let sql = format!("{} WHERE i.id = ?", SELECT_IMPERSONATIONS);
+        let row = sqlx::query_as::<_, ImpersonationRow>(sqlx::AssertSqlSafe(sql))
+            .bind(id.to_string())
+            .fetch_optional(self.pool)
+            .await
+            .context("Failed to get impersonation session")?;
+
+        row.map(row_to_session).transpose()
+    }
+
+    /// Impersonations of an organization, newest first
+    pub async fn list(
+        &self,
+        organization_id: Uuid,
+        impersonator_id: Option<Uuid>,
+        user_id: Option<Uuid>,
+        limit: u32,
+    ) -> Result<Vec<ImpersonationSession>> {
+        let sql = format!(
+            r#"{}
+            WHERE i.organization_id = ?1
+              AND (?2 IS NULL OR i.impersonator_id = ?2)
+              AND (?3 IS NULL OR i.user_id = ?3)
+            ORDER BY i.created_at DESC
+            LIMIT ?4
+            "#,
+            SELECT_IMPERSONATIONS
+        );
+        let rows = sqlx::query_as::<_, ImpersonationRow>(sqlx::AssertSqlSafe(sql))
+            .bind(organization_id.to_string())
+            .bind(impersonator_id.map(|id| id.to_string()))
+            .bind(user_id.map(|id| id.to_string()))
+            .bind(limit as i64)
+            .fetch_all(self.pool)
+            .await
+            .context("Failed to list impersonation sessions")?;
+
+        rows.into_iter().map(row_to_session).collect()
+    }
+
+    /// Mark an impersonation ended; returns false when it already was
+    pub async fn end(&self, id: Uuid) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE impersonation_sessions SET ended_at = ? WHERE id = ? AND ended_at IS NULL",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(id.to_string())
+        .execute(self.pool)
+        .await
+        .context("Failed to end impersonation session")?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn row_to_session(row: ImpersonationRow) -> Result<ImpersonationSession> {
+    Ok(ImpersonationSession {
+        id: Uuid::parse_str(&row.id).context("Invalid impersonation session id")?,
+        organization_id: Uuid::parse_str(&row.organization_id)
+            .context("Invalid organization id")?,
+        impersonator_id: Uuid::parse_str(&row.impersonator_id).context("Invalid user id")?,
+        impersonator_username: row.impersonator_username,
+        user_id: Uuid::parse_str(&row.user_id).context("Invalid user id")?,
+        username: row.username,
+        reason: row.reason,
+        created_at: parse_db_timestamp(&row.created_at),
+        expires_at: parse_db_timestamp(&row.expires_at),
+        ended_at: row.ended_at.as_deref().map(parse_db_timestamp),
+    })
+}
+
+fn parse_db_timestamp(ts: &str) -> DateTime<Utc> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(ts) {
+        return dt.with_timezone(&Utc);
+    }
+    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S") {
+        return DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc);
+    }
+    Utc::now()
+}
@@ -8,6 +8,7 @@ use std::sync::OnceLock;
 use tokio::sync::Mutex;
 use uuid::Uuid;
 
+use crate::db::begin_write;
 use crate::models::{
     ComplianceCategoryNode, CreateGroupUpdateScheduleRequest, FleetRepositoryConfig,
     GroupUpdateSchedule, HostApplicationInventoryItem, HostContainerInventoryItem, HostOsInventory,
@@ -78,9 +79,7 @@ impl InventoryRepository {
             None
         };
 
-        let mut tx = self
-            .pool
-            .begin()
+        let mut tx = begin_write(&self.pool)
             .await
             .context("Failed to begin inventory transaction")?;
 
@@ -939,9 +938,7 @@ impl InventoryRepository {
             UpdateTargetStatus::Queued
         };
 
-        let mut tx = self
-            .pool
-            .begin()
+        let mut tx = begin_write(&self.pool)
             .await
             .context("Failed to begin update job transaction")?;
 
@@ -1059,9 +1056,7 @@ impl InventoryRepository {
         }
 
         let now = Utc::now();
-        let mut tx = self
-            .pool
-            .begin()
+        let mut tx = begin_write(&self.pool)
             .await
             .context("Failed to begin cancel update job transaction")?;
 
@@ -1096,9 +1091,7 @@ impl InventoryRepository {
         let now = Utc::now();
         let cutoff = now - chrono::Duration::minutes(timeout_minutes);
 
-        let mut tx = self
-            .pool
-            .begin()
+        let mut tx = begin_write(&self.pool)
             .await
             .context("Failed to begin stale target timeout transaction")?;
 
@@ -1182,9 +1175,7 @@ impl InventoryRepository {
         let now = Utc::now();
         let cutoff = now - chrono::Duration::minutes(max_runtime_minutes);
 
-        let mut tx = self
-            .pool
-            .begin()
+        let mut tx = begin_write(&self.pool)
             .await
             .context("Failed to begin overrunning job timeout transaction")?;
 
@@ -1283,9 +1274,7 @@ impl InventoryRepository {
             UpdateTargetStatus::Rejected
         };
 
-        let mut tx = self
-            .pool
-            .begin()
+        let mut tx = begin_write(&self.pool)
             .await
             .context("Failed to begin update job approval transaction")?;
 
@@ -1323,9 +1312,7 @@ impl InventoryRepository {
         certname: &str,
     ) -> Result<Vec<NodePendingUpdateJob>> {
         let now = Utc::now();
-        let mut tx = self
-            .pool
-            .begin()
+        let mut tx = begin_write(&self.pool)
             .await
             .context("Failed to begin pending update claim transaction")?;
 
@@ -1438,9 +1425,7 @@ impl InventoryRepository {
         }
 
         let now = Utc::now();
-        let mut tx = self
-            .pool
-            .begin()
+        let mut tx = begin_write(&self.pool)
             .await
             .context("Failed to begin update result transaction")?;
 
@@ -2475,9 +2460,7 @@ impl InventoryRepository {
             return Ok(0);
         }
 
-        let mut tx = self
-            .pool
-            .begin()
+        let mut tx = begin_write(&self.pool)
             .await
             .context("Failed to begin prune transaction")?;
 
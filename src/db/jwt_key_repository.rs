@@ -0,0 +1,161 @@
+//! JWT signing key repository
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+use crate::services::settings_encryption::{self, SettingsCipher};
+
+/// A stored signing key
+///
+/// `private_key` is the decrypted key material: the base64 HMAC secret, or
+/// the base64 DER of an RSA (PKCS#1) or Ed25519 (PKCS#8) private key.
+#[derive(Clone)]
+pub struct JwtSigningKey {
+    pub kid: String,
+    pub algorithm: String,
+    pub private_key: Option<String>,
+    pub public_key: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub retired_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct JwtSigningKeyRow {
+    kid: String,
+    algorithm: String,
+    private_key: Option<String>,
+    public_key: Option<String>,
+    created_at: String,
+    retired_at: Option<String>,
+    expires_at: Option<String>,
+}
+
+pub struct JwtKeyRepository<'a> {
+    pool: &'a SqlitePool,
+    cipher: Option<&'a SettingsCipher>,
+}
+
+impl<'a> JwtKeyRepository<'a> {
+    /// `cipher` encrypts the private keys; without one they are stored as
+    /// plaintext
+    pub fn new(pool: &'a SqlitePool, cipher: Option<&'a SettingsCipher>) -> Self {
+        Self { pool, cipher }
+    }
+
+    /// All keys, oldest first
+    pub async fn list(&self) -> Result<Vec<JwtSigningKey>> {
+        let rows = sqlx::query_as::<_, JwtSigningKeyRow>(
+            r#"
+            SELECT kid, algorithm, private_key, public_key, created_at, retired_at, expires_at
+            FROM jwt_signing_keys
+            ORDER BY created_at
+            "#,
+        )
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to list JWT signing keys")?;
+
+        rows.into_iter()
+            .map(|row| row_to_key(row, self.cipher))
+            .collect()
+    }
+
+    /// Insert a key, replacing any key with the same ID
+    pub async fn upsert(&self, key: &JwtSigningKey) -> Result<()> {
+        let private_key = key
+            .private_key
+            .as_deref()
+            .map(|secret| encode_secret(secret, self.cipher))
+            .transpose()?;
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO jwt_signing_keys
+                (kid, algorithm, private_key, public_key, created_at, retired_at, expires_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(&key.kid)
+        .bind(&key.algorithm)
+        .bind(private_key)
+        .bind(&key.public_key)
+        .bind(key.created_at.to_rfc3339())
+        .bind(key.retired_at.map(|t| t.to_rfc3339()))
+        .bind(key.expires_at.map(|t| t.to_rfc3339()))
+        .execute(self.pool)
+        .await
+        .context("Failed to store JWT signing key")?;
+        Ok(())
+    }
+
+    /// Stop signing with a key; it keeps verifying tokens until `expires_at`
+    pub async fn retire(&self, kid: &str, expires_at: DateTime<Utc>) -> Result<bool> {
+        let result = sqlx::query(
+            "UPDATE jwt_signing_keys SET retired_at = ?, expires_at = ? WHERE kid = ? AND retired_at IS NULL",
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(expires_at.to_rfc3339())
+        .bind(kid)
+        .execute(self.pool)
+        .await
+        .context("Failed to retire JWT signing key")?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Delete keys whose grace window has ended
+    pub async fn delete_expired(&self, now: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query(
+            "DELETE FROM jwt_signing_keys WHERE expires_at IS NOT NULL AND expires_at <= ?",
+        )
+        .bind(now.to_rfc3339())
+        .execute(self.pool)
+        .await
+        .context("Failed to delete expired JWT signing keys")?;
+        Ok(result.rows_affected())
+    }
+}
+
+fn encode_secret(secret: &str, cipher: Option<&SettingsCipher>) -> Result<String> {
+    match cipher {
+        Some(cipher) => cipher.encrypt(secret),
+        None => Ok(secret.to_string()),
+    }
+}
+
+fn decode_secret(kid: &str, stored: String, cipher: Option<&SettingsCipher>) -> Result<String> {
+    if !settings_encryption::is_encrypted(&stored) {
+        return Ok(stored);
+    }
+    let cipher = cipher.with_context(|| {
+        format!(
+            "JWT signing key {} is encrypted but no settings master key is loaded",
+            kid
+        )
+    })?;
+    cipher
+        .decrypt(&stored)
+        .with_context(|| format!("Failed to decrypt JWT signing key {}", kid))
+}
+
+fn row_to_key(row: JwtSigningKeyRow, cipher: Option<&SettingsCipher>) -> Result<JwtSigningKey> {
+    let private_key = row
+        .private_key
+        .map(|stored| decode_secret(&row.kid, stored, cipher))
+        .transpose()?;
+    Ok(JwtSigningKey {
+        private_key,
+        algorithm: row.algorithm,
+        public_key: row.public_key,
+        created_at: parse_timestamp(&row.created_at)?,
+        retired_at: row.retired_at.as_deref().map(parse_timestamp).transpose()?,
+        expires_at: row.expires_at.as_deref().map(parse_timestamp).transpose()?,
+        kid: row.kid,
+    })
+}
+
+fn parse_timestamp(ts: &str) -> Result<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(ts)
+        .map(|dt| dt.with_timezone(&Utc))
+        .with_context(|| format!("Invalid JWT signing key timestamp '{}'", ts))
+}
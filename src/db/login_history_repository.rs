@@ -0,0 +1,146 @@
+//! Login history repository
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::LoginEvent;
+
+#[derive(Debug, sqlx::FromRow)]
+struct LoginEventRow {
+    id: String,
+    user_id: String,
+    success: bool,
+    method: String,
+    ip_address: Option<String>,
+    user_agent: Option<String>,
+    location: Option<String>,
+    new_device: bool,
+    new_location: bool,
+    created_at: String,
+}
+
+/// What the earlier successful logins of a user have in common with a new one
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LoginFamiliarity {
+    /// The user has logged in successfully before
+    pub has_history: bool,
+    pub known_device: bool,
+    pub known_location: bool,
+}
+
+pub struct LoginHistoryRepository<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> LoginHistoryRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Record a login attempt; `device` is the normalized user agent
+    pub async fn record(&self, event: &LoginEvent, device: Option<&str>) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO login_history (
+                id, user_id, success, method, ip_address, user_agent, device,
+                location, new_device, new_location, created_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(event.id.to_string())
+        .bind(event.user_id.to_string())
+        .bind(event.success)
+        .bind(event.method.as_str())
+        .bind(&event.ip_address)
+        .bind(&event.user_agent)
+        .bind(device)
+        .bind(&event.location)
+        .bind(event.new_device)
+        .bind(event.new_location)
+        .bind(event.created_at.to_rfc3339())
+        .execute(self.pool)
+        .await
+        .context("Failed to record login")?;
+        Ok(())
+    }
+
+    /// Compare a login with the user's earlier successful logins
+    pub async fn familiarity(
+        &self,
+        user_id: Uuid,
+        device: Option<&str>,
+        location: Option<&str>,
+    ) -> Result<LoginFamiliarity> {
+        let (has_history, known_device, known_location): (bool, bool, bool) = sqlx::query_as(
+            r#"
+            SELECT
+                EXISTS(SELECT 1 FROM login_history WHERE user_id = ?1 AND success = 1),
+                EXISTS(SELECT 1 FROM login_history WHERE user_id = ?1 AND success = 1 AND device = ?2),
+                EXISTS(SELECT 1 FROM login_history WHERE user_id = ?1 AND success = 1 AND location = ?3)
+            "#,
+        )
+        .bind(user_id.to_string())
+        .bind(device)
+        .bind(location)
+        .fetch_one(self.pool)
+        .await
+        .context("Failed to read login history")?;
+
+        Ok(LoginFamiliarity {
+            has_history,
+            known_device,
+            known_location,
+        })
+    }
+
+    /// Most recent logins of a user, newest first
+    pub async fn list_for_user(&self, user_id: Uuid, limit: u32) -> Result<Vec<LoginEvent>> {
+        let rows = sqlx::query_as::<_, LoginEventRow>(
+            r#"
+            SELECT id, user_id, success, method, ip_address, user_agent, location,
+                   new_device, new_location, created_at
+            FROM login_history
+            WHERE user_id = ?
+            ORDER BY created_at DESC
+            LIMIT ?
+            "#,
+        )
+        .bind(user_id.to_string())
+        .bind(limit as i64)
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to list login history")?;
+
+        rows.into_iter().map(row_to_event).collect()
+    }
+
+    /// Delete logins recorded before a cutoff
+    pub async fn delete_before(&self, cutoff: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query("DELETE FROM login_history WHERE created_at < ?")
+            .bind(cutoff.to_rfc3339())
+            .execute(self.pool)
+            .await
+            .context("Failed to prune login history")?;
+        Ok(result.rows_affected())
+    }
+}
+
+fn row_to_event(row: LoginEventRow) -> Result<LoginEvent> {
+    Ok(LoginEvent {
+        id: Uuid::parse_str(&row.id).context("Invalid login id")?,
+        user_id: Uuid::parse_str(&row.user_id).context("Invalid user id")?,
+        success: row.success,
+        method: row.method.parse().map_err(|e: String| anyhow::anyhow!(e))?,
+        ip_address: row.ip_address,
+        user_agent: row.user_agent,
+        location: row.location,
+        new_device: row.new_device,
+        new_location: row.new_location,
+        created_at: DateTime::parse_from_rfc3339(&row.created_at)
+            .map(|dt| dt.with_timezone(&Utc))
+            .context("Invalid login timestamp")?,
+    })
+}
@@ -0,0 +1,273 @@
+//! Maintenance window repository
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::{
+    CreateMaintenanceWindowRequest, MaintenanceSchedule, MaintenanceTarget, MaintenanceWindow,
+    UpdateMaintenanceWindowRequest,
+};
+
+const WINDOW_COLUMNS: &str = r#"
+    id, organization_id, name, description, target, schedule_type, starts_at, ends_at,
+    cron_expression, duration_minutes, enabled, created_by, created_at, updated_at
+"#;
+
+#[derive(Debug, sqlx::FromRow)]
+struct MaintenanceWindowRow {
+    id: String,
+    organization_id: String,
+    name: String,
+    description: Option<String>,
+    target: String,
+    schedule_type: String,
+    starts_at: Option<String>,
+    ends_at: Option<String>,
+    cron_expression: Option<String>,
+    duration_minutes: Option<i64>,
+    enabled: i32,
+    created_by: String,
+    created_at: String,
+    updated_at: String,
+}
+
+/// Schedule columns: (starts_at, ends_at, cron_expression, duration_minutes)
+type ScheduleColumns = (Option<String>, Option<String>, Option<String>, Option<i64>);
+
+pub struct MaintenanceWindowRepository<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> MaintenanceWindowRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Windows of an organization, by name
+    pub async fn list(&self, organization_id: Uuid) -> Result<Vec<MaintenanceWindow>> {
+        let rows = sqlx::query_as::<_, MaintenanceWindowRow>(sqlx::AssertSqlSafe(format!(
+            "SELECT {} FROM maintenance_windows WHERE organization_id = ? ORDER BY name ASC",
+            WINDOW_COLUMNS
+        )))
+        .bind(organization_id.to_string())
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to list maintenance windows")?;
+
+        rows.into_iter().map(row_to_window).collect()
+    }
+
+    /// Enabled windows of every organization
+    ///
+    /// Used by the evaluators (alerts, dashboards, analytics), which have no
+    /// organization context.
+    pub async fn list_enabled(&self) -> Result<Vec<MaintenanceWindow>> {
+        let rows = sqlx::query_as::<_, MaintenanceWindowRow>(sqlx::AssertSqlSafe(format!(
+            "SELECT {} FROM maintenance_windows WHERE enabled = 1 ORDER BY name ASC",
+            WINDOW_COLUMNS
+        )))
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to list enabled maintenance windows")?;
+
+        rows.into_iter().map(row_to_window).collect()
+    }
+
+    pub async fn get_by_id(
+        &self,
+        organization_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<MaintenanceWindow>> {
+        let row = sqlx::query_as::<_, MaintenanceWindowRow>(sqlx::AssertSqlSafe(format!(
+            "SELECT {} FROM maintenance_windows WHERE organization_id = ? AND id = ?",
+            WINDOW_COLUMNS
+        )))
+        .bind(organization_id.to_string())
+        .bind(id.to_string())
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to get maintenance window")?;
+
+        row.map(row_to_window).transpose()
+    }
+
+    pub async fn create(
+        &self,
+        organization_id: Uuid,
+        created_by: Uuid,
+        req: &CreateMaintenanceWindowRequest,
+    ) -> Result<MaintenanceWindow> {
+        let id = Uuid::new_v4();
+        let now = Utc::now().to_rfc3339();
+        let target = serde_json::to_string(&req.target)
+            .context("Failed to serialize maintenance window target")?;
+        let (starts_at, ends_at, cron_expression, duration_minutes) =
+            schedule_columns(&req.schedule);
+
+        sqlx::query(
+            r#"
+            INSERT INTO maintenance_windows (
+                id, organization_id, name, description, target, schedule_type, starts_at,
+                ends_at, cron_expression, duration_minutes, enabled, created_by, created_at,
+                updated_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(id.to_string())
+        .bind(organization_id.to_string())
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(target)
+        .bind(req.schedule.type_str())
+        .bind(starts_at)
+        .bind(ends_at)
+        .bind(cron_expression)
+        .bind(duration_minutes)
+        .bind(req.enabled as i32)
+        .bind(created_by.to_string())
+        .bind(&now)
+        .bind(&now)
+        .execute(self.pool)
+        .await
+        .context("Failed to create maintenance window")?;
+
+        self.get_by_id(organization_id, id)
+            .await?
+            .context("Failed to retrieve created maintenance window")
+    }
+
+    pub async fn update(
+        &self,
+        organization_id: Uuid,
+        id: Uuid,
+        req: &UpdateMaintenanceWindowRequest,
+    ) -> Result<Option<MaintenanceWindow>> {
+        let Some(existing) = self.get_by_id(organization_id, id).await? else {
+            return Ok(None);
+        };
+
+        let name = req.name.clone().unwrap_or(existing.name);
+        let description = req.description.clone().unwrap_or(existing.description);
+        let target = req.target.clone().unwrap_or(existing.target);
+        let schedule = req.schedule.clone().unwrap_or(existing.schedule);
+        let enabled = req.enabled.unwrap_or(existing.enabled);
+        let target = serde_json::to_string(&target)
+            .context("Failed to serialize maintenance window target")?;
+        let (starts_at, ends_at, cron_expression, duration_minutes) = schedule_columns(&schedule);
+
+        sqlx::query(
+            r#"
+            UPDATE maintenance_windows
+            SET name = ?, description = ?, target = ?, schedule_type = ?, starts_at = ?,
+                ends_at = ?, cron_expression = ?, duration_minutes = ?, enabled = ?,
+                updated_at = ?
+            WHERE organization_id = ? AND id = ?
+            "#,
+        )
+        .bind(name)
+        .bind(description)
+        .bind(target)
+        .bind(schedule.type_str())
+        .bind(starts_at)
+        .bind(ends_at)
+        .bind(cron_expression)
+        .bind(duration_minutes)
+        .bind(enabled as i32)
+        .bind(Utc::now().to_rfc3339())
+        .bind(organization_id.to_string())
+        .bind(id.to_string())
+        .execute(self.pool)
+        .await
+        .context("Failed to update maintenance window")?;
+
+        self.get_by_id(organization_id, id).await
+    }
+
+    pub async fn delete(&self, organization_id: Uuid, id: Uuid) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM maintenance_windows WHERE organization_id = ? AND id = ?")
+                .bind(organization_id.to_string())
+                .bind(id.to_string())
+                .execute(self.pool)
+                .await
+                .context("Failed to delete maintenance window")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn schedule_columns(schedule: &MaintenanceSchedule) -> ScheduleColumns {
+    match schedule {
+        MaintenanceSchedule::OneTime { starts_at, ends_at } => (
+            Some(starts_at.to_rfc3339()),
+            Some(ends_at.to_rfc3339()),
+            None,
+            None,
+        ),
+        MaintenanceSchedule::Recurring {
+            cron_expression,
+            duration_minutes,
+        } => (
+            None,
+            None,
+            Some(cron_expression.clone()),
+            Some(*duration_minutes),
+        ),
+    }
+}
+
+fn row_to_window(row: MaintenanceWindowRow) -> Result<MaintenanceWindow> {
+    let target: MaintenanceTarget =
+        serde_json::from_str(&row.target).context("Invalid maintenance window target")?;
+
+    let schedule = match row.schedule_type.as_str() {
+        "one_time" => MaintenanceSchedule::OneTime {
+            starts_at: parse_db_timestamp(
+                row.starts_at
+                    .as_deref()
+                    .context("One-time maintenance window without starts_at")?,
+            ),
+            ends_at: parse_db_timestamp(
+                row.ends_at
+                    .as_deref()
+                    .context("One-time maintenance window without ends_at")?,
+            ),
+        },
+        "recurring" => MaintenanceSchedule::Recurring {
+            cron_expression: row
+                .cron_expression
+                .context("Recurring maintenance window without cron_expression")?,
+            duration_minutes: row
+                .duration_minutes
+                .context("Recurring maintenance window without duration_minutes")?,
+        },
+        other => anyhow::bail!("Invalid maintenance window schedule type '{}'", other),
+    };
+
+    Ok(MaintenanceWindow {
+        id: Uuid::parse_str(&row.id).context("Invalid maintenance window id")?,
+        organization_id: Uuid::parse_str(&row.organization_id)
+            .context("Invalid organization id")?,
+        name: row.name,
+        description: row.description,
+        target,
+        schedule,
+        enabled: row.enabled != 0,
+        created_by: Uuid::parse_str(&row.created_by).context("Invalid user id")?,
+        created_at: parse_db_timestamp(&row.created_at),
+        updated_at: parse_db_timestamp(&row.updated_at),
+    })
+}
+
+fn parse_db_timestamp(ts: &str) -> DateTime<Utc> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(ts) {
+        return dt.with_timezone(&Utc);
+    }
+    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S") {
+        return DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc);
+    }
+    Utc::now()
+}
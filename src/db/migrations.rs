@@ -3,13 +3,21 @@
 //! Migrations are handled by SQLx and stored in the `migrations/` directory.
 //! This module provides utilities for working with migrations programmatically.
 
-use anyhow::Result;
+use anyhow::{Context, Result};
 use sqlx::SqlitePool;
 
 /// Check if migrations are up to date
+///
+/// True when every migration embedded in the binary has been applied
+/// successfully to the main database.
 pub async fn check_migrations(pool: &SqlitePool) -> Result<bool> {
-    // SQLx automatically tracks migration status
-    // This function can be extended to provide more detailed status
-    let _result = sqlx::query("SELECT 1").fetch_one(pool).await?;
-    Ok(true)
+    let applied: Vec<i64> =
+        sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success = 1")
+            .fetch_all(pool)
+            .await
+            .context("Failed to read applied migrations")?;
+
+    Ok(sqlx::migrate!("./migrations")
+        .iter()
+        .all(|migration| applied.contains(&migration.version)))
 }
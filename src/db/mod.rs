@@ -7,21 +7,43 @@
 //! - Cached PuppetDB data
 //! - Alerting and notifications
 
+pub mod account_lockout_repository;
 pub mod alerting_repository;
 pub mod api_key_repository;
 pub mod audit_repository;
 pub mod backup_repository;
+pub mod busy;
+pub mod ca_snapshot_repository;
+pub mod cert_renewal_repository;
+pub mod classification_key_repository;
 pub mod code_deploy_repository;
 pub mod cve_repository;
+pub mod elevation_repository;
+pub mod fleet_metrics_repository;
+pub mod impersonation_repository;
 pub mod inventory_migration;
 pub mod inventory_repository;
+pub mod jwt_key_repository;
+pub mod login_history_repository;
+pub mod maintenance_repository;
 pub mod migrations;
+pub mod node_metadata_repository;
 pub mod node_removal_repository;
 pub mod organization_repository;
+pub mod recycle_bin_repository;
+pub mod refresh_token_repository;
+pub mod report_email_template_repository;
+pub mod report_ingestion_repository;
 pub mod report_summary_repository;
 pub mod repository;
+pub mod search_repository;
+pub mod session_repository;
 pub mod settings_repository;
+pub mod smart_list_repository;
+pub mod user_preferences_repository;
+pub mod webhook_repository;
 
+pub use account_lockout_repository::AccountLockoutRepository;
 pub use alerting_repository::{
     AlertRepository, AlertRuleRepository, AlertSilenceRepository, NotificationChannelRepository,
     NotificationHistoryRepository,
@@ -29,18 +51,38 @@ pub use alerting_repository::{
 pub use api_key_repository::ApiKeyRepository;
 pub use audit_repository::AuditRepository;
 pub use backup_repository::BackupRepository;
+pub use busy::{begin_write, retry_busy};
+pub use ca_snapshot_repository::CaSnapshotRepository;
+pub use cert_renewal_repository::CertRenewalRepository;
+pub use classification_key_repository::ClassificationKeyRepository;
 pub use code_deploy_repository::{
-    CodeDeploymentRepository, CodeEnvironmentRepository, CodePatTokenRepository,
-    CodeRepositoryRepository, CodeSshKeyRepository,
+    CodeCanaryRepository, CodeDeploymentRepository, CodeEnvironmentRepository,
+    CodePatTokenRepository, CodeRepositoryRepository, CodeSshKeyRepository,
 };
 pub use cve_repository::CveRepository;
+pub use elevation_repository::RoleElevationRepository;
+pub use fleet_metrics_repository::{FleetMetricsRepository, FleetMetricsSnapshot};
+pub use impersonation_repository::ImpersonationRepository;
 pub use inventory_repository::InventoryRepository;
+pub use jwt_key_repository::{JwtKeyRepository, JwtSigningKey};
+pub use login_history_repository::{LoginFamiliarity, LoginHistoryRepository};
+pub use maintenance_repository::MaintenanceWindowRepository;
+pub use node_metadata_repository::NodeMetadataRepository;
 pub use node_removal_repository::NodeRemovalRepository;
 pub use organization_repository::OrganizationRepository;
+pub use recycle_bin_repository::RecycleBinRepository;
+pub use refresh_token_repository::{RefreshTokenRepository, RefreshTokenUse};
+pub use report_email_template_repository::ReportEmailTemplateRepository;
+pub use report_ingestion_repository::{ReportIngestionCursor, ReportIngestionRepository};
 pub use report_summary_repository::{
     ActivityHeatmapCell, ReportDailySummary, ReportHourlySummary, ReportSummaryRepository,
 };
+pub use search_repository::SearchRepository;
+pub use session_repository::AuthSessionRepository;
 pub use settings_repository::SettingsRepository;
+pub use smart_list_repository::SmartListRepository;
+pub use user_preferences_repository::UserPreferencesRepository;
+pub use webhook_repository::WebhookRepository;
 
 use std::time::Duration;
 
@@ -77,12 +119,17 @@ const REQUIRED_TABLES: &[&str] = &[
     "report_schedules",
     "saved_reports",
     "report_executions",
+    "report_email_templates",
+    "fleet_metrics_hourly",
+    "report_ingestion_cursors",
+    "impersonation_sessions",
     // Code Deploy tables
     "code_ssh_keys",
     "code_repositories",
     "code_environments",
     "code_deployments",
     "code_pat_tokens",
+    "code_canaries",
     // Notification tables
     "notifications",
     // Backup tables
@@ -92,8 +139,30 @@ const REQUIRED_TABLES: &[&str] = &[
     // Node removal tracking tables
     "pending_node_removals",
     "node_removal_audit",
+    "node_janitor_runs",
+    "node_janitor_run_nodes",
+    // Local node metadata (tags, owner, notes)
+    "node_metadata",
+    // Maintenance windows
+    "maintenance_windows",
     // Settings table
     "settings",
+    // Saved node filters
+    "smart_lists",
+    // Offline copy of the Puppet CA state
+    "ca_snapshots",
+    // Certificate renewal campaigns
+    "cert_renewal_campaigns",
+    "cert_renewal_targets",
+    // Outbound webhooks and their delivery log
+    "webhooks",
+    "webhook_deliveries",
+    // Global full-text search index
+    "search_index",
+    // Per-user preferences (dashboard layout)
+    "user_preferences",
+    // Managed keys for the classification endpoints
+    "classification_keys",
     // Phase 10 inventory tables
     "host_inventory_snapshots",
     "host_os_inventory",
@@ -118,15 +187,9 @@ pub type DbPool = Pool<Sqlite>;
 
 /// Initialize the database connection pool
 pub async fn init_pool(config: &DatabaseConfig) -> Result<DbPool> {
-    // Parse the database URL and configure SQLite options
-    let connect_options = config
-        .url
-        .parse::<SqliteConnectOptions>()
-        .context("Failed to parse database URL")?
-        .journal_mode(SqliteJournalMode::Wal)
-        .synchronous(SqliteSynchronous::Normal)
-        .busy_timeout(Duration::from_secs(config.connect_timeout_secs))
-        .create_if_missing(true);
+    let connect_options =
+        sqlite_connect_options(&config.url, config).context("Failed to parse database URL")?;
+    busy::set_busy_retries(config.busy_retries);
 
     info!("Initializing database connection pool");
 
@@ -141,8 +204,8 @@ pub async fn init_pool(config: &DatabaseConfig) -> Result<DbPool> {
         .context("Failed to create database connection pool")?;
 
     info!(
-        "Database pool created: max={}, min={}",
-        config.max_connections, config.min_connections
+        "Database pool created: max={}, min={}, busy_timeout={}ms",
+        config.max_connections, config.min_connections, config.busy_timeout_ms
     );
 
     // Run migrations
@@ -154,6 +217,21 @@ pub async fn init_pool(config: &DatabaseConfig) -> Result<DbPool> {
     Ok(pool)
 }
 
+/// SQLite connection options for a database URL
+///
+/// Databases use WAL journaling, so readers do not block the writer, with
+/// `synchronous = NORMAL`, which is durable in WAL mode except on power
+/// loss. Connections wait `busy_timeout_ms` for the write lock.
+pub fn sqlite_connect_options(url: &str, config: &DatabaseConfig) -> Result<SqliteConnectOptions> {
+    Ok(url
+        .parse::<SqliteConnectOptions>()?
+        .journal_mode(SqliteJournalMode::Wal)
+        .synchronous(SqliteSynchronous::Normal)
+        .busy_timeout(Duration::from_millis(config.busy_timeout_ms))
+        .foreign_keys(true)
+        .create_if_missing(true))
+}
+
 /// Run database migrations
 async fn run_migrations(pool: &DbPool) -> Result<()> {
     info!("Running database migrations");
@@ -181,13 +259,8 @@ async fn run_migrations(pool: &DbPool) -> Result<()> {
 /// `sqlite:///var/lib/openvox-webui/inventory.db`). `main_cfg` is only used to
 /// borrow sane pool sizing / timeouts from the main DB configuration.
 pub async fn init_inventory_pool(url: &str, main_cfg: &DatabaseConfig) -> Result<DbPool> {
-    let connect_options = url
-        .parse::<SqliteConnectOptions>()
-        .context("Failed to parse inventory database URL")?
-        .journal_mode(SqliteJournalMode::Wal)
-        .synchronous(SqliteSynchronous::Normal)
-        .busy_timeout(Duration::from_secs(main_cfg.connect_timeout_secs))
-        .create_if_missing(true);
+    let connect_options =
+        sqlite_connect_options(url, main_cfg).context("Failed to parse inventory database URL")?;
 
     info!("Initializing inventory database connection pool");
 
@@ -300,6 +373,17 @@ pub async fn check_health(pool: &DbPool) -> Result<()> {
     Ok(())
 }
 
+/// Check that the built-in RBAC roles have been seeded
+pub async fn check_rbac_seed(pool: &DbPool) -> Result<bool> {
+    let seeded: i64 = sqlx::query_scalar(
+        "SELECT COUNT(*) FROM roles WHERE is_system = TRUE AND name IN ('super_admin', 'admin')",
+    )
+    .fetch_one(pool)
+    .await
+    .context("Failed to check RBAC roles")?;
+    Ok(seeded == 2)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -312,6 +396,8 @@ mod tests {
             min_connections: 1,
             connect_timeout_secs: 5,
             idle_timeout_secs: 60,
+            busy_timeout_ms: 5000,
+            busy_retries: 3,
         };
 
         // Note: This test may fail if migrations require a persistent database
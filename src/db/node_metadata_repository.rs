@@ -0,0 +1,174 @@
+//! Node metadata repository
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+use crate::models::{NodeMetadata, NodeMetadataQuery, TicketLink};
+
+const METADATA_COLUMNS: &str =
+    "certname, tags, owner_team, ticket_links, notes, updated_by, created_at, updated_at";
+
+#[derive(Debug, sqlx::FromRow)]
+struct NodeMetadataRow {
+    certname: String,
+    tags: String,
+    owner_team: Option<String>,
+    ticket_links: String,
+    notes: Option<String>,
+    updated_by: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+pub struct NodeMetadataRepository<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> NodeMetadataRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn get(&self, certname: &str) -> Result<Option<NodeMetadata>> {
+        let row = sqlx::query_as::<_, NodeMetadataRow>(sqlx::AssertSqlSafe(format!(
+            "SELECT {} FROM node_metadata WHERE certname = ?",
+            METADATA_COLUMNS
+        )))
+        .bind(certname)
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to get node metadata")?;
+
+        row.map(row_to_metadata).transpose()
+    }
+
+    /// Search metadata; every populated criterion must match
+    pub async fn search(&self, query: &NodeMetadataQuery) -> Result<Vec<NodeMetadata>> {
+        let pattern = query
+            .q
+            .as_deref()
+            .map(|q| format!("%{}%", q.to_lowercase()));
+
+        let rows = sqlx::query_as::<_, NodeMetadataRow>(sqlx::AssertSqlSafe(format!(
+            r#"
+            SELECT {} FROM node_metadata
+            WHERE (?1 IS NULL OR EXISTS (
+                    SELECT 1 FROM json_each(node_metadata.tags) WHERE lower(value) = lower(?1)))
+              AND (?2 IS NULL OR lower(owner_team) = lower(?2))
+              AND (?3 IS NULL
+                   OR lower(certname) LIKE ?3
+                   OR lower(tags) LIKE ?3
+                   OR lower(COALESCE(owner_team, '')) LIKE ?3
+                   OR lower(COALESCE(notes, '')) LIKE ?3
+                   OR lower(ticket_links) LIKE ?3)
+            ORDER BY certname ASC
+            "#,
+            METADATA_COLUMNS
+        )))
+        .bind(query.tag.as_deref())
+        .bind(query.owner_team.as_deref())
+        .bind(pattern)
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to search node metadata")?;
+
+        rows.into_iter().map(row_to_metadata).collect()
+    }
+
+    /// Certnames carrying `tag`
+    pub async fn certnames_with_tag(&self, tag: &str) -> Result<Vec<String>> {
+        let certnames = sqlx::query_scalar::<_, String>(
+            r#"
+            SELECT certname FROM node_metadata
+            WHERE EXISTS (
+                SELECT 1 FROM json_each(node_metadata.tags) WHERE lower(value) = lower(?))
+            ORDER BY certname ASC
+            "#,
+        )
+        .bind(tag)
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to list nodes by tag")?;
+
+        Ok(certnames)
+    }
+
+    /// Create or replace the metadata of a node
+    pub async fn upsert(
+        &self,
+        certname: &str,
+        tags: &[String],
+        owner_team: Option<&str>,
+        ticket_links: &[TicketLink],
+        notes: Option<&str>,
+        updated_by: &str,
+    ) -> Result<NodeMetadata> {
+        let now = Utc::now().to_rfc3339();
+
+        sqlx::query(
+            r#"
+            INSERT INTO node_metadata (
+                certname, tags, owner_team, ticket_links, notes, updated_by, created_at, updated_at
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?7)
+            ON CONFLICT(certname) DO UPDATE SET
+                tags = ?2,
+                owner_team = ?3,
+                ticket_links = ?4,
+                notes = ?5,
+                updated_by = ?6,
+                updated_at = ?7
+            "#,
+        )
+        .bind(certname)
+        .bind(serde_json::to_string(tags)?)
+        .bind(owner_team)
+        .bind(serde_json::to_string(ticket_links)?)
+        .bind(notes)
+        .bind(updated_by)
+        .bind(&now)
+        .execute(self.pool)
+        .await
+        .context("Failed to save node metadata")?;
+
+        self.get(certname)
+            .await?
+            .context("Node metadata was saved but could not be reloaded")
+    }
+
+    /// Delete the metadata of a node; returns false if there was none
+    pub async fn delete(&self, certname: &str) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM node_metadata WHERE certname = ?")
+            .bind(certname)
+            .execute(self.pool)
+            .await
+            .context("Failed to delete node metadata")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn row_to_metadata(row: NodeMetadataRow) -> Result<NodeMetadata> {
+    Ok(NodeMetadata {
+        tags: serde_json::from_str(&row.tags)
+            .with_context(|| format!("Invalid tags for node '{}'", row.certname))?,
+        ticket_links: serde_json::from_str(&row.ticket_links)
+            .with_context(|| format!("Invalid ticket links for node '{}'", row.certname))?,
+        certname: row.certname,
+        owner_team: row.owner_team,
+        notes: row.notes,
+        updated_by: row.updated_by,
+        created_at: parse_db_timestamp(&row.created_at),
+        updated_at: parse_db_timestamp(&row.updated_at),
+    })
+}
+
+fn parse_db_timestamp(ts: &str) -> DateTime<Utc> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(ts) {
+        return dt.with_timezone(&Utc);
+    }
+    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S") {
+        return DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc);
+    }
+    Utc::now()
+}
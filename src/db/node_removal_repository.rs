@@ -7,8 +7,10 @@ use chrono::{DateTime, Duration, Utc};
 use sqlx::{FromRow, SqlitePool};
 use uuid::Uuid;
 
+use crate::db::begin_write;
 use crate::models::{
-    NodeRemovalAudit, PendingNodeRemoval, PendingRemovalStats, RemovalAuditAction, RemovalReason,
+    JanitorNodeResult, JanitorNodeStatus, JanitorRun, NodeRemovalAudit, PendingNodeRemoval,
+    PendingRemovalStats, RemovalAuditAction, RemovalReason,
 };
 
 /// Repository for node removal tracking operations
@@ -280,6 +282,7 @@ impl NodeRemovalRepository {
                 SUM(CASE WHEN removal_reason = 'revoked_certificate' THEN 1 ELSE 0 END) as revoked_certificates,
                 SUM(CASE WHEN removal_reason = 'no_certificate' THEN 1 ELSE 0 END) as no_certificates,
                 SUM(CASE WHEN removal_reason = 'manual' THEN 1 ELSE 0 END) as manual,
+                SUM(CASE WHEN removal_reason = 'stale_node' THEN 1 ELSE 0 END) as stale_nodes,
                 SUM(CASE WHEN datetime(scheduled_removal_at) <= datetime(?1) THEN 1 ELSE 0 END) as due_today,
                 SUM(CASE WHEN datetime(scheduled_removal_at) <= datetime(?2) THEN 1 ELSE 0 END) as due_this_week
             FROM pending_node_removals
@@ -297,6 +300,7 @@ impl NodeRemovalRepository {
             revoked_certificates: stats.revoked_certificates,
             no_certificates: stats.no_certificates,
             manual: stats.manual,
+            stale_nodes: stats.stale_nodes,
             due_today: stats.due_today,
             due_this_week: stats.due_this_week,
         })
@@ -409,6 +413,133 @@ impl NodeRemovalRepository {
 
         Ok(result.rows_affected())
     }
+
+    // =========================================================================
+    // Stale Node Janitor
+    // =========================================================================
+
+    /// Store a janitor run and its nodes
+    pub async fn save_janitor_run(&self, run: &JanitorRun) -> Result<()> {
+        let mut tx = begin_write(&self.pool)
+            .await
+            .context("Failed to begin janitor run transaction")?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO node_janitor_runs (
+                id, started_at, finished_at, dry_run, triggered_by, stale_after_days,
+                stale_count, purged_count, excluded_count, failed_count
+            ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)
+            "#,
+        )
+        .bind(&run.id)
+        .bind(run.started_at.to_rfc3339())
+        .bind(run.finished_at.to_rfc3339())
+        .bind(run.dry_run)
+        .bind(&run.triggered_by)
+        .bind(run.stale_after_days)
+        .bind(run.stale_count)
+        .bind(run.purged_count)
+        .bind(run.excluded_count)
+        .bind(run.failed_count)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to insert janitor run")?;
+
+        for node in &run.nodes {
+            sqlx::query(
+                r#"
+                INSERT INTO node_janitor_run_nodes (
+                    run_id, certname, last_seen_at, status, deactivated,
+                    certificate_flagged, error
+                ) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+                "#,
+            )
+            .bind(&run.id)
+            .bind(&node.certname)
+            .bind(node.last_seen_at.map(|t| t.to_rfc3339()))
+            .bind(node.status.as_str())
+            .bind(node.deactivated)
+            .bind(node.certificate_flagged)
+            .bind(&node.error)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to insert janitor run node")?;
+        }
+
+        tx.commit().await.context("Failed to commit janitor run")?;
+
+        Ok(())
+    }
+
+    /// List janitor runs, newest first (without their nodes)
+    pub async fn list_janitor_runs(&self, limit: i64) -> Result<Vec<JanitorRun>> {
+        let rows = sqlx::query_as::<_, JanitorRunRow>(
+            r#"
+            SELECT * FROM node_janitor_runs
+            ORDER BY started_at DESC
+            LIMIT ?1
+            "#,
+        )
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to list janitor runs")?;
+
+        Ok(rows.into_iter().map(|r| r.into()).collect())
+    }
+
+    /// Get a janitor run with its nodes
+    pub async fn get_janitor_run(&self, id: &str) -> Result<Option<JanitorRun>> {
+        let row = sqlx::query_as::<_, JanitorRunRow>(
+            r#"
+            SELECT * FROM node_janitor_runs WHERE id = ?1
+            "#,
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await
+        .context("Failed to fetch janitor run")?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+
+        let nodes = sqlx::query_as::<_, JanitorNodeRow>(
+            r#"
+            SELECT certname, last_seen_at, status, deactivated, certificate_flagged, error
+            FROM node_janitor_run_nodes
+            WHERE run_id = ?1
+            ORDER BY certname ASC
+            "#,
+        )
+        .bind(id)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch janitor run nodes")?;
+
+        let mut run: JanitorRun = row.into();
+        run.nodes = nodes.into_iter().map(|r| r.into()).collect();
+        Ok(Some(run))
+    }
+
+    /// Clean up old janitor runs (keep last N days)
+    pub async fn cleanup_janitor_runs(&self, retention_days: i64) -> Result<u64> {
+        let cutoff = Utc::now() - Duration::days(retention_days);
+
+        let result = sqlx::query(
+            r#"
+            DELETE FROM node_janitor_runs
+            WHERE datetime(started_at) < datetime(?1)
+            "#,
+        )
+        .bind(cutoff.to_rfc3339())
+        .execute(&self.pool)
+        .await
+        .context("Failed to cleanup janitor runs")?;
+
+        Ok(result.rows_affected())
+    }
 }
 
 // ============================================================================
@@ -492,6 +623,70 @@ struct StatsRow {
     revoked_certificates: i64,
     no_certificates: i64,
     manual: i64,
+    stale_nodes: i64,
     due_today: i64,
     due_this_week: i64,
 }
+
+#[derive(Debug, FromRow)]
+struct JanitorRunRow {
+    id: String,
+    started_at: String,
+    finished_at: String,
+    dry_run: bool,
+    triggered_by: Option<String>,
+    stale_after_days: i64,
+    stale_count: i64,
+    purged_count: i64,
+    excluded_count: i64,
+    failed_count: i64,
+}
+
+impl From<JanitorRunRow> for JanitorRun {
+    fn from(row: JanitorRunRow) -> Self {
+        Self {
+            id: row.id,
+            started_at: DateTime::parse_from_rfc3339(&row.started_at)
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now()),
+            finished_at: DateTime::parse_from_rfc3339(&row.finished_at)
+                .map(|dt| dt.with_timezone(&Utc))
+                .unwrap_or_else(|_| Utc::now()),
+            dry_run: row.dry_run,
+            triggered_by: row.triggered_by,
+            stale_after_days: row.stale_after_days,
+            stale_count: row.stale_count,
+            purged_count: row.purged_count,
+            excluded_count: row.excluded_count,
+            failed_count: row.failed_count,
+            nodes: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, FromRow)]
+struct JanitorNodeRow {
+    certname: String,
+    last_seen_at: Option<String>,
+    status: String,
+    deactivated: bool,
+    certificate_flagged: bool,
+    error: Option<String>,
+}
+
+impl From<JanitorNodeRow> for JanitorNodeResult {
+    fn from(row: JanitorNodeRow) -> Self {
+        Self {
+            certname: row.certname,
+            last_seen_at: row.last_seen_at.and_then(|s| {
+                DateTime::parse_from_rfc3339(&s)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .ok()
+            }),
+            status: JanitorNodeStatus::parse(&row.status).unwrap_or(JanitorNodeStatus::Failed),
+            deactivated: row.deactivated,
+            certificate_flagged: row.certificate_flagged,
+            error: row.error,
+        }
+    }
+}
@@ -5,13 +5,34 @@ use chrono::{DateTime, Utc};
 use sqlx::SqlitePool;
 use uuid::Uuid;
 
-use crate::models::{CreateOrganizationRequest, Organization, UpdateOrganizationRequest};
+use crate::db::begin_write;
+use crate::models::{
+    CreateOrganizationRequest, Organization, OrganizationPuppetDb, OrganizationQuotas,
+    QuotaResource, SetOrganizationPuppetDbRequest, UpdateOrganizationRequest,
+};
 
 #[derive(Debug, sqlx::FromRow)]
 struct OrganizationRow {
     id: String,
     name: String,
     slug: String,
+    max_groups: Option<i64>,
+    max_saved_reports: Option<i64>,
+    max_api_keys: Option<i64>,
+    max_users: Option<i64>,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct OrganizationPuppetDbRow {
+    organization_id: String,
+    url: String,
+    timeout_secs: i64,
+    ssl_verify: bool,
+    ssl_cert: Option<String>,
+    ssl_key: Option<String>,
+    ssl_ca: Option<String>,
     created_at: String,
     updated_at: String,
 }
@@ -28,7 +49,8 @@ impl<'a> OrganizationRepository<'a> {
     pub async fn list(&self) -> Result<Vec<Organization>> {
         let rows = sqlx::query_as::<_, OrganizationRow>(
             r#"
-            SELECT id, name, slug, created_at, updated_at
+            SELECT id, name, slug, max_groups, max_saved_reports, max_api_keys, max_users,
+                   created_at, updated_at
             FROM organizations
             ORDER BY name
             "#,
@@ -43,7 +65,8 @@ impl<'a> OrganizationRepository<'a> {
     pub async fn get_by_id(&self, id: Uuid) -> Result<Option<Organization>> {
         let row = sqlx::query_as::<_, OrganizationRow>(
             r#"
-            SELECT id, name, slug, created_at, updated_at
+            SELECT id, name, slug, max_groups, max_saved_reports, max_api_keys, max_users,
+                   created_at, updated_at
             FROM organizations
             WHERE id = ?
             "#,
@@ -112,6 +135,127 @@ impl<'a> OrganizationRepository<'a> {
         self.get_by_id(id).await
     }
 
+    /// Replace the resource quotas of an organization
+    pub async fn set_quotas(
+        &self,
+        id: Uuid,
+        quotas: &OrganizationQuotas,
+    ) -> Result<Option<Organization>> {
+        let result = sqlx::query(
+            r#"
+            UPDATE organizations
+            SET max_groups = ?, max_saved_reports = ?, max_api_keys = ?, max_users = ?,
+                updated_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(quotas.max_groups)
+        .bind(quotas.max_saved_reports)
+        .bind(quotas.max_api_keys)
+        .bind(quotas.max_users)
+        .bind(Utc::now().to_rfc3339())
+        .bind(id.to_string())
+        .execute(self.pool)
+        .await
+        .context("Failed to update organization quotas")?;
+
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+        self.get_by_id(id).await
+    }
+
+    /// Number of resources of a kind the organization owns
+    pub async fn count_resources(&self, id: Uuid, resource: QuotaResource) -> Result<i64> {
+        let sql = match resource {
+            QuotaResource::Groups => "SELECT COUNT(*) FROM node_groups WHERE organization_id = ? AND deleted_at IS NULL",
+            QuotaResource::SavedReports => {
+                "SELECT COUNT(*) FROM saved_reports WHERE organization_id = ? AND deleted_at IS NULL"
+            }
+            QuotaResource::ApiKeys => "SELECT COUNT(*) FROM api_keys WHERE organization_id = ?",
+            QuotaResource::Users => "SELECT COUNT(*) FROM users WHERE organization_id = ? AND deleted_at IS NULL",
+        };
+
+        let count: i64 = sqlx::query_scalar(sql)
+            .bind(id.to_string())
+            .fetch_one(self.pool)
+            .await
+            .with_context(|| format!("Failed to count {}", resource.label()))?;
+
+        Ok(count)
+    }
+
+    /// The PuppetDB mapped to an organization, if any
+    pub async fn get_puppetdb(&self, id: Uuid) -> Result<Option<OrganizationPuppetDb>> {
+        let row = sqlx::query_as::<_, OrganizationPuppetDbRow>(
+            r#"
+            SELECT organization_id, url, timeout_secs, ssl_verify, ssl_cert, ssl_key, ssl_ca,
+                   created_at, updated_at
+            FROM organization_puppetdb
+            WHERE organization_id = ?
+            "#,
+        )
+        .bind(id.to_string())
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to get organization PuppetDB")?;
+
+        Ok(row.map(row_to_puppetdb))
+    }
+
+    /// Map an organization to its own PuppetDB, replacing any previous mapping
+    pub async fn set_puppetdb(
+        &self,
+        id: Uuid,
+        req: &SetOrganizationPuppetDbRequest,
+    ) -> Result<OrganizationPuppetDb> {
+        let now = Utc::now().to_rfc3339();
+
+        sqlx::query(
+            r#"
+            INSERT INTO organization_puppetdb
+                (organization_id, url, timeout_secs, ssl_verify, ssl_cert, ssl_key, ssl_ca,
+                 created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(organization_id) DO UPDATE SET
+                url = excluded.url,
+                timeout_secs = excluded.timeout_secs,
+                ssl_verify = excluded.ssl_verify,
+                ssl_cert = excluded.ssl_cert,
+                ssl_key = excluded.ssl_key,
+                ssl_ca = excluded.ssl_ca,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(id.to_string())
+        .bind(&req.url)
+        .bind(req.timeout_secs as i64)
+        .bind(req.ssl_verify)
+        .bind(&req.ssl_cert)
+        .bind(&req.ssl_key)
+        .bind(&req.ssl_ca)
+        .bind(&now)
+        .bind(&now)
+        .execute(self.pool)
+        .await
+        .context("Failed to set organization PuppetDB")?;
+
+        self.get_puppetdb(id)
+            .await?
+            .context("Failed to retrieve organization PuppetDB")
+    }
+
+    /// Remove an organization's PuppetDB mapping
+    pub async fn delete_puppetdb(&self, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM organization_puppetdb WHERE organization_id = ?")
+            .bind(id.to_string())
+            .execute(self.pool)
+            .await
+            .context("Failed to delete organization PuppetDB")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
     pub async fn delete(&self, id: Uuid) -> Result<bool> {
         let result = sqlx::query("DELETE FROM organizations WHERE id = ?")
             .bind(id.to_string())
@@ -121,6 +265,32 @@ impl<'a> OrganizationRepository<'a> {
 
         Ok(result.rows_affected() > 0)
     }
+
+    /// Remove an organization together with its groups
+    ///
+    /// Used to roll back an organization whose creation did not complete;
+    /// groups do not cascade from their organization.
+    pub async fn delete_with_groups(&self, id: Uuid) -> Result<()> {
+        let mut tx = begin_write(self.pool)
+            .await
+            .context("Failed to begin organization removal")?;
+
+        sqlx::query("DELETE FROM node_groups WHERE organization_id = ?")
+            .bind(id.to_string())
+            .execute(&mut *tx)
+            .await
+            .context("Failed to delete organization groups")?;
+        sqlx::query("DELETE FROM organizations WHERE id = ?")
+            .bind(id.to_string())
+            .execute(&mut *tx)
+            .await
+            .context("Failed to delete organization")?;
+
+        tx.commit()
+            .await
+            .context("Failed to commit organization removal")?;
+        Ok(())
+    }
 }
 
 fn parse_db_timestamp(ts: &str) -> DateTime<Utc> {
@@ -138,6 +308,26 @@ fn row_to_org(row: OrganizationRow) -> Organization {
         id: Uuid::parse_str(&row.id).unwrap_or_else(|_| Uuid::nil()),
         name: row.name,
         slug: row.slug,
+        quotas: OrganizationQuotas {
+            max_groups: row.max_groups,
+            max_saved_reports: row.max_saved_reports,
+            max_api_keys: row.max_api_keys,
+            max_users: row.max_users,
+        },
+        created_at: parse_db_timestamp(&row.created_at),
+        updated_at: parse_db_timestamp(&row.updated_at),
+    }
+}
+
+fn row_to_puppetdb(row: OrganizationPuppetDbRow) -> OrganizationPuppetDb {
+    OrganizationPuppetDb {
+        organization_id: Uuid::parse_str(&row.organization_id).unwrap_or_else(|_| Uuid::nil()),
+        url: row.url,
+        timeout_secs: row.timeout_secs.max(1) as u64,
+        ssl_verify: row.ssl_verify,
+        ssl_cert: row.ssl_cert,
+        ssl_key: row.ssl_key,
+        ssl_ca: row.ssl_ca,
         created_at: parse_db_timestamp(&row.created_at),
         updated_at: parse_db_timestamp(&row.updated_at),
     }
@@ -0,0 +1,257 @@
+//! Recycle bin repository
+//!
+//! Node groups, users and saved reports are soft-deleted by setting their
+//! `deleted_at`. This repository lists those records, restores them, and
+//! removes them for good once purged or expired. A group deleted together
+//! with its subgroups shares their deletion timestamp, which is how the
+//! subtree is restored and purged as one.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::db::begin_write;
+use crate::models::{DeletedItem, RecycleBinKind};
+
+/// Subgroups deleted together with the group bound to the first two
+/// parameters (organization and group id)
+const GROUP_SUBTREE: &str = r#"
+    WITH RECURSIVE subtree(id, deleted_at) AS (
+        SELECT id, deleted_at FROM node_groups
+        WHERE organization_id = ? AND id = ? AND deleted_at IS NOT NULL
+        UNION
+        SELECT g.id, g.deleted_at FROM node_groups g
+        JOIN subtree s ON g.parent_id = s.id
+        WHERE g.deleted_at = s.deleted_at
+    )
+"#;
+
+#[derive(sqlx::FromRow)]
+struct DeletedRow {
+    kind: String,
+    id: String,
+    name: String,
+    parent_id: Option<String>,
+    deleted_at: String,
+}
+
+pub struct RecycleBinRepository<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> RecycleBinRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Deleted records of an organization, most recently deleted first
+    pub async fn list(
+        &self,
+        organization_id: Uuid,
+        retention_days: u32,
+    ) -> Result<Vec<DeletedItem>> {
+        let sql = format!(
+            "{} UNION ALL {} UNION ALL {} ORDER BY deleted_at DESC",
+            deleted_select(RecycleBinKind::Group),
+            deleted_select(RecycleBinKind::User),
+            deleted_select(RecycleBinKind::SavedReport),
+        );
+        let org = organization_id.to_string();
+        let rows = sqlx::query_as::<_, DeletedRow>(sqlx::AssertSqlSafe(sql))
+            .bind(&org)
+            .bind(&org)
+            .bind(&org)
+            .fetch_all(self.pool)
+            .await
+            .context("Failed to list the recycle bin")?;
+
+        rows.into_iter()
+            .map(|row| row_to_item(row, retention_days))
+            .collect()
+    }
+
+    /// A deleted record of an organization
+    pub async fn get(
+        &self,
+        organization_id: Uuid,
+        kind: RecycleBinKind,
+        id: Uuid,
+        retention_days: u32,
+    ) -> Result<Option<DeletedItem>> {
+        let sql = format!("{} AND id = ?", deleted_select(kind));
+        let row = sqlx::query_as::<_, DeletedRow>(sqlx::AssertSqlSafe(sql))
+            .bind(organization_id.to_string())
+            .bind(id.to_string())
+            .fetch_optional(self.pool)
+            .await
+            .context("Failed to fetch deleted record")?;
+
+        row.map(|row| row_to_item(row, retention_days)).transpose()
+    }
+
+    /// Name of the parent of a group, if that parent is in the recycle bin
+    ///
+    /// Such a group cannot be restored on its own, as it would be classified
+    /// below a group that no longer exists.
+    pub async fn deleted_parent_name(
+        &self,
+        organization_id: Uuid,
+        group_id: Uuid,
+    ) -> Result<Option<String>> {
+        sqlx::query_scalar(
+            r#"
+            SELECT p.name FROM node_groups g
+            JOIN node_groups p ON p.id = g.parent_id
+            WHERE g.organization_id = ? AND g.id = ? AND p.deleted_at IS NOT NULL
+            "#,
+        )
+        .bind(organization_id.to_string())
+        .bind(group_id.to_string())
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to check parent group")
+    }
+
+    /// Restore a deleted record and return the number of records restored
+    ///
+    /// A group comes back with the subgroups deleted along with it.
+    pub async fn restore(
+        &self,
+        organization_id: Uuid,
+        kind: RecycleBinKind,
+        id: Uuid,
+    ) -> Result<u64> {
+        let sql = match kind {
+            RecycleBinKind::Group => format!(
+                r#"{}
+                UPDATE node_groups
+                SET deleted_at = NULL, version = version + 1, updated_at = CURRENT_TIMESTAMP
+                WHERE id IN (SELECT id FROM subtree)
+                "#,
+                GROUP_SUBTREE
+            ),
+            RecycleBinKind::User => r#"
+                UPDATE users SET deleted_at = NULL, updated_at = ?3
+                WHERE organization_id = ?1 AND id = ?2 AND deleted_at IS NOT NULL
+                "#
+            .to_string(),
+            RecycleBinKind::SavedReport => r#"
+                UPDATE saved_reports SET deleted_at = NULL, updated_at = CURRENT_TIMESTAMP
+                WHERE organization_id = ? AND id = ? AND deleted_at IS NOT NULL
+                "#
+            .to_string(),
+        };
+
+        let mut query = sqlx::query(sqlx::AssertSqlSafe(sql))
+            .bind(organization_id.to_string())
+            .bind(id.to_string());
+        if kind == RecycleBinKind::User {
+            query = query.bind(Utc::now().to_rfc3339());
+        }
+        let result = query
+            .execute(self.pool)
+            .await
+            .with_context(|| format!("Failed to restore {}", kind.as_str()))?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Remove a deleted record for good and return the number of records
+    /// removed
+    ///
+    /// A group is purged with the subgroups deleted along with it. Records
+    /// that cascade from the purged rows are removed as well.
+    pub async fn purge(
+        &self,
+        organization_id: Uuid,
+        kind: RecycleBinKind,
+        id: Uuid,
+    ) -> Result<u64> {
+        let sql = match kind {
+            RecycleBinKind::Group => format!(
+                "{} DELETE FROM node_groups WHERE id IN (SELECT id FROM subtree)",
+                GROUP_SUBTREE
+            ),
+            RecycleBinKind::User => "DELETE FROM users \
+                 WHERE organization_id = ? AND id = ? AND deleted_at IS NOT NULL"
+                .to_string(),
+            RecycleBinKind::SavedReport => "DELETE FROM saved_reports \
+                 WHERE organization_id = ? AND id = ? AND deleted_at IS NOT NULL"
+                .to_string(),
+        };
+
+        let result = sqlx::query(sqlx::AssertSqlSafe(sql))
+            .bind(organization_id.to_string())
+            .bind(id.to_string())
+            .execute(self.pool)
+            .await
+            .with_context(|| format!("Failed to purge {}", kind.as_str()))?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Remove every record deleted before `cutoff`, in all organizations
+    pub async fn purge_expired(&self, cutoff: DateTime<Utc>) -> Result<u64> {
+        let cutoff = cutoff.to_rfc3339();
+        let mut tx = begin_write(self.pool)
+            .await
+            .context("Failed to begin recycle bin purge")?;
+
+        let mut purged = 0;
+        for table in ["saved_reports", "users", "node_groups"] {
+            let sql = format!(
+                "DELETE FROM {} WHERE deleted_at IS NOT NULL AND deleted_at < ?",
+                table
+            );
+            purged += sqlx::query(sqlx::AssertSqlSafe(sql))
+                .bind(&cutoff)
+                .execute(&mut *tx)
+                .await
+                .with_context(|| format!("Failed to purge expired {}", table))?
+                .rows_affected();
+        }
+
+        tx.commit()
+            .await
+            .context("Failed to commit recycle bin purge")?;
+        Ok(purged)
+    }
+}
+
+/// Deleted records of one kind; binds the organization id
+fn deleted_select(kind: RecycleBinKind) -> &'static str {
+    match kind {
+        RecycleBinKind::Group => {
+            "SELECT 'group' AS kind, id, name, parent_id, deleted_at FROM node_groups \
+             WHERE organization_id = ? AND deleted_at IS NOT NULL"
+        }
+        RecycleBinKind::User => {
+            "SELECT 'user' AS kind, id, username AS name, NULL AS parent_id, deleted_at FROM users \
+             WHERE organization_id = ? AND deleted_at IS NOT NULL"
+        }
+        RecycleBinKind::SavedReport => {
+            "SELECT 'saved_report' AS kind, id, name, NULL AS parent_id, deleted_at \
+             FROM saved_reports WHERE organization_id = ? AND deleted_at IS NOT NULL"
+        }
+    }
+}
+
+fn row_to_item(row: DeletedRow, retention_days: u32) -> Result<DeletedItem> {
+    let deleted_at = DateTime::parse_from_rfc3339(&row.deleted_at)
+        .context("Invalid deletion timestamp")?
+        .with_timezone(&Utc);
+
+    Ok(DeletedItem {
+        kind: row.kind.parse().map_err(anyhow::Error::msg)?,
+        id: Uuid::parse_str(&row.id).context("Invalid record ID")?,
+        name: row.name,
+        parent_id: row
+            .parent_id
+            .map(|id| Uuid::parse_str(&id))
+            .transpose()
+            .context("Invalid parent group ID")?,
+        deleted_at,
+        purge_at: deleted_at + Duration::days(i64::from(retention_days)),
+    })
+}
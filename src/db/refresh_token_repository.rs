@@ -0,0 +1,112 @@
+//! Refresh token repository
+//!
+//! Refresh tokens are stored by their SHA-256 hash only.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+/// Outcome of exchanging a refresh token
+#[derive(Debug, Clone, PartialEq)]
+pub enum RefreshTokenUse {
+    /// The token was unused and is now spent
+    Consumed,
+    /// The token was spent before, at the given time; `None` when the
+    /// session has newer tokens but this one was never recorded
+    Reused(Option<DateTime<Utc>>),
+}
+
+pub struct RefreshTokenRepository<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> RefreshTokenRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Record a refresh token issued for a session
+    pub async fn record(
+        &self,
+        session_id: Uuid,
+        token: &str,
+        expires_at: DateTime<Utc>,
+    ) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO refresh_tokens (token_hash, session_id, created_at, expires_at)
+            VALUES (?, ?, ?, ?)
+            "#,
+        )
+        .bind(hash_token(token))
+        .bind(session_id.to_string())
+        .bind(Utc::now().to_rfc3339())
+        .bind(expires_at.to_rfc3339())
+        .execute(self.pool)
+        .await
+        .context("Failed to record refresh token")?;
+        Ok(())
+    }
+
+    /// Spend a refresh token of a session
+    ///
+    /// Tokens of sessions that have no recorded tokens were issued before
+    /// refresh tokens were recorded; they are accepted once.
+    pub async fn consume(&self, session_id: Uuid, token: &str) -> Result<RefreshTokenUse> {
+        let token_hash = hash_token(token);
+        let session_id = session_id.to_string();
+        let result = sqlx::query(
+            r#"
+            UPDATE refresh_tokens SET used_at = ?
+            WHERE token_hash = ? AND session_id = ? AND used_at IS NULL
+            "#,
+        )
+        .bind(Utc::now().to_rfc3339())
+        .bind(&token_hash)
+        .bind(&session_id)
+        .execute(self.pool)
+        .await
+        .context("Failed to use refresh token")?;
+        if result.rows_affected() > 0 {
+            return Ok(RefreshTokenUse::Consumed);
+        }
+
+        let used_at: Option<Option<String>> = sqlx::query_scalar(
+            "SELECT used_at FROM refresh_tokens WHERE token_hash = ? AND session_id = ?",
+        )
+        .bind(&token_hash)
+        .bind(&session_id)
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to look up refresh token")?;
+        if let Some(used_at) = used_at {
+            return Ok(RefreshTokenUse::Reused(
+                used_at.as_deref().and_then(parse_db_timestamp),
+            ));
+        }
+
+        let recorded: i64 =
+            sqlx::query_scalar("SELECT COUNT(*) FROM refresh_tokens WHERE session_id = ?")
+                .bind(&session_id)
+                .fetch_one(self.pool)
+                .await
+                .context("Failed to count refresh tokens")?;
+        Ok(if recorded == 0 {
+            RefreshTokenUse::Consumed
+        } else {
+            RefreshTokenUse::Reused(None)
+        })
+    }
+}
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+fn parse_db_timestamp(ts: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(ts)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
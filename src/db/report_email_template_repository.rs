@@ -0,0 +1,194 @@
+//! Report email template repository
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::db::begin_write;
+use crate::models::{ReportEmailTemplate, ReportType, UpsertReportEmailTemplateRequest};
+
+#[derive(Debug, sqlx::FromRow)]
+struct ReportEmailTemplateRow {
+    id: String,
+    organization_id: String,
+    report_type: Option<String>,
+    subject_template: String,
+    html_template: String,
+    text_template: String,
+    updated_by: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+const SELECT_COLUMNS: &str = "SELECT id, organization_id, report_type, subject_template, \
+     html_template, text_template, updated_by, created_at, updated_at \
+     FROM report_email_templates";
+
+pub struct ReportEmailTemplateRepository<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> ReportEmailTemplateRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// List the templates of an organization, the one for every report type
+    /// first
+    pub async fn list(&self, organization_id: Uuid) -> Result<Vec<ReportEmailTemplate>> {
+        let rows = sqlx::query_as::<_, ReportEmailTemplateRow>(sqlx::AssertSqlSafe(format!(
+            "{} WHERE organization_id = ? ORDER BY report_type IS NOT NULL, report_type",
+            SELECT_COLUMNS
+        )))
+        .bind(organization_id.to_string())
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to list report email templates")?;
+
+        rows.into_iter().map(row_to_template).collect()
+    }
+
+    /// Template to use for a report type: its own, else the organization's
+    /// template for every type
+    pub async fn resolve(
+        &self,
+        organization_id: Uuid,
+        report_type: ReportType,
+    ) -> Result<Option<ReportEmailTemplate>> {
+        let row = sqlx::query_as::<_, ReportEmailTemplateRow>(sqlx::AssertSqlSafe(format!(
+            "{} WHERE organization_id = ? AND (report_type = ? OR report_type IS NULL) \
+             ORDER BY report_type IS NULL LIMIT 1",
+            SELECT_COLUMNS
+        )))
+        .bind(organization_id.to_string())
+        .bind(report_type.as_str())
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to get report email template")?;
+
+        row.map(row_to_template).transpose()
+    }
+
+    /// Create the template of a report type, or replace the existing one
+    pub async fn upsert(
+        &self,
+        organization_id: Uuid,
+        updated_by: Uuid,
+        req: &UpsertReportEmailTemplateRequest,
+    ) -> Result<ReportEmailTemplate> {
+        let report_type = req.report_type.map(|t| t.as_str());
+        let now = Utc::now().to_rfc3339();
+        let mut tx = begin_write(self.pool).await?;
+
+        let existing: Option<String> = sqlx::query_scalar(
+            r#"
+            SELECT id FROM report_email_templates
+            WHERE organization_id = ? AND report_type IS ?
+            "#,
+        )
+        .bind(organization_id.to_string())
+        .bind(report_type)
+        .fetch_optional(&mut *tx)
+        .await
+        .context("Failed to look up report email template")?;
+
+        let id = match existing {
+            Some(id) => {
+                sqlx::query(
+                    r#"
+                    UPDATE report_email_templates
+                    SET subject_template = ?, html_template = ?, text_template = ?,
+                        updated_by = ?, updated_at = ?
+                    WHERE id = ?
+                    "#,
+                )
+                .bind(&req.subject_template)
+                .bind(&req.html_template)
+                .bind(&req.text_template)
+                .bind(updated_by.to_string())
+                .bind(&now)
+                .bind(&id)
+                .execute(&mut *tx)
+                .await
+                .context("Failed to update report email template")?;
+                id
+            }
+            None => {
+                let id = Uuid::new_v4().to_string();
+                sqlx::query(
+                    r#"
+                    INSERT INTO report_email_templates (
+                        id, organization_id, report_type, subject_template, html_template,
+                        text_template, updated_by, created_at, updated_at
+                    )
+                    VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    "#,
+                )
+                .bind(&id)
+                .bind(organization_id.to_string())
+                .bind(report_type)
+                .bind(&req.subject_template)
+                .bind(&req.html_template)
+                .bind(&req.text_template)
+                .bind(updated_by.to_string())
+                .bind(&now)
+                .bind(&now)
+                .execute(&mut *tx)
+                .await
+                .context("Failed to create report email template")?;
+                id
+            }
+        };
+
+        let row = sqlx::query_as::<_, ReportEmailTemplateRow>(sqlx::AssertSqlSafe(format!(
+            "{} WHERE id = ?",
+            SELECT_COLUMNS
+        )))
+        .bind(&id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to retrieve report email template")?;
+        tx.commit().await?;
+
+        row_to_template(row)
+    }
+
+    pub async fn delete(&self, organization_id: Uuid, id: Uuid) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM report_email_templates WHERE organization_id = ? AND id = ?")
+                .bind(organization_id.to_string())
+                .bind(id.to_string())
+                .execute(self.pool)
+                .await
+                .context("Failed to delete report email template")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn row_to_template(row: ReportEmailTemplateRow) -> Result<ReportEmailTemplate> {
+    let report_type = row
+        .report_type
+        .map(|t| ReportType::from_str(&t).context("Invalid report type"))
+        .transpose()?;
+
+    Ok(ReportEmailTemplate {
+        id: Uuid::parse_str(&row.id).context("Invalid template id")?,
+        organization_id: Uuid::parse_str(&row.organization_id)
+            .context("Invalid organization id")?,
+        report_type,
+        subject_template: row.subject_template,
+        html_template: row.html_template,
+        text_template: row.text_template,
+        updated_by: row.updated_by.and_then(|id| Uuid::parse_str(&id).ok()),
+        created_at: parse_db_timestamp(&row.created_at),
+        updated_at: parse_db_timestamp(&row.updated_at),
+    })
+}
+
+fn parse_db_timestamp(ts: &str) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(ts)
+        .map(|dt| dt.with_timezone(&Utc))
+        .unwrap_or_else(|_| Utc::now())
+}
@@ -0,0 +1,74 @@
+//! Report ingestion cursor repository
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+
+/// Position of the report ingester in the reports of a PuppetDB
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportIngestionCursor {
+    /// Latest `receive_time` processed
+    pub receive_time: DateTime<Utc>,
+    /// Hashes of the processed reports received at `receive_time`
+    pub hashes: Vec<String>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct CursorRow {
+    receive_time: String,
+    hashes: String,
+}
+
+pub struct ReportIngestionRepository<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> ReportIngestionRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Get the cursor of a source
+    pub async fn get(&self, source: &str) -> Result<Option<ReportIngestionCursor>> {
+        let row = sqlx::query_as::<_, CursorRow>(
+            "SELECT receive_time, hashes FROM report_ingestion_cursors WHERE source = ?",
+        )
+        .bind(source)
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to load report ingestion cursor")?;
+
+        row.map(|row| {
+            Ok(ReportIngestionCursor {
+                receive_time: DateTime::parse_from_rfc3339(&row.receive_time)
+                    .map(|dt| dt.with_timezone(&Utc))
+                    .context("Invalid receive_time in report ingestion cursor")?,
+                hashes: serde_json::from_str(&row.hashes)
+                    .context("Invalid hashes in report ingestion cursor")?,
+            })
+        })
+        .transpose()
+    }
+
+    /// Replace the cursor of a source
+    pub async fn save(&self, source: &str, cursor: &ReportIngestionCursor) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO report_ingestion_cursors (source, receive_time, hashes, updated_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(source) DO UPDATE SET
+                receive_time = excluded.receive_time,
+                hashes = excluded.hashes,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(source)
+        .bind(cursor.receive_time.to_rfc3339())
+        .bind(serde_json::to_string(&cursor.hashes)?)
+        .bind(Utc::now().to_rfc3339())
+        .execute(self.pool)
+        .await
+        .context("Failed to save report ingestion cursor")?;
+        Ok(())
+    }
+}
@@ -1,10 +1,11 @@
 //! Repository pattern implementations for database access
 
 use anyhow::{Context, Result};
-use sqlx::SqlitePool;
-use std::collections::HashMap;
+use sqlx::{SqliteConnection, SqlitePool};
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 
+use crate::db::begin_write;
 use crate::models::{
     ClassificationRule, CreateGroupRequest, CreateRuleRequest, FactDefinition, FactTemplate,
     NodeGroup, RuleMatchType, RuleOperator, UpdateGroupRequest,
@@ -15,6 +16,17 @@ pub struct GroupRepository<'a> {
     pool: &'a SqlitePool,
 }
 
+/// Outcome of [`GroupRepository::update`]
+#[derive(Debug)]
+pub enum GroupUpdate {
+    Updated(NodeGroup),
+    NotFound,
+    /// The group changed since the expected version was read
+    VersionMismatch {
+        current: i64,
+    },
+}
+
 /// Row returned from node_groups table
 #[derive(Debug, sqlx::FromRow)]
 struct GroupRow {
@@ -31,6 +43,7 @@ struct GroupRow {
     #[allow(dead_code)] // Kept for database backward compatibility
     parameters: String,
     variables: String,
+    version: i64,
 }
 
 /// Row returned from classification_rules table (with group_id for batch loading)
@@ -70,6 +83,11 @@ impl<'a> GroupRepository<'a> {
         Self { pool }
     }
 
+    /// The pool this repository queries
+    pub(crate) fn pool(&self) -> &'a SqlitePool {
+        self.pool
+    }
+
     /// Get all node groups with their rules and pinned nodes
     ///
     /// Optimized to use batch loading instead of N+1 queries.
@@ -78,9 +96,9 @@ impl<'a> GroupRepository<'a> {
         let rows = sqlx::query_as::<_, GroupRow>(
             r#"
             SELECT id, organization_id, name, description, parent_id, environment,
-                   is_environment_group, match_all_nodes, rule_match_type, classes, parameters, variables
+                   is_environment_group, match_all_nodes, rule_match_type, classes, parameters, variables, version
             FROM node_groups
-            WHERE organization_id = ?
+            WHERE organization_id = ? AND deleted_at IS NULL
             ORDER BY name
             "#,
         )
@@ -119,8 +137,9 @@ impl<'a> GroupRepository<'a> {
         let rows = sqlx::query_as::<_, GroupRow>(
             r#"
             SELECT id, organization_id, name, description, parent_id, environment,
-                   is_environment_group, match_all_nodes, rule_match_type, classes, parameters, variables
+                   is_environment_group, match_all_nodes, rule_match_type, classes, parameters, variables, version
             FROM node_groups
+            WHERE deleted_at IS NULL
             ORDER BY organization_id, name
             "#,
         )
@@ -155,9 +174,9 @@ impl<'a> GroupRepository<'a> {
         let row = sqlx::query_as::<_, GroupRow>(
             r#"
             SELECT id, organization_id, name, description, parent_id, environment,
-                   is_environment_group, match_all_nodes, rule_match_type, classes, parameters, variables
+                   is_environment_group, match_all_nodes, rule_match_type, classes, parameters, variables, version
             FROM node_groups
-            WHERE organization_id = ? AND id = ?
+            WHERE organization_id = ? AND id = ? AND deleted_at IS NULL
             "#,
         )
         .bind(organization_id.to_string())
@@ -223,19 +242,28 @@ impl<'a> GroupRepository<'a> {
             .ok_or_else(|| anyhow::anyhow!("Failed to retrieve created group"))
     }
 
-    /// Update a node group
+    /// Update a node group, its rules and its pinned nodes in one transaction
+    ///
+    /// The update only applies while the group is still at `expected_version`,
+    /// or at the version read here when none is given, so concurrent updates
+    /// cannot overwrite each other. Rules and pinned nodes given in the request
+    /// replace the existing ones; nothing is written if any step fails.
     pub async fn update(
         &self,
         organization_id: Uuid,
         id: Uuid,
         req: &UpdateGroupRequest,
-    ) -> Result<Option<NodeGroup>> {
-        // First check if the group exists
-        let existing = self.get_by_id(organization_id, id).await?;
-        if existing.is_none() {
-            return Ok(None);
+        expected_version: Option<i64>,
+    ) -> Result<GroupUpdate> {
+        let Some(existing) = self.get_by_id(organization_id, id).await? else {
+            return Ok(GroupUpdate::NotFound);
+        };
+        let expected_version = expected_version.unwrap_or(existing.version);
+        if existing.version != expected_version {
+            return Ok(GroupUpdate::VersionMismatch {
+                current: existing.version,
+            });
         }
-        let existing = existing.unwrap();
 
         // Build the update with existing values as fallback
         let name = req.name.clone().unwrap_or(existing.name);
@@ -266,13 +294,17 @@ impl<'a> GroupRepository<'a> {
                 serde_json::to_string(&existing.variables).unwrap_or_else(|_| "{}".to_string())
             });
 
-        sqlx::query(
+        let mut tx = begin_write(self.pool)
+            .await
+            .context("Failed to begin group update")?;
+
+        let result = sqlx::query(
             r#"
             UPDATE node_groups
             SET name = ?, description = ?, parent_id = ?, environment = ?,
                 is_environment_group = ?, match_all_nodes = ?, rule_match_type = ?, classes = ?, parameters = ?, variables = ?,
-                updated_at = CURRENT_TIMESTAMP
-            WHERE organization_id = ? AND id = ?
+                version = version + 1, updated_at = CURRENT_TIMESTAMP
+            WHERE organization_id = ? AND id = ? AND version = ? AND deleted_at IS NULL
             "#,
         )
         .bind(&name)
@@ -287,25 +319,106 @@ impl<'a> GroupRepository<'a> {
         .bind(&variables)
         .bind(organization_id.to_string())
         .bind(id.to_string())
-        .execute(self.pool)
+        .bind(expected_version)
+        .execute(&mut *tx)
         .await
         .context("Failed to update group")?;
 
-        self.get_by_id(organization_id, id).await
+        if result.rows_affected() == 0 {
+            // Changed or deleted since it was read above
+            tx.rollback()
+                .await
+                .context("Failed to roll back group update")?;
+            return Ok(match self.get_by_id(organization_id, id).await? {
+                Some(current) => GroupUpdate::VersionMismatch {
+                    current: current.version,
+                },
+                None => GroupUpdate::NotFound,
+            });
+        }
+
+        if let Some(rules) = &req.rules {
+            sqlx::query("DELETE FROM classification_rules WHERE group_id = ?")
+                .bind(id.to_string())
+                .execute(&mut *tx)
+                .await
+                .context("Failed to replace rules")?;
+            for rule in rules {
+                insert_rule(&mut tx, id, rule).await?;
+            }
+        }
+
+        if let Some(certnames) = &req.pinned_nodes {
+            sqlx::query("DELETE FROM pinned_nodes WHERE group_id = ?")
+                .bind(id.to_string())
+                .execute(&mut *tx)
+                .await
+                .context("Failed to replace pinned nodes")?;
+            for certname in certnames {
+                insert_pinned_node(&mut tx, id, certname).await?;
+            }
+        }
+
+        tx.commit().await.context("Failed to commit group update")?;
+
+        Ok(match self.get_by_id(organization_id, id).await? {
+            Some(group) => GroupUpdate::Updated(group),
+            None => GroupUpdate::NotFound,
+        })
     }
 
-    /// Delete a node group (rules and pinned nodes are deleted via CASCADE)
+    /// Move a node group and its subgroups to the recycle bin
+    ///
+    /// The whole subtree shares one deletion timestamp so it is restored or
+    /// purged together. Rules and pinned nodes stay in place until the
+    /// groups are purged.
     pub async fn delete(&self, organization_id: Uuid, id: Uuid) -> Result<bool> {
-        let result = sqlx::query("DELETE FROM node_groups WHERE organization_id = ? AND id = ?")
-            .bind(organization_id.to_string())
-            .bind(id.to_string())
-            .execute(self.pool)
-            .await
-            .context("Failed to delete group")?;
+        let result = sqlx::query(
+            r#"
+            WITH RECURSIVE subtree(id) AS (
+                SELECT id FROM node_groups
+                WHERE organization_id = ? AND id = ? AND deleted_at IS NULL
+                UNION
+                SELECT g.id FROM node_groups g
+                JOIN subtree s ON g.parent_id = s.id
+                WHERE g.deleted_at IS NULL
+            )
+            UPDATE node_groups
+            SET deleted_at = ?, version = version + 1, updated_at = CURRENT_TIMESTAMP
+            WHERE id IN (SELECT id FROM subtree)
+            "#,
+        )
+        .bind(organization_id.to_string())
+        .bind(id.to_string())
+        .bind(chrono::Utc::now().to_rfc3339())
+        .execute(self.pool)
+        .await
+        .context("Failed to delete group")?;
 
         Ok(result.rows_affected() > 0)
     }
 
+    /// Whether a group in the recycle bin still holds a name
+    ///
+    /// Deleted groups keep their name until they are purged, so it cannot be
+    /// reused before.
+    pub async fn name_held_by_deleted_group(
+        &self,
+        organization_id: Uuid,
+        name: &str,
+    ) -> Result<bool> {
+        let held: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM node_groups WHERE organization_id = ? AND name = ? AND deleted_at IS NOT NULL",
+        )
+        .bind(organization_id.to_string())
+        .bind(name)
+        .fetch_one(self.pool)
+        .await
+        .context("Failed to check deleted groups")?;
+
+        Ok(held > 0)
+    }
+
     /// Get all rules for a group
     pub async fn get_rules(&self, group_id: Uuid) -> Result<Vec<ClassificationRule>> {
         let rows = sqlx::query_as::<_, RuleRow>(
@@ -330,43 +443,32 @@ impl<'a> GroupRepository<'a> {
         group_id: Uuid,
         req: &CreateRuleRequest,
     ) -> Result<ClassificationRule> {
-        let id = Uuid::new_v4();
-        let operator = operator_to_string(&req.operator);
-        let value = serde_json::to_string(&req.value).unwrap_or_else(|_| "null".to_string());
+        let mut tx = begin_write(self.pool).await.context("Failed to add rule")?;
+        let rule = insert_rule(&mut tx, group_id, req).await?;
+        bump_version(&mut tx, group_id).await?;
+        tx.commit().await.context("Failed to add rule")?;
 
-        sqlx::query(
-            r#"
-            INSERT INTO classification_rules (id, group_id, fact_path, operator, value)
-            VALUES (?, ?, ?, ?, ?)
-            "#,
-        )
-        .bind(id.to_string())
-        .bind(group_id.to_string())
-        .bind(&req.fact_path)
-        .bind(&operator)
-        .bind(&value)
-        .execute(self.pool)
-        .await
-        .context("Failed to add rule")?;
-
-        Ok(ClassificationRule {
-            id,
-            fact_path: req.fact_path.clone(),
-            operator: req.operator,
-            value: req.value.clone(),
-        })
+        Ok(rule)
     }
 
     /// Delete a rule
     pub async fn delete_rule(&self, group_id: Uuid, rule_id: Uuid) -> Result<bool> {
+        let mut tx = begin_write(self.pool)
+            .await
+            .context("Failed to delete rule")?;
         let result = sqlx::query("DELETE FROM classification_rules WHERE id = ? AND group_id = ?")
             .bind(rule_id.to_string())
             .bind(group_id.to_string())
-            .execute(self.pool)
+            .execute(&mut *tx)
             .await
             .context("Failed to delete rule")?;
+        let deleted = result.rows_affected() > 0;
+        if deleted {
+            bump_version(&mut tx, group_id).await?;
+        }
+        tx.commit().await.context("Failed to delete rule")?;
 
-        Ok(result.rows_affected() > 0)
+        Ok(deleted)
     }
 
     /// Get all pinned nodes for a group
@@ -389,34 +491,69 @@ impl<'a> GroupRepository<'a> {
 
     /// Add a pinned node to a group
     pub async fn add_pinned_node(&self, group_id: Uuid, certname: &str) -> Result<()> {
-        let id = Uuid::new_v4();
-
-        sqlx::query(
-            r#"
-            INSERT OR IGNORE INTO pinned_nodes (id, group_id, certname)
-            VALUES (?, ?, ?)
-            "#,
-        )
-        .bind(id.to_string())
-        .bind(group_id.to_string())
-        .bind(certname)
-        .execute(self.pool)
-        .await
-        .context("Failed to add pinned node")?;
+        let mut tx = begin_write(self.pool)
+            .await
+            .context("Failed to add pinned node")?;
+        if insert_pinned_node(&mut tx, group_id, certname).await? {
+            bump_version(&mut tx, group_id).await?;
+        }
+        tx.commit().await.context("Failed to add pinned node")?;
 
         Ok(())
     }
 
     /// Remove a pinned node from a group
     pub async fn remove_pinned_node(&self, group_id: Uuid, certname: &str) -> Result<bool> {
+        let mut tx = begin_write(self.pool)
+            .await
+            .context("Failed to remove pinned node")?;
         let result = sqlx::query("DELETE FROM pinned_nodes WHERE group_id = ? AND certname = ?")
             .bind(group_id.to_string())
             .bind(certname)
-            .execute(self.pool)
+            .execute(&mut *tx)
             .await
             .context("Failed to remove pinned node")?;
+        let removed = result.rows_affected() > 0;
+        if removed {
+            bump_version(&mut tx, group_id).await?;
+        }
+        tx.commit().await.context("Failed to remove pinned node")?;
 
-        Ok(result.rows_affected() > 0)
+        Ok(removed)
+    }
+
+    /// Move a pinned node from one group to another
+    ///
+    /// Returns false, changing nothing, when the node is not pinned to the
+    /// source group.
+    pub async fn move_pinned_node(
+        &self,
+        from_group_id: Uuid,
+        to_group_id: Uuid,
+        certname: &str,
+    ) -> Result<bool> {
+        let mut tx = begin_write(self.pool)
+            .await
+            .context("Failed to begin pinned node move")?;
+
+        let removed = sqlx::query("DELETE FROM pinned_nodes WHERE group_id = ? AND certname = ?")
+            .bind(from_group_id.to_string())
+            .bind(certname)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to unpin node from source group")?;
+        if removed.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        insert_pinned_node(&mut tx, to_group_id, certname).await?;
+        bump_version(&mut tx, from_group_id).await?;
+        bump_version(&mut tx, to_group_id).await?;
+
+        tx.commit()
+            .await
+            .context("Failed to commit pinned node move")?;
+        Ok(true)
     }
 
     /// Remove all pinned node relationships for a specific certname across all groups
@@ -436,12 +573,13 @@ impl<'a> GroupRepository<'a> {
     /// organization up front. Used by background services (e.g. the update
     /// schedule scheduler) that only have a group id.
     pub async fn get_group_organization_id(&self, group_id: Uuid) -> Result<Option<Uuid>> {
-        let row: Option<(String,)> =
-            sqlx::query_as("SELECT organization_id FROM node_groups WHERE id = ?")
-                .bind(group_id.to_string())
-                .fetch_optional(self.pool)
-                .await
-                .context("Failed to fetch group organization")?;
+        let row: Option<(String,)> = sqlx::query_as(
+            "SELECT organization_id FROM node_groups WHERE id = ? AND deleted_at IS NULL",
+        )
+        .bind(group_id.to_string())
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to fetch group organization")?;
 
         match row {
             Some((org_id,)) => Ok(Some(
@@ -451,6 +589,40 @@ impl<'a> GroupRepository<'a> {
         }
     }
 
+    /// The ids of a group and all of its ancestors, nearest first.
+    ///
+    /// Group-scoped permissions granted on an ancestor apply to the whole
+    /// subtree below it. The walk is capped so a corrupted parent chain cannot
+    /// loop forever.
+    pub async fn get_ancestor_ids(&self, group_id: Uuid) -> Result<Vec<Uuid>> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            r#"
+            WITH RECURSIVE ancestors(id, parent_id, depth) AS (
+                SELECT id, parent_id, 0 FROM node_groups WHERE id = ?
+                UNION
+                SELECT g.id, g.parent_id, a.depth + 1
+                FROM node_groups g
+                JOIN ancestors a ON g.id = a.parent_id
+                WHERE a.depth < 64
+            )
+            SELECT id FROM ancestors ORDER BY depth
+            "#,
+        )
+        .bind(group_id.to_string())
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to fetch group ancestors")?;
+
+        let mut ids = Vec::with_capacity(rows.len());
+        for (id,) in rows {
+            let id = Uuid::parse_str(&id).context("Invalid group ID")?;
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+        Ok(ids)
+    }
+
     /// Convert a database row to a NodeGroup with rules and pinned nodes
     async fn row_to_group(&self, row: GroupRow) -> Result<NodeGroup> {
         let id = Uuid::parse_str(&row.id).context("Invalid group ID")?;
@@ -494,6 +666,7 @@ impl<'a> GroupRepository<'a> {
             variables,
             rules,
             pinned_nodes,
+            version: row.version,
         })
     }
 
@@ -547,6 +720,7 @@ impl<'a> GroupRepository<'a> {
             variables,
             rules,
             pinned_nodes,
+            version: row.version,
         })
     }
 
@@ -659,6 +833,74 @@ impl std::fmt::Display for RuleMatchType {
     }
 }
 
+/// Insert a classification rule for a group
+async fn insert_rule(
+    conn: &mut SqliteConnection,
+    group_id: Uuid,
+    req: &CreateRuleRequest,
+) -> Result<ClassificationRule> {
+    let id = Uuid::new_v4();
+    let operator = operator_to_string(&req.operator);
+    let value = serde_json::to_string(&req.value).unwrap_or_else(|_| "null".to_string());
+
+    sqlx::query(
+        r#"
+        INSERT INTO classification_rules (id, group_id, fact_path, operator, value)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(id.to_string())
+    .bind(group_id.to_string())
+    .bind(&req.fact_path)
+    .bind(&operator)
+    .bind(&value)
+    .execute(conn)
+    .await
+    .context("Failed to add rule")?;
+
+    Ok(ClassificationRule {
+        id,
+        fact_path: req.fact_path.clone(),
+        operator: req.operator,
+        value: req.value.clone(),
+    })
+}
+
+/// Pin a node to a group; returns false when it was already pinned
+async fn insert_pinned_node(
+    conn: &mut SqliteConnection,
+    group_id: Uuid,
+    certname: &str,
+) -> Result<bool> {
+    let result = sqlx::query(
+        r#"
+        INSERT OR IGNORE INTO pinned_nodes (id, group_id, certname)
+        VALUES (?, ?, ?)
+        "#,
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(group_id.to_string())
+    .bind(certname)
+    .execute(conn)
+    .await
+    .context("Failed to add pinned node")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Bump the version of a group whose rules or pinned nodes changed
+async fn bump_version(conn: &mut SqliteConnection, group_id: Uuid) -> Result<()> {
+    sqlx::query(
+        "UPDATE node_groups SET version = version + 1, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+    )
+    .bind(group_id.to_string())
+    .execute(conn)
+    .await
+    .context("Failed to bump group version")?;
+
+    Ok(())
+}
+
 /// Parse rule match type from string
 fn parse_rule_match_type(s: &str) -> RuleMatchType {
     match s.to_lowercase().as_str() {
@@ -899,18 +1141,21 @@ fn row_to_template(row: FactTemplateRow) -> FactTemplate {
 // ============================================================================
 
 use crate::models::{
-    ComplianceBaseline, ComplianceRule, CreateComplianceBaselineRequest,
-    CreateDriftBaselineRequest, CreateSavedReportRequest, CreateScheduleRequest, DriftBaseline,
-    DriftToleranceConfig, ExecutionStatus, OutputFormat, ReportExecution, ReportQueryConfig,
-    ReportSchedule, ReportTemplate, ReportType, SavedReport, SeverityLevel,
-    UpdateComplianceBaselineRequest, UpdateSavedReportRequest, UpdateScheduleRequest,
+    ComplianceBaseline, ComplianceRule, ComplianceRulePack, CreateComplianceBaselineRequest,
+    CreateDriftBaselineRequest, CreateSavedReportRequest, CreateScheduleRequest, DeliveryResult,
+    DeliveryStatus, DeliveryTarget, DriftBaseline, DriftToleranceConfig, ExecutionStatus,
+    OutputFormat, ReportExecution, ReportQueryConfig, ReportSchedule, ReportTemplate, ReportType,
+    RulePackDocument, SavedReport, SeverityLevel, UpdateComplianceBaselineRequest,
+    UpdateSavedReportRequest, UpdateScheduleRequest,
 };
+use crate::services::settings_encryption::{self, SettingsCipher};
 use chrono::{DateTime, Utc};
 
 /// Row returned from saved_reports table
 #[derive(Debug, sqlx::FromRow)]
 struct SavedReportRow {
     id: String,
+    organization_id: String,
     name: String,
     description: Option<String>,
     report_type: String,
@@ -925,12 +1170,14 @@ struct SavedReportRow {
 #[derive(Debug, sqlx::FromRow)]
 struct ReportScheduleRow {
     id: String,
+    organization_id: String,
     report_id: String,
     schedule_cron: String,
     timezone: String,
     is_enabled: bool,
     output_format: String,
     email_recipients: Option<String>,
+    delivery_targets: Option<String>,
     last_run_at: Option<String>,
     next_run_at: Option<String>,
     created_at: String,
@@ -941,6 +1188,7 @@ struct ReportScheduleRow {
 #[derive(Debug, sqlx::FromRow)]
 struct ReportExecutionRow {
     id: String,
+    organization_id: String,
     report_id: String,
     schedule_id: Option<String>,
     executed_by: Option<String>,
@@ -953,25 +1201,49 @@ struct ReportExecutionRow {
     output_file_path: Option<String>,
     error_message: Option<String>,
     execution_time_ms: Option<i32>,
+    delivery_status: Option<String>,
+    delivery_results: Option<String>,
 }
 
 /// Row returned from compliance_baselines table
 #[derive(Debug, sqlx::FromRow)]
 struct ComplianceBaselineRow {
     id: String,
+    organization_id: String,
     name: String,
     description: Option<String>,
     rules: String,
     severity_level: String,
+    node_group_ids: String,
+    rule_pack_id: Option<String>,
+    rule_pack_version: Option<String>,
     created_by: String,
     created_at: String,
     updated_at: String,
 }
 
+/// Row returned from compliance_rule_packs table
+#[derive(Debug, sqlx::FromRow)]
+struct ComplianceRulePackRow {
+    id: String,
+    pack_key: String,
+    name: String,
+    version: String,
+    description: Option<String>,
+    framework: Option<String>,
+    publisher: Option<String>,
+    pack_references: String,
+    rules: String,
+    checksum: String,
+    imported_by: String,
+    imported_at: String,
+}
+
 /// Row returned from drift_baselines table
 #[derive(Debug, sqlx::FromRow)]
 struct DriftBaselineRow {
     id: String,
+    organization_id: String,
     name: String,
     description: Option<String>,
     node_group_id: Option<String>,
@@ -1004,16 +1276,18 @@ impl<'a> SavedReportRepository<'a> {
         Self { pool }
     }
 
-    /// Get all saved reports
-    pub async fn get_all(&self) -> Result<Vec<SavedReport>> {
+    /// Get all saved reports of an organization
+    pub async fn get_all(&self, organization_id: Uuid) -> Result<Vec<SavedReport>> {
         let rows = sqlx::query_as::<_, SavedReportRow>(
             r#"
-            SELECT id, name, description, report_type, query_config,
+            SELECT id, organization_id, name, description, report_type, query_config,
                    created_by, is_public, created_at, updated_at
             FROM saved_reports
+            WHERE organization_id = ? AND deleted_at IS NULL
             ORDER BY name
             "#,
         )
+        .bind(organization_id.to_string())
         .fetch_all(self.pool)
         .await
         .context("Failed to fetch saved reports")?;
@@ -1021,17 +1295,23 @@ impl<'a> SavedReportRepository<'a> {
         Ok(rows.into_iter().map(row_to_saved_report).collect())
     }
 
-    /// Get saved reports by user (including public reports)
-    pub async fn get_by_user(&self, user_id: Uuid) -> Result<Vec<SavedReport>> {
+    /// Get saved reports by user (including the organization's public reports)
+    pub async fn get_by_user(
+        &self,
+        organization_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<SavedReport>> {
         let rows = sqlx::query_as::<_, SavedReportRow>(
             r#"
-            SELECT id, name, description, report_type, query_config,
+            SELECT id, organization_id, name, description, report_type, query_config,
                    created_by, is_public, created_at, updated_at
             FROM saved_reports
-            WHERE created_by = ? OR is_public = TRUE
+            WHERE organization_id = ? AND (created_by = ? OR is_public = TRUE)
+              AND deleted_at IS NULL
             ORDER BY name
             "#,
         )
+        .bind(organization_id.to_string())
         .bind(user_id.to_string())
         .fetch_all(self.pool)
         .await
@@ -1040,17 +1320,22 @@ impl<'a> SavedReportRepository<'a> {
         Ok(rows.into_iter().map(row_to_saved_report).collect())
     }
 
-    /// Get saved reports by type
-    pub async fn get_by_type(&self, report_type: ReportType) -> Result<Vec<SavedReport>> {
+    /// Get saved reports of an organization by type
+    pub async fn get_by_type(
+        &self,
+        organization_id: Uuid,
+        report_type: ReportType,
+    ) -> Result<Vec<SavedReport>> {
         let rows = sqlx::query_as::<_, SavedReportRow>(
             r#"
-            SELECT id, name, description, report_type, query_config,
+            SELECT id, organization_id, name, description, report_type, query_config,
                    created_by, is_public, created_at, updated_at
             FROM saved_reports
-            WHERE report_type = ?
+            WHERE organization_id = ? AND report_type = ? AND deleted_at IS NULL
             ORDER BY name
             "#,
         )
+        .bind(organization_id.to_string())
         .bind(report_type.as_str())
         .fetch_all(self.pool)
         .await
@@ -1059,14 +1344,24 @@ impl<'a> SavedReportRepository<'a> {
         Ok(rows.into_iter().map(row_to_saved_report).collect())
     }
 
-    /// Get a saved report by ID
-    pub async fn get_by_id(&self, id: Uuid) -> Result<Option<SavedReport>> {
+    /// Get a saved report of an organization by ID
+    pub async fn get_by_id(&self, organization_id: Uuid, id: Uuid) -> Result<Option<SavedReport>> {
+        Ok(self
+            .find_by_id(id)
+            .await?
+            .filter(|report| report.organization_id == organization_id))
+    }
+
+    /// Get a saved report by ID, whatever its organization
+    ///
+    /// For background jobs such as the report scheduler.
+    pub async fn find_by_id(&self, id: Uuid) -> Result<Option<SavedReport>> {
         let row = sqlx::query_as::<_, SavedReportRow>(
             r#"
-            SELECT id, name, description, report_type, query_config,
+            SELECT id, organization_id, name, description, report_type, query_config,
                    created_by, is_public, created_at, updated_at
             FROM saved_reports
-            WHERE id = ?
+            WHERE id = ? AND deleted_at IS NULL
             "#,
         )
         .bind(id.to_string())
@@ -1080,6 +1375,7 @@ impl<'a> SavedReportRepository<'a> {
     /// Create a new saved report
     pub async fn create(
         &self,
+        organization_id: Uuid,
         req: &CreateSavedReportRequest,
         user_id: Uuid,
     ) -> Result<SavedReport> {
@@ -1089,11 +1385,12 @@ impl<'a> SavedReportRepository<'a> {
 
         sqlx::query(
             r#"
-            INSERT INTO saved_reports (id, name, description, report_type, query_config, created_by, is_public)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO saved_reports (id, organization_id, name, description, report_type, query_config, created_by, is_public)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(id.to_string())
+        .bind(organization_id.to_string())
         .bind(&req.name)
         .bind(&req.description)
         .bind(req.report_type.as_str())
@@ -1104,7 +1401,7 @@ impl<'a> SavedReportRepository<'a> {
         .await
         .context("Failed to create saved report")?;
 
-        self.get_by_id(id)
+        self.find_by_id(id)
             .await?
             .ok_or_else(|| anyhow::anyhow!("Failed to retrieve created report"))
     }
@@ -1112,10 +1409,11 @@ impl<'a> SavedReportRepository<'a> {
     /// Update a saved report
     pub async fn update(
         &self,
+        organization_id: Uuid,
         id: Uuid,
         req: &UpdateSavedReportRequest,
     ) -> Result<Option<SavedReport>> {
-        let existing = self.get_by_id(id).await?;
+        let existing = self.get_by_id(organization_id, id).await?;
         if existing.is_none() {
             return Ok(None);
         }
@@ -1144,16 +1442,26 @@ impl<'a> SavedReportRepository<'a> {
         .await
         .context("Failed to update saved report")?;
 
-        self.get_by_id(id).await
+        self.find_by_id(id).await
     }
 
-    /// Delete a saved report
-    pub async fn delete(&self, id: Uuid) -> Result<bool> {
-        let result = sqlx::query("DELETE FROM saved_reports WHERE id = ?")
-            .bind(id.to_string())
-            .execute(self.pool)
-            .await
-            .context("Failed to delete saved report")?;
+    /// Move a saved report to the recycle bin
+    ///
+    /// Its schedules and past executions stay until the report is purged.
+    pub async fn delete(&self, organization_id: Uuid, id: Uuid) -> Result<bool> {
+        let result = sqlx::query(
+            r#"
+            UPDATE saved_reports
+            SET deleted_at = ?, updated_at = CURRENT_TIMESTAMP
+            WHERE organization_id = ? AND id = ? AND deleted_at IS NULL
+            "#,
+        )
+        .bind(chrono::Utc::now().to_rfc3339())
+        .bind(organization_id.to_string())
+        .bind(id.to_string())
+        .execute(self.pool)
+        .await
+        .context("Failed to delete saved report")?;
 
         Ok(result.rows_affected() > 0)
     }
@@ -1165,6 +1473,7 @@ fn row_to_saved_report(row: SavedReportRow) -> SavedReport {
 
     SavedReport {
         id: Uuid::parse_str(&row.id).unwrap_or_default(),
+        organization_id: Uuid::parse_str(&row.organization_id).unwrap_or_default(),
         name: row.name,
         description: row.description,
         report_type: ReportType::from_str(&row.report_type).unwrap_or_default(),
@@ -1187,29 +1496,37 @@ fn row_to_saved_report(row: SavedReportRow) -> SavedReport {
 /// Repository for report schedule operations
 pub struct ReportScheduleRepository<'a> {
     pool: &'a SqlitePool,
+    cipher: Option<&'a SettingsCipher>,
 }
 
 impl<'a> ReportScheduleRepository<'a> {
-    pub fn new(pool: &'a SqlitePool) -> Self {
-        Self { pool }
+    /// `cipher` encrypts the delivery targets, which carry credentials;
+    /// without one they are stored as plaintext
+    pub fn new(pool: &'a SqlitePool, cipher: Option<&'a SettingsCipher>) -> Self {
+        Self { pool, cipher }
     }
 
-    /// Get all schedules
-    pub async fn get_all(&self) -> Result<Vec<ReportSchedule>> {
+    /// Get all schedules of an organization
+    pub async fn get_all(&self, organization_id: Uuid) -> Result<Vec<ReportSchedule>> {
         let rows = sqlx::query_as::<_, ReportScheduleRow>(
             r#"
-            SELECT id, report_id, schedule_cron, timezone, is_enabled,
-                   output_format, email_recipients, last_run_at, next_run_at,
-                   created_at, updated_at
+            SELECT id, organization_id, report_id, schedule_cron, timezone, is_enabled,
+                   output_format, email_recipients, delivery_targets, last_run_at,
+                   next_run_at, created_at, updated_at
             FROM report_schedules
+            WHERE organization_id = ?
             ORDER BY next_run_at
             "#,
         )
+        .bind(organization_id.to_string())
         .fetch_all(self.pool)
         .await
         .context("Failed to fetch schedules")?;
 
-        Ok(rows.into_iter().map(row_to_schedule).collect())
+        Ok(rows
+            .into_iter()
+            .map(|row| row_to_schedule(row, self.cipher))
+            .collect())
     }
 
     /// Get all enabled schedules that are due to run now
@@ -1221,11 +1538,12 @@ impl<'a> ReportScheduleRepository<'a> {
     pub async fn get_due_schedules(&self, before: DateTime<Utc>) -> Result<Vec<ReportSchedule>> {
         let rows = sqlx::query_as::<_, ReportScheduleRow>(
             r#"
-            SELECT id, report_id, schedule_cron, timezone, is_enabled,
-                   output_format, email_recipients, last_run_at, next_run_at,
-                   created_at, updated_at
+            SELECT id, organization_id, report_id, schedule_cron, timezone, is_enabled,
+                   output_format, email_recipients, delivery_targets, last_run_at,
+                   next_run_at, created_at, updated_at
             FROM report_schedules
             WHERE is_enabled = TRUE AND next_run_at <= ?
+              AND report_id IN (SELECT id FROM saved_reports WHERE deleted_at IS NULL)
             ORDER BY next_run_at
             "#,
         )
@@ -1234,16 +1552,19 @@ impl<'a> ReportScheduleRepository<'a> {
         .await
         .context("Failed to fetch due schedules")?;
 
-        Ok(rows.into_iter().map(row_to_schedule).collect())
+        Ok(rows
+            .into_iter()
+            .map(|row| row_to_schedule(row, self.cipher))
+            .collect())
     }
 
     /// Get schedules for a report
     pub async fn get_by_report(&self, report_id: Uuid) -> Result<Vec<ReportSchedule>> {
         let rows = sqlx::query_as::<_, ReportScheduleRow>(
             r#"
-            SELECT id, report_id, schedule_cron, timezone, is_enabled,
-                   output_format, email_recipients, last_run_at, next_run_at,
-                   created_at, updated_at
+            SELECT id, organization_id, report_id, schedule_cron, timezone, is_enabled,
+                   output_format, email_recipients, delivery_targets, last_run_at,
+                   next_run_at, created_at, updated_at
             FROM report_schedules
             WHERE report_id = ?
             "#,
@@ -1253,16 +1574,31 @@ impl<'a> ReportScheduleRepository<'a> {
         .await
         .context("Failed to fetch schedules")?;
 
-        Ok(rows.into_iter().map(row_to_schedule).collect())
+        Ok(rows
+            .into_iter()
+            .map(|row| row_to_schedule(row, self.cipher))
+            .collect())
+    }
+
+    /// Get a schedule of an organization by ID
+    pub async fn get_by_id(
+        &self,
+        organization_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<ReportSchedule>> {
+        Ok(self
+            .find_by_id(id)
+            .await?
+            .filter(|schedule| schedule.organization_id == organization_id))
     }
 
-    /// Get a schedule by ID
-    pub async fn get_by_id(&self, id: Uuid) -> Result<Option<ReportSchedule>> {
+    /// Get a schedule by ID, whatever its organization
+    pub async fn find_by_id(&self, id: Uuid) -> Result<Option<ReportSchedule>> {
         let row = sqlx::query_as::<_, ReportScheduleRow>(
             r#"
-            SELECT id, report_id, schedule_cron, timezone, is_enabled,
-                   output_format, email_recipients, last_run_at, next_run_at,
-                   created_at, updated_at
+            SELECT id, organization_id, report_id, schedule_cron, timezone, is_enabled,
+                   output_format, email_recipients, delivery_targets, last_run_at,
+                   next_run_at, created_at, updated_at
             FROM report_schedules
             WHERE id = ?
             "#,
@@ -1272,35 +1608,42 @@ impl<'a> ReportScheduleRepository<'a> {
         .await
         .context("Failed to fetch schedule")?;
 
-        Ok(row.map(row_to_schedule))
+        Ok(row.map(|row| row_to_schedule(row, self.cipher)))
     }
 
     /// Create a new schedule
-    pub async fn create(&self, req: &CreateScheduleRequest) -> Result<ReportSchedule> {
+    pub async fn create(
+        &self,
+        organization_id: Uuid,
+        req: &CreateScheduleRequest,
+    ) -> Result<ReportSchedule> {
         let id = Uuid::new_v4();
         let email_recipients = req
             .email_recipients
             .as_ref()
             .map(|r| serde_json::to_string(r).unwrap_or_else(|_| "[]".to_string()));
+        let delivery_targets = encode_delivery_targets(&req.delivery_targets, self.cipher)?;
 
         sqlx::query(
             r#"
-            INSERT INTO report_schedules (id, report_id, schedule_cron, timezone, is_enabled, output_format, email_recipients)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO report_schedules (id, organization_id, report_id, schedule_cron, timezone, is_enabled, output_format, email_recipients, delivery_targets)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(id.to_string())
+        .bind(organization_id.to_string())
         .bind(req.report_id.to_string())
         .bind(&req.schedule_cron)
         .bind(&req.timezone)
         .bind(req.is_enabled)
         .bind(req.output_format.as_str())
         .bind(&email_recipients)
+        .bind(&delivery_targets)
         .execute(self.pool)
         .await
         .context("Failed to create schedule")?;
 
-        self.get_by_id(id)
+        self.find_by_id(id)
             .await?
             .ok_or_else(|| anyhow::anyhow!("Failed to retrieve created schedule"))
     }
@@ -1308,10 +1651,11 @@ impl<'a> ReportScheduleRepository<'a> {
     /// Update a schedule
     pub async fn update(
         &self,
+        organization_id: Uuid,
         id: Uuid,
         req: &UpdateScheduleRequest,
     ) -> Result<Option<ReportSchedule>> {
-        let existing = self.get_by_id(id).await?;
+        let existing = self.get_by_id(organization_id, id).await?;
         if existing.is_none() {
             return Ok(None);
         }
@@ -1329,12 +1673,18 @@ impl<'a> ReportScheduleRepository<'a> {
             .as_ref()
             .or(existing.email_recipients.as_ref())
             .map(|r| serde_json::to_string(r).unwrap_or_else(|_| "[]".to_string()));
+        let delivery_targets = encode_delivery_targets(
+            req.delivery_targets
+                .as_ref()
+                .unwrap_or(&existing.delivery_targets),
+            self.cipher,
+        )?;
 
         sqlx::query(
             r#"
             UPDATE report_schedules
             SET schedule_cron = ?, timezone = ?, is_enabled = ?, output_format = ?,
-                email_recipients = ?, updated_at = CURRENT_TIMESTAMP
+                email_recipients = ?, delivery_targets = ?, updated_at = CURRENT_TIMESTAMP
             WHERE id = ?
             "#,
         )
@@ -1343,12 +1693,13 @@ impl<'a> ReportScheduleRepository<'a> {
         .bind(is_enabled)
         .bind(output_format.as_str())
         .bind(&email_recipients)
+        .bind(&delivery_targets)
         .bind(id.to_string())
         .execute(self.pool)
         .await
         .context("Failed to update schedule")?;
 
-        self.get_by_id(id).await
+        self.find_by_id(id).await
     }
 
     /// Update next run time
@@ -1376,30 +1727,91 @@ impl<'a> ReportScheduleRepository<'a> {
     }
 
     /// Delete a schedule
-    pub async fn delete(&self, id: Uuid) -> Result<bool> {
-        let result = sqlx::query("DELETE FROM report_schedules WHERE id = ?")
-            .bind(id.to_string())
-            .execute(self.pool)
-            .await
-            .context("Failed to delete schedule")?;
+    pub async fn delete(&self, organization_id: Uuid, id: Uuid) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM report_schedules WHERE organization_id = ? AND id = ?")
+                .bind(organization_id.to_string())
+                .bind(id.to_string())
+                .execute(self.pool)
+                .await
+                .context("Failed to delete schedule")?;
 
         Ok(result.rows_affected() > 0)
     }
 }
 
-fn row_to_schedule(row: ReportScheduleRow) -> ReportSchedule {
+/// Serialize delivery targets for storage, encrypted when a settings master
+/// key is loaded since they carry credentials
+fn encode_delivery_targets(
+    targets: &[DeliveryTarget],
+    cipher: Option<&SettingsCipher>,
+) -> Result<Option<String>> {
+    if targets.is_empty() {
+        return Ok(None);
+    }
+    let json = serde_json::to_string(targets).context("Failed to serialize delivery targets")?;
+    match cipher {
+        Some(cipher) => cipher.encrypt(&json).map(Some),
+        None => Ok(Some(json)),
+    }
+}
+
+fn decode_delivery_targets(
+    schedule_id: &str,
+    stored: Option<String>,
+    cipher: Option<&SettingsCipher>,
+) -> Vec<DeliveryTarget> {
+    let Some(stored) = stored.filter(|s| !s.is_empty()) else {
+        return Vec::new();
+    };
+    let json = if settings_encryption::is_encrypted(&stored) {
+        match cipher.map(|c| c.decrypt(&stored)) {
+            Some(Ok(json)) => json,
+            Some(Err(e)) => {
+                tracing::error!(
+                    "Failed to decrypt delivery targets of schedule {}: {}",
+                    schedule_id,
+                    e
+                );
+                return Vec::new();
+            }
+            None => {
+                tracing::error!(
+                    "Delivery targets of schedule {} are encrypted but no settings master key is loaded",
+                    schedule_id
+                );
+                return Vec::new();
+            }
+        }
+    } else {
+        stored
+    };
+    serde_json::from_str(&json).unwrap_or_else(|e| {
+        tracing::error!(
+            "Invalid delivery targets stored for schedule {}: {}",
+            schedule_id,
+            e
+        );
+        Vec::new()
+    })
+}
+
+fn row_to_schedule(row: ReportScheduleRow, cipher: Option<&SettingsCipher>) -> ReportSchedule {
     let email_recipients: Option<Vec<String>> = row
         .email_recipients
         .and_then(|s| serde_json::from_str(&s).ok());
+    let delivery_targets = decode_delivery_targets(&row.id, row.delivery_targets, cipher);
 
     ReportSchedule {
         id: Uuid::parse_str(&row.id).unwrap_or_default(),
+        organization_id: Uuid::parse_str(&row.organization_id).unwrap_or_default(),
         report_id: Uuid::parse_str(&row.report_id).unwrap_or_default(),
         schedule_cron: row.schedule_cron,
         timezone: row.timezone,
         is_enabled: row.is_enabled,
         output_format: OutputFormat::from_str(&row.output_format).unwrap_or_default(),
         email_recipients,
+        delivery_targets,
         last_run_at: row.last_run_at.and_then(|s| {
             DateTime::parse_from_rfc3339(&s)
                 .ok()
@@ -1433,24 +1845,27 @@ impl<'a> ReportExecutionRepository<'a> {
         Self { pool }
     }
 
-    /// Get executions for a report
+    /// Get executions for a report of an organization
     pub async fn get_by_report(
         &self,
+        organization_id: Uuid,
         report_id: Uuid,
         limit: Option<u32>,
     ) -> Result<Vec<ReportExecution>> {
         let limit = limit.unwrap_or(100);
         let rows = sqlx::query_as::<_, ReportExecutionRow>(
             r#"
-            SELECT id, report_id, schedule_id, executed_by, status, started_at,
+            SELECT id, organization_id, report_id, schedule_id, executed_by, status, started_at,
                    completed_at, row_count, output_format, output_data,
-                   output_file_path, error_message, execution_time_ms
+                   output_file_path, error_message, execution_time_ms,
+                   delivery_status, delivery_results
             FROM report_executions
-            WHERE report_id = ?
+            WHERE organization_id = ? AND report_id = ?
             ORDER BY started_at DESC
             LIMIT ?
             "#,
         )
+        .bind(organization_id.to_string())
         .bind(report_id.to_string())
         .bind(limit)
         .fetch_all(self.pool)
@@ -1460,13 +1875,26 @@ impl<'a> ReportExecutionRepository<'a> {
         Ok(rows.into_iter().map(row_to_execution).collect())
     }
 
-    /// Get an execution by ID
-    pub async fn get_by_id(&self, id: Uuid) -> Result<Option<ReportExecution>> {
+    /// Get an execution of an organization by ID
+    pub async fn get_by_id(
+        &self,
+        organization_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<ReportExecution>> {
+        Ok(self
+            .find_by_id(id)
+            .await?
+            .filter(|execution| execution.organization_id == organization_id))
+    }
+
+    /// Get an execution by ID, whatever its organization
+    pub async fn find_by_id(&self, id: Uuid) -> Result<Option<ReportExecution>> {
         let row = sqlx::query_as::<_, ReportExecutionRow>(
             r#"
-            SELECT id, report_id, schedule_id, executed_by, status, started_at,
+            SELECT id, organization_id, report_id, schedule_id, executed_by, status, started_at,
                    completed_at, row_count, output_format, output_data,
-                   output_file_path, error_message, execution_time_ms
+                   output_file_path, error_message, execution_time_ms,
+                   delivery_status, delivery_results
             FROM report_executions
             WHERE id = ?
             "#,
@@ -1482,6 +1910,7 @@ impl<'a> ReportExecutionRepository<'a> {
     /// Create a new execution
     pub async fn create(
         &self,
+        organization_id: Uuid,
         report_id: Uuid,
         schedule_id: Option<Uuid>,
         executed_by: Option<Uuid>,
@@ -1491,11 +1920,12 @@ impl<'a> ReportExecutionRepository<'a> {
 
         sqlx::query(
             r#"
-            INSERT INTO report_executions (id, report_id, schedule_id, executed_by, status, output_format)
-            VALUES (?, ?, ?, ?, 'pending', ?)
+            INSERT INTO report_executions (id, organization_id, report_id, schedule_id, executed_by, status, output_format)
+            VALUES (?, ?, ?, ?, ?, 'pending', ?)
             "#,
         )
         .bind(id.to_string())
+        .bind(organization_id.to_string())
         .bind(report_id.to_string())
         .bind(schedule_id.map(|s| s.to_string()))
         .bind(executed_by.map(|u| u.to_string()))
@@ -1504,7 +1934,7 @@ impl<'a> ReportExecutionRepository<'a> {
         .await
         .context("Failed to create execution")?;
 
-        self.get_by_id(id)
+        self.find_by_id(id)
             .await?
             .ok_or_else(|| anyhow::anyhow!("Failed to retrieve created execution"))
     }
@@ -1572,16 +2002,67 @@ impl<'a> ReportExecutionRepository<'a> {
         Ok(())
     }
 
+    /// Record the outcome of delivering an execution's output
+    pub async fn record_delivery(&self, id: Uuid, results: &[DeliveryResult]) -> Result<()> {
+        let status = DeliveryStatus::from_results(results);
+        let results_str =
+            serde_json::to_string(results).context("Failed to serialize delivery results")?;
+
+        sqlx::query(
+            r#"
+            UPDATE report_executions
+            SET delivery_status = ?, delivery_results = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(status.map(|s| s.as_str()))
+        .bind(&results_str)
+        .bind(id.to_string())
+        .execute(self.pool)
+        .await
+        .context("Failed to record execution delivery")?;
+
+        Ok(())
+    }
+
     /// Delete old executions
     pub async fn delete_old(&self, older_than: DateTime<Utc>) -> Result<u64> {
-        let result = sqlx::query("DELETE FROM report_executions WHERE started_at < ?")
-            .bind(older_than.to_rfc3339())
-            .execute(self.pool)
-            .await
-            .context("Failed to delete old executions")?;
+        let result =
+            sqlx::query("DELETE FROM report_executions WHERE datetime(started_at) < datetime(?)")
+                .bind(older_than.to_rfc3339())
+                .execute(self.pool)
+                .await
+                .context("Failed to delete old executions")?;
 
         Ok(result.rows_affected())
     }
+
+    /// Output files of the executions [`delete_old`](Self::delete_old) would
+    /// delete
+    pub async fn output_files_before(&self, older_than: DateTime<Utc>) -> Result<Vec<String>> {
+        sqlx::query_scalar(
+            r#"
+            SELECT output_file_path FROM report_executions
+            WHERE output_file_path IS NOT NULL AND datetime(started_at) < datetime(?)
+            "#,
+        )
+        .bind(older_than.to_rfc3339())
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to fetch old execution outputs")
+    }
+
+    /// Output files referenced by any execution
+    pub async fn output_file_paths(&self) -> Result<HashSet<String>> {
+        let paths: Vec<String> = sqlx::query_scalar(
+            "SELECT output_file_path FROM report_executions WHERE output_file_path IS NOT NULL",
+        )
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to fetch execution outputs")?;
+
+        Ok(paths.into_iter().collect())
+    }
 }
 
 fn row_to_execution(row: ReportExecutionRow) -> ReportExecution {
@@ -1590,6 +2071,7 @@ fn row_to_execution(row: ReportExecutionRow) -> ReportExecution {
 
     ReportExecution {
         id: Uuid::parse_str(&row.id).unwrap_or_default(),
+        organization_id: Uuid::parse_str(&row.organization_id).unwrap_or_default(),
         report_id: Uuid::parse_str(&row.report_id).unwrap_or_default(),
         schedule_id: row.schedule_id.and_then(|s| Uuid::parse_str(&s).ok()),
         executed_by: row.executed_by.and_then(|s| Uuid::parse_str(&s).ok()),
@@ -1608,6 +2090,13 @@ fn row_to_execution(row: ReportExecutionRow) -> ReportExecution {
         output_file_path: row.output_file_path,
         error_message: row.error_message,
         execution_time_ms: row.execution_time_ms,
+        delivery_status: row
+            .delivery_status
+            .and_then(|s| DeliveryStatus::from_str(&s)),
+        delivery_results: row
+            .delivery_results
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default(),
     }
 }
 
@@ -1625,15 +2114,19 @@ impl<'a> ComplianceBaselineRepository<'a> {
         Self { pool }
     }
 
-    /// Get all baselines
-    pub async fn get_all(&self) -> Result<Vec<ComplianceBaseline>> {
+    /// Get all baselines of an organization
+    pub async fn get_all(&self, organization_id: Uuid) -> Result<Vec<ComplianceBaseline>> {
         let rows = sqlx::query_as::<_, ComplianceBaselineRow>(
             r#"
-            SELECT id, name, description, rules, severity_level, created_by, created_at, updated_at
+            SELECT id, organization_id, name, description, rules, severity_level,
+                   node_group_ids, rule_pack_id, rule_pack_version, created_by,
+                   created_at, updated_at
             FROM compliance_baselines
+            WHERE organization_id = ?
             ORDER BY name
             "#,
         )
+        .bind(organization_id.to_string())
         .fetch_all(self.pool)
         .await
         .context("Failed to fetch compliance baselines")?;
@@ -1641,15 +2134,22 @@ impl<'a> ComplianceBaselineRepository<'a> {
         Ok(rows.into_iter().map(row_to_compliance_baseline).collect())
     }
 
-    /// Get a baseline by ID
-    pub async fn get_by_id(&self, id: Uuid) -> Result<Option<ComplianceBaseline>> {
+    /// Get a baseline of an organization by ID
+    pub async fn get_by_id(
+        &self,
+        organization_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<ComplianceBaseline>> {
         let row = sqlx::query_as::<_, ComplianceBaselineRow>(
             r#"
-            SELECT id, name, description, rules, severity_level, created_by, created_at, updated_at
+            SELECT id, organization_id, name, description, rules, severity_level,
+                   node_group_ids, rule_pack_id, rule_pack_version, created_by,
+                   created_at, updated_at
             FROM compliance_baselines
-            WHERE id = ?
+            WHERE organization_id = ? AND id = ?
             "#,
         )
+        .bind(organization_id.to_string())
         .bind(id.to_string())
         .fetch_optional(self.pool)
         .await
@@ -1661,29 +2161,34 @@ impl<'a> ComplianceBaselineRepository<'a> {
     /// Create a new baseline
     pub async fn create(
         &self,
+        organization_id: Uuid,
         req: &CreateComplianceBaselineRequest,
         user_id: Uuid,
     ) -> Result<ComplianceBaseline> {
         let id = Uuid::new_v4();
         let rules_json = serde_json::to_string(&req.rules).unwrap_or_else(|_| "[]".to_string());
+        let groups_json =
+            serde_json::to_string(&req.node_group_ids).unwrap_or_else(|_| "[]".to_string());
 
         sqlx::query(
             r#"
-            INSERT INTO compliance_baselines (id, name, description, rules, severity_level, created_by)
-            VALUES (?, ?, ?, ?, ?, ?)
+            INSERT INTO compliance_baselines (id, organization_id, name, description, rules, severity_level, node_group_ids, created_by)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(id.to_string())
+        .bind(organization_id.to_string())
         .bind(&req.name)
         .bind(&req.description)
         .bind(&rules_json)
         .bind(req.severity_level.as_str())
+        .bind(&groups_json)
         .bind(user_id.to_string())
         .execute(self.pool)
         .await
         .context("Failed to create compliance baseline")?;
 
-        self.get_by_id(id)
+        self.get_by_id(organization_id, id)
             .await?
             .ok_or_else(|| anyhow::anyhow!("Failed to retrieve created baseline"))
     }
@@ -1691,10 +2196,11 @@ impl<'a> ComplianceBaselineRepository<'a> {
     /// Update a baseline
     pub async fn update(
         &self,
+        organization_id: Uuid,
         id: Uuid,
         req: &UpdateComplianceBaselineRequest,
     ) -> Result<Option<ComplianceBaseline>> {
-        let existing = self.get_by_id(id).await?;
+        let existing = self.get_by_id(organization_id, id).await?;
         if existing.is_none() {
             return Ok(None);
         }
@@ -1707,12 +2213,19 @@ impl<'a> ComplianceBaselineRepository<'a> {
         };
         let rules = req.rules.as_ref().unwrap_or(&existing.rules);
         let severity = req.severity_level.unwrap_or(existing.severity_level);
+        let node_group_ids = req
+            .node_group_ids
+            .as_ref()
+            .unwrap_or(&existing.node_group_ids);
         let rules_json = serde_json::to_string(rules).unwrap_or_else(|_| "[]".to_string());
+        let groups_json =
+            serde_json::to_string(node_group_ids).unwrap_or_else(|_| "[]".to_string());
 
         sqlx::query(
             r#"
             UPDATE compliance_baselines
-            SET name = ?, description = ?, rules = ?, severity_level = ?, updated_at = CURRENT_TIMESTAMP
+            SET name = ?, description = ?, rules = ?, severity_level = ?, node_group_ids = ?,
+                updated_at = CURRENT_TIMESTAMP
             WHERE id = ?
             "#,
         )
@@ -1720,21 +2233,56 @@ impl<'a> ComplianceBaselineRepository<'a> {
         .bind(description)
         .bind(&rules_json)
         .bind(severity.as_str())
+        .bind(&groups_json)
         .bind(id.to_string())
         .execute(self.pool)
         .await
         .context("Failed to update compliance baseline")?;
 
-        self.get_by_id(id).await
+        self.get_by_id(organization_id, id).await
+    }
+
+    /// Replace a baseline's rules with those of a rule pack version
+    pub async fn apply_rule_pack(
+        &self,
+        organization_id: Uuid,
+        id: Uuid,
+        pack: &ComplianceRulePack,
+    ) -> Result<Option<ComplianceBaseline>> {
+        let rules_json = serde_json::to_string(&pack.rules).unwrap_or_else(|_| "[]".to_string());
+
+        let result = sqlx::query(
+            r#"
+            UPDATE compliance_baselines
+            SET rules = ?, rule_pack_id = ?, rule_pack_version = ?,
+                updated_at = CURRENT_TIMESTAMP
+            WHERE organization_id = ? AND id = ?
+            "#,
+        )
+        .bind(&rules_json)
+        .bind(pack.id.to_string())
+        .bind(&pack.version)
+        .bind(organization_id.to_string())
+        .bind(id.to_string())
+        .execute(self.pool)
+        .await
+        .context("Failed to apply rule pack to compliance baseline")?;
+
+        if result.rows_affected() == 0 {
+            return Ok(None);
+        }
+        self.get_by_id(organization_id, id).await
     }
 
     /// Delete a baseline
-    pub async fn delete(&self, id: Uuid) -> Result<bool> {
-        let result = sqlx::query("DELETE FROM compliance_baselines WHERE id = ?")
-            .bind(id.to_string())
-            .execute(self.pool)
-            .await
-            .context("Failed to delete compliance baseline")?;
+    pub async fn delete(&self, organization_id: Uuid, id: Uuid) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM compliance_baselines WHERE organization_id = ? AND id = ?")
+                .bind(organization_id.to_string())
+                .bind(id.to_string())
+                .execute(self.pool)
+                .await
+                .context("Failed to delete compliance baseline")?;
 
         Ok(result.rows_affected() > 0)
     }
@@ -1742,13 +2290,21 @@ impl<'a> ComplianceBaselineRepository<'a> {
 
 fn row_to_compliance_baseline(row: ComplianceBaselineRow) -> ComplianceBaseline {
     let rules: Vec<ComplianceRule> = serde_json::from_str(&row.rules).unwrap_or_default();
+    let node_group_ids: Vec<Uuid> = serde_json::from_str(&row.node_group_ids).unwrap_or_default();
 
     ComplianceBaseline {
         id: Uuid::parse_str(&row.id).unwrap_or_default(),
+        organization_id: Uuid::parse_str(&row.organization_id).unwrap_or_default(),
         name: row.name,
         description: row.description,
         rules,
         severity_level: SeverityLevel::from_str(&row.severity_level).unwrap_or_default(),
+        node_group_ids,
+        rule_pack_id: row
+            .rule_pack_id
+            .as_deref()
+            .and_then(|id| Uuid::parse_str(id).ok()),
+        rule_pack_version: row.rule_pack_version,
         created_by: Uuid::parse_str(&row.created_by).unwrap_or_default(),
         created_at: DateTime::parse_from_rfc3339(&row.created_at)
             .map(|dt| dt.with_timezone(&Utc))
@@ -1759,6 +2315,155 @@ fn row_to_compliance_baseline(row: ComplianceBaselineRow) -> ComplianceBaseline
     }
 }
 
+// ============================================================================
+// Compliance Rule Pack Repository
+// ============================================================================
+
+/// Repository for compliance rule pack operations
+pub struct ComplianceRulePackRepository<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> ComplianceRulePackRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Get all imported pack versions, newest import of each pack first
+    pub async fn get_all(&self) -> Result<Vec<ComplianceRulePack>> {
+        let rows = sqlx::query_as::<_, ComplianceRulePackRow>(
+            r#"
+            SELECT id, pack_key, name, version, description, framework, publisher,
+                   pack_references, rules, checksum, imported_by, imported_at
+            FROM compliance_rule_packs
+            ORDER BY pack_key, imported_at DESC
+            "#,
+        )
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to fetch compliance rule packs")?;
+
+        Ok(rows.into_iter().map(row_to_compliance_rule_pack).collect())
+    }
+
+    /// Get a pack version by ID
+    pub async fn get_by_id(&self, id: Uuid) -> Result<Option<ComplianceRulePack>> {
+        let row = sqlx::query_as::<_, ComplianceRulePackRow>(
+            r#"
+            SELECT id, pack_key, name, version, description, framework, publisher,
+                   pack_references, rules, checksum, imported_by, imported_at
+            FROM compliance_rule_packs
+            WHERE id = ?
+            "#,
+        )
+        .bind(id.to_string())
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to fetch compliance rule pack")?;
+
+        Ok(row.map(row_to_compliance_rule_pack))
+    }
+
+    /// Whether a version of a pack has been imported
+    pub async fn exists(&self, pack_key: &str, version: &str) -> Result<bool> {
+        let count: (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM compliance_rule_packs WHERE pack_key = ? AND version = ?",
+        )
+        .bind(pack_key)
+        .bind(version)
+        .fetch_one(self.pool)
+        .await
+        .context("Failed to check compliance rule pack")?;
+
+        Ok(count.0 > 0)
+    }
+
+    /// Store an imported pack version
+    pub async fn create(
+        &self,
+        doc: &RulePackDocument,
+        checksum: &str,
+        user_id: Uuid,
+    ) -> Result<ComplianceRulePack> {
+        let id = Uuid::new_v4();
+        let references_json =
+            serde_json::to_string(&doc.references).unwrap_or_else(|_| "[]".to_string());
+        let rules_json = serde_json::to_string(&doc.rules).unwrap_or_else(|_| "[]".to_string());
+
+        sqlx::query(
+            r#"
+            INSERT INTO compliance_rule_packs (id, pack_key, name, version, description, framework,
+                                               publisher, pack_references, rules, checksum,
+                                               imported_by, imported_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(id.to_string())
+        .bind(&doc.id)
+        .bind(&doc.name)
+        .bind(&doc.version)
+        .bind(&doc.description)
+        .bind(&doc.framework)
+        .bind(&doc.publisher)
+        .bind(&references_json)
+        .bind(&rules_json)
+        .bind(checksum)
+        .bind(user_id.to_string())
+        .bind(Utc::now().to_rfc3339())
+        .execute(self.pool)
+        .await
+        .context("Failed to create compliance rule pack")?;
+
+        self.get_by_id(id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Failed to retrieve created rule pack"))
+    }
+
+    /// Delete a pack version
+    ///
+    /// Baselines loaded from it keep their rules and version.
+    pub async fn delete(&self, id: Uuid) -> Result<bool> {
+        let mut tx = begin_write(self.pool)
+            .await
+            .context("Failed to begin rule pack deletion transaction")?;
+
+        sqlx::query("UPDATE compliance_baselines SET rule_pack_id = NULL WHERE rule_pack_id = ?")
+            .bind(id.to_string())
+            .execute(&mut *tx)
+            .await
+            .context("Failed to detach compliance baselines from rule pack")?;
+        let result = sqlx::query("DELETE FROM compliance_rule_packs WHERE id = ?")
+            .bind(id.to_string())
+            .execute(&mut *tx)
+            .await
+            .context("Failed to delete compliance rule pack")?;
+
+        tx.commit()
+            .await
+            .context("Failed to commit rule pack deletion")?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn row_to_compliance_rule_pack(row: ComplianceRulePackRow) -> ComplianceRulePack {
+    ComplianceRulePack {
+        id: Uuid::parse_str(&row.id).unwrap_or_default(),
+        pack_key: row.pack_key,
+        name: row.name,
+        version: row.version,
+        description: row.description,
+        framework: row.framework,
+        publisher: row.publisher,
+        references: serde_json::from_str(&row.pack_references).unwrap_or_default(),
+        rules: serde_json::from_str(&row.rules).unwrap_or_default(),
+        checksum: row.checksum,
+        imported_by: Uuid::parse_str(&row.imported_by).unwrap_or_default(),
+        imported_at: DateTime::parse_from_rfc3339(&row.imported_at)
+            .map(|dt| dt.with_timezone(&Utc))
+            .unwrap_or_else(|_| Utc::now()),
+    }
+}
+
 // ============================================================================
 // Drift Baseline Repository
 // ============================================================================
@@ -1773,16 +2478,18 @@ impl<'a> DriftBaselineRepository<'a> {
         Self { pool }
     }
 
-    /// Get all baselines
-    pub async fn get_all(&self) -> Result<Vec<DriftBaseline>> {
+    /// Get all baselines of an organization
+    pub async fn get_all(&self, organization_id: Uuid) -> Result<Vec<DriftBaseline>> {
         let rows = sqlx::query_as::<_, DriftBaselineRow>(
             r#"
-            SELECT id, name, description, node_group_id, baseline_facts,
+            SELECT id, organization_id, name, description, node_group_id, baseline_facts,
                    tolerance_config, created_by, created_at, updated_at
             FROM drift_baselines
+            WHERE organization_id = ?
             ORDER BY name
             "#,
         )
+        .bind(organization_id.to_string())
         .fetch_all(self.pool)
         .await
         .context("Failed to fetch drift baselines")?;
@@ -1790,16 +2497,21 @@ impl<'a> DriftBaselineRepository<'a> {
         Ok(rows.into_iter().map(row_to_drift_baseline).collect())
     }
 
-    /// Get a baseline by ID
-    pub async fn get_by_id(&self, id: Uuid) -> Result<Option<DriftBaseline>> {
+    /// Get a baseline of an organization by ID
+    pub async fn get_by_id(
+        &self,
+        organization_id: Uuid,
+        id: Uuid,
+    ) -> Result<Option<DriftBaseline>> {
         let row = sqlx::query_as::<_, DriftBaselineRow>(
             r#"
-            SELECT id, name, description, node_group_id, baseline_facts,
+            SELECT id, organization_id, name, description, node_group_id, baseline_facts,
                    tolerance_config, created_by, created_at, updated_at
             FROM drift_baselines
-            WHERE id = ?
+            WHERE organization_id = ? AND id = ?
             "#,
         )
+        .bind(organization_id.to_string())
         .bind(id.to_string())
         .fetch_optional(self.pool)
         .await
@@ -1808,16 +2520,21 @@ impl<'a> DriftBaselineRepository<'a> {
         Ok(row.map(row_to_drift_baseline))
     }
 
-    /// Get baselines by node group
-    pub async fn get_by_group(&self, group_id: Uuid) -> Result<Vec<DriftBaseline>> {
+    /// Get baselines of an organization by node group
+    pub async fn get_by_group(
+        &self,
+        organization_id: Uuid,
+        group_id: Uuid,
+    ) -> Result<Vec<DriftBaseline>> {
         let rows = sqlx::query_as::<_, DriftBaselineRow>(
             r#"
-            SELECT id, name, description, node_group_id, baseline_facts,
+            SELECT id, organization_id, name, description, node_group_id, baseline_facts,
                    tolerance_config, created_by, created_at, updated_at
             FROM drift_baselines
-            WHERE node_group_id = ?
+            WHERE organization_id = ? AND node_group_id = ?
             "#,
         )
+        .bind(organization_id.to_string())
         .bind(group_id.to_string())
         .fetch_all(self.pool)
         .await
@@ -1829,6 +2546,7 @@ impl<'a> DriftBaselineRepository<'a> {
     /// Create a new baseline
     pub async fn create(
         &self,
+        organization_id: Uuid,
         req: &CreateDriftBaselineRequest,
         user_id: Uuid,
     ) -> Result<DriftBaseline> {
@@ -1842,11 +2560,12 @@ impl<'a> DriftBaselineRepository<'a> {
 
         sqlx::query(
             r#"
-            INSERT INTO drift_baselines (id, name, description, node_group_id, baseline_facts, tolerance_config, created_by)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO drift_baselines (id, organization_id, name, description, node_group_id, baseline_facts, tolerance_config, created_by)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )
         .bind(id.to_string())
+        .bind(organization_id.to_string())
         .bind(&req.name)
         .bind(&req.description)
         .bind(req.node_group_id.map(|g| g.to_string()))
@@ -1857,7 +2576,7 @@ impl<'a> DriftBaselineRepository<'a> {
         .await
         .context("Failed to create drift baseline")?;
 
-        self.get_by_id(id)
+        self.get_by_id(organization_id, id)
             .await?
             .ok_or_else(|| anyhow::anyhow!("Failed to retrieve created baseline"))
     }
@@ -1865,10 +2584,11 @@ impl<'a> DriftBaselineRepository<'a> {
     /// Update a baseline
     pub async fn update(
         &self,
+        organization_id: Uuid,
         id: Uuid,
         req: &crate::models::UpdateDriftBaselineRequest,
     ) -> Result<Option<DriftBaseline>> {
-        let existing = self.get_by_id(id).await?;
+        let existing = self.get_by_id(organization_id, id).await?;
         if existing.is_none() {
             return Ok(None);
         }
@@ -1918,16 +2638,18 @@ impl<'a> DriftBaselineRepository<'a> {
         .await
         .context("Failed to update drift baseline")?;
 
-        self.get_by_id(id).await
+        self.get_by_id(organization_id, id).await
     }
 
     /// Delete a baseline
-    pub async fn delete(&self, id: Uuid) -> Result<bool> {
-        let result = sqlx::query("DELETE FROM drift_baselines WHERE id = ?")
-            .bind(id.to_string())
-            .execute(self.pool)
-            .await
-            .context("Failed to delete drift baseline")?;
+    pub async fn delete(&self, organization_id: Uuid, id: Uuid) -> Result<bool> {
+        let result =
+            sqlx::query("DELETE FROM drift_baselines WHERE organization_id = ? AND id = ?")
+                .bind(organization_id.to_string())
+                .bind(id.to_string())
+                .execute(self.pool)
+                .await
+                .context("Failed to delete drift baseline")?;
 
         Ok(result.rows_affected() > 0)
     }
@@ -1942,6 +2664,7 @@ fn row_to_drift_baseline(row: DriftBaselineRow) -> DriftBaseline {
 
     DriftBaseline {
         id: Uuid::parse_str(&row.id).unwrap_or_default(),
+        organization_id: Uuid::parse_str(&row.organization_id).unwrap_or_default(),
         name: row.name,
         description: row.description,
         node_group_id: row.node_group_id.and_then(|s| Uuid::parse_str(&s).ok()),
@@ -0,0 +1,187 @@
+//! Full-text search index repository
+//!
+//! The index is an SQLite FTS5 table holding one document per searchable
+//! object. Documents are never updated in place: the indexer replaces all
+//! documents of an organization at once.
+
+use anyhow::{Context, Result};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::db::begin_write;
+use crate::models::{SearchKind, SearchResult};
+
+/// A document to store in the search index
+#[derive(Debug, Clone)]
+pub struct SearchDocument {
+    pub kind: SearchKind,
+    pub ref_id: String,
+    /// User the document is private to, if any
+    pub owner_id: Option<Uuid>,
+    pub title: String,
+    pub body: String,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct SearchRow {
+    kind: String,
+    ref_id: String,
+    title: String,
+    snippet: Option<String>,
+    rank: f64,
+}
+
+pub struct SearchRepository<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> SearchRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Replace every document of an organization
+    pub async fn replace_organization(
+        &self,
+        organization_id: Uuid,
+        documents: &[SearchDocument],
+    ) -> Result<()> {
+        let mut tx = begin_write(self.pool)
+            .await
+            .context("Failed to begin search index transaction")?;
+
+        sqlx::query("DELETE FROM search_index WHERE organization_id = ?")
+            .bind(organization_id.to_string())
+            .execute(&mut *tx)
+            .await
+            .context("Failed to clear search index")?;
+
+        for document in documents {
+            sqlx::query(
+                r#"
+                INSERT INTO search_index (organization_id, kind, ref_id, owner_id, title, body)
+                VALUES (?, ?, ?, ?, ?, ?)
+                "#,
+            )
+            .bind(organization_id.to_string())
+            .bind(document.kind.as_str())
+            .bind(&document.ref_id)
+            .bind(document.owner_id.map(|id| id.to_string()))
+            .bind(&document.title)
+            .bind(&document.body)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to insert search document")?;
+        }
+
+        tx.commit()
+            .await
+            .context("Failed to commit search index transaction")?;
+        Ok(())
+    }
+
+    /// Search an organization's documents of the given kinds, best match first
+    ///
+    /// Private documents are only returned to their owner.
+    pub async fn search(
+        &self,
+        organization_id: Uuid,
+        query: &str,
+        kinds: &[SearchKind],
+        user_id: Uuid,
+        limit: u32,
+    ) -> Result<Vec<SearchResult>> {
+        let Some(expression) = match_expression(query) else {
+            return Ok(Vec::new());
+        };
+        if kinds.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Titles weigh ten times as much as bodies; the unindexed columns
+        // come first and carry no weight.
+        let placeholders = vec!["?"; kinds.len()].join(", ");
+        let sql = format!(
+            r#"
+            SELECT kind, ref_id, title,
+                   snippet(search_index, -1, '**', '**', '...', 12) AS snippet,
+                   bm25(search_index, 0.0, 0.0, 0.0, 0.0, 10.0, 1.0) AS rank
+            FROM search_index
+            WHERE search_index MATCH ?
+              AND organization_id = ?
+              AND kind IN ({})
+              AND (owner_id IS NULL OR owner_id = ?)
+            ORDER BY rank
+            LIMIT ?
+            "#,
+            placeholders
+        );
+
+        let mut q = sqlx::query_as::<_, SearchRow>(sqlx::AssertSqlSafe(sql.as_str()))
+            .bind(expression)
+            .bind(organization_id.to_string());
+        for kind in kinds {
+            q = q.bind(kind.as_str());
+        }
+        let rows = q
+            .bind(user_id.to_string())
+            .bind(limit as i64)
+            .fetch_all(self.pool)
+            .await
+            .context("Failed to search index")?;
+
+        rows.into_iter()
+            .map(|row| {
+                Ok(SearchResult {
+                    kind: row.kind.parse().map_err(anyhow::Error::msg)?,
+                    id: row.ref_id,
+                    title: row.title,
+                    snippet: row.snippet.filter(|s| !s.is_empty()),
+                    rank: row.rank,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Turn free text into an FTS5 query matching every term as a prefix
+///
+/// Each whitespace-separated term becomes a quoted phrase so that FTS5
+/// operators and punctuation in user input are taken literally. Returns
+/// `None` when nothing searchable is left.
+pub fn match_expression(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| term.replace('"', ""))
+        .filter(|term| term.chars().any(char::is_alphanumeric))
+        .map(|term| format!("\"{}\"*", term))
+        .collect();
+
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_expression_quotes_terms_as_prefixes() {
+        assert_eq!(
+            match_expression("web01.example ntp").as_deref(),
+            Some("\"web01.example\"* \"ntp\"*")
+        );
+    }
+
+    #[test]
+    fn test_match_expression_neutralizes_operators() {
+        assert_eq!(
+            match_expression("apache OR \"nginx").as_deref(),
+            Some("\"apache\"* \"OR\"* \"nginx\"*")
+        );
+        assert_eq!(match_expression("  * - \"\" "), None);
+    }
+}
@@ -0,0 +1,128 @@
+//! Login session repository
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::AuthSession;
+
+#[derive(Debug, sqlx::FromRow)]
+struct AuthSessionRow {
+    id: String,
+    user_id: String,
+    ip_address: Option<String>,
+    user_agent: Option<String>,
+    created_at: String,
+    last_activity_at: String,
+    expires_at: String,
+}
+
+pub struct AuthSessionRepository<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> AuthSessionRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// Sessions of a user that are neither revoked, expired nor idle since
+    /// before `idle_cutoff`, most recently used first
+    pub async fn list_active_for_user(
+        &self,
+        user_id: Uuid,
+        idle_cutoff: DateTime<Utc>,
+        now: DateTime<Utc>,
+    ) -> Result<Vec<AuthSession>> {
+        let rows = sqlx::query_as::<_, AuthSessionRow>(
+            r#"
+            SELECT id, user_id, ip_address, user_agent, created_at, last_activity_at, expires_at
+            FROM auth_sessions
+            WHERE user_id = ? AND revoked_at IS NULL AND expires_at > ? AND last_activity_at > ?
+            ORDER BY last_activity_at DESC
+            "#,
+        )
+        .bind(user_id.to_string())
+        .bind(now.to_rfc3339())
+        .bind(idle_cutoff.to_rfc3339())
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to list auth sessions")?;
+
+        rows.into_iter().map(row_to_session).collect()
+    }
+
+    pub async fn get_by_id(&self, id: Uuid) -> Result<Option<AuthSession>> {
+        let row = sqlx::query_as::<_, AuthSessionRow>(
+            r#"
+            SELECT id, user_id, ip_address, user_agent, created_at, last_activity_at, expires_at
+            FROM auth_sessions
+            WHERE id = ?
+            "#,
+        )
+        .bind(id.to_string())
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to get auth session")?;
+
+        row.map(row_to_session).transpose()
+    }
+
+    /// Revoke one session; returns false when it was already revoked
+    pub async fn revoke(&self, id: Uuid) -> Result<bool> {
+        let now = Utc::now().to_rfc3339();
+        let result = sqlx::query(
+            "UPDATE auth_sessions SET revoked_at = ?1, updated_at = ?1 WHERE id = ?2 AND revoked_at IS NULL",
+        )
+        .bind(&now)
+        .bind(id.to_string())
+        .execute(self.pool)
+        .await
+        .context("Failed to revoke auth session")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Revoke every open session of a user, optionally keeping one
+    pub async fn revoke_all_for_user(&self, user_id: Uuid, keep: Option<Uuid>) -> Result<u64> {
+        let now = Utc::now().to_rfc3339();
+        let result = sqlx::query(
+            r#"
+            UPDATE auth_sessions SET revoked_at = ?1, updated_at = ?1
+            WHERE user_id = ?2 AND revoked_at IS NULL AND (?3 IS NULL OR id != ?3)
+            "#,
+        )
+        .bind(&now)
+        .bind(user_id.to_string())
+        .bind(keep.map(|id| id.to_string()))
+        .execute(self.pool)
+        .await
+        .context("Failed to revoke auth sessions")?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+fn row_to_session(row: AuthSessionRow) -> Result<AuthSession> {
+    Ok(AuthSession {
+        id: Uuid::parse_str(&row.id).context("Invalid auth session id")?,
+        user_id: Uuid::parse_str(&row.user_id).context("Invalid user id")?,
+        ip_address: row.ip_address,
+        user_agent: row.user_agent,
+        created_at: parse_db_timestamp(&row.created_at),
+        last_activity_at: parse_db_timestamp(&row.last_activity_at),
+        expires_at: parse_db_timestamp(&row.expires_at),
+        current: false,
+    })
+}
+
+fn parse_db_timestamp(ts: &str) -> DateTime<Utc> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(ts) {
+        return dt.with_timezone(&Utc);
+    }
+    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S") {
+        return DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc);
+    }
+    Utc::now()
+}
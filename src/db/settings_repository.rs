@@ -1,20 +1,27 @@
 //! Settings repository - database operations for settings
+//!
+//! Values of sensitive keys are encrypted before storage and decrypted on read
+//! (see [`crate::services::settings_encryption`]).
 
 use crate::models::{
     Setting, SmtpSettings, UpdateJobSettings, UpdateSmtpSettingsRequest,
     UpdateUpdateJobSettingsRequest, DEFAULT_UPDATE_JOB_MAX_RUNTIME_MINUTES,
 };
+use crate::services::settings_encryption::{is_encrypted, is_sensitive_setting, SettingsCipher};
 use crate::utils::AppError;
 use chrono::Utc;
 use sqlx::{Pool, Sqlite};
 
 pub struct SettingsRepository {
     pool: Pool<Sqlite>,
+    cipher: Option<SettingsCipher>,
 }
 
 impl SettingsRepository {
-    pub fn new(pool: Pool<Sqlite>) -> Self {
-        Self { pool }
+    /// `cipher` encrypts sensitive values; without one they are stored as
+    /// plaintext and encrypted values cannot be read
+    pub fn new(pool: Pool<Sqlite>, cipher: Option<SettingsCipher>) -> Self {
+        Self { pool, cipher }
     }
 
     /// Get a setting by key
@@ -30,7 +37,7 @@ impl SettingsRepository {
         .fetch_optional(&self.pool)
         .await?;
 
-        Ok(setting)
+        setting.map(|s| self.decrypt_setting(s)).transpose()
     }
 
     /// Get all settings with a key prefix
@@ -48,7 +55,10 @@ impl SettingsRepository {
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(settings)
+        settings
+            .into_iter()
+            .map(|s| self.decrypt_setting(s))
+            .collect()
     }
 
     /// Set or update a setting
@@ -59,6 +69,7 @@ impl SettingsRepository {
         description: Option<&str>,
     ) -> Result<(), AppError> {
         let now = Utc::now().to_rfc3339();
+        let value = self.encrypt_value(key, value)?;
 
         sqlx::query(
             r#"
@@ -71,7 +82,7 @@ impl SettingsRepository {
             "#,
         )
         .bind(key)
-        .bind(value)
+        .bind(&value)
         .bind(description)
         .bind(&now)
         .bind(&now)
@@ -81,6 +92,34 @@ impl SettingsRepository {
         Ok(())
     }
 
+    /// Encrypt sensitive values still stored as plaintext
+    ///
+    /// Returns the number of rows rewritten. Does nothing without a cipher.
+    pub async fn encrypt_plaintext_settings(&self) -> Result<usize, AppError> {
+        if self.cipher.is_none() {
+            return Ok(0);
+        }
+
+        let rows: Vec<(String, String)> = sqlx::query_as("SELECT key, value FROM settings")
+            .fetch_all(&self.pool)
+            .await?;
+
+        let mut count = 0;
+        for (key, value) in rows {
+            if !is_sensitive_setting(&key) || value.is_empty() || is_encrypted(&value) {
+                continue;
+            }
+            sqlx::query("UPDATE settings SET value = ? WHERE key = ?")
+                .bind(self.encrypt_value(&key, &value)?)
+                .bind(&key)
+                .execute(&self.pool)
+                .await?;
+            count += 1;
+        }
+
+        Ok(count)
+    }
+
     /// Get SMTP settings
     pub async fn get_smtp_settings(&self) -> Result<SmtpSettings, AppError> {
         let settings = self.get_settings_by_prefix("smtp.").await?;
@@ -144,4 +183,28 @@ impl SettingsRepository {
             max_runtime_minutes,
         })
     }
+
+    /// Encrypt a value for storage if its key is sensitive
+    fn encrypt_value(&self, key: &str, value: &str) -> Result<String, AppError> {
+        match &self.cipher {
+            Some(cipher) if is_sensitive_setting(key) && !value.is_empty() => {
+                Ok(cipher.encrypt(value)?)
+            }
+            _ => Ok(value.to_string()),
+        }
+    }
+
+    /// Decrypt a stored setting
+    fn decrypt_setting(&self, mut setting: Setting) -> Result<Setting, AppError> {
+        if is_encrypted(&setting.value) {
+            let cipher = self.cipher.as_ref().ok_or_else(|| {
+                AppError::internal(format!(
+                    "Setting '{}' is encrypted but no settings master key is loaded",
+                    setting.key
+                ))
+            })?;
+            setting.value = cipher.decrypt(&setting.value)?;
+        }
+        Ok(setting)
+    }
 }
@@ -0,0 +1,189 @@
+//! Smart list repository
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::{CreateSmartListRequest, SmartList, SmartListFilter, UpdateSmartListRequest};
+
+#[derive(Debug, sqlx::FromRow)]
+struct SmartListRow {
+    id: String,
+    organization_id: String,
+    name: String,
+    description: Option<String>,
+    filter: String,
+    is_shared: i32,
+    created_by: String,
+    created_at: String,
+    updated_at: String,
+}
+
+pub struct SmartListRepository<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> SmartListRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// List the smart lists visible to a user: their own lists plus any list
+    /// shared within the organization
+    pub async fn list_visible(
+        &self,
+        organization_id: Uuid,
+        user_id: Uuid,
+    ) -> Result<Vec<SmartList>> {
+        let rows = sqlx::query_as::<_, SmartListRow>(
+            r#"
+            SELECT id, organization_id, name, description, filter, is_shared,
+                   created_by, created_at, updated_at
+            FROM smart_lists
+            WHERE organization_id = ? AND (created_by = ? OR is_shared = 1)
+            ORDER BY name ASC
+            "#,
+        )
+        .bind(organization_id.to_string())
+        .bind(user_id.to_string())
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to list smart lists")?;
+
+        rows.into_iter().map(row_to_smart_list).collect()
+    }
+
+    pub async fn get_by_id(&self, organization_id: Uuid, id: Uuid) -> Result<Option<SmartList>> {
+        let row = sqlx::query_as::<_, SmartListRow>(
+            r#"
+            SELECT id, organization_id, name, description, filter, is_shared,
+                   created_by, created_at, updated_at
+            FROM smart_lists
+            WHERE organization_id = ? AND id = ?
+            "#,
+        )
+        .bind(organization_id.to_string())
+        .bind(id.to_string())
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to get smart list")?;
+
+        row.map(row_to_smart_list).transpose()
+    }
+
+    pub async fn create(
+        &self,
+        organization_id: Uuid,
+        created_by: Uuid,
+        req: &CreateSmartListRequest,
+    ) -> Result<SmartList> {
+        let id = Uuid::new_v4();
+        let now = Utc::now().to_rfc3339();
+        let filter =
+            serde_json::to_string(&req.filter).context("Failed to serialize smart list filter")?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO smart_lists (
+                id, organization_id, name, description, filter, is_shared,
+                created_by, created_at, updated_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(id.to_string())
+        .bind(organization_id.to_string())
+        .bind(&req.name)
+        .bind(&req.description)
+        .bind(filter)
+        .bind(req.is_shared as i32)
+        .bind(created_by.to_string())
+        .bind(&now)
+        .bind(&now)
+        .execute(self.pool)
+        .await
+        .context("Failed to create smart list")?;
+
+        self.get_by_id(organization_id, id)
+            .await?
+            .context("Failed to retrieve created smart list")
+    }
+
+    pub async fn update(
+        &self,
+        organization_id: Uuid,
+        id: Uuid,
+        req: &UpdateSmartListRequest,
+    ) -> Result<Option<SmartList>> {
+        let Some(existing) = self.get_by_id(organization_id, id).await? else {
+            return Ok(None);
+        };
+
+        let name = req.name.clone().unwrap_or(existing.name);
+        let description = req.description.clone().unwrap_or(existing.description);
+        let filter = req.filter.clone().unwrap_or(existing.filter);
+        let is_shared = req.is_shared.unwrap_or(existing.is_shared);
+        let filter =
+            serde_json::to_string(&filter).context("Failed to serialize smart list filter")?;
+
+        sqlx::query(
+            r#"
+            UPDATE smart_lists
+            SET name = ?, description = ?, filter = ?, is_shared = ?, updated_at = ?
+            WHERE organization_id = ? AND id = ?
+            "#,
+        )
+        .bind(name)
+        .bind(description)
+        .bind(filter)
+        .bind(is_shared as i32)
+        .bind(Utc::now().to_rfc3339())
+        .bind(organization_id.to_string())
+        .bind(id.to_string())
+        .execute(self.pool)
+        .await
+        .context("Failed to update smart list")?;
+
+        self.get_by_id(organization_id, id).await
+    }
+
+    pub async fn delete(&self, organization_id: Uuid, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM smart_lists WHERE organization_id = ? AND id = ?")
+            .bind(organization_id.to_string())
+            .bind(id.to_string())
+            .execute(self.pool)
+            .await
+            .context("Failed to delete smart list")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+fn row_to_smart_list(row: SmartListRow) -> Result<SmartList> {
+    let filter: SmartListFilter =
+        serde_json::from_str(&row.filter).context("Invalid smart list filter")?;
+
+    Ok(SmartList {
+        id: Uuid::parse_str(&row.id).context("Invalid smart list id")?,
+        organization_id: Uuid::parse_str(&row.organization_id)
+            .context("Invalid organization id")?,
+        name: row.name,
+        description: row.description,
+        filter,
+        is_shared: row.is_shared != 0,
+        created_by: Uuid::parse_str(&row.created_by).context("Invalid user id")?,
+        created_at: parse_db_timestamp(&row.created_at),
+        updated_at: parse_db_timestamp(&row.updated_at),
+    })
+}
+
+fn parse_db_timestamp(ts: &str) -> DateTime<Utc> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(ts) {
+        return dt.with_timezone(&Utc);
+    }
+    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S") {
+        return DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc);
+    }
+    Utc::now()
+}
@@ -0,0 +1,73 @@
+//! Per-user preferences repository
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::config::DashboardPreferences;
+
+pub struct UserPreferencesRepository<'a> {
+    pool: &'a SqlitePool,
+}
+
+impl<'a> UserPreferencesRepository<'a> {
+    pub fn new(pool: &'a SqlitePool) -> Self {
+        Self { pool }
+    }
+
+    /// A user's dashboard preferences, if they saved any
+    pub async fn get_dashboard(&self, user_id: Uuid) -> Result<Option<DashboardPreferences>> {
+        let dashboard: Option<String> =
+            sqlx::query_scalar("SELECT dashboard FROM user_preferences WHERE user_id = ?")
+                .bind(user_id.to_string())
+                .fetch_optional(self.pool)
+                .await
+                .context("Failed to get dashboard preferences")?;
+
+        dashboard
+            .map(|json| {
+                serde_json::from_str(&json).context("Failed to parse dashboard preferences")
+            })
+            .transpose()
+    }
+
+    /// Store a user's dashboard preferences, replacing earlier ones
+    pub async fn set_dashboard(
+        &self,
+        user_id: Uuid,
+        preferences: &DashboardPreferences,
+    ) -> Result<()> {
+        let dashboard = serde_json::to_string(preferences)
+            .context("Failed to serialize dashboard preferences")?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO user_preferences (user_id, dashboard, updated_at)
+            VALUES (?, ?, ?)
+            ON CONFLICT(user_id) DO UPDATE SET
+                dashboard = excluded.dashboard,
+                updated_at = excluded.updated_at
+            "#,
+        )
+        .bind(user_id.to_string())
+        .bind(dashboard)
+        .bind(Utc::now().to_rfc3339())
+        .execute(self.pool)
+        .await
+        .context("Failed to save dashboard preferences")?;
+
+        Ok(())
+    }
+
+    /// Forget a user's dashboard preferences; returns whether any existed
+    pub async fn clear_dashboard(&self, user_id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM user_preferences WHERE user_id = ?")
+            .bind(user_id.to_string())
+            .execute(self.pool)
+            .await
+            .context("Failed to clear dashboard preferences")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,468 @@
+//! Webhook and webhook delivery repository
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::models::{
+    CreateWebhookRequest, UpdateWebhookRequest, Webhook, WebhookDelivery, WebhookDeliveryStatus,
+    WebhookEvent,
+};
+use crate::services::settings_encryption::{self, SettingsCipher};
+
+const WEBHOOK_COLUMNS: &str = r#"
+    id, organization_id, name, url, secret, events, enabled, created_by, created_at, updated_at
+"#;
+
+const DELIVERY_COLUMNS: &str = r#"
+    id, webhook_id, event, payload, status, attempts, response_status, response_body, error,
+    next_attempt_at, created_at, delivered_at
+"#;
+
+#[derive(Debug, sqlx::FromRow)]
+struct WebhookRow {
+    id: String,
+    organization_id: String,
+    name: String,
+    url: String,
+    secret: String,
+    events: String,
+    enabled: i32,
+    created_by: Option<String>,
+    created_at: String,
+    updated_at: String,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct WebhookDeliveryRow {
+    id: String,
+    webhook_id: String,
+    event: String,
+    payload: String,
+    status: String,
+    attempts: i64,
+    response_status: Option<i64>,
+    response_body: Option<String>,
+    error: Option<String>,
+    next_attempt_at: Option<String>,
+    created_at: String,
+    delivered_at: Option<String>,
+}
+
+/// Outcome of a delivery attempt
+#[derive(Debug, Clone, Default)]
+pub struct DeliveryAttempt {
+    pub response_status: Option<u16>,
+    pub response_body: Option<String>,
+    pub error: Option<String>,
+}
+
+pub struct WebhookRepository<'a> {
+    pool: &'a SqlitePool,
+    cipher: Option<&'a SettingsCipher>,
+}
+
+impl<'a> WebhookRepository<'a> {
+    /// `cipher` encrypts the signing secrets; without one they are stored as
+    /// plaintext
+    pub fn new(pool: &'a SqlitePool, cipher: Option<&'a SettingsCipher>) -> Self {
+        Self { pool, cipher }
+    }
+
+    /// Webhooks of an organization, by name
+    pub async fn list(&self, organization_id: Uuid) -> Result<Vec<Webhook>> {
+        let rows = sqlx::query_as::<_, WebhookRow>(sqlx::AssertSqlSafe(format!(
+            "SELECT {} FROM webhooks WHERE organization_id = ? ORDER BY name ASC",
+            WEBHOOK_COLUMNS
+        )))
+        .bind(organization_id.to_string())
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to list webhooks")?;
+
+        rows.into_iter()
+            .map(|row| row_to_webhook(row, self.cipher))
+            .collect()
+    }
+
+    pub async fn get_by_id(&self, organization_id: Uuid, id: Uuid) -> Result<Option<Webhook>> {
+        let row = sqlx::query_as::<_, WebhookRow>(sqlx::AssertSqlSafe(format!(
+            "SELECT {} FROM webhooks WHERE organization_id = ? AND id = ?",
+            WEBHOOK_COLUMNS
+        )))
+        .bind(organization_id.to_string())
+        .bind(id.to_string())
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to get webhook")?;
+
+        row.map(|row| row_to_webhook(row, self.cipher)).transpose()
+    }
+
+    /// Look up a webhook regardless of organization, for the delivery worker
+    pub async fn find_by_id(&self, id: Uuid) -> Result<Option<Webhook>> {
+        let row = sqlx::query_as::<_, WebhookRow>(sqlx::AssertSqlSafe(format!(
+            "SELECT {} FROM webhooks WHERE id = ?",
+            WEBHOOK_COLUMNS
+        )))
+        .bind(id.to_string())
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to get webhook")?;
+
+        row.map(|row| row_to_webhook(row, self.cipher)).transpose()
+    }
+
+    /// Enabled webhooks of an organization, or of every organization
+    pub async fn list_enabled(&self, organization_id: Option<Uuid>) -> Result<Vec<Webhook>> {
+        let rows = match organization_id {
+            Some(org_id) => {
+                sqlx::query_as::<_, WebhookRow>(sqlx::AssertSqlSafe(format!(
+                    "SELECT {} FROM webhooks WHERE enabled = 1 AND organization_id = ?",
+                    WEBHOOK_COLUMNS
+                )))
+                .bind(org_id.to_string())
+                .fetch_all(self.pool)
+                .await
+            }
+            None => {
+                sqlx::query_as::<_, WebhookRow>(sqlx::AssertSqlSafe(format!(
+                    "SELECT {} FROM webhooks WHERE enabled = 1",
+                    WEBHOOK_COLUMNS
+                )))
+                .fetch_all(self.pool)
+                .await
+            }
+        }
+        .context("Failed to list enabled webhooks")?;
+
+        rows.into_iter()
+            .map(|row| row_to_webhook(row, self.cipher))
+            .collect()
+    }
+
+    pub async fn create(
+        &self,
+        organization_id: Uuid,
+        created_by: Option<Uuid>,
+        req: &CreateWebhookRequest,
+        secret: &str,
+    ) -> Result<Webhook> {
+        let id = Uuid::new_v4();
+        let now = Utc::now().to_rfc3339();
+        let events =
+            serde_json::to_string(&req.events).context("Failed to serialize webhook events")?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO webhooks (
+                id, organization_id, name, url, secret, events, enabled,
+                created_by, created_at, updated_at
+            )
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+        )
+        .bind(id.to_string())
+        .bind(organization_id.to_string())
+        .bind(&req.name)
+        .bind(&req.url)
+        .bind(encode_secret(secret, self.cipher)?)
+        .bind(events)
+        .bind(req.enabled as i32)
+        .bind(created_by.map(|u| u.to_string()))
+        .bind(&now)
+        .bind(&now)
+        .execute(self.pool)
+        .await
+        .context("Failed to create webhook")?;
+
+        self.get_by_id(organization_id, id)
+            .await?
+            .context("Failed to retrieve created webhook")
+    }
+
+    pub async fn update(
+        &self,
+        organization_id: Uuid,
+        id: Uuid,
+        req: &UpdateWebhookRequest,
+    ) -> Result<Option<Webhook>> {
+        let Some(existing) = self.get_by_id(organization_id, id).await? else {
+            return Ok(None);
+        };
+
+        let name = req.name.clone().unwrap_or(existing.name);
+        let url = req.url.clone().unwrap_or(existing.url);
+        let secret = req.secret.clone().unwrap_or(existing.secret);
+        let events = req.events.clone().unwrap_or(existing.events);
+        let enabled = req.enabled.unwrap_or(existing.enabled);
+        let events =
+            serde_json::to_string(&events).context("Failed to serialize webhook events")?;
+
+        sqlx::query(
+            r#"
+            UPDATE webhooks
+            SET name = ?, url = ?, secret = ?, events = ?, enabled = ?, updated_at = ?
+            WHERE organization_id = ? AND id = ?
+            "#,
+        )
+        .bind(name)
+        .bind(url)
+        .bind(encode_secret(&secret, self.cipher)?)
+        .bind(events)
+        .bind(enabled as i32)
+        .bind(Utc::now().to_rfc3339())
+        .bind(organization_id.to_string())
+        .bind(id.to_string())
+        .execute(self.pool)
+        .await
+        .context("Failed to update webhook")?;
+
+        self.get_by_id(organization_id, id).await
+    }
+
+    pub async fn delete(&self, organization_id: Uuid, id: Uuid) -> Result<bool> {
+        let result = sqlx::query("DELETE FROM webhooks WHERE organization_id = ? AND id = ?")
+            .bind(organization_id.to_string())
+            .bind(id.to_string())
+            .execute(self.pool)
+            .await
+            .context("Failed to delete webhook")?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    // ==================== Deliveries ====================
+
+    /// Queue a delivery for its first attempt
+    pub async fn create_delivery(
+        &self,
+        webhook_id: Uuid,
+        event: WebhookEvent,
+        payload: &serde_json::Value,
+    ) -> Result<WebhookDelivery> {
+        let id = Uuid::new_v4();
+        let now = Utc::now().to_rfc3339();
+
+        sqlx::query(
+            r#"
+            INSERT INTO webhook_deliveries (
+                id, webhook_id, event, payload, status, attempts, next_attempt_at, created_at
+            )
+            VALUES (?, ?, ?, ?, 'pending', 0, ?, ?)
+            "#,
+        )
+        .bind(id.to_string())
+        .bind(webhook_id.to_string())
+        .bind(event.as_str())
+        .bind(payload.to_string())
+        .bind(&now)
+        .bind(&now)
+        .execute(self.pool)
+        .await
+        .context("Failed to create webhook delivery")?;
+
+        self.get_delivery(webhook_id, id)
+            .await?
+            .context("Failed to retrieve created webhook delivery")
+    }
+
+    /// Deliveries of a webhook, newest first
+    pub async fn list_deliveries(
+        &self,
+        webhook_id: Uuid,
+        status: Option<WebhookDeliveryStatus>,
+        limit: u32,
+    ) -> Result<Vec<WebhookDelivery>> {
+        let rows = sqlx::query_as::<_, WebhookDeliveryRow>(sqlx::AssertSqlSafe(format!(
+            r#"
+            SELECT {} FROM webhook_deliveries
+            WHERE webhook_id = ? AND (? IS NULL OR status = ?)
+            ORDER BY created_at DESC
+            LIMIT ?
+            "#,
+            DELIVERY_COLUMNS
+        )))
+        .bind(webhook_id.to_string())
+        .bind(status.map(|s| s.as_str()))
+        .bind(status.map(|s| s.as_str()))
+        .bind(limit as i64)
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to list webhook deliveries")?;
+
+        rows.into_iter().map(row_to_delivery).collect()
+    }
+
+    pub async fn get_delivery(&self, webhook_id: Uuid, id: Uuid) -> Result<Option<WebhookDelivery>> {
+        let row = sqlx::query_as::<_, WebhookDeliveryRow>(sqlx::AssertSqlSafe(format!(
+            "SELECT {} FROM webhook_deliveries WHERE webhook_id = ? AND id = ?",
+            DELIVERY_COLUMNS
+        )))
+        .bind(webhook_id.to_string())
+        .bind(id.to_string())
+        .fetch_optional(self.pool)
+        .await
+        .context("Failed to get webhook delivery")?;
+
+        row.map(row_to_delivery).transpose()
+    }
+
+    /// Pending and retrying deliveries whose next attempt is due, oldest first
+    pub async fn list_due_deliveries(
+        &self,
+        now: DateTime<Utc>,
+        limit: u32,
+    ) -> Result<Vec<WebhookDelivery>> {
+        let rows = sqlx::query_as::<_, WebhookDeliveryRow>(sqlx::AssertSqlSafe(format!(
+            r#"
+            SELECT {} FROM webhook_deliveries
+            WHERE status IN ('pending', 'retrying') AND next_attempt_at <= ?
+            ORDER BY next_attempt_at ASC
+            LIMIT ?
+            "#,
+            DELIVERY_COLUMNS
+        )))
+        .bind(now.to_rfc3339())
+        .bind(limit as i64)
+        .fetch_all(self.pool)
+        .await
+        .context("Failed to list due webhook deliveries")?;
+
+        rows.into_iter().map(row_to_delivery).collect()
+    }
+
+    /// Record an attempt
+    ///
+    /// `next_attempt_at` schedules a retry; without it the delivery ends as
+    /// `status`.
+    pub async fn record_attempt(
+        &self,
+        id: Uuid,
+        status: WebhookDeliveryStatus,
+        attempts: u32,
+        attempt: &DeliveryAttempt,
+        next_attempt_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        let now = Utc::now().to_rfc3339();
+        let delivered_at = (status == WebhookDeliveryStatus::Delivered).then_some(now);
+
+        sqlx::query(
+            r#"
+            UPDATE webhook_deliveries
+            SET status = ?, attempts = ?, response_status = ?, response_body = ?, error = ?,
+                next_attempt_at = ?, delivered_at = ?
+            WHERE id = ?
+            "#,
+        )
+        .bind(status.as_str())
+        .bind(attempts as i64)
+        .bind(attempt.response_status.map(|s| s as i64))
+        .bind(&attempt.response_body)
+        .bind(&attempt.error)
+        .bind(next_attempt_at.map(|t| t.to_rfc3339()))
+        .bind(delivered_at)
+        .bind(id.to_string())
+        .execute(self.pool)
+        .await
+        .context("Failed to record webhook delivery attempt")?;
+
+        Ok(())
+    }
+
+    /// Delete finished deliveries created before `cutoff`
+    pub async fn prune_deliveries(&self, cutoff: DateTime<Utc>) -> Result<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM webhook_deliveries
+            WHERE status IN ('delivered', 'failed') AND created_at < ?
+            "#,
+        )
+        .bind(cutoff.to_rfc3339())
+        .execute(self.pool)
+        .await
+        .context("Failed to prune webhook deliveries")?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// Encrypt a signing secret for storage when a settings master key is loaded
+fn encode_secret(secret: &str, cipher: Option<&SettingsCipher>) -> Result<String> {
+    match cipher {
+        Some(cipher) => cipher.encrypt(secret),
+        None => Ok(secret.to_string()),
+    }
+}
+
+fn decode_secret(
+    webhook_id: &str,
+    stored: String,
+    cipher: Option<&SettingsCipher>,
+) -> Result<String> {
+    if !settings_encryption::is_encrypted(&stored) {
+        return Ok(stored);
+    }
+    let cipher = cipher.with_context(|| {
+        format!(
+            "Secret of webhook {} is encrypted but no settings master key is loaded",
+            webhook_id
+        )
+    })?;
+    cipher
+        .decrypt(&stored)
+        .with_context(|| format!("Failed to decrypt secret of webhook {}", webhook_id))
+}
+
+fn row_to_webhook(row: WebhookRow, cipher: Option<&SettingsCipher>) -> Result<Webhook> {
+    let events: Vec<WebhookEvent> =
+        serde_json::from_str(&row.events).context("Invalid webhook events")?;
+    let secret = decode_secret(&row.id, row.secret, cipher)?;
+
+    Ok(Webhook {
+        id: Uuid::parse_str(&row.id).context("Invalid webhook id")?,
+        organization_id: Uuid::parse_str(&row.organization_id)
+            .context("Invalid organization id")?,
+        name: row.name,
+        url: row.url,
+        secret,
+        events,
+        enabled: row.enabled != 0,
+        created_by: row
+            .created_by
+            .as_deref()
+            .map(Uuid::parse_str)
+            .transpose()
+            .context("Invalid user id")?,
+        created_at: parse_db_timestamp(&row.created_at),
+        updated_at: parse_db_timestamp(&row.updated_at),
+    })
+}
+
+fn row_to_delivery(row: WebhookDeliveryRow) -> Result<WebhookDelivery> {
+    Ok(WebhookDelivery {
+        id: Uuid::parse_str(&row.id).context("Invalid delivery id")?,
+        webhook_id: Uuid::parse_str(&row.webhook_id).context("Invalid webhook id")?,
+        event: row.event.parse().map_err(anyhow::Error::msg)?,
+        payload: serde_json::from_str(&row.payload).context("Invalid delivery payload")?,
+        status: row.status.parse().map_err(anyhow::Error::msg)?,
+        attempts: row.attempts.max(0) as u32,
+        response_status: row.response_status.map(|s| s as u16),
+        response_body: row.response_body,
+        error: row.error,
+        next_attempt_at: row.next_attempt_at.as_deref().map(parse_db_timestamp),
+        created_at: parse_db_timestamp(&row.created_at),
+        delivered_at: row.delivered_at.as_deref().map(parse_db_timestamp),
+    })
+}
+
+fn parse_db_timestamp(ts: &str) -> DateTime<Utc> {
+    if let Ok(dt) = DateTime::parse_from_rfc3339(ts) {
+        return dt.with_timezone(&Utc);
+    }
+    if let Ok(dt) = chrono::NaiveDateTime::parse_from_str(ts, "%Y-%m-%d %H:%M:%S") {
+        return DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc);
+    }
+    Utc::now()
+}
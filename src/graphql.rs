@@ -0,0 +1,464 @@
+//! GraphQL read API
+//!
+//! Serves `POST /api/graphql` when built with the `graphql` feature and
+//! enabled under `server.graphql`. It exposes the read model behind the REST
+//! endpoints (nodes with their facts, reports and groups, and groups with
+//! their members) so a client can fetch the fields it needs in one request.
+//! Queries run as the authenticated user and see that user's organization.
+
+use std::sync::Arc;
+
+use async_graphql::{
+    ComplexObject, Context, EmptyMutation, EmptySubscription, Object, Schema, SimpleObject,
+};
+use axum::{routing::post, Extension, Json, Router};
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    api::groups::resolve_group_member_certnames,
+    config::GraphqlConfig,
+    db::repository::GroupRepository,
+    middleware::AuthUser,
+    models::{Fact, Node, NodeGroup, Report},
+    services::{
+        classification::{build_node_classification_facts, ClassificationService},
+        puppetdb::{PuppetDbClient, QueryBuilder, QueryParams},
+    },
+    utils::AppError,
+    AppState,
+};
+
+pub type ApiSchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+/// Default number of reports returned for a node
+const DEFAULT_REPORT_LIMIT: u32 = 10;
+
+pub fn build_schema(state: AppState, config: &GraphqlConfig) -> ApiSchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .data(state)
+        .limit_depth(config.max_depth)
+        .limit_complexity(config.max_complexity)
+        .finish()
+}
+
+pub fn routes(schema: ApiSchema) -> Router<AppState> {
+    Router::new()
+        .route("/api/graphql", post(graphql_handler))
+        .layer(Extension(schema))
+}
+
+/// Execute a GraphQL request
+///
+/// POST /api/graphql
+async fn graphql_handler(
+    Extension(schema): Extension<ApiSchema>,
+    auth_user: AuthUser,
+    Json(request): Json<async_graphql::Request>,
+) -> Json<async_graphql::Response> {
+    Json(schema.execute(request.data(auth_user)).await)
+}
+
+async fn puppetdb(ctx: &Context<'_>) -> async_graphql::Result<Arc<PuppetDbClient>> {
+    let state = ctx.data::<AppState>()?;
+    let auth_user = ctx.data::<AuthUser>()?;
+    Ok(state
+        .puppetdb_for(auth_user.organization_id)
+        .await
+        .ok_or_else(|| AppError::service_unavailable("PuppetDB is not configured"))?)
+}
+
+async fn groups(ctx: &Context<'_>) -> async_graphql::Result<Vec<NodeGroup>> {
+    let state = ctx.data::<AppState>()?;
+    let auth_user = ctx.data::<AuthUser>()?;
+    Ok(GroupRepository::new(&state.db)
+        .get_all(auth_user.organization_id)
+        .await
+        .map_err(|e| AppError::internal(format!("Failed to get groups: {}", e)))?)
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// Nodes known to PuppetDB
+    async fn nodes(
+        &self,
+        ctx: &Context<'_>,
+        environment: Option<String>,
+        status: Option<String>,
+        search: Option<String>,
+        limit: Option<u32>,
+        offset: Option<u32>,
+    ) -> async_graphql::Result<Vec<NodeObject>> {
+        let state = ctx.data::<AppState>()?;
+        let puppetdb = puppetdb(ctx).await?;
+
+        let mut qb = QueryBuilder::new();
+        if let Some(ref env) = environment {
+            qb = qb.equals("catalog_environment", env);
+        }
+        if let Some(ref status) = status {
+            qb = qb.equals("latest_report_status", status);
+        }
+        if let Some(ref search) = search {
+            qb = qb.matches("certname", search);
+        }
+        let mut params = QueryParams::new()
+            .limit(state.config.pagination.resolve_limit(limit))
+            .order_by("certname", true);
+        if let Some(offset) = offset {
+            params = params.offset(offset);
+        }
+
+        let nodes = puppetdb
+            .query_nodes_with_params(&qb, params)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to query nodes: {}", e)))?;
+        Ok(nodes.into_iter().map(NodeObject::from).collect())
+    }
+
+    /// A node by certname
+    async fn node(
+        &self,
+        ctx: &Context<'_>,
+        certname: String,
+    ) -> async_graphql::Result<Option<NodeObject>> {
+        let node = puppetdb(ctx)
+            .await?
+            .get_node(&certname)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to fetch node: {}", e)))?;
+        Ok(node.map(NodeObject::from))
+    }
+
+    /// Recent reports, newest first
+    async fn reports(
+        &self,
+        ctx: &Context<'_>,
+        certname: Option<String>,
+        status: Option<String>,
+        limit: Option<u32>,
+    ) -> async_graphql::Result<Vec<ReportObject>> {
+        let state = ctx.data::<AppState>()?;
+        let reports = puppetdb(ctx)
+            .await?
+            .query_reports(
+                certname.as_deref(),
+                status.as_deref(),
+                Some(state.config.pagination.resolve_limit(limit)),
+            )
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to fetch reports: {}", e)))?;
+        Ok(reports.into_iter().map(ReportObject::from).collect())
+    }
+
+    /// A report by hash
+    async fn report(
+        &self,
+        ctx: &Context<'_>,
+        hash: String,
+    ) -> async_graphql::Result<Option<ReportObject>> {
+        let report = puppetdb(ctx)
+            .await?
+            .get_report(&hash)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to fetch report: {}", e)))?;
+        Ok(report.map(ReportObject::from))
+    }
+
+    /// Node groups of the organization
+    async fn groups(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<GroupObject>> {
+        Ok(groups(ctx)
+            .await?
+            .into_iter()
+            .map(GroupObject::from)
+            .collect())
+    }
+
+    /// A node group by ID
+    async fn group(
+        &self,
+        ctx: &Context<'_>,
+        id: Uuid,
+    ) -> async_graphql::Result<Option<GroupObject>> {
+        let state = ctx.data::<AppState>()?;
+        let auth_user = ctx.data::<AuthUser>()?;
+        let group = GroupRepository::new(&state.db)
+            .get_by_id(auth_user.organization_id, id)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to get group: {}", e)))?;
+        Ok(group.map(GroupObject::from))
+    }
+}
+
+/// A node known to PuppetDB
+#[derive(SimpleObject)]
+#[graphql(name = "Node", complex)]
+pub struct NodeObject {
+    certname: String,
+    deactivated: Option<DateTime<Utc>>,
+    expired: Option<DateTime<Utc>>,
+    catalog_timestamp: Option<DateTime<Utc>>,
+    facts_timestamp: Option<DateTime<Utc>>,
+    report_timestamp: Option<DateTime<Utc>>,
+    catalog_environment: Option<String>,
+    facts_environment: Option<String>,
+    report_environment: Option<String>,
+    latest_report_status: Option<String>,
+    latest_report_corrective_change: Option<bool>,
+    cached_catalog_status: Option<String>,
+}
+
+impl From<Node> for NodeObject {
+    fn from(node: Node) -> Self {
+        Self {
+            certname: node.certname,
+            deactivated: node.deactivated,
+            expired: node.expired,
+            catalog_timestamp: node.catalog_timestamp,
+            facts_timestamp: node.facts_timestamp,
+            report_timestamp: node.report_timestamp,
+            catalog_environment: node.catalog_environment,
+            facts_environment: node.facts_environment,
+            report_environment: node.report_environment,
+            latest_report_status: node.latest_report_status,
+            latest_report_corrective_change: node.latest_report_corrective_change,
+            cached_catalog_status: node.cached_catalog_status,
+        }
+    }
+}
+
+#[ComplexObject]
+impl NodeObject {
+    /// Facts of the node, optionally only the named ones
+    async fn facts(
+        &self,
+        ctx: &Context<'_>,
+        names: Option<Vec<String>>,
+    ) -> async_graphql::Result<Vec<FactObject>> {
+        let facts = puppetdb(ctx)
+            .await?
+            .get_node_facts(&self.certname)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to fetch facts: {}", e)))?;
+        Ok(facts
+            .into_iter()
+            .filter(|fact| {
+                names
+                    .as_ref()
+                    .is_none_or(|names| names.contains(&fact.name))
+            })
+            .map(FactObject::from)
+            .collect())
+    }
+
+    /// Recent reports of the node, newest first
+    async fn reports(
+        &self,
+        ctx: &Context<'_>,
+        status: Option<String>,
+        limit: Option<u32>,
+    ) -> async_graphql::Result<Vec<ReportObject>> {
+        let reports = puppetdb(ctx)
+            .await?
+            .query_reports(
+                Some(&self.certname),
+                status.as_deref(),
+                Some(limit.unwrap_or(DEFAULT_REPORT_LIMIT)),
+            )
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to fetch reports: {}", e)))?;
+        Ok(reports.into_iter().map(ReportObject::from).collect())
+    }
+
+    /// Groups the node is classified into
+    async fn groups(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<GroupObject>> {
+        let state = ctx.data::<AppState>()?;
+        let facts = puppetdb(ctx)
+            .await?
+            .get_node_facts(&self.certname)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to fetch facts: {}", e)))?;
+        let facts_json = build_node_classification_facts(
+            &state.db,
+            facts,
+            &self.certname,
+            self.catalog_environment.as_deref(),
+        )
+        .await;
+
+        let all_groups = groups(ctx).await?;
+        let classification =
+            ClassificationService::new(all_groups.clone()).classify(&self.certname, &facts_json);
+        Ok(all_groups
+            .into_iter()
+            .filter(|group| classification.groups.iter().any(|m| m.id == group.id))
+            .map(GroupObject::from)
+            .collect())
+    }
+}
+
+/// A fact of a node
+#[derive(SimpleObject)]
+#[graphql(name = "Fact")]
+pub struct FactObject {
+    name: String,
+    value: async_graphql::Json<serde_json::Value>,
+    environment: Option<String>,
+}
+
+impl From<Fact> for FactObject {
+    fn from(fact: Fact) -> Self {
+        Self {
+            name: fact.name,
+            value: async_graphql::Json(fact.value),
+            environment: fact.environment,
+        }
+    }
+}
+
+/// A Puppet run report
+#[derive(SimpleObject)]
+#[graphql(name = "Report", complex)]
+pub struct ReportObject {
+    hash: String,
+    certname: String,
+    puppet_version: Option<String>,
+    configuration_version: Option<String>,
+    start_time: Option<DateTime<Utc>>,
+    end_time: Option<DateTime<Utc>>,
+    producer_timestamp: Option<DateTime<Utc>>,
+    producer: Option<String>,
+    transaction_uuid: Option<String>,
+    /// `changed`, `unchanged` or `failed`
+    status: Option<String>,
+    corrective_change: Option<bool>,
+    noop: Option<bool>,
+    environment: Option<String>,
+    code_id: Option<String>,
+    cached_catalog_status: Option<String>,
+}
+
+impl From<Report> for ReportObject {
+    fn from(report: Report) -> Self {
+        Self {
+            hash: report.hash,
+            certname: report.certname,
+            puppet_version: report.puppet_version,
+            configuration_version: report.configuration_version,
+            start_time: report.start_time,
+            end_time: report.end_time,
+            producer_timestamp: report.producer_timestamp,
+            producer: report.producer,
+            transaction_uuid: report.transaction_uuid,
+            status: report.status.map(|status| status.as_str().to_string()),
+            corrective_change: report.corrective_change,
+            noop: report.noop,
+            environment: report.environment,
+            code_id: report.code_id,
+            cached_catalog_status: report.cached_catalog_status,
+        }
+    }
+}
+
+#[ComplexObject]
+impl ReportObject {
+    /// The node that submitted the report
+    async fn node(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<NodeObject>> {
+        let node = puppetdb(ctx)
+            .await?
+            .get_node(&self.certname)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to fetch node: {}", e)))?;
+        Ok(node.map(NodeObject::from))
+    }
+}
+
+/// A node group
+#[derive(SimpleObject)]
+#[graphql(name = "Group", complex)]
+pub struct GroupObject {
+    id: Uuid,
+    name: String,
+    description: Option<String>,
+    parent_id: Option<Uuid>,
+    environment: Option<String>,
+    is_environment_group: bool,
+    match_all_nodes: bool,
+    classes: async_graphql::Json<serde_json::Value>,
+    variables: async_graphql::Json<serde_json::Value>,
+    pinned_nodes: Vec<String>,
+}
+
+impl From<NodeGroup> for GroupObject {
+    fn from(group: NodeGroup) -> Self {
+        Self {
+            id: group.id,
+            name: group.name,
+            description: group.description,
+            parent_id: group.parent_id,
+            environment: group.environment,
+            is_environment_group: group.is_environment_group,
+            match_all_nodes: group.match_all_nodes,
+            classes: async_graphql::Json(group.classes),
+            variables: async_graphql::Json(group.variables),
+            pinned_nodes: group.pinned_nodes,
+        }
+    }
+}
+
+#[ComplexObject]
+impl GroupObject {
+    /// Certnames of the pinned and rule-matched nodes
+    async fn certnames(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<String>> {
+        let state = ctx.data::<AppState>()?;
+        let auth_user = ctx.data::<AuthUser>()?;
+        Ok(resolve_group_member_certnames(state, auth_user.organization_id, self.id).await?)
+    }
+
+    /// Pinned and rule-matched nodes
+    async fn nodes(&self, ctx: &Context<'_>) -> async_graphql::Result<Vec<NodeObject>> {
+        let certnames = self.certnames(ctx).await?;
+        if certnames.is_empty() {
+            return Ok(Vec::new());
+        }
+        let certnames: Vec<&str> = certnames.iter().map(String::as_str).collect();
+        let nodes = puppetdb(ctx)
+            .await?
+            .query_nodes(&QueryBuilder::new().in_array("certname", &certnames))
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to query nodes: {}", e)))?;
+        Ok(nodes.into_iter().map(NodeObject::from).collect())
+    }
+
+    /// The parent group
+    async fn parent(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<GroupObject>> {
+        let Some(parent_id) = self.parent_id else {
+            return Ok(None);
+        };
+        let state = ctx.data::<AppState>()?;
+        let auth_user = ctx.data::<AuthUser>()?;
+        let group = GroupRepository::new(&state.db)
+            .get_by_id(auth_user.organization_id, parent_id)
+            .await
+            .map_err(|e| AppError::internal(format!("Failed to get group: {}", e)))?;
+        Ok(group.map(GroupObject::from))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_schema_exposes_read_model() {
+        let sdl = Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+            .finish()
+            .sdl();
+        assert!(sdl.contains("type Node"));
+        assert!(sdl.contains("facts(names: [String!]): [Fact!]!"));
+        assert!(sdl.contains("groups: [Group!]!"));
+        assert!(sdl.contains("latestReportStatus: String"));
+    }
+}
@@ -3,4 +3,4 @@
 //! This module contains the HTTP request handlers that process incoming requests
 //! and coordinate with services to produce responses.
 
-// Handler implementations will be added as features are developed
+pub mod spa;
@@ -0,0 +1,82 @@
+//! Single-page application entry point
+//!
+//! Serves `index.html` for the root and every client-side route. The file is
+//! rewritten on the fly so the frontend works under `server.base_path`: root
+//! relative asset URLs get the prefix and the prefix is exposed to the
+//! application (API client, router basename, lazily loaded chunks) through
+//! `window.__OPENVOX_BASE_PATH__`.
+
+use std::path::PathBuf;
+
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::{IntoResponse, Response},
+};
+
+/// Assignment emitted by the frontend build, replaced with the configured prefix
+pub const BASE_PATH_PLACEHOLDER: &str = "window.__OPENVOX_BASE_PATH__ = '';";
+
+/// State for [`serve_index`]
+#[derive(Debug, Clone)]
+pub struct SpaIndex {
+    pub index_file: PathBuf,
+    /// Normalized base path ("" or "/prefix")
+    pub base_path: String,
+}
+
+/// Rewrite `index.html` for the given base path
+pub fn rewrite_index_html(html: &str, base_path: &str) -> String {
+    if base_path.is_empty() {
+        return html.to_string();
+    }
+
+    html.replace(
+        BASE_PATH_PLACEHOLDER,
+        &format!("window.__OPENVOX_BASE_PATH__ = '{}';", base_path),
+    )
+    .replace("src=\"/", &format!("src=\"{}/", base_path))
+    .replace("href=\"/", &format!("href=\"{}/", base_path))
+}
+
+/// Serve the rewritten `index.html`
+pub async fn serve_index(State(spa): State<SpaIndex>) -> Response {
+    match tokio::fs::read_to_string(&spa.index_file).await {
+        Ok(html) => (
+            [(header::CONTENT_TYPE, "text/html; charset=utf-8")],
+            rewrite_index_html(&html, &spa.base_path),
+        )
+            .into_response(),
+        Err(e) => {
+            tracing::error!("Failed to read {:?}: {}", spa.index_file, e);
+            StatusCode::NOT_FOUND.into_response()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INDEX: &str = r#"<html><head>
+<script>window.__OPENVOX_BASE_PATH__ = '';</script>
+<link rel="icon" href="/vite.svg" />
+<script type="module" crossorigin src="/assets/index-BxYz12ab.js"></script>
+<link rel="stylesheet" href="/assets/index-Cd34efGh.css">
+</head></html>"#;
+
+    #[test]
+    fn test_rewrite_index_html_without_base_path() {
+        assert_eq!(rewrite_index_html(INDEX, ""), INDEX);
+    }
+
+    #[test]
+    fn test_rewrite_index_html_with_base_path() {
+        let html = rewrite_index_html(INDEX, "/puppet");
+
+        assert!(html.contains("window.__OPENVOX_BASE_PATH__ = '/puppet';"));
+        assert!(html.contains(r#"href="/puppet/vite.svg""#));
+        assert!(html.contains(r#"src="/puppet/assets/index-BxYz12ab.js""#));
+        assert!(html.contains(r#"href="/puppet/assets/index-Cd34efGh.css""#));
+    }
+}
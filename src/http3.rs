@@ -0,0 +1,166 @@
+//! HTTP/3 (QUIC) listener
+//!
+//! Serves the same router as the HTTPS listener over QUIC. Browsers learn
+//! about it from the `Alt-Svc` header on HTTPS responses and fall back to TCP
+//! on their own when UDP is blocked, so the HTTPS listener stays authoritative.
+//! Only compiled with the `http3` feature.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, State},
+    http::{header, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Router,
+};
+use bytes::{Buf, Bytes};
+use futures::StreamExt;
+use h3::server::RequestStream;
+use tower::ServiceExt;
+use tracing::{debug, info};
+
+/// Largest request body buffered from an HTTP/3 stream
+const MAX_REQUEST_BODY_BYTES: usize = 64 * 1024 * 1024;
+
+/// `Alt-Svc` value advertising an HTTP/3 listener on `port`
+pub fn alt_svc_value(port: u16, max_age_secs: u64) -> String {
+    format!("h3=\":{}\"; ma={}", port, max_age_secs)
+}
+
+/// Middleware that advertises the HTTP/3 listener on HTTPS responses
+pub async fn alt_svc_middleware(
+    State(value): State<HeaderValue>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let mut response = next.run(request).await;
+    response.headers_mut().insert(header::ALT_SVC, value);
+    response
+}
+
+/// Accept QUIC connections on `addr` and serve `app` over HTTP/3
+///
+/// `tls` is the HTTPS listener's rustls configuration; its ALPN protocols are
+/// replaced with `h3`. QUIC requires TLS 1.3, which is always enabled.
+pub async fn serve(addr: SocketAddr, mut tls: rustls::ServerConfig, app: Router) -> Result<()> {
+    tls.alpn_protocols = vec![b"h3".to_vec()];
+
+    let crypto = quinn::crypto::rustls::QuicServerConfig::try_from(tls)
+        .context("TLS configuration cannot be used for QUIC")?;
+    let endpoint =
+        quinn::Endpoint::server(quinn::ServerConfig::with_crypto(Arc::new(crypto)), addr)
+            .with_context(|| format!("Failed to bind HTTP/3 listener on udp/{}", addr))?;
+
+    info!(
+        "HTTP/3 server is ready to accept connections on udp/{}",
+        addr
+    );
+
+    while let Some(incoming) = endpoint.accept().await {
+        let app = app.clone();
+        tokio::spawn(async move {
+            let remote = incoming.remote_address();
+            match incoming.await {
+                Ok(conn) => {
+                    if let Err(e) = handle_connection(conn, remote, app).await {
+                        debug!("HTTP/3 connection from {} closed: {}", remote, e);
+                    }
+                }
+                Err(e) => debug!("QUIC handshake with {} failed: {}", remote, e),
+            }
+        });
+    }
+
+    Ok(())
+}
+
+async fn handle_connection(conn: quinn::Connection, remote: SocketAddr, app: Router) -> Result<()> {
+    let mut h3_conn = h3::server::Connection::new(h3_quinn::Connection::new(conn)).await?;
+
+    while let Some(resolver) = h3_conn.accept().await? {
+        let app = app.clone();
+        tokio::spawn(async move {
+            match resolver.resolve_request().await {
+                Ok((request, stream)) => {
+                    if let Err(e) = handle_request(request, stream, remote, app).await {
+                        debug!("HTTP/3 request from {} failed: {}", remote, e);
+                    }
+                }
+                Err(e) => debug!("Failed to read HTTP/3 request from {}: {}", remote, e),
+            }
+        });
+    }
+
+    Ok(())
+}
+
+async fn handle_request(
+    request: Request<()>,
+    mut stream: RequestStream<h3_quinn::BidiStream<Bytes>, Bytes>,
+    remote: SocketAddr,
+    app: Router,
+) -> Result<()> {
+    let mut body = Vec::new();
+    while let Some(mut chunk) = stream.recv_data().await? {
+        if body.len() + chunk.remaining() > MAX_REQUEST_BODY_BYTES {
+            return send_response(&mut stream, StatusCode::PAYLOAD_TOO_LARGE.into_response()).await;
+        }
+        while chunk.has_remaining() {
+            let part = chunk.chunk();
+            body.extend_from_slice(part);
+            let len = part.len();
+            chunk.advance(len);
+        }
+    }
+
+    let (mut parts, ()) = request.into_parts();
+
+    // HTTP/3 carries the host in the :authority pseudo-header only
+    if !parts.headers.contains_key(header::HOST) {
+        if let Some(host) = parts
+            .uri
+            .authority()
+            .and_then(|authority| HeaderValue::from_str(authority.as_str()).ok())
+        {
+            parts.headers.insert(header::HOST, host);
+        }
+    }
+
+    let mut request = Request::from_parts(parts, Body::from(body));
+    request.extensions_mut().insert(ConnectInfo(remote));
+
+    let response = app.oneshot(request).await?;
+    send_response(&mut stream, response).await
+}
+
+async fn send_response(
+    stream: &mut RequestStream<h3_quinn::BidiStream<Bytes>, Bytes>,
+    response: Response,
+) -> Result<()> {
+    let (parts, body) = response.into_parts();
+    stream
+        .send_response(Response::from_parts(parts, ()))
+        .await?;
+
+    let mut data = body.into_data_stream();
+    while let Some(chunk) = data.next().await {
+        stream.send_data(chunk?).await?;
+    }
+
+    stream.finish().await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_alt_svc_value() {
+        assert_eq!(alt_svc_value(5051, 86400), "h3=\":5051\"; ma=86400");
+    }
+}
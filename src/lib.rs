@@ -5,9 +5,14 @@
 use std::sync::Arc;
 
 pub mod api;
+pub mod cli;
 pub mod config;
 pub mod db;
+#[cfg(feature = "graphql")]
+pub mod graphql;
 pub mod handlers;
+#[cfg(feature = "http3")]
+pub mod http3;
 pub mod middleware;
 pub mod models;
 pub mod services;
@@ -18,18 +23,32 @@ use std::sync::atomic::{AtomicBool, Ordering};
 pub use config::AppConfig;
 use config::{BackupConfig, InventoryConfig};
 pub use db::DbPool;
-use db::InventoryRepository;
+use db::{AuditRepository, InventoryRepository, SettingsRepository};
+use middleware::payload_debug::PayloadDebugRecorder;
 pub use middleware::{
     auth_middleware, check_permission, optional_auth_middleware, require_permission_middleware,
     AuthUser, Claims, RbacError, RequirePermission,
 };
+use services::alerting::AlertingService;
+use services::audit_forwarding::AuditForwarder;
 use services::backup::BackupService;
 use services::code_deploy::{CodeDeployConfig, CodeDeployService};
+use services::config_reload::ConfigReloader;
+use services::health::HealthTracker;
+use services::jwt_keys::JwtKeys;
+use services::log_buffer::LogBuffer;
+use services::mailer::Mailer;
 use services::notification::NotificationService;
 use services::puppet_ca::PuppetCAService;
 use services::puppetdb::PuppetDbClient;
+use services::puppetdb_registry::PuppetDbRegistry;
+use services::secrets::SecretsResolver;
+use services::settings_encryption::SettingsCipher;
+use services::smart_list::MemberCounts;
+use services::webhooks::Webhooks;
 pub use services::{DbRbacService, RbacService};
 use utils::AppError;
+use uuid::Uuid;
 
 /// Application state shared across handlers
 #[derive(Clone)]
@@ -51,6 +70,8 @@ pub struct AppState {
     pub inventory_ready: Arc<AtomicBool>,
     /// PuppetDB client (optional)
     pub puppetdb: Option<Arc<PuppetDbClient>>,
+    /// PuppetDB clients of organizations mapped to their own PuppetDB
+    pub puppetdb_tenants: PuppetDbRegistry,
     /// Puppet CA client (optional)
     pub puppet_ca: Option<Arc<PuppetCAService>>,
     /// RBAC service for permission checking (in-memory, for middleware)
@@ -63,6 +84,27 @@ pub struct AppState {
     pub backup_config: Option<BackupConfig>,
     /// Notification service
     pub notification_service: Arc<NotificationService>,
+    /// Webhook event queue
+    pub webhooks: Webhooks,
+    /// Cipher of secret settings; `None` stores them in plaintext
+    pub settings_cipher: Option<SettingsCipher>,
+    /// Resolver of `secret:` references read after startup (optional)
+    pub secrets: Option<Arc<SecretsResolver>>,
+    /// Forwarder of audit events to external sinks (optional)
+    pub audit_forwarder: Option<Arc<AuditForwarder>>,
+    /// Keys that sign and verify tokens
+    pub jwt_keys: JwtKeys,
+    /// Recent log lines served by the logs API (optional)
+    pub log_buffer: Option<Arc<LogBuffer>>,
+    /// Last successful run of the background jobs
+    pub health: HealthTracker,
+    /// Cached smart list member counts
+    pub smart_list_counts: Arc<MemberCounts>,
+    /// Opt-in request/response capture for debugging integrations
+    pub payload_debug: PayloadDebugRecorder,
+    /// Running values of the settings that can be reloaded without a
+    /// restart; `config` keeps the values read at startup
+    pub config_reloader: ConfigReloader,
 }
 
 impl AppState {
@@ -81,7 +123,12 @@ impl AppState {
             ));
         }
 
-        Ok(CodeDeployService::new(self.db.clone(), config))
+        Ok(CodeDeployService::new(
+            self.db.clone(),
+            config,
+            self.notification_service.clone(),
+            self.webhooks.clone(),
+        ))
     }
 
     /// Get a Backup service instance
@@ -99,7 +146,38 @@ impl AppState {
             ));
         }
 
-        Ok(BackupService::new(self.db.clone(), config))
+        Ok(BackupService::new(self.db.clone(), config).with_secrets(self.secrets.clone()))
+    }
+
+    /// Get an Alerting service instance
+    pub fn alerting_service(&self) -> AlertingService {
+        AlertingService::new(
+            self.db.clone(),
+            self.puppetdb.clone(),
+            Some(self.notification_service.clone()),
+        )
+        .with_webhooks(self.webhooks.clone())
+        .with_settings_cipher(self.settings_cipher.clone())
+        .with_secrets(self.secrets.clone())
+    }
+
+    /// Audit log repository that forwards new events to the audit sinks
+    pub fn audit_repository(&self) -> AuditRepository<'_> {
+        AuditRepository::new(&self.db, self.audit_forwarder.as_deref())
+    }
+
+    /// Settings repository that encrypts and decrypts secret settings
+    pub fn settings_repository(&self) -> SettingsRepository {
+        SettingsRepository::new(self.db.clone(), self.settings_cipher.clone())
+    }
+
+    /// Mailer sending with the configured SMTP settings
+    pub fn mailer(&self) -> Mailer {
+        Mailer::new(
+            self.db.clone(),
+            self.settings_cipher.clone(),
+            self.secrets.clone(),
+        )
     }
 
     /// Construct an `InventoryRepository` bound to the dedicated inventory
@@ -112,6 +190,17 @@ impl AppState {
             .with_keep_raw_payload(self.inventory_config.keep_raw_payload)
     }
 
+    /// The PuppetDB client serving an organization
+    ///
+    /// Organizations without their own PuppetDB use the shared `puppetdb`
+    /// client. Org-scoped handlers should call this instead of reading
+    /// `puppetdb` directly.
+    pub async fn puppetdb_for(&self, organization_id: Uuid) -> Option<Arc<PuppetDbClient>> {
+        self.puppetdb_tenants
+            .resolve(&self.db, organization_id, self.puppetdb.clone())
+            .await
+    }
+
     /// Whether the startup inventory-migration has completed. Handlers that
     /// read or write inventory should return 503 when this is false.
     pub fn is_inventory_ready(&self) -> bool {
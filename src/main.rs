@@ -10,27 +10,42 @@ use std::net::SocketAddr;
 use std::sync::Arc;
 
 use anyhow::{Context, Result};
-use axum::Router;
+use axum::{extract::DefaultBodyLimit, handler::Handler, Router};
 use tower_http::{
     compression::CompressionLayer,
     cors::{Any, CorsLayer},
-    services::{ServeDir, ServeFile},
-    trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer},
+    services::ServeDir,
+    trace::{DefaultOnResponse, TraceLayer},
 };
 use tracing::{info, warn, Level};
 
 use config::LogFormat;
 use openvox_webui::{
-    api, config, db, middleware, services, AppConfig, AppState, DbRbacService, RbacService,
+    api, config, db, handlers, middleware, services, AppConfig, AppState, DbRbacService,
+    RbacService,
 };
+use services::config_reload::LogLevelSetter;
+use services::health::HealthTracker;
+use services::log_buffer::LogBuffer;
 use services::notification::NotificationService;
 use services::puppetdb::PuppetDbClient;
+use services::webhooks::Webhooks;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     // Parse command line arguments
     let args: Vec<String> = env::args().collect();
 
+    // Operational subcommands (admin, db, config) run and exit
+    if let Some(command) = openvox_webui::cli::Command::parse(&args[1..])? {
+        return openvox_webui::cli::run(command).await;
+    }
+
+    // Check for --check-config flag
+    if args.iter().any(|arg| arg == "--check-config") {
+        return openvox_webui::cli::run(openvox_webui::cli::Command::ConfigValidate).await;
+    }
+
     // Check for --fix-database flag
     if args.iter().any(|arg| arg == "--fix-database") {
         return fix_database().await;
@@ -54,25 +69,76 @@ async fn main() -> Result<()> {
     }
 
     // Load configuration first (before logging, so we know log format)
-    let config = AppConfig::load().context("Failed to load configuration")?;
+    let mut config = AppConfig::load().context("Failed to load configuration")?;
+    // Reloads compare against the file as read, before secrets are resolved
+    let file_config = config.clone();
+    let secrets = config
+        .resolve_secrets()
+        .await
+        .context("Failed to resolve configuration secrets")?;
 
     // Initialize logging based on configuration
     // The guard must be kept alive for the duration of the program
     // to ensure log messages are flushed to files
-    let _log_guard = init_logging(&config);
+    let Logging {
+        guard: _log_guard,
+        level_setter,
+        buffer: log_buffer,
+    } = init_logging(&config);
 
     info!("OpenVox WebUI starting up");
     info!("Configuration loaded successfully");
 
+    if let Some(resolver) = &secrets {
+        resolver.spawn_renewal();
+    }
+
     // Ensure data directory exists
     ensure_data_directory(&config)?;
 
+    // Swap in a database restored from a backup before it is opened
+    if let Some(db_path) = services::backup::extract_path_from_sqlite_url(&config.database.url) {
+        if services::backup::apply_staged_restore(&db_path)
+            .context("Failed to apply restored database")?
+        {
+            info!("Applied database restored from backup: {:?}", db_path);
+        }
+    }
+
     // Initialize database connection pool
     info!("Initializing database connection");
     let db = db::init_pool(&config.database)
         .await
         .context("Failed to initialize database")?;
 
+    // Load the settings master key before anything reads sensitive settings
+    let settings_cipher = openvox_webui::services::settings_encryption::init(&config, &db)
+        .await
+        .context("Failed to initialize settings encryption")?;
+
+    // Forward audit entries to syslog/SIEM sinks before anything is audited
+    let audit_forwarder =
+        openvox_webui::services::audit_forwarding::init(&config.audit, secrets.clone())
+            .context("Failed to initialize audit forwarding")?
+            .map(Arc::new);
+
+    // Load the JWT signing keys before any token is issued or verified
+    let jwt_keys = services::JwtKeys::new(Some(settings_cipher.clone()));
+    jwt_keys
+        .init(&db, &config.auth)
+        .await
+        .context("Failed to initialize JWT signing keys")?;
+
+    // Queue of webhook deliveries, sent by the dispatcher started below
+    let webhooks = Webhooks::new(db.clone(), Some(settings_cipher.clone()));
+
+    // Last successful run of the background jobs, reported by /health
+    let health = HealthTracker::default();
+
+    // Initialize notification service
+    info!("Initializing notification service");
+    let notification_service = Arc::new(NotificationService::new(db.clone()));
+
     // Initialize the dedicated inventory database pool. Inventory data
     // (Phase-10 snapshots, packages, applications, update jobs, repo
     // configs, …) lives here so high-write ingestion does not starve the
@@ -181,6 +247,7 @@ async fn main() -> Result<()> {
                 git: services::git::GitServiceConfig {
                     repos_base_dir: cd.repos_base_dir.clone(),
                     ssh_keys_dir: cd.ssh_keys_dir.clone(),
+                    fault_injection: cd.fault_injection.clone(),
                 },
                 r10k: services::r10k::R10kConfig {
                     binary_path: cd.r10k_binary_path.clone(),
@@ -193,7 +260,11 @@ async fn main() -> Result<()> {
                 enabled: true,
                 encryption_key: cd.encryption_key.clone(),
                 webhook_base_url: cd.webhook_base_url.clone(),
+                librarian_puppet_path: cd.librarian_puppet_binary_path.clone(),
                 retain_history_days: cd.retain_history_days,
+                max_concurrent_deployments: cd.max_concurrent_deployments,
+                pat_token_warning_days: cd.pat_token_warning_days,
+                ssh_key_max_age_days: cd.ssh_key_max_age_days,
             })
         } else {
             info!("Code Deploy feature is disabled");
@@ -201,12 +272,20 @@ async fn main() -> Result<()> {
         }
     });
 
+    // PuppetDB clients of organizations, shared by the handlers and the
+    // schedulers checking the nodes of an organization
+    let puppetdb_tenants = services::PuppetDbRegistry::default();
+
     // Start Code Deploy scheduler if enabled
     let _code_deploy_scheduler = if let Some(ref cd_config) = code_deploy_config {
         info!("Starting Code Deploy scheduler");
         Some(services::start_code_deploy_scheduler(
             db.clone(),
             cd_config.clone(),
+            puppetdb.clone(),
+            puppetdb_tenants.clone(),
+            notification_service.clone(),
+            webhooks.clone(),
         ))
     } else {
         None
@@ -232,6 +311,7 @@ async fn main() -> Result<()> {
         Some(services::start_backup_scheduler(
             db.clone(),
             backup_cfg.clone(),
+            secrets.clone(),
         ))
     } else {
         None
@@ -337,9 +417,62 @@ async fn main() -> Result<()> {
         None
     };
 
-    // Initialize notification service
-    info!("Initializing notification service");
-    let notification_service = Arc::new(NotificationService::new(db.clone()));
+    // Hourly fleet snapshots backing the Analytics 30/90-day trend charts
+    let _fleet_metrics_scheduler = if let Some(ref pdb) = puppetdb {
+        info!("Starting Fleet metrics scheduler");
+        Some(services::start_fleet_metrics_scheduler(
+            db.clone(),
+            pdb.clone(),
+        ))
+    } else {
+        None
+    };
+
+    // Keep an offline copy of the CA state for when the CA host is down
+    let _ca_snapshot_sync = match (&puppet_ca, &config.puppet_ca) {
+        (Some(ca), Some(ca_config)) if ca_config.snapshot_interval_secs > 0 => Some(
+            services::start_ca_snapshot_sync(
+                db.clone(),
+                ca.clone(),
+                ca_config.snapshot_interval_secs,
+                notification_service.clone(),
+                webhooks.clone(),
+            ),
+        ),
+        _ => None,
+    };
+
+    // Track certificate renewal campaigns against the CA
+    let _cert_renewal_tracker = puppet_ca.as_ref().map(|ca| {
+        services::start_cert_renewal_tracker(
+            db.clone(),
+            ca.clone(),
+            Some(notification_service.clone()),
+        )
+    });
+
+    // Smart list member counts, dropped by the report ingester when new
+    // reports arrive
+    let smart_list_counts = Arc::new(services::smart_list::MemberCounts::new(
+        std::time::Duration::from_secs(config.cache.smart_list_count_ttl_secs),
+    ));
+
+    // Follow new reports to refresh caches and alert within seconds of a run
+    let _report_ingester = match puppetdb {
+        Some(ref pdb) if config.report_ingestion.enabled => {
+            info!("Starting Report ingester");
+            Some(services::start_report_ingester(
+                db.clone(),
+                pdb.clone(),
+                Some(notification_service.clone()),
+                webhooks.clone(),
+                secrets.clone(),
+                smart_list_counts.clone(),
+                &config.report_ingestion,
+            ))
+        }
+        _ => None,
+    };
 
     // Initialize CVE vulnerability scheduler if enabled
     let _cve_scheduler = if let Some(ref cve_cfg) = config.cve {
@@ -370,7 +503,50 @@ async fn main() -> Result<()> {
         inventory_db.clone(),
         puppetdb.clone(),
         notification_service.clone(),
+        webhooks.clone(),
+        secrets.clone(),
+        health.clone(),
+    );
+
+    // Expire time-bound role elevations and audit their end
+    let _elevation_expiry =
+        services::start_elevation_expiry(db.clone(), rbac_db.clone(), audit_forwarder.clone());
+
+    // Prune (and optionally archive) audit entries past their retention
+    let _audit_retention =
+        services::start_audit_retention(db.clone(), &config.audit, audit_forwarder.clone());
+
+    // Rotate stored JWT signing keys and drop those past their grace window
+    let _jwt_key_rotation =
+        services::start_jwt_key_rotation(db.clone(), jwt_keys.clone(), &config.auth);
+
+    // Purge deleted groups, users and saved reports past their retention
+    let _recycle_bin_purge =
+        services::start_recycle_bin_purge(db.clone(), &config.recycle_bin, audit_forwarder.clone());
+
+    // Delete notifications past their retention
+    let _notification_retention =
+        services::start_notification_retention(notification_service.clone(), &config.notifications);
+
+    // Delete report executions and their offloaded outputs past their retention
+    let _report_retention = services::start_report_retention(db.clone(), &config.reporting);
+
+    // Apply the safe-to-change settings again when the config files change
+    let mut config_reloader = services::ConfigReloader::new(
+        file_config,
+        config.clone(),
+        db.clone(),
+        rbac_db.clone(),
+        audit_forwarder.clone(),
     );
+    if let Some(setter) = level_setter {
+        config_reloader = config_reloader.with_log_level_setter(setter);
+    }
+    for failure in config_reloader.sync_roles(&config.rbac.roles).await {
+        warn!("{}", failure);
+    }
+    let _config_watcher =
+        services::start_config_watcher(config_reloader.clone(), &config.hot_reload);
 
     // Create application state
     let state = AppState {
@@ -380,58 +556,434 @@ async fn main() -> Result<()> {
         inventory_config: inventory_config_full,
         inventory_ready,
         puppetdb,
+        puppetdb_tenants,
         puppet_ca,
         rbac,
         rbac_db,
         code_deploy_config,
         backup_config,
         notification_service,
+        webhooks,
+        settings_cipher: Some(settings_cipher),
+        secrets,
+        audit_forwarder,
+        jwt_keys,
+        log_buffer,
+        health,
+        smart_list_counts,
+        payload_debug: Default::default(),
+        config_reloader,
     };
 
-    // Build the router
+    // Send queued webhook deliveries and watch PuppetDB for failed runs
+    let _webhook_dispatcher = services::start_webhook_dispatcher(
+        state.webhooks.clone(),
+        state.puppetdb.clone(),
+        state.puppetdb_tenants.clone(),
+        &config.webhooks,
+    );
+
+    // Keep the global search index current
+    if config.cache.search_index_interval_secs > 0 {
+        let indexer = services::SearchIndexer::new(
+            state.db.clone(),
+            state.puppetdb.clone(),
+            state.puppetdb_tenants.clone(),
+        );
+        let _search_index_sync = services::CacheSyncJob::search_index_only(
+            indexer,
+            config.cache.search_index_interval_secs,
+            state.health.clone(),
+        )
+        .start();
+    }
+
+    // Build the routers
+    let enc_app = config
+        .server
+        .enc_listener
+        .is_some()
+        .then(|| create_enc_router(state.clone()));
     let app = create_router(state, &config);
 
-    // Start the server
-    let addr: SocketAddr = format!("{}:{}", config.server.host, config.server.port)
-        .parse()
+    // Sockets passed by systemd socket activation replace the configured
+    // main listeners; the ENC listener is still bound from the configuration
+    let activated = systemd_sockets().context("Failed to use sockets passed by systemd")?;
+    let socket_activated = !activated.is_empty();
+    let main_listeners = if socket_activated {
+        info!("Using {} socket(s) passed by systemd", activated.len());
+        Vec::new()
+    } else {
+        config.server.effective_listeners()
+    };
+
+    // Main listeners serve the full application; the optional ENC listener
+    // serves only the classification endpoints
+    let mut listeners: Vec<(config::ListenerConfig, Router, bool)> = main_listeners
+        .into_iter()
+        .map(|listener| (listener, app.clone(), false))
+        .collect();
+    if let (Some(enc_listener), Some(enc_app)) = (config.server.effective_enc_listener(), enc_app)
+    {
+        listeners.push((enc_listener, enc_app, true));
+    }
+
+    // Bind every listener before serving so address errors surface at startup
+    let addrs = listeners
+        .iter()
+        .map(|(listener, _, _)| listener.socket_addr())
+        .collect::<Result<Vec<_>>>()
         .context("Invalid server address configuration")?;
 
-    // Check if TLS is configured
-    if let Some(ref tls_config) = config.server.tls {
-        info!("Starting HTTPS server on https://{}", addr);
+    let mut servers = Vec::with_capacity(listeners.len() + 1);
+    for ((listener, app, is_enc), &addr) in listeners.into_iter().zip(&addrs) {
+        let tcp_listener = bind_tcp_listener(addr, needs_ipv6_only(addr, &addrs))
+            .with_context(|| format!("Failed to bind to {}", addr))?;
+        let tls = listener.tls.as_ref();
+        servers.push(serve_tcp(&config, tcp_listener, addr, tls, app, is_enc).await?);
+    }
+
+    for socket in activated {
+        let local_addr = socket.local_addr()?;
+        if let Some(addr) = local_addr.as_socket() {
+            let tls = config.server.tls.as_ref();
+            servers.push(serve_tcp(&config, socket.into(), addr, tls, app.clone(), false).await?);
+        } else if local_addr.is_unix() {
+            let name = local_addr
+                .as_pathname()
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|| "systemd socket".to_string());
+            servers.push(serve_unix(socket.into(), name, app.clone())?);
+        } else {
+            anyhow::bail!("systemd passed a socket that is neither TCP nor Unix");
+        }
+    }
+
+    if let (false, Some(unix_socket)) = (socket_activated, &config.server.unix_socket) {
+        let unix_listener = bind_unix_listener(unix_socket)?;
+        let name = unix_socket.path.display().to_string();
+        servers.push(serve_unix(unix_listener, name, app.clone())?);
+    }
+
+    info!("Server is ready to accept connections");
+
+    // Listeners only return on error; stop when the first one does
+    let (result, _, _) = futures::future::select_all(servers).await;
+    result.context("Server task panicked")??;
+
+    Ok(())
+}
+
+/// Create the router for the dedicated ENC listener
+///
+/// Only classification, environment lookup and health endpoints; no frontend
+/// and no base path, since Puppet Server talks to it directly.
+fn create_enc_router(state: AppState) -> Router {
+    // Requests come from a handful of Puppet Servers, so use the standard
+    // API limit rather than the per-client login limit
+    let rate_limit = state
+        .config_reloader
+        .rate_limit_state(services::RateLimitKind::Api);
+    middleware::spawn_rate_limit_cleanup(rate_limit.clone());
+
+    let trace_layer = TraceLayer::new_for_http()
+        .make_span_with(middleware::make_request_span)
+        .on_response(DefaultOnResponse::new().level(Level::INFO));
+    let trusted_proxies = middleware::TrustedProxies::new(&state.config.server.trusted_proxies);
+    let security_headers = security_headers(&state.config);
+
+    Router::new()
+        .nest(
+            "/api/v1",
+            api::enc_listener_routes().layer(axum::middleware::from_fn_with_state(
+                rate_limit,
+                middleware::rate_limit_middleware,
+            )),
+        )
+        .layer(axum::middleware::from_fn(middleware::api_error_middleware))
+        .layer(axum::middleware::from_fn(
+            middleware::api_cache_control_middleware,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.payload_debug.clone(),
+            middleware::payload_debug::payload_debug_middleware,
+        ))
+        .with_state(state)
+        .layer(axum::middleware::from_fn_with_state(
+            security_headers,
+            middleware::security_headers_middleware,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            trusted_proxies,
+            middleware::client_ip_middleware,
+        ))
+        .layer(CompressionLayer::new())
+        .layer(trace_layer)
+        .layer(axum::middleware::from_fn(middleware::request_id_middleware))
+}
+
+/// Security headers from the configuration
+///
+/// The configuration was validated at startup, so this falls back to the
+/// defaults only if the check was skipped.
+fn security_headers(config: &AppConfig) -> middleware::SecurityHeaders {
+    middleware::SecurityHeaders::from_config(&config.server.security_headers).unwrap_or_else(|e| {
+        warn!("Invalid server.security_headers, using defaults: {:#}", e);
+        middleware::SecurityHeaders::default()
+    })
+}
+
+fn csrf_protection(config: &AppConfig) -> middleware::CsrfProtection {
+    middleware::CsrfProtection::from_config(&config.auth.csrf).unwrap_or_else(|e| {
+        warn!("Invalid auth.csrf, using defaults: {:#}", e);
+        middleware::CsrfProtection::default()
+    })
+}
+
+/// Bind a TCP listener
+///
+/// `ipv6_only` keeps an IPv6 wildcard socket from also claiming the IPv4
+/// port, so `::` and `0.0.0.0` can be bound side by side.
+fn bind_tcp_listener(addr: SocketAddr, ipv6_only: bool) -> Result<std::net::TcpListener> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    if addr.is_ipv6() {
+        socket.set_only_v6(ipv6_only)?;
+    }
+    socket.set_reuse_address(true)?;
+    socket.bind(&addr.into())?;
+    socket.listen(1024)?;
+    socket.set_nonblocking(true)?;
+
+    Ok(socket.into())
+}
+
+/// Serve the application on a bound TCP listener, with TLS if configured
+async fn serve_tcp(
+    config: &AppConfig,
+    tcp_listener: std::net::TcpListener,
+    addr: SocketAddr,
+    tls: Option<&config::TlsConfig>,
+    app: Router,
+    is_enc: bool,
+) -> Result<tokio::task::JoinHandle<Result<()>>> {
+    let kind = if is_enc { "ENC " } else { "" };
+
+    let server = if let Some(tls_config) = tls {
+        info!("Starting {}HTTPS server on https://{}", kind, addr);
         info!("TLS certificate: {:?}", tls_config.cert_file);
         info!("TLS minimum version: {}", tls_config.min_version);
 
         let rustls_config = create_rustls_config(tls_config).await?;
-        let listener = tokio::net::TcpListener::bind(addr)
-            .await
-            .context("Failed to bind to address")?;
-
-        info!("HTTPS server is ready to accept connections");
+        let app = if is_enc {
+            app
+        } else {
+            start_http3_listener(config, tls_config, addr, app)?
+        };
+        let make_service = app.into_make_service_with_connect_info::<SocketAddr>();
 
         // Use axum-server for TLS with ConnectInfo support
-        axum_server::from_tcp_rustls(listener.into_std()?, rustls_config)?
-            .serve(app.into_make_service_with_connect_info::<SocketAddr>())
-            .await
-            .context("HTTPS server error")?;
+        if tls_config.client_auth.is_some() {
+            let acceptor = middleware::ClientCertAcceptor::new(rustls_config);
+            tokio::spawn(async move {
+                axum_server::from_tcp(tcp_listener)?
+                    .acceptor(acceptor)
+                    .serve(make_service)
+                    .await
+                    .with_context(|| format!("{}HTTPS server error on {}", kind, addr))
+            })
+        } else {
+            tokio::spawn(async move {
+                axum_server::from_tcp_rustls(tcp_listener, rustls_config)?
+                    .serve(make_service)
+                    .await
+                    .with_context(|| format!("{}HTTPS server error on {}", kind, addr))
+            })
+        }
     } else {
-        info!("Starting HTTP server on http://{}", addr);
+        info!("Starting {}HTTP server on http://{}", kind, addr);
 
-        let listener = tokio::net::TcpListener::bind(addr)
+        let tcp_listener = tokio::net::TcpListener::from_std(tcp_listener)?;
+        tokio::spawn(async move {
+            axum::serve(
+                tcp_listener,
+                app.into_make_service_with_connect_info::<SocketAddr>(),
+            )
             .await
-            .context("Failed to bind to address")?;
+            .with_context(|| format!("{}HTTP server error on {}", kind, addr))
+        })
+    };
+    Ok(server)
+}
 
-        info!("HTTP server is ready to accept connections");
+/// Bind the configured Unix domain socket and apply its permissions
+fn bind_unix_listener(
+    unix_socket: &config::UnixSocketConfig,
+) -> Result<std::os::unix::net::UnixListener> {
+    use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+
+    let path = &unix_socket.path;
+    // Replace a socket left behind by a previous run, but never another file
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            anyhow::bail!("{} exists and is not a socket", path.display());
+        }
+        std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove stale socket {}", path.display()))?;
+    }
 
-        axum::serve(
-            listener,
-            app.into_make_service_with_connect_info::<SocketAddr>(),
-        )
-        .await
-        .context("HTTP server error")?;
+    let listener = std::os::unix::net::UnixListener::bind(path)
+        .with_context(|| format!("Failed to bind to {}", path.display()))?;
+    let permissions = std::fs::Permissions::from_mode(unix_socket.mode_bits()?);
+    std::fs::set_permissions(path, permissions)
+        .with_context(|| format!("Failed to set permissions of {}", path.display()))?;
+    if let Some(ref group) = unix_socket.group {
+        std::os::unix::fs::chown(path, None, Some(lookup_group(group)?))
+            .with_context(|| format!("Failed to change the group of {}", path.display()))?;
     }
+    Ok(listener)
+}
 
-    Ok(())
+/// Group ID for a group name or numeric ID
+fn lookup_group(group: &str) -> Result<u32> {
+    if let Ok(gid) = group.parse() {
+        return Ok(gid);
+    }
+    let name = std::ffi::CString::new(group).context("Invalid group name")?;
+    // SAFETY: getgrnam returns NULL or a pointer to a valid group entry,
+    // which is read before any other call can overwrite it
+    let entry = unsafe { libc::getgrnam(name.as_ptr()) };
+    if entry.is_null() {
+        anyhow::bail!("Unknown group '{}'", group);
+    }
+    Ok(unsafe { (*entry).gr_gid })
+}
+
+/// Serve the application over plain HTTP on a Unix domain socket
+///
+/// A Unix socket peer has no IP address, so it is seen as 127.0.0.1, which
+/// the default `server.trusted_proxies` trusts to report the client address.
+fn serve_unix(
+    listener: std::os::unix::net::UnixListener,
+    name: String,
+    app: Router,
+) -> Result<tokio::task::JoinHandle<Result<()>>> {
+    info!("Starting HTTP server on unix:{}", name);
+
+    listener.set_nonblocking(true)?;
+    let listener = tokio::net::UnixListener::from_std(listener)?;
+    let peer = SocketAddr::from(([127, 0, 0, 1], 0));
+    let app = app.layer(axum::Extension(axum::extract::ConnectInfo(peer)));
+    Ok(tokio::spawn(async move {
+        axum::serve(listener, app)
+            .await
+            .with_context(|| format!("HTTP server error on unix:{}", name))
+    }))
+}
+
+/// Sockets passed by systemd socket activation
+///
+/// systemd sets `LISTEN_PID` to this process and `LISTEN_FDS` to the number
+/// of sockets, passed as file descriptors starting at 3. Empty when the
+/// process was not socket-activated.
+fn systemd_sockets() -> Result<Vec<socket2::Socket>> {
+    use std::os::unix::io::FromRawFd;
+
+    const SD_LISTEN_FDS_START: i32 = 3;
+
+    let for_this_process = env::var("LISTEN_PID")
+        .ok()
+        .and_then(|pid| pid.parse::<u32>().ok())
+        == Some(std::process::id());
+    let count = env::var("LISTEN_FDS")
+        .ok()
+        .and_then(|count| count.parse::<i32>().ok())
+        .unwrap_or(0);
+    // Child processes (git, r10k, ...) must not pick the sockets up
+    for var in ["LISTEN_PID", "LISTEN_FDS", "LISTEN_FDNAMES"] {
+        env::remove_var(var);
+    }
+    if !for_this_process || count <= 0 {
+        return Ok(Vec::new());
+    }
+
+    (SD_LISTEN_FDS_START..SD_LISTEN_FDS_START + count)
+        .map(|fd| {
+            // SAFETY: systemd hands these descriptors to this process, and
+            // nothing else in the process owns them. They are inherited
+            // without close-on-exec, which child processes must not get.
+            let socket = unsafe {
+                libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC);
+                socket2::Socket::from_raw_fd(fd)
+            };
+            socket.set_nonblocking(true)?;
+            Ok(socket)
+        })
+        .collect()
+}
+
+/// Whether an IPv6 listener must not accept IPv4 traffic
+///
+/// True when another listener binds IPv4 on the same port.
+fn needs_ipv6_only(addr: SocketAddr, all: &[SocketAddr]) -> bool {
+    addr.is_ipv6()
+        && all
+            .iter()
+            .any(|other| other.is_ipv4() && other.port() == addr.port())
+}
+
+/// Start the HTTP/3 listener if configured
+///
+/// Returns the router for the HTTPS listener, with `Alt-Svc` advertisement
+/// added when HTTP/3 is running.
+#[cfg(feature = "http3")]
+fn start_http3_listener(
+    config: &AppConfig,
+    tls_config: &config::TlsConfig,
+    addr: SocketAddr,
+    app: Router,
+) -> Result<Router> {
+    let Some(h3_config) = config.server.http3.as_ref().filter(|h3| h3.enabled) else {
+        return Ok(app);
+    };
+
+    let h3_addr = SocketAddr::new(addr.ip(), h3_config.port.unwrap_or(addr.port()));
+    let quic_tls = build_tls_server_config(tls_config)?;
+    info!("Starting HTTP/3 server on udp/{}", h3_addr);
+
+    let h3_app = app.clone();
+    tokio::spawn(async move {
+        if let Err(e) = openvox_webui::http3::serve(h3_addr, quic_tls, h3_app).await {
+            tracing::error!("HTTP/3 server error: {:#}", e);
+        }
+    });
+
+    let alt_svc =
+        openvox_webui::http3::alt_svc_value(h3_addr.port(), h3_config.alt_svc_max_age_secs)
+            .parse()
+            .context("Invalid Alt-Svc header value")?;
+
+    Ok(app.layer(axum::middleware::from_fn_with_state(
+        alt_svc,
+        openvox_webui::http3::alt_svc_middleware,
+    )))
+}
+
+/// Start the HTTP/3 listener if configured (unavailable in this build)
+#[cfg(not(feature = "http3"))]
+fn start_http3_listener(
+    config: &AppConfig,
+    _tls_config: &config::TlsConfig,
+    _addr: SocketAddr,
+    app: Router,
+) -> Result<Router> {
+    if config.server.http3.as_ref().is_some_and(|h3| h3.enabled) {
+        warn!(
+            "server.http3 is enabled but this build lacks the `http3` feature; HTTP/3 is disabled"
+        );
+    }
+    Ok(app)
 }
 
 /// Create RusTLS configuration from TLS config
@@ -439,6 +991,22 @@ async fn create_rustls_config(
     tls_config: &config::TlsConfig,
 ) -> Result<axum_server::tls_rustls::RustlsConfig> {
     use axum_server::tls_rustls::RustlsConfig;
+
+    let mut server_config = build_tls_server_config(tls_config)?;
+
+    // Enable ALPN for HTTP/1.1 and HTTP/2
+    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+
+    // Build RustlsConfig from ServerConfig
+    let config = RustlsConfig::from_config(Arc::new(server_config));
+
+    Ok(config)
+}
+
+/// Build the rustls server configuration (without ALPN) from TLS config
+///
+/// Shared by the HTTPS listener and the optional HTTP/3 listener.
+fn build_tls_server_config(tls_config: &config::TlsConfig) -> Result<rustls::ServerConfig> {
     use rustls::crypto::aws_lc_rs::default_provider;
     use rustls::ServerConfig;
 
@@ -488,54 +1056,134 @@ async fn create_rustls_config(
             .collect::<Vec<_>>()
     );
 
+    let provider = Arc::new(provider);
+    let client_verifier = match tls_config.client_auth {
+        Some(ref client_auth) => build_client_verifier(client_auth, provider.clone())?,
+        None => rustls::server::WebPkiClientVerifier::no_client_auth(),
+    };
+
     // Build ServerConfig with specified TLS versions
-    let mut server_config = ServerConfig::builder_with_provider(provider.into())
+    ServerConfig::builder_with_provider(provider)
         .with_protocol_versions(&versions)
         .context("Failed to set TLS protocol versions")?
-        .with_no_client_auth()
+        .with_client_cert_verifier(client_verifier)
         .with_single_cert(certs, key.into())
-        .context("Failed to build TLS server config")?;
+        .context("Failed to build TLS server config")
+}
 
-    // Enable ALPN for HTTP/1.1 and HTTP/2
-    server_config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+/// Build the verifier for TLS client certificates (mTLS)
+fn build_client_verifier(
+    client_auth: &config::ClientAuthConfig,
+    provider: Arc<rustls::crypto::CryptoProvider>,
+) -> Result<Arc<dyn rustls::server::danger::ClientCertVerifier>> {
+    use rustls::server::WebPkiClientVerifier;
+
+    let ca_file = std::fs::File::open(&client_auth.ca_file)
+        .with_context(|| format!("Failed to open client CA file: {:?}", client_auth.ca_file))?;
+    let mut roots = rustls::RootCertStore::empty();
+    let (added, _) = roots.add_parsable_certificates(
+        rustls_pemfile::certs(&mut BufReader::new(ca_file)).filter_map(|r| r.ok()),
+    );
+    if added == 0 {
+        anyhow::bail!("No certificates found in {:?}", client_auth.ca_file);
+    }
 
-    // Build RustlsConfig from ServerConfig
-    let config = RustlsConfig::from_config(Arc::new(server_config));
+    let mut builder = WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider);
+    if let Some(ref crl_file) = client_auth.crl_file {
+        let file = std::fs::File::open(crl_file)
+            .with_context(|| format!("Failed to open client CRL file: {:?}", crl_file))?;
+        let crls = rustls_pemfile::crls(&mut BufReader::new(file))
+            .collect::<Result<Vec<_>, _>>()
+            .with_context(|| format!("Failed to read client CRL file: {:?}", crl_file))?;
+        builder = builder.with_crls(crls);
+    }
+    if client_auth.mode == config::ClientAuthMode::Request {
+        builder = builder.allow_unauthenticated();
+    }
 
-    Ok(config)
+    info!(
+        "TLS client certificates {} (CA: {:?})",
+        match client_auth.mode {
+            config::ClientAuthMode::Request => "requested",
+            config::ClientAuthMode::Require => "required",
+        },
+        client_auth.ca_file
+    );
+    builder
+        .build()
+        .context("Failed to build TLS client certificate verifier")
+}
+
+/// Logging handles kept by `main`
+struct Logging {
+    /// Flushes the log file; must stay alive for the duration of the program
+    guard: Option<tracing_appender::non_blocking::WorkerGuard>,
+    /// Changes the level on configuration reloads, unless RUST_LOG sets it
+    level_setter: Option<LogLevelSetter>,
+    /// Recent records for the admin log viewer
+    buffer: Option<Arc<LogBuffer>>,
 }
 
 /// Initialize the logging/tracing infrastructure
-fn init_logging(config: &AppConfig) -> Option<tracing_appender::non_blocking::WorkerGuard> {
+fn init_logging(config: &AppConfig) -> Logging {
     use config::LogTarget;
     use tracing_subscriber::{prelude::*, EnvFilter};
 
     let env_filter =
         EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(&config.logging.level));
 
+    // Let configuration reloads change the level, unless RUST_LOG sets it
+    let (env_filter, filter_handle) = tracing_subscriber::reload::Layer::new(env_filter);
+    let level_setter = std::env::var_os("RUST_LOG").is_none().then(|| {
+        let setter: LogLevelSetter = Arc::new(move |level: &str| -> Result<()> {
+            filter_handle.reload(EnvFilter::try_new(level)?)?;
+            Ok(())
+        });
+        setter
+    });
+
     let log_config = &config.logging;
 
-    match &log_config.target {
+    // Recent records for the admin log viewer
+    let buffer =
+        (log_config.buffer_lines > 0).then(|| Arc::new(LogBuffer::new(log_config.buffer_lines)));
+    let buffer_layer = buffer
+        .clone()
+        .map(services::log_buffer::LogBufferLayer::new);
+
+    let guard = match &log_config.target {
         LogTarget::Console => {
             // Console-only logging (development mode)
-            let subscriber = tracing_subscriber::registry().with(env_filter);
+            let subscriber = tracing_subscriber::registry()
+                .with(env_filter)
+                .with(buffer_layer);
             init_console_logging(subscriber, &log_config.format);
             None
         }
         LogTarget::File => {
             // File-only logging (production mode)
             let (writer, guard) = create_file_writer(log_config);
-            let subscriber = tracing_subscriber::registry().with(env_filter);
+            let subscriber = tracing_subscriber::registry()
+                .with(env_filter)
+                .with(buffer_layer);
             init_file_logging(subscriber, &log_config.format, writer);
             Some(guard)
         }
         LogTarget::Both => {
             // Both console and file logging
             let (writer, guard) = create_file_writer(log_config);
-            let subscriber = tracing_subscriber::registry().with(env_filter);
+            let subscriber = tracing_subscriber::registry()
+                .with(env_filter)
+                .with(buffer_layer);
             init_both_logging(subscriber, &log_config.format, writer);
             Some(guard)
         }
+    };
+
+    Logging {
+        guard,
+        level_setter,
+        buffer,
     }
 }
 
@@ -707,19 +1355,29 @@ fn create_router(state: AppState, config: &AppConfig) -> Router {
     let cors = CorsLayer::new()
         .allow_origin(Any)
         .allow_methods(Any)
-        .allow_headers(Any);
+        .allow_headers(Any)
+        .expose_headers([middleware::REQUEST_ID_HEADER]);
 
     // Configure tracing for HTTP requests
     let trace_layer = TraceLayer::new_for_http()
-        .make_span_with(DefaultMakeSpan::new().level(Level::INFO))
+        .make_span_with(middleware::make_request_span)
         .on_response(DefaultOnResponse::new().level(Level::INFO));
 
-    // Initialize rate limiting
-    let api_rate_limit = middleware::create_rate_limit_state(middleware::api_rate_limit_config());
-    let auth_rate_limit = middleware::create_rate_limit_state(middleware::auth_rate_limit_config());
-
-    // Spawn background cleanup task for rate limiters
+    // Initialize rate limiting; limits follow configuration reloads
+    let api_rate_limit = state
+        .config_reloader
+        .rate_limit_state(services::RateLimitKind::Api);
+    let auth_rate_limit = state
+        .config_reloader
+        .rate_limit_state(services::RateLimitKind::Auth);
+    let expensive_rate_limit = state
+        .config_reloader
+        .rate_limit_state(services::RateLimitKind::Expensive);
+
+    // Spawn background cleanup tasks for rate limiters
     middleware::spawn_rate_limit_cleanup(api_rate_limit.clone());
+    middleware::spawn_rate_limit_cleanup(auth_rate_limit.clone());
+    middleware::spawn_rate_limit_cleanup(expensive_rate_limit.clone());
 
     // Build the API router
     //
@@ -728,12 +1386,20 @@ fn create_router(state: AppState, config: &AppConfig) -> Router {
     // auth middleware only to protected routes.
     //
     // Rate limiting is applied:
-    // - Stricter limits on auth endpoints (brute force protection)
-    // - Standard limits on all other API endpoints
+    // - Stricter limits on auth endpoints (brute force protection), per client IP
+    // - Standard limits on all other API endpoints, per user or API key, so
+    //   they run inside the auth middleware
+    // - An additional limit on expensive endpoints (queries, reports)
+    let public_routes = if config.server.enc_endpoints_exclusive() {
+        info!("ENC endpoints are served only from the ENC listener");
+        api::public_routes_without_enc()
+    } else {
+        api::public_routes()
+    };
     let api_router = Router::new()
         .nest(
             "/api/v1",
-            api::public_routes().layer(axum::middleware::from_fn_with_state(
+            public_routes.layer(axum::middleware::from_fn_with_state(
                 auth_rate_limit,
                 middleware::rate_limit_middleware,
             )),
@@ -741,18 +1407,38 @@ fn create_router(state: AppState, config: &AppConfig) -> Router {
         .nest(
             "/api/v1",
             api::protected_routes()
-                .layer(axum::middleware::from_fn_with_state(
+                .route_layer(axum::middleware::from_fn_with_state(
                     state.clone(),
-                    middleware::auth::auth_middleware,
+                    middleware::audit_middleware,
                 ))
                 .layer(axum::middleware::from_fn_with_state(
-                    api_rate_limit,
+                    expensive_rate_limit,
                     middleware::rate_limit_middleware,
+                ))
+                .layer(axum::middleware::from_fn_with_state(
+                    api_rate_limit.clone(),
+                    middleware::rate_limit_middleware,
+                ))
+                .layer(axum::middleware::from_fn_with_state(
+                    state.clone(),
+                    middleware::auth::auth_middleware,
                 )),
         )
+        .merge(graphql_routes(&state, config, api_rate_limit))
+        .layer(axum::middleware::from_fn(middleware::api_error_middleware))
         .layer(axum::middleware::from_fn(
             middleware::api_cache_control_middleware,
         ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.payload_debug.clone(),
+            middleware::payload_debug::payload_debug_middleware,
+        ))
+        // Body limits are configured per route; replaces axum's 2 MiB default
+        .layer(DefaultBodyLimit::disable())
+        .layer(axum::middleware::from_fn_with_state(
+            middleware::BodyLimits::new(&config.server.body_limits),
+            middleware::body_limit_middleware,
+        ))
         .with_state(state.clone());
 
     // Optionally serve frontend static files
@@ -761,22 +1447,45 @@ fn create_router(state: AppState, config: &AppConfig) -> Router {
             if static_dir.exists() {
                 info!("Serving frontend from {:?}", static_dir);
 
+                let assets = &config.server.static_assets;
+                let mut serve_dir = ServeDir::new(static_dir);
+                if assets.precompressed {
+                    // Prefer foo.js.br / foo.js.gz produced at build time
+                    serve_dir = serve_dir.precompressed_br().precompressed_gzip();
+                }
+
                 // Serve index.html for the root and as a fallback for SPA routing
                 let index_file = static_dir.join("index.html");
-                if index_file.exists() {
-                    // Create a service that serves static files and falls back to index.html
-                    let serve_dir =
-                        ServeDir::new(static_dir).not_found_service(ServeFile::new(&index_file));
-
-                    api_router.fallback_service(serve_dir)
+                let router = if index_file.exists() {
+                    // index.html is rewritten for the base path, so it is never
+                    // served straight from disk
+                    let serve_index =
+                        handlers::spa::serve_index.with_state(handlers::spa::SpaIndex {
+                            index_file,
+                            base_path: config.server.normalized_base_path(),
+                        });
+
+                    api_router
+                        .route_service("/index.html", serve_index.clone())
+                        .fallback_service(
+                            serve_dir
+                                .append_index_html_on_directories(false)
+                                .fallback(serve_index),
+                        )
                 } else {
                     warn!(
                         "index.html not found in {:?}, SPA fallback disabled",
                         static_dir
                     );
-                    let serve_dir = ServeDir::new(static_dir);
                     api_router.fallback_service(serve_dir)
-                }
+                };
+
+                // Fingerprinted assets are cached forever, everything else is
+                // revalidated (API responses keep their own Cache-Control)
+                router.layer(axum::middleware::from_fn_with_state(
+                    assets.clone(),
+                    middleware::static_cache::static_cache_control_middleware,
+                ))
             } else {
                 warn!(
                     "Static directory {:?} does not exist, frontend not served",
@@ -793,20 +1502,87 @@ fn create_router(state: AppState, config: &AppConfig) -> Router {
         api_router
     };
 
+    // CSRF tokens for browser sessions; inside the base path so exempt
+    // paths match, and around the frontend so loading the app issues a token
+    let router = if config.auth.csrf_enabled() {
+        info!("CSRF protection enabled");
+        router.layer(axum::middleware::from_fn_with_state(
+            csrf_protection(config),
+            middleware::csrf_middleware,
+        ))
+    } else {
+        router
+    };
+
+    // Serve everything under the configured base path (reverse proxy subpath)
+    let base_path = config.server.normalized_base_path();
+    let router = if base_path.is_empty() {
+        router
+    } else {
+        info!("Serving under base path {}", base_path);
+        Router::new().nest_service(&base_path, router)
+    };
+
     // Apply global middleware layers:
     // 1. Security headers (HSTS, CSP, X-Frame-Options, etc.)
-    // 2. Compression
-    // 3. Request tracing
-    // 4. CORS
+    // 2. Client address behind trusted proxies
+    // 3. Compression
+    // 4. Request tracing
+    // 5. Request IDs, assigned before the tracing span is created
+    // 6. CORS
+    let trusted_proxies = middleware::TrustedProxies::new(&config.server.trusted_proxies);
     router
-        .layer(axum::middleware::from_fn(
+        .layer(axum::middleware::from_fn_with_state(
+            security_headers(config),
             middleware::security_headers_middleware,
         ))
+        .layer(axum::middleware::from_fn_with_state(
+            trusted_proxies,
+            middleware::client_ip_middleware,
+        ))
         .layer(CompressionLayer::new())
         .layer(trace_layer)
+        .layer(axum::middleware::from_fn(middleware::request_id_middleware))
         .layer(cors)
 }
 
+/// GraphQL endpoint, authenticated and rate limited like the REST API
+#[cfg(feature = "graphql")]
+fn graphql_routes(
+    state: &AppState,
+    config: &AppConfig,
+    rate_limit: middleware::RateLimitState,
+) -> Router<AppState> {
+    let Some(graphql) = config.server.graphql.as_ref().filter(|g| g.enabled) else {
+        return Router::new();
+    };
+    info!("Serving the GraphQL API at /api/graphql");
+
+    let schema = openvox_webui::graphql::build_schema(state.clone(), graphql);
+    openvox_webui::graphql::routes(schema)
+        .layer(axum::middleware::from_fn_with_state(
+            rate_limit,
+            middleware::rate_limit_middleware,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            middleware::auth::auth_middleware,
+        ))
+}
+
+/// GraphQL endpoint (unavailable in this build)
+#[cfg(not(feature = "graphql"))]
+fn graphql_routes(
+    _state: &AppState,
+    config: &AppConfig,
+    _rate_limit: middleware::RateLimitState,
+) -> Router<AppState> {
+    if config.server.graphql.as_ref().is_some_and(|g| g.enabled) {
+        warn!("server.graphql is enabled but this build lacks the `graphql` feature; GraphQL is disabled");
+    }
+    Router::new()
+}
+
 /// Print help message
 fn print_help() {
     println!(
@@ -814,10 +1590,14 @@ fn print_help() {
 
 USAGE:
     openvox-webui [OPTIONS]
+    openvox-webui <SUBCOMMAND>
 
 OPTIONS:
     -h, --help              Print this help message
     -V, --version           Print version information
+    --check-config          Validate the configuration, list settings taken
+                            from environment variables and warnings, then
+                            exit (non-zero if the configuration is invalid).
     --fix-database          Fix database by running all migrations and ensuring
                             all required tables exist. This is useful when
                             upgrading from an older version or recovering from
@@ -834,6 +1614,8 @@ OPTIONS:
                             if legacy inventory data was written to the main
                             DB by an older binary.
 
+{}
+
 ENVIRONMENT:
     OPENVOX_CONFIG      Path to configuration file (default: config.yaml)
 
@@ -844,18 +1626,15 @@ CONFIGURATION:
     3. /etc/openvox-webui/config.yaml
 
 For more information, see: https://github.com/openvoxproject/openvox-webui"#,
-        env!("CARGO_PKG_VERSION")
+        env!("CARGO_PKG_VERSION"),
+        openvox_webui::cli::USAGE
     );
 }
 
 /// Fix database by running migrations without the integrity check,
 /// then verify all tables exist.
 async fn fix_database() -> Result<()> {
-    use sqlx::{
-        sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous},
-        Row,
-    };
-    use std::time::Duration;
+    use sqlx::{sqlite::SqlitePoolOptions, Row};
 
     println!(
         "OpenVox WebUI Database Repair Tool v{}",
@@ -880,15 +1659,8 @@ async fn fix_database() -> Result<()> {
     println!();
 
     // Parse the database URL and configure SQLite options
-    let connect_options = config
-        .database
-        .url
-        .parse::<SqliteConnectOptions>()
-        .context("Failed to parse database URL")?
-        .journal_mode(SqliteJournalMode::Wal)
-        .synchronous(SqliteSynchronous::Normal)
-        .busy_timeout(Duration::from_secs(config.database.connect_timeout_secs))
-        .create_if_missing(true);
+    let connect_options = db::sqlite_connect_options(&config.database.url, &config.database)
+        .context("Failed to parse database URL")?;
 
     println!("Connecting to database...");
 
@@ -963,6 +1735,7 @@ async fn fix_database() -> Result<()> {
         "backup_restores",
         "pending_node_removals",
         "node_removal_audit",
+        "smart_lists",
     ];
 
     // Check for missing tables
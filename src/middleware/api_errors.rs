@@ -0,0 +1,121 @@
+//! Uniform API error bodies
+//!
+//! Handlers return [`ApiError`] bodies through [`crate::utils::AppError`],
+//! but some error responses are produced elsewhere: extractor rejections
+//! (malformed JSON, bad path or query parameters), unknown routes and
+//! methods, and handlers that return a bare status. Those have plain-text or
+//! empty bodies; this middleware rewrites them into an [`ApiError`] so every
+//! error of the API has the same shape.
+
+use axum::{
+    extract::Request,
+    http::{header, HeaderMap},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use crate::utils::ApiError;
+
+/// Longest plain-text error body used as the message
+const MAX_MESSAGE_BYTES: usize = 4096;
+
+/// Rewrite non-JSON error responses into [`ApiError`] bodies
+pub async fn api_error_middleware(request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+    let status = response.status();
+    if !(status.is_client_error() || status.is_server_error()) || is_json(response.headers()) {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let message = axum::body::to_bytes(body, MAX_MESSAGE_BYTES)
+        .await
+        .map(|bytes| String::from_utf8_lossy(&bytes).trim().to_string())
+        .ok()
+        .filter(|message| !message.is_empty())
+        .unwrap_or_else(|| status.canonical_reason().unwrap_or("Error").to_string());
+
+    parts.headers.remove(header::CONTENT_TYPE);
+    parts.headers.remove(header::CONTENT_LENGTH);
+    (parts, Json(ApiError::for_status(status, message))).into_response()
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json") || ct.contains("+json"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        body::Body,
+        http::{Request as HttpRequest, StatusCode},
+        routing::{get, post},
+        Router,
+    };
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route("/status", get(|| async { StatusCode::NOT_FOUND }))
+            .route(
+                "/json",
+                post(|Json(body): Json<serde_json::Value>| async move { Json(body) }),
+            )
+            .layer(axum::middleware::from_fn(api_error_middleware))
+    }
+
+    async fn body_json(response: Response) -> serde_json::Value {
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_bare_status_gets_error_body() {
+        let response = app()
+            .oneshot(HttpRequest::get("/status").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let json = body_json(response).await;
+        assert_eq!(json["error"], "not_found");
+        assert_eq!(json["code"], "not_found");
+        assert_eq!(json["message"], "Not Found");
+    }
+
+    #[tokio::test]
+    async fn test_rejection_message_is_kept() {
+        let response = app()
+            .oneshot(
+                HttpRequest::post("/json")
+                    .header("Content-Type", "application/json")
+                    .body(Body::from("{not json"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let json = body_json(response).await;
+        assert_eq!(json["error"], "bad_request");
+        assert!(json["message"].as_str().unwrap().contains("JSON"));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_method_gets_error_body() {
+        let response = app()
+            .oneshot(HttpRequest::delete("/status").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(body_json(response).await["error"], "method_not_allowed");
+    }
+}
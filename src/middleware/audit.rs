@@ -0,0 +1,382 @@
+//! Audit trail for mutating API calls
+//!
+//! Every POST, PUT, PATCH and DELETE on the protected API is recorded with the
+//! acting user, the organization, the resource, the route and the response
+//! status. Handlers that know the previous and new state of a resource return
+//! an [`AuditChange`] with their response, and the entry then carries a
+//! field-level diff. Otherwise the JSON request body is recorded. Credentials
+//! are redacted in both.
+
+use std::collections::BTreeSet;
+use std::convert::Infallible;
+
+use axum::{
+    body::Body,
+    extract::{MatchedPath, OriginalUri, State},
+    http::{header, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, IntoResponseParts, Response, ResponseParts},
+    Json,
+};
+use serde::Serialize;
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use super::auth::{with_impersonator, AuthUser};
+use super::payload_debug::{is_sensitive_field, sanitize_json, REDACTED};
+use crate::db::AuditRepository;
+use crate::utils::ApiError;
+use crate::AppState;
+
+/// Largest JSON request body recorded in an audit entry
+const MAX_AUDITED_BODY_BYTES: usize = 64 * 1024;
+
+/// Previous and new state of the resource a request changed
+///
+/// Return it alongside the response body, e.g.
+/// `Ok((AuditChange::new(&before, &after), Json(after)))`.
+#[derive(Debug, Clone, Default)]
+pub struct AuditChange {
+    before: Option<Value>,
+    after: Option<Value>,
+}
+
+impl AuditChange {
+    pub fn new(before: &impl Serialize, after: &impl Serialize) -> Self {
+        Self {
+            before: serde_json::to_value(before).ok(),
+            after: serde_json::to_value(after).ok(),
+        }
+    }
+
+    /// A resource that did not exist before the request
+    pub fn created(after: &impl Serialize) -> Self {
+        Self {
+            before: None,
+            after: serde_json::to_value(after).ok(),
+        }
+    }
+
+    /// A resource removed by the request
+    pub fn deleted(before: &impl Serialize) -> Self {
+        Self {
+            before: serde_json::to_value(before).ok(),
+            after: None,
+        }
+    }
+
+    /// Diff with credentials redacted; a changed secret shows up as changed
+    fn diff(&self) -> Value {
+        let before = self.before.clone().unwrap_or(Value::Null);
+        let after = self.after.clone().unwrap_or(Value::Null);
+        let mut diff = json_diff(&before, &after);
+        match &mut diff {
+            Value::Object(changes) if before.is_object() && after.is_object() => {
+                for (key, change) in changes.iter_mut() {
+                    if is_sensitive_field(key) {
+                        *change = json!({ "before": REDACTED, "after": REDACTED });
+                    } else {
+                        sanitize_json(change);
+                    }
+                }
+            }
+            other => sanitize_json(other),
+        }
+        diff
+    }
+}
+
+impl IntoResponseParts for AuditChange {
+    type Error = Infallible;
+
+    fn into_response_parts(self, mut res: ResponseParts) -> Result<ResponseParts, Self::Error> {
+        res.extensions_mut().insert(self);
+        Ok(res)
+    }
+}
+
+/// Field-level differences between two JSON values
+///
+/// Objects are compared key by key; any other change is reported whole.
+pub fn json_diff(before: &Value, after: &Value) -> Value {
+    match (before, after) {
+        (Value::Object(old), Value::Object(new)) => {
+            let keys: BTreeSet<&String> = old.keys().chain(new.keys()).collect();
+            let mut changes = serde_json::Map::new();
+            for key in keys {
+                let old_value = old.get(key).unwrap_or(&Value::Null);
+                let new_value = new.get(key).unwrap_or(&Value::Null);
+                if old_value != new_value {
+                    changes.insert(
+                        key.clone(),
+                        json!({ "before": old_value, "after": new_value }),
+                    );
+                }
+            }
+            Value::Object(changes)
+        }
+        _ if before == after => Value::Object(serde_json::Map::new()),
+        _ => json!({ "before": before, "after": after }),
+    }
+}
+
+/// What an audit entry records about a request
+#[derive(Debug, Clone, PartialEq)]
+pub struct AuditTarget {
+    pub action: String,
+    pub resource_type: String,
+    pub resource_id: Option<String>,
+}
+
+/// Derive the action and resource of a mutating request
+///
+/// `route` is the matched route template (`/api/v1/groups/{id}/pin`) and
+/// `path` the request path. The first segment names the resource type, the
+/// first parameter is the resource ID, and the other literal segments name
+/// the action: `POST /groups` is `groups.create`, `POST /groups/{id}/pin` is
+/// `groups.pin` and `DELETE /ca/certificates/{certname}` is
+/// `ca.certificates.delete`. Returns `None` for non-mutating methods.
+pub fn describe_request(method: &Method, route: &str, path: &str) -> Option<AuditTarget> {
+    let verb = match *method {
+        Method::POST => "create",
+        Method::PUT | Method::PATCH => "update",
+        Method::DELETE => "delete",
+        _ => return None,
+    };
+
+    let split = |s: &str| -> Vec<String> {
+        let s = s.strip_prefix("/api/v1").unwrap_or(s);
+        s.split('/')
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+            .collect()
+    };
+    let template = split(route);
+    let values = split(path);
+    let resource_type = template.first()?.replace('-', "_");
+
+    // The path may lack the prefixes of nested routers, so align from the end
+    let offset = values.len().saturating_sub(template.len());
+    let mut resource_id = None;
+    let mut names = Vec::new();
+    for (i, segment) in template.iter().enumerate().skip(1) {
+        if segment.starts_with('{') {
+            if resource_id.is_none() {
+                resource_id = values.get(offset + i).cloned();
+            }
+        } else {
+            names.push(segment.replace('-', "_"));
+        }
+    }
+    if names.is_empty() || *method != Method::POST {
+        names.push(verb.to_string());
+    }
+
+    Some(AuditTarget {
+        action: format!("{}.{}", resource_type, names.join(".")),
+        resource_type,
+        resource_id,
+    })
+}
+
+/// JSON request body worth recording, if it is small enough to buffer
+fn should_capture_body(request: &Request<Body>) -> bool {
+    let is_json = request
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json"));
+    let length = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<usize>().ok());
+    is_json && length.is_some_and(|len| len > 0 && len <= MAX_AUDITED_BODY_BYTES)
+}
+
+/// Middleware recording an audit entry for every mutating request
+///
+/// Apply it with `route_layer` inside the auth middleware so the matched
+/// route and the authenticated user are known.
+pub async fn audit_middleware(
+    State(state): State<AppState>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let Some(auth_user) = request.extensions().get::<AuthUser>().cloned() else {
+        return next.run(request).await;
+    };
+    // Nested routers strip their prefix; record the path the client used
+    let path = request
+        .extensions()
+        .get::<OriginalUri>()
+        .map(|uri| uri.path().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| path.clone());
+    let Some(target) = describe_request(request.method(), &route, &path) else {
+        return next.run(request).await;
+    };
+
+    let method = request.method().to_string();
+    let organization_id = request
+        .uri()
+        .query()
+        .and_then(|query| {
+            query
+                .split('&')
+                .find_map(|pair| match pair.split_once('=') {
+                    Some(("organization_id", value)) => Uuid::parse_str(value).ok(),
+                    _ => None,
+                })
+        })
+        .unwrap_or(auth_user.organization_id);
+    let ip_address = super::client_ip(request.extensions()).map(|ip| ip.to_string());
+
+    let (request, body) = if should_capture_body(&request) {
+        let (parts, body) = request.into_parts();
+        let bytes = match axum::body::to_bytes(body, MAX_AUDITED_BODY_BYTES).await {
+            Ok(bytes) => bytes,
+            // The body is longer than its Content-Length announced
+            Err(_) => {
+                let body = ApiError::new(
+                    "payload_too_large",
+                    format!(
+                        "Request body exceeds the limit of {} bytes",
+                        MAX_AUDITED_BODY_BYTES
+                    ),
+                );
+                return (StatusCode::PAYLOAD_TOO_LARGE, Json(body)).into_response();
+            }
+        };
+        let body = serde_json::from_slice::<Value>(&bytes)
+            .ok()
+            .map(|mut json| {
+                sanitize_json(&mut json);
+                json
+            });
+        (Request::from_parts(parts, Body::from(bytes)), body)
+    } else {
+        (request, None)
+    };
+
+    let response = next.run(request).await;
+
+    let mut details = json!({
+        "method": method,
+        "path": path,
+        "route": route,
+        "status": response.status().as_u16(),
+        "username": auth_user.username,
+    });
+    if let Some(impersonator) = &auth_user.impersonator {
+        details["impersonator"] = json!(impersonator.username);
+    }
+    if let Some(change) = response.extensions().get::<AuditChange>() {
+        details["changes"] = change.diff();
+    } else if let Some(body) = body {
+        details["request"] = body;
+    }
+
+    let pool = state.db.clone();
+    let forwarder = state.audit_forwarder.clone();
+    let request_id = super::request_id::current_request_id();
+    let impersonator = auth_user.impersonator.as_ref().map(|i| i.id);
+    tokio::spawn(super::request_id::with_request_id(
+        request_id,
+        with_impersonator(impersonator, async move {
+            let _ = AuditRepository::new(&pool, forwarder.as_deref())
+                .insert(
+                    organization_id,
+                    Some(auth_user.id),
+                    &target.action,
+                    &target.resource_type,
+                    target.resource_id.as_deref(),
+                    Some(&details),
+                    ip_address.as_deref(),
+                )
+                .await;
+        }),
+    ));
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_request() {
+        let target = describe_request(&Method::PUT, "/api/v1/groups/{id}", "/groups/abc").unwrap();
+        assert_eq!(
+            target,
+            AuditTarget {
+                action: "groups.update".to_string(),
+                resource_type: "groups".to_string(),
+                resource_id: Some("abc".to_string()),
+            }
+        );
+
+        let target = describe_request(
+            &Method::POST,
+            "/api/v1/ca/sign/{certname}",
+            "/api/v1/ca/sign/web01",
+        )
+        .unwrap();
+        assert_eq!(target.action, "ca.sign");
+        assert_eq!(target.resource_id.as_deref(), Some("web01"));
+
+        let target = describe_request(
+            &Method::DELETE,
+            "/api/v1/ca/certificates/{certname}",
+            "/ca/certificates/web01",
+        )
+        .unwrap();
+        assert_eq!(target.action, "ca.certificates.delete");
+
+        let target = describe_request(&Method::POST, "/api/v1/api-keys", "/api-keys").unwrap();
+        assert_eq!(target.action, "api_keys.create");
+        assert_eq!(target.resource_id, None);
+
+        assert!(describe_request(&Method::GET, "/api/v1/groups", "/groups").is_none());
+    }
+
+    #[test]
+    fn test_json_diff() {
+        let before = json!({"name": "web", "rules": ["a"], "priority": 1});
+        let after = json!({"name": "web", "rules": ["a", "b"], "enabled": true});
+
+        assert_eq!(
+            json_diff(&before, &after),
+            json!({
+                "enabled": {"before": null, "after": true},
+                "priority": {"before": 1, "after": null},
+                "rules": {"before": ["a"], "after": ["a", "b"]},
+            })
+        );
+        assert_eq!(json_diff(&before, &before), json!({}));
+        assert_eq!(
+            json_diff(&Value::Null, &json!("x")),
+            json!({"before": null, "after": "x"})
+        );
+    }
+
+    #[test]
+    fn test_audit_change_redacts_secrets() {
+        let change = AuditChange::new(
+            &json!({"name": "deploy", "api_token": "old"}),
+            &json!({"name": "deploy", "api_token": "new"}),
+        );
+        assert_eq!(
+            change.diff(),
+            json!({"api_token": {"before": "[REDACTED]", "after": "[REDACTED]"}})
+        );
+
+        let created = AuditChange::created(&json!({"name": "svc", "password": "hunter2"}));
+        assert!(!created.diff().to_string().contains("hunter2"));
+    }
+}
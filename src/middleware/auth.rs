@@ -2,24 +2,39 @@
 //!
 //! This module provides JWT-based authentication for the API.
 
+use std::future::Future;
+use std::net::IpAddr;
+
 use axum::{
     extract::{FromRequestParts, Request, State},
-    http::{header::AUTHORIZATION, request::Parts, StatusCode},
+    http::{
+        header::{AUTHORIZATION, USER_AGENT},
+        request::Parts,
+        HeaderMap, Method, StatusCode,
+    },
     middleware::Next,
     response::{IntoResponse, Response},
     Json,
 };
-use chrono::{Duration, Utc};
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, TokenData, Validation};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{
+    decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, TokenData,
+    Validation,
+};
 use serde::{Deserialize, Serialize};
 use sqlx::{Row, SqlitePool};
 use uuid::Uuid;
 
 use crate::{
-    models::default_organization_uuid, services::AuthService, utils::error::ErrorResponse, AppState,
+    db::ApiKeyRepository,
+    middleware::session_cookie,
+    models::default_organization_uuid,
+    services::{api_key_policy, AuthService, JwtKeys},
+    utils::error::ApiError,
+    AppState,
 };
 
-const SESSION_IDLE_TIMEOUT_MINUTES: i64 = 30;
+pub(crate) const SESSION_IDLE_TIMEOUT_MINUTES: i64 = 30;
 
 /// JWT Claims structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -47,6 +62,20 @@ pub struct Claims {
     /// Organization/tenant ID
     #[serde(default)]
     pub organization_id: Option<String>,
+    /// Real actor of an impersonation token (RFC 8693 `act` claim)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<ActorClaim>,
+    /// Refresh token ID; makes every refresh token of a session unique
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rti: Option<String>,
+}
+
+/// The user acting through a token issued for someone else
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ActorClaim {
+    /// User ID
+    pub sub: String,
+    pub username: String,
 }
 
 fn default_token_type() -> TokenType {
@@ -74,6 +103,17 @@ pub struct AuthUser {
     pub roles: Vec<String>,
     /// Role UUIDs (resolved from database)
     pub role_ids: Vec<Uuid>,
+    /// API key the request was authenticated with, if any
+    pub api_key_id: Option<Uuid>,
+    /// Admin impersonating this user, for impersonation tokens
+    pub impersonator: Option<Impersonator>,
+}
+
+/// Admin acting as another user
+#[derive(Debug, Clone, PartialEq)]
+pub struct Impersonator {
+    pub id: Uuid,
+    pub username: String,
 }
 
 impl TryFrom<Claims> for AuthUser {
@@ -85,6 +125,15 @@ impl TryFrom<Claims> for AuthUser {
             Some(org) => Uuid::parse_str(&org).map_err(|_| "Invalid organization ID in token")?,
             None => default_organization_uuid(),
         };
+        let impersonator = claims
+            .act
+            .map(|act| {
+                Ok::<_, Self::Error>(Impersonator {
+                    id: Uuid::parse_str(&act.sub).map_err(|_| "Invalid actor ID in token")?,
+                    username: act.username,
+                })
+            })
+            .transpose()?;
         Ok(Self {
             id,
             organization_id,
@@ -93,6 +142,8 @@ impl TryFrom<Claims> for AuthUser {
             session_id: claims.jti,
             roles: claims.roles,
             role_ids: vec![], // Will be populated by middleware
+            api_key_id: None,
+            impersonator,
         })
     }
 }
@@ -123,24 +174,45 @@ impl<S> FromRequestParts<S> for AuthUser
 where
     S: Send + Sync,
 {
-    type Rejection = (StatusCode, Json<ErrorResponse>);
+    type Rejection = (StatusCode, Json<ApiError>);
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
         parts.extensions.get::<AuthUser>().cloned().ok_or_else(|| {
             (
                 StatusCode::UNAUTHORIZED,
-                Json(ErrorResponse {
-                    error: "unauthorized".to_string(),
-                    message: "Authentication required".to_string(),
-                    details: None,
-                    code: None,
-                }),
+                Json(ApiError::new("unauthorized", "Authentication required")),
             )
         })
     }
 }
 
+/// Client details recorded for a new login session
+#[derive(Debug, Clone, Default)]
+pub struct SessionClient {
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+}
+
+impl<S> FromRequestParts<S> for SessionClient
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        Ok(Self {
+            ip_address: super::client_ip(&parts.extensions).map(|ip| ip.to_string()),
+            user_agent: parts
+                .headers
+                .get(USER_AGENT)
+                .and_then(|h| h.to_str().ok())
+                .map(|ua| ua.chars().take(512).collect()),
+        })
+    }
+}
+
 /// Create a new JWT access token
+#[allow(clippy::too_many_arguments)]
 pub fn create_access_token(
     user_id: &Uuid,
     organization_id: &Uuid,
@@ -149,10 +221,100 @@ pub fn create_access_token(
     email: &str,
     roles: Vec<String>,
     secret: &str,
+    keys: &JwtKeys,
     expiry_hours: u64,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    create_access_token_until(
+        user_id,
+        organization_id,
+        session_id,
+        username,
+        email,
+        roles,
+        secret,
+        keys,
+        Utc::now() + Duration::hours(expiry_hours as i64),
+    )
+}
+
+/// Create a new JWT access token expiring at a fixed time
+///
+/// Used when the token carries a temporary role, so it cannot outlive the
+/// elevation that granted it.
+#[allow(clippy::too_many_arguments)]
+pub fn create_access_token_until(
+    user_id: &Uuid,
+    organization_id: &Uuid,
+    session_id: &Uuid,
+    username: &str,
+    email: &str,
+    roles: Vec<String>,
+    secret: &str,
+    keys: &JwtKeys,
+    exp: DateTime<Utc>,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    create_token_with_actor(
+        user_id,
+        organization_id,
+        session_id,
+        username,
+        email,
+        roles,
+        secret,
+        keys,
+        exp,
+        None,
+    )
+}
+
+/// Create an access token acting as `user_id` on behalf of `actor`
+///
+/// Permissions are those of the impersonated user; the actor is carried in
+/// the `act` claim so requests made with the token are attributed to both.
+#[allow(clippy::too_many_arguments)]
+pub fn create_impersonation_token(
+    user_id: &Uuid,
+    organization_id: &Uuid,
+    session_id: &Uuid,
+    username: &str,
+    email: &str,
+    roles: Vec<String>,
+    secret: &str,
+    keys: &JwtKeys,
+    exp: DateTime<Utc>,
+    actor: &Impersonator,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    create_token_with_actor(
+        user_id,
+        organization_id,
+        session_id,
+        username,
+        email,
+        roles,
+        secret,
+        keys,
+        exp,
+        Some(ActorClaim {
+            sub: actor.id.to_string(),
+            username: actor.username.clone(),
+        }),
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn create_token_with_actor(
+    user_id: &Uuid,
+    organization_id: &Uuid,
+    session_id: &Uuid,
+    username: &str,
+    email: &str,
+    roles: Vec<String>,
+    secret: &str,
+    keys: &JwtKeys,
+    exp: DateTime<Utc>,
+    act: Option<ActorClaim>,
 ) -> Result<String, jsonwebtoken::errors::Error> {
     let now = Utc::now();
-    let exp = now + Duration::hours(expiry_hours as i64);
 
     let claims = Claims {
         sub: user_id.to_string(),
@@ -165,13 +327,11 @@ pub fn create_access_token(
         token_type: TokenType::Access,
         roles,
         organization_id: Some(organization_id.to_string()),
+        act,
+        rti: None,
     };
 
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(secret.as_bytes()),
-    )
+    sign_claims(&claims, secret, keys)
 }
 
 /// Create a new JWT refresh token
@@ -181,6 +341,7 @@ pub fn create_refresh_token(
     username: &str,
     email: &str,
     secret: &str,
+    keys: &JwtKeys,
     expiry_days: u64,
 ) -> Result<String, jsonwebtoken::errors::Error> {
     let now = Utc::now();
@@ -197,27 +358,69 @@ pub fn create_refresh_token(
         token_type: TokenType::Refresh,
         roles: vec![],
         organization_id: None,
+        act: None,
+        rti: Some(Uuid::new_v4().to_string()),
     };
 
+    sign_claims(&claims, secret, keys)
+}
+
+/// Sign claims with the keyring's signing key, or with `secret` (HS256)
+/// while tokens are signed with `auth.jwt_secret`
+fn sign_claims(
+    claims: &Claims,
+    secret: &str,
+    keys: &JwtKeys,
+) -> Result<String, jsonwebtoken::errors::Error> {
+    if let Some(keyring) = keys.current() {
+        if let Some(key) = keyring.signing_key() {
+            let mut header = Header::new(key.algorithm);
+            header.kid = Some(key.kid.clone());
+            return encode(&header, claims, &key.encoding_key);
+        }
+    }
+
     encode(
         &Header::default(),
-        &claims,
+        claims,
         &EncodingKey::from_secret(secret.as_bytes()),
     )
 }
 
 /// Validate and decode a JWT token
-pub fn validate_token(token: &str, secret: &str) -> Result<TokenData<Claims>, AuthError> {
-    let mut validation = Validation::default();
+///
+/// Tokens naming a key in `kid` are verified with that key of the keyring,
+/// as long as it is within its grace window; tokens without one with
+/// `secret`.
+pub fn validate_token(
+    token: &str,
+    secret: &str,
+    keys: &JwtKeys,
+) -> Result<TokenData<Claims>, AuthError> {
+    let header = decode_header(token).map_err(|_| AuthError::InvalidToken)?;
+    let keyring = keys.current();
+    let now = Utc::now();
+    let secret_key;
+    let (decoding_key, algorithm) = match (&header.kid, &keyring) {
+        (Some(kid), Some(keyring)) => keyring
+            .verifying_key(kid, now)
+            .ok_or(AuthError::InvalidToken)?,
+        (Some(_), None) => return Err(AuthError::InvalidToken),
+        (None, Some(keyring)) if !keyring.accepts_secret_tokens(now) => {
+            return Err(AuthError::InvalidToken)
+        }
+        (None, _) => {
+            secret_key = DecodingKey::from_secret(secret.as_bytes());
+            (&secret_key, Algorithm::HS256)
+        }
+    };
+
+    // Only the key's own algorithm is accepted
+    let mut validation = Validation::new(algorithm);
     validation.validate_exp = true;
     validation.validate_nbf = true;
 
-    decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(secret.as_bytes()),
-        &validation,
-    )
-    .map_err(|e| match e.kind() {
+    decode::<Claims>(token, decoding_key, &validation).map_err(|e| match e.kind() {
         jsonwebtoken::errors::ErrorKind::ExpiredSignature => AuthError::TokenExpired,
         jsonwebtoken::errors::ErrorKind::InvalidToken => AuthError::InvalidToken,
         _ => AuthError::InvalidToken,
@@ -232,29 +435,42 @@ pub enum AuthError {
     TokenExpired,
     SessionExpired,
     InvalidTokenType,
+    /// Authenticated, but the credential may not be used for this request
+    Forbidden(&'static str),
 }
 
 impl IntoResponse for AuthError {
     fn into_response(self) -> Response {
-        let (status, message) = match self {
-            AuthError::MissingToken => (StatusCode::UNAUTHORIZED, "Missing authentication token"),
-            AuthError::InvalidToken => (StatusCode::UNAUTHORIZED, "Invalid authentication token"),
-            AuthError::TokenExpired => {
-                (StatusCode::UNAUTHORIZED, "Authentication token has expired")
-            }
+        let (status, code, message) = match self {
+            AuthError::MissingToken => (
+                StatusCode::UNAUTHORIZED,
+                "missing_token",
+                "Missing authentication token",
+            ),
+            AuthError::InvalidToken => (
+                StatusCode::UNAUTHORIZED,
+                "invalid_token",
+                "Invalid authentication token",
+            ),
+            AuthError::TokenExpired => (
+                StatusCode::UNAUTHORIZED,
+                "token_expired",
+                "Authentication token has expired",
+            ),
             AuthError::SessionExpired => (
                 StatusCode::UNAUTHORIZED,
+                "session_expired",
                 "Session expired due to inactivity",
             ),
-            AuthError::InvalidTokenType => (StatusCode::UNAUTHORIZED, "Invalid token type"),
+            AuthError::InvalidTokenType => (
+                StatusCode::UNAUTHORIZED,
+                "invalid_token_type",
+                "Invalid token type",
+            ),
+            AuthError::Forbidden(message) => (StatusCode::FORBIDDEN, "forbidden", message),
         };
 
-        let body = ErrorResponse {
-            error: "unauthorized".to_string(),
-            message: message.to_string(),
-            details: None,
-            code: None,
-        };
+        let body = ApiError::for_status(status, message).with_code(code);
 
         (status, Json(body)).into_response()
     }
@@ -300,19 +516,25 @@ pub async fn create_auth_session(
     session_id: &Uuid,
     user_id: &Uuid,
     expires_at: chrono::DateTime<Utc>,
+    client: &SessionClient,
 ) -> Result<(), AuthError> {
     let now = Utc::now().to_rfc3339();
 
     sqlx::query(
         r#"
-        INSERT INTO auth_sessions (id, user_id, last_activity_at, expires_at, revoked_at, created_at, updated_at)
-        VALUES (?1, ?2, ?3, ?4, NULL, ?3, ?3)
+        INSERT INTO auth_sessions (
+            id, user_id, last_activity_at, expires_at, revoked_at, ip_address, user_agent,
+            created_at, updated_at
+        )
+        VALUES (?1, ?2, ?3, ?4, NULL, ?5, ?6, ?3, ?3)
         "#,
     )
     .bind(session_id.to_string())
     .bind(user_id.to_string())
     .bind(&now)
     .bind(expires_at.to_rfc3339())
+    .bind(client.ip_address.as_deref())
+    .bind(client.user_agent.as_deref())
     .execute(pool)
     .await
     .map_err(|_| AuthError::InvalidToken)?;
@@ -335,6 +557,25 @@ pub async fn revoke_auth_session(pool: &SqlitePool, session_id: &str) -> Result<
     Ok(())
 }
 
+/// Revoke every open session of a user
+pub async fn revoke_user_auth_sessions(
+    pool: &SqlitePool,
+    user_id: &Uuid,
+) -> Result<u64, AuthError> {
+    let now = Utc::now().to_rfc3339();
+
+    let result = sqlx::query(
+        "UPDATE auth_sessions SET revoked_at = ?1, updated_at = ?1 WHERE user_id = ?2 AND revoked_at IS NULL",
+    )
+    .bind(&now)
+    .bind(user_id.to_string())
+    .execute(pool)
+    .await
+    .map_err(|_| AuthError::InvalidToken)?;
+
+    Ok(result.rows_affected())
+}
+
 pub async fn ensure_auth_session_active(
     pool: &SqlitePool,
     session_id: &str,
@@ -388,16 +629,37 @@ pub async fn ensure_auth_session_active(
     Ok(())
 }
 
-async fn authenticate_api_key(state: &AppState, token: &str) -> Result<AuthUser, AuthError> {
+/// What an API key is used for, checked against the key's restrictions
+struct ApiKeyUse {
+    method: Method,
+    path: String,
+    client_ip: Option<IpAddr>,
+}
+
+impl ApiKeyUse {
+    fn from_request(request: &Request) -> Self {
+        Self {
+            method: request.method().clone(),
+            path: request.uri().path().to_string(),
+            client_ip: super::client_ip(request.extensions()),
+        }
+    }
+}
+
+async fn authenticate_api_key(
+    state: &AppState,
+    token: &str,
+    key_use: &ApiKeyUse,
+) -> Result<AuthUser, AuthError> {
     let (api_key_id, secret) = parse_ovk(token).ok_or(AuthError::InvalidToken)?;
 
     let row = sqlx::query(
         r#"
-        SELECT ak.user_id, ak.organization_id, ak.key_hash, ak.expires_at,
-               u.username, u.email
+        SELECT ak.user_id, ak.organization_id, ak.key_hash, ak.expires_at, ak.allowed_ips,
+               ak.previous_key_hash, ak.previous_key_expires_at, u.username, u.email
         FROM api_keys ak
         INNER JOIN users u ON u.id = ak.user_id
-        WHERE ak.id = ?
+        WHERE ak.id = ? AND u.deleted_at IS NULL
         "#,
     )
     .bind(api_key_id.to_string())
@@ -419,12 +681,51 @@ async fn authenticate_api_key(state: &AppState, token: &str) -> Result<AuthUser,
         }
     }
 
-    let ok =
+    let mut ok =
         AuthService::verify_password(secret, &key_hash).map_err(|_| AuthError::InvalidToken)?;
+    if !ok {
+        // A rotated key's previous secret stays valid during the grace period
+        let previous_hash: Option<String> = row.try_get("previous_key_hash").ok().flatten();
+        let previous_expires_at: Option<String> =
+            row.try_get("previous_key_expires_at").ok().flatten();
+        let in_grace = previous_expires_at
+            .as_deref()
+            .and_then(parse_db_timestamp)
+            .is_some_and(|until| Utc::now() < until);
+        if let (Some(previous_hash), true) = (previous_hash, in_grace) {
+            ok = AuthService::verify_password(secret, &previous_hash)
+                .map_err(|_| AuthError::InvalidToken)?;
+        }
+    }
     if !ok {
         return Err(AuthError::InvalidToken);
     }
 
+    // Enforce the key's IP allowlist and permission scopes
+    let allowed_ips: Vec<String> = row
+        .try_get::<Option<String>, _>("allowed_ips")
+        .ok()
+        .flatten()
+        .map(|json| serde_json::from_str(&json).map_err(|_| AuthError::InvalidToken))
+        .transpose()?
+        .unwrap_or_default();
+    if !api_key_policy::ip_allowed(&allowed_ips, key_use.client_ip) {
+        return Err(AuthError::Forbidden(
+            "API key is not allowed from this address",
+        ));
+    }
+
+    let scopes = ApiKeyRepository::new(&state.db)
+        .get_scopes(api_key_id)
+        .await
+        .map_err(|_| AuthError::InvalidToken)?;
+    let required = api_key_policy::scope_for_request(&key_use.method, &key_use.path);
+    if !api_key_policy::scopes_allow(&scopes, required) {
+        return Err(AuthError::Forbidden(
+            "API key scopes do not cover this request",
+        ));
+    }
+
     // Fetch key-scoped roles
     let role_rows = sqlx::query(
         r#"
@@ -468,9 +769,55 @@ async fn authenticate_api_key(state: &AppState, token: &str) -> Result<AuthUser,
         session_id: String::new(),
         roles,
         role_ids,
+        api_key_id: Some(api_key_id),
+        impersonator: None,
     })
 }
 
+tokio::task_local! {
+    static IMPERSONATOR: Uuid;
+}
+
+/// Admin impersonating the user of the request handled by the current task
+///
+/// Audit entries record it next to the impersonated user.
+pub fn current_impersonator() -> Option<Uuid> {
+    IMPERSONATOR.try_with(|id| *id).ok()
+}
+
+/// Run a future, typically a spawned task, under the impersonator of a
+/// request
+pub async fn with_impersonator<F: Future>(impersonator: Option<Uuid>, future: F) -> F::Output {
+    match impersonator {
+        Some(id) => IMPERSONATOR.scope(id, future).await,
+        None => future.await,
+    }
+}
+
+/// Whether an impersonation token may make a request
+///
+/// Impersonating shows what a user can see and do; it cannot change the
+/// user's credentials, API keys, sessions or roles through elevation, nor
+/// start another impersonation.
+fn impersonation_allows(method: &Method, path: &str) -> bool {
+    if matches!(*method, Method::GET | Method::HEAD | Method::OPTIONS) {
+        return true;
+    }
+    let path = path.strip_prefix("/api/v1").unwrap_or(path);
+    if path == "/impersonation/end" {
+        return true;
+    }
+    ![
+        "/auth/",
+        "/api-keys",
+        "/sessions",
+        "/elevations",
+        "/impersonation",
+    ]
+    .iter()
+    .any(|prefix| path.starts_with(prefix))
+}
+
 /// Extract token from query string (for SSE/EventSource which can't send headers)
 fn extract_query_token(uri: &axum::http::Uri) -> Option<String> {
     uri.query().and_then(|query| {
@@ -487,6 +834,33 @@ fn extract_query_token(uri: &axum::http::Uri) -> Option<String> {
     })
 }
 
+/// The access token of the session cookie, in cookie auth mode
+fn session_cookie_token<'a>(state: &AppState, headers: &'a HeaderMap) -> Option<&'a str> {
+    if !state.config.auth.cookie_mode() {
+        return None;
+    }
+    session_cookie::request_cookie(headers, session_cookie::SESSION_COOKIE)
+}
+
+/// The user of an access token of an active session
+async fn authenticate_access_token(state: &AppState, token: &str) -> Result<AuthUser, AuthError> {
+    let token_data = validate_token(token, &state.config.auth.jwt_secret, &state.jwt_keys)?;
+    if token_data.claims.token_type != TokenType::Access {
+        return Err(AuthError::InvalidTokenType);
+    }
+    ensure_auth_session_active(&state.db, &token_data.claims.jti, true).await?;
+    let mut user: AuthUser = token_data
+        .claims
+        .try_into()
+        .map_err(|_| AuthError::InvalidToken)?;
+    user.role_ids = user
+        .roles
+        .iter()
+        .filter_map(|name| state.rbac.get_role_by_name(name).map(|r| r.id))
+        .collect();
+    Ok(user)
+}
+
 /// Authentication middleware
 ///
 /// This middleware extracts and validates JWT tokens from the Authorization header.
@@ -496,7 +870,10 @@ pub async fn auth_middleware(
     mut request: Request,
     next: Next,
 ) -> Result<Response, AuthError> {
-    // Try Authorization header first; fall back to X-API-Key, then query param (for SSE)
+    let key_use = ApiKeyUse::from_request(&request);
+
+    // Try Authorization header first; fall back to X-API-Key, then query param
+    // (for SSE), then the session cookie (cookie auth mode)
     let auth_header = request
         .headers()
         .get(AUTHORIZATION)
@@ -504,7 +881,7 @@ pub async fn auth_middleware(
 
     let auth_user = if let Some(auth_header) = auth_header {
         if let Some(token) = extract_bearer_token(auth_header) {
-            let token_data = validate_token(token, &state.config.auth.jwt_secret)?;
+            let token_data = validate_token(token, &state.config.auth.jwt_secret, &state.jwt_keys)?;
             if token_data.claims.token_type != TokenType::Access {
                 return Err(AuthError::InvalidTokenType);
             }
@@ -521,7 +898,7 @@ pub async fn auth_middleware(
             user.role_ids = role_ids;
             user
         } else if let Some(token) = extract_api_key_token(auth_header) {
-            authenticate_api_key(&state, token).await?
+            authenticate_api_key(&state, token, &key_use).await?
         } else {
             return Err(AuthError::InvalidToken);
         }
@@ -532,34 +909,28 @@ pub async fn auth_middleware(
         .or_else(|| request.headers().get("x-api-key"))
         .and_then(|h| h.to_str().ok())
     {
-        authenticate_api_key(&state, token).await?
+        authenticate_api_key(&state, token, &key_use).await?
     } else if let Some(token) = extract_query_token(request.uri()) {
         // Support token in query param for SSE/EventSource (which can't send headers)
-        let token_data = validate_token(&token, &state.config.auth.jwt_secret)?;
-        if token_data.claims.token_type != TokenType::Access {
-            return Err(AuthError::InvalidTokenType);
-        }
-        ensure_auth_session_active(&state.db, &token_data.claims.jti, true).await?;
-        let mut user: AuthUser = token_data
-            .claims
-            .try_into()
-            .map_err(|_| AuthError::InvalidToken)?;
-        let role_ids: Vec<Uuid> = user
-            .roles
-            .iter()
-            .filter_map(|name| state.rbac.get_role_by_name(name).map(|r| r.id))
-            .collect();
-        user.role_ids = role_ids;
-        user
+        authenticate_access_token(&state, &token).await?
+    } else if let Some(token) = session_cookie_token(&state, request.headers()) {
+        authenticate_access_token(&state, token).await?
     } else {
         return Err(AuthError::MissingToken);
     };
 
+    if auth_user.impersonator.is_some() && !impersonation_allows(&key_use.method, &key_use.path) {
+        return Err(AuthError::Forbidden(
+            "Not allowed while impersonating a user",
+        ));
+    }
+    let impersonator = auth_user.impersonator.as_ref().map(|i| i.id);
+
     // Insert the authenticated user into request extensions
     request.extensions_mut().insert(auth_user);
 
     // Continue with the request
-    Ok(next.run(request).await)
+    Ok(with_impersonator(impersonator, next.run(request)).await)
 }
 
 /// Optional authentication middleware
@@ -571,6 +942,8 @@ pub async fn optional_auth_middleware(
     mut request: Request,
     next: Next,
 ) -> Response {
+    let key_use = ApiKeyUse::from_request(&request);
+
     // Try to extract and validate token (Bearer or API key)
     let maybe_auth_header = request
         .headers()
@@ -579,7 +952,9 @@ pub async fn optional_auth_middleware(
 
     let maybe_user: Option<AuthUser> = if let Some(auth_header) = maybe_auth_header {
         if let Some(token) = extract_bearer_token(auth_header) {
-            if let Ok(token_data) = validate_token(token, &state.config.auth.jwt_secret) {
+            if let Ok(token_data) =
+                validate_token(token, &state.config.auth.jwt_secret, &state.jwt_keys)
+            {
                 if token_data.claims.token_type == TokenType::Access {
                     if ensure_auth_session_active(&state.db, &token_data.claims.jti, true)
                         .await
@@ -606,7 +981,7 @@ pub async fn optional_auth_middleware(
                 None
             }
         } else if let Some(token) = extract_api_key_token(auth_header) {
-            authenticate_api_key(&state, token).await.ok()
+            authenticate_api_key(&state, token, &key_use).await.ok()
         } else {
             None
         }
@@ -617,16 +992,22 @@ pub async fn optional_auth_middleware(
         .or_else(|| request.headers().get("x-api-key"))
         .and_then(|h| h.to_str().ok())
     {
-        authenticate_api_key(&state, token).await.ok()
+        authenticate_api_key(&state, token, &key_use).await.ok()
+    } else if let Some(token) = session_cookie_token(&state, request.headers()) {
+        authenticate_access_token(&state, token).await.ok()
     } else {
         None
     };
 
+    let impersonator = maybe_user
+        .as_ref()
+        .and_then(|user| user.impersonator.as_ref())
+        .map(|i| i.id);
     if let Some(auth_user) = maybe_user {
         request.extensions_mut().insert(auth_user);
     }
 
-    next.run(request).await
+    with_impersonator(impersonator, next.run(request)).await
 }
 
 #[cfg(test)]
@@ -648,11 +1029,12 @@ mod tests {
             "test@example.com",
             vec!["admin".to_string()],
             TEST_SECRET,
+            &JwtKeys::default(),
             24,
         )
         .unwrap();
 
-        let validated = validate_token(&token, TEST_SECRET).unwrap();
+        let validated = validate_token(&token, TEST_SECRET, &JwtKeys::default()).unwrap();
         assert_eq!(validated.claims.sub, user_id.to_string());
         assert_eq!(validated.claims.username, "testuser");
         assert_eq!(validated.claims.token_type, TokenType::Access);
@@ -668,17 +1050,31 @@ mod tests {
             "testuser",
             "test@example.com",
             TEST_SECRET,
+            &JwtKeys::default(),
             7,
         )
         .unwrap();
 
-        let validated = validate_token(&token, TEST_SECRET).unwrap();
+        let validated = validate_token(&token, TEST_SECRET, &JwtKeys::default()).unwrap();
         assert_eq!(validated.claims.token_type, TokenType::Refresh);
+
+        // Refresh tokens of a session issued in the same second still differ
+        let again = create_refresh_token(
+            &user_id,
+            &session_id,
+            "testuser",
+            "test@example.com",
+            TEST_SECRET,
+            &JwtKeys::default(),
+            7,
+        )
+        .unwrap();
+        assert_ne!(token, again);
     }
 
     #[test]
     fn test_invalid_token() {
-        let result = validate_token("invalid-token", TEST_SECRET);
+        let result = validate_token("invalid-token", TEST_SECRET, &JwtKeys::default());
         assert!(matches!(result, Err(AuthError::InvalidToken)));
     }
 
@@ -695,11 +1091,16 @@ mod tests {
             "test@example.com",
             vec![],
             TEST_SECRET,
+            &JwtKeys::default(),
             24,
         )
         .unwrap();
 
-        let result = validate_token(&token, "wrong-secret-that-is-also-long-enough");
+        let result = validate_token(
+            &token,
+            "wrong-secret-that-is-also-long-enough",
+            &JwtKeys::default(),
+        );
         assert!(matches!(result, Err(AuthError::InvalidToken)));
     }
 
@@ -725,6 +1126,8 @@ mod tests {
             token_type: TokenType::Access,
             roles: vec!["admin".to_string()],
             organization_id: Some(org_id.to_string()),
+            act: None,
+            rti: None,
         };
 
         let auth_user = AuthUser::try_from(claims).unwrap();
@@ -735,6 +1138,58 @@ mod tests {
         assert!(auth_user.role_ids.is_empty()); // Role IDs are resolved by middleware
     }
 
+    #[test]
+    fn test_impersonation_token() {
+        let user_id = Uuid::new_v4();
+        let actor = Impersonator {
+            id: Uuid::new_v4(),
+            username: "admin".to_string(),
+        };
+        let token = create_impersonation_token(
+            &user_id,
+            &Uuid::new_v4(),
+            &Uuid::new_v4(),
+            "testuser",
+            "test@example.com",
+            vec!["viewer".to_string()],
+            TEST_SECRET,
+            &JwtKeys::default(),
+            Utc::now() + Duration::minutes(30),
+            &actor,
+        )
+        .unwrap();
+
+        let claims = validate_token(&token, TEST_SECRET, &JwtKeys::default())
+            .unwrap()
+            .claims;
+        let auth_user = AuthUser::try_from(claims).unwrap();
+        assert_eq!(auth_user.id, user_id);
+        assert_eq!(auth_user.impersonator, Some(actor));
+    }
+
+    #[test]
+    fn test_impersonation_allows() {
+        assert!(impersonation_allows(&Method::GET, "/api/v1/api-keys"));
+        assert!(impersonation_allows(&Method::POST, "/api/v1/groups"));
+        assert!(impersonation_allows(
+            &Method::POST,
+            "/api/v1/impersonation/end"
+        ));
+        assert!(!impersonation_allows(
+            &Method::POST,
+            "/api/v1/impersonation"
+        ));
+        assert!(!impersonation_allows(
+            &Method::POST,
+            "/api/v1/auth/change-password"
+        ));
+        assert!(!impersonation_allows(&Method::POST, "/api-keys"));
+        assert!(!impersonation_allows(
+            &Method::DELETE,
+            "/api/v1/sessions/abc"
+        ));
+    }
+
     #[test]
     fn test_auth_user_with_role_ids() {
         let user_id = Uuid::new_v4();
@@ -747,6 +1202,8 @@ mod tests {
             session_id: Uuid::new_v4().to_string(),
             roles: vec!["admin".to_string()],
             role_ids: vec![],
+            api_key_id: None,
+            impersonator: None,
         };
 
         let auth_user = auth_user.with_role_ids(vec![role_id]);
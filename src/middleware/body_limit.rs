@@ -0,0 +1,138 @@
+//! Request body size limits
+//!
+//! Bodies are capped per route under `server.body_limits`, so a client cannot
+//! make the service buffer arbitrarily large payloads. A `Content-Length`
+//! over the limit is rejected immediately; streamed bodies are cut off once
+//! they exceed it. Either way the client receives `413 Payload Too Large`
+//! with the usual JSON error body.
+
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use http_body_util::Limited;
+
+use crate::config::BodyLimitsConfig;
+use crate::utils::AppError;
+
+/// Body size limits resolved from the configuration
+#[derive(Debug, Clone)]
+pub struct BodyLimits {
+    max_bytes: usize,
+    /// Path prefixes and their limits, longest prefix first
+    routes: Arc<Vec<(String, usize)>>,
+}
+
+impl Default for BodyLimits {
+    fn default() -> Self {
+        Self::new(&BodyLimitsConfig::default())
+    }
+}
+
+impl BodyLimits {
+    pub fn new(config: &BodyLimitsConfig) -> Self {
+        let mut routes: Vec<(String, usize)> = config
+            .routes
+            .iter()
+            .map(|route| {
+                (
+                    route.path.trim_end_matches('/').to_string(),
+                    route.max_bytes,
+                )
+            })
+            .collect();
+        routes.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+        Self {
+            max_bytes: config.max_bytes,
+            routes: Arc::new(routes),
+        }
+    }
+
+    /// Largest body accepted for a request path
+    pub fn limit_for(&self, path: &str) -> usize {
+        let path = path.strip_prefix("/api/v1").unwrap_or(path);
+        self.routes
+            .iter()
+            .find(|(prefix, _)| {
+                path.strip_prefix(prefix.as_str())
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+            })
+            .map(|(_, limit)| *limit)
+            .unwrap_or(self.max_bytes)
+    }
+}
+
+fn payload_too_large(limit: usize) -> Response {
+    AppError::payload_too_large(format!("Request body exceeds the limit of {} bytes", limit))
+        .into_response()
+}
+
+/// Middleware enforcing the body size limit of the requested route
+///
+/// Axum's built-in 2 MiB limit must be disabled with
+/// `DefaultBodyLimit::disable()` so larger configured limits take effect.
+pub async fn body_limit_middleware(
+    State(limits): State<BodyLimits>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let limit = limits.limit_for(request.uri().path());
+
+    let content_length = request
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if content_length.is_some_and(|len| len > limit as u64) {
+        return payload_too_large(limit);
+    }
+
+    let (parts, body) = request.into_parts();
+    let request = Request::from_parts(parts, Body::new(Limited::new(body, limit)));
+    let response = next.run(request).await;
+
+    // Extractors reject a streamed body that hit the limit with plain text
+    let is_json = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("application/json"));
+    if response.status() == StatusCode::PAYLOAD_TOO_LARGE && !is_json {
+        return payload_too_large(limit);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::BodyLimitRoute;
+
+    #[test]
+    fn test_limit_for_routes() {
+        let limits = BodyLimits::new(&BodyLimitsConfig {
+            max_bytes: 1000,
+            routes: vec![
+                BodyLimitRoute {
+                    path: "/auth".to_string(),
+                    max_bytes: 10,
+                },
+                BodyLimitRoute {
+                    path: "/auth/saml/".to_string(),
+                    max_bytes: 100,
+                },
+            ],
+        });
+
+        assert_eq!(limits.limit_for("/api/v1/auth/login"), 10);
+        assert_eq!(limits.limit_for("/auth"), 10);
+        assert_eq!(limits.limit_for("/api/v1/auth/saml/acs"), 100);
+        assert_eq!(limits.limit_for("/api/v1/authx"), 1000);
+        assert_eq!(limits.limit_for("/api/v1/groups"), 1000);
+    }
+}
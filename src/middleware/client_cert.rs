@@ -14,8 +14,10 @@
 //! - `X-SSL-Client-CN`: The certificate's Common Name (certname)
 //! - `X-SSL-Client-Verify`: Verification status ("SUCCESS", "NONE", "FAILED")
 //!
-//! When running with direct TLS termination, the certificate is extracted from
-//! the TLS connection directly (requires axum-server with rustls).
+//! When running with direct TLS termination and `server.tls.client_auth` set,
+//! the server verifies the certificate itself and [`ClientCertAcceptor`]
+//! records it for every request of the connection. Headers are then ignored,
+//! since no proxy sits between the client and the server.
 //!
 //! ## Configuration Example (nginx)
 //!
@@ -34,11 +36,25 @@
 //! }
 //! ```
 
+use std::io;
+
 use axum::{
     extract::FromRequestParts,
     http::{header::HeaderMap, request::Parts, StatusCode},
+    middleware::AddExtension,
+    Extension,
+};
+use axum_server::{
+    accept::Accept,
+    tls_rustls::{RustlsAcceptor, RustlsConfig},
 };
+use futures::future::BoxFuture;
 use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio_rustls::server::TlsStream;
+use tower::Layer;
+
+use crate::utils::{x509, ApiError};
 
 /// Client certificate information extracted from the request
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -56,6 +72,76 @@ impl ClientCert {
     pub fn matches_certname(&self, certname: &str) -> bool {
         self.cn.eq_ignore_ascii_case(certname)
     }
+
+    /// Certificate a TLS client presented, already verified during the handshake
+    pub fn from_der(der: &[u8]) -> Result<Self, ClientCertError> {
+        let cert = x509::parse_certificate_der(der).map_err(ClientCertError::ParseError)?;
+        let cn = extract_cn_from_dn(&cert.subject).ok_or_else(|| {
+            ClientCertError::ParseError("Certificate subject has no CN".to_string())
+        })?;
+
+        Ok(ClientCert {
+            cn,
+            dn: Some(cert.subject),
+            verified: true,
+        })
+    }
+}
+
+/// Client certificate of a TLS connection terminated by this server
+///
+/// Added to every request of a connection accepted by [`ClientCertAcceptor`];
+/// `None` when the client presented no certificate.
+#[derive(Debug, Clone)]
+pub struct TlsClientCert(pub Option<ClientCert>);
+
+/// TLS acceptor recording the client certificate of each connection
+///
+/// Used instead of the plain rustls acceptor on listeners with
+/// `client_auth`, so handlers see the certificate through [`ClientCert`].
+#[derive(Debug, Clone)]
+pub struct ClientCertAcceptor {
+    inner: RustlsAcceptor,
+}
+
+impl ClientCertAcceptor {
+    pub fn new(config: RustlsConfig) -> Self {
+        Self {
+            inner: RustlsAcceptor::new(config),
+        }
+    }
+}
+
+impl<I, S> Accept<I, S> for ClientCertAcceptor
+where
+    I: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    S: Send + 'static,
+{
+    type Stream = TlsStream<I>;
+    type Service = AddExtension<S, TlsClientCert>;
+    type Future = BoxFuture<'static, io::Result<(Self::Stream, Self::Service)>>;
+
+    fn accept(&self, stream: I, service: S) -> Self::Future {
+        let acceptor = self.inner.clone();
+
+        Box::pin(async move {
+            let (stream, service) = acceptor.accept(stream, service).await?;
+            let cert = stream
+                .get_ref()
+                .1
+                .peer_certificates()
+                .and_then(|chain| chain.first())
+                .and_then(|der| match ClientCert::from_der(der) {
+                    Ok(cert) => Some(cert),
+                    Err(e) => {
+                        tracing::warn!("Ignoring TLS client certificate: {}", e);
+                        None
+                    }
+                });
+
+            Ok((stream, Extension(TlsClientCert(cert)).layer(service)))
+        })
+    }
 }
 
 /// Error returned when client certificate authentication fails
@@ -87,24 +173,25 @@ impl std::error::Error for ClientCertError {}
 
 impl axum::response::IntoResponse for ClientCertError {
     fn into_response(self) -> axum::response::Response {
-        let (status, message) = match self {
+        let (status, code, message) = match self {
             ClientCertError::NoCertificate => (
                 StatusCode::UNAUTHORIZED,
+                "client_cert_required",
                 "Client certificate required for this endpoint",
             ),
             ClientCertError::VerificationFailed => (
                 StatusCode::FORBIDDEN,
+                "client_cert_invalid",
                 "Client certificate verification failed",
             ),
-            ClientCertError::ParseError(_) => {
-                (StatusCode::BAD_REQUEST, "Invalid client certificate format")
-            }
+            ClientCertError::ParseError(_) => (
+                StatusCode::BAD_REQUEST,
+                "client_cert_malformed",
+                "Invalid client certificate format",
+            ),
         };
 
-        let body = serde_json::json!({
-            "error": message,
-            "code": status.as_u16()
-        });
+        let body = ApiError::for_status(status, message).with_code(code);
 
         (status, axum::Json(body)).into_response()
     }
@@ -121,7 +208,7 @@ where
     type Rejection = ClientCertError;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        extract_client_cert(&parts.headers)
+        client_cert_from_parts(parts)
     }
 }
 
@@ -138,7 +225,15 @@ where
     type Rejection = std::convert::Infallible;
 
     async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
-        Ok(OptionalClientCert(extract_client_cert(&parts.headers).ok()))
+        Ok(OptionalClientCert(client_cert_from_parts(parts).ok()))
+    }
+}
+
+/// Certificate verified by this server's TLS listener, or else from headers
+fn client_cert_from_parts(parts: &Parts) -> Result<ClientCert, ClientCertError> {
+    match parts.extensions.get::<TlsClientCert>() {
+        Some(TlsClientCert(cert)) => cert.clone().ok_or(ClientCertError::NoCertificate),
+        None => extract_client_cert(&parts.headers),
     }
 }
 
@@ -336,4 +431,33 @@ mod tests {
         assert!(cert.matches_certname("NODE1.EXAMPLE.COM"));
         assert!(!cert.matches_certname("node2.example.com"));
     }
+
+    #[test]
+    fn test_tls_client_cert_overrides_headers() {
+        let request = axum::http::Request::builder()
+            .header("X-SSL-Client-CN", "spoofed.example.com")
+            .body(())
+            .unwrap();
+        let (mut parts, _) = request.into_parts();
+        assert_eq!(
+            client_cert_from_parts(&parts).unwrap().cn,
+            "spoofed.example.com"
+        );
+
+        parts.extensions.insert(TlsClientCert(None));
+        assert!(matches!(
+            client_cert_from_parts(&parts),
+            Err(ClientCertError::NoCertificate)
+        ));
+
+        parts.extensions.insert(TlsClientCert(Some(ClientCert {
+            cn: "node1.example.com".to_string(),
+            dn: None,
+            verified: true,
+        })));
+        assert_eq!(
+            client_cert_from_parts(&parts).unwrap().cn,
+            "node1.example.com"
+        );
+    }
 }
@@ -0,0 +1,170 @@
+//! Client address behind trusted reverse proxies
+//!
+//! Behind nginx or HAProxy every connection comes from the proxy, which
+//! reports the real client in `X-Forwarded-For` and its client certificate in
+//! `X-SSL-Client-*` headers. Anyone connecting directly could send the same
+//! headers, so they are only honored when the peer is listed in
+//! `server.trusted_proxies`. The middleware records the resulting address as
+//! [`ClientIp`] for rate limiting, sessions and audit logs, and removes the
+//! certificate headers from requests of untrusted peers.
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, State},
+    http::{Extensions, HeaderMap, Request},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::services::api_key_policy::IpRule;
+
+/// Address of the client that made a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClientIp(pub IpAddr);
+
+/// Peers allowed to report the client address and certificate
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    rules: Arc<Vec<IpRule>>,
+}
+
+impl TrustedProxies {
+    /// Parse `server.trusted_proxies`; invalid entries are skipped
+    pub fn new(entries: &[String]) -> Self {
+        Self {
+            rules: Arc::new(entries.iter().filter_map(|e| IpRule::parse(e)).collect()),
+        }
+    }
+
+    pub fn is_trusted(&self, ip: IpAddr) -> bool {
+        self.rules.iter().any(|rule| rule.contains(ip))
+    }
+
+    /// Client address of a request received from `peer`
+    ///
+    /// `X-Forwarded-For` is read from the right, skipping trusted proxies,
+    /// so a client cannot prepend addresses of its own choosing.
+    pub fn client_ip(&self, peer: IpAddr, headers: &HeaderMap) -> IpAddr {
+        if !self.is_trusted(peer) {
+            return peer;
+        }
+
+        let hops: Vec<&str> = headers
+            .get_all("x-forwarded-for")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .collect();
+
+        let mut client = peer;
+        for hop in hops.into_iter().rev() {
+            let Some(ip) = parse_hop(hop) else {
+                break;
+            };
+            client = ip;
+            if !self.is_trusted(ip) {
+                break;
+            }
+        }
+        client
+    }
+}
+
+/// Address of a forwarded hop: `10.0.0.5`, `10.0.0.5:1234` or `[2001:db8::1]:1234`
+fn parse_hop(hop: &str) -> Option<IpAddr> {
+    hop.parse::<IpAddr>()
+        .ok()
+        .or_else(|| hop.parse::<SocketAddr>().ok().map(|addr| addr.ip()))
+}
+
+/// Client address recorded for a request
+///
+/// Falls back to the connection peer where the middleware did not run.
+pub fn client_ip(extensions: &Extensions) -> Option<IpAddr> {
+    extensions
+        .get::<ClientIp>()
+        .map(|ClientIp(ip)| *ip)
+        .or_else(|| {
+            extensions
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip())
+        })
+}
+
+/// Middleware resolving the client address and dropping spoofable headers
+pub async fn client_ip_middleware(
+    State(proxies): State<TrustedProxies>,
+    mut request: Request<Body>,
+    next: Next,
+) -> Response {
+    let peer = request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+        .unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED));
+
+    if !proxies.is_trusted(peer) {
+        let spoofable: Vec<_> = request
+            .headers()
+            .keys()
+            .filter(|name| name.as_str().starts_with("x-ssl-client-"))
+            .cloned()
+            .collect();
+        for name in spoofable {
+            request.headers_mut().remove(&name);
+        }
+    }
+
+    let ip = proxies.client_ip(peer, request.headers());
+    request.extensions_mut().insert(ClientIp(ip));
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(forwarded_for: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-forwarded-for", forwarded_for.parse().unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_client_ip_from_trusted_proxy() {
+        let proxies = TrustedProxies::new(&["127.0.0.1".to_string(), "10.0.0.0/8".to_string()]);
+        let proxy: IpAddr = "127.0.0.1".parse().unwrap();
+
+        assert_eq!(
+            proxies.client_ip(proxy, &headers("203.0.113.7")),
+            "203.0.113.7".parse::<IpAddr>().unwrap()
+        );
+        // Addresses prepended by the client are not trusted
+        assert_eq!(
+            proxies.client_ip(proxy, &headers("1.2.3.4, 203.0.113.7, 10.1.2.3")),
+            "203.0.113.7".parse::<IpAddr>().unwrap()
+        );
+        assert_eq!(
+            proxies.client_ip(proxy, &headers("[2001:db8::1]:443")),
+            "2001:db8::1".parse::<IpAddr>().unwrap()
+        );
+        assert_eq!(proxies.client_ip(proxy, &HeaderMap::new()), proxy);
+        assert_eq!(proxies.client_ip(proxy, &headers("unknown")), proxy);
+    }
+
+    #[test]
+    fn test_client_ip_from_untrusted_peer() {
+        let proxies = TrustedProxies::new(&["127.0.0.1".to_string()]);
+        let peer: IpAddr = "198.51.100.9".parse().unwrap();
+
+        assert_eq!(proxies.client_ip(peer, &headers("203.0.113.7")), peer);
+        assert_eq!(
+            TrustedProxies::default().client_ip("127.0.0.1".parse().unwrap(), &headers("1.2.3.4")),
+            "127.0.0.1".parse::<IpAddr>().unwrap()
+        );
+    }
+}
@@ -0,0 +1,329 @@
+//! CSRF protection (double-submit cookie)
+//!
+//! Enabled with `auth.csrf.enabled`. Every response to a browser that has no
+//! token yet sets one in a cookie readable by the frontend, which echoes it
+//! in the `X-CSRF-Token` header of unsafe requests. Requests whose header
+//! does not match the cookie are rejected with `403` and the code
+//! `csrf_token_invalid`. Token-authenticated API clients (`Authorization` or
+//! `X-API-Key` header) and the configured exempt paths are not checked.
+
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, HeaderMap, HeaderValue, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+
+use super::session_cookie::request_cookie;
+use crate::config::CsrfConfig;
+use crate::utils::ApiError;
+
+/// Cookie carrying the token; readable by the frontend
+pub const CSRF_COOKIE: &str = "openvox_csrf";
+
+/// Header unsafe requests echo the token in
+pub const CSRF_HEADER: &str = "x-csrf-token";
+
+/// CSRF settings resolved from the configuration
+#[derive(Debug, Clone)]
+pub struct CsrfProtection {
+    secure_cookie: bool,
+    /// Exempt paths split into segments; `*` matches any one segment
+    exempt_paths: Arc<Vec<Vec<String>>>,
+}
+
+impl Default for CsrfProtection {
+    fn default() -> Self {
+        Self::from_config(&CsrfConfig::default()).expect("default CSRF settings are valid")
+    }
+}
+
+impl CsrfProtection {
+    pub fn from_config(config: &CsrfConfig) -> anyhow::Result<Self> {
+        let mut exempt_paths = Vec::new();
+        for path in &config.exempt_paths {
+            if !path.starts_with('/') {
+                anyhow::bail!("exempt_paths entry must start with '/': {}", path);
+            }
+            exempt_paths.push(segments(path).map(str::to_string).collect());
+        }
+
+        Ok(Self {
+            secure_cookie: config.secure_cookie,
+            exempt_paths: Arc::new(exempt_paths),
+        })
+    }
+
+    /// Whether requests to `path` are exempt from the check
+    ///
+    /// The path must match an exempt entry as a whole; an entry does not
+    /// exempt the paths below it.
+    pub fn is_exempt(&self, path: &str) -> bool {
+        let path = path.strip_prefix("/api/v1").unwrap_or(path);
+        let path: Vec<&str> = segments(path).collect();
+        self.exempt_paths.iter().any(|pattern| {
+            pattern.len() == path.len()
+                && pattern
+                    .iter()
+                    .zip(&path)
+                    .all(|(expected, s)| expected == "*" || s == expected)
+        })
+    }
+
+    fn set_cookie(&self, token: &str) -> Option<HeaderValue> {
+        // Not HttpOnly: the frontend reads the token to echo it
+        let mut cookie = format!("{}={}; Path=/; SameSite=Strict", CSRF_COOKIE, token);
+        if self.secure_cookie {
+            cookie.push_str("; Secure");
+        }
+        HeaderValue::from_str(&cookie).ok()
+    }
+}
+
+/// The token of the request's cookie, if it has a usable one
+fn cookie_token(headers: &HeaderMap) -> Option<&str> {
+    request_cookie(headers, CSRF_COOKIE).filter(|token| is_token(token))
+}
+
+fn segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/').filter(|segment| !segment.is_empty())
+}
+
+/// Tokens are 32 random bytes, hex encoded
+fn is_token(value: &str) -> bool {
+    value.len() == 64 && value.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn new_token() -> String {
+    hex::encode(rand::random::<[u8; 32]>())
+}
+
+/// Compare without exiting early, so the time taken reveals nothing
+fn tokens_match(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |diff, (x, y)| diff | (x ^ y))
+            == 0
+}
+
+fn is_safe(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET | Method::HEAD | Method::OPTIONS | Method::TRACE
+    )
+}
+
+/// API clients send their credentials in a header a cross-site page cannot
+/// set, so their requests cannot be forged
+fn is_token_authenticated(headers: &HeaderMap) -> bool {
+    headers.contains_key(header::AUTHORIZATION) || headers.contains_key("x-api-key")
+}
+
+fn csrf_rejected() -> Response {
+    let body = ApiError::for_status(
+        StatusCode::FORBIDDEN,
+        "Missing or invalid CSRF token. Reload the page and try again.",
+    )
+    .with_code("csrf_token_invalid");
+    (StatusCode::FORBIDDEN, Json(body)).into_response()
+}
+
+/// Middleware checking the CSRF token of unsafe requests and issuing tokens
+///
+/// Must be applied inside the base path, so paths are relative to it.
+pub async fn csrf_middleware(
+    State(csrf): State<CsrfProtection>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let headers = request.headers();
+    let token_authenticated = is_token_authenticated(headers);
+    let cookie = cookie_token(headers).map(str::to_string);
+
+    if !is_safe(request.method()) && !token_authenticated && !csrf.is_exempt(request.uri().path()) {
+        let sent = headers
+            .get(CSRF_HEADER)
+            .and_then(|value| value.to_str().ok());
+        let valid = match (&cookie, sent) {
+            (Some(cookie), Some(sent)) => tokens_match(cookie, sent.trim()),
+            _ => false,
+        };
+        if !valid {
+            tracing::debug!(
+                "Rejected {} {} without a valid CSRF token",
+                request.method(),
+                request.uri().path()
+            );
+            return csrf_rejected();
+        }
+    }
+
+    let mut response = next.run(request).await;
+    if cookie.is_none() && !token_authenticated {
+        if let Some(value) = csrf.set_cookie(&new_token()) {
+            response.headers_mut().append(header::SET_COOKIE, value);
+        }
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Router};
+    use tower::ServiceExt;
+
+    const TOKEN: &str = "0123456789abcdef0123456789abcdef0123456789abcdef0123456789abcdef";
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/api/v1/nodes",
+                post(|| async { "ok" }).get(|| async { "ok" }),
+            )
+            .route("/api/v1/webhooks/github/{id}", post(|| async { "ok" }))
+            .layer(axum::middleware::from_fn_with_state(
+                CsrfProtection::default(),
+                csrf_middleware,
+            ))
+    }
+
+    async fn send(request: Request<Body>) -> Response {
+        app().oneshot(request).await.unwrap()
+    }
+
+    fn post_nodes() -> axum::http::request::Builder {
+        Request::builder().method("POST").uri("/api/v1/nodes")
+    }
+
+    #[tokio::test]
+    async fn test_issues_cookie() {
+        let response = send(
+            Request::builder()
+                .uri("/api/v1/nodes")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let cookie = response.headers()[header::SET_COOKIE].to_str().unwrap();
+        assert!(cookie.starts_with("openvox_csrf="));
+        assert!(cookie.contains("SameSite=Strict"));
+        assert!(cookie.contains("Secure"));
+        assert!(!cookie.contains("HttpOnly"));
+
+        // Not reissued while the browser has one
+        let response = send(
+            Request::builder()
+                .uri("/api/v1/nodes")
+                .header(
+                    header::COOKIE,
+                    format!("theme=dark; openvox_csrf={}", TOKEN),
+                )
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert!(!response.headers().contains_key(header::SET_COOKIE));
+    }
+
+    #[tokio::test]
+    async fn test_unsafe_requests_need_matching_token() {
+        let response = send(post_nodes().body(Body::empty()).unwrap()).await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let cookie = format!("openvox_csrf={}", TOKEN);
+        let response = send(
+            post_nodes()
+                .header(header::COOKIE, &cookie)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let other = TOKEN.replace('0', "f");
+        let response = send(
+            post_nodes()
+                .header(header::COOKIE, &cookie)
+                .header("x-csrf-token", other)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = send(
+            post_nodes()
+                .header(header::COOKIE, &cookie)
+                .header("x-csrf-token", TOKEN)
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_token_authenticated_and_exempt_requests() {
+        let response = send(
+            post_nodes()
+                .header(header::AUTHORIZATION, "Bearer abc")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key(header::SET_COOKIE));
+
+        let response = send(
+            post_nodes()
+                .header("x-api-key", "ovk_abc")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = send(
+            Request::builder()
+                .method("POST")
+                .uri("/api/v1/webhooks/github/1")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[test]
+    fn test_is_exempt() {
+        let csrf = CsrfProtection::default();
+        assert!(csrf.is_exempt("/api/v1/auth/saml/acs"));
+        assert!(csrf.is_exempt("/api/v1/webhooks/gitlab/42"));
+        assert!(csrf.is_exempt("/api/v1/webhooks/bitbucket/42"));
+        assert!(!csrf.is_exempt("/api/v1/webhooks"));
+        assert!(!csrf.is_exempt("/api/v1/webhooks/42/ping"));
+        assert!(!csrf.is_exempt("/api/v1/webhooks/github/42/extra"));
+        assert!(csrf.is_exempt("/api/v1/nodes/web01.example.com/inventory"));
+        assert!(csrf.is_exempt("/api/v1/nodes/web01/update-jobs/1/targets/2/results"));
+        assert!(!csrf.is_exempt("/api/v1/nodes/web01"));
+        assert!(!csrf.is_exempt("/api/v1/nodes/web01/update-jobs/1"));
+        assert!(!csrf.is_exempt("/api/v1/auth/login"));
+        assert!(!csrf.is_exempt("/api/v1/webhooksx"));
+    }
+
+    #[test]
+    fn test_from_config_rejects_relative_paths() {
+        let config = CsrfConfig {
+            exempt_paths: vec!["webhooks".to_string()],
+            ..Default::default()
+        };
+        assert!(CsrfProtection::from_config(&config).is_err());
+    }
+}
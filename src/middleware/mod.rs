@@ -2,22 +2,57 @@
 //!
 //! This module contains middleware for:
 //! - Authentication (JWT)
+//! - Uniform JSON error bodies
+//! - Audit trail of mutating API calls
+//! - CSRF protection of browser sessions
+//! - Request body size limits
 //! - Authorization (RBAC)
 //! - Rate limiting
+//! - Request IDs for tracing requests across services
 //! - Security headers
+//! - Session cookies of cookie auth mode
+//! - Static asset caching
 //! - Client certificate authentication (mTLS)
+//! - Client address resolution behind trusted proxies
+//! - Opt-in payload capture for debugging integrations
 
+pub mod api_errors;
+pub mod audit;
 pub mod auth;
+pub mod body_limit;
 pub mod client_cert;
+pub mod client_ip;
+pub mod csrf;
+pub mod payload_debug;
 pub mod rate_limit;
 pub mod rbac;
+pub mod request_id;
 pub mod security_headers;
+pub mod session_cookie;
+pub mod static_cache;
 
+pub use api_errors::api_error_middleware;
+pub use audit::{audit_middleware, AuditChange};
 pub use auth::{auth_middleware, optional_auth_middleware, AuthUser, Claims, TokenType};
-pub use client_cert::{ClientCert, ClientCertError, OptionalClientCert};
+pub use body_limit::{body_limit_middleware, BodyLimits};
+pub use client_cert::{
+    ClientCert, ClientCertAcceptor, ClientCertError, OptionalClientCert, TlsClientCert,
+};
+pub use client_ip::{client_ip, client_ip_middleware, ClientIp, TrustedProxies};
+pub use csrf::{csrf_middleware, CsrfProtection};
 pub use rate_limit::{
-    api_rate_limit_config, auth_rate_limit_config, create_rate_limit_state, rate_limit_middleware,
-    spawn_rate_limit_cleanup, RateLimitConfig, RateLimitState,
+    api_rate_limit_config, auth_rate_limit_config, create_rate_limit_state,
+    expensive_rate_limit_config, rate_limit_middleware, spawn_rate_limit_cleanup, RateLimitConfig,
+    RateLimitKey, RateLimitState,
+};
+pub use rbac::{
+    check_group_permission, check_permission, require_permission_middleware, RbacError,
+    RequirePermission,
+};
+pub use request_id::{
+    current_request_id, make_request_span, request_id_middleware, with_request_id, RequestId,
+    REQUEST_ID_HEADER,
+};
+pub use security_headers::{
+    api_cache_control_middleware, security_headers_middleware, SecurityHeaders,
 };
-pub use rbac::{check_permission, require_permission_middleware, RbacError, RequirePermission};
-pub use security_headers::{api_cache_control_middleware, security_headers_middleware};
@@ -0,0 +1,570 @@
+//! Opt-in request/response payload capture
+//!
+//! Admins can start a time-limited capture for a route pattern to see what an
+//! integration (webhook sender, ENC script, API client) actually sends and
+//! receives. Captured exchanges are sanitized (credentials in headers, query
+//! strings and JSON/form bodies are redacted), truncated, and kept in a
+//! bounded in-memory ring buffer; nothing is written to disk.
+
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use axum::{
+    body::{Body, Bytes},
+    extract::State,
+    http::{header, HeaderMap, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Number of exchanges kept in the ring buffer
+pub const CAPTURE_CAPACITY: usize = 200;
+
+/// Longest allowed capture session
+pub const MAX_CAPTURE_DURATION_SECS: u64 = 3600;
+
+/// Default number of body bytes kept per request/response
+pub const DEFAULT_MAX_BODY_BYTES: usize = 16 * 1024;
+
+/// Upper bound for `max_body_bytes`
+pub const MAX_BODY_BYTES_LIMIT: usize = 256 * 1024;
+
+/// Largest body buffered while capturing
+const MAX_BUFFERED_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+/// The capture API itself is never captured, so viewing results with a broad
+/// pattern does not fill the buffer with copies of itself
+const CAPTURE_API_PATH: &str = "/debug/payloads";
+
+pub(crate) const REDACTED: &str = "[REDACTED]";
+
+/// Headers whose values are never captured
+const SENSITIVE_HEADERS: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+    "x-gitlab-token",
+    "x-hub-signature",
+    "x-hub-signature-256",
+    "x-gitea-signature",
+    "x-classification-key",
+    "x-csrf-token",
+];
+
+/// Field names containing any of these are redacted in bodies and query strings
+const SENSITIVE_FIELD_MARKERS: &[&str] = &[
+    "password",
+    "passwd",
+    "secret",
+    "token",
+    "api_key",
+    "apikey",
+    "private_key",
+    "credential",
+    "authorization",
+];
+
+/// Field names redacted only on an exact match, e.g. the plaintext `key` of
+/// created or rotated API and classification keys
+const SENSITIVE_FIELD_NAMES: &[&str] = &["key"];
+
+/// Active capture session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureSession {
+    /// Path pattern; `*` matches one segment, `**` any number of segments
+    pub route_pattern: String,
+    pub started_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub max_body_bytes: usize,
+    /// Username of the admin who started the capture
+    pub started_by: String,
+}
+
+/// A captured request/response pair
+#[derive(Debug, Clone, Serialize)]
+pub struct CapturedExchange {
+    pub id: u64,
+    pub timestamp: DateTime<Utc>,
+    pub method: String,
+    /// Path with sensitive query parameters redacted
+    pub uri: String,
+    pub status: u16,
+    pub duration_ms: u64,
+    pub client_ip: Option<String>,
+    pub request_headers: Vec<(String, String)>,
+    pub request_body: Option<String>,
+    pub request_body_truncated: bool,
+    pub response_headers: Vec<(String, String)>,
+    pub response_body: Option<String>,
+    pub response_body_truncated: bool,
+}
+
+/// Capture status and buffered exchanges
+#[derive(Debug, Clone, Serialize)]
+pub struct PayloadDebugSnapshot {
+    pub active: bool,
+    pub session: Option<CaptureSession>,
+    pub capacity: usize,
+    pub exchanges: Vec<CapturedExchange>,
+}
+
+#[derive(Default)]
+struct RecorderState {
+    session: Option<CaptureSession>,
+    exchanges: VecDeque<CapturedExchange>,
+    next_id: u64,
+}
+
+/// Shared recorder holding the capture session and ring buffer
+#[derive(Clone, Default)]
+pub struct PayloadDebugRecorder {
+    inner: Arc<Mutex<RecorderState>>,
+}
+
+impl PayloadDebugRecorder {
+    /// Start (or replace) the capture session
+    pub fn start(&self, session: CaptureSession) {
+        let mut state = self.inner.lock().unwrap();
+        tracing::warn!(
+            "Payload capture started by '{}' for '{}' until {}",
+            session.started_by,
+            session.route_pattern,
+            session.expires_at
+        );
+        state.session = Some(session);
+    }
+
+    /// Stop capturing; buffered exchanges are kept until cleared
+    pub fn stop(&self) {
+        let mut state = self.inner.lock().unwrap();
+        if state.session.take().is_some() {
+            tracing::info!("Payload capture stopped");
+        }
+    }
+
+    /// Drop all buffered exchanges
+    pub fn clear(&self) {
+        self.inner.lock().unwrap().exchanges.clear();
+    }
+
+    /// Current session and buffered exchanges (newest first)
+    pub fn snapshot(&self) -> PayloadDebugSnapshot {
+        let mut state = self.inner.lock().unwrap();
+        expire_session(&mut state);
+        PayloadDebugSnapshot {
+            active: state.session.is_some(),
+            session: state.session.clone(),
+            capacity: CAPTURE_CAPACITY,
+            exchanges: state.exchanges.iter().rev().cloned().collect(),
+        }
+    }
+
+    /// Body limit for `path` if it is being captured
+    fn capture_limit(&self, path: &str) -> Option<usize> {
+        if path.contains(CAPTURE_API_PATH) {
+            return None;
+        }
+        let mut state = self.inner.lock().unwrap();
+        expire_session(&mut state);
+        state
+            .session
+            .as_ref()
+            .filter(|session| route_matches(&session.route_pattern, path))
+            .map(|session| session.max_body_bytes)
+    }
+
+    fn record(&self, mut exchange: CapturedExchange) {
+        let mut state = self.inner.lock().unwrap();
+        state.next_id += 1;
+        exchange.id = state.next_id;
+        if state.exchanges.len() >= CAPTURE_CAPACITY {
+            state.exchanges.pop_front();
+        }
+        state.exchanges.push_back(exchange);
+    }
+}
+
+fn expire_session(state: &mut RecorderState) {
+    if state
+        .session
+        .as_ref()
+        .is_some_and(|session| session.expires_at <= Utc::now())
+    {
+        tracing::info!("Payload capture expired");
+        state.session = None;
+    }
+}
+
+/// Match a path against a pattern where `*` is one segment and `**` any number
+pub fn route_matches(pattern: &str, path: &str) -> bool {
+    fn matches(pattern: &[&str], path: &[&str]) -> bool {
+        match (pattern.first(), path.first()) {
+            (None, None) => true,
+            (Some(&"**"), _) => {
+                matches(&pattern[1..], path) || (!path.is_empty() && matches(pattern, &path[1..]))
+            }
+            (Some(&"*"), Some(_)) => matches(&pattern[1..], &path[1..]),
+            (Some(p), Some(s)) if p == s => matches(&pattern[1..], &path[1..]),
+            _ => false,
+        }
+    }
+
+    let pattern: Vec<&str> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let path: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    matches(&pattern, &path)
+}
+
+pub(crate) fn is_sensitive_field(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    SENSITIVE_FIELD_NAMES.contains(&name.as_str())
+        || SENSITIVE_FIELD_MARKERS
+            .iter()
+            .any(|marker| name.contains(marker))
+}
+
+fn sanitize_headers(headers: &HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            let value = if SENSITIVE_HEADERS.contains(&name.as_str()) {
+                REDACTED.to_string()
+            } else {
+                String::from_utf8_lossy(value.as_bytes()).into_owned()
+            };
+            (name.to_string(), value)
+        })
+        .collect()
+}
+
+/// Redact sensitive `key=value` pairs of a query string or form body
+fn sanitize_form(input: &str) -> String {
+    input
+        .split('&')
+        .map(|pair| match pair.split_once('=') {
+            Some((key, _)) if is_sensitive_field(key) => format!("{}={}", key, REDACTED),
+            _ => pair.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+pub(crate) fn sanitize_json(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                if is_sensitive_field(key) && !value.is_object() && !value.is_array() {
+                    *value = serde_json::Value::String(REDACTED.to_string());
+                } else {
+                    sanitize_json(value);
+                }
+            }
+        }
+        serde_json::Value::Array(items) => items.iter_mut().for_each(sanitize_json),
+        _ => {}
+    }
+}
+
+/// Sanitize and truncate a body for display
+///
+/// Returns `None` for empty bodies and the truncation flag.
+pub fn sanitize_body(headers: &HeaderMap, body: &[u8], max_bytes: usize) -> (Option<String>, bool) {
+    if body.is_empty() {
+        return (None, false);
+    }
+
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+
+    let text = if let Ok(mut json) = serde_json::from_slice::<serde_json::Value>(body) {
+        sanitize_json(&mut json);
+        json.to_string()
+    } else if content_type.starts_with("application/x-www-form-urlencoded") {
+        sanitize_form(&String::from_utf8_lossy(body))
+    } else if std::str::from_utf8(body).is_ok() {
+        String::from_utf8_lossy(body).into_owned()
+    } else {
+        return (
+            Some(format!("<{} bytes of binary data>", body.len())),
+            false,
+        );
+    };
+
+    if text.len() <= max_bytes {
+        return (Some(text), false);
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    (Some(text[..end].to_string()), true)
+}
+
+fn sanitize_uri(uri: &axum::http::Uri) -> String {
+    match uri.query() {
+        Some(query) => format!("{}?{}", uri.path(), sanitize_form(query)),
+        None => uri.path().to_string(),
+    }
+}
+
+/// Middleware capturing matching exchanges while a session is active
+///
+/// Streaming responses (server-sent events) are recorded without their body.
+pub async fn payload_debug_middleware(
+    State(recorder): State<PayloadDebugRecorder>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let Some(max_body_bytes) = recorder.capture_limit(request.uri().path()) else {
+        return next.run(request).await;
+    };
+
+    let started = Instant::now();
+    let timestamp = Utc::now();
+    let method = request.method().to_string();
+    let uri = sanitize_uri(request.uri());
+    let client_ip = super::client_ip(request.extensions()).map(|ip| ip.to_string());
+
+    let (parts, body) = request.into_parts();
+    let request_bytes = match axum::body::to_bytes(body, MAX_BUFFERED_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(_) => return StatusCode::PAYLOAD_TOO_LARGE.into_response(),
+    };
+    let request_headers = sanitize_headers(&parts.headers);
+    let (request_body, request_body_truncated) =
+        sanitize_body(&parts.headers, &request_bytes, max_body_bytes);
+
+    let response = next
+        .run(Request::from_parts(parts, Body::from(request_bytes)))
+        .await;
+
+    let (parts, body) = response.into_parts();
+    let streaming = parts
+        .headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|ct| ct.starts_with("text/event-stream"));
+
+    let (response_bytes, body) = if streaming {
+        (Bytes::new(), body)
+    } else {
+        match axum::body::to_bytes(body, MAX_BUFFERED_BODY_BYTES).await {
+            Ok(bytes) => (bytes.clone(), Body::from(bytes)),
+            Err(e) => {
+                tracing::warn!("Payload capture could not buffer response: {}", e);
+                return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+            }
+        }
+    };
+
+    let (response_body, response_body_truncated) =
+        sanitize_body(&parts.headers, &response_bytes, max_body_bytes);
+
+    recorder.record(CapturedExchange {
+        id: 0,
+        timestamp,
+        method,
+        uri,
+        status: parts.status.as_u16(),
+        duration_ms: started.elapsed().as_millis() as u64,
+        client_ip,
+        request_headers,
+        request_body,
+        request_body_truncated,
+        response_headers: sanitize_headers(&parts.headers),
+        response_body,
+        response_body_truncated,
+    });
+
+    Response::from_parts(parts, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Router};
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_route_matches() {
+        assert!(route_matches(
+            "/api/v1/nodes/*/classify",
+            "/api/v1/nodes/web01/classify"
+        ));
+        assert!(!route_matches(
+            "/api/v1/nodes/*/classify",
+            "/api/v1/nodes/web01/facts"
+        ));
+        assert!(route_matches(
+            "/api/v1/webhooks/**",
+            "/api/v1/webhooks/github/abc"
+        ));
+        assert!(route_matches("/api/v1/webhooks/**", "/api/v1/webhooks"));
+        assert!(route_matches("/**", "/api/v1/anything"));
+        assert!(!route_matches("/api/v1/nodes", "/api/v1/nodes/web01"));
+    }
+
+    #[test]
+    fn test_sanitize_body_redacts_secrets() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONTENT_TYPE, "application/json".parse().unwrap());
+        let body = br#"{"username":"admin","password":"hunter2","nested":{"api_token":"abc"}}"#;
+
+        let (text, truncated) = sanitize_body(&headers, body, 1024);
+        let text = text.unwrap();
+        assert!(!truncated);
+        assert!(text.contains("admin"));
+        assert!(!text.contains("hunter2"));
+        assert!(!text.contains("abc"));
+
+        assert_eq!(
+            sanitize_form("user=bob&password=x&token=y"),
+            "user=bob&password=[REDACTED]&token=[REDACTED]"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_body_truncates() {
+        let (text, truncated) = sanitize_body(&HeaderMap::new(), "é".repeat(10).as_bytes(), 5);
+        assert!(truncated);
+        assert_eq!(text.unwrap(), "éé");
+    }
+
+    #[tokio::test]
+    async fn test_middleware_records_matching_requests() {
+        let recorder = PayloadDebugRecorder::default();
+        recorder.start(CaptureSession {
+            route_pattern: "/hooks/*".to_string(),
+            started_at: Utc::now(),
+            expires_at: Utc::now() + chrono::Duration::minutes(5),
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            started_by: "admin".to_string(),
+        });
+
+        let app = Router::new()
+            .route("/hooks/{name}", post(|body: String| async move { body }))
+            .route("/other", post(|| async { "ok" }))
+            .layer(axum::middleware::from_fn_with_state(
+                recorder.clone(),
+                payload_debug_middleware,
+            ));
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/hooks/github?secret=s3")
+            .header("authorization", "Bearer xyz")
+            .body(Body::from("payload"))
+            .unwrap();
+        let response = app.clone().oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"payload");
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/other")
+            .body(Body::empty())
+            .unwrap();
+        app.oneshot(request).await.unwrap();
+
+        let snapshot = recorder.snapshot();
+        assert_eq!(snapshot.exchanges.len(), 1);
+        let exchange = &snapshot.exchanges[0];
+        assert_eq!(exchange.uri, "/hooks/github?secret=[REDACTED]");
+        assert_eq!(exchange.request_body.as_deref(), Some("payload"));
+        assert_eq!(exchange.response_body.as_deref(), Some("payload"));
+        assert!(exchange
+            .request_headers
+            .contains(&("authorization".to_string(), REDACTED.to_string())));
+    }
+
+    #[tokio::test]
+    async fn test_created_keys_are_not_captured() {
+        let recorder = PayloadDebugRecorder::default();
+        recorder.start(CaptureSession {
+            route_pattern: "/api/v1/api-keys".to_string(),
+            started_at: Utc::now(),
+            expires_at: Utc::now() + chrono::Duration::minutes(5),
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            started_by: "admin".to_string(),
+        });
+
+        let app = Router::new()
+            .route(
+                "/api/v1/api-keys",
+                post(|| async {
+                    axum::Json(serde_json::json!({
+                        "id": "0b7f4a52-54d4-4d8e-9a53-bd0fdc1e1c0c",
+                        "name": "ci",
+                        "key_prefix": "ovx_1a2b",
+                        "key": "ovx_1a2b3c4d5e6f7a8b9c0d",
+                    }))
+                }),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                recorder.clone(),
+                payload_debug_middleware,
+            ));
+
+        let request = Request::builder()
+            .method("POST")
+            .uri("/api/v1/api-keys")
+            .header(header::CONTENT_TYPE, "application/json")
+            .header("x-csrf-token", "f00dfeed")
+            .body(Body::from(r#"{"name":"ci"}"#))
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        // The client still receives the key
+        assert!(String::from_utf8_lossy(&body).contains("ovx_1a2b3c4d5e6f7a8b9c0d"));
+
+        let snapshot = recorder.snapshot();
+        let exchange = &snapshot.exchanges[0];
+        let response_body = exchange.response_body.as_deref().unwrap();
+        assert!(!response_body.contains("ovx_1a2b3c4d5e6f7a8b9c0d"));
+        assert!(response_body.contains(r#""key":"[REDACTED]""#));
+        assert!(response_body.contains(r#""key_prefix":"ovx_1a2b""#));
+        assert!(exchange
+            .request_headers
+            .contains(&("x-csrf-token".to_string(), REDACTED.to_string())));
+    }
+
+    #[test]
+    fn test_expired_session_is_dropped() {
+        let recorder = PayloadDebugRecorder::default();
+        recorder.start(CaptureSession {
+            route_pattern: "/**".to_string(),
+            started_at: Utc::now(),
+            expires_at: Utc::now() - chrono::Duration::seconds(1),
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            started_by: "admin".to_string(),
+        });
+
+        assert!(recorder.capture_limit("/api/v1/nodes").is_none());
+        assert!(!recorder.snapshot().active);
+    }
+
+    #[test]
+    fn test_capture_api_is_not_captured() {
+        let recorder = PayloadDebugRecorder::default();
+        recorder.start(CaptureSession {
+            route_pattern: "/**".to_string(),
+            started_at: Utc::now(),
+            expires_at: Utc::now() + chrono::Duration::minutes(5),
+            max_body_bytes: DEFAULT_MAX_BODY_BYTES,
+            started_by: "admin".to_string(),
+        });
+
+        assert!(recorder.capture_limit("/api/v1/nodes").is_some());
+        assert!(recorder.capture_limit("/api/v1/debug/payloads").is_none());
+    }
+}
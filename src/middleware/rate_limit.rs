@@ -1,38 +1,52 @@
 //! Rate limiting middleware
 //!
-//! Provides IP-based rate limiting to protect against brute force attacks
-//! and API abuse. Uses the governor crate with a keyed rate limiter.
+//! Protects against brute force attacks and API abuse with a token bucket
+//! per client, using the governor crate. Requests authenticated with an API
+//! key are counted per key, other authenticated requests per user and
+//! anonymous requests per client address. A limit can be restricted to some
+//! API paths, which gives expensive endpoints a bucket of their own.
+//! Responses carry the standard `RateLimit-Limit`, `RateLimit-Remaining` and
+//! `RateLimit-Reset` headers.
 
 use axum::{
     body::Body,
-    extract::{ConnectInfo, Request, State},
-    http::StatusCode,
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
+    Json,
 };
 use governor::{
-    clock::DefaultClock,
-    middleware::NoOpMiddleware,
+    clock::{Clock, DefaultClock},
+    middleware::StateInformationMiddleware,
     state::{InMemoryState, NotKeyed},
     Quota, RateLimiter,
 };
+use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
-    net::{IpAddr, SocketAddr},
+    net::{IpAddr, Ipv4Addr},
     num::NonZeroU32,
     sync::Arc,
     time::Duration,
 };
 use tokio::sync::RwLock;
-use tracing::{debug, warn};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use super::AuthUser;
+use crate::utils::ApiError;
 
 /// Rate limiter configuration
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RateLimitConfig {
     /// Maximum requests per window
     pub requests_per_second: u32,
     /// Burst capacity (maximum requests allowed at once)
     pub burst_size: u32,
+    /// API paths (relative to `/api/v1`) the limit applies to; all when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub paths: Vec<String>,
 }
 
 impl Default for RateLimitConfig {
@@ -40,15 +54,37 @@ impl Default for RateLimitConfig {
         Self {
             requests_per_second: 10,
             burst_size: 30,
+            paths: Vec::new(),
         }
     }
 }
 
+impl RateLimitConfig {
+    /// Whether requests to `path` count against this limit
+    pub fn applies_to(&self, path: &str) -> bool {
+        let path = path.strip_prefix("/api/v1").unwrap_or(path);
+        self.paths.is_empty()
+            || self.paths.iter().any(|prefix| {
+                let prefix = prefix.trim_end_matches('/');
+                path == prefix
+                    || path
+                        .strip_prefix(prefix)
+                        .is_some_and(|rest| rest.starts_with('/'))
+            })
+    }
+
+    fn quota(&self) -> Quota {
+        Quota::per_second(NonZeroU32::new(self.requests_per_second).unwrap_or(NonZeroU32::MIN))
+            .allow_burst(NonZeroU32::new(self.burst_size).unwrap_or(NonZeroU32::MIN))
+    }
+}
+
 /// Stricter rate limit for authentication endpoints
 pub fn auth_rate_limit_config() -> RateLimitConfig {
     RateLimitConfig {
         requests_per_second: 1,
         burst_size: 5,
+        paths: Vec::new(),
     }
 }
 
@@ -57,19 +93,70 @@ pub fn api_rate_limit_config() -> RateLimitConfig {
     RateLimitConfig {
         requests_per_second: 50,
         burst_size: 100,
+        paths: Vec::new(),
+    }
+}
+
+/// Additional limit for expensive endpoints (PQL queries and reports)
+pub fn expensive_rate_limit_config() -> RateLimitConfig {
+    RateLimitConfig {
+        requests_per_second: 5,
+        burst_size: 30,
+        paths: vec!["/query".to_string(), "/reports".to_string()],
+    }
+}
+
+/// Who a request is counted against
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RateLimitKey {
+    Ip(IpAddr),
+    User(Uuid),
+    ApiKey(Uuid),
+}
+
+impl RateLimitKey {
+    /// The API key, user or client address of a request
+    pub fn for_request(request: &Request<Body>) -> Self {
+        match request.extensions().get::<AuthUser>() {
+            Some(AuthUser {
+                api_key_id: Some(id),
+                ..
+            }) => RateLimitKey::ApiKey(*id),
+            Some(user) => RateLimitKey::User(user.id),
+            None => RateLimitKey::Ip(
+                super::client_ip(request.extensions()).unwrap_or(IpAddr::V4(Ipv4Addr::UNSPECIFIED)),
+            ),
+        }
+    }
+}
+
+impl From<IpAddr> for RateLimitKey {
+    fn from(ip: IpAddr) -> Self {
+        RateLimitKey::Ip(ip)
     }
 }
 
-/// Per-IP rate limiter using governor
-pub type IpRateLimiter = RateLimiter<NotKeyed, InMemoryState, DefaultClock, NoOpMiddleware>;
+impl std::fmt::Display for RateLimitKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RateLimitKey::Ip(ip) => write!(f, "ip {}", ip),
+            RateLimitKey::User(id) => write!(f, "user {}", id),
+            RateLimitKey::ApiKey(id) => write!(f, "API key {}", id),
+        }
+    }
+}
 
-/// Thread-safe map of IP addresses to their rate limiters
+/// Per-client rate limiter using governor
+pub type ClientRateLimiter =
+    RateLimiter<NotKeyed, InMemoryState, DefaultClock, StateInformationMiddleware>;
+
+/// Thread-safe map of clients to their rate limiters
 #[derive(Clone)]
 pub struct RateLimitState {
-    /// Map of IP to rate limiter
-    limiters: Arc<RwLock<HashMap<IpAddr, Arc<IpRateLimiter>>>>,
+    /// Map of client to rate limiter
+    limiters: Arc<RwLock<HashMap<RateLimitKey, Arc<ClientRateLimiter>>>>,
     /// Configuration for creating new limiters
-    config: RateLimitConfig,
+    config: Arc<std::sync::RwLock<RateLimitConfig>>,
 }
 
 impl RateLimitState {
@@ -77,16 +164,37 @@ impl RateLimitState {
     pub fn new(config: RateLimitConfig) -> Self {
         Self {
             limiters: Arc::new(RwLock::new(HashMap::new())),
-            config,
+            config: Arc::new(std::sync::RwLock::new(config)),
         }
     }
 
-    /// Get or create a rate limiter for the given IP address
-    async fn get_limiter(&self, ip: IpAddr) -> Arc<IpRateLimiter> {
+    /// The configuration new limiters are created with
+    pub fn config(&self) -> RateLimitConfig {
+        self.config.read().unwrap().clone()
+    }
+
+    /// Change the limits at runtime
+    ///
+    /// Existing per-client limiters are dropped so every client gets the new
+    /// limits on its next request.
+    pub async fn set_config(&self, config: RateLimitConfig) {
+        if self.config() == config {
+            return;
+        }
+        info!(
+            "Rate limit changed to {}/s (burst {})",
+            config.requests_per_second, config.burst_size
+        );
+        *self.config.write().unwrap() = config;
+        self.limiters.write().await.clear();
+    }
+
+    /// Get or create the rate limiter of a client
+    async fn get_limiter(&self, key: RateLimitKey) -> Arc<ClientRateLimiter> {
         // Try to get existing limiter with read lock first
         {
             let limiters = self.limiters.read().await;
-            if let Some(limiter) = limiters.get(&ip) {
+            if let Some(limiter) = limiters.get(&key) {
                 return limiter.clone();
             }
         }
@@ -95,18 +203,14 @@ impl RateLimitState {
         let mut limiters = self.limiters.write().await;
 
         // Double-check after acquiring write lock
-        if let Some(limiter) = limiters.get(&ip) {
+        if let Some(limiter) = limiters.get(&key) {
             return limiter.clone();
         }
 
         // Create new limiter
-        let quota = Quota::per_second(
-            NonZeroU32::new(self.config.requests_per_second).unwrap_or(NonZeroU32::MIN),
-        )
-        .allow_burst(NonZeroU32::new(self.config.burst_size).unwrap_or(NonZeroU32::MIN));
-
-        let limiter = Arc::new(RateLimiter::direct(quota));
-        limiters.insert(ip, limiter.clone());
+        let quota = self.config().quota();
+        let limiter = Arc::new(RateLimiter::direct(quota).with_middleware());
+        limiters.insert(key, limiter.clone());
         limiter
     }
 
@@ -118,15 +222,15 @@ impl RateLimitState {
 
         // Remove limiters that haven't been used recently
         // In practice, governor limiters don't have an "idle" concept,
-        // so we just limit the total number of tracked IPs
-        const MAX_TRACKED_IPS: usize = 10000;
+        // so we just limit the total number of tracked clients
+        const MAX_TRACKED_CLIENTS: usize = 10000;
 
-        if limiters.len() > MAX_TRACKED_IPS {
+        if limiters.len() > MAX_TRACKED_CLIENTS {
             // Remove oldest entries (in practice, just clear half)
             let to_remove: Vec<_> = limiters.keys().take(limiters.len() / 2).cloned().collect();
 
-            for ip in to_remove {
-                limiters.remove(&ip);
+            for key in to_remove {
+                limiters.remove(&key);
             }
 
             debug!(
@@ -139,42 +243,88 @@ impl RateLimitState {
 }
 
 /// Rate limiting middleware for Axum
+///
+/// Apply it inside the auth middleware to count authenticated requests per
+/// user or API key; otherwise requests are counted per client address.
 pub async fn rate_limit_middleware(
     State(rate_limit): State<RateLimitState>,
-    ConnectInfo(addr): ConnectInfo<SocketAddr>,
     request: Request<Body>,
     next: Next,
 ) -> Response {
-    let ip = addr.ip();
-    let limiter = rate_limit.get_limiter(ip).await;
+    let config = rate_limit.config();
+    if !config.applies_to(request.uri().path()) {
+        return next.run(request).await;
+    }
+
+    let key = RateLimitKey::for_request(&request);
+    let limiter = rate_limit.get_limiter(key).await;
 
     match limiter.check() {
-        Ok(_) => {
-            debug!(ip = %ip, "Rate limit check passed");
-            next.run(request).await
+        Ok(snapshot) => {
+            debug!(client = %key, "Rate limit check passed");
+            let mut response = next.run(request).await;
+            let remaining = snapshot.remaining_burst_capacity();
+            set_rate_limit_headers(response.headers_mut(), &config, remaining);
+            response
         }
-        Err(_) => {
-            warn!(ip = %ip, "Rate limit exceeded");
-            RateLimitExceeded.into_response()
+        Err(not_until) => {
+            warn!(client = %key, "Rate limit exceeded");
+            let wait = not_until.wait_time_from(limiter.clock().now());
+            RateLimitExceeded {
+                config,
+                retry_after: wait,
+            }
+            .into_response()
         }
     }
 }
 
+/// Add the `RateLimit-*` headers
+///
+/// The reset time is how long the bucket takes to refill completely. A
+/// response passing several limits reports the one with fewest requests left.
+fn set_rate_limit_headers(headers: &mut HeaderMap, config: &RateLimitConfig, remaining: u32) {
+    let already_remaining = headers
+        .get("ratelimit-remaining")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u32>().ok());
+    if already_remaining.is_some_and(|other| other <= remaining) {
+        return;
+    }
+
+    let used = config.burst_size.max(1).saturating_sub(remaining);
+    let reset = config.quota().replenish_interval() * used;
+    headers.insert(
+        "ratelimit-limit",
+        HeaderValue::from(config.burst_size.max(1)),
+    );
+    headers.insert("ratelimit-remaining", HeaderValue::from(remaining));
+    headers.insert("ratelimit-reset", HeaderValue::from(ceil_secs(reset)));
+}
+
+fn ceil_secs(duration: Duration) -> u64 {
+    duration.as_secs() + u64::from(duration.subsec_nanos() > 0)
+}
+
 /// Rate limit exceeded response
-pub struct RateLimitExceeded;
+pub struct RateLimitExceeded {
+    config: RateLimitConfig,
+    retry_after: Duration,
+}
 
 impl IntoResponse for RateLimitExceeded {
     fn into_response(self) -> Response {
-        (
+        let retry_after = ceil_secs(self.retry_after).max(1);
+        let body = ApiError::for_status(
             StatusCode::TOO_MANY_REQUESTS,
-            [
-                ("Retry-After", "1"),
-                ("X-RateLimit-Limit", ""),
-                ("X-RateLimit-Remaining", "0"),
-            ],
             "Too many requests. Please try again later.",
         )
-            .into_response()
+        .with_code("rate_limited");
+        let mut response = (StatusCode::TOO_MANY_REQUESTS, Json(body)).into_response();
+        let headers = response.headers_mut();
+        set_rate_limit_headers(headers, &self.config, 0);
+        headers.insert("retry-after", HeaderValue::from(retry_after));
+        response
     }
 }
 
@@ -217,11 +367,12 @@ mod tests {
         let config = RateLimitConfig {
             requests_per_second: 10,
             burst_size: 20,
+            paths: Vec::new(),
         };
         let state = RateLimitState::new(config);
 
         let ip: IpAddr = "127.0.0.1".parse().unwrap();
-        let limiter = state.get_limiter(ip).await;
+        let limiter = state.get_limiter(ip.into()).await;
 
         // Should allow the first request
         assert!(limiter.check().is_ok());
@@ -232,11 +383,12 @@ mod tests {
         let config = RateLimitConfig {
             requests_per_second: 1,
             burst_size: 3,
+            paths: Vec::new(),
         };
         let state = RateLimitState::new(config);
 
         let ip: IpAddr = "192.168.1.1".parse().unwrap();
-        let limiter = state.get_limiter(ip).await;
+        let limiter = state.get_limiter(ip.into()).await;
 
         // Should allow burst_size requests
         assert!(limiter.check().is_ok());
@@ -252,14 +404,15 @@ mod tests {
         let config = RateLimitConfig {
             requests_per_second: 1,
             burst_size: 1,
+            paths: Vec::new(),
         };
         let state = RateLimitState::new(config);
 
         let ip1: IpAddr = "192.168.1.1".parse().unwrap();
         let ip2: IpAddr = "192.168.1.2".parse().unwrap();
 
-        let limiter1 = state.get_limiter(ip1).await;
-        let limiter2 = state.get_limiter(ip2).await;
+        let limiter1 = state.get_limiter(ip1.into()).await;
+        let limiter2 = state.get_limiter(ip2.into()).await;
 
         // Exhaust ip1's limit
         assert!(limiter1.check().is_ok());
@@ -268,4 +421,84 @@ mod tests {
         // ip2 should still have its own limit
         assert!(limiter2.check().is_ok());
     }
+
+    #[tokio::test]
+    async fn test_set_config_resets_limiters() {
+        let state = RateLimitState::new(RateLimitConfig {
+            requests_per_second: 1,
+            burst_size: 1,
+            paths: Vec::new(),
+        });
+        let ip: IpAddr = "10.0.0.1".parse().unwrap();
+
+        assert!(state.get_limiter(ip.into()).await.check().is_ok());
+        assert!(state.get_limiter(ip.into()).await.check().is_err());
+
+        state
+            .set_config(RateLimitConfig {
+                requests_per_second: 1,
+                burst_size: 3,
+                paths: Vec::new(),
+            })
+            .await;
+        let limiter = state.get_limiter(ip.into()).await;
+        assert!(limiter.check().is_ok());
+        assert!(limiter.check().is_ok());
+        assert!(limiter.check().is_ok());
+        assert!(limiter.check().is_err());
+    }
+
+    #[test]
+    fn test_applies_to_paths() {
+        let config = expensive_rate_limit_config();
+        assert!(config.applies_to("/query"));
+        assert!(config.applies_to("/reports/abc"));
+        assert!(config.applies_to("/api/v1/query/validate"));
+        assert!(!config.applies_to("/reportsx"));
+        assert!(!config.applies_to("/nodes"));
+        assert!(api_rate_limit_config().applies_to("/nodes"));
+    }
+
+    #[tokio::test]
+    async fn test_users_and_api_keys_have_separate_limits() {
+        let state = RateLimitState::new(RateLimitConfig {
+            requests_per_second: 1,
+            burst_size: 1,
+            paths: Vec::new(),
+        });
+        let user = RateLimitKey::User(Uuid::new_v4());
+        let api_key = RateLimitKey::ApiKey(Uuid::new_v4());
+
+        assert!(state.get_limiter(user).await.check().is_ok());
+        assert!(state.get_limiter(user).await.check().is_err());
+        assert!(state.get_limiter(api_key).await.check().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_headers() {
+        use axum::{routing::get, Router};
+        use tower::ServiceExt;
+
+        let state = RateLimitState::new(RateLimitConfig {
+            requests_per_second: 1,
+            burst_size: 2,
+            paths: Vec::new(),
+        });
+        let app = Router::new().route("/test", get(|| async { "OK" })).layer(
+            axum::middleware::from_fn_with_state(state, rate_limit_middleware),
+        );
+        let request = || Request::builder().uri("/test").body(Body::empty()).unwrap();
+
+        let response = app.clone().oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["ratelimit-limit"], "2");
+        assert_eq!(response.headers()["ratelimit-remaining"], "1");
+        assert_eq!(response.headers()["ratelimit-reset"], "1");
+
+        app.clone().oneshot(request()).await.unwrap();
+        let response = app.oneshot(request()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(response.headers()["ratelimit-remaining"], "0");
+        assert!(response.headers().contains_key("retry-after"));
+    }
 }
@@ -12,9 +12,10 @@ use axum::{
 use uuid::Uuid;
 
 use crate::{
+    db::repository::GroupRepository,
     models::{Action, Resource, SystemRole},
     services::RbacService,
-    utils::error::ErrorResponse,
+    utils::error::ApiError,
     AppState,
 };
 
@@ -66,6 +67,8 @@ pub enum RbacError {
     },
     /// Role not found
     RoleNotFound(String),
+    /// Permission lookup failed
+    Internal(String),
 }
 
 impl std::fmt::Display for RbacError {
@@ -84,13 +87,14 @@ impl std::fmt::Display for RbacError {
                 reason
             ),
             RbacError::RoleNotFound(name) => write!(f, "Role not found: {}", name),
+            RbacError::Internal(msg) => write!(f, "Permission check failed: {}", msg),
         }
     }
 }
 
 impl IntoResponse for RbacError {
     fn into_response(self) -> Response {
-        let (status, error_type, message) = match &self {
+        let (status, code, message) = match &self {
             RbacError::NotAuthenticated => (
                 StatusCode::UNAUTHORIZED,
                 "unauthorized",
@@ -102,7 +106,7 @@ impl IntoResponse for RbacError {
                 reason,
             } => (
                 StatusCode::FORBIDDEN,
-                "forbidden",
+                "permission_denied",
                 format!(
                     "Permission denied: {} {} on {}",
                     action.as_str(),
@@ -112,17 +116,17 @@ impl IntoResponse for RbacError {
             ),
             RbacError::RoleNotFound(name) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                "internal_error",
+                "role_not_found",
                 format!("Role not found: {}", name),
             ),
+            RbacError::Internal(msg) => (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "permission_check_failed",
+                format!("Permission check failed: {}", msg),
+            ),
         };
 
-        let body = ErrorResponse {
-            error: error_type.to_string(),
-            message,
-            details: None,
-            code: None,
-        };
+        let body = ApiError::for_status(status, message).with_code(code);
 
         (status, Json(body)).into_response()
     }
@@ -162,6 +166,55 @@ pub fn check_permission(
     }
 }
 
+/// Check a node group permission against the group's place in the hierarchy
+///
+/// A permission scoped to a group also covers every group below it, so a
+/// team granted `update` on its own group can manage that whole subtree.
+/// Without a `group_id` only unscoped permissions apply.
+pub async fn check_group_permission(
+    state: &AppState,
+    auth_user: &AuthUser,
+    action: Action,
+    group_id: Option<Uuid>,
+) -> Result<(), RbacError> {
+    let user_id = auth_user.user_id();
+
+    let scope_ids = match group_id {
+        Some(group_id) => {
+            let ancestors = GroupRepository::new(&state.db)
+                .get_ancestor_ids(group_id)
+                .await
+                .map_err(|e| RbacError::Internal(e.to_string()))?;
+            if ancestors.is_empty() {
+                vec![Some(group_id)]
+            } else {
+                ancestors.into_iter().map(Some).collect()
+            }
+        }
+        None => vec![None],
+    };
+
+    let mut reason = None;
+    for scope_id in scope_ids {
+        let check = state
+            .rbac_db
+            .check_permission(&user_id, Resource::Groups, action, scope_id, None)
+            .await
+            .map_err(|e| RbacError::Internal(e.to_string()))?;
+
+        if check.allowed {
+            return Ok(());
+        }
+        reason = reason.or(check.reason);
+    }
+
+    Err(RbacError::PermissionDenied {
+        resource: Resource::Groups,
+        action,
+        reason: reason.unwrap_or_else(|| "No matching permission".to_string()),
+    })
+}
+
 /// Middleware factory for requiring a specific permission
 ///
 /// Usage:
@@ -181,16 +234,22 @@ pub fn check_permission(
 ///
 /// // Create minimal in-memory database config for the example
 /// let config = AppConfig {
-///     server: ServerConfig { host: "127.0.0.1".into(), port: 3000, workers: 1, request_timeout_secs: None, tls: None, static_dir: None, serve_frontend: false },
+///     server: ServerConfig { host: "127.0.0.1".into(), port: 3000, workers: 1, request_timeout_secs: None, tls: None, static_dir: None, serve_frontend: false, static_assets: Default::default(), base_path: String::new(), http3: None, listeners: Vec::new(), enc_listener: None, unix_socket: None, trusted_proxies: Vec::new(), security_headers: Default::default(), body_limits: Default::default(), graphql: None },
 ///     database: DatabaseConfig {
 ///         url: "sqlite::memory:".into(),
 ///         max_connections: 1, min_connections: 1,
 ///         connect_timeout_secs: 30, idle_timeout_secs: 600,
+///         busy_timeout_ms: 5000, busy_retries: 3,
 ///     },
 ///     auth: AuthConfig {
 ///         jwt_secret: "test_secret_at_least_32_chars_long".into(),
 ///         token_expiry_hours: 24, refresh_token_expiry_days: 7,
 ///         bcrypt_cost: 4, password_min_length: 8,
+///         api_key_rotation_grace_minutes: 60,
+///         password_policy: Default::default(), password_reset_url: None,
+///         signing_keys: Default::default(), login_alerts: Default::default(),
+///         csrf: Default::default(), mode: Default::default(),
+///         cookie: Default::default(),
 ///     },
 ///     puppetdb: None,
 ///     puppet_ca: None,
@@ -208,16 +267,28 @@ pub fn check_permission(
 ///     node_bootstrap: None,
 ///     cve: None,
 ///     pagination: PaginationConfig::default(),
+///     secrets: None,
+///     settings_encryption: Default::default(),
+///     audit: Default::default(),
+///     rate_limit: Default::default(),
+///     hot_reload: Default::default(),
+///     webhooks: Default::default(),
+///     recycle_bin: Default::default(),
+///     health: Default::default(),
+///     notifications: Default::default(),
+///     reporting: Default::default(),
+///     report_ingestion: Default::default(),
 /// };
 ///
 /// let db = openvox_webui::db::init_pool(&config.database).await.unwrap();
 /// let state = AppState {
-///     config,
+///     config: config.clone(),
 ///     db: db.clone(),
 ///     inventory_db: db.clone(),
 ///     inventory_config: InventoryConfig::default(),
 ///     inventory_ready: Arc::new(AtomicBool::new(true)),
 ///     puppetdb: None,
+///     puppetdb_tenants: Default::default(),
 ///     puppet_ca: None,
 ///     rbac: Arc::new(RbacService::new()),
 ///     rbac_db: Arc::new(DbRbacService::new(openvox_webui::db::init_pool(
@@ -225,10 +296,29 @@ pub fn check_permission(
 ///             url: "sqlite::memory:".into(),
 ///             max_connections: 1, min_connections: 1,
 ///             connect_timeout_secs: 30, idle_timeout_secs: 600,
+///             busy_timeout_ms: 5000, busy_retries: 3,
 ///         }).await.unwrap())),
 ///     code_deploy_config: None,
 ///     backup_config: None,
 ///     notification_service: Arc::new(NotificationService::new(db.clone())),
+///     webhooks: openvox_webui::services::webhooks::Webhooks::new(db.clone(), None),
+///     settings_cipher: None,
+///     secrets: None,
+///     audit_forwarder: None,
+///     jwt_keys: Default::default(),
+///     log_buffer: None,
+///     health: Default::default(),
+///     smart_list_counts: Arc::new(openvox_webui::services::smart_list::MemberCounts::new(
+///         std::time::Duration::from_secs(60),
+///     )),
+///     payload_debug: Default::default(),
+///     config_reloader: openvox_webui::services::ConfigReloader::new(
+///         config.clone(),
+///         config,
+///         db.clone(),
+///         Arc::new(DbRbacService::new(db.clone())),
+///         None,
+///     ),
 /// };
 ///
 /// let app = Router::<AppState>::new()
@@ -332,6 +422,8 @@ mod tests {
             session_id: Uuid::new_v4().to_string(),
             roles: vec!["admin".to_string()],
             role_ids: vec![SystemRole::Admin.uuid()],
+            api_key_id: None,
+            impersonator: None,
         };
 
         let result = check_permission(
@@ -357,6 +449,8 @@ mod tests {
             session_id: Uuid::new_v4().to_string(),
             roles: vec!["viewer".to_string()],
             role_ids: vec![SystemRole::Viewer.uuid()],
+            api_key_id: None,
+            impersonator: None,
         };
 
         let result = check_permission(
@@ -383,6 +477,8 @@ mod tests {
             session_id: Uuid::new_v4().to_string(),
             roles: vec!["operator".to_string()],
             role_ids: vec![SystemRole::Operator.uuid()],
+            api_key_id: None,
+            impersonator: None,
         };
 
         // Operator can create groups
@@ -419,6 +515,8 @@ mod tests {
             session_id: Uuid::new_v4().to_string(),
             roles: vec!["super_admin".to_string()],
             role_ids: vec![SystemRole::SuperAdmin.uuid()],
+            api_key_id: None,
+            impersonator: None,
         };
 
         // SuperAdmin should have all permissions on all resources
@@ -448,6 +546,8 @@ mod tests {
             session_id: Uuid::new_v4().to_string(),
             roles: vec!["super_admin".to_string()],
             role_ids: vec![SystemRole::SuperAdmin.uuid()],
+            api_key_id: None,
+            impersonator: None,
         };
 
         // SuperAdmin should bypass the permission check entirely
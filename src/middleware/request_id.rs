@@ -0,0 +1,166 @@
+//! Request IDs
+//!
+//! Every request gets an ID, taken from its `X-Request-Id` header when a
+//! proxy or client already assigned one, so a request can be followed across
+//! services. The ID is returned in the `X-Request-Id` response header, added
+//! to the request's tracing span, and available to the code handling the
+//! request through [`current_request_id`], which error bodies and audit
+//! entries use.
+
+use std::future::Future;
+
+use axum::{
+    extract::Request,
+    http::{HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+use uuid::Uuid;
+
+/// Header carrying the request ID
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// Longest incoming request ID that is kept
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// ID of the request, as a request extension
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+/// ID of the request being handled by the current task, if any
+pub fn current_request_id() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
+
+/// Run a future, typically a spawned task, under the ID of a request
+///
+/// Tasks started with `tokio::spawn` do not see the ID of the request that
+/// started them; pass them [`current_request_id`] through this.
+pub async fn with_request_id<F: Future>(request_id: Option<String>, future: F) -> F::Output {
+    match request_id {
+        Some(id) => REQUEST_ID.scope(id, future).await,
+        None => future.await,
+    }
+}
+
+/// Assign a request ID and return it in the response
+pub async fn request_id_middleware(mut request: Request, next: Next) -> Response {
+    let id = request
+        .headers()
+        .get(REQUEST_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| is_valid_request_id(value))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+
+    request.extensions_mut().insert(RequestId(id.clone()));
+    let mut response = REQUEST_ID.scope(id.clone(), next.run(request)).await;
+
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        response.headers_mut().insert(REQUEST_ID_HEADER, value);
+    }
+    response
+}
+
+/// Tracing span of a request, with its ID
+///
+/// Used with `TraceLayer::make_span_with` inside [`request_id_middleware`].
+pub fn make_request_span<B>(request: &axum::http::Request<B>) -> tracing::Span {
+    let request_id = request
+        .extensions()
+        .get::<RequestId>()
+        .map(|id| id.0.as_str())
+        .unwrap_or_default();
+
+    tracing::info_span!(
+        "request",
+        method = %request.method(),
+        uri = %request.uri(),
+        version = ?request.version(),
+        request_id = %request_id,
+    )
+}
+
+/// Incoming IDs are kept when short and made of visible ASCII characters
+fn is_valid_request_id(value: &str) -> bool {
+    !value.is_empty()
+        && value.len() <= MAX_REQUEST_ID_LEN
+        && value.bytes().all(|b| b.is_ascii_graphic())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request as HttpRequest, routing::get, Router};
+    use tower::ServiceExt;
+
+    fn app() -> Router {
+        Router::new()
+            .route(
+                "/",
+                get(|| async { current_request_id().unwrap_or_default() }),
+            )
+            .layer(axum::middleware::from_fn(request_id_middleware))
+    }
+
+    async fn call(request_id: Option<&str>) -> (String, String) {
+        let mut request = HttpRequest::get("/");
+        if let Some(id) = request_id {
+            request = request.header("X-Request-Id", id);
+        }
+        let response = app()
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+
+        let header = response.headers()["x-request-id"]
+            .to_str()
+            .unwrap()
+            .to_string();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (header, String::from_utf8(bytes.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_incoming_request_id_is_kept() {
+        let (header, body) = call(Some("edge-1234")).await;
+        assert_eq!(header, "edge-1234");
+        assert_eq!(body, "edge-1234");
+    }
+
+    #[tokio::test]
+    async fn test_request_id_is_generated() {
+        let (header, body) = call(None).await;
+        assert!(Uuid::parse_str(&header).is_ok());
+        assert_eq!(body, header);
+    }
+
+    #[tokio::test]
+    async fn test_invalid_request_id_is_replaced() {
+        let (header, _) = call(Some(&"x".repeat(MAX_REQUEST_ID_LEN + 1))).await;
+        assert!(Uuid::parse_str(&header).is_ok());
+
+        let (header, _) = call(Some("has space")).await;
+        assert!(Uuid::parse_str(&header).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_spawned_task_keeps_request_id() {
+        let id = with_request_id(Some("job-1".to_string()), async {
+            tokio::spawn(with_request_id(current_request_id(), async {
+                current_request_id()
+            }))
+            .await
+            .unwrap()
+        })
+        .await;
+        assert_eq!(id.as_deref(), Some("job-1"));
+        assert_eq!(current_request_id(), None);
+    }
+}
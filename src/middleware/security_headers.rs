@@ -1,72 +1,206 @@
 //! Security headers middleware
 //!
 //! Adds security headers to all responses to protect against common web vulnerabilities.
-//! Headers follow OWASP security best practices.
+//! Headers follow OWASP security best practices. HSTS, framing, the referrer
+//! and permissions policies and the Content-Security-Policy can be adjusted
+//! under `server.security_headers`.
 
-use axum::{body::Body, http::Request, middleware::Next, response::Response};
+use std::sync::Arc;
 
-/// Middleware that adds security headers to all responses
-pub async fn security_headers_middleware(request: Request<Body>, next: Next) -> Response {
-    let mut response = next.run(request).await;
+use anyhow::Context;
+use axum::{
+    body::Body,
+    extract::State,
+    http::{HeaderName, HeaderValue, Request},
+    middleware::Next,
+    response::Response,
+};
 
-    let headers = response.headers_mut();
+use crate::config::SecurityHeadersConfig;
 
-    // Strict-Transport-Security (HSTS)
-    // Forces browsers to use HTTPS for all future requests to this domain
-    // max-age=31536000 = 1 year, includeSubDomains applies to all subdomains
-    headers.insert(
-        "Strict-Transport-Security",
-        "max-age=31536000; includeSubDomains".parse().unwrap(),
-    );
+/// Default Content-Security-Policy directives, in header order
+///
+/// This is a relatively permissive policy suitable for an admin UI
+/// - default-src 'self': Only allow resources from the same origin by default
+/// - script-src 'self' 'unsafe-inline': Allow scripts from same origin and inline scripts (needed for React)
+/// - style-src 'self' 'unsafe-inline': Allow styles from same origin and inline styles (needed for Tailwind)
+/// - img-src 'self' data: blob:: Allow images from same origin, data URIs, and blob URIs
+/// - font-src 'self': Allow fonts from same origin
+/// - connect-src 'self': Allow XHR/fetch to same origin
+/// - frame-ancestors: Set from `frame_ancestors` (reinforces X-Frame-Options)
+const DEFAULT_CSP_DIRECTIVES: &[(&str, &str)] = &[
+    ("default-src", "'self'"),
+    ("script-src", "'self' 'unsafe-inline' 'unsafe-eval'"),
+    ("style-src", "'self' 'unsafe-inline'"),
+    ("img-src", "'self' data: blob:"),
+    ("font-src", "'self' data:"),
+    ("connect-src", "'self'"),
+    ("frame-ancestors", "'self'"),
+    ("base-uri", "'self'"),
+    ("form-action", "'self'"),
+];
 
-    // X-Content-Type-Options
-    // Prevents browsers from MIME-sniffing a response away from the declared content-type
-    headers.insert("X-Content-Type-Options", "nosniff".parse().unwrap());
+/// Restricts which browser features can be used
+const DEFAULT_PERMISSIONS_POLICY: &str = "accelerometer=(), camera=(), geolocation=(), gyroscope=(), magnetometer=(), microphone=(), payment=(), usb=()";
 
-    // X-Frame-Options
-    // Protects against clickjacking attacks by preventing the page from being embedded in iframes
-    // SAMEORIGIN allows embedding only from the same origin
-    headers.insert("X-Frame-Options", "SAMEORIGIN".parse().unwrap());
+/// Headers added to every response, built once from the configuration
+#[derive(Debug, Clone)]
+pub struct SecurityHeaders {
+    headers: Arc<Vec<(HeaderName, HeaderValue)>>,
+}
 
-    // X-XSS-Protection is intentionally NOT set.
-    // The header is deprecated: modern browsers ignore it (Chrome/Edge removed the
-    // XSS Auditor, Firefox never implemented it), and the legacy filter could itself
-    // be abused to introduce vulnerabilities. XSS protection is provided by the
-    // Content-Security-Policy below and React's default output escaping.
+impl Default for SecurityHeaders {
+    fn default() -> Self {
+        Self::from_config(&SecurityHeadersConfig::default())
+            .expect("default security headers are valid")
+    }
+}
 
-    // Referrer-Policy
-    // Controls how much referrer information is included with requests
-    // strict-origin-when-cross-origin sends full URL for same-origin, origin only for cross-origin HTTPS
-    headers.insert(
-        "Referrer-Policy",
-        "strict-origin-when-cross-origin".parse().unwrap(),
-    );
+impl SecurityHeaders {
+    pub fn from_config(config: &SecurityHeadersConfig) -> anyhow::Result<Self> {
+        let mut headers = Vec::new();
+        let mut add = |name: &'static str, value: String| -> anyhow::Result<()> {
+            let value = HeaderValue::from_str(&value)
+                .with_context(|| format!("Invalid {} value: {}", name, value))?;
+            headers.push((HeaderName::from_static(name), value));
+            Ok(())
+        };
 
-    // Permissions-Policy (formerly Feature-Policy)
-    // Restricts which browser features can be used
-    headers.insert(
-        "Permissions-Policy",
-        "accelerometer=(), camera=(), geolocation=(), gyroscope=(), magnetometer=(), microphone=(), payment=(), usb=()"
-            .parse()
-            .unwrap(),
-    );
+        // Strict-Transport-Security (HSTS)
+        // Forces browsers to use HTTPS for all future requests to this domain
+        // includeSubDomains applies to all subdomains
+        if config.hsts_max_age_secs > 0 {
+            if config.hsts_preload
+                && (config.hsts_max_age_secs < 31_536_000 || !config.hsts_include_subdomains)
+            {
+                anyhow::bail!(
+                    "hsts_preload requires hsts_max_age_secs of at least one year and hsts_include_subdomains"
+                );
+            }
+            let mut hsts = format!("max-age={}", config.hsts_max_age_secs);
+            if config.hsts_include_subdomains {
+                hsts.push_str("; includeSubDomains");
+            }
+            if config.hsts_preload {
+                hsts.push_str("; preload");
+            }
+            add("strict-transport-security", hsts)?;
+        }
 
-    // Content-Security-Policy
-    // Restricts the sources from which content can be loaded
-    // This is a relatively permissive policy suitable for an admin UI
-    // - default-src 'self': Only allow resources from the same origin by default
-    // - script-src 'self' 'unsafe-inline': Allow scripts from same origin and inline scripts (needed for React)
-    // - style-src 'self' 'unsafe-inline': Allow styles from same origin and inline styles (needed for Tailwind)
-    // - img-src 'self' data: blob:: Allow images from same origin, data URIs, and blob URIs
-    // - font-src 'self': Allow fonts from same origin
-    // - connect-src 'self': Allow XHR/fetch to same origin
-    // - frame-ancestors 'self': Only allow framing from same origin (reinforces X-Frame-Options)
-    headers.insert(
-        "Content-Security-Policy",
-        "default-src 'self'; script-src 'self' 'unsafe-inline' 'unsafe-eval'; style-src 'self' 'unsafe-inline'; img-src 'self' data: blob:; font-src 'self' data:; connect-src 'self'; frame-ancestors 'self'; base-uri 'self'; form-action 'self'"
-            .parse()
-            .unwrap(),
+        // X-Content-Type-Options
+        // Prevents browsers from MIME-sniffing a response away from the declared content-type
+        add("x-content-type-options", "nosniff".to_string())?;
+
+        // X-Frame-Options
+        // Protects against clickjacking attacks by preventing the page from being embedded in iframes
+        // SAMEORIGIN allows embedding only from the same origin. It cannot list
+        // other origins, so embedding sites rely on CSP frame-ancestors alone.
+        if config.frame_ancestors.is_empty() {
+            anyhow::bail!("frame_ancestors cannot be empty; use \"'none'\" to forbid framing");
+        }
+        match config.frame_ancestors.as_slice() {
+            [only] if only == "'self'" => add("x-frame-options", "SAMEORIGIN".to_string())?,
+            [only] if only == "'none'" => add("x-frame-options", "DENY".to_string())?,
+            _ => {}
+        }
+
+        // X-XSS-Protection is intentionally NOT set.
+        // The header is deprecated: modern browsers ignore it (Chrome/Edge removed the
+        // XSS Auditor, Firefox never implemented it), and the legacy filter could itself
+        // be abused to introduce vulnerabilities. XSS protection is provided by the
+        // Content-Security-Policy below and React's default output escaping.
+
+        // Referrer-Policy
+        // Controls how much referrer information is included with requests
+        // strict-origin-when-cross-origin sends full URL for same-origin, origin only for cross-origin HTTPS
+        add("referrer-policy", config.referrer_policy.clone())?;
+
+        // Permissions-Policy (formerly Feature-Policy)
+        add(
+            "permissions-policy",
+            config
+                .permissions_policy
+                .clone()
+                .unwrap_or_else(|| DEFAULT_PERMISSIONS_POLICY.to_string()),
+        )?;
+
+        // Content-Security-Policy
+        // Restricts the sources from which content can be loaded
+        let name = if config.csp_report_only {
+            "content-security-policy-report-only"
+        } else {
+            "content-security-policy"
+        };
+        add(name, content_security_policy(config)?)?;
+
+        Ok(Self {
+            headers: Arc::new(headers),
+        })
+    }
+}
+
+/// The default directives with the configured overrides applied
+fn content_security_policy(config: &SecurityHeadersConfig) -> anyhow::Result<String> {
+    let mut directives: Vec<(String, String)> = DEFAULT_CSP_DIRECTIVES
+        .iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+
+    if config
+        .content_security_policy
+        .keys()
+        .any(|name| name.trim().eq_ignore_ascii_case("frame-ancestors"))
+    {
+        anyhow::bail!("Set frame-ancestors with frame_ancestors, which also sets X-Frame-Options");
+    }
+    let frame_ancestors = (
+        "frame-ancestors".to_string(),
+        config.frame_ancestors.join(" "),
     );
+    let overrides = config
+        .content_security_policy
+        .iter()
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()));
+    for (name, value) in std::iter::once(frame_ancestors).chain(overrides) {
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            anyhow::bail!("Invalid Content-Security-Policy directive name: {}", name);
+        }
+        if value.contains(';') || value.contains(',') {
+            anyhow::bail!(
+                "Invalid value for Content-Security-Policy directive {}",
+                name
+            );
+        }
+        let existing = directives.iter().position(|(n, _)| *n == name);
+        match (existing, value.is_empty()) {
+            (Some(index), true) => {
+                directives.remove(index);
+            }
+            (Some(index), false) => directives[index].1 = value,
+            (None, true) => {}
+            (None, false) => directives.push((name, value)),
+        }
+    }
+
+    Ok(directives
+        .iter()
+        .map(|(name, value)| format!("{} {}", name, value))
+        .collect::<Vec<_>>()
+        .join("; "))
+}
+
+/// Middleware that adds security headers to all responses
+pub async fn security_headers_middleware(
+    State(security_headers): State<SecurityHeaders>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let mut response = next.run(request).await;
+
+    let headers = response.headers_mut();
+    for (name, value) in security_headers.headers.iter() {
+        headers.insert(name.clone(), value.clone());
+    }
 
     // Cache-Control for API responses
     // Prevents caching of sensitive data
@@ -107,9 +241,12 @@ mod tests {
 
     #[tokio::test]
     async fn test_security_headers_are_added() {
-        let app = Router::new()
-            .route("/test", get(test_handler))
-            .layer(axum::middleware::from_fn(security_headers_middleware));
+        let app = Router::new().route("/test", get(test_handler)).layer(
+            axum::middleware::from_fn_with_state(
+                SecurityHeaders::default(),
+                security_headers_middleware,
+            ),
+        );
 
         let request = Request::builder().uri("/test").body(Body::empty()).unwrap();
 
@@ -135,6 +272,65 @@ mod tests {
             response.headers().get("x-frame-options").unwrap(),
             "SAMEORIGIN"
         );
+        assert_eq!(
+            response.headers().get("strict-transport-security").unwrap(),
+            "max-age=31536000; includeSubDomains"
+        );
+    }
+
+    fn header<'a>(headers: &'a SecurityHeaders, name: &str) -> Option<&'a str> {
+        headers
+            .headers
+            .iter()
+            .find(|(n, _)| n == name)
+            .map(|(_, v)| v.to_str().unwrap())
+    }
+
+    #[test]
+    fn test_security_headers_from_config() {
+        let mut config = SecurityHeadersConfig {
+            hsts_preload: true,
+            frame_ancestors: vec![
+                "'self'".to_string(),
+                "https://portal.example.com".to_string(),
+            ],
+            ..Default::default()
+        };
+        config.content_security_policy.insert(
+            "script-src".to_string(),
+            "'self' https://cdn.example.com".to_string(),
+        );
+        config
+            .content_security_policy
+            .insert("form-action".to_string(), String::new());
+        config
+            .content_security_policy
+            .insert("worker-src".to_string(), "'self' blob:".to_string());
+
+        let headers = SecurityHeaders::from_config(&config).unwrap();
+        assert_eq!(
+            header(&headers, "strict-transport-security"),
+            Some("max-age=31536000; includeSubDomains; preload")
+        );
+        // X-Frame-Options cannot allow another origin
+        assert_eq!(header(&headers, "x-frame-options"), None);
+
+        let csp = header(&headers, "content-security-policy").unwrap();
+        assert!(csp.contains("script-src 'self' https://cdn.example.com;"));
+        assert!(csp.contains("frame-ancestors 'self' https://portal.example.com;"));
+        assert!(csp.ends_with("; worker-src 'self' blob:"));
+        assert!(!csp.contains("form-action"));
+
+        config.hsts_max_age_secs = 0;
+        config.csp_report_only = true;
+        let headers = SecurityHeaders::from_config(&config).unwrap();
+        assert_eq!(header(&headers, "strict-transport-security"), None);
+        assert!(header(&headers, "content-security-policy-report-only").is_some());
+
+        config
+            .content_security_policy
+            .insert("img-src".to_string(), "'self'; script-src *".to_string());
+        assert!(SecurityHeaders::from_config(&config).is_err());
     }
 
     #[tokio::test]
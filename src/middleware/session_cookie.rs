@@ -0,0 +1,195 @@
+//! Session cookies of cookie auth mode
+//!
+//! With `auth.mode: cookie`, login, SAML login and token refresh put the
+//! access and refresh tokens in HttpOnly cookies instead of the response
+//! body, so scripts (and a cross-site scripting bug) never see them.
+//! [`auth_middleware`](super::auth_middleware) accepts the access cookie
+//! when a request has no other credentials, and logout clears both. The
+//! refresh cookie is only sent to the auth endpoints.
+
+use axum::{
+    http::{header, HeaderMap, HeaderName, HeaderValue},
+    response::AppendHeaders,
+};
+
+use crate::config::AppConfig;
+
+/// Cookie carrying the access token
+pub const SESSION_COOKIE: &str = "openvox_session";
+
+/// Cookie carrying the refresh token
+pub const REFRESH_COOKIE: &str = "openvox_refresh";
+
+/// `Set-Cookie` headers of a response; empty outside cookie mode
+pub type SetCookies = AppendHeaders<Vec<(HeaderName, HeaderValue)>>;
+
+/// The value of a request cookie
+pub fn request_cookie<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers
+        .get_all(header::COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(';'))
+        .filter_map(|pair| pair.trim().split_once('='))
+        .find(|(cookie, _)| *cookie == name)
+        .map(|(_, value)| value.trim())
+        .filter(|value| !value.is_empty())
+}
+
+/// Cookies holding the tokens of a login or refresh
+///
+/// `expires_in` is the lifetime of the access token in seconds.
+pub fn session_cookies(
+    config: &AppConfig,
+    access_token: &str,
+    expires_in: u64,
+    refresh_token: &str,
+) -> SetCookies {
+    if !config.auth.cookie_mode() {
+        return AppendHeaders(Vec::new());
+    }
+    let refresh_max_age = config.auth.refresh_token_expiry_days * 24 * 60 * 60;
+    AppendHeaders(
+        [
+            cookie(config, SESSION_COOKIE, access_token, expires_in),
+            cookie(config, REFRESH_COOKIE, refresh_token, refresh_max_age),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|value| (header::SET_COOKIE, value))
+        .collect(),
+    )
+}
+
+/// Cookie holding an access token reissued for the current session, e.g.
+/// with an elevated role
+pub fn access_cookie(config: &AppConfig, access_token: &str, expires_in: u64) -> SetCookies {
+    if !config.auth.cookie_mode() {
+        return AppendHeaders(Vec::new());
+    }
+    AppendHeaders(
+        cookie(config, SESSION_COOKIE, access_token, expires_in)
+            .map(|value| (header::SET_COOKIE, value))
+            .into_iter()
+            .collect(),
+    )
+}
+
+/// Cookies removing the session cookies
+pub fn clear_session_cookies(config: &AppConfig) -> SetCookies {
+    if !config.auth.cookie_mode() {
+        return AppendHeaders(Vec::new());
+    }
+    AppendHeaders(
+        [
+            cookie(config, SESSION_COOKIE, "", 0),
+            cookie(config, REFRESH_COOKIE, "", 0),
+        ]
+        .into_iter()
+        .flatten()
+        .map(|value| (header::SET_COOKIE, value))
+        .collect(),
+    )
+}
+
+fn cookie(config: &AppConfig, name: &str, value: &str, max_age: u64) -> Option<HeaderValue> {
+    let base_path = config.server.normalized_base_path();
+    let path = if name == REFRESH_COOKIE {
+        format!("{}/api/v1/auth", base_path)
+    } else if base_path.is_empty() {
+        "/".to_string()
+    } else {
+        base_path
+    };
+
+    let mut cookie = format!(
+        "{}={}; Path={}; Max-Age={}; HttpOnly; SameSite={}",
+        name,
+        value,
+        path,
+        max_age,
+        config.auth.cookie.same_site.as_str()
+    );
+    if config.auth.cookie.secure {
+        cookie.push_str("; Secure");
+    }
+    match HeaderValue::from_str(&cookie) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            tracing::error!("Invalid {} cookie: {}", name, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{AuthMode, CookieSameSite};
+
+    fn cookie_config() -> AppConfig {
+        let mut config = AppConfig::default();
+        config.auth.mode = AuthMode::Cookie;
+        config
+    }
+
+    fn values(cookies: SetCookies) -> Vec<String> {
+        cookies
+            .0
+            .into_iter()
+            .map(|(_, value)| value.to_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_session_cookies() {
+        let config = cookie_config();
+        let cookies = values(session_cookies(&config, "access", 3600, "refresh"));
+        assert_eq!(
+            cookies,
+            vec![
+                "openvox_session=access; Path=/; Max-Age=3600; HttpOnly; SameSite=Strict; Secure",
+                "openvox_refresh=refresh; Path=/api/v1/auth; Max-Age=604800; HttpOnly; SameSite=Strict; Secure",
+            ]
+        );
+
+        let cookies = values(clear_session_cookies(&config));
+        assert!(cookies.iter().all(|cookie| cookie.contains("=; ")));
+        assert!(cookies.iter().all(|cookie| cookie.contains("Max-Age=0")));
+    }
+
+    #[test]
+    fn test_session_cookies_attributes() {
+        let mut config = cookie_config();
+        config.server.base_path = "/openvox/".to_string();
+        config.auth.cookie.same_site = CookieSameSite::Lax;
+        config.auth.cookie.secure = false;
+        let cookies = values(session_cookies(&config, "access", 60, "refresh"));
+        assert_eq!(
+            cookies[0],
+            "openvox_session=access; Path=/openvox; Max-Age=60; HttpOnly; SameSite=Lax"
+        );
+        assert!(cookies[1].contains("Path=/openvox/api/v1/auth;"));
+    }
+
+    #[test]
+    fn test_no_cookies_in_token_mode() {
+        let config = AppConfig::default();
+        assert!(session_cookies(&config, "access", 60, "refresh")
+            .0
+            .is_empty());
+        assert!(clear_session_cookies(&config).0.is_empty());
+    }
+
+    #[test]
+    fn test_request_cookie() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::COOKIE,
+            HeaderValue::from_static("theme=dark; openvox_session=abc; openvox_refresh="),
+        );
+        assert_eq!(request_cookie(&headers, SESSION_COOKIE), Some("abc"));
+        assert_eq!(request_cookie(&headers, REFRESH_COOKIE), None);
+        assert_eq!(request_cookie(&headers, "missing"), None);
+    }
+}
@@ -0,0 +1,153 @@
+//! Cache headers for the co-located frontend
+//!
+//! Build tools such as Vite emit content-hashed file names
+//! (`assets/index-BxYz12ab.js`), so those files can be cached forever: a new
+//! build produces new names. `index.html` and the SPA fallback must always be
+//! revalidated so clients pick up the new asset names after an upgrade.
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, HeaderValue, Request, StatusCode},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::config::StaticAssetsConfig;
+
+/// Returns true if the last path segment carries a content hash
+///
+/// Recognises both `name-<hash>.ext` (Vite/Rollup) and `name.<hash>.ext`
+/// (webpack). The hash must be 8-64 word characters and contain a digit or
+/// mixed case, so ordinary names like `vendor-fallback.js` are not mistaken
+/// for fingerprinted files.
+pub fn is_fingerprinted_asset(path: &str) -> bool {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    let Some((stem, _ext)) = file_name.rsplit_once('.') else {
+        return false;
+    };
+    let Some(hash) = stem.rsplit(['-', '.']).next().filter(|h| *h != stem) else {
+        return false;
+    };
+
+    let valid_len = (8..=64).contains(&hash.len());
+    let word_chars = hash.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    let has_digit = hash.chars().any(|c| c.is_ascii_digit());
+    let mixed_case = hash.chars().any(|c| c.is_ascii_uppercase())
+        && hash.chars().any(|c| c.is_ascii_lowercase());
+
+    valid_len && word_chars && (has_digit || mixed_case)
+}
+
+/// Middleware that sets `Cache-Control` on static frontend responses
+///
+/// Responses that already carry a `Cache-Control` header (API routes) are left
+/// untouched.
+pub async fn static_cache_control_middleware(
+    State(config): State<StaticAssetsConfig>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let fingerprinted = is_fingerprinted_asset(request.uri().path());
+    let mut response = next.run(request).await;
+
+    let status = response.status();
+    if response.headers().contains_key(header::CACHE_CONTROL)
+        || !(status.is_success() || status == StatusCode::NOT_MODIFIED)
+    {
+        return response;
+    }
+
+    let value = if fingerprinted {
+        format!(
+            "public, max-age={}, immutable",
+            config.immutable_max_age_secs
+        )
+    } else {
+        "no-cache".to_string()
+    };
+
+    if let Ok(value) = HeaderValue::from_str(&value) {
+        response.headers_mut().insert(header::CACHE_CONTROL, value);
+    }
+
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::get, Router};
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_is_fingerprinted_asset() {
+        assert!(is_fingerprinted_asset("/assets/index-BxYz12ab.js"));
+        assert!(is_fingerprinted_asset("/assets/vendor-DqzXGbAm.css"));
+        assert!(is_fingerprinted_asset("/static/js/main.3f2a1b9c.js"));
+
+        assert!(!is_fingerprinted_asset("/"));
+        assert!(!is_fingerprinted_asset("/index.html"));
+        assert!(!is_fingerprinted_asset("/favicon.ico"));
+        assert!(!is_fingerprinted_asset("/assets/vendor-fallback.js"));
+        assert!(!is_fingerprinted_asset("/nodes/web01.example.com"));
+    }
+
+    async fn ok_handler() -> &'static str {
+        "OK"
+    }
+
+    fn app() -> Router {
+        Router::new()
+            .route("/assets/index-BxYz12ab.js", get(ok_handler))
+            .route("/index.html", get(ok_handler))
+            .layer(axum::middleware::from_fn_with_state(
+                StaticAssetsConfig::default(),
+                static_cache_control_middleware,
+            ))
+    }
+
+    #[tokio::test]
+    async fn test_fingerprinted_asset_is_immutable() {
+        let request = Request::builder()
+            .uri("/assets/index-BxYz12ab.js")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+
+        let cache_control = response.headers().get("cache-control").unwrap();
+        assert_eq!(cache_control, "public, max-age=31536000, immutable");
+    }
+
+    #[tokio::test]
+    async fn test_index_html_is_revalidated() {
+        let request = Request::builder()
+            .uri("/index.html")
+            .body(Body::empty())
+            .unwrap();
+        let response = app().oneshot(request).await.unwrap();
+
+        assert_eq!(response.headers().get("cache-control").unwrap(), "no-cache");
+    }
+
+    #[tokio::test]
+    async fn test_existing_cache_control_is_preserved() {
+        let app = Router::new()
+            .route(
+                "/api/test",
+                get(|| async { ([(header::CACHE_CONTROL, "no-store")], "OK") }),
+            )
+            .layer(axum::middleware::from_fn_with_state(
+                StaticAssetsConfig::default(),
+                static_cache_control_middleware,
+            ));
+
+        let request = Request::builder()
+            .uri("/api/test")
+            .body(Body::empty())
+            .unwrap();
+        let response = app.oneshot(request).await.unwrap();
+
+        assert_eq!(response.headers().get("cache-control").unwrap(), "no-store");
+    }
+}
@@ -52,6 +52,8 @@ pub enum OutputFormat {
     Json,
     Csv,
     Pdf,
+    Xlsx,
+    Html,
 }
 
 impl OutputFormat {
@@ -60,6 +62,8 @@ impl OutputFormat {
             OutputFormat::Json => "json",
             OutputFormat::Csv => "csv",
             OutputFormat::Pdf => "pdf",
+            OutputFormat::Xlsx => "xlsx",
+            OutputFormat::Html => "html",
         }
     }
 
@@ -68,6 +72,8 @@ impl OutputFormat {
             "json" => Some(OutputFormat::Json),
             "csv" => Some(OutputFormat::Csv),
             "pdf" => Some(OutputFormat::Pdf),
+            "xlsx" => Some(OutputFormat::Xlsx),
+            "html" => Some(OutputFormat::Html),
             _ => None,
         }
     }
@@ -77,6 +83,10 @@ impl OutputFormat {
             OutputFormat::Json => "application/json",
             OutputFormat::Csv => "text/csv",
             OutputFormat::Pdf => "application/pdf",
+            OutputFormat::Xlsx => {
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+            }
+            OutputFormat::Html => "text/html; charset=utf-8",
         }
     }
 
@@ -85,6 +95,8 @@ impl OutputFormat {
             OutputFormat::Json => "json",
             OutputFormat::Csv => "csv",
             OutputFormat::Pdf => "pdf",
+            OutputFormat::Xlsx => "xlsx",
+            OutputFormat::Html => "html",
         }
     }
 }
@@ -157,6 +169,7 @@ impl SeverityLevel {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SavedReport {
     pub id: Uuid,
+    pub organization_id: Uuid,
     pub name: String,
     pub description: Option<String>,
     pub report_type: ReportType,
@@ -185,6 +198,9 @@ pub struct ReportQueryConfig {
     /// Filter by certname pattern
     #[serde(default)]
     pub certname_pattern: Option<String>,
+    /// Restrict the report to the nodes matching a saved smart list
+    #[serde(default)]
+    pub smart_list_id: Option<Uuid>,
     /// Group results by field
     #[serde(default)]
     pub group_by: Option<String>,
@@ -200,6 +216,9 @@ pub struct ReportQueryConfig {
     /// Severity filter for compliance reports
     #[serde(default)]
     pub severity_filter: Option<Vec<String>>,
+    /// Compliance baselines to evaluate; every baseline when unset
+    #[serde(default)]
+    pub compliance_baseline_ids: Option<Vec<Uuid>>,
     /// Comparison mode for drift detection
     #[serde(default)]
     pub compare_mode: Option<String>,
@@ -215,12 +234,16 @@ pub struct ReportQueryConfig {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReportSchedule {
     pub id: Uuid,
+    pub organization_id: Uuid,
     pub report_id: Uuid,
     pub schedule_cron: String,
     pub timezone: String,
     pub is_enabled: bool,
     pub output_format: OutputFormat,
     pub email_recipients: Option<Vec<String>>,
+    /// Destinations the output is published to after each run
+    #[serde(default)]
+    pub delivery_targets: Vec<DeliveryTarget>,
     pub last_run_at: Option<DateTime<Utc>>,
     pub next_run_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
@@ -231,6 +254,7 @@ pub struct ReportSchedule {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ReportExecution {
     pub id: Uuid,
+    pub organization_id: Uuid,
     pub report_id: Uuid,
     pub schedule_id: Option<Uuid>,
     pub executed_by: Option<Uuid>,
@@ -243,16 +267,234 @@ pub struct ReportExecution {
     pub output_file_path: Option<String>,
     pub error_message: Option<String>,
     pub execution_time_ms: Option<i32>,
+    /// Overall delivery outcome, absent when there was nothing to deliver
+    pub delivery_status: Option<DeliveryStatus>,
+    #[serde(default)]
+    pub delivery_results: Vec<DeliveryResult>,
+}
+
+/// Placeholder returned instead of delivery target credentials
+pub const REDACTED_SECRET: &str = "********";
+
+/// Destination the output of a scheduled report is published to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DeliveryTarget {
+    /// Directory on the server's filesystem
+    Filesystem { directory: String },
+    /// S3-compatible bucket, addressed path-style (`{endpoint}/{bucket}/{key}`)
+    S3 {
+        endpoint: String,
+        bucket: String,
+        #[serde(default = "default_s3_region")]
+        region: String,
+        /// Key prefix, e.g. `reports/weekly`
+        #[serde(default)]
+        prefix: Option<String>,
+        access_key_id: String,
+        /// Secret key or `secret:` reference
+        secret_access_key: String,
+    },
+    /// WebDAV collection the file is uploaded into
+    Webdav {
+        url: String,
+        #[serde(default)]
+        username: Option<String>,
+        /// Password or `secret:` reference
+        #[serde(default)]
+        password: Option<String>,
+    },
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
+impl DeliveryTarget {
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            DeliveryTarget::Filesystem { .. } => "filesystem",
+            DeliveryTarget::S3 { .. } => "s3",
+            DeliveryTarget::Webdav { .. } => "webdav",
+        }
+    }
+
+    /// Where the target points, without credentials
+    pub fn describe(&self) -> String {
+        match self {
+            DeliveryTarget::Filesystem { directory } => directory.clone(),
+            DeliveryTarget::S3 {
+                endpoint,
+                bucket,
+                prefix,
+                ..
+            } => match prefix.as_deref().map(|p| p.trim_matches('/')) {
+                Some(p) if !p.is_empty() => {
+                    format!("{}/{}/{}", endpoint.trim_end_matches('/'), bucket, p)
+                }
+                _ => format!("{}/{}", endpoint.trim_end_matches('/'), bucket),
+            },
+            DeliveryTarget::Webdav { url, .. } => url.clone(),
+        }
+    }
+
+    /// Copy of the target with its credentials replaced by [`REDACTED_SECRET`]
+    pub fn redacted(&self) -> Self {
+        let mut target = self.clone();
+        match &mut target {
+            DeliveryTarget::Filesystem { .. } => {}
+            DeliveryTarget::S3 {
+                secret_access_key, ..
+            } => *secret_access_key = REDACTED_SECRET.to_string(),
+            DeliveryTarget::Webdav { password, .. } => {
+                if password.is_some() {
+                    *password = Some(REDACTED_SECRET.to_string());
+                }
+            }
+        }
+        target
+    }
+
+    /// Whether a credential is the [`REDACTED_SECRET`] placeholder
+    pub fn has_redacted_secret(&self) -> bool {
+        match self {
+            DeliveryTarget::Filesystem { .. } => false,
+            DeliveryTarget::S3 {
+                secret_access_key, ..
+            } => secret_access_key == REDACTED_SECRET,
+            DeliveryTarget::Webdav { password, .. } => password.as_deref() == Some(REDACTED_SECRET),
+        }
+    }
+
+    /// Replace a [`REDACTED_SECRET`] placeholder sent back by a client with
+    /// the credential of the stored target it came from
+    pub fn restore_secrets(&mut self, existing: &DeliveryTarget) {
+        match (self, existing) {
+            (
+                DeliveryTarget::S3 {
+                    secret_access_key, ..
+                },
+                DeliveryTarget::S3 {
+                    secret_access_key: stored,
+                    ..
+                },
+            ) if secret_access_key == REDACTED_SECRET => *secret_access_key = stored.clone(),
+            (
+                DeliveryTarget::Webdav { password, .. },
+                DeliveryTarget::Webdav {
+                    password: stored, ..
+                },
+            ) if password.as_deref() == Some(REDACTED_SECRET) => *password = stored.clone(),
+            _ => {}
+        }
+    }
+}
+
+/// Overall outcome of delivering an execution's output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    /// Every target received the output
+    Delivered,
+    /// Some targets failed
+    Partial,
+    /// No target received the output
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Delivered => "delivered",
+            DeliveryStatus::Partial => "partial",
+            DeliveryStatus::Failed => "failed",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "delivered" => Some(DeliveryStatus::Delivered),
+            "partial" => Some(DeliveryStatus::Partial),
+            "failed" => Some(DeliveryStatus::Failed),
+            _ => None,
+        }
+    }
+
+    /// Overall status of a set of target results, `None` when there are none
+    pub fn from_results(results: &[DeliveryResult]) -> Option<Self> {
+        if results.is_empty() {
+            return None;
+        }
+        let delivered = results.iter().filter(|r| r.success).count();
+        Some(if delivered == results.len() {
+            DeliveryStatus::Delivered
+        } else if delivered == 0 {
+            DeliveryStatus::Failed
+        } else {
+            DeliveryStatus::Partial
+        })
+    }
+}
+
+/// Result of delivering an execution's output to one target
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeliveryResult {
+    pub target_type: String,
+    /// Target location, without credentials
+    pub target: String,
+    pub success: bool,
+    /// Path or URL of the delivered file
+    pub location: Option<String>,
+    pub error: Option<String>,
+    pub delivered_at: DateTime<Utc>,
+}
+
+/// Email template of scheduled reports
+///
+/// The subject and bodies are Tera templates. A template without a report
+/// type applies to every type that has no template of its own.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportEmailTemplate {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub report_type: Option<ReportType>,
+    pub subject_template: String,
+    pub html_template: String,
+    pub text_template: String,
+    pub updated_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request to create or replace the email template of a report type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpsertReportEmailTemplateRequest {
+    /// Report type the template is for; every type when absent
+    #[serde(default)]
+    pub report_type: Option<ReportType>,
+    pub subject_template: String,
+    pub html_template: String,
+    pub text_template: String,
 }
 
 /// Compliance baseline definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComplianceBaseline {
     pub id: Uuid,
+    pub organization_id: Uuid,
     pub name: String,
     pub description: Option<String>,
     pub rules: Vec<ComplianceRule>,
     pub severity_level: SeverityLevel,
+    /// Node groups the baseline applies to; every node when empty
+    #[serde(default)]
+    pub node_group_ids: Vec<Uuid>,
+    /// Rule pack the rules were loaded from
+    #[serde(default)]
+    pub rule_pack_id: Option<Uuid>,
+    /// Version of that rule pack
+    #[serde(default)]
+    pub rule_pack_version: Option<String>,
     pub created_by: Uuid,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -268,12 +510,90 @@ pub struct ComplianceRule {
     pub operator: String,
     pub expected_value: serde_json::Value,
     pub severity: SeverityLevel,
+    /// Benchmark references (e.g. "CIS 5.2.8", "V-230296")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub references: Vec<String>,
+    /// How to fix a node that violates the rule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
+}
+
+/// Imported version of a compliance rule pack
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceRulePack {
+    pub id: Uuid,
+    /// Identifier shared by all versions of the pack
+    pub pack_key: String,
+    pub name: String,
+    pub version: String,
+    pub description: Option<String>,
+    /// Benchmark family, e.g. "CIS" or "STIG"
+    pub framework: Option<String>,
+    pub publisher: Option<String>,
+    pub references: Vec<String>,
+    pub rules: Vec<ComplianceRule>,
+    /// SHA-256 of the imported document
+    pub checksum: String,
+    pub imported_by: Uuid,
+    pub imported_at: DateTime<Utc>,
+}
+
+/// Rule pack document, as written in YAML or JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RulePackDocument {
+    /// Identifier shared by all versions of the pack
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub framework: Option<String>,
+    #[serde(default)]
+    pub publisher: Option<String>,
+    #[serde(default)]
+    pub references: Vec<String>,
+    pub rules: Vec<ComplianceRule>,
+}
+
+/// Format of a rule pack document
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RulePackFormat {
+    Yaml,
+    Json,
+}
+
+/// Request to import a rule pack
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportRulePackRequest {
+    /// The YAML or JSON document
+    pub content: String,
+    /// Format of `content`; detected when unset
+    #[serde(default)]
+    pub format: Option<RulePackFormat>,
+}
+
+/// Request to load a rule pack's rules into a compliance baseline
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ApplyRulePackRequest {
+    /// Baseline to replace the rules of; a new baseline is created when unset
+    #[serde(default)]
+    pub baseline_id: Option<Uuid>,
+    /// Name of a new baseline; defaults to the pack name
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub severity_level: Option<SeverityLevel>,
+    #[serde(default)]
+    pub node_group_ids: Option<Vec<Uuid>>,
 }
 
 /// Drift baseline definition
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DriftBaseline {
     pub id: Uuid,
+    pub organization_id: Uuid,
     pub name: String,
     pub description: Option<String>,
     pub node_group_id: Option<Uuid>,
@@ -344,6 +664,8 @@ pub struct CreateScheduleRequest {
     #[serde(default)]
     pub output_format: OutputFormat,
     pub email_recipients: Option<Vec<String>>,
+    #[serde(default)]
+    pub delivery_targets: Vec<DeliveryTarget>,
 }
 
 fn default_timezone() -> String {
@@ -362,6 +684,9 @@ pub struct UpdateScheduleRequest {
     pub is_enabled: Option<bool>,
     pub output_format: Option<OutputFormat>,
     pub email_recipients: Option<Vec<String>>,
+    /// Replaces all targets; redacted credentials keep the stored value of
+    /// the target at the same position
+    pub delivery_targets: Option<Vec<DeliveryTarget>>,
 }
 
 /// Request to execute a report
@@ -381,6 +706,8 @@ pub struct CreateComplianceBaselineRequest {
     pub rules: Vec<ComplianceRule>,
     #[serde(default)]
     pub severity_level: SeverityLevel,
+    #[serde(default)]
+    pub node_group_ids: Vec<Uuid>,
 }
 
 /// Request to update a compliance baseline
@@ -390,6 +717,7 @@ pub struct UpdateComplianceBaselineRequest {
     pub description: Option<Option<String>>,
     pub rules: Option<Vec<ComplianceRule>>,
     pub severity_level: Option<SeverityLevel>,
+    pub node_group_ids: Option<Vec<Uuid>>,
 }
 
 /// Request to create a drift baseline
@@ -412,6 +740,54 @@ pub struct UpdateDriftBaselineRequest {
     pub tolerance_config: Option<Option<DriftToleranceConfig>>,
 }
 
+/// Node or group whose current facts a drift baseline is captured from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DriftSnapshotSource {
+    /// All facts of a golden node
+    Node { certname: String },
+    /// Facts with the same value on every member of a node group
+    Group { group_id: Uuid },
+}
+
+/// What to capture into a drift baseline snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftSnapshotRequest {
+    pub source: DriftSnapshotSource,
+    /// Facts to leave out of the baseline
+    #[serde(default)]
+    pub exclude_facts: Vec<String>,
+    /// Leave out facts known to change between runs (uptime, free memory, ...)
+    #[serde(default = "default_true")]
+    pub exclude_volatile: bool,
+}
+
+/// Facts a snapshot would capture, for reviewing exclusions before saving
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftSnapshotPreview {
+    pub source: DriftSnapshotSource,
+    /// Number of nodes the facts were taken from
+    pub node_count: usize,
+    /// Facts the baseline would hold
+    pub baseline_facts: serde_json::Value,
+    /// Captured facts that are considered volatile, whether excluded or not
+    pub volatile_facts: Vec<String>,
+    /// Facts left out of the baseline
+    pub excluded_facts: Vec<String>,
+    /// Facts left out because their value differs between group members
+    pub differing_facts: Vec<String>,
+}
+
+/// Request to create a drift baseline from a snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateDriftBaselineFromSnapshotRequest {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(flatten)]
+    pub snapshot: DriftSnapshotRequest,
+    pub tolerance_config: Option<DriftToleranceConfig>,
+}
+
 // ==================== Report Results ====================
 
 /// Node health report result
@@ -434,6 +810,10 @@ pub struct NodeHealthSummary {
     pub failed_count: i64,
     pub noop_count: i64,
     pub unreported_count: i64,
+    /// Failed nodes covered by an active maintenance window, not counted
+    /// in `failed_count`
+    #[serde(default)]
+    pub maintenance_count: i64,
     pub compliance_rate: f64,
 }
 
@@ -467,18 +847,39 @@ pub struct NodeHealthDetail {
     pub last_report_at: Option<DateTime<Utc>>,
     pub failed_resources: Option<i64>,
     pub changed_resources: Option<i64>,
+    /// Whether an active maintenance window covers the node
+    #[serde(default)]
+    pub in_maintenance: bool,
 }
 
 /// Compliance report result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComplianceReport {
     pub generated_at: DateTime<Utc>,
+    /// Names of the evaluated baselines, comma separated
     pub baseline_name: String,
+    /// Totals across all baselines; a node is compliant when it passes every
+    /// baseline that applies to it
     pub summary: ComplianceSummary,
     pub by_severity: Vec<SeverityBreakdown>,
+    /// Results of each evaluated baseline
+    #[serde(default)]
+    pub baselines: Vec<BaselineComplianceSummary>,
     pub violations: Vec<ComplianceViolation>,
 }
 
+/// Compliance results of a single baseline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BaselineComplianceSummary {
+    pub baseline_id: Uuid,
+    pub baseline_name: String,
+    pub severity_level: SeverityLevel,
+    /// Node groups the baseline was scoped to; all nodes in scope when empty
+    pub node_group_ids: Vec<Uuid>,
+    pub summary: ComplianceSummary,
+    pub by_severity: Vec<SeverityBreakdown>,
+}
+
 /// Summary of compliance status
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComplianceSummary {
@@ -501,12 +902,20 @@ pub struct SeverityBreakdown {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ComplianceViolation {
     pub certname: String,
+    /// Baseline the violated rule belongs to
+    #[serde(default)]
+    pub baseline_id: Option<Uuid>,
+    #[serde(default)]
+    pub baseline_name: Option<String>,
     pub rule_id: String,
     pub rule_name: String,
     pub fact_name: String,
     pub expected_value: serde_json::Value,
     pub actual_value: serde_json::Value,
     pub severity: SeverityLevel,
+    /// Remediation hint of the violated rule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remediation: Option<String>,
 }
 
 /// Change tracking report result
@@ -526,6 +935,12 @@ pub struct ChangeSummary {
     pub nodes_affected: i64,
     pub resources_changed: i64,
     pub resources_failed: i64,
+    /// Changed resources that Puppet brought back to the desired state
+    #[serde(default)]
+    pub corrective_changes: i64,
+    /// Changed resources whose desired state changed
+    #[serde(default)]
+    pub intentional_changes: i64,
 }
 
 /// Breakdown by resource type
@@ -546,6 +961,107 @@ pub struct ChangeDetail {
     pub old_value: Option<serde_json::Value>,
     pub new_value: Option<serde_json::Value>,
     pub status: String,
+    /// Whether the change corrected drift rather than applying new code
+    #[serde(default)]
+    pub corrective_change: bool,
+}
+
+/// Corrective and intentional changes across the fleet
+///
+/// Counts come from the `corrective_change` and `changed` resource metrics
+/// of non-noop reports: corrective changes put back state that drifted,
+/// intentional changes apply new desired state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrectiveChangeAnalytics {
+    pub generated_at: DateTime<Utc>,
+    pub days: u32,
+    pub summary: CorrectiveChangeSummary,
+    /// One entry per UTC day, oldest first
+    pub daily: Vec<CorrectiveChangeDay>,
+    /// Nodes with changes, most corrective changes first
+    pub nodes: Vec<NodeCorrectiveChanges>,
+}
+
+/// Fleet totals of corrective and intentional changes
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct CorrectiveChangeSummary {
+    pub reports_analyzed: i64,
+    pub corrective_changes: i64,
+    pub intentional_changes: i64,
+    /// Nodes with at least one corrective change
+    pub nodes_with_drift: i64,
+}
+
+/// Drift corrected on one UTC day
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CorrectiveChangeDay {
+    /// Date (YYYY-MM-DD)
+    pub date: String,
+    pub corrective_changes: i64,
+    pub intentional_changes: i64,
+    /// Nodes with at least one corrective change that day
+    pub nodes_corrected: i64,
+}
+
+/// Corrective and intentional changes of one node
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NodeCorrectiveChanges {
+    pub certname: String,
+    pub corrective_changes: i64,
+    pub intentional_changes: i64,
+    /// Reports with at least one corrective change
+    pub reports_with_drift: i64,
+    pub last_corrective_at: Option<DateTime<Utc>>,
+}
+
+/// Catalog compilation errors across the fleet, grouped by signature
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompileErrorAnalytics {
+    pub generated_at: DateTime<Utc>,
+    pub days: u32,
+    /// Failed reports analyzed
+    pub failed_reports: i64,
+    /// Failed reports whose catalog could not be compiled
+    pub compile_failures: i64,
+    /// Nodes with at least one compile failure
+    pub nodes_affected: i64,
+    /// Distinct error signatures, before `errors` is truncated
+    pub total_signatures: i64,
+    /// Most widespread errors first
+    pub errors: Vec<CompileErrorGroup>,
+}
+
+/// Kind of catalog compilation error
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum CompileErrorKind {
+    MissingClass,
+    DuplicateDeclaration,
+    HieraLookup,
+    SyntaxError,
+    Other,
+}
+
+/// Compile failures sharing an error signature
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CompileErrorGroup {
+    /// Error with the node names and code locations left out, e.g.
+    /// "Could not find class profile::web"
+    pub signature: String,
+    pub kind: CompileErrorKind,
+    /// Server error of the latest occurrence
+    pub message: String,
+    /// Manifest where the latest occurrence failed
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    /// Reports failing with this error
+    pub occurrences: i64,
+    pub node_count: i64,
+    /// Affected nodes, sorted (at most 20)
+    pub nodes: Vec<String>,
+    pub environments: Vec<String>,
+    pub first_seen: Option<DateTime<Utc>>,
+    pub last_seen: Option<DateTime<Utc>>,
 }
 
 /// Drift detection report result
@@ -619,6 +1135,8 @@ mod tests {
         assert_eq!(OutputFormat::Json.content_type(), "application/json");
         assert_eq!(OutputFormat::Csv.content_type(), "text/csv");
         assert_eq!(OutputFormat::Pdf.content_type(), "application/pdf");
+        assert_eq!(OutputFormat::from_str("xlsx"), Some(OutputFormat::Xlsx));
+        assert_eq!(OutputFormat::Html.file_extension(), "html");
     }
 
     #[test]
@@ -649,4 +1167,58 @@ mod tests {
         assert!(json.contains("\"time_range\":\"24h\""));
         assert!(json.contains("\"include_error_details\":true"));
     }
+
+    #[test]
+    fn test_delivery_target_redaction() {
+        let target: DeliveryTarget = serde_json::from_value(serde_json::json!({
+            "type": "s3",
+            "endpoint": "https://s3.example.com/",
+            "bucket": "reports",
+            "prefix": "/weekly/",
+            "access_key_id": "AKID",
+            "secret_access_key": "s3cr3t",
+        }))
+        .unwrap();
+        assert_eq!(target.type_name(), "s3");
+        assert_eq!(target.describe(), "https://s3.example.com/reports/weekly");
+
+        let mut redacted = target.redacted();
+        assert!(!serde_json::to_string(&redacted).unwrap().contains("s3cr3t"));
+        assert!(redacted.has_redacted_secret());
+        redacted.restore_secrets(&target);
+        assert_eq!(redacted, target);
+
+        // A WebDAV target without a password stays without one
+        let webdav = DeliveryTarget::Webdav {
+            url: "https://dav.example.com/reports".to_string(),
+            username: None,
+            password: None,
+        };
+        assert_eq!(webdav.redacted(), webdav);
+    }
+
+    #[test]
+    fn test_delivery_status_from_results() {
+        let result = |success| DeliveryResult {
+            target_type: "filesystem".to_string(),
+            target: "/srv/reports".to_string(),
+            success,
+            location: None,
+            error: None,
+            delivered_at: Utc::now(),
+        };
+        assert_eq!(DeliveryStatus::from_results(&[]), None);
+        assert_eq!(
+            DeliveryStatus::from_results(&[result(true), result(true)]),
+            Some(DeliveryStatus::Delivered)
+        );
+        assert_eq!(
+            DeliveryStatus::from_results(&[result(true), result(false)]),
+            Some(DeliveryStatus::Partial)
+        );
+        assert_eq!(
+            DeliveryStatus::from_results(&[result(false)]),
+            Some(DeliveryStatus::Failed)
+        );
+    }
 }
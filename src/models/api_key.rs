@@ -4,6 +4,15 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::{Action, Resource};
+
+/// A resource/action pair an API key is limited to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ApiKeyScope {
+    pub resource: Resource,
+    pub action: Action,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ApiKey {
     pub id: Uuid,
@@ -11,8 +20,16 @@ pub struct ApiKey {
     pub user_id: Uuid,
     pub name: String,
     pub role_ids: Vec<Uuid>,
+    /// Permission scopes; empty means the key is limited by its roles only
+    pub scopes: Vec<ApiKeyScope>,
+    /// IP addresses and CIDR ranges the key may be used from; empty means any
+    pub allowed_ips: Vec<String>,
     pub last_used_at: Option<DateTime<Utc>>,
     pub expires_at: Option<DateTime<Utc>>,
+    /// When the secret was last rotated
+    pub rotated_at: Option<DateTime<Utc>>,
+    /// Until when the secret replaced by the last rotation is still accepted
+    pub previous_key_expires_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -25,6 +42,10 @@ pub struct CreateApiKeyRequest {
     pub organization_id: Option<Uuid>,
     /// Optional role scope; defaults to caller's roles
     pub role_ids: Option<Vec<Uuid>>,
+    /// Optional permission scopes; each must be granted by the key's roles
+    pub scopes: Option<Vec<ApiKeyScope>>,
+    /// Optional IP allowlist (addresses or CIDR ranges)
+    pub allowed_ips: Option<Vec<String>>,
     /// Optional expiry (RFC3339 timestamp)
     pub expires_at: Option<DateTime<Utc>>,
 }
@@ -33,6 +54,6 @@ pub struct CreateApiKeyRequest {
 pub struct CreateApiKeyResponse {
     #[serde(flatten)]
     pub api_key: ApiKey,
-    /// Plaintext API key (only returned on creation)
+    /// Plaintext API key (only returned on creation and rotation)
     pub key: String,
 }
@@ -4,16 +4,24 @@ use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use crate::models::UserKind;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuditLogEntry {
     pub id: Uuid,
     pub organization_id: Uuid,
     pub user_id: Option<Uuid>,
+    /// Admin who made the change while impersonating `user_id`
+    pub impersonator_id: Option<Uuid>,
+    /// Kind of `user_id`, to tell service accounts from people
+    pub user_kind: Option<UserKind>,
     pub action: String,
     pub resource_type: String,
     pub resource_id: Option<String>,
     pub details: Option<serde_json::Value>,
     pub ip_address: Option<String>,
+    /// ID of the API request that made the change
+    pub request_id: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -21,8 +29,18 @@ pub struct AuditLogEntry {
 pub struct AuditLogQuery {
     pub organization_id: Option<Uuid>,
     pub user_id: Option<Uuid>,
+    /// Entries recorded while this admin impersonated a user
+    pub impersonator_id: Option<Uuid>,
+    /// Entries of users of this kind, e.g. only service accounts
+    pub user_kind: Option<UserKind>,
     pub resource_type: Option<String>,
     pub action: Option<String>,
+    /// Entries recorded by one API request
+    pub request_id: Option<String>,
+    /// Entries created at or after this time
+    pub from: Option<DateTime<Utc>>,
+    /// Entries created before this time
+    pub to: Option<DateTime<Utc>>,
     pub limit: Option<u32>,
     pub offset: Option<u32>,
 }
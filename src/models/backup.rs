@@ -250,6 +250,9 @@ pub struct BackupRestoreResponse {
     pub restored_by: Option<Uuid>,
     pub restored_by_username: Option<String>,
     pub created_at: DateTime<Utc>,
+    /// The restored database is staged and takes effect on the next restart
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub restart_required: bool,
 }
 
 impl BackupRestoreResponse {
@@ -275,6 +278,7 @@ impl BackupRestoreResponse {
             restored_by: restore.restored_by,
             restored_by_username: username,
             created_at: restore.created_at,
+            restart_required: false,
         }
     }
 }
@@ -0,0 +1,170 @@
+//! Certificate renewal campaign models
+//!
+//! A campaign collects node certificates close to expiry, optionally asks the
+//! agents to renew them, and tracks each node until the CA holds a newer
+//! certificate or the deadline passes.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Campaign lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RenewalCampaignStatus {
+    /// Targets are still being tracked
+    Active,
+    /// Every target was renewed or failed
+    Completed,
+    /// Stopped by an administrator
+    Cancelled,
+}
+
+impl RenewalCampaignStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Active => "active",
+            Self::Completed => "completed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "active" => Some(Self::Active),
+            "completed" => Some(Self::Completed),
+            "cancelled" => Some(Self::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+/// Renewal state of a single node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RenewalTargetStatus {
+    /// Still on the certificate it had when the campaign started
+    Pending,
+    /// The CA holds a newer certificate for the node
+    Renewed,
+    /// Not renewed before the deadline, or the certificate was revoked
+    Failed,
+}
+
+impl RenewalTargetStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Renewed => "renewed",
+            Self::Failed => "failed",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(Self::Pending),
+            "renewed" => Some(Self::Renewed),
+            "failed" => Some(Self::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// A signed certificate close to expiry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenewalCandidate {
+    pub certname: String,
+    pub serial: String,
+    pub not_after: DateTime<Utc>,
+    pub days_until_expiry: i64,
+}
+
+/// Target counts of a campaign
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RenewalSummary {
+    pub total: usize,
+    pub pending: usize,
+    pub renewed: usize,
+    pub failed: usize,
+}
+
+/// A certificate renewal campaign
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenewalCampaign {
+    pub id: Uuid,
+    pub name: String,
+    pub status: RenewalCampaignStatus,
+    /// Targets not renewed by this time are marked failed
+    pub deadline: DateTime<Utc>,
+    /// Whether renewal was dispatched to the agents as an update job
+    pub trigger_agents: bool,
+    /// Update job carrying the agent renewal requests
+    pub update_job_id: Option<String>,
+    /// User ID of the creator
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub summary: RenewalSummary,
+}
+
+/// A node tracked by a campaign
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenewalTarget {
+    pub certname: String,
+    pub status: RenewalTargetStatus,
+    pub old_serial: String,
+    pub old_not_after: DateTime<Utc>,
+    pub new_serial: Option<String>,
+    pub new_not_after: Option<DateTime<Utc>>,
+    /// Why the renewal failed
+    pub error: Option<String>,
+    pub updated_at: DateTime<Utc>,
+    /// Manual renewal steps (pending targets only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub instructions: Vec<String>,
+}
+
+impl RenewalTarget {
+    /// When the target fails if still not renewed
+    pub fn due_at(&self, campaign_deadline: DateTime<Utc>) -> DateTime<Utc> {
+        campaign_deadline.min(self.old_not_after)
+    }
+}
+
+/// A campaign with its targets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenewalCampaignDetail {
+    #[serde(flatten)]
+    pub campaign: RenewalCampaign,
+    pub targets: Vec<RenewalTarget>,
+}
+
+/// Request body for creating a renewal campaign
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRenewalCampaignRequest {
+    pub name: Option<String>,
+    /// Nodes to renew; defaults to every candidate within `within_days`
+    #[serde(default)]
+    pub certnames: Vec<String>,
+    /// Expiry window for picking candidates (defaults to
+    /// `puppet_ca.leaf_expiry_warning_days`)
+    pub within_days: Option<i64>,
+    /// Defaults to the latest expiry among the targets
+    pub deadline: Option<DateTime<Utc>>,
+    /// Queue a `cert_renewal` update job so agents renew on their next run
+    #[serde(default)]
+    pub trigger_agents: bool,
+}
+
+/// Query parameters for listing renewal candidates
+#[derive(Debug, Clone, Deserialize)]
+pub struct RenewalCandidatesQuery {
+    pub within_days: Option<i64>,
+}
+
+/// Query parameters for a campaign's targets
+#[derive(Debug, Clone, Deserialize)]
+pub struct RenewalTargetsQuery {
+    pub status: Option<RenewalTargetStatus>,
+}
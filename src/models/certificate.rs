@@ -51,6 +51,51 @@ pub struct Certificate {
     pub fingerprint: String,
     /// Certificate state
     pub state: CertificateStatus,
+    /// Revocation details from the CRL (revoked certificates only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revocation: Option<RevocationInfo>,
+}
+
+/// Revocation details of a certificate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevocationInfo {
+    /// When the certificate was revoked
+    pub revoked_at: DateTime<Utc>,
+    /// RFC 5280 reason, e.g. `keyCompromise`
+    pub reason: Option<String>,
+    /// Issuer of the CRL listing the certificate
+    pub crl_issuer: String,
+}
+
+/// A revoked certificate entry of a CRL
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevokedCertificate {
+    /// Serial number (decimal)
+    pub serial: String,
+    pub revoked_at: DateTime<Utc>,
+    /// RFC 5280 reason, e.g. `keyCompromise`
+    pub reason: Option<String>,
+}
+
+/// A decoded certificate revocation list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertificateRevocationList {
+    /// Issuer distinguished name
+    pub issuer: String,
+    /// CRL number extension
+    pub crl_number: Option<String>,
+    pub this_update: DateTime<Utc>,
+    pub next_update: Option<DateTime<Utc>>,
+    pub revoked_count: usize,
+    pub revoked: Vec<RevokedCertificate>,
+}
+
+/// CRL as served by the CA (one CRL per CA in the chain)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrlResponse {
+    /// When the CRL was fetched from the CA
+    pub fetched_at: DateTime<Utc>,
+    pub crls: Vec<CertificateRevocationList>,
 }
 
 /// CA status information
@@ -66,6 +111,168 @@ pub struct CAStatus {
     pub pending_requests: usize,
     /// Number of signed certificates
     pub signed_certificates: usize,
+    /// Set when the CA is unreachable and the counts come from the last snapshot
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot_at: Option<DateTime<Utc>>,
+}
+
+/// Validity of a node certificate, as reported by the lightweight status check
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CertificateValidity {
+    /// Signed and within its validity period
+    Valid,
+    Revoked,
+    /// Signed but past `not_after`
+    Expired,
+    /// No signed certificate (missing, pending or rejected request)
+    Unknown,
+}
+
+impl CertificateValidity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Valid => "valid",
+            Self::Revoked => "revoked",
+            Self::Expired => "expired",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+/// Lightweight certificate status for load balancers and provisioning tools
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertificateStatusCheck {
+    pub certname: String,
+    pub status: CertificateValidity,
+    pub serial: Option<String>,
+    pub not_after: Option<DateTime<Utc>>,
+    /// When the certificate was revoked, if known from the CRL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl CertificateStatusCheck {
+    /// Status of `certificate` at `now`; `None` means the CA has no certificate
+    pub fn from_certificate(
+        certname: &str,
+        certificate: Option<&Certificate>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let status = match certificate.map(|c| &c.state) {
+            Some(CertificateStatus::Revoked) => CertificateValidity::Revoked,
+            Some(CertificateStatus::Signed) => {
+                if certificate.is_some_and(|c| c.not_after <= now) {
+                    CertificateValidity::Expired
+                } else {
+                    CertificateValidity::Valid
+                }
+            }
+            _ => CertificateValidity::Unknown,
+        };
+
+        // Pending or rejected requests carry no usable serial or expiry
+        let certificate = certificate.filter(|_| status != CertificateValidity::Unknown);
+        Self {
+            certname: certname.to_string(),
+            status,
+            serial: certificate.map(|c| c.serial.clone()),
+            not_after: certificate.map(|c| c.not_after),
+            revoked_at: certificate
+                .and_then(|c| c.revocation.as_ref())
+                .map(|r| r.revoked_at),
+        }
+    }
+
+    /// Entity tag identifying this status
+    pub fn etag(&self) -> String {
+        format!(
+            "\"{}-{}-{}\"",
+            self.status.as_str(),
+            self.serial.as_deref().unwrap_or("none"),
+            self.not_after.map(|t| t.timestamp()).unwrap_or(0)
+        )
+    }
+}
+
+/// Last known state of the CA, kept for when the CA is unreachable
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaSnapshot {
+    /// CA certificate bundle (PEM, includes intermediates)
+    pub ca_bundle_pem: Option<String>,
+    /// Certificate revocation list (PEM)
+    pub crl_pem: Option<String>,
+    pub certificates: Vec<Certificate>,
+    pub requests: Vec<CertificateRequest>,
+    pub synced_at: DateTime<Utc>,
+}
+
+/// Counts describing a CA snapshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaSnapshotSummary {
+    pub synced_at: DateTime<Utc>,
+    pub certificates: usize,
+    pub requests: usize,
+    pub has_crl: bool,
+}
+
+impl From<&CaSnapshot> for CaSnapshotSummary {
+    fn from(snapshot: &CaSnapshot) -> Self {
+        Self {
+            synced_at: snapshot.synced_at,
+            certificates: snapshot.certificates.len(),
+            requests: snapshot.requests.len(),
+            has_crl: snapshot.crl_pem.is_some(),
+        }
+    }
+}
+
+/// Position of a certificate in a CA chain
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ChainRole {
+    Root,
+    Intermediate,
+    Leaf,
+}
+
+/// A certificate of a CA chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainCertificate {
+    pub subject: String,
+    pub issuer: String,
+    /// Serial number (decimal)
+    pub serial: String,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+    /// Fingerprint (SHA256)
+    pub fingerprint: String,
+    pub role: ChainRole,
+    /// Days until `not_after` (negative once expired)
+    pub days_until_expiry: i64,
+    /// Within the expiry warning window for its role
+    pub expiring_soon: bool,
+}
+
+/// The CA certificates of the CA bundle, each chain leaf-most CA first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaChain {
+    pub certificates: Vec<ChainCertificate>,
+    /// Expiry warnings for root and intermediate CAs
+    pub warnings: Vec<String>,
+}
+
+/// Result of validating a node certificate against the CA bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainValidation {
+    pub certname: String,
+    pub valid: bool,
+    /// Why validation failed
+    pub error: Option<String>,
+    /// The node certificate followed by its issuers
+    pub chain: Vec<ChainCertificate>,
+    /// Expiry warnings for the node certificate and its issuers
+    pub warnings: Vec<String>,
 }
 
 /// Request body for signing a certificate
@@ -103,6 +310,55 @@ pub struct RevokeResponse {
     pub message: String,
 }
 
+/// Request body for bulk certificate signing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkSignRequest {
+    /// Certnames of the pending requests to sign
+    #[serde(default)]
+    pub certnames: Vec<String>,
+    /// Sign every pending request instead of `certnames`
+    #[serde(default)]
+    pub all_pending: bool,
+}
+
+/// Request body for bulk certificate revocation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkRevokeRequest {
+    /// Certnames of the certificates to revoke
+    pub certnames: Vec<String>,
+}
+
+/// Outcome of a bulk operation for a single certname
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkCertificateResult {
+    pub certname: String,
+    pub success: bool,
+    /// Status or error message
+    pub message: String,
+}
+
+/// Response from a bulk sign or revoke operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkCertificateResponse {
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<BulkCertificateResult>,
+}
+
+impl BulkCertificateResponse {
+    /// Build the response from per-item results
+    pub fn from_results(results: Vec<BulkCertificateResult>) -> Self {
+        let succeeded = results.iter().filter(|r| r.success).count();
+        Self {
+            total: results.len(),
+            succeeded,
+            failed: results.len() - succeeded,
+            results,
+        }
+    }
+}
+
 /// Request body for CA renewal
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RenewCARequest {
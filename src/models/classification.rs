@@ -3,6 +3,8 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::NodeMaintenance;
+
 /// Result of classifying a node
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ClassificationResult {
@@ -30,6 +32,10 @@ pub struct ClassificationResult {
     /// Error if node matches groups from multiple organizations
     #[serde(skip_serializing_if = "Option::is_none")]
     pub conflict_error: Option<String>,
+
+    /// Active maintenance windows covering the node
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub maintenance: Vec<NodeMaintenance>,
 }
 
 /// A group that a node matches
@@ -100,6 +106,7 @@ mod tests {
             variables: serde_json::json!({}),
             environment: Some("production".to_string()),
             conflict_error: None,
+            maintenance: vec![],
         };
 
         assert_eq!(result.certname, "node1.example.com");
@@ -0,0 +1,100 @@
+//! Classification key models
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::services::node_janitor::matches_pattern;
+
+/// A managed shared key for the classification endpoints
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassificationKey {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    /// Certname patterns (`*` and `?` wildcards) the key may be used for;
+    /// empty allows any node
+    pub allowed_cn_patterns: Vec<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    /// When the secret was last rotated
+    pub rotated_at: Option<DateTime<Utc>>,
+    /// Until when the secret replaced by the last rotation is still accepted
+    pub previous_key_expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    /// Last `--ping` of an ENC script using the key
+    pub last_handshake_at: Option<DateTime<Utc>>,
+    /// Host that sent the last handshake
+    pub last_handshake_host: Option<String>,
+}
+
+impl ClassificationKey {
+    /// Whether the key may be used for a node
+    pub fn allows(&self, certname: &str) -> bool {
+        self.allowed_cn_patterns.is_empty()
+            || self
+                .allowed_cn_patterns
+                .iter()
+                .any(|pattern| matches_pattern(certname, pattern))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateClassificationKeyRequest {
+    pub name: String,
+    pub description: Option<String>,
+    /// Optional certname patterns; any node when omitted
+    pub allowed_cn_patterns: Option<Vec<String>>,
+}
+
+/// Changes to a key; omitted fields are kept
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdateClassificationKeyRequest {
+    pub description: Option<String>,
+    pub allowed_cn_patterns: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CreateClassificationKeyResponse {
+    #[serde(flatten)]
+    pub classification_key: ClassificationKey,
+    /// Plaintext key (only returned on creation and rotation)
+    pub key: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key_with_patterns(patterns: &[&str]) -> ClassificationKey {
+        ClassificationKey {
+            id: Uuid::new_v4(),
+            name: "puppetserver".to_string(),
+            description: None,
+            allowed_cn_patterns: patterns.iter().map(|p| p.to_string()).collect(),
+            created_by: None,
+            created_at: Utc::now(),
+            rotated_at: None,
+            previous_key_expires_at: None,
+            revoked_at: None,
+            last_used_at: None,
+            last_handshake_at: None,
+            last_handshake_host: None,
+        }
+    }
+
+    #[test]
+    fn test_allows_any_node_without_patterns() {
+        assert!(key_with_patterns(&[]).allows("web1.example.com"));
+    }
+
+    #[test]
+    fn test_allows_only_matching_nodes() {
+        let key = key_with_patterns(&["*.web.example.com", "db?.example.com"]);
+        assert!(key.allows("app1.web.example.com"));
+        assert!(key.allows("DB1.example.com"));
+        assert!(!key.allows("db10.example.com"));
+        assert!(!key.allows("mail.example.com"));
+    }
+}
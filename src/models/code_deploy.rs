@@ -115,6 +115,18 @@ pub struct CreateSshKeyRequest {
     pub private_key: String,
 }
 
+/// Request to generate a new SSH keypair server-side
+///
+/// The private key is generated, encrypted and stored on the server; only the
+/// public key is ever returned to the client.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerateSshKeyRequest {
+    pub name: String,
+    /// Comment appended to the public key (defaults to `openvox-webui@<name>`)
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
 // ============================================================================
 // PAT Token Models
 // ============================================================================
@@ -249,6 +261,39 @@ impl Default for AuthType {
     }
 }
 
+/// How the environments of a repository are deployed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum DeployStrategy {
+    /// r10k deploys the environment and the modules of its Puppetfile
+    #[default]
+    R10k,
+    /// The branch is checked out into the environment directory and
+    /// librarian-puppet installs the modules of its Puppetfile
+    LibrarianPuppet,
+    /// The branch is checked out into the environment directory
+    GitCheckout,
+}
+
+impl DeployStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeployStrategy::R10k => "r10k",
+            DeployStrategy::LibrarianPuppet => "librarian_puppet",
+            DeployStrategy::GitCheckout => "git_checkout",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "r10k" => Some(DeployStrategy::R10k),
+            "librarian_puppet" => Some(DeployStrategy::LibrarianPuppet),
+            "git_checkout" => Some(DeployStrategy::GitCheckout),
+            _ => None,
+        }
+    }
+}
+
 /// Git repository configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodeRepository {
@@ -267,6 +312,9 @@ pub struct CodeRepository {
     /// Encrypted GitHub PAT (used when auth_type = pat) - DEPRECATED, kept for migration
     #[serde(skip_serializing)]
     pub github_pat_encrypted: Option<String>,
+    /// How environments are deployed
+    #[serde(default)]
+    pub deploy_strategy: DeployStrategy,
     /// Webhook secret for verifying incoming webhooks
     #[serde(skip_serializing_if = "Option::is_none")]
     pub webhook_secret: Option<String>,
@@ -294,6 +342,7 @@ pub struct CodeRepositoryResponse {
     pub ssh_key_name: Option<String>,
     /// Indicates if PAT is configured (true/false, never exposes actual token)
     pub has_pat: bool,
+    pub deploy_strategy: DeployStrategy,
     /// Webhook URL for this repository
     pub webhook_url: Option<String>,
     pub poll_interval_seconds: i32,
@@ -320,6 +369,9 @@ pub struct CreateRepositoryRequest {
     pub pat_token_id: Option<Uuid>,
     /// GitHub Personal Access Token (required if auth_type = pat) - DEPRECATED, use pat_token_id
     pub github_pat: Option<String>,
+    /// How environments are deployed (defaults to r10k)
+    #[serde(default)]
+    pub deploy_strategy: DeployStrategy,
     #[serde(default = "default_poll_interval")]
     pub poll_interval_seconds: i32,
     #[serde(default)]
@@ -355,6 +407,7 @@ pub struct UpdateRepositoryRequest {
     /// Set to true to clear the PAT - DEPRECATED
     #[serde(default)]
     pub clear_github_pat: bool,
+    pub deploy_strategy: Option<DeployStrategy>,
     pub poll_interval_seconds: Option<i32>,
     pub is_control_repo: Option<bool>,
     /// Regenerate the webhook secret
@@ -439,10 +492,67 @@ pub struct CodeDeployment {
     pub error_message: Option<String>,
     /// Full r10k output
     pub r10k_output: Option<String>,
+    /// r10k flags requested for this deployment
+    pub options: DeploymentOptions,
+    /// Command line run by the deployment, once started
+    pub command_line: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
 
+/// r10k flags of a deployment
+///
+/// The defaults deploy the whole environment with the flags of the `r10k`
+/// configuration.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeploymentOptions {
+    /// Deploy only this module of the environment's Puppetfile
+    /// (`r10k deploy module`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+    /// Deploy the environment without processing its Puppetfile
+    pub skip_puppetfile: bool,
+    /// Only deploy the modules whose Puppetfile entries changed
+    /// (`--incremental`)
+    pub incremental: bool,
+    /// Run `--generate-types`, overriding the `r10k` configuration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generate_types: Option<bool>,
+}
+
+impl DeploymentOptions {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Check that the options can be combined
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(module) = &self.module {
+            let valid = module
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_lowercase())
+                && module
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+            if !valid {
+                return Err(format!("Invalid module name: {}", module));
+            }
+            if self.skip_puppetfile || self.incremental {
+                return Err(
+                    "skip_puppetfile and incremental do not apply to a module deployment"
+                        .to_string(),
+                );
+            }
+        }
+        if self.skip_puppetfile && self.incremental {
+            return Err("incremental requires processing the Puppetfile".to_string());
+        }
+        Ok(())
+    }
+}
+
 /// Summary view of a deployment (for embedding in other responses)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodeDeploymentSummary {
@@ -488,6 +598,8 @@ pub struct CodeDeploymentResponse {
     pub duration_seconds: Option<i64>,
     pub error_message: Option<String>,
     pub r10k_output: Option<String>,
+    pub options: DeploymentOptions,
+    pub command_line: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -498,6 +610,9 @@ pub struct TriggerDeploymentRequest {
     pub environment_id: Uuid,
     /// Optional specific commit SHA (defaults to latest)
     pub commit_sha: Option<String>,
+    /// r10k flags (defaults to a full environment deployment)
+    #[serde(default)]
+    pub options: DeploymentOptions,
 }
 
 /// Request to approve a deployment
@@ -561,6 +676,281 @@ pub enum WebhookProvider {
     Bitbucket,
 }
 
+// ============================================================================
+// Credential Health Models
+// ============================================================================
+
+/// Health of a credential or of a repository's authentication
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CredentialHealthStatus {
+    Healthy,
+    /// Needs attention soon, e.g. a token about to expire
+    Warning,
+    /// Repositories cannot be synced, e.g. an expired token
+    Critical,
+}
+
+/// Health of a PAT token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PatTokenHealth {
+    pub id: Uuid,
+    pub name: String,
+    pub status: CredentialHealthStatus,
+    pub issues: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub days_until_expiration: Option<i64>,
+    pub last_validated_at: Option<DateTime<Utc>>,
+    /// Repositories authenticating with this token
+    pub repository_count: usize,
+}
+
+/// Health of an SSH key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SshKeyHealth {
+    pub id: Uuid,
+    pub name: String,
+    pub status: CredentialHealthStatus,
+    pub issues: Vec<String>,
+    /// Days since the key was created
+    pub age_days: i64,
+    /// Repositories authenticating with this key
+    pub repository_count: usize,
+}
+
+/// Health of a repository's authentication
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepositoryAuthHealth {
+    pub id: Uuid,
+    pub name: String,
+    pub url: String,
+    pub auth_type: AuthType,
+    pub status: CredentialHealthStatus,
+    pub issues: Vec<String>,
+    /// Whether the last sync failed to authenticate
+    pub auth_failed: bool,
+    pub last_error: Option<String>,
+    pub last_error_at: Option<DateTime<Utc>>,
+}
+
+/// Number of credentials and repositories in each health status
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CredentialHealthSummary {
+    pub healthy: usize,
+    pub warning: usize,
+    pub critical: usize,
+}
+
+/// Health of all Code Deploy credentials and repository authentication
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CredentialHealthReport {
+    /// Worst status of any credential or repository
+    pub status: CredentialHealthStatus,
+    pub summary: CredentialHealthSummary,
+    pub pat_tokens: Vec<PatTokenHealth>,
+    pub ssh_keys: Vec<SshKeyHealth>,
+    pub repositories: Vec<RepositoryAuthHealth>,
+    pub generated_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// Deployment Impact
+// ============================================================================
+
+/// How a module changed in a deployment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ModuleChangeSource {
+    /// Files of a module kept in the repository changed
+    Code,
+    /// The module's Puppetfile declaration was added, changed or removed
+    Puppetfile,
+}
+
+/// Class whose manifest changed in a deployment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassImpact {
+    pub name: String,
+    /// Nodes whose current catalog in the environment includes the class
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_count: Option<u64>,
+}
+
+/// Module changed in a deployment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleImpact {
+    pub name: String,
+    pub sources: Vec<ModuleChangeSource>,
+    /// Classes whose manifest changed; empty when only other files of the
+    /// module changed
+    pub classes: Vec<ClassImpact>,
+    /// Nodes whose current catalog in the environment includes any class of
+    /// the module
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub node_count: Option<u64>,
+}
+
+/// Blast radius of a deployment, from the changes between the environment's
+/// last successful deployment and the deployed commit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeploymentImpact {
+    pub deployment_id: Uuid,
+    pub environment: String,
+    /// Commit of the last successful deployment, else the parent of the
+    /// deployed commit
+    pub base_commit: Option<String>,
+    pub commit_sha: String,
+    pub changed_files: Vec<String>,
+    pub modules: Vec<ModuleImpact>,
+    /// Whether the environment's main manifest changed, which affects every
+    /// node of the environment
+    pub site_manifest_changed: bool,
+    /// Whether Hiera data or the environment configuration changed, which may
+    /// affect every node of the environment
+    pub data_changed: bool,
+    /// Distinct nodes using a changed class or module, or every node of the
+    /// environment when its main manifest or data changed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub affected_nodes: Option<u64>,
+    /// Nodes whose current catalog was compiled in the environment
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment_nodes: Option<u64>,
+    /// Why node counts are missing
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub puppetdb_error: Option<String>,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// State of a canary run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CanaryStatus {
+    /// Waiting for reports of the canary nodes
+    #[default]
+    Running,
+    /// Every canary node reported successfully; waiting to be promoted
+    Passed,
+    /// A canary node failed, or did not report in time
+    Failed,
+    /// The deployment was approved for the target environment
+    Promoted,
+    /// The deployment was rejected
+    RolledBack,
+    /// The deployment was approved, rejected or cancelled outside the canary
+    Abandoned,
+}
+
+impl CanaryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CanaryStatus::Running => "running",
+            CanaryStatus::Passed => "passed",
+            CanaryStatus::Failed => "failed",
+            CanaryStatus::Promoted => "promoted",
+            CanaryStatus::RolledBack => "rolled_back",
+            CanaryStatus::Abandoned => "abandoned",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "running" => Some(CanaryStatus::Running),
+            "passed" => Some(CanaryStatus::Passed),
+            "failed" => Some(CanaryStatus::Failed),
+            "promoted" => Some(CanaryStatus::Promoted),
+            "rolled_back" => Some(CanaryStatus::RolledBack),
+            "abandoned" => Some(CanaryStatus::Abandoned),
+            _ => None,
+        }
+    }
+
+    /// Whether the canary nodes are still pinned to the temporary environment
+    pub fn is_active(&self) -> bool {
+        matches!(self, CanaryStatus::Running | CanaryStatus::Passed)
+    }
+}
+
+/// Reports of a canary node in the temporary environment
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CanaryNodeReports {
+    pub certname: String,
+    /// Reports that did not fail
+    pub successful_reports: u32,
+    pub failed_reports: u32,
+    pub last_status: Option<String>,
+    pub last_report_at: Option<DateTime<Utc>>,
+}
+
+/// Canary run of a deployment
+///
+/// The deployed commit is checked out into a temporary environment and a
+/// sample of nodes from a group is pinned to it until their reports decide
+/// whether the deployment is promoted or rolled back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CodeCanary {
+    pub id: Uuid,
+    pub deployment_id: Uuid,
+    pub organization_id: Uuid,
+    /// Name of the temporary environment
+    pub environment: String,
+    /// Group the sample was taken from
+    pub source_group_id: Uuid,
+    /// Group pinning the sample to the temporary environment
+    pub canary_group_id: Option<Uuid>,
+    pub nodes: Vec<String>,
+    /// Successful reports each node must send before the canary passes
+    pub required_reports: u32,
+    /// Promote once passed and roll back once failed without waiting for an
+    /// operator
+    pub auto_promote: bool,
+    pub status: CanaryStatus,
+    pub node_reports: Vec<CanaryNodeReports>,
+    pub error_message: Option<String>,
+    pub started_by: Option<Uuid>,
+    /// When the canary fails if some nodes have not reported enough
+    pub deadline_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn default_canary_sample_size() -> usize {
+    3
+}
+
+fn default_canary_required_reports() -> u32 {
+    1
+}
+
+fn default_canary_timeout_minutes() -> u32 {
+    120
+}
+
+/// Request to start a canary run of a pending deployment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartCanaryRequest {
+    /// Group whose nodes the sample is taken from
+    pub group_id: Uuid,
+    /// Number of nodes to pin to the temporary environment
+    #[serde(default = "default_canary_sample_size")]
+    pub sample_size: usize,
+    /// Successful reports each node must send
+    #[serde(default = "default_canary_required_reports")]
+    pub required_reports: u32,
+    /// Minutes to wait for the reports before failing the canary
+    #[serde(default = "default_canary_timeout_minutes")]
+    pub timeout_minutes: u32,
+    #[serde(default)]
+    pub auto_promote: bool,
+}
+
+/// Request to roll a canary back
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RollbackCanaryRequest {
+    /// Recorded as the rejection reason of the deployment
+    pub reason: Option<String>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -594,6 +984,55 @@ mod tests {
         assert!(DeploymentStatus::Rejected.can_retry());
     }
 
+    #[test]
+    fn test_deployment_options_validate() {
+        assert!(DeploymentOptions::default().validate().is_ok());
+
+        let module = DeploymentOptions {
+            module: Some("ntp".to_string()),
+            generate_types: Some(true),
+            ..Default::default()
+        };
+        assert!(module.validate().is_ok());
+
+        let invalid_name = DeploymentOptions {
+            module: Some("puppetlabs/ntp".to_string()),
+            ..Default::default()
+        };
+        assert!(invalid_name.validate().is_err());
+
+        let incremental_module = DeploymentOptions {
+            module: Some("ntp".to_string()),
+            incremental: true,
+            ..Default::default()
+        };
+        assert!(incremental_module.validate().is_err());
+
+        let incremental_without_puppetfile = DeploymentOptions {
+            skip_puppetfile: true,
+            incremental: true,
+            ..Default::default()
+        };
+        assert!(incremental_without_puppetfile.validate().is_err());
+    }
+
+    #[test]
+    fn test_start_canary_request_defaults() {
+        let req: StartCanaryRequest =
+            serde_json::from_str(r#"{"group_id":"6f1c8c52-1d7e-4b1b-9a57-3f0e6c1d2a90"}"#).unwrap();
+        assert_eq!(req.sample_size, 3);
+        assert_eq!(req.required_reports, 1);
+        assert_eq!(req.timeout_minutes, 120);
+        assert!(!req.auto_promote);
+
+        assert_eq!(
+            serde_json::to_string(&CanaryStatus::RolledBack).unwrap(),
+            "\"rolled_back\""
+        );
+        assert!(CanaryStatus::Passed.is_active());
+        assert!(!CanaryStatus::Failed.is_active());
+    }
+
     #[test]
     fn test_ssh_key_response_excludes_private_key() {
         let key = CodeSshKey {
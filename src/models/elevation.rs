@@ -0,0 +1,91 @@
+//! Privilege elevation models
+//!
+//! A user can ask for a higher role for a limited number of hours. Once an
+//! approver confirms the request the role is granted until the elevation
+//! expires or is revoked.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Longest elevation that can be requested
+pub const MAX_ELEVATION_HOURS: u32 = 24;
+
+/// Elevation lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ElevationStatus {
+    /// Waiting for an approver
+    Pending,
+    /// Granted until `expires_at`
+    Approved,
+    /// Rejected by an approver
+    Denied,
+    /// Ended early by the requester or an approver
+    Revoked,
+    /// Ran out of time
+    Expired,
+}
+
+impl ElevationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Denied => "denied",
+            Self::Revoked => "revoked",
+            Self::Expired => "expired",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "pending" => Some(Self::Pending),
+            "approved" => Some(Self::Approved),
+            "denied" => Some(Self::Denied),
+            "revoked" => Some(Self::Revoked),
+            "expired" => Some(Self::Expired),
+            _ => None,
+        }
+    }
+}
+
+/// A request for a temporary role
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleElevation {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub user_id: Uuid,
+    pub role_id: Uuid,
+    pub role_name: String,
+    pub reason: String,
+    pub duration_hours: u32,
+    pub status: ElevationStatus,
+    pub decided_by: Option<Uuid>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub decision_comment: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl RoleElevation {
+    /// Whether the elevation grants its role at `now`
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.status == ElevationStatus::Approved && self.expires_at.is_some_and(|exp| exp > now)
+    }
+}
+
+/// Request body for asking for a temporary role
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateElevationRequest {
+    pub role_id: Uuid,
+    pub duration_hours: u32,
+    pub reason: String,
+}
+
+/// Request body for approving, denying or revoking an elevation
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ElevationDecisionRequest {
+    pub comment: Option<String>,
+}
@@ -54,6 +54,15 @@ pub struct NodeGroup {
 
     /// Pinned (static) nodes
     pub pinned_nodes: Vec<String>,
+
+    /// Revision bumped by every change to the group, its rules or its pinned
+    /// nodes; served as the group's ETag
+    #[serde(default = "default_group_version")]
+    pub version: i64,
+}
+
+fn default_group_version() -> i64 {
+    1
 }
 
 impl Default for NodeGroup {
@@ -72,6 +81,7 @@ impl Default for NodeGroup {
             variables: serde_json::json!({}),
             rules: vec![],
             pinned_nodes: vec![],
+            version: 1,
         }
     }
 }
@@ -121,6 +131,12 @@ pub struct UpdateGroupRequest {
     /// Classes in Puppet Enterprise format: {"class_name": {"param": "value"}, ...}
     pub classes: Option<serde_json::Value>,
     pub variables: Option<serde_json::Value>,
+    /// Replaces all classification rules of the group when given
+    #[serde(default)]
+    pub rules: Option<Vec<CreateRuleRequest>>,
+    /// Replaces all pinned nodes of the group when given
+    #[serde(default)]
+    pub pinned_nodes: Option<Vec<String>>,
 }
 
 /// Request to create a classification rule
@@ -137,6 +153,81 @@ pub struct AddPinnedNodeRequest {
     pub certname: String,
 }
 
+/// Request to pin or unpin many nodes of a group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkPinnedNodesRequest {
+    pub group_id: Uuid,
+    pub certnames: Vec<String>,
+}
+
+/// Request to move pinned nodes from one group to another
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MovePinnedNodesRequest {
+    pub from_group_id: Uuid,
+    pub to_group_id: Uuid,
+    pub certnames: Vec<String>,
+}
+
+/// Request to declare a class on many groups
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkAddClassRequest {
+    pub group_ids: Vec<Uuid>,
+    /// Class name, e.g. "ntp"
+    pub class: String,
+    /// Class parameters; an empty object when omitted
+    #[serde(default)]
+    pub parameters: Option<serde_json::Value>,
+    /// Replace the parameters of groups that already declare the class
+    #[serde(default)]
+    pub overwrite: bool,
+}
+
+/// Request to create classification rules on any number of groups
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkCreateRulesRequest {
+    pub rules: Vec<BulkRuleItem>,
+}
+
+/// A rule to create on a group
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkRuleItem {
+    pub group_id: Uuid,
+    #[serde(flatten)]
+    pub rule: CreateRuleRequest,
+}
+
+/// Outcome of a bulk group operation for a single item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkGroupResult {
+    /// Certname or group ID the result is about
+    pub item: String,
+    pub success: bool,
+    /// Status or error message
+    pub message: String,
+}
+
+/// Response from a bulk group operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkGroupResponse {
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<BulkGroupResult>,
+}
+
+impl BulkGroupResponse {
+    /// Build the response from per-item results
+    pub fn from_results(results: Vec<BulkGroupResult>) -> Self {
+        let succeeded = results.iter().filter(|r| r.success).count();
+        Self {
+            total: results.len(),
+            succeeded,
+            failed: results.len() - succeeded,
+            results,
+        }
+    }
+}
+
 /// Classification rule for matching nodes to groups
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ClassificationRule {
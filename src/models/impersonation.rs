@@ -0,0 +1,60 @@
+//! User impersonation models
+//!
+//! An admin with the `impersonate` action on users can act as another user
+//! for a few minutes, e.g. to reproduce a permission problem. The token
+//! carries the admin as its real actor, so every audited action is
+//! attributed to both.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Longest impersonation that can be started
+pub const MAX_IMPERSONATION_MINUTES: u32 = 60;
+
+/// Impersonation length when none is given
+pub const DEFAULT_IMPERSONATION_MINUTES: u32 = 30;
+
+/// An admin acting as another user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpersonationSession {
+    /// Also the ID of the login session of the token
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub impersonator_id: Uuid,
+    pub impersonator_username: String,
+    pub user_id: Uuid,
+    pub username: String,
+    pub reason: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// Set when the impersonation was ended before expiring
+    pub ended_at: Option<DateTime<Utc>>,
+}
+
+impl ImpersonationSession {
+    /// Whether the impersonation token is still usable at `now`
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        self.ended_at.is_none() && self.expires_at > now
+    }
+}
+
+/// Request body for starting an impersonation
+#[derive(Debug, Clone, Deserialize)]
+pub struct StartImpersonationRequest {
+    pub user_id: Uuid,
+    pub reason: String,
+    /// Defaults to [`DEFAULT_IMPERSONATION_MINUTES`]
+    pub duration_minutes: Option<u32>,
+}
+
+/// Access token acting as the impersonated user
+///
+/// There is no refresh token: the impersonation ends when the token expires.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImpersonationResponse {
+    pub access_token: String,
+    pub token_type: String,
+    pub expires_in: u64,
+    pub session: ImpersonationSession,
+}
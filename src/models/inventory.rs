@@ -430,6 +430,8 @@ pub enum UpdateOperationType {
     PackageRemove,
     SystemPatch,
     SecurityPatch,
+    /// Renew the agent certificate via the CA's certificate_renewal endpoint
+    CertRenewal,
 }
 
 impl UpdateOperationType {
@@ -440,6 +442,7 @@ impl UpdateOperationType {
             Self::PackageRemove => "package_remove",
             Self::SystemPatch => "system_patch",
             Self::SecurityPatch => "security_patch",
+            Self::CertRenewal => "cert_renewal",
         }
     }
 
@@ -450,6 +453,7 @@ impl UpdateOperationType {
             "package_remove" => Some(Self::PackageRemove),
             "system_patch" => Some(Self::SystemPatch),
             "security_patch" => Some(Self::SecurityPatch),
+            "cert_renewal" => Some(Self::CertRenewal),
             _ => None,
         }
     }
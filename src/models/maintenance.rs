@@ -0,0 +1,117 @@
+//! Maintenance window models
+//!
+//! Nodes covered by an active maintenance window are expected to misbehave:
+//! their report failures are left out of dashboards and alerts, and the
+//! classification and analytics endpoints flag them as in maintenance.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::SmartListFactCondition;
+
+/// The nodes a maintenance window covers
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MaintenanceTarget {
+    /// Explicit nodes
+    Nodes { certnames: Vec<String> },
+    /// Every member (pinned or rule-matched) of a node group
+    Group { group_id: Uuid },
+    /// Nodes whose facts match every condition
+    FactQuery { facts: Vec<SmartListFactCondition> },
+}
+
+/// When a maintenance window is active
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MaintenanceSchedule {
+    /// A single period
+    OneTime {
+        starts_at: DateTime<Utc>,
+        ends_at: DateTime<Utc>,
+    },
+    /// A period starting at every occurrence of a 6-field cron expression (UTC)
+    Recurring {
+        cron_expression: String,
+        duration_minutes: i64,
+    },
+}
+
+impl MaintenanceSchedule {
+    pub fn type_str(&self) -> &'static str {
+        match self {
+            Self::OneTime { .. } => "one_time",
+            Self::Recurring { .. } => "recurring",
+        }
+    }
+}
+
+/// A maintenance window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub target: MaintenanceTarget,
+    pub schedule: MaintenanceSchedule,
+    pub enabled: bool,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A maintenance window with its current state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceWindowStatus {
+    #[serde(flatten)]
+    pub window: MaintenanceWindow,
+    /// Whether the window is active now
+    pub active: bool,
+    /// End of the current period (active windows only)
+    pub active_until: Option<DateTime<Utc>>,
+    /// Start of the next period, if any
+    pub next_start: Option<DateTime<Utc>>,
+}
+
+/// An active window covering a node
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NodeMaintenance {
+    pub window_id: Uuid,
+    pub name: String,
+    /// End of the current period
+    pub ends_at: DateTime<Utc>,
+}
+
+/// A node currently in maintenance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeInMaintenance {
+    pub certname: String,
+    pub windows: Vec<NodeMaintenance>,
+}
+
+/// Request body for creating a maintenance window
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateMaintenanceWindowRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub target: MaintenanceTarget,
+    pub schedule: MaintenanceSchedule,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+/// Request body for updating a maintenance window
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateMaintenanceWindowRequest {
+    pub name: Option<String>,
+    pub description: Option<Option<String>>,
+    pub target: Option<MaintenanceTarget>,
+    pub schedule: Option<MaintenanceSchedule>,
+    pub enabled: Option<bool>,
+}
+
+fn default_true() -> bool {
+    true
+}
@@ -5,39 +5,63 @@ mod analytics;
 mod api_key;
 mod audit;
 mod backup;
+mod cert_renewal;
 mod certificate;
 mod classification;
+mod classification_key;
 mod code_deploy;
 mod cve;
+mod elevation;
 mod fact;
 mod group;
+mod impersonation;
 mod inventory;
+mod maintenance;
 mod node;
+mod node_metadata;
 mod node_removal;
 mod notification;
 mod organization;
 mod rbac;
+mod recycle_bin;
 mod report;
+mod search;
+mod session;
 mod settings;
+mod smart_list;
+mod topology;
 mod user;
+mod webhook;
 
 pub use alerting::*;
 pub use analytics::*;
 pub use api_key::*;
 pub use audit::*;
 pub use backup::*;
+pub use cert_renewal::*;
 pub use certificate::*;
 pub use classification::*;
+pub use classification_key::*;
 pub use code_deploy::*;
 pub use cve::*;
+pub use elevation::*;
 pub use fact::*;
 pub use group::*;
+pub use impersonation::*;
 pub use inventory::*;
+pub use maintenance::*;
 pub use node::*;
+pub use node_metadata::*;
 pub use node_removal::*;
 pub use notification::*;
 pub use organization::*;
 pub use rbac::*;
+pub use recycle_bin::*;
 pub use report::*;
+pub use search::*;
+pub use session::*;
 pub use settings::*;
+pub use smart_list::*;
+pub use topology::*;
 pub use user::*;
+pub use webhook::*;
@@ -0,0 +1,54 @@
+//! Local node metadata models
+//!
+//! Metadata the WebUI keeps about a node independently of PuppetDB: tags,
+//! the owning team, ticket links and notes.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Link to an external ticket or document
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TicketLink {
+    pub url: String,
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// Metadata of a single node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeMetadata {
+    pub certname: String,
+    /// Free-form tags, exposed to classification as `webui.tags`
+    pub tags: Vec<String>,
+    /// Team responsible for the node, exposed as `webui.owner_team`
+    pub owner_team: Option<String>,
+    pub ticket_links: Vec<TicketLink>,
+    /// Markdown notes
+    pub notes: Option<String>,
+    /// Username of the last editor
+    pub updated_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Request body replacing a node's metadata
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdateNodeMetadataRequest {
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub owner_team: Option<String>,
+    #[serde(default)]
+    pub ticket_links: Vec<TicketLink>,
+    pub notes: Option<String>,
+}
+
+/// Query parameters for searching node metadata
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct NodeMetadataQuery {
+    /// Nodes carrying this tag
+    pub tag: Option<String>,
+    /// Nodes owned by this team
+    pub owner_team: Option<String>,
+    /// Substring matched against certname, tags, owner team, notes and links
+    pub q: Option<String>,
+}
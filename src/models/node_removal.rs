@@ -16,6 +16,8 @@ pub enum RemovalReason {
     NoCertificate,
     /// Manually marked for removal by an administrator
     Manual,
+    /// Flagged by the stale node janitor
+    StaleNode,
 }
 
 impl RemovalReason {
@@ -24,6 +26,7 @@ impl RemovalReason {
             RemovalReason::RevokedCertificate => "revoked_certificate",
             RemovalReason::NoCertificate => "no_certificate",
             RemovalReason::Manual => "manual",
+            RemovalReason::StaleNode => "stale_node",
         }
     }
 
@@ -32,6 +35,7 @@ impl RemovalReason {
             "revoked_certificate" => Some(RemovalReason::RevokedCertificate),
             "no_certificate" => Some(RemovalReason::NoCertificate),
             "manual" => Some(RemovalReason::Manual),
+            "stale_node" => Some(RemovalReason::StaleNode),
             _ => None,
         }
     }
@@ -41,6 +45,7 @@ impl RemovalReason {
             RemovalReason::RevokedCertificate => "Certificate was revoked",
             RemovalReason::NoCertificate => "No certificate found",
             RemovalReason::Manual => "Manually marked for removal",
+            RemovalReason::StaleNode => "Node stopped reporting",
         }
     }
 }
@@ -185,6 +190,78 @@ pub struct PendingRemovalStats {
     pub revoked_certificates: i64,
     pub no_certificates: i64,
     pub manual: i64,
+    pub stale_nodes: i64,
     pub due_today: i64,
     pub due_this_week: i64,
 }
+
+/// What the stale node janitor did with a node
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JanitorNodeStatus {
+    /// Deactivated and/or flagged (or would be, in a dry run)
+    Purged,
+    /// Matched an exclusion pattern
+    Excluded,
+    /// Deactivation or flagging failed
+    Failed,
+}
+
+impl JanitorNodeStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JanitorNodeStatus::Purged => "purged",
+            JanitorNodeStatus::Excluded => "excluded",
+            JanitorNodeStatus::Failed => "failed",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "purged" => Some(JanitorNodeStatus::Purged),
+            "excluded" => Some(JanitorNodeStatus::Excluded),
+            "failed" => Some(JanitorNodeStatus::Failed),
+            _ => None,
+        }
+    }
+}
+
+/// A stale node found by a janitor run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JanitorNodeResult {
+    pub certname: String,
+    /// Latest report, facts or catalog submission
+    pub last_seen_at: Option<DateTime<Utc>>,
+    pub status: JanitorNodeStatus,
+    pub deactivated: bool,
+    pub certificate_flagged: bool,
+    pub error: Option<String>,
+}
+
+/// Report of a stale node janitor run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JanitorRun {
+    pub id: String,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    /// Nothing was changed; `nodes` shows what would have been purged
+    pub dry_run: bool,
+    /// User who started the run (None for the scheduler)
+    pub triggered_by: Option<String>,
+    pub stale_after_days: i64,
+    pub stale_count: i64,
+    pub purged_count: i64,
+    pub excluded_count: i64,
+    pub failed_count: i64,
+    /// Stale nodes (omitted from run listings)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub nodes: Vec<JanitorNodeResult>,
+}
+
+/// Request to run the stale node janitor now
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RunJanitorRequest {
+    /// Report stale nodes without deactivating or flagging them
+    #[serde(default)]
+    pub dry_run: bool,
+}
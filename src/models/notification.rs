@@ -2,6 +2,9 @@
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{Action, Resource, SystemRole};
 
 /// Notification type
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, sqlx::Type)]
@@ -118,10 +121,75 @@ pub struct CreateNotificationRequest {
     pub expires_at: Option<DateTime<Utc>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<serde_json::Value>,
+    /// Skip the notification if the user already has one with this key
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dedup_key: Option<String>,
+}
+
+/// Users a notification is sent to
+#[derive(Debug, Clone, PartialEq)]
+pub enum NotificationAudience {
+    User(Uuid),
+    /// Users holding any of the roles, by name
+    Roles(Vec<String>),
+    /// Users allowed an action on a resource
+    Permission(Resource, Action),
+    AllUsers,
+}
+
+impl NotificationAudience {
+    /// Super admins, admins and operators, who run Code Deploy and act on
+    /// operational warnings
+    pub fn operators() -> Self {
+        NotificationAudience::Roles(
+            [
+                SystemRole::SuperAdmin,
+                SystemRole::Admin,
+                SystemRole::Operator,
+            ]
+            .iter()
+            .map(|role| role.as_str().to_string())
+            .collect(),
+        )
+    }
+}
+
+/// Notification sent to every user of an audience
+#[derive(Debug, Clone)]
+pub struct NewNotification {
+    /// Also limits the audience to the organization's users
+    pub organization_id: Option<Uuid>,
+    pub title: String,
+    pub message: String,
+    pub r#type: NotificationType,
+    pub category: Option<String>,
+    pub link: Option<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub metadata: Option<serde_json::Value>,
+    /// Users that already have a notification with this key are skipped
+    pub dedup_key: Option<String>,
+}
+
+impl NewNotification {
+    /// Request creating this notification for one user
+    pub fn for_user(&self, user_id: &str) -> CreateNotificationRequest {
+        CreateNotificationRequest {
+            user_id: user_id.to_string(),
+            organization_id: self.organization_id.map(|id| id.to_string()),
+            title: self.title.clone(),
+            message: self.message.clone(),
+            r#type: self.r#type.clone(),
+            category: self.category.clone(),
+            link: self.link.clone(),
+            expires_at: self.expires_at,
+            metadata: self.metadata.clone(),
+            dedup_key: self.dedup_key.clone(),
+        }
+    }
 }
 
 /// Notification query parameters
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct NotificationQuery {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub unread_only: Option<bool>,
@@ -15,6 +15,7 @@ pub struct Organization {
     pub id: Uuid,
     pub name: String,
     pub slug: String,
+    pub quotas: OrganizationQuotas,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -30,3 +31,220 @@ pub struct UpdateOrganizationRequest {
     pub name: Option<String>,
     pub slug: Option<String>,
 }
+
+/// Resource limits of an organization; an unset limit means unlimited
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct OrganizationQuotas {
+    #[serde(default)]
+    pub max_groups: Option<i64>,
+    #[serde(default)]
+    pub max_saved_reports: Option<i64>,
+    #[serde(default)]
+    pub max_api_keys: Option<i64>,
+    #[serde(default)]
+    pub max_users: Option<i64>,
+}
+
+impl OrganizationQuotas {
+    /// The limit set for a resource
+    pub fn limit(&self, resource: QuotaResource) -> Option<i64> {
+        match resource {
+            QuotaResource::Groups => self.max_groups,
+            QuotaResource::SavedReports => self.max_saved_reports,
+            QuotaResource::ApiKeys => self.max_api_keys,
+            QuotaResource::Users => self.max_users,
+        }
+    }
+}
+
+/// Resources counted against organization quotas
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuotaResource {
+    Groups,
+    SavedReports,
+    ApiKeys,
+    Users,
+}
+
+impl QuotaResource {
+    pub const ALL: [QuotaResource; 4] = [
+        QuotaResource::Groups,
+        QuotaResource::SavedReports,
+        QuotaResource::ApiKeys,
+        QuotaResource::Users,
+    ];
+
+    /// Human-readable plural name, for error messages
+    pub fn label(&self) -> &'static str {
+        match self {
+            QuotaResource::Groups => "node groups",
+            QuotaResource::SavedReports => "saved reports",
+            QuotaResource::ApiKeys => "API keys",
+            QuotaResource::Users => "users",
+        }
+    }
+}
+
+/// Consumption of one resource against its quota
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaUsage {
+    pub resource: QuotaResource,
+    pub used: i64,
+    pub limit: Option<i64>,
+}
+
+impl QuotaUsage {
+    /// Whether no more of the resource may be created
+    pub fn is_reached(&self) -> bool {
+        self.limit.is_some_and(|limit| self.used >= limit)
+    }
+}
+
+/// Resource consumption of an organization
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrganizationUsage {
+    pub organization_id: Uuid,
+    pub usage: Vec<QuotaUsage>,
+}
+
+/// PuppetDB serving an organization instead of the globally configured one
+///
+/// Certificate paths are read on the WebUI host.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrganizationPuppetDb {
+    pub organization_id: Uuid,
+    pub url: String,
+    pub timeout_secs: u64,
+    pub ssl_verify: bool,
+    pub ssl_cert: Option<String>,
+    pub ssl_key: Option<String>,
+    pub ssl_ca: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetOrganizationPuppetDbRequest {
+    pub url: String,
+    #[serde(default = "default_puppetdb_timeout")]
+    pub timeout_secs: u64,
+    #[serde(default = "default_puppetdb_ssl_verify")]
+    pub ssl_verify: bool,
+    pub ssl_cert: Option<String>,
+    pub ssl_key: Option<String>,
+    pub ssl_ca: Option<String>,
+}
+
+fn default_puppetdb_timeout() -> u64 {
+    30
+}
+
+fn default_puppetdb_ssl_verify() -> bool {
+    true
+}
+
+/// How a merge handles names already taken in the target organization
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NameConflictStrategy {
+    /// Append the source organization's slug to the moved item's name
+    #[default]
+    Rename,
+    /// Refuse the merge
+    Fail,
+}
+
+/// Request to move everything of an organization into another one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MergeOrganizationRequest {
+    pub target_id: Uuid,
+    #[serde(default)]
+    pub on_conflict: NameConflictStrategy,
+    /// Report what would be moved without changing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Request to move node groups of an organization into a new one
+///
+/// Each selected group moves with all groups below it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SplitOrganizationRequest {
+    pub name: String,
+    pub slug: String,
+    pub group_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub user_ids: Vec<Uuid>,
+    #[serde(default)]
+    pub fact_template_ids: Vec<Uuid>,
+    /// Report what would be moved without changing anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// Kind of record moved between organizations
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransferKind {
+    Group,
+    User,
+    FactTemplate,
+}
+
+/// A record moved between organizations
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransferItem {
+    pub kind: TransferKind,
+    pub id: Uuid,
+    pub name: String,
+    /// The name is already taken in the target organization
+    pub conflict: bool,
+    /// New name of a conflicting record
+    pub renamed_to: Option<String>,
+}
+
+/// Outcome, or with `dry_run` the plan, of a merge or split
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrganizationTransferReport {
+    pub dry_run: bool,
+    pub source_id: Uuid,
+    /// Not set for a split dry run, which creates no organization
+    pub target_id: Option<Uuid>,
+    pub items: Vec<TransferItem>,
+    /// API keys moved with their users
+    pub api_keys: u64,
+}
+
+impl OrganizationTransferReport {
+    /// Conflicting names that were not resolved by renaming
+    pub fn unresolved_conflicts(&self) -> Vec<&str> {
+        self.items
+            .iter()
+            .filter(|item| item.conflict && item.renamed_to.is_none())
+            .map(|item| item.name.as_str())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quota_usage_is_reached() {
+        let quotas = OrganizationQuotas {
+            max_groups: Some(2),
+            ..Default::default()
+        };
+        let usage = |resource, used| QuotaUsage {
+            resource,
+            used,
+            limit: quotas.limit(resource),
+        };
+
+        assert!(!usage(QuotaResource::Groups, 1).is_reached());
+        assert!(usage(QuotaResource::Groups, 2).is_reached());
+        assert!(!usage(QuotaResource::Users, 1000).is_reached());
+    }
+}
@@ -22,9 +22,13 @@ pub struct Role {
     /// Whether this is a built-in system role
     pub is_system: bool,
 
-    /// Parent role for inheritance (optional)
+    /// First parent role, kept for clients predating composite roles
     pub parent_id: Option<Uuid>,
 
+    /// Roles whose permissions this role inherits
+    #[serde(default)]
+    pub parent_ids: Vec<Uuid>,
+
     /// Permissions assigned to this role
     #[serde(default)]
     pub permissions: Vec<Permission>,
@@ -45,6 +49,7 @@ impl Default for Role {
             description: None,
             is_system: false,
             parent_id: None,
+            parent_ids: vec![],
             permissions: vec![],
             created_at: Utc::now(),
             updated_at: Utc::now(),
@@ -52,6 +57,17 @@ impl Default for Role {
     }
 }
 
+impl Role {
+    /// Parent roles, falling back to `parent_id` when `parent_ids` is unset
+    pub fn parents(&self) -> Vec<Uuid> {
+        if self.parent_ids.is_empty() {
+            self.parent_id.into_iter().collect()
+        } else {
+            self.parent_ids.clone()
+        }
+    }
+}
+
 /// A permission that can be granted to roles
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub struct Permission {
@@ -173,6 +189,12 @@ pub enum Action {
     Reject,
     /// Revoke certificates
     Revoke,
+    /// Sign many certificate requests in one operation
+    BulkSign,
+    /// Revoke many certificates in one operation
+    BulkRevoke,
+    /// Act as another user
+    Impersonate,
 }
 
 impl Action {
@@ -190,6 +212,9 @@ impl Action {
             Action::Sign,
             Action::Reject,
             Action::Revoke,
+            Action::BulkSign,
+            Action::BulkRevoke,
+            Action::Impersonate,
         ]
     }
 
@@ -207,6 +232,9 @@ impl Action {
             Action::Sign => "sign",
             Action::Reject => "reject",
             Action::Revoke => "revoke",
+            Action::BulkSign => "bulk_sign",
+            Action::BulkRevoke => "bulk_revoke",
+            Action::Impersonate => "impersonate",
         }
     }
 }
@@ -259,9 +287,28 @@ pub struct CreateRoleRequest {
     pub display_name: String,
     pub description: Option<String>,
     pub parent_id: Option<Uuid>,
+    /// Parent roles of a composite role; takes precedence over `parent_id`
+    #[serde(default)]
+    pub parent_ids: Option<Vec<Uuid>>,
     pub permissions: Option<Vec<CreatePermissionRequest>>,
 }
 
+impl CreateRoleRequest {
+    /// The parent roles requested, from `parent_ids` or else `parent_id`
+    pub fn requested_parents(&self) -> Vec<Uuid> {
+        match &self.parent_ids {
+            Some(ids) => ids.clone(),
+            None => self.parent_id.into_iter().collect(),
+        }
+    }
+}
+
+/// Request to replace the parent roles of a role
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetRoleParentsRequest {
+    pub parent_ids: Vec<Uuid>,
+}
+
 /// Request to create a permission
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreatePermissionRequest {
@@ -304,6 +351,16 @@ pub struct PermissionWithRole {
     pub role_name: String,
 }
 
+/// Permissions a role grants, directly or through its ancestors
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleEffectivePermissions {
+    pub role_id: Uuid,
+    /// Every ancestor role, nearest first
+    pub inherited_role_ids: Vec<Uuid>,
+    /// Each permission with the role that grants it
+    pub permissions: Vec<PermissionWithRole>,
+}
+
 /// Built-in system roles
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SystemRole {
@@ -373,6 +430,7 @@ impl SystemRole {
             description: Some(self.description().to_string()),
             is_system: true,
             parent_id: None,
+            parent_ids: vec![],
             permissions: self.default_permissions(),
             created_at: Utc::now(),
             updated_at: Utc::now(),
@@ -466,7 +524,7 @@ impl SystemRole {
                         scope: Scope::All,
                         constraint: None,
                     },
-                    // Read reports and facts
+                    // Manage reports, read facts
                     Permission {
                         id: Uuid::new_v4(),
                         resource: Resource::Reports,
@@ -474,6 +532,27 @@ impl SystemRole {
                         scope: Scope::All,
                         constraint: None,
                     },
+                    Permission {
+                        id: Uuid::new_v4(),
+                        resource: Resource::Reports,
+                        action: Action::Create,
+                        scope: Scope::All,
+                        constraint: None,
+                    },
+                    Permission {
+                        id: Uuid::new_v4(),
+                        resource: Resource::Reports,
+                        action: Action::Update,
+                        scope: Scope::All,
+                        constraint: None,
+                    },
+                    Permission {
+                        id: Uuid::new_v4(),
+                        resource: Resource::Reports,
+                        action: Action::Delete,
+                        scope: Scope::All,
+                        constraint: None,
+                    },
                     Permission {
                         id: Uuid::new_v4(),
                         resource: Resource::Facts,
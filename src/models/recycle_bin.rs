@@ -0,0 +1,91 @@
+//! Recycle bin models
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::models::{QuotaResource, Resource};
+
+/// Kinds of records that are soft-deleted into the recycle bin
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RecycleBinKind {
+    Group,
+    User,
+    SavedReport,
+}
+
+impl RecycleBinKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RecycleBinKind::Group => "group",
+            RecycleBinKind::User => "user",
+            RecycleBinKind::SavedReport => "saved_report",
+        }
+    }
+
+    /// RBAC resource whose `delete` permission is needed to list, restore
+    /// or purge records of this kind
+    pub fn resource(&self) -> Resource {
+        match self {
+            RecycleBinKind::Group => Resource::Groups,
+            RecycleBinKind::User => Resource::Users,
+            RecycleBinKind::SavedReport => Resource::Reports,
+        }
+    }
+
+    /// Quota a restored record counts against again
+    pub fn quota(&self) -> QuotaResource {
+        match self {
+            RecycleBinKind::Group => QuotaResource::Groups,
+            RecycleBinKind::User => QuotaResource::Users,
+            RecycleBinKind::SavedReport => QuotaResource::SavedReports,
+        }
+    }
+}
+
+impl std::str::FromStr for RecycleBinKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "group" => Ok(RecycleBinKind::Group),
+            "user" => Ok(RecycleBinKind::User),
+            "saved_report" => Ok(RecycleBinKind::SavedReport),
+            _ => Err(format!("Unknown recycle bin type: {}", s)),
+        }
+    }
+}
+
+/// A soft-deleted record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedItem {
+    #[serde(rename = "type")]
+    pub kind: RecycleBinKind,
+    pub id: Uuid,
+    /// Group or report name, or username
+    pub name: String,
+    /// Parent group of a deleted group
+    pub parent_id: Option<Uuid>,
+    pub deleted_at: DateTime<Utc>,
+    /// When the record is removed for good
+    pub purge_at: DateTime<Utc>,
+}
+
+/// Contents of the recycle bin
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecycleBinResponse {
+    pub retention_days: u32,
+    pub items: Vec<DeletedItem>,
+}
+
+/// Outcome of restoring a record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreResponse {
+    #[serde(rename = "type")]
+    pub kind: RecycleBinKind,
+    pub id: Uuid,
+    /// Records brought back: a group comes back with the subgroups deleted
+    /// along with it
+    pub restored: u64,
+}
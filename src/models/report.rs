@@ -241,6 +241,174 @@ pub enum EventStatus {
     Skipped,
 }
 
+impl EventStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventStatus::Success => "success",
+            EventStatus::Failure => "failure",
+            EventStatus::Noop => "noop",
+            EventStatus::Skipped => "skipped",
+        }
+    }
+}
+
+/// Log line from a report (PuppetDB `/reports/<hash>/logs` format)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportLog {
+    /// Log level (debug, info, notice, warning, err, alert, emerg, crit)
+    pub level: String,
+
+    /// Log message
+    pub message: String,
+
+    /// Log source, e.g. "Puppet" or a resource path such as
+    /// "/Stage[main]/Motd/File[/etc/motd]/content"
+    #[serde(default)]
+    pub source: Option<String>,
+
+    /// Tags of the log source
+    #[serde(default)]
+    pub tags: Vec<String>,
+
+    /// Log timestamp
+    #[serde(default)]
+    pub time: Option<DateTime<Utc>>,
+
+    /// Source file path
+    #[serde(default)]
+    pub file: Option<String>,
+
+    /// Line number in source file
+    #[serde(default)]
+    pub line: Option<u32>,
+}
+
+impl ReportLog {
+    /// Whether the log is at err level or worse
+    pub fn is_error(&self) -> bool {
+        matches!(self.level.as_str(), "err" | "alert" | "emerg" | "crit")
+    }
+}
+
+/// Metric from a report (PuppetDB `/reports/<hash>/metrics` format)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportMetric {
+    /// Metric category (resources, time, changes, events)
+    pub category: String,
+    /// Metric name, e.g. "total" or a resource type for time metrics
+    pub name: String,
+    pub value: f64,
+}
+
+/// Resource events and log lines of a report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportDrillDown {
+    pub hash: String,
+    pub certname: String,
+    pub status: Option<ReportStatus>,
+    /// Events matching the filters
+    pub events: Vec<ResourceEvent>,
+    /// Log lines matching the filters
+    pub logs: Vec<ReportLog>,
+    /// Number of events in the report, before filtering
+    pub total_events: usize,
+    /// Number of log lines in the report, before filtering
+    pub total_logs: usize,
+}
+
+/// Summary of one side of a report diff
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportDiffSide {
+    pub hash: String,
+    pub status: Option<ReportStatus>,
+    pub start_time: Option<DateTime<Utc>>,
+    pub end_time: Option<DateTime<Utc>>,
+    pub configuration_version: Option<String>,
+    pub environment: Option<String>,
+}
+
+impl From<&Report> for ReportDiffSide {
+    fn from(report: &Report) -> Self {
+        Self {
+            hash: report.hash.clone(),
+            status: report.status,
+            start_time: report.start_time,
+            end_time: report.end_time,
+            configuration_version: report.configuration_version.clone(),
+            environment: report.environment.clone(),
+        }
+    }
+}
+
+/// A resource whose failure state differs between two reports
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ResourceDiffEntry {
+    pub resource_type: String,
+    pub resource_title: String,
+    /// Failure message (from the report in which the resource failed)
+    pub message: Option<String>,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+}
+
+/// A time metric that grew between two reports
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TimingRegression {
+    /// Metric name (e.g. "total", "config_retrieval" or a resource type)
+    pub name: String,
+    pub from_seconds: f64,
+    pub to_seconds: f64,
+    pub increase_seconds: f64,
+    /// Increase relative to the earlier report, if it took any time
+    pub increase_percent: Option<f64>,
+}
+
+/// Differences between two reports of a node
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportDiff {
+    pub certname: String,
+    pub from: ReportDiffSide,
+    pub to: ReportDiffSide,
+    /// Resources failing in `to` but not in `from`
+    pub newly_failing: Vec<ResourceDiffEntry>,
+    /// Resources failing in `from` but not in `to`
+    pub newly_fixed: Vec<ResourceDiffEntry>,
+    /// Resources failing in both reports
+    pub still_failing: Vec<ResourceDiffEntry>,
+    /// Time metrics that grew past the thresholds, largest increase first
+    pub timing_regressions: Vec<TimingRegression>,
+}
+
+/// Run duration and resource counts of one report of a node
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct NodePerformancePoint {
+    pub hash: String,
+    pub end_time: Option<DateTime<Utc>>,
+    pub status: Option<ReportStatus>,
+    pub noop: bool,
+    /// The `time.total` metric of the report
+    pub run_time_seconds: Option<f64>,
+    pub total_resources: Option<u64>,
+    pub changed_resources: Option<u64>,
+    pub failed_resources: Option<u64>,
+}
+
+/// Performance history of a node with its run time regression check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodePerformance {
+    pub certname: String,
+    pub days: u32,
+    /// Reports of the period, oldest first
+    pub points: Vec<NodePerformancePoint>,
+    /// Median run time of the runs before the recent ones
+    pub baseline_run_time_seconds: Option<f64>,
+    /// Median run time of the most recent runs
+    pub recent_run_time_seconds: Option<f64>,
+    /// Whether the recent runs are slower than the baseline by the
+    /// regression thresholds
+    pub run_time_regressed: bool,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -295,4 +463,32 @@ mod tests {
         // Verify null data is handled correctly
         assert!(reports[0].resource_events.as_ref().unwrap().data.is_none());
     }
+
+    #[test]
+    fn test_parse_puppetdb_report_logs() {
+        let json = r#"[{
+            "file": "/etc/puppetlabs/code/environments/production/modules/motd/manifests/init.pp",
+            "line": 12,
+            "level": "err",
+            "message": "Could not set 'file' on ensure: Permission denied",
+            "source": "/Stage[main]/Motd/File[/etc/motd]/ensure",
+            "tags": ["err", "file", "motd", "class"],
+            "time": "2025-12-18T11:49:51.102Z"
+        }, {
+            "file": null,
+            "line": null,
+            "level": "notice",
+            "message": "Applied catalog in 1.23 seconds",
+            "source": "Puppet",
+            "tags": ["notice"],
+            "time": "2025-12-18T11:49:52.400Z"
+        }]"#;
+
+        let logs: Vec<ReportLog> = serde_json::from_str(json).expect("Failed to parse logs");
+        assert_eq!(logs.len(), 2);
+        assert!(logs[0].is_error());
+        assert_eq!(logs[0].line, Some(12));
+        assert!(!logs[1].is_error());
+        assert!(logs[1].file.is_none());
+    }
 }
@@ -0,0 +1,91 @@
+//! Global search models
+
+use serde::{Deserialize, Serialize};
+
+use crate::models::Resource;
+
+/// Kinds of objects covered by the search index
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchKind {
+    Node,
+    Group,
+    Class,
+    Report,
+    User,
+    SavedReport,
+}
+
+impl SearchKind {
+    pub fn all() -> Vec<SearchKind> {
+        vec![
+            SearchKind::Node,
+            SearchKind::Group,
+            SearchKind::Class,
+            SearchKind::Report,
+            SearchKind::User,
+            SearchKind::SavedReport,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SearchKind::Node => "node",
+            SearchKind::Group => "group",
+            SearchKind::Class => "class",
+            SearchKind::Report => "report",
+            SearchKind::User => "user",
+            SearchKind::SavedReport => "saved_report",
+        }
+    }
+
+    /// RBAC resource whose `read` permission is needed to see results of
+    /// this kind
+    pub fn resource(&self) -> Resource {
+        match self {
+            SearchKind::Node => Resource::Nodes,
+            SearchKind::Group | SearchKind::Class => Resource::Groups,
+            SearchKind::Report | SearchKind::SavedReport => Resource::Reports,
+            SearchKind::User => Resource::Users,
+        }
+    }
+}
+
+impl std::str::FromStr for SearchKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "node" => Ok(SearchKind::Node),
+            "group" => Ok(SearchKind::Group),
+            "class" => Ok(SearchKind::Class),
+            "report" => Ok(SearchKind::Report),
+            "user" => Ok(SearchKind::User),
+            "saved_report" => Ok(SearchKind::SavedReport),
+            _ => Err(format!("Unknown search type: {}", s)),
+        }
+    }
+}
+
+/// A single search hit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResult {
+    /// Kind of object that matched
+    #[serde(rename = "type")]
+    pub kind: SearchKind,
+    /// Identifier of the object within its kind: certname for nodes, name
+    /// for classes, hash for reports and UUID otherwise
+    pub id: String,
+    pub title: String,
+    /// Excerpt of the matching text with matches wrapped in `**`
+    pub snippet: Option<String>,
+    /// Relevance; lower is better
+    pub rank: f64,
+}
+
+/// Response of the global search endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchResponse {
+    pub query: String,
+    pub results: Vec<SearchResult>,
+}
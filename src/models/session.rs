@@ -0,0 +1,90 @@
+//! Login session models
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// An active login session
+///
+/// Every access and refresh token belongs to a session; revoking the session
+/// invalidates its tokens on their next use.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuthSession {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    /// Client address at login
+    pub ip_address: Option<String>,
+    /// Client `User-Agent` at login
+    pub user_agent: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub last_activity_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    /// Whether this is the session of the request that listed it
+    #[serde(default)]
+    pub current: bool,
+}
+
+/// How a user logged in
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LoginMethod {
+    Password,
+    Saml,
+}
+
+impl LoginMethod {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoginMethod::Password => "password",
+            LoginMethod::Saml => "saml",
+        }
+    }
+}
+
+impl std::str::FromStr for LoginMethod {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "password" => Ok(LoginMethod::Password),
+            "saml" => Ok(LoginMethod::Saml),
+            _ => Err(format!("Invalid login method: {}", s)),
+        }
+    }
+}
+
+/// A recorded login attempt of a user
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginEvent {
+    pub id: Uuid,
+    pub user_id: Uuid,
+    pub success: bool,
+    pub method: LoginMethod,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    /// Country and city from the proxy's geo headers, or the client network
+    pub location: Option<String>,
+    /// First successful login from this browser and operating system
+    pub new_device: bool,
+    /// First successful login from this location
+    pub new_location: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Failed password logins of an account and its lockout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountLockout {
+    pub user_id: Uuid,
+    /// Failures since the last lockout, within the lockout window
+    pub failed_attempts: u32,
+    pub last_failed_at: Option<DateTime<Utc>>,
+    pub locked_until: Option<DateTime<Utc>>,
+    /// Consecutive lockouts; each one doubles the next lockout's duration
+    pub lockouts: u32,
+}
+
+impl AccountLockout {
+    pub fn is_locked(&self, now: DateTime<Utc>) -> bool {
+        self.locked_until.is_some_and(|until| until > now)
+    }
+}
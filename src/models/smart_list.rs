@@ -0,0 +1,100 @@
+//! Smart list (saved node filter) models
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::RuleOperator;
+
+/// A named, persisted node filter
+///
+/// Smart lists can be used anywhere a set of nodes needs to be selected: the
+/// nodes API, exports, alert rule scoping and report scoping.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SmartList {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub filter: SmartListFilter,
+    /// Whether the list is visible to every user in the organization
+    pub is_shared: bool,
+    pub created_by: Uuid,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Filter criteria for a smart list
+///
+/// All populated criteria must match (logical AND). Within a single criterion
+/// holding several values (e.g. `environments`), any value may match.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+pub struct SmartListFilter {
+    /// Catalog environments to include
+    #[serde(default)]
+    pub environments: Vec<String>,
+    /// Latest report statuses to include (changed, unchanged, failed, noop)
+    #[serde(default)]
+    pub statuses: Vec<String>,
+    /// Regex applied to the certname
+    #[serde(default)]
+    pub certname_pattern: Option<String>,
+    /// Node group IDs; nodes must belong to at least one of them
+    #[serde(default)]
+    pub group_ids: Vec<Uuid>,
+    /// Fact conditions; every condition must match
+    #[serde(default)]
+    pub facts: Vec<SmartListFactCondition>,
+    /// Tags; nodes must carry at least one of them in their `tags` fact
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl SmartListFilter {
+    /// Whether evaluating this filter requires fetching node facts
+    pub fn needs_facts(&self) -> bool {
+        !self.facts.is_empty() || !self.tags.is_empty()
+    }
+}
+
+/// A single fact condition within a smart list filter
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SmartListFactCondition {
+    /// Dotted fact path (e.g. "os.family")
+    pub fact_path: String,
+    /// Comparison operator, using the same syntax as classification rules
+    #[serde(default)]
+    pub operator: RuleOperator,
+    pub value: serde_json::Value,
+}
+
+/// Request to create a smart list
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateSmartListRequest {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub filter: SmartListFilter,
+    #[serde(default)]
+    pub is_shared: bool,
+}
+
+/// Request to update a smart list
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateSmartListRequest {
+    pub name: Option<String>,
+    pub description: Option<Option<String>>,
+    pub filter: Option<SmartListFilter>,
+    pub is_shared: Option<bool>,
+}
+
+/// Number of nodes currently matching a smart list
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SmartListCount {
+    pub smart_list_id: Uuid,
+    pub count: usize,
+    /// When the membership was resolved
+    pub computed_at: DateTime<Utc>,
+    /// Whether the count was served from the cache
+    pub cached: bool,
+}
@@ -0,0 +1,85 @@
+//! Infrastructure topology models
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Role of a node in the infrastructure topology
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TopologyNodeKind {
+    /// Primary server, the one running the CA
+    PuppetServer,
+    /// Additional server compiling catalogs
+    CompileServer,
+    Puppetdb,
+    CertificateAuthority,
+    Agent,
+}
+
+/// Health of a node in the infrastructure topology
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TopologyStatus {
+    Healthy,
+    Degraded,
+    Unhealthy,
+    Unknown,
+}
+
+/// Relation between two nodes of the infrastructure topology
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TopologyEdgeKind {
+    /// Agent gets its catalog from a server
+    Catalog,
+    /// Server stores facts, catalogs and reports in PuppetDB
+    Storage,
+    /// Server relies on the CA for certificates
+    Certificates,
+}
+
+/// Node of the infrastructure topology
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopologyNode {
+    /// Unique ID, referenced by the edges
+    pub id: String,
+    pub kind: TopologyNodeKind,
+    /// Host name or certname
+    pub name: String,
+    pub status: TopologyStatus,
+    /// Why the node is not healthy
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Agents getting their catalog from this server
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_count: Option<usize>,
+    /// Agents of this server whose last run failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failed_agent_count: Option<usize>,
+    /// Environment of an agent's last catalog
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub environment: Option<String>,
+    /// Last report of an agent, or of any agent of a server
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_report: Option<DateTime<Utc>>,
+}
+
+/// Edge of the infrastructure topology
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TopologyEdge {
+    pub source: String,
+    pub target: String,
+    pub kind: TopologyEdgeKind,
+}
+
+/// Graph of servers, PuppetDB, the CA and agents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InfrastructureTopology {
+    pub nodes: Vec<TopologyNode>,
+    pub edges: Vec<TopologyEdge>,
+    /// Agents not attached to any server, because no server fact is known
+    pub unassigned_agents: usize,
+    /// Whether agent nodes beyond the limit were left out
+    pub truncated: bool,
+    pub generated_at: DateTime<Utc>,
+}
@@ -54,6 +54,39 @@ impl std::str::FromStr for AuthProvider {
     }
 }
 
+/// Kind of user account
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum UserKind {
+    /// A person who logs in interactively
+    #[default]
+    Human,
+    /// Automation that authenticates with API keys only; cannot log in with
+    /// a password or SAML and is not subject to the password policy
+    ServiceAccount,
+}
+
+impl std::fmt::Display for UserKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UserKind::Human => write!(f, "human"),
+            UserKind::ServiceAccount => write!(f, "service_account"),
+        }
+    }
+}
+
+impl std::str::FromStr for UserKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "human" => Ok(UserKind::Human),
+            "service_account" => Ok(UserKind::ServiceAccount),
+            _ => Err(format!("Invalid user kind: {}", s)),
+        }
+    }
+}
+
 /// User entity
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
@@ -79,6 +112,8 @@ pub struct User {
     /// Last SAML authentication timestamp
     #[serde(default)]
     pub last_saml_auth_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub kind: UserKind,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -115,10 +150,16 @@ impl User {
             external_id: None,
             idp_entity_id: None,
             last_saml_auth_at: None,
+            kind: UserKind::Human,
             created_at: now,
             updated_at: now,
         }
     }
+
+    /// Whether this is a service account, which has no interactive login
+    pub fn is_service_account(&self) -> bool {
+        self.kind == UserKind::ServiceAccount
+    }
 }
 
 /// User without password hash for safe serialization
@@ -135,6 +176,8 @@ pub struct UserPublic {
     /// Authentication provider (local, saml, or both)
     #[serde(default)]
     pub auth_provider: AuthProvider,
+    #[serde(default)]
+    pub kind: UserKind,
     /// RBAC roles assigned to the user (optional for backwards compatibility)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub roles: Option<Vec<UserRoleInfo>>,
@@ -160,6 +203,7 @@ impl From<User> for UserPublic {
             role: user.role,
             force_password_change: user.force_password_change,
             auth_provider: user.auth_provider,
+            kind: user.kind,
             roles: None,
             created_at: user.created_at,
             updated_at: user.updated_at,
@@ -209,13 +253,18 @@ pub struct LoginRequest {
 /// Token refresh request
 #[derive(Debug, Clone, Deserialize)]
 pub struct RefreshTokenRequest {
+    /// Taken from the refresh cookie when empty, in cookie auth mode
+    #[serde(default)]
     pub refresh_token: String,
 }
 
 /// Authentication response with tokens
 #[derive(Debug, Clone, Serialize)]
 pub struct AuthResponse {
+    /// Empty in cookie auth mode, where the tokens are set as cookies
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub access_token: String,
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub refresh_token: String,
     pub token_type: String,
     pub expires_in: u64,
@@ -225,11 +274,77 @@ pub struct AuthResponse {
 /// Token response for refresh
 #[derive(Debug, Clone, Serialize)]
 pub struct TokenResponse {
+    /// Empty in cookie auth mode, where the tokens are set as cookies
+    #[serde(skip_serializing_if = "String::is_empty")]
     pub access_token: String,
+    /// Replaces the refresh token that was exchanged, which is now spent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
     pub token_type: String,
     pub expires_in: u64,
 }
 
+/// One user of a bulk import
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ImportUserRow {
+    pub username: String,
+    pub email: String,
+    /// Role name; defaults to viewer
+    #[serde(default)]
+    pub role: Option<String>,
+    /// Organization slug or id; defaults to the caller's organization
+    #[serde(default)]
+    pub organization: Option<String>,
+    /// Initial password; one is generated when neither this nor
+    /// `password_hash` is given
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Argon2 hash in PHC format, e.g. exported from another instance
+    #[serde(default)]
+    pub password_hash: Option<String>,
+}
+
+/// Outcome of one import row
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportUserStatus {
+    Created,
+    /// Passed every check in a dry run
+    Valid,
+    Failed,
+}
+
+/// Per-row result of a bulk import
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportUserResult {
+    /// 1-based position of the row in the import
+    pub row: usize,
+    pub username: String,
+    pub status: ImportUserStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub organization_id: Option<Uuid>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    /// Generated password, shown only in this response; the user must
+    /// change it at first login
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub generated_password: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Response of a bulk import
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportUsersResponse {
+    pub dry_run: bool,
+    pub total: usize,
+    pub created: usize,
+    pub failed: usize,
+    pub results: Vec<ImportUserResult>,
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -272,4 +387,26 @@ mod tests {
         let req: CreateUserRequest = serde_json::from_str(json).unwrap();
         assert_eq!(req.role, "viewer");
     }
+
+    #[test]
+    fn test_user_kind() {
+        let user = User::new(
+            "backup-bot".to_string(),
+            "backup@example.com".to_string(),
+            "hash".to_string(),
+            "viewer".to_string(),
+        );
+        assert!(!user.is_service_account());
+
+        assert_eq!(
+            serde_json::to_value(UserKind::ServiceAccount).unwrap(),
+            "service_account"
+        );
+        assert_eq!(
+            "service_account".parse::<UserKind>().unwrap(),
+            UserKind::ServiceAccount
+        );
+        assert_eq!(UserKind::ServiceAccount.to_string(), "service_account");
+        assert!("robot".parse::<UserKind>().is_err());
+    }
 }
@@ -0,0 +1,179 @@
+//! Outbound webhook models
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// Events a webhook can subscribe to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WebhookEvent {
+    /// A node reported a failed Puppet run
+    #[serde(rename = "node.failed_run")]
+    NodeFailedRun,
+    /// A code deployment finished, successfully or not
+    #[serde(rename = "deployment.completed")]
+    DeploymentCompleted,
+    /// A new certificate signing request is waiting on the CA
+    #[serde(rename = "ca.request_pending")]
+    CertRequestPending,
+    /// An alert rule fired
+    #[serde(rename = "alert.fired")]
+    AlertFired,
+    /// A node group was created, updated or deleted
+    #[serde(rename = "group.changed")]
+    GroupChanged,
+    /// Test delivery sent on request; every webhook receives it
+    #[serde(rename = "ping")]
+    Ping,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::NodeFailedRun => "node.failed_run",
+            WebhookEvent::DeploymentCompleted => "deployment.completed",
+            WebhookEvent::CertRequestPending => "ca.request_pending",
+            WebhookEvent::AlertFired => "alert.fired",
+            WebhookEvent::GroupChanged => "group.changed",
+            WebhookEvent::Ping => "ping",
+        }
+    }
+}
+
+impl std::str::FromStr for WebhookEvent {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "node.failed_run" => Ok(WebhookEvent::NodeFailedRun),
+            "deployment.completed" => Ok(WebhookEvent::DeploymentCompleted),
+            "ca.request_pending" => Ok(WebhookEvent::CertRequestPending),
+            "alert.fired" => Ok(WebhookEvent::AlertFired),
+            "group.changed" => Ok(WebhookEvent::GroupChanged),
+            "ping" => Ok(WebhookEvent::Ping),
+            _ => Err(format!("Unknown webhook event: {}", s)),
+        }
+    }
+}
+
+/// An HTTP endpoint notified of events in an organization
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: Uuid,
+    pub organization_id: Uuid,
+    pub name: String,
+    pub url: String,
+    /// HMAC-SHA256 signing secret; never returned by the API
+    #[serde(skip_serializing, default)]
+    pub secret: String,
+    /// Subscribed events; empty means every event
+    pub events: Vec<WebhookEvent>,
+    pub enabled: bool,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Webhook {
+    /// Whether the webhook receives an event
+    pub fn subscribes_to(&self, event: WebhookEvent) -> bool {
+        event == WebhookEvent::Ping || self.events.is_empty() || self.events.contains(&event)
+    }
+}
+
+/// A newly created webhook with its signing secret
+///
+/// The secret is only returned once, when the webhook is created.
+#[derive(Debug, Clone, Serialize)]
+pub struct CreatedWebhook {
+    #[serde(flatten)]
+    pub webhook: Webhook,
+    pub secret: String,
+}
+
+/// Request to create a webhook
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateWebhookRequest {
+    pub name: String,
+    pub url: String,
+    /// Signing secret; generated when omitted
+    #[serde(default)]
+    pub secret: Option<String>,
+    #[serde(default)]
+    pub events: Vec<WebhookEvent>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Request to update a webhook
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateWebhookRequest {
+    pub name: Option<String>,
+    pub url: Option<String>,
+    /// Replaces the signing secret
+    pub secret: Option<String>,
+    pub events: Option<Vec<WebhookEvent>>,
+    pub enabled: Option<bool>,
+}
+
+/// State of a webhook delivery
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookDeliveryStatus {
+    /// Waiting for its first attempt
+    Pending,
+    /// Failed at least once and scheduled for another attempt
+    Retrying,
+    Delivered,
+    /// Gave up after the last attempt
+    Failed,
+}
+
+impl WebhookDeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookDeliveryStatus::Pending => "pending",
+            WebhookDeliveryStatus::Retrying => "retrying",
+            WebhookDeliveryStatus::Delivered => "delivered",
+            WebhookDeliveryStatus::Failed => "failed",
+        }
+    }
+}
+
+impl std::str::FromStr for WebhookDeliveryStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(WebhookDeliveryStatus::Pending),
+            "retrying" => Ok(WebhookDeliveryStatus::Retrying),
+            "delivered" => Ok(WebhookDeliveryStatus::Delivered),
+            "failed" => Ok(WebhookDeliveryStatus::Failed),
+            _ => Err(format!("Unknown delivery status: {}", s)),
+        }
+    }
+}
+
+/// One event sent (or to be sent) to a webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event: WebhookEvent,
+    /// JSON body sent to the endpoint
+    pub payload: serde_json::Value,
+    pub status: WebhookDeliveryStatus,
+    pub attempts: u32,
+    /// HTTP status of the last attempt, if the endpoint answered
+    pub response_status: Option<u16>,
+    /// Start of the response body of the last attempt
+    pub response_body: Option<String>,
+    pub error: Option<String>,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
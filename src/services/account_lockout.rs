@@ -0,0 +1,123 @@
+//! Per-account brute force protection
+//!
+//! The rate limiter counts requests per client address, which does not stop
+//! a password guessing attack spread over many addresses. Failed password
+//! logins are therefore also counted per account: `rbac.max_failed_logins`
+//! failures within `rbac.lockout_duration_minutes` lock the account for that
+//! long, and every lockout that follows doubles the duration up to
+//! `rbac.max_lockout_minutes`. A successful login or an admin unlock clears
+//! the count; so does a quiet period of `max_lockout_minutes` after the last
+//! lockout ended.
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::{config::RbacConfig, db::AccountLockoutRepository};
+
+/// How long the next lockout of an account lasts after `previous` lockouts
+pub fn lockout_duration(rbac: &RbacConfig, previous: u32) -> Duration {
+    let base = rbac.lockout_duration_minutes.max(1);
+    let max = rbac.max_lockout_minutes.max(base);
+    let minutes = base
+        .checked_mul(1u64.checked_shl(previous).unwrap_or(u64::MAX))
+        .unwrap_or(u64::MAX)
+        .min(max);
+    Duration::minutes(minutes as i64)
+}
+
+/// When a locked account may log in again; `None` when it is not locked
+///
+/// Errors are logged and treated as not locked.
+pub async fn locked_until(
+    pool: &SqlitePool,
+    user_id: Uuid,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    match AccountLockoutRepository::new(pool).get(user_id).await {
+        Ok(Some(lockout)) if lockout.is_locked(now) => lockout.locked_until,
+        Ok(_) => None,
+        Err(e) => {
+            tracing::warn!("Failed to check lockout of user {}: {}", user_id, e);
+            None
+        }
+    }
+}
+
+/// Count a failed password login and lock the account once the limit is
+/// reached
+///
+/// Returns when the account is locked until if this failure locked it.
+pub async fn register_failure(
+    pool: &SqlitePool,
+    rbac: &RbacConfig,
+    user_id: Uuid,
+    now: DateTime<Utc>,
+) -> Option<DateTime<Utc>> {
+    if rbac.max_failed_logins == 0 {
+        return None;
+    }
+
+    let repo = AccountLockoutRepository::new(pool);
+    let window = Duration::minutes(rbac.lockout_duration_minutes as i64);
+    let quiet = Duration::minutes(rbac.max_lockout_minutes as i64);
+    let lockout = match repo
+        .record_failure(user_id, now, now - window, now - quiet)
+        .await
+    {
+        Ok(lockout) => lockout,
+        Err(e) => {
+            tracing::warn!("Failed to count failed login of user {}: {}", user_id, e);
+            return None;
+        }
+    };
+    if lockout.failed_attempts < rbac.max_failed_logins {
+        return None;
+    }
+
+    let until = now + lockout_duration(rbac, lockout.lockouts);
+    if let Err(e) = repo.lock(user_id, until).await {
+        tracing::error!("Failed to lock user {}: {}", user_id, e);
+        return None;
+    }
+    Some(until)
+}
+
+/// Forget the failed logins of an account after a successful login
+pub async fn clear(pool: &SqlitePool, user_id: Uuid) {
+    if let Err(e) = AccountLockoutRepository::new(pool).clear(user_id).await {
+        tracing::warn!("Failed to clear lockout of user {}: {}", user_id, e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lockout_duration_doubles_up_to_max() {
+        let rbac = RbacConfig {
+            lockout_duration_minutes: 30,
+            max_lockout_minutes: 240,
+            ..Default::default()
+        };
+        let minutes: Vec<i64> = (0..6)
+            .map(|previous| lockout_duration(&rbac, previous).num_minutes())
+            .collect();
+        assert_eq!(minutes, vec![30, 60, 120, 240, 240, 240]);
+
+        // Never overflows
+        assert_eq!(lockout_duration(&rbac, 200).num_minutes(), 240);
+    }
+
+    #[test]
+    fn test_lockout_duration_max_below_base() {
+        let rbac = RbacConfig {
+            lockout_duration_minutes: 30,
+            max_lockout_minutes: 10,
+            ..Default::default()
+        };
+        assert_eq!(lockout_duration(&rbac, 0).num_minutes(), 30);
+        assert_eq!(lockout_duration(&rbac, 3).num_minutes(), 30);
+    }
+}
@@ -5,6 +5,7 @@
 //! - Notification dispatch to various channels
 //! - Alert lifecycle management
 
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -28,12 +29,23 @@ use crate::models::{
     AlertWebhookData, ChannelType, CreateAlertRuleRequest, CreateChannelRequest,
     CreateSilenceRequest, EmailConfig, NotificationChannel, SlackConfig, TeamsConfig,
     TestChannelRequest, TestChannelResponse, UpdateAlertRuleRequest, UpdateChannelRequest,
-    WebhookConfig, WebhookPayload,
+    WebhookConfig, WebhookEvent, WebhookPayload,
 };
-use crate::models::{CreateNotificationRequest, NotificationType};
+use crate::models::{NewNotification, NotificationAudience, NotificationType};
+use crate::services::auth::AuthService;
+use crate::services::maintenance::ActiveMaintenance;
 use crate::services::notification::NotificationService;
+use crate::services::secrets::SecretsResolver;
+use crate::services::settings_encryption::SettingsCipher;
+use crate::services::smart_list::{load_smart_list_for_user, resolve_smart_list_certnames};
+use crate::services::webhooks::Webhooks;
 use crate::services::PuppetDbClient;
 
+/// Context field listing the smart lists a node belongs to. Rules scope
+/// themselves to a smart list with `{"field": "node.smart_lists",
+/// "operator": "contains", "value": "<smart list id>"}`.
+pub const SMART_LISTS_FIELD: &str = "node.smart_lists";
+
 /// Alerting service for managing alerts and notifications
 pub struct AlertingService {
     pool: SqlitePool,
@@ -43,6 +55,9 @@ pub struct AlertingService {
     /// Dedicated inventory DB pool (update_jobs live here). Required to evaluate
     /// `update_job` rules; when absent those rules are skipped.
     inventory_pool: Option<SqlitePool>,
+    webhooks: Option<Webhooks>,
+    cipher: Option<SettingsCipher>,
+    secrets: Option<Arc<SecretsResolver>>,
 }
 
 impl AlertingService {
@@ -63,6 +78,9 @@ impl AlertingService {
             puppetdb,
             notification_service,
             inventory_pool: None,
+            webhooks: None,
+            cipher: None,
+            secrets: None,
         }
     }
 
@@ -72,6 +90,24 @@ impl AlertingService {
         self
     }
 
+    /// Announce fired alerts to the subscribed webhooks
+    pub fn with_webhooks(mut self, webhooks: Webhooks) -> Self {
+        self.webhooks = Some(webhooks);
+        self
+    }
+
+    /// Decrypt the stored SMTP settings used by new email channels
+    pub fn with_settings_cipher(mut self, cipher: Option<SettingsCipher>) -> Self {
+        self.cipher = cipher;
+        self
+    }
+
+    /// Resolve channel passwords written as secret references
+    pub fn with_secrets(mut self, secrets: Option<Arc<SecretsResolver>>) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
     // ========================================================================
     // Notification Channels
     // ========================================================================
@@ -99,7 +135,7 @@ impl AlertingService {
         // If creating an email channel, merge SMTP config from settings
         let mut req = req.clone();
         if req.channel_type == ChannelType::Email {
-            let settings_repo = SettingsRepository::new(self.pool.clone());
+            let settings_repo = SettingsRepository::new(self.pool.clone(), self.cipher.clone());
             let smtp_settings = settings_repo.get_smtp_settings().await?;
 
             // Extract recipient from request config
@@ -331,6 +367,32 @@ impl AlertingService {
         info!("Starting rule evaluation");
 
         let rules = self.get_enabled_rules().await?;
+        self.evaluate_rule_set(rules).await
+    }
+
+    /// Evaluate the enabled rules that look at Puppet runs (node status and
+    /// report failure), e.g. when new reports are ingested
+    pub async fn evaluate_report_rules(&self) -> Result<Vec<Alert>> {
+        let rules: Vec<AlertRule> = self
+            .get_enabled_rules()
+            .await?
+            .into_iter()
+            .filter(|r| {
+                matches!(
+                    r.rule_type,
+                    AlertRuleType::NodeStatus | AlertRuleType::ReportFailure
+                )
+            })
+            .collect();
+        if rules.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.evaluate_rule_set(rules).await
+    }
+
+    /// Evaluate the given rules and trigger alerts as needed
+    async fn evaluate_rule_set(&self, rules: Vec<AlertRule>) -> Result<Vec<Alert>> {
         info!("Found {} enabled rules to evaluate", rules.len());
 
         if rules.is_empty() {
@@ -342,6 +404,11 @@ impl AlertingService {
             warn!("PuppetDB not configured - node status rules will be skipped");
         }
 
+        // Nodes in maintenance are expected to fail and never raise alerts
+        let maintenance =
+            ActiveMaintenance::load_or_empty(&self.pool, self.puppetdb.as_deref(), Utc::now())
+                .await;
+
         let mut triggered_alerts = Vec::new();
 
         for rule in rules {
@@ -368,7 +435,7 @@ impl AlertingService {
             }
 
             // Evaluate the rule based on its type
-            match self.evaluate_rule(&rule).await {
+            match self.evaluate_rule(&rule, &maintenance).await {
                 Ok(Some(alert)) => {
                     info!("Rule {} triggered alert: {}", rule.name, alert.title);
                     triggered_alerts.push(alert);
@@ -390,12 +457,18 @@ impl AlertingService {
     }
 
     /// Evaluate a single rule
-    async fn evaluate_rule(&self, rule: &AlertRule) -> Result<Option<Alert>> {
+    async fn evaluate_rule(
+        &self,
+        rule: &AlertRule,
+        maintenance: &ActiveMaintenance,
+    ) -> Result<Option<Alert>> {
         match rule.rule_type {
-            AlertRuleType::NodeStatus => self.evaluate_node_status_rule(rule).await,
-            AlertRuleType::Compliance => self.evaluate_compliance_rule(rule).await,
+            AlertRuleType::NodeStatus => self.evaluate_node_status_rule(rule, maintenance).await,
+            AlertRuleType::Compliance => self.evaluate_compliance_rule(rule, maintenance).await,
             AlertRuleType::Drift => self.evaluate_drift_rule(rule).await,
-            AlertRuleType::ReportFailure => self.evaluate_report_failure_rule(rule).await,
+            AlertRuleType::ReportFailure => {
+                self.evaluate_report_failure_rule(rule, maintenance).await
+            }
             AlertRuleType::Vulnerability => {
                 // Vulnerability alerts are triggered by the CVE scheduler, not rule evaluation
                 Ok(None)
@@ -411,7 +484,11 @@ impl AlertingService {
     }
 
     /// Evaluate node status rule
-    async fn evaluate_node_status_rule(&self, rule: &AlertRule) -> Result<Option<Alert>> {
+    async fn evaluate_node_status_rule(
+        &self,
+        rule: &AlertRule,
+        maintenance: &ActiveMaintenance,
+    ) -> Result<Option<Alert>> {
         let Some(puppetdb) = &self.puppetdb else {
             warn!("PuppetDB not configured, cannot evaluate node status rule");
             return Ok(None);
@@ -419,9 +496,10 @@ impl AlertingService {
 
         // Get nodes from PuppetDB
         let nodes = puppetdb.get_nodes().await?;
+        let smart_lists = self.resolve_rule_smart_lists(rule, puppetdb).await?;
 
         let mut failed_nodes = Vec::new();
-        for node in &nodes {
+        for node in nodes.iter().filter(|n| !maintenance.contains(&n.certname)) {
             // Check if node matches any condition
             let matches = self.evaluate_conditions(
                 &rule.conditions,
@@ -429,6 +507,7 @@ impl AlertingService {
                     "node.certname": node.certname,
                     "node.status": node.latest_report_status.as_deref().unwrap_or("unknown"),
                     "node.environment": node.report_environment.as_deref().unwrap_or(""),
+                    SMART_LISTS_FIELD: smart_list_memberships(&smart_lists, &node.certname),
                 }),
                 rule.condition_operator,
             );
@@ -457,7 +536,11 @@ impl AlertingService {
     }
 
     /// Evaluate compliance rule
-    async fn evaluate_compliance_rule(&self, rule: &AlertRule) -> Result<Option<Alert>> {
+    async fn evaluate_compliance_rule(
+        &self,
+        rule: &AlertRule,
+        maintenance: &ActiveMaintenance,
+    ) -> Result<Option<Alert>> {
         let Some(puppetdb) = &self.puppetdb else {
             warn!("PuppetDB not configured, cannot evaluate compliance rule");
             return Ok(None);
@@ -467,7 +550,10 @@ impl AlertingService {
         let reports = puppetdb.query_reports(None, None, Some(100)).await?;
 
         let mut non_compliant = Vec::new();
-        for report in &reports {
+        for report in reports
+            .iter()
+            .filter(|r| !maintenance.contains(&r.certname))
+        {
             let context = json!({
                 "compliance.status": report.status,
                 "compliance.certname": report.certname,
@@ -506,7 +592,11 @@ impl AlertingService {
     }
 
     /// Evaluate report failure rule
-    async fn evaluate_report_failure_rule(&self, rule: &AlertRule) -> Result<Option<Alert>> {
+    async fn evaluate_report_failure_rule(
+        &self,
+        rule: &AlertRule,
+        maintenance: &ActiveMaintenance,
+    ) -> Result<Option<Alert>> {
         let Some(puppetdb) = &self.puppetdb else {
             warn!("PuppetDB not configured, cannot evaluate report failure rule");
             return Ok(None);
@@ -517,11 +607,17 @@ impl AlertingService {
             .query_reports(None, Some("failed"), Some(100))
             .await?;
 
+        let smart_lists = self.resolve_rule_smart_lists(rule, puppetdb).await?;
+
         let mut failed_reports = Vec::new();
-        for report in &reports {
+        for report in reports
+            .iter()
+            .filter(|r| !maintenance.contains(&r.certname))
+        {
             let context = json!({
                 "report.status": report.status,
                 "report.certname": report.certname,
+                SMART_LISTS_FIELD: smart_list_memberships(&smart_lists, &report.certname),
             });
 
             if self.evaluate_conditions(&rule.conditions, &context, rule.condition_operator) {
@@ -609,7 +705,7 @@ impl AlertingService {
 
         // Resolve any condition value referencing the configured maximum runtime
         // so rules can reuse the Settings value instead of a hard-coded number.
-        let max_runtime = SettingsRepository::new(self.pool.clone())
+        let max_runtime = SettingsRepository::new(self.pool.clone(), self.cipher.clone())
             .get_update_job_settings()
             .await
             .map(|s| s.max_runtime_minutes)
@@ -722,6 +818,60 @@ impl AlertingService {
         Ok(count > 0)
     }
 
+    /// Resolve the smart lists referenced by a rule's conditions
+    ///
+    /// Returns a map of smart list ID to member certnames. Only lists named in
+    /// `node.smart_lists` conditions are resolved, so rules without smart list
+    /// scoping cost nothing extra. Lists are looked up in the organization of
+    /// the rule's creator and must be visible to them.
+    async fn resolve_rule_smart_lists(
+        &self,
+        rule: &AlertRule,
+        puppetdb: &PuppetDbClient,
+    ) -> Result<HashMap<String, HashSet<String>>> {
+        let ids = rule_smart_list_ids(&rule.conditions);
+        if ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let owner = match rule.created_by {
+            Some(user_id) => {
+                AuthService::new(self.pool.clone())
+                    .get_user_by_id(&user_id)
+                    .await?
+            }
+            None => None,
+        };
+        let Some(owner) = owner else {
+            warn!(
+                "Rule {} references smart lists but has no creator to resolve them for",
+                rule.name
+            );
+            return Ok(HashMap::new());
+        };
+
+        let mut resolved = HashMap::new();
+        for id in ids {
+            let Ok(uuid) = Uuid::parse_str(&id) else {
+                warn!(
+                    "Rule {} references invalid smart list ID '{}'",
+                    rule.name, id
+                );
+                continue;
+            };
+            let Some(list) =
+                load_smart_list_for_user(&self.pool, owner.organization_id, uuid, owner.id).await?
+            else {
+                warn!("Rule {} references missing smart list {}", rule.name, id);
+                continue;
+            };
+            let members = resolve_smart_list_certnames(&self.pool, puppetdb, &list).await?;
+            resolved.insert(id, members);
+        }
+
+        Ok(resolved)
+    }
+
     /// Evaluate conditions against a context
     fn evaluate_conditions(
         &self,
@@ -872,6 +1022,23 @@ impl AlertingService {
         // Send notifications to all associated channels
         self.send_alert_notifications(&alert, rule).await?;
 
+        if let Some(webhooks) = &self.webhooks {
+            webhooks.emit(
+                None,
+                WebhookEvent::AlertFired,
+                json!({
+                    "alert_id": alert.id,
+                    "rule_id": rule.id,
+                    "rule_name": rule.name,
+                    "severity": rule.severity.as_str(),
+                    "title": alert.title,
+                    "message": alert.message,
+                    "context": alert.context,
+                    "triggered_at": alert.triggered_at,
+                }),
+            );
+        }
+
         // Create an in-app notification for every user so the alert appears in
         // each user's notification bell (not only the rule creator).
         if let Some(notification_service) = &self.notification_service {
@@ -888,47 +1055,35 @@ impl AlertingService {
                 "severity": rule.severity.as_str(),
             });
 
-            // Fetch all user ids so the alert reaches everyone's notification bell.
-            let user_ids: Vec<String> = sqlx::query_scalar::<_, String>("SELECT id FROM users")
-                .fetch_all(&self.pool)
-                .await
-                .unwrap_or_else(|e| {
-                    error!("Failed to list users for alert notification: {}", e);
-                    Vec::new()
-                });
+            let notification = NewNotification {
+                organization_id: None,
+                title: format!("Alert: {}", title),
+                message: message.to_string(),
+                r#type: notification_type,
+                category: Some("alert".to_string()),
+                link: Some(format!("/alerting?alert_id={}", alert.id)),
+                expires_at: None,
+                metadata: Some(metadata),
+                dedup_key: None,
+            };
 
-            if user_ids.is_empty() {
-                warn!(
+            match notification_service
+                .notify(&NotificationAudience::AllUsers, &notification)
+                .await
+            {
+                Ok(0) => warn!(
                     "No users found to notify for alert {} - skipping in-app notification",
                     alert.id
-                );
-            }
-
-            for user_id in user_ids {
-                let notification_req = CreateNotificationRequest {
-                    user_id,
-                    organization_id: None,
-                    title: format!("Alert: {}", title),
-                    message: message.to_string(),
-                    r#type: notification_type.clone(),
-                    category: Some("alert".to_string()),
-                    link: Some(format!("/alerting?alert_id={}", alert.id)),
-                    expires_at: None,
-                    metadata: Some(metadata.clone()),
-                };
-
-                if let Err(e) = notification_service
-                    .create_notification(notification_req)
-                    .await
-                {
-                    error!(
-                        "Failed to create in-app notification for alert {}: {}",
-                        alert.id, e
-                    );
-                }
+                ),
+                Ok(sent) => info!(
+                    "Created in-app notifications for alert {} ({} users)",
+                    alert.id, sent
+                ),
+                Err(e) => error!(
+                    "Failed to create in-app notifications for alert {}: {}",
+                    alert.id, e
+                ),
             }
-
-            info!("Created in-app notifications for alert {}", alert.id);
         }
 
         Ok(alert)
@@ -1083,8 +1238,7 @@ impl AlertingService {
         payload: &WebhookPayload,
     ) -> Result<i32> {
         use lettre::message::{header, Message, MultiPart, SinglePart};
-        use lettre::transport::smtp::authentication::Credentials;
-        use lettre::{AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
+        use lettre::AsyncTransport;
 
         let config: EmailConfig =
             serde_json::from_value(channel.config.clone()).context("Invalid email config")?;
@@ -1181,24 +1335,15 @@ impl AlertingService {
             .context("Failed to build email")?;
 
         // Build SMTP transport
-        let mut mailer_builder = if config.use_tls {
-            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.smtp_host)
-                .context("Failed to create SMTP transport")?
-        } else {
-            AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.smtp_host)
-        };
-
-        mailer_builder = mailer_builder.port(config.smtp_port);
-
-        // Add credentials if provided
-        if let (Some(username), Some(password)) = (&config.smtp_username, &config.smtp_password) {
-            if !username.is_empty() && !password.is_empty() {
-                mailer_builder = mailer_builder
-                    .credentials(Credentials::new(username.clone(), password.clone()));
-            }
-        }
-
-        let mailer = mailer_builder.build();
+        let mailer = crate::services::mailer::smtp_transport(
+            &config.smtp_host,
+            config.smtp_port,
+            config.use_tls,
+            config.smtp_username.as_deref(),
+            config.smtp_password.as_deref(),
+            self.secrets.as_deref(),
+        )
+        .await?;
 
         // Send email
         mailer.send(email).await.context("Failed to send email")?;
@@ -1422,6 +1567,40 @@ impl AlertingService {
     }
 }
 
+/// List the smart list IDs (from a resolved set) that contain a certname
+/// IDs of the smart lists named in `node.smart_lists` conditions
+pub fn rule_smart_list_ids(conditions: &[AlertCondition]) -> HashSet<String> {
+    let mut ids = HashSet::new();
+    for condition in conditions {
+        if condition.field.as_deref() != Some(SMART_LISTS_FIELD) {
+            continue;
+        }
+        match &condition.value {
+            Some(serde_json::Value::String(id)) => {
+                ids.insert(id.clone());
+            }
+            Some(serde_json::Value::Array(values)) => {
+                ids.extend(values.iter().filter_map(|v| v.as_str().map(str::to_string)));
+            }
+            _ => {}
+        }
+    }
+    ids
+}
+
+fn smart_list_memberships(
+    smart_lists: &HashMap<String, HashSet<String>>,
+    certname: &str,
+) -> Vec<String> {
+    let mut ids: Vec<String> = smart_lists
+        .iter()
+        .filter(|(_, members)| members.contains(certname))
+        .map(|(id, _)| id.clone())
+        .collect();
+    ids.sort();
+    ids
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1528,4 +1707,35 @@ mod tests {
         );
         assert_eq!(get_field_value(&value, "node.missing"), None);
     }
+
+    #[test]
+    fn test_smart_list_memberships() {
+        let mut lists = HashMap::new();
+        lists.insert(
+            "b-list".to_string(),
+            HashSet::from(["web1".to_string(), "web2".to_string()]),
+        );
+        lists.insert("a-list".to_string(), HashSet::from(["web1".to_string()]));
+
+        assert_eq!(
+            smart_list_memberships(&lists, "web1"),
+            vec!["a-list".to_string(), "b-list".to_string()]
+        );
+        assert_eq!(
+            smart_list_memberships(&lists, "web2"),
+            vec!["b-list".to_string()]
+        );
+        assert!(smart_list_memberships(&lists, "db1").is_empty());
+
+        // Scoping condition matches via "contains" on the membership array
+        let condition = AlertCondition {
+            condition_type: None,
+            operator: "contains".to_string(),
+            value: Some(json!("a-list")),
+            enabled: true,
+            field: Some(SMART_LISTS_FIELD.to_string()),
+        };
+        let context = json!({ "node": { "smart_lists": smart_list_memberships(&lists, "web1") } });
+        assert!(evaluate_condition(&condition, &context));
+    }
 }
@@ -0,0 +1,241 @@
+//! API key usage restrictions
+//!
+//! Keys can be limited to a list of client addresses and to a set of
+//! resource/action scopes. Both are checked by the auth middleware on every
+//! request made with the key, before any handler runs.
+
+use std::net::IpAddr;
+
+use axum::http::Method;
+
+use crate::models::{Action, ApiKeyScope, Resource};
+
+/// An IP address or CIDR range from a key's allowlist
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpRule {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl IpRule {
+    /// Parse `10.0.0.5`, `10.0.0.0/8`, `2001:db8::1` or `2001:db8::/32`
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let (addr, prefix) = match s.split_once('/') {
+            Some((addr, prefix)) => (addr, Some(prefix.parse::<u8>().ok()?)),
+            None => (s, None),
+        };
+        let network: IpAddr = addr.parse().ok()?;
+        let max = match network {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+        let prefix = prefix.unwrap_or(max);
+        if prefix > max {
+            return None;
+        }
+        Some(Self { network, prefix })
+    }
+
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip.to_canonical()) {
+            (IpAddr::V4(net), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(net) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(net) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Whether a client address may use a key with this allowlist
+///
+/// An empty allowlist allows any address. A key with an allowlist is refused
+/// when the client address is unknown.
+pub fn ip_allowed(allowed_ips: &[String], client_ip: Option<IpAddr>) -> bool {
+    if allowed_ips.is_empty() {
+        return true;
+    }
+    let Some(client_ip) = client_ip else {
+        return false;
+    };
+    allowed_ips
+        .iter()
+        .filter_map(|entry| IpRule::parse(entry))
+        .any(|rule| rule.contains(client_ip))
+}
+
+/// The resource and action a request needs, for checking key scopes
+///
+/// `path` is relative to the API root; a leading `/api/v1` is ignored.
+/// Returns `None` for endpoints that no scope covers.
+pub fn scope_for_request(method: &Method, path: &str) -> Option<ApiKeyScope> {
+    let path = path.strip_prefix("/api/v1").unwrap_or(path);
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let first = *segments.first()?;
+
+    let resource = match first {
+        "nodes"
+        | "query"
+        | "smart-lists"
+        | "maintenance-windows"
+        | "node-removal"
+        | "inventory"
+        | "cve" => Resource::Nodes,
+        "groups" => Resource::Groups,
+        "reports" | "analytics" => Resource::Reports,
+        "facts" => Resource::Facts,
+        "facter" => Resource::FacterTemplates,
+        "users" | "organizations" | "sessions" => Resource::Users,
+        "roles" | "permissions" | "elevations" => Resource::Roles,
+        "settings" | "code" | "backup" | "alerting" | "notifications" | "logs" | "debug" => {
+            Resource::Settings
+        }
+        "audit-logs" => Resource::AuditLogs,
+        "api-keys" => Resource::ApiKeys,
+        "ca" => Resource::Certificates,
+        _ => return None,
+    };
+
+    let action = if resource == Resource::Certificates {
+        match (method, segments.get(1).copied(), segments.get(2).copied()) {
+            (&Method::POST, Some("sign"), _) => Action::Sign,
+            (&Method::POST, Some("reject"), _) => Action::Reject,
+            (&Method::POST, Some("bulk"), Some("sign")) => Action::BulkSign,
+            (&Method::POST, Some("bulk"), Some("revoke")) => Action::BulkRevoke,
+            (&Method::DELETE, Some("certificates"), _) => Action::Revoke,
+            _ => method_action(method)?,
+        }
+    } else {
+        method_action(method)?
+    };
+
+    Some(ApiKeyScope { resource, action })
+}
+
+fn method_action(method: &Method) -> Option<Action> {
+    match *method {
+        Method::GET | Method::HEAD => Some(Action::Read),
+        Method::POST => Some(Action::Create),
+        Method::PUT | Method::PATCH => Some(Action::Update),
+        Method::DELETE => Some(Action::Delete),
+        _ => None,
+    }
+}
+
+/// Whether a key with these scopes may perform `required`
+///
+/// A key without scopes is limited by its roles only. An admin scope covers
+/// every action on its resource.
+pub fn scopes_allow(scopes: &[ApiKeyScope], required: Option<ApiKeyScope>) -> bool {
+    if scopes.is_empty() {
+        return true;
+    }
+    let Some(required) = required else {
+        return false;
+    };
+    scopes.iter().any(|scope| {
+        scope.resource == required.resource
+            && (scope.action == required.action || scope.action == Action::Admin)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ip_rules() {
+        let rule = IpRule::parse("10.1.0.0/16").unwrap();
+        assert!(rule.contains("10.1.200.3".parse().unwrap()));
+        assert!(!rule.contains("10.2.0.1".parse().unwrap()));
+        assert!(rule.contains("::ffff:10.1.0.9".parse().unwrap()));
+
+        let single = IpRule::parse("192.168.1.10").unwrap();
+        assert!(single.contains("192.168.1.10".parse().unwrap()));
+        assert!(!single.contains("192.168.1.11".parse().unwrap()));
+
+        let v6 = IpRule::parse("2001:db8::/32").unwrap();
+        assert!(v6.contains("2001:db8:1::1".parse().unwrap()));
+        assert!(!v6.contains("10.1.0.1".parse().unwrap()));
+
+        assert!(IpRule::parse("0.0.0.0/0")
+            .unwrap()
+            .contains("8.8.8.8".parse().unwrap()));
+        assert!(IpRule::parse("10.0.0.0/33").is_none());
+        assert!(IpRule::parse("not-an-ip").is_none());
+    }
+
+    #[test]
+    fn test_ip_allowed() {
+        let allowlist = vec!["10.0.0.0/8".to_string()];
+        assert!(ip_allowed(&[], None));
+        assert!(ip_allowed(&allowlist, Some("10.3.4.5".parse().unwrap())));
+        assert!(!ip_allowed(&allowlist, Some("172.16.0.1".parse().unwrap())));
+        assert!(!ip_allowed(&allowlist, None));
+    }
+
+    #[test]
+    fn test_scope_for_request() {
+        assert_eq!(
+            scope_for_request(&Method::GET, "/api/v1/nodes/web01"),
+            Some(ApiKeyScope {
+                resource: Resource::Nodes,
+                action: Action::Read
+            })
+        );
+        assert_eq!(
+            scope_for_request(&Method::PUT, "/groups/abc"),
+            Some(ApiKeyScope {
+                resource: Resource::Groups,
+                action: Action::Update
+            })
+        );
+        assert_eq!(
+            scope_for_request(&Method::POST, "/ca/bulk/sign"),
+            Some(ApiKeyScope {
+                resource: Resource::Certificates,
+                action: Action::BulkSign
+            })
+        );
+        assert_eq!(
+            scope_for_request(&Method::DELETE, "/ca/certificates/web01"),
+            Some(ApiKeyScope {
+                resource: Resource::Certificates,
+                action: Action::Revoke
+            })
+        );
+        assert_eq!(scope_for_request(&Method::GET, "/auth/me"), None);
+    }
+
+    #[test]
+    fn test_scopes_allow() {
+        let scopes = vec![
+            ApiKeyScope {
+                resource: Resource::Nodes,
+                action: Action::Read,
+            },
+            ApiKeyScope {
+                resource: Resource::Groups,
+                action: Action::Admin,
+            },
+        ];
+        let need = |resource, action| Some(ApiKeyScope { resource, action });
+
+        assert!(scopes_allow(&scopes, need(Resource::Nodes, Action::Read)));
+        assert!(!scopes_allow(
+            &scopes,
+            need(Resource::Nodes, Action::Delete)
+        ));
+        assert!(scopes_allow(
+            &scopes,
+            need(Resource::Groups, Action::Delete)
+        ));
+        assert!(!scopes_allow(&scopes, None));
+        assert!(scopes_allow(&[], None));
+    }
+}
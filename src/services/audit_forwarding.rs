@@ -0,0 +1,618 @@
+//! Forwarding of audit log entries to syslog and SIEM collectors
+//!
+//! Every entry written by [`AuditRepository`](crate::db::AuditRepository) is
+//! handed to the [`AuditForwarder`] it was created with, which queues it for
+//! each configured sink. A background task per sink sends the queue in batches:
+//! syslog (RFC 5424 or CEF, over UDP or TCP) or an HTTP collector (plain
+//! JSON, Splunk HEC or the Elasticsearch bulk API). Failed batches are
+//! retried with exponential backoff; the database remains the system of
+//! record, so entries that cannot be delivered are dropped with an error in
+//! the log instead of blocking requests.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpStream, UdpSocket};
+use tokio::sync::mpsc;
+use tracing::{error, info, warn};
+
+use crate::config::{
+    AuditConfig, AuditHttpFormat, AuditSinkConfig, AuditSinkTarget, SyslogFormat, SyslogProtocol,
+};
+use crate::models::AuditLogEntry;
+use crate::services::secrets::{resolve_runtime_value, SecretsResolver};
+
+/// Timeout of a single delivery attempt
+const DELIVERY_TIMEOUT_SECS: u64 = 15;
+
+/// Syslog severity of audit entries (notice)
+const SYSLOG_SEVERITY: u8 = 5;
+
+/// Private enterprise number used for the structured data element
+const SD_ID: &str = "audit@32473";
+
+/// A destination that accepts batches of audit entries
+#[async_trait]
+trait AuditSink: Send + Sync {
+    async fn deliver(&self, entries: &[AuditLogEntry]) -> Result<()>;
+}
+
+struct SinkHandle {
+    name: String,
+    tx: mpsc::Sender<AuditLogEntry>,
+}
+
+/// Queues audit entries for every configured sink
+pub struct AuditForwarder {
+    sinks: Vec<SinkHandle>,
+}
+
+impl AuditForwarder {
+    /// Queue an entry for every sink without waiting
+    pub fn forward(&self, entry: &AuditLogEntry) {
+        for sink in &self.sinks {
+            if let Err(mpsc::error::TrySendError::Full(_)) = sink.tx.try_send(entry.clone()) {
+                warn!(
+                    "Audit sink '{}' queue is full; dropping entry {}",
+                    sink.name, entry.id
+                );
+            }
+        }
+    }
+}
+
+/// Start a background task per configured sink and return the forwarder
+///
+/// Returns `None` without sinks. `secrets` resolves collector tokens written
+/// as secret references.
+pub fn init(
+    config: &AuditConfig,
+    secrets: Option<Arc<SecretsResolver>>,
+) -> Result<Option<AuditForwarder>> {
+    if config.sinks.is_empty() {
+        return Ok(None);
+    }
+
+    let mut sinks = Vec::with_capacity(config.sinks.len());
+    for sink_config in &config.sinks {
+        let name = sink_name(sink_config);
+        let sink = build_sink(&sink_config.target, secrets.clone())
+            .with_context(|| format!("Failed to set up audit sink '{}'", name))?;
+        let (tx, rx) = mpsc::channel(sink_config.buffer_size);
+        tokio::spawn(run_sink(name.clone(), sink, rx, sink_config.clone()));
+        info!("Forwarding audit log to '{}'", name);
+        sinks.push(SinkHandle { name, tx });
+    }
+
+    Ok(Some(AuditForwarder { sinks }))
+}
+
+fn sink_name(config: &AuditSinkConfig) -> String {
+    config.name.clone().unwrap_or_else(|| match &config.target {
+        AuditSinkTarget::Syslog { address, .. } => format!("syslog {}", address),
+        AuditSinkTarget::Http { url, .. } => format!("http {}", url),
+    })
+}
+
+fn build_sink(
+    target: &AuditSinkTarget,
+    secrets: Option<Arc<SecretsResolver>>,
+) -> Result<Box<dyn AuditSink>> {
+    Ok(match target {
+        AuditSinkTarget::Syslog {
+            address,
+            protocol,
+            format,
+            facility,
+            app_name,
+        } => Box::new(SyslogSink {
+            address: address.clone(),
+            protocol: *protocol,
+            format: *format,
+            facility: *facility,
+            app_name: app_name.clone(),
+            hostname: local_hostname(),
+        }),
+        AuditSinkTarget::Http {
+            url,
+            format,
+            token,
+            index,
+            ssl_verify,
+        } => {
+            let mut builder = reqwest::Client::builder()
+                .timeout(Duration::from_secs(DELIVERY_TIMEOUT_SECS))
+                .user_agent("OpenVox-AuditForwarder/1.0");
+            if !ssl_verify {
+                builder = builder.danger_accept_invalid_certs(true);
+            }
+            Box::new(HttpSink {
+                client: builder.build().context("Failed to build HTTP client")?,
+                url: url.clone(),
+                format: *format,
+                token: token.clone(),
+                index: index.clone(),
+                hostname: local_hostname(),
+                secrets,
+            })
+        }
+    })
+}
+
+/// Send queued entries in batches until the forwarder is dropped
+async fn run_sink(
+    name: String,
+    sink: Box<dyn AuditSink>,
+    mut rx: mpsc::Receiver<AuditLogEntry>,
+    config: AuditSinkConfig,
+) {
+    let mut batch = Vec::with_capacity(config.batch_size);
+    while rx.recv_many(&mut batch, config.batch_size).await > 0 {
+        let mut attempt = 0;
+        loop {
+            let result = sink.deliver(&batch).await;
+            match result {
+                Ok(()) => break,
+                Err(e) if attempt < config.max_retries => {
+                    let delay = config
+                        .retry_delay_secs
+                        .saturating_mul(1u64 << attempt.min(10));
+                    warn!(
+                        "Audit sink '{}' delivery failed (attempt {}), retrying in {}s: {:#}",
+                        name,
+                        attempt + 1,
+                        delay,
+                        e
+                    );
+                    tokio::time::sleep(Duration::from_secs(delay)).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    error!(
+                        "Audit sink '{}' dropped {} entries after {} attempts: {:#}",
+                        name,
+                        batch.len(),
+                        attempt + 1,
+                        e
+                    );
+                    break;
+                }
+            }
+        }
+        batch.clear();
+    }
+}
+
+fn local_hostname() -> String {
+    std::env::var("HOSTNAME")
+        .ok()
+        .or_else(|| std::fs::read_to_string("/proc/sys/kernel/hostname").ok())
+        .map(|h| h.trim().to_string())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+struct SyslogSink {
+    address: String,
+    protocol: SyslogProtocol,
+    format: SyslogFormat,
+    facility: u8,
+    app_name: String,
+    hostname: String,
+}
+
+#[async_trait]
+impl AuditSink for SyslogSink {
+    async fn deliver(&self, entries: &[AuditLogEntry]) -> Result<()> {
+        let messages: Vec<String> = entries
+            .iter()
+            .map(|entry| match self.format {
+                SyslogFormat::Rfc5424 => {
+                    rfc5424_message(entry, self.facility, &self.hostname, &self.app_name)
+                }
+                SyslogFormat::Cef => {
+                    cef_syslog_message(entry, self.facility, &self.hostname, &self.app_name)
+                }
+            })
+            .collect();
+
+        let send = async {
+            match self.protocol {
+                SyslogProtocol::Udp => {
+                    let socket = UdpSocket::bind(if self.address.starts_with('[') {
+                        "[::]:0"
+                    } else {
+                        "0.0.0.0:0"
+                    })
+                    .await
+                    .context("Failed to bind UDP socket")?;
+                    socket
+                        .connect(&self.address)
+                        .await
+                        .with_context(|| format!("Failed to resolve {}", self.address))?;
+                    for message in &messages {
+                        socket
+                            .send(message.as_bytes())
+                            .await
+                            .context("Failed to send syslog datagram")?;
+                    }
+                }
+                SyslogProtocol::Tcp => {
+                    let mut stream = TcpStream::connect(&self.address)
+                        .await
+                        .with_context(|| format!("Failed to connect to {}", self.address))?;
+                    let mut framed = String::new();
+                    for message in &messages {
+                        framed.push_str(&format!("{} {}", message.len(), message));
+                    }
+                    stream
+                        .write_all(framed.as_bytes())
+                        .await
+                        .context("Failed to send syslog messages")?;
+                    stream.shutdown().await.ok();
+                }
+            }
+            Ok::<(), anyhow::Error>(())
+        };
+
+        tokio::time::timeout(Duration::from_secs(DELIVERY_TIMEOUT_SECS), send)
+            .await
+            .context("Syslog delivery timed out")?
+    }
+}
+
+struct HttpSink {
+    client: reqwest::Client,
+    url: String,
+    format: AuditHttpFormat,
+    token: Option<String>,
+    index: Option<String>,
+    hostname: String,
+    secrets: Option<Arc<SecretsResolver>>,
+}
+
+#[async_trait]
+impl AuditSink for HttpSink {
+    async fn deliver(&self, entries: &[AuditLogEntry]) -> Result<()> {
+        let token = match &self.token {
+            Some(token) => Some(
+                resolve_runtime_value(self.secrets.as_deref(), token)
+                    .await
+                    .context("Failed to resolve collector token")?,
+            ),
+            None => None,
+        };
+
+        let request = self.client.post(&self.url);
+        let request =
+            match self.format {
+                AuditHttpFormat::Json => {
+                    let request = request.json(entries);
+                    match token {
+                        Some(token) => request.bearer_auth(token),
+                        None => request,
+                    }
+                }
+                AuditHttpFormat::SplunkHec => {
+                    let body = splunk_hec_body(entries, &self.hostname, self.index.as_deref());
+                    let request = request
+                        .header(reqwest::header::CONTENT_TYPE, "application/json")
+                        .body(body);
+                    match token {
+                        Some(token) => request
+                            .header(reqwest::header::AUTHORIZATION, format!("Splunk {}", token)),
+                        None => request,
+                    }
+                }
+                AuditHttpFormat::Elastic => {
+                    let index = self.index.as_deref().unwrap_or("openvox-audit");
+                    let request = request
+                        .header(reqwest::header::CONTENT_TYPE, "application/x-ndjson")
+                        .body(elastic_bulk_body(entries, index));
+                    match token {
+                        Some(token) => request
+                            .header(reqwest::header::AUTHORIZATION, format!("ApiKey {}", token)),
+                        None => request,
+                    }
+                }
+            };
+
+        let response = request.send().await.context("Collector request failed")?;
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Collector returned {}: {}", status, body);
+        }
+
+        // The bulk API reports per-document failures with a 200 status
+        if self.format == AuditHttpFormat::Elastic {
+            let result: serde_json::Value =
+                response.json().await.context("Invalid bulk API response")?;
+            if result.get("errors").and_then(|e| e.as_bool()) == Some(true) {
+                anyhow::bail!("Bulk API rejected some entries");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Escape a PARAM-VALUE of RFC 5424 structured data
+fn sd_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace(']', "\\]")
+}
+
+/// RFC 5424 header fields are printable ASCII without spaces
+fn header_field(value: &str, max_len: usize) -> String {
+    let field: String = value
+        .chars()
+        .filter(|c| c.is_ascii_graphic())
+        .take(max_len)
+        .collect();
+    if field.is_empty() {
+        "-".to_string()
+    } else {
+        field
+    }
+}
+
+fn syslog_header(
+    entry: &AuditLogEntry,
+    facility: u8,
+    hostname: &str,
+    app_name: &str,
+    msg_id: &str,
+) -> String {
+    format!(
+        "<{}>1 {} {} {} {} {}",
+        facility as u16 * 8 + SYSLOG_SEVERITY as u16,
+        entry
+            .created_at
+            .to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        header_field(hostname, 255),
+        header_field(app_name, 48),
+        std::process::id(),
+        header_field(msg_id, 32),
+    )
+}
+
+/// RFC 5424 message: entry fields as structured data, details as message
+fn rfc5424_message(entry: &AuditLogEntry, facility: u8, hostname: &str, app_name: &str) -> String {
+    let mut params = vec![
+        ("id", entry.id.to_string()),
+        ("organization_id", entry.organization_id.to_string()),
+        ("action", entry.action.clone()),
+        ("resource_type", entry.resource_type.clone()),
+    ];
+    if let Some(user_id) = entry.user_id {
+        params.push(("user_id", user_id.to_string()));
+    }
+    if let Some(impersonator_id) = entry.impersonator_id {
+        params.push(("impersonator_id", impersonator_id.to_string()));
+    }
+    if let Some(user_kind) = entry.user_kind {
+        params.push(("user_kind", user_kind.to_string()));
+    }
+    if let Some(resource_id) = &entry.resource_id {
+        params.push(("resource_id", resource_id.clone()));
+    }
+    if let Some(ip_address) = &entry.ip_address {
+        params.push(("ip_address", ip_address.clone()));
+    }
+    if let Some(request_id) = &entry.request_id {
+        params.push(("request_id", request_id.clone()));
+    }
+    let structured_data: String = params
+        .iter()
+        .map(|(key, value)| format!(" {}=\"{}\"", key, sd_escape(value)))
+        .collect();
+
+    let mut message = format!(
+        "{} [{}{}]",
+        syslog_header(entry, facility, hostname, app_name, &entry.action),
+        SD_ID,
+        structured_data
+    );
+    if let Some(details) = &entry.details {
+        message.push(' ');
+        message.push_str(&details.to_string());
+    }
+    message
+}
+
+/// Escape a CEF header field
+fn cef_header_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+/// Escape a CEF extension value
+fn cef_value_escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('=', "\\=")
+        .replace('\r', "\\r")
+        .replace('\n', "\\n")
+}
+
+/// CEF event for an entry
+fn cef_event(entry: &AuditLogEntry) -> String {
+    let mut extension = vec![
+        ("rt", entry.created_at.timestamp_millis().to_string()),
+        ("externalId", entry.id.to_string()),
+        ("act", entry.action.clone()),
+        ("cs1Label", "organizationId".to_string()),
+        ("cs1", entry.organization_id.to_string()),
+        ("cs2Label", "resourceType".to_string()),
+        ("cs2", entry.resource_type.clone()),
+    ];
+    if let Some(resource_id) = &entry.resource_id {
+        extension.push(("cs3Label", "resourceId".to_string()));
+        extension.push(("cs3", resource_id.clone()));
+    }
+    if let Some(user_id) = entry.user_id {
+        extension.push(("suid", user_id.to_string()));
+    }
+    if let Some(impersonator_id) = entry.impersonator_id {
+        extension.push(("cs5Label", "impersonatorId".to_string()));
+        extension.push(("cs5", impersonator_id.to_string()));
+    }
+    if let Some(user_kind) = entry.user_kind {
+        extension.push(("cs6Label", "userKind".to_string()));
+        extension.push(("cs6", user_kind.to_string()));
+    }
+    if let Some(ip_address) = &entry.ip_address {
+        extension.push(("src", ip_address.clone()));
+    }
+    if let Some(request_id) = &entry.request_id {
+        extension.push(("cs4Label", "requestId".to_string()));
+        extension.push(("cs4", request_id.clone()));
+    }
+    if let Some(details) = &entry.details {
+        extension.push(("msg", details.to_string()));
+    }
+
+    let extension: Vec<String> = extension
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, cef_value_escape(value)))
+        .collect();
+
+    format!(
+        "CEF:0|OpenVox|OpenVox WebUI|{}|{}|{}|3|{}",
+        cef_header_escape(env!("CARGO_PKG_VERSION")),
+        cef_header_escape(&entry.action),
+        cef_header_escape(&entry.action),
+        extension.join(" ")
+    )
+}
+
+fn cef_syslog_message(
+    entry: &AuditLogEntry,
+    facility: u8,
+    hostname: &str,
+    app_name: &str,
+) -> String {
+    format!(
+        "{} - {}",
+        syslog_header(entry, facility, hostname, app_name, &entry.action),
+        cef_event(entry)
+    )
+}
+
+/// Splunk HEC body: one event object per entry, concatenated
+fn splunk_hec_body(entries: &[AuditLogEntry], hostname: &str, index: Option<&str>) -> String {
+    entries
+        .iter()
+        .map(|entry| {
+            let mut event = serde_json::json!({
+                "time": entry.created_at.timestamp_millis() as f64 / 1000.0,
+                "host": hostname,
+                "source": "openvox-webui",
+                "sourcetype": "openvox:audit",
+                "event": entry,
+            });
+            if let Some(index) = index {
+                event["index"] = serde_json::Value::from(index);
+            }
+            event.to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Elasticsearch bulk body: an index action and a document per entry
+fn elastic_bulk_body(entries: &[AuditLogEntry], index: &str) -> String {
+    let mut body = String::new();
+    for entry in entries {
+        let action = serde_json::json!({ "index": { "_index": index, "_id": entry.id } });
+        let mut document = serde_json::to_value(entry).unwrap_or_default();
+        document["@timestamp"] = serde_json::Value::from(entry.created_at.to_rfc3339());
+        body.push_str(&action.to_string());
+        body.push('\n');
+        body.push_str(&document.to_string());
+        body.push('\n');
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use uuid::Uuid;
+
+    fn entry() -> AuditLogEntry {
+        AuditLogEntry {
+            id: Uuid::nil(),
+            organization_id: Uuid::nil(),
+            user_id: None,
+            impersonator_id: None,
+            user_kind: None,
+            action: "group.update".to_string(),
+            resource_type: "groups".to_string(),
+            resource_id: Some("web]\"servers".to_string()),
+            details: Some(serde_json::json!({ "name": "web" })),
+            ip_address: Some("10.0.0.5".to_string()),
+            request_id: None,
+            created_at: chrono::Utc.with_ymd_and_hms(2026, 1, 2, 3, 4, 5).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_rfc5424_message() {
+        let message = rfc5424_message(&entry(), 13, "web01", "openvox-webui");
+        let prefix = format!(
+            "<109>1 2026-01-02T03:04:05.000Z web01 openvox-webui {} group.update [audit@32473 ",
+            std::process::id()
+        );
+        assert!(message.starts_with(&prefix), "{}", message);
+        assert!(message.contains(r#"resource_id="web\]\"servers""#));
+        assert!(message.contains(r#"ip_address="10.0.0.5""#));
+        assert!(!message.contains("user_id="));
+        assert!(message.ends_with(r#"] {"name":"web"}"#));
+    }
+
+    #[test]
+    fn test_cef_event() {
+        let mut entry = entry();
+        entry.action = "ca|sign".to_string();
+        entry.details = Some(serde_json::json!({ "filter": "a=b" }));
+        entry.user_kind = Some(crate::models::UserKind::ServiceAccount);
+        let event = cef_event(&entry);
+
+        assert!(event.starts_with("CEF:0|OpenVox|OpenVox WebUI|"));
+        assert!(event.contains(r"|ca\|sign|ca\|sign|3|rt=1767323045000 "));
+        assert!(event.contains("src=10.0.0.5"));
+        assert!(event.contains("cs6Label=userKind cs6=service_account"));
+        assert!(event.contains(r#"msg={"filter":"a\=b"}"#));
+    }
+
+    #[test]
+    fn test_collector_bodies() {
+        let entries = vec![entry(), entry()];
+
+        let hec = splunk_hec_body(&entries, "web01", Some("audit"));
+        let events: Vec<serde_json::Value> = hec
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0]["index"], "audit");
+        assert_eq!(events[0]["sourcetype"], "openvox:audit");
+        assert_eq!(events[0]["event"]["action"], "group.update");
+
+        let bulk = elastic_bulk_body(&entries, "openvox-audit");
+        let lines: Vec<serde_json::Value> = bulk
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0]["index"]["_index"], "openvox-audit");
+        assert_eq!(lines[1]["@timestamp"], "2026-01-02T03:04:05+00:00");
+    }
+}
@@ -0,0 +1,195 @@
+//! Audit log retention
+//!
+//! With `audit.retention_days` set, a background task deletes older entries
+//! once an hour. With `audit.archive_dir` set as well, each run first writes
+//! the expired entries to a JSON Lines file in that directory and deletes
+//! only what was written, so nothing is lost if archiving fails.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::RwLock;
+use tokio::time::interval;
+use tracing::{error, info};
+
+use crate::config::AuditConfig;
+use crate::db::{AuditRepository, DbPool};
+use crate::models::default_organization_uuid;
+use crate::services::audit_forwarding::AuditForwarder;
+
+/// How often expired entries are pruned
+const PRUNE_INTERVAL_SECS: u64 = 3600;
+
+/// Entries archived and deleted per batch
+const PRUNE_BATCH_SIZE: u32 = 1000;
+
+/// Outcome of a pruning run
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PruneResult {
+    pub deleted: u64,
+    pub archive_file: Option<PathBuf>,
+}
+
+/// Handle for stopping the retention task
+#[derive(Clone)]
+pub struct AuditRetentionState {
+    running: Arc<RwLock<bool>>,
+    pool: DbPool,
+    retention_days: u32,
+    archive_dir: Option<PathBuf>,
+    forwarder: Option<Arc<AuditForwarder>>,
+}
+
+impl AuditRetentionState {
+    pub async fn is_running(&self) -> bool {
+        *self.running.read().await
+    }
+
+    /// Request the retention loop to stop at its next tick
+    pub async fn stop(&self) {
+        *self.running.write().await = false;
+        info!("Audit log retention stop requested");
+    }
+}
+
+/// Spawn the background retention task if a retention period is configured
+///
+/// Each run is audited itself, and forwarded through `forwarder`.
+pub fn start_audit_retention(
+    pool: DbPool,
+    config: &AuditConfig,
+    forwarder: Option<Arc<AuditForwarder>>,
+) -> Option<AuditRetentionState> {
+    let retention_days = config.retention_days?;
+    let state = AuditRetentionState {
+        running: Arc::new(RwLock::new(true)),
+        pool,
+        retention_days,
+        archive_dir: config.archive_dir.clone(),
+        forwarder,
+    };
+
+    let loop_state = state.clone();
+    tokio::spawn(async move {
+        retention_loop(loop_state).await;
+    });
+
+    info!(
+        "Audit log retention started (keeping {} days, archive: {})",
+        retention_days,
+        state
+            .archive_dir
+            .as_ref()
+            .map(|d| d.display().to_string())
+            .unwrap_or_else(|| "none".to_string())
+    );
+    Some(state)
+}
+
+async fn retention_loop(state: AuditRetentionState) {
+    let mut timer = interval(Duration::from_secs(PRUNE_INTERVAL_SECS));
+
+    loop {
+        timer.tick().await;
+
+        if !*state.running.read().await {
+            info!("Audit log retention stopping");
+            break;
+        }
+
+        let cutoff = Utc::now() - chrono::Duration::days(state.retention_days as i64);
+        match prune_audit_logs(&state.pool, cutoff, state.archive_dir.as_deref()).await {
+            Ok(result) if result.deleted > 0 => {
+                info!("Pruned {} audit log entries", result.deleted);
+                let _ = AuditRepository::new(&state.pool, state.forwarder.as_deref())
+                    .insert(
+                        default_organization_uuid(),
+                        None,
+                        "audit_log.prune",
+                        "audit_log",
+                        None,
+                        Some(&serde_json::json!({
+                            "deleted": result.deleted,
+                            "cutoff": cutoff,
+                            "archive_file": result.archive_file,
+                        })),
+                        None,
+                    )
+                    .await;
+            }
+            Ok(_) => {}
+            Err(e) => error!("Audit log pruning failed: {:#}", e),
+        }
+    }
+}
+
+/// Delete entries created before `cutoff`, archiving them first if
+/// `archive_dir` is set
+pub async fn prune_audit_logs(
+    pool: &DbPool,
+    cutoff: DateTime<Utc>,
+    archive_dir: Option<&Path>,
+) -> Result<PruneResult> {
+    let repo = AuditRepository::new(pool, None);
+    let Some(archive_dir) = archive_dir else {
+        return Ok(PruneResult {
+            deleted: repo.delete_before(cutoff).await?,
+            archive_file: None,
+        });
+    };
+
+    let mut result = PruneResult::default();
+    let mut file = None;
+    loop {
+        let entries = repo.list_before(cutoff, PRUNE_BATCH_SIZE).await?;
+        if entries.is_empty() {
+            break;
+        }
+
+        if file.is_none() {
+            tokio::fs::create_dir_all(archive_dir)
+                .await
+                .with_context(|| format!("Failed to create {}", archive_dir.display()))?;
+            let path = archive_dir.join(format!(
+                "audit-{}.jsonl",
+                Utc::now().format("%Y%m%dT%H%M%SZ")
+            ));
+            let handle = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .await
+                .with_context(|| format!("Failed to open {}", path.display()))?;
+            result.archive_file = Some(path);
+            file = Some(handle);
+        }
+        let handle = file.as_mut().expect("archive file is open");
+
+        let mut lines = String::new();
+        for entry in &entries {
+            lines.push_str(&serde_json::to_string(entry)?);
+            lines.push('\n');
+        }
+        handle
+            .write_all(lines.as_bytes())
+            .await
+            .context("Failed to write audit archive")?;
+        handle
+            .sync_data()
+            .await
+            .context("Failed to flush audit archive")?;
+
+        let ids: Vec<_> = entries.iter().map(|entry| entry.id).collect();
+        let deleted = repo.delete_by_ids(&ids).await?;
+        if deleted == 0 {
+            anyhow::bail!("Archived audit log entries could not be deleted");
+        }
+        result.deleted += deleted;
+    }
+
+    Ok(result)
+}
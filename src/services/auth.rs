@@ -10,7 +10,13 @@ use argon2::{
 use sqlx::{Row, SqlitePool};
 use uuid::Uuid;
 
-use crate::models::{default_organization_uuid, AuthProvider, User, UserPublic};
+use crate::models::{default_organization_uuid, AuthProvider, User, UserKind, UserPublic};
+
+/// How long a password reset token stays valid
+pub const PASSWORD_RESET_TOKEN_TTL_MINUTES: i64 = 60;
+
+/// Minimum time between two password reset tokens for the same user
+pub const PASSWORD_RESET_COOLDOWN_MINUTES: i64 = 5;
 
 /// Authentication service for user management
 pub struct AuthService {
@@ -43,11 +49,20 @@ impl AuthService {
             .is_ok())
     }
 
+    /// Whether `password_hash` is a PHC-format Argon2 hash this service can verify
+    pub fn is_supported_password_hash(password_hash: &str) -> bool {
+        PasswordHash::new(password_hash)
+            .map(|hash| hash.algorithm.as_str().starts_with("argon2"))
+            .unwrap_or(false)
+    }
+
     /// Authenticate a user by username and password
     pub async fn authenticate(&self, username: &str, password: &str) -> Result<Option<User>> {
         let user = self.get_user_by_username(username).await?;
 
         match user {
+            // Service accounts authenticate with API keys only
+            Some(user) if user.is_service_account() => Ok(None),
             Some(user) => {
                 if Self::verify_password(password, &user.password_hash)? {
                     Ok(Some(user))
@@ -62,7 +77,7 @@ impl AuthService {
     /// Get a user by username
     pub async fn get_user_by_username(&self, username: &str) -> Result<Option<User>> {
         let row = sqlx::query(
-            "SELECT id, organization_id, username, email, password_hash, role, force_password_change, auth_provider, external_id, idp_entity_id, last_saml_auth_at, created_at, updated_at FROM users WHERE username = ?"
+            "SELECT id, organization_id, username, email, password_hash, role, force_password_change, auth_provider, external_id, idp_entity_id, last_saml_auth_at, kind, created_at, updated_at FROM users WHERE username = ? AND deleted_at IS NULL"
         )
         .bind(username)
         .fetch_optional(&self.pool)
@@ -76,7 +91,7 @@ impl AuthService {
     pub async fn get_user_by_id(&self, id: &Uuid) -> Result<Option<User>> {
         let id_str = id.to_string();
         let row = sqlx::query(
-            "SELECT id, organization_id, username, email, password_hash, role, force_password_change, auth_provider, external_id, idp_entity_id, last_saml_auth_at, created_at, updated_at FROM users WHERE id = ?"
+            "SELECT id, organization_id, username, email, password_hash, role, force_password_change, auth_provider, external_id, idp_entity_id, last_saml_auth_at, kind, created_at, updated_at FROM users WHERE id = ? AND deleted_at IS NULL"
         )
         .bind(&id_str)
         .fetch_optional(&self.pool)
@@ -89,7 +104,7 @@ impl AuthService {
     /// Get a user by email
     pub async fn get_user_by_email(&self, email: &str) -> Result<Option<User>> {
         let row = sqlx::query(
-            "SELECT id, organization_id, username, email, password_hash, role, force_password_change, auth_provider, external_id, idp_entity_id, last_saml_auth_at, created_at, updated_at FROM users WHERE email = ?"
+            "SELECT id, organization_id, username, email, password_hash, role, force_password_change, auth_provider, external_id, idp_entity_id, last_saml_auth_at, kind, created_at, updated_at FROM users WHERE email = ? AND deleted_at IS NULL"
         )
         .bind(email)
         .fetch_optional(&self.pool)
@@ -142,15 +157,7 @@ impl AuthService {
         auth_provider: AuthProvider,
         external_id: Option<&str>,
     ) -> Result<User> {
-        // Check if username already exists
-        if self.get_user_by_username(username).await?.is_some() {
-            anyhow::bail!("Username already exists");
-        }
-
-        // Check if email already exists
-        if self.get_user_by_email(email).await?.is_some() {
-            anyhow::bail!("Email already exists");
-        }
+        self.check_available(username, email).await?;
 
         // Hash password if provided, otherwise use a placeholder for SAML-only users
         let password_hash = match password {
@@ -171,10 +178,109 @@ impl AuthService {
             external_id: external_id.map(|s| s.to_string()),
             idp_entity_id: None,
             last_saml_auth_at: None,
+            kind: UserKind::Human,
+            created_at: now,
+            updated_at: now,
+        };
+
+        self.insert_user(&user, password.is_some()).await?;
+        Ok(user)
+    }
+
+    /// Create a local user from an existing Argon2 password hash, e.g. when
+    /// importing users from another system
+    pub async fn create_user_with_password_hash(
+        &self,
+        username: &str,
+        email: &str,
+        password_hash: &str,
+        role: &str,
+        organization_id: Uuid,
+        force_password_change: bool,
+    ) -> Result<User> {
+        if !Self::is_supported_password_hash(password_hash) {
+            anyhow::bail!("Password hash must be a PHC-format Argon2 hash");
+        }
+        self.check_available(username, email).await?;
+
+        let now = chrono::Utc::now();
+        let user = User {
+            id: Uuid::new_v4(),
+            organization_id,
+            username: username.to_string(),
+            email: email.to_string(),
+            password_hash: password_hash.to_string(),
+            role: role.to_string(),
+            force_password_change,
+            auth_provider: AuthProvider::Local,
+            external_id: None,
+            idp_entity_id: None,
+            last_saml_auth_at: None,
+            kind: UserKind::Human,
+            created_at: now,
+            updated_at: now,
+        };
+
+        self.insert_user(&user, true).await?;
+        Ok(user)
+    }
+
+    /// Create a service account, which has no password and authenticates
+    /// with API keys only
+    pub async fn create_service_account(
+        &self,
+        username: &str,
+        email: &str,
+        role: &str,
+        organization_id: Uuid,
+    ) -> Result<User> {
+        self.check_available(username, email).await?;
+
+        let now = chrono::Utc::now();
+        let user = User {
+            id: Uuid::new_v4(),
+            organization_id,
+            username: username.to_string(),
+            email: email.to_string(),
+            password_hash: "!SERVICE_ACCOUNT!".to_string(), // Can never match a real hash
+            role: role.to_string(),
+            force_password_change: false,
+            auth_provider: AuthProvider::Local,
+            external_id: None,
+            idp_entity_id: None,
+            last_saml_auth_at: None,
+            kind: UserKind::ServiceAccount,
             created_at: now,
             updated_at: now,
         };
 
+        self.insert_user(&user, false).await?;
+        Ok(user)
+    }
+
+    /// Fail when the username or email is taken, including in the recycle bin
+    pub async fn check_available(&self, username: &str, email: &str) -> Result<()> {
+        // Check if username already exists
+        if self.get_user_by_username(username).await?.is_some() {
+            anyhow::bail!("Username already exists");
+        }
+
+        // Check if email already exists
+        if self.get_user_by_email(email).await?.is_some() {
+            anyhow::bail!("Email already exists");
+        }
+
+        if self
+            .held_by_deleted_user(Some(username), Some(email))
+            .await?
+        {
+            anyhow::bail!("Username or email already exists on a deleted user in the recycle bin");
+        }
+        Ok(())
+    }
+
+    /// Insert a new user row, remembering its password in the history
+    async fn insert_user(&self, user: &User, record_password: bool) -> Result<()> {
         let id_str = user.id.to_string();
         let org_id_str = user.organization_id.to_string();
         let created_at = user.created_at.to_rfc3339();
@@ -182,7 +288,7 @@ impl AuthService {
         let auth_provider_str = user.auth_provider.to_string();
 
         sqlx::query(
-            "INSERT INTO users (id, organization_id, username, email, password_hash, role, auth_provider, external_id, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+            "INSERT INTO users (id, organization_id, username, email, password_hash, role, force_password_change, auth_provider, external_id, kind, created_at, updated_at) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
         )
         .bind(&id_str)
         .bind(&org_id_str)
@@ -190,15 +296,21 @@ impl AuthService {
         .bind(&user.email)
         .bind(&user.password_hash)
         .bind(&user.role)
+        .bind(user.force_password_change)
         .bind(&auth_provider_str)
         .bind(&user.external_id)
+        .bind(user.kind.to_string())
         .bind(&created_at)
         .bind(&updated_at)
         .execute(&self.pool)
         .await
         .context("Failed to create user")?;
 
-        Ok(user)
+        if record_password {
+            self.record_password(&user.id, &user.password_hash).await?;
+        }
+
+        Ok(())
     }
 
     /// Update a user
@@ -261,6 +373,16 @@ impl AuthService {
             anyhow::bail!("Email already exists");
         }
 
+        if self
+            .held_by_deleted_user(
+                (new_username != existing.username).then_some(new_username),
+                (new_email != existing.email).then_some(new_email),
+            )
+            .await?
+        {
+            anyhow::bail!("Username or email already exists on a deleted user in the recycle bin");
+        }
+
         let new_password_hash = match password {
             Some(p) => Self::hash_password(p)?,
             None => existing.password_hash.clone(),
@@ -285,27 +407,59 @@ impl AuthService {
         .await
         .context("Failed to update user")?;
 
+        if password.is_some() {
+            self.record_password(id, &new_password_hash).await?;
+        }
+
         self.get_user_by_id(id)
             .await?
             .context("User not found after update")
     }
 
-    /// Delete a user
+    /// Move a user to the recycle bin
+    ///
+    /// The user can no longer sign in; their roles, API keys and other owned
+    /// records stay until the user is purged.
     pub async fn delete_user(&self, id: &Uuid) -> Result<bool> {
         let id_str = id.to_string();
-        let result = sqlx::query("DELETE FROM users WHERE id = ?")
-            .bind(&id_str)
-            .execute(&self.pool)
-            .await
-            .context("Failed to delete user")?;
+        let now = chrono::Utc::now().to_rfc3339();
+        let result = sqlx::query(
+            "UPDATE users SET deleted_at = ?, updated_at = ? WHERE id = ? AND deleted_at IS NULL",
+        )
+        .bind(&now)
+        .bind(&now)
+        .bind(&id_str)
+        .execute(&self.pool)
+        .await
+        .context("Failed to delete user")?;
 
         Ok(result.rows_affected() > 0)
     }
 
+    /// Whether a user in the recycle bin still holds a username or email
+    ///
+    /// Deleted users keep their unique values so they can be restored.
+    async fn held_by_deleted_user(
+        &self,
+        username: Option<&str>,
+        email: Option<&str>,
+    ) -> Result<bool> {
+        let held: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM users WHERE (username = ? OR email = ?) AND deleted_at IS NOT NULL",
+        )
+        .bind(username)
+        .bind(email)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to check deleted users")?;
+
+        Ok(held > 0)
+    }
+
     /// List all users
     pub async fn list_users(&self) -> Result<Vec<UserPublic>> {
         let rows = sqlx::query(
-            "SELECT id, organization_id, username, email, password_hash, role, force_password_change, auth_provider, external_id, idp_entity_id, last_saml_auth_at, created_at, updated_at FROM users ORDER BY username"
+            "SELECT id, organization_id, username, email, password_hash, role, force_password_change, auth_provider, external_id, idp_entity_id, last_saml_auth_at, kind, created_at, updated_at FROM users WHERE deleted_at IS NULL ORDER BY username"
         )
         .fetch_all(&self.pool)
         .await
@@ -316,7 +470,7 @@ impl AuthService {
 
     pub async fn list_users_in_org(&self, organization_id: Uuid) -> Result<Vec<UserPublic>> {
         let rows = sqlx::query(
-            "SELECT id, organization_id, username, email, password_hash, role, force_password_change, auth_provider, external_id, idp_entity_id, last_saml_auth_at, created_at, updated_at FROM users WHERE organization_id = ? ORDER BY username",
+            "SELECT id, organization_id, username, email, password_hash, role, force_password_change, auth_provider, external_id, idp_entity_id, last_saml_auth_at, kind, created_at, updated_at FROM users WHERE organization_id = ? AND deleted_at IS NULL ORDER BY username",
         )
         .bind(organization_id.to_string())
         .fetch_all(&self.pool)
@@ -332,7 +486,7 @@ impl AuthService {
         id: &Uuid,
     ) -> Result<Option<User>> {
         let row = sqlx::query(
-            "SELECT id, organization_id, username, email, password_hash, role, force_password_change, auth_provider, external_id, idp_entity_id, last_saml_auth_at, created_at, updated_at FROM users WHERE organization_id = ? AND id = ?",
+            "SELECT id, organization_id, username, email, password_hash, role, force_password_change, auth_provider, external_id, idp_entity_id, last_saml_auth_at, kind, created_at, updated_at FROM users WHERE organization_id = ? AND id = ? AND deleted_at IS NULL",
         )
         .bind(organization_id.to_string())
         .bind(id.to_string())
@@ -391,44 +545,61 @@ impl AuthService {
 
     /// Create a password reset token for a user
     ///
-    /// Returns the reset token if the email exists, None otherwise.
-    /// The token is valid for 1 hour.
-    pub async fn create_password_reset_token(&self, email: &str) -> Result<Option<String>> {
-        let user = self.get_user_by_email(email).await?;
+    /// Replaces any earlier token of the user. Returns None without creating
+    /// a token if the last one was issued less than
+    /// [`PASSWORD_RESET_COOLDOWN_MINUTES`] ago, so a mailbox cannot be
+    /// flooded with reset emails. The token is valid for
+    /// [`PASSWORD_RESET_TOKEN_TTL_MINUTES`].
+    pub async fn create_password_reset_token(&self, user: &User) -> Result<Option<String>> {
+        use rand::Rng;
 
-        match user {
-            Some(user) => {
-                let token = Uuid::new_v4().to_string();
-                let token_hash = Self::hash_reset_token(&token);
-                let user_id_str = user.id.to_string();
-                let expires_at = (chrono::Utc::now() + chrono::Duration::hours(1)).to_rfc3339();
-                let created_at = chrono::Utc::now().to_rfc3339();
-
-                // Delete any existing reset tokens for this user
-                sqlx::query("DELETE FROM password_reset_tokens WHERE user_id = ?")
-                    .bind(&user_id_str)
-                    .execute(&self.pool)
-                    .await
-                    .context("Failed to delete existing reset tokens")?;
-
-                // Insert new reset token
-                let id = Uuid::new_v4().to_string();
-                sqlx::query(
-                    "INSERT INTO password_reset_tokens (id, user_id, token_hash, expires_at, created_at) VALUES (?, ?, ?, ?, ?)"
-                )
-                .bind(&id)
-                .bind(&user_id_str)
-                .bind(&token_hash)
-                .bind(&expires_at)
-                .bind(&created_at)
-                .execute(&self.pool)
-                .await
-                .context("Failed to create reset token")?;
+        let now = chrono::Utc::now();
+        let user_id_str = user.id.to_string();
 
-                Ok(Some(token))
+        let last_issued: Option<String> = sqlx::query_scalar(
+            "SELECT MAX(created_at) FROM password_reset_tokens WHERE user_id = ?",
+        )
+        .bind(&user_id_str)
+        .fetch_one(&self.pool)
+        .await
+        .context("Failed to check existing reset tokens")?;
+        if let Some(last_issued) = last_issued {
+            let cooldown = chrono::Duration::minutes(PASSWORD_RESET_COOLDOWN_MINUTES);
+            if parse_db_timestamp(&last_issued) + cooldown > now {
+                return Ok(None);
             }
-            None => Ok(None),
         }
+
+        let mut token_bytes = [0u8; 32];
+        rand::rng().fill_bytes(&mut token_bytes);
+        let token = hex::encode(token_bytes);
+        let token_hash = Self::hash_reset_token(&token);
+        let expires_at =
+            (now + chrono::Duration::minutes(PASSWORD_RESET_TOKEN_TTL_MINUTES)).to_rfc3339();
+        let created_at = now.to_rfc3339();
+
+        // Delete any existing reset tokens for this user
+        sqlx::query("DELETE FROM password_reset_tokens WHERE user_id = ?")
+            .bind(&user_id_str)
+            .execute(&self.pool)
+            .await
+            .context("Failed to delete existing reset tokens")?;
+
+        // Insert new reset token
+        let id = Uuid::new_v4().to_string();
+        sqlx::query(
+            "INSERT INTO password_reset_tokens (id, user_id, token_hash, expires_at, created_at) VALUES (?, ?, ?, ?, ?)"
+        )
+        .bind(&id)
+        .bind(&user_id_str)
+        .bind(&token_hash)
+        .bind(&expires_at)
+        .bind(&created_at)
+        .execute(&self.pool)
+        .await
+        .context("Failed to create reset token")?;
+
+        Ok(Some(token))
     }
 
     /// Validate a password reset token and return the associated user ID
@@ -458,6 +629,8 @@ impl AuthService {
     }
 
     /// Reset a user's password using a valid reset token
+    ///
+    /// This also clears the force_password_change flag.
     pub async fn reset_password(&self, token: &str, new_password: &str) -> Result<bool> {
         let user_id = self.validate_reset_token(token).await?;
 
@@ -468,13 +641,16 @@ impl AuthService {
                 let user_id_str = user_id.to_string();
                 let updated_at = chrono::Utc::now().to_rfc3339();
 
-                sqlx::query("UPDATE users SET password_hash = ?, updated_at = ? WHERE id = ?")
-                    .bind(&new_password_hash)
-                    .bind(&updated_at)
-                    .bind(&user_id_str)
-                    .execute(&self.pool)
-                    .await
-                    .context("Failed to update password")?;
+                sqlx::query(
+                    "UPDATE users SET password_hash = ?, force_password_change = 0, updated_at = ? WHERE id = ?",
+                )
+                .bind(&new_password_hash)
+                .bind(&updated_at)
+                .bind(&user_id_str)
+                .execute(&self.pool)
+                .await
+                .context("Failed to update password")?;
+                self.record_password(&user_id, &new_password_hash).await?;
 
                 // Delete the used token
                 let token_hash = Self::hash_reset_token(token);
@@ -536,10 +712,107 @@ impl AuthService {
         .execute(&self.pool)
         .await
         .context("Failed to update password")?;
+        self.record_password(user_id, &new_password_hash).await?;
 
         Ok(true)
     }
 
+    /// Remember a newly set password for history checks and max-age
+    /// enforcement
+    async fn record_password(&self, user_id: &Uuid, password_hash: &str) -> Result<()> {
+        let user_id_str = user_id.to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        sqlx::query(
+            "INSERT INTO password_history (id, user_id, password_hash, created_at) VALUES (?, ?, ?, ?)",
+        )
+        .bind(Uuid::new_v4().to_string())
+        .bind(&user_id_str)
+        .bind(password_hash)
+        .bind(&now)
+        .execute(&self.pool)
+        .await
+        .context("Failed to record password history")?;
+
+        // Keep only as many entries as the longest allowed history
+        sqlx::query(
+            r#"
+            DELETE FROM password_history
+            WHERE user_id = ?1 AND id NOT IN (
+                SELECT id FROM password_history WHERE user_id = ?1
+                ORDER BY created_at DESC LIMIT ?2
+            )
+            "#,
+        )
+        .bind(&user_id_str)
+        .bind(crate::services::password_policy::MAX_PASSWORD_HISTORY as i64)
+        .execute(&self.pool)
+        .await
+        .context("Failed to prune password history")?;
+
+        sqlx::query("UPDATE users SET password_changed_at = ? WHERE id = ?")
+            .bind(&now)
+            .bind(&user_id_str)
+            .execute(&self.pool)
+            .await
+            .context("Failed to record password change time")?;
+
+        Ok(())
+    }
+
+    /// Hashes of the user's most recent passwords, newest first
+    ///
+    /// Includes the current password even for users whose history predates
+    /// password history tracking.
+    pub async fn recent_password_hashes(&self, user_id: &Uuid, limit: u32) -> Result<Vec<String>> {
+        let user_id_str = user_id.to_string();
+        let mut hashes: Vec<String> = sqlx::query_scalar(
+            "SELECT password_hash FROM password_history WHERE user_id = ? ORDER BY created_at DESC LIMIT ?",
+        )
+        .bind(&user_id_str)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch password history")?;
+
+        let current: Option<String> =
+            sqlx::query_scalar("SELECT password_hash FROM users WHERE id = ?")
+                .bind(&user_id_str)
+                .fetch_optional(&self.pool)
+                .await
+                .context("Failed to fetch current password")?;
+        if let Some(current) = current {
+            if !hashes.contains(&current) {
+                hashes.insert(0, current);
+            }
+        }
+
+        Ok(hashes)
+    }
+
+    /// When the user's password was last set
+    pub async fn password_changed_at(
+        &self,
+        user_id: &Uuid,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>> {
+        let changed_at: Option<Option<String>> =
+            sqlx::query_scalar("SELECT password_changed_at FROM users WHERE id = ?")
+                .bind(user_id.to_string())
+                .fetch_optional(&self.pool)
+                .await
+                .context("Failed to fetch password change time")?;
+
+        Ok(changed_at.flatten().and_then(|ts| {
+            chrono::DateTime::parse_from_rfc3339(&ts)
+                .map(|dt| dt.with_timezone(&chrono::Utc))
+                .or_else(|_| {
+                    chrono::NaiveDateTime::parse_from_str(&ts, "%Y-%m-%d %H:%M:%S")
+                        .map(|dt| dt.and_utc())
+                })
+                .ok()
+        }))
+    }
+
     /// Clear the force_password_change flag for a user
     pub async fn clear_force_password_change(&self, user_id: &Uuid) -> Result<()> {
         let user_id_str = user_id.to_string();
@@ -600,7 +873,7 @@ impl AuthService {
     /// Get a user by external ID (SAML NameID)
     pub async fn get_user_by_external_id(&self, external_id: &str) -> Result<Option<User>> {
         let row = sqlx::query(
-            "SELECT id, organization_id, username, email, password_hash, role, force_password_change, auth_provider, external_id, idp_entity_id, last_saml_auth_at, created_at, updated_at FROM users WHERE external_id = ?"
+            "SELECT id, organization_id, username, email, password_hash, role, force_password_change, auth_provider, external_id, idp_entity_id, last_saml_auth_at, kind, created_at, updated_at FROM users WHERE external_id = ? AND deleted_at IS NULL"
         )
         .bind(external_id)
         .fetch_optional(&self.pool)
@@ -612,12 +885,9 @@ impl AuthService {
 
     /// Hash a reset token using SHA-256 for storage
     fn hash_reset_token(token: &str) -> String {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
+        use sha2::{Digest, Sha256};
 
-        let mut hasher = DefaultHasher::new();
-        token.hash(&mut hasher);
-        format!("{:x}", hasher.finish())
+        hex::encode(Sha256::digest(token.as_bytes()))
     }
 }
 
@@ -667,6 +937,12 @@ fn row_to_user(row: &sqlx::sqlite::SqliteRow) -> User {
         .ok()
         .map(|s| parse_db_timestamp(&s));
 
+    let kind: UserKind = row
+        .try_get::<String, _>("kind")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or_default();
+
     User {
         id: Uuid::parse_str(&id_str).unwrap_or_else(|_| Uuid::nil()),
         organization_id: Uuid::parse_str(&org_id_str)
@@ -680,6 +956,7 @@ fn row_to_user(row: &sqlx::sqlite::SqliteRow) -> User {
         external_id,
         idp_entity_id,
         last_saml_auth_at,
+        kind,
         created_at: parse_db_timestamp(&created_at_str),
         updated_at: parse_db_timestamp(&updated_at_str),
     }
@@ -2,6 +2,11 @@
 //!
 //! Main orchestration service for server backup and restore operations.
 //! Handles backup creation, encryption, restoration, and cleanup.
+//!
+//! The database is copied online with `VACUUM INTO`, which gives a
+//! consistent snapshot without stopping the server. A restore cannot replace
+//! the database while it is open, so it stages the restored file next to it;
+//! [`apply_staged_restore`] swaps it in on the next start.
 
 use std::fs::{self, File};
 use std::io::Read;
@@ -26,6 +31,14 @@ use crate::models::{
     BackupRestore, BackupSchedule, BackupStatus, BackupTrigger, ServerBackup, VerifyBackupResponse,
 };
 use crate::services::backup_encryption::{self, EncryptedData};
+use crate::services::report_delivery::ReportDelivery;
+use crate::services::secrets::SecretsResolver;
+
+/// Suffix of database files staged by a restore
+const STAGED_RESTORE_SUFFIX: &str = ".restore";
+
+/// Suffix the database files replaced by a restore are kept with
+const PRE_RESTORE_SUFFIX: &str = ".pre-restore";
 
 /// Backup service for managing server backups
 pub struct BackupService {
@@ -33,6 +46,8 @@ pub struct BackupService {
     config: BackupConfig,
     /// Lock to ensure only one backup/restore runs at a time
     operation_lock: Arc<Mutex<()>>,
+    /// Resolves upload target credentials written as secret references
+    secrets: Option<Arc<SecretsResolver>>,
 }
 
 impl BackupService {
@@ -42,9 +57,16 @@ impl BackupService {
             pool,
             config,
             operation_lock: Arc::new(Mutex::new(())),
+            secrets: None,
         }
     }
 
+    /// Resolve upload target credentials through a secrets provider
+    pub fn with_secrets(mut self, secrets: Option<Arc<SecretsResolver>>) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
     /// Check if backup feature is enabled
     pub fn is_enabled(&self) -> bool {
         self.config.enabled
@@ -153,8 +175,23 @@ impl BackupService {
         let repo = BackupRepository::new(self.pool.clone());
         repo.create_backup(&backup).await?;
 
+        // Take a consistent copy of the live database
+        let snapshot = if include_database && self.config.include.database {
+            match self.snapshot_database(backup_id).await {
+                Ok(path) => Some(path),
+                Err(e) => {
+                    let msg = format!("Failed to snapshot the database: {:#}", e);
+                    error!("{}", msg);
+                    repo.fail_backup(backup_id, &msg).await?;
+                    return Err(e);
+                }
+            }
+        } else {
+            None
+        };
+
         // Collect files to backup
-        let files_to_backup = self.collect_backup_files(include_database, include_config)?;
+        let files_to_backup = self.collect_backup_files(snapshot.as_deref(), include_config);
 
         if files_to_backup.is_empty() {
             repo.fail_backup(backup_id, "No files to backup").await?;
@@ -164,7 +201,11 @@ impl BackupService {
         info!("Creating backup with {} files", files_to_backup.len());
 
         // Create tar.gz archive
-        match self.create_archive(&files_to_backup) {
+        let archive = self.create_archive(&files_to_backup);
+        if let Some(ref snapshot) = snapshot {
+            fs::remove_file(snapshot).ok();
+        }
+        match archive {
             Ok((archive_data, uncompressed_size)) => {
                 backup.uncompressed_size = Some(uncompressed_size as i64);
 
@@ -219,6 +260,19 @@ impl BackupService {
                     filename, file_size
                 );
 
+                // Copy to the configured destinations; failures are logged
+                // and leave the local backup in place
+                if !self.config.upload_targets.is_empty() {
+                    ReportDelivery::new(self.secrets.clone())
+                        .deliver_all(
+                            &self.config.upload_targets,
+                            &filename,
+                            "application/gzip",
+                            &final_data,
+                        )
+                        .await;
+                }
+
                 Ok(backup)
             }
             Err(e) => {
@@ -233,29 +287,13 @@ impl BackupService {
     /// Collect files to include in the backup
     fn collect_backup_files(
         &self,
-        include_database: bool,
+        database_snapshot: Option<&Path>,
         include_config: bool,
-    ) -> Result<Vec<(PathBuf, String)>> {
+    ) -> Vec<(PathBuf, String)> {
         let mut files = Vec::new();
 
-        if include_database && self.config.include.database {
-            // Database files - extract path from sqlite URL
-            if let Some(db_path) = self.get_database_path() {
-                // Main database file
-                if db_path.exists() {
-                    files.push((db_path.clone(), "database/openvox.db".to_string()));
-                }
-                // WAL file
-                let wal_path = PathBuf::from(format!("{}-wal", db_path.display()));
-                if wal_path.exists() {
-                    files.push((wal_path, "database/openvox.db-wal".to_string()));
-                }
-                // SHM file
-                let shm_path = PathBuf::from(format!("{}-shm", db_path.display()));
-                if shm_path.exists() {
-                    files.push((shm_path, "database/openvox.db-shm".to_string()));
-                }
-            }
+        if let Some(snapshot) = database_snapshot {
+            files.push((snapshot.to_path_buf(), "database/openvox.db".to_string()));
         }
 
         if include_config && self.config.include.config_files {
@@ -274,29 +312,33 @@ impl BackupService {
             }
         }
 
-        Ok(files)
+        files
     }
 
-    /// Get the database path from environment or default
-    fn get_database_path(&self) -> Option<PathBuf> {
-        // Try environment variable first
-        if let Ok(url) = std::env::var("DATABASE_URL") {
-            return extract_path_from_sqlite_url(&url);
-        }
-
-        // Default path
-        let default_path = PathBuf::from("/var/lib/openvox-webui/openvox.db");
-        if default_path.exists() {
-            return Some(default_path);
-        }
-
-        // Development fallback
-        let dev_path = PathBuf::from("openvox.db");
-        if dev_path.exists() {
-            return Some(dev_path);
-        }
+    /// Copy the live database into the backup directory with `VACUUM INTO`
+    ///
+    /// The copy is a consistent snapshot, taken without blocking readers.
+    async fn snapshot_database(&self, backup_id: Uuid) -> Result<PathBuf> {
+        let path = self
+            .config
+            .backup_dir
+            .join(format!(".snapshot_{}.db", backup_id));
+        sqlx::query("VACUUM INTO ?")
+            .bind(path.to_string_lossy().to_string())
+            .execute(&self.pool)
+            .await
+            .context("VACUUM INTO failed")?;
+        Ok(path)
+    }
 
-        None
+    /// Path of the database file the service is connected to
+    async fn live_database_path(&self) -> Result<Option<PathBuf>> {
+        let file: Option<String> =
+            sqlx::query_scalar("SELECT file FROM pragma_database_list WHERE name = 'main'")
+                .fetch_optional(&self.pool)
+                .await
+                .context("Failed to look up the database file")?;
+        Ok(file.filter(|f| !f.is_empty()).map(PathBuf::from))
     }
 
     /// Create a tar.gz archive from the given files
@@ -417,9 +459,14 @@ impl BackupService {
         };
 
         // Extract archive
+        let database_path = if backup.includes_database {
+            self.live_database_path().await?
+        } else {
+            None
+        };
         match self.extract_archive(
             &archive_data,
-            backup.includes_database,
+            database_path.as_deref(),
             backup.includes_config,
         ) {
             Ok(_) => {
@@ -446,12 +493,20 @@ impl BackupService {
     }
 
     /// Extract tar.gz archive to restore files
+    ///
+    /// The database is staged next to `database_path` and replaces it on the
+    /// next start; configuration files are written in place.
     fn extract_archive(
         &self,
         archive_data: &[u8],
-        includes_database: bool,
+        database_path: Option<&Path>,
         includes_config: bool,
     ) -> Result<()> {
+        if let Some(db_path) = database_path {
+            // A WAL staged by an earlier restore must not be applied to this one
+            fs::remove_file(staged_restore_path(db_path, "-wal")).ok();
+        }
+
         let decoder = GzDecoder::new(archive_data);
         let mut archive = Archive::new(decoder);
 
@@ -461,15 +516,19 @@ impl BackupService {
             let path_str = path.to_string_lossy();
 
             // Determine destination based on archive path
-            let dest = if path_str.starts_with("database/") && includes_database {
-                // Extract database files
-                if let Some(db_path) = self.get_database_path() {
-                    let db_dir = db_path.parent().unwrap_or(Path::new("."));
-                    let filename = path.file_name().unwrap();
-                    Some(db_dir.join(filename))
-                } else {
-                    warn!("Cannot determine database path for restore");
-                    None
+            let dest = if path_str.starts_with("database/") {
+                // Stage database files; backups taken before online
+                // snapshots also carry the WAL
+                match database_path {
+                    Some(db_path) if path_str.ends_with("-wal") => {
+                        Some(staged_restore_path(db_path, "-wal"))
+                    }
+                    Some(_) if path_str.ends_with("-shm") => None,
+                    Some(db_path) => Some(staged_restore_path(db_path, "")),
+                    None => {
+                        warn!("Cannot determine database path for restore");
+                        None
+                    }
                 }
             } else if path_str.starts_with("config/") && includes_config {
                 // Extract config files to /etc/openvox-webui/
@@ -487,8 +546,9 @@ impl BackupService {
                     fs::create_dir_all(parent)?;
                 }
 
-                // Create backup of existing file
-                if dest_path.exists() {
+                // Create backup of existing file; staged files are replaced
+                let staged = path_str.starts_with("database/");
+                if dest_path.exists() && !staged {
                     let backup_name = format!("{}.backup", dest_path.display());
                     fs::rename(&dest_path, &backup_name).ok();
                 }
@@ -798,13 +858,55 @@ fn calculate_sha256(data: &[u8]) -> String {
 }
 
 /// Extract file path from SQLite URL
-fn extract_path_from_sqlite_url(url: &str) -> Option<PathBuf> {
+pub fn extract_path_from_sqlite_url(url: &str) -> Option<PathBuf> {
     // Handle formats like "sqlite:///path/to/db.sqlite" or "sqlite:path/to/db.sqlite"
     url.strip_prefix("sqlite://")
         .or_else(|| url.strip_prefix("sqlite:"))
+        .and_then(|path| path.split('?').next())
+        .filter(|path| !path.is_empty() && *path != ":memory:")
         .map(PathBuf::from)
 }
 
+/// Path of a database file (`suffix` "" or "-wal") staged by a restore
+fn staged_restore_path(db_path: &Path, suffix: &str) -> PathBuf {
+    PathBuf::from(format!(
+        "{}{}{}",
+        db_path.display(),
+        STAGED_RESTORE_SUFFIX,
+        suffix
+    ))
+}
+
+/// Swap in a database staged by a restore
+///
+/// Must run before the database is opened. The replaced database and its
+/// WAL and shared-memory files are kept with a `.pre-restore` suffix.
+/// Returns whether a staged database was applied.
+pub fn apply_staged_restore(db_path: &Path) -> Result<bool> {
+    let staged = staged_restore_path(db_path, "");
+    if !staged.exists() {
+        return Ok(false);
+    }
+
+    for suffix in ["", "-wal", "-shm"] {
+        let current = PathBuf::from(format!("{}{}", db_path.display(), suffix));
+        if current.exists() {
+            let kept = format!("{}{}{}", db_path.display(), PRE_RESTORE_SUFFIX, suffix);
+            fs::rename(&current, &kept)
+                .with_context(|| format!("Failed to move {:?} aside", current))?;
+        }
+    }
+    fs::rename(&staged, db_path)
+        .with_context(|| format!("Failed to move {:?} into place", staged))?;
+    let staged_wal = staged_restore_path(db_path, "-wal");
+    if staged_wal.exists() {
+        fs::rename(&staged_wal, format!("{}-wal", db_path.display()))
+            .with_context(|| format!("Failed to move {:?} into place", staged_wal))?;
+    }
+
+    Ok(true)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -833,5 +935,77 @@ mod tests {
             extract_path_from_sqlite_url("postgres://localhost/db"),
             None
         );
+        assert_eq!(
+            extract_path_from_sqlite_url("sqlite:///var/lib/openvox/db.sqlite?mode=rwc"),
+            Some(PathBuf::from("/var/lib/openvox/db.sqlite"))
+        );
+        assert_eq!(extract_path_from_sqlite_url("sqlite::memory:"), None);
+    }
+
+    #[test]
+    fn test_apply_staged_restore() {
+        let dir = std::env::temp_dir().join(format!("openvox-restore-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("openvox.db");
+
+        assert!(!apply_staged_restore(&db_path).unwrap());
+
+        fs::write(&db_path, b"live").unwrap();
+        fs::write(dir.join("openvox.db-wal"), b"live wal").unwrap();
+        fs::write(staged_restore_path(&db_path, ""), b"restored").unwrap();
+
+        assert!(apply_staged_restore(&db_path).unwrap());
+        assert_eq!(fs::read(&db_path).unwrap(), b"restored");
+        assert!(!dir.join("openvox.db-wal").exists());
+        assert!(!staged_restore_path(&db_path, "").exists());
+        assert_eq!(
+            fs::read(dir.join("openvox.db.pre-restore")).unwrap(),
+            b"live"
+        );
+        assert_eq!(
+            fs::read(dir.join("openvox.db.pre-restore-wal")).unwrap(),
+            b"live wal"
+        );
+
+        fs::remove_dir_all(&dir).ok();
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_database() {
+        let dir = std::env::temp_dir().join(format!("openvox-snapshot-{}", Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE t (v TEXT)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO t VALUES ('kept')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let service = BackupService::new(
+            pool,
+            BackupConfig {
+                backup_dir: dir.clone(),
+                ..BackupConfig::default()
+            },
+        );
+        let snapshot = service.snapshot_database(Uuid::new_v4()).await.unwrap();
+
+        let copy = SqlitePool::connect(&format!("sqlite://{}", snapshot.display()))
+            .await
+            .unwrap();
+        let value: String = sqlx::query_scalar("SELECT v FROM t")
+            .fetch_one(&copy)
+            .await
+            .unwrap();
+        assert_eq!(value, "kept");
+
+        fs::remove_dir_all(&dir).ok();
     }
 }
@@ -14,9 +14,10 @@ use crate::config::{BackupConfig, BackupFrequency};
 use crate::db::{BackupRepository, DbPool};
 use crate::models::BackupTrigger;
 use crate::services::backup::BackupService;
+use crate::services::secrets::SecretsResolver;
 
 /// Scheduler state
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct BackupSchedulerState {
     /// Whether the scheduler is running
     running: Arc<RwLock<bool>>,
@@ -24,15 +25,18 @@ pub struct BackupSchedulerState {
     pool: DbPool,
     /// Backup configuration
     config: BackupConfig,
+    /// Resolves upload target credentials
+    secrets: Option<Arc<SecretsResolver>>,
 }
 
 impl BackupSchedulerState {
     /// Create a new scheduler state
-    pub fn new(pool: DbPool, config: BackupConfig) -> Self {
+    pub fn new(pool: DbPool, config: BackupConfig, secrets: Option<Arc<SecretsResolver>>) -> Self {
         Self {
             running: Arc::new(RwLock::new(false)),
             pool,
             config,
+            secrets,
         }
     }
 
@@ -54,8 +58,12 @@ impl BackupSchedulerState {
 /// This spawns background tasks for:
 /// - Creating scheduled backups
 /// - Cleaning up old backups based on retention policy
-pub fn start_backup_scheduler(pool: DbPool, config: BackupConfig) -> BackupSchedulerState {
-    let state = BackupSchedulerState::new(pool.clone(), config.clone());
+pub fn start_backup_scheduler(
+    pool: DbPool,
+    config: BackupConfig,
+    secrets: Option<Arc<SecretsResolver>>,
+) -> BackupSchedulerState {
+    let state = BackupSchedulerState::new(pool.clone(), config.clone(), secrets);
     let state_clone = state.clone();
 
     // Mark as running
@@ -175,7 +183,8 @@ async fn check_and_run_scheduled_backup(state: &BackupSchedulerState) -> anyhow:
     info!("Running scheduled backup");
 
     // Create the backup
-    let service = BackupService::new(state.pool.clone(), state.config.clone());
+    let service = BackupService::new(state.pool.clone(), state.config.clone())
+        .with_secrets(state.secrets.clone());
 
     // For scheduled backups, we don't use a password (or use a system key in the future)
     // The admin should configure encryption appropriately
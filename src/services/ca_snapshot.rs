@@ -0,0 +1,181 @@
+//! Offline snapshot of the Puppet CA
+//!
+//! Some sites only power the CA host on during signing windows. While the CA
+//! is reachable, the certificate and request lists, the CA bundle and the CRL
+//! are copied into the `ca_snapshots` table; the CA endpoints serve that copy
+//! when the CA cannot be reached.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::RwLock;
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+
+use crate::db::{CaSnapshotRepository, DbPool};
+use crate::models::{
+    Action, CaSnapshot, CertificateStatus, NewNotification, NotificationAudience, NotificationType,
+    Resource, WebhookEvent,
+};
+use crate::services::notification::NotificationService;
+use crate::services::puppet_ca::PuppetCAService;
+use crate::services::webhooks::Webhooks;
+use crate::utils::AppError;
+
+/// Handle for stopping the CA snapshot sync
+#[derive(Clone)]
+pub struct CaSnapshotSyncState {
+    running: Arc<RwLock<bool>>,
+    pool: DbPool,
+    ca: Arc<PuppetCAService>,
+    interval_secs: u64,
+    notifications: Arc<NotificationService>,
+    webhooks: Webhooks,
+}
+
+impl CaSnapshotSyncState {
+    pub async fn is_running(&self) -> bool {
+        *self.running.read().await
+    }
+
+    /// Request the sync loop to stop at its next tick
+    pub async fn stop(&self) {
+        *self.running.write().await = false;
+        info!("CA snapshot sync stop requested");
+    }
+}
+
+/// Spawn the periodic snapshot sync
+pub fn start_ca_snapshot_sync(
+    pool: DbPool,
+    ca: Arc<PuppetCAService>,
+    interval_secs: u64,
+    notifications: Arc<NotificationService>,
+    webhooks: Webhooks,
+) -> CaSnapshotSyncState {
+    let state = CaSnapshotSyncState {
+        running: Arc::new(RwLock::new(true)),
+        pool,
+        ca,
+        interval_secs,
+        notifications,
+        webhooks,
+    };
+
+    let loop_state = state.clone();
+    tokio::spawn(async move {
+        sync_loop(loop_state).await;
+    });
+
+    info!("CA snapshot sync started (interval: {}s)", interval_secs);
+    state
+}
+
+async fn sync_loop(state: CaSnapshotSyncState) {
+    // Clamp so a misconfiguration can't hammer the CA
+    let mut timer = interval(Duration::from_secs(state.interval_secs.max(60)));
+
+    loop {
+        timer.tick().await;
+
+        if !*state.running.read().await {
+            info!("CA snapshot sync stopping");
+            break;
+        }
+
+        if let Err(e) = sync_snapshot(
+            &state.pool,
+            &state.ca,
+            &state.notifications,
+            &state.webhooks,
+        )
+        .await
+        {
+            match e {
+                // Expected while the CA host is powered off
+                AppError::ServiceUnavailable(msg) => {
+                    debug!("CA unreachable, keeping previous snapshot: {}", msg)
+                }
+                e => error!("CA snapshot sync failed: {}", e),
+            }
+        }
+    }
+}
+
+/// Take a snapshot from the live CA and store it
+///
+/// Requests that were not in the previous snapshot are announced to webhooks
+/// as `ca.request_pending` and to the users allowed to sign them.
+pub async fn sync_snapshot(
+    pool: &DbPool,
+    ca: &PuppetCAService,
+    notifications: &NotificationService,
+    webhooks: &Webhooks,
+) -> Result<CaSnapshot, AppError> {
+    let snapshot = ca.take_snapshot().await?;
+    let previous = load_snapshot(pool).await;
+    CaSnapshotRepository::new(pool)
+        .save(&snapshot)
+        .await
+        .map_err(|e| AppError::internal(e.to_string()))?;
+
+    let known: HashSet<&str> = previous
+        .iter()
+        .flat_map(|p| p.requests.iter().map(|r| r.fingerprint.as_str()))
+        .collect();
+    for request in &snapshot.requests {
+        if request.state == CertificateStatus::Requested
+            && !known.contains(request.fingerprint.as_str())
+        {
+            webhooks.emit(
+                None,
+                WebhookEvent::CertRequestPending,
+                serde_json::json!({
+                    "certname": request.certname,
+                    "fingerprint": request.fingerprint,
+                    "dns_alt_names": request.dns_alt_names,
+                    "requested_at": request.requested_at,
+                }),
+            );
+            notifications.notify_in_background(
+                NotificationAudience::Permission(Resource::Certificates, Action::Sign),
+                NewNotification {
+                    organization_id: None,
+                    title: format!("Certificate request from {}", request.certname),
+                    message: format!(
+                        "{} is waiting for its certificate to be signed",
+                        request.certname
+                    ),
+                    r#type: NotificationType::Info,
+                    category: Some("certificates".to_string()),
+                    link: Some("/ca".to_string()),
+                    expires_at: None,
+                    metadata: Some(serde_json::json!({
+                        "certname": request.certname,
+                        "fingerprint": request.fingerprint,
+                    })),
+                    dedup_key: Some(format!("ca_request:{}", request.fingerprint)),
+                },
+            );
+        }
+    }
+
+    debug!(
+        "CA snapshot synced ({} certificates, {} requests)",
+        snapshot.certificates.len(),
+        snapshot.requests.len()
+    );
+    Ok(snapshot)
+}
+
+/// Load the stored snapshot, if any
+pub async fn load_snapshot(pool: &DbPool) -> Option<CaSnapshot> {
+    match CaSnapshotRepository::new(pool).get().await {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            warn!("Failed to load CA snapshot: {}", e);
+            None
+        }
+    }
+}
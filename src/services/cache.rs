@@ -14,7 +14,9 @@ use tracing::{debug, info, warn};
 
 use crate::config::CacheConfig;
 use crate::models::{Fact, Node, Report};
+use crate::services::health::{self, HealthTracker};
 use crate::services::puppetdb::{Catalog, PuppetDbClient, Resource};
+use crate::services::search_index::SearchIndexer;
 
 /// Cache entry with expiration tracking
 #[derive(Debug, Clone)]
@@ -529,47 +531,101 @@ pub struct CacheServiceStats {
 }
 
 /// Background sync job for keeping cache fresh
+///
+/// The job can also rebuild the global search index, either alongside the
+/// cache refresh or on its own, and records each rebuild with `health`.
 pub struct CacheSyncJob {
-    service: CachedPuppetDbService,
+    service: Option<CachedPuppetDbService>,
     interval: Duration,
+    search_index: Option<(SearchIndexer, Duration)>,
+    health: HealthTracker,
 }
 
 impl CacheSyncJob {
     pub fn new(service: CachedPuppetDbService, interval_secs: u64) -> Self {
         Self {
-            service,
+            service: Some(service),
             interval: Duration::from_secs(interval_secs),
+            search_index: None,
+            health: HealthTracker::default(),
         }
     }
 
+    /// Sync job that only rebuilds the search index
+    pub fn search_index_only(
+        indexer: SearchIndexer,
+        interval_secs: u64,
+        health: HealthTracker,
+    ) -> Self {
+        let interval = Duration::from_secs(interval_secs);
+        Self {
+            service: None,
+            interval,
+            search_index: Some((indexer, interval)),
+            health,
+        }
+    }
+
+    /// Also rebuild the search index, at most every `interval_secs`
+    ///
+    /// The index is checked on the job's own ticks, so it is never rebuilt
+    /// more often than the cache is refreshed.
+    pub fn with_search_index(
+        mut self,
+        indexer: SearchIndexer,
+        interval_secs: u64,
+        health: HealthTracker,
+    ) -> Self {
+        self.search_index = Some((indexer, Duration::from_secs(interval_secs)));
+        self.health = health;
+        self
+    }
+
     /// Start the background sync job
     pub fn start(self) -> tokio::task::JoinHandle<()> {
         tokio::spawn(async move {
             info!("Starting cache sync job with interval {:?}", self.interval);
 
             let mut interval = tokio::time::interval(self.interval);
+            let mut last_indexed: Option<Instant> = None;
 
             loop {
-                interval.tick().await;
-
-                // Evict expired entries
-                let eviction_stats = self.service.evict_expired().await;
-                if eviction_stats.total > 0 {
-                    debug!(
-                        "Cache sync: evicted {} expired entries",
-                        eviction_stats.total
-                    );
+                let tick = interval.tick().await.into_std();
+
+                if let Some(service) = &self.service {
+                    // Evict expired entries
+                    let eviction_stats = service.evict_expired().await;
+                    if eviction_stats.total > 0 {
+                        debug!(
+                            "Cache sync: evicted {} expired entries",
+                            eviction_stats.total
+                        );
+                    }
+
+                    // Refresh node list
+                    match service.client.get_nodes().await {
+                        Ok(nodes) => {
+                            debug!("Cache sync: refreshed {} nodes", nodes.len());
+                            // The get_nodes call will automatically cache the results
+                            // through the CachedPuppetDbService
+                        }
+                        Err(e) => {
+                            warn!("Cache sync: failed to refresh nodes: {}", e);
+                        }
+                    }
                 }
 
-                // Refresh node list
-                match self.service.client.get_nodes().await {
-                    Ok(nodes) => {
-                        debug!("Cache sync: refreshed {} nodes", nodes.len());
-                        // The get_nodes call will automatically cache the results
-                        // through the CachedPuppetDbService
+                if let Some((indexer, every)) = &self.search_index {
+                    if last_indexed.is_some_and(|at| tick.duration_since(at) < *every) {
+                        continue;
                     }
-                    Err(e) => {
-                        warn!("Cache sync: failed to refresh nodes: {}", e);
+                    last_indexed = Some(tick);
+                    match indexer.rebuild_all().await {
+                        Ok(count) => {
+                            self.health.record_success(health::SEARCH_INDEX);
+                            debug!("Cache sync: indexed {} search documents", count)
+                        }
+                        Err(e) => warn!("Cache sync: failed to rebuild search index: {}", e),
                     }
                 }
             }
@@ -707,5 +763,7 @@ mod tests {
         assert_eq!(config.catalog_ttl_secs, 600);
         assert_eq!(config.max_entries, 10000);
         assert_eq!(config.sync_interval_secs, 0);
+        assert_eq!(config.search_index_interval_secs, 300);
+        assert_eq!(config.smart_list_count_ttl_secs, 300);
     }
 }
@@ -0,0 +1,417 @@
+//! Certificate renewal campaigns
+//!
+//! Picks node certificates close to expiry and tracks each node until the CA
+//! holds a newer certificate for it. A background tracker re-checks active
+//! campaigns against the CA, fails nodes still on their old certificate once
+//! their deadline passes, and notifies the campaign creator when it is done.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::RwLock;
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+
+use crate::db::{CertRenewalRepository, DbPool};
+use crate::models::{
+    Certificate, CertificateStatus, CreateNotificationRequest, NotificationType, RenewalCampaign,
+    RenewalCampaignStatus, RenewalCandidate, RenewalTarget, RenewalTargetStatus,
+};
+use crate::services::notification::NotificationService;
+use crate::services::puppet_ca::PuppetCAService;
+use crate::utils::AppError;
+
+/// How often active campaigns are checked against the CA
+const TRACK_INTERVAL_SECS: u64 = 300;
+
+/// Signed, unexpired certificates expiring within `within_days`, soonest first
+pub fn renewal_candidates(
+    certificates: &[Certificate],
+    within_days: i64,
+    now: DateTime<Utc>,
+) -> Vec<RenewalCandidate> {
+    let mut candidates: Vec<RenewalCandidate> = certificates
+        .iter()
+        .filter(|c| c.state == CertificateStatus::Signed && c.not_after > now)
+        .map(|c| RenewalCandidate {
+            certname: c.certname.clone(),
+            serial: c.serial.clone(),
+            not_after: c.not_after,
+            days_until_expiry: (c.not_after - now).num_days(),
+        })
+        .filter(|c| c.days_until_expiry <= within_days)
+        .collect();
+
+    candidates.sort_by(|a, b| a.not_after.cmp(&b.not_after));
+    candidates
+}
+
+/// Manual renewal steps for a node
+pub fn renewal_instructions(certname: &str) -> Vec<String> {
+    vec![
+        format!(
+            "With auto-renewal enabled on the CA (`allow-auto-renewal: true`), run on {}: \
+             `puppet agent -t --hostcert_renewal_interval 3650d`",
+            certname
+        ),
+        format!(
+            "Otherwise re-issue the certificate: on the CA run \
+             `puppetserver ca clean --certname {0}`, on {0} run \
+             `puppet ssl clean && puppet ssl bootstrap`, then sign the new request",
+            certname
+        ),
+    ]
+}
+
+/// New state of a pending target
+#[derive(Debug, PartialEq)]
+enum TargetUpdate {
+    Renewed {
+        serial: String,
+        not_after: DateTime<Utc>,
+    },
+    Failed(String),
+    Unchanged,
+}
+
+/// Compare a pending target with the certificate the CA currently holds
+fn evaluate_target(
+    target: &RenewalTarget,
+    deadline: DateTime<Utc>,
+    current: Option<&Certificate>,
+    now: DateTime<Utc>,
+) -> TargetUpdate {
+    if let Some(cert) = current {
+        if cert.state == CertificateStatus::Revoked {
+            return TargetUpdate::Failed("Certificate was revoked".to_string());
+        }
+        if cert.serial != target.old_serial && cert.not_after > target.old_not_after {
+            return TargetUpdate::Renewed {
+                serial: cert.serial.clone(),
+                not_after: cert.not_after,
+            };
+        }
+    }
+
+    if now < target.due_at(deadline) {
+        TargetUpdate::Unchanged
+    } else if now >= target.old_not_after {
+        TargetUpdate::Failed("Certificate expired before it was renewed".to_string())
+    } else {
+        TargetUpdate::Failed("Not renewed before the campaign deadline".to_string())
+    }
+}
+
+/// Update a campaign's pending targets from the CA's certificate list
+///
+/// Completes the campaign once no target is pending. Returns the reloaded
+/// campaign.
+pub async fn refresh_campaign(
+    pool: &DbPool,
+    campaign: &RenewalCampaign,
+    certificates: &[Certificate],
+) -> anyhow::Result<RenewalCampaign> {
+    let repo = CertRenewalRepository::new(pool);
+    let now = Utc::now();
+
+    let pending = repo
+        .list_targets(campaign.id, Some(RenewalTargetStatus::Pending))
+        .await?;
+    for target in &pending {
+        let current = certificates.iter().find(|c| c.certname == target.certname);
+        match evaluate_target(target, campaign.deadline, current, now) {
+            TargetUpdate::Renewed { serial, not_after } => {
+                debug!(
+                    "Renewal campaign {}: {} renewed (serial {})",
+                    campaign.id, target.certname, serial
+                );
+                repo.mark_renewed(campaign.id, &target.certname, &serial, not_after)
+                    .await?;
+            }
+            TargetUpdate::Failed(reason) => {
+                warn!(
+                    "Renewal campaign {}: {} failed: {}",
+                    campaign.id, target.certname, reason
+                );
+                repo.mark_failed(campaign.id, &target.certname, &reason)
+                    .await?;
+            }
+            TargetUpdate::Unchanged => {}
+        }
+    }
+
+    let refreshed = repo
+        .get(campaign.id)
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Renewal campaign {} disappeared", campaign.id))?;
+    if refreshed.status == RenewalCampaignStatus::Active && refreshed.summary.pending == 0 {
+        repo.finish(campaign.id, RenewalCampaignStatus::Completed)
+            .await?;
+        info!(
+            "Renewal campaign '{}' completed: {} renewed, {} failed",
+            refreshed.name, refreshed.summary.renewed, refreshed.summary.failed
+        );
+        return repo
+            .get(campaign.id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Renewal campaign {} disappeared", campaign.id));
+    }
+
+    Ok(refreshed)
+}
+
+/// Handle for stopping the renewal tracker
+#[derive(Clone)]
+pub struct CertRenewalTrackerState {
+    running: Arc<RwLock<bool>>,
+    pool: DbPool,
+    ca: Arc<PuppetCAService>,
+    notifications: Option<Arc<NotificationService>>,
+}
+
+impl CertRenewalTrackerState {
+    pub async fn is_running(&self) -> bool {
+        *self.running.read().await
+    }
+
+    /// Request the tracker loop to stop at its next tick
+    pub async fn stop(&self) {
+        *self.running.write().await = false;
+        info!("Certificate renewal tracker stop requested");
+    }
+}
+
+/// Spawn the background renewal tracker
+pub fn start_cert_renewal_tracker(
+    pool: DbPool,
+    ca: Arc<PuppetCAService>,
+    notifications: Option<Arc<NotificationService>>,
+) -> CertRenewalTrackerState {
+    let state = CertRenewalTrackerState {
+        running: Arc::new(RwLock::new(true)),
+        pool,
+        ca,
+        notifications,
+    };
+
+    let loop_state = state.clone();
+    tokio::spawn(async move {
+        tracker_loop(loop_state).await;
+    });
+
+    info!(
+        "Certificate renewal tracker started (interval: {}s)",
+        TRACK_INTERVAL_SECS
+    );
+    state
+}
+
+async fn tracker_loop(state: CertRenewalTrackerState) {
+    let mut timer = interval(Duration::from_secs(TRACK_INTERVAL_SECS));
+
+    loop {
+        timer.tick().await;
+
+        if !*state.running.read().await {
+            info!("Certificate renewal tracker stopping");
+            break;
+        }
+
+        if let Err(e) = track_campaigns(&state).await {
+            match e {
+                AppError::ServiceUnavailable(msg) => {
+                    debug!("CA unreachable, skipping renewal tracking: {}", msg)
+                }
+                e => error!("Certificate renewal tracking failed: {}", e),
+            }
+        }
+    }
+}
+
+/// One tracking pass over every active campaign
+async fn track_campaigns(state: &CertRenewalTrackerState) -> Result<(), AppError> {
+    let repo = CertRenewalRepository::new(&state.pool);
+    let campaigns = repo
+        .list_active()
+        .await
+        .map_err(|e| AppError::internal(e.to_string()))?;
+    if campaigns.is_empty() {
+        return Ok(());
+    }
+
+    let certificates = state.ca.list_certificates().await?;
+    for campaign in campaigns {
+        match refresh_campaign(&state.pool, &campaign, &certificates).await {
+            Ok(refreshed) if refreshed.status == RenewalCampaignStatus::Completed => {
+                notify_completed(state.notifications.as_deref(), &refreshed).await;
+            }
+            Ok(_) => {}
+            Err(e) => error!("Failed to refresh renewal campaign {}: {}", campaign.id, e),
+        }
+    }
+
+    Ok(())
+}
+
+async fn notify_completed(notifications: Option<&NotificationService>, campaign: &RenewalCampaign) {
+    let Some(notifications) = notifications else {
+        return;
+    };
+
+    let summary = &campaign.summary;
+    let (r#type, message) = if summary.failed > 0 {
+        (
+            NotificationType::Warning,
+            format!(
+                "{} of {} nodes failed to renew their certificate before the deadline",
+                summary.failed, summary.total
+            ),
+        )
+    } else {
+        (
+            NotificationType::Success,
+            format!("All {} certificates were renewed", summary.total),
+        )
+    };
+
+    let req = CreateNotificationRequest {
+        user_id: campaign.created_by.clone(),
+        organization_id: None,
+        title: format!("Renewal campaign '{}' completed", campaign.name),
+        message,
+        r#type,
+        category: Some("certificates".to_string()),
+        link: Some("/ca".to_string()),
+        expires_at: None,
+        metadata: Some(serde_json::json!({ "campaign_id": campaign.id })),
+        dedup_key: None,
+    };
+
+    if let Err(e) = notifications.create_notification(req).await {
+        error!("Failed to create renewal campaign notification: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration as ChronoDuration;
+
+    fn cert(certname: &str, serial: &str, not_after: DateTime<Utc>) -> Certificate {
+        Certificate {
+            certname: certname.to_string(),
+            serial: serial.to_string(),
+            not_before: not_after - ChronoDuration::days(365),
+            not_after,
+            dns_alt_names: vec![],
+            fingerprint: String::new(),
+            state: CertificateStatus::Signed,
+            revocation: None,
+        }
+    }
+
+    fn target(old_serial: &str, old_not_after: DateTime<Utc>) -> RenewalTarget {
+        RenewalTarget {
+            certname: "node1.example.com".to_string(),
+            status: RenewalTargetStatus::Pending,
+            old_serial: old_serial.to_string(),
+            old_not_after,
+            new_serial: None,
+            new_not_after: None,
+            error: None,
+            updated_at: Utc::now(),
+            instructions: vec![],
+        }
+    }
+
+    #[test]
+    fn test_renewal_candidates() {
+        let now = Utc::now();
+        let mut revoked = cert("revoked", "3", now + ChronoDuration::days(5));
+        revoked.state = CertificateStatus::Revoked;
+        let certificates = vec![
+            cert("later", "1", now + ChronoDuration::days(20)),
+            cert("sooner", "2", now + ChronoDuration::days(3)),
+            cert("far", "4", now + ChronoDuration::days(200)),
+            cert("expired", "5", now - ChronoDuration::days(1)),
+            revoked,
+        ];
+
+        let candidates = renewal_candidates(&certificates, 30, now);
+        let names: Vec<_> = candidates.iter().map(|c| c.certname.as_str()).collect();
+        assert_eq!(names, vec!["sooner", "later"]);
+    }
+
+    #[test]
+    fn test_evaluate_target_renewed() {
+        let now = Utc::now();
+        let old_not_after = now + ChronoDuration::days(10);
+        let current = cert("node1.example.com", "42", now + ChronoDuration::days(1800));
+
+        assert_eq!(
+            evaluate_target(
+                &target("7", old_not_after),
+                now + ChronoDuration::days(5),
+                Some(&current),
+                now
+            ),
+            TargetUpdate::Renewed {
+                serial: "42".to_string(),
+                not_after: current.not_after,
+            }
+        );
+    }
+
+    #[test]
+    fn test_evaluate_target_deadlines() {
+        let now = Utc::now();
+        let old_not_after = now + ChronoDuration::days(10);
+        let unchanged = cert("node1.example.com", "7", old_not_after);
+        let pending = target("7", old_not_after);
+
+        // Before the deadline nothing changes
+        assert_eq!(
+            evaluate_target(
+                &pending,
+                now + ChronoDuration::days(5),
+                Some(&unchanged),
+                now
+            ),
+            TargetUpdate::Unchanged
+        );
+        // Past the deadline the target fails
+        assert!(matches!(
+            evaluate_target(
+                &pending,
+                now - ChronoDuration::hours(1),
+                Some(&unchanged),
+                now
+            ),
+            TargetUpdate::Failed(_)
+        ));
+        // An earlier certificate expiry overrides a later deadline
+        let expired = target("7", now - ChronoDuration::hours(1));
+        assert_eq!(
+            evaluate_target(&expired, now + ChronoDuration::days(5), None, now),
+            TargetUpdate::Failed("Certificate expired before it was renewed".to_string())
+        );
+    }
+
+    #[test]
+    fn test_evaluate_target_revoked() {
+        let now = Utc::now();
+        let mut revoked = cert("node1.example.com", "7", now + ChronoDuration::days(10));
+        revoked.state = CertificateStatus::Revoked;
+
+        assert_eq!(
+            evaluate_target(
+                &target("7", revoked.not_after),
+                now + ChronoDuration::days(5),
+                Some(&revoked),
+                now
+            ),
+            TargetUpdate::Failed("Certificate was revoked".to_string())
+        );
+    }
+}
@@ -1,11 +1,14 @@
 //! Node classification service
 
 use regex::Regex;
+use sqlx::SqlitePool;
 use std::collections::{HashMap, VecDeque};
+use tracing::warn;
 use uuid::Uuid;
 
+use crate::db::NodeMetadataRepository;
 use crate::models::{
-    ClassificationResult, ClassificationRule, Fact, GroupMatch, MatchType, NodeGroup,
+    ClassificationResult, ClassificationRule, Fact, GroupMatch, MatchType, NodeGroup, NodeMetadata,
     RuleEvaluation, RuleMatchType, RuleOperator,
 };
 
@@ -101,6 +104,7 @@ impl ClassificationService {
                 variables: serde_json::json!({}),
                 environment: None,
                 conflict_error: None,
+                maintenance: vec![],
             }
         }
     }
@@ -461,6 +465,7 @@ impl ClassificationService {
             variables: all_variables,
             environment,
             conflict_error: None,
+            maintenance: vec![],
         }
     }
 
@@ -550,8 +555,58 @@ pub fn build_classification_facts(
     serde_json::Value::Object(root)
 }
 
+/// Add a node's local metadata as `webui.*` pseudo-facts.
+///
+/// `webui.tags` is always set (empty without metadata) so that negated rules
+/// such as `webui.tags != "decommissioned"` still evaluate. `webui.owner_team`
+/// is only set when the node has an owner. A reported fact named `webui` is
+/// replaced.
+pub fn apply_node_metadata(facts: &mut serde_json::Value, metadata: Option<&NodeMetadata>) {
+    let Some(root) = facts.as_object_mut() else {
+        return;
+    };
+
+    let mut webui = serde_json::Map::new();
+    webui.insert(
+        "tags".to_string(),
+        serde_json::json!(metadata.map(|m| m.tags.as_slice()).unwrap_or_default()),
+    );
+    if let Some(owner_team) = metadata.and_then(|m| m.owner_team.as_deref()) {
+        webui.insert(
+            "owner_team".to_string(),
+            serde_json::Value::String(owner_team.to_string()),
+        );
+    }
+
+    root.insert("webui".to_string(), serde_json::Value::Object(webui));
+}
+
+/// [`build_classification_facts`] plus the node's `webui.*` pseudo-facts.
+///
+/// Metadata lookup failures are logged and classification continues without
+/// the node's tags.
+pub async fn build_node_classification_facts(
+    pool: &SqlitePool,
+    facts: Vec<Fact>,
+    certname: &str,
+    catalog_environment: Option<&str>,
+) -> serde_json::Value {
+    let mut payload = build_classification_facts(facts, certname, catalog_environment);
+
+    let metadata = match NodeMetadataRepository::new(pool).get(certname).await {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            warn!("Failed to load metadata for node '{}': {}", certname, e);
+            None
+        }
+    };
+    apply_node_metadata(&mut payload, metadata.as_ref());
+
+    payload
+}
+
 /// Get a fact value by path (e.g., "os.family" -> facts["os"]["family"])
-fn get_fact_value(facts: &serde_json::Value, path: &str) -> Option<serde_json::Value> {
+pub(crate) fn get_fact_value(facts: &serde_json::Value, path: &str) -> Option<serde_json::Value> {
     let parts: Vec<&str> = path.split('.').collect();
     let mut current = facts;
 
@@ -592,11 +647,43 @@ fn insert_fact_value(
 }
 
 /// Match a fact value against a rule value
-fn match_value(
+///
+/// List facts such as `webui.tags` are matched element by element when the
+/// rule value is a scalar (or, for `in`/`not_in`, a list of candidates):
+/// positive operators match if any element matches, negated operators only if
+/// no element matches.
+pub(crate) fn match_value(
     fact_value: &serde_json::Value,
     operator: &RuleOperator,
     rule_value: &serde_json::Value,
 ) -> bool {
+    if let Some(items) = fact_value.as_array() {
+        let element_wise = match operator {
+            RuleOperator::In | RuleOperator::NotIn => true,
+            RuleOperator::Equals
+            | RuleOperator::NotEquals
+            | RuleOperator::Regex
+            | RuleOperator::NotRegex => !rule_value.is_array(),
+            _ => false,
+        };
+
+        if element_wise {
+            let negated = matches!(
+                operator,
+                RuleOperator::NotEquals | RuleOperator::NotRegex | RuleOperator::NotIn
+            );
+            return if negated {
+                items
+                    .iter()
+                    .all(|item| match_value(item, operator, rule_value))
+            } else {
+                items
+                    .iter()
+                    .any(|item| match_value(item, operator, rule_value))
+            };
+        }
+    }
+
     match operator {
         RuleOperator::Equals => fact_value == rule_value,
         RuleOperator::NotEquals => fact_value != rule_value,
@@ -779,6 +866,104 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_apply_node_metadata() {
+        let mut facts = serde_json::json!({ "certname": "node1.example.com" });
+        apply_node_metadata(&mut facts, None);
+        assert_eq!(
+            get_fact_value(&facts, "webui.tags"),
+            Some(serde_json::json!([]))
+        );
+        assert_eq!(get_fact_value(&facts, "webui.owner_team"), None);
+
+        let metadata = NodeMetadata {
+            certname: "node1.example.com".to_string(),
+            tags: vec!["pci".to_string(), "web".to_string()],
+            owner_team: Some("platform".to_string()),
+            ticket_links: Vec::new(),
+            notes: None,
+            updated_by: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        };
+        apply_node_metadata(&mut facts, Some(&metadata));
+        assert_eq!(
+            get_fact_value(&facts, "webui.tags"),
+            Some(serde_json::json!(["pci", "web"]))
+        );
+        assert_eq!(
+            get_fact_value(&facts, "webui.owner_team"),
+            Some(serde_json::json!("platform"))
+        );
+    }
+
+    #[test]
+    fn test_match_value_list_fact() {
+        let tags = serde_json::json!(["pci", "web"]);
+        let no_tags = serde_json::json!([]);
+
+        assert!(match_value(
+            &tags,
+            &RuleOperator::Equals,
+            &serde_json::json!("web")
+        ));
+        assert!(!match_value(
+            &tags,
+            &RuleOperator::Equals,
+            &serde_json::json!("db")
+        ));
+        assert!(!match_value(
+            &no_tags,
+            &RuleOperator::Equals,
+            &serde_json::json!("web")
+        ));
+
+        assert!(match_value(
+            &tags,
+            &RuleOperator::NotEquals,
+            &serde_json::json!("db")
+        ));
+        assert!(!match_value(
+            &tags,
+            &RuleOperator::NotEquals,
+            &serde_json::json!("web")
+        ));
+        assert!(match_value(
+            &no_tags,
+            &RuleOperator::NotEquals,
+            &serde_json::json!("web")
+        ));
+
+        assert!(match_value(
+            &tags,
+            &RuleOperator::Regex,
+            &serde_json::json!("^pc")
+        ));
+        assert!(!match_value(
+            &tags,
+            &RuleOperator::NotRegex,
+            &serde_json::json!("^pc")
+        ));
+
+        assert!(match_value(
+            &tags,
+            &RuleOperator::In,
+            &serde_json::json!(["db", "web"])
+        ));
+        assert!(!match_value(
+            &tags,
+            &RuleOperator::NotIn,
+            &serde_json::json!(["db", "web"])
+        ));
+
+        // A list rule value still compares the whole list
+        assert!(match_value(
+            &tags,
+            &RuleOperator::Equals,
+            &serde_json::json!(["pci", "web"])
+        ));
+    }
+
     #[test]
     fn test_match_value_equals() {
         assert!(match_value(
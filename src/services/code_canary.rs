@@ -0,0 +1,200 @@
+//! Canary runs of code deployments
+//!
+//! A canary checks the deployed commit out into a temporary environment and
+//! pins a sample of nodes from a group to it through a classifier environment
+//! group, nested under the sampled group so the nodes keep its classes. The
+//! reports the nodes send from the temporary environment decide whether the
+//! deployment is promoted to its target environment or rolled back.
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use rand::seq::IndexedRandom;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::models::{CanaryNodeReports, CanaryStatus};
+use crate::services::deploy_strategy::environment_dir_name;
+use crate::services::deployment_impact::pql_string;
+use crate::services::puppetdb::PuppetDbClient;
+
+/// Name of the temporary environment of a canary
+pub fn environment_name(environment: &str, canary_id: Uuid) -> String {
+    format!(
+        "{}_canary_{}",
+        environment_dir_name(environment),
+        &canary_id.simple().to_string()[..8]
+    )
+}
+
+/// Random sample of the nodes of a group, sorted by certname
+pub fn sample(certnames: &[String], size: usize) -> Vec<String> {
+    let mut sample: Vec<String> = certnames.sample(&mut rand::rng(), size).cloned().collect();
+    sample.sort();
+    sample
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportRow {
+    certname: String,
+    status: Option<String>,
+    receive_time: Option<DateTime<Utc>>,
+}
+
+/// Reports the canary nodes sent from the temporary environment since the
+/// canary started
+///
+/// No-op runs are ignored since they do not apply the new code.
+pub async fn node_reports(
+    puppetdb: &PuppetDbClient,
+    environment: &str,
+    nodes: &[String],
+    since: DateTime<Utc>,
+) -> Result<Vec<CanaryNodeReports>> {
+    let certnames = nodes
+        .iter()
+        .map(|n| pql_string(n))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let pql = format!(
+        "reports[certname, status, receive_time] {{ environment = {} and noop = false and \
+         receive_time >= {} and certname in [{}] }}",
+        pql_string(environment),
+        pql_string(&since.to_rfc3339()),
+        certnames
+    );
+    let mut rows: Vec<ReportRow> = puppetdb.query(&pql).await?;
+    rows.sort_by_key(|r| r.receive_time);
+
+    let mut reports: HashMap<&str, CanaryNodeReports> = nodes
+        .iter()
+        .map(|n| {
+            (
+                n.as_str(),
+                CanaryNodeReports {
+                    certname: n.clone(),
+                    ..Default::default()
+                },
+            )
+        })
+        .collect();
+    for row in &rows {
+        let Some(node) = reports.get_mut(row.certname.as_str()) else {
+            continue;
+        };
+        if row.status.as_deref() == Some("failed") {
+            node.failed_reports += 1;
+        } else {
+            node.successful_reports += 1;
+        }
+        node.last_status = row.status.clone();
+        node.last_report_at = row.receive_time;
+    }
+
+    Ok(nodes
+        .iter()
+        .filter_map(|n| reports.remove(n.as_str()))
+        .collect())
+}
+
+/// Status of a running canary given the reports of its nodes, with the reason
+/// it failed
+///
+/// A single failed run fails the canary; it passes once every node sent
+/// `required_reports` successful runs, and fails at `deadline` otherwise.
+pub fn evaluate(
+    reports: &[CanaryNodeReports],
+    required_reports: u32,
+    now: DateTime<Utc>,
+    deadline: DateTime<Utc>,
+) -> (CanaryStatus, Option<String>) {
+    let failed: Vec<&str> = reports
+        .iter()
+        .filter(|r| r.failed_reports > 0)
+        .map(|r| r.certname.as_str())
+        .collect();
+    if !failed.is_empty() {
+        return (
+            CanaryStatus::Failed,
+            Some(format!("Failed runs on {}", failed.join(", "))),
+        );
+    }
+
+    let waiting: Vec<&str> = reports
+        .iter()
+        .filter(|r| r.successful_reports < required_reports)
+        .map(|r| r.certname.as_str())
+        .collect();
+    if waiting.is_empty() {
+        (CanaryStatus::Passed, None)
+    } else if now >= deadline {
+        (
+            CanaryStatus::Failed,
+            Some(format!("No reports in time from {}", waiting.join(", "))),
+        )
+    } else {
+        (CanaryStatus::Running, None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn reports(certname: &str, successful: u32, failed: u32) -> CanaryNodeReports {
+        CanaryNodeReports {
+            certname: certname.to_string(),
+            successful_reports: successful,
+            failed_reports: failed,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_environment_name() {
+        let id = Uuid::parse_str("6f1c8c52-1d7e-4b1b-9a57-3f0e6c1d2a90").unwrap();
+        assert_eq!(
+            environment_name("feature/db", id),
+            "feature_db_canary_6f1c8c52"
+        );
+    }
+
+    #[test]
+    fn test_sample() {
+        let certnames: Vec<String> = (1..=10).map(|i| format!("web{:02}", i)).collect();
+        let picked = sample(&certnames, 3);
+        assert_eq!(picked.len(), 3);
+        assert!(picked.windows(2).all(|w| w[0] < w[1]));
+        assert!(picked.iter().all(|c| certnames.contains(c)));
+
+        assert_eq!(sample(&certnames[..2], 5).len(), 2);
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let now = Utc::now();
+        let later = now + Duration::minutes(30);
+
+        assert_eq!(
+            evaluate(&[reports("a", 2, 0), reports("b", 0, 0)], 2, now, later),
+            (CanaryStatus::Running, None)
+        );
+        assert_eq!(
+            evaluate(&[reports("a", 2, 0), reports("b", 3, 0)], 2, now, later),
+            (CanaryStatus::Passed, None)
+        );
+        assert_eq!(
+            evaluate(&[reports("a", 2, 0), reports("b", 1, 1)], 2, now, later),
+            (CanaryStatus::Failed, Some("Failed runs on b".to_string()))
+        );
+        assert_eq!(
+            evaluate(&[reports("a", 2, 0), reports("b", 1, 0)], 2, later, now),
+            (
+                CanaryStatus::Failed,
+                Some("No reports in time from b".to_string())
+            )
+        );
+    }
+}
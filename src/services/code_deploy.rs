@@ -3,27 +3,43 @@
 //! Main orchestration service for Git-based environment management.
 //! Coordinates Git operations, r10k deployments, and database state.
 
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock, OnceLock};
 
 use anyhow::{Context, Result};
 use sqlx::SqlitePool;
-use tokio::sync::Mutex;
+use tokio::sync::{Mutex, OwnedMutexGuard, Semaphore};
 use tracing::{error, info, warn};
 use uuid::Uuid;
 
+use crate::api::groups::classify_group_members;
+use crate::db::repository::GroupRepository;
 use crate::db::{
-    CodeDeploymentRepository, CodeEnvironmentRepository, CodePatTokenRepository,
-    CodeRepositoryRepository, CodeSshKeyRepository,
+    CodeCanaryRepository, CodeDeploymentRepository, CodeEnvironmentRepository,
+    CodePatTokenRepository, CodeRepositoryRepository, CodeSshKeyRepository,
 };
 use crate::models::{
-    CodeDeployment, CodeDeploymentResponse, CodeDeploymentSummary, CodeEnvironment,
-    CodeEnvironmentResponse, CodePatTokenResponse, CodeRepository, CodeRepositoryResponse,
-    CodeSshKeyResponse, CreatePatTokenRequest, CreateRepositoryRequest, CreateSshKeyRequest,
-    DeploymentStatus, ListDeploymentsQuery, ListEnvironmentsQuery, UpdateEnvironmentRequest,
-    UpdatePatTokenRequest, UpdateRepositoryRequest,
+    CanaryNodeReports, CanaryStatus, CodeCanary, CodeDeployment, CodeDeploymentResponse,
+    CodeDeploymentSummary, CodeEnvironment, CodeEnvironmentResponse, CodePatTokenResponse,
+    CodeRepository, CodeRepositoryResponse, CodeSshKeyResponse, CreateGroupRequest,
+    CreatePatTokenRequest, CreateRepositoryRequest, CreateSshKeyRequest, CredentialHealthReport,
+    DeployStrategy, DeploymentImpact, DeploymentOptions, DeploymentStatus, GenerateSshKeyRequest,
+    ListDeploymentsQuery, ListEnvironmentsQuery, NewNotification, NotificationAudience,
+    NotificationType, StartCanaryRequest, UpdateEnvironmentRequest, UpdatePatTokenRequest,
+    UpdateRepositoryRequest, WebhookEvent,
 };
+use crate::services::code_canary;
+use crate::services::credential_health::{self, CredentialHealthThresholds};
+use crate::services::deploy_strategy::{self, DeployTarget};
+use crate::services::deployment_impact::{self, CodeChanges};
 use crate::services::git::{GitService, GitServiceConfig};
+use crate::services::notification::NotificationService;
+use crate::services::puppetdb::PuppetDbClient;
+use crate::services::puppetdb_registry::PuppetDbRegistry;
 use crate::services::r10k::{R10kConfig, R10kService, R10kSource};
+use crate::services::webhooks::Webhooks;
 
 /// Code Deploy service configuration
 #[derive(Debug, Clone)]
@@ -36,8 +52,16 @@ pub struct CodeDeployConfig {
     pub encryption_key: String,
     /// Base URL for webhook URLs
     pub webhook_base_url: Option<String>,
+    /// Path to the librarian-puppet binary
+    pub librarian_puppet_path: PathBuf,
     /// Retain deployment history for this many days
     pub retain_history_days: u32,
+    /// Maximum number of environments deployed in parallel
+    pub max_concurrent_deployments: usize,
+    /// Days before expiration operators are warned about a PAT token
+    pub pat_token_warning_days: i64,
+    /// Age in days after which an SSH key should be rotated (0 = never)
+    pub ssh_key_max_age_days: i64,
 }
 
 impl Default for CodeDeployConfig {
@@ -48,7 +72,11 @@ impl Default for CodeDeployConfig {
             enabled: false,
             encryption_key: String::new(),
             webhook_base_url: None,
+            librarian_puppet_path: PathBuf::from("/opt/puppetlabs/puppet/bin/librarian-puppet"),
             retain_history_days: 90,
+            max_concurrent_deployments: 1,
+            pat_token_warning_days: 30,
+            ssh_key_max_age_days: 365,
         }
     }
 }
@@ -59,13 +87,41 @@ pub struct CodeDeployService {
     git: GitService,
     r10k: R10kService,
     config: CodeDeployConfig,
-    /// Lock to ensure only one deployment runs at a time
-    deployment_lock: Arc<Mutex<()>>,
+    notifications: Arc<NotificationService>,
+    webhooks: Webhooks,
 }
 
+/// Held while a queue run picks the deployments it runs
+static QUEUE_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Held while an environment's queued deployments run, so queue runs never
+/// deploy the same environment at once
+static ENVIRONMENT_LOCKS: LazyLock<std::sync::Mutex<HashMap<Uuid, Arc<Mutex<()>>>>> =
+    LazyLock::new(Default::default);
+
+/// Bounds the deployments running at once across queue runs
+static DEPLOYMENT_SLOTS: OnceLock<Semaphore> = OnceLock::new();
+
+/// Held while `.netrc` is rewritten
+static NETRC_LOCK: Mutex<()> = Mutex::const_new(());
+
+/// Held by a deploy using the `.netrc` entry of a host. The file has one
+/// entry per host, so deploys of repositories with different PATs on the
+/// same host must not overlap.
+static CREDENTIAL_HOST_LOCKS: LazyLock<std::sync::Mutex<HashMap<String, Arc<Mutex<()>>>>> =
+    LazyLock::new(Default::default);
+
 impl CodeDeployService {
     /// Create a new Code Deploy service
-    pub fn new(pool: SqlitePool, config: CodeDeployConfig) -> Self {
+    ///
+    /// Finished deployments, canaries and credential issues are announced
+    /// through `notifications` and `webhooks`.
+    pub fn new(
+        pool: SqlitePool,
+        config: CodeDeployConfig,
+        notifications: Arc<NotificationService>,
+        webhooks: Webhooks,
+    ) -> Self {
         let git = GitService::new(config.git.clone());
         let r10k = R10kService::new(config.r10k.clone());
 
@@ -74,7 +130,8 @@ impl CodeDeployService {
             git,
             r10k,
             config,
-            deployment_lock: Arc::new(Mutex::new(())),
+            notifications,
+            webhooks,
         }
     }
 
@@ -135,6 +192,46 @@ impl CodeDeployService {
         Ok(CodeSshKeyResponse::from(key))
     }
 
+    /// Generate a new Ed25519 SSH keypair on the server
+    ///
+    /// The private key is encrypted and stored like an uploaded key; only the
+    /// public key is returned so it can be registered as a deploy key.
+    pub async fn generate_ssh_key(
+        &self,
+        req: &GenerateSshKeyRequest,
+    ) -> Result<CodeSshKeyResponse> {
+        let repo = CodeSshKeyRepository::new(&self.pool);
+
+        if req.name.trim().is_empty() {
+            return Err(anyhow::anyhow!("SSH key name cannot be empty"));
+        }
+
+        if repo.get_by_name(&req.name).await?.is_some() {
+            return Err(anyhow::anyhow!(
+                "SSH key with name '{}' already exists",
+                req.name
+            ));
+        }
+
+        let comment = req
+            .comment
+            .clone()
+            .filter(|c| !c.trim().is_empty())
+            .unwrap_or_else(|| format!("openvox-webui@{}", req.name));
+
+        let (private_key, public_key) = GitService::generate_ed25519_keypair(&comment)?;
+        let encrypted = self.encrypt_private_key(&private_key)?;
+
+        let create_req = CreateSshKeyRequest {
+            name: req.name.clone(),
+            private_key,
+        };
+        let key = repo.create(&create_req, &encrypted, &public_key).await?;
+
+        info!("Generated SSH key '{}' ({})", key.name, key.id);
+        Ok(CodeSshKeyResponse::from(key))
+    }
+
     /// Delete an SSH key
     pub async fn delete_ssh_key(&self, id: Uuid) -> Result<bool> {
         let repo = CodeSshKeyRepository::new(&self.pool);
@@ -256,22 +353,102 @@ impl CodeDeployService {
         Ok(tokens.into_iter().map(CodePatTokenResponse::from).collect())
     }
 
-    /// Check for expiring PAT tokens and return notifications to be created
-    /// Returns a list of (token_name, days_until_expiration, is_expired) tuples
-    pub async fn check_expiring_pat_tokens(
-        &self,
-        days_threshold: i64,
-    ) -> Result<Vec<(String, i64, bool)>> {
+    /// Notify Code Deploy users of PAT tokens expiring within
+    /// `days_threshold` days or already expired
+    ///
+    /// Users are notified once when a token starts expiring and once when it
+    /// has expired; a new expiration date notifies them again. Returns the
+    /// number of tokens found.
+    pub async fn notify_expiring_pat_tokens(&self, days_threshold: i64) -> Result<usize> {
         let tokens = self.list_expiring_pat_tokens(days_threshold).await?;
-        let mut warnings = Vec::new();
 
-        for token in tokens {
-            if let Some(days) = token.days_until_expiration {
-                warnings.push((token.name, days, token.is_expired));
-            }
+        for token in &tokens {
+            let (Some(expires_at), Some(days)) = (token.expires_at, token.days_until_expiration)
+            else {
+                continue;
+            };
+            let (state, r#type, title, message) = if token.is_expired {
+                (
+                    "expired",
+                    NotificationType::Error,
+                    format!("PAT token '{}' expired", token.name),
+                    format!(
+                        "The token expired on {}; repositories using it can no longer be synced",
+                        expires_at.format("%Y-%m-%d")
+                    ),
+                )
+            } else {
+                (
+                    "expiring",
+                    NotificationType::Warning,
+                    format!("PAT token '{}' expires soon", token.name),
+                    format!(
+                        "The token expires on {} ({} days left)",
+                        expires_at.format("%Y-%m-%d"),
+                        days
+                    ),
+                )
+            };
+
+            self.notifications.notify_in_background(
+                NotificationAudience::operators(),
+                NewNotification {
+                    organization_id: None,
+                    title,
+                    message,
+                    r#type,
+                    category: Some("code_deploy".to_string()),
+                    link: Some("/code-deploy".to_string()),
+                    expires_at: None,
+                    metadata: Some(serde_json::json!({
+                        "pat_token_id": token.id,
+                        "expires_at": expires_at,
+                    })),
+                    dedup_key: Some(format!(
+                        "pat_token:{}:{}:{}",
+                        token.id,
+                        expires_at.timestamp(),
+                        state
+                    )),
+                },
+            );
         }
 
-        Ok(warnings)
+        Ok(tokens.len())
+    }
+
+    // ========================================================================
+    // Credential Health
+    // ========================================================================
+
+    /// Assess the health of the PAT tokens, SSH keys and repository
+    /// authentication
+    pub async fn credential_health(&self) -> Result<CredentialHealthReport> {
+        let tokens = CodePatTokenRepository::new(&self.pool).get_all().await?;
+        let keys = CodeSshKeyRepository::new(&self.pool).get_all().await?;
+        let repositories = CodeRepositoryRepository::new(&self.pool).get_all().await?;
+
+        let thresholds = CredentialHealthThresholds {
+            pat_token_warning_days: self.config.pat_token_warning_days,
+            ssh_key_max_age_days: self.config.ssh_key_max_age_days,
+        };
+        Ok(credential_health::assess(
+            &tokens,
+            &keys,
+            &repositories,
+            &thresholds,
+            chrono::Utc::now(),
+        ))
+    }
+
+    /// Notify operators of expiring PAT tokens, repositories that cannot
+    /// authenticate and SSH keys due for rotation
+    pub async fn notify_credential_issues(&self) -> Result<CredentialHealthReport> {
+        self.notify_expiring_pat_tokens(self.config.pat_token_warning_days)
+            .await?;
+        let report = self.credential_health().await?;
+        credential_health::notify_issues(&self.notifications, &report);
+        Ok(report)
     }
 
     // ========================================================================
@@ -612,7 +789,8 @@ impl CodeDeployService {
             AuthType::Pat | AuthType::None => self.git.fetch_with_pat(&git_repo, github_pat_ref),
             AuthType::Ssh => self.git.fetch(&git_repo, ssh_key_ref),
         } {
-            repo.set_error(id, Some(&e.to_string())).await?;
+            // Keep the Git error, which tells authentication failures apart
+            repo.set_error(id, Some(&format!("{:#}", e))).await?;
             return Err(e);
         }
 
@@ -828,6 +1006,7 @@ impl CodeDeployService {
         &self,
         environment_id: Uuid,
         commit_sha: Option<&str>,
+        options: &DeploymentOptions,
         requested_by: Option<Uuid>,
     ) -> Result<CodeDeployment> {
         let env_repo = CodeEnvironmentRepository::new(&self.pool);
@@ -837,6 +1016,23 @@ impl CodeDeployService {
             return Err(anyhow::anyhow!("Environment not found"));
         };
 
+        options
+            .validate()
+            .map_err(|e| anyhow::anyhow!("Invalid deployment options: {}", e))?;
+        if !options.is_default() {
+            let repo_repo = CodeRepositoryRepository::new(&self.pool);
+            let strategy = repo_repo
+                .get_by_id(env.repository_id)
+                .await?
+                .map(|r| r.deploy_strategy)
+                .unwrap_or_default();
+            if strategy != DeployStrategy::R10k {
+                return Err(anyhow::anyhow!(
+                    "Invalid deployment options: only r10k repositories support them"
+                ));
+            }
+        }
+
         // Use provided commit or current commit
         let commit = commit_sha
             .map(|s| s.to_string())
@@ -853,17 +1049,124 @@ impl CodeDeployService {
         };
 
         deploy_repo
-            .create(
+            .create_with_options(
                 environment_id,
                 &commit,
                 env.current_commit_message.as_deref(),
                 env.current_commit_author.as_deref(),
                 status,
                 requested_by,
+                options,
             )
             .await
     }
 
+    /// Impact preview of a deployment
+    ///
+    /// Changes are taken between the environment's last successful
+    /// deployment, else the parent of the deployed commit, and the deployed
+    /// commit. Node counts come from `puppetdb`; without it, or when it fails,
+    /// only the changed modules and classes are reported.
+    pub async fn deployment_impact(
+        &self,
+        id: Uuid,
+        puppetdb: Option<&PuppetDbClient>,
+    ) -> Result<Option<DeploymentImpact>> {
+        let deploy_repo = CodeDeploymentRepository::new(&self.pool);
+        let env_repo = CodeEnvironmentRepository::new(&self.pool);
+
+        let Some(deployment) = deploy_repo.get_by_id(id).await? else {
+            return Ok(None);
+        };
+        let Some(env) = env_repo.get_by_id(deployment.environment_id).await? else {
+            return Err(anyhow::anyhow!("Environment not found"));
+        };
+
+        let last_success = deploy_repo
+            .get_by_environment(env.id, Some(100))
+            .await?
+            .into_iter()
+            .find(|d| d.status == DeploymentStatus::Success && d.created_at < deployment.created_at)
+            .map(|d| d.commit_sha);
+
+        // The local clone is not Send: finish with it before querying PuppetDB
+        let (base_commit, changed_files, puppetfile_modules) = {
+            let repo = self.git.open(&env.repository_id.to_string())?;
+            let base_commit = match last_success {
+                Some(sha) => Some(sha),
+                None => self.git.parent_commit(&repo, &deployment.commit_sha)?,
+            };
+            let changed_files =
+                self.git
+                    .changed_files(&repo, base_commit.as_deref(), &deployment.commit_sha)?;
+
+            let puppetfile_modules = if changed_files.iter().any(|f| f == "Puppetfile") {
+                let old = match &base_commit {
+                    Some(sha) => self.git.file_at_commit(&repo, sha, "Puppetfile")?,
+                    None => None,
+                };
+                let new = self
+                    .git
+                    .file_at_commit(&repo, &deployment.commit_sha, "Puppetfile")?;
+                deployment_impact::puppetfile_changes(old.as_deref(), new.as_deref())
+            } else {
+                Vec::new()
+            };
+
+            (base_commit, changed_files, puppetfile_modules)
+        };
+
+        let changes = CodeChanges::from_files(&changed_files, &puppetfile_modules);
+        let environment = deploy_strategy::environment_dir_name(&env.name);
+
+        let usage = match puppetdb {
+            Some(puppetdb) => deployment_impact::node_usage(puppetdb, &environment, &changes)
+                .await
+                .map_err(|e| {
+                    warn!("Failed to count nodes for deployment {}: {:#}", id, e);
+                    format!("{:#}", e)
+                }),
+            None => Err("PuppetDB is not configured".to_string()),
+        };
+
+        let (modules, affected_nodes, environment_nodes, puppetdb_error) = match usage {
+            Ok(usage) => {
+                let affected = if changes.site_manifest_changed || changes.data_changed {
+                    usage.environment_nodes
+                } else {
+                    usage.module_nodes
+                };
+                (
+                    usage.modules,
+                    Some(affected),
+                    Some(usage.environment_nodes),
+                    None,
+                )
+            }
+            Err(e) => (
+                deployment_impact::without_usage(&changes),
+                None,
+                None,
+                Some(e),
+            ),
+        };
+
+        Ok(Some(DeploymentImpact {
+            deployment_id: deployment.id,
+            environment,
+            base_commit,
+            commit_sha: deployment.commit_sha,
+            changed_files,
+            modules,
+            site_manifest_changed: changes.site_manifest_changed,
+            data_changed: changes.data_changed,
+            affected_nodes,
+            environment_nodes,
+            puppetdb_error,
+            generated_at: chrono::Utc::now(),
+        }))
+    }
+
     /// Approve a pending deployment
     pub async fn approve_deployment(
         &self,
@@ -900,16 +1203,97 @@ impl CodeDeployService {
     }
 
     /// Process the deployment queue (run approved deployments)
+    ///
+    /// Deployments of distinct environments run in parallel, up to
+    /// `max_concurrent_deployments` at a time. Deployments to the same
+    /// environment always run one after another, in approval order.
+    /// Environments still deploying from an earlier run are left to the next
+    /// run, so runs may overlap without waiting for each other.
     pub async fn process_deployment_queue(&self) -> Result<u32> {
-        let _lock = self.deployment_lock.lock().await;
+        let queues = self.claim_deployment_queues().await?;
+        if queues.is_empty() {
+            return Ok(0);
+        }
+
+        // Sized by the first run; the setting only changes on restart
+        let slots = DEPLOYMENT_SLOTS
+            .get_or_init(|| Semaphore::new(self.config.max_concurrent_deployments.max(1)));
+
+        let results = futures::future::join_all(queues.into_iter().map(
+            |(environment_id, deployments, environment_guard)| async move {
+                let _permit = slots.acquire().await;
+                let result = self.run_environment_queue(deployments).await;
+                drop(environment_guard);
+                (environment_id, result)
+            },
+        ))
+        .await;
+
+        let mut processed = 0;
+        for (environment_id, result) in results {
+            match result {
+                Ok(count) => processed += count,
+                Err(e) => error!(
+                    "Failed to process deployments for environment {}: {}",
+                    environment_id, e
+                ),
+            }
+        }
+
+        Ok(processed)
+    }
+
+    /// Lock the environments with approved deployments that no other run is
+    /// deploying, and return their queues with the held locks
+    async fn claim_deployment_queues(
+        &self,
+    ) -> Result<Vec<(Uuid, Vec<CodeDeployment>, OwnedMutexGuard<()>)>> {
+        let _lock = QUEUE_LOCK.lock().await;
 
+        let deploy_repo = CodeDeploymentRepository::new(&self.pool);
+        let ready = deploy_repo.get_ready_to_deploy().await?;
+        if ready.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut claimed = HashMap::new();
+        let environments: HashSet<Uuid> = ready.iter().map(|d| d.environment_id).collect();
+        for environment_id in environments {
+            if let Ok(guard) = keyed_lock(&ENVIRONMENT_LOCKS, environment_id).try_lock_owned() {
+                claimed.insert(environment_id, guard);
+            }
+        }
+        if claimed.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Read the queue again now the environments are ours: the run that
+        // held one may have deployed some of its deployments meanwhile
+        let ready = deploy_repo
+            .get_ready_to_deploy()
+            .await?
+            .into_iter()
+            .filter(|d| claimed.contains_key(&d.environment_id))
+            .collect();
+
+        Ok(group_by_environment(ready)
+            .into_iter()
+            .filter_map(|(environment_id, deployments)| {
+                let guard = claimed.remove(&environment_id)?;
+                Some((environment_id, deployments, guard))
+            })
+            .collect())
+    }
+
+    /// Run the queued deployments of a single environment sequentially
+    async fn run_environment_queue(&self, deployments: Vec<CodeDeployment>) -> Result<u32> {
         let deploy_repo = CodeDeploymentRepository::new(&self.pool);
         let env_repo = CodeEnvironmentRepository::new(&self.pool);
+        let repo_repo = CodeRepositoryRepository::new(&self.pool);
 
-        let ready = deploy_repo.get_ready_to_deploy().await?;
         let mut processed = 0;
 
-        for deployment in ready {
+        for deployment in deployments {
             let Some(env) = env_repo.get_by_id(deployment.environment_id).await? else {
                 warn!(
                     "Environment not found for deployment {}, marking as failed",
@@ -926,32 +1310,62 @@ impl CodeDeployService {
                 deployment.id, env.name
             );
 
+            let Some(repo) = repo_repo.get_by_id(env.repository_id).await? else {
+                warn!(
+                    "Repository not found for deployment {}, marking as failed",
+                    deployment.id
+                );
+                deploy_repo
+                    .mark_failed(deployment.id, "Repository not found", None)
+                    .await?;
+                continue;
+            };
+
             // Mark as deploying
             deploy_repo.mark_deploying(deployment.id).await?;
 
-            // Setup .netrc for PAT authentication if needed
-            if let Err(e) = self.setup_netrc_for_repository(env.repository_id).await {
-                // Log warning but continue - might still work with existing credentials
-                warn!(
-                    "Failed to setup .netrc for repository (deployment may still succeed): {}",
-                    e
-                );
-            }
+            // Setup .netrc for PAT authentication if needed, and keep it for
+            // the deploy; checkouts are made from the local clone and need no
+            // credentials
+            let _credentials = if repo.deploy_strategy != DeployStrategy::GitCheckout {
+                let guard = self.lock_credentials_host(&repo).await;
+                if let Err(e) = self.setup_netrc_for_repository(env.repository_id).await {
+                    // Log warning but continue - might still work with existing credentials
+                    warn!(
+                        "Failed to setup .netrc for repository (deployment may still succeed): {}",
+                        e
+                    );
+                }
+                guard
+            } else {
+                None
+            };
 
-            // Run r10k deploy with process tracking for cancellation support
-            let result = self
-                .r10k
-                .deploy_environment_with_tracking(&env.name, Some(deployment.id))
+            // Run the repository's deploy strategy with process tracking for
+            // cancellation support
+            let source = self.git.repo_path(&repo.id.to_string());
+            let target = DeployTarget {
+                deployment_id: deployment.id,
+                environment: &env.name,
+                commit_sha: &deployment.commit_sha,
+                source: &source,
+                options: &deployment.options,
+            };
+            let executor =
+                deploy_strategy::executor(repo.deploy_strategy, &self.r10k, &self.config);
+            let command_line = executor.command_line(&target);
+            deploy_repo
+                .set_command_line(deployment.id, &command_line)
                 .await?;
+            let result = executor.deploy(&target).await?;
+            let output = format!("$ {}\n{}\n{}", command_line, result.stdout, result.stderr);
 
             if result.success {
                 deploy_repo
-                    .mark_success(
-                        deployment.id,
-                        Some(&format!("{}\n{}", result.stdout, result.stderr)),
-                    )
+                    .mark_success(deployment.id, Some(&output))
                     .await?;
                 info!("Deployment {} completed successfully", deployment.id);
+                self.emit_deployment_completed(&deployment, &env, DeploymentStatus::Success, None);
             } else {
                 let error_msg = if result.stderr.is_empty() {
                     format!("Deployment failed with exit code {:?}", result.exit_code)
@@ -960,13 +1374,15 @@ impl CodeDeployService {
                 };
 
                 deploy_repo
-                    .mark_failed(
-                        deployment.id,
-                        &error_msg,
-                        Some(&format!("{}\n{}", result.stdout, result.stderr)),
-                    )
+                    .mark_failed(deployment.id, &error_msg, Some(&output))
                     .await?;
                 error!("Deployment {} failed: {}", deployment.id, error_msg);
+                self.emit_deployment_completed(
+                    &deployment,
+                    &env,
+                    DeploymentStatus::Failed,
+                    Some(&error_msg),
+                );
             }
 
             processed += 1;
@@ -994,19 +1410,416 @@ impl CodeDeployService {
             ));
         }
 
-        // Create a new deployment with the same commit
+        // Create a new deployment with the same commit and options
         deploy_repo
-            .create(
+            .create_with_options(
                 deployment.environment_id,
                 &deployment.commit_sha,
                 deployment.commit_message.as_deref(),
                 deployment.commit_author.as_deref(),
                 DeploymentStatus::Approved, // Skip pending for retries
                 requested_by,
+                &deployment.options,
             )
             .await
     }
 
+    // ========================================================================
+    // Canary Operations
+    // ========================================================================
+
+    /// Start a canary run of a pending deployment
+    ///
+    /// Checks the deployed commit out into a temporary environment and pins a
+    /// random sample of the group's nodes to it. Nodes matched by rules are
+    /// only found with `puppetdb`; without it only pinned nodes are sampled.
+    pub async fn start_canary(
+        &self,
+        deployment_id: Uuid,
+        organization_id: Uuid,
+        req: &StartCanaryRequest,
+        puppetdb: Option<&PuppetDbClient>,
+        started_by: Uuid,
+    ) -> Result<CodeCanary> {
+        let deploy_repo = CodeDeploymentRepository::new(&self.pool);
+        let env_repo = CodeEnvironmentRepository::new(&self.pool);
+        let repo_repo = CodeRepositoryRepository::new(&self.pool);
+        let canary_repo = CodeCanaryRepository::new(&self.pool);
+        let group_repo = GroupRepository::new(&self.pool);
+
+        if req.sample_size == 0 || req.required_reports == 0 || req.timeout_minutes == 0 {
+            return Err(anyhow::anyhow!(
+                "Invalid canary: sample_size, required_reports and timeout_minutes must be positive"
+            ));
+        }
+
+        let Some(deployment) = deploy_repo.get_by_id(deployment_id).await? else {
+            return Err(anyhow::anyhow!("Deployment not found"));
+        };
+        if deployment.status != DeploymentStatus::Pending {
+            return Err(anyhow::anyhow!(
+                "Invalid canary: only pending deployments can be canaried (status: {})",
+                deployment.status.as_str()
+            ));
+        }
+        let latest = canary_repo.get_latest_for_deployment(deployment_id).await?;
+        if latest.is_some_and(|c| c.status.is_active()) {
+            return Err(anyhow::anyhow!(
+                "Invalid canary: the deployment already has an active canary"
+            ));
+        }
+        let Some(env) = env_repo.get_by_id(deployment.environment_id).await? else {
+            return Err(anyhow::anyhow!("Environment not found"));
+        };
+        let Some(repo) = repo_repo.get_by_id(env.repository_id).await? else {
+            return Err(anyhow::anyhow!("Repository not found"));
+        };
+        if group_repo
+            .get_by_id(organization_id, req.group_id)
+            .await?
+            .is_none()
+        {
+            return Err(anyhow::anyhow!("Group not found"));
+        }
+
+        let members =
+            classify_group_members(&group_repo, puppetdb, organization_id, req.group_id).await?;
+        if members.is_empty() {
+            return Err(anyhow::anyhow!("Invalid canary: the group has no nodes"));
+        }
+        let nodes = code_canary::sample(&members, req.sample_size);
+
+        let id = Uuid::new_v4();
+        let environment = code_canary::environment_name(&env.name, id);
+
+        let _credentials = if repo.deploy_strategy != DeployStrategy::GitCheckout {
+            let guard = self.lock_credentials_host(&repo).await;
+            if let Err(e) = self.setup_netrc_for_repository(repo.id).await {
+                warn!(
+                    "Failed to setup .netrc for repository (canary may still deploy): {}",
+                    e
+                );
+            }
+            guard
+        } else {
+            None
+        };
+        let source = self.git.repo_path(&repo.id.to_string());
+        let target = DeployTarget {
+            deployment_id: id,
+            environment: &environment,
+            commit_sha: &deployment.commit_sha,
+            source: &source,
+            options: &DeploymentOptions::default(),
+        };
+        let result =
+            deploy_strategy::canary_executor(repo.deploy_strategy, &self.r10k, &self.config)
+                .deploy(&target)
+                .await?;
+        if !result.success {
+            self.remove_canary_environment(&environment).await;
+            return Err(anyhow::anyhow!(
+                "Canary deployment failed: {}",
+                result.stderr
+            ));
+        }
+
+        let group_id = match pin_canary_nodes(
+            &group_repo,
+            organization_id,
+            req.group_id,
+            &environment,
+            &nodes,
+            &deployment,
+        )
+        .await
+        {
+            Ok(group_id) => group_id,
+            Err(e) => {
+                self.remove_canary_environment(&environment).await;
+                return Err(e);
+            }
+        };
+
+        let now = chrono::Utc::now();
+        let canary = canary_repo
+            .create(&CodeCanary {
+                id,
+                deployment_id,
+                organization_id,
+                environment: environment.clone(),
+                source_group_id: req.group_id,
+                canary_group_id: Some(group_id),
+                node_reports: nodes
+                    .iter()
+                    .map(|certname| CanaryNodeReports {
+                        certname: certname.clone(),
+                        ..Default::default()
+                    })
+                    .collect(),
+                nodes,
+                required_reports: req.required_reports,
+                auto_promote: req.auto_promote,
+                status: CanaryStatus::Running,
+                error_message: None,
+                started_by: Some(started_by),
+                deadline_at: now + chrono::Duration::minutes(req.timeout_minutes.into()),
+                completed_at: None,
+                created_at: now,
+                updated_at: now,
+            })
+            .await?;
+
+        info!(
+            "Started canary {} of deployment {} in environment {} with {} nodes",
+            canary.id,
+            deployment_id,
+            environment,
+            canary.nodes.len()
+        );
+        Ok(canary)
+    }
+
+    /// Get the latest canary of a deployment
+    pub async fn get_canary(&self, deployment_id: Uuid) -> Result<Option<CodeCanary>> {
+        CodeCanaryRepository::new(&self.pool)
+            .get_latest_for_deployment(deployment_id)
+            .await
+    }
+
+    /// Check the reports of running canaries
+    ///
+    /// Canaries whose deployment is no longer pending are abandoned. Operators
+    /// are notified of passed and failed canaries, which are promoted or
+    /// rolled back right away when started with `auto_promote`. Failed
+    /// canaries release their nodes immediately.
+    pub async fn check_canaries(
+        &self,
+        puppetdb: &PuppetDbRegistry,
+        default_puppetdb: Option<Arc<PuppetDbClient>>,
+    ) -> Result<u32> {
+        let deploy_repo = CodeDeploymentRepository::new(&self.pool);
+        let env_repo = CodeEnvironmentRepository::new(&self.pool);
+        let canary_repo = CodeCanaryRepository::new(&self.pool);
+
+        let mut completed = 0;
+
+        for canary in canary_repo.get_running().await? {
+            let deployment = deploy_repo.get_by_id(canary.deployment_id).await?;
+            let Some(deployment) = deployment.filter(|d| d.status == DeploymentStatus::Pending)
+            else {
+                if canary_repo
+                    .transition(
+                        canary.id,
+                        &[CanaryStatus::Running],
+                        CanaryStatus::Abandoned,
+                        Some("The deployment is no longer pending"),
+                    )
+                    .await?
+                {
+                    self.end_canary(&canary).await;
+                    completed += 1;
+                }
+                continue;
+            };
+
+            let client = puppetdb
+                .resolve(&self.pool, canary.organization_id, default_puppetdb.clone())
+                .await;
+            let reports = match client {
+                Some(client) => match code_canary::node_reports(
+                    &client,
+                    &canary.environment,
+                    &canary.nodes,
+                    canary.created_at,
+                )
+                .await
+                {
+                    Ok(reports) => {
+                        canary_repo.set_node_reports(canary.id, &reports).await?;
+                        reports
+                    }
+                    Err(e) => {
+                        warn!("Failed to fetch reports of canary {}: {:#}", canary.id, e);
+                        canary.node_reports.clone()
+                    }
+                },
+                None => canary.node_reports.clone(),
+            };
+
+            let (status, error) = code_canary::evaluate(
+                &reports,
+                canary.required_reports,
+                chrono::Utc::now(),
+                canary.deadline_at,
+            );
+            if status == CanaryStatus::Running
+                || !canary_repo
+                    .transition(
+                        canary.id,
+                        &[CanaryStatus::Running],
+                        status,
+                        error.as_deref(),
+                    )
+                    .await?
+            {
+                continue;
+            }
+            completed += 1;
+
+            if status == CanaryStatus::Failed {
+                self.end_canary(&canary).await;
+            }
+            let env_name = env_repo
+                .get_by_id(deployment.environment_id)
+                .await?
+                .map(|e| e.name)
+                .unwrap_or_default();
+            self.notify_canary(&canary, &env_name, status, error.as_deref());
+
+            let Some(user) = canary.started_by.filter(|_| canary.auto_promote) else {
+                continue;
+            };
+            let outcome = if status == CanaryStatus::Passed {
+                self.promote_canary(deployment.id, user).await
+            } else {
+                self.rollback_canary(deployment.id, user, error.as_deref())
+                    .await
+            };
+            if let Err(e) = outcome {
+                error!(
+                    "Failed to conclude canary {} automatically: {:#}",
+                    canary.id, e
+                );
+            }
+        }
+
+        Ok(completed)
+    }
+
+    /// Promote a passed canary: approve the deployment for its target
+    /// environment and release the canary nodes
+    pub async fn promote_canary(
+        &self,
+        deployment_id: Uuid,
+        approved_by: Uuid,
+    ) -> Result<Option<CodeCanary>> {
+        let canary_repo = CodeCanaryRepository::new(&self.pool);
+        let Some(canary) = canary_repo.get_latest_for_deployment(deployment_id).await? else {
+            return Ok(None);
+        };
+        if canary.status != CanaryStatus::Passed {
+            return Err(anyhow::anyhow!(
+                "Invalid canary: only passed canaries can be promoted (status: {})",
+                canary.status.as_str()
+            ));
+        }
+
+        let deploy_repo = CodeDeploymentRepository::new(&self.pool);
+        if deploy_repo
+            .approve(deployment_id, approved_by)
+            .await?
+            .is_none()
+        {
+            return Err(anyhow::anyhow!(
+                "Invalid canary: the deployment is no longer pending"
+            ));
+        }
+        canary_repo
+            .transition(
+                canary.id,
+                &[CanaryStatus::Passed],
+                CanaryStatus::Promoted,
+                None,
+            )
+            .await?;
+        self.end_canary(&canary).await;
+
+        info!(
+            "Promoted canary {} of deployment {}",
+            canary.id, deployment_id
+        );
+        canary_repo.get_by_id(canary.id).await
+    }
+
+    /// Roll a canary back: reject the deployment and release the canary
+    /// nodes
+    pub async fn rollback_canary(
+        &self,
+        deployment_id: Uuid,
+        rejected_by: Uuid,
+        reason: Option<&str>,
+    ) -> Result<Option<CodeCanary>> {
+        let canary_repo = CodeCanaryRepository::new(&self.pool);
+        let Some(canary) = canary_repo.get_latest_for_deployment(deployment_id).await? else {
+            return Ok(None);
+        };
+        if !canary_repo
+            .transition(
+                canary.id,
+                &[
+                    CanaryStatus::Running,
+                    CanaryStatus::Passed,
+                    CanaryStatus::Failed,
+                ],
+                CanaryStatus::RolledBack,
+                None,
+            )
+            .await?
+        {
+            return Err(anyhow::anyhow!(
+                "Invalid canary: the canary has already ended (status: {})",
+                canary.status.as_str()
+            ));
+        }
+        if canary.status.is_active() {
+            self.end_canary(&canary).await;
+        }
+
+        let reason = format!(
+            "Canary rolled back: {}",
+            reason.unwrap_or("no reason given")
+        );
+        CodeDeploymentRepository::new(&self.pool)
+            .reject(deployment_id, rejected_by, &reason)
+            .await?;
+
+        info!(
+            "Rolled back canary {} of deployment {}",
+            canary.id, deployment_id
+        );
+        canary_repo.get_by_id(canary.id).await
+    }
+
+    /// Release the nodes of a canary and remove its temporary environment
+    ///
+    /// Failures are logged: the canary has already ended.
+    async fn end_canary(&self, canary: &CodeCanary) {
+        if let Some(group_id) = canary.canary_group_id {
+            if let Err(e) = GroupRepository::new(&self.pool)
+                .delete(canary.organization_id, group_id)
+                .await
+            {
+                error!(
+                    "Failed to delete group {} of canary {}: {:#}",
+                    group_id, canary.id, e
+                );
+            }
+        }
+        self.remove_canary_environment(&canary.environment).await;
+    }
+
+    async fn remove_canary_environment(&self, environment: &str) {
+        if let Err(e) =
+            deploy_strategy::remove_environment(&self.config.r10k.basedir, environment).await
+        {
+            error!(
+                "Failed to remove canary environment {}: {:#}",
+                environment, e
+            );
+        }
+    }
+
     // ========================================================================
     // Webhook Operations
     // ========================================================================
@@ -1069,7 +1882,7 @@ impl CodeDeployService {
         let repositories = repo_repo.get_all().await?;
         let sources: Vec<R10kSource> = repositories
             .iter()
-            .filter(|r| r.is_control_repo)
+            .filter(|r| r.is_control_repo && r.deploy_strategy == DeployStrategy::R10k)
             .map(|r| R10kSource {
                 name: r.name.clone(),
                 remote: r.url.clone(),
@@ -1105,6 +1918,7 @@ impl CodeDeployService {
             ssh_key_id: repo.ssh_key_id,
             ssh_key_name,
             has_pat: repo.github_pat_encrypted.is_some(),
+            deploy_strategy: repo.deploy_strategy,
             webhook_url: self.webhook_url(repo.id, "github"),
             poll_interval_seconds: repo.poll_interval_seconds,
             is_control_repo: repo.is_control_repo,
@@ -1191,6 +2005,8 @@ impl CodeDeployService {
             duration_seconds,
             error_message: deployment.error_message,
             r10k_output: deployment.r10k_output,
+            options: deployment.options,
+            command_line: deployment.command_line,
             created_at: deployment.created_at,
             updated_at: deployment.updated_at,
         })
@@ -1237,9 +2053,25 @@ impl CodeDeployService {
     // .netrc management for HTTPS PAT authentication
     // =========================================================================
 
+    /// Wait until no other deploy uses the `.netrc` entry of the host a
+    /// PAT-authenticated repository is on
+    ///
+    /// Hold the returned guard from writing the entry until the deploy is
+    /// done. Returns `None` for repositories without PAT authentication.
+    async fn lock_credentials_host(&self, repo: &CodeRepository) -> Option<OwnedMutexGuard<()>> {
+        if repo.auth_type != crate::models::AuthType::Pat {
+            return None;
+        }
+        let host = extract_hostname_from_url(&repo.url)?;
+        Some(keyed_lock(&CREDENTIAL_HOST_LOCKS, host).lock_owned().await)
+    }
+
     /// Setup .netrc file for PAT authentication before r10k deployment.
     /// This allows git to authenticate via HTTPS using the PAT token.
     ///
+    /// Deploys must hold [`Self::lock_credentials_host`] around this and the
+    /// deploy itself.
+    ///
     /// Returns Ok(true) if netrc was created/updated, Ok(false) if not needed.
     pub async fn setup_netrc_for_repository(&self, repository_id: Uuid) -> Result<bool> {
         use std::fs::{self, OpenOptions};
@@ -1302,7 +2134,9 @@ impl CodeDeployService {
             machine, username, netrc_path.display(), system_username
         );
 
-        // Read existing .netrc content (if any)
+        // Read existing .netrc content (if any); concurrent updates of other
+        // hosts' entries must not be lost
+        let _netrc = NETRC_LOCK.lock().await;
         let existing_content = fs::read_to_string(&netrc_path).unwrap_or_default();
 
         // Build new entry
@@ -1407,6 +2241,194 @@ impl CodeDeployService {
 
         Ok(count)
     }
+
+    /// Notify webhooks and users that a deployment finished
+    ///
+    /// The requester hears about a successful deployment, every Code Deploy user
+    /// about a failed one.
+    fn emit_deployment_completed(
+        &self,
+        deployment: &CodeDeployment,
+        env: &CodeEnvironment,
+        status: DeploymentStatus,
+        error: Option<&str>,
+    ) {
+        self.webhooks.emit(
+            None,
+            WebhookEvent::DeploymentCompleted,
+            serde_json::json!({
+                "deployment_id": deployment.id,
+                "environment": env.name,
+                "branch": env.branch,
+                "commit_sha": deployment.commit_sha,
+                "commit_message": deployment.commit_message,
+                "requested_by": deployment.requested_by,
+                "status": status.as_str(),
+                "error": error,
+            }),
+        );
+
+        let failed = status != DeploymentStatus::Success;
+        let audience = match deployment.requested_by {
+            Some(user_id) if !failed => NotificationAudience::User(user_id),
+            _ => NotificationAudience::operators(),
+        };
+        let (r#type, title, message) = if failed {
+            (
+                NotificationType::Error,
+                format!("Deployment of {} failed", env.name),
+                error.unwrap_or("The deployment failed").to_string(),
+            )
+        } else {
+            (
+                NotificationType::Success,
+                format!("Deployed {}", env.name),
+                format!(
+                    "Commit {} of branch {} is deployed",
+                    deployment
+                        .commit_sha
+                        .get(..7)
+                        .unwrap_or(&deployment.commit_sha),
+                    env.branch
+                ),
+            )
+        };
+        self.notifications.notify_in_background(
+            audience,
+            NewNotification {
+                organization_id: None,
+                title,
+                message,
+                r#type,
+                category: Some("deployment".to_string()),
+                link: Some("/code-deploy".to_string()),
+                expires_at: None,
+                metadata: Some(serde_json::json!({
+                    "deployment_id": deployment.id,
+                    "environment": env.name,
+                    "status": status.as_str(),
+                })),
+                dedup_key: None,
+            },
+        );
+    }
+
+    /// Notify operators that a canary passed or failed
+    fn notify_canary(
+        &self,
+        canary: &CodeCanary,
+        env_name: &str,
+        status: CanaryStatus,
+        error: Option<&str>,
+    ) {
+        let (r#type, title, message) = if status == CanaryStatus::Passed {
+            (
+                NotificationType::Success,
+                format!("Canary of {} passed", env_name),
+                if canary.auto_promote {
+                    "The deployment is promoted automatically".to_string()
+                } else {
+                    "The deployment can be promoted".to_string()
+                },
+            )
+        } else {
+            (
+                NotificationType::Error,
+                format!("Canary of {} failed", env_name),
+                error.unwrap_or("The canary failed").to_string(),
+            )
+        };
+        self.notifications.notify_in_background(
+            NotificationAudience::operators(),
+            NewNotification {
+                organization_id: Some(canary.organization_id),
+                title,
+                message,
+                r#type,
+                category: Some("deployment".to_string()),
+                link: Some("/code-deploy".to_string()),
+                expires_at: None,
+                metadata: Some(serde_json::json!({
+                    "deployment_id": canary.deployment_id,
+                    "canary_id": canary.id,
+                    "environment": canary.environment,
+                    "status": status.as_str(),
+                })),
+                dedup_key: None,
+            },
+        );
+    }
+}
+
+/// The lock of `key`, created on first use
+fn keyed_lock<K: Eq + Hash>(
+    locks: &std::sync::Mutex<HashMap<K, Arc<Mutex<()>>>>,
+    key: K,
+) -> Arc<Mutex<()>> {
+    locks
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .entry(key)
+        .or_default()
+        .clone()
+}
+
+/// Split queued deployments into per-environment queues
+///
+/// Environments keep the order of their first queued deployment, and each
+/// queue keeps the original (approval) order.
+fn group_by_environment(deployments: Vec<CodeDeployment>) -> Vec<(Uuid, Vec<CodeDeployment>)> {
+    let mut queues: Vec<(Uuid, Vec<CodeDeployment>)> = Vec::new();
+    for deployment in deployments {
+        match queues
+            .iter_mut()
+            .find(|(environment_id, _)| *environment_id == deployment.environment_id)
+        {
+            Some((_, queue)) => queue.push(deployment),
+            None => queues.push((deployment.environment_id, vec![deployment])),
+        }
+    }
+    queues
+}
+
+/// Create the environment group pinning the nodes of a canary to its
+/// temporary environment, under the group the nodes were sampled from
+async fn pin_canary_nodes(
+    group_repo: &GroupRepository<'_>,
+    organization_id: Uuid,
+    parent_id: Uuid,
+    environment: &str,
+    nodes: &[String],
+    deployment: &CodeDeployment,
+) -> Result<Uuid> {
+    let group = group_repo
+        .create(
+            organization_id,
+            &CreateGroupRequest {
+                name: format!("Canary {}", environment),
+                description: Some(format!(
+                    "Canary of commit {} (deployment {}); removed when the canary ends",
+                    deployment.commit_sha, deployment.id
+                )),
+                parent_id: Some(parent_id),
+                environment: Some(environment.to_string()),
+                is_environment_group: Some(true),
+                match_all_nodes: Some(false),
+                rule_match_type: None,
+                classes: None,
+                variables: None,
+            },
+        )
+        .await?;
+
+    for certname in nodes {
+        if let Err(e) = group_repo.add_pinned_node(group.id, certname).await {
+            group_repo.delete(organization_id, group.id).await?;
+            return Err(e);
+        }
+    }
+
+    Ok(group.id)
 }
 
 /// Extract hostname from a git URL (HTTPS or SSH)
@@ -1491,4 +2513,72 @@ mod tests {
         assert_eq!("mytoken", "mytoken");
         assert_ne!("mytoken", "wrongtoken");
     }
+
+    fn queued_deployment(environment_id: Uuid) -> CodeDeployment {
+        let now = chrono::Utc::now();
+        CodeDeployment {
+            id: Uuid::new_v4(),
+            environment_id,
+            commit_sha: "abc123".to_string(),
+            commit_message: None,
+            commit_author: None,
+            status: DeploymentStatus::Approved,
+            requested_by: None,
+            approved_by: None,
+            approved_at: Some(now),
+            rejected_at: None,
+            rejection_reason: None,
+            started_at: None,
+            completed_at: None,
+            error_message: None,
+            r10k_output: None,
+            options: DeploymentOptions::default(),
+            command_line: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_group_by_environment_preserves_order() {
+        let production = Uuid::new_v4();
+        let staging = Uuid::new_v4();
+        let deployments = vec![
+            queued_deployment(production),
+            queued_deployment(staging),
+            queued_deployment(production),
+        ];
+        let ids: Vec<Uuid> = deployments.iter().map(|d| d.id).collect();
+
+        let queues = group_by_environment(deployments);
+
+        assert_eq!(queues.len(), 2);
+        assert_eq!(queues[0].0, production);
+        assert_eq!(
+            queues[0].1.iter().map(|d| d.id).collect::<Vec<_>>(),
+            vec![ids[0], ids[2]]
+        );
+        assert_eq!(queues[1].0, staging);
+        assert_eq!(queues[1].1[0].id, ids[1]);
+    }
+
+    #[test]
+    fn test_keyed_lock_is_shared_per_key() {
+        let locks = std::sync::Mutex::new(HashMap::new());
+        let guard = keyed_lock(&locks, "git.example.com")
+            .try_lock_owned()
+            .unwrap();
+
+        assert!(keyed_lock(&locks, "git.example.com")
+            .try_lock_owned()
+            .is_err());
+        assert!(keyed_lock(&locks, "gitlab.example.com")
+            .try_lock_owned()
+            .is_ok());
+
+        drop(guard);
+        assert!(keyed_lock(&locks, "git.example.com")
+            .try_lock_owned()
+            .is_ok());
+    }
 }
@@ -1,6 +1,7 @@
 //! Background task scheduler for Code Deploy
 //!
-//! Provides periodic polling for repository updates and deployment queue processing.
+//! Provides periodic polling for repository updates, deployment queue processing
+//! and canary checks.
 
 use std::sync::Arc;
 use std::time::Duration;
@@ -11,9 +12,13 @@ use tracing::{debug, error, info, warn};
 
 use crate::db::DbPool;
 use crate::services::code_deploy::{CodeDeployConfig, CodeDeployService};
+use crate::services::notification::NotificationService;
+use crate::services::puppetdb::PuppetDbClient;
+use crate::services::puppetdb_registry::PuppetDbRegistry;
+use crate::services::webhooks::Webhooks;
 
 /// Scheduler state
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub struct CodeDeploySchedulerState {
     /// Whether the scheduler is running
     running: Arc<RwLock<bool>>,
@@ -21,18 +26,37 @@ pub struct CodeDeploySchedulerState {
     pool: DbPool,
     /// Code deploy configuration
     config: CodeDeployConfig,
+    /// Announces finished deployments, canaries and credential issues
+    notifications: Arc<NotificationService>,
+    webhooks: Webhooks,
 }
 
 impl CodeDeploySchedulerState {
     /// Create a new scheduler state
-    pub fn new(pool: DbPool, config: CodeDeployConfig) -> Self {
+    pub fn new(
+        pool: DbPool,
+        config: CodeDeployConfig,
+        notifications: Arc<NotificationService>,
+        webhooks: Webhooks,
+    ) -> Self {
         Self {
             running: Arc::new(RwLock::new(false)),
             pool,
             config,
+            notifications,
+            webhooks,
         }
     }
 
+    fn service(&self) -> CodeDeployService {
+        CodeDeployService::new(
+            self.pool.clone(),
+            self.config.clone(),
+            self.notifications.clone(),
+            self.webhooks.clone(),
+        )
+    }
+
     /// Check if the scheduler is running
     pub async fn is_running(&self) -> bool {
         *self.running.read().await
@@ -52,11 +76,18 @@ impl CodeDeploySchedulerState {
 /// - Polling repositories for updates
 /// - Processing the deployment queue
 /// - Cleaning up old deployments
+/// - Checking the health of credentials
+/// - Checking the reports of canary nodes
 pub fn start_code_deploy_scheduler(
     pool: DbPool,
     config: CodeDeployConfig,
+    puppetdb: Option<Arc<PuppetDbClient>>,
+    puppetdb_tenants: PuppetDbRegistry,
+    notifications: Arc<NotificationService>,
+    webhooks: Webhooks,
 ) -> CodeDeploySchedulerState {
-    let state = CodeDeploySchedulerState::new(pool.clone(), config.clone());
+    let state =
+        CodeDeploySchedulerState::new(pool.clone(), config.clone(), notifications, webhooks);
     let state_clone = state.clone();
 
     // Mark as running
@@ -84,6 +115,18 @@ pub fn start_code_deploy_scheduler(
         cleanup_task(cleanup_state).await;
     });
 
+    // Spawn credential health task
+    let credential_state = state.clone();
+    tokio::spawn(async move {
+        credential_health_task(credential_state).await;
+    });
+
+    // Spawn canary task
+    let canary_state = state.clone();
+    tokio::spawn(async move {
+        canary_task(canary_state, puppetdb, puppetdb_tenants).await;
+    });
+
     info!("Code Deploy scheduler started");
     state
 }
@@ -111,7 +154,7 @@ async fn repository_poll_task(state: CodeDeploySchedulerState) {
 
         debug!("Running repository poll cycle");
 
-        let service = CodeDeployService::new(state.pool.clone(), state.config.clone());
+        let service = state.service();
 
         // Get all repositories that need polling
         match service.list_repositories_for_polling().await {
@@ -131,7 +174,7 @@ async fn repository_poll_task(state: CodeDeploySchedulerState) {
                             warn!("Failed to sync repository {}: {}", repo.name, e);
                             // Record the error on the repository
                             if let Err(e2) = service
-                                .record_repository_error(repo.id, &e.to_string())
+                                .record_repository_error(repo.id, &format!("{:#}", e))
                                 .await
                             {
                                 error!("Failed to record repository error: {}", e2);
@@ -170,18 +213,22 @@ async fn deployment_queue_task(state: CodeDeploySchedulerState) {
 
         debug!("Processing deployment queue");
 
-        let service = CodeDeployService::new(state.pool.clone(), state.config.clone());
+        let service = state.service();
 
-        match service.process_deployment_queue().await {
-            Ok(processed) => {
-                if processed > 0 {
-                    info!("Processed {} deployments from queue", processed);
+        // Runs in the background so a long deploy does not hold up other
+        // environments' deployments until it is done
+        tokio::spawn(async move {
+            match service.process_deployment_queue().await {
+                Ok(processed) => {
+                    if processed > 0 {
+                        info!("Processed {} deployments from queue", processed);
+                    }
+                }
+                Err(e) => {
+                    error!("Failed to process deployment queue: {}", e);
                 }
             }
-            Err(e) => {
-                error!("Failed to process deployment queue: {}", e);
-            }
-        }
+        });
     }
 }
 
@@ -208,7 +255,7 @@ async fn cleanup_task(state: CodeDeploySchedulerState) {
 
         debug!("Running cleanup cycle");
 
-        let service = CodeDeployService::new(state.pool.clone(), state.config.clone());
+        let service = state.service();
 
         match service.cleanup_old_deployments().await {
             Ok(deleted) => {
@@ -223,6 +270,93 @@ async fn cleanup_task(state: CodeDeploySchedulerState) {
     }
 }
 
+/// Credential health task
+///
+/// Periodically notifies operators of expiring PAT tokens, repositories that
+/// cannot authenticate and SSH keys due for rotation.
+async fn credential_health_task(state: CodeDeploySchedulerState) {
+    // Check credentials once per hour
+    let check_interval = Duration::from_secs(3600);
+    let mut interval_timer = interval(check_interval);
+
+    info!(
+        "Credential health task started (interval: {}s)",
+        check_interval.as_secs()
+    );
+
+    loop {
+        interval_timer.tick().await;
+
+        if !*state.running.read().await {
+            info!("Credential health task stopping");
+            break;
+        }
+
+        debug!("Checking credential health");
+
+        let service = state.service();
+
+        match service.notify_credential_issues().await {
+            Ok(report) => {
+                if report.summary.critical > 0 {
+                    warn!(
+                        "{} Code Deploy credentials or repositories are unhealthy",
+                        report.summary.critical
+                    );
+                }
+            }
+            Err(e) => {
+                error!("Failed to check credential health: {}", e);
+            }
+        }
+    }
+}
+
+/// Canary task
+///
+/// Checks the reports of canary nodes, then passes or fails their canaries.
+async fn canary_task(
+    state: CodeDeploySchedulerState,
+    puppetdb: Option<Arc<PuppetDbClient>>,
+    puppetdb_tenants: PuppetDbRegistry,
+) {
+    // Check canaries every minute
+    let check_interval = Duration::from_secs(60);
+    let mut interval_timer = interval(check_interval);
+
+    info!(
+        "Canary task started (interval: {}s)",
+        check_interval.as_secs()
+    );
+
+    loop {
+        interval_timer.tick().await;
+
+        if !*state.running.read().await {
+            info!("Canary task stopping");
+            break;
+        }
+
+        debug!("Checking canaries");
+
+        let service = state.service();
+
+        match service
+            .check_canaries(&puppetdb_tenants, puppetdb.clone())
+            .await
+        {
+            Ok(completed) => {
+                if completed > 0 {
+                    info!("{} canaries completed", completed);
+                }
+            }
+            Err(e) => {
+                error!("Failed to check canaries: {}", e);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     #[allow(unused_imports)]
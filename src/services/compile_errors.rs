@@ -0,0 +1,396 @@
+//! Catalog compilation errors across the fleet
+//!
+//! An agent whose catalog fails to compile logs the server error, e.g.
+//! "Could not retrieve catalog from remote server: Error 500 on SERVER:
+//! Server Error: Evaluation Error: ... Could not find class ::profile::web
+//! for web01 (file: .../site.pp, line: 5, column: 3) on node web01". The
+//! error is reduced to a signature without node names or code locations, so
+//! one code mistake breaking many nodes shows up as a single entry.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::models::{
+    CompileErrorAnalytics, CompileErrorGroup, CompileErrorKind, PuppetDbDataRef, ReportLog,
+};
+use crate::services::deployment_impact::pql_string;
+use crate::services::puppetdb::PuppetDbClient;
+
+/// Nodes listed per error
+pub const NODE_SAMPLE: usize = 20;
+
+const CATALOG_FAILURE: &str = "Could not retrieve catalog from remote server: ";
+
+static LOCATION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:\s+at)?\s*\((?:file: ([^,)]+), )?line: (\d+)[^)]*\)").unwrap());
+static ON_NODE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+on node \S+").unwrap());
+static MISSING_CLASS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"Could not find class (?:::)?([\w:]+)").unwrap());
+static DUPLICATE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"Duplicate declaration: (\S+?\[[^\]]*\])").unwrap());
+static HIERA_LOOKUP: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?:did not find a value for the name|Lookup of key) '([^']+)'").unwrap()
+});
+
+#[derive(Debug, Deserialize)]
+struct FailedReport {
+    certname: String,
+    environment: Option<String>,
+    end_time: Option<DateTime<Utc>>,
+    logs: Option<PuppetDbDataRef>,
+}
+
+/// A compilation error parsed from a report log
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompileError {
+    pub kind: CompileErrorKind,
+    pub signature: String,
+    /// Server error, without the agent's prefix
+    pub message: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+}
+
+/// Parse the compilation error of a failed catalog request logged by the
+/// agent of `certname`
+pub fn parse_compile_error(message: &str, certname: &str) -> Option<CompileError> {
+    let (_, detail) = message.split_once(CATALOG_FAILURE)?;
+    let detail = match detail.rfind("Server Error: ") {
+        Some(pos) => &detail[pos + "Server Error: ".len()..],
+        None => detail,
+    };
+    let detail = detail.trim();
+
+    // The innermost location is the last one given
+    let location = LOCATION.captures_iter(detail).last();
+    let file = location
+        .as_ref()
+        .and_then(|c| c.get(1))
+        .map(|m| m.as_str().to_string());
+    let line = location
+        .as_ref()
+        .and_then(|c| c.get(2))
+        .and_then(|m| m.as_str().parse().ok());
+
+    let (kind, signature) = if let Some(c) = MISSING_CLASS.captures(detail) {
+        (
+            CompileErrorKind::MissingClass,
+            format!("Could not find class {}", &c[1]),
+        )
+    } else if let Some(c) = DUPLICATE.captures(detail) {
+        (
+            CompileErrorKind::DuplicateDeclaration,
+            format!("Duplicate declaration: {}", &c[1]),
+        )
+    } else if let Some(c) = HIERA_LOOKUP.captures(detail) {
+        (
+            CompileErrorKind::HieraLookup,
+            format!("Hiera lookup found no value for '{}'", &c[1]),
+        )
+    } else if detail.contains("Syntax error") || detail.contains("Could not parse") {
+        let signature = match &file {
+            Some(file) => format!("Syntax error in {}", file),
+            None => "Syntax error".to_string(),
+        };
+        (CompileErrorKind::SyntaxError, signature)
+    } else {
+        let generic = LOCATION.replace_all(detail, "");
+        let generic = ON_NODE.replace_all(&generic, "");
+        let generic = if certname.is_empty() {
+            generic.to_string()
+        } else {
+            generic.replace(certname, "<node>")
+        };
+        (CompileErrorKind::Other, generic.trim().to_string())
+    };
+
+    Some(CompileError {
+        kind,
+        signature,
+        message: detail.to_string(),
+        file,
+        line,
+    })
+}
+
+/// Compilation errors of the failed reports of the last `days` days, the
+/// `limit` most widespread first
+pub async fn compile_error_analytics(
+    puppetdb: &PuppetDbClient,
+    days: u32,
+    limit: usize,
+) -> Result<CompileErrorAnalytics> {
+    let since = Utc::now() - chrono::Duration::days(days as i64);
+    let pql = format!(
+        r#"reports[certname, environment, end_time, logs] {{ status = "failed" and end_time >= {} }}"#,
+        pql_string(&since.to_rfc3339())
+    );
+    let reports: Vec<FailedReport> = puppetdb.query(&pql).await?;
+    Ok(aggregate_compile_errors(&reports, days, limit))
+}
+
+fn aggregate_compile_errors(
+    reports: &[FailedReport],
+    days: u32,
+    limit: usize,
+) -> CompileErrorAnalytics {
+    let mut groups: HashMap<String, CompileErrorGroup> = HashMap::new();
+    let mut nodes: HashMap<String, BTreeSet<String>> = HashMap::new();
+    let mut environments: HashMap<String, BTreeSet<String>> = HashMap::new();
+    let mut compile_failures = 0;
+    let mut nodes_affected = HashSet::new();
+
+    for report in reports {
+        let logs = report.logs.as_ref().and_then(|l| l.data.as_ref());
+        let mut seen = HashSet::new();
+        for value in logs.into_iter().flatten() {
+            let Ok(log) = serde_json::from_value::<ReportLog>(value.clone()) else {
+                continue;
+            };
+            if !log.is_error() {
+                continue;
+            }
+            let Some(error) = parse_compile_error(&log.message, &report.certname) else {
+                continue;
+            };
+            // Count each error once per report
+            if !seen.insert(error.signature.clone()) {
+                continue;
+            }
+
+            let group =
+                groups
+                    .entry(error.signature.clone())
+                    .or_insert_with(|| CompileErrorGroup {
+                        signature: error.signature.clone(),
+                        kind: error.kind,
+                        message: error.message.clone(),
+                        file: error.file.clone(),
+                        line: error.line,
+                        occurrences: 0,
+                        node_count: 0,
+                        nodes: Vec::new(),
+                        environments: Vec::new(),
+                        first_seen: report.end_time,
+                        last_seen: report.end_time,
+                    });
+            group.occurrences += 1;
+            if let Some(end_time) = report.end_time {
+                if group.first_seen.is_none_or(|first| end_time < first) {
+                    group.first_seen = Some(end_time);
+                }
+            }
+            if report.end_time > group.last_seen {
+                group.last_seen = report.end_time;
+                group.message = error.message;
+                group.file = error.file;
+                group.line = error.line;
+            }
+            nodes
+                .entry(error.signature.clone())
+                .or_default()
+                .insert(report.certname.clone());
+            if let Some(environment) = &report.environment {
+                environments
+                    .entry(error.signature)
+                    .or_default()
+                    .insert(environment.clone());
+            }
+        }
+        if !seen.is_empty() {
+            compile_failures += 1;
+            nodes_affected.insert(report.certname.as_str());
+        }
+    }
+
+    let mut errors: Vec<CompileErrorGroup> = groups
+        .into_values()
+        .map(|mut group| {
+            let group_nodes = nodes.remove(&group.signature).unwrap_or_default();
+            group.node_count = group_nodes.len() as i64;
+            group.nodes = group_nodes.into_iter().take(NODE_SAMPLE).collect();
+            group.environments = environments
+                .remove(&group.signature)
+                .unwrap_or_default()
+                .into_iter()
+                .collect();
+            group
+        })
+        .collect();
+    errors.sort_by(|a, b| {
+        b.node_count
+            .cmp(&a.node_count)
+            .then(b.occurrences.cmp(&a.occurrences))
+            .then_with(|| a.signature.cmp(&b.signature))
+    });
+    let total_signatures = errors.len() as i64;
+    errors.truncate(limit);
+
+    CompileErrorAnalytics {
+        generated_at: Utc::now(),
+        days,
+        failed_reports: reports.len() as i64,
+        compile_failures,
+        nodes_affected: nodes_affected.len() as i64,
+        total_signatures,
+        errors,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn catalog_failure(error: &str, certname: &str) -> String {
+        format!(
+            "Could not retrieve catalog from remote server: Error 500 on SERVER: Server Error: {} on node {}",
+            error, certname
+        )
+    }
+
+    #[test]
+    fn test_parse_compile_error() {
+        let error = parse_compile_error(
+            &catalog_failure(
+                "Evaluation Error: Error while evaluating a Function Call, Could not find class ::profile::web for web01.example.com (file: /etc/puppetlabs/code/environments/production/manifests/site.pp, line: 5, column: 3)",
+                "web01.example.com",
+            ),
+            "web01.example.com",
+        )
+        .unwrap();
+        assert_eq!(error.kind, CompileErrorKind::MissingClass);
+        assert_eq!(error.signature, "Could not find class profile::web");
+        assert_eq!(
+            error.file.as_deref(),
+            Some("/etc/puppetlabs/code/environments/production/manifests/site.pp")
+        );
+        assert_eq!(error.line, Some(5));
+
+        let error = parse_compile_error(
+            &catalog_failure(
+                "Evaluation Error: Error while evaluating a Resource Statement, Duplicate declaration: Package[nginx] is already declared at (file: /etc/puppetlabs/code/modules/nginx/manifests/init.pp, line: 10); cannot redeclare (file: /etc/puppetlabs/code/modules/profile/manifests/web.pp, line: 4) (file: /etc/puppetlabs/code/modules/profile/manifests/web.pp, line: 4, column: 3)",
+                "web02",
+            ),
+            "web02",
+        )
+        .unwrap();
+        assert_eq!(error.kind, CompileErrorKind::DuplicateDeclaration);
+        assert_eq!(error.signature, "Duplicate declaration: Package[nginx]");
+        assert_eq!(error.line, Some(4));
+
+        let error = parse_compile_error(
+            &catalog_failure(
+                "Function lookup() did not find a value for the name 'profile::db::password' (file: /etc/puppetlabs/code/modules/profile/manifests/db.pp, line: 2, column: 14)",
+                "db01",
+            ),
+            "db01",
+        )
+        .unwrap();
+        assert_eq!(error.kind, CompileErrorKind::HieraLookup);
+        assert_eq!(
+            error.signature,
+            "Hiera lookup found no value for 'profile::db::password'"
+        );
+
+        let error = parse_compile_error(
+            &catalog_failure(
+                "Could not parse for environment production: Syntax error at '}' (file: /etc/puppetlabs/code/environments/production/manifests/site.pp, line: 12, column: 1)",
+                "db01",
+            ),
+            "db01",
+        )
+        .unwrap();
+        assert_eq!(error.kind, CompileErrorKind::SyntaxError);
+        assert_eq!(
+            error.signature,
+            "Syntax error in /etc/puppetlabs/code/environments/production/manifests/site.pp"
+        );
+
+        // Node names and locations are left out of other signatures
+        let error = parse_compile_error(
+            &catalog_failure(
+                "Evaluation Error: Unknown variable: '::role' for app01 (file: /tmp/site.pp, line: 1, column: 1)",
+                "app01",
+            ),
+            "app01",
+        )
+        .unwrap();
+        assert_eq!(error.kind, CompileErrorKind::Other);
+        assert_eq!(
+            error.signature,
+            "Evaluation Error: Unknown variable: '::role' for <node>"
+        );
+
+        assert!(parse_compile_error("Could not retrieve catalog; skipping run", "app01").is_none());
+    }
+
+    #[test]
+    fn test_aggregate_compile_errors() {
+        let report = |certname: &str, end_time: &str, message: &str| FailedReport {
+            certname: certname.to_string(),
+            environment: Some("production".to_string()),
+            end_time: Some(end_time.parse().unwrap()),
+            logs: Some(PuppetDbDataRef {
+                data: Some(vec![
+                    json!({"level": "err", "message": message, "source": "Puppet"}),
+                    json!({"level": "err", "message": "Could not retrieve catalog; skipping run", "source": "Puppet"}),
+                ]),
+                href: None,
+            }),
+        };
+        let missing_class = |certname: &str| {
+            catalog_failure(
+                &format!("Could not find class ::profile::web for {}", certname),
+                certname,
+            )
+        };
+
+        let reports = vec![
+            report("web01", "2026-10-15T10:00:00Z", &missing_class("web01")),
+            report("web01", "2026-10-15T11:00:00Z", &missing_class("web01")),
+            report("web02", "2026-10-15T12:00:00Z", &missing_class("web02")),
+            report(
+                "db01",
+                "2026-10-15T12:30:00Z",
+                &catalog_failure(
+                    "Function lookup() did not find a value for the name 'db::password'",
+                    "db01",
+                ),
+            ),
+            report(
+                "app01",
+                "2026-10-15T13:00:00Z",
+                "Could not apply complete catalog",
+            ),
+        ];
+
+        let analytics = aggregate_compile_errors(&reports, 7, 10);
+        assert_eq!(analytics.failed_reports, 5);
+        assert_eq!(analytics.compile_failures, 4);
+        assert_eq!(analytics.nodes_affected, 3);
+        assert_eq!(analytics.total_signatures, 2);
+
+        let top = &analytics.errors[0];
+        assert_eq!(top.signature, "Could not find class profile::web");
+        assert_eq!(top.occurrences, 3);
+        assert_eq!(top.node_count, 2);
+        assert_eq!(top.nodes, ["web01", "web02"]);
+        assert_eq!(top.environments, ["production"]);
+        assert_eq!(
+            top.first_seen,
+            Some("2026-10-15T10:00:00Z".parse().unwrap())
+        );
+        assert_eq!(top.last_seen, Some("2026-10-15T12:00:00Z".parse().unwrap()));
+        assert_eq!(analytics.errors[1].kind, CompileErrorKind::HieraLookup);
+
+        let analytics = aggregate_compile_errors(&reports, 7, 1);
+        assert_eq!(analytics.errors.len(), 1);
+        assert_eq!(analytics.total_signatures, 2);
+    }
+}
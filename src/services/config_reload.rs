@@ -0,0 +1,483 @@
+//! Hot reload of configuration
+//!
+//! The settings that are safe to change while running (the log level, the
+//! dashboard, cache and rate limit settings and the custom RBAC roles) are
+//! applied again when the configuration file or groups.yaml changes, or on
+//! `POST /api/v1/settings/reload`. Other changed settings are reported as
+//! needing a restart and keep their running value. A file that fails to
+//! parse or validate is rejected as a whole, so a half-edited file never
+//! takes effect.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use tokio::sync::{Mutex, RwLock};
+use tokio::time::interval;
+use tracing::{error, info, warn};
+
+use crate::config::{AppConfig, GroupsConfig, HotReloadConfig, RoleDefinition};
+use crate::db::{AuditRepository, DbPool};
+use crate::middleware::{RateLimitConfig, RateLimitState};
+use crate::models::{default_organization_uuid, CreatePermissionRequest, CreateRoleRequest};
+use crate::services::audit_forwarding::AuditForwarder;
+use crate::services::rbac_db::{db_to_scope, parse_action, parse_resource};
+use crate::services::DbRbacService;
+
+/// Settings applied at runtime; everything else needs a restart
+pub const RELOADABLE_SETTINGS: &[&str] = &[
+    "logging.level",
+    "dashboard",
+    "cache",
+    "rate_limit",
+    "rbac.roles",
+];
+
+/// Changes the log filter of the running process
+pub type LogLevelSetter = Arc<dyn Fn(&str) -> Result<()> + Send + Sync>;
+
+/// Whether a dotted setting path is applied by a reload
+pub fn is_reloadable(path: &str) -> bool {
+    RELOADABLE_SETTINGS.iter().any(|setting| {
+        path == *setting
+            || path
+                .strip_prefix(setting)
+                .is_some_and(|rest| rest.starts_with('.'))
+    })
+}
+
+/// Which rate limit a limiter enforces
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitKind {
+    Api,
+    Auth,
+    Expensive,
+}
+
+impl RateLimitKind {
+    fn config(self, config: &AppConfig) -> RateLimitConfig {
+        match self {
+            RateLimitKind::Api => config.rate_limit.api.clone(),
+            RateLimitKind::Auth => config.rate_limit.auth.clone(),
+            RateLimitKind::Expensive => config.rate_limit.expensive.clone(),
+        }
+    }
+}
+
+/// Outcome of a reload
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReloadResult {
+    /// Configuration file that was read, if any
+    pub config_file: Option<PathBuf>,
+    /// Node groups file that was read, if any
+    pub groups_file: Option<PathBuf>,
+    /// Settings that changed and were applied
+    pub applied: Vec<String>,
+    /// Settings that differ from the running values until a restart
+    pub restart_required: Vec<String>,
+    /// Node group definitions in the groups file
+    pub groups: usize,
+    /// Changes that could not be applied
+    pub warnings: Vec<String>,
+}
+
+impl ReloadResult {
+    /// Whether the reload applied anything or found pending changes
+    pub fn has_changes(&self) -> bool {
+        !self.applied.is_empty() || !self.restart_required.is_empty()
+    }
+}
+
+/// Applies reloadable settings to the running server
+#[derive(Clone)]
+pub struct ConfigReloader {
+    /// Configuration read at startup, before secrets were resolved
+    startup: Arc<AppConfig>,
+    /// Configuration last read from disk; the lock serializes reloads
+    file: Arc<Mutex<AppConfig>>,
+    /// Running configuration with the reloaded settings applied
+    live: Arc<std::sync::RwLock<Arc<AppConfig>>>,
+    /// Node group definitions from groups.yaml
+    groups: Arc<std::sync::RwLock<Option<Arc<GroupsConfig>>>>,
+    rate_limits: Arc<std::sync::Mutex<Vec<(RateLimitKind, RateLimitState)>>>,
+    pool: DbPool,
+    rbac_db: Arc<DbRbacService>,
+    /// Forwards the audit entries of reloads triggered by file changes
+    audit_forwarder: Option<Arc<AuditForwarder>>,
+    log_level_setter: Option<LogLevelSetter>,
+}
+
+impl ConfigReloader {
+    /// `file_config` is the configuration as read from disk and `live` the
+    /// running configuration, with secrets resolved
+    pub fn new(
+        file_config: AppConfig,
+        live: AppConfig,
+        pool: DbPool,
+        rbac_db: Arc<DbRbacService>,
+        audit_forwarder: Option<Arc<AuditForwarder>>,
+    ) -> Self {
+        let groups = live
+            .groups_file_path()
+            .and_then(|path| match GroupsConfig::load(&path) {
+                Ok(groups) => Some(Arc::new(groups)),
+                Err(e) => {
+                    warn!("{:#}", e);
+                    None
+                }
+            });
+
+        Self {
+            startup: Arc::new(file_config.clone()),
+            file: Arc::new(Mutex::new(file_config)),
+            live: Arc::new(std::sync::RwLock::new(Arc::new(live))),
+            groups: Arc::new(std::sync::RwLock::new(groups)),
+            rate_limits: Arc::new(std::sync::Mutex::new(Vec::new())),
+            pool,
+            rbac_db,
+            audit_forwarder,
+            log_level_setter: None,
+        }
+    }
+
+    /// Apply `logging.level` changes through `setter`
+    ///
+    /// Not set when `RUST_LOG` sets the filter, which then stays fixed.
+    pub fn with_log_level_setter(mut self, setter: LogLevelSetter) -> Self {
+        self.log_level_setter = Some(setter);
+        self
+    }
+
+    /// The running configuration
+    pub fn current(&self) -> Arc<AppConfig> {
+        self.live.read().unwrap().clone()
+    }
+
+    /// The node group definitions from groups.yaml, if there is one
+    pub fn groups(&self) -> Option<Arc<GroupsConfig>> {
+        self.groups.read().unwrap().clone()
+    }
+
+    /// Create a rate limiter that follows the configured limit
+    pub fn rate_limit_state(&self, kind: RateLimitKind) -> RateLimitState {
+        let state = RateLimitState::new(kind.config(&self.current()));
+        self.rate_limits.lock().unwrap().push((kind, state.clone()));
+        state
+    }
+
+    /// Read the configuration files again and apply the reloadable settings
+    pub async fn reload(&self) -> Result<ReloadResult> {
+        let mut previous = self.file.lock().await;
+
+        let config = AppConfig::reload().context("Configuration not reloaded")?;
+        let groups_file = config.groups_file_path();
+        let groups = groups_file
+            .as_ref()
+            .map(GroupsConfig::load)
+            .transpose()
+            .context("Configuration not reloaded")?;
+
+        let mut result = ReloadResult {
+            config_file: AppConfig::config_file_path().filter(|path| path.exists()),
+            groups_file,
+            groups: groups.as_ref().map_or(0, |g| g.groups.len()),
+            restart_required: self
+                .startup
+                .changed_settings(&config)
+                .into_iter()
+                .filter(|path| !is_reloadable(path))
+                .collect(),
+            ..Default::default()
+        };
+        *self.groups.write().unwrap() = groups.map(Arc::new);
+
+        let changed = previous.changed_settings(&config);
+        let section_changed = |section: &str| {
+            changed
+                .iter()
+                .any(|path| path == section || path.starts_with(&format!("{}.", section)))
+        };
+        let mut live = (*self.current()).clone();
+
+        if section_changed("logging.level") {
+            match &self.log_level_setter {
+                Some(set_level) => match set_level(config.logging.level.as_str()) {
+                    Ok(()) => {
+                        live.logging.level = config.logging.level.clone();
+                        result.applied.push("logging.level".to_string());
+                    }
+                    Err(e) => result
+                        .warnings
+                        .push(format!("logging.level not applied: {:#}", e)),
+                },
+                None => result
+                    .warnings
+                    .push("logging.level not applied: RUST_LOG sets the log filter".to_string()),
+            }
+        }
+        if section_changed("dashboard") {
+            live.dashboard = config.dashboard.clone();
+            result.applied.push("dashboard".to_string());
+        }
+        if section_changed("cache") {
+            live.cache = config.cache.clone();
+            result.applied.push("cache".to_string());
+        }
+        if section_changed("rate_limit") {
+            live.rate_limit = config.rate_limit.clone();
+            let limiters = self.rate_limits.lock().unwrap().clone();
+            for (kind, state) in limiters {
+                state.set_config(kind.config(&live)).await;
+            }
+            result.applied.push("rate_limit".to_string());
+        }
+        if section_changed("rbac.roles") {
+            live.rbac.roles = config.rbac.roles.clone();
+            result
+                .warnings
+                .extend(self.sync_roles(&live.rbac.roles).await);
+            result.applied.push("rbac.roles".to_string());
+        }
+
+        *self.live.write().unwrap() = Arc::new(live);
+        *previous = config;
+
+        if !result.applied.is_empty() {
+            info!("Configuration reloaded: {}", result.applied.join(", "));
+        }
+        if !result.restart_required.is_empty() {
+            warn!(
+                "Changed settings need a restart: {}",
+                result.restart_required.join(", ")
+            );
+        }
+        for warning in &result.warnings {
+            warn!("{}", warning);
+        }
+
+        Ok(result)
+    }
+
+    /// Create or update the roles defined under `rbac.roles`
+    ///
+    /// Roles removed from the configuration are kept, since users may still
+    /// hold them. Returns a message for each role that could not be applied.
+    pub async fn sync_roles(&self, roles: &[RoleDefinition]) -> Vec<String> {
+        let mut failures = Vec::new();
+        for definition in roles {
+            if let Err(e) = self.sync_role(definition).await {
+                failures.push(format!("Role '{}' not applied: {:#}", definition.name, e));
+            }
+        }
+        failures
+    }
+
+    async fn sync_role(&self, definition: &RoleDefinition) -> Result<()> {
+        let request = role_request(definition)?;
+        match self.rbac_db.get_role_by_name(&definition.name).await? {
+            Some(role) if role.is_system => {
+                anyhow::bail!("a built-in role cannot be redefined")
+            }
+            Some(role) => {
+                let permissions = request.permissions.clone().unwrap_or_default();
+                let request = CreateRoleRequest {
+                    parent_ids: Some(role.parent_ids.clone()),
+                    permissions: None,
+                    ..request
+                };
+                self.rbac_db.update_role(&role.id, request).await?;
+                self.rbac_db
+                    .set_role_permissions(&role.id, permissions)
+                    .await?;
+            }
+            None => {
+                self.rbac_db.create_role(request).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Record a reload triggered by a file change in the audit log
+    async fn audit_file_reload(&self, result: &ReloadResult) {
+        let _ = AuditRepository::new(&self.pool, self.audit_forwarder.as_deref())
+            .insert(
+                default_organization_uuid(),
+                None,
+                "settings.reload",
+                "settings",
+                None,
+                Some(&serde_json::json!({
+                    "trigger": "file_change",
+                    "applied": result.applied,
+                    "restart_required": result.restart_required,
+                    "warnings": result.warnings,
+                })),
+                None,
+            )
+            .await;
+    }
+}
+
+/// The role and permissions a role definition describes
+fn role_request(definition: &RoleDefinition) -> Result<CreateRoleRequest> {
+    let permissions = definition
+        .permissions
+        .iter()
+        .map(|permission| {
+            Ok(CreatePermissionRequest {
+                resource: parse_resource(&permission.resource)?,
+                action: parse_action(&permission.action)?,
+                scope: Some(db_to_scope(
+                    &permission.scope,
+                    permission.scope_value.clone(),
+                )?),
+                constraint: None,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(CreateRoleRequest {
+        name: definition.name.clone(),
+        display_name: definition
+            .display_name
+            .clone()
+            .unwrap_or_else(|| definition.name.clone()),
+        description: definition.description.clone(),
+        parent_id: None,
+        parent_ids: None,
+        permissions: Some(permissions),
+    })
+}
+
+/// Handle for stopping the configuration file watcher
+#[derive(Clone)]
+pub struct ConfigWatcherState {
+    running: Arc<RwLock<bool>>,
+}
+
+impl ConfigWatcherState {
+    pub async fn is_running(&self) -> bool {
+        *self.running.read().await
+    }
+
+    /// Request the watcher to stop at its next tick
+    pub async fn stop(&self) {
+        *self.running.write().await = false;
+        info!("Configuration watcher stop requested");
+    }
+}
+
+/// Spawn the configuration file watcher if watching is enabled
+pub fn start_config_watcher(
+    reloader: ConfigReloader,
+    config: &HotReloadConfig,
+) -> Option<ConfigWatcherState> {
+    if !config.watch {
+        return None;
+    }
+    let state = ConfigWatcherState {
+        running: Arc::new(RwLock::new(true)),
+    };
+    let poll_interval = Duration::from_secs(config.poll_interval_secs.max(1));
+
+    let loop_state = state.clone();
+    tokio::spawn(async move {
+        watch_loop(loop_state, reloader, poll_interval).await;
+    });
+
+    info!(
+        "Watching configuration files for changes every {}s",
+        poll_interval.as_secs()
+    );
+    Some(state)
+}
+
+/// Modification times of the configuration and groups files
+fn file_versions(reloader: &ConfigReloader) -> Vec<Option<SystemTime>> {
+    [
+        AppConfig::config_file_path(),
+        reloader.current().groups_file_path(),
+    ]
+    .iter()
+    .map(|path| {
+        path.as_ref()
+            .and_then(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+    })
+    .collect()
+}
+
+async fn watch_loop(state: ConfigWatcherState, reloader: ConfigReloader, poll_interval: Duration) {
+    let mut timer = interval(poll_interval);
+    let mut versions = file_versions(&reloader);
+
+    loop {
+        timer.tick().await;
+
+        if !*state.running.read().await {
+            info!("Configuration watcher stopping");
+            break;
+        }
+
+        let current = file_versions(&reloader);
+        if current == versions {
+            continue;
+        }
+        versions = current;
+
+        match reloader.reload().await {
+            Ok(result) if result.has_changes() => reloader.audit_file_reload(&result).await,
+            Ok(_) => {}
+            Err(e) => error!("{:#}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::PermissionDefinition;
+    use crate::models::Scope;
+
+    #[test]
+    fn test_is_reloadable() {
+        assert!(is_reloadable("logging.level"));
+        assert!(is_reloadable("dashboard.theme"));
+        assert!(is_reloadable("rate_limit.api.burst_size"));
+        assert!(is_reloadable("rbac.roles"));
+        assert!(!is_reloadable("logging.format"));
+        assert!(!is_reloadable("rbac.default_role"));
+        assert!(!is_reloadable("cache_dir"));
+        assert!(!is_reloadable("server.port"));
+    }
+
+    #[test]
+    fn test_role_request() {
+        let definition = RoleDefinition {
+            name: "deployer".to_string(),
+            display_name: None,
+            description: Some("Deploys code".to_string()),
+            is_system: false,
+            permissions: vec![PermissionDefinition {
+                resource: "nodes".to_string(),
+                action: "read".to_string(),
+                scope: "environment".to_string(),
+                scope_value: Some("production".to_string()),
+            }],
+        };
+
+        let request = role_request(&definition).unwrap();
+        assert_eq!(request.display_name, "deployer");
+        let permissions = request.permissions.unwrap();
+        assert_eq!(permissions.len(), 1);
+        assert_eq!(
+            permissions[0].scope,
+            Some(Scope::Environment("production".to_string()))
+        );
+
+        let mut invalid = definition.clone();
+        invalid.permissions[0].action = "teleport".to_string();
+        assert!(role_request(&invalid).is_err());
+    }
+}
@@ -0,0 +1,454 @@
+//! Code Deploy credential health
+//!
+//! Assesses PAT tokens, SSH keys and the authentication of repositories, and
+//! notifies operators of repositories that cannot authenticate and SSH keys
+//! due for rotation. Expiring PAT tokens are notified by
+//! [`CodeDeployService::notify_expiring_pat_tokens`](crate::services::code_deploy::CodeDeployService::notify_expiring_pat_tokens).
+
+use chrono::{DateTime, Utc};
+
+use crate::models::{
+    AuthType, CodePatToken, CodeRepository, CodeSshKey, CredentialHealthReport,
+    CredentialHealthStatus, CredentialHealthSummary, NewNotification, NotificationAudience,
+    NotificationType, PatTokenHealth, RepositoryAuthHealth, SshKeyHealth,
+};
+use crate::services::notification::NotificationService;
+
+/// Fragments of Git errors caused by rejected or missing credentials
+const AUTH_FAILURE_MARKERS: &[&str] = &[
+    "code=auth",
+    "authentication",
+    "permission denied",
+    "publickey",
+    "status code: 401",
+    "status code: 403",
+];
+
+/// Thresholds credentials are assessed against
+#[derive(Debug, Clone, Copy)]
+pub struct CredentialHealthThresholds {
+    /// Days before expiration a PAT token is reported as expiring
+    pub pat_token_warning_days: i64,
+    /// Age in days after which an SSH key should be rotated (0 = never)
+    pub ssh_key_max_age_days: i64,
+}
+
+/// Whether a repository sync error was caused by its credentials
+pub fn is_auth_failure(error: &str) -> bool {
+    let error = error.to_lowercase();
+    AUTH_FAILURE_MARKERS
+        .iter()
+        .any(|marker| error.contains(marker))
+}
+
+/// Assess PAT tokens, SSH keys and the authentication of repositories
+pub fn assess(
+    tokens: &[CodePatToken],
+    keys: &[CodeSshKey],
+    repositories: &[CodeRepository],
+    thresholds: &CredentialHealthThresholds,
+    now: DateTime<Utc>,
+) -> CredentialHealthReport {
+    let pat_tokens: Vec<PatTokenHealth> = tokens
+        .iter()
+        .map(|token| assess_pat_token(token, repositories, thresholds, now))
+        .collect();
+    let ssh_keys: Vec<SshKeyHealth> = keys
+        .iter()
+        .map(|key| assess_ssh_key(key, repositories, thresholds, now))
+        .collect();
+    let repositories: Vec<RepositoryAuthHealth> = repositories
+        .iter()
+        .map(|repository| assess_repository(repository, &pat_tokens, &ssh_keys))
+        .collect();
+
+    let statuses = pat_tokens
+        .iter()
+        .map(|t| t.status)
+        .chain(ssh_keys.iter().map(|k| k.status))
+        .chain(repositories.iter().map(|r| r.status));
+    let mut summary = CredentialHealthSummary::default();
+    let mut status = CredentialHealthStatus::Healthy;
+    for s in statuses {
+        match s {
+            CredentialHealthStatus::Healthy => summary.healthy += 1,
+            CredentialHealthStatus::Warning => summary.warning += 1,
+            CredentialHealthStatus::Critical => summary.critical += 1,
+        }
+        status = status.max(s);
+    }
+
+    CredentialHealthReport {
+        status,
+        summary,
+        pat_tokens,
+        ssh_keys,
+        repositories,
+        generated_at: now,
+    }
+}
+
+fn assess_pat_token(
+    token: &CodePatToken,
+    repositories: &[CodeRepository],
+    thresholds: &CredentialHealthThresholds,
+    now: DateTime<Utc>,
+) -> PatTokenHealth {
+    let repository_count = repositories
+        .iter()
+        .filter(|r| r.auth_type == AuthType::Pat && r.pat_token_id == Some(token.id))
+        .count();
+
+    let mut status = CredentialHealthStatus::Healthy;
+    let mut issues = Vec::new();
+    let days_until_expiration = token.expires_at.map(|expires_at| {
+        let days = expires_at.signed_duration_since(now).num_days();
+        if expires_at <= now {
+            status = CredentialHealthStatus::Critical;
+            issues.push(format!("Expired on {}", expires_at.format("%Y-%m-%d")));
+        } else if days <= thresholds.pat_token_warning_days {
+            status = CredentialHealthStatus::Warning;
+            issues.push(format!(
+                "Expires on {} ({} days left)",
+                expires_at.format("%Y-%m-%d"),
+                days
+            ));
+        }
+        days
+    });
+
+    PatTokenHealth {
+        id: token.id,
+        name: token.name.clone(),
+        status,
+        issues,
+        expires_at: token.expires_at,
+        days_until_expiration,
+        last_validated_at: token.last_validated_at,
+        repository_count,
+    }
+}
+
+fn assess_ssh_key(
+    key: &CodeSshKey,
+    repositories: &[CodeRepository],
+    thresholds: &CredentialHealthThresholds,
+    now: DateTime<Utc>,
+) -> SshKeyHealth {
+    let repository_count = repositories
+        .iter()
+        .filter(|r| r.auth_type == AuthType::Ssh && r.ssh_key_id == Some(key.id))
+        .count();
+    let age_days = now.signed_duration_since(key.created_at).num_days();
+
+    let mut issues = Vec::new();
+    if thresholds.ssh_key_max_age_days > 0 && age_days >= thresholds.ssh_key_max_age_days {
+        issues.push(format!("Not rotated for {} days", age_days));
+    }
+    if repository_count == 0 {
+        issues.push("Not used by any repository".to_string());
+    }
+    let status = if issues.is_empty() {
+        CredentialHealthStatus::Healthy
+    } else {
+        CredentialHealthStatus::Warning
+    };
+
+    SshKeyHealth {
+        id: key.id,
+        name: key.name.clone(),
+        status,
+        issues,
+        age_days,
+        repository_count,
+    }
+}
+
+fn assess_repository(
+    repository: &CodeRepository,
+    pat_tokens: &[PatTokenHealth],
+    ssh_keys: &[SshKeyHealth],
+) -> RepositoryAuthHealth {
+    let mut critical = Vec::new();
+    let mut warnings = Vec::new();
+
+    match repository.auth_type {
+        AuthType::Ssh => match repository.ssh_key_id {
+            None => critical.push("No SSH key configured".to_string()),
+            Some(id) if !ssh_keys.iter().any(|k| k.id == id) => {
+                critical.push("SSH key no longer exists".to_string())
+            }
+            Some(_) => {}
+        },
+        AuthType::Pat => match repository.pat_token_id {
+            Some(id) => match pat_tokens.iter().find(|t| t.id == id) {
+                None => critical.push("PAT token no longer exists".to_string()),
+                Some(token) if token.status == CredentialHealthStatus::Critical => {
+                    critical.push(format!("PAT token '{}' has expired", token.name))
+                }
+                Some(token) if token.status == CredentialHealthStatus::Warning => {
+                    warnings.push(format!("PAT token '{}' expires soon", token.name))
+                }
+                Some(_) => {}
+            },
+            None if repository.github_pat_encrypted.is_some() => {
+                warnings.push("Uses a deprecated embedded PAT".to_string())
+            }
+            None => critical.push("No PAT token configured".to_string()),
+        },
+        AuthType::None => {}
+    }
+
+    let auth_failed = repository
+        .last_error
+        .as_deref()
+        .is_some_and(is_auth_failure);
+    if auth_failed {
+        critical.push("Last sync failed to authenticate".to_string());
+    } else if repository.last_error.is_some() {
+        warnings.push("Last sync failed".to_string());
+    }
+
+    let status = if !critical.is_empty() {
+        CredentialHealthStatus::Critical
+    } else if !warnings.is_empty() {
+        CredentialHealthStatus::Warning
+    } else {
+        CredentialHealthStatus::Healthy
+    };
+    critical.extend(warnings);
+
+    RepositoryAuthHealth {
+        id: repository.id,
+        name: repository.name.clone(),
+        url: repository.url.clone(),
+        auth_type: repository.auth_type,
+        status,
+        issues: critical,
+        auth_failed,
+        last_error: repository.last_error.clone(),
+        last_error_at: repository.last_error_at,
+    }
+}
+
+/// Notify operators of repositories that cannot authenticate and of SSH keys
+/// due for rotation
+///
+/// A failing repository is notified at most once a day; a key once.
+pub fn notify_issues(notifications: &NotificationService, report: &CredentialHealthReport) {
+    let day = report.generated_at.format("%Y-%m-%d");
+
+    for repository in &report.repositories {
+        if repository.status != CredentialHealthStatus::Critical {
+            continue;
+        }
+        notifications.notify_in_background(
+            NotificationAudience::operators(),
+            NewNotification {
+                organization_id: None,
+                title: format!("Repository '{}' cannot authenticate", repository.name),
+                message: repository.issues.join("; "),
+                r#type: NotificationType::Error,
+                category: Some("code_deploy".to_string()),
+                link: Some("/code-deploy".to_string()),
+                expires_at: None,
+                metadata: Some(serde_json::json!({
+                    "repository_id": repository.id,
+                    "last_error": repository.last_error,
+                })),
+                dedup_key: Some(format!("repository_auth:{}:{}", repository.id, day)),
+            },
+        );
+    }
+
+    for key in &report.ssh_keys {
+        // Unused keys are only reported by the health endpoint
+        if key.status == CredentialHealthStatus::Healthy || key.repository_count == 0 {
+            continue;
+        }
+        notifications.notify_in_background(
+            NotificationAudience::operators(),
+            NewNotification {
+                organization_id: None,
+                title: format!("SSH key '{}' should be rotated", key.name),
+                message: format!(
+                    "The key was created {} days ago and is used by {} repositories",
+                    key.age_days, key.repository_count
+                ),
+                r#type: NotificationType::Warning,
+                category: Some("code_deploy".to_string()),
+                link: Some("/code-deploy".to_string()),
+                expires_at: None,
+                metadata: Some(serde_json::json!({ "ssh_key_id": key.id })),
+                dedup_key: Some(format!("ssh_key_stale:{}", key.id)),
+            },
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DeployStrategy;
+    use chrono::Duration;
+    use uuid::Uuid;
+
+    const THRESHOLDS: CredentialHealthThresholds = CredentialHealthThresholds {
+        pat_token_warning_days: 30,
+        ssh_key_max_age_days: 365,
+    };
+
+    fn token(expires_in_days: Option<i64>) -> CodePatToken {
+        let now = Utc::now();
+        CodePatToken {
+            id: Uuid::new_v4(),
+            name: "deploy".to_string(),
+            description: None,
+            username: None,
+            token_encrypted: String::new(),
+            expires_at: expires_in_days.map(|d| now + Duration::days(d) + Duration::hours(1)),
+            last_validated_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    fn key(age_days: i64) -> CodeSshKey {
+        let created_at = Utc::now() - Duration::days(age_days);
+        CodeSshKey {
+            id: Uuid::new_v4(),
+            name: "control-repo".to_string(),
+            public_key: "ssh-ed25519 AAAA".to_string(),
+            private_key_encrypted: String::new(),
+            created_at,
+            updated_at: created_at,
+        }
+    }
+
+    fn repository(auth_type: AuthType) -> CodeRepository {
+        let now = Utc::now();
+        CodeRepository {
+            id: Uuid::new_v4(),
+            name: "control".to_string(),
+            url: "git@example.com:puppet/control.git".to_string(),
+            branch_pattern: "*".to_string(),
+            auth_type,
+            ssh_key_id: None,
+            pat_token_id: None,
+            github_pat_encrypted: None,
+            deploy_strategy: DeployStrategy::R10k,
+            webhook_secret: None,
+            poll_interval_seconds: 300,
+            is_control_repo: true,
+            last_error: None,
+            last_error_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_auth_failures_are_recognized() {
+        assert!(is_auth_failure(
+            "Failed to fetch from remote: remote authentication required but no \
+             callback set; class=Http (34); code=Auth (-16)"
+        ));
+        assert!(is_auth_failure(
+            "Failed to open repository: SSH authentication required but no SSH key provided"
+        ));
+        assert!(is_auth_failure("unexpected http status code: 403"));
+        assert!(!is_auth_failure(
+            "Failed to fetch from remote: failed to resolve address for example.com"
+        ));
+    }
+
+    #[test]
+    fn test_expired_token_breaks_its_repositories() {
+        let expired = token(Some(-3));
+        let expiring = token(Some(10));
+        let fine = token(None);
+        let mut uses_expired = repository(AuthType::Pat);
+        uses_expired.pat_token_id = Some(expired.id);
+        let mut uses_expiring = repository(AuthType::Pat);
+        uses_expiring.pat_token_id = Some(expiring.id);
+
+        let report = assess(
+            &[expired, expiring, fine],
+            &[],
+            &[uses_expired, uses_expiring],
+            &THRESHOLDS,
+            Utc::now(),
+        );
+
+        let statuses: Vec<_> = report.pat_tokens.iter().map(|t| t.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                CredentialHealthStatus::Critical,
+                CredentialHealthStatus::Warning,
+                CredentialHealthStatus::Healthy,
+            ]
+        );
+        assert_eq!(report.pat_tokens[0].repository_count, 1);
+        assert_eq!(
+            report.repositories[0].status,
+            CredentialHealthStatus::Critical
+        );
+        assert_eq!(
+            report.repositories[1].status,
+            CredentialHealthStatus::Warning
+        );
+        assert_eq!(report.status, CredentialHealthStatus::Critical);
+        assert_eq!(
+            report.summary,
+            CredentialHealthSummary {
+                healthy: 1,
+                warning: 2,
+                critical: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn test_ssh_keys_and_repository_errors() {
+        let stale = key(400);
+        let recent = key(10);
+        let mut uses_stale = repository(AuthType::Ssh);
+        uses_stale.ssh_key_id = Some(stale.id);
+        uses_stale.last_error = Some("Failed to fetch from remote: timed out".to_string());
+        let mut rejected = repository(AuthType::Ssh);
+        rejected.ssh_key_id = Some(recent.id);
+        rejected.last_error =
+            Some("Failed to fetch from remote: Permission denied (publickey)".to_string());
+        let mut keyless = repository(AuthType::Ssh);
+        keyless.ssh_key_id = None;
+
+        let report = assess(
+            &[],
+            &[stale, recent, key(1)],
+            &[uses_stale, rejected, keyless],
+            &THRESHOLDS,
+            Utc::now(),
+        );
+
+        assert_eq!(report.ssh_keys[0].status, CredentialHealthStatus::Warning);
+        assert_eq!(report.ssh_keys[1].status, CredentialHealthStatus::Healthy);
+        // Unused
+        assert_eq!(report.ssh_keys[2].status, CredentialHealthStatus::Warning);
+
+        assert_eq!(
+            report.repositories[0].status,
+            CredentialHealthStatus::Warning
+        );
+        assert!(!report.repositories[0].auth_failed);
+        assert_eq!(
+            report.repositories[1].status,
+            CredentialHealthStatus::Critical
+        );
+        assert!(report.repositories[1].auth_failed);
+        assert_eq!(
+            report.repositories[2].issues,
+            vec!["No SSH key configured".to_string()]
+        );
+    }
+}
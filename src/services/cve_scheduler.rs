@@ -9,7 +9,7 @@ use tracing::{error, info};
 
 use crate::config::CveConfig;
 use crate::db::{CveRepository, DbPool};
-use crate::models::{CreateNotificationRequest, NotificationType};
+use crate::models::{NewNotification, NotificationAudience, NotificationType};
 use crate::services::cve_feed::CveFeedService;
 use crate::services::notification::NotificationService;
 
@@ -182,8 +182,7 @@ async fn vulnerability_match_task(state: CveSchedulerState) {
                                         parts.join(", ")
                                     );
 
-                                    let req = CreateNotificationRequest {
-                                        user_id: "system".to_string(),
+                                    let notification = NewNotification {
                                         organization_id: None,
                                         title: "Vulnerabilities Detected".to_string(),
                                         message,
@@ -192,9 +191,11 @@ async fn vulnerability_match_task(state: CveSchedulerState) {
                                         link: Some("/updates".to_string()),
                                         expires_at: None,
                                         metadata: None,
+                                        dedup_key: None,
                                     };
+                                    let audience = NotificationAudience::operators();
 
-                                    if let Err(e) = ns.create_notification(req).await {
+                                    if let Err(e) = ns.notify(&audience, &notification).await {
                                         error!(
                                             "Failed to create vulnerability notification: {}",
                                             e
@@ -0,0 +1,466 @@
+//! Deploy strategies of code deploy repositories
+//!
+//! Each repository selects how its environments land in the environments
+//! directory:
+//!
+//! - `r10k` runs `r10k deploy environment`, which clones the control
+//!   repository and installs the modules of its Puppetfile.
+//! - `librarian_puppet` checks the deployed commit out into the environment
+//!   directory, then runs `librarian-puppet install` when it has a
+//!   Puppetfile.
+//! - `git_checkout` only checks the deployed commit out, for repositories
+//!   that vendor their modules.
+//!
+//! Checkouts are made from the local clone kept up to date by repository
+//! syncs, so they need no credentials of their own. Deployment options only
+//! apply to `r10k`.
+//!
+//! Canary environments are not branches, so r10k cannot deploy them: they are
+//! checked out like `git_checkout` environments, then `r10k` or
+//! `librarian-puppet` installs their Puppetfile.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use git2::build::CheckoutBuilder;
+use git2::{Oid, Repository};
+use tokio::process::Command;
+use tokio::time::timeout;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::models::{DeployStrategy, DeploymentOptions};
+use crate::services::code_deploy::CodeDeployConfig;
+use crate::services::r10k::{DeploymentResult, ProcessRegistry, R10kService};
+
+/// Refspec fetching the branches of the local clone into an environment
+const LOCAL_CLONE_REFSPEC: &str = "+refs/remotes/origin/*:refs/remotes/webui/*";
+
+/// Environment deployed by an executor
+pub struct DeployTarget<'a> {
+    pub deployment_id: Uuid,
+    /// Environment name, as r10k would name its directory
+    pub environment: &'a str,
+    pub commit_sha: &'a str,
+    /// Local clone of the repository
+    pub source: &'a Path,
+    /// r10k flags of the deployment
+    pub options: &'a DeploymentOptions,
+}
+
+/// Deploys an environment of a repository
+#[async_trait]
+pub trait DeployExecutor: Send + Sync {
+    /// Command line recorded on the deployment
+    fn command_line(&self, target: &DeployTarget<'_>) -> String;
+
+    async fn deploy(&self, target: &DeployTarget<'_>) -> Result<DeploymentResult>;
+}
+
+/// Build the executor of a deploy strategy
+pub fn executor<'a>(
+    strategy: DeployStrategy,
+    r10k: &'a R10kService,
+    config: &CodeDeployConfig,
+) -> Box<dyn DeployExecutor + 'a> {
+    match strategy {
+        DeployStrategy::R10k => Box::new(R10kExecutor { r10k }),
+        DeployStrategy::LibrarianPuppet => Box::new(LibrarianPuppetExecutor {
+            binary_path: config.librarian_puppet_path.clone(),
+            basedir: config.r10k.basedir.clone(),
+            timeout: Duration::from_secs(config.r10k.timeout_seconds),
+            processes: r10k.process_registry(),
+        }),
+        DeployStrategy::GitCheckout => Box::new(GitCheckoutExecutor {
+            basedir: config.r10k.basedir.clone(),
+        }),
+    }
+}
+
+/// Build the executor deploying a commit into a canary environment
+pub fn canary_executor<'a>(
+    strategy: DeployStrategy,
+    r10k: &'a R10kService,
+    config: &CodeDeployConfig,
+) -> Box<dyn DeployExecutor + 'a> {
+    match strategy {
+        DeployStrategy::R10k => Box::new(R10kPuppetfileExecutor {
+            binary_path: config.r10k.binary_path.clone(),
+            basedir: config.r10k.basedir.clone(),
+            timeout: Duration::from_secs(config.r10k.timeout_seconds),
+            processes: r10k.process_registry(),
+        }),
+        strategy => executor(strategy, r10k, config),
+    }
+}
+
+/// Deploys with `r10k deploy environment`
+struct R10kExecutor<'a> {
+    r10k: &'a R10kService,
+}
+
+#[async_trait]
+impl DeployExecutor for R10kExecutor<'_> {
+    fn command_line(&self, target: &DeployTarget<'_>) -> String {
+        self.r10k
+            .command_line(&self.r10k.deploy_args(target.environment, target.options))
+    }
+
+    async fn deploy(&self, target: &DeployTarget<'_>) -> Result<DeploymentResult> {
+        self.r10k
+            .deploy_environment_with_options(
+                target.environment,
+                target.options,
+                Some(target.deployment_id),
+            )
+            .await
+    }
+}
+
+/// Checks the deployed commit out into the environment directory
+struct GitCheckoutExecutor {
+    basedir: PathBuf,
+}
+
+#[async_trait]
+impl DeployExecutor for GitCheckoutExecutor {
+    fn command_line(&self, target: &DeployTarget<'_>) -> String {
+        checkout_command_line(&self.basedir, target)
+    }
+
+    async fn deploy(&self, target: &DeployTarget<'_>) -> Result<DeploymentResult> {
+        let start = Instant::now();
+        let env_dir = self.basedir.join(environment_dir_name(target.environment));
+
+        let result = match checkout(target.source, &env_dir, target.commit_sha).await {
+            Ok(()) => DeploymentResult {
+                success: true,
+                stdout: format!(
+                    "Checked out {} into {}",
+                    target.commit_sha,
+                    env_dir.display()
+                ),
+                stderr: String::new(),
+                exit_code: None,
+                duration_ms: 0,
+            },
+            Err(e) => failure(e),
+        };
+
+        Ok(DeploymentResult {
+            duration_ms: start.elapsed().as_millis() as u64,
+            ..result
+        })
+    }
+}
+
+/// Checks the deployed commit out, then installs its Puppetfile with
+/// librarian-puppet
+struct LibrarianPuppetExecutor {
+    binary_path: PathBuf,
+    basedir: PathBuf,
+    timeout: Duration,
+    processes: ProcessRegistry,
+}
+
+#[async_trait]
+impl DeployExecutor for LibrarianPuppetExecutor {
+    fn command_line(&self, target: &DeployTarget<'_>) -> String {
+        format!(
+            "{} && {} install --verbose",
+            checkout_command_line(&self.basedir, target),
+            self.binary_path.display()
+        )
+    }
+
+    async fn deploy(&self, target: &DeployTarget<'_>) -> Result<DeploymentResult> {
+        let start = Instant::now();
+        let env_dir = self.basedir.join(environment_dir_name(target.environment));
+
+        let result = if let Err(e) = checkout(target.source, &env_dir, target.commit_sha).await {
+            failure(e)
+        } else if !env_dir.join("Puppetfile").exists() {
+            DeploymentResult {
+                success: true,
+                stdout: format!(
+                    "Checked out {} into {}, no Puppetfile to install",
+                    target.commit_sha,
+                    env_dir.display()
+                ),
+                stderr: String::new(),
+                exit_code: None,
+                duration_ms: 0,
+            }
+        } else {
+            let mut cmd = Command::new(&self.binary_path);
+            cmd.args(["install", "--verbose"]).current_dir(&env_dir);
+            run_tracked(cmd, target.deployment_id, &self.processes, self.timeout)
+                .await
+                .unwrap_or_else(failure)
+        };
+
+        Ok(DeploymentResult {
+            duration_ms: start.elapsed().as_millis() as u64,
+            ..result
+        })
+    }
+}
+
+/// Checks the deployed commit out, then installs its Puppetfile with
+/// `r10k puppetfile install`
+struct R10kPuppetfileExecutor {
+    binary_path: PathBuf,
+    basedir: PathBuf,
+    timeout: Duration,
+    processes: ProcessRegistry,
+}
+
+#[async_trait]
+impl DeployExecutor for R10kPuppetfileExecutor {
+    fn command_line(&self, target: &DeployTarget<'_>) -> String {
+        format!(
+            "{} && {} puppetfile install --verbose",
+            checkout_command_line(&self.basedir, target),
+            self.binary_path.display()
+        )
+    }
+
+    async fn deploy(&self, target: &DeployTarget<'_>) -> Result<DeploymentResult> {
+        let start = Instant::now();
+        let env_dir = self.basedir.join(environment_dir_name(target.environment));
+
+        let result = if let Err(e) = checkout(target.source, &env_dir, target.commit_sha).await {
+            failure(e)
+        } else if !env_dir.join("Puppetfile").exists() {
+            DeploymentResult {
+                success: true,
+                stdout: format!(
+                    "Checked out {} into {}, no Puppetfile to install",
+                    target.commit_sha,
+                    env_dir.display()
+                ),
+                stderr: String::new(),
+                exit_code: None,
+                duration_ms: 0,
+            }
+        } else {
+            let mut cmd = Command::new(&self.binary_path);
+            cmd.args(["puppetfile", "install", "--verbose"])
+                .current_dir(&env_dir);
+            run_tracked(cmd, target.deployment_id, &self.processes, self.timeout)
+                .await
+                .unwrap_or_else(failure)
+        };
+
+        Ok(DeploymentResult {
+            duration_ms: start.elapsed().as_millis() as u64,
+            ..result
+        })
+    }
+}
+
+/// Directory name of an environment, with the characters Puppet does not
+/// allow replaced by underscores like r10k does
+pub fn environment_dir_name(environment: &str) -> String {
+    environment
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/// Shell equivalent of [`checkout`], for the deployment record
+fn checkout_command_line(basedir: &Path, target: &DeployTarget<'_>) -> String {
+    format!(
+        "cd {} && git fetch {} && git checkout --force {}",
+        basedir
+            .join(environment_dir_name(target.environment))
+            .display(),
+        target.source.display(),
+        target.commit_sha
+    )
+}
+
+/// Check a commit of the local clone out into an environment directory
+async fn checkout(source: &Path, env_dir: &Path, commit_sha: &str) -> Result<()> {
+    let source = source.to_path_buf();
+    let env_dir = env_dir.to_path_buf();
+    let commit_sha = commit_sha.to_string();
+
+    tokio::task::spawn_blocking(move || checkout_blocking(&source, &env_dir, &commit_sha))
+        .await
+        .context("Checkout task panicked")?
+}
+
+fn checkout_blocking(source: &Path, env_dir: &Path, commit_sha: &str) -> Result<()> {
+    let source_url = source
+        .to_str()
+        .context("Local clone path is not valid UTF-8")?;
+
+    let repo = if env_dir.join(".git").exists() {
+        Repository::open(env_dir)
+            .with_context(|| format!("Failed to open checkout at {}", env_dir.display()))?
+    } else {
+        if env_dir.exists()
+            && std::fs::read_dir(env_dir)
+                .with_context(|| format!("Failed to read {}", env_dir.display()))?
+                .next()
+                .is_some()
+        {
+            bail!(
+                "{} exists and is not a Git checkout; remove it or deploy with r10k",
+                env_dir.display()
+            );
+        }
+        std::fs::create_dir_all(env_dir)
+            .with_context(|| format!("Failed to create {}", env_dir.display()))?;
+        Repository::init(env_dir)
+            .with_context(|| format!("Failed to initialize {}", env_dir.display()))?
+    };
+
+    repo.remote_anonymous(source_url)
+        .context("Failed to open the local clone")?
+        .fetch(&[LOCAL_CLONE_REFSPEC], None, None)
+        .context("Failed to fetch from the local clone")?;
+
+    let oid = Oid::from_str(commit_sha).context("Invalid commit SHA")?;
+    let commit = repo
+        .find_commit(oid)
+        .with_context(|| format!("Commit {} not found in the local clone", commit_sha))?;
+
+    repo.checkout_tree(
+        commit.as_object(),
+        Some(CheckoutBuilder::new().force().remove_untracked(true)),
+    )
+    .context("Failed to check the commit out")?;
+    repo.set_head_detached(oid)
+        .context("Failed to update HEAD")?;
+
+    info!("Checked out {} into {}", commit_sha, env_dir.display());
+    Ok(())
+}
+
+/// Remove the directory of an environment deployed by an executor
+pub async fn remove_environment(basedir: &Path, environment: &str) -> Result<()> {
+    let env_dir = basedir.join(environment_dir_name(environment));
+    if tokio::fs::try_exists(&env_dir).await.unwrap_or(false) {
+        tokio::fs::remove_dir_all(&env_dir)
+            .await
+            .with_context(|| format!("Failed to remove {}", env_dir.display()))?;
+        info!("Removed environment directory {}", env_dir.display());
+    }
+    Ok(())
+}
+
+/// Run a deploy command, registered for cancellation under the deployment ID
+async fn run_tracked(
+    mut cmd: Command,
+    deployment_id: Uuid,
+    processes: &ProcessRegistry,
+    limit: Duration,
+) -> Result<DeploymentResult> {
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let child = cmd.spawn().context("Failed to spawn deploy command")?;
+    if let Some(pid) = child.id() {
+        processes.write().await.insert(deployment_id, pid);
+    }
+
+    let output = timeout(limit, child.wait_with_output()).await;
+    processes.write().await.remove(&deployment_id);
+
+    match output {
+        Ok(output) => {
+            let output = output.context("Failed to wait for deploy command")?;
+            let result = DeploymentResult {
+                success: output.status.success(),
+                stdout: String::from_utf8_lossy(&output.stdout).into_owned(),
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+                exit_code: output.status.code(),
+                duration_ms: 0,
+            };
+            if !result.success {
+                error!(
+                    "Deploy command failed for deployment {}: exit code {:?}",
+                    deployment_id, result.exit_code
+                );
+            }
+            Ok(result)
+        }
+        Err(_) => {
+            error!(
+                "Deploy command timed out after {}s for deployment {}",
+                limit.as_secs(),
+                deployment_id
+            );
+            Ok(DeploymentResult {
+                success: false,
+                stdout: String::new(),
+                stderr: format!("Deployment timed out after {} seconds", limit.as_secs()),
+                exit_code: None,
+                duration_ms: 0,
+            })
+        }
+    }
+}
+
+fn failure(e: anyhow::Error) -> DeploymentResult {
+    error!("Deployment failed: {:#}", e);
+    DeploymentResult {
+        success: false,
+        stdout: String::new(),
+        stderr: format!("{:#}", e),
+        exit_code: None,
+        duration_ms: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_environment_dir_name() {
+        assert_eq!(environment_dir_name("production"), "production");
+        assert_eq!(environment_dir_name("feature/new-db"), "feature_new_db");
+        assert_eq!(environment_dir_name("release-1.2"), "release_1_2");
+    }
+
+    #[tokio::test]
+    async fn test_git_checkout_refuses_foreign_directory() {
+        let basedir = std::env::temp_dir().join(format!("openvox-deploy-{}", Uuid::new_v4()));
+        std::fs::create_dir_all(basedir.join("production")).unwrap();
+        std::fs::write(basedir.join("production/site.pp"), "node default {}").unwrap();
+
+        let executor = GitCheckoutExecutor {
+            basedir: basedir.clone(),
+        };
+        let result = executor
+            .deploy(&DeployTarget {
+                deployment_id: Uuid::new_v4(),
+                environment: "production",
+                commit_sha: "0123456789abcdef0123456789abcdef01234567",
+                source: &basedir.join("clone"),
+                options: &DeploymentOptions::default(),
+            })
+            .await
+            .unwrap();
+
+        assert!(!result.success);
+        assert!(result.stderr.contains("is not a Git checkout"));
+        assert!(basedir.join("production/site.pp").exists());
+
+        std::fs::remove_dir_all(&basedir).unwrap();
+    }
+}
@@ -0,0 +1,377 @@
+//! Impact preview of code deployments
+//!
+//! Maps the files changed by a deployment to Puppet modules and classes, then
+//! counts the nodes whose current catalog in the environment uses them, so
+//! approvers can gauge the blast radius of a deployment before approving it.
+//!
+//! Modules kept in the repository are found under the usual modulepath
+//! directories; modules managed by the Puppetfile count as changed when their
+//! declaration is added, changed or removed. Changes to the main manifest,
+//! Hiera data or `environment.conf` may affect every node of the environment.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use anyhow::Result;
+use serde::Deserialize;
+
+use crate::models::{ClassImpact, ModuleChangeSource, ModuleImpact};
+use crate::services::puppetdb::PuppetDbClient;
+
+/// Directories of the control repository holding modules
+const MODULE_DIRS: &[&str] = &["site", "site-modules", "modules", "dist"];
+
+/// Files and directories whose changes may affect every node
+const DATA_PATHS: &[&str] = &["data/", "hieradata/", "hiera.yaml", "environment.conf"];
+
+/// Modules and classes changed by a deployment
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CodeChanges {
+    /// Changed modules with how they changed and the classes whose manifest
+    /// changed
+    pub modules: BTreeMap<String, (BTreeSet<ModuleChangeSource>, BTreeSet<String>)>,
+    pub site_manifest_changed: bool,
+    pub data_changed: bool,
+}
+
+impl CodeChanges {
+    /// Classify the changed files and Puppetfile modules of a deployment
+    pub fn from_files(changed_files: &[String], puppetfile_modules: &[String]) -> Self {
+        let mut changes = Self::default();
+
+        for path in changed_files {
+            if path.starts_with("manifests/") {
+                changes.site_manifest_changed = true;
+            } else if DATA_PATHS
+                .iter()
+                .any(|p| path == p.trim_end_matches('/') || path.starts_with(p))
+            {
+                changes.data_changed = true;
+            } else if let Some((module, class)) = module_change(path) {
+                let entry = changes.modules.entry(module).or_default();
+                entry.0.insert(ModuleChangeSource::Code);
+                if let Some(class) = class {
+                    entry.1.insert(class);
+                }
+            }
+        }
+
+        for module in puppetfile_modules {
+            changes
+                .modules
+                .entry(module.clone())
+                .or_default()
+                .0
+                .insert(ModuleChangeSource::Puppetfile);
+        }
+
+        changes
+    }
+}
+
+/// Module, and class when the file is a manifest, of a file of the
+/// repository
+///
+/// `site/profile/manifests/web/nginx.pp` is class `profile::web::nginx` of
+/// module `profile`; `init.pp` holds the class named after the module.
+pub fn module_change(path: &str) -> Option<(String, Option<String>)> {
+    let mut parts = path.split('/');
+    let dir = parts.next()?;
+    if !MODULE_DIRS.contains(&dir) {
+        return None;
+    }
+    let module = parts.next()?;
+    if !is_module_name(module) {
+        return None;
+    }
+
+    let rest: Vec<&str> = parts.collect();
+    let class = match rest.split_first() {
+        Some((&"manifests", segments)) if !segments.is_empty() => {
+            let (file, dirs) = segments.split_last()?;
+            let name = file.strip_suffix(".pp")?;
+            let mut class = module.to_string();
+            for segment in dirs.iter().copied().chain(std::iter::once(name)) {
+                if !(dirs.is_empty() && segment == "init") {
+                    class.push_str("::");
+                    class.push_str(segment);
+                }
+            }
+            Some(class)
+        }
+        // A file directly in the module directory is not part of the module
+        None => return None,
+        _ => None,
+    };
+
+    Some((module.to_string(), class))
+}
+
+/// Modules whose Puppetfile declaration was added, changed or removed
+pub fn puppetfile_changes(old: Option<&str>, new: Option<&str>) -> Vec<String> {
+    let old = old.map(puppetfile_declarations).unwrap_or_default();
+    let new = new.map(puppetfile_declarations).unwrap_or_default();
+
+    let mut changed: BTreeSet<String> = BTreeSet::new();
+    for (name, declaration) in &new {
+        if old.get(name) != Some(declaration) {
+            changed.insert(name.clone());
+        }
+    }
+    for name in old.keys() {
+        if !new.contains_key(name) {
+            changed.insert(name.clone());
+        }
+    }
+
+    changed.into_iter().collect()
+}
+
+/// Declaration of each module of a Puppetfile, by module name, with comments
+/// and whitespace removed
+fn puppetfile_declarations(content: &str) -> BTreeMap<String, String> {
+    let mut declarations = BTreeMap::new();
+    let mut current: Option<(String, String)> = None;
+
+    for line in content.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with("mod ") || line.starts_with("mod(") {
+            if let Some((name, declaration)) = current.take() {
+                declarations.insert(name, declaration);
+            }
+            let name = line
+                .split(['\'', '"'])
+                .nth(1)
+                .map(|full| {
+                    // 'puppetlabs/stdlib' and 'puppetlabs-stdlib' install stdlib
+                    full.rsplit(['/', '-']).next().unwrap_or(full).to_string()
+                })
+                .unwrap_or_default();
+            current = Some((name, line.to_string()));
+        } else if let Some((_, declaration)) = current.as_mut() {
+            declaration.push(' ');
+            declaration.push_str(line);
+        }
+    }
+
+    if let Some((name, declaration)) = current {
+        declarations.insert(name, declaration);
+    }
+    declarations.remove("");
+    declarations
+}
+
+/// Node counts of the changed modules and classes
+pub struct NodeUsage {
+    pub modules: Vec<ModuleImpact>,
+    /// Distinct nodes using any changed module
+    pub module_nodes: u64,
+    pub environment_nodes: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClassRow {
+    certname: String,
+    title: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CountRow {
+    count: u64,
+}
+
+/// Count the nodes of the environment using the changed modules and classes
+pub async fn node_usage(
+    puppetdb: &PuppetDbClient,
+    environment: &str,
+    changes: &CodeChanges,
+) -> Result<NodeUsage> {
+    let environment = pql_string(environment);
+
+    let pql = format!(
+        "nodes[count()] {{ catalog_environment = {} and deactivated is null }}",
+        environment
+    );
+    let rows: Vec<CountRow> = puppetdb.query(&pql).await?;
+    let environment_nodes = rows.first().map(|r| r.count).unwrap_or(0);
+
+    let mut modules = Vec::new();
+    let mut all_nodes: HashSet<String> = HashSet::new();
+
+    for (name, (sources, classes)) in &changes.modules {
+        let pql = format!(
+            "resources[certname, title] {{ type = \"Class\" and environment = {} and \
+             title ~ {} }}",
+            environment,
+            pql_string(&format!("^{}(::|$)", class_title(name)))
+        );
+        let rows: Vec<ClassRow> = puppetdb.query(&pql).await?;
+
+        let module_nodes: HashSet<&str> = rows.iter().map(|r| r.certname.as_str()).collect();
+        let classes = classes
+            .iter()
+            .map(|class| {
+                let title = class_title(class);
+                let nodes: HashSet<&str> = rows
+                    .iter()
+                    .filter(|r| r.title == title)
+                    .map(|r| r.certname.as_str())
+                    .collect();
+                ClassImpact {
+                    name: class.clone(),
+                    node_count: Some(nodes.len() as u64),
+                }
+            })
+            .collect();
+
+        all_nodes.extend(module_nodes.iter().map(|c| c.to_string()));
+        modules.push(ModuleImpact {
+            name: name.clone(),
+            sources: sources.iter().copied().collect(),
+            classes,
+            node_count: Some(module_nodes.len() as u64),
+        });
+    }
+
+    Ok(NodeUsage {
+        modules,
+        module_nodes: all_nodes.len() as u64,
+        environment_nodes,
+    })
+}
+
+/// Changed modules and classes without node counts, when PuppetDB is not
+/// available
+pub fn without_usage(changes: &CodeChanges) -> Vec<ModuleImpact> {
+    changes
+        .modules
+        .iter()
+        .map(|(name, (sources, classes))| ModuleImpact {
+            name: name.clone(),
+            sources: sources.iter().copied().collect(),
+            classes: classes
+                .iter()
+                .map(|class| ClassImpact {
+                    name: class.clone(),
+                    node_count: None,
+                })
+                .collect(),
+            node_count: None,
+        })
+        .collect()
+}
+
+/// Title of a class resource in PuppetDB: `profile::web` is `Profile::Web`
+fn class_title(class: &str) -> String {
+    class
+        .split("::")
+        .map(|segment| {
+            let mut chars = segment.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("::")
+}
+
+/// Quote a string for a PQL query
+pub(crate) fn pql_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn is_module_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_lowercase())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_change() {
+        assert_eq!(
+            module_change("site/profile/manifests/web/nginx.pp"),
+            Some((
+                "profile".to_string(),
+                Some("profile::web::nginx".to_string())
+            ))
+        );
+        assert_eq!(
+            module_change("site-modules/ntp/manifests/init.pp"),
+            Some(("ntp".to_string(), Some("ntp".to_string())))
+        );
+        assert_eq!(
+            module_change("modules/ntp/templates/ntp.conf.epp"),
+            Some(("ntp".to_string(), None))
+        );
+        assert_eq!(module_change("site/README.md"), None);
+        assert_eq!(module_change("scripts/deploy.sh"), None);
+    }
+
+    #[test]
+    fn test_code_changes_from_files() {
+        let files = vec![
+            "manifests/site.pp".to_string(),
+            "data/common.yaml".to_string(),
+            "site/role/manifests/web.pp".to_string(),
+            "site/role/files/motd".to_string(),
+        ];
+        let changes = CodeChanges::from_files(&files, &["stdlib".to_string()]);
+
+        assert!(changes.site_manifest_changed);
+        assert!(changes.data_changed);
+        assert_eq!(
+            changes.modules.keys().collect::<Vec<_>>(),
+            ["role", "stdlib"]
+        );
+        let (sources, classes) = &changes.modules["role"];
+        assert_eq!(
+            sources.iter().copied().collect::<Vec<_>>(),
+            [ModuleChangeSource::Code]
+        );
+        assert_eq!(classes.iter().collect::<Vec<_>>(), ["role::web"]);
+    }
+
+    #[test]
+    fn test_puppetfile_changes() {
+        let old = r#"
+mod 'puppetlabs/stdlib', '9.0.0'
+mod 'puppetlabs-concat', '7.3.0'
+mod 'custom',
+  git: 'https://git.example.com/custom.git',
+  tag: 'v1.0.0'
+"#;
+        let new = r#"
+# Bumped stdlib
+mod 'puppetlabs/stdlib', '9.1.0'
+mod 'puppetlabs-concat', '7.3.0'
+mod 'custom',
+  git: 'https://git.example.com/custom.git',
+  tag: 'v1.1.0'
+mod 'puppetlabs/ntp', '10.0.0'
+"#;
+
+        assert_eq!(
+            puppetfile_changes(Some(old), Some(new)),
+            ["custom", "ntp", "stdlib"]
+        );
+        assert_eq!(
+            puppetfile_changes(Some(old), None),
+            ["concat", "custom", "stdlib"]
+        );
+    }
+
+    #[test]
+    fn test_class_title() {
+        assert_eq!(class_title("profile::web_server"), "Profile::Web_server");
+        assert_eq!(class_title("ntp"), "Ntp");
+    }
+}
@@ -0,0 +1,228 @@
+//! Drift baselines captured from live facts
+//!
+//! Instead of writing baseline JSON by hand, a baseline can be taken from the
+//! current facts of a golden node, or from the facts shared by every member of
+//! a node group. Volatile facts and any facts the user picks are left out.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::api::groups::classify_group_members;
+use crate::db::repository::GroupRepository;
+use crate::models::{DriftSnapshotPreview, DriftSnapshotRequest, DriftSnapshotSource};
+use crate::services::PuppetDbClient;
+use crate::utils::AppError;
+
+/// Fact name fragments of facts that change between runs
+const VOLATILE_FACT_PATTERNS: &[&str] = &[
+    "system_uptime",
+    "uptime",
+    "uptime_seconds",
+    "uptime_days",
+    "uptime_hours",
+    "memoryfree",
+    "memoryfree_mb",
+    "swapfree",
+    "swapfree_mb",
+];
+
+/// Whether a fact changes between runs and so should not be part of a baseline
+pub fn is_volatile_fact(name: &str) -> bool {
+    VOLATILE_FACT_PATTERNS.iter().any(|p| name.contains(p))
+}
+
+/// Capture the facts of a snapshot source and apply the requested exclusions
+///
+/// A group source must belong to the organization `org_id`.
+pub async fn capture_snapshot(
+    pool: &SqlitePool,
+    puppetdb: &PuppetDbClient,
+    org_id: Uuid,
+    req: &DriftSnapshotRequest,
+) -> Result<DriftSnapshotPreview, AppError> {
+    let node_facts = match &req.source {
+        DriftSnapshotSource::Node { certname } => {
+            let facts = fetch_facts(puppetdb, certname).await?;
+            if facts.is_empty() {
+                return Err(AppError::not_found(format!(
+                    "No facts found for node {}",
+                    certname
+                )));
+            }
+            vec![facts]
+        }
+        DriftSnapshotSource::Group { group_id } => {
+            let group_repo = GroupRepository::new(pool);
+            if group_repo.get_group_organization_id(*group_id).await? != Some(org_id) {
+                return Err(AppError::not_found("Node group not found"));
+            }
+
+            let members =
+                classify_group_members(&group_repo, Some(puppetdb), org_id, *group_id).await?;
+            if members.is_empty() {
+                return Err(AppError::validation("Node group has no members"));
+            }
+
+            let mut node_facts = Vec::with_capacity(members.len());
+            for certname in &members {
+                let facts = fetch_facts(puppetdb, certname).await?;
+                if !facts.is_empty() {
+                    node_facts.push(facts);
+                }
+            }
+            if node_facts.is_empty() {
+                return Err(AppError::not_found("No facts found for the group's nodes"));
+            }
+            node_facts
+        }
+    };
+
+    let (facts, differing_facts) = common_facts(&node_facts);
+    Ok(build_preview(req, node_facts.len(), facts, differing_facts))
+}
+
+async fn fetch_facts(
+    puppetdb: &PuppetDbClient,
+    certname: &str,
+) -> Result<HashMap<String, serde_json::Value>, AppError> {
+    let facts = puppetdb.get_node_facts(certname).await.map_err(|e| {
+        tracing::error!("Failed to get facts for {}: {}", certname, e);
+        AppError::ServiceUnavailable(format!("Failed to get facts for {}", certname))
+    })?;
+    Ok(facts.into_iter().map(|f| (f.name, f.value)).collect())
+}
+
+/// Facts with the same value on every node, and the names of the others
+fn common_facts(
+    nodes: &[HashMap<String, serde_json::Value>],
+) -> (BTreeMap<String, serde_json::Value>, Vec<String>) {
+    let Some((first, rest)) = nodes.split_first() else {
+        return (BTreeMap::new(), Vec::new());
+    };
+
+    let mut common = BTreeMap::new();
+    let mut differing = BTreeSet::new();
+    for (name, value) in first {
+        if rest.iter().all(|facts| facts.get(name) == Some(value)) {
+            common.insert(name.clone(), value.clone());
+        } else {
+            differing.insert(name.clone());
+        }
+    }
+    // Facts missing from the first node but present elsewhere
+    for facts in rest {
+        for name in facts.keys() {
+            if !first.contains_key(name) {
+                differing.insert(name.clone());
+            }
+        }
+    }
+
+    (common, differing.into_iter().collect())
+}
+
+fn build_preview(
+    req: &DriftSnapshotRequest,
+    node_count: usize,
+    mut facts: BTreeMap<String, serde_json::Value>,
+    differing_facts: Vec<String>,
+) -> DriftSnapshotPreview {
+    let volatile_facts: Vec<String> = facts
+        .keys()
+        .filter(|name| is_volatile_fact(name))
+        .cloned()
+        .collect();
+
+    let mut excluded_facts = BTreeSet::new();
+    for name in &req.exclude_facts {
+        if facts.remove(name).is_some() {
+            excluded_facts.insert(name.clone());
+        }
+    }
+    if req.exclude_volatile {
+        for name in &volatile_facts {
+            if facts.remove(name).is_some() {
+                excluded_facts.insert(name.clone());
+            }
+        }
+    }
+
+    DriftSnapshotPreview {
+        source: req.source.clone(),
+        node_count,
+        baseline_facts: serde_json::Value::Object(facts.into_iter().collect()),
+        volatile_facts,
+        excluded_facts: excluded_facts.into_iter().collect(),
+        differing_facts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn facts(pairs: &[(&str, serde_json::Value)]) -> HashMap<String, serde_json::Value> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_common_facts() {
+        let nodes = vec![
+            facts(&[
+                ("os", json!({"family": "RedHat"})),
+                ("hostname", json!("web1")),
+                ("timezone", json!("UTC")),
+            ]),
+            facts(&[
+                ("os", json!({"family": "RedHat"})),
+                ("hostname", json!("web2")),
+                ("kernel", json!("Linux")),
+            ]),
+        ];
+
+        let (common, differing) = common_facts(&nodes);
+        assert_eq!(common.keys().collect::<Vec<_>>(), vec!["os"]);
+        assert_eq!(differing, vec!["hostname", "kernel", "timezone"]);
+    }
+
+    #[test]
+    fn test_build_preview_exclusions() {
+        let req = DriftSnapshotRequest {
+            source: DriftSnapshotSource::Node {
+                certname: "web1".to_string(),
+            },
+            exclude_facts: vec!["hostname".to_string(), "not_captured".to_string()],
+            exclude_volatile: true,
+        };
+        let captured: BTreeMap<String, serde_json::Value> = [
+            ("hostname", json!("web1")),
+            ("kernel", json!("Linux")),
+            ("system_uptime", json!({"seconds": 42})),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v))
+        .collect();
+
+        let preview = build_preview(&req, 1, captured.clone(), vec![]);
+        assert_eq!(preview.baseline_facts, json!({"kernel": "Linux"}));
+        assert_eq!(preview.volatile_facts, vec!["system_uptime"]);
+        assert_eq!(preview.excluded_facts, vec!["hostname", "system_uptime"]);
+
+        // Volatile facts are still reported when kept
+        let req = DriftSnapshotRequest {
+            exclude_volatile: false,
+            exclude_facts: vec![],
+            ..req
+        };
+        let preview = build_preview(&req, 1, captured, vec![]);
+        assert_eq!(preview.volatile_facts, vec!["system_uptime"]);
+        assert!(preview.baseline_facts.get("system_uptime").is_some());
+        assert!(preview.excluded_facts.is_empty());
+    }
+}
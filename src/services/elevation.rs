@@ -0,0 +1,245 @@
+//! Time-bound privilege elevation
+//!
+//! An approved elevation adds its role to the user's database permission
+//! checks and, once activated, to the user's token claims. Tokens carrying a
+//! temporary role expire together with it. A background task marks elevations
+//! whose time is up as expired and records that in the audit log.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use tokio::sync::RwLock;
+use tokio::time::interval;
+use tracing::{error, info};
+use uuid::Uuid;
+
+use crate::db::{AuditRepository, DbPool, RoleElevationRepository};
+use crate::models::RoleElevation;
+use crate::services::audit_forwarding::AuditForwarder;
+use crate::services::DbRbacService;
+
+/// How often approved elevations are checked for expiry
+const EXPIRY_INTERVAL_SECS: u64 = 60;
+
+/// Roles and expiry for an access token
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenGrant {
+    pub roles: Vec<String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl TokenGrant {
+    /// Seconds until the token expires, for `expires_in` in token responses
+    pub fn expires_in(&self, now: DateTime<Utc>) -> u64 {
+        (self.expires_at - now).num_seconds().max(0) as u64
+    }
+}
+
+/// Add the roles of active elevations to a token
+///
+/// The token expires at `default_expiry` or when the first elevation it
+/// carries ends, whichever comes first.
+pub fn merge_elevations(
+    mut roles: Vec<String>,
+    elevations: &[RoleElevation],
+    default_expiry: DateTime<Utc>,
+    now: DateTime<Utc>,
+) -> TokenGrant {
+    let mut expires_at = default_expiry;
+    for elevation in elevations.iter().filter(|e| e.is_active(now)) {
+        if roles.contains(&elevation.role_name) {
+            continue;
+        }
+        roles.push(elevation.role_name.clone());
+        if let Some(ends) = elevation.expires_at {
+            expires_at = expires_at.min(ends);
+        }
+    }
+
+    TokenGrant { roles, expires_at }
+}
+
+/// Roles and expiry for a user's next access token
+///
+/// A failure to load elevations is logged and the token is issued with the
+/// user's regular roles only.
+pub async fn token_grant(
+    pool: &DbPool,
+    user_id: Uuid,
+    roles: Vec<String>,
+    default_expiry: DateTime<Utc>,
+) -> TokenGrant {
+    let now = Utc::now();
+    let elevations = RoleElevationRepository::new(pool)
+        .list_active_for_user(user_id, now)
+        .await
+        .unwrap_or_else(|e| {
+            error!("Failed to load role elevations for {}: {}", user_id, e);
+            Vec::new()
+        });
+
+    merge_elevations(roles, &elevations, default_expiry, now)
+}
+
+/// Handle for stopping the elevation expiry task
+#[derive(Clone)]
+pub struct ElevationExpiryState {
+    running: Arc<RwLock<bool>>,
+    pool: DbPool,
+    rbac_db: Arc<DbRbacService>,
+    forwarder: Option<Arc<AuditForwarder>>,
+}
+
+impl ElevationExpiryState {
+    pub async fn is_running(&self) -> bool {
+        *self.running.read().await
+    }
+
+    /// Request the expiry loop to stop at its next tick
+    pub async fn stop(&self) {
+        *self.running.write().await = false;
+        info!("Role elevation expiry stop requested");
+    }
+}
+
+/// Spawn the background elevation expiry task
+///
+/// Expiries are audited and forwarded through `forwarder`.
+pub fn start_elevation_expiry(
+    pool: DbPool,
+    rbac_db: Arc<DbRbacService>,
+    forwarder: Option<Arc<AuditForwarder>>,
+) -> ElevationExpiryState {
+    let state = ElevationExpiryState {
+        running: Arc::new(RwLock::new(true)),
+        pool,
+        rbac_db,
+        forwarder,
+    };
+
+    let loop_state = state.clone();
+    tokio::spawn(async move {
+        expiry_loop(loop_state).await;
+    });
+
+    info!(
+        "Role elevation expiry started (interval: {}s)",
+        EXPIRY_INTERVAL_SECS
+    );
+    state
+}
+
+async fn expiry_loop(state: ElevationExpiryState) {
+    let mut timer = interval(Duration::from_secs(EXPIRY_INTERVAL_SECS));
+
+    loop {
+        timer.tick().await;
+
+        if !*state.running.read().await {
+            info!("Role elevation expiry stopping");
+            break;
+        }
+
+        if let Err(e) =
+            expire_elevations(&state.pool, &state.rbac_db, state.forwarder.as_deref()).await
+        {
+            error!("Role elevation expiry failed: {}", e);
+        }
+    }
+}
+
+/// Expire every elevation whose time is up
+pub async fn expire_elevations(
+    pool: &DbPool,
+    rbac_db: &DbRbacService,
+    forwarder: Option<&AuditForwarder>,
+) -> anyhow::Result<Vec<RoleElevation>> {
+    let expired = RoleElevationRepository::new(pool)
+        .expire_due(Utc::now())
+        .await?;
+
+    let audit = AuditRepository::new(pool, forwarder);
+    for elevation in &expired {
+        rbac_db.invalidate_user_cache(&elevation.user_id);
+        let details = serde_json::json!({
+            "user_id": elevation.user_id,
+            "role": elevation.role_name,
+            "expires_at": elevation.expires_at,
+        });
+        let _ = audit
+            .insert(
+                elevation.organization_id,
+                None,
+                "role_elevation.expire",
+                "role_elevations",
+                Some(&elevation.id.to_string()),
+                Some(&details),
+                None,
+            )
+            .await;
+        info!(
+            "Role elevation {} ({} for user {}) expired",
+            elevation.id, elevation.role_name, elevation.user_id
+        );
+    }
+
+    Ok(expired)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ElevationStatus;
+
+    fn elevation(role: &str, status: ElevationStatus, expires_at: DateTime<Utc>) -> RoleElevation {
+        let now = Utc::now();
+        RoleElevation {
+            id: Uuid::new_v4(),
+            organization_id: Uuid::new_v4(),
+            user_id: Uuid::new_v4(),
+            role_id: Uuid::new_v4(),
+            role_name: role.to_string(),
+            reason: "incident".to_string(),
+            duration_hours: 2,
+            status,
+            decided_by: Some(Uuid::new_v4()),
+            decided_at: Some(now),
+            decision_comment: None,
+            expires_at: Some(expires_at),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    #[test]
+    fn test_merge_elevations_caps_expiry() {
+        let now = Utc::now();
+        let default_expiry = now + chrono::Duration::hours(24);
+        let ends = now + chrono::Duration::hours(2);
+        let elevations = vec![
+            elevation("admin", ElevationStatus::Approved, ends),
+            elevation("operator", ElevationStatus::Pending, ends),
+            elevation(
+                "auditor",
+                ElevationStatus::Approved,
+                now - chrono::Duration::minutes(1),
+            ),
+        ];
+
+        let grant = merge_elevations(vec!["viewer".to_string()], &elevations, default_expiry, now);
+        assert_eq!(grant.roles, vec!["viewer".to_string(), "admin".to_string()]);
+        assert_eq!(grant.expires_at, ends);
+        assert_eq!(grant.expires_in(now), 2 * 3600);
+    }
+
+    #[test]
+    fn test_merge_elevations_without_elevations() {
+        let now = Utc::now();
+        let default_expiry = now + chrono::Duration::hours(24);
+
+        let grant = merge_elevations(vec!["viewer".to_string()], &[], default_expiry, now);
+        assert_eq!(grant.roles, vec!["viewer".to_string()]);
+        assert_eq!(grant.expires_at, default_expiry);
+    }
+}
@@ -1,10 +1,12 @@
 //! Facter generation service
 
 use anyhow::{Context, Result};
+use flate2::{write::GzEncoder, Compression};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 
-use crate::models::{ClassificationResult, FactTemplate, FactValueSource};
+use crate::models::{ClassificationResult, FactTemplate, FactValueSource, MatchType};
 
 /// Service for generating external facts based on classification
 pub struct FacterService {
@@ -30,7 +32,7 @@ impl FacterService {
             .find(|t| t.name == template_name)
             .context(format!("Template '{}' not found", template_name))?;
 
-        let mut facts: HashMap<String, serde_json::Value> = HashMap::new();
+        let mut facts: BTreeMap<String, serde_json::Value> = BTreeMap::new();
 
         for fact_def in &template.facts {
             let value = self.resolve_fact_value(&fact_def.value, classification, existing_facts)?;
@@ -54,28 +56,8 @@ impl FacterService {
         match source {
             FactValueSource::Static(value) => Ok(value.clone()),
 
-            FactValueSource::FromClassification(key) => {
-                // Get value from classification result
-                match key.as_str() {
-                    "environment" => Ok(serde_json::json!(classification.environment)),
-                    "classes" => Ok(serde_json::json!(classification.classes)),
-                    "groups" => {
-                        let group_names: Vec<&str> = classification
-                            .groups
-                            .iter()
-                            .map(|g| g.name.as_str())
-                            .collect();
-                        Ok(serde_json::json!(group_names))
-                    }
-                    "variables" => Ok(classification.variables.clone()),
-                    _ => {
-                        // Try to get from variables
-                        classification.variables.get(key).cloned().ok_or_else(|| {
-                            anyhow::anyhow!("Classification key '{}' not found", key)
-                        })
-                    }
-                }
-            }
+            FactValueSource::FromClassification(key) => classification_value(classification, key)
+                .ok_or_else(|| anyhow::anyhow!("Classification key '{}' not found", key)),
 
             FactValueSource::FromFact(path) => get_fact_by_path(existing_facts, path)
                 .ok_or_else(|| anyhow::anyhow!("Fact '{}' not found", path)),
@@ -108,17 +90,22 @@ impl FacterService {
         let var_regex = regex::Regex::new(r"\{\{var:([^}]+)\}\}")?;
         let replaced = var_regex.replace_all(&result, |caps: &regex::Captures| {
             let key = &caps[1];
-            classification
-                .variables
-                .get(key)
-                .and_then(|v| match v {
-                    serde_json::Value::String(s) => Some(s.clone()),
-                    _ => Some(v.to_string()),
-                })
+            variable_value(&classification.variables, key)
+                .map(|v| template_string(&v))
                 .unwrap_or_else(|| format!("{{{{var:{}}}}}", key))
         });
         result = replaced.to_string();
 
+        // Replace classification references: {{classification:key}}
+        let classification_regex = regex::Regex::new(r"\{\{classification:([^}]+)\}\}")?;
+        let replaced = classification_regex.replace_all(&result, |caps: &regex::Captures| {
+            let key = &caps[1];
+            classification_value(classification, key)
+                .map(|v| template_string(&v))
+                .unwrap_or_else(|| format!("{{{{classification:{}}}}}", key))
+        });
+        result = replaced.to_string();
+
         // Replace fact variables: {{fact:path}}
         let fact_regex = regex::Regex::new(r"\{\{fact:([^}]+)\}\}")?;
         let replaced = fact_regex.replace_all(&result, |caps: &regex::Captures| {
@@ -131,6 +118,37 @@ impl FacterService {
         Ok(replaced.to_string())
     }
 
+    /// Render the external fact file of every template for a node
+    ///
+    /// Files are named `openvox_<template>.json` or `.yaml` so they can be
+    /// dropped into a `facts.d` directory side by side.
+    pub fn render_files(
+        &self,
+        classification: &ClassificationResult,
+        existing_facts: &serde_json::Value,
+        format: ExportFormat,
+    ) -> Result<Vec<ExternalFactFile>> {
+        let extension = match format {
+            ExportFormat::Json => "json",
+            ExportFormat::Yaml => "yaml",
+            ExportFormat::Shell => anyhow::bail!("Shell exports are not external fact files"),
+        };
+
+        self.templates
+            .iter()
+            .map(|template| {
+                let facts = self
+                    .generate_facts(classification, existing_facts, &template.name)
+                    .with_context(|| format!("Template '{}'", template.name))?;
+                Ok(ExternalFactFile {
+                    template: template.name.clone(),
+                    filename: format!("openvox_{}.{}", file_stem(&template.name), extension),
+                    content: Self::export_facts(&facts, format)?,
+                })
+            })
+            .collect()
+    }
+
     /// Export generated facts in various formats
     pub fn export_facts(facts: &GeneratedFacts, format: ExportFormat) -> Result<String> {
         match format {
@@ -159,6 +177,89 @@ impl FacterService {
     }
 }
 
+/// Value of a classification key
+///
+/// Besides `environment`, `classes`, `groups` and `variables`, accepts
+/// `group` (the most specific group the node matched directly),
+/// `organization_id`, `class_names`, dotted paths into variables
+/// (`variables.network.vlan`) and class parameters (`classes.ntp.servers`).
+/// Any other key is looked up in the variables.
+fn classification_value(
+    classification: &ClassificationResult,
+    key: &str,
+) -> Option<serde_json::Value> {
+    match key {
+        "environment" => Some(serde_json::json!(classification.environment)),
+        "classes" => Some(classification.classes.clone()),
+        "groups" => {
+            let group_names: Vec<&str> = classification
+                .groups
+                .iter()
+                .map(|g| g.name.as_str())
+                .collect();
+            Some(serde_json::json!(group_names))
+        }
+        "group" => classification
+            .groups
+            .iter()
+            .rev()
+            .find(|g| g.match_type != MatchType::Inherited)
+            .map(|g| serde_json::json!(g.name)),
+        "organization_id" => classification
+            .organization_id
+            .map(|id| serde_json::json!(id)),
+        "class_names" => {
+            let mut names: Vec<&String> = classification
+                .classes
+                .as_object()
+                .map(|classes| classes.keys().collect())
+                .unwrap_or_default();
+            names.sort();
+            Some(serde_json::json!(names))
+        }
+        "variables" => Some(classification.variables.clone()),
+        _ => {
+            if let Some(path) = key.strip_prefix("variables.") {
+                variable_value(&classification.variables, path)
+            } else if let Some(path) = key.strip_prefix("classes.") {
+                get_fact_by_path(&classification.classes, path)
+            } else {
+                variable_value(&classification.variables, key)
+            }
+        }
+    }
+}
+
+/// Variable by name, or by dot-notation path into nested variables
+fn variable_value(variables: &serde_json::Value, key: &str) -> Option<serde_json::Value> {
+    variables
+        .get(key)
+        .cloned()
+        .or_else(|| get_fact_by_path(variables, key))
+}
+
+/// Text substituted for a value in a template string
+fn template_string(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        _ => value.to_string(),
+    }
+}
+
+/// File name stem for a template name: lowercase, with anything other than
+/// letters, digits and underscores replaced
+fn file_stem(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
 /// Get a fact value by dot-notation path
 fn get_fact_by_path(facts: &serde_json::Value, path: &str) -> Option<serde_json::Value> {
     let parts: Vec<&str> = path.split('.').collect();
@@ -176,7 +277,68 @@ fn get_fact_by_path(facts: &serde_json::Value, path: &str) -> Option<serde_json:
 pub struct GeneratedFacts {
     pub certname: String,
     pub template: String,
-    pub facts: HashMap<String, serde_json::Value>,
+    /// Generated facts, sorted by name so exports are stable
+    pub facts: BTreeMap<String, serde_json::Value>,
+}
+
+/// External fact file rendered from a template
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExternalFactFile {
+    pub template: String,
+    pub filename: String,
+    pub content: String,
+}
+
+/// External fact files of a node bundled for agents
+#[derive(Debug, Clone)]
+pub struct FactsPackage {
+    /// tar.gz archive of the files, to extract into a `facts.d` directory
+    pub archive: Vec<u8>,
+    /// SHA-256 of the archive
+    pub checksum: String,
+    pub files: Vec<FactsPackageFile>,
+}
+
+/// File of a facts package with its SHA-256
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FactsPackageFile {
+    pub filename: String,
+    pub sha256: String,
+}
+
+impl FactsPackage {
+    /// Bundle external fact files
+    ///
+    /// The archive only depends on the file names and contents, so its
+    /// checksum changes exactly when the facts do.
+    pub fn build(files: &[ExternalFactFile]) -> Result<Self> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for file in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(file.content.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(0);
+            builder
+                .append_data(&mut header, &file.filename, file.content.as_bytes())
+                .with_context(|| format!("Failed to add {} to the package", file.filename))?;
+        }
+        let archive = builder
+            .into_inner()
+            .and_then(|encoder| encoder.finish())
+            .context("Failed to build the facts package")?;
+
+        Ok(Self {
+            checksum: hex::encode(Sha256::digest(&archive)),
+            files: files
+                .iter()
+                .map(|file| FactsPackageFile {
+                    filename: file.filename.clone(),
+                    sha256: hex::encode(Sha256::digest(file.content.as_bytes())),
+                })
+                .collect(),
+            archive,
+        })
+    }
 }
 
 /// Export format for generated facts
@@ -209,6 +371,7 @@ mod tests {
             variables: serde_json::json!({"datacenter": "us-west-1", "role": "webserver"}),
             environment: Some("production".to_string()),
             conflict_error: None,
+            maintenance: vec![],
         }
     }
 
@@ -332,6 +495,122 @@ mod tests {
         assert!(output.contains("export FACTER_MY_FACT=\"my_value\""));
     }
 
+    #[test]
+    fn test_generate_facts_from_group_context() {
+        let template = FactTemplate {
+            id: None,
+            organization_id: default_organization_uuid(),
+            name: "context".to_string(),
+            description: None,
+            facts: vec![
+                FactDefinition {
+                    name: "role".to_string(),
+                    value: FactValueSource::FromClassification("group".to_string()),
+                },
+                FactDefinition {
+                    name: "http_port".to_string(),
+                    value: FactValueSource::FromClassification(
+                        "classes.profile::webserver.http_port".to_string(),
+                    ),
+                },
+                FactDefinition {
+                    name: "vlan".to_string(),
+                    value: FactValueSource::FromClassification(
+                        "variables.network.vlan".to_string(),
+                    ),
+                },
+                FactDefinition {
+                    name: "location".to_string(),
+                    value: FactValueSource::Template(
+                        "{{var:datacenter}}/{{classification:group}}/{{var:network.vlan}}"
+                            .to_string(),
+                    ),
+                },
+            ],
+        };
+
+        let service = FacterService::new(vec![template]);
+        let mut classification = sample_classification();
+        classification.groups.push(GroupMatch {
+            id: Uuid::new_v4(),
+            name: "nginx".to_string(),
+            match_type: MatchType::Inherited,
+            matched_rules: vec![],
+        });
+        classification.variables["network"] = serde_json::json!({"vlan": 120});
+
+        let result = service
+            .generate_facts(&classification, &serde_json::json!({}), "context")
+            .unwrap();
+
+        assert_eq!(result.facts["role"], serde_json::json!("webservers"));
+        assert_eq!(result.facts["http_port"], serde_json::json!(8080));
+        assert_eq!(result.facts["vlan"], serde_json::json!(120));
+        assert_eq!(
+            result.facts["location"],
+            serde_json::json!("us-west-1/webservers/120")
+        );
+    }
+
+    #[test]
+    fn test_render_files() {
+        let template = FactTemplate {
+            id: None,
+            organization_id: default_organization_uuid(),
+            name: "Web Role".to_string(),
+            description: None,
+            facts: vec![FactDefinition {
+                name: "role".to_string(),
+                value: FactValueSource::FromClassification("role".to_string()),
+            }],
+        };
+
+        let service = FacterService::new(vec![template]);
+        let files = service
+            .render_files(
+                &sample_classification(),
+                &serde_json::json!({}),
+                ExportFormat::Yaml,
+            )
+            .unwrap();
+
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].filename, "openvox_web_role.yaml");
+        assert_eq!(files[0].content.trim(), "role: webserver");
+        assert!(service
+            .render_files(
+                &sample_classification(),
+                &serde_json::json!({}),
+                ExportFormat::Shell,
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn test_facts_package() {
+        let files = vec![ExternalFactFile {
+            template: "web".to_string(),
+            filename: "openvox_web.yaml".to_string(),
+            content: "role: webserver\n".to_string(),
+        }];
+
+        let package = FactsPackage::build(&files).unwrap();
+        assert_eq!(package.checksum.len(), 64);
+        assert_eq!(package.files[0].filename, "openvox_web.yaml");
+        assert_eq!(
+            package.checksum,
+            FactsPackage::build(&files).unwrap().checksum
+        );
+
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(&package.archive[..]));
+        let names: Vec<String> = archive
+            .entries()
+            .unwrap()
+            .map(|e| e.unwrap().path().unwrap().display().to_string())
+            .collect();
+        assert_eq!(names, ["openvox_web.yaml"]);
+    }
+
     #[test]
     fn test_generate_facts_from_variables() {
         let template = FactTemplate {
@@ -0,0 +1,164 @@
+//! Fault injection for dependency clients
+//!
+//! With the `fault-injection` build feature, the PuppetDB, Puppet CA and Git
+//! clients can be told to fail a share of their requests and to add latency
+//! (see [`FaultInjectionConfig`]). Integration tests and staging use this to
+//! check that cache fallback and error handling hold up during partial
+//! outages. Without the feature the injector does nothing.
+
+#[cfg(feature = "fault-injection")]
+use std::time::Duration;
+
+use thiserror::Error;
+
+use crate::config::FaultInjectionConfig;
+use crate::utils::error::AppError;
+
+/// Error returned for an injected failure
+#[derive(Debug, Clone, Error)]
+#[error("Injected {target} fault")]
+pub struct InjectedFault {
+    pub target: &'static str,
+}
+
+impl From<InjectedFault> for AppError {
+    fn from(fault: InjectedFault) -> Self {
+        AppError::ServiceUnavailable(fault.to_string())
+    }
+}
+
+/// Per-client fault injector
+#[derive(Debug, Clone, Default)]
+pub struct FaultInjector {
+    #[cfg(feature = "fault-injection")]
+    faults: Option<(&'static str, FaultInjectionConfig)>,
+}
+
+#[cfg(feature = "fault-injection")]
+impl FaultInjector {
+    /// Create the injector of the client named `target`
+    pub fn new(target: &'static str, config: Option<&FaultInjectionConfig>) -> Self {
+        let faults = config.filter(|c| c.is_active()).map(|c| {
+            tracing::warn!(
+                "Fault injection enabled for {}: error_rate={}, latency={}ms (+{}ms jitter)",
+                target,
+                c.error_rate,
+                c.latency_ms,
+                c.latency_jitter_ms
+            );
+            (target, c.clone())
+        });
+        Self { faults }
+    }
+
+    /// Latency to add and whether to fail, for the next request
+    fn draw(&self) -> Option<(&'static str, Duration, bool)> {
+        use rand::RngExt;
+
+        let (target, config) = self.faults.as_ref()?;
+        let mut rng = rand::rng();
+        let jitter = if config.latency_jitter_ms > 0 {
+            rng.random_range(0..=config.latency_jitter_ms)
+        } else {
+            0
+        };
+        let fail = rng.random_bool(config.error_rate.clamp(0.0, 1.0));
+        Some((
+            target,
+            Duration::from_millis(config.latency_ms + jitter),
+            fail,
+        ))
+    }
+
+    /// Apply the configured faults before an async request
+    pub async fn inject(&self) -> Result<(), InjectedFault> {
+        let Some((target, delay, fail)) = self.draw() else {
+            return Ok(());
+        };
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+        if fail {
+            tracing::debug!("Injecting {} fault", target);
+            return Err(InjectedFault { target });
+        }
+        Ok(())
+    }
+
+    /// Apply the configured faults before a blocking operation
+    pub fn inject_blocking(&self) -> Result<(), InjectedFault> {
+        let Some((target, delay, fail)) = self.draw() else {
+            return Ok(());
+        };
+        if !delay.is_zero() {
+            std::thread::sleep(delay);
+        }
+        if fail {
+            tracing::debug!("Injecting {} fault", target);
+            return Err(InjectedFault { target });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(not(feature = "fault-injection"))]
+impl FaultInjector {
+    /// Create the injector of the client named `target`
+    ///
+    /// Fault injection is not compiled in, so the configuration is ignored.
+    pub fn new(target: &'static str, config: Option<&FaultInjectionConfig>) -> Self {
+        if config.is_some_and(|c| c.is_active()) {
+            tracing::warn!(
+                "{}.fault_injection is set, but this build lacks the `fault-injection` feature; ignoring it",
+                target
+            );
+        }
+        Self {}
+    }
+
+    /// Apply the configured faults before an async request
+    pub async fn inject(&self) -> Result<(), InjectedFault> {
+        Ok(())
+    }
+
+    /// Apply the configured faults before a blocking operation
+    pub fn inject_blocking(&self) -> Result<(), InjectedFault> {
+        Ok(())
+    }
+}
+
+#[cfg(all(test, feature = "fault-injection"))]
+mod tests {
+    use super::*;
+
+    fn injector(error_rate: f64, latency_ms: u64) -> FaultInjector {
+        FaultInjector::new(
+            "puppetdb",
+            Some(&FaultInjectionConfig {
+                error_rate,
+                latency_ms,
+                latency_jitter_ms: 0,
+            }),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_always_fails() {
+        let err = injector(1.0, 0).inject().await.unwrap_err();
+        assert_eq!(err.target, "puppetdb");
+        assert!(injector(1.0, 0).inject_blocking().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_never_fails() {
+        assert!(injector(0.0, 0).inject().await.is_ok());
+        assert!(FaultInjector::default().inject().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_adds_latency() {
+        let start = std::time::Instant::now();
+        injector(0.0, 50).inject().await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(50));
+    }
+}
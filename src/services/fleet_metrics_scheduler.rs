@@ -0,0 +1,257 @@
+//! Hourly snapshots of fleet-wide metrics.
+//!
+//! Each cycle records the node counts by latest report status and the
+//! average run time, catalog compile time and resource totals of the reports
+//! received over the last hour into `fleet_metrics_hourly`. The Analytics
+//! fleet trend charts read those rows, so 30 and 90 day trends are fast and
+//! outlive PuppetDB's report retention.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Duration as ChronoDuration, Timelike, Utc};
+use serde::Deserialize;
+use tokio::sync::RwLock;
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+
+use crate::db::{DbPool, FleetMetricsRepository, FleetMetricsSnapshot};
+use crate::models::{PuppetDbDataRef, ReportMetric};
+use crate::services::puppetdb::PuppetDbClient;
+
+/// How long snapshots are kept. The longest trend chart covers 90 days.
+const RETENTION_DAYS: i64 = 400;
+
+/// How often the loop fires.
+const SNAPSHOT_INTERVAL_SECS: u64 = 60 * 60;
+
+#[derive(Debug, Clone)]
+pub struct FleetMetricsSchedulerState {
+    running: Arc<RwLock<bool>>,
+}
+
+impl FleetMetricsSchedulerState {
+    pub async fn stop(&self) {
+        let mut running = self.running.write().await;
+        *running = false;
+        info!("Fleet metrics scheduler stop requested");
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportRow {
+    certname: String,
+    end_time: Option<DateTime<Utc>>,
+    metrics: Option<PuppetDbDataRef>,
+}
+
+/// Metrics of one report used by the snapshot
+#[derive(Debug, Default)]
+struct ReportValues {
+    run_time: Option<f64>,
+    compile_time: Option<f64>,
+    total_resources: i64,
+    changed_resources: i64,
+    failed_resources: i64,
+}
+
+impl ReportRow {
+    fn values(&self) -> ReportValues {
+        let mut values = ReportValues::default();
+        let metrics = self.metrics.as_ref().and_then(|m| m.data.as_ref());
+        for value in metrics.into_iter().flatten() {
+            let Ok(metric) = serde_json::from_value::<ReportMetric>(value.clone()) else {
+                continue;
+            };
+            match (metric.category.as_str(), metric.name.as_str()) {
+                ("time", "total") => values.run_time = Some(metric.value),
+                // Catalog retrieval time is dominated by the compilation on
+                // Puppet Server
+                ("time", "config_retrieval") => values.compile_time = Some(metric.value),
+                ("resources", "total") => values.total_resources = metric.value as i64,
+                ("resources", "changed") => values.changed_resources = metric.value as i64,
+                ("resources", "failed") => values.failed_resources = metric.value as i64,
+                _ => {}
+            }
+        }
+        values
+    }
+}
+
+pub fn start_fleet_metrics_scheduler(
+    pool: DbPool,
+    puppetdb: Arc<PuppetDbClient>,
+) -> FleetMetricsSchedulerState {
+    let running = Arc::new(RwLock::new(true));
+    let state = FleetMetricsSchedulerState {
+        running: running.clone(),
+    };
+
+    tokio::spawn(async move {
+        // Snapshot once at startup so the current hour is not missing after
+        // a restart.
+        if let Err(e) = record_snapshot(&pool, &puppetdb).await {
+            warn!("Initial fleet metrics snapshot failed: {}", e);
+        }
+
+        let mut timer = interval(Duration::from_secs(SNAPSHOT_INTERVAL_SECS));
+        timer.tick().await; // First tick fires immediately; skip it.
+
+        loop {
+            timer.tick().await;
+            if !*running.read().await {
+                info!("Fleet metrics scheduler stopping");
+                break;
+            }
+            if let Err(e) = record_snapshot(&pool, &puppetdb).await {
+                error!("Fleet metrics snapshot failed: {}", e);
+            }
+        }
+    });
+
+    info!(
+        "Fleet metrics scheduler started (every {}s, keeping {} days)",
+        SNAPSHOT_INTERVAL_SECS, RETENTION_DAYS
+    );
+    state
+}
+
+async fn record_snapshot(pool: &DbPool, puppetdb: &PuppetDbClient) -> anyhow::Result<()> {
+    let repo = FleetMetricsRepository::new(pool.clone());
+    let now = Utc::now();
+
+    let stats = puppetdb.get_node_stats().await?;
+    let status_count = |status: &str| stats.by_status.get(status).copied().unwrap_or(0) as i64;
+
+    let pql = format!(
+        r#"reports[certname, end_time, metrics] {{ end_time >= "{}" }}"#,
+        (now - ChronoDuration::hours(1)).to_rfc3339()
+    );
+    let rows: Vec<ReportRow> = puppetdb.query(&pql).await?;
+
+    let mut snapshot = aggregate_reports(&rows);
+    snapshot.bucket = format_hour(now);
+    snapshot.total_nodes = stats.total as i64;
+    snapshot.changed_nodes = status_count("changed");
+    snapshot.unchanged_nodes = status_count("unchanged");
+    snapshot.failed_nodes = status_count("failed");
+    snapshot.unreported_nodes = status_count("unknown");
+    repo.upsert(&snapshot).await?;
+
+    let cutoff = format_hour(now - ChronoDuration::days(RETENTION_DAYS));
+    let deleted = repo.delete_before(&cutoff).await?;
+
+    debug!(
+        "Fleet metrics snapshot for {}: {} node(s), {} report(s), {} old snapshot(s) deleted",
+        snapshot.bucket, snapshot.total_nodes, snapshot.report_count, deleted
+    );
+    Ok(())
+}
+
+/// Report metrics of a snapshot
+///
+/// Run and compile times are averaged over every report; resource counts
+/// are summed over the latest report of each node, so a node running twice
+/// in the hour is not counted twice.
+fn aggregate_reports(rows: &[ReportRow]) -> FleetMetricsSnapshot {
+    let mut run_times = Vec::new();
+    let mut compile_times = Vec::new();
+    let mut latest: HashMap<&str, (Option<DateTime<Utc>>, ReportValues)> = HashMap::new();
+
+    for row in rows {
+        let values = row.values();
+        run_times.extend(values.run_time);
+        compile_times.extend(values.compile_time);
+
+        match latest.get(row.certname.as_str()) {
+            Some((end_time, _)) if *end_time >= row.end_time => {}
+            _ => {
+                latest.insert(row.certname.as_str(), (row.end_time, values));
+            }
+        }
+    }
+
+    let mut snapshot = FleetMetricsSnapshot {
+        report_count: rows.len() as i64,
+        avg_run_time_seconds: average(&run_times),
+        avg_compile_time_seconds: average(&compile_times),
+        ..Default::default()
+    };
+    for (_, values) in latest.values() {
+        snapshot.total_resources += values.total_resources;
+        snapshot.changed_resources += values.changed_resources;
+        snapshot.failed_resources += values.failed_resources;
+    }
+    snapshot
+}
+
+fn average(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<f64>() / values.len() as f64)
+    }
+}
+
+/// UTC hour of a timestamp, formatted like the `hour` column
+pub fn format_hour(ts: DateTime<Utc>) -> String {
+    ts.with_minute(0)
+        .and_then(|t| t.with_second(0))
+        .and_then(|t| t.with_nanosecond(0))
+        .unwrap_or(ts)
+        .format("%Y-%m-%dT%H:00:00Z")
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn report(certname: &str, end_time: &str, run: f64, compile: f64, resources: i64) -> ReportRow {
+        serde_json::from_value(json!({
+            "certname": certname,
+            "end_time": end_time,
+            "metrics": {
+                "data": [
+                    {"category": "time", "name": "total", "value": run},
+                    {"category": "time", "name": "config_retrieval", "value": compile},
+                    {"category": "resources", "name": "total", "value": resources},
+                    {"category": "resources", "name": "changed", "value": 1},
+                ],
+                "href": "/pdb/query/v4/reports/x/metrics"
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_aggregate_reports() {
+        let rows = vec![
+            report("web01", "2026-10-16T12:10:00Z", 10.0, 2.0, 100),
+            report("web01", "2026-10-16T12:40:00Z", 20.0, 4.0, 120),
+            report("db01", "2026-10-16T12:20:00Z", 30.0, 6.0, 50),
+        ];
+
+        let snapshot = aggregate_reports(&rows);
+        assert_eq!(snapshot.report_count, 3);
+        assert_eq!(snapshot.avg_run_time_seconds, Some(20.0));
+        assert_eq!(snapshot.avg_compile_time_seconds, Some(4.0));
+        assert_eq!(snapshot.total_resources, 170);
+        assert_eq!(snapshot.changed_resources, 2);
+        assert_eq!(snapshot.failed_resources, 0);
+
+        let empty = aggregate_reports(&[]);
+        assert_eq!(empty.report_count, 0);
+        assert_eq!(empty.avg_run_time_seconds, None);
+    }
+
+    #[test]
+    fn test_format_hour() {
+        let ts = DateTime::parse_from_rfc3339("2026-10-16T13:47:12.5Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(format_hour(ts), "2026-10-16T13:00:00Z");
+    }
+}
@@ -0,0 +1,166 @@
+//! Default node groups of new organizations
+//!
+//! `organizations.group_templates` lists the groups every new organization
+//! starts with. Templates refer to their parent by name, so the list is
+//! created in order and each parent must come before its children.
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result};
+use uuid::Uuid;
+
+use crate::config::GroupTemplate;
+use crate::db::repository::GroupRepository;
+use crate::db::DbPool;
+use crate::models::{CreateGroupRequest, NodeGroup};
+
+/// Check that names are unique and parents are listed before their children
+pub fn validate(templates: &[GroupTemplate]) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    for (i, template) in templates.iter().enumerate() {
+        let name = template.name.trim();
+        if name.is_empty() {
+            return Err(format!("[{}]: name cannot be empty", i));
+        }
+        if let Some(parent) = &template.parent {
+            if !seen.contains(parent.trim()) {
+                return Err(format!(
+                    "[{}]: parent '{}' must be a template listed before '{}'",
+                    i, parent, name
+                ));
+            }
+        }
+        if !template.classes.is_object() {
+            return Err(format!("[{}]: classes must be a map of class names", i));
+        }
+        if !seen.insert(name) {
+            return Err(format!("[{}]: duplicate template name '{}'", i, name));
+        }
+    }
+    Ok(())
+}
+
+/// Create the template groups in an organization, parents first
+pub async fn instantiate(
+    pool: &DbPool,
+    organization_id: Uuid,
+    templates: &[GroupTemplate],
+) -> Result<Vec<NodeGroup>> {
+    validate(templates).map_err(anyhow::Error::msg)?;
+
+    let repo = GroupRepository::new(pool);
+    let mut ids: HashMap<&str, Uuid> = HashMap::new();
+    let mut groups = Vec::with_capacity(templates.len());
+    for template in templates {
+        let parent_id = template
+            .parent
+            .as_deref()
+            .and_then(|parent| ids.get(parent.trim()).copied());
+        let group = repo
+            .create(organization_id, &create_request(template, parent_id))
+            .await
+            .with_context(|| format!("Failed to create group '{}'", template.name))?;
+        for rule in &template.rules {
+            repo.add_rule(group.id, rule)
+                .await
+                .with_context(|| format!("Failed to add rule to group '{}'", template.name))?;
+        }
+        ids.insert(template.name.trim(), group.id);
+        groups.push(group);
+    }
+    Ok(groups)
+}
+
+fn create_request(template: &GroupTemplate, parent_id: Option<Uuid>) -> CreateGroupRequest {
+    CreateGroupRequest {
+        name: template.name.trim().to_string(),
+        description: template.description.clone(),
+        parent_id,
+        environment: template.environment.clone(),
+        is_environment_group: Some(template.is_environment_group),
+        match_all_nodes: Some(template.match_all_nodes),
+        rule_match_type: Some(template.rule_match_type),
+        classes: Some(template.classes.clone()),
+        variables: Some(template.variables.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::OrganizationsConfig;
+
+    fn templates(yaml: &str) -> Vec<GroupTemplate> {
+        serde_norway::from_str::<OrganizationsConfig>(yaml)
+            .unwrap()
+            .group_templates
+    }
+
+    #[test]
+    fn test_default_templates() {
+        let defaults = OrganizationsConfig::default().group_templates;
+        let names: Vec<_> = defaults.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["All Nodes", "Production", "Development"]);
+        assert!(validate(&defaults).is_ok());
+        assert_eq!(defaults[1].parent.as_deref(), Some("All Nodes"));
+        assert_eq!(defaults[1].environment.as_deref(), Some("production"));
+        // Defaults match no nodes and assign nothing
+        assert!(defaults.iter().all(|t| !t.match_all_nodes
+            && t.rules.is_empty()
+            && t.classes.as_object().is_some_and(|c| c.is_empty())));
+
+        // Templates can be turned off with an empty list
+        assert!(templates("group_templates: []").is_empty());
+    }
+
+    #[test]
+    fn test_validate_templates() {
+        let parsed = templates(
+            r#"
+group_templates:
+  - name: All Nodes
+    match_all_nodes: true
+  - name: Web
+    parent: All Nodes
+    classes:
+      role::web: {}
+    rules:
+      - fact_path: trusted.extensions.pp_role
+        operator: "="
+        value: web
+"#,
+        );
+        assert!(validate(&parsed).is_ok());
+        assert_eq!(parsed[1].rules.len(), 1);
+
+        let child_first = templates(
+            r#"
+group_templates:
+  - name: Web
+    parent: All Nodes
+  - name: All Nodes
+"#,
+        );
+        assert!(validate(&child_first)
+            .unwrap_err()
+            .contains("listed before"));
+
+        let duplicate = templates(
+            r#"
+group_templates:
+  - name: All Nodes
+  - name: All Nodes
+"#,
+        );
+        assert!(validate(&duplicate).unwrap_err().contains("duplicate"));
+
+        let bad_classes = templates(
+            r#"
+group_templates:
+  - name: All Nodes
+    classes: [profile::base]
+"#,
+        );
+        assert!(validate(&bad_classes).is_err());
+    }
+}
@@ -0,0 +1,59 @@
+//! Last successful check of each monitored component
+//!
+//! `/health/detailed` records here when a dependency answered a probe, and
+//! background jobs record each run of their loop, so the health report can
+//! tell how long a failing component has been down, whether the scheduler
+//! is still running and whether the caches have been warmed up.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+
+/// Component name of the background scheduler
+pub const SCHEDULER: &str = "scheduler";
+
+/// Component name of the search index built from PuppetDB
+pub const SEARCH_INDEX: &str = "search_index";
+
+/// When each component was last seen working
+///
+/// Clones share their records; the server keeps one in `AppState` and hands
+/// clones to the background jobs it starts.
+#[derive(Clone, Default)]
+pub struct HealthTracker {
+    last_success: Arc<Mutex<HashMap<&'static str, DateTime<Utc>>>>,
+}
+
+impl HealthTracker {
+    /// Record that a component was just seen working
+    pub fn record_success(&self, component: &'static str) {
+        if let Ok(mut last_success) = self.last_success.lock() {
+            last_success.insert(component, Utc::now());
+        }
+    }
+
+    /// When a component was last seen working
+    pub fn last_success(&self, component: &str) -> Option<DateTime<Utc>> {
+        self.last_success
+            .lock()
+            .ok()
+            .and_then(|last_success| last_success.get(component).copied())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_success() {
+        let health = HealthTracker::default();
+        assert!(health.last_success("test-component").is_none());
+
+        let before = Utc::now();
+        health.clone().record_success("test-component");
+        let recorded = health.last_success("test-component").unwrap();
+        assert!(recorded >= before && recorded <= Utc::now());
+    }
+}
@@ -0,0 +1,65 @@
+//! Hiera data of classified nodes
+//!
+//! Exposes the classification of a node in the layout Hiera backends such as
+//! hiera-http expect: one flat hash whose keys are the group variables and the
+//! class parameters in `class::parameter` form, so automatic parameter lookup
+//! resolves them like data from the control repository.
+
+use serde_json::{Map, Value};
+
+use crate::models::ClassificationResult;
+
+/// Hiera keys and values of a classified node
+///
+/// Class parameters take precedence over a group variable of the same name.
+pub fn node_data(classification: &ClassificationResult) -> Map<String, Value> {
+    let mut data = classification
+        .variables
+        .as_object()
+        .cloned()
+        .unwrap_or_default();
+
+    if let Some(classes) = classification.classes.as_object() {
+        for (class, parameters) in classes {
+            let Some(parameters) = parameters.as_object() else {
+                continue;
+            };
+            for (parameter, value) in parameters {
+                data.insert(format!("{}::{}", class, parameter), value.clone());
+            }
+        }
+    }
+
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_node_data() {
+        let classification = ClassificationResult {
+            certname: "web01.example.com".to_string(),
+            organization_id: None,
+            groups: vec![],
+            classes: json!({
+                "ntp": {"servers": ["ntp1.example.com"]},
+                "apache": {},
+            }),
+            variables: json!({
+                "datacenter": "dc1",
+                "ntp::servers": ["ntp9.example.com"],
+            }),
+            environment: Some("production".to_string()),
+            conflict_error: None,
+            maintenance: vec![],
+        };
+
+        let data = node_data(&classification);
+        assert_eq!(data.len(), 2);
+        assert_eq!(data["datacenter"], json!("dc1"));
+        assert_eq!(data["ntp::servers"], json!(["ntp1.example.com"]));
+    }
+}
@@ -0,0 +1,694 @@
+//! JWT signing keys
+//!
+//! With `auth.signing_keys` choosing RS256 or EdDSA, or a rotation period,
+//! tokens are signed with keys generated and stored in `jwt_signing_keys`
+//! instead of `auth.jwt_secret`, and name their key in the `kid` header. A
+//! replaced key is retired rather than deleted and keeps verifying tokens for
+//! the grace window, so rotation never logs anyone out; tokens signed with
+//! `jwt_secret` get the same window when stored keys are first enabled.
+//!
+//! The keys in use are loaded into the [`JwtKeys`] handle held by `AppState`
+//! and passed to the token functions in `middleware::auth`. Without a loaded
+//! keyring (e.g. in tests) tokens are signed and verified with the secret
+//! passed to them.
+
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock as StdRwLock};
+use std::time::Duration;
+
+use anyhow::{anyhow, Context, Result};
+use base64::{
+    engine::general_purpose::{STANDARD as BASE64, URL_SAFE_NO_PAD},
+    Engine,
+};
+use chrono::{DateTime, Utc};
+use jsonwebtoken::{
+    jwk::{Jwk, JwkSet},
+    Algorithm, DecodingKey, EncodingKey,
+};
+use tokio::sync::RwLock;
+use tokio::time::interval;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::config::{AuthConfig, JwtAlgorithm};
+use crate::db::{DbPool, JwtKeyRepository, JwtSigningKey};
+use crate::services::settings_encryption::SettingsCipher;
+
+/// How often the signing key is checked for rotation
+const ROTATION_CHECK_INTERVAL_SECS: u64 = 3600;
+
+/// Key ID of the row that bounds acceptance of tokens signed with
+/// `auth.jwt_secret`
+pub const LEGACY_KID: &str = "legacy";
+
+/// Size of generated RSA keys
+const RSA_KEY_BITS: usize = 2048;
+
+/// Size of generated HS256 secrets
+const HMAC_SECRET_BYTES: usize = 64;
+
+/// The key new tokens are signed with
+pub struct SigningKey {
+    pub kid: String,
+    pub algorithm: Algorithm,
+    pub encoding_key: EncodingKey,
+}
+
+struct VerifyingKey {
+    algorithm: Algorithm,
+    decoding_key: DecodingKey,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+/// Keys that sign and verify tokens
+#[derive(Default)]
+pub struct Keyring {
+    signing_key: Option<SigningKey>,
+    verifying_keys: HashMap<String, VerifyingKey>,
+    /// End of the window for tokens signed with `auth.jwt_secret`
+    legacy_expires_at: Option<DateTime<Utc>>,
+    public_keys: Vec<(Jwk, Option<DateTime<Utc>>)>,
+}
+
+impl Keyring {
+    /// Build a keyring from stored keys
+    ///
+    /// A retired key that cannot be decoded is skipped with a warning; the
+    /// signing key must decode.
+    pub fn from_keys(keys: &[JwtSigningKey]) -> Result<Self> {
+        let mut keyring = Keyring::default();
+        for key in keys {
+            if key.kid == LEGACY_KID {
+                keyring.legacy_expires_at = key.expires_at;
+                continue;
+            }
+
+            let decoded = match decode_key(key) {
+                Ok(decoded) => decoded,
+                Err(e) if key.retired_at.is_none() => {
+                    return Err(e.context(format!("Invalid JWT signing key {}", key.kid)));
+                }
+                Err(e) => {
+                    warn!("Skipping JWT signing key {}: {:#}", key.kid, e);
+                    continue;
+                }
+            };
+            if key.retired_at.is_none() {
+                keyring.signing_key = Some(SigningKey {
+                    kid: key.kid.clone(),
+                    algorithm: decoded.algorithm,
+                    encoding_key: decoded.encoding_key,
+                });
+            }
+            if let Some(jwk) = decoded.jwk {
+                keyring.public_keys.push((jwk, key.expires_at));
+            }
+            keyring.verifying_keys.insert(
+                key.kid.clone(),
+                VerifyingKey {
+                    algorithm: decoded.algorithm,
+                    decoding_key: decoded.decoding_key,
+                    expires_at: key.expires_at,
+                },
+            );
+        }
+        Ok(keyring)
+    }
+
+    /// The key that signs new tokens; `None` signs with `auth.jwt_secret`
+    pub fn signing_key(&self) -> Option<&SigningKey> {
+        self.signing_key.as_ref()
+    }
+
+    /// The key that verifies tokens with `kid`, unless its grace window ended
+    pub fn verifying_key(
+        &self,
+        kid: &str,
+        now: DateTime<Utc>,
+    ) -> Option<(&DecodingKey, Algorithm)> {
+        self.verifying_keys
+            .get(kid)
+            .filter(|key| key.expires_at.is_none_or(|expires_at| now < expires_at))
+            .map(|key| (&key.decoding_key, key.algorithm))
+    }
+
+    /// Whether tokens without `kid`, signed with `auth.jwt_secret`, are valid
+    pub fn accepts_secret_tokens(&self, now: DateTime<Utc>) -> bool {
+        self.signing_key.is_none()
+            || self
+                .legacy_expires_at
+                .is_some_and(|expires_at| now < expires_at)
+    }
+
+    /// Public keys that verify tokens; HS256 keys are never published
+    pub fn jwks(&self, now: DateTime<Utc>) -> JwkSet {
+        JwkSet {
+            keys: self
+                .public_keys
+                .iter()
+                .filter(|(_, expires_at)| expires_at.is_none_or(|expires_at| now < expires_at))
+                .map(|(jwk, _)| jwk.clone())
+                .collect(),
+        }
+    }
+}
+
+/// The keyring in use, shared by everything that signs or verifies tokens
+#[derive(Clone, Default)]
+pub struct JwtKeys {
+    keyring: Arc<StdRwLock<Option<Arc<Keyring>>>>,
+    cipher: Option<SettingsCipher>,
+}
+
+impl JwtKeys {
+    /// An empty handle; stored keys are encrypted with `cipher`
+    pub fn new(cipher: Option<SettingsCipher>) -> Self {
+        Self {
+            keyring: Arc::default(),
+            cipher,
+        }
+    }
+
+    /// The loaded keyring, if any
+    pub fn current(&self) -> Option<Arc<Keyring>> {
+        self.keyring
+            .read()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clone()
+    }
+
+    /// Sync the stored keys with the configuration and load the keyring
+    ///
+    /// Called at startup, after the settings master key is loaded.
+    pub async fn init(&self, pool: &DbPool, config: &AuthConfig) -> Result<()> {
+        let keyring = self.reload(pool, config).await?;
+        match keyring.signing_key() {
+            Some(key) => info!(
+                "Signing tokens with {:?} key {} ({} keys verify tokens)",
+                key.algorithm,
+                key.kid,
+                keyring.verifying_keys.len()
+            ),
+            None if !keyring.verifying_keys.is_empty() => info!(
+                "Signing tokens with auth.jwt_secret ({} retired keys still verify tokens)",
+                keyring.verifying_keys.len()
+            ),
+            None => {}
+        }
+        Ok(())
+    }
+
+    /// Sync the stored keys with the configuration and replace the keyring
+    pub async fn reload(&self, pool: &DbPool, config: &AuthConfig) -> Result<Arc<Keyring>> {
+        let cipher = self.cipher.as_ref();
+        sync_keys(pool, cipher, config, Utc::now()).await?;
+        let keys = JwtKeyRepository::new(pool, cipher).list().await?;
+        let keyring = Arc::new(Keyring::from_keys(&keys)?);
+        *self
+            .keyring
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(keyring.clone());
+        Ok(keyring)
+    }
+}
+
+/// Bring the stored keys in line with `auth.signing_keys`
+///
+/// Creates the first key when stored keys are enabled, replaces the signing
+/// key when the algorithm changed or it is due for rotation, retires it when
+/// stored keys are disabled again, and deletes keys past their grace window.
+pub async fn sync_keys(
+    pool: &DbPool,
+    cipher: Option<&SettingsCipher>,
+    config: &AuthConfig,
+    now: DateTime<Utc>,
+) -> Result<()> {
+    let repo = JwtKeyRepository::new(pool, cipher);
+    let deleted = repo.delete_expired(now).await?;
+    if deleted > 0 {
+        info!("Deleted {} expired JWT signing keys", deleted);
+    }
+
+    let settings = &config.signing_keys;
+    let grace_until = now + grace_period(config);
+    let keys = repo.list().await?;
+    let active = keys
+        .iter()
+        .find(|key| key.kid != LEGACY_KID && key.retired_at.is_none());
+
+    match active {
+        Some(key) if !settings.uses_stored_keys() => {
+            repo.retire(&key.kid, grace_until).await?;
+            info!(
+                "Retired JWT signing key {}; signing tokens with auth.jwt_secret",
+                key.kid
+            );
+        }
+        Some(key) if needs_replacement(key, config, now) => {
+            repo.retire(&key.kid, grace_until).await?;
+            let new_key = create_key(&repo, settings.algorithm, now).await?;
+            info!(
+                "Rotated JWT signing key {} to {} ({}); the old key verifies tokens until {}",
+                key.kid,
+                new_key.kid,
+                new_key.algorithm,
+                grace_until.to_rfc3339()
+            );
+        }
+        Some(_) => {}
+        None if settings.uses_stored_keys() => {
+            // Tokens signed with jwt_secret so far stay valid for the grace window
+            repo.upsert(&JwtSigningKey {
+                kid: LEGACY_KID.to_string(),
+                algorithm: JwtAlgorithm::Hs256.as_str().to_string(),
+                private_key: None,
+                public_key: None,
+                created_at: now,
+                retired_at: Some(now),
+                expires_at: Some(grace_until),
+            })
+            .await?;
+            let new_key = create_key(&repo, settings.algorithm, now).await?;
+            info!(
+                "Created JWT signing key {} ({}); tokens signed with auth.jwt_secret are accepted until {}",
+                new_key.kid,
+                new_key.algorithm,
+                grace_until.to_rfc3339()
+            );
+        }
+        None => {}
+    }
+    Ok(())
+}
+
+/// How long a replaced key keeps verifying tokens
+pub fn grace_period(config: &AuthConfig) -> chrono::Duration {
+    let hours = config.signing_keys.grace_hours.unwrap_or_else(|| {
+        config
+            .token_expiry_hours
+            .max(config.refresh_token_expiry_days * 24)
+    });
+    chrono::Duration::hours(hours as i64)
+}
+
+/// Whether the signing key must be replaced by one of the configured kind
+fn needs_replacement(key: &JwtSigningKey, config: &AuthConfig, now: DateTime<Utc>) -> bool {
+    let settings = &config.signing_keys;
+    key.algorithm != settings.algorithm.as_str()
+        || (settings.rotation_days > 0
+            && now - key.created_at >= chrono::Duration::days(settings.rotation_days as i64))
+}
+
+async fn create_key(
+    repo: &JwtKeyRepository<'_>,
+    algorithm: JwtAlgorithm,
+    now: DateTime<Utc>,
+) -> Result<JwtSigningKey> {
+    // RSA key generation takes a while
+    let key = tokio::task::spawn_blocking(move || generate_key(algorithm, now))
+        .await
+        .context("JWT key generation panicked")??;
+    repo.upsert(&key).await?;
+    Ok(key)
+}
+
+/// Generate a signing key
+pub fn generate_key(algorithm: JwtAlgorithm, now: DateTime<Utc>) -> Result<JwtSigningKey> {
+    use rand::Rng;
+
+    let kid = Uuid::new_v4().to_string();
+    let (private_key, public_key) = match algorithm {
+        JwtAlgorithm::Hs256 => {
+            let mut secret = [0u8; HMAC_SECRET_BYTES];
+            rand::rng().fill_bytes(&mut secret);
+            let encoded = BASE64.encode(secret);
+            secret.fill(0);
+            (encoded, None)
+        }
+        JwtAlgorithm::EdDsa => {
+            use ed25519_dalek::pkcs8::EncodePrivateKey;
+
+            let mut seed = [0u8; 32];
+            rand::rng().fill_bytes(&mut seed);
+            let key = ed25519_dalek::SigningKey::from_bytes(&seed);
+            seed.fill(0);
+            let der = key
+                .to_pkcs8_der()
+                .map_err(|e| anyhow!("Failed to encode Ed25519 key: {}", e))?;
+            let jwk = serde_json::json!({
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "x": URL_SAFE_NO_PAD.encode(key.verifying_key().as_bytes()),
+                "kid": kid,
+                "alg": "EdDSA",
+                "use": "sig",
+            });
+            (BASE64.encode(der.as_bytes()), Some(jwk.to_string()))
+        }
+        JwtAlgorithm::Rs256 => {
+            use rsa::pkcs1::EncodeRsaPrivateKey;
+            use rsa::traits::PublicKeyParts;
+
+            let key = rsa::RsaPrivateKey::new(&mut rsa::rand_core::OsRng, RSA_KEY_BITS)
+                .context("Failed to generate RSA key")?;
+            let der = key
+                .to_pkcs1_der()
+                .map_err(|e| anyhow!("Failed to encode RSA key: {}", e))?;
+            let jwk = serde_json::json!({
+                "kty": "RSA",
+                "n": URL_SAFE_NO_PAD.encode(key.n().to_bytes_be()),
+                "e": URL_SAFE_NO_PAD.encode(key.e().to_bytes_be()),
+                "kid": kid,
+                "alg": "RS256",
+                "use": "sig",
+            });
+            (BASE64.encode(der.as_bytes()), Some(jwk.to_string()))
+        }
+    };
+
+    Ok(JwtSigningKey {
+        kid,
+        algorithm: algorithm.as_str().to_string(),
+        private_key: Some(private_key),
+        public_key,
+        created_at: now,
+        retired_at: None,
+        expires_at: None,
+    })
+}
+
+struct DecodedKey {
+    algorithm: Algorithm,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    jwk: Option<Jwk>,
+}
+
+fn decode_key(key: &JwtSigningKey) -> Result<DecodedKey> {
+    let material = BASE64
+        .decode(key.private_key.as_deref().context("Key material missing")?)
+        .context("Invalid key material")?;
+    let algorithm = match key.algorithm.as_str() {
+        "HS256" => {
+            return Ok(DecodedKey {
+                algorithm: Algorithm::HS256,
+                encoding_key: EncodingKey::from_secret(&material),
+                decoding_key: DecodingKey::from_secret(&material),
+                jwk: None,
+            })
+        }
+        "RS256" => Algorithm::RS256,
+        "EdDSA" => Algorithm::EdDSA,
+        other => anyhow::bail!("Unsupported algorithm '{}'", other),
+    };
+
+    let jwk: Jwk = serde_json::from_str(key.public_key.as_deref().context("Public key missing")?)
+        .context("Invalid public key")?;
+    let encoding_key = match algorithm {
+        Algorithm::RS256 => EncodingKey::from_rsa_der(&material),
+        _ => EncodingKey::from_ed_der(&material),
+    };
+    Ok(DecodedKey {
+        algorithm,
+        encoding_key,
+        decoding_key: DecodingKey::from_jwk(&jwk).context("Invalid public key")?,
+        jwk: Some(jwk),
+    })
+}
+
+/// Handle for stopping the rotation task
+#[derive(Clone)]
+pub struct JwtKeyRotationState {
+    running: Arc<RwLock<bool>>,
+    pool: DbPool,
+    keys: JwtKeys,
+    config: AuthConfig,
+}
+
+impl JwtKeyRotationState {
+    pub async fn is_running(&self) -> bool {
+        *self.running.read().await
+    }
+
+    /// Request the rotation loop to stop at its next tick
+    pub async fn stop(&self) {
+        *self.running.write().await = false;
+        info!("JWT signing key rotation stop requested");
+    }
+}
+
+/// Spawn the background task that rotates stored signing keys
+///
+/// Not started while tokens are signed with `auth.jwt_secret`.
+pub fn start_jwt_key_rotation(
+    pool: DbPool,
+    keys: JwtKeys,
+    config: &AuthConfig,
+) -> Option<JwtKeyRotationState> {
+    if !config.signing_keys.uses_stored_keys() {
+        return None;
+    }
+    let state = JwtKeyRotationState {
+        running: Arc::new(RwLock::new(true)),
+        pool,
+        keys,
+        config: config.clone(),
+    };
+
+    let loop_state = state.clone();
+    tokio::spawn(async move {
+        rotation_loop(loop_state).await;
+    });
+
+    info!(
+        "JWT signing key rotation started ({} keys, rotating {})",
+        config.signing_keys.algorithm.as_str(),
+        match config.signing_keys.rotation_days {
+            0 => "never".to_string(),
+            days => format!("every {} days", days),
+        }
+    );
+    Some(state)
+}
+
+async fn rotation_loop(state: JwtKeyRotationState) {
+    let mut timer = interval(Duration::from_secs(ROTATION_CHECK_INTERVAL_SECS));
+    // The keys were just synced at startup
+    timer.tick().await;
+
+    loop {
+        timer.tick().await;
+
+        if !*state.running.read().await {
+            info!("JWT signing key rotation stopping");
+            break;
+        }
+
+        if let Err(e) = state.keys.reload(&state.pool, &state.config).await {
+            error!("JWT signing key rotation failed: {:#}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SigningKeysConfig;
+    use jsonwebtoken::{decode, encode, Header, Validation};
+    use serde_json::Value;
+
+    fn auth_config(signing_keys: SigningKeysConfig) -> AuthConfig {
+        let mut config = crate::config::AppConfig::default().auth;
+        config.signing_keys = signing_keys;
+        config
+    }
+
+    fn sign(keyring: &Keyring) -> String {
+        let key = keyring.signing_key().unwrap();
+        let mut header = Header::new(key.algorithm);
+        header.kid = Some(key.kid.clone());
+        let claims = serde_json::json!({"sub": "alice", "exp": Utc::now().timestamp() + 60});
+        encode(&header, &claims, &key.encoding_key).unwrap()
+    }
+
+    fn verify(keyring: &Keyring, kid: &str, token: &str, now: DateTime<Utc>) -> bool {
+        let Some((decoding_key, algorithm)) = keyring.verifying_key(kid, now) else {
+            return false;
+        };
+        decode::<Value>(token, decoding_key, &Validation::new(algorithm)).is_ok()
+    }
+
+    #[test]
+    fn test_signing_keys_config() {
+        let config: SigningKeysConfig =
+            serde_norway::from_str("algorithm: EdDSA\nrotation_days: 30").unwrap();
+        assert_eq!(config.algorithm, JwtAlgorithm::EdDsa);
+        assert!(config.uses_stored_keys());
+
+        assert!(!SigningKeysConfig::default().uses_stored_keys());
+        let rotated_hmac = SigningKeysConfig {
+            rotation_days: 7,
+            ..Default::default()
+        };
+        assert!(rotated_hmac.uses_stored_keys());
+
+        // Grace defaults to the longest token lifetime
+        let auth = auth_config(SigningKeysConfig::default());
+        assert_eq!(grace_period(&auth), chrono::Duration::days(7));
+        let auth = auth_config(SigningKeysConfig {
+            grace_hours: Some(2),
+            ..Default::default()
+        });
+        assert_eq!(grace_period(&auth), chrono::Duration::hours(2));
+    }
+
+    #[test]
+    fn test_needs_replacement() {
+        let now = Utc::now();
+        let key = generate_key(JwtAlgorithm::EdDsa, now - chrono::Duration::days(10)).unwrap();
+
+        let auth = auth_config(SigningKeysConfig {
+            algorithm: JwtAlgorithm::EdDsa,
+            ..Default::default()
+        });
+        assert!(!needs_replacement(&key, &auth, now));
+
+        let auth = auth_config(SigningKeysConfig {
+            algorithm: JwtAlgorithm::EdDsa,
+            rotation_days: 10,
+            ..Default::default()
+        });
+        assert!(needs_replacement(&key, &auth, now));
+
+        let auth = auth_config(SigningKeysConfig {
+            algorithm: JwtAlgorithm::Rs256,
+            ..Default::default()
+        });
+        assert!(needs_replacement(&key, &auth, now));
+    }
+
+    #[test]
+    fn test_keyring_rotation_grace() {
+        let now = Utc::now();
+        let mut old = generate_key(JwtAlgorithm::EdDsa, now).unwrap();
+        let old_keyring = Keyring::from_keys(std::slice::from_ref(&old)).unwrap();
+        let token = sign(&old_keyring);
+        assert!(verify(&old_keyring, &old.kid, &token, now));
+        assert!(!old_keyring.accepts_secret_tokens(now));
+
+        // Retired keys verify tokens until their grace window ends
+        old.retired_at = Some(now);
+        old.expires_at = Some(now + chrono::Duration::hours(1));
+        let new = generate_key(JwtAlgorithm::Hs256, now).unwrap();
+        let legacy = JwtSigningKey {
+            kid: LEGACY_KID.to_string(),
+            algorithm: "HS256".to_string(),
+            private_key: None,
+            public_key: None,
+            created_at: now,
+            retired_at: Some(now),
+            expires_at: Some(now + chrono::Duration::hours(1)),
+        };
+        let keyring = Keyring::from_keys(&[legacy, old.clone(), new.clone()]).unwrap();
+
+        assert_eq!(keyring.signing_key().unwrap().kid, new.kid);
+        assert!(verify(&keyring, &old.kid, &token, now));
+        assert!(!verify(
+            &keyring,
+            &old.kid,
+            &token,
+            now + chrono::Duration::hours(2)
+        ));
+        assert!(verify(&keyring, &new.kid, &sign(&keyring), now));
+        assert!(!verify(&keyring, "unknown", &token, now));
+
+        assert!(keyring.accepts_secret_tokens(now));
+        assert!(!keyring.accepts_secret_tokens(now + chrono::Duration::hours(2)));
+
+        // Only the asymmetric key is published
+        let jwks = keyring.jwks(now);
+        assert_eq!(jwks.keys.len(), 1);
+        assert!(jwks.find(&old.kid).is_some());
+        assert!(keyring
+            .jwks(now + chrono::Duration::hours(2))
+            .keys
+            .is_empty());
+    }
+
+    async fn test_pool() -> DbPool {
+        let pool = sqlx::sqlite::SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("./migrations").run(&pool).await.unwrap();
+        pool
+    }
+
+    async fn load(pool: &DbPool) -> Keyring {
+        let keys = JwtKeyRepository::new(pool, None).list().await.unwrap();
+        Keyring::from_keys(&keys).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_sync_keys_rotation() {
+        let pool = test_pool().await;
+        let auth = auth_config(SigningKeysConfig {
+            algorithm: JwtAlgorithm::EdDsa,
+            rotation_days: 30,
+            grace_hours: Some(2),
+        });
+        let now = Utc::now();
+        sync_keys(&pool, None, &auth, now).await.unwrap();
+        let keyring = load(&pool).await;
+        let old_kid = keyring.signing_key().unwrap().kid.clone();
+        let token = sign(&keyring);
+
+        // Nothing changes before the key is due
+        sync_keys(&pool, None, &auth, now + chrono::Duration::days(29))
+            .await
+            .unwrap();
+        assert_eq!(load(&pool).await.signing_key().unwrap().kid, old_kid);
+
+        // A token signed with the previous key validates during the grace window
+        let rotated_at = now + chrono::Duration::days(30);
+        sync_keys(&pool, None, &auth, rotated_at).await.unwrap();
+        let keyring = load(&pool).await;
+        let new_kid = keyring.signing_key().unwrap().kid.clone();
+        assert_ne!(new_kid, old_kid);
+        assert!(verify(&keyring, &old_kid, &token, rotated_at));
+        assert!(verify(&keyring, &new_kid, &sign(&keyring), rotated_at));
+
+        // ... and is rejected once it ends
+        let after_grace = rotated_at + chrono::Duration::hours(3);
+        assert!(!verify(&keyring, &old_kid, &token, after_grace));
+        sync_keys(&pool, None, &auth, after_grace).await.unwrap();
+        let keyring = load(&pool).await;
+        assert!(!verify(&keyring, &old_kid, &token, after_grace));
+        assert!(!JwtKeyRepository::new(&pool, None)
+            .list()
+            .await
+            .unwrap()
+            .iter()
+            .any(|key| key.kid == old_kid));
+
+        // A kid the keyring never had is rejected
+        let unknown = Uuid::new_v4().to_string();
+        assert!(!verify(&keyring, &unknown, &token, rotated_at));
+    }
+
+    #[test]
+    fn test_keyring_rejects_undecodable_signing_key() {
+        let now = Utc::now();
+        let mut key = generate_key(JwtAlgorithm::EdDsa, now).unwrap();
+        key.public_key = None;
+        assert!(Keyring::from_keys(std::slice::from_ref(&key)).is_err());
+
+        // A broken retired key is skipped
+        key.retired_at = Some(now);
+        let keyring = Keyring::from_keys(&[key]).unwrap();
+        assert!(keyring.signing_key().is_none());
+        assert!(keyring.accepts_secret_tokens(now));
+    }
+}
@@ -0,0 +1,318 @@
+//! In-memory buffer of recent log records
+//!
+//! A `tracing` layer copies every emitted event into a bounded ring buffer
+//! and broadcasts it to live subscribers, so administrators can read and
+//! follow the application log through the API without shell access to the
+//! host. Only events that pass the configured log level are recorded.
+
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Capacity of the broadcast channel used for follow mode
+const FOLLOW_CHANNEL_CAPACITY: usize = 1024;
+
+/// Maximum records returned by a single query
+pub const MAX_QUERY_LIMIT: usize = 5000;
+
+/// A captured log record
+#[derive(Debug, Clone, Serialize)]
+pub struct LogRecord {
+    /// Monotonic sequence number, usable as a cursor
+    pub seq: u64,
+    pub timestamp: DateTime<Utc>,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    /// Structured fields other than the message
+    #[serde(skip_serializing_if = "serde_json::Map::is_empty")]
+    pub fields: serde_json::Map<String, serde_json::Value>,
+    #[serde(skip)]
+    level_value: Level,
+}
+
+/// Filter applied to buffered and followed records
+#[derive(Debug, Clone, Default)]
+pub struct LogFilter {
+    /// Minimum level (`error`, `warn`, `info`, `debug`, `trace`)
+    pub level: Option<String>,
+    /// Target prefix, e.g. `openvox_webui::services::puppetdb`
+    pub target: Option<String>,
+    /// Case-insensitive substring of the message or fields
+    pub search: Option<String>,
+    /// Only records with a sequence number greater than this
+    pub after: Option<u64>,
+}
+
+impl LogFilter {
+    /// Parse the level filter
+    pub fn min_level(&self) -> Result<Option<Level>, String> {
+        self.level
+            .as_deref()
+            .filter(|l| !l.is_empty())
+            .map(|l| {
+                l.parse::<Level>()
+                    .map_err(|_| format!("Invalid log level '{}'", l))
+            })
+            .transpose()
+    }
+
+    /// Returns true if `record` passes the filter
+    pub fn matches(&self, record: &LogRecord, min_level: Option<Level>) -> bool {
+        // Level ordering: ERROR is the least verbose
+        if min_level.is_some_and(|min| record.level_value > min) {
+            return false;
+        }
+        if self.after.is_some_and(|after| record.seq <= after) {
+            return false;
+        }
+        if let Some(target) = self.target.as_deref().filter(|t| !t.is_empty()) {
+            if !record.target.starts_with(target) {
+                return false;
+            }
+        }
+        if let Some(search) = self.search.as_deref().filter(|s| !s.is_empty()) {
+            let search = search.to_lowercase();
+            let in_fields = record
+                .fields
+                .values()
+                .any(|v| v.to_string().to_lowercase().contains(&search));
+            if !record.message.to_lowercase().contains(&search) && !in_fields {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+struct BufferState {
+    records: VecDeque<LogRecord>,
+    next_seq: u64,
+}
+
+/// Bounded buffer of recent log records
+pub struct LogBuffer {
+    capacity: usize,
+    state: Mutex<BufferState>,
+    sender: broadcast::Sender<LogRecord>,
+}
+
+impl LogBuffer {
+    /// Create a buffer keeping the last `capacity` records
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(FOLLOW_CHANNEL_CAPACITY);
+        Self {
+            capacity,
+            state: Mutex::new(BufferState {
+                records: VecDeque::with_capacity(capacity.min(4096)),
+                next_seq: 0,
+            }),
+            sender,
+        }
+    }
+
+    /// Maximum number of buffered records
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    fn push(
+        &self,
+        level: Level,
+        target: &str,
+        message: String,
+        fields: serde_json::Map<String, serde_json::Value>,
+    ) {
+        let record = {
+            let Ok(mut state) = self.state.lock() else {
+                return;
+            };
+            state.next_seq += 1;
+            let record = LogRecord {
+                seq: state.next_seq,
+                timestamp: Utc::now(),
+                level: level.to_string(),
+                target: target.to_string(),
+                message,
+                fields,
+                level_value: level,
+            };
+            if state.records.len() >= self.capacity {
+                state.records.pop_front();
+            }
+            state.records.push_back(record.clone());
+            record
+        };
+
+        // No receivers is the normal case
+        let _ = self.sender.send(record);
+    }
+
+    /// The most recent `limit` records matching `filter`, oldest first
+    pub fn query(
+        &self,
+        filter: &LogFilter,
+        min_level: Option<Level>,
+        limit: usize,
+    ) -> Vec<LogRecord> {
+        let state = self.state.lock().unwrap();
+        let mut records: Vec<LogRecord> = state
+            .records
+            .iter()
+            .rev()
+            .filter(|record| filter.matches(record, min_level))
+            .take(limit)
+            .cloned()
+            .collect();
+        records.reverse();
+        records
+    }
+
+    /// Subscribe to records as they are logged
+    pub fn subscribe(&self) -> broadcast::Receiver<LogRecord> {
+        self.sender.subscribe()
+    }
+}
+
+/// `tracing` layer feeding a [`LogBuffer`]
+pub struct LogBufferLayer {
+    buffer: Arc<LogBuffer>,
+}
+
+impl LogBufferLayer {
+    pub fn new(buffer: Arc<LogBuffer>) -> Self {
+        Self { buffer }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for LogBufferLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = RecordVisitor::default();
+        event.record(&mut visitor);
+
+        let metadata = event.metadata();
+        self.buffer.push(
+            *metadata.level(),
+            metadata.target(),
+            visitor.message,
+            visitor.fields,
+        );
+    }
+}
+
+#[derive(Default)]
+struct RecordVisitor {
+    message: String,
+    fields: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Visit for RecordVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields
+                .insert(field.name().to_string(), serde_json::Value::from(value));
+        }
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.fields
+            .insert(field.name().to_string(), serde_json::Value::from(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.fields
+            .insert(field.name().to_string(), serde_json::Value::from(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.fields
+            .insert(field.name().to_string(), serde_json::Value::from(value));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        let mut text = String::new();
+        let _ = write!(text, "{:?}", value);
+        if field.name() == "message" {
+            self.message = text;
+        } else {
+            self.fields
+                .insert(field.name().to_string(), serde_json::Value::from(text));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::prelude::*;
+
+    fn buffer_with_events() -> Arc<LogBuffer> {
+        let buffer = Arc::new(LogBuffer::new(3));
+        let subscriber = tracing_subscriber::registry().with(LogBufferLayer::new(buffer.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(target: "openvox_webui::api", "first");
+            tracing::debug!(target: "openvox_webui::db", "second");
+            tracing::warn!(target: "openvox_webui::api", certname = "web01", "third");
+            tracing::error!(target: "openvox_webui::services::puppetdb", "fourth");
+        });
+
+        buffer
+    }
+
+    #[test]
+    fn test_ring_buffer_keeps_latest() {
+        let buffer = buffer_with_events();
+        let records = buffer.query(&LogFilter::default(), None, 10);
+        let messages: Vec<_> = records.iter().map(|r| r.message.as_str()).collect();
+        assert_eq!(messages, vec!["second", "third", "fourth"]);
+        assert_eq!(records[1].fields["certname"], "web01");
+        assert_eq!(records[2].seq, 4);
+    }
+
+    #[test]
+    fn test_filters() {
+        let buffer = buffer_with_events();
+
+        let filter = LogFilter {
+            level: Some("warn".to_string()),
+            ..Default::default()
+        };
+        let min_level = filter.min_level().unwrap();
+        assert_eq!(buffer.query(&filter, min_level, 10).len(), 2);
+
+        let filter = LogFilter {
+            target: Some("openvox_webui::api".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(buffer.query(&filter, None, 10).len(), 1);
+
+        let filter = LogFilter {
+            search: Some("WEB01".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(buffer.query(&filter, None, 10)[0].message, "third");
+
+        let filter = LogFilter {
+            after: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(buffer.query(&filter, None, 10)[0].message, "fourth");
+
+        assert!(LogFilter {
+            level: Some("loud".to_string()),
+            ..Default::default()
+        }
+        .min_level()
+        .is_err());
+    }
+}
@@ -0,0 +1,324 @@
+//! Login history and alerts
+//!
+//! Every password and SAML login of a known user is recorded with the client
+//! address, user agent and location. A successful login from a browser or
+//! location the user has not logged in from before, and a lockout of their
+//! account after failed attempts, notify the user in the app and optionally
+//! by email. See [`LoginAlertsConfig`].
+
+use std::net::IpAddr;
+
+use axum::http::HeaderMap;
+use chrono::{DateTime, Duration, Utc};
+use uuid::Uuid;
+
+use crate::{
+    config::LoginAlertsConfig,
+    db::LoginHistoryRepository,
+    middleware::auth::SessionClient,
+    models::{
+        LoginEvent, LoginMethod, NewNotification, NotificationAudience, NotificationType, User,
+    },
+    AppState,
+};
+
+/// Logins listed on `/auth/me`
+pub const RECENT_LOGINS: u32 = 10;
+
+/// Record a login attempt of a user and notify them if it is unusual
+///
+/// Failures are logged; a login never fails because it could not be
+/// recorded.
+pub async fn record_login(
+    state: &AppState,
+    user: &User,
+    method: LoginMethod,
+    client: &SessionClient,
+    headers: &HeaderMap,
+    success: bool,
+) {
+    let alerts = &state.config.auth.login_alerts;
+    let device = client.user_agent.as_deref().map(device_of);
+    let location = location_of(headers, alerts, client.ip_address.as_deref());
+    let repo = LoginHistoryRepository::new(&state.db);
+
+    // The first successful login of a user is not news
+    let (new_device, new_location) = if success {
+        match repo
+            .familiarity(user.id, device.as_deref(), location.as_deref())
+            .await
+        {
+            Ok(seen) if seen.has_history => (
+                device.is_some() && !seen.known_device,
+                location.is_some() && !seen.known_location,
+            ),
+            Ok(_) => (false, false),
+            Err(e) => {
+                tracing::warn!("Failed to read login history of {}: {}", user.username, e);
+                (false, false)
+            }
+        }
+    } else {
+        (false, false)
+    };
+
+    let event = LoginEvent {
+        id: Uuid::new_v4(),
+        user_id: user.id,
+        success,
+        method,
+        ip_address: client.ip_address.clone(),
+        user_agent: client.user_agent.clone(),
+        location,
+        new_device,
+        new_location,
+        created_at: Utc::now(),
+    };
+    if let Err(e) = repo.record(&event, device.as_deref()).await {
+        tracing::warn!("Failed to record login of {}: {}", user.username, e);
+        return;
+    }
+
+    let cutoff = event.created_at - Duration::days(alerts.history_days as i64);
+    if let Err(e) = repo.delete_before(cutoff).await {
+        tracing::warn!("Failed to prune login history: {}", e);
+    }
+
+    if (new_device && alerts.new_device) || (new_location && alerts.new_location) {
+        alert(
+            state,
+            user,
+            "New login to your account",
+            &unusual_login_message(&event),
+        );
+    }
+}
+
+/// Tell a user their account was locked after failed logins
+pub fn notify_locked(state: &AppState, user: &User, client: &SessionClient, until: DateTime<Utc>) {
+    let config = &state.config;
+    if !config.auth.login_alerts.failed_attempts {
+        return;
+    }
+    let origin = client
+        .ip_address
+        .as_deref()
+        .map(|ip| format!(" The last attempt came from IP {}.", ip))
+        .unwrap_or_default();
+    let message = format!(
+        "Your account was locked until {} after {} failed login attempts.{} If these were not you, change your password once the lock ends.",
+        until.format("%Y-%m-%d %H:%M UTC"),
+        config.rbac.max_failed_logins,
+        origin
+    );
+    alert(state, user, "Your account was locked", &message);
+}
+
+/// Send a security notification to a user, and an email if configured
+fn alert(state: &AppState, user: &User, title: &str, message: &str) {
+    state.notification_service.notify_in_background(
+        NotificationAudience::User(user.id),
+        NewNotification {
+            organization_id: Some(user.organization_id),
+            title: title.to_string(),
+            message: message.to_string(),
+            r#type: NotificationType::Warning,
+            category: Some("security".to_string()),
+            link: Some("/profile".to_string()),
+            expires_at: None,
+            metadata: None,
+            dedup_key: None,
+        },
+    );
+
+    if state.config.auth.login_alerts.email && !user.email.is_empty() {
+        let mailer = state.mailer();
+        let email = user.email.clone();
+        let username = user.username.clone();
+        let subject = format!("OpenVox: {}", title);
+        let text_body = message.to_string();
+        let html_body = format!("<p>{}</p>", html_escape(message));
+        tokio::spawn(async move {
+            if let Err(e) = mailer.send(&email, &subject, &text_body, &html_body).await {
+                tracing::error!("Failed to send login alert to {}: {}", username, e);
+            }
+        });
+    }
+}
+
+fn unusual_login_message(event: &LoginEvent) -> String {
+    let what = match (event.new_device, event.new_location) {
+        (true, true) => "a new device and location",
+        (true, false) => "a new device",
+        _ => "a new location",
+    };
+    format!(
+        "Your account was logged in to from {}: {}. If this was not you, change your password and end your other sessions.",
+        what,
+        describe_origin(event)
+    )
+}
+
+/// Browser, location and address of a login, as far as known
+fn describe_origin(event: &LoginEvent) -> String {
+    let mut parts = Vec::new();
+    if let Some(user_agent) = &event.user_agent {
+        parts.push(user_agent.clone());
+    }
+    if let Some(location) = &event.location {
+        parts.push(location.clone());
+    }
+    if let Some(ip) = &event.ip_address {
+        parts.push(format!("IP {}", ip));
+    }
+    if parts.is_empty() {
+        "an unknown client".to_string()
+    } else {
+        parts.join(", ")
+    }
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A user agent without its version numbers
+///
+/// Browser updates change the version on every release; the browser and
+/// operating system make the device.
+pub fn device_of(user_agent: &str) -> String {
+    let mut device = String::with_capacity(user_agent.len());
+    let mut run = String::new();
+    for c in user_agent.chars() {
+        if c.is_ascii_digit() || c == '.' || c == '_' {
+            run.push(c);
+            continue;
+        }
+        if !run.chars().any(|c| c.is_ascii_digit()) {
+            device.push_str(&run);
+        }
+        run.clear();
+        device.push(c);
+    }
+    if !run.chars().any(|c| c.is_ascii_digit()) {
+        device.push_str(&run);
+    }
+    device.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Location of a client from the configured geo headers, or else its network
+pub fn location_of(
+    headers: &HeaderMap,
+    alerts: &LoginAlertsConfig,
+    ip_address: Option<&str>,
+) -> Option<String> {
+    let header = |name: &Option<String>| {
+        name.as_deref()
+            .and_then(|name| headers.get(name))
+            .and_then(|value| value.to_str().ok())
+            .map(str::trim)
+            // Cloudflare reports unknown countries as XX
+            .filter(|value| !value.is_empty() && *value != "XX")
+            .map(|value| value.chars().take(100).collect::<String>())
+    };
+
+    match (header(&alerts.city_header), header(&alerts.country_header)) {
+        (Some(city), Some(country)) => Some(format!("{}, {}", city, country)),
+        (None, Some(country)) => Some(country),
+        _ => ip_address.and_then(client_network),
+    }
+}
+
+/// The /24 (IPv4) or /48 (IPv6) network of an address
+pub fn client_network(ip_address: &str) -> Option<String> {
+    match ip_address.parse::<IpAddr>().ok()? {
+        IpAddr::V4(ip) => {
+            let [a, b, c, _] = ip.octets();
+            Some(format!("{}.{}.{}.0/24", a, b, c))
+        }
+        IpAddr::V6(ip) => {
+            if let Some(ip) = ip.to_ipv4_mapped() {
+                return client_network(&ip.to_string());
+            }
+            let s = ip.segments();
+            Some(format!("{:x}:{:x}:{:x}::/48", s[0], s[1], s[2]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    #[test]
+    fn test_device_of() {
+        let chrome_120 = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/120.0.6099.71 Safari/537.36";
+        let chrome_121 = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/121.0.6167.85 Safari/537.36";
+        assert_eq!(device_of(chrome_120), device_of(chrome_121));
+        assert_eq!(
+            device_of(chrome_120),
+            "Mozilla/ (Windows NT ; Win; x) AppleWebKit/ (KHTML, like Gecko) Chrome/ Safari/"
+        );
+
+        let firefox = "Mozilla/5.0 (X11; Linux x86_64; rv:121.0) Gecko/20100101 Firefox/121.0";
+        assert_ne!(device_of(chrome_120), device_of(firefox));
+        assert_eq!(device_of("curl"), "curl");
+    }
+
+    #[test]
+    fn test_client_network() {
+        assert_eq!(
+            client_network("203.0.113.77").as_deref(),
+            Some("203.0.113.0/24")
+        );
+        assert_eq!(
+            client_network("2001:db8:abcd:12::1").as_deref(),
+            Some("2001:db8:abcd::/48")
+        );
+        assert_eq!(
+            client_network("::ffff:192.0.2.9").as_deref(),
+            Some("192.0.2.0/24")
+        );
+        assert_eq!(client_network("not an ip"), None);
+    }
+
+    #[test]
+    fn test_location_of() {
+        let alerts = LoginAlertsConfig {
+            country_header: Some("CF-IPCountry".to_string()),
+            city_header: Some("CF-IPCity".to_string()),
+            ..Default::default()
+        };
+        let mut headers = HeaderMap::new();
+        assert_eq!(
+            location_of(&headers, &alerts, Some("203.0.113.77")).as_deref(),
+            Some("203.0.113.0/24")
+        );
+
+        headers.insert("cf-ipcountry", HeaderValue::from_static("DE"));
+        assert_eq!(
+            location_of(&headers, &alerts, Some("203.0.113.77")).as_deref(),
+            Some("DE")
+        );
+        headers.insert("cf-ipcity", HeaderValue::from_static("Berlin"));
+        assert_eq!(
+            location_of(&headers, &alerts, None).as_deref(),
+            Some("Berlin, DE")
+        );
+
+        headers.insert("cf-ipcountry", HeaderValue::from_static("XX"));
+        assert_eq!(location_of(&headers, &alerts, None), None);
+
+        // Headers are ignored unless configured
+        let mut headers = HeaderMap::new();
+        headers.insert("cf-ipcountry", HeaderValue::from_static("DE"));
+        assert_eq!(
+            location_of(&headers, &LoginAlertsConfig::default(), Some("192.0.2.1")).as_deref(),
+            Some("192.0.2.0/24")
+        );
+    }
+}
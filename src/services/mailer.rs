@@ -0,0 +1,195 @@
+//! Outgoing email over the SMTP settings configured in Admin Settings
+//!
+//! Used for account emails such as password reset links and for scheduled
+//! report emails. Alert channels keep their own copy of the SMTP settings but
+//! share the transport setup.
+
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use lettre::message::{header, Attachment, Message, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
+use sqlx::SqlitePool;
+
+use crate::db::SettingsRepository;
+use crate::models::SmtpSettings;
+use crate::services::secrets::{self, SecretsResolver};
+use crate::services::settings_encryption::SettingsCipher;
+
+/// Build an SMTP transport
+///
+/// `password` may be a secret reference; it is resolved through `secrets` on
+/// every call so rotated secrets are picked up.
+pub async fn smtp_transport(
+    host: &str,
+    port: u16,
+    use_tls: bool,
+    username: Option<&str>,
+    password: Option<&str>,
+    secrets: Option<&SecretsResolver>,
+) -> Result<AsyncSmtpTransport<Tokio1Executor>> {
+    let mut builder = if use_tls {
+        AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+            .context("Failed to create SMTP transport")?
+    } else {
+        AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(host)
+    };
+
+    builder = builder.port(port);
+
+    if let (Some(username), Some(password)) = (username, password) {
+        if !username.is_empty() && !password.is_empty() {
+            let password = secrets::resolve_runtime_value(secrets, password)
+                .await
+                .context("Failed to resolve SMTP password")?;
+            builder = builder.credentials(Credentials::new(username.to_string(), password));
+        }
+    }
+
+    Ok(builder.build())
+}
+
+/// Sends email through the SMTP server from the settings
+pub struct Mailer {
+    pool: SqlitePool,
+    cipher: Option<SettingsCipher>,
+    secrets: Option<Arc<SecretsResolver>>,
+}
+
+impl Mailer {
+    /// `cipher` decrypts the stored SMTP password and `secrets` resolves it
+    /// when it is a secret reference
+    pub fn new(
+        pool: SqlitePool,
+        cipher: Option<SettingsCipher>,
+        secrets: Option<Arc<SecretsResolver>>,
+    ) -> Self {
+        Self {
+            pool,
+            cipher,
+            secrets,
+        }
+    }
+
+    /// Send a plain text email with an HTML alternative
+    pub async fn send(
+        &self,
+        to: &str,
+        subject: &str,
+        text_body: &str,
+        html_body: &str,
+    ) -> Result<()> {
+        let smtp = self.smtp_settings().await?;
+        let email = build_message(&smtp, to, subject, text_body, html_body)?;
+        self.deliver(&smtp, email).await?;
+        tracing::info!("Email sent to {} via {}:{}", to, smtp.host, smtp.port);
+
+        Ok(())
+    }
+
+    /// Send a plain text email with an HTML alternative and a file attached
+    /// to several recipients
+    #[allow(clippy::too_many_arguments)]
+    pub async fn send_with_attachment(
+        &self,
+        to: &[String],
+        subject: &str,
+        text_body: &str,
+        html_body: &str,
+        filename: &str,
+        content_type: &str,
+        data: &[u8],
+    ) -> Result<()> {
+        let smtp = self.smtp_settings().await?;
+
+        let mut builder = Message::builder()
+            .from(smtp.from_address.parse().context("Invalid from address")?)
+            .subject(subject);
+        for address in to {
+            builder = builder.to(address
+                .parse()
+                .with_context(|| format!("Invalid to address: {}", address))?);
+        }
+        let attachment = Attachment::new(filename.to_string()).body(
+            data.to_vec(),
+            header::ContentType::parse(content_type).context("Invalid attachment content type")?,
+        );
+        let email = builder
+            .multipart(
+                MultiPart::mixed()
+                    .multipart(MultiPart::alternative_plain_html(
+                        text_body.to_string(),
+                        html_body.to_string(),
+                    ))
+                    .singlepart(attachment),
+            )
+            .context("Failed to build email")?;
+
+        self.deliver(&smtp, email).await?;
+        tracing::info!(
+            "Email with {} sent to {} recipients via {}:{}",
+            filename,
+            to.len(),
+            smtp.host,
+            smtp.port
+        );
+
+        Ok(())
+    }
+
+    async fn smtp_settings(&self) -> Result<SmtpSettings> {
+        let smtp = SettingsRepository::new(self.pool.clone(), self.cipher.clone())
+            .get_smtp_settings()
+            .await
+            .context("Failed to load SMTP settings")?;
+        if !smtp.configured {
+            anyhow::bail!(
+                "SMTP is not configured. Please configure SMTP settings in Admin Settings."
+            );
+        }
+        Ok(smtp)
+    }
+
+    async fn deliver(&self, smtp: &SmtpSettings, email: Message) -> Result<()> {
+        let mailer = smtp_transport(
+            &smtp.host,
+            smtp.port,
+            smtp.use_tls,
+            smtp.username.as_deref(),
+            smtp.password.as_deref(),
+            self.secrets.as_deref(),
+        )
+        .await?;
+
+        mailer.send(email).await.context("Failed to send email")?;
+        Ok(())
+    }
+}
+
+fn build_message(
+    smtp: &SmtpSettings,
+    to: &str,
+    subject: &str,
+    text_body: &str,
+    html_body: &str,
+) -> Result<Message> {
+    Message::builder()
+        .from(smtp.from_address.parse().context("Invalid from address")?)
+        .to(to.parse().context("Invalid to address")?)
+        .subject(subject)
+        .multipart(
+            MultiPart::alternative()
+                .singlepart(
+                    SinglePart::builder()
+                        .header(header::ContentType::TEXT_PLAIN)
+                        .body(text_body.to_string()),
+                )
+                .singlepart(
+                    SinglePart::builder()
+                        .header(header::ContentType::TEXT_HTML)
+                        .body(html_body.to_string()),
+                ),
+        )
+        .context("Failed to build email")
+}
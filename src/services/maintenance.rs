@@ -0,0 +1,422 @@
+//! Maintenance window evaluation
+//!
+//! Works out which windows are active and which nodes they cover. Explicit
+//! nodes are taken as-is, groups go through the canonical group resolver and
+//! fact queries through the smart list evaluator, so a window covers exactly
+//! the nodes the rest of the UI would show for the same criteria.
+
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration, Utc};
+use cron::Schedule;
+use sqlx::SqlitePool;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::db::repository::GroupRepository;
+use crate::db::MaintenanceWindowRepository;
+use crate::models::{
+    MaintenanceSchedule, MaintenanceTarget, MaintenanceWindow, Node, NodeInMaintenance,
+    NodeMaintenance, SmartListFilter,
+};
+use crate::services::puppetdb::{NodeStats, PuppetDbClient};
+use crate::services::scheduler::validate_cron_expression;
+use crate::services::smart_list::{facts_match, resolve_filter_nodes, validate_filter};
+
+/// Longest period a recurring window may last
+pub const MAX_RECURRING_DURATION_MINUTES: i64 = 7 * 24 * 60;
+
+/// `by_status` / `by_health` bucket holding failed nodes in maintenance
+pub const MAINTENANCE_BUCKET: &str = "maintenance";
+
+/// Validate a window's target and schedule before it is persisted
+pub fn validate_window(
+    target: &MaintenanceTarget,
+    schedule: &MaintenanceSchedule,
+) -> Result<(), String> {
+    match target {
+        MaintenanceTarget::Nodes { certnames } => {
+            if certnames.iter().all(|c| c.trim().is_empty()) {
+                return Err("A node target needs at least one certname".to_string());
+            }
+        }
+        MaintenanceTarget::Group { .. } => {}
+        MaintenanceTarget::FactQuery { facts } => {
+            if facts.is_empty() {
+                return Err("A fact query target needs at least one condition".to_string());
+            }
+            validate_filter(&fact_query_filter(facts))?;
+        }
+    }
+
+    match schedule {
+        MaintenanceSchedule::OneTime { starts_at, ends_at } => {
+            if ends_at <= starts_at {
+                return Err("ends_at must be after starts_at".to_string());
+            }
+        }
+        MaintenanceSchedule::Recurring {
+            cron_expression,
+            duration_minutes,
+        } => {
+            validate_cron_expression(cron_expression)?;
+            if !(1..=MAX_RECURRING_DURATION_MINUTES).contains(duration_minutes) {
+                return Err(format!(
+                    "duration_minutes must be between 1 and {}",
+                    MAX_RECURRING_DURATION_MINUTES
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The period of `schedule` that contains `now`, as `(start, end)`
+pub fn active_period(
+    schedule: &MaintenanceSchedule,
+    now: DateTime<Utc>,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    match schedule {
+        MaintenanceSchedule::OneTime { starts_at, ends_at } => {
+            (*starts_at <= now && now < *ends_at).then_some((*starts_at, *ends_at))
+        }
+        MaintenanceSchedule::Recurring {
+            cron_expression,
+            duration_minutes,
+        } => {
+            let cron = Schedule::from_str(cron_expression).ok()?;
+            let duration = Duration::minutes(*duration_minutes);
+            // Latest start within one duration of now; overlapping periods
+            // extend the window to the end of the latest one
+            let start = cron
+                .after(&(now - duration))
+                .take_while(|start| *start <= now)
+                .last()?;
+            Some((start, start + duration))
+        }
+    }
+}
+
+/// Start of the next period of `schedule` after `now`
+pub fn next_start(schedule: &MaintenanceSchedule, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    match schedule {
+        MaintenanceSchedule::OneTime { starts_at, .. } => (*starts_at > now).then_some(*starts_at),
+        MaintenanceSchedule::Recurring {
+            cron_expression, ..
+        } => Schedule::from_str(cron_expression).ok()?.after(&now).next(),
+    }
+}
+
+/// Maintenance annotation for `window` if it is enabled and active at `now`
+fn annotation(window: &MaintenanceWindow, now: DateTime<Utc>) -> Option<NodeMaintenance> {
+    if !window.enabled {
+        return None;
+    }
+    active_period(&window.schedule, now).map(|(_, ends_at)| NodeMaintenance {
+        window_id: window.id,
+        name: window.name.clone(),
+        ends_at,
+    })
+}
+
+fn fact_query_filter(facts: &[crate::models::SmartListFactCondition]) -> SmartListFilter {
+    SmartListFilter {
+        facts: facts.to_vec(),
+        ..Default::default()
+    }
+}
+
+/// Nodes covered by the active maintenance windows
+#[derive(Debug, Clone, Default)]
+pub struct ActiveMaintenance {
+    nodes: HashMap<String, Vec<NodeMaintenance>>,
+}
+
+impl ActiveMaintenance {
+    /// Resolve every enabled window that is active at `now`
+    ///
+    /// Without PuppetDB, group windows only cover pinned nodes and fact query
+    /// windows cover nothing.
+    pub async fn load(
+        pool: &SqlitePool,
+        puppetdb: Option<&PuppetDbClient>,
+        now: DateTime<Utc>,
+    ) -> Result<Self> {
+        let windows = MaintenanceWindowRepository::new(pool)
+            .list_enabled()
+            .await?;
+
+        let mut active = Self::default();
+        for window in &windows {
+            let Some(maintenance) = annotation(window, now) else {
+                continue;
+            };
+            for certname in resolve_target(pool, puppetdb, window).await? {
+                active
+                    .nodes
+                    .entry(certname)
+                    .or_default()
+                    .push(maintenance.clone());
+            }
+        }
+
+        Ok(active)
+    }
+
+    /// Like [`ActiveMaintenance::load`], but logs failures and treats every
+    /// node as out of maintenance so callers keep reporting failures
+    pub async fn load_or_empty(
+        pool: &SqlitePool,
+        puppetdb: Option<&PuppetDbClient>,
+        now: DateTime<Utc>,
+    ) -> Self {
+        Self::load(pool, puppetdb, now).await.unwrap_or_else(|e| {
+            warn!("Failed to resolve maintenance windows: {}", e);
+            Self::default()
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn contains(&self, certname: &str) -> bool {
+        self.nodes.contains_key(certname)
+    }
+
+    /// Active windows covering `certname`
+    pub fn for_node(&self, certname: &str) -> &[NodeMaintenance] {
+        self.nodes.get(certname).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn certnames(&self) -> impl Iterator<Item = &str> {
+        self.nodes.keys().map(String::as_str)
+    }
+
+    /// Nodes in maintenance, by certname
+    pub fn into_nodes(self) -> Vec<NodeInMaintenance> {
+        let mut nodes: Vec<NodeInMaintenance> = self
+            .nodes
+            .into_iter()
+            .map(|(certname, windows)| NodeInMaintenance { certname, windows })
+            .collect();
+        nodes.sort_by(|a, b| a.certname.cmp(&b.certname));
+        nodes
+    }
+
+    /// Move failed nodes in maintenance out of the failure buckets
+    ///
+    /// `nodes` must hold the PuppetDB records of the nodes in maintenance.
+    /// Their `failed` status is counted under [`MAINTENANCE_BUCKET`], and so
+    /// is their `critical` health when it stems from the failure rather than
+    /// from a stale report.
+    pub fn exclude_from_stats(&self, stats: &mut NodeStats, nodes: &[Node], now: DateTime<Utc>) {
+        for node in nodes.iter().filter(|n| self.contains(&n.certname)) {
+            if node.latest_report_status.as_deref() != Some("failed") {
+                continue;
+            }
+
+            move_count(&mut stats.by_status, "failed", MAINTENANCE_BUCKET);
+            let recent = node
+                .report_timestamp
+                .is_some_and(|t| now - t <= Duration::hours(6));
+            if recent {
+                move_count(&mut stats.by_health, "critical", MAINTENANCE_BUCKET);
+            }
+        }
+    }
+}
+
+fn move_count(buckets: &mut HashMap<String, u64>, from: &str, to: &str) {
+    let Some(count) = buckets.get_mut(from).filter(|c| **c > 0) else {
+        return;
+    };
+    *count -= 1;
+    *buckets.entry(to.to_string()).or_default() += 1;
+}
+
+/// Certnames covered by a window's target
+async fn resolve_target(
+    pool: &SqlitePool,
+    puppetdb: Option<&PuppetDbClient>,
+    window: &MaintenanceWindow,
+) -> Result<HashSet<String>> {
+    match &window.target {
+        MaintenanceTarget::Nodes { certnames } => Ok(certnames
+            .iter()
+            .map(|c| c.trim().to_string())
+            .filter(|c| !c.is_empty())
+            .collect()),
+        MaintenanceTarget::Group { group_id } => {
+            let members = crate::api::groups::classify_group_members(
+                &GroupRepository::new(pool),
+                puppetdb,
+                window.organization_id,
+                *group_id,
+            )
+            .await?;
+            Ok(members.into_iter().collect())
+        }
+        MaintenanceTarget::FactQuery { facts } => {
+            let Some(puppetdb) = puppetdb else {
+                return Ok(HashSet::new());
+            };
+            let nodes = resolve_filter_nodes(
+                pool,
+                puppetdb,
+                window.organization_id,
+                &fact_query_filter(facts),
+            )
+            .await?;
+            Ok(nodes.into_iter().map(|n| n.certname).collect())
+        }
+    }
+}
+
+/// Active windows covering a single node
+///
+/// Cheaper than [`ActiveMaintenance::load`] when the caller already has the
+/// node's classification facts and groups.
+pub async fn node_maintenance(
+    pool: &SqlitePool,
+    certname: &str,
+    facts: &serde_json::Value,
+    group_ids: &[Uuid],
+    now: DateTime<Utc>,
+) -> Result<Vec<NodeMaintenance>> {
+    let windows = MaintenanceWindowRepository::new(pool)
+        .list_enabled()
+        .await?;
+
+    Ok(windows
+        .iter()
+        .filter(|window| match &window.target {
+            MaintenanceTarget::Nodes { certnames } => {
+                certnames.iter().any(|c| c.trim() == certname)
+            }
+            MaintenanceTarget::Group { group_id } => group_ids.contains(group_id),
+            MaintenanceTarget::FactQuery { facts: conditions } => {
+                facts_match(&fact_query_filter(conditions), facts)
+            }
+        })
+        .filter_map(|window| annotation(window, now))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(h: u32, m: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 10, 16, h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn test_one_time_period() {
+        let schedule = MaintenanceSchedule::OneTime {
+            starts_at: at(2, 0),
+            ends_at: at(4, 0),
+        };
+        assert_eq!(active_period(&schedule, at(1, 59)), None);
+        assert_eq!(
+            active_period(&schedule, at(2, 0)),
+            Some((at(2, 0), at(4, 0)))
+        );
+        assert_eq!(active_period(&schedule, at(4, 0)), None);
+        assert_eq!(next_start(&schedule, at(1, 0)), Some(at(2, 0)));
+        assert_eq!(next_start(&schedule, at(3, 0)), None);
+    }
+
+    #[test]
+    fn test_recurring_period() {
+        // Daily at 02:00 for 90 minutes
+        let schedule = MaintenanceSchedule::Recurring {
+            cron_expression: "0 0 2 * * *".to_string(),
+            duration_minutes: 90,
+        };
+        assert_eq!(active_period(&schedule, at(1, 59)), None);
+        assert_eq!(
+            active_period(&schedule, at(2, 0)),
+            Some((at(2, 0), at(3, 30)))
+        );
+        assert_eq!(
+            active_period(&schedule, at(3, 29)),
+            Some((at(2, 0), at(3, 30)))
+        );
+        assert_eq!(active_period(&schedule, at(3, 30)), None);
+        assert_eq!(next_start(&schedule, at(1, 0)), Some(at(2, 0)));
+    }
+
+    #[test]
+    fn test_validate_window() {
+        let nodes = MaintenanceTarget::Nodes {
+            certnames: vec!["web01".to_string()],
+        };
+        let recurring = |cron: &str, minutes| MaintenanceSchedule::Recurring {
+            cron_expression: cron.to_string(),
+            duration_minutes: minutes,
+        };
+
+        assert!(validate_window(&nodes, &recurring("0 0 2 * * *", 60)).is_ok());
+        assert!(validate_window(&nodes, &recurring("invalid", 60)).is_err());
+        assert!(validate_window(&nodes, &recurring("0 0 2 * * *", 0)).is_err());
+        assert!(validate_window(
+            &nodes,
+            &MaintenanceSchedule::OneTime {
+                starts_at: at(4, 0),
+                ends_at: at(2, 0),
+            }
+        )
+        .is_err());
+        assert!(validate_window(
+            &MaintenanceTarget::Nodes { certnames: vec![] },
+            &recurring("0 0 2 * * *", 60)
+        )
+        .is_err());
+        assert!(validate_window(
+            &MaintenanceTarget::FactQuery { facts: vec![] },
+            &recurring("0 0 2 * * *", 60)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_exclude_from_stats() {
+        let now = at(12, 0);
+        let mut active = ActiveMaintenance::default();
+        for certname in ["web01", "web02", "web03"] {
+            active.nodes.insert(certname.to_string(), Vec::new());
+        }
+
+        let node = |certname: &str, status: &str, hours_ago: i64| Node {
+            certname: certname.to_string(),
+            latest_report_status: Some(status.to_string()),
+            report_timestamp: Some(now - Duration::hours(hours_ago)),
+            ..Default::default()
+        };
+        let nodes = vec![
+            node("web01", "failed", 1),
+            node("web02", "failed", 30),
+            node("web03", "changed", 1),
+            node("db01", "failed", 1),
+        ];
+
+        let mut stats = NodeStats::default();
+        stats.by_status.insert("failed".to_string(), 3);
+        stats.by_status.insert("changed".to_string(), 1);
+        stats.by_health.insert("critical".to_string(), 3);
+        stats.by_health.insert("healthy".to_string(), 1);
+
+        active.exclude_from_stats(&mut stats, &nodes, now);
+
+        assert_eq!(stats.by_status["failed"], 1);
+        assert_eq!(stats.by_status[MAINTENANCE_BUCKET], 2);
+        // web02's report is stale, so it stays critical
+        assert_eq!(stats.by_health["critical"], 2);
+        assert_eq!(stats.by_health[MAINTENANCE_BUCKET], 1);
+    }
+}
@@ -1,68 +1,132 @@
 //! Business logic services
 
+pub mod account_lockout;
 pub mod alerting;
+pub mod api_key_policy;
+pub mod audit_forwarding;
+pub mod audit_retention;
 pub mod auth;
 pub mod backup;
 pub mod backup_encryption;
 pub mod backup_scheduler;
+pub mod ca_snapshot;
 pub mod cache;
+pub mod cert_renewal;
 pub mod classification;
+pub mod code_canary;
 pub mod code_deploy;
 pub mod code_deploy_scheduler;
+pub mod compile_errors;
+pub mod config_reload;
+pub mod credential_health;
 pub mod cve_feed;
 pub mod cve_scheduler;
+pub mod deploy_strategy;
+pub mod deployment_impact;
+pub mod drift_snapshot;
+pub mod elevation;
 pub mod facter;
+pub mod fault_injection;
+pub mod fleet_metrics_scheduler;
 pub mod git;
+pub mod group_templates;
+pub mod health;
+pub mod hiera;
 pub mod inventory_maintenance;
 pub mod inventory_scheduler;
+pub mod jwt_keys;
+pub mod log_buffer;
+pub mod login_history;
+pub mod mailer;
+pub mod maintenance;
+pub mod node_janitor;
+pub mod node_performance;
 pub mod node_removal_scheduler;
 pub mod notification;
+pub mod organization_transfer;
+pub mod password_policy;
 pub mod puppet_ca;
 pub mod puppetdb;
+pub mod puppetdb_registry;
+pub mod quotas;
 pub mod r10k;
 pub mod rbac;
 pub mod rbac_db;
+pub mod recycle_bin;
 pub mod repo_checker;
 pub mod repo_checker_scheduler;
+pub mod report_delivery;
+pub mod report_diff;
+pub mod report_email;
+pub mod report_export;
+pub mod report_ingester;
+pub mod report_output;
 pub mod report_summary_scheduler;
 pub mod reporting;
+pub mod rule_packs;
 pub mod saml;
 pub mod scheduler;
+pub mod search_index;
+pub mod secrets;
+pub mod settings_encryption;
+pub mod smart_list;
+pub mod topology;
 pub mod update_schedule_scheduler;
+pub mod user_import;
+pub mod webhooks;
+pub mod widget_data;
 
 pub use alerting::AlertingService;
+pub use audit_retention::{start_audit_retention, AuditRetentionState};
 pub use auth::AuthService;
 pub use backup::BackupService;
 pub use backup_encryption::EncryptedData;
 pub use backup_scheduler::{start_backup_scheduler, BackupSchedulerState};
+pub use ca_snapshot::{start_ca_snapshot_sync, CaSnapshotSyncState};
 pub use cache::{
     Cache, CacheEntry, CacheEvictionStats, CacheServiceStats, CacheStats, CacheSyncJob,
     CachedPuppetDbService,
 };
+pub use cert_renewal::{start_cert_renewal_tracker, CertRenewalTrackerState};
 pub use code_deploy::{CodeDeployConfig, CodeDeployService};
 pub use code_deploy_scheduler::{start_code_deploy_scheduler, CodeDeploySchedulerState};
+pub use config_reload::{
+    start_config_watcher, ConfigReloader, ConfigWatcherState, RateLimitKind, ReloadResult,
+};
 pub use cve_scheduler::{start_cve_scheduler, CveSchedulerState};
+pub use elevation::{start_elevation_expiry, ElevationExpiryState};
 pub use facter::{ExportFormat, FacterService, GeneratedFacts};
+pub use fleet_metrics_scheduler::{start_fleet_metrics_scheduler, FleetMetricsSchedulerState};
 pub use git::{BranchInfo, CommitInfo, GitService, GitServiceConfig};
 pub use inventory_maintenance::{start_inventory_maintenance, InventoryMaintenanceState};
 pub use inventory_scheduler::{start_inventory_scheduler, InventorySchedulerState};
+pub use jwt_keys::{start_jwt_key_rotation, JwtKeyRotationState, JwtKeys};
 pub use node_removal_scheduler::{start_node_removal_scheduler, NodeRemovalSchedulerState};
-pub use notification::{NotificationEvent, NotificationService};
+pub use notification::{
+    start_notification_retention, NotificationEvent, NotificationRetentionState,
+    NotificationService,
+};
 pub use puppet_ca::PuppetCAService;
 pub use puppetdb::{
     Catalog, CatalogEdge, CatalogResource, Environment, Event, FactContent, FactPath,
     PaginatedResponse, PuppetDbClient, QueryBuilder, QueryParams, Resource, ResourceRef,
     ServerVersion,
 };
+pub use puppetdb_registry::PuppetDbRegistry;
 pub use r10k::{R10kConfig, R10kService, R10kSource};
 pub use rbac::RbacService;
 pub use rbac_db::DbRbacService;
+pub use recycle_bin::{start_recycle_bin_purge, RecycleBinPurgeState};
 pub use repo_checker::RepoCheckerService;
 pub use repo_checker_scheduler::{start_repo_checker_scheduler, RepoCheckerSchedulerState};
+pub use report_ingester::{start_report_ingester, ReportIngesterState};
+pub use report_output::{start_report_retention, ReportOutputStore, ReportRetentionState};
 pub use report_summary_scheduler::{start_report_summary_scheduler, ReportSummarySchedulerState};
 pub use reporting::ReportingService;
 pub use saml::{SamlAssertion, SamlService};
 pub use scheduler::{ReportScheduler, ScheduleExecutionResult};
+pub use search_index::SearchIndexer;
 pub use update_schedule_scheduler::{
     start_update_schedule_scheduler, UpdateScheduleSchedulerState,
 };
+pub use webhooks::{start_webhook_dispatcher, WebhookDispatcherState};
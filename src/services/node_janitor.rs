@@ -0,0 +1,253 @@
+//! Stale node janitor
+//!
+//! Finds nodes that have not submitted a report, facts or a catalog within
+//! `node_removal.janitor.stale_after_days` and purges them: they are
+//! deactivated in PuppetDB and/or marked for removal with the `stale_node`
+//! reason. Nodes matching an exclusion pattern are reported but left alone.
+//! Every run is stored as a report of what was purged.
+
+use chrono::{DateTime, Duration, Utc};
+use tracing::{debug, info, warn};
+use uuid::Uuid;
+
+use crate::config::NodeRemovalConfig;
+use crate::db::{DbPool, NodeRemovalRepository};
+use crate::models::{JanitorNodeResult, JanitorNodeStatus, JanitorRun, Node, RemovalReason};
+use crate::services::puppetdb::{PuppetDbClient, QueryBuilder};
+
+/// Latest report, facts or catalog submission of a node
+pub fn last_seen(node: &Node) -> Option<DateTime<Utc>> {
+    [
+        node.report_timestamp,
+        node.facts_timestamp,
+        node.catalog_timestamp,
+    ]
+    .into_iter()
+    .flatten()
+    .max()
+}
+
+/// Match a certname against a pattern with `*` and `?` wildcards
+///
+/// Matching is case-insensitive, like certnames themselves.
+pub fn matches_pattern(certname: &str, pattern: &str) -> bool {
+    let name: Vec<char> = certname.to_ascii_lowercase().chars().collect();
+    let pattern: Vec<char> = pattern.trim().to_ascii_lowercase().chars().collect();
+
+    let (mut n, mut p) = (0, 0);
+    // Position after the last `*` and the name position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while n < name.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == name[n]) {
+            n += 1;
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p + 1, n));
+            p += 1;
+        } else if let Some((star_p, star_n)) = backtrack {
+            p = star_p;
+            n = star_n + 1;
+            backtrack = Some((star_p, star_n + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Active nodes not seen since `cutoff`, oldest first
+fn find_stale_nodes(nodes: &[Node], cutoff: DateTime<Utc>) -> Vec<(String, Option<DateTime<Utc>>)> {
+    let mut stale: Vec<(String, Option<DateTime<Utc>>)> = nodes
+        .iter()
+        .filter(|n| n.deactivated.is_none())
+        .map(|n| (n.certname.clone(), last_seen(n)))
+        .filter(|(_, seen)| seen.is_none_or(|t| t < cutoff))
+        .collect();
+
+    stale.sort_by(|a, b| a.1.cmp(&b.1).then_with(|| a.0.cmp(&b.0)));
+    stale
+}
+
+/// Run the janitor once and store its report
+///
+/// With `dry_run` nothing is changed; the report lists the nodes that would
+/// have been purged and what would have been done to them.
+pub async fn run_janitor(
+    pool: &DbPool,
+    puppetdb: &PuppetDbClient,
+    config: &NodeRemovalConfig,
+    dry_run: bool,
+    triggered_by: Option<&str>,
+) -> anyhow::Result<JanitorRun> {
+    let janitor = &config.janitor;
+    let repo = NodeRemovalRepository::new(pool.clone());
+    let started_at = Utc::now();
+    let cutoff = started_at - Duration::days(janitor.stale_after_days);
+
+    let nodes = puppetdb.query_nodes(&QueryBuilder::new()).await?;
+    let stale = find_stale_nodes(&nodes, cutoff);
+    debug!(
+        "Janitor found {} stale node(s) among {} (cutoff {})",
+        stale.len(),
+        nodes.len(),
+        cutoff
+    );
+
+    let mut results = Vec::new();
+    for (certname, last_seen_at) in stale {
+        if repo.is_marked_for_removal(&certname).await? {
+            debug!("Janitor: '{}' is already pending removal", certname);
+            continue;
+        }
+
+        let mut result = JanitorNodeResult {
+            certname,
+            last_seen_at,
+            status: JanitorNodeStatus::Purged,
+            deactivated: false,
+            certificate_flagged: false,
+            error: None,
+        };
+
+        if janitor
+            .exclude
+            .iter()
+            .any(|pattern| matches_pattern(&result.certname, pattern))
+        {
+            result.status = JanitorNodeStatus::Excluded;
+            results.push(result);
+            continue;
+        }
+
+        if dry_run {
+            result.deactivated = janitor.deactivate;
+            result.certificate_flagged = janitor.flag_certificates;
+            results.push(result);
+            continue;
+        }
+
+        if janitor.deactivate {
+            match puppetdb.deactivate_node(&result.certname).await {
+                Ok(()) => result.deactivated = true,
+                Err(e) => {
+                    warn!("Janitor failed to deactivate '{}': {}", result.certname, e);
+                    result.status = JanitorNodeStatus::Failed;
+                    result.error = Some(format!("Deactivation failed: {}", e));
+                }
+            }
+        }
+
+        if janitor.flag_certificates && result.status == JanitorNodeStatus::Purged {
+            let notes = match last_seen_at {
+                Some(t) => format!("No report since {}", t.format("%Y-%m-%d %H:%M UTC")),
+                None => "Node never reported".to_string(),
+            };
+            match repo
+                .mark_for_removal(
+                    &result.certname,
+                    RemovalReason::StaleNode,
+                    config.retention_days,
+                    Some(&notes),
+                    triggered_by,
+                )
+                .await
+            {
+                Ok(_) => result.certificate_flagged = true,
+                Err(e) => {
+                    warn!("Janitor failed to flag '{}': {}", result.certname, e);
+                    result.status = JanitorNodeStatus::Failed;
+                    result.error = Some(format!("Flagging failed: {}", e));
+                }
+            }
+        }
+
+        results.push(result);
+    }
+
+    let count = |status: JanitorNodeStatus| results.iter().filter(|r| r.status == status).count();
+    let run = JanitorRun {
+        id: Uuid::new_v4().to_string(),
+        started_at,
+        finished_at: Utc::now(),
+        dry_run,
+        triggered_by: triggered_by.map(str::to_string),
+        stale_after_days: janitor.stale_after_days,
+        stale_count: results.len() as i64,
+        purged_count: count(JanitorNodeStatus::Purged) as i64,
+        excluded_count: count(JanitorNodeStatus::Excluded) as i64,
+        failed_count: count(JanitorNodeStatus::Failed) as i64,
+        nodes: results,
+    };
+    repo.save_janitor_run(&run).await?;
+
+    info!(
+        "Janitor run {}{}: {} stale, {} purged, {} excluded, {} failed",
+        run.id,
+        if dry_run { " (dry run)" } else { "" },
+        run.stale_count,
+        run.purged_count,
+        run.excluded_count,
+        run.failed_count
+    );
+
+    Ok(run)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn node(certname: &str, report: Option<DateTime<Utc>>) -> Node {
+        Node {
+            certname: certname.to_string(),
+            report_timestamp: report,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_matches_pattern() {
+        assert!(matches_pattern("db01.example.com", "db01.example.com"));
+        assert!(matches_pattern("db01.example.com", "DB*.example.com"));
+        assert!(matches_pattern("db01.example.com", "*.example.com"));
+        assert!(matches_pattern("db01.example.com", "db0?.*"));
+        assert!(matches_pattern("db01.example.com", "*"));
+        assert!(matches_pattern("a.b.c", "*.*.*"));
+        assert!(!matches_pattern("db01.example.com", "web*"));
+        assert!(!matches_pattern("db01.example.com", "db0?.example"));
+        assert!(!matches_pattern("db01.example.com", ""));
+    }
+
+    #[test]
+    fn test_last_seen_uses_latest_submission() {
+        let now = Utc::now();
+        let mut n = node("web01", Some(now - Duration::days(40)));
+        n.facts_timestamp = Some(now - Duration::days(2));
+        assert_eq!(last_seen(&n), Some(now - Duration::days(2)));
+        assert_eq!(last_seen(&node("web02", None)), None);
+    }
+
+    #[test]
+    fn test_find_stale_nodes() {
+        let now = Utc::now();
+        let cutoff = now - Duration::days(30);
+        let mut deactivated = node("old-deactivated", Some(now - Duration::days(90)));
+        deactivated.deactivated = Some(now - Duration::days(60));
+
+        let stale = find_stale_nodes(
+            &[
+                node("fresh", Some(now - Duration::days(1))),
+                node("old", Some(now - Duration::days(45))),
+                node("older", Some(now - Duration::days(60))),
+                node("never", None),
+                deactivated,
+            ],
+            cutoff,
+        );
+
+        let names: Vec<&str> = stale.iter().map(|(c, _)| c.as_str()).collect();
+        assert_eq!(names, vec!["never", "older", "old"]);
+    }
+}
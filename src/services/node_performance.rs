@@ -0,0 +1,223 @@
+//! Performance history of a node
+//!
+//! Builds the run duration and resource counts of each report of a node from
+//! the report metrics, and flags nodes whose recent runs got significantly
+//! slower: the median run time of the last runs is compared with the median
+//! of the runs before them, so a single slow run is not an outlier.
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+
+use crate::models::{
+    NodePerformance, NodePerformancePoint, PuppetDbDataRef, ReportMetric, ReportStatus,
+};
+use crate::services::deployment_impact::pql_string;
+use crate::services::puppetdb::PuppetDbClient;
+
+/// Runs making up the recent median
+pub const RECENT_RUNS: usize = 5;
+
+/// Earlier runs needed before a regression can be detected
+pub const MIN_BASELINE_RUNS: usize = 5;
+
+/// Default minimum increase, in seconds, of the recent median run time
+pub const DEFAULT_REGRESSION_SECONDS: f64 = 5.0;
+
+/// Default minimum increase, in percent of the baseline median, of the
+/// recent median run time
+pub const DEFAULT_REGRESSION_PERCENT: f64 = 50.0;
+
+#[derive(Debug, Deserialize)]
+struct ReportRow {
+    hash: String,
+    end_time: Option<DateTime<Utc>>,
+    status: Option<ReportStatus>,
+    noop: Option<bool>,
+    metrics: Option<PuppetDbDataRef>,
+}
+
+impl ReportRow {
+    fn into_point(self) -> NodePerformancePoint {
+        let mut point = NodePerformancePoint {
+            hash: self.hash,
+            end_time: self.end_time,
+            status: self.status,
+            noop: self.noop.unwrap_or(false),
+            run_time_seconds: None,
+            total_resources: None,
+            changed_resources: None,
+            failed_resources: None,
+        };
+        let metrics = self.metrics.as_ref().and_then(|m| m.data.as_ref());
+        for value in metrics.into_iter().flatten() {
+            let Ok(metric) = serde_json::from_value::<ReportMetric>(value.clone()) else {
+                continue;
+            };
+            match (metric.category.as_str(), metric.name.as_str()) {
+                ("time", "total") => point.run_time_seconds = Some(metric.value),
+                ("resources", "total") => point.total_resources = Some(metric.value as u64),
+                ("resources", "changed") => point.changed_resources = Some(metric.value as u64),
+                ("resources", "failed") => point.failed_resources = Some(metric.value as u64),
+                _ => {}
+            }
+        }
+        point
+    }
+}
+
+/// Performance history of a node over the last `days` days
+pub async fn node_performance(
+    puppetdb: &PuppetDbClient,
+    certname: &str,
+    days: u32,
+    min_increase_seconds: f64,
+    min_increase_percent: f64,
+) -> Result<NodePerformance> {
+    let since = Utc::now() - chrono::Duration::days(days as i64);
+    let pql = format!(
+        "reports[hash, end_time, status, noop, metrics] {{ certname = {} and end_time >= {} }}",
+        pql_string(certname),
+        pql_string(&since.to_rfc3339())
+    );
+    let rows: Vec<ReportRow> = puppetdb.query(&pql).await?;
+
+    let mut points: Vec<NodePerformancePoint> =
+        rows.into_iter().map(ReportRow::into_point).collect();
+    points.sort_by_key(|p| p.end_time);
+
+    let (baseline, recent, regressed) =
+        run_time_regression(&points, min_increase_seconds, min_increase_percent);
+
+    Ok(NodePerformance {
+        certname: certname.to_string(),
+        days,
+        points,
+        baseline_run_time_seconds: baseline,
+        recent_run_time_seconds: recent,
+        run_time_regressed: regressed,
+    })
+}
+
+/// Baseline and recent median run times of the points (oldest first), and
+/// whether the recent median exceeds the baseline by both thresholds
+///
+/// No-op runs are left out, since they skip the changes a normal run makes.
+pub fn run_time_regression(
+    points: &[NodePerformancePoint],
+    min_increase_seconds: f64,
+    min_increase_percent: f64,
+) -> (Option<f64>, Option<f64>, bool) {
+    let run_times: Vec<f64> = points
+        .iter()
+        .filter(|p| !p.noop)
+        .filter_map(|p| p.run_time_seconds)
+        .collect();
+
+    let split = run_times.len().saturating_sub(RECENT_RUNS);
+    let recent = median(&run_times[split..]);
+    if split < MIN_BASELINE_RUNS {
+        return (None, recent, false);
+    }
+    let baseline = median(&run_times[..split]);
+
+    let regressed = match (baseline, recent) {
+        (Some(baseline), Some(recent)) => {
+            let increase = recent - baseline;
+            increase >= min_increase_seconds
+                && (baseline <= 0.0 || increase / baseline * 100.0 >= min_increase_percent)
+        }
+        _ => false,
+    };
+    (baseline, recent, regressed)
+}
+
+fn median(values: &[f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    let mut sorted = values.to_vec();
+    sorted.sort_by(f64::total_cmp);
+    let mid = sorted.len() / 2;
+    Some(if sorted.len().is_multiple_of(2) {
+        (sorted[mid - 1] + sorted[mid]) / 2.0
+    } else {
+        sorted[mid]
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn point(run_time: f64, noop: bool) -> NodePerformancePoint {
+        NodePerformancePoint {
+            hash: String::new(),
+            end_time: None,
+            status: Some(ReportStatus::Unchanged),
+            noop,
+            run_time_seconds: Some(run_time),
+            total_resources: None,
+            changed_resources: None,
+            failed_resources: None,
+        }
+    }
+
+    #[test]
+    fn test_into_point() {
+        let row: ReportRow = serde_json::from_value(json!({
+            "hash": "abc",
+            "end_time": "2026-10-16T12:00:00Z",
+            "status": "changed",
+            "noop": false,
+            "metrics": {
+                "data": [
+                    {"category": "time", "name": "total", "value": 42.5},
+                    {"category": "time", "name": "file", "value": 3.0},
+                    {"category": "resources", "name": "total", "value": 310},
+                    {"category": "resources", "name": "changed", "value": 2},
+                    {"category": "resources", "name": "failed", "value": 0},
+                ],
+                "href": "/pdb/query/v4/reports/abc/metrics"
+            }
+        }))
+        .unwrap();
+
+        let point = row.into_point();
+        assert_eq!(point.status, Some(ReportStatus::Changed));
+        assert_eq!(point.run_time_seconds, Some(42.5));
+        assert_eq!(point.total_resources, Some(310));
+        assert_eq!(point.changed_resources, Some(2));
+        assert_eq!(point.failed_resources, Some(0));
+    }
+
+    #[test]
+    fn test_run_time_regression() {
+        let mut points: Vec<_> = [20.0, 22.0, 19.0, 21.0, 20.0, 60.0]
+            .iter()
+            .map(|t| point(*t, false))
+            .collect();
+
+        // Too few earlier runs for a baseline
+        let (baseline, recent, regressed) = run_time_regression(&points, 5.0, 50.0);
+        assert_eq!((baseline, regressed), (None, false));
+        assert_eq!(recent, Some(21.0));
+
+        // One slow run among the recent ones is not a regression
+        points.extend([20.0, 21.0, 19.0, 22.0].iter().map(|t| point(*t, false)));
+        let (baseline, recent, regressed) = run_time_regression(&points, 5.0, 50.0);
+        assert_eq!(baseline, Some(20.0));
+        assert_eq!(recent, Some(21.0));
+        assert!(!regressed);
+
+        // Consistently slower recent runs are; no-op runs are ignored
+        points.extend([45.0, 50.0, 48.0].iter().map(|t| point(*t, false)));
+        points.push(point(5.0, true));
+        let (baseline, recent, regressed) = run_time_regression(&points, 5.0, 50.0);
+        assert_eq!(baseline, Some(20.5));
+        assert_eq!(recent, Some(45.0));
+        assert!(regressed);
+        assert!(!run_time_regression(&points, 30.0, 50.0).2);
+    }
+}
@@ -4,6 +4,7 @@
 //! - Nodes with revoked certificates in Puppet CA
 //! - Nodes that appear in PuppetDB but have no certificate in Puppet CA
 //! - Nodes that are due for automatic removal after the retention period
+//! - Nodes that stopped reporting (stale node janitor, when enabled)
 //!
 //! Nodes meeting these criteria are marked as "pending removal" and automatically
 //! removed after a configurable period (default 10 days).
@@ -19,6 +20,7 @@ use tracing::{debug, error, info, warn};
 use crate::config::NodeRemovalConfig;
 use crate::db::{DbPool, NodeRemovalRepository};
 use crate::models::RemovalReason;
+use crate::services::node_janitor;
 use crate::services::puppet_ca::PuppetCAService;
 use crate::services::puppetdb::PuppetDbClient;
 
@@ -72,6 +74,7 @@ impl NodeRemovalSchedulerState {
 /// This spawns background tasks for:
 /// - Checking certificate status and marking nodes for removal
 /// - Executing automatic removal of nodes past their retention period
+/// - Purging stale nodes (if the janitor is enabled)
 /// - Cleaning up old audit log entries
 pub fn start_node_removal_scheduler(
     pool: DbPool,
@@ -101,6 +104,14 @@ pub fn start_node_removal_scheduler(
         removal_execution_task(removal_state).await;
     });
 
+    // Spawn stale node janitor task
+    if state.config.janitor.enabled {
+        let janitor_state = state.clone();
+        tokio::spawn(async move {
+            janitor_task(janitor_state).await;
+        });
+    }
+
     // Spawn cleanup task for audit logs
     let cleanup_state = state.clone();
     tokio::spawn(async move {
@@ -265,6 +276,25 @@ async fn check_certificate_status(state: &NodeRemovalSchedulerState) -> anyhow::
     // Unmark nodes that now have valid certificates
     let pending = repo.get_all_pending().await?;
     for removal in pending {
+        // Stale nodes stay flagged until they check in again; deactivated
+        // nodes drop out of PuppetDB and their certificates remain signed
+        if removal.removal_reason == RemovalReason::StaleNode {
+            let checked_in = puppetdb_nodes
+                .iter()
+                .find(|n| n.certname == removal.certname)
+                .and_then(node_janitor::last_seen)
+                .is_some_and(|seen| seen > removal.marked_at);
+            if checked_in {
+                info!(
+                    "Stale node '{}' reported again, unmarking from pending removal",
+                    removal.certname
+                );
+                repo.unmark_removal(&removal.certname, None, Some("Node reported again"))
+                    .await?;
+            }
+            continue;
+        }
+
         // Check if node still exists in PuppetDB
         if !puppetdb_certnames.contains(&removal.certname) {
             // Node no longer in PuppetDB, it was probably already removed
@@ -418,6 +448,41 @@ async fn deactivate_node_in_puppetdb(
     }
 }
 
+/// Stale node janitor task
+///
+/// Periodically purges nodes that have stopped reporting.
+async fn janitor_task(state: NodeRemovalSchedulerState) {
+    let check_interval = Duration::from_secs(state.config.janitor.interval_secs);
+    let mut interval_timer = interval(check_interval);
+
+    info!(
+        "Stale node janitor started (interval: {}s, stale after {} days, deactivate: {}, flag certificates: {})",
+        check_interval.as_secs(),
+        state.config.janitor.stale_after_days,
+        state.config.janitor.deactivate,
+        state.config.janitor.flag_certificates
+    );
+
+    // Wait before first run to let services initialize
+    tokio::time::sleep(Duration::from_secs(120)).await;
+
+    loop {
+        interval_timer.tick().await;
+
+        if !*state.running.read().await {
+            info!("Stale node janitor stopping");
+            break;
+        }
+
+        if let Err(e) =
+            node_janitor::run_janitor(&state.pool, &state.puppetdb, &state.config, false, None)
+                .await
+        {
+            error!("Stale node janitor run failed: {}", e);
+        }
+    }
+}
+
 /// Audit log cleanup task
 ///
 /// Periodically cleans up old audit log entries and removed node records.
@@ -467,6 +532,18 @@ async fn audit_cleanup_task(state: NodeRemovalSchedulerState) {
                 error!("Failed to clean up removed entries: {}", e);
             }
         }
+
+        // Janitor reports follow the same retention
+        match repo.cleanup_janitor_runs(audit_retention).await {
+            Ok(count) => {
+                if count > 0 {
+                    info!("Cleaned up {} old janitor runs", count);
+                }
+            }
+            Err(e) => {
+                error!("Failed to clean up janitor runs: {}", e);
+            }
+        }
     }
 }
 
@@ -482,6 +559,7 @@ mod tests {
             retention_days: 10,
             check_interval_secs: Some(300),
             audit_retention_days: Some(90),
+            janitor: Default::default(),
         };
 
         assert!(config.enabled);
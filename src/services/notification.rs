@@ -1,17 +1,36 @@
 //! Notification service for managing user notifications
+//!
+//! Notifications are per user. Background services post them to an audience
+//! (a user, roles, a permission or everyone) through
+//! [`NotificationService::notify`], which creates one copy per user so each
+//! has its own read state.
 
+use crate::config::NotificationsConfig;
 use crate::models::{
-    BulkMarkReadRequest, CreateNotificationRequest, Notification, NotificationQuery,
-    NotificationStats,
+    BulkMarkReadRequest, CreateNotificationRequest, NewNotification, Notification,
+    NotificationAudience, NotificationQuery, NotificationStats,
 };
+use crate::services::DbRbacService;
 use crate::utils::AppError;
 use chrono::Utc;
 use sqlx::{Pool, Sqlite};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::broadcast;
+use std::time::Duration;
+use tokio::sync::{broadcast, RwLock};
+use tokio::time::interval;
+use tracing::{error, info};
 use uuid::Uuid;
 
+/// Notifications returned by a list request unless it asks for fewer
+const DEFAULT_LIST_LIMIT: i64 = 100;
+
+/// Most notifications a list request can return
+const MAX_LIST_LIMIT: i64 = 500;
+
+/// How often old notifications are deleted
+const RETENTION_INTERVAL_SECS: u64 = 3600;
+
 /// Notification service
 #[derive(Clone)]
 pub struct NotificationService {
@@ -38,16 +57,160 @@ impl NotificationService {
         }
     }
 
+    /// Send a notification to an audience without waiting
+    pub fn notify_in_background(
+        &self,
+        audience: NotificationAudience,
+        notification: NewNotification,
+    ) {
+        let service = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = service.notify(&audience, &notification).await {
+                error!(
+                    "Failed to create notification '{}': {}",
+                    notification.title, e
+                );
+            }
+        });
+    }
+
     /// Subscribe to notification events
     pub fn subscribe(&self) -> broadcast::Receiver<NotificationEvent> {
         self.broadcast.subscribe()
     }
 
     /// Create a new notification
+    ///
+    /// With a dedup key the user already has a notification for, that
+    /// notification is returned instead.
     pub async fn create_notification(
         &self,
         req: CreateNotificationRequest,
     ) -> Result<Notification, AppError> {
+        if let Some(key) = &req.dedup_key {
+            if let Some(existing) = self.find_by_dedup_key(&req.user_id, key).await? {
+                return Ok(existing);
+            }
+        }
+        self.insert(req).await
+    }
+
+    /// Send a notification to every user of an audience
+    ///
+    /// Returns how many users were notified; users that already have a
+    /// notification with the same dedup key are skipped.
+    pub async fn notify(
+        &self,
+        audience: &NotificationAudience,
+        notification: &NewNotification,
+    ) -> Result<usize, AppError> {
+        let user_ids = self
+            .resolve_audience(audience, notification.organization_id)
+            .await?;
+
+        let mut sent = 0;
+        for user_id in user_ids {
+            if let Some(key) = &notification.dedup_key {
+                if self.find_by_dedup_key(&user_id, key).await?.is_some() {
+                    continue;
+                }
+            }
+            self.insert(notification.for_user(&user_id)).await?;
+            sent += 1;
+        }
+        Ok(sent)
+    }
+
+    /// IDs of the active users of an audience
+    async fn resolve_audience(
+        &self,
+        audience: &NotificationAudience,
+        organization_id: Option<Uuid>,
+    ) -> Result<Vec<String>, AppError> {
+        let mut sql = String::from("SELECT DISTINCT u.id FROM users u");
+        let mut conditions = vec!["u.deleted_at IS NULL".to_string()];
+        let mut binds: Vec<String> = Vec::new();
+
+        match audience {
+            NotificationAudience::User(id) => {
+                conditions.push("u.id = ?".to_string());
+                binds.push(id.to_string());
+            }
+            NotificationAudience::Roles(names) => {
+                if names.is_empty() {
+                    return Ok(Vec::new());
+                }
+                let placeholders = vec!["?"; names.len()].join(",");
+                sql.push_str(
+                    " LEFT JOIN user_roles ur ON ur.user_id = u.id \
+                     LEFT JOIN roles r ON r.id = ur.role_id",
+                );
+                // Users created before role assignments only have `users.role`
+                conditions.push(format!(
+                    "(r.name IN ({0}) OR u.role IN ({0}))",
+                    placeholders
+                ));
+                binds.extend(names.iter().cloned());
+                binds.extend(names.iter().cloned());
+            }
+            NotificationAudience::Permission(..) | NotificationAudience::AllUsers => {}
+        }
+        if let Some(org_id) = organization_id {
+            conditions.push("u.organization_id = ?".to_string());
+            binds.push(org_id.to_string());
+        }
+        sql.push_str(" WHERE ");
+        sql.push_str(&conditions.join(" AND "));
+
+        let mut query = sqlx::query_scalar::<_, String>(sqlx::AssertSqlSafe(sql));
+        for bind in &binds {
+            query = query.bind(bind);
+        }
+        let user_ids = query
+            .fetch_all(&self.db)
+            .await
+            .map_err(|e| AppError::Database(e.to_string()))?;
+
+        let NotificationAudience::Permission(resource, action) = audience else {
+            return Ok(user_ids);
+        };
+        let rbac = DbRbacService::new(self.db.clone());
+        let mut allowed = Vec::new();
+        for user_id in user_ids {
+            let Ok(uuid) = Uuid::parse_str(&user_id) else {
+                continue;
+            };
+            let check = rbac
+                .check_permission(&uuid, *resource, *action, None, None)
+                .await
+                .map_err(|e| AppError::internal(format!("Permission check failed: {}", e)))?;
+            if check.allowed {
+                allowed.push(user_id);
+            }
+        }
+        Ok(allowed)
+    }
+
+    async fn find_by_dedup_key(
+        &self,
+        user_id: &str,
+        dedup_key: &str,
+    ) -> Result<Option<Notification>, AppError> {
+        sqlx::query_as::<_, Notification>(
+            r#"
+            SELECT * FROM notifications
+            WHERE user_id = ? AND dedup_key = ?
+            LIMIT 1
+            "#,
+        )
+        .bind(user_id)
+        .bind(dedup_key)
+        .fetch_optional(&self.db)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))
+    }
+
+    async fn insert(&self, req: CreateNotificationRequest) -> Result<Notification, AppError> {
         let id = Uuid::new_v4().to_string();
         let created_at = Utc::now().to_rfc3339();
         let expires_at = req.expires_at.map(|dt| dt.to_rfc3339());
@@ -57,9 +220,9 @@ impl NotificationService {
             r#"
             INSERT INTO notifications (
                 id, user_id, organization_id, title, message, type,
-                category, link, created_at, expires_at, metadata
+                category, link, created_at, expires_at, metadata, dedup_key
             )
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             RETURNING *
             "#,
         )
@@ -74,6 +237,7 @@ impl NotificationService {
         .bind(&created_at)
         .bind(&expires_at)
         .bind(&metadata)
+        .bind(&req.dedup_key)
         .fetch_one(&self.db)
         .await
         .map_err(|e| AppError::Database(e.to_string()))?;
@@ -86,38 +250,56 @@ impl NotificationService {
         Ok(notification)
     }
 
-    /// Get notifications for a user
+    /// Get notifications for a user, newest first
+    ///
+    /// Dismissed and expired notifications and those of other organizations
+    /// are left out.
     pub async fn get_notifications(
         &self,
         user_id: &str,
-        _organization_id: Option<&str>,
+        organization_id: Option<&str>,
         query: NotificationQuery,
     ) -> Result<Vec<Notification>, AppError> {
-        // Simple implementation for now - can be extended later
-        let final_query = if query.unread_only == Some(true) {
-            sqlx::query_as::<_, Notification>(
-                r#"
-                SELECT * FROM notifications
-                WHERE user_id = ?
-                AND read = 0
-                AND (expires_at IS NULL OR datetime(expires_at) > datetime('now'))
-                ORDER BY created_at DESC
-                "#,
-            )
-            .bind(user_id)
-        } else {
-            sqlx::query_as::<_, Notification>(
-                r#"
-                SELECT * FROM notifications
-                WHERE user_id = ?
-                AND (expires_at IS NULL OR datetime(expires_at) > datetime('now'))
-                ORDER BY created_at DESC
-                "#,
-            )
-            .bind(user_id)
-        };
+        let mut sql = String::from(
+            r#"
+            SELECT * FROM notifications
+            WHERE user_id = ?
+            AND dismissed = 0
+            AND (expires_at IS NULL OR datetime(expires_at) > datetime('now'))
+            "#,
+        );
+        let mut binds: Vec<String> = vec![user_id.to_string()];
+
+        if let Some(org_id) = organization_id {
+            sql.push_str(" AND (organization_id IS NULL OR organization_id = ?)");
+            binds.push(org_id.to_string());
+        }
+        if query.unread_only == Some(true) {
+            sql.push_str(" AND read = 0");
+        }
+        if let Some(r#type) = &query.r#type {
+            sql.push_str(" AND type = ?");
+            binds.push(r#type.as_str().to_string());
+        }
+        if let Some(category) = &query.category {
+            sql.push_str(" AND category = ?");
+            binds.push(category.clone());
+        }
+        sql.push_str(" ORDER BY created_at DESC LIMIT ? OFFSET ?");
+
+        let mut final_query = sqlx::query_as::<_, Notification>(sqlx::AssertSqlSafe(sql));
+        for bind in &binds {
+            final_query = final_query.bind(bind);
+        }
+        let limit = query
+            .limit
+            .unwrap_or(DEFAULT_LIST_LIMIT)
+            .clamp(1, MAX_LIST_LIMIT);
+        let offset = query.offset.unwrap_or(0).max(0);
 
         let notifications = final_query
+            .bind(limit)
+            .bind(offset)
             .fetch_all(&self.db)
             .await
             .map_err(|e| AppError::Database(e.to_string()))?;
@@ -319,7 +501,7 @@ impl NotificationService {
     pub async fn get_stats(
         &self,
         user_id: &str,
-        _organization_id: Option<&str>,
+        organization_id: Option<&str>,
     ) -> Result<NotificationStats, AppError> {
         // Get total count
         let total: i64 = sqlx::query_scalar(
@@ -328,9 +510,12 @@ impl NotificationService {
             WHERE user_id = ?
             AND (expires_at IS NULL OR datetime(expires_at) > datetime('now'))
             AND dismissed = 0
+            AND (? IS NULL OR organization_id IS NULL OR organization_id = ?)
             "#,
         )
         .bind(user_id)
+        .bind(organization_id)
+        .bind(organization_id)
         .fetch_one(&self.db)
         .await
         .map_err(|e| AppError::Database(e.to_string()))?;
@@ -342,9 +527,12 @@ impl NotificationService {
             WHERE user_id = ? AND read = 0
             AND (expires_at IS NULL OR datetime(expires_at) > datetime('now'))
             AND dismissed = 0
+            AND (? IS NULL OR organization_id IS NULL OR organization_id = ?)
             "#,
         )
         .bind(user_id)
+        .bind(organization_id)
+        .bind(organization_id)
         .fetch_one(&self.db)
         .await
         .map_err(|e| AppError::Database(e.to_string()))?;
@@ -357,10 +545,13 @@ impl NotificationService {
             WHERE user_id = ?
             AND (expires_at IS NULL OR datetime(expires_at) > datetime('now'))
             AND dismissed = 0
+            AND (? IS NULL OR organization_id IS NULL OR organization_id = ?)
             GROUP BY type
             "#,
         )
         .bind(user_id)
+        .bind(organization_id)
+        .bind(organization_id)
         .fetch_all(&self.db)
         .await
         .map_err(|e| AppError::Database(e.to_string()))?;
@@ -390,7 +581,7 @@ impl NotificationService {
         Ok(result.rows_affected() as i64)
     }
 
-    /// Clean up old read notifications (older than 30 days)
+    /// Clean up read notifications read more than `days` ago
     pub async fn cleanup_old_read(&self, days: i64) -> Result<i64, AppError> {
         let result = sqlx::query(
             r#"
@@ -406,6 +597,91 @@ impl NotificationService {
 
         Ok(result.rows_affected() as i64)
     }
+
+    /// Clean up notifications created more than `days` ago, read or not
+    ///
+    /// With `dismissed_only`, only dismissed notifications are removed.
+    pub async fn cleanup_old(&self, days: i64, dismissed_only: bool) -> Result<i64, AppError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM notifications
+            WHERE (dismissed = 1 OR ? = 0)
+            AND datetime(created_at) <= datetime('now', ? || ' days')
+            "#,
+        )
+        .bind(dismissed_only)
+        .bind(format!("-{}", days))
+        .execute(&self.db)
+        .await
+        .map_err(|e| AppError::Database(e.to_string()))?;
+
+        Ok(result.rows_affected() as i64)
+    }
+
+    /// Apply the retention periods once
+    pub async fn apply_retention(&self, config: &NotificationsConfig) -> Result<i64, AppError> {
+        let read_days = config.read_retention_days as i64;
+        Ok(self.cleanup_expired().await?
+            + self.cleanup_old_read(read_days).await?
+            + self.cleanup_old(read_days, true).await?
+            + self
+                .cleanup_old(config.retention_days as i64, false)
+                .await?)
+    }
+}
+
+/// Handle for stopping the notification retention task
+#[derive(Clone)]
+pub struct NotificationRetentionState {
+    running: Arc<RwLock<bool>>,
+}
+
+impl NotificationRetentionState {
+    pub async fn is_running(&self) -> bool {
+        *self.running.read().await
+    }
+
+    /// Request the retention loop to stop at its next tick
+    pub async fn stop(&self) {
+        *self.running.write().await = false;
+        info!("Notification retention stop requested");
+    }
+}
+
+/// Spawn the background task deleting old notifications
+pub fn start_notification_retention(
+    service: Arc<NotificationService>,
+    config: &NotificationsConfig,
+) -> NotificationRetentionState {
+    let state = NotificationRetentionState {
+        running: Arc::new(RwLock::new(true)),
+    };
+
+    let loop_state = state.clone();
+    let config = config.clone();
+    tokio::spawn(async move {
+        let mut timer = interval(Duration::from_secs(RETENTION_INTERVAL_SECS));
+        loop {
+            timer.tick().await;
+
+            if !*loop_state.running.read().await {
+                info!("Notification retention stopping");
+                break;
+            }
+
+            match service.apply_retention(&config).await {
+                Ok(deleted) if deleted > 0 => info!("Deleted {} old notifications", deleted),
+                Ok(_) => {}
+                Err(e) => error!("Notification retention failed: {}", e),
+            }
+        }
+    });
+
+    info!(
+        "Notification retention started (read: {} days, unread: {} days)",
+        config.read_retention_days, config.retention_days
+    );
+    state
 }
 
 #[cfg(test)]
@@ -0,0 +1,522 @@
+//! Merging and splitting organizations
+//!
+//! A merge moves the node groups, users (with their API keys) and fact
+//! templates of one organization into another. A split moves selected node
+//! groups, together with the groups below them, and optionally users and fact
+//! templates into a new organization. Each runs in one transaction; a dry run
+//! builds the same report without changing anything.
+//!
+//! Node group and fact template names are unique per organization, so a merge
+//! either renames the moved records whose name is taken in the target or
+//! refuses to run. Usernames are unique across organizations and never
+//! conflict.
+
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use sqlx::{Row, SqliteConnection};
+use uuid::Uuid;
+
+use crate::db::{begin_write, DbPool};
+use crate::models::{
+    NameConflictStrategy, Organization, OrganizationTransferReport, SplitOrganizationRequest,
+    TransferItem, TransferKind,
+};
+use crate::utils::AppError;
+
+/// A node group, user or fact template of an organization
+#[derive(Debug, Clone)]
+struct Record {
+    id: Uuid,
+    name: String,
+    parent_id: Option<Uuid>,
+}
+
+fn table(kind: TransferKind) -> &'static str {
+    match kind {
+        TransferKind::Group => "node_groups",
+        TransferKind::User => "users",
+        TransferKind::FactTemplate => "fact_templates",
+    }
+}
+
+fn name_column(kind: TransferKind) -> &'static str {
+    match kind {
+        TransferKind::User => "username",
+        TransferKind::Group | TransferKind::FactTemplate => "name",
+    }
+}
+
+fn label(kind: TransferKind) -> &'static str {
+    match kind {
+        TransferKind::Group => "Group",
+        TransferKind::User => "User",
+        TransferKind::FactTemplate => "Fact template",
+    }
+}
+
+async fn load(
+    conn: &mut SqliteConnection,
+    kind: TransferKind,
+    organization_id: Uuid,
+) -> Result<Vec<Record>, AppError> {
+    let parent = if kind == TransferKind::Group {
+        "parent_id"
+    } else {
+        "NULL"
+    };
+    let sql = format!(
+        "SELECT id, {} AS name, {} AS parent_id FROM {} WHERE organization_id = ? ORDER BY created_at, id",
+        name_column(kind),
+        parent,
+        table(kind)
+    );
+    let rows = sqlx::query(sqlx::AssertSqlSafe(sql))
+        .bind(organization_id.to_string())
+        .fetch_all(&mut *conn)
+        .await?;
+
+    Ok(rows
+        .iter()
+        .filter_map(|row| {
+            Some(Record {
+                id: Uuid::parse_str(row.get::<&str, _>("id")).ok()?,
+                name: row.get("name"),
+                parent_id: row
+                    .get::<Option<String>, _>("parent_id")
+                    .and_then(|id| Uuid::parse_str(&id).ok()),
+            })
+        })
+        .collect())
+}
+
+/// Plan the names of records moving into an organization
+///
+/// `taken` holds the names in use in the source and target organizations; a
+/// renamed record gets `name (suffix)`, numbered when that is taken too.
+fn resolve_names(
+    kind: TransferKind,
+    records: &[Record],
+    target_names: &HashSet<String>,
+    taken: &mut HashSet<String>,
+    suffix: &str,
+    strategy: NameConflictStrategy,
+) -> Vec<TransferItem> {
+    records
+        .iter()
+        .map(|record| {
+            let conflict = target_names.contains(&record.name);
+            let renamed_to = (conflict && strategy == NameConflictStrategy::Rename).then(|| {
+                let mut candidate = format!("{} ({})", record.name, suffix);
+                let mut n = 2;
+                while taken.contains(&candidate) {
+                    candidate = format!("{} ({} {})", record.name, suffix, n);
+                    n += 1;
+                }
+                taken.insert(candidate.clone());
+                candidate
+            });
+            TransferItem {
+                kind,
+                id: record.id,
+                name: record.name.clone(),
+                conflict,
+                renamed_to,
+            }
+        })
+        .collect()
+}
+
+/// The given groups and every group below them, in `groups` order
+fn subtree(groups: &[Record], roots: &[Uuid]) -> Vec<Uuid> {
+    let mut children: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+    for group in groups {
+        if let Some(parent_id) = group.parent_id {
+            children.entry(parent_id).or_default().push(group.id);
+        }
+    }
+
+    let mut selected: HashSet<Uuid> = HashSet::new();
+    let mut pending: Vec<Uuid> = roots.to_vec();
+    while let Some(id) = pending.pop() {
+        if selected.insert(id) {
+            pending.extend(children.get(&id).into_iter().flatten().copied());
+        }
+    }
+
+    groups
+        .iter()
+        .map(|group| group.id)
+        .filter(|id| selected.contains(id))
+        .collect()
+}
+
+fn moved_items(kind: TransferKind, records: &[Record]) -> Vec<TransferItem> {
+    records
+        .iter()
+        .map(|record| TransferItem {
+            kind,
+            id: record.id,
+            name: record.name.clone(),
+            conflict: false,
+            renamed_to: None,
+        })
+        .collect()
+}
+
+/// Pick the records with the given IDs, which must all be in the organization
+fn select<'a>(
+    records: &'a [Record],
+    ids: &[Uuid],
+    kind: TransferKind,
+) -> Result<Vec<&'a Record>, AppError> {
+    let by_id: HashMap<Uuid, &Record> = records.iter().map(|r| (r.id, r)).collect();
+    ids.iter()
+        .map(|id| {
+            by_id.get(id).copied().ok_or_else(|| {
+                AppError::validation(format!(
+                    "{} {} is not in the source organization",
+                    label(kind),
+                    id
+                ))
+            })
+        })
+        .collect()
+}
+
+async fn count_api_keys(
+    conn: &mut SqliteConnection,
+    organization_id: Uuid,
+    user_ids: Option<&[Uuid]>,
+) -> Result<u64, AppError> {
+    let count: i64 = match user_ids {
+        None => {
+            sqlx::query_scalar("SELECT COUNT(*) FROM api_keys WHERE organization_id = ?")
+                .bind(organization_id.to_string())
+                .fetch_one(&mut *conn)
+                .await?
+        }
+        Some(user_ids) => {
+            let mut total = 0;
+            for user_id in user_ids {
+                let count: i64 = sqlx::query_scalar(
+                    "SELECT COUNT(*) FROM api_keys WHERE organization_id = ? AND user_id = ?",
+                )
+                .bind(organization_id.to_string())
+                .bind(user_id.to_string())
+                .fetch_one(&mut *conn)
+                .await?;
+                total += count;
+            }
+            total
+        }
+    };
+    Ok(count as u64)
+}
+
+async fn rename(
+    conn: &mut SqliteConnection,
+    item: &TransferItem,
+    name: &str,
+) -> Result<(), AppError> {
+    let version = if item.kind == TransferKind::Group {
+        ", version = version + 1"
+    } else {
+        ""
+    };
+    let sql = format!(
+        "UPDATE {} SET {} = ?, updated_at = ?{} WHERE id = ?",
+        table(item.kind),
+        name_column(item.kind),
+        version
+    );
+    sqlx::query(sqlx::AssertSqlSafe(sql))
+        .bind(name)
+        .bind(Utc::now().to_rfc3339())
+        .bind(item.id.to_string())
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+async fn move_record(
+    conn: &mut SqliteConnection,
+    kind: TransferKind,
+    id: Uuid,
+    organization_id: Uuid,
+) -> Result<(), AppError> {
+    let sql = format!(
+        "UPDATE {} SET organization_id = ? WHERE id = ?",
+        table(kind)
+    );
+    sqlx::query(sqlx::AssertSqlSafe(sql))
+        .bind(organization_id.to_string())
+        .bind(id.to_string())
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Move the node groups, users, API keys and fact templates of `source`
+/// into `target`
+pub async fn merge(
+    pool: &DbPool,
+    source: &Organization,
+    target: &Organization,
+    strategy: NameConflictStrategy,
+    dry_run: bool,
+) -> Result<OrganizationTransferReport, AppError> {
+    if source.id == target.id {
+        return Err(AppError::validation(
+            "An organization cannot be merged into itself",
+        ));
+    }
+
+    let mut tx = begin_write(pool).await?;
+    let mut items = Vec::new();
+    for kind in [TransferKind::Group, TransferKind::FactTemplate] {
+        let records = load(&mut tx, kind, source.id).await?;
+        let target_names: HashSet<String> = load(&mut tx, kind, target.id)
+            .await?
+            .into_iter()
+            .map(|r| r.name)
+            .collect();
+        let mut taken: HashSet<String> = target_names
+            .iter()
+            .cloned()
+            .chain(records.iter().map(|r| r.name.clone()))
+            .collect();
+        items.extend(resolve_names(
+            kind,
+            &records,
+            &target_names,
+            &mut taken,
+            &source.slug,
+            strategy,
+        ));
+    }
+    let users = load(&mut tx, TransferKind::User, source.id).await?;
+    items.extend(moved_items(TransferKind::User, &users));
+
+    let report = OrganizationTransferReport {
+        dry_run,
+        source_id: source.id,
+        target_id: Some(target.id),
+        items,
+        api_keys: count_api_keys(&mut tx, source.id, None).await?,
+    };
+    if dry_run {
+        return Ok(report);
+    }
+
+    let unresolved = report.unresolved_conflicts();
+    if !unresolved.is_empty() {
+        return Err(AppError::conflict(format!(
+            "Names already used in organization '{}': {}",
+            target.slug,
+            unresolved.join(", ")
+        )));
+    }
+
+    for item in &report.items {
+        if let Some(name) = &item.renamed_to {
+            rename(&mut tx, item, name).await?;
+        }
+    }
+    for table in ["node_groups", "fact_templates", "users", "api_keys"] {
+        let sql = format!(
+            "UPDATE {} SET organization_id = ? WHERE organization_id = ?",
+            table
+        );
+        sqlx::query(sqlx::AssertSqlSafe(sql))
+            .bind(target.id.to_string())
+            .bind(source.id.to_string())
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+
+    Ok(report)
+}
+
+/// Move the selected node groups, with the groups below them, and the
+/// selected users and fact templates of `source` into a new organization
+///
+/// Moved groups whose parent stays behind become top-level groups.
+pub async fn split(
+    pool: &DbPool,
+    source: &Organization,
+    req: &SplitOrganizationRequest,
+) -> Result<OrganizationTransferReport, AppError> {
+    let name = req.name.trim();
+    let slug = req.slug.trim();
+    if name.is_empty() || slug.is_empty() {
+        return Err(AppError::validation("name and slug are required"));
+    }
+    if req.group_ids.is_empty() {
+        return Err(AppError::validation("Select at least one group to split"));
+    }
+
+    let mut tx = begin_write(pool).await?;
+    let taken: i64 =
+        sqlx::query_scalar("SELECT COUNT(*) FROM organizations WHERE name = ? OR slug = ?")
+            .bind(name)
+            .bind(slug)
+            .fetch_one(&mut *tx)
+            .await?;
+    if taken > 0 {
+        return Err(AppError::conflict("Organization name/slug already exists"));
+    }
+
+    let groups = load(&mut tx, TransferKind::Group, source.id).await?;
+    select(&groups, &req.group_ids, TransferKind::Group)?;
+    let moved_ids: HashSet<Uuid> = subtree(&groups, &req.group_ids).into_iter().collect();
+    let moved_groups: Vec<Record> = groups
+        .iter()
+        .filter(|g| moved_ids.contains(&g.id))
+        .cloned()
+        .collect();
+
+    let users = load(&mut tx, TransferKind::User, source.id).await?;
+    let moved_users: Vec<Record> = select(&users, &req.user_ids, TransferKind::User)?
+        .into_iter()
+        .cloned()
+        .collect();
+    let templates = load(&mut tx, TransferKind::FactTemplate, source.id).await?;
+    let moved_templates: Vec<Record> = select(
+        &templates,
+        &req.fact_template_ids,
+        TransferKind::FactTemplate,
+    )?
+    .into_iter()
+    .cloned()
+    .collect();
+
+    let mut items = moved_items(TransferKind::Group, &moved_groups);
+    items.extend(moved_items(TransferKind::User, &moved_users));
+    items.extend(moved_items(TransferKind::FactTemplate, &moved_templates));
+    let user_ids: Vec<Uuid> = moved_users.iter().map(|u| u.id).collect();
+    let mut report = OrganizationTransferReport {
+        dry_run: req.dry_run,
+        source_id: source.id,
+        target_id: None,
+        items,
+        api_keys: count_api_keys(&mut tx, source.id, Some(&user_ids)).await?,
+    };
+    if req.dry_run {
+        return Ok(report);
+    }
+
+    let target_id = Uuid::new_v4();
+    let now = Utc::now().to_rfc3339();
+    sqlx::query(
+        "INSERT INTO organizations (id, name, slug, created_at, updated_at) VALUES (?, ?, ?, ?, ?)",
+    )
+    .bind(target_id.to_string())
+    .bind(name)
+    .bind(slug)
+    .bind(&now)
+    .bind(&now)
+    .execute(&mut *tx)
+    .await?;
+
+    for item in &report.items {
+        move_record(&mut tx, item.kind, item.id, target_id).await?;
+    }
+    for group in &moved_groups {
+        if group.parent_id.is_some_and(|p| !moved_ids.contains(&p)) {
+            sqlx::query("UPDATE node_groups SET parent_id = NULL, updated_at = ? WHERE id = ?")
+                .bind(&now)
+                .bind(group.id.to_string())
+                .execute(&mut *tx)
+                .await?;
+        }
+    }
+    for user_id in &user_ids {
+        sqlx::query(
+            "UPDATE api_keys SET organization_id = ? WHERE organization_id = ? AND user_id = ?",
+        )
+        .bind(target_id.to_string())
+        .bind(source.id.to_string())
+        .bind(user_id.to_string())
+        .execute(&mut *tx)
+        .await?;
+    }
+    tx.commit().await?;
+
+    report.target_id = Some(target_id);
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(id: u128, name: &str, parent: Option<u128>) -> Record {
+        Record {
+            id: Uuid::from_u128(id),
+            name: name.to_string(),
+            parent_id: parent.map(Uuid::from_u128),
+        }
+    }
+
+    #[test]
+    fn test_resolve_names() {
+        let records = vec![record(1, "All Nodes", None), record(2, "Web", Some(1))];
+        let target_names: HashSet<String> = ["All Nodes", "All Nodes (acme)"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let mut taken: HashSet<String> = target_names
+            .iter()
+            .cloned()
+            .chain(records.iter().map(|r| r.name.clone()))
+            .collect();
+
+        let items = resolve_names(
+            TransferKind::Group,
+            &records,
+            &target_names,
+            &mut taken.clone(),
+            "acme",
+            NameConflictStrategy::Rename,
+        );
+        assert!(items[0].conflict);
+        assert_eq!(items[0].renamed_to.as_deref(), Some("All Nodes (acme 2)"));
+        assert!(!items[1].conflict);
+        assert_eq!(items[1].renamed_to, None);
+
+        let items = resolve_names(
+            TransferKind::Group,
+            &records,
+            &target_names,
+            &mut taken,
+            "acme",
+            NameConflictStrategy::Fail,
+        );
+        let report = OrganizationTransferReport {
+            dry_run: true,
+            source_id: Uuid::nil(),
+            target_id: None,
+            items,
+            api_keys: 0,
+        };
+        assert_eq!(report.unresolved_conflicts(), ["All Nodes"]);
+    }
+
+    #[test]
+    fn test_subtree() {
+        let groups = vec![
+            record(1, "All Nodes", None),
+            record(2, "Production", Some(1)),
+            record(3, "Web", Some(2)),
+            record(4, "Development", Some(1)),
+            record(5, "Db", Some(2)),
+        ];
+        let ids = |v: &[u128]| v.iter().map(|&i| Uuid::from_u128(i)).collect::<Vec<_>>();
+
+        assert_eq!(subtree(&groups, &ids(&[2])), ids(&[2, 3, 5]));
+        assert_eq!(subtree(&groups, &ids(&[4, 3])), ids(&[3, 4]));
+        assert_eq!(subtree(&groups, &ids(&[1])).len(), 5);
+    }
+}
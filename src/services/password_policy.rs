@@ -0,0 +1,352 @@
+//! Password policy enforcement
+//!
+//! Checks a new password against the configured policy: minimum length,
+//! character classes, the username, a list of common passwords, the user's
+//! previous passwords and, optionally, the Have I Been Pwned breach corpus.
+//! The breach check only sends the first five hex characters of the SHA-1
+//! hash and fails open when the service cannot be reached.
+
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use sha1::{Digest, Sha1};
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::config::{AuthConfig, PasswordPolicyConfig};
+use crate::services::AuthService;
+
+/// Most previous passwords that can be remembered per user
+pub const MAX_PASSWORD_HISTORY: u32 = 24;
+
+/// Length of generated passwords, unless the policy asks for more
+pub const GENERATED_PASSWORD_LENGTH: usize = 20;
+
+/// Generated passwords tried before giving up on the policy
+const MAX_GENERATE_ATTEMPTS: usize = 100;
+
+/// Timeout for the breach corpus lookup
+const BREACH_CHECK_TIMEOUT_SECS: u64 = 5;
+
+/// Passwords rejected by the dictionary check, lowercase
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456",
+    "12345678",
+    "123456789",
+    "1234567890",
+    "1q2w3e4r",
+    "abc123",
+    "admin",
+    "admin123",
+    "administrator",
+    "changeme",
+    "default",
+    "dragon",
+    "football",
+    "iloveyou",
+    "letmein",
+    "login",
+    "master",
+    "monkey",
+    "openvox",
+    "passw0rd",
+    "password",
+    "password1",
+    "password123",
+    "puppet",
+    "qwerty",
+    "qwerty123",
+    "qwertyuiop",
+    "secret",
+    "sunshine",
+    "superman",
+    "trustno1",
+    "welcome",
+    "welcome1",
+];
+
+/// Password policy built from the auth configuration
+pub struct PasswordPolicy<'a> {
+    min_length: usize,
+    config: &'a PasswordPolicyConfig,
+}
+
+impl<'a> PasswordPolicy<'a> {
+    pub fn new(auth: &'a AuthConfig) -> Self {
+        Self {
+            min_length: auth.password_min_length,
+            config: &auth.password_policy,
+        }
+    }
+
+    /// Violations of the rules that need no lookups
+    pub fn check_rules(&self, password: &str, username: Option<&str>) -> Vec<String> {
+        let mut violations = Vec::new();
+
+        if password.chars().count() < self.min_length {
+            violations.push(format!(
+                "Password must be at least {} characters",
+                self.min_length
+            ));
+        }
+        if self.config.require_uppercase && !password.chars().any(char::is_uppercase) {
+            violations.push("Password must contain an uppercase letter".to_string());
+        }
+        if self.config.require_lowercase && !password.chars().any(char::is_lowercase) {
+            violations.push("Password must contain a lowercase letter".to_string());
+        }
+        if self.config.require_digit && !password.chars().any(|c| c.is_ascii_digit()) {
+            violations.push("Password must contain a digit".to_string());
+        }
+        if self.config.require_symbol && password.chars().all(char::is_alphanumeric) {
+            violations.push("Password must contain a symbol".to_string());
+        }
+        if self.config.reject_username {
+            if let Some(username) = username.filter(|u| !u.is_empty()) {
+                if password.to_lowercase().contains(&username.to_lowercase()) {
+                    violations.push("Password must not contain the username".to_string());
+                }
+            }
+        }
+        if self.config.dictionary_check && is_common_password(password) {
+            violations.push("Password is too common".to_string());
+        }
+
+        violations
+    }
+
+    /// All policy violations for a new password
+    ///
+    /// `user_id` is the user whose password changes, if they already exist;
+    /// it enables the history check.
+    pub async fn validate(
+        &self,
+        pool: &SqlitePool,
+        user_id: Option<Uuid>,
+        username: Option<&str>,
+        password: &str,
+    ) -> Result<Vec<String>> {
+        let mut violations = self.check_rules(password, username);
+
+        if self.config.dictionary_check {
+            if let Some(path) = &self.config.dictionary_path {
+                let words = tokio::fs::read_to_string(path)
+                    .await
+                    .with_context(|| format!("Failed to read password dictionary {:?}", path))?;
+                let lowered = password.to_lowercase();
+                if words.lines().any(|w| w.trim().to_lowercase() == lowered)
+                    && !is_common_password(password)
+                {
+                    violations.push("Password is too common".to_string());
+                }
+            }
+        }
+
+        if let (Some(user_id), true) = (user_id, self.config.history_count > 0) {
+            let hashes = AuthService::new(pool.clone())
+                .recent_password_hashes(&user_id, self.config.history_count)
+                .await?;
+            let reused = hashes
+                .iter()
+                .any(|hash| AuthService::verify_password(password, hash).unwrap_or(false));
+            if reused {
+                violations.push(format!(
+                    "Password must differ from your last {} passwords",
+                    self.config.history_count
+                ));
+            }
+        }
+
+        if self.config.breach_check {
+            match is_breached(&self.config.breach_api_url, password).await {
+                Ok(true) => violations
+                    .push("Password appears in a known data breach; choose another".to_string()),
+                Ok(false) => {}
+                Err(e) => tracing::warn!("Password breach check skipped: {}", e),
+            }
+        }
+
+        Ok(violations)
+    }
+
+    /// A random password that satisfies the rules needing no lookups
+    pub fn generate(&self, username: Option<&str>) -> Option<String> {
+        let length = GENERATED_PASSWORD_LENGTH.max(self.min_length);
+        (0..MAX_GENERATE_ATTEMPTS)
+            .map(|_| generate_password(length))
+            .find(|candidate| self.check_rules(candidate, username).is_empty())
+    }
+
+    /// Whether a password set at `changed_at` has exceeded the maximum age
+    pub fn is_expired(&self, changed_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        self.config
+            .max_age_days
+            .is_some_and(|days| now - changed_at >= chrono::Duration::days(days as i64))
+    }
+}
+
+/// Random password from letters, digits and symbols
+pub fn generate_password(length: usize) -> String {
+    use rand::RngExt;
+
+    const CHARSET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz23456789!@#%^*-_=+";
+    let mut rng = rand::rng();
+    (0..length)
+        .map(|_| CHARSET[rng.random_range(0..CHARSET.len())] as char)
+        .collect()
+}
+
+fn is_common_password(password: &str) -> bool {
+    let lowered = password.to_lowercase();
+    COMMON_PASSWORDS.contains(&lowered.as_str())
+}
+
+/// Hash prefix sent to the range API and suffix looked up in its answer
+fn breach_hash_parts(password: &str) -> (String, String) {
+    let hash = hex::encode_upper(Sha1::digest(password.as_bytes()));
+    let (prefix, suffix) = hash.split_at(5);
+    (prefix.to_string(), suffix.to_string())
+}
+
+/// Whether a range API answer (`SUFFIX:COUNT` lines) lists the suffix
+///
+/// Padding entries with a count of 0 do not count as matches.
+fn range_contains(body: &str, suffix: &str) -> bool {
+    body.lines().any(|line| {
+        line.trim()
+            .split_once(':')
+            .is_some_and(|(s, count)| s.eq_ignore_ascii_case(suffix) && count.trim() != "0")
+    })
+}
+
+/// Look a password up in the breach corpus by k-anonymity range query
+async fn is_breached(api_url: &str, password: &str) -> Result<bool> {
+    let (prefix, suffix) = breach_hash_parts(password);
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(BREACH_CHECK_TIMEOUT_SECS))
+        .build()
+        .context("Failed to build HTTP client")?;
+
+    let body = client
+        .get(format!("{}{}", api_url, prefix))
+        .header("Add-Padding", "true")
+        .send()
+        .await
+        .context("Breach corpus request failed")?
+        .error_for_status()
+        .context("Breach corpus returned an error")?
+        .text()
+        .await
+        .context("Failed to read breach corpus response")?;
+
+    Ok(range_contains(&body, &suffix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn auth_config(policy: PasswordPolicyConfig) -> AuthConfig {
+        AuthConfig {
+            jwt_secret: "test_secret_at_least_32_chars_long".to_string(),
+            token_expiry_hours: 24,
+            refresh_token_expiry_days: 7,
+            bcrypt_cost: 4,
+            password_min_length: 10,
+            api_key_rotation_grace_minutes: 60,
+            password_policy: policy,
+            password_reset_url: None,
+            signing_keys: Default::default(),
+            login_alerts: Default::default(),
+            csrf: Default::default(),
+            mode: Default::default(),
+            cookie: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_check_rules() {
+        let auth = auth_config(PasswordPolicyConfig {
+            require_uppercase: true,
+            require_digit: true,
+            require_symbol: true,
+            reject_username: true,
+            dictionary_check: true,
+            ..Default::default()
+        });
+        let policy = PasswordPolicy::new(&auth);
+
+        assert!(policy
+            .check_rules("Correct-Horse-42", Some("alice"))
+            .is_empty());
+        assert_eq!(policy.check_rules("short", None).len(), 4);
+        assert_eq!(
+            policy.check_rules("Alice-Rocks-42", Some("alice")),
+            vec!["Password must not contain the username".to_string()]
+        );
+        assert!(policy
+            .check_rules("Password123", None)
+            .contains(&"Password is too common".to_string()));
+    }
+
+    #[test]
+    fn test_default_policy_only_checks_length() {
+        let auth = auth_config(PasswordPolicyConfig::default());
+        let policy = PasswordPolicy::new(&auth);
+
+        assert!(policy
+            .check_rules("password123", Some("password"))
+            .is_empty());
+        assert_eq!(policy.check_rules("pass", None).len(), 1);
+    }
+
+    #[test]
+    fn test_breach_range_lookup() {
+        // SHA-1("password") = 5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8
+        let (prefix, suffix) = breach_hash_parts("password");
+        assert_eq!(prefix, "5BAA6");
+        assert_eq!(suffix, "1E4C9B93F3F0682250B6CF8331B7EE68FD8");
+
+        let body = "0018A45C4D1DEF81644B54AB7F969B88D65:1\r\n\
+                    1E4C9B93F3F0682250B6CF8331B7EE68FD8:9545824\r\n";
+        assert!(range_contains(body, &suffix));
+        assert!(!range_contains(
+            "1E4C9B93F3F0682250B6CF8331B7EE68FD8:0\r\n",
+            &suffix
+        ));
+        assert!(!range_contains("", &suffix));
+    }
+
+    #[test]
+    fn test_max_age() {
+        let auth = auth_config(PasswordPolicyConfig {
+            max_age_days: Some(90),
+            ..Default::default()
+        });
+        let policy = PasswordPolicy::new(&auth);
+        let now = Utc::now();
+
+        assert!(policy.is_expired(now - chrono::Duration::days(91), now));
+        assert!(!policy.is_expired(now - chrono::Duration::days(10), now));
+
+        let unlimited = auth_config(PasswordPolicyConfig::default());
+        assert!(!PasswordPolicy::new(&unlimited).is_expired(now - chrono::Duration::days(999), now));
+    }
+
+    #[test]
+    fn test_generate_password() {
+        let password = generate_password(GENERATED_PASSWORD_LENGTH);
+        assert_eq!(password.chars().count(), GENERATED_PASSWORD_LENGTH);
+        assert_ne!(password, generate_password(GENERATED_PASSWORD_LENGTH));
+
+        let auth = auth_config(PasswordPolicyConfig {
+            require_uppercase: true,
+            require_digit: true,
+            ..Default::default()
+        });
+        let policy = PasswordPolicy::new(&auth);
+        let generated = policy.generate(Some("alice")).unwrap();
+        assert!(policy.check_rules(&generated, Some("alice")).is_empty());
+    }
+}
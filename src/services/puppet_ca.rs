@@ -2,13 +2,22 @@
 
 use crate::config::PuppetCAConfig;
 use crate::models::{
-    CAStatus, Certificate, CertificateRequest, CertificateStatus, RejectResponse, RenewCARequest,
-    RenewCAResponse, RevokeResponse, SignRequest, SignResponse,
+    BulkCertificateResponse, BulkCertificateResult, CAStatus, CaChain, CaSnapshot, Certificate,
+    CertificateRequest, CertificateRevocationList, CertificateStatus, ChainCertificate, ChainRole,
+    ChainValidation, CrlResponse, RejectResponse, RenewCARequest, RenewCAResponse, RevocationInfo,
+    RevokeResponse, SignRequest, SignResponse,
 };
+use crate::services::fault_injection::FaultInjector;
 use crate::utils::error::AppError;
+use crate::utils::x509;
 use chrono::{DateTime, NaiveDateTime, Utc};
 use reqwest::{Client, Identity, StatusCode};
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::{RwLock, Semaphore};
+
+/// Concurrent requests to the CA during bulk operations
+const BULK_CONCURRENCY: usize = 8;
 
 /// Parse Puppet CA date format (e.g., "2030-12-17T10:50:34UTC")
 /// Puppet CA returns dates with "UTC" suffix instead of "Z" or offset
@@ -33,11 +42,68 @@ fn parse_puppet_date(date_str: &str) -> Option<DateTime<Utc>> {
     None
 }
 
+/// CRL fetched from the CA
+struct CachedCrl {
+    pem: String,
+    crls: Vec<CertificateRevocationList>,
+    fetched_at: DateTime<Utc>,
+    expires: Instant,
+}
+
+/// `start` followed by its issuers found in `bundle`, up to a self-signed root
+fn issuer_path<'a>(
+    start: &'a x509::ParsedCertificate,
+    bundle: &'a [x509::ParsedCertificate],
+) -> Vec<&'a x509::ParsedCertificate> {
+    let mut path = vec![start];
+    let mut current = start;
+    // Bounded by the bundle size in case of an issuer cycle
+    while !current.is_self_signed() && path.len() <= bundle.len() {
+        let Some(issuer) = bundle.iter().find(|c| c.subject == current.issuer) else {
+            break;
+        };
+        path.push(issuer);
+        current = issuer;
+    }
+    path
+}
+
+/// Warnings for certificates within their expiry window
+fn expiry_warnings(certificates: &[ChainCertificate]) -> Vec<String> {
+    certificates
+        .iter()
+        .filter(|c| c.expiring_soon)
+        .map(|c| {
+            let kind = match c.role {
+                ChainRole::Root => "Root CA",
+                ChainRole::Intermediate => "Intermediate CA",
+                ChainRole::Leaf => "Certificate",
+            };
+            if c.days_until_expiry < 0 {
+                format!("{} '{}' has expired", kind, c.subject)
+            } else {
+                format!(
+                    "{} '{}' expires in {} days ({})",
+                    kind,
+                    c.subject,
+                    c.days_until_expiry,
+                    c.not_after.format("%Y-%m-%d")
+                )
+            }
+        })
+        .collect()
+}
+
 /// Puppet CA client for managing certificates
 #[derive(Clone)]
 pub struct PuppetCAService {
     client: Client,
     base_url: String,
+    faults: FaultInjector,
+    crl_cache: Arc<RwLock<Option<CachedCrl>>>,
+    crl_cache_ttl: Duration,
+    leaf_expiry_warning_days: i64,
+    ca_expiry_warning_days: i64,
 }
 
 impl PuppetCAService {
@@ -128,9 +194,19 @@ impl PuppetCAService {
         Ok(Self {
             client,
             base_url: config.url.trim_end_matches('/').to_string(),
+            faults: FaultInjector::new("puppet_ca", config.fault_injection.as_ref()),
+            crl_cache: Arc::new(RwLock::new(None)),
+            crl_cache_ttl: Duration::from_secs(config.crl_cache_secs),
+            leaf_expiry_warning_days: config.leaf_expiry_warning_days,
+            ca_expiry_warning_days: config.ca_expiry_warning_days,
         })
     }
 
+    /// URL of the CA server
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
     /// Get CA status information
     pub async fn get_status(&self) -> Result<CAStatus, AppError> {
         tracing::debug!("Puppet CA: Fetching CA status");
@@ -156,6 +232,7 @@ impl PuppetCAService {
                 .and_then(parse_puppet_date),
             pending_requests: requests.len(),
             signed_certificates: certificates.len(),
+            snapshot_at: None,
         })
     }
 
@@ -171,6 +248,8 @@ impl PuppetCAService {
             url
         );
 
+        self.faults.inject().await?;
+
         let response = self
             .client
             .get(&url)
@@ -221,6 +300,8 @@ impl PuppetCAService {
         );
         tracing::debug!("Puppet CA: Fetching certificate requests from {}", url);
 
+        self.faults.inject().await?;
+
         let response = self
             .client
             .get(&url)
@@ -258,6 +339,8 @@ impl PuppetCAService {
         );
         tracing::debug!("Puppet CA: Fetching signed certificates from {}", url);
 
+        self.faults.inject().await?;
+
         let response = self
             .client
             .get(&url)
@@ -294,6 +377,8 @@ impl PuppetCAService {
         );
         tracing::debug!("Puppet CA: Fetching certificate {} from {}", certname, url);
 
+        self.faults.inject().await?;
+
         let response = self
             .client
             .get(&url)
@@ -307,7 +392,11 @@ impl PuppetCAService {
                 let cert_data: serde_json::Value = response.json().await.map_err(|e| {
                     AppError::Internal(format!("Failed to parse certificate: {}", e))
                 })?;
-                self.parse_certificate(cert_data)
+                let mut certificate = self.parse_certificate(cert_data)?;
+                if certificate.state == CertificateStatus::Revoked {
+                    certificate.revocation = self.revocation_info(&certificate.serial).await;
+                }
+                Ok(certificate)
             }
             StatusCode::NOT_FOUND => Err(AppError::NotFound(format!(
                 "Certificate not found: {}",
@@ -340,6 +429,8 @@ impl PuppetCAService {
             body["dns_alt_names"] = serde_json::json!(request.dns_alt_names);
         }
 
+        self.faults.inject().await?;
+
         let response = self
             .client
             .put(&url)
@@ -385,6 +476,8 @@ impl PuppetCAService {
             "desired_state": "revoked"
         });
 
+        self.faults.inject().await?;
+
         let response = self
             .client
             .put(&url)
@@ -418,6 +511,8 @@ impl PuppetCAService {
         );
         tracing::info!("Puppet CA: Revoking certificate {}", certname);
 
+        self.faults.inject().await?;
+
         let response = self
             .client
             .delete(&url)
@@ -427,10 +522,14 @@ impl PuppetCAService {
             .map_err(|e| AppError::ServiceUnavailable(format!("CA service error: {}", e)))?;
 
         match response.status() {
-            StatusCode::OK | StatusCode::NO_CONTENT => Ok(RevokeResponse {
-                certname: certname.to_string(),
-                message: format!("Certificate revoked successfully: {}", certname),
-            }),
+            StatusCode::OK | StatusCode::NO_CONTENT => {
+                // The CA publishes a new CRL
+                self.crl_cache.write().await.take();
+                Ok(RevokeResponse {
+                    certname: certname.to_string(),
+                    message: format!("Certificate revoked successfully: {}", certname),
+                })
+            }
             StatusCode::NOT_FOUND => Err(AppError::NotFound(format!(
                 "Certificate not found: {}",
                 certname
@@ -442,6 +541,353 @@ impl PuppetCAService {
         }
     }
 
+    /// Sign several certificate requests, reporting the outcome per certname
+    pub async fn sign_certificates(&self, certnames: &[String]) -> BulkCertificateResponse {
+        let request = SignRequest {
+            dns_alt_names: Vec::new(),
+        };
+        self.bulk(certnames, |certname| {
+            let request = request.clone();
+            async move {
+                self.sign_certificate(&certname, &request)
+                    .await
+                    .map(|r| r.message)
+            }
+        })
+        .await
+    }
+
+    /// Revoke several certificates, reporting the outcome per certname
+    pub async fn revoke_certificates(&self, certnames: &[String]) -> BulkCertificateResponse {
+        self.bulk(certnames, |certname| async move {
+            self.revoke_certificate(&certname).await.map(|r| r.message)
+        })
+        .await
+    }
+
+    /// Run `operation` for each certname with bounded concurrency
+    async fn bulk<F, Fut>(&self, certnames: &[String], operation: F) -> BulkCertificateResponse
+    where
+        F: Fn(String) -> Fut,
+        Fut: std::future::Future<Output = Result<String, AppError>>,
+    {
+        let semaphore = Arc::new(Semaphore::new(BULK_CONCURRENCY));
+        let results = futures::future::join_all(certnames.iter().map(|certname| {
+            let sem = semaphore.clone();
+            let operation = operation(certname.clone());
+            async move {
+                let _permit = sem.acquire().await;
+                match operation.await {
+                    Ok(message) => BulkCertificateResult {
+                        certname: certname.clone(),
+                        success: true,
+                        message,
+                    },
+                    Err(e) => BulkCertificateResult {
+                        certname: certname.clone(),
+                        success: false,
+                        message: e.to_string(),
+                    },
+                }
+            }
+        }))
+        .await;
+
+        BulkCertificateResponse::from_results(results)
+    }
+
+    /// Get the CA certificate bundle (PEM, including any intermediates)
+    pub async fn get_ca_bundle_pem(&self) -> Result<String, AppError> {
+        let url = format!(
+            "{}/puppet-ca/v1/certificate/ca?environment=production",
+            self.base_url
+        );
+        tracing::debug!("Puppet CA: Fetching CA bundle from {}", url);
+
+        self.faults.inject().await?;
+
+        let response = self
+            .client
+            .get(&url)
+            .header("Accept", "text/plain")
+            .send()
+            .await
+            .map_err(|e| AppError::ServiceUnavailable(format!("CA service error: {}", e)))?;
+
+        match response.status() {
+            StatusCode::OK => response
+                .text()
+                .await
+                .map_err(|e| AppError::Internal(format!("Failed to read CA bundle: {}", e))),
+            status => Err(AppError::ServiceUnavailable(format!(
+                "CA service returned status: {}",
+                status
+            ))),
+        }
+    }
+
+    /// Get a node certificate in PEM form
+    pub async fn get_certificate_pem(&self, certname: &str) -> Result<String, AppError> {
+        let url = format!(
+            "{}/puppet-ca/v1/certificate/{}?environment=production",
+            self.base_url, certname
+        );
+        tracing::debug!("Puppet CA: Fetching certificate PEM for {}", certname);
+
+        self.faults.inject().await?;
+
+        let response = self
+            .client
+            .get(&url)
+            .header("Accept", "text/plain")
+            .send()
+            .await
+            .map_err(|e| AppError::ServiceUnavailable(format!("CA service error: {}", e)))?;
+
+        match response.status() {
+            StatusCode::OK => response
+                .text()
+                .await
+                .map_err(|e| AppError::Internal(format!("Failed to read certificate: {}", e))),
+            StatusCode::NOT_FOUND => Err(AppError::NotFound(format!(
+                "Certificate not found: {}",
+                certname
+            ))),
+            status => Err(AppError::ServiceUnavailable(format!(
+                "CA service returned status: {}",
+                status
+            ))),
+        }
+    }
+
+    /// Get the CA certificate chain
+    pub async fn get_ca_chain(&self) -> Result<CaChain, AppError> {
+        let pem = self.get_ca_bundle_pem().await?;
+        self.ca_chain_from_pem(&pem)
+    }
+
+    /// Decode a CA bundle into its chains
+    ///
+    /// Bundles may hold several chains (e.g. while a new root is rolled out);
+    /// each chain is listed from its leaf-most CA up to the root.
+    pub fn ca_chain_from_pem(&self, pem: &str) -> Result<CaChain, AppError> {
+        let bundle = x509::parse_certificates_pem(pem).map_err(AppError::Internal)?;
+
+        // Chains start at CAs that did not issue another CA of the bundle
+        let mut ordered: Vec<&x509::ParsedCertificate> = Vec::new();
+        for start in bundle.iter().filter(|c| {
+            !bundle
+                .iter()
+                .any(|other| !other.is_self_signed() && other.issuer == c.subject)
+        }) {
+            for cert in issuer_path(start, &bundle) {
+                if !ordered.iter().any(|c| c.fingerprint == cert.fingerprint) {
+                    ordered.push(cert);
+                }
+            }
+        }
+        // Anything left over is part of a cycle; list it rather than drop it
+        for cert in &bundle {
+            if !ordered.iter().any(|c| c.fingerprint == cert.fingerprint) {
+                ordered.push(cert);
+            }
+        }
+
+        let certificates: Vec<ChainCertificate> = ordered
+            .into_iter()
+            .map(|c| self.chain_certificate(c))
+            .collect();
+        let warnings = expiry_warnings(&certificates);
+
+        Ok(CaChain {
+            certificates,
+            warnings,
+        })
+    }
+
+    /// Check that a node certificate chains to the CA bundle
+    pub async fn validate_certificate_chain(
+        &self,
+        certname: &str,
+    ) -> Result<ChainValidation, AppError> {
+        let leaf_pem = self.get_certificate_pem(certname).await?;
+        let bundle_pem = self.get_ca_bundle_pem().await?;
+
+        let leaf = x509::parse_certificates_pem(&leaf_pem)
+            .map_err(AppError::Internal)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::Internal("Empty certificate PEM".to_string()))?;
+        let bundle = x509::parse_certificates_pem(&bundle_pem).map_err(AppError::Internal)?;
+
+        let error = x509::verify_chain(&leaf.der, &bundle).err();
+        let chain: Vec<ChainCertificate> = issuer_path(&leaf, &bundle)
+            .into_iter()
+            .map(|c| self.chain_certificate(c))
+            .collect();
+        let warnings = expiry_warnings(&chain);
+
+        Ok(ChainValidation {
+            certname: certname.to_string(),
+            valid: error.is_none(),
+            error,
+            chain,
+            warnings,
+        })
+    }
+
+    fn chain_certificate(&self, cert: &x509::ParsedCertificate) -> ChainCertificate {
+        let role = if cert.is_self_signed() {
+            ChainRole::Root
+        } else if cert.is_ca {
+            ChainRole::Intermediate
+        } else {
+            ChainRole::Leaf
+        };
+        let warning_days = match role {
+            ChainRole::Leaf => self.leaf_expiry_warning_days,
+            ChainRole::Root | ChainRole::Intermediate => self.ca_expiry_warning_days,
+        };
+        let days_until_expiry = (cert.not_after - Utc::now()).num_days();
+
+        ChainCertificate {
+            subject: cert.subject.clone(),
+            issuer: cert.issuer.clone(),
+            serial: cert.serial.clone(),
+            not_before: cert.not_before,
+            not_after: cert.not_after,
+            fingerprint: cert.fingerprint.clone(),
+            role,
+            days_until_expiry,
+            expiring_soon: days_until_expiry <= warning_days,
+        }
+    }
+
+    /// Capture the current CA state for offline use
+    pub async fn take_snapshot(&self) -> Result<CaSnapshot, AppError> {
+        let certificates = self.list_certificates().await?;
+        let requests = self.list_requests().await?;
+        let ca_bundle_pem = self.get_ca_bundle_pem().await?;
+        // A CA without revocations may not publish a CRL
+        let crl_pem = match self.get_crl_pem().await {
+            Ok(pem) => Some(pem),
+            Err(AppError::NotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+
+        Ok(CaSnapshot {
+            ca_bundle_pem: Some(ca_bundle_pem),
+            crl_pem,
+            certificates,
+            requests,
+            synced_at: Utc::now(),
+        })
+    }
+
+    /// Get the CRL in PEM form
+    pub async fn get_crl_pem(&self) -> Result<String, AppError> {
+        self.refresh_crl().await?;
+        let cache = self.crl_cache.read().await;
+        Ok(cache.as_ref().map(|c| c.pem.clone()).unwrap_or_default())
+    }
+
+    /// Get the decoded CRL
+    pub async fn get_crl(&self) -> Result<CrlResponse, AppError> {
+        self.refresh_crl().await?;
+        let cache = self.crl_cache.read().await;
+        let cached = cache
+            .as_ref()
+            .ok_or_else(|| AppError::Internal("CRL cache is empty".to_string()))?;
+        Ok(CrlResponse {
+            fetched_at: cached.fetched_at,
+            crls: cached.crls.clone(),
+        })
+    }
+
+    /// Revocation details for a serial number, if a CRL lists it
+    async fn revocation_info(&self, serial: &str) -> Option<RevocationInfo> {
+        let crl = match self.get_crl().await {
+            Ok(crl) => crl,
+            Err(e) => {
+                tracing::warn!("Puppet CA: Could not load CRL: {}", e);
+                return None;
+            }
+        };
+
+        crl.crls.into_iter().find_map(|crl| {
+            crl.revoked
+                .into_iter()
+                .find(|entry| entry.serial == serial)
+                .map(|entry| RevocationInfo {
+                    revoked_at: entry.revoked_at,
+                    reason: entry.reason,
+                    crl_issuer: crl.issuer.clone(),
+                })
+        })
+    }
+
+    /// Fetch the CRL unless the cached copy is still fresh
+    async fn refresh_crl(&self) -> Result<(), AppError> {
+        if self
+            .crl_cache
+            .read()
+            .await
+            .as_ref()
+            .is_some_and(|c| c.expires > Instant::now())
+        {
+            return Ok(());
+        }
+
+        let mut cache = self.crl_cache.write().await;
+        // Another request may have refreshed it while we waited
+        if cache.as_ref().is_some_and(|c| c.expires > Instant::now()) {
+            return Ok(());
+        }
+
+        let url = format!(
+            "{}/puppet-ca/v1/certificate_revocation_list/ca?environment=production",
+            self.base_url
+        );
+        tracing::debug!("Puppet CA: Fetching CRL from {}", url);
+
+        self.faults.inject().await?;
+
+        let response = self
+            .client
+            .get(&url)
+            .header("Accept", "text/plain")
+            .send()
+            .await
+            .map_err(|e| AppError::ServiceUnavailable(format!("CA service error: {}", e)))?;
+
+        let pem = match response.status() {
+            StatusCode::OK => response
+                .text()
+                .await
+                .map_err(|e| AppError::Internal(format!("Failed to read CRL: {}", e)))?,
+            StatusCode::NOT_FOUND => {
+                return Err(AppError::NotFound("CA has no CRL".to_string()));
+            }
+            status => {
+                return Err(AppError::ServiceUnavailable(format!(
+                    "CA service returned status: {}",
+                    status
+                )));
+            }
+        };
+
+        let crls = x509::parse_crl_pem(&pem)
+            .map_err(|e| AppError::Internal(format!("Failed to parse CRL: {}", e)))?;
+
+        *cache = Some(CachedCrl {
+            pem,
+            crls,
+            fetched_at: Utc::now(),
+            expires: Instant::now() + self.crl_cache_ttl,
+        });
+        Ok(())
+    }
+
     /// Renew the CA certificate
     pub async fn renew_ca(&self, request: &RenewCARequest) -> Result<RenewCAResponse, AppError> {
         let url = format!(
@@ -457,6 +903,8 @@ impl PuppetCAService {
             "ttl": format!("{}d", request.days)
         });
 
+        self.faults.inject().await?;
+
         let response = self
             .client
             .post(&url)
@@ -555,11 +1003,13 @@ impl PuppetCAService {
                 .as_str()
                 .ok_or_else(|| AppError::Internal("Missing certname".to_string()))?
                 .to_string(),
+            // Puppet Server reports the serial as a number
             serial: data["serial_number"]
-                .as_str()
-                .or(data["serial"].as_str())
-                .unwrap_or("unknown")
-                .to_string(),
+                .as_u64()
+                .map(|n| n.to_string())
+                .or_else(|| data["serial_number"].as_str().map(String::from))
+                .or_else(|| data["serial"].as_str().map(String::from))
+                .unwrap_or_else(|| "unknown".to_string()),
             not_before,
             not_after,
             dns_alt_names: data["dns_alt_names"]
@@ -575,6 +1025,7 @@ impl PuppetCAService {
                 .unwrap_or("unknown")
                 .to_string(),
             state,
+            revocation: None,
         })
     }
 }
@@ -13,7 +13,8 @@ use std::time::Duration;
 use tracing::{debug, error, info, warn};
 
 use crate::config::PuppetDbConfig;
-use crate::models::{Fact, Node, Report, ResourceEvent};
+use crate::models::{Fact, Node, Report, ReportLog, ReportMetric, ResourceEvent};
+use crate::services::fault_injection::FaultInjector;
 
 /// Check if an SSL file exists and is readable, logging the result
 fn check_ssl_file_access(path: &Path, file_type: &str) -> Result<usize, String> {
@@ -102,6 +103,7 @@ fn check_ssl_file_access(path: &Path, file_type: &str) -> Result<usize, String>
 pub struct PuppetDbClient {
     client: Client,
     base_url: String,
+    faults: FaultInjector,
 }
 
 /// Query parameters for paginated requests
@@ -515,13 +517,21 @@ impl PuppetDbClient {
         Ok(Self {
             client,
             base_url: config.url.trim_end_matches('/').to_string(),
+            faults: FaultInjector::new("puppetdb", config.fault_injection.as_ref()),
         })
     }
 
+    /// URL of the PuppetDB server
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
     /// Execute a raw PQL query
     pub async fn query<T: DeserializeOwned>(&self, query: &str) -> Result<Vec<T>> {
         let url = format!("{}/pdb/query/v4", self.base_url);
 
+        self.faults.inject().await?;
+
         let response = self
             .client
             .post(&url)
@@ -543,6 +553,8 @@ impl PuppetDbClient {
     ) -> Result<PaginatedResponse<T>> {
         let url = format!("{}/pdb/query/v4{}", self.base_url, params.to_query_string());
 
+        self.faults.inject().await?;
+
         let response = self
             .client
             .post(&url)
@@ -763,6 +775,8 @@ impl PuppetDbClient {
             urlencoding::encode(certname)
         );
 
+        self.faults.inject().await?;
+
         let response = self
             .client
             .get(&url)
@@ -1058,6 +1072,8 @@ impl PuppetDbClient {
             urlencoding::encode(hash)
         );
 
+        self.faults.inject().await?;
+
         let response = self
             .client
             .get(&url)
@@ -1122,6 +1138,8 @@ impl PuppetDbClient {
             urlencoding::encode(title)
         );
 
+        self.faults.inject().await?;
+
         let response = self
             .client
             .get(&url)
@@ -1183,6 +1201,36 @@ impl PuppetDbClient {
         self.query_events(&query).await
     }
 
+    /// Get all events of a report from the report's `events` child endpoint
+    pub async fn get_report_resource_events(&self, hash: &str) -> Result<Vec<ResourceEvent>> {
+        let url = format!(
+            "{}/pdb/query/v4/reports/{}/events",
+            self.base_url,
+            urlencoding::encode(hash)
+        );
+        self.get(&url).await
+    }
+
+    /// Get all metrics of a report from the report's `metrics` child endpoint
+    pub async fn get_report_metrics(&self, hash: &str) -> Result<Vec<ReportMetric>> {
+        let url = format!(
+            "{}/pdb/query/v4/reports/{}/metrics",
+            self.base_url,
+            urlencoding::encode(hash)
+        );
+        self.get(&url).await
+    }
+
+    /// Get all log lines of a report from the report's `logs` child endpoint
+    pub async fn get_report_logs(&self, hash: &str) -> Result<Vec<ReportLog>> {
+        let url = format!(
+            "{}/pdb/query/v4/reports/{}/logs",
+            self.base_url,
+            urlencoding::encode(hash)
+        );
+        self.get(&url).await
+    }
+
     /// Query events
     pub async fn query_events(&self, query: &QueryBuilder) -> Result<Vec<ResourceEvent>> {
         self.query_events_with_params(query, QueryParams::default())
@@ -1224,6 +1272,8 @@ impl PuppetDbClient {
             urlencoding::encode(certname)
         );
 
+        self.faults.inject().await?;
+
         let response = self
             .client
             .get(&url)
@@ -1283,6 +1333,8 @@ impl PuppetDbClient {
             urlencoding::encode(name)
         );
 
+        self.faults.inject().await?;
+
         let response = self
             .client
             .get(&url)
@@ -1354,6 +1406,8 @@ impl PuppetDbClient {
 
         debug!("PuppetDB: Deactivating node '{}' via command API", certname);
 
+        self.faults.inject().await?;
+
         let response = self
             .client
             .post(&url)
@@ -1387,6 +1441,7 @@ impl PuppetDbClient {
     /// Internal GET request handler
     async fn get<T: DeserializeOwned>(&self, url: &str) -> Result<T> {
         debug!("PuppetDB: Sending GET request to {}", url);
+        self.faults.inject().await?;
         let response = self.client.get(url).send().await.map_err(|e| {
             // Log detailed error information
             error!("PuppetDB ERROR: HTTP request failed to {}: {}", url, e);
@@ -1443,6 +1498,7 @@ impl PuppetDbClient {
     /// PuppetDB returns when a query is issued with `include_total=true`.
     async fn get_paginated<T: DeserializeOwned>(&self, url: &str) -> Result<PaginatedResponse<T>> {
         debug!("PuppetDB: Sending paginated GET request to {}", url);
+        self.faults.inject().await?;
         let response = self
             .client
             .get(url)
@@ -1534,6 +1590,7 @@ mod tests {
             ssl_key: None,
             ssl_ca: None,
             ssl: None,
+            fault_injection: None,
         };
 
         let client = PuppetDbClient::new(&config).unwrap();
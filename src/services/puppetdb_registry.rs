@@ -0,0 +1,130 @@
+//! Per-organization PuppetDB clients
+//!
+//! An organization can be mapped to its own PuppetDB, so one WebUI can serve
+//! several Puppet infrastructures. Organizations without a mapping use the
+//! PuppetDB from the configuration file.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use sqlx::SqlitePool;
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use crate::config::PuppetDbConfig;
+use crate::db::OrganizationRepository;
+use crate::models::OrganizationPuppetDb;
+use crate::services::PuppetDbClient;
+
+/// Cache of PuppetDB clients built from organization mappings
+///
+/// A cached `None` means the organization has no mapping of its own.
+#[derive(Clone, Default)]
+pub struct PuppetDbRegistry {
+    clients: Arc<RwLock<HashMap<Uuid, Option<Arc<PuppetDbClient>>>>>,
+}
+
+impl PuppetDbRegistry {
+    /// The PuppetDB client serving an organization
+    ///
+    /// Falls back to `default` only for organizations without a mapping. A
+    /// mapping that cannot be loaded yields `None` rather than the shared
+    /// client, so one tenant never sees another infrastructure's nodes.
+    pub async fn resolve(
+        &self,
+        pool: &SqlitePool,
+        organization_id: Uuid,
+        default: Option<Arc<PuppetDbClient>>,
+    ) -> Option<Arc<PuppetDbClient>> {
+        if let Some(cached) = self.clients.read().await.get(&organization_id) {
+            return cached.clone().or(default);
+        }
+
+        let mapping = match OrganizationRepository::new(pool)
+            .get_puppetdb(organization_id)
+            .await
+        {
+            Ok(mapping) => mapping,
+            Err(e) => {
+                tracing::error!(
+                    "Failed to load PuppetDB mapping for organization {}: {}",
+                    organization_id,
+                    e
+                );
+                return None;
+            }
+        };
+
+        let client = match mapping {
+            Some(mapping) => match PuppetDbClient::new(&client_config(&mapping)) {
+                Ok(client) => Some(Arc::new(client)),
+                Err(e) => {
+                    tracing::error!(
+                        "Failed to create PuppetDB client for organization {}: {}",
+                        organization_id,
+                        e
+                    );
+                    return None;
+                }
+            },
+            None => None,
+        };
+
+        self.clients
+            .write()
+            .await
+            .insert(organization_id, client.clone());
+        client.or(default)
+    }
+
+    /// Drop the cached client so the next request reloads the mapping
+    pub async fn invalidate(&self, organization_id: Uuid) {
+        self.clients.write().await.remove(&organization_id);
+    }
+}
+
+/// Client configuration for an organization's PuppetDB
+pub fn client_config(mapping: &OrganizationPuppetDb) -> PuppetDbConfig {
+    PuppetDbConfig {
+        url: mapping.url.clone(),
+        timeout_secs: mapping.timeout_secs,
+        ssl_verify: mapping.ssl_verify,
+        ssl_cert: mapping.ssl_cert.as_ref().map(PathBuf::from),
+        ssl_key: mapping.ssl_key.as_ref().map(PathBuf::from),
+        ssl_ca: mapping.ssl_ca.as_ref().map(PathBuf::from),
+        ssl: None,
+        fault_injection: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_client_config_from_mapping() {
+        let mapping = OrganizationPuppetDb {
+            organization_id: Uuid::new_v4(),
+            url: "https://puppetdb.tenant.example:8081".to_string(),
+            timeout_secs: 10,
+            ssl_verify: false,
+            ssl_cert: Some("/etc/openvox-webui/tenant/cert.pem".to_string()),
+            ssl_key: Some("/etc/openvox-webui/tenant/key.pem".to_string()),
+            ssl_ca: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        let config = client_config(&mapping);
+        assert_eq!(config.url, mapping.url);
+        assert_eq!(config.timeout_secs, 10);
+        assert!(!config.effective_ssl_verify());
+        assert_eq!(
+            config.effective_ssl_cert(),
+            Some(&PathBuf::from("/etc/openvox-webui/tenant/cert.pem"))
+        );
+        assert!(config.effective_ssl_ca().is_none());
+    }
+}
@@ -0,0 +1,66 @@
+//! Organization resource quotas
+//!
+//! Organizations may cap how many node groups, saved reports, API keys and
+//! users they own. Create paths call [`check_quota`] before inserting.
+
+use sqlx::SqlitePool;
+use uuid::Uuid;
+
+use crate::db::OrganizationRepository;
+use crate::models::{OrganizationUsage, QuotaResource, QuotaUsage};
+use crate::utils::AppError;
+
+/// Fail when the organization may not create another resource of a kind
+pub async fn check_quota(
+    pool: &SqlitePool,
+    organization_id: Uuid,
+    resource: QuotaResource,
+) -> Result<(), AppError> {
+    let repo = OrganizationRepository::new(pool);
+    let Some(org) = repo.get_by_id(organization_id).await? else {
+        return Ok(());
+    };
+    let Some(limit) = org.quotas.limit(resource) else {
+        return Ok(());
+    };
+
+    let usage = QuotaUsage {
+        resource,
+        used: repo.count_resources(organization_id, resource).await?,
+        limit: Some(limit),
+    };
+    if usage.is_reached() {
+        return Err(AppError::conflict(format!(
+            "Organization quota reached: at most {} {}",
+            limit,
+            resource.label()
+        )));
+    }
+    Ok(())
+}
+
+/// Consumption of every quota resource of an organization
+pub async fn organization_usage(
+    pool: &SqlitePool,
+    organization_id: Uuid,
+) -> Result<OrganizationUsage, AppError> {
+    let repo = OrganizationRepository::new(pool);
+    let org = repo
+        .get_by_id(organization_id)
+        .await?
+        .ok_or_else(|| AppError::not_found("Organization not found"))?;
+
+    let mut usage = Vec::with_capacity(QuotaResource::ALL.len());
+    for resource in QuotaResource::ALL {
+        usage.push(QuotaUsage {
+            resource,
+            used: repo.count_resources(organization_id, resource).await?,
+            limit: org.quotas.limit(resource),
+        });
+    }
+
+    Ok(OrganizationUsage {
+        organization_id,
+        usage,
+    })
+}
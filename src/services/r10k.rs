@@ -19,6 +19,8 @@ use tokio::time::timeout;
 use tracing::{debug, error, info, warn};
 use uuid::Uuid;
 
+use crate::models::DeploymentOptions;
+
 #[cfg(unix)]
 use std::os::unix::process::ExitStatusExt;
 
@@ -210,39 +212,29 @@ impl R10kService {
         &self,
         environment: &str,
         deployment_id: Option<Uuid>,
+    ) -> Result<DeploymentResult> {
+        self.deploy_environment_with_options(
+            environment,
+            &DeploymentOptions::default(),
+            deployment_id,
+        )
+        .await
+    }
+
+    /// Deploy an environment, or a module of it, with the flags of a
+    /// deployment request
+    pub async fn deploy_environment_with_options(
+        &self,
+        environment: &str,
+        options: &DeploymentOptions,
+        deployment_id: Option<Uuid>,
     ) -> Result<DeploymentResult> {
         let start = std::time::Instant::now();
 
         info!("Starting r10k deployment for environment: {}", environment);
 
-        let mut args = vec!["deploy", "environment", environment];
-
-        // Add Puppetfile processing flag
-        if self.config.deploy_puppetfile {
-            args.push("-p");
-        }
-
-        // Add generate types flag
-        if self.config.generate_types {
-            args.push("-g");
-        }
-
-        // Add config file path
-        args.push("-c");
-        args.push(
-            self.config
-                .config_path
-                .to_str()
-                .unwrap_or("/etc/puppetlabs/r10k/r10k.yaml"),
-        );
-
-        // Add verbose output for logging
-        args.push("-v");
-
-        // Add extra args
-        for arg in &self.config.extra_args {
-            args.push(arg);
-        }
+        let args = self.deploy_args(environment, options);
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
 
         debug!("Executing: {:?} {:?}", self.config.binary_path, args);
 
@@ -281,6 +273,57 @@ impl R10kService {
         }
     }
 
+    /// Arguments of `r10k deploy` for an environment, or a module of it
+    pub fn deploy_args(&self, environment: &str, options: &DeploymentOptions) -> Vec<String> {
+        let mut args: Vec<String> = match &options.module {
+            Some(module) => vec![
+                "deploy".to_string(),
+                "module".to_string(),
+                "-e".to_string(),
+                environment.to_string(),
+                module.clone(),
+            ],
+            None => {
+                let mut args = vec![
+                    "deploy".to_string(),
+                    "environment".to_string(),
+                    environment.to_string(),
+                ];
+
+                // Add Puppetfile processing flag
+                if self.config.deploy_puppetfile && !options.skip_puppetfile {
+                    args.push("-p".to_string());
+                    if options.incremental {
+                        args.push("--incremental".to_string());
+                    }
+                }
+                args
+            }
+        };
+
+        // Add generate types flag
+        if options.generate_types.unwrap_or(self.config.generate_types) {
+            args.push("-g".to_string());
+        }
+
+        // Add config file path
+        args.push("-c".to_string());
+        args.push(self.config.config_path.to_string_lossy().to_string());
+
+        // Add verbose output for logging
+        args.push("-v".to_string());
+
+        // Add extra args
+        args.extend(self.config.extra_args.iter().cloned());
+
+        args
+    }
+
+    /// Command line run for the given `r10k` arguments
+    pub fn command_line(&self, args: &[String]) -> String {
+        format!("{} {}", self.config.binary_path.display(), args.join(" "))
+    }
+
     /// Deploy all environments
     pub async fn deploy_all(&self) -> Result<DeploymentResult> {
         let start = std::time::Instant::now();
@@ -870,6 +913,53 @@ mod 'legacy_module',
         assert_eq!(modules[0].tag(), Some("v1.0.0"));
     }
 
+    #[test]
+    fn test_deploy_args() {
+        let service = R10kService::new(R10kConfig::default());
+
+        let args = service.deploy_args("production", &DeploymentOptions::default());
+        assert_eq!(
+            args,
+            [
+                "deploy",
+                "environment",
+                "production",
+                "-p",
+                "-c",
+                "/etc/puppetlabs/r10k/r10k.yaml",
+                "-v"
+            ]
+        );
+
+        let incremental = DeploymentOptions {
+            incremental: true,
+            generate_types: Some(true),
+            ..Default::default()
+        };
+        let args = service.deploy_args("production", &incremental);
+        assert_eq!(&args[3..6], ["-p", "--incremental", "-g"]);
+
+        let skip = DeploymentOptions {
+            skip_puppetfile: true,
+            ..Default::default()
+        };
+        assert!(!service
+            .deploy_args("production", &skip)
+            .contains(&"-p".to_string()));
+
+        let module = DeploymentOptions {
+            module: Some("ntp".to_string()),
+            ..Default::default()
+        };
+        let args = service.deploy_args("production", &module);
+        assert_eq!(&args[..5], ["deploy", "module", "-e", "production", "ntp"]);
+        assert_eq!(
+            service.command_line(&args),
+            "/opt/puppetlabs/puppet/bin/r10k deploy module -e production ntp \
+             -c /etc/puppetlabs/r10k/r10k.yaml -v"
+        );
+    }
+
     #[tokio::test]
     async fn test_r10k_service_creation() {
         let config = R10kConfig::default();
@@ -1,7 +1,7 @@
 //! RBAC (Role-Based Access Control) service
 
 use anyhow::{Context, Result};
-use std::collections::HashSet;
+use std::collections::{HashSet, VecDeque};
 use uuid::Uuid;
 
 use crate::models::{
@@ -63,8 +63,8 @@ impl RbacService {
             anyhow::bail!("Role with name '{}' already exists", role.name);
         }
 
-        // Validate parent exists if specified
-        if let Some(parent_id) = role.parent_id {
+        // Validate parents exist if specified
+        for parent_id in role.parents() {
             if !self.roles.iter().any(|r| r.id == parent_id) {
                 anyhow::bail!("Parent role not found");
             }
@@ -76,6 +76,14 @@ impl RbacService {
 
     /// Update a role
     pub fn update_role(&mut self, id: Uuid, updates: Role) -> Result<&Role> {
+        if creates_role_cycle(id, &updates.parents(), |role_id| {
+            self.get_role(*role_id)
+                .map(|r| r.parents())
+                .unwrap_or_default()
+        }) {
+            anyhow::bail!("Role cannot inherit from itself");
+        }
+
         let role = self
             .roles
             .iter_mut()
@@ -89,6 +97,7 @@ impl RbacService {
         role.display_name = updates.display_name;
         role.description = updates.description;
         role.parent_id = updates.parent_id;
+        role.parent_ids = updates.parent_ids;
         role.permissions = updates.permissions;
         role.updated_at = chrono::Utc::now();
 
@@ -118,29 +127,21 @@ impl RbacService {
     /// Get effective permissions for a user based on their roles
     pub fn get_effective_permissions(&self, role_ids: &[Uuid]) -> EffectivePermissions {
         let mut all_permissions: HashSet<Permission> = HashSet::new();
-        let mut role_names: Vec<String> = vec![];
-
-        for role_id in role_ids {
-            if let Some(role) = self.get_role(*role_id) {
-                role_names.push(role.name.clone());
-
-                // Add direct permissions
+        let role_names: Vec<String> = role_ids
+            .iter()
+            .filter_map(|id| self.get_role(*id))
+            .map(|role| role.name.clone())
+            .collect();
+
+        // Direct permissions plus those inherited from every ancestor role
+        let expanded = expand_role_hierarchy(role_ids, |id| {
+            self.get_role(*id).map(|r| r.parents()).unwrap_or_default()
+        });
+        for role_id in expanded {
+            if let Some(role) = self.get_role(role_id) {
                 for perm in &role.permissions {
                     all_permissions.insert(perm.clone());
                 }
-
-                // Add inherited permissions from parent roles
-                let mut current_parent = role.parent_id;
-                while let Some(parent_id) = current_parent {
-                    if let Some(parent_role) = self.get_role(parent_id) {
-                        for perm in &parent_role.permissions {
-                            all_permissions.insert(perm.clone());
-                        }
-                        current_parent = parent_role.parent_id;
-                    } else {
-                        break;
-                    }
-                }
             }
         }
 
@@ -303,6 +304,38 @@ impl PermissionGuard {
     }
 }
 
+/// Roles reachable from `role_ids` through parent links, breadth first
+///
+/// The starting roles come first and every role appears once, so a cycle left
+/// in stored data cannot loop forever.
+pub fn expand_role_hierarchy<F>(role_ids: &[Uuid], parents_of: F) -> Vec<Uuid>
+where
+    F: Fn(&Uuid) -> Vec<Uuid>,
+{
+    let mut seen = HashSet::new();
+    let mut order = Vec::new();
+    let mut queue: VecDeque<Uuid> = role_ids.iter().copied().collect();
+
+    while let Some(role_id) = queue.pop_front() {
+        if !seen.insert(role_id) {
+            continue;
+        }
+        order.push(role_id);
+        queue.extend(parents_of(&role_id));
+    }
+
+    order
+}
+
+/// Whether giving `role_id` the parents `parent_ids` would make the role its
+/// own ancestor
+pub fn creates_role_cycle<F>(role_id: Uuid, parent_ids: &[Uuid], parents_of: F) -> bool
+where
+    F: Fn(&Uuid) -> Vec<Uuid>,
+{
+    expand_role_hierarchy(parent_ids, parents_of).contains(&role_id)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -415,6 +448,7 @@ mod tests {
             description: Some("A custom role".to_string()),
             is_system: false,
             parent_id: Some(SystemRole::Viewer.uuid()),
+            parent_ids: vec![SystemRole::Viewer.uuid()],
             permissions: vec![Permission {
                 id: Uuid::new_v4(),
                 resource: Resource::Groups,
@@ -446,6 +480,57 @@ mod tests {
             .any(|p| p.resource == Resource::Nodes && p.action == Action::Read));
     }
 
+    #[test]
+    fn test_composite_role_inherits_all_parents() {
+        let mut service = RbacService::new();
+
+        let sre = Role {
+            name: "sre".to_string(),
+            display_name: "SRE".to_string(),
+            parent_ids: vec![SystemRole::Viewer.uuid(), SystemRole::Operator.uuid()],
+            ..Role::default()
+        };
+        let sre_id = sre.id;
+        service.create_role(sre).unwrap();
+
+        let effective = service.get_effective_permissions(&[sre_id]);
+        assert_eq!(effective.roles, vec!["sre"]);
+        for parent in [SystemRole::Viewer, SystemRole::Operator] {
+            for perm in parent.default_permissions() {
+                assert!(effective
+                    .permissions
+                    .iter()
+                    .any(|p| p.resource == perm.resource && p.action == perm.action));
+            }
+        }
+    }
+
+    #[test]
+    fn test_role_cycle_detection() {
+        let a = Uuid::new_v4();
+        let b = Uuid::new_v4();
+        let c = Uuid::new_v4();
+        // c -> b -> a
+        let parents = |id: &Uuid| -> Vec<Uuid> {
+            if *id == c {
+                vec![b]
+            } else if *id == b {
+                vec![a]
+            } else {
+                vec![]
+            }
+        };
+
+        assert_eq!(expand_role_hierarchy(&[c], parents), vec![c, b, a]);
+        assert!(creates_role_cycle(a, &[c], parents));
+        assert!(creates_role_cycle(a, &[a], parents));
+        assert!(!creates_role_cycle(c, &[a], parents));
+
+        // Stored cycles are expanded once
+        let cyclic = |id: &Uuid| -> Vec<Uuid> { vec![if *id == a { b } else { a }] };
+        assert_eq!(expand_role_hierarchy(&[a], cyclic), vec![a, b]);
+    }
+
     #[test]
     fn test_cannot_delete_system_role() {
         let mut service = RbacService::new();
@@ -519,6 +604,7 @@ mod tests {
             description: Some("Can edit a specific group".to_string()),
             is_system: false,
             parent_id: None,
+            parent_ids: vec![],
             permissions: vec![Permission {
                 id: Uuid::new_v4(),
                 resource: Resource::Groups,
@@ -584,6 +670,7 @@ mod tests {
             description: Some("Can edit multiple specific groups".to_string()),
             is_system: false,
             parent_id: None,
+            parent_ids: vec![],
             permissions: vec![Permission {
                 id: Uuid::new_v4(),
                 resource: Resource::Groups,
@@ -10,10 +10,13 @@ use std::sync::RwLock;
 use std::time::{Duration, Instant};
 use uuid::Uuid;
 
+use crate::db::begin_write;
 use crate::models::{
     Action, CreatePermissionRequest, CreateRoleRequest, EffectivePermissions, Permission,
-    PermissionCheck, PermissionConstraint, PermissionWithRole, Resource, Role, Scope, SystemRole,
+    PermissionCheck, PermissionConstraint, PermissionWithRole, Resource, Role,
+    RoleEffectivePermissions, Scope, SystemRole,
 };
+use crate::services::rbac::{creates_role_cycle, expand_role_hierarchy};
 
 /// Cache entry with TTL
 struct CacheEntry<T> {
@@ -181,6 +184,7 @@ impl DbRbacService {
 
         // Batch load all permissions for all roles in a single query
         let permissions_map = self.batch_get_role_permissions(&role_ids).await?;
+        let graph = self.load_role_graph().await?;
 
         let mut roles = Vec::new();
         for row in rows {
@@ -189,7 +193,9 @@ impl DbRbacService {
                 .get(&role_id_str)
                 .cloned()
                 .unwrap_or_default();
-            roles.push(row_to_role(&row, permissions));
+            let mut role = row_to_role(&row, permissions);
+            role.parent_ids = graph.get(&role.id).cloned().unwrap_or_default();
+            roles.push(role);
         }
 
         Ok(roles)
@@ -220,7 +226,8 @@ impl DbRbacService {
         match row {
             Some(row) => {
                 let permissions = self.get_role_permissions_db(id).await?;
-                let role = row_to_role(&row, permissions);
+                let mut role = row_to_role(&row, permissions);
+                role.parent_ids = self.get_role_parents_db(id).await?;
 
                 // Update cache
                 if let Ok(mut cache) = self.role_cache.write() {
@@ -248,7 +255,9 @@ impl DbRbacService {
             Some(row) => {
                 let role_id = parse_uuid(row.get::<String, _>("id"))?;
                 let permissions = self.get_role_permissions_db(&role_id).await?;
-                Ok(Some(row_to_role(&row, permissions)))
+                let mut role = row_to_role(&row, permissions);
+                role.parent_ids = self.get_role_parents_db(&role_id).await?;
+                Ok(Some(role))
             }
             None => Ok(None),
         }
@@ -261,16 +270,13 @@ impl DbRbacService {
             anyhow::bail!("Role with name '{}' already exists", request.name);
         }
 
-        // Validate parent exists if specified
-        if let Some(parent_id) = &request.parent_id {
-            if self.get_role(parent_id).await?.is_none() {
-                anyhow::bail!("Parent role not found");
-            }
-        }
+        // Validate parents exist if specified
+        let parent_ids = request.requested_parents();
+        self.validate_parents(&parent_ids).await?;
 
         let role_id = Uuid::new_v4();
         let id_str = role_id.to_string();
-        let parent_id_str = request.parent_id.map(|id| id.to_string());
+        let parent_id_str = parent_ids.first().map(|id| id.to_string());
         let now = chrono::Utc::now().to_rfc3339();
 
         sqlx::query(
@@ -288,6 +294,8 @@ impl DbRbacService {
         .await
         .context("Failed to create role")?;
 
+        self.write_role_parents(&role_id, &parent_ids).await?;
+
         // Add permissions if provided
         if let Some(permissions) = request.permissions {
             for perm_request in permissions {
@@ -313,8 +321,12 @@ impl DbRbacService {
             anyhow::bail!("Role with name '{}' already exists", request.name);
         }
 
+        let parent_ids = request.requested_parents();
+        self.validate_parents(&parent_ids).await?;
+        self.check_role_cycle(id, &parent_ids).await?;
+
         let id_str = id.to_string();
-        let parent_id_str = request.parent_id.map(|id| id.to_string());
+        let parent_id_str = parent_ids.first().map(|id| id.to_string());
         let now = chrono::Utc::now().to_rfc3339();
 
         sqlx::query(
@@ -331,6 +343,7 @@ impl DbRbacService {
         .await
         .context("Failed to update role")?;
 
+        self.write_role_parents(id, &parent_ids).await?;
         self.invalidate_role_cache(id);
 
         self.get_role(id)
@@ -358,6 +371,149 @@ impl DbRbacService {
         Ok(result.rows_affected() > 0)
     }
 
+    // =========================================================================
+    // Role Hierarchy
+    // =========================================================================
+
+    /// Replace the parent roles of a role
+    ///
+    /// Fails when a parent does not exist or when the role would end up
+    /// inheriting from itself.
+    pub async fn set_role_parents(&self, id: &Uuid, parent_ids: &[Uuid]) -> Result<Role> {
+        let existing = self.get_role(id).await?.context("Role not found")?;
+
+        if existing.is_system {
+            anyhow::bail!("Cannot modify system roles");
+        }
+
+        self.validate_parents(parent_ids).await?;
+        self.check_role_cycle(id, parent_ids).await?;
+
+        let parent_id_str = parent_ids.first().map(|id| id.to_string());
+        let now = chrono::Utc::now().to_rfc3339();
+        sqlx::query("UPDATE roles SET parent_id = ?, updated_at = ? WHERE id = ?")
+            .bind(&parent_id_str)
+            .bind(&now)
+            .bind(id.to_string())
+            .execute(&self.pool)
+            .await
+            .context("Failed to update role parent")?;
+
+        self.write_role_parents(id, parent_ids).await?;
+        self.invalidate_role_cache(id);
+
+        self.get_role(id)
+            .await?
+            .context("Failed to fetch updated role")
+    }
+
+    /// Permissions a role grants directly and through its ancestors
+    pub async fn get_role_effective_permissions(
+        &self,
+        id: &Uuid,
+    ) -> Result<RoleEffectivePermissions> {
+        let expanded = self.expand_roles(&[*id]).await?;
+
+        let mut permissions = Vec::new();
+        for role_id in &expanded {
+            if let Some(role) = self.get_role(role_id).await? {
+                for permission in role.permissions {
+                    permissions.push(PermissionWithRole {
+                        permission,
+                        role_id: role.id,
+                        role_name: role.name.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(RoleEffectivePermissions {
+            role_id: *id,
+            inherited_role_ids: expanded.into_iter().skip(1).collect(),
+            permissions,
+        })
+    }
+
+    /// Parent links of every role (role_id -> parent role ids, in order)
+    async fn load_role_graph(&self) -> Result<HashMap<Uuid, Vec<Uuid>>> {
+        let rows = sqlx::query(
+            "SELECT role_id, parent_role_id FROM role_parents ORDER BY role_id, position",
+        )
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch role hierarchy")?;
+
+        let mut graph: HashMap<Uuid, Vec<Uuid>> = HashMap::new();
+        for row in rows {
+            let role_id = parse_uuid(row.get::<String, _>("role_id"))?;
+            let parent_id = parse_uuid(row.get::<String, _>("parent_role_id"))?;
+            graph.entry(role_id).or_default().push(parent_id);
+        }
+
+        Ok(graph)
+    }
+
+    /// Parent roles of a role from database
+    async fn get_role_parents_db(&self, role_id: &Uuid) -> Result<Vec<Uuid>> {
+        let rows = sqlx::query(
+            "SELECT parent_role_id FROM role_parents WHERE role_id = ? ORDER BY position",
+        )
+        .bind(role_id.to_string())
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch role parents")?;
+
+        rows.into_iter()
+            .map(|row| parse_uuid(row.get::<String, _>("parent_role_id")))
+            .collect()
+    }
+
+    async fn write_role_parents(&self, role_id: &Uuid, parent_ids: &[Uuid]) -> Result<()> {
+        let role_id_str = role_id.to_string();
+        let mut tx = begin_write(&self.pool).await?;
+
+        sqlx::query("DELETE FROM role_parents WHERE role_id = ?")
+            .bind(&role_id_str)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to clear role parents")?;
+
+        for (position, parent_id) in parent_ids.iter().enumerate() {
+            sqlx::query(
+                "INSERT OR IGNORE INTO role_parents (role_id, parent_role_id, position)
+                 VALUES (?, ?, ?)",
+            )
+            .bind(&role_id_str)
+            .bind(parent_id.to_string())
+            .bind(position as i64)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to add role parent")?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn validate_parents(&self, parent_ids: &[Uuid]) -> Result<()> {
+        for parent_id in parent_ids {
+            if self.get_role(parent_id).await?.is_none() {
+                anyhow::bail!("Parent role {} not found", parent_id);
+            }
+        }
+        Ok(())
+    }
+
+    async fn check_role_cycle(&self, role_id: &Uuid, parent_ids: &[Uuid]) -> Result<()> {
+        let graph = self.load_role_graph().await?;
+        if creates_role_cycle(*role_id, parent_ids, |id| {
+            graph.get(id).cloned().unwrap_or_default()
+        }) {
+            anyhow::bail!("Role hierarchy cycle: a role cannot inherit from itself");
+        }
+        Ok(())
+    }
+
     // =========================================================================
     // Permission Operations
     // =========================================================================
@@ -594,6 +750,7 @@ impl DbRbacService {
 
         // Batch load all permissions for all roles in a single query
         let permissions_map = self.batch_get_role_permissions(&role_ids).await?;
+        let graph = self.load_role_graph().await?;
 
         let mut roles = Vec::new();
         for row in rows {
@@ -602,7 +759,9 @@ impl DbRbacService {
                 .get(&role_id_str)
                 .cloned()
                 .unwrap_or_default();
-            roles.push(row_to_role(&row, permissions));
+            let mut role = row_to_role(&row, permissions);
+            role.parent_ids = graph.get(&role.id).cloned().unwrap_or_default();
+            roles.push(role);
         }
 
         Ok(roles)
@@ -625,6 +784,26 @@ impl DbRbacService {
         Ok(role_ids)
     }
 
+    /// Role IDs a user holds through an approved, unexpired elevation
+    pub async fn get_elevated_role_ids(&self, user_id: &Uuid) -> Result<Vec<Uuid>> {
+        let rows = sqlx::query(
+            "SELECT role_id FROM role_elevations
+             WHERE user_id = ? AND status = 'approved' AND expires_at > ?",
+        )
+        .bind(user_id.to_string())
+        .bind(chrono::Utc::now().to_rfc3339())
+        .fetch_all(&self.pool)
+        .await
+        .context("Failed to fetch elevated role IDs")?;
+
+        let mut role_ids = Vec::new();
+        for row in rows {
+            role_ids.push(parse_uuid(row.get::<String, _>("role_id"))?);
+        }
+
+        Ok(role_ids)
+    }
+
     /// Assign roles to a user
     pub async fn assign_roles(&self, user_id: &Uuid, role_ids: &[Uuid]) -> Result<()> {
         let user_id_str = user_id.to_string();
@@ -735,31 +914,25 @@ impl DbRbacService {
 
         // Compute effective permissions
         let roles = self.get_user_roles(user_id).await?;
-        let mut all_permissions = std::collections::HashSet::new();
-        let mut role_names = Vec::new();
+        let mut role_names: Vec<String> = roles.iter().map(|role| role.name.clone()).collect();
+        let mut role_ids: Vec<Uuid> = roles.iter().map(|role| role.id).collect();
 
-        for role in &roles {
-            role_names.push(role.name.clone());
-
-            // Add direct permissions
-            for perm in &role.permissions {
-                all_permissions.insert(perm.clone());
+        // Temporary roles granted through an approved elevation
+        for role_id in self.get_elevated_role_ids(user_id).await? {
+            if role_ids.contains(&role_id) {
+                continue;
             }
-
-            // Add inherited permissions from parent roles
-            let mut current_parent = role.parent_id;
-            while let Some(parent_id) = current_parent {
-                if let Some(parent_role) = self.get_role(&parent_id).await? {
-                    for perm in &parent_role.permissions {
-                        all_permissions.insert(perm.clone());
-                    }
-                    current_parent = parent_role.parent_id;
-                } else {
-                    break;
-                }
+            if let Some(role) = self.get_role(&role_id).await? {
+                role_names.push(role.name);
+                role_ids.push(role_id);
             }
         }
 
+        // Inherited roles affect this user too, so track them for invalidation
+        let expanded = self.expand_roles(&role_ids).await?;
+        self.update_role_tracking(user_id, &expanded);
+        let all_permissions = self.collect_permissions(&expanded).await?;
+
         let effective = EffectivePermissions {
             user_id: *user_id,
             permissions: all_permissions.into_iter().collect(),
@@ -774,6 +947,29 @@ impl DbRbacService {
         Ok(effective)
     }
 
+    /// The given roles followed by all of their ancestors
+    async fn expand_roles(&self, role_ids: &[Uuid]) -> Result<Vec<Uuid>> {
+        let graph = self.load_role_graph().await?;
+        Ok(expand_role_hierarchy(role_ids, |id| {
+            graph.get(id).cloned().unwrap_or_default()
+        }))
+    }
+
+    /// Permissions granted directly by a set of roles
+    async fn collect_permissions(
+        &self,
+        role_ids: &[Uuid],
+    ) -> Result<std::collections::HashSet<Permission>> {
+        let mut all_permissions = std::collections::HashSet::new();
+        for role_id in role_ids {
+            if let Some(role) = self.get_role(role_id).await? {
+                all_permissions.extend(role.permissions);
+            }
+        }
+
+        Ok(all_permissions)
+    }
+
     /// Check if a user has permission for an action on a resource
     pub async fn check_permission(
         &self,
@@ -784,7 +980,8 @@ impl DbRbacService {
         environment: Option<&str>,
     ) -> Result<PermissionCheck> {
         // Check if user has SuperAdmin role - SuperAdmin always has all permissions
-        let role_ids = self.get_user_role_ids(user_id).await?;
+        let mut role_ids = self.get_user_role_ids(user_id).await?;
+        role_ids.extend(self.get_elevated_role_ids(user_id).await?);
         if role_ids.contains(&SystemRole::SuperAdmin.uuid()) {
             return Ok(PermissionCheck {
                 allowed: true,
@@ -853,6 +1050,28 @@ impl DbRbacService {
         })
     }
 
+    /// Whether a set of roles grants an action on a resource in any scope
+    ///
+    /// Used to confirm that API key scopes stay within what the key's roles
+    /// allow, without a concrete resource to check against.
+    pub async fn roles_grant_action(
+        &self,
+        role_ids: &[Uuid],
+        resource: Resource,
+        action: Action,
+    ) -> Result<bool> {
+        if role_ids.contains(&SystemRole::SuperAdmin.uuid()) {
+            return Ok(true);
+        }
+
+        let expanded = self.expand_roles(role_ids).await?;
+        let all_permissions = self.collect_permissions(&expanded).await?;
+
+        Ok(all_permissions.iter().any(|perm| {
+            perm.resource == resource && (perm.action == action || perm.action == Action::Admin)
+        }))
+    }
+
     /// Check permission using role IDs directly (for middleware)
     pub async fn check_permission_by_roles(
         &self,
@@ -873,29 +1092,8 @@ impl DbRbacService {
             });
         }
 
-        let mut all_permissions = std::collections::HashSet::new();
-
-        for role_id in role_ids {
-            if let Some(role) = self.get_role(role_id).await? {
-                // Add direct permissions
-                for perm in &role.permissions {
-                    all_permissions.insert(perm.clone());
-                }
-
-                // Add inherited permissions
-                let mut current_parent = role.parent_id;
-                while let Some(parent_id) = current_parent {
-                    if let Some(parent_role) = self.get_role(&parent_id).await? {
-                        for perm in &parent_role.permissions {
-                            all_permissions.insert(perm.clone());
-                        }
-                        current_parent = parent_role.parent_id;
-                    } else {
-                        break;
-                    }
-                }
-            }
-        }
+        let expanded = self.expand_roles(role_ids).await?;
+        let all_permissions = self.collect_permissions(&expanded).await?;
 
         for perm in &all_permissions {
             if perm.resource != resource {
@@ -949,12 +1147,44 @@ impl DbRbacService {
             reason: Some("No matching permission found".to_string()),
         })
     }
+
+    /// Whether `holder` has every permission of `user`
+    ///
+    /// Roles are compared through the permissions they grant, so a custom
+    /// role is covered by a system role that grants the same or more.
+    /// Elevated roles count for both users.
+    pub async fn covers_permissions_of(&self, holder: &Uuid, user: &Uuid) -> Result<bool> {
+        let mut holder_roles = self.get_user_role_ids(holder).await?;
+        holder_roles.extend(self.get_elevated_role_ids(holder).await?);
+        if holder_roles.contains(&SystemRole::SuperAdmin.uuid()) {
+            return Ok(true);
+        }
+        let mut user_roles = self.get_user_role_ids(user).await?;
+        user_roles.extend(self.get_elevated_role_ids(user).await?);
+        if user_roles.contains(&SystemRole::SuperAdmin.uuid()) {
+            return Ok(false);
+        }
+
+        let granted = self.get_effective_permissions(holder).await?.permissions;
+        let required = self.get_effective_permissions(user).await?.permissions;
+        Ok(required
+            .iter()
+            .all(|needed| granted.iter().any(|perm| permission_covers(perm, needed))))
+    }
 }
 
 // =============================================================================
 // Helper Functions
 // =============================================================================
 
+/// Whether `granted` allows everything `required` allows
+fn permission_covers(granted: &Permission, required: &Permission) -> bool {
+    granted.resource == required.resource
+        && (granted.action == Action::Admin || granted.action == required.action)
+        && (granted.scope == Scope::All
+            || (granted.scope == required.scope && granted.constraint == required.constraint))
+}
+
 fn parse_uuid(s: String) -> Result<Uuid> {
     Uuid::parse_str(&s).map_err(|_| anyhow::anyhow!("Invalid UUID: {}", s))
 }
@@ -993,6 +1223,7 @@ fn row_to_role(row: &sqlx::sqlite::SqliteRow, permissions: Vec<Permission>) -> R
         description: row.get("description"),
         is_system: parse_db_bool(row, "is_system"),
         parent_id: parent_id_str.and_then(|s| Uuid::parse_str(&s).ok()),
+        parent_ids: vec![],
         permissions,
         created_at: parse_db_timestamp(&created_at_str),
         updated_at: parse_db_timestamp(&updated_at_str),
@@ -1017,7 +1248,7 @@ fn row_to_permission(row: &sqlx::sqlite::SqliteRow) -> Result<Permission> {
     })
 }
 
-fn parse_resource(s: &str) -> Result<Resource> {
+pub(crate) fn parse_resource(s: &str) -> Result<Resource> {
     match s {
         "nodes" => Ok(Resource::Nodes),
         "groups" => Ok(Resource::Groups),
@@ -1034,7 +1265,7 @@ fn parse_resource(s: &str) -> Result<Resource> {
     }
 }
 
-fn parse_action(s: &str) -> Result<Action> {
+pub(crate) fn parse_action(s: &str) -> Result<Action> {
     match s {
         "read" => Ok(Action::Read),
         "create" => Ok(Action::Create),
@@ -1047,6 +1278,9 @@ fn parse_action(s: &str) -> Result<Action> {
         "sign" => Ok(Action::Sign),
         "reject" => Ok(Action::Reject),
         "revoke" => Ok(Action::Revoke),
+        "bulk_sign" => Ok(Action::BulkSign),
+        "bulk_revoke" => Ok(Action::BulkRevoke),
+        "impersonate" => Ok(Action::Impersonate),
         _ => anyhow::bail!("Unknown action: {}", s),
     }
 }
@@ -1062,7 +1296,7 @@ fn scope_to_db(scope: &Scope) -> (String, Option<String>) {
     }
 }
 
-fn db_to_scope(scope_type: &str, scope_value: Option<String>) -> Result<Scope> {
+pub(crate) fn db_to_scope(scope_type: &str, scope_value: Option<String>) -> Result<Scope> {
     match scope_type {
         "all" => Ok(Scope::All),
         "owned" => Ok(Scope::Owned),
@@ -1127,6 +1361,31 @@ fn db_to_constraint(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_permission_covers() {
+        let perm = |resource, action, scope| Permission {
+            resource,
+            action,
+            scope,
+            ..Permission::default()
+        };
+        let env = || Scope::Environment("production".to_string());
+
+        let admin = perm(Resource::Groups, Action::Admin, Scope::All);
+        let read_prod = perm(Resource::Groups, Action::Read, env());
+        assert!(permission_covers(&admin, &read_prod));
+        assert!(permission_covers(&read_prod, &read_prod));
+        assert!(!permission_covers(&read_prod, &admin));
+        assert!(!permission_covers(
+            &read_prod,
+            &perm(Resource::Groups, Action::Read, Scope::All)
+        ));
+        assert!(!permission_covers(
+            &perm(Resource::Nodes, Action::Admin, Scope::All),
+            &read_prod
+        ));
+    }
+
     #[test]
     fn test_scope_conversion() {
         let (scope_type, scope_value) = scope_to_db(&Scope::All);
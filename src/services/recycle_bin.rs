@@ -0,0 +1,106 @@
+//! Recycle bin purging
+//!
+//! Deleted node groups, users and saved reports stay restorable for
+//! `recycle_bin.retention_days`. A background task removes older ones for
+//! good once an hour.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use tokio::sync::RwLock;
+use tokio::time::interval;
+use tracing::{error, info};
+
+use crate::config::RecycleBinConfig;
+use crate::db::{AuditRepository, DbPool, RecycleBinRepository};
+use crate::models::default_organization_uuid;
+use crate::services::audit_forwarding::AuditForwarder;
+
+/// How often expired records are purged
+const PURGE_INTERVAL_SECS: u64 = 3600;
+
+/// Handle for stopping the purge task
+#[derive(Clone)]
+pub struct RecycleBinPurgeState {
+    running: Arc<RwLock<bool>>,
+    pool: DbPool,
+    retention_days: u32,
+    forwarder: Option<Arc<AuditForwarder>>,
+}
+
+impl RecycleBinPurgeState {
+    pub async fn is_running(&self) -> bool {
+        *self.running.read().await
+    }
+
+    /// Request the purge loop to stop at its next tick
+    pub async fn stop(&self) {
+        *self.running.write().await = false;
+        info!("Recycle bin purge stop requested");
+    }
+}
+
+/// Spawn the background task purging expired records from the recycle bin
+pub fn start_recycle_bin_purge(
+    pool: DbPool,
+    config: &RecycleBinConfig,
+    forwarder: Option<Arc<AuditForwarder>>,
+) -> RecycleBinPurgeState {
+    let state = RecycleBinPurgeState {
+        running: Arc::new(RwLock::new(true)),
+        pool,
+        retention_days: config.retention_days,
+        forwarder,
+    };
+
+    let loop_state = state.clone();
+    tokio::spawn(async move {
+        purge_loop(loop_state).await;
+    });
+
+    info!(
+        "Recycle bin purge started (keeping deleted records {} days)",
+        config.retention_days
+    );
+    state
+}
+
+async fn purge_loop(state: RecycleBinPurgeState) {
+    let mut timer = interval(Duration::from_secs(PURGE_INTERVAL_SECS));
+
+    loop {
+        timer.tick().await;
+
+        if !*state.running.read().await {
+            info!("Recycle bin purge stopping");
+            break;
+        }
+
+        let cutoff = Utc::now() - chrono::Duration::days(state.retention_days as i64);
+        match RecycleBinRepository::new(&state.pool)
+            .purge_expired(cutoff)
+            .await
+        {
+            Ok(purged) if purged > 0 => {
+                info!("Purged {} expired records from the recycle bin", purged);
+                let _ = AuditRepository::new(&state.pool, state.forwarder.as_deref())
+                    .insert(
+                        default_organization_uuid(),
+                        None,
+                        "recycle_bin.purge",
+                        "recycle_bin",
+                        None,
+                        Some(&serde_json::json!({
+                            "purged": purged,
+                            "cutoff": cutoff,
+                        })),
+                        None,
+                    )
+                    .await;
+            }
+            Ok(_) => {}
+            Err(e) => error!("Recycle bin purge failed: {:#}", e),
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! Comparison of two Puppet runs of a node
+//!
+//! A resource counts as failing in a report when any of its events failed.
+//! Timing regressions come from the `time` metrics of both reports.
+
+use std::collections::BTreeMap;
+
+use crate::models::{
+    EventStatus, ReportMetric, ResourceDiffEntry, ResourceEvent, TimingRegression,
+};
+
+/// Default minimum increase, in seconds, for a timing regression
+pub const DEFAULT_MIN_INCREASE_SECONDS: f64 = 1.0;
+
+/// Default minimum increase, in percent of the earlier time, for a timing
+/// regression
+pub const DEFAULT_MIN_INCREASE_PERCENT: f64 = 20.0;
+
+/// Resources of both reports by failure state
+#[derive(Debug, Default, PartialEq)]
+pub struct ResourceChanges {
+    pub newly_failing: Vec<ResourceDiffEntry>,
+    pub newly_fixed: Vec<ResourceDiffEntry>,
+    pub still_failing: Vec<ResourceDiffEntry>,
+}
+
+/// Failed resources of a report, keyed (and so sorted) by type and title
+fn failed_resources(events: &[ResourceEvent]) -> BTreeMap<(String, String), ResourceDiffEntry> {
+    let mut failed = BTreeMap::new();
+    for event in events.iter().filter(|e| e.status == EventStatus::Failure) {
+        failed
+            .entry((event.resource_type.clone(), event.resource_title.clone()))
+            .or_insert_with(|| ResourceDiffEntry {
+                resource_type: event.resource_type.clone(),
+                resource_title: event.resource_title.clone(),
+                message: event.message.clone(),
+                file: event.file.clone(),
+                line: event.line,
+            });
+    }
+    failed
+}
+
+/// Compare the failed resources of two reports
+pub fn diff_resources(from: &[ResourceEvent], to: &[ResourceEvent]) -> ResourceChanges {
+    let mut from_failed = failed_resources(from);
+    let mut changes = ResourceChanges::default();
+
+    for (key, entry) in failed_resources(to) {
+        if from_failed.remove(&key).is_some() {
+            changes.still_failing.push(entry);
+        } else {
+            changes.newly_failing.push(entry);
+        }
+    }
+    changes.newly_fixed = from_failed.into_values().collect();
+
+    changes
+}
+
+/// Time metrics that grew by at least `min_increase_seconds` and
+/// `min_increase_percent`, largest increase first
+///
+/// Metrics missing from either report are skipped.
+pub fn timing_regressions(
+    from: &[ReportMetric],
+    to: &[ReportMetric],
+    min_increase_seconds: f64,
+    min_increase_percent: f64,
+) -> Vec<TimingRegression> {
+    let from_times: BTreeMap<&str, f64> = from
+        .iter()
+        .filter(|m| m.category == "time")
+        .map(|m| (m.name.as_str(), m.value))
+        .collect();
+
+    let mut regressions: Vec<TimingRegression> = to
+        .iter()
+        .filter(|m| m.category == "time")
+        .filter_map(|m| {
+            let from_seconds = *from_times.get(m.name.as_str())?;
+            let increase_seconds = m.value - from_seconds;
+            let increase_percent =
+                (from_seconds > 0.0).then(|| increase_seconds / from_seconds * 100.0);
+            let regressed = increase_seconds > 0.0
+                && increase_seconds >= min_increase_seconds
+                && increase_percent.is_none_or(|p| p >= min_increase_percent);
+            regressed.then(|| TimingRegression {
+                name: m.name.clone(),
+                from_seconds,
+                to_seconds: m.value,
+                increase_seconds,
+                increase_percent,
+            })
+        })
+        .collect();
+
+    regressions.sort_by(|a, b| b.increase_seconds.total_cmp(&a.increase_seconds));
+    regressions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(resource_type: &str, title: &str, status: &str) -> ResourceEvent {
+        serde_json::from_value(serde_json::json!({
+            "resource_type": resource_type,
+            "resource_title": title,
+            "status": status,
+            "message": format!("{} {}", title, status),
+        }))
+        .unwrap()
+    }
+
+    fn time(name: &str, value: f64) -> ReportMetric {
+        ReportMetric {
+            category: "time".to_string(),
+            name: name.to_string(),
+            value,
+        }
+    }
+
+    fn titles(entries: &[ResourceDiffEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.resource_title.as_str()).collect()
+    }
+
+    #[test]
+    fn test_diff_resources() {
+        let from = vec![
+            event("File", "/etc/motd", "failure"),
+            event("Service", "sshd", "failure"),
+            event("Package", "vim", "success"),
+        ];
+        let to = vec![
+            event("Service", "sshd", "failure"),
+            event("Service", "sshd", "failure"),
+            event("Package", "vim", "failure"),
+            event("File", "/etc/motd", "success"),
+        ];
+
+        let changes = diff_resources(&from, &to);
+        assert_eq!(titles(&changes.newly_failing), vec!["vim"]);
+        assert_eq!(titles(&changes.newly_fixed), vec!["/etc/motd"]);
+        assert_eq!(titles(&changes.still_failing), vec!["sshd"]);
+        // Newly fixed resources keep the message of the earlier failure
+        assert_eq!(
+            changes.newly_fixed[0].message.as_deref(),
+            Some("/etc/motd failure")
+        );
+    }
+
+    #[test]
+    fn test_diff_resources_identical() {
+        let events = vec![event("File", "/etc/motd", "success")];
+        assert_eq!(diff_resources(&events, &events), ResourceChanges::default());
+    }
+
+    #[test]
+    fn test_timing_regressions() {
+        let from = vec![
+            time("total", 10.0),
+            time("file", 2.0),
+            time("package", 0.5),
+            time("exec", 0.0),
+            time("service", 3.0),
+        ];
+        let to = vec![
+            time("total", 20.0),
+            // Large relative, small absolute increase
+            time("file", 2.5),
+            // Above both thresholds
+            time("package", 4.0),
+            // No earlier time to compare against
+            time("exec", 1.5),
+            time("service", 1.0),
+            time("user", 9.0),
+            ReportMetric {
+                category: "resources".to_string(),
+                name: "total".to_string(),
+                value: 100.0,
+            },
+        ];
+
+        let regressions = timing_regressions(
+            &from,
+            &to,
+            DEFAULT_MIN_INCREASE_SECONDS,
+            DEFAULT_MIN_INCREASE_PERCENT,
+        );
+        let names: Vec<_> = regressions.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(names, vec!["total", "package", "exec"]);
+        assert_eq!(regressions[0].increase_percent, Some(100.0));
+        assert_eq!(regressions[2].increase_percent, None);
+    }
+
+    #[test]
+    fn test_timing_regressions_percent_threshold() {
+        let from = vec![time("total", 100.0)];
+        let to = vec![time("total", 110.0)];
+        assert!(timing_regressions(&from, &to, 1.0, 20.0).is_empty());
+        assert_eq!(timing_regressions(&from, &to, 1.0, 5.0).len(), 1);
+    }
+}
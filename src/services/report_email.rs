@@ -0,0 +1,498 @@
+//! Email of scheduled report results
+//!
+//! Summarizes a report result into its key metrics and top failures and
+//! renders them with the organization's Tera templates, falling back to the
+//! built-in ones. The scheduler attaches the full export to the email.
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use crate::models::{
+    ReportEmailTemplate, ReportExecution, ReportResult, SavedReport, SeverityLevel,
+    UpsertReportEmailTemplateRequest,
+};
+
+pub const DEFAULT_SUBJECT_TEMPLATE: &str =
+    include_str!("../../templates/report_email/subject.tera");
+pub const DEFAULT_HTML_TEMPLATE: &str = include_str!("../../templates/report_email/body.html.tera");
+pub const DEFAULT_TEXT_TEMPLATE: &str = include_str!("../../templates/report_email/body.txt.tera");
+
+/// Failures listed in the email; the rest are only counted
+const MAX_FAILURES: usize = 10;
+
+/// Key figure of a report result
+#[derive(Debug, Clone, Serialize)]
+pub struct SummaryMetric {
+    pub label: String,
+    pub value: String,
+}
+
+/// Node that failed, violated a rule or drifted
+#[derive(Debug, Clone, Serialize)]
+pub struct SummaryFailure {
+    pub certname: String,
+    pub detail: String,
+}
+
+/// What the email shows of a report result
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportSummary {
+    pub metrics: Vec<SummaryMetric>,
+    /// Heading of the failure list, depending on the report type
+    pub failures_title: String,
+    /// Worst failures first, at most [`MAX_FAILURES`]
+    pub failures: Vec<SummaryFailure>,
+    /// Failures left out of the list
+    pub omitted_failures: usize,
+}
+
+/// Rendered email of a report execution
+#[derive(Debug, Clone)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
+
+/// Export attached to the email
+pub struct Attachment<'a> {
+    pub filename: &'a str,
+    pub size: usize,
+}
+
+/// Summarize a report result
+pub fn summarize(result: &ReportResult) -> ReportSummary {
+    let (metrics, failures_title, mut failures) = match result {
+        ReportResult::NodeHealth(report) => {
+            let s = &report.summary;
+            let mut failed: Vec<_> = report
+                .nodes
+                .iter()
+                .flatten()
+                .filter(|n| n.status == "failed" && !n.in_maintenance)
+                .collect();
+            failed.sort_by_key(|n| std::cmp::Reverse(n.failed_resources.unwrap_or(0)));
+            let failures = failed
+                .into_iter()
+                .map(|n| SummaryFailure {
+                    certname: n.certname.clone(),
+                    detail: match (&n.environment, n.failed_resources) {
+                        (Some(env), Some(count)) => {
+                            format!("{} failed resources in {}", count, env)
+                        }
+                        (None, Some(count)) => format!("{} failed resources", count),
+                        (Some(env), None) => format!("failed in {}", env),
+                        (None, None) => "failed".to_string(),
+                    },
+                })
+                .collect();
+            (
+                vec![
+                    metric("Total nodes", s.total_nodes),
+                    metric("Failed", s.failed_count),
+                    metric("Changed", s.changed_count),
+                    metric("Unchanged", s.unchanged_count),
+                    metric("Noop", s.noop_count),
+                    metric("Unreported", s.unreported_count),
+                    metric("In maintenance", s.maintenance_count),
+                    metric("Compliance rate", percent(s.compliance_rate)),
+                ],
+                "Failed nodes",
+                failures,
+            )
+        }
+        ReportResult::Compliance(report) => {
+            let s = &report.summary;
+            let mut violations: Vec<_> = report.violations.iter().collect();
+            violations.sort_by_key(|v| std::cmp::Reverse(severity_rank(v.severity)));
+            let failures = violations
+                .into_iter()
+                .map(|v| SummaryFailure {
+                    certname: v.certname.clone(),
+                    detail: format!(
+                        "{}: {} ({} is {}, expected {})",
+                        v.severity.as_str(),
+                        v.rule_name,
+                        v.fact_name,
+                        v.actual_value,
+                        v.expected_value
+                    ),
+                })
+                .collect();
+            (
+                vec![
+                    metric("Total nodes", s.total_nodes),
+                    metric("Compliant", s.compliant_nodes),
+                    metric("Non-compliant", s.non_compliant_nodes),
+                    metric("Compliance rate", percent(s.compliance_rate)),
+                    metric("Violations", s.total_violations),
+                ],
+                "Top violations",
+                failures,
+            )
+        }
+        ReportResult::ChangeTracking(report) => {
+            let s = &report.summary;
+            let failures = report
+                .changes
+                .iter()
+                .filter(|c| c.status == "failure")
+                .map(|c| SummaryFailure {
+                    certname: c.certname.clone(),
+                    detail: format!("{}[{}] failed", c.resource_type, c.resource_title),
+                })
+                .collect();
+            (
+                vec![
+                    metric("Total changes", s.total_changes),
+                    metric("Nodes affected", s.nodes_affected),
+                    metric("Resources changed", s.resources_changed),
+                    metric("Resources failed", s.resources_failed),
+                    metric("Corrective changes", s.corrective_changes),
+                    metric("Intentional changes", s.intentional_changes),
+                ],
+                "Failed resources",
+                failures,
+            )
+        }
+        ReportResult::DriftDetection(report) => {
+            let s = &report.summary;
+            let mut drifted: Vec<_> = report.drifted_nodes.iter().collect();
+            drifted.sort_by_key(|n| std::cmp::Reverse(n.drift_count));
+            let failures = drifted
+                .into_iter()
+                .map(|n| SummaryFailure {
+                    certname: n.certname.clone(),
+                    detail: format!("{} drifted facts", n.drift_count),
+                })
+                .collect();
+            (
+                vec![
+                    metric("Baseline", &report.baseline_name),
+                    metric("Total nodes", s.total_nodes),
+                    metric("Nodes with drift", s.nodes_with_drift),
+                    metric("Nodes without drift", s.nodes_without_drift),
+                    metric("Drift rate", percent(s.drift_rate)),
+                    metric("Drifted facts", s.total_drifted_facts),
+                ],
+                "Most drifted nodes",
+                failures,
+            )
+        }
+        ReportResult::Custom(_) => (Vec::new(), "Failures", Vec::new()),
+    };
+
+    let omitted_failures = failures.len().saturating_sub(MAX_FAILURES);
+    failures.truncate(MAX_FAILURES);
+
+    ReportSummary {
+        metrics,
+        failures_title: failures_title.to_string(),
+        failures,
+        omitted_failures,
+    }
+}
+
+/// Build the template context of a report execution
+///
+/// Besides the summary, templates get the report, the execution with
+/// pre-formatted times, the attachment and the full `result`.
+pub fn email_context(
+    report: &SavedReport,
+    execution: &ReportExecution,
+    result: &ReportResult,
+    attachment: &Attachment<'_>,
+) -> tera::Context {
+    let mut context = tera::Context::new();
+    context.insert(
+        "report",
+        &serde_json::json!({
+            "name": report.name,
+            "description": report.description,
+            "report_type": report.report_type.as_str(),
+        }),
+    );
+    context.insert(
+        "execution",
+        &serde_json::json!({
+            "id": execution.id,
+            "started_at": execution.started_at.format("%Y-%m-%d %H:%M UTC").to_string(),
+            "completed_at": execution
+                .completed_at
+                .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string()),
+            "execution_time_ms": execution.execution_time_ms,
+            "row_count": execution.row_count,
+        }),
+    );
+    context.insert(
+        "attachment",
+        &serde_json::json!({
+            "filename": attachment.filename,
+            "size": attachment.size,
+        }),
+    );
+    context.insert("summary", &summarize(result));
+    // Custom results that are not objects cannot carry the type tag
+    context.insert(
+        "result",
+        &serde_json::to_value(result).unwrap_or(serde_json::Value::Null),
+    );
+    context
+}
+
+/// Render an email with a stored template, or the built-in one
+pub fn render(
+    template: Option<&ReportEmailTemplate>,
+    context: &tera::Context,
+) -> Result<RenderedEmail> {
+    let (subject, html, text) = match template {
+        Some(t) => (
+            t.subject_template.as_str(),
+            t.html_template.as_str(),
+            t.text_template.as_str(),
+        ),
+        None => (
+            DEFAULT_SUBJECT_TEMPLATE,
+            DEFAULT_HTML_TEMPLATE,
+            DEFAULT_TEXT_TEMPLATE,
+        ),
+    };
+
+    let subject = tera::Tera::one_off(subject, context, false)
+        .context("Failed to render the email subject")?;
+    let html =
+        tera::Tera::one_off(html, context, true).context("Failed to render the HTML body")?;
+    let text =
+        tera::Tera::one_off(text, context, false).context("Failed to render the text body")?;
+
+    Ok(RenderedEmail {
+        // Headers are single line
+        subject: subject.split_whitespace().collect::<Vec<_>>().join(" "),
+        html,
+        text,
+    })
+}
+
+/// Check that the templates of a request parse
+pub fn validate_templates(req: &UpsertReportEmailTemplateRequest) -> Result<()> {
+    let mut tera = tera::Tera::default();
+    for (name, source) in [
+        ("subject", &req.subject_template),
+        ("HTML", &req.html_template),
+        ("text", &req.text_template),
+    ] {
+        if source.trim().is_empty() {
+            anyhow::bail!("The {} template is empty", name);
+        }
+        tera.add_raw_template(name, source)
+            .with_context(|| format!("Invalid {} template", name))?;
+    }
+    Ok(())
+}
+
+fn metric(label: &str, value: impl ToString) -> SummaryMetric {
+    SummaryMetric {
+        label: label.to_string(),
+        value: value.to_string(),
+    }
+}
+
+fn percent(rate: f64) -> String {
+    format!("{:.1}%", rate)
+}
+
+fn severity_rank(severity: SeverityLevel) -> u8 {
+    match severity {
+        SeverityLevel::Low => 0,
+        SeverityLevel::Medium => 1,
+        SeverityLevel::High => 2,
+        SeverityLevel::Critical => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        ComplianceReport, ComplianceSummary, ComplianceViolation, ExecutionStatus,
+        NodeHealthDetail, NodeHealthReport, NodeHealthSummary, OutputFormat, ReportQueryConfig,
+        ReportType,
+    };
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn node(certname: &str, status: &str, failed: i64) -> NodeHealthDetail {
+        NodeHealthDetail {
+            certname: certname.to_string(),
+            environment: Some("production".to_string()),
+            status: status.to_string(),
+            last_report_at: None,
+            failed_resources: Some(failed),
+            changed_resources: None,
+            in_maintenance: false,
+        }
+    }
+
+    fn node_health(nodes: Vec<NodeHealthDetail>) -> ReportResult {
+        ReportResult::NodeHealth(NodeHealthReport {
+            generated_at: Utc::now(),
+            time_range: "24h".to_string(),
+            summary: NodeHealthSummary {
+                total_nodes: nodes.len() as i64,
+                changed_count: 0,
+                unchanged_count: 0,
+                failed_count: nodes.iter().filter(|n| n.status == "failed").count() as i64,
+                noop_count: 0,
+                unreported_count: 0,
+                maintenance_count: 0,
+                compliance_rate: 50.0,
+            },
+            by_environment: None,
+            by_group: None,
+            nodes: Some(nodes),
+        })
+    }
+
+    #[test]
+    fn test_summarize_node_health() {
+        let mut nodes: Vec<_> = (0..12)
+            .map(|i| node(&format!("web{:02}", i), "failed", i))
+            .collect();
+        nodes.push(node("db01", "unchanged", 0));
+
+        let summary = summarize(&node_health(nodes));
+        assert_eq!(summary.failures.len(), MAX_FAILURES);
+        assert_eq!(summary.omitted_failures, 2);
+        // Most failed resources first
+        assert_eq!(summary.failures[0].certname, "web11");
+        assert!(summary
+            .metrics
+            .iter()
+            .any(|m| m.label == "Compliance rate" && m.value == "50.0%"));
+    }
+
+    #[test]
+    fn test_summarize_compliance_orders_by_severity() {
+        let violation = |certname: &str, severity| ComplianceViolation {
+            certname: certname.to_string(),
+            baseline_id: None,
+            baseline_name: None,
+            rule_id: "r1".to_string(),
+            rule_name: "SSH root login".to_string(),
+            fact_name: "ssh.permit_root_login".to_string(),
+            expected_value: serde_json::json!("no"),
+            actual_value: serde_json::json!("yes"),
+            severity,
+            remediation: None,
+        };
+        let result = ReportResult::Compliance(ComplianceReport {
+            generated_at: Utc::now(),
+            baseline_name: "CIS".to_string(),
+            summary: ComplianceSummary {
+                total_nodes: 2,
+                compliant_nodes: 0,
+                non_compliant_nodes: 2,
+                compliance_rate: 0.0,
+                total_violations: 2,
+            },
+            by_severity: Vec::new(),
+            baselines: Vec::new(),
+            violations: vec![
+                violation("low01", SeverityLevel::Low),
+                violation("crit01", SeverityLevel::Critical),
+            ],
+        });
+
+        let summary = summarize(&result);
+        assert_eq!(summary.failures[0].certname, "crit01");
+        assert!(summary.failures[0]
+            .detail
+            .starts_with("critical: SSH root login"));
+    }
+
+    #[test]
+    fn test_render_default_and_custom_templates() {
+        let report = SavedReport {
+            id: Uuid::new_v4(),
+            organization_id: Uuid::new_v4(),
+            name: "Nightly <health>".to_string(),
+            description: None,
+            report_type: ReportType::NodeHealth,
+            query_config: ReportQueryConfig::default(),
+            created_by: Uuid::new_v4(),
+            is_public: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let execution = ReportExecution {
+            id: Uuid::new_v4(),
+            organization_id: report.organization_id,
+            report_id: report.id,
+            schedule_id: None,
+            executed_by: None,
+            status: ExecutionStatus::Completed,
+            started_at: Utc::now(),
+            completed_at: None,
+            row_count: Some(2),
+            output_format: OutputFormat::Csv,
+            output_data: None,
+            output_file_path: None,
+            error_message: None,
+            execution_time_ms: None,
+            delivery_status: None,
+            delivery_results: Vec::new(),
+        };
+        let result = node_health(vec![node("web01", "failed", 3)]);
+        let context = email_context(
+            &report,
+            &execution,
+            &result,
+            &Attachment {
+                filename: "nightly.csv",
+                size: 10,
+            },
+        );
+
+        let email = render(None, &context).unwrap();
+        assert!(email.subject.starts_with("[OpenVox] Nightly <health>"));
+        assert!(!email.subject.contains('\n'));
+        // Only the HTML body is escaped
+        assert!(email.html.contains("Nightly &lt;health&gt;"));
+        assert!(email.text.contains("Nightly <health>"));
+        assert!(email
+            .text
+            .contains("web01: 3 failed resources in production"));
+        assert!(email.html.contains("nightly.csv"));
+
+        let custom = ReportEmailTemplate {
+            id: Uuid::new_v4(),
+            organization_id: report.organization_id,
+            report_type: None,
+            subject_template: "{{ summary.failures | length }} failed".to_string(),
+            html_template: "<p>{{ result.summary.total_nodes }}</p>".to_string(),
+            text_template: "{{ attachment.size }}".to_string(),
+            updated_by: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let email = render(Some(&custom), &context).unwrap();
+        assert_eq!(email.subject, "1 failed");
+        assert_eq!(email.html, "<p>1</p>");
+        assert_eq!(email.text, "10");
+    }
+
+    #[test]
+    fn test_validate_templates() {
+        let mut req = UpsertReportEmailTemplateRequest {
+            report_type: None,
+            subject_template: DEFAULT_SUBJECT_TEMPLATE.to_string(),
+            html_template: DEFAULT_HTML_TEMPLATE.to_string(),
+            text_template: DEFAULT_TEXT_TEMPLATE.to_string(),
+        };
+        assert!(validate_templates(&req).is_ok());
+
+        req.html_template = "{% if summary.failures %}unclosed".to_string();
+        let err = validate_templates(&req).unwrap_err();
+        assert!(err.to_string().contains("HTML"));
+    }
+}
@@ -0,0 +1,633 @@
+//! Spreadsheet and HTML report exports
+//!
+//! Both formats render the same tabular view of a report: a summary of
+//! key/value metrics followed by its detail tables. XLSX puts the summary and
+//! each table on its own sheet; HTML renders them as a standalone page with
+//! inline styles, so it can be mailed or opened offline.
+
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use rust_xlsxwriter::{Color, Format, Workbook};
+
+use crate::models::{ReportResult, SeverityLevel};
+
+/// A table cell
+#[derive(Debug, Clone, PartialEq)]
+enum Cell {
+    Text(String),
+    Number(f64),
+    Bool(bool),
+    Empty,
+}
+
+impl Cell {
+    fn text(value: impl Into<String>) -> Self {
+        Cell::Text(value.into())
+    }
+
+    fn number(value: impl Into<f64>) -> Self {
+        Cell::Number(value.into())
+    }
+
+    fn count(value: i64) -> Self {
+        Cell::Number(value as f64)
+    }
+
+    fn optional(value: Option<impl Into<String>>) -> Self {
+        value.map(Cell::text).unwrap_or(Cell::Empty)
+    }
+
+    fn time(value: Option<DateTime<Utc>>) -> Self {
+        Cell::optional(value.map(|t| t.to_rfc3339()))
+    }
+
+    /// JSON values as shown in a cell: strings without quotes, the rest as JSON
+    fn json(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => Cell::Empty,
+            serde_json::Value::String(s) => Cell::text(s.as_str()),
+            other => Cell::text(other.to_string()),
+        }
+    }
+
+    fn severity(value: SeverityLevel) -> Self {
+        Cell::text(value.as_str())
+    }
+
+    fn display(&self) -> String {
+        match self {
+            Cell::Text(s) => s.clone(),
+            Cell::Number(n) => {
+                if n.fract() == 0.0 {
+                    format!("{}", *n as i64)
+                } else {
+                    format!("{:.2}", n)
+                }
+            }
+            Cell::Bool(true) => "Yes".to_string(),
+            Cell::Bool(false) => "No".to_string(),
+            Cell::Empty => String::new(),
+        }
+    }
+}
+
+/// A detail table of a report
+#[derive(Debug)]
+struct Table {
+    name: &'static str,
+    headers: &'static [&'static str],
+    rows: Vec<Vec<Cell>>,
+}
+
+/// Tabular view of a report
+#[derive(Debug)]
+struct ExportDocument {
+    title: &'static str,
+    generated_at: DateTime<Utc>,
+    /// Report parameters, e.g. the time range or baseline
+    details: Vec<(&'static str, String)>,
+    summary: Vec<(&'static str, Cell)>,
+    tables: Vec<Table>,
+    /// Raw JSON of custom reports
+    raw: Option<String>,
+}
+
+fn document(result: &ReportResult) -> ExportDocument {
+    match result {
+        ReportResult::NodeHealth(report) => {
+            let s = &report.summary;
+            let mut tables = Vec::new();
+            if let Some(by_env) = &report.by_environment {
+                tables.push(Table {
+                    name: "By Environment",
+                    headers: &["Environment", "Total", "Changed", "Unchanged", "Failed"],
+                    rows: by_env
+                        .iter()
+                        .map(|e| {
+                            vec![
+                                Cell::text(e.environment.as_str()),
+                                Cell::count(e.total_nodes),
+                                Cell::count(e.changed_count),
+                                Cell::count(e.unchanged_count),
+                                Cell::count(e.failed_count),
+                            ]
+                        })
+                        .collect(),
+                });
+            }
+            if let Some(by_group) = &report.by_group {
+                tables.push(Table {
+                    name: "By Group",
+                    headers: &["Group", "Total", "Changed", "Unchanged", "Failed"],
+                    rows: by_group
+                        .iter()
+                        .map(|g| {
+                            vec![
+                                Cell::text(g.group_name.as_str()),
+                                Cell::count(g.total_nodes),
+                                Cell::count(g.changed_count),
+                                Cell::count(g.unchanged_count),
+                                Cell::count(g.failed_count),
+                            ]
+                        })
+                        .collect(),
+                });
+            }
+            if let Some(nodes) = &report.nodes {
+                tables.push(Table {
+                    name: "Nodes",
+                    headers: &[
+                        "Certname",
+                        "Environment",
+                        "Status",
+                        "Last Report",
+                        "Failed Resources",
+                        "Changed Resources",
+                        "In Maintenance",
+                    ],
+                    rows: nodes
+                        .iter()
+                        .map(|n| {
+                            vec![
+                                Cell::text(n.certname.as_str()),
+                                Cell::optional(n.environment.as_deref()),
+                                Cell::text(n.status.as_str()),
+                                Cell::time(n.last_report_at),
+                                n.failed_resources.map(Cell::count).unwrap_or(Cell::Empty),
+                                n.changed_resources.map(Cell::count).unwrap_or(Cell::Empty),
+                                Cell::Bool(n.in_maintenance),
+                            ]
+                        })
+                        .collect(),
+                });
+            }
+
+            ExportDocument {
+                title: "Node Health Report",
+                generated_at: report.generated_at,
+                details: vec![("Time Range", report.time_range.clone())],
+                summary: vec![
+                    ("Total Nodes", Cell::count(s.total_nodes)),
+                    ("Changed", Cell::count(s.changed_count)),
+                    ("Unchanged", Cell::count(s.unchanged_count)),
+                    ("Failed", Cell::count(s.failed_count)),
+                    ("Noop", Cell::count(s.noop_count)),
+                    ("Unreported", Cell::count(s.unreported_count)),
+                    ("In Maintenance", Cell::count(s.maintenance_count)),
+                    ("Compliance Rate (%)", Cell::number(s.compliance_rate)),
+                ],
+                tables,
+                raw: None,
+            }
+        }
+        ReportResult::Compliance(report) => {
+            let s = &report.summary;
+            ExportDocument {
+                title: "Compliance Report",
+                generated_at: report.generated_at,
+                details: vec![("Baseline", report.baseline_name.clone())],
+                summary: vec![
+                    ("Total Nodes", Cell::count(s.total_nodes)),
+                    ("Compliant", Cell::count(s.compliant_nodes)),
+                    ("Non-Compliant", Cell::count(s.non_compliant_nodes)),
+                    ("Compliance Rate (%)", Cell::number(s.compliance_rate)),
+                    ("Total Violations", Cell::count(s.total_violations)),
+                ],
+                tables: vec![
+                    Table {
+                        name: "By Baseline",
+                        headers: &[
+                            "Baseline",
+                            "Nodes",
+                            "Compliant",
+                            "Non-Compliant",
+                            "Compliance Rate (%)",
+                            "Violations",
+                        ],
+                        rows: report
+                            .baselines
+                            .iter()
+                            .map(|b| {
+                                vec![
+                                    Cell::text(b.baseline_name.as_str()),
+                                    Cell::count(b.summary.total_nodes),
+                                    Cell::count(b.summary.compliant_nodes),
+                                    Cell::count(b.summary.non_compliant_nodes),
+                                    Cell::number(b.summary.compliance_rate),
+                                    Cell::count(b.summary.total_violations),
+                                ]
+                            })
+                            .collect(),
+                    },
+                    Table {
+                        name: "By Severity",
+                        headers: &["Severity", "Violations", "Affected Nodes"],
+                        rows: report
+                            .by_severity
+                            .iter()
+                            .map(|b| {
+                                vec![
+                                    Cell::severity(b.severity),
+                                    Cell::count(b.violation_count),
+                                    Cell::count(b.affected_nodes),
+                                ]
+                            })
+                            .collect(),
+                    },
+                    Table {
+                        name: "Violations",
+                        headers: &[
+                            "Certname", "Baseline", "Rule", "Fact", "Expected", "Actual",
+                            "Severity",
+                        ],
+                        rows: report
+                            .violations
+                            .iter()
+                            .map(|v| {
+                                vec![
+                                    Cell::text(v.certname.as_str()),
+                                    Cell::optional(v.baseline_name.as_deref()),
+                                    Cell::text(v.rule_name.as_str()),
+                                    Cell::text(v.fact_name.as_str()),
+                                    Cell::json(&v.expected_value),
+                                    Cell::json(&v.actual_value),
+                                    Cell::severity(v.severity),
+                                ]
+                            })
+                            .collect(),
+                    },
+                ],
+                raw: None,
+            }
+        }
+        ReportResult::ChangeTracking(report) => {
+            let s = &report.summary;
+            ExportDocument {
+                title: "Change Tracking Report",
+                generated_at: report.generated_at,
+                details: vec![("Time Range", report.time_range.clone())],
+                summary: vec![
+                    ("Total Changes", Cell::count(s.total_changes)),
+                    ("Nodes Affected", Cell::count(s.nodes_affected)),
+                    ("Resources Changed", Cell::count(s.resources_changed)),
+                    ("Resources Failed", Cell::count(s.resources_failed)),
+                    ("Corrective Changes", Cell::count(s.corrective_changes)),
+                    ("Intentional Changes", Cell::count(s.intentional_changes)),
+                ],
+                tables: vec![
+                    Table {
+                        name: "By Resource Type",
+                        headers: &["Resource Type", "Changes"],
+                        rows: report
+                            .changes_by_type
+                            .iter()
+                            .map(|c| {
+                                vec![
+                                    Cell::text(c.resource_type.as_str()),
+                                    Cell::count(c.change_count),
+                                ]
+                            })
+                            .collect(),
+                    },
+                    Table {
+                        name: "Changes",
+                        headers: &[
+                            "Certname",
+                            "Time",
+                            "Resource Type",
+                            "Title",
+                            "Property",
+                            "Old Value",
+                            "New Value",
+                            "Status",
+                            "Corrective",
+                        ],
+                        rows: report
+                            .changes
+                            .iter()
+                            .map(|c| {
+                                vec![
+                                    Cell::text(c.certname.as_str()),
+                                    Cell::time(Some(c.report_time)),
+                                    Cell::text(c.resource_type.as_str()),
+                                    Cell::text(c.resource_title.as_str()),
+                                    Cell::optional(c.property.as_deref()),
+                                    c.old_value.as_ref().map(Cell::json).unwrap_or(Cell::Empty),
+                                    c.new_value.as_ref().map(Cell::json).unwrap_or(Cell::Empty),
+                                    Cell::text(c.status.as_str()),
+                                    Cell::Bool(c.corrective_change),
+                                ]
+                            })
+                            .collect(),
+                    },
+                ],
+                raw: None,
+            }
+        }
+        ReportResult::DriftDetection(report) => {
+            let s = &report.summary;
+            ExportDocument {
+                title: "Drift Detection Report",
+                generated_at: report.generated_at,
+                details: vec![("Baseline", report.baseline_name.clone())],
+                summary: vec![
+                    ("Total Nodes", Cell::count(s.total_nodes)),
+                    ("Nodes With Drift", Cell::count(s.nodes_with_drift)),
+                    ("Nodes Without Drift", Cell::count(s.nodes_without_drift)),
+                    ("Drift Rate (%)", Cell::number(s.drift_rate)),
+                    ("Total Drifted Facts", Cell::count(s.total_drifted_facts)),
+                ],
+                tables: vec![Table {
+                    name: "Drifted Facts",
+                    headers: &[
+                        "Certname",
+                        "Fact",
+                        "Baseline Value",
+                        "Current Value",
+                        "Severity",
+                    ],
+                    rows: report
+                        .drifted_nodes
+                        .iter()
+                        .flat_map(|node| {
+                            node.drifted_facts.iter().map(|f| {
+                                vec![
+                                    Cell::text(node.certname.as_str()),
+                                    Cell::text(f.fact_name.as_str()),
+                                    Cell::json(&f.baseline_value),
+                                    Cell::json(&f.current_value),
+                                    Cell::severity(f.drift_severity),
+                                ]
+                            })
+                        })
+                        .collect(),
+                }],
+                raw: None,
+            }
+        }
+        ReportResult::Custom(data) => ExportDocument {
+            title: "Custom Report",
+            generated_at: Utc::now(),
+            details: Vec::new(),
+            summary: Vec::new(),
+            tables: Vec::new(),
+            raw: Some(serde_json::to_string_pretty(data).unwrap_or_default()),
+        },
+    }
+}
+
+/// Export a report as an XLSX workbook
+///
+/// The first sheet holds the report parameters and summary metrics; every
+/// detail table gets its own sheet with a frozen header row.
+pub fn to_xlsx(result: &ReportResult) -> Result<Vec<u8>> {
+    let doc = document(result);
+    let mut workbook = Workbook::new();
+    let title = Format::new().set_bold().set_font_size(14);
+    let bold = Format::new().set_bold();
+    let header = Format::new()
+        .set_bold()
+        .set_background_color(Color::RGB(0xD9E1F2));
+
+    let summary = workbook.add_worksheet();
+    summary.set_name("Summary")?;
+    summary.write_string_with_format(0, 0, doc.title, &title)?;
+    summary.write_string_with_format(1, 0, "Generated At", &bold)?;
+    summary.write_string(1, 1, &doc.generated_at.to_rfc3339())?;
+    let mut row = 2;
+    for (label, value) in &doc.details {
+        summary.write_string_with_format(row, 0, *label, &bold)?;
+        summary.write_string(row, 1, value.as_str())?;
+        row += 1;
+    }
+    if !doc.summary.is_empty() {
+        row += 1;
+        summary.write_string_with_format(row, 0, "Metric", &header)?;
+        summary.write_string_with_format(row, 1, "Value", &header)?;
+        for (label, value) in &doc.summary {
+            row += 1;
+            summary.write_string(row, 0, *label)?;
+            write_cell(summary, row, 1, value)?;
+        }
+    }
+    if let Some(raw) = &doc.raw {
+        summary.write_string(row + 1, 0, raw.as_str())?;
+    }
+    summary.autofit();
+
+    for table in &doc.tables {
+        let sheet = workbook.add_worksheet();
+        sheet.set_name(table.name)?;
+        for (col, name) in table.headers.iter().enumerate() {
+            sheet.write_string_with_format(0, col as u16, *name, &header)?;
+        }
+        for (i, cells) in table.rows.iter().enumerate() {
+            for (col, cell) in cells.iter().enumerate() {
+                write_cell(sheet, i as u32 + 1, col as u16, cell)?;
+            }
+        }
+        sheet.set_freeze_panes(1, 0)?;
+        sheet.autofit();
+    }
+
+    workbook
+        .save_to_buffer()
+        .context("Failed to write XLSX workbook")
+}
+
+fn write_cell(
+    sheet: &mut rust_xlsxwriter::Worksheet,
+    row: u32,
+    col: u16,
+    cell: &Cell,
+) -> Result<()> {
+    match cell {
+        Cell::Text(s) => {
+            sheet.write_string(row, col, s.as_str())?;
+        }
+        Cell::Number(n) => {
+            sheet.write_number(row, col, *n)?;
+        }
+        Cell::Bool(b) => {
+            sheet.write_boolean(row, col, *b)?;
+        }
+        Cell::Empty => {}
+    }
+    Ok(())
+}
+
+const HTML_STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; color: #1f2937; margin: 2rem; }
+h1 { font-size: 1.5rem; margin-bottom: 0.25rem; }
+h2 { font-size: 1.1rem; margin-top: 2rem; border-bottom: 2px solid #e5e7eb; padding-bottom: 0.25rem; }
+.meta { color: #6b7280; margin: 0.1rem 0; }
+table { border-collapse: collapse; margin-top: 0.5rem; font-size: 0.875rem; }
+th, td { border: 1px solid #e5e7eb; padding: 0.35rem 0.75rem; text-align: left; vertical-align: top; }
+th { background: #f3f4f6; font-weight: 600; }
+tr:nth-child(even) td { background: #f9fafb; }
+td.num { text-align: right; font-variant-numeric: tabular-nums; }
+pre { background: #f3f4f6; padding: 1rem; overflow-x: auto; }
+"#;
+
+/// Export a report as a standalone, styled HTML page
+pub fn to_html(result: &ReportResult) -> String {
+    let doc = document(result);
+    let mut html = String::new();
+
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", html_escape(doc.title)));
+    html.push_str(&format!("<style>{}</style>\n</head>\n<body>\n", HTML_STYLE));
+    html.push_str(&format!("<h1>{}</h1>\n", html_escape(doc.title)));
+    html.push_str(&format!(
+        "<p class=\"meta\">Generated at {}</p>\n",
+        doc.generated_at.format("%Y-%m-%d %H:%M:%S UTC")
+    ));
+    for (label, value) in &doc.details {
+        html.push_str(&format!(
+            "<p class=\"meta\">{}: {}</p>\n",
+            html_escape(label),
+            html_escape(value)
+        ));
+    }
+
+    if !doc.summary.is_empty() {
+        html.push_str("<h2>Summary</h2>\n<table>\n");
+        for (label, value) in &doc.summary {
+            html.push_str(&format!("<tr><th>{}</th>", html_escape(label)));
+            push_cell(&mut html, value);
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</table>\n");
+    }
+
+    for table in &doc.tables {
+        html.push_str(&format!("<h2>{}</h2>\n", html_escape(table.name)));
+        if table.rows.is_empty() {
+            html.push_str("<p class=\"meta\">None</p>\n");
+            continue;
+        }
+        html.push_str("<table>\n<thead><tr>");
+        for name in table.headers {
+            html.push_str(&format!("<th>{}</th>", html_escape(name)));
+        }
+        html.push_str("</tr></thead>\n<tbody>\n");
+        for cells in &table.rows {
+            html.push_str("<tr>");
+            for cell in cells {
+                push_cell(&mut html, cell);
+            }
+            html.push_str("</tr>\n");
+        }
+        html.push_str("</tbody>\n</table>\n");
+    }
+
+    if let Some(raw) = &doc.raw {
+        html.push_str(&format!("<pre>{}</pre>\n", html_escape(raw)));
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn push_cell(html: &mut String, cell: &Cell) {
+    let class = if matches!(cell, Cell::Number(_)) {
+        " class=\"num\""
+    } else {
+        ""
+    };
+    html.push_str(&format!(
+        "<td{}>{}</td>",
+        class,
+        html_escape(&cell.display())
+    ));
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#x27;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{
+        BaselineComplianceSummary, ComplianceReport, ComplianceSummary, ComplianceViolation,
+        SeverityBreakdown,
+    };
+
+    fn compliance_report() -> ReportResult {
+        let summary = ComplianceSummary {
+            total_nodes: 10,
+            compliant_nodes: 9,
+            non_compliant_nodes: 1,
+            compliance_rate: 90.0,
+            total_violations: 1,
+        };
+        let by_severity = vec![SeverityBreakdown {
+            severity: SeverityLevel::High,
+            violation_count: 1,
+            affected_nodes: 1,
+        }];
+        let baseline_id = uuid::Uuid::new_v4();
+        ReportResult::Compliance(ComplianceReport {
+            generated_at: Utc::now(),
+            baseline_name: "CIS <Level 1>".to_string(),
+            summary: summary.clone(),
+            by_severity: by_severity.clone(),
+            baselines: vec![BaselineComplianceSummary {
+                baseline_id,
+                baseline_name: "CIS <Level 1>".to_string(),
+                severity_level: SeverityLevel::High,
+                node_group_ids: vec![],
+                summary,
+                by_severity,
+            }],
+            violations: vec![ComplianceViolation {
+                certname: "web1.example.com".to_string(),
+                baseline_id: Some(baseline_id),
+                baseline_name: Some("CIS <Level 1>".to_string()),
+                rule_id: "r1".to_string(),
+                rule_name: "SELinux enforcing".to_string(),
+                fact_name: "os.selinux.current_mode".to_string(),
+                expected_value: serde_json::json!("enforcing"),
+                actual_value: serde_json::json!("permissive"),
+                severity: SeverityLevel::High,
+                remediation: None,
+            }],
+        })
+    }
+
+    #[test]
+    fn test_document_tables() {
+        let doc = document(&compliance_report());
+        assert_eq!(doc.title, "Compliance Report");
+        let names: Vec<_> = doc.tables.iter().map(|t| t.name).collect();
+        assert_eq!(names, vec!["By Baseline", "By Severity", "Violations"]);
+        for table in &doc.tables {
+            for row in &table.rows {
+                assert_eq!(row.len(), table.headers.len());
+            }
+        }
+        // JSON strings are shown without quotes
+        assert_eq!(doc.tables[2].rows[0][4], Cell::text("enforcing"));
+    }
+
+    #[test]
+    fn test_to_html_escapes() {
+        let html = to_html(&compliance_report());
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("Baseline: CIS &lt;Level 1&gt;"));
+        assert!(html.contains("<td>web1.example.com</td>"));
+        assert!(html.contains("<td class=\"num\">90</td>"));
+    }
+
+    #[test]
+    fn test_to_xlsx_is_zip() {
+        let xlsx = to_xlsx(&compliance_report()).unwrap();
+        // XLSX files are ZIP archives
+        assert!(xlsx.starts_with(b"PK"));
+    }
+}
@@ -0,0 +1,302 @@
+//! Near real-time ingestion of Puppet reports
+//!
+//! PuppetDB has no stream of submitted commands to subscribe to, so the
+//! ingester polls `reports` with a high-water mark on `receive_time` every
+//! few seconds. The mark is stored with the hashes of the reports received at
+//! that instant, so reports sharing a timestamp with the last batch are
+//! neither skipped nor processed twice, including across restarts.
+//!
+//! New reports drop the cached smart list counts, and a failed run or a node
+//! changing status evaluates the alert rules looking at Puppet runs, instead
+//! of waiting for the next manual or scheduled evaluation.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use tokio::sync::RwLock;
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+
+use crate::config::ReportIngestionConfig;
+use crate::db::{DbPool, ReportIngestionCursor, ReportIngestionRepository};
+use crate::models::ReportStatus;
+use crate::services::alerting::AlertingService;
+use crate::services::deployment_impact::pql_string;
+use crate::services::notification::NotificationService;
+use crate::services::puppetdb::PuppetDbClient;
+use crate::services::secrets::SecretsResolver;
+use crate::services::smart_list::MemberCounts;
+use crate::services::webhooks::Webhooks;
+
+/// Cursor source of the global PuppetDB
+const SOURCE: &str = "puppetdb";
+
+/// A report as fetched by the ingester
+#[derive(Debug, Clone, Deserialize)]
+pub struct IngestedReport {
+    pub hash: String,
+    pub certname: String,
+    pub status: Option<ReportStatus>,
+    pub receive_time: DateTime<Utc>,
+}
+
+/// Handle for stopping the report ingester
+#[derive(Debug, Clone)]
+pub struct ReportIngesterState {
+    running: Arc<RwLock<bool>>,
+}
+
+impl ReportIngesterState {
+    pub async fn is_running(&self) -> bool {
+        *self.running.read().await
+    }
+
+    /// Request the ingester loop to stop at its next poll
+    pub async fn stop(&self) {
+        *self.running.write().await = false;
+        info!("Report ingester stop requested");
+    }
+}
+
+/// Spawn the background task following new reports
+pub fn start_report_ingester(
+    pool: DbPool,
+    puppetdb: Arc<PuppetDbClient>,
+    notification_service: Option<Arc<NotificationService>>,
+    webhooks: Webhooks,
+    secrets: Option<Arc<SecretsResolver>>,
+    member_counts: Arc<MemberCounts>,
+    config: &ReportIngestionConfig,
+) -> ReportIngesterState {
+    let state = ReportIngesterState {
+        running: Arc::new(RwLock::new(true)),
+    };
+
+    let loop_state = state.clone();
+    let config = config.clone();
+    tokio::spawn(async move {
+        let mut ingester = ReportIngester {
+            pool,
+            puppetdb,
+            notification_service,
+            webhooks,
+            secrets,
+            member_counts,
+            batch_size: config.batch_size.max(1),
+            evaluate_alerts: config.evaluate_alerts,
+            last_status: HashMap::new(),
+        };
+        let mut timer = interval(Duration::from_secs(config.poll_interval_secs.max(1)));
+
+        loop {
+            timer.tick().await;
+
+            if !*loop_state.running.read().await {
+                info!("Report ingester stopping");
+                break;
+            }
+
+            if let Err(e) = ingester.poll().await {
+                error!("Report ingestion failed: {:#}", e);
+            }
+        }
+    });
+
+    info!(
+        "Report ingester started (polling every {}s)",
+        config.poll_interval_secs
+    );
+    state
+}
+
+struct ReportIngester {
+    pool: DbPool,
+    puppetdb: Arc<PuppetDbClient>,
+    notification_service: Option<Arc<NotificationService>>,
+    webhooks: Webhooks,
+    secrets: Option<Arc<SecretsResolver>>,
+    /// Cached smart list member counts, dropped when node statuses change
+    member_counts: Arc<MemberCounts>,
+    batch_size: u32,
+    evaluate_alerts: bool,
+    /// Status of the last report ingested for each node
+    last_status: HashMap<String, Option<ReportStatus>>,
+}
+
+impl ReportIngester {
+    /// Process the reports received since the last poll
+    async fn poll(&mut self) -> Result<()> {
+        let repo = ReportIngestionRepository::new(&self.pool);
+        let Some(mut cursor) = repo.get(SOURCE).await? else {
+            // Start from now rather than replaying the history PuppetDB keeps
+            let cursor = ReportIngestionCursor {
+                receive_time: Utc::now(),
+                hashes: Vec::new(),
+            };
+            repo.save(SOURCE, &cursor).await?;
+            return Ok(());
+        };
+
+        let mut ingested = 0;
+        let mut needs_evaluation = false;
+        loop {
+            let pql = format!(
+                "reports[hash, certname, status, receive_time] {{ receive_time >= {} order by receive_time limit {} }}",
+                pql_string(&cursor.receive_time.to_rfc3339()),
+                self.batch_size
+            );
+            let rows: Vec<IngestedReport> = self.puppetdb.query(&pql).await?;
+            let full_batch = rows.len() >= self.batch_size as usize;
+
+            let (reports, next) = advance(&cursor, rows);
+            if reports.is_empty() {
+                break;
+            }
+
+            for report in &reports {
+                let previous = self
+                    .last_status
+                    .insert(report.certname.clone(), report.status);
+                if report.status == Some(ReportStatus::Failed)
+                    || previous.is_some_and(|p| p != report.status)
+                {
+                    needs_evaluation = true;
+                }
+            }
+            ingested += reports.len();
+            repo.save(SOURCE, &next).await?;
+            cursor = next;
+
+            if !full_batch {
+                break;
+            }
+        }
+
+        if ingested == 0 {
+            return Ok(());
+        }
+        debug!(
+            "Ingested {} new report(s) up to {}",
+            ingested, cursor.receive_time
+        );
+        self.member_counts.clear().await;
+
+        if needs_evaluation && self.evaluate_alerts {
+            let alerting = AlertingService::new(
+                self.pool.clone(),
+                Some(self.puppetdb.clone()),
+                self.notification_service.clone(),
+            )
+            .with_webhooks(self.webhooks.clone())
+            .with_secrets(self.secrets.clone());
+            match alerting.evaluate_report_rules().await {
+                Ok(alerts) if !alerts.is_empty() => {
+                    info!("New reports triggered {} alert(s)", alerts.len())
+                }
+                Ok(_) => {}
+                Err(e) => warn!("Alert evaluation after report ingestion failed: {:#}", e),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Reports of a batch (sorted by `receive_time`) not processed yet, and the
+/// cursor after them
+fn advance(
+    cursor: &ReportIngestionCursor,
+    rows: Vec<IngestedReport>,
+) -> (Vec<IngestedReport>, ReportIngestionCursor) {
+    let reports: Vec<IngestedReport> = rows
+        .into_iter()
+        .filter(|r| {
+            r.receive_time > cursor.receive_time
+                || (r.receive_time == cursor.receive_time && !cursor.hashes.contains(&r.hash))
+        })
+        .collect();
+
+    let Some(last) = reports.iter().map(|r| r.receive_time).max() else {
+        return (reports, cursor.clone());
+    };
+    let mut hashes: Vec<String> = if last == cursor.receive_time {
+        cursor.hashes.clone()
+    } else {
+        Vec::new()
+    };
+    hashes.extend(
+        reports
+            .iter()
+            .filter(|r| r.receive_time == last)
+            .map(|r| r.hash.clone()),
+    );
+
+    (
+        reports,
+        ReportIngestionCursor {
+            receive_time: last,
+            hashes,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(hash: &str, receive_time: &str) -> IngestedReport {
+        IngestedReport {
+            hash: hash.to_string(),
+            certname: "web01.example.com".to_string(),
+            status: Some(ReportStatus::Unchanged),
+            receive_time: receive_time.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_advance() {
+        let cursor = ReportIngestionCursor {
+            receive_time: "2026-10-16T12:00:00Z".parse().unwrap(),
+            hashes: vec!["a".to_string()],
+        };
+
+        // Reports already seen at the mark are skipped
+        let (reports, next) = advance(
+            &cursor,
+            vec![
+                report("a", "2026-10-16T12:00:00Z"),
+                report("b", "2026-10-16T12:00:00Z"),
+            ],
+        );
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].hash, "b");
+        assert_eq!(next.receive_time, cursor.receive_time);
+        assert_eq!(next.hashes, ["a", "b"]);
+
+        // Moving past the mark only keeps the hashes of the new instant
+        let (reports, next) = advance(
+            &next,
+            vec![
+                report("a", "2026-10-16T12:00:00Z"),
+                report("b", "2026-10-16T12:00:00Z"),
+                report("c", "2026-10-16T12:00:05Z"),
+                report("d", "2026-10-16T12:00:05Z"),
+            ],
+        );
+        assert_eq!(reports.len(), 2);
+        assert_eq!(
+            next.receive_time,
+            "2026-10-16T12:00:05Z".parse::<DateTime<Utc>>().unwrap()
+        );
+        assert_eq!(next.hashes, ["c", "d"]);
+
+        // Nothing new keeps the cursor
+        let (reports, unchanged) = advance(&next, vec![report("d", "2026-10-16T12:00:05Z")]);
+        assert!(reports.is_empty());
+        assert_eq!(unchanged, next);
+    }
+}
@@ -0,0 +1,287 @@
+//! Storage of report execution outputs
+//!
+//! Outputs up to `reporting.offload_threshold_bytes` are kept in the
+//! `output_data` column of the execution; larger ones are written to a JSON
+//! file under `reporting.output_dir` and referenced by `output_file_path`, so
+//! big reports do not bloat the database. A background task deletes
+//! executions older than `reporting.execution_retention_days` along with
+//! their files, and files no execution references anymore.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use chrono::Utc;
+use tokio::sync::RwLock;
+use tokio::time::interval;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+use crate::config::ReportingConfig;
+use crate::db::repository::ReportExecutionRepository;
+use crate::db::DbPool;
+use crate::models::ReportExecution;
+
+/// How often old executions are deleted
+const RETENTION_INTERVAL_SECS: u64 = 3600;
+
+/// Files younger than this are never treated as orphans, since the execution
+/// referencing them may not be recorded yet
+const ORPHAN_GRACE_SECS: u64 = 3600;
+
+/// Where an execution output was stored
+#[derive(Debug, Clone, PartialEq)]
+pub enum StoredOutput {
+    /// Small enough for the `output_data` column
+    Inline(serde_json::Value),
+    /// Written to this file
+    File(PathBuf),
+}
+
+/// Stores execution outputs inline or in files depending on their size
+#[derive(Debug, Clone)]
+pub struct ReportOutputStore {
+    dir: PathBuf,
+    threshold: usize,
+}
+
+impl ReportOutputStore {
+    pub fn new(config: &ReportingConfig) -> Self {
+        Self {
+            dir: config.output_dir.clone(),
+            threshold: config.offload_threshold_bytes,
+        }
+    }
+
+    /// Store the output of an execution
+    ///
+    /// Files are written to `<output_dir>/<organization>/<execution>.json`
+    /// through a temporary file, so a partial file is never referenced.
+    pub async fn store(
+        &self,
+        organization_id: Uuid,
+        execution_id: Uuid,
+        output: &serde_json::Value,
+    ) -> Result<StoredOutput> {
+        let data = serde_json::to_vec(output).context("Failed to serialize report output")?;
+        if data.len() <= self.threshold {
+            return Ok(StoredOutput::Inline(output.clone()));
+        }
+
+        let dir = self.dir.join(organization_id.to_string());
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .with_context(|| format!("Failed to create {}", dir.display()))?;
+        let path = dir.join(format!("{}.json", execution_id));
+        let partial = path.with_extension("json.partial");
+        tokio::fs::write(&partial, &data)
+            .await
+            .with_context(|| format!("Failed to write {}", partial.display()))?;
+        tokio::fs::rename(&partial, &path)
+            .await
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+
+        Ok(StoredOutput::File(path))
+    }
+
+    /// Output of an execution, wherever it is stored
+    pub async fn load(execution: &ReportExecution) -> Result<Option<serde_json::Value>> {
+        if let Some(data) = &execution.output_data {
+            return Ok(Some(data.clone()));
+        }
+        let Some(path) = &execution.output_file_path else {
+            return Ok(None);
+        };
+
+        let data = tokio::fs::read(path)
+            .await
+            .with_context(|| format!("Failed to read report output {}", path))?;
+        let output = serde_json::from_slice(&data)
+            .with_context(|| format!("Failed to parse report output {}", path))?;
+        Ok(Some(output))
+    }
+
+    /// Delete executions older than the retention period with their files,
+    /// then the files no execution references
+    pub async fn apply_retention(&self, pool: &DbPool, retention_days: u32) -> Result<u64> {
+        let repo = ReportExecutionRepository::new(pool);
+        let cutoff = Utc::now() - chrono::Duration::days(retention_days as i64);
+
+        for path in repo.output_files_before(cutoff).await? {
+            remove_file(Path::new(&path)).await;
+        }
+        let deleted = repo.delete_old(cutoff).await?;
+
+        let referenced = repo.output_file_paths().await?;
+        for path in self.stored_files().await? {
+            if !referenced.contains(path.to_string_lossy().as_ref()) && is_stale(&path).await {
+                warn!("Removing orphaned report output {}", path.display());
+                remove_file(&path).await;
+            }
+        }
+
+        Ok(deleted)
+    }
+
+    /// Output files under the output directory
+    async fn stored_files(&self) -> Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        let mut dirs = match tokio::fs::read_dir(&self.dir).await {
+            Ok(dirs) => dirs,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(files),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to list {}", self.dir.display()))
+            }
+        };
+
+        while let Some(dir) = dirs.next_entry().await? {
+            if !dir.file_type().await?.is_dir() {
+                continue;
+            }
+            let mut entries = tokio::fs::read_dir(dir.path()).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                files.push(entry.path());
+            }
+        }
+        Ok(files)
+    }
+}
+
+/// Whether a file is older than the orphan grace period
+async fn is_stale(path: &Path) -> bool {
+    tokio::fs::metadata(path)
+        .await
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|age| age > Duration::from_secs(ORPHAN_GRACE_SECS))
+}
+
+async fn remove_file(path: &Path) {
+    match tokio::fs::remove_file(path).await {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => warn!("Failed to remove report output {}: {}", path.display(), e),
+    }
+}
+
+/// Handle for stopping the report retention task
+#[derive(Clone)]
+pub struct ReportRetentionState {
+    running: Arc<RwLock<bool>>,
+}
+
+impl ReportRetentionState {
+    pub async fn is_running(&self) -> bool {
+        *self.running.read().await
+    }
+
+    /// Request the retention loop to stop at its next tick
+    pub async fn stop(&self) {
+        *self.running.write().await = false;
+        info!("Report retention stop requested");
+    }
+}
+
+/// Spawn the background task deleting old report executions and outputs
+pub fn start_report_retention(pool: DbPool, config: &ReportingConfig) -> ReportRetentionState {
+    let state = ReportRetentionState {
+        running: Arc::new(RwLock::new(true)),
+    };
+
+    let loop_state = state.clone();
+    let store = ReportOutputStore::new(config);
+    let retention_days = config.execution_retention_days;
+    tokio::spawn(async move {
+        let mut timer = interval(Duration::from_secs(RETENTION_INTERVAL_SECS));
+        loop {
+            timer.tick().await;
+
+            if !*loop_state.running.read().await {
+                info!("Report retention stopping");
+                break;
+            }
+
+            match store.apply_retention(&pool, retention_days).await {
+                Ok(deleted) if deleted > 0 => info!("Deleted {} old report executions", deleted),
+                Ok(_) => {}
+                Err(e) => error!("Report retention failed: {:#}", e),
+            }
+        }
+    });
+
+    info!(
+        "Report retention started (keeping executions {} days)",
+        config.execution_retention_days
+    );
+    state
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn store(threshold: usize) -> ReportOutputStore {
+        ReportOutputStore {
+            dir: std::env::temp_dir().join(format!("openvox-report-output-{}", Uuid::new_v4())),
+            threshold,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_store_inline_below_threshold() {
+        let store = store(1024);
+        let output = json!({"rows": [1, 2, 3]});
+
+        let stored = store
+            .store(Uuid::new_v4(), Uuid::new_v4(), &output)
+            .await
+            .unwrap();
+        assert_eq!(stored, StoredOutput::Inline(output));
+        assert!(!store.dir.exists());
+    }
+
+    #[tokio::test]
+    async fn test_store_file_above_threshold() {
+        let store = store(8);
+        let output = json!({"rows": [1, 2, 3]});
+        let execution_id = Uuid::new_v4();
+
+        let StoredOutput::File(path) = store
+            .store(Uuid::new_v4(), execution_id, &output)
+            .await
+            .unwrap()
+        else {
+            panic!("expected the output in a file");
+        };
+        assert!(path.ends_with(format!("{}.json", execution_id)));
+        assert_eq!(store.stored_files().await.unwrap(), [path.clone()]);
+
+        let execution: ReportExecution = serde_json::from_value(json!({
+            "id": execution_id,
+            "organization_id": Uuid::new_v4(),
+            "report_id": Uuid::new_v4(),
+            "schedule_id": null,
+            "executed_by": null,
+            "status": "completed",
+            "started_at": Utc::now(),
+            "completed_at": null,
+            "row_count": 3,
+            "output_format": "json",
+            "output_data": null,
+            "output_file_path": path.to_string_lossy(),
+            "error_message": null,
+            "execution_time_ms": 5,
+            "delivery_status": null,
+        }))
+        .unwrap();
+        assert_eq!(
+            ReportOutputStore::load(&execution).await.unwrap(),
+            Some(output)
+        );
+
+        tokio::fs::remove_dir_all(&store.dir).await.unwrap();
+    }
+}
@@ -3,50 +3,79 @@
 //! This service handles report generation, execution, and export functionality.
 
 use anyhow::{Context, Result};
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, NaiveDate, Utc};
 use printpdf::{
     BuiltinFont, Mm, Op, PdfDocument, PdfFontHandle, PdfPage, PdfSaveOptions, Pt, TextItem,
 };
 use sqlx::SqlitePool;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::api::groups::classify_group_members;
 use crate::db::repository::{
-    ComplianceBaselineRepository, DriftBaselineRepository, ReportExecutionRepository,
+    ComplianceBaselineRepository, DriftBaselineRepository, GroupRepository,
+    ReportExecutionRepository,
 };
+use crate::db::SmartListRepository;
 use crate::models::{
-    ChangeSummary, ChangeTrackingReport, ChangeTypeBreakdown, ComplianceReport, ComplianceSummary,
-    ComplianceViolation, DriftReport, DriftSummary, DriftedFact, DriftedNode, EnvironmentHealth,
-    ExecuteReportRequest, NodeHealthDetail, NodeHealthReport, NodeHealthSummary, OutputFormat,
-    ReportExecution, ReportQueryConfig, ReportResult, ReportType, SavedReport, SeverityBreakdown,
-    SeverityLevel,
+    BaselineComplianceSummary, ChangeSummary, ChangeTrackingReport, ChangeTypeBreakdown,
+    ComplianceBaseline, ComplianceReport, ComplianceSummary, ComplianceViolation,
+    CorrectiveChangeAnalytics, CorrectiveChangeDay, CorrectiveChangeSummary, DriftReport,
+    DriftSummary, DriftedFact, DriftedNode, EnvironmentHealth, ExecuteReportRequest, Node,
+    NodeCorrectiveChanges, NodeHealthDetail, NodeHealthReport, NodeHealthSummary, OutputFormat,
+    PuppetDbDataRef, ReportExecution, ReportMetric, ReportQueryConfig, ReportResult, ReportType,
+    SavedReport, SeverityBreakdown, SeverityLevel,
 };
+use crate::services::drift_snapshot::is_volatile_fact;
+use crate::services::maintenance::ActiveMaintenance;
+use crate::services::report_export;
+use crate::services::report_output::{ReportOutputStore, StoredOutput};
+use crate::services::smart_list::{load_smart_list_for_user, resolve_smart_list_certnames};
 use crate::services::PuppetDbClient;
 
 /// Service for generating and executing reports
 pub struct ReportingService {
     pool: SqlitePool,
     puppetdb: Option<Arc<PuppetDbClient>>,
+    output_store: Option<ReportOutputStore>,
 }
 
 impl ReportingService {
     pub fn new(pool: SqlitePool, puppetdb: Option<Arc<PuppetDbClient>>) -> Self {
-        Self { pool, puppetdb }
+        Self {
+            pool,
+            puppetdb,
+            output_store: None,
+        }
+    }
+
+    /// Offload large execution outputs to files; without a store every
+    /// output is kept in the database
+    pub fn with_output_store(mut self, store: ReportOutputStore) -> Self {
+        self.output_store = Some(store);
+        self
     }
 
-    /// Execute a saved report
+    /// Execute a saved report, on behalf of a user or of a schedule
     pub async fn execute_report(
         &self,
         report: &SavedReport,
         req: &ExecuteReportRequest,
         user_id: Option<Uuid>,
+        schedule_id: Option<Uuid>,
     ) -> Result<ReportExecution> {
         let exec_repo = ReportExecutionRepository::new(&self.pool);
 
         // Create execution record
         let mut execution = exec_repo
-            .create(report.id, None, user_id, req.output_format)
+            .create(
+                report.organization_id,
+                report.id,
+                schedule_id,
+                user_id,
+                req.output_format,
+            )
             .await?;
 
         // Mark as running
@@ -54,30 +83,43 @@ impl ReportingService {
 
         let start_time = std::time::Instant::now();
 
-        // Use override config if provided, otherwise use report's config
-        let config = req
-            .query_config_override
-            .as_ref()
-            .unwrap_or(&report.query_config);
+        // Use override config if provided, otherwise use report's config.
+        // A saved config runs as the report's owner, an override as the
+        // user who sent it.
+        let (config, run_as) = match (&req.query_config_override, user_id) {
+            (Some(config), Some(user_id)) => (config, user_id),
+            (Some(config), None) => (config, report.created_by),
+            (None, _) => (&report.query_config, report.created_by),
+        };
 
         // Generate the report
-        let result = self.generate_report(report.report_type, config).await;
+        let result = self
+            .generate_report(report.organization_id, run_as, report.report_type, config)
+            .await;
 
         let execution_time_ms = start_time.elapsed().as_millis() as i32;
 
+        let result = match result {
+            Ok((report_result, row_count)) => self
+                .store_output(report.organization_id, execution.id, &report_result)
+                .await
+                .map(|stored| (stored, row_count)),
+            Err(e) => Err(e),
+        };
+
         match result {
-            Ok((report_result, row_count)) => {
-                let output_data = serde_json::to_value(&report_result).ok();
+            Ok(((output_data, output_file_path), row_count)) => {
                 exec_repo
                     .complete(
                         execution.id,
                         row_count,
                         output_data.clone(),
-                        None,
+                        output_file_path.as_deref(),
                         execution_time_ms,
                     )
                     .await?;
                 execution.output_data = output_data;
+                execution.output_file_path = output_file_path;
                 execution.row_count = Some(row_count);
                 execution.status = crate::models::ExecutionStatus::Completed;
             }
@@ -94,30 +136,69 @@ impl ReportingService {
         Ok(execution)
     }
 
-    /// Generate a report based on type and configuration
+    /// Store an execution output inline or, when large, in a file
+    ///
+    /// Returns the `output_data` and `output_file_path` of the execution.
+    async fn store_output(
+        &self,
+        organization_id: Uuid,
+        execution_id: Uuid,
+        report_result: &ReportResult,
+    ) -> Result<(Option<serde_json::Value>, Option<String>)> {
+        let output_data = serde_json::to_value(report_result).ok();
+        let (Some(store), Some(data)) = (&self.output_store, output_data.as_ref()) else {
+            return Ok((output_data, None));
+        };
+
+        Ok(
+            match store.store(organization_id, execution_id, data).await? {
+                StoredOutput::Inline(data) => (Some(data), None),
+                StoredOutput::File(path) => (None, Some(path.to_string_lossy().into_owned())),
+            },
+        )
+    }
+
+    /// Generate a report of an organization based on type and configuration
+    ///
+    /// `user_id` is who the report runs as; a smart list scoping it must be
+    /// visible to them.
     pub async fn generate_report(
         &self,
+        organization_id: Uuid,
+        user_id: Uuid,
         report_type: ReportType,
         config: &ReportQueryConfig,
     ) -> Result<(ReportResult, i32)> {
+        if let Some(list_id) = config.smart_list_id {
+            load_smart_list_for_user(&self.pool, organization_id, list_id, user_id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Smart list {} not found", list_id))?;
+        }
+
         match report_type {
             ReportType::NodeHealth => {
-                let report = self.generate_node_health_report(config).await?;
+                let report = self
+                    .generate_node_health_report(organization_id, config)
+                    .await?;
                 let row_count = report.summary.total_nodes as i32;
                 Ok((ReportResult::NodeHealth(report), row_count))
             }
             ReportType::Compliance => {
-                let report = self.generate_compliance_report(config).await?;
+                let report = self
+                    .generate_compliance_report(organization_id, config)
+                    .await?;
                 let row_count = report.violations.len() as i32;
                 Ok((ReportResult::Compliance(report), row_count))
             }
             ReportType::ChangeTracking => {
-                let report = self.generate_change_tracking_report(config).await?;
+                let report = self
+                    .generate_change_tracking_report(organization_id, config)
+                    .await?;
                 let row_count = report.changes.len() as i32;
                 Ok((ReportResult::ChangeTracking(report), row_count))
             }
             ReportType::DriftDetection => {
-                let report = self.generate_drift_report(config).await?;
+                let report = self.generate_drift_report(organization_id, config).await?;
                 let row_count = report.drifted_nodes.len() as i32;
                 Ok((ReportResult::DriftDetection(report), row_count))
             }
@@ -128,9 +209,51 @@ impl ReportingService {
         }
     }
 
+    /// Resolve the certnames a report is scoped to via `smart_list_id`
+    ///
+    /// Returns `None` when the report is not scoped and covers every node.
+    /// The smart list must belong to the report's organization.
+    async fn smart_list_scope(
+        &self,
+        organization_id: Uuid,
+        puppetdb: &PuppetDbClient,
+        config: &ReportQueryConfig,
+    ) -> Result<Option<HashSet<String>>> {
+        let Some(list_id) = config.smart_list_id else {
+            return Ok(None);
+        };
+
+        let list = SmartListRepository::new(&self.pool)
+            .get_by_id(organization_id, list_id)
+            .await?
+            .ok_or_else(|| anyhow::anyhow!("Smart list {} not found", list_id))?;
+
+        Ok(Some(
+            resolve_smart_list_certnames(&self.pool, puppetdb, &list).await?,
+        ))
+    }
+
+    /// Get the nodes a report covers, honouring any smart list scope
+    async fn scoped_nodes(
+        &self,
+        organization_id: Uuid,
+        puppetdb: &PuppetDbClient,
+        config: &ReportQueryConfig,
+    ) -> Result<Vec<Node>> {
+        let mut nodes = puppetdb.get_nodes().await?;
+        if let Some(scope) = self
+            .smart_list_scope(organization_id, puppetdb, config)
+            .await?
+        {
+            nodes.retain(|n| scope.contains(&n.certname));
+        }
+        Ok(nodes)
+    }
+
     /// Generate node health report
     async fn generate_node_health_report(
         &self,
+        organization_id: Uuid,
         config: &ReportQueryConfig,
     ) -> Result<NodeHealthReport> {
         let puppetdb = self
@@ -143,12 +266,14 @@ impl ReportingService {
             .clone()
             .unwrap_or_else(|| "24h".to_string());
 
-        // Get all nodes
-        let nodes = puppetdb.get_nodes().await?;
+        // Get all nodes in scope
+        let nodes = self.scoped_nodes(organization_id, puppetdb, config).await?;
         let total_nodes = nodes.len() as i64;
+        let in_scope: HashSet<&str> = nodes.iter().map(|n| n.certname.as_str()).collect();
 
         // Get recent reports to determine status
-        let reports = puppetdb.query_reports(None, None, Some(1000)).await?;
+        let mut reports = puppetdb.query_reports(None, None, Some(1000)).await?;
+        reports.retain(|r| in_scope.contains(r.certname.as_str()));
 
         // Build certname -> latest status map
         let mut node_statuses: HashMap<String, (String, Option<DateTime<Utc>>)> = HashMap::new();
@@ -166,24 +291,31 @@ impl ReportingService {
             }
         }
 
+        // Failures of nodes in maintenance are expected and counted apart
+        let maintenance =
+            ActiveMaintenance::load_or_empty(&self.pool, self.puppetdb.as_deref(), Utc::now())
+                .await;
+
         // Count statuses
         let mut changed_count = 0i64;
         let mut unchanged_count = 0i64;
         let mut failed_count = 0i64;
         let mut noop_count = 0i64;
+        let mut maintenance_count = 0i64;
 
-        for (status, _) in node_statuses.values() {
+        for (certname, (status, _)) in &node_statuses {
             match status.as_str() {
                 "changed" => changed_count += 1,
                 "unchanged" => unchanged_count += 1,
+                "failed" if maintenance.contains(certname) => maintenance_count += 1,
                 "failed" => failed_count += 1,
                 "noop" => noop_count += 1,
                 _ => unchanged_count += 1,
             }
         }
 
-        let unreported_count =
-            total_nodes - (changed_count + unchanged_count + failed_count + noop_count);
+        let unreported_count = total_nodes
+            - (changed_count + unchanged_count + failed_count + noop_count + maintenance_count);
         let compliance_rate = if total_nodes > 0 {
             ((total_nodes - failed_count) as f64 / total_nodes as f64) * 100.0
         } else {
@@ -209,6 +341,7 @@ impl ReportingService {
                 match status {
                     "changed" => entry.1 += 1,
                     "unchanged" => entry.2 += 1,
+                    "failed" if maintenance.contains(&node.certname) => {}
                     "failed" => entry.3 += 1,
                     _ => entry.2 += 1,
                 }
@@ -250,6 +383,7 @@ impl ReportingService {
                             last_report_at,
                             failed_resources: None,
                             changed_resources: None,
+                            in_maintenance: maintenance.contains(&n.certname),
                         }
                     })
                     .collect(),
@@ -268,6 +402,7 @@ impl ReportingService {
                 failed_count,
                 noop_count,
                 unreported_count,
+                maintenance_count,
                 compliance_rate,
             },
             by_environment,
@@ -277,132 +412,137 @@ impl ReportingService {
     }
 
     /// Generate compliance report
+    ///
+    /// Evaluates the baselines selected in the query config, or every baseline
+    /// when none are selected. A baseline scoped to node groups only covers the
+    /// members of those groups.
     async fn generate_compliance_report(
         &self,
-        _config: &ReportQueryConfig,
+        organization_id: Uuid,
+        config: &ReportQueryConfig,
     ) -> Result<ComplianceReport> {
         let puppetdb = self
             .puppetdb
             .as_ref()
             .ok_or_else(|| anyhow::anyhow!("PuppetDB not configured"))?;
 
-        let baseline_repo = ComplianceBaselineRepository::new(&self.pool);
-        let baselines = baseline_repo.get_all().await?;
+        let baselines = self.selected_baselines(organization_id, config).await?;
 
         if baselines.is_empty() {
             // Return empty report if no baselines defined
             return Ok(ComplianceReport {
                 generated_at: Utc::now(),
                 baseline_name: "No baseline defined".to_string(),
-                summary: ComplianceSummary {
-                    total_nodes: 0,
-                    compliant_nodes: 0,
-                    non_compliant_nodes: 0,
-                    compliance_rate: 100.0,
-                    total_violations: 0,
-                },
+                summary: compliance_summary(0, 0, 0),
                 by_severity: vec![],
+                baselines: vec![],
                 violations: vec![],
             });
         }
 
-        // Use first baseline for now (TODO: allow selection)
-        let baseline = &baselines[0];
-
-        // Get all nodes and their facts
-        let nodes = puppetdb.get_nodes().await?;
-        let mut violations = Vec::new();
-        let mut compliant_nodes = 0i64;
-        let mut non_compliant_nodes = 0i64;
-        let mut severity_counts: HashMap<SeverityLevel, (i64, i64)> = HashMap::new();
-
+        // Get all nodes in scope and their facts, once for all baselines
+        let nodes = self.scoped_nodes(organization_id, puppetdb, config).await?;
+        let mut node_facts = Vec::with_capacity(nodes.len());
         for node in &nodes {
             let facts = puppetdb
                 .get_node_facts(&node.certname)
                 .await
                 .unwrap_or_default();
-            let facts_map: HashMap<String, serde_json::Value> =
-                facts.into_iter().map(|f| (f.name, f.value)).collect();
+            node_facts.push(NodeFacts {
+                certname: node.certname.clone(),
+                facts: facts.into_iter().map(|f| (f.name, f.value)).collect(),
+            });
+        }
 
-            let mut node_has_violation = false;
+        let mut group_members = HashMap::new();
+        let mut evaluations = Vec::with_capacity(baselines.len());
+        for baseline in &baselines {
+            let members = self
+                .baseline_members(puppetdb, baseline, &mut group_members)
+                .await?;
+            let in_scope: Vec<&NodeFacts> = node_facts
+                .iter()
+                .filter(|n| members.as_ref().is_none_or(|m| m.contains(&n.certname)))
+                .collect();
+            evaluations.push(evaluate_baseline(baseline, &in_scope));
+        }
 
-            for rule in &baseline.rules {
-                let actual_value = facts_map.get(&rule.fact_name);
-                let is_compliant = match actual_value {
-                    Some(actual) => check_compliance(&rule.operator, &rule.expected_value, actual),
-                    None => false, // Missing fact is non-compliant
-                };
+        Ok(combine_baseline_evaluations(evaluations))
+    }
 
-                if !is_compliant {
-                    node_has_violation = true;
-                    violations.push(ComplianceViolation {
-                        certname: node.certname.clone(),
-                        rule_id: rule.id.clone(),
-                        rule_name: rule.name.clone(),
-                        fact_name: rule.fact_name.clone(),
-                        expected_value: rule.expected_value.clone(),
-                        actual_value: actual_value.cloned().unwrap_or(serde_json::Value::Null),
-                        severity: rule.severity,
-                    });
-
-                    let entry = severity_counts.entry(rule.severity).or_insert((0, 0));
-                    entry.0 += 1; // violation count
-                }
-            }
+    /// Baselines of an organization selected by a report, in the order given
+    async fn selected_baselines(
+        &self,
+        organization_id: Uuid,
+        config: &ReportQueryConfig,
+    ) -> Result<Vec<ComplianceBaseline>> {
+        let baseline_repo = ComplianceBaselineRepository::new(&self.pool);
+        let Some(ids) = config
+            .compliance_baseline_ids
+            .as_ref()
+            .filter(|ids| !ids.is_empty())
+        else {
+            return baseline_repo.get_all(organization_id).await;
+        };
 
-            if node_has_violation {
-                non_compliant_nodes += 1;
-                for severity in [
-                    SeverityLevel::Low,
-                    SeverityLevel::Medium,
-                    SeverityLevel::High,
-                    SeverityLevel::Critical,
-                ] {
-                    let entry = severity_counts.entry(severity).or_insert((0, 0));
-                    entry.1 += 1; // affected nodes (counted once per node)
-                }
-            } else {
-                compliant_nodes += 1;
+        let mut baselines = Vec::with_capacity(ids.len());
+        for id in ids {
+            if baselines.iter().any(|b: &ComplianceBaseline| b.id == *id) {
+                continue;
             }
+            let baseline = baseline_repo
+                .get_by_id(organization_id, *id)
+                .await?
+                .ok_or_else(|| anyhow::anyhow!("Compliance baseline {} not found", id))?;
+            baselines.push(baseline);
         }
+        Ok(baselines)
+    }
 
-        let total_nodes = nodes.len() as i64;
-        let compliance_rate = if total_nodes > 0 {
-            (compliant_nodes as f64 / total_nodes as f64) * 100.0
-        } else {
-            100.0
-        };
-
-        let by_severity: Vec<SeverityBreakdown> = severity_counts
-            .into_iter()
-            .filter(|(_, (count, _))| *count > 0)
-            .map(
-                |(severity, (violation_count, affected_nodes))| SeverityBreakdown {
-                    severity,
-                    violation_count,
-                    affected_nodes,
-                },
-            )
-            .collect();
+    /// Certnames a baseline is scoped to, `None` when it covers every node
+    ///
+    /// Group members are cached in `group_members` across baselines.
+    async fn baseline_members(
+        &self,
+        puppetdb: &PuppetDbClient,
+        baseline: &ComplianceBaseline,
+        group_members: &mut HashMap<Uuid, HashSet<String>>,
+    ) -> Result<Option<HashSet<String>>> {
+        if baseline.node_group_ids.is_empty() {
+            return Ok(None);
+        }
 
-        Ok(ComplianceReport {
-            generated_at: Utc::now(),
-            baseline_name: baseline.name.clone(),
-            summary: ComplianceSummary {
-                total_nodes,
-                compliant_nodes,
-                non_compliant_nodes,
-                compliance_rate,
-                total_violations: violations.len() as i64,
-            },
-            by_severity,
-            violations,
-        })
+        let group_repo = GroupRepository::new(&self.pool);
+        let mut members = HashSet::new();
+        for group_id in &baseline.node_group_ids {
+            if !group_members.contains_key(group_id) {
+                let certnames = match group_repo.get_group_organization_id(*group_id).await? {
+                    Some(org_id) => {
+                        classify_group_members(&group_repo, Some(puppetdb), org_id, *group_id)
+                            .await?
+                            .into_iter()
+                            .collect()
+                    }
+                    None => {
+                        tracing::warn!(
+                            "Node group {} of compliance baseline '{}' no longer exists",
+                            group_id,
+                            baseline.name
+                        );
+                        HashSet::new()
+                    }
+                };
+                group_members.insert(*group_id, certnames);
+            }
+            members.extend(group_members[group_id].iter().cloned());
+        }
+        Ok(Some(members))
     }
 
     /// Generate change tracking report
     async fn generate_change_tracking_report(
         &self,
+        organization_id: Uuid,
         config: &ReportQueryConfig,
     ) -> Result<ChangeTrackingReport> {
         let puppetdb = self
@@ -418,7 +558,13 @@ impl ReportingService {
 
         // Get reports (filter by status if specified)
         let status = status_filter.and_then(|f| f.first().map(|s| s.as_str()));
-        let reports = puppetdb.query_reports(None, status, Some(500)).await?;
+        let mut reports = puppetdb.query_reports(None, status, Some(500)).await?;
+        if let Some(scope) = self
+            .smart_list_scope(organization_id, puppetdb, config)
+            .await?
+        {
+            reports.retain(|r| scope.contains(&r.certname));
+        }
 
         let mut changes = Vec::new();
         let mut nodes_affected: std::collections::HashSet<String> =
@@ -426,6 +572,7 @@ impl ReportingService {
         let mut resource_type_counts: HashMap<String, i64> = HashMap::new();
         let mut resources_changed = 0i64;
         let mut resources_failed = 0i64;
+        let mut corrective_changes = 0i64;
 
         for report in &reports {
             // Only include reports with changes
@@ -452,8 +599,12 @@ impl ReportingService {
                                     .entry(resource_type.clone())
                                     .or_insert(0) += 1;
 
+                                let corrective_change = event.corrective_change == Some(true);
                                 if event.status.as_deref() == Some("success") {
                                     resources_changed += 1;
+                                    if corrective_change {
+                                        corrective_changes += 1;
+                                    }
                                 } else if event.status.as_deref() == Some("failure") {
                                     resources_failed += 1;
                                 }
@@ -467,6 +618,7 @@ impl ReportingService {
                                     old_value: event.old_value,
                                     new_value: event.new_value,
                                     status: event.status.unwrap_or_else(|| "unknown".to_string()),
+                                    corrective_change,
                                 });
                             }
                         }
@@ -491,6 +643,8 @@ impl ReportingService {
                 nodes_affected: nodes_affected.len() as i64,
                 resources_changed,
                 resources_failed,
+                corrective_changes,
+                intentional_changes: resources_changed - corrective_changes,
             },
             changes_by_type,
             changes,
@@ -498,14 +652,18 @@ impl ReportingService {
     }
 
     /// Generate drift detection report
-    async fn generate_drift_report(&self, config: &ReportQueryConfig) -> Result<DriftReport> {
+    async fn generate_drift_report(
+        &self,
+        organization_id: Uuid,
+        config: &ReportQueryConfig,
+    ) -> Result<DriftReport> {
         let puppetdb = self
             .puppetdb
             .as_ref()
             .ok_or_else(|| anyhow::anyhow!("PuppetDB not configured"))?;
 
         let baseline_repo = DriftBaselineRepository::new(&self.pool);
-        let baselines = baseline_repo.get_all().await?;
+        let baselines = baseline_repo.get_all(organization_id).await?;
 
         if baselines.is_empty() {
             return Ok(DriftReport {
@@ -523,7 +681,7 @@ impl ReportingService {
         }
 
         let baseline = &baselines[0];
-        let nodes = puppetdb.get_nodes().await?;
+        let nodes = self.scoped_nodes(organization_id, puppetdb, config).await?;
 
         let mut drifted_nodes = Vec::new();
         let mut total_drifted_facts = 0i64;
@@ -564,9 +722,7 @@ impl ReportingService {
                     if ignored_facts.contains(fact_name) {
                         continue;
                     }
-                    if config.ignore_volatile_facts
-                        && default_ignored.iter().any(|f| fact_name.contains(f))
-                    {
+                    if config.ignore_volatile_facts && is_volatile_fact(fact_name) {
                         continue;
                     }
 
@@ -621,6 +777,36 @@ impl ReportingService {
         })
     }
 
+    /// Corrective and intentional changes over the last `days` UTC days
+    ///
+    /// Returns the fleet totals, a per-day series and the `node_limit` nodes
+    /// with the most corrective changes.
+    pub async fn corrective_change_analytics(
+        &self,
+        days: u32,
+        node_limit: usize,
+    ) -> Result<CorrectiveChangeAnalytics> {
+        let puppetdb = self
+            .puppetdb
+            .as_ref()
+            .ok_or_else(|| anyhow::anyhow!("PuppetDB not configured"))?;
+
+        let today = Utc::now().date_naive();
+        let start = today - chrono::Duration::days(days.max(1) as i64 - 1);
+        let pql = format!(
+            r#"reports[certname, end_time, noop, metrics] {{ end_time >= "{}" }}"#,
+            start
+                .and_time(chrono::NaiveTime::MIN)
+                .and_utc()
+                .to_rfc3339()
+        );
+        let reports: Vec<ReportChangeMetrics> = puppetdb.query(&pql).await?;
+
+        Ok(aggregate_corrective_changes(
+            &reports, start, today, node_limit,
+        ))
+    }
+
     /// Export report data to specified format
     pub fn export_report(&self, result: &ReportResult, format: OutputFormat) -> Result<Vec<u8>> {
         match format {
@@ -637,6 +823,8 @@ impl ReportingService {
                 let pdf = self.export_to_pdf(result)?;
                 Ok(pdf)
             }
+            OutputFormat::Xlsx => report_export::to_xlsx(result),
+            OutputFormat::Html => Ok(report_export::to_html(result).into_bytes()),
         }
     }
 
@@ -770,6 +958,10 @@ impl ReportingService {
             "Unreported: {}\n",
             report.summary.unreported_count
         ));
+        content.push_str(&format!(
+            "In Maintenance: {}\n",
+            report.summary.maintenance_count
+        ));
         content.push_str(&format!(
             "Compliance Rate: {:.2}%\n\n",
             report.summary.compliance_rate
@@ -812,6 +1004,21 @@ impl ReportingService {
             report.summary.total_violations
         ));
 
+        if report.baselines.len() > 1 {
+            content.push_str("=== Baselines ===\n");
+            for b in &report.baselines {
+                content.push_str(&format!(
+                    "{}: {:.2}% compliant ({} of {} nodes), {} violations\n",
+                    b.baseline_name,
+                    b.summary.compliance_rate,
+                    b.summary.compliant_nodes,
+                    b.summary.total_nodes,
+                    b.summary.total_violations
+                ));
+            }
+            content.push('\n');
+        }
+
         if !report.violations.is_empty() {
             content.push_str("=== Violations ===\n");
             for v in report.violations.iter().take(30) {
@@ -852,9 +1059,17 @@ impl ReportingService {
             report.summary.resources_changed
         ));
         content.push_str(&format!(
-            "Resources Failed: {}\n\n",
+            "Resources Failed: {}\n",
             report.summary.resources_failed
         ));
+        content.push_str(&format!(
+            "Corrective Changes: {}\n",
+            report.summary.corrective_changes
+        ));
+        content.push_str(&format!(
+            "Intentional Changes: {}\n\n",
+            report.summary.intentional_changes
+        ));
 
         if !report.changes_by_type.is_empty() {
             content.push_str("=== Changes by Type ===\n");
@@ -924,6 +1139,10 @@ impl ReportingService {
                 csv.push_str(&format!("Failed,{}\n", report.summary.failed_count));
                 csv.push_str(&format!("Noop,{}\n", report.summary.noop_count));
                 csv.push_str(&format!("Unreported,{}\n", report.summary.unreported_count));
+                csv.push_str(&format!(
+                    "In Maintenance,{}\n",
+                    report.summary.maintenance_count
+                ));
                 csv.push_str(&format!(
                     "Compliance Rate,{:.2}%\n",
                     report.summary.compliance_rate
@@ -931,16 +1150,17 @@ impl ReportingService {
 
                 if let Some(nodes) = &report.nodes {
                     csv.push_str("\nNode Details\n");
-                    csv.push_str("Certname,Environment,Status,Last Report\n");
+                    csv.push_str("Certname,Environment,Status,Last Report,In Maintenance\n");
                     for node in nodes {
                         csv.push_str(&format!(
-                            "{},{},{},{}\n",
+                            "{},{},{},{},{}\n",
                             node.certname,
                             node.environment.as_deref().unwrap_or(""),
                             node.status,
                             node.last_report_at
                                 .map(|t| t.to_rfc3339())
-                                .unwrap_or_default()
+                                .unwrap_or_default(),
+                            node.in_maintenance
                         ));
                     }
                 }
@@ -962,12 +1182,31 @@ impl ReportingService {
                     report.summary.compliance_rate
                 ));
 
+                if !report.baselines.is_empty() {
+                    csv.push_str("\nBaselines\n");
+                    csv.push_str(
+                        "Baseline,Nodes,Compliant,Non-Compliant,Compliance Rate,Violations\n",
+                    );
+                    for b in &report.baselines {
+                        csv.push_str(&format!(
+                            "{},{},{},{},{:.2}%,{}\n",
+                            b.baseline_name,
+                            b.summary.total_nodes,
+                            b.summary.compliant_nodes,
+                            b.summary.non_compliant_nodes,
+                            b.summary.compliance_rate,
+                            b.summary.total_violations
+                        ));
+                    }
+                }
+
                 csv.push_str("\nViolations\n");
-                csv.push_str("Certname,Rule,Fact,Expected,Actual,Severity\n");
+                csv.push_str("Certname,Baseline,Rule,Fact,Expected,Actual,Severity\n");
                 for v in &report.violations {
                     csv.push_str(&format!(
-                        "{},{},{},{},{},{}\n",
+                        "{},{},{},{},{},{},{}\n",
                         v.certname,
+                        v.baseline_name.as_deref().unwrap_or(""),
                         v.rule_name,
                         v.fact_name,
                         v.expected_value,
@@ -995,18 +1234,27 @@ impl ReportingService {
                     "Resources Failed,{}\n",
                     report.summary.resources_failed
                 ));
+                csv.push_str(&format!(
+                    "Corrective Changes,{}\n",
+                    report.summary.corrective_changes
+                ));
+                csv.push_str(&format!(
+                    "Intentional Changes,{}\n",
+                    report.summary.intentional_changes
+                ));
 
                 csv.push_str("\nChanges\n");
-                csv.push_str("Certname,Time,Resource Type,Title,Property,Status\n");
+                csv.push_str("Certname,Time,Resource Type,Title,Property,Status,Corrective\n");
                 for c in &report.changes {
                     csv.push_str(&format!(
-                        "{},{},{},{},{},{}\n",
+                        "{},{},{},{},{},{},{}\n",
                         c.certname,
                         c.report_time.to_rfc3339(),
                         c.resource_type,
                         c.resource_title,
                         c.property.as_deref().unwrap_or(""),
-                        c.status
+                        c.status,
+                        c.corrective_change
                     ));
                 }
             }
@@ -1060,8 +1308,316 @@ struct ResourceEventData {
     old_value: Option<serde_json::Value>,
     new_value: Option<serde_json::Value>,
     status: Option<String>,
+    corrective_change: Option<bool>,
+}
+
+/// Report fields used by the corrective change analytics
+#[derive(Debug, serde::Deserialize)]
+struct ReportChangeMetrics {
+    certname: String,
+    end_time: Option<DateTime<Utc>>,
+    noop: Option<bool>,
+    metrics: Option<PuppetDbDataRef>,
+}
+
+impl ReportChangeMetrics {
+    /// (changed, corrective) resource counts
+    fn change_counts(&self) -> (i64, i64) {
+        let mut changed = 0;
+        let mut corrective = 0;
+        let metrics = self.metrics.as_ref().and_then(|m| m.data.as_ref());
+        for value in metrics.into_iter().flatten() {
+            let Ok(metric) = serde_json::from_value::<ReportMetric>(value.clone()) else {
+                continue;
+            };
+            if metric.category != "resources" {
+                continue;
+            }
+            match metric.name.as_str() {
+                "changed" => changed = metric.value as i64,
+                "corrective_change" => corrective = metric.value as i64,
+                _ => {}
+            }
+        }
+        (changed, corrective.min(changed))
+    }
+}
+
+/// Bucket the changes of non-noop reports between `start` and `end` (UTC
+/// days, inclusive)
+fn aggregate_corrective_changes(
+    reports: &[ReportChangeMetrics],
+    start: NaiveDate,
+    end: NaiveDate,
+    node_limit: usize,
+) -> CorrectiveChangeAnalytics {
+    let mut daily: BTreeMap<NaiveDate, (CorrectiveChangeDay, HashSet<&str>)> = BTreeMap::new();
+    let mut day = start;
+    while day <= end {
+        daily.insert(
+            day,
+            (
+                CorrectiveChangeDay {
+                    date: day.to_string(),
+                    corrective_changes: 0,
+                    intentional_changes: 0,
+                    nodes_corrected: 0,
+                },
+                HashSet::new(),
+            ),
+        );
+        day += chrono::Duration::days(1);
+    }
+
+    let mut summary = CorrectiveChangeSummary::default();
+    let mut nodes: HashMap<&str, NodeCorrectiveChanges> = HashMap::new();
+
+    for report in reports {
+        if report.noop == Some(true) {
+            continue;
+        }
+        let Some(end_time) = report.end_time else {
+            continue;
+        };
+        let Some((bucket, corrected)) = daily.get_mut(&end_time.date_naive()) else {
+            continue;
+        };
+        summary.reports_analyzed += 1;
+
+        let (changed, corrective) = report.change_counts();
+        if changed == 0 {
+            continue;
+        }
+        let intentional = changed - corrective;
+
+        bucket.corrective_changes += corrective;
+        bucket.intentional_changes += intentional;
+        summary.corrective_changes += corrective;
+        summary.intentional_changes += intentional;
+
+        let node = nodes
+            .entry(report.certname.as_str())
+            .or_insert_with(|| NodeCorrectiveChanges {
+                certname: report.certname.clone(),
+                corrective_changes: 0,
+                intentional_changes: 0,
+                reports_with_drift: 0,
+                last_corrective_at: None,
+            });
+        node.corrective_changes += corrective;
+        node.intentional_changes += intentional;
+        if corrective > 0 {
+            corrected.insert(report.certname.as_str());
+            node.reports_with_drift += 1;
+            node.last_corrective_at = node.last_corrective_at.max(Some(end_time));
+        }
+    }
+
+    let daily = daily
+        .into_values()
+        .map(|(mut day, corrected)| {
+            day.nodes_corrected = corrected.len() as i64;
+            day
+        })
+        .collect();
+
+    let mut nodes: Vec<NodeCorrectiveChanges> = nodes.into_values().collect();
+    summary.nodes_with_drift = nodes.iter().filter(|n| n.corrective_changes > 0).count() as i64;
+    nodes.sort_by(|a, b| {
+        b.corrective_changes
+            .cmp(&a.corrective_changes)
+            .then(b.intentional_changes.cmp(&a.intentional_changes))
+            .then_with(|| a.certname.cmp(&b.certname))
+    });
+    nodes.truncate(node_limit);
+
+    CorrectiveChangeAnalytics {
+        generated_at: Utc::now(),
+        days: ((end - start).num_days() + 1) as u32,
+        summary,
+        daily,
+        nodes,
+    }
+}
+
+/// Facts of a node, by name
+struct NodeFacts {
+    certname: String,
+    facts: HashMap<String, serde_json::Value>,
+}
+
+/// Result of evaluating one compliance baseline
+struct BaselineEvaluation {
+    summary: BaselineComplianceSummary,
+    /// Nodes the baseline was evaluated against
+    certnames: Vec<String>,
+    violations: Vec<ComplianceViolation>,
+}
+
+/// Evaluate a baseline's rules against the nodes it covers
+///
+/// A missing fact violates the rule.
+fn evaluate_baseline(baseline: &ComplianceBaseline, nodes: &[&NodeFacts]) -> BaselineEvaluation {
+    let mut violations = Vec::new();
+    let mut non_compliant_nodes = 0i64;
+
+    for node in nodes {
+        let violations_before = violations.len();
+        for rule in &baseline.rules {
+            let actual_value = node.facts.get(&rule.fact_name);
+            let is_compliant = match actual_value {
+                Some(actual) => check_compliance(&rule.operator, &rule.expected_value, actual),
+                None => false,
+            };
+
+            if !is_compliant {
+                violations.push(ComplianceViolation {
+                    certname: node.certname.clone(),
+                    baseline_id: Some(baseline.id),
+                    baseline_name: Some(baseline.name.clone()),
+                    rule_id: rule.id.clone(),
+                    rule_name: rule.name.clone(),
+                    fact_name: rule.fact_name.clone(),
+                    expected_value: rule.expected_value.clone(),
+                    actual_value: actual_value.cloned().unwrap_or(serde_json::Value::Null),
+                    severity: rule.severity,
+                    remediation: rule.remediation.clone(),
+                });
+            }
+        }
+        if violations.len() > violations_before {
+            non_compliant_nodes += 1;
+        }
+    }
+
+    BaselineEvaluation {
+        summary: BaselineComplianceSummary {
+            baseline_id: baseline.id,
+            baseline_name: baseline.name.clone(),
+            severity_level: baseline.severity_level,
+            node_group_ids: baseline.node_group_ids.clone(),
+            summary: compliance_summary(nodes.len() as i64, non_compliant_nodes, violations.len()),
+            by_severity: severity_breakdown(&violations),
+        },
+        certnames: nodes.iter().map(|n| n.certname.clone()).collect(),
+        violations,
+    }
+}
+
+/// Combine baseline results into a report
+///
+/// A node counts once in the totals, and is compliant only when it passes
+/// every baseline that covers it.
+fn combine_baseline_evaluations(evaluations: Vec<BaselineEvaluation>) -> ComplianceReport {
+    let evaluated: HashSet<&str> = evaluations
+        .iter()
+        .flat_map(|e| e.certnames.iter().map(String::as_str))
+        .collect();
+    let total_nodes = evaluated.len() as i64;
+
+    let baseline_name = evaluations
+        .iter()
+        .map(|e| e.summary.baseline_name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let mut baselines = Vec::with_capacity(evaluations.len());
+    let mut violations = Vec::new();
+    for evaluation in evaluations {
+        baselines.push(evaluation.summary);
+        violations.extend(evaluation.violations);
+    }
+
+    let non_compliant_nodes = violations
+        .iter()
+        .map(|v| v.certname.as_str())
+        .collect::<HashSet<_>>()
+        .len() as i64;
+
+    ComplianceReport {
+        generated_at: Utc::now(),
+        baseline_name,
+        summary: compliance_summary(total_nodes, non_compliant_nodes, violations.len()),
+        by_severity: severity_breakdown(&violations),
+        baselines,
+        violations,
+    }
+}
+
+fn compliance_summary(
+    total_nodes: i64,
+    non_compliant_nodes: i64,
+    total_violations: usize,
+) -> ComplianceSummary {
+    let compliant_nodes = total_nodes - non_compliant_nodes;
+    let compliance_rate = if total_nodes > 0 {
+        (compliant_nodes as f64 / total_nodes as f64) * 100.0
+    } else {
+        100.0
+    };
+
+    ComplianceSummary {
+        total_nodes,
+        compliant_nodes,
+        non_compliant_nodes,
+        compliance_rate,
+        total_violations: total_violations as i64,
+    }
+}
+
+/// Violations and affected nodes per severity, most severe first
+fn severity_breakdown(violations: &[ComplianceViolation]) -> Vec<SeverityBreakdown> {
+    [
+        SeverityLevel::Critical,
+        SeverityLevel::High,
+        SeverityLevel::Medium,
+        SeverityLevel::Low,
+    ]
+    .into_iter()
+    .filter_map(|severity| {
+        let matching: Vec<&ComplianceViolation> = violations
+            .iter()
+            .filter(|v| v.severity == severity)
+            .collect();
+        if matching.is_empty() {
+            return None;
+        }
+        let affected_nodes = matching
+            .iter()
+            .map(|v| v.certname.as_str())
+            .collect::<HashSet<_>>()
+            .len() as i64;
+        Some(SeverityBreakdown {
+            severity,
+            violation_count: matching.len() as i64,
+            affected_nodes,
+        })
+    })
+    .collect()
 }
 
+/// Operators understood by [`check_compliance`]
+pub(crate) const COMPLIANCE_OPERATORS: &[&str] = &[
+    "=",
+    "==",
+    "equals",
+    "!=",
+    "not_equals",
+    ">",
+    "greater_than",
+    ">=",
+    "greater_than_or_equal",
+    "<",
+    "less_than",
+    "<=",
+    "less_than_or_equal",
+    "~",
+    "matches",
+    "regex",
+    "in",
+    "not_in",
+];
+
 /// Check if a fact value complies with a rule
 fn check_compliance(
     operator: &str,
@@ -1159,4 +1715,174 @@ mod tests {
         assert!(check_compliance("~", &expected, &actual));
         assert!(check_compliance("regex", &expected, &actual));
     }
+
+    fn change_report(
+        certname: &str,
+        end_time: &str,
+        noop: bool,
+        changed: i64,
+        corrective: i64,
+    ) -> ReportChangeMetrics {
+        serde_json::from_value(serde_json::json!({
+            "certname": certname,
+            "end_time": end_time,
+            "noop": noop,
+            "metrics": {
+                "data": [
+                    {"category": "resources", "name": "changed", "value": changed},
+                    {"category": "resources", "name": "corrective_change", "value": corrective},
+                    {"category": "resources", "name": "total", "value": 100},
+                    {"category": "events", "name": "success", "value": changed},
+                ],
+                "href": "/pdb/query/v4/reports/x/metrics"
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_aggregate_corrective_changes() {
+        let start = NaiveDate::from_ymd_opt(2025, 6, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2025, 6, 3).unwrap();
+        let reports = vec![
+            change_report("a.example.com", "2025-06-01T10:00:00Z", false, 5, 2),
+            change_report("a.example.com", "2025-06-03T10:00:00Z", false, 1, 1),
+            change_report("b.example.com", "2025-06-01T12:00:00Z", false, 3, 0),
+            change_report("c.example.com", "2025-06-01T12:00:00Z", false, 0, 0),
+            // Noop runs and reports outside the window are ignored
+            change_report("d.example.com", "2025-06-02T12:00:00Z", true, 4, 4),
+            change_report("e.example.com", "2025-05-31T23:59:59Z", false, 4, 4),
+        ];
+
+        let analytics = aggregate_corrective_changes(&reports, start, end, 10);
+        assert_eq!(analytics.days, 3);
+        assert_eq!(
+            analytics.summary,
+            CorrectiveChangeSummary {
+                reports_analyzed: 4,
+                corrective_changes: 3,
+                intentional_changes: 6,
+                nodes_with_drift: 1,
+            }
+        );
+
+        let days: Vec<_> = analytics
+            .daily
+            .iter()
+            .map(|d| {
+                (
+                    d.date.as_str(),
+                    d.corrective_changes,
+                    d.intentional_changes,
+                    d.nodes_corrected,
+                )
+            })
+            .collect();
+        assert_eq!(
+            days,
+            vec![
+                ("2025-06-01", 2, 6, 1),
+                ("2025-06-02", 0, 0, 0),
+                ("2025-06-03", 1, 0, 1),
+            ]
+        );
+
+        let certnames: Vec<_> = analytics
+            .nodes
+            .iter()
+            .map(|n| n.certname.as_str())
+            .collect();
+        assert_eq!(certnames, vec!["a.example.com", "b.example.com"]);
+        assert_eq!(analytics.nodes[0].reports_with_drift, 2);
+        assert_eq!(
+            analytics.nodes[0].last_corrective_at.unwrap().to_rfc3339(),
+            "2025-06-03T10:00:00+00:00"
+        );
+
+        let limited = aggregate_corrective_changes(&reports, start, end, 1);
+        assert_eq!(limited.nodes.len(), 1);
+        assert_eq!(limited.summary.nodes_with_drift, 1);
+    }
+
+    fn baseline(name: &str, rules: &[(&str, &str, SeverityLevel)]) -> ComplianceBaseline {
+        ComplianceBaseline {
+            id: Uuid::new_v4(),
+            organization_id: Uuid::nil(),
+            name: name.to_string(),
+            description: None,
+            rules: rules
+                .iter()
+                .map(|(fact, expected, severity)| crate::models::ComplianceRule {
+                    id: fact.to_string(),
+                    name: fact.to_string(),
+                    description: None,
+                    fact_name: fact.to_string(),
+                    operator: "=".to_string(),
+                    expected_value: serde_json::json!(expected),
+                    severity: *severity,
+                    references: vec![],
+                    remediation: None,
+                })
+                .collect(),
+            severity_level: SeverityLevel::Medium,
+            node_group_ids: vec![],
+            rule_pack_id: None,
+            rule_pack_version: None,
+            created_by: Uuid::nil(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn node(certname: &str, facts: &[(&str, &str)]) -> NodeFacts {
+        NodeFacts {
+            certname: certname.to_string(),
+            facts: facts
+                .iter()
+                .map(|(name, value)| (name.to_string(), serde_json::json!(value)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_multiple_baselines() {
+        let os = baseline("OS", &[("os", "linux", SeverityLevel::High)]);
+        let selinux = baseline(
+            "SELinux",
+            &[("selinux", "enforcing", SeverityLevel::Critical)],
+        );
+        let web1 = node("web1", &[("os", "linux"), ("selinux", "permissive")]);
+        let web2 = node("web2", &[("os", "linux"), ("selinux", "enforcing")]);
+        // db1 lacks the selinux fact but is only covered by the OS baseline
+        let db1 = node("db1", &[("os", "windows")]);
+
+        let report = combine_baseline_evaluations(vec![
+            evaluate_baseline(&os, &[&web1, &web2, &db1]),
+            evaluate_baseline(&selinux, &[&web1, &web2]),
+        ]);
+
+        assert_eq!(report.baseline_name, "OS, SELinux");
+        assert_eq!(report.summary.total_nodes, 3);
+        assert_eq!(report.summary.compliant_nodes, 1);
+        assert_eq!(report.summary.total_violations, 2);
+
+        assert_eq!(report.baselines.len(), 2);
+        assert_eq!(report.baselines[0].summary.non_compliant_nodes, 1);
+        assert_eq!(report.baselines[1].summary.total_nodes, 2);
+        assert_eq!(report.baselines[1].by_severity.len(), 1);
+        assert_eq!(
+            report.baselines[1].by_severity[0].severity,
+            SeverityLevel::Critical
+        );
+
+        let severities: Vec<_> = report.by_severity.iter().map(|b| b.severity).collect();
+        assert_eq!(
+            severities,
+            vec![SeverityLevel::Critical, SeverityLevel::High]
+        );
+        assert_eq!(
+            report.violations[1].baseline_name.as_deref(),
+            Some("SELinux")
+        );
+    }
 }
@@ -0,0 +1,186 @@
+//! Compliance rule packs
+//!
+//! A rule pack is a versioned bundle of compliance rules, usually derived from
+//! a published benchmark such as CIS or DISA STIG, written in YAML or JSON:
+//!
+//! ```yaml
+//! id: cis-rhel9-l1
+//! name: CIS Red Hat Enterprise Linux 9 - Level 1
+//! version: 2.0.0
+//! framework: CIS
+//! references: [https://www.cisecurity.org/benchmark/red_hat_linux]
+//! rules:
+//!   - id: "1.6.1.3"
+//!     name: SELinux is enforcing
+//!     fact_name: os.selinux.current_mode
+//!     operator: "="
+//!     expected_value: enforcing
+//!     severity: high
+//!     references: ["CIS 1.6.1.3"]
+//!     remediation: Set SELINUX=enforcing in /etc/selinux/config and reboot
+//! ```
+
+use std::collections::HashSet;
+
+use sha2::{Digest, Sha256};
+
+use crate::models::{RulePackDocument, RulePackFormat};
+use crate::services::reporting::COMPLIANCE_OPERATORS;
+use crate::utils::AppError;
+
+/// Parse and validate a rule pack document
+///
+/// When `format` is unset, content starting with `{` is read as JSON and
+/// anything else as YAML.
+pub fn parse_rule_pack(
+    content: &str,
+    format: Option<RulePackFormat>,
+) -> Result<RulePackDocument, AppError> {
+    let format = format.unwrap_or_else(|| {
+        if content.trim_start().starts_with('{') {
+            RulePackFormat::Json
+        } else {
+            RulePackFormat::Yaml
+        }
+    });
+
+    let doc: RulePackDocument = match format {
+        RulePackFormat::Json => serde_json::from_str(content)
+            .map_err(|e| AppError::bad_request(format!("Invalid rule pack JSON: {}", e)))?,
+        RulePackFormat::Yaml => serde_norway::from_str(content)
+            .map_err(|e| AppError::bad_request(format!("Invalid rule pack YAML: {}", e)))?,
+    };
+
+    validate_rule_pack(&doc)?;
+    Ok(doc)
+}
+
+/// SHA-256 of a rule pack document, hex encoded
+pub fn rule_pack_checksum(content: &str) -> String {
+    hex::encode(Sha256::digest(content.as_bytes()))
+}
+
+fn validate_rule_pack(doc: &RulePackDocument) -> Result<(), AppError> {
+    for (field, value) in [
+        ("id", &doc.id),
+        ("name", &doc.name),
+        ("version", &doc.version),
+    ] {
+        if value.trim().is_empty() {
+            return Err(AppError::validation(format!(
+                "Rule pack {} is required",
+                field
+            )));
+        }
+    }
+    if doc.rules.is_empty() {
+        return Err(AppError::validation("Rule pack has no rules"));
+    }
+
+    let mut rule_ids = HashSet::new();
+    for rule in &doc.rules {
+        if rule.id.trim().is_empty() || rule.fact_name.trim().is_empty() {
+            return Err(AppError::validation(format!(
+                "Rule '{}' needs an id and a fact_name",
+                rule.name
+            )));
+        }
+        if !rule_ids.insert(rule.id.as_str()) {
+            return Err(AppError::validation(format!(
+                "Duplicate rule id '{}'",
+                rule.id
+            )));
+        }
+        if !COMPLIANCE_OPERATORS.contains(&rule.operator.as_str()) {
+            return Err(AppError::validation(format!(
+                "Rule '{}' has unknown operator '{}'",
+                rule.id, rule.operator
+            )));
+        }
+        if matches!(rule.operator.as_str(), "~" | "matches" | "regex") {
+            let pattern = rule.expected_value.as_str().ok_or_else(|| {
+                AppError::validation(format!("Rule '{}' needs a string pattern", rule.id))
+            })?;
+            regex::Regex::new(pattern).map_err(|e| {
+                AppError::validation(format!("Rule '{}' has an invalid pattern: {}", rule.id, e))
+            })?;
+        }
+        if matches!(rule.operator.as_str(), "in" | "not_in") && !rule.expected_value.is_array() {
+            return Err(AppError::validation(format!(
+                "Rule '{}' needs a list of values",
+                rule.id
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SeverityLevel;
+
+    const YAML_PACK: &str = r#"
+id: cis-rhel9-l1
+name: CIS RHEL 9 Level 1
+version: 2.0.0
+framework: CIS
+rules:
+  - id: "1.6.1.3"
+    name: SELinux is enforcing
+    fact_name: os.selinux.current_mode
+    operator: "="
+    expected_value: enforcing
+    severity: high
+    references: ["CIS 1.6.1.3"]
+    remediation: Set SELINUX=enforcing
+  - id: "5.2.1"
+    name: Supported OS release
+    fact_name: os.release.major
+    operator: in
+    expected_value: ["9"]
+    severity: medium
+"#;
+
+    #[test]
+    fn test_parse_yaml_pack() {
+        let doc = parse_rule_pack(YAML_PACK, None).unwrap();
+        assert_eq!(doc.id, "cis-rhel9-l1");
+        assert_eq!(doc.framework.as_deref(), Some("CIS"));
+        assert_eq!(doc.rules.len(), 2);
+        assert_eq!(doc.rules[0].severity, SeverityLevel::High);
+        assert_eq!(doc.rules[0].references, vec!["CIS 1.6.1.3"]);
+        assert_eq!(
+            doc.rules[0].remediation.as_deref(),
+            Some("Set SELINUX=enforcing")
+        );
+        assert!(doc.rules[1].references.is_empty());
+    }
+
+    #[test]
+    fn test_parse_json_pack() {
+        let yaml: serde_json::Value = serde_norway::from_str(YAML_PACK).unwrap();
+        let json = serde_json::to_string(&yaml).unwrap();
+        let doc = parse_rule_pack(&json, None).unwrap();
+        assert_eq!(doc.version, "2.0.0");
+        assert_eq!(doc.rules[1].expected_value, serde_json::json!(["9"]));
+    }
+
+    #[test]
+    fn test_rejects_invalid_packs() {
+        let unknown_operator = YAML_PACK.replace("operator: in", "operator: contains");
+        assert!(parse_rule_pack(&unknown_operator, None).is_err());
+
+        let duplicate_id = YAML_PACK.replace("\"5.2.1\"", "\"1.6.1.3\"");
+        assert!(parse_rule_pack(&duplicate_id, None).is_err());
+
+        let bad_pattern = YAML_PACK
+            .replace("operator: \"=\"", "operator: regex")
+            .replace("expected_value: enforcing", "expected_value: \"(\"");
+        assert!(parse_rule_pack(&bad_pattern, None).is_err());
+
+        assert!(parse_rule_pack("{\"id\": \"x\"}", None).is_err());
+        assert!(parse_rule_pack(YAML_PACK, Some(RulePackFormat::Json)).is_err());
+    }
+}
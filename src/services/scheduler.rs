@@ -11,19 +11,57 @@ use std::str::FromStr;
 use std::sync::Arc;
 use tracing::{error, info, warn};
 
-use crate::db::repository::{ReportScheduleRepository, SavedReportRepository};
-use crate::models::{ExecuteReportRequest, ReportSchedule};
-use crate::services::{PuppetDbClient, ReportingService};
+use crate::db::repository::{
+    ReportExecutionRepository, ReportScheduleRepository, SavedReportRepository,
+};
+use crate::db::ReportEmailTemplateRepository;
+use crate::models::{
+    DeliveryResult, DeliveryStatus, ExecuteReportRequest, ExecutionStatus, ReportExecution,
+    ReportResult, ReportSchedule, SavedReport,
+};
+use crate::services::mailer::Mailer;
+use crate::services::report_delivery::{output_filename, ReportDelivery};
+use crate::services::report_email;
+use crate::services::report_output::ReportOutputStore;
+use crate::services::secrets::SecretsResolver;
+use crate::services::settings_encryption::SettingsCipher;
+use crate::services::{PuppetDbClient, PuppetDbRegistry, ReportingService};
 
 /// Report scheduler that executes due scheduled reports
 pub struct ReportScheduler {
     pool: SqlitePool,
     puppetdb: Option<Arc<PuppetDbClient>>,
+    tenants: PuppetDbRegistry,
+    delivery: ReportDelivery,
+    output_store: Option<ReportOutputStore>,
+    cipher: Option<SettingsCipher>,
+    secrets: Option<Arc<SecretsResolver>>,
 }
 
 impl ReportScheduler {
-    pub fn new(pool: SqlitePool, puppetdb: Option<Arc<PuppetDbClient>>) -> Self {
-        Self { pool, puppetdb }
+    /// `cipher` decrypts stored delivery targets and the SMTP password;
+    /// `secrets` resolves credentials written as secret references
+    pub fn new(
+        pool: SqlitePool,
+        puppetdb: Option<Arc<PuppetDbClient>>,
+        cipher: Option<SettingsCipher>,
+        secrets: Option<Arc<SecretsResolver>>,
+    ) -> Self {
+        Self {
+            pool,
+            puppetdb,
+            tenants: PuppetDbRegistry::default(),
+            delivery: ReportDelivery::new(secrets.clone()),
+            output_store: None,
+            cipher,
+            secrets,
+        }
+    }
+
+    /// Offload large execution outputs to files
+    pub fn with_output_store(mut self, store: ReportOutputStore) -> Self {
+        self.output_store = Some(store);
+        self
     }
 
     /// Run all due scheduled reports
@@ -31,9 +69,8 @@ impl ReportScheduler {
     /// This method checks all enabled schedules and executes any that are due.
     /// It updates the last_run_at and next_run_at timestamps after execution.
     pub async fn run_due_schedules(&self) -> Result<Vec<ScheduleExecutionResult>> {
-        let schedule_repo = ReportScheduleRepository::new(&self.pool);
+        let schedule_repo = ReportScheduleRepository::new(&self.pool, self.cipher.as_ref());
         let report_repo = SavedReportRepository::new(&self.pool);
-        let reporting_service = ReportingService::new(self.pool.clone(), self.puppetdb.clone());
 
         let schedules = schedule_repo.get_due().await?;
         let mut results = Vec::new();
@@ -42,7 +79,7 @@ impl ReportScheduler {
 
         for schedule in schedules {
             let result = self
-                .execute_schedule(&schedule, &report_repo, &reporting_service, &schedule_repo)
+                .execute_schedule(&schedule, &report_repo, &schedule_repo)
                 .await;
 
             results.push(result);
@@ -56,7 +93,6 @@ impl ReportScheduler {
         &self,
         schedule: &ReportSchedule,
         report_repo: &SavedReportRepository<'_>,
-        reporting_service: &ReportingService,
         schedule_repo: &ReportScheduleRepository<'_>,
     ) -> ScheduleExecutionResult {
         info!(
@@ -65,7 +101,10 @@ impl ReportScheduler {
         );
 
         // Get the associated report
-        let report = match report_repo.get_by_id(schedule.report_id).await {
+        let report = match report_repo
+            .get_by_id(schedule.organization_id, schedule.report_id)
+            .await
+        {
             Ok(Some(r)) => r,
             Ok(None) => {
                 warn!(
@@ -77,6 +116,7 @@ impl ReportScheduler {
                     success: false,
                     error: Some("Report not found".to_string()),
                     execution_time_ms: 0,
+                    delivery_status: None,
                 };
             }
             Err(e) => {
@@ -86,10 +126,21 @@ impl ReportScheduler {
                     success: false,
                     error: Some(e.to_string()),
                     execution_time_ms: 0,
+                    delivery_status: None,
                 };
             }
         };
 
+        // Reports run against the PuppetDB of the schedule's organization
+        let puppetdb = self
+            .tenants
+            .resolve(&self.pool, schedule.organization_id, self.puppetdb.clone())
+            .await;
+        let mut reporting_service = ReportingService::new(self.pool.clone(), puppetdb);
+        if let Some(store) = &self.output_store {
+            reporting_service = reporting_service.with_output_store(store.clone());
+        }
+
         let start = std::time::Instant::now();
 
         // Execute the report
@@ -99,7 +150,7 @@ impl ReportScheduler {
         };
 
         let result = reporting_service
-            .execute_report(&report, &execute_req, None)
+            .execute_report(&report, &execute_req, None, Some(schedule.id))
             .await;
 
         let execution_time_ms = start.elapsed().as_millis() as i32;
@@ -123,11 +174,21 @@ impl ReportScheduler {
                     execution.id,
                     execution.row_count.unwrap_or(0)
                 );
+                let delivery_status = if execution.status == ExecutionStatus::Completed
+                    && (!schedule.delivery_targets.is_empty()
+                        || !email_recipients(schedule).is_empty())
+                {
+                    self.deliver_output(schedule, &report, &execution, &reporting_service)
+                        .await
+                } else {
+                    None
+                };
                 ScheduleExecutionResult {
                     schedule_id: schedule.id,
                     success: true,
                     error: None,
                     execution_time_ms,
+                    delivery_status,
                 }
             }
             Err(e) => {
@@ -137,28 +198,178 @@ impl ReportScheduler {
                     success: false,
                     error: Some(e.to_string()),
                     execution_time_ms,
+                    delivery_status: None,
                 }
             }
         }
     }
 
+    /// Export an execution's output, publish it to the schedule's delivery
+    /// targets and email it to the schedule's recipients, recording the
+    /// outcome on the execution
+    async fn deliver_output(
+        &self,
+        schedule: &ReportSchedule,
+        report: &SavedReport,
+        execution: &ReportExecution,
+        reporting_service: &ReportingService,
+    ) -> Option<DeliveryStatus> {
+        let exported = ReportOutputStore::load(execution)
+            .await
+            .and_then(|data| data.ok_or_else(|| anyhow::anyhow!("Execution has no output data")))
+            .and_then(|data| {
+                serde_json::from_value::<ReportResult>(data)
+                    .map_err(|e| anyhow::anyhow!("Failed to parse report output: {}", e))
+            })
+            .and_then(|result| {
+                let data = reporting_service.export_report(&result, schedule.output_format)?;
+                Ok((result, data))
+            });
+
+        let (result, data) = match exported {
+            Ok(exported) => exported,
+            Err(e) => {
+                error!(
+                    "Failed to export execution {} for delivery: {}",
+                    execution.id, e
+                );
+                return None;
+            }
+        };
+
+        let filename = output_filename(&report.name, execution.started_at, schedule.output_format);
+        let mut results = self
+            .delivery
+            .deliver_all(
+                &schedule.delivery_targets,
+                &filename,
+                schedule.output_format.content_type(),
+                &data,
+            )
+            .await;
+        let recipients = email_recipients(schedule);
+        if !recipients.is_empty() {
+            results.push(
+                self.email_output(
+                    schedule,
+                    report,
+                    execution,
+                    &result,
+                    &filename,
+                    &data,
+                    &recipients,
+                )
+                .await,
+            );
+        }
+
+        let exec_repo = ReportExecutionRepository::new(&self.pool);
+        if let Err(e) = exec_repo.record_delivery(execution.id, &results).await {
+            error!(
+                "Failed to record delivery of execution {}: {}",
+                execution.id, e
+            );
+        }
+
+        let status = DeliveryStatus::from_results(&results);
+        if let Some(status) = status {
+            info!(
+                "Schedule {} delivery {} ({} targets)",
+                schedule.id,
+                status.as_str(),
+                results.len()
+            );
+        }
+        status
+    }
+
+    /// Email the summary of an execution to the schedule's recipients, with
+    /// the export attached
+    #[allow(clippy::too_many_arguments)]
+    async fn email_output(
+        &self,
+        schedule: &ReportSchedule,
+        report: &SavedReport,
+        execution: &ReportExecution,
+        result: &ReportResult,
+        filename: &str,
+        data: &[u8],
+        recipients: &[String],
+    ) -> DeliveryResult {
+        let sent = async {
+            let template = ReportEmailTemplateRepository::new(&self.pool)
+                .resolve(schedule.organization_id, report.report_type)
+                .await?;
+            let context = report_email::email_context(
+                report,
+                execution,
+                result,
+                &report_email::Attachment {
+                    filename,
+                    size: data.len(),
+                },
+            );
+            let email = report_email::render(template.as_ref(), &context)?;
+            Mailer::new(self.pool.clone(), self.cipher.clone(), self.secrets.clone())
+                .send_with_attachment(
+                    recipients,
+                    &email.subject,
+                    &email.text,
+                    &email.html,
+                    filename,
+                    schedule.output_format.content_type(),
+                    data,
+                )
+                .await
+        }
+        .await;
+
+        let target = recipients.join(", ");
+        let error = match sent {
+            Ok(()) => None,
+            Err(e) => {
+                warn!("Failed to email {} to {}: {:#}", filename, target, e);
+                Some(format!("{:#}", e))
+            }
+        };
+        DeliveryResult {
+            target_type: "email".to_string(),
+            target,
+            success: error.is_none(),
+            location: None,
+            error,
+            delivered_at: Utc::now(),
+        }
+    }
+
     /// Run a specific schedule by ID
     pub async fn run_schedule(&self, schedule_id: uuid::Uuid) -> Result<ScheduleExecutionResult> {
-        let schedule_repo = ReportScheduleRepository::new(&self.pool);
+        let schedule_repo = ReportScheduleRepository::new(&self.pool, self.cipher.as_ref());
         let report_repo = SavedReportRepository::new(&self.pool);
-        let reporting_service = ReportingService::new(self.pool.clone(), self.puppetdb.clone());
 
         let schedule = schedule_repo
-            .get_by_id(schedule_id)
+            .find_by_id(schedule_id)
             .await?
             .ok_or_else(|| anyhow::anyhow!("Schedule not found"))?;
 
         Ok(self
-            .execute_schedule(&schedule, &report_repo, &reporting_service, &schedule_repo)
+            .execute_schedule(&schedule, &report_repo, &schedule_repo)
             .await)
     }
 }
 
+/// Non-blank email recipients of a schedule
+fn email_recipients(schedule: &ReportSchedule) -> Vec<String> {
+    schedule
+        .email_recipients
+        .iter()
+        .flatten()
+        .map(|r| r.trim())
+        .filter(|r| !r.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 /// Result of executing a scheduled report
 #[derive(Debug)]
 pub struct ScheduleExecutionResult {
@@ -166,6 +377,8 @@ pub struct ScheduleExecutionResult {
     pub success: bool,
     pub error: Option<String>,
     pub execution_time_ms: i32,
+    /// Outcome of publishing the output to the schedule's delivery targets
+    pub delivery_status: Option<DeliveryStatus>,
 }
 
 /// Calculate the next run time for a cron expression
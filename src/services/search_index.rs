@@ -0,0 +1,326 @@
+//! Global search index maintenance
+//!
+//! Builds the documents of the full-text search index from PuppetDB (nodes,
+//! their key facts, latest reports and applied classes) and the database
+//! (groups, users, saved reports). The cache sync job calls [`SearchIndexer`]
+//! periodically; admins can also trigger a rebuild through the API.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+use crate::db::repository::{GroupRepository, SavedReportRepository};
+use crate::db::search_repository::SearchDocument;
+use crate::db::{DbPool, OrganizationRepository, SearchRepository};
+use crate::models::SearchKind;
+use crate::services::puppetdb::PuppetDbClient;
+use crate::services::puppetdb_registry::PuppetDbRegistry;
+use crate::services::AuthService;
+
+/// Facts whose values are searchable for every node
+const INDEXED_FACTS: &[&str] = &[
+    "fqdn",
+    "hostname",
+    "domain",
+    "ipaddress",
+    "ipaddress6",
+    "os",
+    "kernel",
+    "kernelrelease",
+    "virtual",
+    "serialnumber",
+    "productname",
+];
+
+/// Classes PuppetDB reports for every catalog
+const IMPLICIT_CLASSES: &[&str] = &["main", "settings"];
+
+#[derive(Debug, Deserialize)]
+struct FactRow {
+    certname: String,
+    value: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportRow {
+    hash: String,
+    certname: String,
+    status: Option<String>,
+    environment: Option<String>,
+    configuration_version: Option<String>,
+    puppet_version: Option<String>,
+    receive_time: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClassRow {
+    title: String,
+    count: u64,
+}
+
+/// Rebuilds the search index of organizations
+#[derive(Clone)]
+pub struct SearchIndexer {
+    pool: DbPool,
+    puppetdb: Option<Arc<PuppetDbClient>>,
+    tenants: PuppetDbRegistry,
+}
+
+impl SearchIndexer {
+    pub fn new(
+        pool: DbPool,
+        puppetdb: Option<Arc<PuppetDbClient>>,
+        tenants: PuppetDbRegistry,
+    ) -> Self {
+        Self {
+            pool,
+            puppetdb,
+            tenants,
+        }
+    }
+
+    /// Rebuild the index of every organization
+    ///
+    /// An organization that fails to index keeps its previous documents.
+    pub async fn rebuild_all(&self) -> Result<usize> {
+        let organizations = OrganizationRepository::new(&self.pool).list().await?;
+        let mut total = 0;
+        for organization in organizations {
+            match self.rebuild(organization.id).await {
+                Ok(count) => total += count,
+                Err(e) => warn!(
+                    "Failed to rebuild search index for organization {}: {:#}",
+                    organization.id, e
+                ),
+            }
+        }
+        Ok(total)
+    }
+
+    /// Rebuild the index of one organization and return its document count
+    pub async fn rebuild(&self, organization_id: Uuid) -> Result<usize> {
+        let mut documents = Vec::new();
+
+        let groups = GroupRepository::new(&self.pool)
+            .get_all(organization_id)
+            .await?;
+        // Class name -> groups declaring it
+        let mut classes: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for group in &groups {
+            let class_names: Vec<String> = group
+                .classes
+                .as_object()
+                .map(|classes| classes.keys().cloned().collect())
+                .unwrap_or_default();
+            for class in &class_names {
+                classes
+                    .entry(class.to_lowercase())
+                    .or_default()
+                    .push(group.name.clone());
+            }
+            documents.push(SearchDocument {
+                kind: SearchKind::Group,
+                ref_id: group.id.to_string(),
+                owner_id: None,
+                title: group.name.clone(),
+                body: join_text([
+                    group.description.clone(),
+                    group.environment.clone(),
+                    Some(class_names.join(" ")),
+                ]),
+            });
+        }
+
+        let client = self
+            .tenants
+            .resolve(&self.pool, organization_id, self.puppetdb.clone())
+            .await;
+        let mut applied_classes: BTreeMap<String, u64> = BTreeMap::new();
+        if let Some(client) = client {
+            documents.extend(node_documents(&client).await?);
+            documents.extend(report_documents(&client).await?);
+            applied_classes = class_node_counts(&client).await?;
+        }
+
+        for name in applied_classes.keys() {
+            classes.entry(name.clone()).or_default();
+        }
+        for (name, groups) in classes {
+            let nodes = applied_classes.get(&name).copied().unwrap_or(0);
+            documents.push(SearchDocument {
+                kind: SearchKind::Class,
+                body: join_text([Some(groups.join(" ")), Some(format!("{} nodes", nodes))]),
+                ref_id: name.clone(),
+                owner_id: None,
+                title: name,
+            });
+        }
+
+        let users = AuthService::new(self.pool.clone())
+            .list_users_in_org(organization_id)
+            .await?;
+        documents.extend(users.into_iter().map(|user| SearchDocument {
+            kind: SearchKind::User,
+            ref_id: user.id.to_string(),
+            owner_id: None,
+            title: user.username,
+            body: join_text([Some(user.email), Some(user.role)]),
+        }));
+
+        let saved_reports = SavedReportRepository::new(&self.pool)
+            .get_all(organization_id)
+            .await?;
+        documents.extend(saved_reports.into_iter().map(|report| SearchDocument {
+            kind: SearchKind::SavedReport,
+            ref_id: report.id.to_string(),
+            owner_id: (!report.is_public).then_some(report.created_by),
+            title: report.name,
+            body: join_text([
+                report.description,
+                Some(report.report_type.as_str().to_string()),
+            ]),
+        }));
+
+        SearchRepository::new(&self.pool)
+            .replace_organization(organization_id, &documents)
+            .await?;
+        debug!(
+            "Search index: {} documents for organization {}",
+            documents.len(),
+            organization_id
+        );
+        Ok(documents.len())
+    }
+}
+
+/// Active nodes with their environment, status and key facts
+async fn node_documents(client: &PuppetDbClient) -> Result<Vec<SearchDocument>> {
+    let nodes = client.get_nodes().await?;
+
+    let fact_names = INDEXED_FACTS
+        .iter()
+        .map(|name| format!("\"{}\"", name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let pql = format!("facts[certname, value] {{ name in [{}] }}", fact_names);
+    let rows: Vec<FactRow> = client.query(&pql).await?;
+    let mut facts: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for row in rows {
+        flatten_value(&row.value, facts.entry(row.certname).or_default());
+    }
+
+    Ok(nodes
+        .into_iter()
+        .filter(|node| node.deactivated.is_none())
+        .map(|node| {
+            let values = facts.remove(&node.certname).unwrap_or_default();
+            SearchDocument {
+                kind: SearchKind::Node,
+                ref_id: node.certname.clone(),
+                owner_id: None,
+                body: join_text([
+                    node.catalog_environment,
+                    node.latest_report_status,
+                    Some(values.join(" ")),
+                ]),
+                title: node.certname,
+            }
+        })
+        .collect())
+}
+
+/// The latest report of every node
+async fn report_documents(client: &PuppetDbClient) -> Result<Vec<SearchDocument>> {
+    let pql = "reports[hash, certname, status, environment, configuration_version, \
+               puppet_version, receive_time] { latest_report? = true }";
+    let rows: Vec<ReportRow> = client.query(pql).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|report| SearchDocument {
+            kind: SearchKind::Report,
+            title: format!(
+                "{} ({})",
+                report.certname,
+                report.status.as_deref().unwrap_or("unknown")
+            ),
+            body: join_text([
+                Some(report.certname),
+                report.environment,
+                report.configuration_version,
+                report.puppet_version.map(|v| format!("puppet {}", v)),
+                report.receive_time,
+            ]),
+            ref_id: report.hash,
+            owner_id: None,
+        })
+        .collect())
+}
+
+/// Classes in current catalogs, lowercased, with the number of nodes each
+async fn class_node_counts(client: &PuppetDbClient) -> Result<BTreeMap<String, u64>> {
+    let pql = "resources[title, count()] { type = \"Class\" group by title }";
+    let rows: Vec<ClassRow> = client.query(pql).await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.title.to_lowercase(), row.count))
+        .filter(|(title, _)| !IMPLICIT_CLASSES.contains(&title.as_str()))
+        .collect())
+}
+
+/// Collect the scalar values of a fact, descending into structured facts
+fn flatten_value(value: &Value, out: &mut Vec<String>) {
+    match value {
+        Value::Null => {}
+        Value::String(s) => out.push(s.clone()),
+        Value::Bool(_) | Value::Number(_) => out.push(value.to_string()),
+        Value::Array(items) => items.iter().for_each(|item| flatten_value(item, out)),
+        Value::Object(map) => map.values().for_each(|item| flatten_value(item, out)),
+    }
+}
+
+fn join_text<const N: usize>(parts: [Option<String>; N]) -> String {
+    parts
+        .into_iter()
+        .flatten()
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_flatten_value_collects_nested_scalars() {
+        let mut out = Vec::new();
+        flatten_value(
+            &json!({
+                "family": "RedHat",
+                "release": { "full": "9.4", "major": "9" },
+                "selinux": { "enabled": true },
+                "architectures": ["x86_64"],
+                "missing": null
+            }),
+            &mut out,
+        );
+        out.sort();
+        assert_eq!(out, vec!["9", "9.4", "RedHat", "true", "x86_64"]);
+    }
+
+    #[test]
+    fn test_join_text_skips_missing_parts() {
+        assert_eq!(
+            join_text([Some("production".to_string()), None, Some(String::new())]),
+            "production"
+        );
+    }
+}
@@ -0,0 +1,652 @@
+//! External secrets resolution
+//!
+//! Configuration values written as `secret:<path>#<key>` are resolved from
+//! HashiCorp Vault (KV v1/v2) or an executable provider instead of being kept
+//! in plaintext. Resolved values are cached for `cache_ttl_secs`; Vault tokens
+//! are renewed in the background (AppRole logins are redone when renewal is
+//! no longer possible) so runtime lookups such as the SMTP password keep
+//! working for the lifetime of the process.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use tokio::process::Command;
+use tokio::sync::RwLock;
+use tracing::{debug, info, warn};
+
+use crate::config::{
+    AppConfig, ExecSecretsConfig, SecretsConfig, SecretsProviderKind, VaultSecretsConfig,
+};
+
+/// Prefix marking a configuration value as a secret reference
+pub const SECRET_REF_PREFIX: &str = "secret:";
+
+/// Minimum interval between Vault token renewals
+const MIN_RENEWAL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// A parsed `secret:<path>#<key>` reference
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SecretRef {
+    pub path: String,
+    /// Field within the secret; the provider decides the default when omitted
+    pub key: Option<String>,
+}
+
+/// Parse a configuration value as a secret reference
+///
+/// Returns `None` for plain values.
+pub fn parse_secret_ref(value: &str) -> Option<SecretRef> {
+    let reference = value.trim().strip_prefix(SECRET_REF_PREFIX)?;
+    let (path, key) = match reference.split_once('#') {
+        Some((path, key)) => (path, Some(key.to_string()).filter(|k| !k.is_empty())),
+        None => (reference, None),
+    };
+    let path = path.trim_matches('/');
+    if path.is_empty() {
+        return None;
+    }
+    Some(SecretRef {
+        path: path.to_string(),
+        key,
+    })
+}
+
+/// Whether a configuration value is a secret reference
+pub fn is_secret_ref(value: &str) -> bool {
+    parse_secret_ref(value).is_some()
+}
+
+struct CachedSecret {
+    value: String,
+    fetched_at: Instant,
+}
+
+/// Resolves secret references through the configured provider
+pub struct SecretsResolver {
+    provider: SecretProvider,
+    cache: RwLock<HashMap<String, CachedSecret>>,
+    cache_ttl: Duration,
+}
+
+enum SecretProvider {
+    Vault(VaultProvider),
+    Exec(ExecSecretsConfig),
+}
+
+impl SecretsResolver {
+    /// Create a resolver for the configured provider
+    pub async fn new(config: &SecretsConfig) -> Result<Self> {
+        let provider = match config.provider {
+            SecretsProviderKind::Vault => {
+                let vault = config
+                    .vault
+                    .as_ref()
+                    .context("secrets.provider is 'vault' but secrets.vault is not configured")?;
+                SecretProvider::Vault(VaultProvider::new(vault).await?)
+            }
+            SecretsProviderKind::Exec => {
+                let exec = config
+                    .exec
+                    .clone()
+                    .context("secrets.provider is 'exec' but secrets.exec is not configured")?;
+                SecretProvider::Exec(exec)
+            }
+        };
+
+        Ok(Self {
+            provider,
+            cache: RwLock::new(HashMap::new()),
+            cache_ttl: Duration::from_secs(config.cache_ttl_secs),
+        })
+    }
+
+    /// Resolve a value, returning plain values unchanged
+    pub async fn resolve_value(&self, value: &str) -> Result<String> {
+        let Some(reference) = parse_secret_ref(value) else {
+            return Ok(value.to_string());
+        };
+
+        let cache_key = value.trim().to_string();
+        if let Some(cached) = self.cache.read().await.get(&cache_key) {
+            if cached.fetched_at.elapsed() < self.cache_ttl {
+                return Ok(cached.value.clone());
+            }
+        }
+
+        let secret = match &self.provider {
+            SecretProvider::Vault(vault) => vault.read(&reference).await,
+            SecretProvider::Exec(exec) => exec_read(exec, &reference).await,
+        }
+        .with_context(|| format!("Failed to resolve secret '{}'", reference.path))?;
+
+        self.cache.write().await.insert(
+            cache_key,
+            CachedSecret {
+                value: secret.clone(),
+                fetched_at: Instant::now(),
+            },
+        );
+
+        Ok(secret)
+    }
+
+    /// Keep the provider's credentials alive in the background
+    ///
+    /// Only Vault tokens need renewal; this is a no-op for other providers.
+    pub fn spawn_renewal(self: &Arc<Self>) {
+        if !matches!(self.provider, SecretProvider::Vault(_)) {
+            return;
+        }
+
+        let resolver = Arc::clone(self);
+        tokio::spawn(async move {
+            loop {
+                let SecretProvider::Vault(vault) = &resolver.provider else {
+                    return;
+                };
+                tokio::time::sleep(vault.renewal_interval().await).await;
+                if let Err(e) = vault.renew().await {
+                    warn!("Failed to renew Vault token: {:#}", e);
+                }
+            }
+        });
+    }
+}
+
+/// Resolve a value read at runtime (e.g. from the settings database)
+///
+/// Plain values are returned unchanged. A secret reference without a
+/// resolver is an error rather than being used verbatim.
+pub async fn resolve_runtime_value(
+    resolver: Option<&SecretsResolver>,
+    value: &str,
+) -> Result<String> {
+    if !is_secret_ref(value) {
+        return Ok(value.to_string());
+    }
+    let resolver =
+        resolver.context("Secret reference found but no secrets provider is configured")?;
+    resolver.resolve_value(value).await
+}
+
+/// Configuration fields that may hold a secret reference
+fn secret_fields(config: &AppConfig) -> Vec<(&'static str, &str)> {
+    let mut fields = vec![
+        ("auth.jwt_secret", config.auth.jwt_secret.as_str()),
+        ("database.url", config.database.url.as_str()),
+    ];
+    if let Some(inventory) = &config.inventory {
+        fields.push(("inventory.database_url", inventory.database_url.as_str()));
+    }
+    if let Some(master_key) = &config.settings_encryption.master_key {
+        fields.push(("settings_encryption.master_key", master_key.as_str()));
+    }
+    if let Some(code_deploy) = &config.code_deploy {
+        fields.push((
+            "code_deploy.encryption_key",
+            code_deploy.encryption_key.as_str(),
+        ));
+    }
+    fields
+}
+
+/// Resolve every secret reference in the configuration
+///
+/// Covers the JWT secret, the database URLs, the settings master key and the
+/// Code Deploy encryption key. Returns the resolver (if any) so callers can
+/// keep it for values read later, such as the SMTP password.
+pub async fn resolve_config_secrets(
+    config: &mut AppConfig,
+) -> Result<Option<Arc<SecretsResolver>>> {
+    let Some(secrets_config) = config.secrets.clone() else {
+        if let Some((field, _)) = secret_fields(config)
+            .into_iter()
+            .find(|(_, value)| is_secret_ref(value))
+        {
+            anyhow::bail!(
+                "{} is a secret reference but no secrets provider is configured",
+                field
+            );
+        }
+        return Ok(None);
+    };
+
+    let resolver = Arc::new(SecretsResolver::new(&secrets_config).await?);
+
+    config.auth.jwt_secret = resolver.resolve_value(&config.auth.jwt_secret).await?;
+    config.database.url = resolver.resolve_value(&config.database.url).await?;
+
+    if let Some(inventory) = config.inventory.as_mut() {
+        inventory.database_url = resolver.resolve_value(&inventory.database_url).await?;
+    }
+
+    if let Some(master_key) = config.settings_encryption.master_key.as_ref() {
+        config.settings_encryption.master_key = Some(resolver.resolve_value(master_key).await?);
+    }
+
+    if let Some(code_deploy) = config.code_deploy.as_mut() {
+        code_deploy.encryption_key = resolver.resolve_value(&code_deploy.encryption_key).await?;
+    }
+
+    info!(
+        "Resolved configuration secrets using the {:?} provider",
+        secrets_config.provider
+    );
+
+    Ok(Some(resolver))
+}
+
+// ============================================================================
+// Executable provider
+// ============================================================================
+
+async fn exec_read(config: &ExecSecretsConfig, reference: &SecretRef) -> Result<String> {
+    let mut cmd = Command::new(&config.command);
+    cmd.args(&config.args)
+        .arg(&reference.path)
+        .args(reference.key.as_deref())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+
+    let output = tokio::time::timeout(Duration::from_secs(config.timeout_secs), cmd.output())
+        .await
+        .with_context(|| {
+            format!(
+                "Secrets command {:?} timed out after {}s",
+                config.command, config.timeout_secs
+            )
+        })?
+        .with_context(|| format!("Failed to run secrets command {:?}", config.command))?;
+
+    if !output.status.success() {
+        anyhow::bail!(
+            "Secrets command {:?} exited with {}: {}",
+            config.command,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let value = String::from_utf8(output.stdout).context("Secrets command output is not UTF-8")?;
+    let value = value.trim_end_matches(['\r', '\n']).to_string();
+    if value.is_empty() {
+        anyhow::bail!(
+            "Secrets command {:?} printed an empty value",
+            config.command
+        );
+    }
+
+    Ok(value)
+}
+
+// ============================================================================
+// Vault provider
+// ============================================================================
+
+struct VaultToken {
+    token: String,
+    renewable: bool,
+    /// Lease duration; zero for non-expiring tokens
+    ttl: Duration,
+}
+
+struct VaultProvider {
+    client: reqwest::Client,
+    config: VaultSecretsConfig,
+    token: RwLock<VaultToken>,
+}
+
+#[derive(Deserialize)]
+struct VaultAuthResponse {
+    auth: VaultAuth,
+}
+
+#[derive(Deserialize)]
+struct VaultAuth {
+    client_token: Option<String>,
+    #[serde(default)]
+    lease_duration: u64,
+    #[serde(default)]
+    renewable: bool,
+}
+
+#[derive(Deserialize)]
+struct VaultLookupResponse {
+    data: VaultLookupData,
+}
+
+#[derive(Deserialize)]
+struct VaultLookupData {
+    #[serde(default)]
+    ttl: u64,
+    #[serde(default)]
+    renewable: bool,
+}
+
+#[derive(Deserialize)]
+struct VaultSecretResponse {
+    data: serde_json::Value,
+}
+
+impl VaultProvider {
+    async fn new(config: &VaultSecretsConfig) -> Result<Self> {
+        if config.kv_version != 1 && config.kv_version != 2 {
+            anyhow::bail!("secrets.vault.kv_version must be 1 or 2");
+        }
+
+        let mut builder =
+            reqwest::Client::builder().timeout(Duration::from_secs(config.timeout_secs));
+        if let Some(ca_path) = &config.ca_cert {
+            let ca_pem = std::fs::read(ca_path)
+                .with_context(|| format!("Failed to read Vault CA bundle {:?}", ca_path))?;
+            let certs = reqwest::Certificate::from_pem_bundle(&ca_pem)
+                .context("Failed to parse Vault CA bundle")?;
+            builder = builder.tls_certs_only(certs);
+        }
+        let client = builder
+            .build()
+            .context("Failed to create Vault HTTP client")?;
+
+        let provider = Self {
+            client,
+            config: config.clone(),
+            token: RwLock::new(VaultToken {
+                token: String::new(),
+                renewable: false,
+                ttl: Duration::ZERO,
+            }),
+        };
+        provider.authenticate().await?;
+        Ok(provider)
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/v1/{}", self.config.address.trim_end_matches('/'), path)
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str, token: &str) -> reqwest::RequestBuilder {
+        let mut request = self.client.request(method, self.url(path));
+        if !token.is_empty() {
+            request = request.header("X-Vault-Token", token);
+        }
+        if let Some(namespace) = &self.config.namespace {
+            request = request.header("X-Vault-Namespace", namespace);
+        }
+        request
+    }
+
+    /// Obtain a token via AppRole, or load the static token and look up its lease
+    async fn authenticate(&self) -> Result<()> {
+        let token = if let Some(approle) = &self.config.approle {
+            let secret_id = match (&approle.secret_id, &approle.secret_id_file) {
+                (Some(secret_id), _) => secret_id.clone(),
+                (None, Some(path)) => read_trimmed(path)?,
+                (None, None) => String::new(),
+            };
+            let response: VaultAuthResponse = self
+                .request(
+                    reqwest::Method::POST,
+                    &format!("auth/{}/login", approle.mount.trim_matches('/')),
+                    "",
+                )
+                .json(&serde_json::json!({ "role_id": approle.role_id, "secret_id": secret_id }))
+                .send()
+                .await
+                .context("Vault AppRole login failed")?
+                .error_for_status()
+                .context("Vault AppRole login rejected")?
+                .json()
+                .await
+                .context("Invalid Vault AppRole login response")?;
+
+            VaultToken {
+                token: response
+                    .auth
+                    .client_token
+                    .context("Vault AppRole login returned no token")?,
+                renewable: response.auth.renewable,
+                ttl: Duration::from_secs(response.auth.lease_duration),
+            }
+        } else {
+            let token = match (&self.config.token, &self.config.token_file) {
+                (Some(token), _) => token.clone(),
+                (None, Some(path)) => read_trimmed(path)?,
+                (None, None) => std::env::var("VAULT_TOKEN").context(
+                    "No Vault token configured (secrets.vault.token, token_file or VAULT_TOKEN)",
+                )?,
+            };
+
+            let lookup: VaultLookupResponse = self
+                .request(reqwest::Method::GET, "auth/token/lookup-self", &token)
+                .send()
+                .await
+                .context("Vault token lookup failed")?
+                .error_for_status()
+                .context("Vault token rejected")?
+                .json()
+                .await
+                .context("Invalid Vault token lookup response")?;
+
+            VaultToken {
+                token,
+                renewable: lookup.data.renewable,
+                ttl: Duration::from_secs(lookup.data.ttl),
+            }
+        };
+
+        debug!(
+            "Authenticated to Vault (renewable: {}, ttl: {}s)",
+            token.renewable,
+            token.ttl.as_secs()
+        );
+        *self.token.write().await = token;
+        Ok(())
+    }
+
+    /// Renew the token, logging in again when it cannot be renewed
+    async fn renew(&self) -> Result<()> {
+        let (token, renewable) = {
+            let current = self.token.read().await;
+            (current.token.clone(), current.renewable)
+        };
+
+        if renewable {
+            let renewed = self
+                .request(reqwest::Method::POST, "auth/token/renew-self", &token)
+                .send()
+                .await
+                .and_then(|r| r.error_for_status());
+
+            match renewed {
+                Ok(response) => {
+                    let response: VaultAuthResponse = response
+                        .json()
+                        .await
+                        .context("Invalid Vault token renewal response")?;
+                    let mut current = self.token.write().await;
+                    current.ttl = Duration::from_secs(response.auth.lease_duration);
+                    current.renewable = response.auth.renewable;
+                    debug!("Renewed Vault token (ttl: {}s)", current.ttl.as_secs());
+                    return Ok(());
+                }
+                Err(e) if self.config.approle.is_none() && self.config.token_file.is_none() => {
+                    return Err(e).context("Vault token renewal failed");
+                }
+                Err(e) => warn!("Vault token renewal failed, authenticating again: {}", e),
+            }
+        }
+
+        if self.config.approle.is_some() || self.config.token_file.is_some() {
+            // AppRole can log in again; a token file may have been rotated by Vault Agent
+            self.authenticate().await?;
+        }
+        Ok(())
+    }
+
+    /// Renew at half the remaining lease (hourly for non-expiring tokens)
+    async fn renewal_interval(&self) -> Duration {
+        let ttl = self.token.read().await.ttl;
+        if ttl.is_zero() {
+            Duration::from_secs(3600)
+        } else {
+            (ttl / 2).max(MIN_RENEWAL_INTERVAL)
+        }
+    }
+
+    async fn read(&self, reference: &SecretRef) -> Result<String> {
+        let mount = self.config.kv_mount.trim_matches('/');
+        let path = if self.config.kv_version == 2 {
+            format!("{}/data/{}", mount, reference.path)
+        } else {
+            format!("{}/{}", mount, reference.path)
+        };
+
+        let token = self.token.read().await.token.clone();
+        let response: VaultSecretResponse = self
+            .request(reqwest::Method::GET, &path, &token)
+            .send()
+            .await
+            .context("Vault request failed")?
+            .error_for_status()
+            .context("Vault returned an error")?
+            .json()
+            .await
+            .context("Invalid Vault secret response")?;
+
+        // KV v2 nests the secret under data.data
+        let data = if self.config.kv_version == 2 {
+            response.data.get("data").cloned().unwrap_or_default()
+        } else {
+            response.data
+        };
+
+        extract_field(&data, reference.key.as_deref())
+    }
+}
+
+/// Pick a field out of a Vault secret
+///
+/// Without a key, a secret holding exactly one field yields that field.
+fn extract_field(data: &serde_json::Value, key: Option<&str>) -> Result<String> {
+    let object = data.as_object().context("Vault secret has no data")?;
+
+    let value = match key {
+        Some(key) => object
+            .get(key)
+            .with_context(|| format!("Vault secret has no field '{}'", key))?,
+        None if object.len() == 1 => object.values().next().expect("one field"),
+        None => anyhow::bail!("Vault secret has several fields; add '#<field>' to the reference"),
+    };
+
+    match value {
+        serde_json::Value::String(s) => Ok(s.clone()),
+        other => Ok(other.to_string()),
+    }
+}
+
+fn read_trimmed(path: &Path) -> Result<String> {
+    Ok(std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {:?}", path))?
+        .trim()
+        .to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_secret_ref() {
+        assert_eq!(
+            parse_secret_ref("secret:openvox/webui#jwt_secret"),
+            Some(SecretRef {
+                path: "openvox/webui".to_string(),
+                key: Some("jwt_secret".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_secret_ref("secret:/openvox/db/"),
+            Some(SecretRef {
+                path: "openvox/db".to_string(),
+                key: None,
+            })
+        );
+        assert_eq!(parse_secret_ref("plain-value"), None);
+        assert_eq!(parse_secret_ref("secret:"), None);
+        assert_eq!(parse_secret_ref("sqlite://openvox.db"), None);
+    }
+
+    #[test]
+    fn test_extract_field() {
+        let data = json!({ "jwt_secret": "abc", "port": 5432 });
+        assert_eq!(extract_field(&data, Some("jwt_secret")).unwrap(), "abc");
+        assert_eq!(extract_field(&data, Some("port")).unwrap(), "5432");
+        assert!(extract_field(&data, Some("missing")).is_err());
+        assert!(extract_field(&data, None).is_err());
+        assert_eq!(extract_field(&json!({ "only": "x" }), None).unwrap(), "x");
+    }
+
+    #[tokio::test]
+    async fn test_exec_provider_and_cache() {
+        let config = SecretsConfig {
+            provider: SecretsProviderKind::Exec,
+            vault: None,
+            exec: Some(ExecSecretsConfig {
+                command: "/bin/echo".into(),
+                args: vec!["value-for".to_string()],
+                timeout_secs: 5,
+            }),
+            cache_ttl_secs: 300,
+        };
+        let resolver = SecretsResolver::new(&config).await.unwrap();
+
+        assert_eq!(
+            resolver
+                .resolve_value("secret:db/main#password")
+                .await
+                .unwrap(),
+            "value-for db/main password"
+        );
+        assert_eq!(resolver.resolve_value("plain").await.unwrap(), "plain");
+        assert_eq!(resolver.cache.read().await.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_secret_refs_require_a_provider() {
+        let mut config = AppConfig::default();
+        assert!(resolve_config_secrets(&mut config).await.unwrap().is_none());
+
+        config.inventory = Some(Default::default());
+        config.settings_encryption.master_key = Some("plain-master-key".to_string());
+        config.code_deploy = Some(Default::default());
+        assert!(resolve_config_secrets(&mut config).await.unwrap().is_none());
+
+        let fields: [(&str, fn(&mut AppConfig) -> &mut String); 5] = [
+            ("auth.jwt_secret", |c| &mut c.auth.jwt_secret),
+            ("database.url", |c| &mut c.database.url),
+            ("inventory.database_url", |c| {
+                &mut c.inventory.as_mut().unwrap().database_url
+            }),
+            ("settings_encryption.master_key", |c| {
+                c.settings_encryption.master_key.as_mut().unwrap()
+            }),
+            ("code_deploy.encryption_key", |c| {
+                &mut c.code_deploy.as_mut().unwrap().encryption_key
+            }),
+        ];
+        for (name, field) in fields {
+            let mut config = config.clone();
+            *field(&mut config) = "secret:openvox/webui#value".to_string();
+            let error = resolve_config_secrets(&mut config).await.unwrap_err();
+            assert!(error.to_string().starts_with(name), "{}: {}", name, error);
+        }
+    }
+}
@@ -0,0 +1,253 @@
+//! Encryption of sensitive settings stored in the database
+//!
+//! Values of sensitive keys (passwords, secrets, tokens) are encrypted with
+//! ChaCha20-Poly1305 before they reach the `settings` table and decrypted on
+//! read by [`SettingsRepository`](crate::db::SettingsRepository). Encrypted
+//! values are stored as `enc:v1:<base64(nonce || ciphertext)>`, so plaintext
+//! rows written by older versions are recognised and re-encrypted at startup.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use chacha20poly1305::{
+    aead::{Aead, Generate, KeyInit},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use sqlx::SqlitePool;
+use tracing::info;
+
+use crate::config::AppConfig;
+use crate::db::SettingsRepository;
+
+/// Prefix of encrypted setting values
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+/// Key suffixes whose values are encrypted at rest
+pub const SENSITIVE_KEY_SUFFIXES: &[&str] =
+    &[".password", ".secret", ".token", ".api_key", ".private_key"];
+
+/// Fixed salt for deriving the cipher key from the master key
+///
+/// The master key is expected to be high-entropy; the salt only separates
+/// this use of it from any other.
+const KEY_DERIVATION_SALT: &[u8] = b"openvox-webui/settings/v1";
+
+/// Name of the generated key file placed next to the SQLite database
+const DEFAULT_KEY_FILE_NAME: &str = "settings.key";
+
+/// Returns true if values stored under `key` must be encrypted
+pub fn is_sensitive_setting(key: &str) -> bool {
+    SENSITIVE_KEY_SUFFIXES
+        .iter()
+        .any(|suffix| key.ends_with(suffix))
+}
+
+/// Returns true if a stored value is encrypted
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// Cipher for settings values
+#[derive(Clone)]
+pub struct SettingsCipher {
+    cipher: ChaCha20Poly1305,
+}
+
+impl SettingsCipher {
+    /// Derive the cipher from a master key using Argon2id
+    pub fn from_master_key(master_key: &str) -> Result<Self> {
+        if master_key.is_empty() {
+            anyhow::bail!("Settings master key cannot be empty");
+        }
+
+        let mut key = [0u8; 32];
+        Argon2::default()
+            .hash_password_into(master_key.as_bytes(), KEY_DERIVATION_SALT, &mut key)
+            .map_err(|e| anyhow::anyhow!("Key derivation failed: {}", e))?;
+
+        Ok(Self {
+            cipher: ChaCha20Poly1305::new(&Key::from(key)),
+        })
+    }
+
+    /// Encrypt a value for storage
+    pub fn encrypt(&self, plaintext: &str) -> Result<String> {
+        let nonce = Nonce::generate();
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|e| anyhow::anyhow!("Encryption failed: {}", e))?;
+
+        let mut payload = Vec::with_capacity(nonce.len() + ciphertext.len());
+        payload.extend_from_slice(&nonce);
+        payload.extend_from_slice(&ciphertext);
+
+        Ok(format!("{}{}", ENCRYPTED_PREFIX, BASE64.encode(payload)))
+    }
+
+    /// Decrypt a stored value; plaintext values are returned unchanged
+    pub fn decrypt(&self, stored: &str) -> Result<String> {
+        let Some(encoded) = stored.strip_prefix(ENCRYPTED_PREFIX) else {
+            return Ok(stored.to_string());
+        };
+
+        let payload = BASE64
+            .decode(encoded)
+            .context("Failed to decode encrypted setting")?;
+        if payload.len() < 12 {
+            anyhow::bail!("Encrypted setting is truncated");
+        }
+        let (nonce, ciphertext) = payload.split_at(12);
+        let mut nonce_bytes = [0u8; 12];
+        nonce_bytes.copy_from_slice(nonce);
+
+        let plaintext = self
+            .cipher
+            .decrypt(&Nonce::from(nonce_bytes), ciphertext)
+            .map_err(|_| {
+                anyhow::anyhow!("Failed to decrypt setting - wrong master key or corrupted value")
+            })?;
+
+        String::from_utf8(plaintext).context("Invalid UTF-8 in decrypted setting")
+    }
+}
+
+/// Key file used when no master key is configured
+///
+/// Lives next to the SQLite database so it is covered by the same
+/// filesystem permissions (and kept out of database backups).
+pub fn default_key_file(database_url: &str) -> PathBuf {
+    let db_path = database_url
+        .strip_prefix("sqlite://")
+        .or_else(|| database_url.strip_prefix("sqlite:"))
+        .unwrap_or(database_url);
+    let db_path = db_path.split('?').next().unwrap_or(db_path);
+
+    Path::new(db_path)
+        .parent()
+        .filter(|p| !p.as_os_str().is_empty())
+        .unwrap_or_else(|| Path::new("."))
+        .join(DEFAULT_KEY_FILE_NAME)
+}
+
+/// Load the master key from configuration, generating a key file if needed
+pub fn load_master_key(config: &AppConfig) -> Result<String> {
+    let settings = &config.settings_encryption;
+    if let Some(key) = settings.master_key.as_ref().filter(|k| !k.is_empty()) {
+        return Ok(key.clone());
+    }
+
+    let path = settings
+        .master_key_file
+        .clone()
+        .unwrap_or_else(|| default_key_file(&config.database.url));
+
+    if path.exists() {
+        let key = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read settings master key {:?}", path))?;
+        return Ok(key.trim().to_string());
+    }
+
+    if settings.master_key_file.is_some() {
+        anyhow::bail!("Settings master key file not found: {:?}", path);
+    }
+
+    generate_key_file(&path)
+}
+
+fn generate_key_file(path: &Path) -> Result<String> {
+    use std::fs::OpenOptions;
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let key = BASE64.encode(rand::random::<[u8; 32]>());
+
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)
+        .with_context(|| format!("Failed to create settings master key {:?}", path))?;
+    writeln!(file, "{}", key)
+        .with_context(|| format!("Failed to write settings master key {:?}", path))?;
+
+    info!(
+        "Generated settings master key at {:?}; back this file up, encrypted settings cannot be read without it",
+        path
+    );
+
+    Ok(key)
+}
+
+/// Load the master key, encrypt leftover plaintext values and return the
+/// cipher
+pub async fn init(config: &AppConfig, pool: &SqlitePool) -> Result<SettingsCipher> {
+    let master_key = load_master_key(config)?;
+    let cipher = SettingsCipher::from_master_key(&master_key)?;
+
+    let encrypted = SettingsRepository::new(pool.clone(), Some(cipher.clone()))
+        .encrypt_plaintext_settings()
+        .await
+        .context("Failed to encrypt existing sensitive settings")?;
+    if encrypted > 0 {
+        info!("Encrypted {} plaintext sensitive setting(s)", encrypted);
+    }
+
+    Ok(cipher)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_sensitive_setting() {
+        assert!(is_sensitive_setting("smtp.password"));
+        assert!(is_sensitive_setting("webhooks.secret"));
+        assert!(is_sensitive_setting("integrations.slack.token"));
+        assert!(!is_sensitive_setting("smtp.host"));
+        assert!(!is_sensitive_setting("update_jobs.max_runtime_minutes"));
+    }
+
+    #[test]
+    fn test_encrypt_decrypt_roundtrip() {
+        let cipher = SettingsCipher::from_master_key("test-master-key").unwrap();
+
+        let stored = cipher.encrypt("hunter2").unwrap();
+        assert!(is_encrypted(&stored));
+        assert!(!stored.contains("hunter2"));
+        assert_eq!(cipher.decrypt(&stored).unwrap(), "hunter2");
+
+        // Plaintext rows from older versions pass through
+        assert_eq!(cipher.decrypt("legacy").unwrap(), "legacy");
+    }
+
+    #[test]
+    fn test_wrong_master_key_fails() {
+        let stored = SettingsCipher::from_master_key("key-one")
+            .unwrap()
+            .encrypt("value")
+            .unwrap();
+
+        let other = SettingsCipher::from_master_key("key-two").unwrap();
+        assert!(other.decrypt(&stored).is_err());
+    }
+
+    #[test]
+    fn test_default_key_file() {
+        assert_eq!(
+            default_key_file("sqlite://data/openvox.db"),
+            PathBuf::from("data/settings.key")
+        );
+        assert_eq!(
+            default_key_file("sqlite:///var/lib/openvox-webui/openvox.db?mode=rwc"),
+            PathBuf::from("/var/lib/openvox-webui/settings.key")
+        );
+        assert_eq!(
+            default_key_file("sqlite://openvox.db"),
+            PathBuf::from("./settings.key")
+        );
+    }
+}
@@ -0,0 +1,569 @@
+//! Smart list evaluation
+//!
+//! Resolves a saved node filter into the set of matching nodes. Cheap criteria
+//! (environment, status, certname) are pushed down to PuppetDB; group
+//! membership reuses the canonical group resolver; fact and tag criteria are
+//! evaluated locally against each remaining node's facts.
+//!
+//! Resolving a list with fact criteria fetches the facts of every candidate
+//! node, so member counts are cached in [`MemberCounts`] for
+//! `cache.smart_list_count_ttl_secs`. A cached count belongs to one version of
+//! a list and is dropped when the list is changed or deleted.
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use regex::Regex;
+use sqlx::SqlitePool;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::db::repository::GroupRepository;
+use crate::db::SmartListRepository;
+use crate::models::{Node, RuleOperator, SmartList, SmartListCount, SmartListFilter};
+use crate::services::auth::AuthService;
+use crate::services::cache::Cache;
+use crate::services::classification::{
+    build_node_classification_facts, get_fact_value, match_value,
+};
+use crate::services::puppetdb::{PuppetDbClient, QueryBuilder};
+
+/// Fact holding node tags evaluated by the `tags` criterion
+pub const TAGS_FACT: &str = "tags";
+
+/// Pseudo-fact holding the tags kept in the WebUI's node metadata
+pub const WEBUI_TAGS_FACT: &str = "webui.tags";
+
+/// Validate a smart list filter before it is persisted
+pub fn validate_filter(filter: &SmartListFilter) -> Result<(), String> {
+    let has_unsafe_chars = |s: &str| s.contains('"') || s.contains('\\');
+
+    for env in &filter.environments {
+        if env.is_empty() || has_unsafe_chars(env) {
+            return Err(format!("Invalid environment name: '{}'", env));
+        }
+    }
+
+    for status in &filter.statuses {
+        if !matches!(
+            status.as_str(),
+            "changed" | "unchanged" | "failed" | "noop" | "unreported"
+        ) {
+            return Err(format!("Invalid status: '{}'", status));
+        }
+    }
+
+    if let Some(pattern) = &filter.certname_pattern {
+        if has_unsafe_chars(pattern) {
+            return Err("Certname pattern must not contain quotes or backslashes".to_string());
+        }
+        Regex::new(pattern).map_err(|e| format!("Invalid certname pattern: {}", e))?;
+    }
+
+    for condition in &filter.facts {
+        if condition.fact_path.trim().is_empty() {
+            return Err("Fact conditions require a fact_path".to_string());
+        }
+        if matches!(
+            condition.operator,
+            RuleOperator::Regex | RuleOperator::NotRegex
+        ) {
+            let pattern = condition
+                .value
+                .as_str()
+                .ok_or_else(|| "Regex fact conditions require a string value".to_string())?;
+            Regex::new(pattern).map_err(|e| format!("Invalid fact regex: {}", e))?;
+        }
+        if matches!(condition.operator, RuleOperator::In | RuleOperator::NotIn)
+            && !condition.value.is_array()
+        {
+            return Err("'in' and 'not_in' fact conditions require an array value".to_string());
+        }
+    }
+
+    Ok(())
+}
+
+/// Build the PuppetDB query for the criteria PuppetDB can evaluate directly
+fn build_query(filter: &SmartListFilter) -> QueryBuilder {
+    let mut qb = QueryBuilder::new();
+
+    let environments: Vec<&str> = filter.environments.iter().map(String::as_str).collect();
+    match environments.as_slice() {
+        [] => {}
+        [env] => qb = qb.equals("catalog_environment", env),
+        envs => qb = qb.in_array("catalog_environment", envs),
+    }
+
+    // "unreported" has no latest_report_status, so status is filtered locally
+    // whenever it is requested.
+    if !filter.statuses.is_empty() && !filter.statuses.iter().any(|s| s == "unreported") {
+        let statuses: Vec<&str> = filter.statuses.iter().map(String::as_str).collect();
+        qb = qb.in_array("latest_report_status", &statuses);
+    }
+
+    if let Some(pattern) = &filter.certname_pattern {
+        qb = qb.matches("certname", pattern);
+    }
+
+    qb
+}
+
+/// Check a node's status against the filter's status criterion
+pub fn status_matches(filter: &SmartListFilter, node: &Node) -> bool {
+    if filter.statuses.is_empty() {
+        return true;
+    }
+    let status = node.latest_report_status.as_deref().unwrap_or("unreported");
+    filter.statuses.iter().any(|s| s == status)
+}
+
+/// Check a node's facts against the filter's fact and tag criteria
+///
+/// `facts` must be the nested structure produced by
+/// [`build_node_classification_facts`].
+pub fn facts_match(filter: &SmartListFilter, facts: &serde_json::Value) -> bool {
+    let facts_ok = filter.facts.iter().all(|condition| {
+        get_fact_value(facts, &condition.fact_path)
+            .map(|value| match_value(&value, &condition.operator, &condition.value))
+            .unwrap_or(false)
+    });
+    if !facts_ok {
+        return false;
+    }
+
+    if filter.tags.is_empty() {
+        return true;
+    }
+
+    let node_tags = node_tags(facts);
+    filter.tags.iter().any(|tag| node_tags.contains(tag))
+}
+
+/// Extract tags from the `tags` fact, which may be an array or a
+/// comma-separated string, and from the WebUI metadata tags
+fn node_tags(facts: &serde_json::Value) -> HashSet<String> {
+    [TAGS_FACT, WEBUI_TAGS_FACT]
+        .into_iter()
+        .flat_map(|path| match get_fact_value(facts, path) {
+            Some(serde_json::Value::Array(items)) => items
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect(),
+            Some(serde_json::Value::String(s)) => s
+                .split(',')
+                .map(|t| t.trim().to_string())
+                .filter(|t| !t.is_empty())
+                .collect(),
+            _ => Vec::new(),
+        })
+        .collect()
+}
+
+/// Resolve the nodes currently matching a smart list
+///
+/// AppState-free so background services (alert evaluation, scheduled reports)
+/// can share the exact same logic as the API.
+pub async fn resolve_smart_list_nodes(
+    pool: &SqlitePool,
+    puppetdb: &PuppetDbClient,
+    list: &SmartList,
+) -> Result<Vec<Node>> {
+    resolve_filter_nodes(pool, puppetdb, list.organization_id, &list.filter).await
+}
+
+/// Resolve the nodes matching an ad-hoc filter
+///
+/// `organization_id` scopes the groups named in `filter.group_ids`.
+pub async fn resolve_filter_nodes(
+    pool: &SqlitePool,
+    puppetdb: &PuppetDbClient,
+    organization_id: Uuid,
+    filter: &SmartListFilter,
+) -> Result<Vec<Node>> {
+    let mut nodes: Vec<Node> = puppetdb
+        .query_nodes(&build_query(filter))
+        .await?
+        .into_iter()
+        .filter(|node| status_matches(filter, node))
+        .collect();
+
+    if !filter.group_ids.is_empty() {
+        let repo = GroupRepository::new(pool);
+        let mut members: HashSet<String> = HashSet::new();
+        for group_id in &filter.group_ids {
+            let certnames = crate::api::groups::classify_group_members(
+                &repo,
+                Some(puppetdb),
+                organization_id,
+                *group_id,
+            )
+            .await?;
+            members.extend(certnames);
+        }
+        nodes.retain(|node| members.contains(&node.certname));
+    }
+
+    if filter.needs_facts() {
+        let mut matched = Vec::with_capacity(nodes.len());
+        for node in nodes {
+            let facts = match puppetdb.get_node_facts(&node.certname).await {
+                Ok(facts) => {
+                    build_node_classification_facts(
+                        pool,
+                        facts,
+                        &node.certname,
+                        node.catalog_environment.as_deref(),
+                    )
+                    .await
+                }
+                Err(e) => {
+                    warn!("Failed to get facts for {}: {}", node.certname, e);
+                    continue;
+                }
+            };
+            if facts_match(filter, &facts) {
+                matched.push(node);
+            }
+        }
+        nodes = matched;
+    }
+
+    nodes.sort_by(|a, b| a.certname.cmp(&b.certname));
+    Ok(nodes)
+}
+
+/// Resolve only the certnames matching a smart list
+pub async fn resolve_smart_list_certnames(
+    pool: &SqlitePool,
+    puppetdb: &PuppetDbClient,
+    list: &SmartList,
+) -> Result<HashSet<String>> {
+    Ok(resolve_smart_list_nodes(pool, puppetdb, list)
+        .await?
+        .into_iter()
+        .map(|node| node.certname)
+        .collect())
+}
+
+/// Whether a user may use a smart list
+///
+/// Shared lists are visible to their whole organization, private lists only
+/// to their owner and to admins.
+pub fn is_visible_to(list: &SmartList, user_id: Uuid, is_admin: bool) -> bool {
+    list.is_shared || list.created_by == user_id || is_admin
+}
+
+/// Load a smart list on behalf of a user outside of a request, e.g. the
+/// owner of a saved report or alert rule
+///
+/// Returns `None` when the list is not in the organization or the user may
+/// not see it, like the smart list API does.
+pub async fn load_smart_list_for_user(
+    pool: &SqlitePool,
+    organization_id: Uuid,
+    id: Uuid,
+    user_id: Uuid,
+) -> Result<Option<SmartList>> {
+    let Some(list) = SmartListRepository::new(pool)
+        .get_by_id(organization_id, id)
+        .await?
+    else {
+        return Ok(None);
+    };
+    if is_visible_to(&list, user_id, false) {
+        return Ok(Some(list));
+    }
+
+    let roles = AuthService::new(pool.clone())
+        .get_user_roles(&user_id)
+        .await?;
+    let is_admin = roles
+        .iter()
+        .any(|r| matches!(r.as_str(), "admin" | "super_admin" | "superadmin"));
+    Ok(is_visible_to(&list, user_id, is_admin).then_some(list))
+}
+
+/// Most smart lists whose member counts are cached at once
+const MEMBER_COUNT_CACHE_ENTRIES: usize = 10_000;
+
+/// Member count of one version of a smart list
+#[derive(Debug, Clone)]
+pub struct CachedCount {
+    list_updated_at: DateTime<Utc>,
+    count: SmartListCount,
+}
+
+/// Cached member counts of smart lists, by list ID
+pub struct MemberCounts {
+    cache: Cache<Uuid, CachedCount>,
+    ttl: Duration,
+}
+
+impl MemberCounts {
+    /// Counts are kept for `ttl`; a zero TTL disables the cache
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            cache: Cache::new(MEMBER_COUNT_CACHE_ENTRIES, ttl),
+            ttl,
+        }
+    }
+
+    /// The cached count of this version of the list, unless it expired
+    pub async fn get(&self, list: &SmartList) -> Option<SmartListCount> {
+        let cached = self.cache.get(&list.id).await?;
+        (cached.list_updated_at == list.updated_at).then_some(SmartListCount {
+            cached: true,
+            ..cached.count
+        })
+    }
+
+    /// Cache the member count of a list resolved elsewhere
+    pub async fn record(&self, list: &SmartList, count: usize) -> SmartListCount {
+        let count = SmartListCount {
+            smart_list_id: list.id,
+            count,
+            computed_at: Utc::now(),
+            cached: false,
+        };
+        if !self.ttl.is_zero() {
+            self.cache
+                .set(
+                    list.id,
+                    CachedCount {
+                        list_updated_at: list.updated_at,
+                        count: count.clone(),
+                    },
+                )
+                .await;
+        }
+        count
+    }
+
+    /// Drop the cached count of a list that was changed or deleted
+    pub async fn forget(&self, list_id: Uuid) {
+        self.cache.remove(&list_id).await;
+    }
+
+    /// Forget every cached count, e.g. when new reports may have changed the
+    /// status of nodes
+    pub async fn clear(&self) {
+        self.cache.clear().await;
+    }
+}
+
+/// Count the nodes matching a smart list, from `counts` when a cached count
+/// is available
+///
+/// `refresh` resolves the list again even when a cached count is available.
+pub async fn smart_list_member_count(
+    pool: &SqlitePool,
+    puppetdb: &PuppetDbClient,
+    counts: &MemberCounts,
+    list: &SmartList,
+    refresh: bool,
+) -> Result<SmartListCount> {
+    if !refresh {
+        if let Some(count) = counts.get(list).await {
+            return Ok(count);
+        }
+    }
+
+    let count = resolve_smart_list_nodes(pool, puppetdb, list).await?.len();
+    Ok(counts.record(list, count).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SmartListFactCondition;
+    use serde_json::json;
+
+    fn filter_with_facts(facts: Vec<SmartListFactCondition>) -> SmartListFilter {
+        SmartListFilter {
+            facts,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_facts_match_all_conditions() {
+        let facts = json!({ "os": { "family": "RedHat" }, "processors": { "count": 8 } });
+        let filter = filter_with_facts(vec![
+            SmartListFactCondition {
+                fact_path: "os.family".to_string(),
+                operator: RuleOperator::Equals,
+                value: json!("RedHat"),
+            },
+            SmartListFactCondition {
+                fact_path: "processors.count".to_string(),
+                operator: RuleOperator::GreaterThanOrEqual,
+                value: json!(4),
+            },
+        ]);
+        assert!(facts_match(&filter, &facts));
+
+        let filter = filter_with_facts(vec![SmartListFactCondition {
+            fact_path: "os.family".to_string(),
+            operator: RuleOperator::Equals,
+            value: json!("Debian"),
+        }]);
+        assert!(!facts_match(&filter, &facts));
+    }
+
+    #[test]
+    fn test_facts_match_missing_fact_fails() {
+        let filter = filter_with_facts(vec![SmartListFactCondition {
+            fact_path: "missing.fact".to_string(),
+            operator: RuleOperator::NotEquals,
+            value: json!("x"),
+        }]);
+        assert!(!facts_match(&filter, &json!({})));
+    }
+
+    #[test]
+    fn test_tags_from_array_and_string() {
+        let filter = SmartListFilter {
+            tags: vec!["web".to_string()],
+            ..Default::default()
+        };
+        assert!(facts_match(&filter, &json!({ "tags": ["db", "web"] })));
+        assert!(facts_match(&filter, &json!({ "tags": "db, web" })));
+        assert!(!facts_match(&filter, &json!({ "tags": ["db"] })));
+        assert!(facts_match(
+            &filter,
+            &json!({ "tags": ["db"], "webui": { "tags": ["web"] } })
+        ));
+        assert!(!facts_match(&filter, &json!({})));
+    }
+
+    #[test]
+    fn test_status_matches_unreported() {
+        let filter = SmartListFilter {
+            statuses: vec!["unreported".to_string()],
+            ..Default::default()
+        };
+        let unreported = Node {
+            certname: "a".to_string(),
+            ..Default::default()
+        };
+        let failed = Node {
+            certname: "b".to_string(),
+            latest_report_status: Some("failed".to_string()),
+            ..Default::default()
+        };
+        assert!(status_matches(&filter, &unreported));
+        assert!(!status_matches(&filter, &failed));
+    }
+
+    #[test]
+    fn test_build_query_pushes_down_simple_criteria() {
+        let filter = SmartListFilter {
+            environments: vec!["production".to_string()],
+            statuses: vec!["failed".to_string()],
+            certname_pattern: Some("^web".to_string()),
+            ..Default::default()
+        };
+        let query = build_query(&filter).build().unwrap();
+        assert!(query.contains(r#"["=","catalog_environment","production"]"#));
+        assert!(query.contains("latest_report_status"));
+        assert!(query.contains(r#"["~","certname","^web"]"#));
+    }
+
+    #[test]
+    fn test_validate_filter() {
+        assert!(validate_filter(&SmartListFilter::default()).is_ok());
+
+        let bad_status = SmartListFilter {
+            statuses: vec!["broken".to_string()],
+            ..Default::default()
+        };
+        assert!(validate_filter(&bad_status).is_err());
+
+        let bad_regex = SmartListFilter {
+            certname_pattern: Some("([".to_string()),
+            ..Default::default()
+        };
+        assert!(validate_filter(&bad_regex).is_err());
+
+        let injected_env = SmartListFilter {
+            environments: vec!["prod\"]".to_string()],
+            ..Default::default()
+        };
+        assert!(validate_filter(&injected_env).is_err());
+
+        let bad_in = filter_with_facts(vec![SmartListFactCondition {
+            fact_path: "os.family".to_string(),
+            operator: RuleOperator::In,
+            value: json!("RedHat"),
+        }]);
+        assert!(validate_filter(&bad_in).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_member_counts() {
+        let list = SmartList {
+            id: Uuid::new_v4(),
+            organization_id: Uuid::new_v4(),
+            name: "web".to_string(),
+            description: None,
+            filter: SmartListFilter::default(),
+            is_shared: false,
+            created_by: Uuid::new_v4(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        let counts = MemberCounts::new(Duration::from_millis(50));
+        assert!(counts.get(&list).await.is_none());
+
+        assert!(!counts.record(&list, 12).await.cached);
+        let cached = counts.get(&list).await.unwrap();
+        assert_eq!(cached.count, 12);
+        assert!(cached.cached);
+
+        // Dropped when the list is changed, or on request
+        let mut changed = list.clone();
+        changed.updated_at += chrono::Duration::seconds(1);
+        assert!(counts.get(&changed).await.is_none());
+        counts.forget(list.id).await;
+        assert!(counts.get(&list).await.is_none());
+        counts.record(&list, 12).await;
+        counts.clear().await;
+        assert!(counts.get(&list).await.is_none());
+
+        // Expired
+        counts.record(&list, 12).await;
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(counts.get(&list).await.is_none());
+
+        // Disabled by a zero TTL
+        let disabled = MemberCounts::new(Duration::ZERO);
+        disabled.record(&list, 12).await;
+        assert!(disabled.get(&list).await.is_none());
+    }
+
+    #[test]
+    fn test_is_visible_to() {
+        let owner = Uuid::new_v4();
+        let other = Uuid::new_v4();
+        let mut list = SmartList {
+            id: Uuid::new_v4(),
+            organization_id: Uuid::new_v4(),
+            name: "web".to_string(),
+            description: None,
+            filter: SmartListFilter::default(),
+            is_shared: false,
+            created_by: owner,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        assert!(is_visible_to(&list, owner, false));
+        assert!(!is_visible_to(&list, other, false));
+        assert!(is_visible_to(&list, other, true));
+
+        list.is_shared = true;
+        assert!(is_visible_to(&list, other, false));
+    }
+}
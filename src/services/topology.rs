@@ -0,0 +1,530 @@
+//! Infrastructure topology
+//!
+//! Builds the graph of the infrastructure topology widget: the servers,
+//! PuppetDB, the CA and the agents. Servers are found from the facts of the
+//! agents: `servername`, set by the server that compiled an agent's last
+//! catalog, or else the agent's `server` setting. The server on the CA's
+//! host is the primary and the others are compile servers; without a CA
+//! configured, all of them are shown as primaries.
+//!
+//! PuppetDB and the CA are probed like in `/health/detailed`. An agent is
+//! unhealthy when its last run failed or it has not reported within the
+//! dashboard's inactivity threshold, and a server is unhealthy when none of
+//! its agents has.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Display;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+
+use crate::config::HealthConfig;
+use crate::models::{
+    Fact, InfrastructureTopology, Node, TopologyEdge, TopologyEdgeKind, TopologyNode,
+    TopologyNodeKind, TopologyStatus,
+};
+use crate::services::puppet_ca::PuppetCAService;
+use crate::services::puppetdb::{PuppetDbClient, QueryBuilder, QueryParams};
+
+/// Facts naming an agent's server, by precedence
+pub const SERVER_FACTS: [&str; 2] = ["servername", "server"];
+
+/// Agent nodes returned at most
+pub const MAX_AGENTS: usize = 2000;
+
+const PUPPETDB_ID: &str = "puppetdb";
+const CA_ID: &str = "ca";
+
+/// Outcome of probing PuppetDB or the CA
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentProbe {
+    /// Host name from the component's URL
+    pub host: String,
+    pub status: TopologyStatus,
+    pub message: Option<String>,
+}
+
+/// What the topology is built from
+pub struct TopologyInput<'a> {
+    pub agents: &'a [Node],
+    /// Facts of the agents named in [`SERVER_FACTS`]
+    pub server_facts: &'a [Fact],
+    pub puppetdb: Option<ComponentProbe>,
+    pub ca: Option<ComponentProbe>,
+    /// Whether to return a node for each agent, or only the servers' counts
+    pub include_agents: bool,
+    /// Agents that have not reported for this long are unhealthy
+    pub inactive_after: chrono::Duration,
+    pub now: DateTime<Utc>,
+}
+
+/// Query PuppetDB, probe the components and build the topology
+pub async fn load_topology(
+    puppetdb: &PuppetDbClient,
+    puppet_ca: Option<&PuppetCAService>,
+    health: &HealthConfig,
+    inactive_threshold_hours: u64,
+    include_agents: bool,
+) -> Result<InfrastructureTopology> {
+    let timeout = Duration::from_millis(health.timeout_ms);
+    let server_facts_query = QueryBuilder::new().in_array("name", &SERVER_FACTS);
+
+    let (agents, server_facts, puppetdb_probe, ca_probe) = tokio::join!(
+        puppetdb.get_nodes(),
+        puppetdb.query_facts_advanced(&server_facts_query, QueryParams::default()),
+        probe(
+            puppetdb.base_url(),
+            health.puppetdb_budget_ms,
+            timeout,
+            puppetdb.get_version(),
+        ),
+        async {
+            match puppet_ca {
+                Some(ca) => Some(
+                    probe(
+                        ca.base_url(),
+                        health.puppet_ca_budget_ms,
+                        timeout,
+                        ca.get_ca_bundle_pem(),
+                    )
+                    .await,
+                ),
+                None => None,
+            }
+        },
+    );
+
+    Ok(build_topology(TopologyInput {
+        agents: &agents?,
+        server_facts: &server_facts?,
+        puppetdb: Some(puppetdb_probe),
+        ca: ca_probe,
+        include_agents,
+        inactive_after: chrono::Duration::hours(inactive_threshold_hours as i64),
+        now: Utc::now(),
+    }))
+}
+
+/// Probe a component within the timeout and grade it against its latency
+/// budget
+async fn probe<T, E: Display>(
+    url: &str,
+    budget_ms: u64,
+    timeout: Duration,
+    check: impl Future<Output = Result<T, E>>,
+) -> ComponentProbe {
+    let started = Instant::now();
+    let result = tokio::time::timeout(timeout, check).await;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    let (status, message) = match result {
+        Ok(Ok(_)) if latency_ms > budget_ms => (
+            TopologyStatus::Degraded,
+            Some(format!(
+                "Responded in {} ms, over the {} ms budget",
+                latency_ms, budget_ms
+            )),
+        ),
+        Ok(Ok(_)) => (TopologyStatus::Healthy, None),
+        Ok(Err(e)) => (TopologyStatus::Unhealthy, Some(e.to_string())),
+        Err(_) => (
+            TopologyStatus::Unhealthy,
+            Some(format!("No response within {} ms", timeout.as_millis())),
+        ),
+    };
+
+    ComponentProbe {
+        host: url_host(url).unwrap_or_else(|| url.to_string()),
+        status,
+        message,
+    }
+}
+
+/// Lowercase host name of a URL
+fn url_host(url: &str) -> Option<String> {
+    reqwest::Url::parse(url)
+        .ok()?
+        .host_str()
+        .map(str::to_ascii_lowercase)
+}
+
+/// Agents of one server
+#[derive(Default)]
+struct ServerAgents {
+    count: usize,
+    failed: usize,
+    last_report: Option<DateTime<Utc>>,
+}
+
+/// Build the topology graph
+pub fn build_topology(input: TopologyInput<'_>) -> InfrastructureTopology {
+    let servers_by_agent = servers_by_agent(input.server_facts);
+    let inactive_hours = input.inactive_after.num_hours();
+    let is_inactive = |last_report: Option<DateTime<Utc>>| {
+        last_report.is_none_or(|at| input.now - at > input.inactive_after)
+    };
+
+    let mut servers: BTreeMap<&str, ServerAgents> = BTreeMap::new();
+    let mut agent_nodes = Vec::new();
+    let mut agent_edges = Vec::new();
+    let mut unassigned_agents = 0;
+    let mut truncated = false;
+
+    let mut agents: Vec<&Node> = input.agents.iter().collect();
+    agents.sort_by(|a, b| a.certname.cmp(&b.certname));
+    for agent in agents {
+        let failed = agent.latest_report_status.as_deref() == Some("failed");
+        let server = servers_by_agent.get(agent.certname.as_str());
+        match server {
+            Some(server) => {
+                let stats = servers.entry(server.as_str()).or_default();
+                stats.count += 1;
+                stats.failed += usize::from(failed);
+                stats.last_report = stats.last_report.max(agent.report_timestamp);
+            }
+            None => unassigned_agents += 1,
+        }
+
+        if !input.include_agents {
+            continue;
+        }
+        if agent_nodes.len() == MAX_AGENTS {
+            truncated = true;
+            continue;
+        }
+        let (status, message) = if is_inactive(agent.report_timestamp) {
+            (
+                TopologyStatus::Unhealthy,
+                Some(format!("No report for more than {} hours", inactive_hours)),
+            )
+        } else if failed {
+            (
+                TopologyStatus::Unhealthy,
+                Some("Last run failed".to_string()),
+            )
+        } else {
+            (TopologyStatus::Healthy, None)
+        };
+        let id = format!("agent:{}", agent.certname);
+        if let Some(server) = server {
+            agent_edges.push(TopologyEdge {
+                source: id.clone(),
+                target: server_id(server),
+                kind: TopologyEdgeKind::Catalog,
+            });
+        }
+        agent_nodes.push(TopologyNode {
+            id,
+            kind: TopologyNodeKind::Agent,
+            name: agent.certname.clone(),
+            status,
+            message,
+            agent_count: None,
+            failed_agent_count: None,
+            environment: agent.catalog_environment.clone(),
+            last_report: agent.report_timestamp,
+        });
+    }
+
+    let mut nodes = Vec::new();
+    let mut edges = Vec::new();
+    for (id, component, kind) in [
+        (PUPPETDB_ID, &input.puppetdb, TopologyNodeKind::Puppetdb),
+        (CA_ID, &input.ca, TopologyNodeKind::CertificateAuthority),
+    ] {
+        if let Some(component) = component {
+            nodes.push(component_node(id, kind, component));
+        }
+    }
+
+    let ca_host = input.ca.as_ref().map(|ca| ca.host.as_str());
+    let mut server_nodes: Vec<TopologyNode> = servers
+        .into_iter()
+        .map(|(name, agents)| {
+            let (status, message) = if is_inactive(agents.last_report) {
+                (
+                    TopologyStatus::Unhealthy,
+                    Some(format!(
+                        "No agent has reported for more than {} hours",
+                        inactive_hours
+                    )),
+                )
+            } else if agents.failed == agents.count {
+                (
+                    TopologyStatus::Degraded,
+                    Some("The last run of every agent failed".to_string()),
+                )
+            } else {
+                (TopologyStatus::Healthy, None)
+            };
+            let kind = if ca_host.is_none_or(|host| host == name) {
+                TopologyNodeKind::PuppetServer
+            } else {
+                TopologyNodeKind::CompileServer
+            };
+
+            TopologyNode {
+                id: server_id(name),
+                kind,
+                name: name.to_string(),
+                status,
+                message,
+                agent_count: Some(agents.count),
+                failed_agent_count: Some(agents.failed),
+                environment: None,
+                last_report: agents.last_report,
+            }
+        })
+        .collect();
+    // The primary first
+    server_nodes.sort_by_key(|node| node.kind != TopologyNodeKind::PuppetServer);
+
+    for server in &server_nodes {
+        if input.puppetdb.is_some() {
+            edges.push(TopologyEdge {
+                source: server.id.clone(),
+                target: PUPPETDB_ID.to_string(),
+                kind: TopologyEdgeKind::Storage,
+            });
+        }
+        if input.ca.is_some() {
+            edges.push(TopologyEdge {
+                source: server.id.clone(),
+                target: CA_ID.to_string(),
+                kind: TopologyEdgeKind::Certificates,
+            });
+        }
+    }
+    nodes.extend(server_nodes);
+
+    nodes.extend(agent_nodes);
+    edges.extend(agent_edges);
+
+    InfrastructureTopology {
+        nodes,
+        edges,
+        unassigned_agents,
+        truncated,
+        generated_at: input.now,
+    }
+}
+
+/// Server of each agent, from the first of [`SERVER_FACTS`] it reports
+fn servers_by_agent(facts: &[Fact]) -> HashMap<&str, String> {
+    let mut servers: HashMap<&str, (usize, String)> = HashMap::new();
+    for fact in facts {
+        let Some(precedence) = SERVER_FACTS.iter().position(|name| *name == fact.name) else {
+            continue;
+        };
+        let Some(server) = fact
+            .value
+            .as_str()
+            .map(|value| value.trim().to_ascii_lowercase())
+            .filter(|value| !value.is_empty())
+        else {
+            continue;
+        };
+        let current = servers.get(fact.certname.as_str());
+        if current.is_none_or(|(current, _)| precedence < *current) {
+            servers.insert(fact.certname.as_str(), (precedence, server));
+        }
+    }
+
+    servers
+        .into_iter()
+        .map(|(agent, (_, server))| (agent, server))
+        .collect()
+}
+
+fn server_id(name: &str) -> String {
+    format!("server:{}", name)
+}
+
+fn component_node(id: &str, kind: TopologyNodeKind, probe: &ComponentProbe) -> TopologyNode {
+    TopologyNode {
+        id: id.to_string(),
+        kind,
+        name: probe.host.clone(),
+        status: probe.status,
+        message: probe.message.clone(),
+        agent_count: None,
+        failed_agent_count: None,
+        environment: None,
+        last_report: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn agent(certname: &str, status: &str, hours_ago: i64, now: DateTime<Utc>) -> Node {
+        serde_json::from_value(json!({
+            "certname": certname,
+            "latest_report_status": status,
+            "report_timestamp": now - chrono::Duration::hours(hours_ago),
+            "catalog_environment": "production",
+        }))
+        .unwrap()
+    }
+
+    fn fact(certname: &str, name: &str, value: &str) -> Fact {
+        Fact {
+            certname: certname.to_string(),
+            name: name.to_string(),
+            value: json!(value),
+            environment: None,
+        }
+    }
+
+    fn component(host: &str) -> ComponentProbe {
+        ComponentProbe {
+            host: host.to_string(),
+            status: TopologyStatus::Healthy,
+            message: None,
+        }
+    }
+
+    fn input<'a>(agents: &'a [Node], facts: &'a [Fact], now: DateTime<Utc>) -> TopologyInput<'a> {
+        TopologyInput {
+            agents,
+            server_facts: facts,
+            puppetdb: Some(component("puppetdb.example.com")),
+            ca: Some(component("primary.example.com")),
+            include_agents: true,
+            inactive_after: chrono::Duration::hours(24),
+            now,
+        }
+    }
+
+    fn node<'a>(topology: &'a InfrastructureTopology, id: &str) -> &'a TopologyNode {
+        topology.nodes.iter().find(|node| node.id == id).unwrap()
+    }
+
+    #[test]
+    fn test_build_topology() {
+        let now = Utc::now();
+        let agents = [
+            agent("web01", "changed", 1, now),
+            agent("web02", "failed", 1, now),
+            agent("db01", "unchanged", 48, now),
+            agent("orphan", "unchanged", 1, now),
+        ];
+        let facts = [
+            fact("web01", "server", "puppet.example.com"),
+            fact("web01", "servername", "Primary.example.com"),
+            fact("web02", "server", "compile01.example.com"),
+            fact("db01", "servername", "compile02.example.com"),
+        ];
+
+        let topology = build_topology(input(&agents, &facts, now));
+
+        // servername wins over server
+        let primary = node(&topology, "server:primary.example.com");
+        assert_eq!(primary.kind, TopologyNodeKind::PuppetServer);
+        assert_eq!(primary.agent_count, Some(1));
+        assert_eq!(primary.status, TopologyStatus::Healthy);
+        assert_eq!(topology.nodes[2].id, primary.id);
+
+        let compile01 = node(&topology, "server:compile01.example.com");
+        assert_eq!(compile01.kind, TopologyNodeKind::CompileServer);
+        assert_eq!(compile01.status, TopologyStatus::Degraded);
+        assert_eq!(
+            node(&topology, "server:compile02.example.com").status,
+            TopologyStatus::Unhealthy
+        );
+
+        assert_eq!(
+            node(&topology, "agent:web01").status,
+            TopologyStatus::Healthy
+        );
+        assert_eq!(
+            node(&topology, "agent:web02").status,
+            TopologyStatus::Unhealthy
+        );
+        assert_eq!(
+            node(&topology, "agent:db01").status,
+            TopologyStatus::Unhealthy
+        );
+        assert_eq!(topology.unassigned_agents, 1);
+
+        assert!(topology.edges.contains(&TopologyEdge {
+            source: "agent:web02".to_string(),
+            target: "server:compile01.example.com".to_string(),
+            kind: TopologyEdgeKind::Catalog,
+        }));
+        assert!(!topology
+            .edges
+            .iter()
+            .any(|edge| edge.source == "agent:orphan"));
+        // Each of the three servers uses PuppetDB and the CA
+        let server_edges = topology
+            .edges
+            .iter()
+            .filter(|edge| edge.kind != TopologyEdgeKind::Catalog)
+            .count();
+        assert_eq!(server_edges, 6);
+    }
+
+    #[test]
+    fn test_build_topology_without_agents() {
+        let now = Utc::now();
+        let agents = [agent("web01", "changed", 1, now)];
+        let facts = [fact("web01", "servername", "primary.example.com")];
+        let mut input = input(&agents, &facts, now);
+        input.include_agents = false;
+        input.ca = None;
+
+        let topology = build_topology(input);
+        let kinds: Vec<_> = topology.nodes.iter().map(|node| node.kind).collect();
+        assert_eq!(
+            kinds,
+            [TopologyNodeKind::Puppetdb, TopologyNodeKind::PuppetServer]
+        );
+        assert_eq!(topology.nodes[1].agent_count, Some(1));
+        assert_eq!(topology.edges.len(), 1);
+    }
+
+    #[test]
+    fn test_build_topology_caps_agents() {
+        let now = Utc::now();
+        let agents: Vec<Node> = (0..MAX_AGENTS + 5)
+            .map(|i| agent(&format!("node{:05}", i), "changed", 1, now))
+            .collect();
+        let facts: Vec<Fact> = agents
+            .iter()
+            .map(|agent| fact(&agent.certname, "servername", "primary.example.com"))
+            .collect();
+
+        let topology = build_topology(input(&agents, &facts, now));
+        assert!(topology.truncated);
+        let agent_nodes = topology
+            .nodes
+            .iter()
+            .filter(|node| node.kind == TopologyNodeKind::Agent)
+            .count();
+        assert_eq!(agent_nodes, MAX_AGENTS);
+        let catalog_edges = topology
+            .edges
+            .iter()
+            .filter(|edge| edge.kind == TopologyEdgeKind::Catalog)
+            .count();
+        assert_eq!(catalog_edges, MAX_AGENTS);
+        assert_eq!(
+            node(&topology, "server:primary.example.com").agent_count,
+            Some(MAX_AGENTS + 5)
+        );
+    }
+
+    #[test]
+    fn test_url_host() {
+        assert_eq!(
+            url_host("https://PuppetDB.example.com:8081").as_deref(),
+            Some("puppetdb.example.com")
+        );
+        assert_eq!(url_host("not a url"), None);
+    }
+}
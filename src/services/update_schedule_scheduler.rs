@@ -12,9 +12,12 @@ use tokio::time::{interval, Duration};
 use tracing::{error, info, warn};
 
 use crate::db::{repository::GroupRepository, InventoryRepository, SettingsRepository};
+use crate::services::health::{self, HealthTracker};
 use crate::services::notification::NotificationService;
 use crate::services::puppetdb::PuppetDbClient;
 use crate::services::scheduler::calculate_next_run;
+use crate::services::secrets::SecretsResolver;
+use crate::services::webhooks::Webhooks;
 use crate::services::AlertingService;
 
 type DbPool = SqlitePool;
@@ -35,6 +38,12 @@ pub struct UpdateScheduleSchedulerState {
     puppetdb: Option<Arc<PuppetDbClient>>,
     /// Notification service used to deliver update-job alerts.
     notification_service: Arc<NotificationService>,
+    /// Webhooks that fired update-job alerts are announced to.
+    webhooks: Webhooks,
+    /// Resolves alert channel passwords written as secret references.
+    secrets: Option<Arc<SecretsResolver>>,
+    /// Records each run for the scheduler health check.
+    health: HealthTracker,
 }
 
 impl UpdateScheduleSchedulerState {
@@ -43,6 +52,9 @@ impl UpdateScheduleSchedulerState {
         inventory_pool: DbPool,
         puppetdb: Option<Arc<PuppetDbClient>>,
         notification_service: Arc<NotificationService>,
+        webhooks: Webhooks,
+        secrets: Option<Arc<SecretsResolver>>,
+        health: HealthTracker,
     ) -> Self {
         Self {
             running: Arc::new(RwLock::new(false)),
@@ -50,6 +62,9 @@ impl UpdateScheduleSchedulerState {
             inventory_pool,
             puppetdb,
             notification_service,
+            webhooks,
+            secrets,
+            health,
         }
     }
 
@@ -66,12 +81,18 @@ pub fn start_update_schedule_scheduler(
     inventory_pool: DbPool,
     puppetdb: Option<Arc<PuppetDbClient>>,
     notification_service: Arc<NotificationService>,
+    webhooks: Webhooks,
+    secrets: Option<Arc<SecretsResolver>>,
+    health: HealthTracker,
 ) -> UpdateScheduleSchedulerState {
     let state = UpdateScheduleSchedulerState::new(
         main_pool,
         inventory_pool,
         puppetdb,
         notification_service,
+        webhooks,
+        secrets,
+        health,
     );
     let state_clone = state.clone();
 
@@ -91,6 +112,8 @@ pub fn start_update_schedule_scheduler(
 }
 
 async fn schedule_check_task(state: UpdateScheduleSchedulerState) {
+    state.health.record_success(health::SCHEDULER);
+
     // Wait a bit before first check to let the app fully start
     tokio::time::sleep(Duration::from_secs(30)).await;
 
@@ -105,6 +128,7 @@ async fn schedule_check_task(state: UpdateScheduleSchedulerState) {
             break;
         }
         drop(running);
+        state.health.record_success(health::SCHEDULER);
 
         if let Err(e) = process_due_schedules(
             &state.main_pool,
@@ -277,7 +301,7 @@ fn compute_next_run(
 /// Reads the configured maximum update-job runtime (minutes) from settings,
 /// defaulting to [`DEFAULT_MAX_RUNTIME_MINUTES`].
 async fn read_max_runtime_minutes(main_pool: &SqlitePool) -> i64 {
-    let settings_repo = SettingsRepository::new(main_pool.clone());
+    let settings_repo = SettingsRepository::new(main_pool.clone(), None);
     match settings_repo.get_setting("update_jobs.max_runtime_minutes").await {
         Ok(Some(setting)) => setting
             .value
@@ -321,7 +345,9 @@ async fn enforce_update_job_limits_and_alerts(
         state.puppetdb.clone(),
         Some(state.notification_service.clone()),
     )
-    .with_inventory_pool(state.inventory_pool.clone());
+    .with_inventory_pool(state.inventory_pool.clone())
+    .with_webhooks(state.webhooks.clone())
+    .with_secrets(state.secrets.clone());
 
     match alerting.evaluate_update_job_rules().await {
         Ok(alerts) if !alerts.is_empty() => {
@@ -0,0 +1,258 @@
+//! Bulk user import
+//!
+//! Parses the CSV or JSON body of `POST /api/v1/users/import` into rows and
+//! runs the checks that need no database. The endpoint resolves roles and
+//! organizations and creates the users row by row, so one bad row never
+//! stops the others.
+
+use serde::Deserialize;
+
+use crate::models::ImportUserRow;
+
+/// Most rows a single import may contain
+pub const MAX_IMPORT_ROWS: usize = 1000;
+
+/// Columns a CSV import may have; `username` and `email` are required
+const CSV_COLUMNS: &[&str] = &[
+    "username",
+    "email",
+    "role",
+    "organization",
+    "password",
+    "password_hash",
+];
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonImport {
+    Rows(Vec<ImportUserRow>),
+    Wrapped { users: Vec<ImportUserRow> },
+}
+
+/// Parse a JSON array of rows, or an object with a `users` array
+pub fn parse_json(body: &[u8]) -> Result<Vec<ImportUserRow>, String> {
+    let rows = match serde_json::from_slice::<JsonImport>(body) {
+        Ok(JsonImport::Rows(rows)) | Ok(JsonImport::Wrapped { users: rows }) => rows,
+        Err(e) => return Err(format!("Invalid JSON import: {}", e)),
+    };
+    check_row_count(rows)
+}
+
+/// Parse CSV with a header row naming the columns
+///
+/// Fields may be quoted with `"`; a doubled quote inside a quoted field is a
+/// literal quote. Empty fields are treated as missing.
+pub fn parse_csv(body: &str) -> Result<Vec<ImportUserRow>, String> {
+    let mut records = csv_records(body.trim_start_matches('\u{feff}'))?.into_iter();
+    let header = records
+        .next()
+        .ok_or_else(|| "CSV import is empty".to_string())?;
+
+    let columns: Vec<String> = header.iter().map(|c| c.trim().to_lowercase()).collect();
+    for (i, column) in columns.iter().enumerate() {
+        if !CSV_COLUMNS.contains(&column.as_str()) {
+            return Err(format!(
+                "Unknown CSV column '{}'; expected {}",
+                column,
+                CSV_COLUMNS.join(", ")
+            ));
+        }
+        if columns[..i].contains(column) {
+            return Err(format!("Duplicate CSV column '{}'", column));
+        }
+    }
+    for required in ["username", "email"] {
+        if !columns.iter().any(|c| c == required) {
+            return Err(format!("CSV column '{}' is required", required));
+        }
+    }
+
+    let mut rows = Vec::new();
+    for (line, record) in records.enumerate() {
+        // Skip blank lines, e.g. a trailing newline
+        if record.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+        if record.len() != columns.len() {
+            return Err(format!(
+                "CSV record {} has {} fields, expected {}",
+                line + 1,
+                record.len(),
+                columns.len()
+            ));
+        }
+
+        let mut row = ImportUserRow::default();
+        for (column, value) in columns.iter().zip(record) {
+            let value = value.trim().to_string();
+            let optional = (!value.is_empty()).then(|| value.clone());
+            match column.as_str() {
+                "username" => row.username = value,
+                "email" => row.email = value,
+                "role" => row.role = optional,
+                "organization" => row.organization = optional,
+                "password" => row.password = optional,
+                "password_hash" => row.password_hash = optional,
+                _ => unreachable!("columns are checked above"),
+            }
+        }
+        rows.push(row);
+    }
+    check_row_count(rows)
+}
+
+fn check_row_count(rows: Vec<ImportUserRow>) -> Result<Vec<ImportUserRow>, String> {
+    if rows.is_empty() {
+        return Err("The import contains no users".to_string());
+    }
+    if rows.len() > MAX_IMPORT_ROWS {
+        return Err(format!(
+            "The import contains {} users; at most {} are allowed",
+            rows.len(),
+            MAX_IMPORT_ROWS
+        ));
+    }
+    Ok(rows)
+}
+
+/// Split CSV text into records of fields
+fn csv_records(body: &str) -> Result<Vec<Vec<String>>, String> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = body.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    chars.next();
+                    field.push('"');
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                in_quotes = true;
+            }
+            ',' => record.push(std::mem::take(&mut field)),
+            '\r' if chars.peek() == Some(&'\n') => {}
+            '\n' | '\r' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err("CSV import has an unterminated quoted field".to_string());
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// Checks of a single row that need no database
+pub fn validate_row(row: &ImportUserRow) -> Result<(), String> {
+    if row.username.chars().count() < 3 {
+        return Err("Username must be at least 3 characters".to_string());
+    }
+    if !row.email.contains('@') {
+        return Err("Invalid email address".to_string());
+    }
+    if row.password.is_some() && row.password_hash.is_some() {
+        return Err("Specify either password or password_hash, not both".to_string());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_csv() {
+        let rows = parse_csv(
+            "Username,Email,Role,Organization\r\n\
+             alice,alice@example.com,operator,acme\r\n\
+             \"bob\",\"bob@example.com\",,\r\n\
+             \"o\"\"neil\",\"o'neil@example.com, x\",viewer,\n\n",
+        )
+        .unwrap();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].username, "alice");
+        assert_eq!(rows[0].role.as_deref(), Some("operator"));
+        assert_eq!(rows[0].organization.as_deref(), Some("acme"));
+        assert_eq!(rows[1].username, "bob");
+        assert_eq!(rows[1].role, None);
+        assert_eq!(rows[1].organization, None);
+        assert_eq!(rows[2].username, "o\"neil");
+        assert_eq!(rows[2].email, "o'neil@example.com, x");
+    }
+
+    #[test]
+    fn test_parse_csv_errors() {
+        assert!(parse_csv("").unwrap_err().contains("empty"));
+        assert!(parse_csv("username,email\n")
+            .unwrap_err()
+            .contains("no users"));
+        assert!(parse_csv("username,email,shell\na,b,c")
+            .unwrap_err()
+            .contains("Unknown CSV column"));
+        assert!(parse_csv("username,role\nalice,viewer")
+            .unwrap_err()
+            .contains("'email' is required"));
+        assert!(parse_csv("username,email\nalice")
+            .unwrap_err()
+            .contains("has 1 fields"));
+        assert!(parse_csv("username,email\n\"alice,a@b")
+            .unwrap_err()
+            .contains("unterminated"));
+    }
+
+    #[test]
+    fn test_parse_json() {
+        let rows = parse_json(br#"[{"username": "alice", "email": "a@example.com"}]"#).unwrap();
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].role, None);
+
+        let rows = parse_json(
+            br#"{"users": [{"username": "bob", "email": "b@example.com", "role": "admin"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(rows[0].role.as_deref(), Some("admin"));
+
+        assert!(parse_json(b"[]").unwrap_err().contains("no users"));
+        assert!(parse_json(b"{\"users\": 1}").is_err());
+    }
+
+    #[test]
+    fn test_validate_row() {
+        let mut row = ImportUserRow {
+            username: "alice".to_string(),
+            email: "alice@example.com".to_string(),
+            ..Default::default()
+        };
+        assert!(validate_row(&row).is_ok());
+
+        row.password = Some("secret".to_string());
+        row.password_hash = Some("$argon2id$...".to_string());
+        assert!(validate_row(&row).unwrap_err().contains("not both"));
+
+        row.password_hash = None;
+        row.email = "alice".to_string();
+        assert!(validate_row(&row).is_err());
+
+        row.email = "alice@example.com".to_string();
+        row.username = "al".to_string();
+        assert!(validate_row(&row).is_err());
+    }
+}
@@ -0,0 +1,538 @@
+//! Outbound webhooks
+//!
+//! [`Webhooks::emit`] turns an event into one queued delivery per enabled webhook that
+//! subscribes to it. Events scoped to an organization (failed runs, group
+//! changes) reach that organization's webhooks; instance-wide events
+//! (deployments, certificate requests, alerts) reach every organization's.
+//!
+//! A background dispatcher sends due deliveries as a JSON `POST`. The body is
+//! signed with the webhook secret: `X-OpenVox-Signature` carries
+//! `sha256=<hex HMAC-SHA256 of the body>`, next to `X-OpenVox-Event` and
+//! `X-OpenVox-Delivery`. Any 2xx response counts as delivered; anything else
+//! is retried with exponential backoff until `webhooks.max_attempts` is
+//! reached. Deliveries live in the database, so pending retries survive a
+//! restart and the delivery log can be inspected through the API.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use hmac::{Hmac, KeyInit, Mac};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use tokio::sync::{Notify, RwLock};
+use tokio::time::interval;
+use tracing::{debug, error, info, warn};
+use uuid::Uuid;
+
+use crate::config::WebhooksConfig;
+use crate::db::webhook_repository::DeliveryAttempt;
+use crate::db::{DbPool, WebhookRepository};
+use crate::models::{Webhook, WebhookDelivery, WebhookDeliveryStatus, WebhookEvent};
+use crate::services::maintenance::ActiveMaintenance;
+use crate::services::puppetdb::{PuppetDbClient, QueryBuilder, QueryParams};
+use crate::services::puppetdb_registry::PuppetDbRegistry;
+use crate::services::settings_encryption::SettingsCipher;
+
+/// How often the dispatcher looks for due retries when not woken by an event
+const DISPATCH_INTERVAL_SECS: u64 = 15;
+
+/// Deliveries sent per dispatcher pass
+const DISPATCH_BATCH_SIZE: u32 = 50;
+
+/// Longest response body kept in the delivery log
+const MAX_RESPONSE_BODY_CHARS: usize = 2048;
+
+/// Failed reports fetched per organization and poll
+const FAILED_RUN_QUERY_LIMIT: u32 = 500;
+
+/// Queues events for delivery and wakes the dispatcher
+///
+/// Clones share the wake-up signal of the dispatcher they are passed to.
+#[derive(Clone)]
+pub struct Webhooks {
+    pool: DbPool,
+    cipher: Option<SettingsCipher>,
+    wake: Arc<Notify>,
+}
+
+impl Webhooks {
+    /// `cipher` encrypts the webhook secrets stored in the database
+    pub fn new(pool: DbPool, cipher: Option<SettingsCipher>) -> Self {
+        Self {
+            pool,
+            cipher,
+            wake: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Repository of the webhooks and their deliveries
+    pub fn repository(&self) -> WebhookRepository<'_> {
+        WebhookRepository::new(&self.pool, self.cipher.as_ref())
+    }
+
+    /// Queue an event for every subscribed webhook without waiting
+    ///
+    /// `organization_id` limits the event to one organization's webhooks;
+    /// `None` sends it to every organization.
+    pub fn emit(&self, organization_id: Option<Uuid>, event: WebhookEvent, data: Value) {
+        let webhooks = self.clone();
+        tokio::spawn(async move {
+            match webhooks.enqueue(organization_id, event, data).await {
+                Ok(0) => {}
+                Ok(queued) => {
+                    debug!(
+                        "Queued {} webhook deliveries for {}",
+                        queued,
+                        event.as_str()
+                    );
+                    webhooks.wake.notify_one();
+                }
+                Err(e) => error!("Failed to queue webhook event {}: {:#}", event.as_str(), e),
+            }
+        });
+    }
+
+    /// Queue a delivery of an event to one webhook, e.g. a ping or a
+    /// redelivery
+    pub async fn queue_delivery(
+        &self,
+        webhook: &Webhook,
+        event: WebhookEvent,
+        payload: &Value,
+    ) -> Result<WebhookDelivery> {
+        let delivery = self
+            .repository()
+            .create_delivery(webhook.id, event, payload)
+            .await?;
+        self.wake.notify_one();
+        Ok(delivery)
+    }
+
+    async fn enqueue(
+        &self,
+        organization_id: Option<Uuid>,
+        event: WebhookEvent,
+        data: Value,
+    ) -> Result<usize> {
+        let repo = self.repository();
+        let mut queued = 0;
+        for webhook in repo.list_enabled(organization_id).await? {
+            if !webhook.subscribes_to(event) {
+                continue;
+            }
+            let payload = event_payload(webhook.organization_id, event, data.clone());
+            repo.create_delivery(webhook.id, event, &payload).await?;
+            queued += 1;
+        }
+        Ok(queued)
+    }
+}
+
+/// Handle for stopping the webhook dispatcher
+#[derive(Clone)]
+pub struct WebhookDispatcherState {
+    running: Arc<RwLock<bool>>,
+}
+
+impl WebhookDispatcherState {
+    pub async fn is_running(&self) -> bool {
+        *self.running.read().await
+    }
+
+    /// Request the dispatcher loops to stop at their next tick
+    pub async fn stop(&self) {
+        *self.running.write().await = false;
+        info!("Webhook dispatcher stop requested");
+    }
+}
+
+/// Spawn the delivery dispatcher and the failed run watcher
+///
+/// Events emitted through `webhooks` and its clones wake the dispatcher.
+/// `puppetdb` is the shared client; organizations with their own PuppetDB
+/// mapping are resolved through `tenants`.
+pub fn start_webhook_dispatcher(
+    webhooks: Webhooks,
+    puppetdb: Option<Arc<PuppetDbClient>>,
+    tenants: PuppetDbRegistry,
+    config: &WebhooksConfig,
+) -> WebhookDispatcherState {
+    let state = WebhookDispatcherState {
+        running: Arc::new(RwLock::new(true)),
+    };
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(config.timeout_secs))
+        .user_agent("OpenVox-Webhooks/1.0")
+        .build()
+        .expect("Failed to build HTTP client for webhooks");
+
+    tokio::spawn(dispatch_loop(
+        state.clone(),
+        webhooks.clone(),
+        client,
+        config.clone(),
+    ));
+    if config.failed_run_poll_secs > 0 {
+        tokio::spawn(failed_run_loop(
+            state.clone(),
+            webhooks,
+            puppetdb,
+            tenants,
+            config.failed_run_poll_secs,
+        ));
+    }
+
+    info!(
+        "Webhook dispatcher started (max attempts: {}, failed run poll: {}s)",
+        config.max_attempts, config.failed_run_poll_secs
+    );
+    state
+}
+
+/// Body sent for an event
+pub fn event_payload(organization_id: Uuid, event: WebhookEvent, data: Value) -> Value {
+    json!({
+        "id": Uuid::new_v4(),
+        "event": event.as_str(),
+        "organization_id": organization_id,
+        "created_at": Utc::now(),
+        "data": data,
+    })
+}
+
+/// Signature header value of a body: `sha256=<hex HMAC-SHA256>`
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Delay before attempt `attempts + 1`, doubling from `retry_delay_secs`
+pub fn retry_delay(config: &WebhooksConfig, attempts: u32) -> Duration {
+    let delay = config
+        .retry_delay_secs
+        .saturating_mul(1u64 << attempts.saturating_sub(1).min(20));
+    Duration::from_secs(delay.min(config.max_retry_delay_secs))
+}
+
+async fn dispatch_loop(
+    state: WebhookDispatcherState,
+    webhooks: Webhooks,
+    client: reqwest::Client,
+    config: WebhooksConfig,
+) {
+    let mut timer = interval(Duration::from_secs(DISPATCH_INTERVAL_SECS));
+    let mut last_prune: Option<DateTime<Utc>> = None;
+
+    loop {
+        tokio::select! {
+            _ = timer.tick() => {}
+            _ = webhooks.wake.notified() => {}
+        }
+
+        if !*state.running.read().await {
+            info!("Webhook dispatcher stopping");
+            break;
+        }
+
+        if let Err(e) = dispatch_due(&webhooks, &client, &config).await {
+            error!("Webhook dispatch failed: {:#}", e);
+        }
+
+        let now = Utc::now();
+        if last_prune.is_none_or(|at| now - at > ChronoDuration::hours(1)) {
+            last_prune = Some(now);
+            let cutoff = now - ChronoDuration::days(config.retention_days as i64);
+            match webhooks.repository().prune_deliveries(cutoff).await {
+                Ok(0) => {}
+                Ok(pruned) => info!("Pruned {} old webhook deliveries", pruned),
+                Err(e) => warn!("Failed to prune webhook deliveries: {:#}", e),
+            }
+        }
+    }
+}
+
+/// Send every due delivery, in batches
+async fn dispatch_due(
+    webhooks: &Webhooks,
+    client: &reqwest::Client,
+    config: &WebhooksConfig,
+) -> Result<()> {
+    let repo = webhooks.repository();
+    loop {
+        let due = repo
+            .list_due_deliveries(Utc::now(), DISPATCH_BATCH_SIZE)
+            .await?;
+        if due.is_empty() {
+            return Ok(());
+        }
+        for delivery in due {
+            let error = match repo.find_by_id(delivery.webhook_id).await {
+                Ok(Some(webhook)) => {
+                    attempt_delivery(&repo, client, config, &webhook, &delivery).await?;
+                    continue;
+                }
+                Ok(None) => "Webhook no longer exists".to_string(),
+                Err(e) => format!("{:#}", e),
+            };
+            let attempt = DeliveryAttempt {
+                error: Some(error),
+                ..Default::default()
+            };
+            repo.record_attempt(
+                delivery.id,
+                WebhookDeliveryStatus::Failed,
+                delivery.attempts,
+                &attempt,
+                None,
+            )
+            .await?;
+        }
+    }
+}
+
+async fn attempt_delivery(
+    repo: &WebhookRepository<'_>,
+    client: &reqwest::Client,
+    config: &WebhooksConfig,
+    webhook: &Webhook,
+    delivery: &WebhookDelivery,
+) -> Result<()> {
+    let attempts = delivery.attempts + 1;
+    let attempt = send(client, webhook, delivery).await;
+    let delivered = attempt
+        .response_status
+        .is_some_and(|status| (200..300).contains(&status));
+
+    if delivered {
+        debug!(
+            "Delivered {} to webhook '{}' ({})",
+            delivery.event.as_str(),
+            webhook.name,
+            delivery.id
+        );
+        return repo
+            .record_attempt(
+                delivery.id,
+                WebhookDeliveryStatus::Delivered,
+                attempts,
+                &attempt,
+                None,
+            )
+            .await;
+    }
+
+    let reason = attempt
+        .error
+        .clone()
+        .or_else(|| attempt.response_status.map(|s| format!("HTTP {}", s)))
+        .unwrap_or_default();
+    if attempts >= config.max_attempts {
+        warn!(
+            "Giving up on {} delivery {} to webhook '{}' after {} attempts: {}",
+            delivery.event.as_str(),
+            delivery.id,
+            webhook.name,
+            attempts,
+            reason
+        );
+        return repo
+            .record_attempt(
+                delivery.id,
+                WebhookDeliveryStatus::Failed,
+                attempts,
+                &attempt,
+                None,
+            )
+            .await;
+    }
+
+    let delay = retry_delay(config, attempts);
+    debug!(
+        "Webhook '{}' delivery {} failed (attempt {}), retrying in {}s: {}",
+        webhook.name,
+        delivery.id,
+        attempts,
+        delay.as_secs(),
+        reason
+    );
+    let next_attempt_at =
+        Utc::now() + ChronoDuration::from_std(delay).unwrap_or(ChronoDuration::zero());
+    repo.record_attempt(
+        delivery.id,
+        WebhookDeliveryStatus::Retrying,
+        attempts,
+        &attempt,
+        Some(next_attempt_at),
+    )
+    .await
+}
+
+async fn send(
+    client: &reqwest::Client,
+    webhook: &Webhook,
+    delivery: &WebhookDelivery,
+) -> DeliveryAttempt {
+    let body = delivery.payload.to_string();
+    let result = client
+        .post(&webhook.url)
+        .header("Content-Type", "application/json")
+        .header("X-OpenVox-Event", delivery.event.as_str())
+        .header("X-OpenVox-Delivery", delivery.id.to_string())
+        .header(
+            "X-OpenVox-Signature",
+            sign(&webhook.secret, body.as_bytes()),
+        )
+        .body(body)
+        .send()
+        .await;
+
+    match result {
+        Ok(response) => {
+            let status = response.status().as_u16();
+            let body = response.text().await.unwrap_or_default();
+            DeliveryAttempt {
+                response_status: Some(status),
+                response_body: (!body.is_empty())
+                    .then(|| body.chars().take(MAX_RESPONSE_BODY_CHARS).collect()),
+                error: None,
+            }
+        }
+        Err(e) => DeliveryAttempt {
+            error: Some(e.to_string()),
+            ..Default::default()
+        },
+    }
+}
+
+/// Poll PuppetDB for failed runs of organizations that subscribe to them
+///
+/// Only reports received after the watcher started are considered. Nodes in
+/// an active maintenance window are skipped, as they are for alerts.
+async fn failed_run_loop(
+    state: WebhookDispatcherState,
+    webhooks: Webhooks,
+    puppetdb: Option<Arc<PuppetDbClient>>,
+    tenants: PuppetDbRegistry,
+    poll_secs: u64,
+) {
+    let mut timer = interval(Duration::from_secs(poll_secs.max(30)));
+    let mut watermarks: HashMap<Uuid, DateTime<Utc>> = HashMap::new();
+
+    loop {
+        timer.tick().await;
+
+        if !*state.running.read().await {
+            break;
+        }
+
+        let subscribed = match webhooks.repository().list_enabled(None).await {
+            Ok(subscribed) => subscribed,
+            Err(e) => {
+                error!("Failed to list webhooks: {:#}", e);
+                continue;
+            }
+        };
+        let mut organizations: Vec<Uuid> = subscribed
+            .iter()
+            .filter(|w| w.subscribes_to(WebhookEvent::NodeFailedRun))
+            .map(|w| w.organization_id)
+            .collect();
+        organizations.sort();
+        organizations.dedup();
+
+        for org_id in organizations {
+            let now = Utc::now();
+            let Some(since) = watermarks.insert(org_id, now) else {
+                continue;
+            };
+            let Some(client) = tenants
+                .resolve(&webhooks.pool, org_id, puppetdb.clone())
+                .await
+            else {
+                continue;
+            };
+            if let Err(e) = emit_failed_runs(&webhooks, &client, org_id, since).await {
+                // Try the same window again on the next poll
+                watermarks.insert(org_id, since);
+                warn!(
+                    "Failed to check organization {} for failed runs: {:#}",
+                    org_id, e
+                );
+            }
+        }
+    }
+}
+
+async fn emit_failed_runs(
+    webhooks: &Webhooks,
+    client: &PuppetDbClient,
+    organization_id: Uuid,
+    since: DateTime<Utc>,
+) -> Result<()> {
+    let query = QueryBuilder::new()
+        .equals("status", "failed")
+        .greater_than("receive_time", &since.to_rfc3339());
+    let params = QueryParams::new()
+        .order_by("receive_time", true)
+        .limit(FAILED_RUN_QUERY_LIMIT);
+    let reports = client.query_reports_advanced(&query, params).await?;
+    if reports.is_empty() {
+        return Ok(());
+    }
+
+    let maintenance =
+        ActiveMaintenance::load_or_empty(&webhooks.pool, Some(client), Utc::now()).await;
+    for report in reports {
+        if maintenance.contains(&report.certname) {
+            continue;
+        }
+        webhooks.emit(
+            Some(organization_id),
+            WebhookEvent::NodeFailedRun,
+            json!({
+                "certname": report.certname,
+                "report_hash": report.hash,
+                "environment": report.environment,
+                "start_time": report.start_time,
+                "end_time": report.end_time,
+                "receive_time": report.receive_time,
+                "puppet_version": report.puppet_version,
+            }),
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign() {
+        // RFC 4231 test case 2
+        assert_eq!(
+            sign("Jefe", b"what do ya want for nothing?"),
+            "sha256=5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_retry_delay_doubles_up_to_the_cap() {
+        let config = WebhooksConfig {
+            retry_delay_secs: 30,
+            max_retry_delay_secs: 300,
+            ..Default::default()
+        };
+        assert_eq!(retry_delay(&config, 1), Duration::from_secs(30));
+        assert_eq!(retry_delay(&config, 2), Duration::from_secs(60));
+        assert_eq!(retry_delay(&config, 4), Duration::from_secs(240));
+        assert_eq!(retry_delay(&config, 5), Duration::from_secs(300));
+        assert_eq!(retry_delay(&config, 40), Duration::from_secs(300));
+    }
+}
@@ -0,0 +1,241 @@
+//! Data for custom dashboard widgets
+//!
+//! Runs a widget binding (a PQL query or a saved report) and shapes the
+//! outcome into chart-ready series: one list of labels shared by every
+//! series, plus the binding's thresholds.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::config::{WidgetBinding, WidgetDataSource, WidgetThreshold};
+use crate::db::repository::SavedReportRepository;
+use crate::db::DbPool;
+use crate::models::ReportResult;
+use crate::services::puppetdb::PuppetDbClient;
+use crate::services::ReportingService;
+
+/// Points returned for one widget at most
+const MAX_POINTS: usize = 500;
+
+/// Chart-ready data of a widget
+#[derive(Debug, Clone, Serialize)]
+pub struct WidgetData {
+    pub labels: Vec<String>,
+    pub series: Vec<WidgetSeries>,
+    pub thresholds: Vec<WidgetThreshold>,
+    /// Whether points beyond the limit were dropped
+    pub truncated: bool,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// One series of values, aligned with [`WidgetData::labels`]
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct WidgetSeries {
+    pub name: String,
+    pub values: Vec<f64>,
+}
+
+/// Labels and series before thresholds are attached
+#[derive(Debug, Default, PartialEq)]
+struct Chart {
+    labels: Vec<String>,
+    series: Vec<WidgetSeries>,
+    truncated: bool,
+}
+
+/// Who is loading the widget, to check saved report visibility
+pub struct WidgetViewer {
+    pub organization_id: Uuid,
+    pub user_id: Uuid,
+    /// Sees every saved report of the organization
+    pub sees_private_reports: bool,
+}
+
+/// Run a binding on behalf of a viewer
+pub async fn load_widget_data(
+    pool: &DbPool,
+    puppetdb: Option<Arc<PuppetDbClient>>,
+    viewer: &WidgetViewer,
+    binding: &WidgetBinding,
+) -> Result<WidgetData> {
+    let chart = match &binding.source {
+        WidgetDataSource::Pql {
+            query,
+            label_field,
+            value_fields,
+        } => {
+            let puppetdb = puppetdb.ok_or_else(|| anyhow!("PuppetDB is not configured"))?;
+            let rows: Vec<Value> = puppetdb.query(query).await?;
+            chart_from_rows(&rows, label_field, value_fields)
+        }
+        WidgetDataSource::SavedReport { report_id } => {
+            let report = SavedReportRepository::new(pool)
+                .get_by_id(viewer.organization_id, *report_id)
+                .await?
+                .filter(|report| {
+                    report.is_public
+                        || report.created_by == viewer.user_id
+                        || viewer.sees_private_reports
+                })
+                .ok_or_else(|| anyhow!("Saved report {} not found", report_id))?;
+            let (result, _) = ReportingService::new(pool.clone(), puppetdb)
+                .generate_report(
+                    report.organization_id,
+                    report.created_by,
+                    report.report_type,
+                    &report.query_config,
+                )
+                .await?;
+            chart_from_report(&result)
+        }
+    };
+
+    Ok(WidgetData {
+        labels: chart.labels,
+        series: chart.series,
+        thresholds: binding.thresholds.clone(),
+        truncated: chart.truncated,
+        generated_at: Utc::now(),
+    })
+}
+
+/// One point per row: the label from `label_field`, one series per value field
+///
+/// Missing or non-numeric values chart as zero.
+fn chart_from_rows(rows: &[Value], label_field: &str, value_fields: &[String]) -> Chart {
+    let truncated = rows.len() > MAX_POINTS;
+    let rows = &rows[..rows.len().min(MAX_POINTS)];
+
+    let labels = rows
+        .iter()
+        .map(|row| match row.get(label_field) {
+            Some(Value::String(s)) => s.clone(),
+            Some(Value::Null) | None => "unknown".to_string(),
+            Some(other) => other.to_string(),
+        })
+        .collect();
+    let series = value_fields
+        .iter()
+        .map(|field| WidgetSeries {
+            name: field.clone(),
+            values: rows.iter().map(|row| number(row.get(field))).collect(),
+        })
+        .collect();
+
+    Chart {
+        labels,
+        series,
+        truncated,
+    }
+}
+
+fn number(value: Option<&Value>) -> f64 {
+    match value {
+        Some(Value::Number(n)) => n.as_f64().unwrap_or(0.0),
+        Some(Value::String(s)) => s.parse().unwrap_or(0.0),
+        Some(Value::Bool(b)) => f64::from(u8::from(*b)),
+        _ => 0.0,
+    }
+}
+
+/// The headline breakdown of each report type
+fn chart_from_report(result: &ReportResult) -> Chart {
+    let points: Vec<(String, f64)> = match result {
+        ReportResult::NodeHealth(report) => {
+            let s = &report.summary;
+            vec![
+                ("changed".to_string(), s.changed_count as f64),
+                ("unchanged".to_string(), s.unchanged_count as f64),
+                ("failed".to_string(), s.failed_count as f64),
+                ("noop".to_string(), s.noop_count as f64),
+                ("unreported".to_string(), s.unreported_count as f64),
+            ]
+        }
+        ReportResult::Compliance(report) => report
+            .by_severity
+            .iter()
+            .map(|b| (b.severity.as_str().to_string(), b.violation_count as f64))
+            .collect(),
+        ReportResult::ChangeTracking(report) => report
+            .changes_by_type
+            .iter()
+            .map(|b| (b.resource_type.clone(), b.change_count as f64))
+            .collect(),
+        ReportResult::DriftDetection(report) => report
+            .drifted_nodes
+            .iter()
+            .map(|n| (n.certname.clone(), n.drift_count as f64))
+            .collect(),
+        ReportResult::Custom(_) => Vec::new(),
+    };
+
+    let truncated = points.len() > MAX_POINTS;
+    let (labels, values) = points.into_iter().take(MAX_POINTS).unzip();
+    let name = match result {
+        ReportResult::NodeHealth(_) => "nodes",
+        ReportResult::Compliance(_) => "violations",
+        ReportResult::ChangeTracking(_) => "changes",
+        ReportResult::DriftDetection(_) => "drifted_facts",
+        ReportResult::Custom(_) => "values",
+    };
+
+    Chart {
+        labels,
+        series: vec![WidgetSeries {
+            name: name.to_string(),
+            values,
+        }],
+        truncated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_chart_from_rows_aligns_series_with_labels() {
+        let rows = vec![
+            json!({"catalog_environment": "production", "count": 12, "failed": "2"}),
+            json!({"catalog_environment": null, "count": 3}),
+        ];
+        let chart = chart_from_rows(
+            &rows,
+            "catalog_environment",
+            &["count".to_string(), "failed".to_string()],
+        );
+
+        assert_eq!(chart.labels, vec!["production", "unknown"]);
+        assert_eq!(
+            chart.series,
+            vec![
+                WidgetSeries {
+                    name: "count".to_string(),
+                    values: vec![12.0, 3.0],
+                },
+                WidgetSeries {
+                    name: "failed".to_string(),
+                    values: vec![2.0, 0.0],
+                },
+            ]
+        );
+        assert!(!chart.truncated);
+    }
+
+    #[test]
+    fn test_chart_from_rows_caps_points() {
+        let rows: Vec<Value> = (0..MAX_POINTS + 5)
+            .map(|i| json!({"certname": format!("node{}", i), "count": i}))
+            .collect();
+        let chart = chart_from_rows(&rows, "certname", &["count".to_string()]);
+
+        assert_eq!(chart.labels.len(), MAX_POINTS);
+        assert!(chart.truncated);
+    }
+}
@@ -1,7 +1,7 @@
 //! Error types and handling
 //!
 //! This module provides a comprehensive error handling framework for the application.
-//! All errors are converted to a consistent JSON response format.
+//! All errors are converted to a consistent JSON response format, [`ApiError`].
 
 use axum::{
     http::StatusCode,
@@ -12,6 +12,8 @@ use serde::Serialize;
 use thiserror::Error;
 use tracing::error;
 
+use super::validation::{format_validation_errors, ValidationError};
+
 /// Application error types
 #[derive(Debug, Error)]
 pub enum AppError {
@@ -35,10 +37,26 @@ pub enum AppError {
     #[error("Conflict: {0}")]
     Conflict(String),
 
+    /// Precondition failed - the resource changed since it was read (412)
+    #[error("Precondition failed: {0}")]
+    PreconditionFailed(String),
+
+    /// Request body too large (413)
+    #[error("Payload too large: {0}")]
+    PayloadTooLarge(String),
+
     /// Unprocessable entity - validation failed (422)
     #[error("Validation error: {0}")]
     ValidationError(String),
 
+    /// Unprocessable entity - fields of the request are invalid (422)
+    #[error("Validation error: {}", format_validation_errors(.0))]
+    InvalidFields(Vec<ValidationError>),
+
+    /// Precondition required - a conditional header is missing (428)
+    #[error("Precondition required: {0}")]
+    PreconditionRequired(String),
+
     /// Internal server error (500)
     #[error("Internal error: {0}")]
     Internal(String),
@@ -86,11 +104,31 @@ impl AppError {
         AppError::Conflict(message.into())
     }
 
+    /// Create a precondition failed error
+    pub fn precondition_failed(message: impl Into<String>) -> Self {
+        AppError::PreconditionFailed(message.into())
+    }
+
+    /// Create a precondition required error
+    pub fn precondition_required(message: impl Into<String>) -> Self {
+        AppError::PreconditionRequired(message.into())
+    }
+
+    /// Create a payload too large error
+    pub fn payload_too_large(message: impl Into<String>) -> Self {
+        AppError::PayloadTooLarge(message.into())
+    }
+
     /// Create a validation error
     pub fn validation(message: impl Into<String>) -> Self {
         AppError::ValidationError(message.into())
     }
 
+    /// Create a validation error for a single field
+    pub fn invalid_field(field: impl Into<String>, message: impl Into<String>) -> Self {
+        AppError::InvalidFields(vec![ValidationError::new(field, message)])
+    }
+
     /// Create an internal server error
     pub fn internal(message: impl Into<String>) -> Self {
         AppError::Internal(message.into())
@@ -107,71 +145,140 @@ impl AppError {
     }
 }
 
-/// Error response body
+/// Error body returned by every API endpoint
+///
+/// `error` is the category of the error, derived from the HTTP status, and
+/// `code` a machine-readable code for it; both are stable and safe to match
+/// on. `message` is for humans and may change.
 #[derive(Serialize, Debug)]
-pub struct ErrorResponse {
-    /// Error type identifier
+pub struct ApiError {
+    /// Error category (e.g. `not_found`)
     pub error: String,
+    /// Machine-readable error code; the category unless a more specific code
+    /// applies (e.g. `token_expired`)
+    pub code: String,
     /// Human-readable error message
     pub message: String,
     /// Additional error details (optional)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub details: Option<serde_json::Value>,
-    /// Error code for programmatic handling (optional)
+    /// Invalid request fields (validation errors only)
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<ValidationError>,
+    /// ID of the request, to find it in the server logs
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub code: Option<String>,
+    pub request_id: Option<String>,
 }
 
-impl ErrorResponse {
-    /// Create a new error response
+impl ApiError {
+    /// Create a new error body whose code is its category
     pub fn new(error: impl Into<String>, message: impl Into<String>) -> Self {
+        let error = error.into();
         Self {
-            error: error.into(),
+            code: error.clone(),
+            error,
             message: message.into(),
             details: None,
-            code: None,
+            fields: Vec::new(),
+            request_id: crate::middleware::current_request_id(),
         }
     }
 
+    /// Create an error body with the category of an HTTP status
+    pub fn for_status(status: StatusCode, message: impl Into<String>) -> Self {
+        Self::new(error_type_for_status(status), message)
+    }
+
     /// Add details to the error response
     pub fn with_details(mut self, details: serde_json::Value) -> Self {
         self.details = Some(details);
         self
     }
 
-    /// Add an error code
+    /// Use a more specific error code than the category
     pub fn with_code(mut self, code: impl Into<String>) -> Self {
-        self.code = Some(code.into());
+        self.code = code.into();
+        self
+    }
+
+    /// Add the invalid fields of a request
+    pub fn with_fields(mut self, fields: Vec<ValidationError>) -> Self {
+        self.fields = fields;
         self
     }
 }
 
-impl IntoResponse for AppError {
-    fn into_response(self) -> Response {
-        let (status, error_type, should_log) = match &self {
-            AppError::NotFound(_) => (StatusCode::NOT_FOUND, "not_found", false),
-            AppError::BadRequest(_) => (StatusCode::BAD_REQUEST, "bad_request", false),
-            AppError::Unauthorized(_) => (StatusCode::UNAUTHORIZED, "unauthorized", false),
-            AppError::Forbidden(_) => (StatusCode::FORBIDDEN, "forbidden", true),
-            AppError::Conflict(_) => (StatusCode::CONFLICT, "conflict", false),
-            AppError::ValidationError(_) => {
-                (StatusCode::UNPROCESSABLE_ENTITY, "validation_error", false)
+/// Error category of an HTTP status
+pub fn error_type_for_status(status: StatusCode) -> &'static str {
+    match status {
+        StatusCode::BAD_REQUEST => "bad_request",
+        StatusCode::UNAUTHORIZED => "unauthorized",
+        StatusCode::FORBIDDEN => "forbidden",
+        StatusCode::NOT_FOUND => "not_found",
+        StatusCode::METHOD_NOT_ALLOWED => "method_not_allowed",
+        StatusCode::CONFLICT => "conflict",
+        StatusCode::PRECONDITION_FAILED => "precondition_failed",
+        StatusCode::PAYLOAD_TOO_LARGE => "payload_too_large",
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => "unsupported_media_type",
+        StatusCode::UNPROCESSABLE_ENTITY => "validation_error",
+        StatusCode::PRECONDITION_REQUIRED => "precondition_required",
+        StatusCode::TOO_MANY_REQUESTS => "too_many_requests",
+        StatusCode::BAD_GATEWAY => "bad_gateway",
+        StatusCode::SERVICE_UNAVAILABLE => "service_unavailable",
+        status if status.is_server_error() => "internal_error",
+        _ => "bad_request",
+    }
+}
+
+impl AppError {
+    /// HTTP status of the error
+    pub fn status(&self) -> StatusCode {
+        match self {
+            AppError::NotFound(_) => StatusCode::NOT_FOUND,
+            AppError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            AppError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            AppError::Forbidden(_) => StatusCode::FORBIDDEN,
+            AppError::Conflict(_) => StatusCode::CONFLICT,
+            AppError::PreconditionFailed(_) => StatusCode::PRECONDITION_FAILED,
+            AppError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            AppError::ValidationError(_) | AppError::InvalidFields(_) => {
+                StatusCode::UNPROCESSABLE_ENTITY
             }
-            AppError::Internal(_) => (StatusCode::INTERNAL_SERVER_ERROR, "internal_error", true),
-            AppError::PuppetDb(_) => (StatusCode::BAD_GATEWAY, "puppetdb_error", true),
-            AppError::Database(_) => (StatusCode::INTERNAL_SERVER_ERROR, "database_error", true),
-            AppError::Config(_) => (StatusCode::INTERNAL_SERVER_ERROR, "config_error", true),
-            AppError::ServiceUnavailable(_) => {
-                (StatusCode::SERVICE_UNAVAILABLE, "service_unavailable", true)
+            AppError::PreconditionRequired(_) => StatusCode::PRECONDITION_REQUIRED,
+            AppError::Internal(_) | AppError::Database(_) | AppError::Config(_) => {
+                StatusCode::INTERNAL_SERVER_ERROR
             }
-        };
+            AppError::PuppetDb(_) => StatusCode::BAD_GATEWAY,
+            AppError::ServiceUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+        }
+    }
+
+    /// Machine-readable code of the error
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::InvalidFields(_) => "invalid_fields",
+            AppError::PuppetDb(_) => "puppetdb_error",
+            AppError::Database(_) => "database_error",
+            AppError::Config(_) => "config_error",
+            other => error_type_for_status(other.status()),
+        }
+    }
+}
+
+impl IntoResponse for AppError {
+    fn into_response(self) -> Response {
+        let status = self.status();
 
-        // Log server errors
-        if should_log {
-            error!(error = %self, error_type = error_type, "Request error");
+        // Log server errors and permission denials
+        if status.is_server_error() || status == StatusCode::FORBIDDEN {
+            error!(error = %self, error_code = self.code(), "Request error");
         }
 
-        let body = ErrorResponse::new(error_type, self.to_string());
+        let body = ApiError::for_status(status, self.to_string()).with_code(self.code());
+        let body = match self {
+            AppError::InvalidFields(fields) => body.with_fields(fields),
+            _ => body,
+        };
 
         (status, Json(body)).into_response()
     }
@@ -179,9 +286,26 @@ impl IntoResponse for AppError {
 
 // Implement From for common error types
 
+/// Errors from services and repositories
+///
+/// An [`AppError`], database or PuppetDB error anywhere in the chain keeps
+/// its status; anything else is an internal error with the full chain as
+/// message.
 impl From<anyhow::Error> for AppError {
     fn from(err: anyhow::Error) -> Self {
-        AppError::Internal(err.to_string())
+        let err = match err.downcast::<AppError>() {
+            Ok(app_err) => return app_err,
+            Err(err) => err,
+        };
+        let err = match err.downcast::<sqlx::Error>() {
+            Ok(db_err) => return db_err.into(),
+            Err(err) => err,
+        };
+        let err = match err.downcast::<reqwest::Error>() {
+            Ok(http_err) => return http_err.into(),
+            Err(err) => err,
+        };
+        AppError::Internal(format!("{:#}", err))
     }
 }
 
@@ -222,7 +346,24 @@ impl From<serde_json::Error> for AppError {
 
 impl From<validator::ValidationErrors> for AppError {
     fn from(err: validator::ValidationErrors) -> Self {
-        AppError::ValidationError(err.to_string())
+        let mut fields: Vec<ValidationError> = err
+            .field_errors()
+            .into_iter()
+            .flat_map(|(field, errors)| {
+                errors.iter().map(move |e| {
+                    let message = e.message.as_deref().unwrap_or(&e.code).to_string();
+                    ValidationError::new(field.to_string(), message)
+                })
+            })
+            .collect();
+        fields.sort_by(|a, b| a.field.cmp(&b.field));
+        AppError::InvalidFields(fields)
+    }
+}
+
+impl From<Vec<ValidationError>> for AppError {
+    fn from(fields: Vec<ValidationError>) -> Self {
+        AppError::InvalidFields(fields)
     }
 }
 
@@ -241,21 +382,56 @@ mod tests {
 
     #[test]
     fn test_error_response_serialization() {
-        let response = ErrorResponse::new("not_found", "Resource not found");
-
-        let json = serde_json::to_string(&response).unwrap();
-        assert!(json.contains("not_found"));
-        assert!(json.contains("Resource not found"));
+        let response = ApiError::new("not_found", "Resource not found");
+
+        let json = serde_json::to_value(&response).unwrap();
+        assert_eq!(json["error"], "not_found");
+        assert_eq!(json["code"], "not_found");
+        assert_eq!(json["message"], "Resource not found");
+        assert!(json.get("fields").is_none());
+        assert!(json.get("request_id").is_none());
     }
 
     #[test]
     fn test_error_response_with_details() {
-        let response = ErrorResponse::new("validation_error", "Invalid input")
+        let response = ApiError::new("validation_error", "Invalid input")
             .with_details(serde_json::json!({"field": "email", "reason": "invalid format"}));
 
         assert!(response.details.is_some());
     }
 
+    #[tokio::test]
+    async fn test_invalid_fields_response() {
+        let err = AppError::invalid_field("name", "is required");
+        let response = err.into_response();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["error"], "validation_error");
+        assert_eq!(json["code"], "invalid_fields");
+        assert_eq!(json["fields"][0]["field"], "name");
+        assert_eq!(json["fields"][0]["message"], "is required");
+    }
+
+    #[test]
+    fn test_anyhow_conversion_keeps_app_errors() {
+        let err: AppError = anyhow::Error::new(AppError::not_found("Node not found")).into();
+        assert!(matches!(err, AppError::NotFound(_)));
+
+        let err: AppError = anyhow::Error::new(sqlx::Error::RowNotFound)
+            .context("Failed to load node")
+            .into();
+        assert!(matches!(err, AppError::NotFound(_)));
+
+        let err: AppError = anyhow::anyhow!("disk full")
+            .context("Failed to save")
+            .into();
+        assert_eq!(err.to_string(), "Internal error: Failed to save: disk full");
+    }
+
     #[test]
     fn test_sqlx_not_found_conversion() {
         let err: AppError = sqlx::Error::RowNotFound.into();
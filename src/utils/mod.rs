@@ -2,5 +2,6 @@
 
 pub mod error;
 pub mod validation;
+pub mod x509;
 
 pub use error::*;
@@ -2,6 +2,7 @@
 
 use once_cell::sync::Lazy;
 use regex::Regex;
+use serde::Serialize;
 
 use crate::models::{FactDefinition, FactTemplate, FactValueSource};
 
@@ -41,8 +42,8 @@ pub fn validate_fact_path(path: &str) -> bool {
         .all(|part| !part.is_empty() && part.chars().all(|c| c.is_alphanumeric() || c == '_'))
 }
 
-/// Validation error for fact templates
-#[derive(Debug, Clone, PartialEq)]
+/// Validation error for a field of a request
+#[derive(Debug, Clone, PartialEq, Serialize)]
 pub struct ValidationError {
     pub field: String,
     pub message: String,
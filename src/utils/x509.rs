@@ -0,0 +1,534 @@
+//! Minimal X.509 parsing for Puppet CA artifacts
+//!
+//! Only the fields the UI shows are decoded. CRL signatures are not verified
+//! (the CA is trusted over mTLS); certificate chains are verified with webpki.
+
+use chrono::{DateTime, Utc};
+use rustls::pki_types::{CertificateDer, UnixTime};
+use sha2::{Digest, Sha256};
+use simple_asn1::{from_der, oid, ASN1Block, ASN1Class, OID};
+
+use crate::models::{CertificateRevocationList, RevokedCertificate};
+
+/// Decoded fields of an X.509 certificate
+#[derive(Debug, Clone)]
+pub struct ParsedCertificate {
+    pub subject: String,
+    pub issuer: String,
+    /// Serial number (decimal)
+    pub serial: String,
+    pub not_before: DateTime<Utc>,
+    pub not_after: DateTime<Utc>,
+    /// basicConstraints cA flag
+    pub is_ca: bool,
+    /// SHA256 of the DER encoding, as colon-separated hex
+    pub fingerprint: String,
+    pub der: Vec<u8>,
+}
+
+impl ParsedCertificate {
+    pub fn is_self_signed(&self) -> bool {
+        self.subject == self.issuer
+    }
+}
+
+/// Decode all `CERTIFICATE` blocks of a PEM document
+pub fn parse_certificates_pem(pem: &str) -> Result<Vec<ParsedCertificate>, String> {
+    let certs = rustls_pemfile::certs(&mut pem.as_bytes())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Invalid certificate PEM: {}", e))?;
+
+    if certs.is_empty() {
+        return Err("No certificate found in PEM data".to_string());
+    }
+
+    certs
+        .iter()
+        .map(|der| parse_certificate_der(der.as_ref()))
+        .collect()
+}
+
+/// Decode a DER-encoded certificate
+pub fn parse_certificate_der(der: &[u8]) -> Result<ParsedCertificate, String> {
+    let blocks = from_der(der).map_err(|e| format!("Invalid certificate: {}", e))?;
+    let Some(ASN1Block::Sequence(_, certificate)) = blocks.first() else {
+        return Err("Invalid certificate: expected a sequence".to_string());
+    };
+    let Some(ASN1Block::Sequence(_, tbs)) = certificate.first() else {
+        return Err("Invalid certificate: missing tbsCertificate".to_string());
+    };
+
+    let mut fields = tbs.iter().peekable();
+
+    // version [0] EXPLICIT, absent for v1
+    if matches!(fields.peek(), Some(ASN1Block::Explicit(..))) {
+        fields.next();
+    }
+    let serial = match fields.next() {
+        Some(ASN1Block::Integer(_, n)) => n.to_string(),
+        _ => return Err("Invalid certificate: missing serial number".to_string()),
+    };
+    // signature AlgorithmIdentifier
+    fields.next();
+
+    let issuer = fields
+        .next()
+        .map(format_name)
+        .ok_or("Invalid certificate: missing issuer")?;
+    let (not_before, not_after) = match fields.next() {
+        Some(ASN1Block::Sequence(_, validity)) => (
+            validity.first().and_then(as_time),
+            validity.get(1).and_then(as_time),
+        ),
+        _ => (None, None),
+    };
+    let (Some(not_before), Some(not_after)) = (not_before, not_after) else {
+        return Err("Invalid certificate: missing validity".to_string());
+    };
+    let subject = fields
+        .next()
+        .map(format_name)
+        .ok_or("Invalid certificate: missing subject")?;
+
+    // subjectPublicKeyInfo, then the unique IDs (implicit) and [3] extensions
+    let is_ca = fields
+        .find_map(|block| match block {
+            ASN1Block::Explicit(ASN1Class::ContextSpecific, _, _, extensions) => {
+                extension_value(extensions, &oid!(2, 5, 29, 19))
+            }
+            _ => None,
+        })
+        .and_then(|value| from_der(&value).ok())
+        .map(|inner| match inner.first() {
+            Some(ASN1Block::Sequence(_, constraints)) => {
+                matches!(constraints.first(), Some(ASN1Block::Boolean(_, true)))
+            }
+            _ => false,
+        })
+        .unwrap_or(false);
+
+    Ok(ParsedCertificate {
+        subject,
+        issuer,
+        serial,
+        not_before,
+        not_after,
+        is_ca,
+        fingerprint: fingerprint(der),
+        der: der.to_vec(),
+    })
+}
+
+/// Verify that `leaf_der` chains to a self-signed certificate of `bundle`
+///
+/// Checks signatures, validity periods, CA constraints and that the leaf may
+/// be used for client authentication, as the Puppet server would.
+pub fn verify_chain(leaf_der: &[u8], bundle: &[ParsedCertificate]) -> Result<(), String> {
+    let leaf_der = CertificateDer::from(leaf_der);
+    let leaf = webpki::EndEntityCert::try_from(&leaf_der)
+        .map_err(|e| format!("Invalid certificate: {}", e))?;
+
+    let roots: Vec<CertificateDer> = bundle
+        .iter()
+        .filter(|c| c.is_self_signed())
+        .map(|c| CertificateDer::from(c.der.as_slice()))
+        .collect();
+    let anchors = roots
+        .iter()
+        .map(webpki::anchor_from_trusted_cert)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Invalid root certificate in CA bundle: {}", e))?;
+    if anchors.is_empty() {
+        return Err("CA bundle contains no root certificate".to_string());
+    }
+    let intermediates: Vec<CertificateDer> = bundle
+        .iter()
+        .filter(|c| !c.is_self_signed())
+        .map(|c| CertificateDer::from(c.der.as_slice()))
+        .collect();
+
+    leaf.verify_for_usage(
+        webpki::ALL_VERIFICATION_ALGS,
+        &anchors,
+        &intermediates,
+        UnixTime::now(),
+        webpki::KeyUsage::client_auth(),
+        None,
+        None,
+    )
+    .map(|_| ())
+    .map_err(describe_chain_error)
+}
+
+fn describe_chain_error(error: webpki::Error) -> String {
+    match error {
+        webpki::Error::UnknownIssuer => "Issuer is not in the CA bundle".to_string(),
+        webpki::Error::InvalidSignatureForPublicKey => {
+            "Signature does not match the issuing CA".to_string()
+        }
+        webpki::Error::CertExpired { .. } => {
+            "Certificate or a CA in its chain has expired".to_string()
+        }
+        webpki::Error::CertNotValidYet { .. } => {
+            "Certificate or a CA in its chain is not valid yet".to_string()
+        }
+        webpki::Error::CaUsedAsEndEntity => "Certificate is a CA certificate".to_string(),
+        webpki::Error::EndEntityUsedAsCa => "Issuer is not a CA certificate".to_string(),
+        webpki::Error::PathLenConstraintViolated => {
+            "Chain exceeds an intermediate's path length constraint".to_string()
+        }
+        webpki::Error::RequiredEkuNotFound | webpki::Error::RequiredEkuNotFoundContext(_) => {
+            "Certificate is not valid for client authentication".to_string()
+        }
+        e => format!("Chain validation failed: {}", e),
+    }
+}
+
+/// SHA256 fingerprint in the `AB:CD:...` form Puppet uses
+fn fingerprint(der: &[u8]) -> String {
+    Sha256::digest(der)
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+/// Decode all `X509 CRL` blocks of a PEM document
+pub fn parse_crl_pem(pem: &str) -> Result<Vec<CertificateRevocationList>, String> {
+    let crls = rustls_pemfile::crls(&mut pem.as_bytes())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Invalid CRL PEM: {}", e))?;
+
+    if crls.is_empty() {
+        return Err("No CRL found in PEM data".to_string());
+    }
+
+    crls.iter().map(|der| parse_crl_der(der.as_ref())).collect()
+}
+
+/// Decode a DER-encoded CRL
+pub fn parse_crl_der(der: &[u8]) -> Result<CertificateRevocationList, String> {
+    let blocks = from_der(der).map_err(|e| format!("Invalid CRL: {}", e))?;
+    let Some(ASN1Block::Sequence(_, cert_list)) = blocks.first() else {
+        return Err("Invalid CRL: expected a sequence".to_string());
+    };
+    let Some(ASN1Block::Sequence(_, tbs)) = cert_list.first() else {
+        return Err("Invalid CRL: missing tbsCertList".to_string());
+    };
+
+    let mut fields = tbs.iter().peekable();
+
+    // version INTEGER OPTIONAL
+    if matches!(fields.peek(), Some(ASN1Block::Integer(..))) {
+        fields.next();
+    }
+    // signature AlgorithmIdentifier
+    fields.next();
+
+    let issuer = fields
+        .next()
+        .map(format_name)
+        .ok_or("Invalid CRL: missing issuer")?;
+    let this_update = fields
+        .next()
+        .and_then(as_time)
+        .ok_or("Invalid CRL: missing thisUpdate")?;
+    let next_update = fields.peek().and_then(|b| as_time(b));
+    if next_update.is_some() {
+        fields.next();
+    }
+
+    let mut revoked = Vec::new();
+    let mut crl_number = None;
+    for block in fields {
+        match block {
+            ASN1Block::Sequence(_, entries) => {
+                revoked = entries.iter().filter_map(parse_revoked_entry).collect();
+            }
+            ASN1Block::Explicit(ASN1Class::ContextSpecific, _, _, extensions) => {
+                crl_number = extension_value(extensions, &oid!(2, 5, 29, 20))
+                    .and_then(|value| from_der(&value).ok())
+                    .and_then(|inner| match inner.first() {
+                        Some(ASN1Block::Integer(_, n)) => Some(n.to_string()),
+                        _ => None,
+                    });
+            }
+            _ => {}
+        }
+    }
+
+    Ok(CertificateRevocationList {
+        issuer,
+        crl_number,
+        this_update,
+        next_update,
+        revoked_count: revoked.len(),
+        revoked,
+    })
+}
+
+fn parse_revoked_entry(block: &ASN1Block) -> Option<RevokedCertificate> {
+    let ASN1Block::Sequence(_, items) = block else {
+        return None;
+    };
+    let serial = match items.first()? {
+        ASN1Block::Integer(_, n) => n.to_string(),
+        _ => return None,
+    };
+    let revoked_at = as_time(items.get(1)?)?;
+
+    // reasonCode is an ENUMERATED wrapped in the extension's OCTET STRING
+    let reason = items
+        .get(2)
+        .and_then(|extensions| extension_value(extensions, &oid!(2, 5, 29, 21)))
+        .and_then(|value| match value.as_slice() {
+            [0x0a, 0x01, code] => Some(reason_name(*code).to_string()),
+            _ => None,
+        });
+
+    Some(RevokedCertificate {
+        serial,
+        revoked_at,
+        reason,
+    })
+}
+
+/// Value of extension `id` in an `Extensions` sequence
+fn extension_value(extensions: &ASN1Block, id: &OID) -> Option<Vec<u8>> {
+    let ASN1Block::Sequence(_, extensions) = extensions else {
+        return None;
+    };
+    extensions.iter().find_map(|extension| {
+        let ASN1Block::Sequence(_, parts) = extension else {
+            return None;
+        };
+        match (parts.first(), parts.last()) {
+            (Some(ASN1Block::ObjectIdentifier(_, oid)), Some(ASN1Block::OctetString(_, value)))
+                if oid == id =>
+            {
+                Some(value.clone())
+            }
+            _ => None,
+        }
+    })
+}
+
+fn as_time(block: &ASN1Block) -> Option<DateTime<Utc>> {
+    match block {
+        ASN1Block::UTCTime(_, t) | ASN1Block::GeneralizedTime(_, t) => {
+            DateTime::from_timestamp(t.assume_utc().unix_timestamp(), 0)
+        }
+        _ => None,
+    }
+}
+
+/// Render a distinguished name as `CN=..., O=...`
+pub(crate) fn format_name(block: &ASN1Block) -> String {
+    let ASN1Block::Sequence(_, rdns) = block else {
+        return String::new();
+    };
+
+    rdns.iter()
+        .filter_map(|rdn| match rdn {
+            ASN1Block::Set(_, attributes) => attributes.first(),
+            _ => None,
+        })
+        .filter_map(|attribute| {
+            let ASN1Block::Sequence(_, parts) = attribute else {
+                return None;
+            };
+            let ASN1Block::ObjectIdentifier(_, oid) = parts.first()? else {
+                return None;
+            };
+            let value = match parts.get(1)? {
+                ASN1Block::UTF8String(_, s)
+                | ASN1Block::PrintableString(_, s)
+                | ASN1Block::IA5String(_, s)
+                | ASN1Block::TeletexString(_, s)
+                | ASN1Block::BMPString(_, s)
+                | ASN1Block::UniversalString(_, s) => s.clone(),
+                _ => return None,
+            };
+            Some(format!("{}={}", attribute_name(oid), value))
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn attribute_name(id: &OID) -> String {
+    const NAMES: &[(u64, &str)] = &[
+        (3, "CN"),
+        (6, "C"),
+        (7, "L"),
+        (8, "ST"),
+        (10, "O"),
+        (11, "OU"),
+    ];
+
+    NAMES
+        .iter()
+        .find(|(arc, _)| id == oid!(2, 5, 4, *arc))
+        .map(|(_, name)| name.to_string())
+        .unwrap_or_else(|| {
+            id.as_vec::<u64>()
+                .map(|arcs| {
+                    arcs.iter()
+                        .map(|a| a.to_string())
+                        .collect::<Vec<_>>()
+                        .join(".")
+                })
+                .unwrap_or_else(|_| "?".to_string())
+        })
+}
+
+/// RFC 5280 CRLReason names
+fn reason_name(code: u8) -> &'static str {
+    match code {
+        0 => "unspecified",
+        1 => "keyCompromise",
+        2 => "cACompromise",
+        3 => "affiliationChanged",
+        4 => "superseded",
+        5 => "cessationOfOperation",
+        6 => "certificateHold",
+        8 => "removeFromCRL",
+        9 => "privilegeWithdrawn",
+        10 => "aACompromise",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// CRL from a throwaway test CA with serial 0x1000 revoked for key compromise
+    const TEST_CRL: &str = "-----BEGIN X509 CRL-----
+MIHWMH4CAQEwCgYIKoZIzj0EAwIwGjEYMBYGA1UEAwwPUHVwcGV0IENBOiB0ZXN0
+Fw0yNjEwMTYxMDI1MzBaFw0yNjExMTUxMDI1MzBaMCMwIQICEAAXDTI2MTAxNjEw
+MjUzMFowDDAKBgNVHRUEAwoBAaAOMAwwCgYDVR0UBAMCAQEwCgYIKoZIzj0EAwID
+SAAwRQIgYxBe16b7UikHoswdKxsOYw1Crn9U1mwYOXDL8JTHQdECIQCcXr42wxQO
+nLkOFJmlNClbFd1BlQSbpPvpCZbRPS0QdQ==
+-----END X509 CRL-----
+";
+
+    #[test]
+    fn test_parse_crl_pem() {
+        let crls = parse_crl_pem(TEST_CRL).unwrap();
+        assert_eq!(crls.len(), 1);
+
+        let crl = &crls[0];
+        assert_eq!(crl.issuer, "CN=Puppet CA: test");
+        assert_eq!(crl.crl_number.as_deref(), Some("1"));
+        assert_eq!(crl.this_update.to_rfc3339(), "2026-10-16T10:25:30+00:00");
+        assert_eq!(
+            crl.next_update.unwrap().to_rfc3339(),
+            "2026-11-15T10:25:30+00:00"
+        );
+        assert_eq!(crl.revoked_count, 1);
+        assert_eq!(crl.revoked[0].serial, "4096");
+        assert_eq!(crl.revoked[0].reason.as_deref(), Some("keyCompromise"));
+    }
+
+    #[test]
+    fn test_parse_crl_pem_rejects_garbage() {
+        assert!(parse_crl_pem("not a crl").is_err());
+    }
+
+    /// Test chain: root CA -> intermediate "Puppet CA: test" -> agent (serial 0x1000)
+    const TEST_ROOT: &str = "-----BEGIN CERTIFICATE-----
+MIIBpjCCAUugAwIBAgIUf6tLfMoClRYWY0nCzqZ4yYx5UE4wCgYIKoZIzj0EAwIw
+HzEdMBsGA1UEAwwUUHVwcGV0IFJvb3QgQ0E6IHRlc3QwIBcNMjYxMDE2MTAzMTUy
+WhgPMjEyNjA5MjIxMDMxNTJaMB8xHTAbBgNVBAMMFFB1cHBldCBSb290IENBOiB0
+ZXN0MFkwEwYHKoZIzj0CAQYIKoZIzj0DAQcDQgAE/fLyoxUR3eRPGUSitOg5OaGu
+L6TfRXGqpsABhPd+wudX5GbcVEZpeSiPDuzFmG/LhVUr3nOvnCaNHyHOIZoMmqNj
+MGEwHQYDVR0OBBYEFB08el2AywkhVRLmyO9T2lOybwlPMB8GA1UdIwQYMBaAFB08
+el2AywkhVRLmyO9T2lOybwlPMA8GA1UdEwEB/wQFMAMBAf8wDgYDVR0PAQH/BAQD
+AgEGMAoGCCqGSM49BAMCA0kAMEYCIQCWM6HQu3j8Yqrl8RSuyQ4r905egwE7lv0r
+9hPhBauCDQIhAPDEPuGKcPg6LyAG/1hCMVz1dbQldb9sQkEpx8zn7NB7
+-----END CERTIFICATE-----
+";
+    const TEST_INTERMEDIATE: &str = "-----BEGIN CERTIFICATE-----
+MIIBkDCCATagAwIBAgIBAjAKBggqhkjOPQQDAjAfMR0wGwYDVQQDDBRQdXBwZXQg
+Um9vdCBDQTogdGVzdDAgFw0yNjEwMTYxMDMxNTJaGA8yMTI2MDkyMjEwMzE1Mlow
+GjEYMBYGA1UEAwwPUHVwcGV0IENBOiB0ZXN0MFkwEwYHKoZIzj0CAQYIKoZIzj0D
+AQcDQgAEojBwtQqgd5XcIWIxufX+9bn+q58a2itIG7YGVbWaIVQGc7iIviTrfMyu
+o03+ZrA9bmnPfg9fBnrmOY6MJdYUiqNmMGQwEgYDVR0TAQH/BAgwBgEB/wIBADAO
+BgNVHQ8BAf8EBAMCAQYwHQYDVR0OBBYEFMV2cHpHsIBVKHEozRtZioAwRrqVMB8G
+A1UdIwQYMBaAFB08el2AywkhVRLmyO9T2lOybwlPMAoGCCqGSM49BAMCA0gAMEUC
+IQC73XPMgI0DspYJCx2RsnJ7OS5BWpO88qC1SyOHJ3ztqgIgN1/p4JNeV3hszuLp
+m5ICOpk/+CIx3Efl3GEFO/+mP2k=
+-----END CERTIFICATE-----
+";
+    const TEST_LEAF: &str = "-----BEGIN CERTIFICATE-----
+MIIBpzCCAU2gAwIBAgICEAAwCgYIKoZIzj0EAwIwGjEYMBYGA1UEAwwPUHVwcGV0
+IENBOiB0ZXN0MCAXDTI2MTAxNjEwMzE1MloYDzIxMjYwOTIyMTAzMTUyWjAcMRow
+GAYDVQQDDBFhZ2VudC5leGFtcGxlLmNvbTBZMBMGByqGSM49AgEGCCqGSM49AwEH
+A0IABM4kS91fCxQUUzeYne4xz+EKBMf5J5aDQQmZ2nP9RuG+xY5zTYf3ZidqAC/i
+KOM8bJCQEOiO1ZIvtpoQVtgA/o2jfzB9MAwGA1UdEwEB/wQCMAAwDgYDVR0PAQH/
+BAQDAgWgMB0GA1UdJQQWMBQGCCsGAQUFBwMBBggrBgEFBQcDAjAdBgNVHQ4EFgQU
+L93v3H8YodDa4IYTbnBJL4Q/Ef4wHwYDVR0jBBgwFoAUxXZwekewgFUocSjNG1mK
+gDBGupUwCgYIKoZIzj0EAwIDSAAwRQIgKXUQhL5Mxde3fj/z5LO+bPYhthLikeX8
+RftsWWO3394CIQCAUvtrbgA/gFBmtbL+q4d5R9PYmG4SDltMhyY+ImYw/w==
+-----END CERTIFICATE-----
+";
+    /// Same subject and issuer name as `TEST_LEAF`, signed by an unrelated key
+    const TEST_FORGED_LEAF: &str = "-----BEGIN CERTIFICATE-----
+MIIBqDCCAU2gAwIBAgICEAEwCgYIKoZIzj0EAwIwGjEYMBYGA1UEAwwPUHVwcGV0
+IENBOiB0ZXN0MCAXDTI2MTAxNjEwMzE1MloYDzIxMjYwOTIyMTAzMTUyWjAcMRow
+GAYDVQQDDBFhZ2VudC5leGFtcGxlLmNvbTBZMBMGByqGSM49AgEGCCqGSM49AwEH
+A0IABM4kS91fCxQUUzeYne4xz+EKBMf5J5aDQQmZ2nP9RuG+xY5zTYf3ZidqAC/i
+KOM8bJCQEOiO1ZIvtpoQVtgA/o2jfzB9MAwGA1UdEwEB/wQCMAAwDgYDVR0PAQH/
+BAQDAgWgMB0GA1UdJQQWMBQGCCsGAQUFBwMBBggrBgEFBQcDAjAdBgNVHQ4EFgQU
+L93v3H8YodDa4IYTbnBJL4Q/Ef4wHwYDVR0jBBgwFoAUQIw3nZuaw25iJqyoCnCu
+QEB+saEwCgYIKoZIzj0EAwIDSQAwRgIhAPXoG1Rqq+IoECuInasJU9UrbBusUqyD
+ufCMd9Y+oojMAiEAmGbHvdgogjYe9Jy2Se788cQx10pWbLs4A2DUVii5fqw=
+-----END CERTIFICATE-----
+";
+
+    fn test_bundle() -> Vec<ParsedCertificate> {
+        parse_certificates_pem(&format!("{}{}", TEST_INTERMEDIATE, TEST_ROOT)).unwrap()
+    }
+
+    #[test]
+    fn test_parse_certificates_pem() {
+        let bundle = test_bundle();
+        assert_eq!(bundle.len(), 2);
+
+        let intermediate = &bundle[0];
+        assert_eq!(intermediate.subject, "CN=Puppet CA: test");
+        assert_eq!(intermediate.issuer, "CN=Puppet Root CA: test");
+        assert!(intermediate.is_ca);
+        assert!(!intermediate.is_self_signed());
+        assert!(bundle[1].is_self_signed());
+
+        let leaf = &parse_certificates_pem(TEST_LEAF).unwrap()[0];
+        assert_eq!(leaf.subject, "CN=agent.example.com");
+        assert_eq!(leaf.serial, "4096");
+        assert!(!leaf.is_ca);
+        assert_eq!(leaf.not_before.to_rfc3339(), "2026-10-16T10:31:52+00:00");
+        assert_eq!(leaf.not_after.to_rfc3339(), "2126-09-22T10:31:52+00:00");
+        assert_eq!(
+            leaf.fingerprint,
+            "55:8C:91:F9:4C:DA:DA:D8:09:09:50:E6:9E:27:87:AA:DF:6F:65:0B:F6:D0:04:9B:E4:30:A5:03:83:E3:CB:42"
+        );
+    }
+
+    #[test]
+    fn test_verify_chain() {
+        let bundle = test_bundle();
+        let leaf = &parse_certificates_pem(TEST_LEAF).unwrap()[0];
+        assert!(verify_chain(&leaf.der, &bundle).is_ok());
+
+        // Without the intermediate the issuer is unknown
+        let roots: Vec<_> = bundle
+            .iter()
+            .filter(|c| c.is_self_signed())
+            .cloned()
+            .collect();
+        assert!(verify_chain(&leaf.der, &roots).is_err());
+
+        let forged = &parse_certificates_pem(TEST_FORGED_LEAF).unwrap()[0];
+        assert!(verify_chain(&forged.der, &bundle).is_err());
+    }
+}
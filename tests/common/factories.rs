@@ -1,15 +1,20 @@
 //! Test factories for generating test data
 //!
 //! Factories create randomized test data, useful for property-based testing
-//! and when you need unique data for each test.
+//! and when you need unique data for each test. [`FleetFactory`] instead
+//! generates large fleets deterministically from a seed, for reproducible
+//! performance-sensitive tests.
 
-use chrono::Utc;
-use rand::RngExt;
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rand::rngs::Xoshiro256PlusPlus;
+use rand::{RngExt, SeedableRng};
 use uuid::Uuid;
 
 use openvox_webui::models::{
-    default_organization_uuid, Action, Node, NodeGroup, Permission, Report, ReportStatus, Resource,
-    Role, RuleMatchType, Scope,
+    default_organization_uuid, Action, CreateGroupRequest, Node, NodeGroup, Permission, Report,
+    ReportStatus, Resource, Role, RuleMatchType, Scope,
 };
 
 /// Factory for creating test users
@@ -232,6 +237,21 @@ impl GroupBuilder {
         self
     }
 
+    /// Request creating this group, see [`crate::common::TestDb::create_group`]
+    pub fn into_request(self) -> CreateGroupRequest {
+        CreateGroupRequest {
+            name: self.name,
+            description: self.description,
+            parent_id: self.parent_id,
+            environment: self.environment,
+            is_environment_group: None,
+            match_all_nodes: None,
+            rule_match_type: None,
+            classes: Some(self.classes),
+            variables: None,
+        }
+    }
+
     pub fn build(self) -> NodeGroup {
         NodeGroup {
             id: self.id,
@@ -247,6 +267,7 @@ impl GroupBuilder {
             variables: serde_json::json!({}),
             rules: vec![],
             pinned_nodes: vec![],
+            version: 1,
         }
     }
 }
@@ -427,6 +448,7 @@ impl RoleBuilder {
             description: self.description,
             is_system: false,
             parent_id: None,
+            parent_ids: vec![],
             permissions: self.permissions,
             created_at: now,
             updated_at: now,
@@ -434,9 +456,190 @@ impl RoleBuilder {
     }
 }
 
+/// Reference time of generated fleets
+///
+/// Fleet timestamps are offsets from this instant rather than from the wall
+/// clock, so a seed always produces the same data.
+pub fn fleet_epoch() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap()
+}
+
+/// Factory for deterministic large fleets of nodes, facts and reports
+///
+/// The same seed and settings always produce the same fleet: certnames,
+/// facts, statuses, report hashes and timestamps included.
+#[derive(Debug, Clone)]
+pub struct FleetFactory {
+    seed: u64,
+    node_count: usize,
+    reports_per_node: usize,
+    environments: Vec<String>,
+    failure_rate: f64,
+    stale_rate: f64,
+    base_time: DateTime<Utc>,
+}
+
+impl FleetFactory {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            node_count: 1000,
+            reports_per_node: 1,
+            environments: vec!["production".to_string(), "staging".to_string()],
+            failure_rate: 0.05,
+            stale_rate: 0.02,
+            base_time: fleet_epoch(),
+        }
+    }
+
+    pub fn with_nodes(mut self, count: usize) -> Self {
+        self.node_count = count;
+        self
+    }
+
+    pub fn with_reports_per_node(mut self, count: usize) -> Self {
+        self.reports_per_node = count;
+        self
+    }
+
+    pub fn with_environments(mut self, environments: &[&str]) -> Self {
+        self.environments = environments.iter().map(|e| e.to_string()).collect();
+        self
+    }
+
+    /// Share of nodes whose latest report failed
+    pub fn with_failure_rate(mut self, rate: f64) -> Self {
+        self.failure_rate = rate;
+        self
+    }
+
+    /// Share of nodes that have not reported for over a day
+    pub fn with_stale_rate(mut self, rate: f64) -> Self {
+        self.stale_rate = rate;
+        self
+    }
+
+    /// Anchor timestamps at `base_time` instead of [`fleet_epoch`]
+    ///
+    /// Use `Utc::now()` when the code under test measures report age against
+    /// the wall clock; the fleet is then only reproducible up to its
+    /// timestamps.
+    pub fn with_base_time(mut self, base_time: DateTime<Utc>) -> Self {
+        self.base_time = base_time;
+        self
+    }
+
+    pub fn build(&self) -> Fleet {
+        let mut rng = Xoshiro256PlusPlus::seed_from_u64(self.seed);
+        let mut fleet = Fleet {
+            seed: self.seed,
+            nodes: Vec::with_capacity(self.node_count),
+            facts: BTreeMap::new(),
+            reports: Vec::with_capacity(self.node_count * self.reports_per_node),
+        };
+
+        for i in 0..self.node_count {
+            let datacenter = ["dc1", "dc2", "dc3"][rng.random_range(0..3)];
+            let certname = format!("node{:05}.{}.example.com", i, datacenter);
+            let environment =
+                self.environments[rng.random_range(0..self.environments.len())].clone();
+
+            let status = if rng.random_bool(self.failure_rate) {
+                ReportStatus::Failed
+            } else if rng.random_bool(0.2) {
+                ReportStatus::Changed
+            } else {
+                ReportStatus::Unchanged
+            };
+            let age = if rng.random_bool(self.stale_rate) {
+                Duration::hours(rng.random_range(25..24 * 30))
+            } else {
+                Duration::minutes(rng.random_range(0..120))
+            };
+            let report_time = self.base_time - age;
+
+            let mut facts = facts_with_rng(&mut rng);
+            facts["networking"]["fqdn"] = serde_json::json!(certname);
+            facts["networking"]["hostname"] =
+                serde_json::json!(certname.split('.').next().unwrap_or_default());
+            facts["datacenter"] = serde_json::json!(datacenter);
+
+            for n in 0..self.reports_per_node {
+                // Earlier runs every 30 minutes, with the fleet's failure rate
+                let run_status = if n == 0 {
+                    status
+                } else if rng.random_bool(self.failure_rate) {
+                    ReportStatus::Failed
+                } else {
+                    ReportStatus::Unchanged
+                };
+                let end_time = report_time - Duration::minutes(30 * n as i64);
+                let mut report = ReportFactory::new()
+                    .create()
+                    .with_certname(&certname)
+                    .with_status(run_status)
+                    .with_environment(&environment)
+                    .build();
+                report.hash = format!("{:032x}{:08x}", rng.random::<u128>(), rng.random::<u32>());
+                report.transaction_uuid = Some(Uuid::from_u128(rng.random()).to_string());
+                report.catalog_uuid = Some(Uuid::from_u128(rng.random()).to_string());
+                report.start_time = Some(end_time - Duration::seconds(rng.random_range(5..300)));
+                report.end_time = Some(end_time);
+                report.producer_timestamp = Some(end_time);
+                report.receive_time = Some(end_time);
+                fleet.reports.push(report);
+            }
+
+            let mut node = NodeFactory::new()
+                .create()
+                .with_certname(&certname)
+                .with_environment(&environment)
+                .with_status(status.as_str())
+                .build();
+            node.report_timestamp = Some(report_time);
+            node.catalog_timestamp = Some(report_time);
+            node.facts_timestamp = Some(report_time);
+
+            fleet.nodes.push(node);
+            fleet.facts.insert(certname, facts);
+        }
+
+        fleet
+    }
+}
+
+/// A generated fleet
+#[derive(Debug, Clone)]
+pub struct Fleet {
+    pub seed: u64,
+    /// Nodes, by certname
+    pub nodes: Vec<Node>,
+    /// Facts by certname
+    pub facts: BTreeMap<String, serde_json::Value>,
+    /// Reports, newest first for each node
+    pub reports: Vec<Report>,
+}
+
+impl Fleet {
+    pub fn certnames(&self) -> impl Iterator<Item = &str> {
+        self.nodes.iter().map(|n| n.certname.as_str())
+    }
+
+    /// Number of nodes with the given latest report status
+    pub fn count_status(&self, status: &str) -> usize {
+        self.nodes
+            .iter()
+            .filter(|n| n.latest_report_status.as_deref() == Some(status))
+            .count()
+    }
+}
+
 /// Generate random facts for a node
 pub fn random_facts() -> serde_json::Value {
-    let mut rng = rand::rng();
+    facts_with_rng(&mut rand::rng())
+}
+
+fn facts_with_rng<R: RngExt>(rng: &mut R) -> serde_json::Value {
     let os_families = ["RedHat", "Debian", "Windows"];
     let os_family = os_families[rng.random_range(0..os_families.len())];
 
@@ -519,6 +722,45 @@ mod tests {
         assert_eq!(role.permissions.len(), 2);
     }
 
+    #[test]
+    fn test_fleet_factory_is_deterministic() {
+        let factory = FleetFactory::new(42)
+            .with_nodes(200)
+            .with_reports_per_node(3);
+        let a = factory.build();
+        let b = factory.build();
+
+        assert_eq!(a.nodes.len(), 200);
+        assert_eq!(a.reports.len(), 600);
+        assert!(a.certnames().eq(b.certnames()));
+        assert_eq!(a.facts, b.facts);
+        assert!(a
+            .reports
+            .iter()
+            .zip(&b.reports)
+            .all(|(x, y)| x.hash == y.hash && x.end_time == y.end_time));
+
+        let other = FleetFactory::new(43).with_nodes(200).build();
+        assert_ne!(a.facts, other.facts);
+    }
+
+    #[test]
+    fn test_fleet_factory_large_fleet() {
+        let fleet = FleetFactory::new(7)
+            .with_nodes(5000)
+            .with_failure_rate(0.1)
+            .build();
+
+        assert_eq!(fleet.nodes.len(), 5000);
+        assert_eq!(fleet.facts.len(), 5000);
+        let failed = fleet.count_status("failed");
+        assert!((300..700).contains(&failed), "failed = {}", failed);
+        assert!(fleet
+            .nodes
+            .iter()
+            .all(|n| n.report_timestamp.unwrap() <= fleet_epoch()));
+    }
+
     #[test]
     fn test_random_facts() {
         let facts = random_facts();
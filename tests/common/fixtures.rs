@@ -147,6 +147,7 @@ impl GroupFixtures {
             variables: serde_json::json!({}),
             rules: vec![],
             pinned_nodes: vec![],
+            version: 1,
         }
     }
 
@@ -166,6 +167,7 @@ impl GroupFixtures {
             variables: serde_json::json!({}),
             rules: vec![],
             pinned_nodes: vec![],
+            version: 1,
         }
     }
 }
@@ -226,6 +228,7 @@ impl RoleFixtures {
             description: Some("A custom test role".to_string()),
             is_system: false,
             parent_id: None,
+            parent_ids: vec![],
             permissions: vec![Permission {
                 id: Uuid::new_v4(),
                 resource: Resource::Nodes,
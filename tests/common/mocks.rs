@@ -158,6 +158,19 @@ impl MockPuppetDb {
         self.clear_error_mode();
     }
 
+    /// Seed with a generated fleet's nodes, facts and reports
+    pub fn seed_fleet(&self, fleet: &crate::common::factories::Fleet) {
+        self.add_nodes(fleet.nodes.clone());
+        let mut reports = self.reports.write().unwrap();
+        for report in &fleet.reports {
+            reports.insert(report.hash.clone(), report.clone());
+        }
+        let mut facts = self.facts.write().unwrap();
+        for (certname, value) in &fleet.facts {
+            facts.insert(certname.clone(), value.clone());
+        }
+    }
+
     /// Seed with test data
     pub fn seed_test_data(&self) {
         use crate::common::fixtures::{FactsFixtures, NodeFixtures, ReportFixtures};
@@ -269,6 +282,13 @@ impl MockScenario {
         scenario
     }
 
+    /// Create a scenario with a generated fleet
+    pub fn with_fleet(factory: &crate::common::factories::FleetFactory) -> Self {
+        let scenario = Self::new();
+        scenario.puppetdb.seed_fleet(&factory.build());
+        scenario
+    }
+
     /// Create a scenario simulating PuppetDB failure
     pub fn with_puppetdb_failure(error: MockError) -> Self {
         let scenario = Self::new();
@@ -336,6 +356,30 @@ mod tests {
         assert!(!mock.check("nodes", "delete"));
     }
 
+    #[test]
+    fn test_mock_scenario_with_fleet() {
+        let factory = crate::common::factories::FleetFactory::new(1)
+            .with_nodes(2000)
+            .with_reports_per_node(2);
+        let fleet = factory.build();
+        let scenario = MockScenario::with_fleet(&factory);
+
+        assert_eq!(scenario.puppetdb.get_nodes().unwrap().len(), 2000);
+        let certname = &fleet.nodes[1234].certname;
+        assert_eq!(
+            scenario.puppetdb.get_facts(certname).unwrap().as_ref(),
+            fleet.facts.get(certname)
+        );
+        assert_eq!(
+            scenario
+                .puppetdb
+                .get_reports_for_node(certname)
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
     #[test]
     fn test_mock_scenario_with_test_data() {
         let scenario = MockScenario::with_test_data();
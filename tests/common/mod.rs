@@ -3,15 +3,17 @@
 //! This module provides shared test infrastructure including:
 //! - Test fixtures and factories
 //! - Mock services
-//! - Test database setup
+//! - Per-test databases for repository and service tests
 //! - API test client
 
 pub mod factories;
 pub mod fixtures;
 pub mod mocks;
 pub mod test_app;
+pub mod test_db;
 
 pub use factories::*;
 pub use fixtures::*;
 pub use mocks::*;
 pub use test_app::*;
+pub use test_db::*;
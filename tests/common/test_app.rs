@@ -4,6 +4,7 @@
 //! with in-memory databases and mock services.
 
 use std::sync::{atomic::AtomicBool, Arc};
+use std::time::Duration;
 
 use axum::{body::Body, http::Request, Router};
 use chrono::Utc;
@@ -18,9 +19,15 @@ use openvox_webui::{
         InventoryConfig, LoggingConfig, RbacConfig, ServerConfig,
     },
     db,
-    middleware::auth::{Claims, TokenType},
+    middleware::{
+        auth::{Claims, TokenType},
+        CsrfProtection,
+    },
     models::default_organization_uuid,
-    services::notification::NotificationService,
+    services::{
+        notification::NotificationService, smart_list::MemberCounts, webhooks::Webhooks,
+        ConfigReloader,
+    },
     AppState, DbRbacService, RbacService,
 };
 
@@ -75,10 +82,15 @@ impl TestApp {
                     enabled: c.enabled,
                     encryption_key: c.encryption_key.clone(),
                     webhook_base_url: c.webhook_base_url.clone(),
+                    librarian_puppet_path: c.librarian_puppet_binary_path.clone(),
                     retain_history_days: c.retain_history_days,
+                    max_concurrent_deployments: c.max_concurrent_deployments,
+                    pat_token_warning_days: c.pat_token_warning_days,
+                    ssh_key_max_age_days: c.ssh_key_max_age_days,
                     git: openvox_webui::services::git::GitServiceConfig {
                         repos_base_dir: c.repos_base_dir.clone(),
                         ssh_keys_dir: c.ssh_keys_dir.clone(),
+                        fault_injection: c.fault_injection.clone(),
                     },
                     r10k: openvox_webui::services::r10k::R10kConfig {
                         binary_path: c.r10k_binary_path.clone(),
@@ -97,6 +109,17 @@ impl TestApp {
         let notification_service = Arc::new(NotificationService::new(db.clone()));
 
         // Create application state
+        let config_reloader = ConfigReloader::new(
+            config.clone(),
+            config.clone(),
+            db.clone(),
+            rbac_db.clone(),
+            None,
+        );
+        let webhooks = Webhooks::new(db.clone(), None);
+        let smart_list_counts = Arc::new(MemberCounts::new(Duration::from_secs(
+            config.cache.smart_list_count_ttl_secs,
+        )));
         let state = AppState {
             config,
             db,
@@ -104,12 +127,23 @@ impl TestApp {
             inventory_config,
             inventory_ready,
             puppetdb: None,
+            puppetdb_tenants: Default::default(),
             puppet_ca: None,
             rbac,
             rbac_db,
             code_deploy_config,
             backup_config: None,
             notification_service,
+            webhooks,
+            settings_cipher: None,
+            secrets: None,
+            audit_forwarder: None,
+            jwt_keys: Default::default(),
+            log_buffer: None,
+            health: Default::default(),
+            smart_list_counts,
+            payload_debug: Default::default(),
+            config_reloader,
         };
 
         // Build the router
@@ -117,13 +151,36 @@ impl TestApp {
             .nest("/api/v1", api::public_routes())
             .nest(
                 "/api/v1",
-                api::protected_routes().layer(axum::middleware::from_fn_with_state(
-                    state.clone(),
-                    openvox_webui::middleware::auth::auth_middleware,
-                )),
+                api::protected_routes()
+                    .route_layer(axum::middleware::from_fn_with_state(
+                        state.clone(),
+                        openvox_webui::middleware::audit_middleware,
+                    ))
+                    .layer(axum::middleware::from_fn_with_state(
+                        state.clone(),
+                        openvox_webui::middleware::auth::auth_middleware,
+                    )),
             )
+            .layer(axum::middleware::from_fn(
+                openvox_webui::middleware::api_error_middleware,
+            ))
             .with_state(state.clone());
 
+        // As in create_router: browser sessions must echo the CSRF token
+        let router = if state.config.auth.csrf_enabled() {
+            let csrf =
+                CsrfProtection::from_config(&state.config.auth.csrf).expect("valid CSRF settings");
+            router.layer(axum::middleware::from_fn_with_state(
+                csrf,
+                openvox_webui::middleware::csrf_middleware,
+            ))
+        } else {
+            router
+        };
+        let router = router.layer(axum::middleware::from_fn(
+            openvox_webui::middleware::request_id_middleware,
+        ));
+
         Self { router, state }
     }
 
@@ -295,6 +352,16 @@ pub fn test_config() -> AppConfig {
             tls: None,
             static_dir: None,
             serve_frontend: false,
+            static_assets: Default::default(),
+            base_path: String::new(),
+            http3: None,
+            listeners: Vec::new(),
+            enc_listener: None,
+            unix_socket: None,
+            trusted_proxies: Vec::new(),
+            security_headers: Default::default(),
+            body_limits: Default::default(),
+            graphql: None,
         },
         database: DatabaseConfig {
             url: format!("sqlite://{}?mode=rwc", db_path),
@@ -302,6 +369,8 @@ pub fn test_config() -> AppConfig {
             min_connections: 1,
             connect_timeout_secs: 30,
             idle_timeout_secs: 600,
+            busy_timeout_ms: 5000,
+            busy_retries: 3,
         },
         auth: AuthConfig {
             jwt_secret: "test_secret_key_that_is_at_least_32_bytes_long".to_string(),
@@ -309,6 +378,14 @@ pub fn test_config() -> AppConfig {
             refresh_token_expiry_days: 7,
             bcrypt_cost: 4, // Lower cost for faster tests
             password_min_length: 8,
+            api_key_rotation_grace_minutes: 60,
+            password_policy: Default::default(),
+            password_reset_url: None,
+            signing_keys: Default::default(),
+            login_alerts: Default::default(),
+            csrf: Default::default(),
+            mode: Default::default(),
+            cookie: Default::default(),
         },
         puppetdb: None,
         puppet_ca: None,
@@ -329,6 +406,18 @@ pub fn test_config() -> AppConfig {
         node_bootstrap: None,
         cve: None,
         pagination: Default::default(),
+        secrets: None,
+        settings_encryption: Default::default(),
+        audit: Default::default(),
+        rate_limit: Default::default(),
+        hot_reload: Default::default(),
+        webhooks: Default::default(),
+        recycle_bin: Default::default(),
+        health: Default::default(),
+        notifications: Default::default(),
+        reporting: Default::default(),
+        report_ingestion: Default::default(),
+        organizations: Default::default(),
     }
 }
 
@@ -363,6 +452,8 @@ pub fn generate_test_token(
         jti: Uuid::new_v4().to_string(),
         token_type: TokenType::Access,
         organization_id: Some(default_organization_uuid().to_string()),
+        act: None,
+        rti: None,
     };
 
     encode(
@@ -397,6 +488,8 @@ pub async fn generate_test_token_with_session(
         jti: session_id.to_string(),
         token_type: TokenType::Access,
         organization_id: Some(default_organization_uuid().to_string()),
+        act: None,
+        rti: None,
     };
 
     openvox_webui::middleware::auth::create_auth_session(
@@ -404,6 +497,7 @@ pub async fn generate_test_token_with_session(
         &session_id,
         &user_id,
         Utc::now() + chrono::Duration::hours(1),
+        &openvox_webui::middleware::auth::SessionClient::default(),
     )
     .await
     .expect("Failed to create test auth session");
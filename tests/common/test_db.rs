@@ -0,0 +1,224 @@
+//! Per-test databases for repository and service tests
+//!
+//! [`TestDb`] gives a test its own migrated SQLite database without starting
+//! the application, so repository and service logic can be tested directly.
+//! The migrations run once per test binary, into a template database; each
+//! [`TestDb`] is a copy of it that the test may change freely and that is
+//! deleted when the [`TestDb`] is dropped, so no test sees another's data.
+//!
+//! The `create_*` helpers persist records built by the factories.
+
+use std::path::{Path, PathBuf};
+
+use chrono::Utc;
+use serde_json::Value;
+use sqlx::sqlite::SqlitePoolOptions;
+use tokio::sync::OnceCell;
+use uuid::Uuid;
+
+use openvox_webui::{
+    config::DatabaseConfig,
+    db::{self, CodeEnvironmentRepository, CodeRepositoryRepository, DbPool, GroupRepository},
+    models::{
+        default_organization_uuid, AuthType, ClassificationRule, CodeEnvironment,
+        CreateRepositoryRequest, CreateRuleRequest, DeployStrategy, NodeGroup, RuleOperator,
+    },
+    services::DbRbacService,
+};
+
+use crate::common::factories::{GroupBuilder, TestUserBuilder};
+use crate::common::fixtures::TestUser;
+
+/// Migrated database the per-test databases are copied from
+static TEMPLATE: OnceCell<PathBuf> = OnceCell::const_new();
+
+/// Isolated, migrated database of a single test
+pub struct TestDb {
+    pub pool: DbPool,
+    path: PathBuf,
+}
+
+impl TestDb {
+    /// Create a database with all migrations applied and the built-in
+    /// organization and roles seeded
+    pub async fn new() -> Self {
+        let template = TEMPLATE.get_or_init(create_template).await;
+        let path = unique_path("openvox_test_db");
+        std::fs::copy(template, &path).expect("Failed to copy template database");
+
+        let config = database_config(&path);
+        let options =
+            db::sqlite_connect_options(&config.url, &config).expect("Invalid test database URL");
+        let pool = SqlitePoolOptions::new()
+            .max_connections(config.max_connections)
+            .connect_with(options)
+            .await
+            .expect("Failed to open test database");
+
+        Self { pool, path }
+    }
+
+    /// Group repository on this database
+    pub fn groups(&self) -> GroupRepository<'_> {
+        GroupRepository::new(&self.pool)
+    }
+
+    /// Database-backed RBAC service on this database
+    pub fn rbac(&self) -> DbRbacService {
+        DbRbacService::new(self.pool.clone())
+    }
+
+    /// Persist a group built by [`GroupFactory`](crate::common::GroupFactory)
+    /// in the default organization
+    pub async fn create_group(&self, group: GroupBuilder) -> NodeGroup {
+        self.groups()
+            .create(default_organization_uuid(), &group.into_request())
+            .await
+            .expect("Failed to create test group")
+    }
+
+    /// Add a classification rule to a group
+    pub async fn add_rule(
+        &self,
+        group_id: Uuid,
+        fact_path: &str,
+        operator: RuleOperator,
+        value: Value,
+    ) -> ClassificationRule {
+        self.groups()
+            .add_rule(
+                group_id,
+                &CreateRuleRequest {
+                    fact_path: fact_path.to_string(),
+                    operator,
+                    value,
+                },
+            )
+            .await
+            .expect("Failed to add test rule")
+    }
+
+    /// Persist a user built by [`UserFactory`](crate::common::UserFactory) in
+    /// the default organization, with its roles assigned
+    ///
+    /// The user has no usable password; tests authenticate with tokens.
+    pub async fn create_user(&self, user: TestUserBuilder) -> TestUser {
+        let user = user.build();
+        let now = Utc::now().to_rfc3339();
+        sqlx::query(
+            r#"
+            INSERT INTO users (id, organization_id, username, email, password_hash, role,
+                               auth_provider, created_at, updated_at)
+            VALUES (?, ?, ?, ?, '!', ?, 'local', ?, ?)
+            "#,
+        )
+        .bind(user.id.to_string())
+        .bind(default_organization_uuid().to_string())
+        .bind(&user.username)
+        .bind(&user.email)
+        .bind(user.roles.first().map(String::as_str).unwrap_or("viewer"))
+        .bind(&now)
+        .bind(&now)
+        .execute(&self.pool)
+        .await
+        .expect("Failed to create test user");
+
+        let rbac = self.rbac();
+        let mut role_ids = user.role_ids.clone();
+        for name in &user.roles {
+            let role = rbac
+                .get_role_by_name(name)
+                .await
+                .expect("Failed to look up role")
+                .unwrap_or_else(|| panic!("Unknown test role: {}", name));
+            role_ids.push(role.id);
+        }
+        rbac.assign_roles(&user.id, &role_ids)
+            .await
+            .expect("Failed to assign test user roles");
+
+        user
+    }
+
+    /// Create a public control repository with one environment on `branch`
+    pub async fn create_environment(&self, branch: &str) -> CodeEnvironment {
+        let repository = CodeRepositoryRepository::new(&self.pool)
+            .create(
+                &CreateRepositoryRequest {
+                    name: format!("control-{}", Uuid::new_v4().simple()),
+                    url: "https://git.example.com/puppet/control.git".to_string(),
+                    branch_pattern: "*".to_string(),
+                    auth_type: AuthType::None,
+                    ssh_key_id: None,
+                    pat_token_id: None,
+                    github_pat: None,
+                    deploy_strategy: DeployStrategy::R10k,
+                    poll_interval_seconds: 300,
+                    is_control_repo: true,
+                },
+                None,
+            )
+            .await
+            .expect("Failed to create test repository");
+
+        CodeEnvironmentRepository::new(&self.pool)
+            .upsert(
+                repository.id,
+                branch,
+                branch,
+                Some("0000000"),
+                None,
+                None,
+                None,
+            )
+            .await
+            .expect("Failed to create test environment")
+    }
+}
+
+impl Drop for TestDb {
+    fn drop(&mut self) {
+        remove_database(&self.path);
+    }
+}
+
+/// Migrate a database and compact it into the template
+async fn create_template() -> PathBuf {
+    let staging = unique_path("openvox_test_migrate");
+    let template = unique_path("openvox_test_template");
+
+    let pool = db::init_pool(&database_config(&staging))
+        .await
+        .expect("Failed to migrate template database");
+    sqlx::query("VACUUM INTO ?")
+        .bind(template.to_string_lossy().to_string())
+        .execute(&pool)
+        .await
+        .expect("Failed to write template database");
+    pool.close().await;
+    remove_database(&staging);
+
+    template
+}
+
+fn database_config(path: &Path) -> DatabaseConfig {
+    DatabaseConfig {
+        url: format!("sqlite://{}?mode=rwc", path.display()),
+        max_connections: 4,
+        min_connections: 1,
+        connect_timeout_secs: 30,
+        idle_timeout_secs: 600,
+        busy_timeout_ms: 5000,
+        busy_retries: 3,
+    }
+}
+
+fn unique_path(prefix: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("{}_{}.db", prefix, Uuid::new_v4().simple()))
+}
+
+fn remove_database(path: &Path) {
+    for suffix in ["", "-wal", "-shm"] {
+        std::fs::remove_file(format!("{}{}", path.display(), suffix)).ok();
+    }
+}
@@ -26,7 +26,12 @@ async fn test_detailed_health_endpoint() {
     let json: serde_json::Value = response.json();
     assert!(json.get("status").is_some());
     assert!(json.get("components").is_some());
-    assert!(json["components"].get("database").is_some());
+    assert_eq!(json["components"]["database"]["status"], "healthy");
+    assert!(json["components"]["database"]["latency_ms"].is_u64());
+    assert!(json["components"]["database"]["last_success"].is_string());
+    assert_eq!(json["components"]["puppetdb"]["status"], "not_configured");
+    assert_eq!(json["components"]["puppet_ca"]["status"], "not_configured");
+    assert!(json["components"].get("scheduler").is_some());
 }
 
 #[tokio::test]
@@ -43,6 +48,12 @@ async fn test_readiness_probe() {
     let response = app.get("/api/v1/health/ready").await;
 
     response.assert_ok();
+
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["status"], "ready");
+    assert_eq!(json["checks"]["migrations"], "passed");
+    assert_eq!(json["checks"]["rbac_seed"], "passed");
+    assert_eq!(json["checks"]["cache_warmup"], "disabled");
 }
 
 #[tokio::test]
@@ -118,6 +129,124 @@ async fn test_groups_endpoint_returns_default_group() {
     assert!(has_all_nodes, "Should have 'All Nodes' group");
 }
 
+#[tokio::test]
+async fn test_cookie_mode_accepts_the_session_cookie() {
+    let mut config = crate::common::test_config();
+    config.auth.mode = openvox_webui::config::AuthMode::Cookie;
+    let app = TestApp::with_config(config).await;
+    let token = generate_test_token(
+        &app.state.config,
+        Uuid::new_v4(),
+        "admin",
+        vec!["admin".to_string()],
+    );
+
+    app.get("/api/v1/groups")
+        .await
+        .assert_status(axum::http::StatusCode::UNAUTHORIZED);
+
+    let request = axum::http::Request::builder()
+        .method("GET")
+        .uri("/api/v1/groups")
+        .header("Cookie", format!("theme=dark; openvox_session={}", token))
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let response = app.request(request).await;
+    response.assert_ok();
+
+    // Cookie mode turns CSRF protection on; the first response issues the
+    // token the frontend echoes
+    let csrf = response
+        .headers
+        .get_all("set-cookie")
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .find_map(|cookie| cookie.strip_prefix("openvox_csrf="))
+        .and_then(|cookie| cookie.split(';').next())
+        .expect("CSRF cookie")
+        .to_string();
+    let logout = |csrf_header: Option<&str>| {
+        let mut builder = axum::http::Request::builder()
+            .method("POST")
+            .uri("/api/v1/auth/logout")
+            .header("Content-Type", "application/json")
+            .header(
+                "Cookie",
+                format!("openvox_session={}; openvox_csrf={}", token, csrf),
+            );
+        if let Some(value) = csrf_header {
+            builder = builder.header("X-CSRF-Token", value);
+        }
+        builder.body(axum::body::Body::from("{}")).unwrap()
+    };
+
+    let response = app.request(logout(None)).await;
+    response.assert_status(axum::http::StatusCode::FORBIDDEN);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["code"], "csrf_token_invalid");
+    app.request(logout(Some(&"0".repeat(64))))
+        .await
+        .assert_status(axum::http::StatusCode::FORBIDDEN);
+
+    let response = app.request(logout(Some(&csrf))).await;
+    response.assert_ok();
+    let cleared: Vec<_> = response
+        .headers
+        .get_all("set-cookie")
+        .iter()
+        .map(|value| value.to_str().unwrap().to_string())
+        .collect();
+    assert_eq!(cleared.len(), 2);
+    assert!(cleared.iter().all(|cookie| cookie.contains("Max-Age=0")));
+}
+
+#[tokio::test]
+async fn test_cookie_session_cannot_create_webhooks_without_csrf_token() {
+    let mut config = crate::common::test_config();
+    config.auth.mode = openvox_webui::config::AuthMode::Cookie;
+    let app = TestApp::with_config(config).await;
+    let token = generate_test_token(
+        &app.state.config,
+        Uuid::from_u128(1),
+        "admin",
+        vec!["super_admin".to_string()],
+    );
+    let csrf = "0123456789abcdef".repeat(4);
+    let create = |csrf_header: Option<&str>| {
+        let mut builder = axum::http::Request::builder()
+            .method("POST")
+            .uri("/api/v1/webhooks")
+            .header("Content-Type", "application/json")
+            .header(
+                "Cookie",
+                format!("openvox_session={}; openvox_csrf={}", token, csrf),
+            );
+        if let Some(value) = csrf_header {
+            builder = builder.header("X-CSRF-Token", value);
+        }
+        builder
+            .body(axum::body::Body::from(
+                serde_json::json!({
+                    "name": "Exfiltrate",
+                    "url": "https://attacker.example.com/hook",
+                    "events": ["alert.fired"]
+                })
+                .to_string(),
+            ))
+            .unwrap()
+    };
+
+    // Outgoing webhooks share the /webhooks prefix with the exempt code
+    // deploy receivers, but are not exempt themselves
+    let response = app.request(create(None)).await;
+    response.assert_status(axum::http::StatusCode::FORBIDDEN);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["code"], "csrf_token_invalid");
+
+    let response = app.request(create(Some(&csrf))).await;
+    assert_ne!(response.status, axum::http::StatusCode::FORBIDDEN);
+}
+
 #[tokio::test]
 async fn test_roles_endpoint_returns_system_roles() {
     let app = TestApp::new().await;
@@ -147,6 +276,968 @@ async fn test_roles_endpoint_returns_system_roles() {
     assert!(has_admin, "Should have admin role");
 }
 
+#[tokio::test]
+async fn test_openapi_spec_is_served() {
+    let app = TestApp::new().await;
+    let response = app.get("/api/v1/openapi.json").await;
+
+    response.assert_ok();
+
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["openapi"], "3.1.0");
+    assert!(json["paths"]["/groups/{id}"]["put"].is_object());
+
+    let response = app.get("/api/v1/docs").await;
+    response.assert_ok();
+    assert!(response.text().contains("/groups/{id}"));
+}
+
+#[tokio::test]
+async fn test_errors_share_the_api_error_shape() {
+    let app = TestApp::new().await;
+
+    let response = app.get("/api/v1/groups").await;
+    response.assert_unauthorized();
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["error"], "unauthorized");
+    assert_eq!(json["code"], "missing_token");
+
+    let token = generate_test_token(
+        &app.state.config,
+        Uuid::from_u128(1),
+        "admin",
+        vec!["super_admin".to_string()],
+    );
+    let response = app
+        .request_with_auth(
+            axum::http::Request::builder()
+                .method("POST")
+                .uri("/api/v1/groups")
+                .header("Content-Type", "application/json")
+                .body(axum::body::Body::from("{not json"))
+                .unwrap(),
+            &token,
+        )
+        .await;
+    response.assert_bad_request();
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["error"], "bad_request");
+    assert_eq!(json["code"], "bad_request");
+    assert!(json["message"].is_string());
+}
+
+#[tokio::test]
+async fn test_request_id_is_propagated() {
+    let app = TestApp::new().await;
+
+    let response = app
+        .request(
+            axum::http::Request::builder()
+                .uri("/api/v1/groups")
+                .header("X-Request-Id", "proxy-42")
+                .body(axum::body::Body::empty())
+                .unwrap(),
+        )
+        .await;
+    response.assert_unauthorized();
+    assert_eq!(response.headers["x-request-id"], "proxy-42");
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["request_id"], "proxy-42");
+
+    let response = app.get("/api/v1/groups").await;
+    let request_id = response.headers["x-request-id"].to_str().unwrap();
+    assert!(Uuid::parse_str(request_id).is_ok());
+}
+
+#[tokio::test]
+async fn test_webhook_lifecycle() {
+    let app = TestApp::new().await;
+    let token = generate_test_token(
+        &app.state.config,
+        Uuid::new_v4(),
+        "admin",
+        vec!["admin".to_string()],
+    );
+
+    let create_request = axum::http::Request::builder()
+        .method("POST")
+        .uri("/api/v1/webhooks")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(
+            serde_json::json!({
+                "name": "chatops",
+                "url": "https://hooks.example.com/openvox",
+                "events": ["node.failed_run", "group.changed"]
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    let create_response = app.request_with_auth(create_request, &token).await;
+    create_response.assert_created();
+    let created: serde_json::Value = create_response.json();
+    let webhook_id = created["id"].as_str().expect("webhook id").to_string();
+    assert_eq!(created["secret"].as_str().map(str::len), Some(64));
+
+    // The secret is only returned on creation
+    let get_request = axum::http::Request::builder()
+        .method("GET")
+        .uri(format!("/api/v1/webhooks/{}", webhook_id))
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let get_response = app.request_with_auth(get_request, &token).await;
+    get_response.assert_ok();
+    let webhook: serde_json::Value = get_response.json();
+    assert!(webhook.get("secret").is_none());
+    assert_eq!(
+        webhook["events"],
+        serde_json::json!(["node.failed_run", "group.changed"])
+    );
+
+    let ping_request = axum::http::Request::builder()
+        .method("POST")
+        .uri(format!("/api/v1/webhooks/{}/ping", webhook_id))
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let ping_response = app.request_with_auth(ping_request, &token).await;
+    ping_response.assert_status(axum::http::StatusCode::ACCEPTED);
+    let ping: serde_json::Value = ping_response.json();
+    assert_eq!(ping["event"], "ping");
+    assert_eq!(ping["status"], "pending");
+
+    let deliveries_request = axum::http::Request::builder()
+        .method("GET")
+        .uri(format!("/api/v1/webhooks/{}/deliveries", webhook_id))
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let deliveries_response = app.request_with_auth(deliveries_request, &token).await;
+    deliveries_response.assert_ok();
+    let deliveries: Vec<serde_json::Value> = deliveries_response.json();
+    assert_eq!(deliveries.len(), 1);
+    assert_eq!(deliveries[0]["payload"]["event"], "ping");
+
+    let viewer_token = generate_test_token(
+        &app.state.config,
+        Uuid::new_v4(),
+        "viewer",
+        vec!["viewer".to_string()],
+    );
+    let list_request = axum::http::Request::builder()
+        .method("GET")
+        .uri("/api/v1/webhooks")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    app.request_with_auth(list_request, &viewer_token)
+        .await
+        .assert_forbidden();
+}
+
+#[tokio::test]
+async fn test_global_search_finds_indexed_groups() {
+    use openvox_webui::db::repository::GroupRepository;
+    use openvox_webui::models::{default_organization_uuid, CreateGroupRequest};
+
+    let app = TestApp::new().await;
+    let token = generate_test_token(
+        &app.state.config,
+        Uuid::new_v4(),
+        "root",
+        vec!["super_admin".to_string()],
+    );
+
+    GroupRepository::new(&app.state.db)
+        .create(
+            default_organization_uuid(),
+            &CreateGroupRequest {
+                name: "Webservers".to_string(),
+                description: Some("Frontend nginx fleet".to_string()),
+                parent_id: None,
+                environment: None,
+                is_environment_group: None,
+                match_all_nodes: None,
+                rule_match_type: None,
+                classes: Some(serde_json::json!({ "profile::nginx": {} })),
+                variables: None,
+            },
+        )
+        .await
+        .expect("create group");
+
+    let reindex_request = axum::http::Request::builder()
+        .method("POST")
+        .uri("/api/v1/search/reindex")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    app.request_with_auth(reindex_request, &token)
+        .await
+        .assert_ok();
+
+    let search_request = axum::http::Request::builder()
+        .method("GET")
+        .uri("/api/v1/search?q=ngin")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let search_response = app.request_with_auth(search_request, &token).await;
+    search_response.assert_ok();
+    let found: serde_json::Value = search_response.json();
+    let types: Vec<&str> = found["results"]
+        .as_array()
+        .expect("results")
+        .iter()
+        .filter_map(|r| r["type"].as_str())
+        .collect();
+    assert!(types.contains(&"group"), "results: {}", found);
+    assert!(types.contains(&"class"), "results: {}", found);
+
+    // Users without any RBAC role see nothing
+    let viewer_token = generate_test_token(
+        &app.state.config,
+        Uuid::new_v4(),
+        "nobody",
+        vec!["viewer".to_string()],
+    );
+    let search_request = axum::http::Request::builder()
+        .method("GET")
+        .uri("/api/v1/search?q=webservers&types=group")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let search_response = app.request_with_auth(search_request, &viewer_token).await;
+    search_response.assert_ok();
+    let found: serde_json::Value = search_response.json();
+    assert_eq!(found["results"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn test_dashboard_preferences_are_per_user() {
+    use openvox_webui::services::AuthService;
+
+    let app = TestApp::new().await;
+    let user = AuthService::new(app.state.db.clone())
+        .create_user("dashuser", "dash@example.com", "Sup3r-Secret!", "viewer")
+        .await
+        .expect("create user");
+    let token = generate_test_token(
+        &app.state.config,
+        user.id,
+        "dashuser",
+        vec!["viewer".to_string()],
+    );
+
+    let update_request = axum::http::Request::builder()
+        .method("PUT")
+        .uri("/api/v1/settings/dashboard")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(
+            serde_json::json!({
+                "theme": "dark",
+                "widgets": [{
+                    "id": "status",
+                    "type": "node_status",
+                    "position": { "row": 0, "col": 6 }
+                }],
+                "default_filters": { "environment": "production" }
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    app.request_with_auth(update_request, &token)
+        .await
+        .assert_ok();
+
+    let get_request = axum::http::Request::builder()
+        .method("GET")
+        .uri("/api/v1/settings/dashboard")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let get_response = app.request_with_auth(get_request, &token).await;
+    get_response.assert_ok();
+    let dashboard: serde_json::Value = get_response.json();
+    assert_eq!(dashboard["theme"], "dark");
+    assert_eq!(dashboard["widgets"][0]["position"]["col"], 6);
+    assert_eq!(dashboard["default_filters"]["environment"], "production");
+    // Unset preferences follow the global configuration
+    assert_eq!(dashboard["nodes_per_page"], 50);
+
+    // Other users keep the global dashboard
+    let other_token = generate_test_token(
+        &app.state.config,
+        Uuid::new_v4(),
+        "other",
+        vec!["viewer".to_string()],
+    );
+    let get_request = axum::http::Request::builder()
+        .method("GET")
+        .uri("/api/v1/settings/dashboard")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let other: serde_json::Value = app
+        .request_with_auth(get_request, &other_token)
+        .await
+        .json();
+    assert_eq!(other["theme"], "light");
+
+    let reset_request = axum::http::Request::builder()
+        .method("DELETE")
+        .uri("/api/v1/settings/dashboard")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let reset_response = app.request_with_auth(reset_request, &token).await;
+    reset_response.assert_ok();
+    let dashboard: serde_json::Value = reset_response.json();
+    assert_eq!(dashboard["theme"], "light");
+}
+
+#[tokio::test]
+async fn test_widget_data_requires_a_runnable_binding() {
+    use openvox_webui::services::AuthService;
+
+    let app = TestApp::new().await;
+    let user = AuthService::new(app.state.db.clone())
+        .create_user(
+            "widgetuser",
+            "widget@example.com",
+            "Sup3r-Secret!",
+            "viewer",
+        )
+        .await
+        .expect("create user");
+    let token = generate_test_token(
+        &app.state.config,
+        user.id,
+        "widgetuser",
+        vec!["viewer".to_string()],
+    );
+
+    let update_request = axum::http::Request::builder()
+        .method("PUT")
+        .uri("/api/v1/settings/dashboard")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(
+            serde_json::json!({
+                "widgets": [
+                    { "id": "plain", "type": "node_status" },
+                    {
+                        "id": "missing-report",
+                        "type": "custom",
+                        "binding": {
+                            "source": "saved_report",
+                            "report_id": Uuid::new_v4(),
+                            "thresholds": [{ "value": 10.0, "level": "warning" }]
+                        }
+                    }
+                ]
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    app.request_with_auth(update_request, &token)
+        .await
+        .assert_ok();
+
+    for (widget, status) in [
+        ("plain", axum::http::StatusCode::BAD_REQUEST),
+        ("missing-report", axum::http::StatusCode::NOT_FOUND),
+        ("unknown", axum::http::StatusCode::NOT_FOUND),
+    ] {
+        let request = axum::http::Request::builder()
+            .method("GET")
+            .uri(format!(
+                "/api/v1/settings/dashboard/widgets/{}/data",
+                widget
+            ))
+            .body(axum::body::Body::empty())
+            .unwrap();
+        app.request_with_auth(request, &token)
+            .await
+            .assert_status(status);
+    }
+
+    // The test app has no PuppetDB to run PQL against
+    let preview_request = axum::http::Request::builder()
+        .method("POST")
+        .uri("/api/v1/settings/dashboard/widgets/preview")
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(
+            serde_json::json!({
+                "source": "pql",
+                "query": "nodes[count(), catalog_environment] { group by catalog_environment }",
+                "label_field": "catalog_environment"
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    app.request_with_auth(preview_request, &token)
+        .await
+        .assert_status(axum::http::StatusCode::SERVICE_UNAVAILABLE);
+}
+
+#[tokio::test]
+async fn test_group_bulk_operations_report_per_item_results() {
+    use openvox_webui::db::repository::GroupRepository;
+    use openvox_webui::models::{default_organization_uuid, CreateGroupRequest};
+
+    let app = TestApp::new().await;
+    // The default admin account holds the super_admin role in the database
+    let token = generate_test_token(
+        &app.state.config,
+        Uuid::from_u128(1),
+        "admin",
+        vec!["super_admin".to_string()],
+    );
+
+    let repo = GroupRepository::new(&app.state.db);
+    let mut group_ids = Vec::new();
+    for name in ["Web", "Database"] {
+        let group = repo
+            .create(
+                default_organization_uuid(),
+                &CreateGroupRequest {
+                    name: name.to_string(),
+                    description: None,
+                    parent_id: None,
+                    environment: None,
+                    is_environment_group: None,
+                    match_all_nodes: None,
+                    rule_match_type: None,
+                    classes: None,
+                    variables: None,
+                },
+            )
+            .await
+            .expect("create group");
+        group_ids.push(group.id);
+    }
+    let (web, database) = (group_ids[0], group_ids[1]);
+
+    let bulk = |path: &str, body: serde_json::Value| {
+        axum::http::Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/groups/bulk/{}", path))
+            .header("Content-Type", "application/json")
+            .body(axum::body::Body::from(body.to_string()))
+            .unwrap()
+    };
+
+    let response = app
+        .request_with_auth(
+            bulk(
+                "pin",
+                serde_json::json!({
+                    "group_id": web,
+                    "certnames": ["web1.example.com", "web2.example.com", "web1.example.com"]
+                }),
+            ),
+            &token,
+        )
+        .await;
+    response.assert_ok();
+    let pinned: serde_json::Value = response.json();
+    assert_eq!(pinned["total"], 2);
+    assert_eq!(pinned["succeeded"], 2);
+
+    let response = app
+        .request_with_auth(
+            bulk(
+                "move",
+                serde_json::json!({
+                    "from_group_id": web,
+                    "to_group_id": database,
+                    "certnames": ["web2.example.com", "db1.example.com"]
+                }),
+            ),
+            &token,
+        )
+        .await;
+    response.assert_ok();
+    let moved: serde_json::Value = response.json();
+    assert_eq!(moved["succeeded"], 1);
+    assert_eq!(moved["failed"], 1);
+    assert_eq!(moved["results"][1]["item"], "db1.example.com");
+    assert_eq!(moved["results"][1]["success"], false);
+    assert_eq!(
+        repo.get_pinned_nodes(web).await.unwrap(),
+        vec!["web1.example.com"]
+    );
+    assert_eq!(
+        repo.get_pinned_nodes(database).await.unwrap(),
+        vec!["web2.example.com"]
+    );
+
+    let response = app
+        .request_with_auth(
+            bulk(
+                "classes",
+                serde_json::json!({
+                    "group_ids": [web, database, Uuid::new_v4()],
+                    "class": "ntp",
+                    "parameters": { "servers": ["ntp1.example.com"] }
+                }),
+            ),
+            &token,
+        )
+        .await;
+    response.assert_ok();
+    let classes: serde_json::Value = response.json();
+    assert_eq!(classes["succeeded"], 2);
+    assert_eq!(classes["results"][2]["message"], "Group not found");
+    let group = repo
+        .get_by_id(default_organization_uuid(), database)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(group.classes["ntp"]["servers"][0], "ntp1.example.com");
+
+    let response = app
+        .request_with_auth(
+            bulk(
+                "rules",
+                serde_json::json!({
+                    "rules": [
+                        { "group_id": web, "fact_path": "role", "operator": "=", "value": "web" },
+                        { "group_id": database, "fact_path": "", "operator": "=", "value": "db" }
+                    ]
+                }),
+            ),
+            &token,
+        )
+        .await;
+    response.assert_ok();
+    let rules: serde_json::Value = response.json();
+    assert_eq!(rules["succeeded"], 1);
+    assert_eq!(rules["results"][1]["message"], "fact_path is required");
+    assert_eq!(repo.get_rules(web).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn test_group_updates_require_current_etag() {
+    use openvox_webui::db::repository::GroupRepository;
+    use openvox_webui::models::{default_organization_uuid, CreateGroupRequest};
+
+    let app = TestApp::new().await;
+    let token = generate_test_token(
+        &app.state.config,
+        Uuid::from_u128(1),
+        "admin",
+        vec!["super_admin".to_string()],
+    );
+    let group = GroupRepository::new(&app.state.db)
+        .create(
+            default_organization_uuid(),
+            &CreateGroupRequest {
+                name: "Mail".to_string(),
+                description: None,
+                parent_id: None,
+                environment: None,
+                is_environment_group: None,
+                match_all_nodes: None,
+                rule_match_type: None,
+                classes: None,
+                variables: None,
+            },
+        )
+        .await
+        .expect("create group");
+    let uri = format!("/api/v1/groups/{}", group.id);
+
+    let get_request = axum::http::Request::builder()
+        .method("GET")
+        .uri(&uri)
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let response = app.request_with_auth(get_request, &token).await;
+    response.assert_ok();
+    let etag = response.headers["etag"].to_str().unwrap().to_string();
+    assert_eq!(etag, "\"1\"");
+
+    let update = |if_match: Option<&str>, body: serde_json::Value| {
+        let mut builder = axum::http::Request::builder()
+            .method("PUT")
+            .uri(&uri)
+            .header("Content-Type", "application/json");
+        if let Some(if_match) = if_match {
+            builder = builder.header("If-Match", if_match);
+        }
+        builder
+            .body(axum::body::Body::from(body.to_string()))
+            .unwrap()
+    };
+
+    app.request_with_auth(update(None, serde_json::json!({ "name": "Mail" })), &token)
+        .await
+        .assert_status(axum::http::StatusCode::PRECONDITION_REQUIRED);
+
+    let response = app
+        .request_with_auth(
+            update(
+                Some(etag.as_str()),
+                serde_json::json!({
+                    "description": "Mail relays",
+                    "rules": [{ "fact_path": "role", "operator": "=", "value": "mail" }],
+                    "pinned_nodes": ["mx1.example.com", " mx1.example.com", "mx2.example.com"]
+                }),
+            ),
+            &token,
+        )
+        .await;
+    response.assert_ok();
+    assert_eq!(response.headers["etag"], "\"2\"");
+    let updated: serde_json::Value = response.json();
+    assert_eq!(updated["version"], 2);
+    assert_eq!(updated["rules"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        updated["pinned_nodes"],
+        serde_json::json!(["mx1.example.com", "mx2.example.com"])
+    );
+
+    // A second writer still holding the first ETag must not overwrite it
+    app.request_with_auth(
+        update(
+            Some(etag.as_str()),
+            serde_json::json!({ "description": "Stale" }),
+        ),
+        &token,
+    )
+    .await
+    .assert_status(axum::http::StatusCode::PRECONDITION_FAILED);
+
+    let group = GroupRepository::new(&app.state.db)
+        .get_by_id(default_organization_uuid(), group.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(group.description.as_deref(), Some("Mail relays"));
+}
+
+#[tokio::test]
+async fn test_deleted_groups_can_be_restored_from_recycle_bin() {
+    use openvox_webui::db::repository::GroupRepository;
+    use openvox_webui::models::{default_organization_uuid, CreateGroupRequest};
+
+    let app = TestApp::new().await;
+    let token = generate_test_token(
+        &app.state.config,
+        Uuid::from_u128(1),
+        "admin",
+        vec!["super_admin".to_string()],
+    );
+    let repo = GroupRepository::new(&app.state.db);
+    let group_request = |name: &str, parent_id: Option<Uuid>| CreateGroupRequest {
+        name: name.to_string(),
+        description: None,
+        parent_id,
+        environment: None,
+        is_environment_group: None,
+        match_all_nodes: None,
+        rule_match_type: None,
+        classes: None,
+        variables: None,
+    };
+    let parent = repo
+        .create(default_organization_uuid(), &group_request("Web", None))
+        .await
+        .expect("create parent");
+    let child = repo
+        .create(
+            default_organization_uuid(),
+            &group_request("Web Frontend", Some(parent.id)),
+        )
+        .await
+        .expect("create child");
+
+    let send = |method: &str, uri: String| {
+        axum::http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(axum::body::Body::empty())
+            .unwrap()
+    };
+
+    app.request_with_auth(
+        send("DELETE", format!("/api/v1/groups/{}", parent.id)),
+        &token,
+    )
+    .await
+    .assert_ok();
+    app.request_with_auth(send("GET", format!("/api/v1/groups/{}", child.id)), &token)
+        .await
+        .assert_status(axum::http::StatusCode::NOT_FOUND);
+
+    let response = app
+        .request_with_auth(send("GET", "/api/v1/recycle-bin".to_string()), &token)
+        .await;
+    response.assert_ok();
+    let bin: serde_json::Value = response.json();
+    assert_eq!(bin["retention_days"], 30);
+    let items = bin["items"].as_array().unwrap();
+    assert_eq!(items.len(), 2);
+    assert!(items.iter().all(|item| item["type"] == "group"));
+
+    // The subgroup went with its parent, so it cannot come back alone
+    app.request_with_auth(
+        send(
+            "POST",
+            format!("/api/v1/recycle-bin/group/{}/restore", child.id),
+        ),
+        &token,
+    )
+    .await
+    .assert_status(axum::http::StatusCode::CONFLICT);
+    app.request_with_auth(
+        send(
+            "POST",
+            format!("/api/v1/recycle-bin/widget/{}/restore", child.id),
+        ),
+        &token,
+    )
+    .await
+    .assert_status(axum::http::StatusCode::BAD_REQUEST);
+
+    let response = app
+        .request_with_auth(
+            send(
+                "POST",
+                format!("/api/v1/recycle-bin/group/{}/restore", parent.id),
+            ),
+            &token,
+        )
+        .await;
+    response.assert_ok();
+    let restored: serde_json::Value = response.json();
+    assert_eq!(restored["restored"], 2);
+    app.request_with_auth(send("GET", format!("/api/v1/groups/{}", child.id)), &token)
+        .await
+        .assert_ok();
+
+    app.request_with_auth(
+        send("DELETE", format!("/api/v1/groups/{}", parent.id)),
+        &token,
+    )
+    .await
+    .assert_ok();
+    app.request_with_auth(
+        send("DELETE", format!("/api/v1/recycle-bin/group/{}", parent.id)),
+        &token,
+    )
+    .await
+    .assert_status(axum::http::StatusCode::NO_CONTENT);
+    let bin: serde_json::Value = app
+        .request_with_auth(send("GET", "/api/v1/recycle-bin".to_string()), &token)
+        .await
+        .json();
+    assert!(bin["items"].as_array().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_deleted_group_name_is_held_until_purged() {
+    let app = TestApp::new().await;
+    let token = generate_test_token(
+        &app.state.config,
+        Uuid::from_u128(1),
+        "admin",
+        vec!["super_admin".to_string()],
+    );
+    let create = || {
+        axum::http::Request::builder()
+            .method("POST")
+            .uri("/api/v1/groups")
+            .header("Content-Type", "application/json")
+            .body(axum::body::Body::from(
+                serde_json::json!({ "name": "Databases" }).to_string(),
+            ))
+            .unwrap()
+    };
+    let send = |method: &str, uri: String| {
+        axum::http::Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(axum::body::Body::empty())
+            .unwrap()
+    };
+
+    let response = app.request_with_auth(create(), &token).await;
+    response.assert_status(axum::http::StatusCode::CREATED);
+    let group: serde_json::Value = response.json();
+    let id = group["id"].as_str().unwrap().to_string();
+
+    let response = app.request_with_auth(create(), &token).await;
+    response.assert_status(axum::http::StatusCode::CONFLICT);
+    let json: serde_json::Value = response.json();
+    assert!(!json["message"].as_str().unwrap().contains("recycle bin"));
+
+    app.request_with_auth(send("DELETE", format!("/api/v1/groups/{}", id)), &token)
+        .await
+        .assert_ok();
+
+    // The deleted group keeps its name while it can still be restored
+    let response = app.request_with_auth(create(), &token).await;
+    response.assert_status(axum::http::StatusCode::CONFLICT);
+    let json: serde_json::Value = response.json();
+    assert!(json["message"].as_str().unwrap().contains("recycle bin"));
+
+    app.request_with_auth(
+        send("DELETE", format!("/api/v1/recycle-bin/group/{}", id)),
+        &token,
+    )
+    .await
+    .assert_status(axum::http::StatusCode::NO_CONTENT);
+
+    let response = app.request_with_auth(create(), &token).await;
+    response.assert_status(axum::http::StatusCode::CREATED);
+    let recreated: serde_json::Value = response.json();
+    assert_eq!(recreated["name"], "Databases");
+    assert_ne!(recreated["id"], group["id"]);
+}
+
+#[tokio::test]
+async fn test_audited_body_longer_than_announced_is_rejected() {
+    let app = TestApp::new().await;
+    let token = generate_test_token(
+        &app.state.config,
+        Uuid::from_u128(1),
+        "admin",
+        vec!["super_admin".to_string()],
+    );
+    let body = serde_json::json!({ "name": "Web", "description": "x".repeat(70 * 1024) });
+    let request = axum::http::Request::builder()
+        .method("POST")
+        .uri("/api/v1/groups")
+        .header("Content-Type", "application/json")
+        .header("Content-Length", "64")
+        .body(axum::body::Body::from(body.to_string()))
+        .unwrap();
+
+    let response = app.request_with_auth(request, &token).await;
+    response.assert_status(axum::http::StatusCode::PAYLOAD_TOO_LARGE);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["error"], "payload_too_large");
+    assert!(json["message"].is_string());
+}
+
+#[tokio::test]
+async fn test_classification_keys_are_limited_to_their_cn_patterns() {
+    let app = TestApp::new().await;
+    let token = generate_test_token(
+        &app.state.config,
+        Uuid::from_u128(1),
+        "admin",
+        vec!["super_admin".to_string()],
+    );
+
+    let response = app
+        .request_with_auth(
+            axum::http::Request::builder()
+                .method("POST")
+                .uri("/api/v1/classification-keys")
+                .header("Content-Type", "application/json")
+                .body(axum::body::Body::from(
+                    serde_json::json!({
+                        "name": "puppetserver",
+                        "allowed_cn_patterns": ["*.web.example.com"],
+                    })
+                    .to_string(),
+                ))
+                .unwrap(),
+            &token,
+        )
+        .await;
+    response.assert_created();
+    let created: serde_json::Value = response.json();
+    let key = created["key"].as_str().unwrap().to_string();
+    let id = created["id"].as_str().unwrap().to_string();
+    assert!(key.starts_with("ock_"));
+
+    let update_jobs = |certname: &str, key: &str| {
+        axum::http::Request::builder()
+            .uri(format!("/api/v1/nodes/{}/update-jobs", certname))
+            .header("X-Classification-Key", key)
+            .body(axum::body::Body::empty())
+            .unwrap()
+    };
+
+    app.request(update_jobs("app1.web.example.com", &key))
+        .await
+        .assert_ok();
+    app.request(update_jobs("db1.example.com", &key))
+        .await
+        .assert_forbidden();
+    app.request(update_jobs("app1.web.example.com", "ock_not-a-key"))
+        .await
+        .assert_unauthorized();
+
+    app.request_with_auth(
+        axum::http::Request::builder()
+            .method("POST")
+            .uri(format!("/api/v1/classification-keys/{}/revoke", id))
+            .body(axum::body::Body::empty())
+            .unwrap(),
+        &token,
+    )
+    .await
+    .assert_status(axum::http::StatusCode::NO_CONTENT);
+    app.request(update_jobs("app1.web.example.com", &key))
+        .await
+        .assert_unauthorized();
+}
+
+#[tokio::test]
+async fn test_enc_script_key_handshake_is_recorded() {
+    let app = TestApp::new().await;
+    let token = generate_test_token(
+        &app.state.config,
+        Uuid::from_u128(1),
+        "admin",
+        vec!["super_admin".to_string()],
+    );
+    let get = |uri: &str| {
+        axum::http::Request::builder()
+            .uri(uri)
+            .header("Host", "webui.example.com:8443")
+            .body(axum::body::Body::empty())
+            .unwrap()
+    };
+
+    let response = app
+        .request_with_auth(get("/api/v1/enc/bootstrap?name=puppetserver"), &token)
+        .await;
+    response.assert_ok();
+    let key_id = response.headers["x-classification-key-id"]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let script = response.text();
+    assert!(script.contains("WEBUI_URL=\"http://webui.example.com:8443\""));
+    let key = script
+        .lines()
+        .find_map(|line| line.strip_prefix("CLASSIFICATION_KEY=\""))
+        .and_then(|rest| rest.strip_suffix('"'))
+        .expect("script contains the key")
+        .to_string();
+
+    app.request(get("/api/v1/enc/ping?host=puppet.example.com"))
+        .await
+        .assert_unauthorized();
+    let mut ping = get("/api/v1/enc/ping?host=puppet.example.com");
+    ping.headers_mut()
+        .insert("X-Classification-Key", key.parse().unwrap());
+    let response = app.request(ping).await;
+    response.assert_ok();
+    let pong: serde_json::Value = response.json();
+    assert_eq!(pong["key_name"], "puppetserver");
+
+    let response = app
+        .request_with_auth(get("/api/v1/classification-keys"), &token)
+        .await;
+    response.assert_ok();
+    let keys: serde_json::Value = response.json();
+    let key = keys
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|k| k["id"] == key_id.as_str())
+        .expect("minted key is listed");
+    assert_eq!(key["last_handshake_host"], "puppet.example.com");
+    assert!(key["last_handshake_at"].is_string());
+}
+
 #[tokio::test]
 async fn test_not_found_returns_404() {
     let app = TestApp::new().await;
@@ -248,3 +1339,50 @@ async fn test_inventory_update_job_api_lifecycle() {
     let fetched: serde_json::Value = get_response.json();
     assert_eq!(fetched["results"].as_array().map(|v| v.len()), Some(1));
 }
+
+#[tokio::test]
+async fn test_group_ancestor_ids_walk_up_the_hierarchy() {
+    use openvox_webui::db::repository::GroupRepository;
+    use openvox_webui::models::{default_organization_uuid, CreateGroupRequest};
+
+    let app = TestApp::new().await;
+    let repo = GroupRepository::new(&app.state.db);
+    let org_id = default_organization_uuid();
+
+    let group = |name: &str, parent_id: Option<Uuid>| CreateGroupRequest {
+        name: name.to_string(),
+        description: None,
+        parent_id,
+        environment: None,
+        is_environment_group: None,
+        match_all_nodes: None,
+        rule_match_type: None,
+        classes: None,
+        variables: None,
+    };
+
+    let team = repo
+        .create(org_id, &group("Team", None))
+        .await
+        .expect("create team group");
+    let web = repo
+        .create(org_id, &group("Team Web", Some(team.id)))
+        .await
+        .expect("create web group");
+    let frontend = repo
+        .create(org_id, &group("Team Web Frontend", Some(web.id)))
+        .await
+        .expect("create frontend group");
+
+    let ancestors = repo
+        .get_ancestor_ids(frontend.id)
+        .await
+        .expect("load ancestors");
+    assert_eq!(ancestors, vec![frontend.id, web.id, team.id]);
+
+    let ancestors = repo
+        .get_ancestor_ids(team.id)
+        .await
+        .expect("load ancestors");
+    assert_eq!(ancestors, vec![team.id]);
+}
@@ -0,0 +1,654 @@
+//! Authentication and session integration tests
+//!
+//! Impersonation, token refresh, session revocation and password resets,
+//! exercised through the API with real users and login sessions.
+
+use std::time::Duration;
+
+use axum::{body::Body, http::Request};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use openvox_webui::{
+    db::OrganizationRepository,
+    models::{
+        default_organization_uuid, Action, AuditLogEntry, AuditLogQuery, CreateOrganizationRequest,
+        CreatePermissionRequest, CreateRoleRequest, Resource, Scope, SystemRole, User,
+    },
+    services::AuthService,
+};
+
+use crate::common::{generate_test_token_with_session, TestApp, TestResponse};
+
+const PASSWORD: &str = "Sup3r-Secret!";
+
+/// A user of the default organization with one system role
+async fn create_user(app: &TestApp, username: &str, role: SystemRole) -> User {
+    let auth = AuthService::new(app.state.db.clone());
+    let user = auth
+        .create_user(
+            username,
+            &format!("{}@example.com", username),
+            PASSWORD,
+            role.name(),
+        )
+        .await
+        .expect("create user");
+    auth.assign_role(&user.id, &role.uuid())
+        .await
+        .expect("assign role");
+    user
+}
+
+/// An access token of a new login session of `user`
+async fn login_token(app: &TestApp, user: &User) -> String {
+    let roles = AuthService::new(app.state.db.clone())
+        .get_user_roles(&user.id)
+        .await
+        .expect("user roles");
+    generate_test_token_with_session(app, user.id, &user.username, roles).await
+}
+
+async fn send(
+    app: &TestApp,
+    method: &str,
+    uri: &str,
+    token: &str,
+    body: Option<Value>,
+) -> TestResponse {
+    let builder = Request::builder().method(method).uri(uri);
+    let request = match body {
+        Some(body) => builder
+            .header("Content-Type", "application/json")
+            .body(Body::from(body.to_string())),
+        None => builder.body(Body::empty()),
+    }
+    .unwrap();
+    app.request_with_auth(request, token).await
+}
+
+/// Audit entries recorded while `impersonator` acted as another user
+///
+/// Middleware entries are written in the background, so this waits until
+/// `action` shows up.
+async fn impersonated_entries(
+    app: &TestApp,
+    impersonator: Uuid,
+    action: &str,
+) -> Vec<AuditLogEntry> {
+    let query = AuditLogQuery {
+        impersonator_id: Some(impersonator),
+        ..Default::default()
+    };
+    for _ in 0..50 {
+        let entries = app
+            .state
+            .audit_repository()
+            .list(default_organization_uuid(), &query)
+            .await
+            .expect("list audit entries");
+        if entries.iter().any(|e| e.action == action) {
+            return entries;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    panic!(
+        "no audit entry for {} by impersonator {}",
+        action, impersonator
+    );
+}
+
+async fn start_impersonation(app: &TestApp, token: &str, user_id: Uuid) -> TestResponse {
+    send(
+        app,
+        "POST",
+        "/api/v1/impersonation",
+        token,
+        Some(json!({ "user_id": user_id, "reason": "Reproduce support ticket" })),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_impersonation_starts_and_ends() {
+    let app = TestApp::new().await;
+    let admin = create_user(&app, "support", SystemRole::Admin).await;
+    let operator = create_user(&app, "operator1", SystemRole::Operator).await;
+    let admin_token = login_token(&app, &admin).await;
+
+    let response = start_impersonation(&app, &admin_token, operator.id).await;
+    response.assert_created();
+    let started: Value = response.json();
+    assert_eq!(started["session"]["user_id"], operator.id.to_string());
+    assert_eq!(started["session"]["impersonator_id"], admin.id.to_string());
+    let token = started["access_token"].as_str().unwrap().to_string();
+
+    let response = send(&app, "GET", "/api/v1/impersonation/current", &token, None).await;
+    response.assert_ok();
+    let current: Value = response.json();
+    assert_eq!(current["username"], "operator1");
+    assert!(current["ended_at"].is_null());
+
+    // Changes made under the impersonation are attributed to both users
+    send(
+        &app,
+        "POST",
+        "/api/v1/groups",
+        &token,
+        Some(json!({ "name": "Made While Impersonating" })),
+    )
+    .await
+    .assert_created();
+    let entries = impersonated_entries(&app, admin.id, "groups.create").await;
+    let created = entries
+        .iter()
+        .find(|e| e.action == "groups.create")
+        .unwrap();
+    assert_eq!(created.user_id, Some(operator.id));
+    assert_eq!(created.impersonator_id, Some(admin.id));
+
+    let response = send(&app, "POST", "/api/v1/impersonation/end", &token, None).await;
+    response.assert_ok();
+    let ended: Value = response.json();
+    assert!(ended["ended_at"].is_string());
+    let entries = impersonated_entries(&app, admin.id, "impersonation.end").await;
+    let end = entries
+        .iter()
+        .find(|e| e.action == "impersonation.end")
+        .unwrap();
+    assert_eq!(end.user_id, Some(operator.id));
+    assert_eq!(end.impersonator_id, Some(admin.id));
+
+    // The token stops working; the admin's own token is unaffected
+    send(&app, "GET", "/api/v1/impersonation/current", &token, None)
+        .await
+        .assert_unauthorized();
+    send(&app, "GET", "/api/v1/groups", &admin_token, None)
+        .await
+        .assert_ok();
+}
+
+#[tokio::test]
+async fn test_impersonation_token_cannot_change_credentials() {
+    let app = TestApp::new().await;
+    let admin = create_user(&app, "support", SystemRole::Admin).await;
+    let operator = create_user(&app, "operator1", SystemRole::Operator).await;
+    let admin_token = login_token(&app, &admin).await;
+    let started: Value = start_impersonation(&app, &admin_token, operator.id)
+        .await
+        .json();
+    let token = started["access_token"].as_str().unwrap().to_string();
+
+    let blocked = [
+        ("POST", "/api/v1/impersonation", Some(json!({}))),
+        (
+            "POST",
+            "/api/v1/api-keys",
+            Some(json!({ "name": "escape hatch" })),
+        ),
+        (
+            "POST",
+            "/api/v1/auth/change-password",
+            Some(json!({ "current_password": PASSWORD, "new_password": "An0ther-Secret!" })),
+        ),
+        (
+            "DELETE",
+            "/api/v1/sessions/00000000-0000-0000-0000-000000000000",
+            None,
+        ),
+        (
+            "POST",
+            "/api/v1/elevations",
+            Some(json!({ "role_id": SystemRole::Admin.uuid(), "reason": "x" })),
+        ),
+    ];
+    for (method, uri, body) in blocked {
+        let response = send(&app, method, uri, &token, body).await;
+        response.assert_forbidden();
+        let error: Value = response.json();
+        assert_eq!(
+            error["message"], "Not allowed while impersonating a user",
+            "{} {}",
+            method, uri
+        );
+    }
+
+    // Reading is allowed
+    send(&app, "GET", "/api/v1/api-keys", &token, None)
+        .await
+        .assert_ok();
+}
+
+#[tokio::test]
+async fn test_impersonation_cannot_escalate_privileges() {
+    let app = TestApp::new().await;
+    let helpdesk = create_user(&app, "helpdesk", SystemRole::Operator).await;
+    let role = app
+        .state
+        .rbac_db
+        .create_role(CreateRoleRequest {
+            name: "impersonator".to_string(),
+            display_name: "Impersonator".to_string(),
+            description: None,
+            parent_id: None,
+            parent_ids: None,
+            permissions: Some(vec![CreatePermissionRequest {
+                resource: Resource::Users,
+                action: Action::Impersonate,
+                scope: Some(Scope::All),
+                constraint: None,
+            }]),
+        })
+        .await
+        .expect("create role");
+    AuthService::new(app.state.db.clone())
+        .assign_role(&helpdesk.id, &role.id)
+        .await
+        .expect("assign role");
+    let admin = create_user(&app, "admin2", SystemRole::Admin).await;
+    let token = login_token(&app, &helpdesk).await;
+
+    let response = start_impersonation(&app, &token, admin.id).await;
+    response.assert_forbidden();
+    let error: Value = response.json();
+    assert_eq!(
+        error["message"],
+        "You can only impersonate users whose permissions you have"
+    );
+}
+
+#[tokio::test]
+async fn test_impersonation_is_limited_to_the_callers_organization() {
+    let app = TestApp::new().await;
+    let root = create_user(&app, "root", SystemRole::SuperAdmin).await;
+    let org = OrganizationRepository::new(&app.state.db)
+        .create(&CreateOrganizationRequest {
+            name: "Other".to_string(),
+            slug: "other".to_string(),
+        })
+        .await
+        .expect("create organization");
+    let outsider = AuthService::new(app.state.db.clone())
+        .create_user_in_org(
+            "outsider",
+            "outsider@example.com",
+            PASSWORD,
+            "viewer",
+            org.id,
+        )
+        .await
+        .expect("create user");
+    let token = login_token(&app, &root).await;
+
+    start_impersonation(&app, &token, outsider.id)
+        .await
+        .assert_not_found();
+}
+
+/// Tokens of a password login of `username`
+async fn login(app: &TestApp, username: &str) -> Value {
+    let response = app
+        .post_json(
+            "/api/v1/auth/login",
+            json!({ "username": username, "password": PASSWORD }),
+        )
+        .await;
+    response.assert_ok();
+    response.json()
+}
+
+async fn refresh(app: &TestApp, refresh_token: &Value) -> TestResponse {
+    app.post_json(
+        "/api/v1/auth/refresh",
+        json!({ "refresh_token": refresh_token }),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn test_refresh_rotates_the_refresh_token() {
+    let app = TestApp::new().await;
+    create_user(&app, "rotator", SystemRole::Viewer).await;
+    let tokens = login(&app, "rotator").await;
+
+    let response = refresh(&app, &tokens["refresh_token"]).await;
+    response.assert_ok();
+    let rotated: Value = response.json();
+    assert!(rotated["refresh_token"].is_string());
+    assert_ne!(rotated["refresh_token"], tokens["refresh_token"]);
+    let access_token = rotated["access_token"].as_str().unwrap();
+    send(&app, "GET", "/api/v1/auth/me", access_token, None)
+        .await
+        .assert_ok();
+
+    // The new refresh token continues the session
+    refresh(&app, &rotated["refresh_token"]).await.assert_ok();
+}
+
+#[tokio::test]
+async fn test_refresh_token_reuse_within_grace_is_only_rejected() {
+    let app = TestApp::new().await;
+    create_user(&app, "twotabs", SystemRole::Viewer).await;
+    let tokens = login(&app, "twotabs").await;
+    let rotated: Value = refresh(&app, &tokens["refresh_token"]).await.json();
+
+    // A second tab refreshing with the same token moments later
+    let response = refresh(&app, &tokens["refresh_token"]).await;
+    response.assert_unauthorized();
+    let error: Value = response.json();
+    assert_eq!(error["message"], "Refresh token has already been used");
+
+    // The session survives
+    let access_token = tokens["access_token"].as_str().unwrap();
+    send(&app, "GET", "/api/v1/auth/me", access_token, None)
+        .await
+        .assert_ok();
+    refresh(&app, &rotated["refresh_token"]).await.assert_ok();
+}
+
+#[tokio::test]
+async fn test_refresh_token_reuse_after_grace_revokes_the_session() {
+    let app = TestApp::new().await;
+    let user = create_user(&app, "stolen", SystemRole::Viewer).await;
+    let tokens = login(&app, "stolen").await;
+    let rotated: Value = refresh(&app, &tokens["refresh_token"]).await.json();
+
+    // The spent token shows up again a minute later
+    sqlx::query("UPDATE refresh_tokens SET used_at = ? WHERE used_at IS NOT NULL")
+        .bind((chrono::Utc::now() - chrono::Duration::minutes(1)).to_rfc3339())
+        .execute(&app.state.db)
+        .await
+        .unwrap();
+    let response = refresh(&app, &tokens["refresh_token"]).await;
+    response.assert_unauthorized();
+    let error: Value = response.json();
+    assert_eq!(
+        error["message"],
+        "Refresh token reuse detected; the session has been revoked"
+    );
+
+    // Every token of the session stops working
+    refresh(&app, &rotated["refresh_token"])
+        .await
+        .assert_unauthorized();
+    let access_token = rotated["access_token"].as_str().unwrap();
+    send(&app, "GET", "/api/v1/auth/me", access_token, None)
+        .await
+        .assert_unauthorized();
+
+    let entries = app
+        .state
+        .audit_repository()
+        .list(
+            default_organization_uuid(),
+            &AuditLogQuery {
+                action: Some("auth.refresh_token_reuse".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].user_id, Some(user.id));
+    assert_eq!(
+        entries[0].details.as_ref().unwrap()["session_revoked"],
+        true
+    );
+}
+
+/// IDs of the active sessions of the token's user
+async fn session_ids(app: &TestApp, token: &str) -> Vec<(String, bool)> {
+    let response = send(app, "GET", "/api/v1/sessions", token, None).await;
+    response.assert_ok();
+    let sessions: Vec<Value> = response.json();
+    sessions
+        .iter()
+        .map(|s| {
+            (
+                s["id"].as_str().unwrap().to_string(),
+                s["current"].as_bool().unwrap(),
+            )
+        })
+        .collect()
+}
+
+#[tokio::test]
+async fn test_revoked_session_tokens_are_rejected() {
+    let app = TestApp::new().await;
+    create_user(&app, "laptop", SystemRole::Viewer).await;
+    let kept = login(&app, "laptop").await;
+    let lost = login(&app, "laptop").await;
+    let kept_token = kept["access_token"].as_str().unwrap();
+    let lost_token = lost["access_token"].as_str().unwrap();
+
+    let sessions = session_ids(&app, kept_token).await;
+    assert_eq!(sessions.len(), 2);
+    let (lost_id, _) = sessions.iter().find(|(_, current)| !current).unwrap();
+    send(
+        &app,
+        "DELETE",
+        &format!("/api/v1/sessions/{}", lost_id),
+        kept_token,
+        None,
+    )
+    .await
+    .assert_status(axum::http::StatusCode::NO_CONTENT);
+
+    // Both tokens of the revoked session stop working
+    send(&app, "GET", "/api/v1/auth/me", lost_token, None)
+        .await
+        .assert_unauthorized();
+    refresh(&app, &lost["refresh_token"])
+        .await
+        .assert_unauthorized();
+
+    // The other session is unaffected
+    send(&app, "GET", "/api/v1/auth/me", kept_token, None)
+        .await
+        .assert_ok();
+    refresh(&app, &kept["refresh_token"]).await.assert_ok();
+}
+
+#[tokio::test]
+async fn test_sessions_of_other_users_cannot_be_revoked() {
+    let app = TestApp::new().await;
+    let victim = create_user(&app, "victim", SystemRole::Viewer).await;
+    create_user(&app, "mallory", SystemRole::Operator).await;
+    let victim_tokens = login(&app, "victim").await;
+    let victim_token = victim_tokens["access_token"].as_str().unwrap();
+    let mallory_tokens = login(&app, "mallory").await;
+    let mallory_token = mallory_tokens["access_token"].as_str().unwrap();
+    let (victim_session, _) = session_ids(&app, victim_token).await.remove(0);
+
+    send(
+        &app,
+        "DELETE",
+        &format!("/api/v1/sessions/{}", victim_session),
+        mallory_token,
+        None,
+    )
+    .await
+    .assert_not_found();
+    send(
+        &app,
+        "DELETE",
+        &format!("/api/v1/sessions?user_id={}", victim.id),
+        mallory_token,
+        None,
+    )
+    .await
+    .assert_forbidden();
+    send(
+        &app,
+        "GET",
+        &format!("/api/v1/sessions?user_id={}", victim.id),
+        mallory_token,
+        None,
+    )
+    .await
+    .assert_forbidden();
+
+    // The victim stays signed in
+    send(&app, "GET", "/api/v1/auth/me", victim_token, None)
+        .await
+        .assert_ok();
+    refresh(&app, &victim_tokens["refresh_token"])
+        .await
+        .assert_ok();
+}
+
+async fn forgot_password(app: &TestApp, email: &str) -> Value {
+    let response = app
+        .post_json("/api/v1/auth/forgot-password", json!({ "email": email }))
+        .await;
+    response.assert_ok();
+    response.json()
+}
+
+async fn reset_password(app: &TestApp, token: &Value, new_password: &str) -> TestResponse {
+    app.post_json(
+        "/api/v1/auth/reset-password",
+        json!({ "token": token, "new_password": new_password }),
+    )
+    .await
+}
+
+fn assert_invalid_reset_token(response: &TestResponse) {
+    response.assert_bad_request();
+    let error: Value = response.json();
+    assert_eq!(error["code"], "invalid_token");
+    assert_eq!(error["message"], "Invalid or expired reset token");
+}
+
+async fn login_status(app: &TestApp, username: &str, password: &str) -> axum::http::StatusCode {
+    app.post_json(
+        "/api/v1/auth/login",
+        json!({ "username": username, "password": password }),
+    )
+    .await
+    .status
+}
+
+#[tokio::test]
+async fn test_repeated_password_reset_requests_are_throttled() {
+    let app = TestApp::new().await;
+    let user = create_user(&app, "forgetful", SystemRole::Viewer).await;
+
+    let first = forgot_password(&app, "forgetful@example.com").await;
+    assert!(first["reset_token"].is_string());
+
+    // The same answer, but no new token or email within the cooldown
+    let second = forgot_password(&app, "forgetful@example.com").await;
+    assert_eq!(second["message"], first["message"]);
+    assert!(second.get("reset_token").is_none());
+    let entries = app
+        .state
+        .audit_repository()
+        .list(
+            default_organization_uuid(),
+            &AuditLogQuery {
+                action: Some("user.password_reset_requested".to_string()),
+                ..Default::default()
+            },
+        )
+        .await
+        .unwrap();
+    let outcomes: Vec<&Value> = entries
+        .iter()
+        .filter(|e| e.user_id == Some(user.id))
+        .map(|e| &e.details.as_ref().unwrap()["outcome"])
+        .collect();
+    assert_eq!(outcomes.len(), 2);
+    assert!(outcomes.contains(&&json!("sent")));
+    assert!(outcomes.contains(&&json!("throttled")));
+
+    // Once the cooldown has passed a new token replaces the first one
+    sqlx::query("UPDATE password_reset_tokens SET created_at = ?")
+        .bind((chrono::Utc::now() - chrono::Duration::minutes(6)).to_rfc3339())
+        .execute(&app.state.db)
+        .await
+        .unwrap();
+    let third = forgot_password(&app, "forgetful@example.com").await;
+    assert!(third["reset_token"].is_string());
+    assert_ne!(third["reset_token"], first["reset_token"]);
+    assert_invalid_reset_token(
+        &reset_password(&app, &first["reset_token"], "An0ther-Secret!").await,
+    );
+    reset_password(&app, &third["reset_token"], "An0ther-Secret!")
+        .await
+        .assert_ok();
+}
+
+#[tokio::test]
+async fn test_expired_password_reset_token_is_rejected() {
+    let app = TestApp::new().await;
+    create_user(&app, "late", SystemRole::Viewer).await;
+    let requested = forgot_password(&app, "late@example.com").await;
+
+    sqlx::query("UPDATE password_reset_tokens SET expires_at = ?")
+        .bind((chrono::Utc::now() - chrono::Duration::minutes(1)).to_rfc3339())
+        .execute(&app.state.db)
+        .await
+        .unwrap();
+    let response = reset_password(&app, &requested["reset_token"], "An0ther-Secret!").await;
+    assert_invalid_reset_token(&response);
+
+    // The password is unchanged
+    assert_eq!(
+        login_status(&app, "late", PASSWORD).await,
+        axum::http::StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn test_password_reset_token_cannot_be_used_twice() {
+    let app = TestApp::new().await;
+    create_user(&app, "once", SystemRole::Viewer).await;
+    let requested = forgot_password(&app, "once@example.com").await;
+
+    reset_password(&app, &requested["reset_token"], "An0ther-Secret!")
+        .await
+        .assert_ok();
+    let response = reset_password(&app, &requested["reset_token"], "Y3t-An0ther-Secret!").await;
+    assert_invalid_reset_token(&response);
+
+    // The first reset stands
+    assert_eq!(
+        login_status(&app, "once", "An0ther-Secret!").await,
+        axum::http::StatusCode::OK
+    );
+    assert_eq!(
+        login_status(&app, "once", "Y3t-An0ther-Secret!").await,
+        axum::http::StatusCode::UNAUTHORIZED
+    );
+}
+
+#[tokio::test]
+async fn test_locked_account_answers_like_an_unknown_username() {
+    let app = TestApp::new().await;
+    create_user(&app, "guessed", SystemRole::Viewer).await;
+    let attempt = |username: &str, password: &str| {
+        app.post_json(
+            "/api/v1/auth/login",
+            json!({ "username": username, "password": password }),
+        )
+    };
+
+    for _ in 0..app.state.config.rbac.max_failed_logins {
+        attempt("guessed", "Wr0ng-Secret!")
+            .await
+            .assert_unauthorized();
+    }
+
+    // The right password of the locked account, a wrong one and an unknown
+    // username all get the same answer
+    let unknown: Value = attempt("nobody", "Wr0ng-Secret!").await.json();
+    for password in [PASSWORD, "Wr0ng-Secret!"] {
+        let response = attempt("guessed", password).await;
+        response.assert_unauthorized();
+        let error: Value = response.json();
+        for field in ["error", "code", "message", "details"] {
+            assert_eq!(error.get(field), unknown.get(field), "{}", field);
+        }
+    }
+}
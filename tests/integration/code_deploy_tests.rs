@@ -5,6 +5,7 @@
 
 use crate::common::{generate_test_token, TestApp};
 use axum::http::{Request, StatusCode};
+use openvox_webui::db::CodeEnvironmentRepository;
 use serde_json::json;
 use uuid::Uuid;
 
@@ -271,6 +272,7 @@ async fn test_create_repository_with_defaults() {
     assert_eq!(json["branch_pattern"], "*"); // default
     assert_eq!(json["poll_interval_seconds"], 300); // default
     assert_eq!(json["is_control_repo"], false); // default
+    assert_eq!(json["deploy_strategy"], "r10k"); // default
 }
 
 #[tokio::test]
@@ -311,7 +313,8 @@ async fn test_update_repository() {
             json!({
                 "name": "updated-repo",
                 "poll_interval_seconds": 600,
-                "is_control_repo": true
+                "is_control_repo": true,
+                "deploy_strategy": "librarian_puppet"
             })
             .to_string(),
         ))
@@ -323,6 +326,7 @@ async fn test_update_repository() {
     assert_eq!(json["name"], "updated-repo");
     assert_eq!(json["poll_interval_seconds"], 600);
     assert_eq!(json["is_control_repo"], true);
+    assert_eq!(json["deploy_strategy"], "librarian_puppet");
 }
 
 #[tokio::test]
@@ -454,6 +458,265 @@ async fn test_trigger_deployment_invalid_environment() {
     response.assert_not_found();
 }
 
+#[tokio::test]
+async fn test_trigger_deployment_with_options() {
+    let app = TestApp::with_code_deploy().await;
+    let token = generate_test_token(
+        &app.state.config,
+        Uuid::new_v4(),
+        "admin",
+        vec!["admin".to_string()],
+    );
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/code/repositories")
+        .header("Content-Type", "application/json")
+        .header("Authorization", format!("Bearer {}", token))
+        .body(axum::body::Body::from(
+            json!({
+                "name": "control",
+                "url": "https://git.example.com/puppet/control.git",
+                "auth_type": "none",
+                "is_control_repo": true
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    let response = app.request(request).await;
+    response.assert_status(StatusCode::CREATED);
+    let repo: serde_json::Value = response.json();
+    let repo_id = Uuid::parse_str(repo["id"].as_str().unwrap()).unwrap();
+
+    let environment = CodeEnvironmentRepository::new(&app.state.db)
+        .upsert(
+            repo_id,
+            "production",
+            "production",
+            Some("abc1234"),
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+    let trigger = |options: serde_json::Value| {
+        Request::builder()
+            .method("POST")
+            .uri("/api/v1/code/deployments")
+            .header("Content-Type", "application/json")
+            .header("Authorization", format!("Bearer {}", token))
+            .body(axum::body::Body::from(
+                json!({
+                    "environment_id": environment.id.to_string(),
+                    "options": options
+                })
+                .to_string(),
+            ))
+            .unwrap()
+    };
+
+    let response = app
+        .request(trigger(json!({ "module": "ntp", "generate_types": true })))
+        .await;
+    response.assert_status(StatusCode::CREATED);
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["options"]["module"], "ntp");
+    assert_eq!(json["options"]["generate_types"], true);
+    assert!(json["command_line"].is_null());
+
+    // A single module cannot be deployed incrementally
+    let response = app
+        .request(trigger(json!({ "module": "ntp", "incremental": true })))
+        .await;
+    response.assert_status(StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn test_deployment_impact_not_found() {
+    let app = TestApp::with_code_deploy().await;
+    let token = generate_test_token(
+        &app.state.config,
+        Uuid::new_v4(),
+        "admin",
+        vec!["admin".to_string()],
+    );
+
+    let request = Request::builder()
+        .method("GET")
+        .uri(format!(
+            "/api/v1/code/deployments/{}/impact",
+            Uuid::new_v4()
+        ))
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let response = app.request_with_auth(request, &token).await;
+
+    response.assert_not_found();
+}
+
+#[tokio::test]
+async fn test_canary_validation() {
+    let app = TestApp::with_code_deploy().await;
+    let token = generate_test_token(
+        &app.state.config,
+        Uuid::new_v4(),
+        "admin",
+        vec!["admin".to_string()],
+    );
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/code/repositories")
+        .header("Content-Type", "application/json")
+        .header("Authorization", format!("Bearer {}", token))
+        .body(axum::body::Body::from(
+            json!({
+                "name": "control",
+                "url": "https://git.example.com/puppet/control.git",
+                "auth_type": "none",
+                "is_control_repo": true
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    let response = app.request(request).await;
+    response.assert_status(StatusCode::CREATED);
+    let repo: serde_json::Value = response.json();
+    let repo_id = Uuid::parse_str(repo["id"].as_str().unwrap()).unwrap();
+
+    let environment = CodeEnvironmentRepository::new(&app.state.db)
+        .upsert(
+            repo_id,
+            "production",
+            "production",
+            Some("abc1234"),
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/code/deployments")
+        .header("Content-Type", "application/json")
+        .header("Authorization", format!("Bearer {}", token))
+        .body(axum::body::Body::from(
+            json!({ "environment_id": environment.id.to_string() }).to_string(),
+        ))
+        .unwrap();
+    let response = app.request(request).await;
+    response.assert_status(StatusCode::CREATED);
+    let deployment: serde_json::Value = response.json();
+    let canary_uri = format!(
+        "/api/v1/code/deployments/{}/canary",
+        deployment["id"].as_str().unwrap()
+    );
+
+    let request = Request::builder()
+        .method("GET")
+        .uri(&canary_uri)
+        .body(axum::body::Body::empty())
+        .unwrap();
+    app.request_with_auth(request, &token)
+        .await
+        .assert_not_found();
+
+    let request = Request::builder()
+        .method("POST")
+        .uri(&canary_uri)
+        .header("Content-Type", "application/json")
+        .header("Authorization", format!("Bearer {}", token))
+        .body(axum::body::Body::from(
+            json!({ "group_id": Uuid::new_v4().to_string(), "sample_size": 0 }).to_string(),
+        ))
+        .unwrap();
+    app.request(request)
+        .await
+        .assert_status(StatusCode::BAD_REQUEST);
+
+    let request = Request::builder()
+        .method("POST")
+        .uri(format!("{}/promote", canary_uri))
+        .body(axum::body::Body::empty())
+        .unwrap();
+    app.request_with_auth(request, &token)
+        .await
+        .assert_not_found();
+}
+
+// ============================================================================
+// Credential Health Tests
+// ============================================================================
+
+#[tokio::test]
+async fn test_credential_health_reports_expired_token() {
+    let app = TestApp::with_code_deploy().await;
+    let token = generate_test_token(
+        &app.state.config,
+        Uuid::new_v4(),
+        "admin",
+        vec!["admin".to_string()],
+    );
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/code/pat-tokens")
+        .header("Content-Type", "application/json")
+        .header("Authorization", format!("Bearer {}", token))
+        .body(axum::body::Body::from(
+            json!({
+                "name": "deploy",
+                "token": "ghp_expired",
+                "expires_at": "2020-01-01T00:00:00Z"
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    let response = app.request(request).await;
+    response.assert_status(StatusCode::CREATED);
+    let pat: serde_json::Value = response.json();
+
+    let request = Request::builder()
+        .method("POST")
+        .uri("/api/v1/code/repositories")
+        .header("Content-Type", "application/json")
+        .header("Authorization", format!("Bearer {}", token))
+        .body(axum::body::Body::from(
+            json!({
+                "name": "control-repo",
+                "url": "https://github.com/example/control-repo.git",
+                "auth_type": "pat",
+                "pat_token_id": pat["id"]
+            })
+            .to_string(),
+        ))
+        .unwrap();
+    app.request(request)
+        .await
+        .assert_status(StatusCode::CREATED);
+
+    let request = Request::builder()
+        .method("GET")
+        .uri("/api/v1/code/credentials/health")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let response = app.request_with_auth(request, &token).await;
+
+    response.assert_ok();
+    let json: serde_json::Value = response.json();
+    assert_eq!(json["status"], "critical");
+    assert_eq!(json["summary"]["critical"], 2);
+    assert_eq!(json["pat_tokens"][0]["repository_count"], 1);
+    assert_eq!(
+        json["repositories"][0]["issues"][0],
+        "PAT token 'deploy' has expired"
+    );
+}
+
 // ============================================================================
 // Authentication Tests
 // ============================================================================
@@ -468,6 +731,7 @@ async fn test_code_deploy_endpoints_require_auth() {
         ("/api/v1/code/repositories", "GET"),
         ("/api/v1/code/environments", "GET"),
         ("/api/v1/code/deployments", "GET"),
+        ("/api/v1/code/credentials/health", "GET"),
     ];
 
     for (uri, method) in endpoints {
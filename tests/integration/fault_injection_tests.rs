@@ -0,0 +1,68 @@
+//! Fault injection in the dependency clients
+//!
+//! Requires the `fault-injection` feature:
+//! `cargo test --features fault-injection`.
+
+use std::time::{Duration, Instant};
+
+use openvox_webui::config::{FaultInjectionConfig, PuppetDbConfig};
+use openvox_webui::services::PuppetDbClient;
+use wiremock::matchers::{method, path};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+async fn puppetdb_server() -> MockServer {
+    let server = MockServer::start().await;
+    Mock::given(method("GET"))
+        .and(path("/pdb/query/v4/nodes"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([])))
+        .mount(&server)
+        .await;
+    server
+}
+
+fn client(server: &MockServer, faults: FaultInjectionConfig) -> PuppetDbClient {
+    PuppetDbClient::new(&PuppetDbConfig {
+        url: server.uri(),
+        timeout_secs: 5,
+        ssl_verify: false,
+        ssl_cert: None,
+        ssl_key: None,
+        ssl_ca: None,
+        ssl: None,
+        fault_injection: Some(faults),
+    })
+    .expect("PuppetDB client")
+}
+
+#[tokio::test]
+async fn test_injected_errors_never_reach_puppetdb() {
+    let server = puppetdb_server().await;
+    let client = client(
+        &server,
+        FaultInjectionConfig {
+            error_rate: 1.0,
+            ..Default::default()
+        },
+    );
+
+    let err = client.get_nodes().await.unwrap_err();
+    assert!(err.to_string().contains("Injected puppetdb fault"));
+    assert!(server.received_requests().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_injected_latency_delays_requests() {
+    let server = puppetdb_server().await;
+    let client = client(
+        &server,
+        FaultInjectionConfig {
+            latency_ms: 100,
+            ..Default::default()
+        },
+    );
+
+    let start = Instant::now();
+    let nodes = client.get_nodes().await.expect("request succeeds");
+    assert!(nodes.is_empty());
+    assert!(start.elapsed() >= Duration::from_millis(100));
+}
@@ -1,6 +1,12 @@
 //! Integration tests for OpenVox WebUI
 //!
 //! These tests verify the behavior of the API endpoints with a real
-//! (in-memory) database and all middleware.
+//! (in-memory) database and all middleware, and of repositories and services
+//! against a per-test database.
 
 mod alert_conditions_tests;
+mod auth_tests;
+#[cfg(feature = "fault-injection")]
+mod fault_injection_tests;
+mod organization_isolation_tests;
+mod repository_tests;
@@ -0,0 +1,204 @@
+//! Organization isolation integration tests
+//!
+//! Records of one organization are invisible to the users of another: they
+//! answer 404 as if they did not exist.
+
+use axum::{body::Body, http::Request};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use openvox_webui::{
+    db::OrganizationRepository,
+    models::{CreateOrganizationRequest, SystemRole},
+    services::AuthService,
+};
+
+use crate::common::{generate_test_token_with_session, TestApp, TestResponse};
+
+/// Seeded admin of the default organization
+const DEFAULT_ADMIN: &str = "00000000-0000-0000-0000-000000000001";
+
+async fn send(
+    app: &TestApp,
+    method: &str,
+    uri: &str,
+    token: &str,
+    body: Option<Value>,
+) -> TestResponse {
+    let builder = Request::builder().method(method).uri(uri);
+    let request = match body {
+        Some(body) => builder
+            .header("Content-Type", "application/json")
+            .body(Body::from(body.to_string())),
+        None => builder.body(Body::empty()),
+    }
+    .unwrap();
+    app.request_with_auth(request, token).await
+}
+
+/// A new organization and an access token of an admin of it
+async fn other_organization(app: &TestApp) -> (Uuid, String) {
+    let org = OrganizationRepository::new(&app.state.db)
+        .create(&CreateOrganizationRequest {
+            name: "Other".to_string(),
+            slug: "other".to_string(),
+        })
+        .await
+        .expect("create organization");
+    let auth = AuthService::new(app.state.db.clone());
+    let user = auth
+        .create_user_in_org(
+            "otheradmin",
+            "otheradmin@example.com",
+            "Sup3r-Secret!",
+            "admin",
+            org.id,
+        )
+        .await
+        .expect("create user");
+    auth.assign_role(&user.id, &SystemRole::Admin.uuid())
+        .await
+        .expect("assign role");
+
+    let response = app
+        .post_json(
+            "/api/v1/auth/login",
+            json!({ "username": "otheradmin", "password": "Sup3r-Secret!" }),
+        )
+        .await;
+    response.assert_ok();
+    let tokens: Value = response.json();
+    (org.id, tokens["access_token"].as_str().unwrap().to_string())
+}
+
+async fn default_admin_token(app: &TestApp) -> String {
+    generate_test_token_with_session(
+        app,
+        Uuid::parse_str(DEFAULT_ADMIN).unwrap(),
+        "admin",
+        vec!["admin".to_string()],
+    )
+    .await
+}
+
+/// Create a record and return its ID
+async fn create(app: &TestApp, token: &str, uri: &str, body: Value) -> String {
+    let response = send(app, "POST", uri, token, Some(body)).await;
+    response.assert_ok();
+    let created: Value = response.json();
+    created["id"].as_str().unwrap().to_string()
+}
+
+#[tokio::test]
+async fn test_reports_and_baselines_of_other_organizations_are_not_found() {
+    let app = TestApp::new().await;
+    let token = default_admin_token(&app).await;
+    let report_id = create(
+        &app,
+        &token,
+        "/api/v1/analytics/saved-reports",
+        json!({ "name": "Fleet health", "report_type": "node_health", "query_config": {} }),
+    )
+    .await;
+    let schedule_id = create(
+        &app,
+        &token,
+        "/api/v1/analytics/schedules",
+        json!({ "report_id": report_id, "schedule_cron": "0 8 * * *" }),
+    )
+    .await;
+    let compliance_id = create(
+        &app,
+        &token,
+        "/api/v1/analytics/compliance-baselines",
+        json!({ "name": "CIS", "rules": [] }),
+    )
+    .await;
+    let drift_id = create(
+        &app,
+        &token,
+        "/api/v1/analytics/drift-baselines",
+        json!({ "name": "Golden", "baseline_facts": { "os": { "family": "RedHat" } } }),
+    )
+    .await;
+    let (_, other_token) = other_organization(&app).await;
+
+    let report = format!("/api/v1/analytics/saved-reports/{}", report_id);
+    let schedule = format!("/api/v1/analytics/schedules/{}", schedule_id);
+    let compliance = format!("/api/v1/analytics/compliance-baselines/{}", compliance_id);
+    let drift = format!("/api/v1/analytics/drift-baselines/{}", drift_id);
+    let rename = || Some(json!({ "name": "Taken over" }));
+    let attempts = [
+        ("GET", report.clone(), None),
+        ("PUT", report.clone(), rename()),
+        ("POST", format!("{}/execute", report), Some(json!({}))),
+        ("DELETE", report.clone(), None),
+        ("GET", schedule.clone(), None),
+        (
+            "PUT",
+            schedule.clone(),
+            Some(json!({ "is_enabled": false })),
+        ),
+        ("DELETE", schedule.clone(), None),
+        ("GET", compliance.clone(), None),
+        ("PUT", compliance.clone(), rename()),
+        ("DELETE", compliance.clone(), None),
+        ("GET", drift.clone(), None),
+        ("PUT", drift.clone(), rename()),
+        ("DELETE", drift.clone(), None),
+    ];
+    for (method, uri, body) in attempts {
+        let response = send(&app, method, &uri, &other_token, body).await;
+        assert_eq!(
+            response.status,
+            axum::http::StatusCode::NOT_FOUND,
+            "{} {}: {}",
+            method,
+            uri,
+            response.text()
+        );
+    }
+
+    // Nothing was changed or deleted
+    for uri in [&report, &schedule, &compliance, &drift] {
+        send(&app, "GET", uri, &token, None).await.assert_ok();
+    }
+    let report: Value = send(&app, "GET", &report, &token, None).await.json();
+    assert_eq!(report["name"], "Fleet health");
+}
+
+#[tokio::test]
+async fn test_organization_override_requires_super_admin() {
+    let app = TestApp::new().await;
+    let token = default_admin_token(&app).await;
+    let (other_org, _) = other_organization(&app).await;
+
+    for path in [
+        "saved-reports",
+        "schedules",
+        "compliance-baselines",
+        "drift-baselines",
+    ] {
+        let uri = format!("/api/v1/analytics/{}?organization_id={}", path, other_org);
+        let response = send(&app, "GET", &uri, &token, None).await;
+        response.assert_forbidden();
+        let error: Value = response.json();
+        assert_eq!(
+            error["message"],
+            "organization_id can only be specified by super_admin"
+        );
+    }
+
+    let response = send(
+        &app,
+        "POST",
+        &format!(
+            "/api/v1/analytics/saved-reports?organization_id={}",
+            other_org
+        ),
+        &token,
+        Some(json!({ "name": "Planted", "report_type": "node_health", "query_config": {} })),
+    )
+    .await;
+    response.assert_forbidden();
+}
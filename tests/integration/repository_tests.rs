@@ -0,0 +1,283 @@
+//! Repository and service tests
+//!
+//! Exercise repositories and the services built on them directly against a
+//! per-test [`TestDb`], without the HTTP layer.
+
+use serde_json::json;
+use uuid::Uuid;
+
+use openvox_webui::{
+    db::{CodeDeploymentRepository, FleetMetricsRepository, FleetMetricsSnapshot},
+    models::{
+        default_organization_uuid, Action, DeploymentStatus, MatchType, NewNotification,
+        NotificationAudience, NotificationQuery, NotificationType, Resource, RuleOperator,
+    },
+    services::{classification::ClassificationService, NotificationService},
+};
+
+use crate::common::{GroupFactory, TestDb, UserFactory};
+
+#[tokio::test]
+async fn test_databases_are_isolated() {
+    let first = TestDb::new().await;
+    let second = TestDb::new().await;
+
+    first
+        .create_group(GroupFactory::new().create().with_name("only-in-first"))
+        .await;
+
+    let org = default_organization_uuid();
+    assert_eq!(first.groups().get_all(org).await.unwrap().len(), 1);
+    assert!(second.groups().get_all(org).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_classification_of_stored_groups() {
+    let db = TestDb::new().await;
+    let groups = GroupFactory::new();
+
+    let web = db
+        .create_group(
+            groups
+                .create()
+                .with_name("web")
+                .with_classes(json!({"nginx": {}})),
+        )
+        .await;
+    db.add_rule(web.id, "os.family", RuleOperator::Equals, json!("RedHat"))
+        .await;
+    let web_prod = db
+        .create_group(groups.create().with_name("web-prod").with_parent(web.id))
+        .await;
+    db.add_rule(web_prod.id, "role", RuleOperator::Equals, json!("web"))
+        .await;
+
+    let stored = db
+        .groups()
+        .get_all(default_organization_uuid())
+        .await
+        .unwrap();
+    let service = ClassificationService::new(stored);
+
+    let facts = json!({
+        "catalog_environment": "production",
+        "os": {"family": "RedHat"},
+        "role": "web",
+    });
+    let result = service.classify("web01.example.com", &facts);
+    let matched: Vec<_> = result.groups.iter().map(|g| g.name.as_str()).collect();
+    assert_eq!(matched.len(), 2, "matched {:?}", matched);
+    assert!(result
+        .groups
+        .iter()
+        .all(|g| g.match_type == MatchType::Rules));
+    assert!(result.classes.get("nginx").is_some());
+
+    // Children are not considered when their parent does not match
+    let facts = json!({
+        "catalog_environment": "production",
+        "os": {"family": "Debian"},
+        "role": "web",
+    });
+    assert!(service
+        .classify("web02.example.com", &facts)
+        .groups
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_rbac_permissions_of_stored_users() {
+    let db = TestDb::new().await;
+    let users = UserFactory::new();
+    let viewer = db.create_user(users.create()).await;
+    let operator = db
+        .create_user(users.create().with_roles(vec!["operator".to_string()]))
+        .await;
+    let rbac = db.rbac();
+
+    let check = |user_id: Uuid, action: Action| {
+        let rbac = &rbac;
+        async move {
+            rbac.check_permission(&user_id, Resource::Groups, action, None, None)
+                .await
+                .unwrap()
+                .allowed
+        }
+    };
+
+    assert!(check(viewer.id, Action::Read).await);
+    assert!(!check(viewer.id, Action::Update).await);
+    assert!(check(operator.id, Action::Update).await);
+    assert!(!check(operator.id, Action::Admin).await);
+}
+
+#[tokio::test]
+async fn test_code_deployment_state_machine() {
+    let db = TestDb::new().await;
+    let environment = db.create_environment("production").await;
+    let deployments = CodeDeploymentRepository::new(&db.pool);
+    let approver = Uuid::new_v4();
+
+    let deployment = deployments
+        .create(
+            environment.id,
+            "abc1234",
+            Some("Bump modules"),
+            None,
+            DeploymentStatus::Pending,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(deployment.status, DeploymentStatus::Pending);
+
+    let approved = deployments.approve(deployment.id, approver).await.unwrap();
+    assert_eq!(approved.unwrap().status, DeploymentStatus::Approved);
+
+    // Only pending deployments can be approved or rejected
+    assert!(deployments
+        .approve(deployment.id, approver)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(deployments
+        .reject(deployment.id, approver, "too late")
+        .await
+        .unwrap()
+        .is_none());
+
+    deployments.mark_deploying(deployment.id).await.unwrap();
+    deployments
+        .mark_success(deployment.id, Some("r10k done"))
+        .await
+        .unwrap();
+    let finished = deployments.get_by_id(deployment.id).await.unwrap().unwrap();
+    assert_eq!(finished.status, DeploymentStatus::Success);
+
+    // Finished deployments cannot be cancelled
+    assert!(!deployments.cancel(deployment.id).await.unwrap());
+}
+
+#[tokio::test]
+async fn test_notifications_reach_their_audience_once() {
+    let db = TestDb::new().await;
+    let users = UserFactory::new();
+    let viewer = db.create_user(users.create()).await;
+    let operator = db
+        .create_user(users.create().with_roles(vec!["operator".to_string()]))
+        .await;
+    let service = NotificationService::new(db.pool.clone());
+
+    let expiring = NewNotification {
+        organization_id: None,
+        title: "PAT token 'deploy' expires soon".to_string(),
+        message: "Renew it before code deployments stop".to_string(),
+        r#type: NotificationType::Warning,
+        category: Some("code_deploy".to_string()),
+        link: Some("/code-deploy".to_string()),
+        expires_at: None,
+        metadata: None,
+        dedup_key: Some("pat_token:1".to_string()),
+    };
+    let operators = NotificationAudience::Roles(vec!["operator".to_string()]);
+    assert_eq!(service.notify(&operators, &expiring).await.unwrap(), 1);
+    // Users already notified with the same key are skipped
+    assert_eq!(service.notify(&operators, &expiring).await.unwrap(), 0);
+
+    let list = |user_id: Uuid, query: NotificationQuery| {
+        let service = &service;
+        async move {
+            service
+                .get_notifications(&user_id.to_string(), None, query)
+                .await
+                .unwrap()
+        }
+    };
+    assert!(list(viewer.id, NotificationQuery::default())
+        .await
+        .is_empty());
+    let by_category = |category: &str| NotificationQuery {
+        category: Some(category.to_string()),
+        ..Default::default()
+    };
+    let received = list(operator.id, by_category("code_deploy")).await;
+    assert_eq!(received.len(), 1);
+    assert!(list(operator.id, by_category("alert")).await.is_empty());
+
+    // Read state is kept per user
+    let deployment = NewNotification {
+        title: "Deployment to production failed".to_string(),
+        r#type: NotificationType::Error,
+        category: Some("deployment".to_string()),
+        dedup_key: None,
+        ..expiring
+    };
+    assert!(
+        service
+            .notify(&NotificationAudience::AllUsers, &deployment)
+            .await
+            .unwrap()
+            >= 2
+    );
+    service
+        .mark_as_read(&received[0].id, &operator.id.to_string(), true)
+        .await
+        .unwrap();
+    let unread = NotificationQuery {
+        unread_only: Some(true),
+        ..Default::default()
+    };
+    let operator_unread = list(operator.id, unread.clone()).await;
+    assert_eq!(operator_unread.len(), 1);
+    assert_eq!(operator_unread[0].category.as_deref(), Some("deployment"));
+    assert_eq!(list(viewer.id, unread).await.len(), 1);
+}
+
+#[tokio::test]
+async fn test_fleet_metrics_daily_rollup() {
+    let db = TestDb::new().await;
+    let repo = FleetMetricsRepository::new(db.pool.clone());
+
+    let snapshot =
+        |bucket: &str, nodes: i64, reports: i64, run_time: Option<f64>| FleetMetricsSnapshot {
+            bucket: bucket.to_string(),
+            total_nodes: nodes,
+            changed_nodes: nodes / 2,
+            report_count: reports,
+            avg_run_time_seconds: run_time,
+            total_resources: nodes * 100,
+            ..Default::default()
+        };
+    for s in [
+        snapshot("2026-10-14T23:00:00Z", 8, 4, Some(30.0)),
+        snapshot("2026-10-15T10:00:00Z", 10, 1, Some(10.0)),
+        snapshot("2026-10-15T11:00:00Z", 12, 3, Some(30.0)),
+        snapshot("2026-10-15T12:00:00Z", 14, 0, None),
+    ] {
+        repo.upsert(&s).await.unwrap();
+    }
+    // Later snapshots of an hour replace earlier ones
+    repo.upsert(&snapshot("2026-10-15T12:00:00Z", 11, 0, None))
+        .await
+        .unwrap();
+
+    let hourly = repo
+        .range_hourly("2026-10-15T00:00:00Z", "2026-10-15T23:00:00Z")
+        .await
+        .unwrap();
+    assert_eq!(hourly.len(), 3);
+    assert_eq!(hourly[2].total_nodes, 11);
+
+    let daily = repo
+        .range_daily("2026-10-14T00:00:00Z", "2026-10-15T23:00:00Z")
+        .await
+        .unwrap();
+    assert_eq!(daily.len(), 2);
+    assert_eq!(daily[1].bucket, "2026-10-15T00:00:00Z");
+    assert_eq!(daily[1].total_nodes, 11);
+    assert_eq!(daily[1].report_count, 4);
+    assert_eq!(daily[1].avg_run_time_seconds, Some(25.0));
+    assert_eq!(daily[1].total_resources, 1100);
+
+    assert_eq!(repo.delete_before("2026-10-15T00:00:00Z").await.unwrap(), 1);
+}